@@ -0,0 +1,72 @@
+/// Benchmarks the bandwidth saved by compressing the transactions exchanged while synchronizing.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use num::BigUint;
+
+use summercash::{
+    common::address::Address,
+    core::types::transaction::Transaction,
+    p2p::{compression, sync},
+};
+
+/// Builds a single synchronization round worth of serialized transactions, each carrying a
+/// payload of roughly the given size.
+fn sync_round(payload_size: usize) -> Vec<Vec<u8>> {
+    (0..sync::TRANSACTIONS_PER_SYNCHRONIZATION_ROUND as u64)
+        .map(|nonce| {
+            // Make a structured, text-heavy payload, like most real payloads are
+            let payload = format!("{{\"memo\": \"invoice #{}\", \"lines\": [", nonce)
+                + &"{\"item\": \"widget\", \"qty\": 1}, ".repeat(payload_size / 32);
+
+            Transaction::new(
+                nonce,
+                Address::default(),
+                Address::default(),
+                BigUint::from(nonce),
+                payload.as_bytes(),
+                vec![],
+            )
+            .to_bytes()
+        })
+        .collect()
+}
+
+fn bench_sync_round(c: &mut Criterion) {
+    for payload_size in [256, 4096, 65536].iter() {
+        let round = sync_round(*payload_size); // Build the round's transactions
+        let sealed: Vec<Vec<u8>> = round.iter().map(|tx| compression::seal(tx)).collect();
+
+        // Report how much bandwidth the round takes up with and without compression
+        println!(
+            "sync round with {} byte payloads: {} bytes raw, {} bytes sealed",
+            payload_size,
+            round.iter().map(Vec::len).sum::<usize>(),
+            sealed.iter().map(Vec::len).sum::<usize>()
+        );
+
+        c.bench_function(
+            &format!("seal sync round ({}b payloads)", payload_size),
+            |b| {
+                b.iter(|| {
+                    for tx in &round {
+                        black_box(compression::seal(tx));
+                    }
+                })
+            },
+        );
+
+        c.bench_function(
+            &format!("open sync round ({}b payloads)", payload_size),
+            |b| {
+                b.iter(|| {
+                    for envelope in &sealed {
+                        black_box(compression::open(envelope).unwrap());
+                    }
+                })
+            },
+        );
+    }
+}
+
+criterion_group!(benches, bench_sync_round);
+criterion_main!(benches);
@@ -5,32 +5,65 @@ use rand::rngs::OsRng; // Import the os's rng
 
 use walkdir::WalkDir; // Import the walkdir utility
 
+use cryptolib::{
+    aes::{self, KeySize},
+    blockmodes,
+    buffer::{self, BufferResult, ReadBuffer, WriteBuffer},
+}; // Import the keystore cipher
+
+use rand::{rngs::StdRng, RngCore, SeedableRng}; // Import the rng used to derive keystore IVs
+
 use std::{fmt, fs, io, io::Write}; // Import the io library
 
+use zeroize::{Zeroize, Zeroizing}; // Import helpers for wiping secrets from memory
+
 use serde::{Deserialize, Serialize}; // Import serde serialization
 
-use super::super::{common, common::address::Address, crypto::blake3}; // Import the address module
+use super::{
+    super::{common, common::address::Address, crypto::blake3},
+    keystore::{self, KeystoreError},
+    mnemonic::{self, DerivationPath, MnemonicError},
+}; // Import the address, keystore & mnemonic key derivation modules
 
-/// A SummerCash account.
+/// A SummerCash account. The account's keys are wiped from memory once it is dropped, so copies of it (and of its
+/// keypairs) should be kept for no longer than they're needed.
 #[derive(Serialize, Clone, Deserialize)]
 pub struct Account {
     /// The account's private and public keys
     keypair: Vec<u8>,
     /// The account's p2p identity
     p2p_keypair: Vec<u8>,
+    /// The mnemonic phrase that the account's keys were derived from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mnemonic: Option<String>,
+    /// The path that the account's keys were derived from the mnemonic phrase's seed with, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    derivation_path: Option<String>,
+}
+
+/// The public details of an account, which are safe to hand out over RPC, or to print.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PublicAccount {
+    /// The address of the account
+    pub address: Address,
+
+    /// The account's base58-encoded public key
+    pub public_key: String,
 }
 
 impl Default for Account {
     /// Generates a new account.
     fn default() -> Self {
-        let mut csprng = OsRng {};
+        Self::new()
+    }
+}
 
-        Account {
-            keypair: ed25519_dalek::Keypair::generate(&mut csprng)
-                .to_bytes()
-                .to_vec(), // Generate keypair
-            p2p_keypair: Keypair::generate().encode().to_vec(), // Generate p2p keypair
-        } // Return account
+impl Drop for Account {
+    /// Wipes the account's keys from memory.
+    fn drop(&mut self) {
+        self.keypair.zeroize();
+        self.p2p_keypair.zeroize();
+        self.mnemonic.zeroize();
     }
 }
 
@@ -40,14 +73,79 @@ impl Account {
     pub fn new() -> Account {
         let mut csprng = OsRng {};
 
+        // Wipe the encoded keypairs once they've been copied into the account
+        let keypair = Zeroizing::new(ed25519_dalek::Keypair::generate(&mut csprng).to_bytes());
+        let p2p_keypair = Zeroizing::new(Keypair::generate().encode());
+
         Account {
-            keypair: ed25519_dalek::Keypair::generate(&mut csprng)
-                .to_bytes()
-                .to_vec(), // Generate keypair
-            p2p_keypair: Keypair::generate().encode().to_vec(), // Generate p2p keypair
+            keypair: keypair.to_vec(),         // Generate keypair
+            p2p_keypair: p2p_keypair.to_vec(), // Generate p2p keypair
+            mnemonic: None,
+            derivation_path: None,
         } // Return account
     }
 
+    /// Derives an account from a BIP-39 mnemonic phrase, so that it can be recovered from the phrase alone. The
+    /// account's p2p identity is derived from the first child of its key, so that it survives recovery, too.
+    ///
+    /// # Arguments
+    ///
+    /// * `phrase` - The mnemonic phrase
+    /// * `passphrase` - The optional passphrase that the phrase's seed is salted with (may be empty)
+    /// * `path` - The path of the account's key (see DerivationPath::for_account)
+    pub fn from_mnemonic(
+        phrase: &str,
+        passphrase: &str,
+        path: &DerivationPath,
+    ) -> Result<Account, MnemonicError> {
+        let keypair =
+            Zeroizing::new(mnemonic::derive_keypair(phrase, passphrase, path)?.to_bytes());
+        let mut p2p_secret = Zeroizing::new(
+            mnemonic::derive_keypair(phrase, passphrase, &path.child(1))?.to_bytes(),
+        );
+        let p2p_keypair = Zeroizing::new(
+            Keypair::decode(&mut p2p_secret[..])
+                .map_err(|e| MnemonicError::InvalidKey {
+                    error: e.to_string(),
+                })?
+                .encode(),
+        );
+
+        Ok(Account {
+            keypair: keypair.to_vec(),
+            p2p_keypair: p2p_keypair.to_vec(),
+            mnemonic: Some(phrase.split_whitespace().collect::<Vec<&str>>().join(" ")),
+            derivation_path: Some(path.to_string()),
+        })
+    }
+
+    /// Generates a new account backed by a mnemonic phrase with the given number of words, which can be read back
+    /// with Account::mnemonic.
+    ///
+    /// # Arguments
+    ///
+    /// * `words` - The number of words in the phrase (12, 15, 18, 21, or 24)
+    /// * `passphrase` - The optional passphrase that the phrase's seed should be salted with (may be empty)
+    /// * `path` - The path of the account's key (see DerivationPath::for_account)
+    pub fn with_mnemonic(
+        words: usize,
+        passphrase: &str,
+        path: &DerivationPath,
+    ) -> Result<Account, MnemonicError> {
+        Self::from_mnemonic(&mnemonic::generate_phrase(words)?, passphrase, path)
+    }
+
+    /// Gets the mnemonic phrase that the account's keys were derived from, if the account was made from one. Anyone
+    /// holding the phrase can recover every account derived from it, so it should never be handed out over RPC.
+    pub fn mnemonic(&self) -> Option<&str> {
+        self.mnemonic.as_deref()
+    }
+
+    /// Gets the path that the account's keys were derived from its mnemonic phrase with, if it was made from one.
+    pub fn derivation_path(&self) -> Option<&str> {
+        self.derivation_path.as_deref()
+    }
+
     /// Get the address of a particular account.
     pub fn address(&self) -> Result<Address, ed25519_dalek::SignatureError> {
         Ok(Address::from_public_key(&self.keypair()?.public)) // Return address
@@ -59,7 +157,25 @@ impl Account {
 
     /// Get the p2p keypair of a particular account.
     pub fn p2p_keypair(&self) -> Result<Keypair, error::DecodingError> {
-        Keypair::decode(self.p2p_keypair.clone().as_mut_slice()) // Return decoded keypair
+        // Decoding consumes the copy, which is wiped even if it turns out to be corrupt
+        let mut encoded = Zeroizing::new(self.p2p_keypair.clone());
+
+        Keypair::decode(encoded.as_mut_slice()) // Return decoded keypair
+    }
+
+    /// Get the public details of a particular account, leaving out its keys.
+    pub fn public(&self) -> Result<PublicAccount, ed25519_dalek::SignatureError> {
+        let keypair = self.keypair()?;
+
+        Ok(PublicAccount {
+            address: Address::from_public_key(&keypair.public),
+            public_key: bs58::encode(keypair.public.as_bytes()).into_string(),
+        })
+    }
+
+    /// Serializes the account into the contents of a keystore file, which are wiped from memory once they're dropped.
+    fn to_keystore_json(&self) -> io::Result<Zeroizing<Vec<u8>>> {
+        Ok(Zeroizing::new(serde_json::to_vec_pretty(self)?))
     }
 
     /// Persist the account to the disk.
@@ -72,7 +188,7 @@ impl Account {
                 "{}.json",
                 address.to_str()
             )))?; // Initialize file
-            file.write_all(serde_json::to_vec_pretty(self)?.as_slice())?; // Serialize
+            file.write_all(&self.to_keystore_json()?)?; // Serialize
             Ok(()) // All good!
         } else {
             Err(io::Error::from(io::ErrorKind::InvalidData)) // Return error
@@ -90,7 +206,7 @@ impl Account {
             let mut file =
                 fs::File::create(&format!("{}/keystore/{}.json", data_dir, address.to_str()))?;
             // Serializ the account + write it to the file
-            file.write_all(serde_json::to_vec_pretty(self)?.as_slice())?;
+            file.write_all(&self.to_keystore_json()?)?;
 
             // Everything's good!
             Ok(())
@@ -115,7 +231,7 @@ impl Account {
             blake3::hash_slice(s.as_bytes()).to_str()
         ))?;
 
-        file.write_all(serde_json::to_vec_pretty(self)?.as_slice())?; // Serialize the account, and put it in the new file
+        file.write_all(&self.to_keystore_json()?)?; // Serialize the account, and put it in the new file
 
         Ok(()) // All good!
     }
@@ -128,7 +244,7 @@ impl Account {
             blake3::hash_slice(s.as_bytes()).to_str()
         )))?; // Initialize file
 
-        file.write_all(serde_json::to_vec_pretty(self)?.as_slice())?; // Serialize
+        file.write_all(&self.to_keystore_json()?)?; // Serialize
 
         Ok(()) // All good!
     }
@@ -144,6 +260,25 @@ impl Account {
         Ok(serde_json::from_reader(file)?) // Deserialize the account from the information held in the file + return
     }
 
+    /// Read a locked account from the disk at a given data directory, decrypting it in memory. The file on the disk
+    /// is left locked. Files locked by older builds are read, too.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the account that should be read
+    /// * `data_dir` - The data directory containing the account's keystore
+    /// * `dec_key` - The key that the account was locked with
+    pub fn read_locked_from_disk_at_data_directory(
+        address: Address,
+        data_dir: &str,
+        dec_key: &str,
+    ) -> io::Result<Account> {
+        keystore::read_locked_account(&address, data_dir, dec_key).map_err(|e| match e {
+            KeystoreError::Io { error } => io::Error::new(io::ErrorKind::Other, error),
+            e => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+        })
+    }
+
     /// Read an account from the disk.
     pub fn read_from_disk(address: Address) -> io::Result<Account> {
         let file = fs::File::open(common::io::format_keystore_dir(&format!(
@@ -156,39 +291,128 @@ impl Account {
 }
 
 impl fmt::Display for Account {
+    /// Writes out the public details of the account. The account's private key is never printed, so that it can't end
+    /// up in a log.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        #[derive(Serialize)]
-        struct HexRepresentation {
-            private_key: String,
-            address: String,
+        match self.public() {
+            Ok(public) => write!(f, "{}", public),
+            Err(_) => write!(f, "invalid_key"),
         }
+    }
+}
 
-        // Convert the account into a string struct
-        let acc_hex = HexRepresentation {
-            private_key: {
-                if let Ok(kp) = self.keypair() {
-                    bs58::encode(kp.secret).into_string()
-                } else {
-                    "invalid_key".to_owned()
-                }
-            },
-            address: {
-                if let Ok(addr) = self.address() {
-                    bs58::encode(addr).into_string()
-                } else {
-                    "invalid_key".to_owned()
-                }
-            },
-        };
-
+impl fmt::Display for PublicAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "{}",
-            serde_json::to_string_pretty(&acc_hex).unwrap_or_default()
+            serde_json::to_string_pretty(self).unwrap_or_default()
         )
     }
 }
 
+/// Encrypts the contents of a keystore file with the given key, returning None if encryption fails. This is the format
+/// that older builds locked keystore files in; the key is used as is, so it should already be strong (see the keystore
+/// module for locking files with passwords).
+///
+/// # Arguments
+///
+/// * `contents` - The plaintext contents of the keystore file
+/// * `enc_key` - The key that the keystore file should be locked with
+pub fn encrypt_keystore(contents: &[u8], enc_key: &str) -> Option<Vec<u8>> {
+    // Derive the IV from the key, so that the same key can be used to unlock the file later
+    let mut iv: [u8; 16] = [0; 16];
+    let mut rng: StdRng = SeedableRng::from_seed(*blake3::hash_slice(enc_key.as_bytes()));
+    rng.fill_bytes(&mut iv);
+
+    // Make an instance of the encryption helper for the file
+    let mut enc = aes::cbc_encryptor(
+        KeySize::KeySize128,
+        &*blake3::hash_slice(enc_key.as_bytes()),
+        &iv,
+        blockmodes::PkcsPadding,
+    );
+
+    // Generate a few buffers, set the encoder to read from the file's contents
+    let mut final_result = Vec::<u8>::new();
+    let mut read_buffer = buffer::RefReadBuffer::new(contents);
+    let mut buffer = [0; 2048];
+    let mut write_buffer = buffer::RefWriteBuffer::new(&mut buffer);
+
+    loop {
+        let result = enc
+            .encrypt(&mut read_buffer, &mut write_buffer, true)
+            .ok()?;
+
+        // Put the encrypted data block into the overall buffer
+        final_result.extend(
+            write_buffer
+                .take_read_buffer()
+                .take_remaining()
+                .iter()
+                .copied(),
+        );
+
+        match result {
+            BufferResult::BufferUnderflow => break,
+            BufferResult::BufferOverflow => {}
+        }
+    }
+
+    Some(final_result)
+}
+
+/// Decrypts the contents of a keystore file locked with the given key, returning None if the key is wrong or the
+/// contents aren't encrypted. The decrypted contents are wiped from memory once they're dropped.
+///
+/// # Arguments
+///
+/// * `contents` - The encrypted contents of the keystore file
+/// * `dec_key` - The key that the keystore file was locked with
+pub fn decrypt_keystore(contents: &[u8], dec_key: &str) -> Option<Zeroizing<Vec<u8>>> {
+    // The IV is derived from the key, just as it was when the file was locked
+    let mut iv: [u8; 16] = [0; 16];
+    let mut rng: StdRng = SeedableRng::from_seed(*blake3::hash_slice(dec_key.as_bytes()));
+    rng.fill_bytes(&mut iv);
+
+    // Make an instance of the decryption helper for the file
+    let mut dec = aes::cbc_decryptor(
+        KeySize::KeySize128,
+        &*blake3::hash_slice(dec_key.as_bytes()),
+        &iv,
+        blockmodes::PkcsPadding,
+    );
+
+    // Generate a few buffers, set the decoder to read from the file's contents. The plaintext is never longer than the
+    // ciphertext, so the result is never reallocated, which would leave stray copies of it behind.
+    let mut final_result = Zeroizing::new(Vec::<u8>::with_capacity(contents.len()));
+    let mut read_buffer = buffer::RefReadBuffer::new(contents);
+    let mut buffer = Zeroizing::new([0; 2048]);
+    let mut write_buffer = buffer::RefWriteBuffer::new(&mut *buffer);
+
+    loop {
+        let result = dec
+            .decrypt(&mut read_buffer, &mut write_buffer, true)
+            .ok()?;
+
+        // Put the decrypted data block into the overall buffer
+        final_result.extend(
+            write_buffer
+                .take_read_buffer()
+                .take_remaining()
+                .iter()
+                .copied(),
+        );
+
+        match result {
+            BufferResult::BufferUnderflow => break,
+            BufferResult::BufferOverflow => {}
+        }
+    }
+
+    Some(final_result)
+}
+
 /// Gets a list of unlocked, localized accounts in a given directory.
 pub fn get_all_unlocked_accounts_in_data_directory(data_dir: &str) -> Vec<Address> {
     // Initialize a buffer that we'll each of the account addresses in
@@ -287,4 +511,49 @@ mod tests {
 
         assert_eq!(test_account.address(), read_account.address()); // Ensure accounts have same address
     }
+
+    #[test]
+    fn test_public() {
+        let test_account = Account::new(); // Generate a new account
+        let keypair = test_account.keypair().unwrap();
+
+        let public = test_account.public().unwrap();
+        assert_eq!(public.address, test_account.address().unwrap());
+
+        // The account's private key is never printed
+        let printed = test_account.to_string();
+        assert!(printed.contains(&public.public_key));
+        assert!(!printed.contains(&bs58::encode(keypair.secret.as_bytes()).into_string()));
+    }
+
+    #[test]
+    fn test_from_mnemonic() {
+        let test_account =
+            Account::with_mnemonic(12, "passphrase", &DerivationPath::for_account(1)).unwrap();
+        let phrase = test_account.mnemonic().unwrap().to_owned();
+
+        // The account, along with its p2p identity, can be recovered from its phrase alone
+        let path: DerivationPath = test_account.derivation_path().unwrap().parse().unwrap();
+        let recovered = Account::from_mnemonic(&phrase, "passphrase", &path).unwrap();
+        assert_eq!(recovered.address(), test_account.address());
+        assert_eq!(
+            recovered.p2p_keypair().unwrap().public().encode(),
+            test_account.p2p_keypair().unwrap().public().encode()
+        );
+
+        // A different passphrase or account index yields a different account
+        assert_ne!(
+            Account::from_mnemonic(&phrase, "", &path)
+                .unwrap()
+                .address(),
+            test_account.address()
+        );
+        assert_ne!(
+            Account::from_mnemonic(&phrase, "passphrase", &DerivationPath::default())
+                .unwrap()
+                .address(),
+            test_account.address()
+        );
+        assert!(Account::new().mnemonic().is_none());
+    }
 }
@@ -0,0 +1,557 @@
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use cryptolib::{hmac::Hmac, mac::Mac, sha2::Sha256, util}; // Import the backup authentication primitives
+
+use rand::{rngs::OsRng, RngCore}; // Import the os's rng
+
+use std::{collections::BTreeMap, fs, io}; // Import the filesystem library
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use zeroize::Zeroizing; // Import helpers for wiping secrets from memory
+
+use super::{
+    account,
+    contacts::AddressBook,
+    kdf::{self, KdfError, KdfParams},
+}; // Import the keystore cipher, address book & key derivation types
+
+/// The bytes that every backup archive begins with.
+pub const BACKUP_MAGIC: &[u8; 6] = b"SMCBAK";
+
+/// The version of the backup archive format written by this build. Archives of any other version are refused.
+pub const BACKUP_VERSION: u32 = 1;
+
+/// The number of bytes in a backup archive's integrity MAC.
+pub const MAC_SIZE: usize = 32;
+
+/// The number of bytes in the salt that a backup password is stretched with.
+pub const SALT_SIZE: usize = 32;
+
+/// An error encountered while exporting or importing a backup.
+#[derive(Debug, Fail)]
+pub enum BackupError {
+    #[fail(display = "not a SummerCash backup (the file doesn't begin with the backup header)")]
+    NotABackup,
+    #[fail(
+        display = "unsupported backup version {} (this build reads version {})",
+        version, BACKUP_VERSION
+    )]
+    UnsupportedVersion {
+        version: u32, // The version of the archive
+    },
+    #[fail(display = "the backup is malformed: {}", error)]
+    Malformed {
+        error: String, // The underlying error
+    },
+    #[fail(
+        display = "the backup failed its integrity check (the password is wrong, or the file has been tampered with)"
+    )]
+    IntegrityCheckFailed,
+    #[fail(
+        display = "{} already exists with different contents; pass --overwrite to replace it",
+        path
+    )]
+    Conflict {
+        path: String, // The file or contact that would have been replaced
+    },
+    #[fail(display = "failed to read or write the data directory: {}", error)]
+    Io {
+        error: String, // The underlying error
+    },
+}
+
+impl From<io::Error> for BackupError {
+    /// Converts the given IO error into a BackupError.
+    fn from(e: io::Error) -> Self {
+        Self::Io {
+            error: e.to_string(),
+        }
+    }
+}
+
+impl From<KdfError> for BackupError {
+    /// Converts the given key derivation error into a BackupError.
+    fn from(e: KdfError) -> Self {
+        Self::Malformed {
+            error: e.to_string(),
+        }
+    }
+}
+
+impl From<bincode::Error> for BackupError {
+    /// Converts the given serialization error into a BackupError.
+    fn from(e: bincode::Error) -> Self {
+        Self::Malformed {
+            error: e.to_string(),
+        }
+    }
+}
+
+/// Everything needed to move a wallet from one machine to another. Keystore files are carried exactly as they sit on
+/// disk, so locked accounts stay locked under their own keys. Secret store passwords are deliberately left behind.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BackupContents {
+    /// The time at which the backup was taken
+    pub created_at: DateTime<Utc>,
+
+    /// The contents of each of the files in the keystore directory, keyed by file name
+    pub keystore: BTreeMap<String, Vec<u8>>,
+
+    /// The local address book
+    pub contacts: AddressBook,
+
+    /// The contents of each of the network config files in the config directory, keyed by file name
+    pub config: BTreeMap<String, Vec<u8>>,
+}
+
+/// A password-protected backup. On disk, an archive is laid out as the backup header, the format version (as a
+/// little-endian u32), the key derivation parameters, salt & ciphertext, and finally an HMAC-SHA256 over everything
+/// before it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BackupArchive {
+    /// The version of the archive format
+    pub version: u32,
+
+    /// The cost of stretching the password into the archive's keys
+    pub kdf: KdfParams,
+
+    /// The random salt that the password is stretched with
+    pub salt: Vec<u8>,
+
+    /// The encrypted backup contents
+    pub ciphertext: Vec<u8>,
+
+    /// The integrity MAC over the rest of the archive
+    pub mac: Vec<u8>,
+}
+
+/// The number of files, and contacts, written back to a data directory by an import.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RestoreSummary {
+    /// The number of keystore files written
+    pub keystore_files: usize,
+
+    /// The number of contacts added to the address book
+    pub contacts: usize,
+
+    /// The number of config files written
+    pub config_files: usize,
+}
+
+/// Bundles the keystore, address book & config of the given data directory into an archive protected by the given
+/// password.
+///
+/// # Arguments
+///
+/// * `data_dir` - The data directory that should be backed up
+/// * `password` - The password that the archive should be protected with
+pub fn export(data_dir: &str, password: &str) -> Result<Vec<u8>, BackupError> {
+    BackupArchive::seal(
+        &BackupContents::collect(data_dir)?,
+        password,
+        KdfParams::default(),
+    )?
+    .to_bytes()
+}
+
+/// Opens the given archive with the given password, and writes its contents back to the given data directory.
+///
+/// # Arguments
+///
+/// * `archive` - The bytes of the archive
+/// * `password` - The password that the archive was protected with
+/// * `data_dir` - The data directory that the backup should be restored to
+/// * `overwrite` - Whether or not existing files & contacts that differ from those in the backup may be replaced
+pub fn import(
+    archive: &[u8],
+    password: &str,
+    data_dir: &str,
+    overwrite: bool,
+) -> Result<RestoreSummary, BackupError> {
+    BackupArchive::from_bytes(archive)?
+        .open(password)?
+        .restore(data_dir, overwrite)
+}
+
+/// Implement a set of backup contents helper methods.
+impl BackupContents {
+    /// Gathers the keystore, address book & network config files of the given data directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The data directory that should be backed up
+    pub fn collect(data_dir: &str) -> Result<Self, BackupError> {
+        Ok(Self {
+            created_at: Utc::now(),
+            keystore: read_json_files(&format!("{}/keystore", data_dir))?,
+            contacts: AddressBook::read_from_disk_at_data_directory(data_dir)?,
+            config: read_json_files(&format!("{}/config", data_dir))?,
+        })
+    }
+
+    /// Writes the backed up files & contacts to the given data directory. Nothing is written unless the entire backup
+    /// can be restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The data directory that the backup should be restored to
+    /// * `overwrite` - Whether or not existing files & contacts that differ from those in the backup may be replaced
+    pub fn restore(&self, data_dir: &str, overwrite: bool) -> Result<RestoreSummary, BackupError> {
+        let keystore_dir = format!("{}/keystore", data_dir);
+        let config_dir = format!("{}/config", data_dir);
+
+        let mut summary = RestoreSummary::default();
+
+        // Work out which files need writing, refusing to clobber anything unless asked to
+        let keystore = changed_files(&keystore_dir, &self.keystore, overwrite)?;
+        let config = changed_files(&config_dir, &self.config, overwrite)?;
+
+        let mut book = AddressBook::read_from_disk_at_data_directory(data_dir)?;
+        for (name, contact) in self.contacts.contacts.iter() {
+            match book.get(name) {
+                Some(existing) if existing == contact => continue,
+                Some(_) if !overwrite => {
+                    return Err(BackupError::Conflict {
+                        path: format!("contact '{}'", name),
+                    })
+                }
+                _ => {}
+            }
+
+            book.contacts.insert(name.clone(), contact.clone());
+            summary.contacts += 1;
+        }
+
+        // Everything checks out, so write the backup out
+        summary.keystore_files = write_files(&keystore_dir, &keystore)?;
+        summary.config_files = write_files(&config_dir, &config)?;
+
+        if summary.contacts > 0 {
+            book.write_to_disk_at_data_directory(data_dir)?;
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Implement a set of backup archive helper methods.
+impl BackupArchive {
+    /// Encrypts the given backup contents with a key derived from the given password.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - The contents of the backup
+    /// * `password` - The password that the archive should be protected with
+    /// * `kdf` - The cost of stretching the password into the archive's keys
+    pub fn seal(
+        contents: &BackupContents,
+        password: &str,
+        kdf: KdfParams,
+    ) -> Result<Self, BackupError> {
+        let mut salt = vec![0; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+
+        let keys = kdf::derive_keys(password, &salt, kdf)?;
+        let enc_key = encryption_key(&keys);
+
+        let mut archive = Self {
+            version: BACKUP_VERSION,
+            kdf,
+            salt,
+            ciphertext: account::encrypt_keystore(&bincode::serialize(contents)?, &enc_key)
+                .ok_or_else(|| BackupError::Malformed {
+                    error: "the backup couldn't be encrypted".to_owned(),
+                })?,
+            mac: vec![],
+        };
+        archive.mac = archive.compute_mac(&keys[32..])?;
+
+        Ok(archive)
+    }
+
+    /// Checks the archive's integrity, and decrypts its contents with a key derived from the given password.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - The password that the archive was protected with
+    pub fn open(&self, password: &str) -> Result<BackupContents, BackupError> {
+        let keys = kdf::derive_keys(password, &self.salt, self.kdf)?;
+        let enc_key = encryption_key(&keys);
+
+        // Check the MAC before touching the ciphertext
+        if !util::fixed_time_eq(&self.compute_mac(&keys[32..])?, &self.mac) {
+            return Err(BackupError::IntegrityCheckFailed);
+        }
+
+        let contents = account::decrypt_keystore(&self.ciphertext, &enc_key)
+            .ok_or(BackupError::IntegrityCheckFailed)?;
+
+        Ok(bincode::deserialize(&contents)?)
+    }
+
+    /// Serializes the archive.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BackupError> {
+        let mut bytes = self.authenticated_bytes()?;
+        bytes.extend_from_slice(&self.mac);
+
+        Ok(bytes)
+    }
+
+    /// Deserializes an archive, without checking its integrity.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes of the archive
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BackupError> {
+        let header_len = BACKUP_MAGIC.len() + 4;
+
+        if bytes.len() < header_len || &bytes[..BACKUP_MAGIC.len()] != BACKUP_MAGIC {
+            return Err(BackupError::NotABackup);
+        }
+
+        // Refuse anything written in a format we don't understand
+        let mut version = [0; 4];
+        version.copy_from_slice(&bytes[BACKUP_MAGIC.len()..header_len]);
+        let version = u32::from_le_bytes(version);
+        if version != BACKUP_VERSION {
+            return Err(BackupError::UnsupportedVersion { version });
+        }
+
+        if bytes.len() < header_len + MAC_SIZE {
+            return Err(BackupError::Malformed {
+                error: "the archive is truncated".to_owned(),
+            });
+        }
+
+        let (body, mac) = bytes[header_len..].split_at(bytes.len() - header_len - MAC_SIZE);
+        let (kdf, salt, ciphertext): (KdfParams, Vec<u8>, Vec<u8>) = bincode::deserialize(body)?;
+
+        Ok(Self {
+            version,
+            kdf,
+            salt,
+            ciphertext,
+            mac: mac.to_vec(),
+        })
+    }
+
+    /// Serializes each part of the archive covered by its MAC.
+    fn authenticated_bytes(&self) -> Result<Vec<u8>, BackupError> {
+        let mut bytes = BACKUP_MAGIC.to_vec();
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend(bincode::serialize(&(
+            &self.kdf,
+            &self.salt,
+            &self.ciphertext,
+        ))?);
+
+        Ok(bytes)
+    }
+
+    /// Computes the MAC of the archive with the given key.
+    fn compute_mac(&self, mac_key: &[u8]) -> Result<Vec<u8>, BackupError> {
+        let mut hmac = Hmac::new(Sha256::new(), mac_key);
+        hmac.input(&self.authenticated_bytes()?);
+
+        Ok(hmac.result().code().to_vec())
+    }
+}
+
+/// Gets the encryption key from the given key material. The keystore cipher takes a textual key, so it is handed the
+/// encoded first half.
+fn encryption_key(keys: &[u8; kdf::KEY_MATERIAL_SIZE]) -> Zeroizing<String> {
+    Zeroizing::new(bs58::encode(&keys[..32]).into_string())
+}
+
+/// Reads each of the json files directly inside the given directory. A missing directory has no files.
+fn read_json_files(dir: &str) -> Result<BTreeMap<String, Vec<u8>>, BackupError> {
+    let mut files = BTreeMap::new();
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(files),
+        Err(e) => return Err(e.into()),
+    };
+
+    for entry in entries {
+        let entry = entry?;
+
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+
+        if let Some(name) = entry.file_name().to_str() {
+            if name.ends_with(".json") {
+                files.insert(name.to_owned(), fs::read(entry.path())?);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Writes each of the given files to the given directory, and returns the number of files written.
+fn write_files(dir: &str, files: &[(&String, &Vec<u8>)]) -> io::Result<usize> {
+    if files.is_empty() {
+        return Ok(0);
+    }
+
+    fs::create_dir_all(dir)?; // Make the directory, if it doesn't exist
+
+    for (name, contents) in files {
+        fs::write(format!("{}/{}", dir, name), contents)?;
+    }
+
+    Ok(files.len())
+}
+
+/// Gets the files from the given set that differ from those already in the given directory, refusing to replace any
+/// existing file unless asked to.
+fn changed_files<'a>(
+    dir: &str,
+    files: &'a BTreeMap<String, Vec<u8>>,
+    overwrite: bool,
+) -> Result<Vec<(&'a String, &'a Vec<u8>)>, BackupError> {
+    let mut changed = vec![];
+
+    for (name, contents) in files.iter() {
+        // A crafted archive shouldn't be able to write outside of the data directory
+        if name.is_empty()
+            || name.contains(|c| c == '/' || c == '\\')
+            || name == "."
+            || name == ".."
+        {
+            return Err(BackupError::Malformed {
+                error: format!("invalid file name '{}'", name),
+            });
+        }
+
+        let path = format!("{}/{}", dir, name);
+
+        match fs::read(&path) {
+            Ok(existing) if &existing == contents => continue,
+            Ok(_) if !overwrite => return Err(BackupError::Conflict { path }),
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        changed.push((name, contents));
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{common::io, crypto::blake3}; // Import the io & hashing modules
+    use super::*; // Import names from our parent module
+
+    /// Keeps the tests quick; real archives use the default cost.
+    const TEST_KDF: KdfParams = KdfParams {
+        log_n: 4,
+        r: 8,
+        p: 1,
+    };
+
+    #[test]
+    fn test_seal_open() {
+        let mut contents = BackupContents {
+            created_at: Utc::now(),
+            keystore: BTreeMap::new(),
+            contacts: AddressBook::default(),
+            config: BTreeMap::new(),
+        };
+        contents
+            .keystore
+            .insert("alice.json".to_owned(), b"locked".to_vec());
+        contents
+            .contacts
+            .add(
+                "alice",
+                &blake3::hash_slice(b"alice").to_str(),
+                String::new(),
+            )
+            .unwrap();
+
+        let bytes = BackupArchive::seal(&contents, "hunter2", TEST_KDF)
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        let archive = BackupArchive::from_bytes(&bytes).unwrap();
+        assert_eq!(archive.open("hunter2").unwrap(), contents); // Ensure the contents survived
+
+        // A wrong password and a flipped bit are both caught by the MAC
+        assert!(match archive.open("hunter3") {
+            Err(BackupError::IntegrityCheckFailed) => true,
+            _ => false,
+        });
+
+        let mut tampered = bytes.clone();
+        let middle = tampered.len() / 2;
+        tampered[middle] ^= 1;
+        assert!(
+            match BackupArchive::from_bytes(&tampered).and_then(|a| a.open("hunter2")) {
+                Err(BackupError::IntegrityCheckFailed) | Err(BackupError::Malformed { .. }) => true,
+                _ => false,
+            }
+        );
+
+        // Archives from the future are refused outright
+        let mut future = bytes;
+        future[BACKUP_MAGIC.len()] = (BACKUP_VERSION + 1) as u8;
+        assert!(match BackupArchive::from_bytes(&future) {
+            Err(BackupError::UnsupportedVersion { version }) => version == BACKUP_VERSION + 1,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn test_restore() {
+        let data_dir = format!("{}/.tests/backup", io::data_dir()); // Get a test directory
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let mut contents = BackupContents {
+            created_at: Utc::now(),
+            keystore: BTreeMap::new(),
+            contacts: AddressBook::default(),
+            config: BTreeMap::new(),
+        };
+        contents
+            .keystore
+            .insert("alice.json".to_owned(), b"locked".to_vec());
+        contents
+            .config
+            .insert("network_olympia.json".to_owned(), b"{}".to_vec());
+
+        let summary = contents.restore(&data_dir, false).unwrap();
+        assert_eq!(summary.keystore_files, 1);
+        assert_eq!(summary.config_files, 1);
+        assert_eq!(
+            BackupContents::collect(&data_dir).unwrap().keystore,
+            contents.keystore
+        );
+
+        // Restoring the same backup twice is a no-op, but differing files are left alone unless asked
+        assert_eq!(
+            contents.restore(&data_dir, false).unwrap(),
+            RestoreSummary::default()
+        );
+
+        contents
+            .keystore
+            .insert("alice.json".to_owned(), b"other".to_vec());
+        assert!(contents.restore(&data_dir, false).is_err());
+        assert_eq!(contents.restore(&data_dir, true).unwrap().keystore_files, 1);
+
+        // File names can't escape the data directory
+        contents
+            .keystore
+            .insert("../escape.json".to_owned(), vec![]);
+        assert!(match contents.restore(&data_dir, true) {
+            Err(BackupError::Malformed { .. }) => true,
+            _ => false,
+        });
+    }
+}
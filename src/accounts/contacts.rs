@@ -0,0 +1,222 @@
+use std::{collections::BTreeMap, fs, io, io::Write}; // Import the filesystem library
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::super::common::address::Address; // Import the address type
+
+/// The name of the file in a data directory that the address book is stored in.
+pub const CONTACTS_FILE: &str = "contacts.json";
+
+/// A named account that the user pays regularly. Contacts never leave the node, and have no bearing on consensus.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Contact {
+    /// The address of the contact's account
+    pub address: Address,
+
+    /// Any text the user wants to keep alongside the contact
+    #[serde(default)]
+    pub memo: String,
+}
+
+/// A set of contacts, keyed by name.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct AddressBook {
+    /// Each of the contacts in the book, sorted by name
+    #[serde(default)]
+    pub contacts: BTreeMap<String, Contact>,
+}
+
+/// An error encountered while editing the address book, or looking something up in it.
+#[derive(Debug, Fail, PartialEq)]
+pub enum ContactError {
+    #[fail(
+        display = "{} is not a valid address (it doesn't decode to a 32-byte address)",
+        address
+    )]
+    InvalidAddress {
+        address: String, // The address that was given
+    },
+    #[fail(
+        display = "invalid contact name '{}' (names may not be empty, contain whitespace, or be addresses themselves)",
+        name
+    )]
+    InvalidName {
+        name: String, // The name that was given
+    },
+    #[fail(display = "no contact is named '{}', and it isn't an address", name)]
+    NoSuchContact {
+        name: String, // The name that was looked up
+    },
+}
+
+/// Decodes the given address, refusing anything that doesn't decode to a full address. Malformed addresses would
+/// otherwise decode to the zero address, and any funds sent to them would be lost.
+///
+/// # Arguments
+///
+/// * `address` - The base58-encoded address
+pub fn parse_address(address: &str) -> Result<Address, ContactError> {
+    let decoded = Address::from(address.trim());
+
+    if decoded == Address::default() {
+        return Err(ContactError::InvalidAddress {
+            address: address.to_owned(),
+        });
+    }
+
+    Ok(decoded)
+}
+
+/// Implement a set of address book helper methods.
+impl AddressBook {
+    /// Gets the contact with the given name, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the contact
+    pub fn get(&self, name: &str) -> Option<&Contact> {
+        self.contacts.get(name)
+    }
+
+    /// Adds a contact with the given name, replacing any existing contact with that name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the contact
+    /// * `address` - The base58-encoded address of the contact's account
+    /// * `memo` - Any text that should be kept alongside the contact
+    pub fn add(
+        &mut self,
+        name: &str,
+        address: &str,
+        memo: String,
+    ) -> Result<Contact, ContactError> {
+        // Names that could be mistaken for addresses would make paying by name ambiguous
+        if name.is_empty() || name.contains(char::is_whitespace) || parse_address(name).is_ok() {
+            return Err(ContactError::InvalidName {
+                name: name.to_owned(),
+            });
+        }
+
+        let contact = Contact {
+            address: parse_address(address)?,
+            memo,
+        };
+        self.contacts.insert(name.to_owned(), contact.clone());
+
+        Ok(contact)
+    }
+
+    /// Removes the contact with the given name, and returns it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the contact
+    pub fn remove(&mut self, name: &str) -> Option<Contact> {
+        self.contacts.remove(name)
+    }
+
+    /// Gets the address of the contact with the given name, or, if no contact has the name, decodes it as an
+    /// address. This lets anything that pays an address pay a contact by name instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `name_or_address` - The name of a contact, or a base58-encoded address
+    pub fn resolve(&self, name_or_address: &str) -> Result<Address, ContactError> {
+        if let Some(contact) = self.get(name_or_address) {
+            return Ok(contact.address);
+        }
+
+        parse_address(name_or_address).map_err(|_| ContactError::NoSuchContact {
+            name: name_or_address.to_owned(),
+        })
+    }
+
+    /// Persist the address book to the given data directory.
+    pub fn write_to_disk_at_data_directory(&self, data_dir: &str) -> io::Result<()> {
+        fs::create_dir_all(data_dir)?; // Make the data directory, if it doesn't exist
+
+        let mut file = fs::File::create(format!("{}/{}", data_dir, CONTACTS_FILE))?; // Initialize file
+
+        file.write_all(serde_json::to_vec_pretty(self)?.as_slice())?; // Serialize
+
+        Ok(()) // All good!
+    }
+
+    /// Read the address book persisted in the given data directory. If no contacts have been saved yet, an empty
+    /// address book is returned.
+    pub fn read_from_disk_at_data_directory(data_dir: &str) -> io::Result<Self> {
+        // Open the contacts file, or start fresh if there isn't one
+        let file = match fs::File::open(format!("{}/{}", data_dir, CONTACTS_FILE)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+
+        Ok(serde_json::from_reader(file)?) // Return read address book
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{common::io, crypto::blake3}; // Import the io & hashing modules
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_resolve() {
+        let alice = blake3::hash_slice(b"alice");
+        let mut book = AddressBook::default();
+
+        book.add("alice", &alice.to_str(), "landlord".to_owned())
+            .unwrap();
+        assert_eq!(book.resolve("alice"), Ok(alice)); // Contacts can be paid by name
+        assert_eq!(book.resolve(&alice.to_str()), Ok(alice)); // Addresses pass straight through
+        assert_eq!(
+            book.resolve("bob"),
+            Err(ContactError::NoSuchContact {
+                name: "bob".to_owned()
+            })
+        );
+
+        // Malformed addresses and ambiguous names are refused on entry
+        assert_eq!(
+            book.add("bob", "not an address", String::new()),
+            Err(ContactError::InvalidAddress {
+                address: "not an address".to_owned()
+            })
+        );
+        assert!(book.add("", &alice.to_str(), String::new()).is_err());
+        assert!(book
+            .add("alice smith", &alice.to_str(), String::new())
+            .is_err());
+        assert!(book
+            .add(
+                &blake3::hash_slice(b"bob").to_str(),
+                &alice.to_str(),
+                String::new()
+            )
+            .is_err());
+
+        assert!(book.remove("alice").is_some());
+        assert!(book.resolve("alice").is_err());
+    }
+
+    #[test]
+    fn test_read_from_disk() {
+        let data_dir = format!("{}/.tests/contacts", io::data_dir()); // Get a test directory
+
+        let mut book = AddressBook::default();
+        book.add(
+            "alice",
+            &blake3::hash_slice(b"alice").to_str(),
+            String::new(),
+        )
+        .unwrap();
+        book.write_to_disk_at_data_directory(&data_dir).unwrap(); // Write the address book
+
+        assert_eq!(
+            AddressBook::read_from_disk_at_data_directory(&data_dir).unwrap(),
+            book
+        ); // Ensure the address book survived
+    }
+}
@@ -0,0 +1,125 @@
+use cryptolib::scrypt::{self, ScryptParams}; // Import the key derivation function
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use zeroize::Zeroizing; // Import helpers for wiping secrets from memory
+
+/// The most memory, in bytes, that stretching a password may take. Anything more expensive is refused, so that a
+/// forged keystore file or backup can't exhaust the memory of whoever opens it.
+pub const MAX_KDF_MEMORY: u64 = 1 << 30;
+
+/// The number of bytes of key material that a password is stretched into: an encryption key, followed by a separate
+/// MAC key of the same size.
+pub const KEY_MATERIAL_SIZE: usize = 64;
+
+/// An error encountered while stretching a password.
+#[derive(Debug, Fail, PartialEq)]
+pub enum KdfError {
+    #[fail(display = "unreasonable key derivation parameters {:?}", params)]
+    UnreasonableParams {
+        params: KdfParams, // The parameters that were refused
+    },
+}
+
+/// The cost of stretching a password into its keys.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct KdfParams {
+    /// The scrypt CPU/memory cost, as a power of two
+    pub log_n: u8,
+
+    /// The scrypt block size
+    pub r: u32,
+
+    /// The scrypt parallelization factor
+    pub p: u32,
+}
+
+impl Default for KdfParams {
+    /// Uses roughly 32 MiB of memory per attempt at guessing the password.
+    fn default() -> Self {
+        Self {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+/// Implement a set of key derivation helper methods.
+impl KdfParams {
+    /// Checks that the parameters are accepted by scrypt, and cheap enough to be worth trying.
+    pub fn is_reasonable(&self) -> bool {
+        self.log_n > 0
+            && self.log_n < 64
+            && self.r > 0
+            && self.r <= 64
+            && self.p > 0
+            && self.p <= 16
+            && (self.log_n as u32) < self.r * 16
+            && (128 * self.r as u64)
+                .checked_shl(self.log_n as u32)
+                .unwrap_or(u64::MAX)
+                <= MAX_KDF_MEMORY
+    }
+}
+
+/// Stretches the given password into an encryption key, followed by a separate MAC key. The key material is wiped
+/// from memory once it is dropped.
+///
+/// # Arguments
+///
+/// * `password` - The password that should be stretched
+/// * `salt` - The random salt that the password should be stretched with
+/// * `params` - The cost of stretching the password
+pub fn derive_keys(
+    password: &str,
+    salt: &[u8],
+    params: KdfParams,
+) -> Result<Zeroizing<[u8; KEY_MATERIAL_SIZE]>, KdfError> {
+    if !params.is_reasonable() {
+        return Err(KdfError::UnreasonableParams { params });
+    }
+
+    let mut derived = Zeroizing::new([0; KEY_MATERIAL_SIZE]);
+    scrypt::scrypt(
+        password.as_bytes(),
+        salt,
+        &ScryptParams::new(params.log_n, params.r, params.p),
+        &mut *derived,
+    );
+
+    Ok(derived)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_derive_keys() {
+        let cheap = KdfParams {
+            log_n: 4,
+            r: 8,
+            p: 1,
+        };
+
+        // The same password & salt always stretch into the same keys
+        let keys = derive_keys("password", b"salt", cheap).unwrap();
+        assert_eq!(
+            &keys[..],
+            &derive_keys("password", b"salt", cheap).unwrap()[..]
+        );
+        assert_ne!(
+            &keys[..],
+            &derive_keys("password", b"pepper", cheap).unwrap()[..]
+        );
+        assert_ne!(&keys[..32], &keys[32..]); // Ensure the encryption & MAC keys differ
+
+        // Parameters that would take too much memory are refused
+        let expensive = KdfParams { log_n: 40, ..cheap };
+        assert_eq!(
+            derive_keys("password", b"salt", expensive).err(),
+            Some(KdfError::UnreasonableParams { params: expensive })
+        );
+    }
+}
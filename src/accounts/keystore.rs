@@ -0,0 +1,447 @@
+use cryptolib::{
+    aes::{self, KeySize},
+    hmac::Hmac,
+    mac::Mac,
+    sha2::Sha256,
+    util,
+}; // Import the keystore cipher & authentication primitives
+
+use rand::{rngs::OsRng, RngCore}; // Import the os's rng
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::{fs, io}; // Import the filesystem library
+
+use zeroize::Zeroizing; // Import helpers for wiping secrets from memory
+
+use super::{
+    super::common::address::Address,
+    account::{self, Account},
+    kdf::{self, KdfError, KdfParams},
+}; // Import the account, address & key derivation types
+
+/// The version of the locked keystore format written by this build. Files of any other version are refused, except
+/// for the unversioned files written by older builds, which are still read.
+pub const KEYSTORE_VERSION: u32 = 2;
+
+/// The cipher that locked keystore files are encrypted with.
+pub const KEYSTORE_CIPHER: &str = "aes-256-ctr";
+
+/// The function that passwords are stretched into keystore keys with.
+pub const KEYSTORE_KDF: &str = "scrypt";
+
+/// The number of bytes in the salt that a keystore password is stretched with.
+pub const SALT_SIZE: usize = 32;
+
+/// The number of bytes in the IV that a keystore file is encrypted with.
+pub const IV_SIZE: usize = 16;
+
+/// An error encountered while locking or unlocking a keystore file.
+#[derive(Debug, Fail, PartialEq)]
+pub enum KeystoreError {
+    #[fail(display = "the password is wrong, or the keystore file has been tampered with")]
+    WrongPassword,
+    #[fail(display = "the account is already locked")]
+    AlreadyLocked,
+    #[fail(display = "the account isn't locked")]
+    NotLocked,
+    #[fail(
+        display = "unsupported keystore version {} (this build reads version {})",
+        version, KEYSTORE_VERSION
+    )]
+    UnsupportedVersion {
+        version: u32, // The version of the keystore file
+    },
+    #[fail(display = "the keystore file is malformed: {}", error)]
+    Malformed {
+        error: String, // The underlying error
+    },
+    #[fail(display = "failed to read or write the keystore file: {}", error)]
+    Io {
+        error: String, // The underlying error
+    },
+}
+
+impl From<io::Error> for KeystoreError {
+    /// Converts the given IO error into a KeystoreError.
+    fn from(e: io::Error) -> Self {
+        Self::Io {
+            error: e.to_string(),
+        }
+    }
+}
+
+impl From<KdfError> for KeystoreError {
+    /// Converts the given key derivation error into a KeystoreError.
+    fn from(e: KdfError) -> Self {
+        Self::Malformed {
+            error: e.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for KeystoreError {
+    /// Converts the given serialization error into a KeystoreError.
+    fn from(e: serde_json::Error) -> Self {
+        Self::Malformed {
+            error: e.to_string(),
+        }
+    }
+}
+
+/// The contents of a locked keystore file. The password is stretched with a random salt into an encryption key and a
+/// separate MAC key; the account is encrypted under a random IV, and the MAC covers everything else in the file, so
+/// that a wrong password is told apart from a damaged file before anything is decrypted. Binary fields are
+/// base58-encoded.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LockedKeystore {
+    /// The version of the keystore format
+    pub version: u32,
+
+    /// The cipher that the account is encrypted with
+    pub cipher: String,
+
+    /// The function that the password is stretched with
+    pub kdf: String,
+
+    /// The cost of stretching the password
+    pub kdf_params: KdfParams,
+
+    /// The random salt that the password is stretched with
+    pub salt: String,
+
+    /// The random IV that the account is encrypted under
+    pub iv: String,
+
+    /// The encrypted account
+    pub ciphertext: String,
+
+    /// The HMAC-SHA256 of the rest of the file
+    pub mac: String,
+}
+
+/// Implement a set of locked keystore helper methods.
+impl LockedKeystore {
+    /// Encrypts the given keystore file contents with keys derived from the given password.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - The plaintext contents of the keystore file
+    /// * `password` - The password that the file should be locked with
+    /// * `kdf_params` - The cost of stretching the password
+    pub fn seal(
+        contents: &[u8],
+        password: &str,
+        kdf_params: KdfParams,
+    ) -> Result<Self, KeystoreError> {
+        let (mut salt, mut iv) = (vec![0; SALT_SIZE], vec![0; IV_SIZE]);
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut iv);
+
+        let keys = kdf::derive_keys(password, &salt, kdf_params)?;
+
+        let mut ciphertext = vec![0; contents.len()];
+        aes::ctr(KeySize::KeySize256, &keys[..32], &iv).process(contents, &mut ciphertext);
+
+        let mut locked = Self {
+            version: KEYSTORE_VERSION,
+            cipher: KEYSTORE_CIPHER.to_owned(),
+            kdf: KEYSTORE_KDF.to_owned(),
+            kdf_params,
+            salt: bs58::encode(salt).into_string(),
+            iv: bs58::encode(iv).into_string(),
+            ciphertext: bs58::encode(ciphertext).into_string(),
+            mac: String::new(),
+        };
+        locked.mac = bs58::encode(locked.compute_mac(&keys[32..])?).into_string();
+
+        Ok(locked)
+    }
+
+    /// Checks the file's MAC, and decrypts its contents with keys derived from the given password.
+    ///
+    /// # Arguments
+    ///
+    /// * `password` - The password that the file was locked with
+    pub fn open(&self, password: &str) -> Result<Zeroizing<Vec<u8>>, KeystoreError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion {
+                version: self.version,
+            });
+        }
+        if self.cipher != KEYSTORE_CIPHER || self.kdf != KEYSTORE_KDF {
+            return Err(KeystoreError::Malformed {
+                error: format!("unsupported cipher {} or kdf {}", self.cipher, self.kdf),
+            });
+        }
+
+        let (salt, iv) = (decode(&self.salt)?, decode(&self.iv)?);
+        if salt.len() != SALT_SIZE {
+            return Err(KeystoreError::Malformed {
+                error: format!("the salt is {} bytes long, not {}", salt.len(), SALT_SIZE),
+            });
+        }
+        if iv.len() != IV_SIZE {
+            return Err(KeystoreError::Malformed {
+                error: format!("the IV is {} bytes long, not {}", iv.len(), IV_SIZE),
+            });
+        }
+
+        let keys = kdf::derive_keys(password, &salt, self.kdf_params)?;
+
+        // Check the MAC before touching the ciphertext
+        if !util::fixed_time_eq(&self.compute_mac(&keys[32..])?, &decode(&self.mac)?) {
+            return Err(KeystoreError::WrongPassword);
+        }
+
+        let ciphertext = decode(&self.ciphertext)?;
+
+        let mut contents = Zeroizing::new(vec![0; ciphertext.len()]);
+        aes::ctr(KeySize::KeySize256, &keys[..32], &iv).process(&ciphertext, &mut contents[..]);
+
+        Ok(contents)
+    }
+
+    /// Serializes the file into pretty-printed JSON.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, KeystoreError> {
+        Ok(serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Deserializes a locked keystore file, without checking its MAC. Results in None if the file isn't in the
+    /// versioned format at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `b` - The contents of the file
+    pub fn from_bytes(b: &[u8]) -> Option<Result<Self, KeystoreError>> {
+        // Anything that carries a version claims to be a versioned file, and is held to the format
+        let value: serde_json::Value = serde_json::from_slice(b).ok()?;
+        value.get("version")?;
+
+        Some(serde_json::from_value(value).map_err(KeystoreError::from))
+    }
+
+    /// Computes the MAC of the file with the given key. Each field is prefixed with its length, so that bytes can't be
+    /// moved from one field to the next without changing the MAC.
+    fn compute_mac(&self, mac_key: &[u8]) -> Result<Vec<u8>, KeystoreError> {
+        let mut hmac = Hmac::new(Sha256::new(), mac_key);
+        hmac.input(&self.version.to_le_bytes());
+        input_field(&mut hmac, self.cipher.as_bytes());
+        input_field(&mut hmac, self.kdf.as_bytes());
+        input_field(
+            &mut hmac,
+            &bincode::serialize(&self.kdf_params).map_err(|e| KeystoreError::Malformed {
+                error: e.to_string(),
+            })?,
+        );
+        input_field(&mut hmac, &decode(&self.salt)?);
+        input_field(&mut hmac, &decode(&self.iv)?);
+        input_field(&mut hmac, &decode(&self.ciphertext)?);
+
+        Ok(hmac.result().code().to_vec())
+    }
+}
+
+/// Feeds a variable-length field of a locked keystore file into its MAC, prefixed with the field's length.
+fn input_field(hmac: &mut Hmac<Sha256>, field: &[u8]) {
+    hmac.input(&(field.len() as u64).to_le_bytes());
+    hmac.input(field);
+}
+
+/// Decodes a base58-encoded field of a locked keystore file.
+fn decode(field: &str) -> Result<Vec<u8>, KeystoreError> {
+    bs58::decode(field)
+        .into_vec()
+        .map_err(|e| KeystoreError::Malformed {
+            error: e.to_string(),
+        })
+}
+
+/// Checks whether or not the given keystore file contents are locked, in either the versioned format or the one
+/// written by older builds.
+///
+/// # Arguments
+///
+/// * `contents` - The contents of the keystore file
+pub fn is_locked(contents: &[u8]) -> bool {
+    match LockedKeystore::from_bytes(contents) {
+        Some(_) => true,
+
+        // Older builds wrote AES ciphertext, which comes in whole blocks, straight to the file
+        None => {
+            serde_json::from_slice::<serde_json::Value>(contents).is_err()
+                && !contents.is_empty()
+                && contents.len() % 16 == 0
+        }
+    }
+}
+
+/// Locks the given keystore file contents with the given password, in the versioned format.
+///
+/// # Arguments
+///
+/// * `contents` - The plaintext contents of the keystore file
+/// * `password` - The password that the file should be locked with
+pub fn lock(contents: &[u8], password: &str) -> Result<Vec<u8>, KeystoreError> {
+    LockedKeystore::seal(contents, password, KdfParams::default())?.to_bytes()
+}
+
+/// Unlocks the given keystore file contents with the given password, whether they were locked in the versioned
+/// format, or by an older build.
+///
+/// # Arguments
+///
+/// * `contents` - The locked contents of the keystore file
+/// * `password` - The password that the file was locked with
+pub fn unlock(contents: &[u8], password: &str) -> Result<Zeroizing<Vec<u8>>, KeystoreError> {
+    match LockedKeystore::from_bytes(contents) {
+        Some(locked) => locked?.open(password),
+        None => account::decrypt_keystore(contents, password).ok_or(KeystoreError::WrongPassword),
+    }
+}
+
+/// Gets the path of the keystore file holding the account with the given address.
+fn keystore_path(address: &Address, data_dir: &str) -> String {
+    format!("{}/keystore/{}.json", data_dir, address.to_str())
+}
+
+/// Locks the keystore file of the account with the given address in the given data directory.
+///
+/// # Arguments
+///
+/// * `address` - The address of the account
+/// * `data_dir` - The data directory containing the account's keystore
+/// * `password` - The password that the account should be locked with
+pub fn lock_account(
+    address: &Address,
+    data_dir: &str,
+    password: &str,
+) -> Result<(), KeystoreError> {
+    let path = keystore_path(address, data_dir);
+    let contents = Zeroizing::new(fs::read(&path)?);
+
+    if is_locked(&contents) {
+        return Err(KeystoreError::AlreadyLocked);
+    }
+
+    // Make sure that what's being locked is actually an account, so that it can be unlocked again
+    serde_json::from_slice::<Account>(&contents)?;
+
+    fs::write(&path, lock(&contents, password)?)?;
+
+    Ok(())
+}
+
+/// Unlocks the keystore file of the account with the given address in the given data directory, leaving it unlocked
+/// on the disk. Files locked by older builds are rewritten in plaintext just the same.
+///
+/// # Arguments
+///
+/// * `address` - The address of the account
+/// * `data_dir` - The data directory containing the account's keystore
+/// * `password` - The password that the account was locked with
+pub fn unlock_account(
+    address: &Address,
+    data_dir: &str,
+    password: &str,
+) -> Result<Account, KeystoreError> {
+    let acc = read_locked_account(address, data_dir, password)?;
+    acc.write_to_disk_at_data_directory(data_dir)?;
+
+    Ok(acc)
+}
+
+/// Decrypts the keystore file of the account with the given address in the given data directory in memory, leaving
+/// the file on the disk locked.
+///
+/// # Arguments
+///
+/// * `address` - The address of the account
+/// * `data_dir` - The data directory containing the account's keystore
+/// * `password` - The password that the account was locked with
+pub fn read_locked_account(
+    address: &Address,
+    data_dir: &str,
+    password: &str,
+) -> Result<Account, KeystoreError> {
+    let contents = fs::read(keystore_path(address, data_dir))?;
+
+    if !is_locked(&contents) {
+        return Err(KeystoreError::NotLocked);
+    }
+
+    Ok(serde_json::from_slice(&unlock(&contents, password)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::common::io; // Import the io module
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_keystore() {
+        let cheap = KdfParams {
+            log_n: 10,
+            ..Default::default()
+        };
+
+        // Each sealing uses a fresh salt & IV, and only the right password opens the file
+        let (first, second) = (
+            LockedKeystore::seal(b"account", "hunter2", cheap).unwrap(),
+            LockedKeystore::seal(b"account", "hunter2", cheap).unwrap(),
+        );
+        assert_ne!(first.ciphertext, second.ciphertext);
+        assert_eq!(&first.open("hunter2").unwrap()[..], b"account");
+        assert_eq!(first.open("hunter3"), Err(KeystoreError::WrongPassword));
+
+        // Tampering is caught by the MAC
+        let mut tampered = first.clone();
+        tampered.kdf_params.log_n = 11;
+        assert_eq!(tampered.open("hunter2"), Err(KeystoreError::WrongPassword));
+
+        // Salts & IVs of the wrong size are refused outright
+        let mut short_salt = first.clone();
+        short_salt.salt = bs58::encode(vec![0; SALT_SIZE - 1]).into_string();
+        assert!(matches!(
+            short_salt.open("hunter2"),
+            Err(KeystoreError::Malformed { .. })
+        ));
+        let mut long_iv = first.clone();
+        long_iv.iv = bs58::encode(vec![0; IV_SIZE + 1]).into_string();
+        assert!(matches!(
+            long_iv.open("hunter2"),
+            Err(KeystoreError::Malformed { .. })
+        ));
+
+        // Files locked by older builds still unlock
+        let legacy = account::encrypt_keystore(b"{\"keypair\":[]}", "hunter2").unwrap();
+        assert!(is_locked(&legacy));
+        assert_eq!(
+            &unlock(&legacy, "hunter2").unwrap()[..],
+            b"{\"keypair\":[]}"
+        );
+
+        // Accounts can be locked & unlocked in place
+        let data_dir = format!("{}/.tests/keystore", io::data_dir());
+        let acc = Account::new();
+        let address = acc.address().unwrap();
+        acc.write_to_disk_at_data_directory(&data_dir).unwrap();
+
+        lock_account(&address, &data_dir, "hunter2").unwrap();
+        assert_eq!(
+            lock_account(&address, &data_dir, "hunter2"),
+            Err(KeystoreError::AlreadyLocked)
+        );
+        assert!(read_locked_account(&address, &data_dir, "hunter3").is_err());
+        assert_eq!(
+            unlock_account(&address, &data_dir, "hunter2")
+                .unwrap()
+                .address(),
+            Ok(address)
+        );
+        assert_eq!(
+            read_locked_account(&address, &data_dir, "hunter2").err(),
+            Some(KeystoreError::NotLocked)
+        );
+    }
+}
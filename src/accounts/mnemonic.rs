@@ -0,0 +1,242 @@
+use bip39::{Language, Mnemonic, MnemonicType, Seed}; // Import the BIP-39 mnemonic encoding
+
+use cryptolib::{hmac::Hmac, mac::Mac, sha2::Sha512}; // Import the SLIP-0010 key derivation primitives
+
+use std::{fmt, str::FromStr}; // Import the formatting & parsing traits
+
+use zeroize::Zeroizing; // Import helpers for wiping secrets from memory
+
+/// The coin type used in the default derivation path.
+pub const COIN_TYPE: u32 = 0x5343;
+
+/// The number of words in a newly generated mnemonic phrase, unless another length is asked for.
+pub const DEFAULT_WORD_COUNT: usize = 24;
+
+/// The index past which each index in a derivation path is hardened. Only hardened indices can be used with
+/// edwards25519 keys.
+pub const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// The key that the master key is derived from a seed with, as defined by SLIP-0010.
+const MASTER_KEY_SALT: &[u8] = b"ed25519 seed";
+
+/// An error encountered while generating or recovering a mnemonic-backed account.
+#[derive(Debug, Fail, PartialEq)]
+pub enum MnemonicError {
+    #[fail(display = "the mnemonic phrase is invalid: {}", error)]
+    InvalidPhrase {
+        error: String, // The underlying error
+    },
+    #[fail(display = "mnemonic phrases can't be {} words long", words)]
+    InvalidWordCount {
+        words: usize, // The number of words asked for
+    },
+    #[fail(display = "{} is not a valid derivation path: {}", path, reason)]
+    InvalidPath {
+        path: String,   // The derivation path
+        reason: String, // Why the path was refused
+    },
+    #[fail(display = "the derived key is invalid: {}", error)]
+    InvalidKey {
+        error: String, // The underlying error
+    },
+}
+
+/// A path of hardened indices leading from the master key of a seed to one of its accounts' keys (e.g.
+/// m/44'/21315'/0'/0'/0').
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivationPath {
+    /// The unhardened value of each index in the path, from the master key down
+    pub indices: Vec<u32>,
+}
+
+impl Default for DerivationPath {
+    /// Gets the path of the first account derived from a seed.
+    fn default() -> Self {
+        Self::for_account(0)
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = MnemonicError;
+
+    /// Parses a derivation path in the m/44'/21315'/0'/0'/0' notation. Since only hardened indices can be used, the
+    /// trailing apostrophes may be left out.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| MnemonicError::InvalidPath {
+            path: s.to_owned(),
+            reason: reason.to_owned(),
+        };
+
+        let mut parts = s.trim().split('/');
+        if parts.next() != Some("m") {
+            return Err(invalid("the path must begin with m"));
+        }
+
+        let indices = parts
+            .map(|part| {
+                part.trim_end_matches(|c| c == '\'' || c == 'h')
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|index| *index < HARDENED_OFFSET)
+                    .ok_or_else(|| invalid(&format!("{} is not a valid index", part)))
+            })
+            .collect::<Result<Vec<u32>, MnemonicError>>()?;
+
+        Ok(Self { indices })
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    /// Formats the path in the m/44'/21315'/0'/0'/0' notation.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+
+        for index in &self.indices {
+            write!(f, "/{}'", index)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Implement a set of derivation path helper methods.
+impl DerivationPath {
+    /// Gets the default path of the account with the given index, so that several accounts can be derived from the
+    /// same seed.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the account
+    pub fn for_account(index: u32) -> Self {
+        Self {
+            indices: vec![44, COIN_TYPE, index, 0, 0],
+        }
+    }
+
+    /// Gets the path of the given child of the key at this path.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The unhardened index of the child
+    pub fn child(&self, index: u32) -> Self {
+        let mut indices = self.indices.clone();
+        indices.push(index);
+
+        Self { indices }
+    }
+}
+
+/// Generates a new English mnemonic phrase with the given number of words (12, 15, 18, 21, or 24).
+///
+/// # Arguments
+///
+/// * `words` - The number of words in the phrase
+pub fn generate_phrase(words: usize) -> Result<Zeroizing<String>, MnemonicError> {
+    let kind = MnemonicType::for_word_count(words)
+        .map_err(|_| MnemonicError::InvalidWordCount { words })?;
+
+    Ok(Zeroizing::new(
+        Mnemonic::new(kind, Language::English).phrase().to_owned(),
+    ))
+}
+
+/// Derives the edwards25519 keypair at the given path from an English mnemonic phrase, per BIP-39 & SLIP-0010.
+///
+/// # Arguments
+///
+/// * `phrase` - The mnemonic phrase
+/// * `passphrase` - The optional passphrase that the phrase's seed is salted with (may be empty)
+/// * `path` - The path of the key that should be derived
+pub fn derive_keypair(
+    phrase: &str,
+    passphrase: &str,
+    path: &DerivationPath,
+) -> Result<ed25519_dalek::Keypair, MnemonicError> {
+    let mnemonic = Mnemonic::from_phrase(phrase.trim(), Language::English).map_err(|e| {
+        MnemonicError::InvalidPhrase {
+            error: e.to_string(),
+        }
+    })?;
+
+    derive_keypair_from_seed(Seed::new(&mnemonic, passphrase).as_bytes(), path)
+}
+
+/// Derives the edwards25519 keypair at the given path from a seed, per SLIP-0010.
+///
+/// # Arguments
+///
+/// * `seed` - The seed that the master key should be derived from
+/// * `path` - The path of the key that should be derived
+pub fn derive_keypair_from_seed(
+    seed: &[u8],
+    path: &DerivationPath,
+) -> Result<ed25519_dalek::Keypair, MnemonicError> {
+    let mut key = hmac_sha512(MASTER_KEY_SALT, seed);
+
+    for index in &path.indices {
+        // Each child is derived from its parent's key & chain code
+        let mut data = Zeroizing::new(Vec::with_capacity(37));
+        data.push(0);
+        data.extend_from_slice(&key[..32]);
+        data.extend_from_slice(&(index | HARDENED_OFFSET).to_be_bytes());
+
+        key = hmac_sha512(&key[32..], &data);
+    }
+
+    let secret = ed25519_dalek::SecretKey::from_bytes(&key[..32]).map_err(|e| {
+        MnemonicError::InvalidKey {
+            error: e.to_string(),
+        }
+    })?;
+    let public = ed25519_dalek::PublicKey::from(&secret);
+
+    Ok(ed25519_dalek::Keypair { secret, public })
+}
+
+/// Computes the HMAC-SHA512 of the given data, whose first half is a key, and whose second half is a chain code.
+///
+/// # Arguments
+///
+/// * `key` - The key that the data should be authenticated with
+/// * `data` - The data that should be authenticated
+fn hmac_sha512(key: &[u8], data: &[u8]) -> Zeroizing<Vec<u8>> {
+    let mut mac = Hmac::new(Sha512::new(), key);
+    mac.input(data);
+
+    Zeroizing::new(mac.result().code().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_derive_keypair() {
+        // The first test vector of SLIP-0010
+        let seed: Vec<u8> = (0..16).collect();
+        let keypair = derive_keypair_from_seed(&seed, &"m/0'".parse().unwrap()).unwrap();
+        assert_eq!(keypair.secret.as_bytes()[..4], [0x68, 0xe0, 0xfe, 0x46]);
+
+        // Paths survive being printed & parsed again, and refuse unhardenable indices
+        let path = DerivationPath::for_account(3);
+        assert_eq!(path.to_string().parse::<DerivationPath>().unwrap(), path);
+        assert!("m/2147483648".parse::<DerivationPath>().is_err());
+        assert!("44'/0'".parse::<DerivationPath>().is_err());
+
+        // The same phrase always derives the same keys, and different accounts get different keys
+        let phrase = generate_phrase(DEFAULT_WORD_COUNT).unwrap();
+        assert_eq!(phrase.split_whitespace().count(), DEFAULT_WORD_COUNT);
+        assert_eq!(
+            derive_keypair(&phrase, "", &path).unwrap().to_bytes()[..],
+            derive_keypair(&phrase, "", &path).unwrap().to_bytes()[..]
+        );
+        assert_ne!(
+            derive_keypair(&phrase, "", &path).unwrap().public,
+            derive_keypair(&phrase, "", &DerivationPath::default())
+                .unwrap()
+                .public
+        );
+        assert!(generate_phrase(13).is_err());
+        assert!(derive_keypair("not a mnemonic", "", &path).is_err());
+    }
+}
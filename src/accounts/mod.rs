@@ -1 +1,11 @@
 pub mod account; // Export the account module
+pub mod backup; // Export the wallet backup module
+pub mod contacts; // Export the address book module
+pub mod kdf; // Export the password stretching module
+pub mod keystore; // Export the versioned keystore encryption module
+pub mod mnemonic; // Export the mnemonic key derivation module
+pub mod policy; // Export the spending policy module
+pub mod resubmission; // Export the transaction resubmission module
+pub mod secrets; // Export the keystore secret store module
+pub mod signer; // Export the remote signer module
+pub mod unlock; // Export the unlock session module
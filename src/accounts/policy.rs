@@ -0,0 +1,451 @@
+use std::{collections::BTreeMap, fs, io, io::Write}; // Import the filesystem library
+
+use chrono::{DateTime, Duration, Utc}; // Import time utilities
+
+use num::{BigUint, Zero}; // Add support for large unsigned integers
+
+use rand::{rngs::OsRng, RngCore}; // Import the os's rng
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::super::{
+    common::address::Address,
+    core::types::{batch::BatchTransfer, transaction::Transaction},
+    crypto::{blake3, hash::Hash},
+}; // Import the address, transaction & hash types
+
+/// The name of the file in a data directory that the spending policy is read from. The policy can only be changed by
+/// editing this file, never over RPC, so that whoever is calling the RPC API can't loosen it.
+pub const SPENDING_POLICY_FILE: &str = "spending_policy.json";
+
+/// The name of the file in a data directory that recent spends, and pending confirmations, are recorded in.
+pub const SPENDING_LOG_FILE: &str = "spending_log.json";
+
+/// The number of hours that a daily spending limit covers.
+pub const SPENDING_WINDOW_HOURS: i64 = 24;
+
+/// An error encountered while checking a transaction against the spending policy.
+#[derive(Debug, Fail, PartialEq)]
+pub enum PolicyError {
+    #[fail(
+        display = "signing would take {} past its daily limit of {} finks ({} spent today, {} requested)",
+        account, limit, spent, requested
+    )]
+    DailyLimitExceeded {
+        account: String,    // The address of the account
+        limit: BigUint,     // The number of finks that the account may spend each day
+        spent: BigUint,     // The number of finks spent in the last day
+        requested: BigUint, // The number of finks that the transaction sends
+    },
+    #[fail(display = "{} may not send funds to {}", account, recipient)]
+    RecipientNotAllowed {
+        account: String,   // The address of the account
+        recipient: String, // The address that isn't on the account's allowlist
+    },
+    #[fail(
+        display = "transaction {} sends more than {} finks, and must be confirmed with the code in the node's log",
+        tx, threshold
+    )]
+    ConfirmationRequired {
+        tx: String,         // The hash of the transaction
+        threshold: BigUint, // The value above which transactions must be confirmed
+    },
+    #[fail(display = "the confirmation code for transaction {} is wrong", tx)]
+    InvalidConfirmation {
+        tx: String, // The hash of the transaction
+    },
+}
+
+/// The limits placed on a single account.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct AccountPolicy {
+    /// The greatest number of finks that the account may spend over any 24 hours, or None for no limit
+    pub daily_limit: Option<BigUint>,
+
+    /// The only addresses that the account may send funds to, or None to allow any address
+    pub allowed_recipients: Option<Vec<Address>>,
+
+    /// The value above which each transaction must be confirmed with a one-time code written to the node's log, or
+    /// None to never require confirmation
+    pub confirmation_threshold: Option<BigUint>,
+}
+
+/// The limits placed on each of the accounts in a data directory. Accounts without a policy aren't limited at all.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SpendingPolicy {
+    /// The limits placed on each account, by address
+    #[serde(default)]
+    pub accounts: BTreeMap<String, AccountPolicy>,
+}
+
+/// A transaction signed by an account.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Spend {
+    /// The hash of the transaction
+    pub tx: Hash,
+
+    /// The number of finks that the transaction sends
+    pub value: BigUint,
+
+    /// The time at which the transaction was signed
+    pub at: DateTime<Utc>,
+}
+
+/// The spends made by each account over the last day, along with the transactions waiting on a confirmation code.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SpendingLog {
+    /// The transactions signed by each account over the last day, by address
+    #[serde(default)]
+    pub spends: BTreeMap<String, Vec<Spend>>,
+
+    /// The digest of the confirmation code issued for each transaction waiting on one, by transaction hash
+    #[serde(default)]
+    pub confirmations: BTreeMap<String, Hash>,
+}
+
+/// How much an account has spent recently, and how much more it may spend.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SpendingStatus {
+    /// The limits placed on the account
+    pub policy: AccountPolicy,
+
+    /// The number of finks spent over the last day
+    pub spent_today: BigUint,
+
+    /// The number of finks that may still be spent today, or None if the account has no daily limit
+    pub remaining_today: Option<BigUint>,
+}
+
+/// Gets the value of the given transaction, and each of the addresses that it pays.
+///
+/// # Arguments
+///
+/// * `tx` - The transaction
+fn payments(tx: &Transaction) -> (BigUint, Vec<Address>) {
+    let data = &tx.transaction_data;
+
+    match BatchTransfer::from_payload(&data.payload) {
+        Ok(Some(batch)) => (
+            data.value.clone(),
+            batch
+                .transfers
+                .iter()
+                .map(|transfer| transfer.recipient)
+                .collect(),
+        ),
+        _ => (data.value.clone(), vec![data.recipient]),
+    }
+}
+
+/// Implement a set of spending policy helper methods.
+impl SpendingPolicy {
+    /// Gets the limits placed on the given account, if there are any.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The address of the account
+    pub fn get(&self, account: &Address) -> Option<&AccountPolicy> {
+        self.accounts.get(&account.to_str())
+    }
+
+    /// Read the spending policy from the given data directory. If no policy has been written, no account is limited.
+    pub fn read_from_disk_at_data_directory(data_dir: &str) -> io::Result<Self> {
+        let file = match fs::File::open(format!("{}/{}", data_dir, SPENDING_POLICY_FILE)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+
+        Ok(serde_json::from_reader(file)?) // Return read policy
+    }
+
+    /// Persist the spending policy to the given data directory.
+    pub fn write_to_disk_at_data_directory(&self, data_dir: &str) -> io::Result<()> {
+        fs::create_dir_all(data_dir)?; // Make the data directory, if it doesn't exist
+
+        let mut file = fs::File::create(format!("{}/{}", data_dir, SPENDING_POLICY_FILE))?; // Initialize file
+
+        file.write_all(serde_json::to_vec_pretty(self)?.as_slice())?; // Serialize
+
+        Ok(()) // All good!
+    }
+
+    /// Checks the given transaction against the policy of the account signing it, and records it as spent if it's
+    /// allowed. A transaction above the account's confirmation threshold is refused until it's signed again with the
+    /// confirmation code issued for it. The code is only ever written to the node's log, and never handed back to the
+    /// caller, so that someone who can only reach the RPC API can't confirm their own transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `log` - The spends made recently, and the confirmations issued so far
+    /// * `account` - The address of the account signing the transaction
+    /// * `tx` - The transaction being signed
+    /// * `confirmation` - The confirmation code given by the caller, if any
+    /// * `now` - The current time
+    pub fn authorize(
+        &self,
+        log: &mut SpendingLog,
+        account: &Address,
+        tx: &Transaction,
+        confirmation: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<(), PolicyError> {
+        log.prune(now);
+
+        let policy = match self.get(account) {
+            Some(policy) => policy,
+            None => return Ok(()),
+        };
+        let (value, recipients) = payments(tx);
+
+        if let Some(allowed) = &policy.allowed_recipients {
+            if let Some(recipient) = recipients.iter().find(|r| !allowed.contains(r)) {
+                return Err(PolicyError::RecipientNotAllowed {
+                    account: account.to_str(),
+                    recipient: recipient.to_str(),
+                });
+            }
+        }
+
+        // Signing the same transaction again doesn't spend anything more
+        let already_spent = log.spends.get(&account.to_str()).map_or(false, |spends| {
+            spends.iter().any(|spend| spend.tx == tx.hash)
+        });
+
+        if let Some(limit) = &policy.daily_limit {
+            let spent = log.spent_by(account);
+
+            if !already_spent && &spent + &value > *limit {
+                return Err(PolicyError::DailyLimitExceeded {
+                    account: account.to_str(),
+                    limit: limit.clone(),
+                    spent,
+                    requested: value,
+                });
+            }
+        }
+
+        if let Some(threshold) = &policy.confirmation_threshold {
+            if value > *threshold {
+                log.confirm(tx.hash, threshold, confirmation)
+                    .map_err(|(e, code)| {
+                        warn!(
+                            "Confirmation code for transaction {} ({} finks from {}): {}",
+                            tx.hash, value, account, code
+                        );
+
+                        e
+                    })?;
+            }
+        }
+
+        if !already_spent {
+            log.spends.entry(account.to_str()).or_default().push(Spend {
+                tx: tx.hash,
+                value,
+                at: now,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Implement a set of spending log helper methods.
+impl SpendingLog {
+    /// Forgets each of the spends that no longer count towards a daily limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        let cutoff = now - Duration::hours(SPENDING_WINDOW_HOURS);
+
+        for spends in self.spends.values_mut() {
+            spends.retain(|spend| spend.at > cutoff);
+        }
+        self.spends.retain(|_, spends| !spends.is_empty());
+    }
+
+    /// Gets the number of finks spent by the given account over the last day.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The address of the account
+    pub fn spent_by(&self, account: &Address) -> BigUint {
+        self.spends
+            .get(&account.to_str())
+            .map_or_else(BigUint::zero, |spends| {
+                spends
+                    .iter()
+                    .fold(BigUint::zero(), |total, spend| total + &spend.value)
+            })
+    }
+
+    /// Gets how much the given account has spent recently, and how much more it may spend.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The spending policy of the data directory holding the account
+    /// * `account` - The address of the account
+    pub fn status(&self, policy: &SpendingPolicy, account: &Address) -> SpendingStatus {
+        let policy = policy.get(account).cloned().unwrap_or_default();
+        let spent_today = self.spent_by(account);
+
+        SpendingStatus {
+            remaining_today: policy.daily_limit.as_ref().map(|limit| {
+                if *limit > spent_today {
+                    limit - &spent_today
+                } else {
+                    BigUint::zero()
+                }
+            }),
+            policy,
+            spent_today,
+        }
+    }
+
+    /// Checks the confirmation code given for the transaction with the given hash. If no code has been issued for the
+    /// transaction yet, or the given code is wrong, a fresh code is issued, and handed back alongside the error.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The hash of the transaction
+    /// * `threshold` - The value above which the transaction's sender must confirm its transactions
+    /// * `code` - The confirmation code given by the caller, if any
+    fn confirm(
+        &mut self,
+        tx: Hash,
+        threshold: &BigUint,
+        code: Option<&str>,
+    ) -> Result<(), (PolicyError, String)> {
+        let key = tx.to_str();
+
+        let error = match (self.confirmations.get(&key), code) {
+            (Some(digest), Some(code)) if blake3::hash_slice(code.as_bytes()) == *digest => {
+                self.confirmations.remove(&key);
+
+                return Ok(());
+            }
+            (Some(_), Some(_)) => PolicyError::InvalidConfirmation { tx: key.clone() },
+            _ => PolicyError::ConfirmationRequired {
+                tx: key.clone(),
+                threshold: threshold.clone(),
+            },
+        };
+
+        // Issue a fresh code, so that a wrong guess can't be followed by another
+        let mut bytes = [0u8; 4];
+        OsRng.fill_bytes(&mut bytes);
+        let code = format!("{:08}", u32::from_le_bytes(bytes) % 100_000_000);
+        self.confirmations
+            .insert(key, blake3::hash_slice(code.as_bytes()));
+
+        Err((error, code))
+    }
+
+    /// Read the spending log from the given data directory. If nothing has been spent yet, an empty log is returned.
+    pub fn read_from_disk_at_data_directory(data_dir: &str) -> io::Result<Self> {
+        let file = match fs::File::open(format!("{}/{}", data_dir, SPENDING_LOG_FILE)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+
+        Ok(serde_json::from_reader(file)?) // Return read log
+    }
+
+    /// Persist the spending log to the given data directory.
+    pub fn write_to_disk_at_data_directory(&self, data_dir: &str) -> io::Result<()> {
+        fs::create_dir_all(data_dir)?; // Make the data directory, if it doesn't exist
+
+        let mut file = fs::File::create(format!("{}/{}", data_dir, SPENDING_LOG_FILE))?; // Initialize file
+
+        file.write_all(serde_json::to_vec_pretty(self)?.as_slice())?; // Serialize
+
+        Ok(()) // All good!
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_authorize() {
+        let alice = blake3::hash_slice(b"alice");
+        let bob = blake3::hash_slice(b"bob");
+        let mallory = blake3::hash_slice(b"mallory");
+
+        let mut policy = SpendingPolicy::default();
+        policy.accounts.insert(
+            alice.to_str(),
+            AccountPolicy {
+                daily_limit: Some(BigUint::from(100 as u8)),
+                allowed_recipients: Some(vec![bob]),
+                confirmation_threshold: Some(BigUint::from(50 as u8)),
+            },
+        );
+        let mut log = SpendingLog::default();
+        let now = Utc::now();
+        let tx = |nonce, recipient, value: u8| {
+            Transaction::new(nonce, alice, recipient, BigUint::from(value), b"", vec![])
+        };
+
+        // Only allowlisted recipients may be paid
+        assert_eq!(
+            policy.authorize(&mut log, &alice, &tx(0, mallory, 1), None, now),
+            Err(PolicyError::RecipientNotAllowed {
+                account: alice.to_str(),
+                recipient: mallory.to_str(),
+            })
+        );
+        assert!(policy
+            .authorize(&mut log, &alice, &tx(0, bob, 40), None, now)
+            .is_ok());
+
+        // Large transactions wait on the code written to the log
+        let large = tx(1, bob, 60);
+        assert!(policy
+            .authorize(&mut log, &alice, &large, None, now)
+            .is_err());
+        assert!(log.confirmations.contains_key(&large.hash.to_str()));
+
+        // A wrong guess burns the code, and the fresh one lets the transaction through exactly once
+        let threshold = BigUint::from(50 as u8);
+        let (e, code) = log
+            .confirm(large.hash, &threshold, Some("wrong"))
+            .unwrap_err();
+        assert_eq!(
+            e,
+            PolicyError::InvalidConfirmation {
+                tx: large.hash.to_str()
+            }
+        );
+        assert!(policy
+            .authorize(&mut log, &alice, &large, Some(&code), now)
+            .is_ok());
+        assert!(log.confirmations.is_empty());
+        assert_eq!(log.spent_by(&alice), BigUint::from(100 as u8));
+
+        // The day's limit has been reached, but it frees up once the spends are a day old
+        assert!(policy
+            .authorize(&mut log, &alice, &tx(2, bob, 1), None, now)
+            .is_err());
+        assert!(policy
+            .authorize(
+                &mut log,
+                &alice,
+                &tx(2, bob, 1),
+                None,
+                now + Duration::hours(25)
+            )
+            .is_ok());
+
+        // Accounts without a policy aren't limited
+        assert!(policy
+            .authorize(&mut log, &bob, &tx(0, mallory, 255), None, now)
+            .is_ok());
+    }
+}
@@ -0,0 +1,272 @@
+use std::str::FromStr; // Allow strategies to be parsed from the command line
+
+use chrono::{DateTime, Duration, Utc}; // Import time utilities
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::super::crypto::hash::Hash; // Import the hash type
+
+/// The number of seconds that a published transaction is given to be executed before it's resubmitted, by default.
+pub const DEFAULT_RESUBMISSION_WINDOW_SECS: u64 = 60;
+
+/// The number of times that a transaction is resubmitted before it's given up on, by default.
+pub const DEFAULT_MAX_RESUBMISSIONS: u32 = 3;
+
+/// The number of seconds waited between checks on whether a published transaction has been executed.
+pub const POLL_INTERVAL_SECS: u64 = 5;
+
+/// An error encountered while configuring the resubmission of a transaction.
+#[derive(Debug, Fail, PartialEq)]
+pub enum ResubmissionError {
+    #[fail(
+        display = "unknown resubmission strategy {} (expected regossip or rebuild)",
+        strategy
+    )]
+    UnknownStrategy {
+        strategy: String, // The strategy that was asked for
+    },
+}
+
+/// How a transaction that missed its deadline is resubmitted.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResubmissionStrategy {
+    /// Gossip the same transaction to the network again
+    Regossip,
+
+    /// Build a replacement spending the same nonce on top of the DAG's new head, with fresh parents, and sign it
+    Rebuild,
+}
+
+impl FromStr for ResubmissionStrategy {
+    type Err = ResubmissionError;
+
+    /// Parses a strategy written as regossip or rebuild.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "regossip" => Ok(Self::Regossip),
+            "rebuild" => Ok(Self::Rebuild),
+            _ => Err(ResubmissionError::UnknownStrategy {
+                strategy: s.to_owned(),
+            }),
+        }
+    }
+}
+
+/// How long a published transaction is given to be executed, and what's done about it if it isn't.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResubmissionPolicy {
+    /// The number of seconds that each attempt is given to be executed
+    pub window_secs: u64,
+
+    /// The number of times that the transaction may be resubmitted before it's given up on
+    pub max_resubmissions: u32,
+
+    /// How the transaction is resubmitted
+    pub strategy: ResubmissionStrategy,
+}
+
+impl Default for ResubmissionPolicy {
+    /// Builds a policy regossiping the transaction with the default window & number of resubmissions.
+    fn default() -> Self {
+        Self {
+            window_secs: DEFAULT_RESUBMISSION_WINDOW_SECS,
+            max_resubmissions: DEFAULT_MAX_RESUBMISSIONS,
+            strategy: ResubmissionStrategy::Regossip,
+        }
+    }
+}
+
+/// What should be done next about a published transaction that hasn't been executed yet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResubmissionAction {
+    /// Keep waiting for the transaction to be executed, until the given deadline
+    Wait { deadline: DateTime<Utc> },
+
+    /// Resubmit the transaction in the given way; this is the given resubmission, counting from one
+    Resubmit {
+        resubmission: u32,
+        strategy: ResubmissionStrategy,
+    },
+
+    /// Stop waiting for the transaction, since it has been resubmitted as many times as it may be
+    GiveUp,
+}
+
+/// How the submission of a transaction turned out.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResubmissionOutcome {
+    /// The transaction (or one of its replacements) was executed after the given number of resubmissions
+    Executed { hash: Hash, resubmissions: u32 },
+
+    /// None of the submitted transactions were executed in time
+    Abandoned {
+        hashes: Vec<Hash>,
+        resubmissions: u32,
+    },
+}
+
+/// A published transaction being watched until it's executed, and resubmitted each time it misses its deadline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resubmission {
+    /// How the transaction is resubmitted
+    pub policy: ResubmissionPolicy,
+
+    /// The hash of each of the transactions submitted so far, the original first. Rebuilding a transaction gives it a
+    /// new hash, and any of them may still be executed.
+    hashes: Vec<Hash>,
+
+    /// The number of times that the transaction has been resubmitted
+    resubmissions: u32,
+
+    /// When the latest submission is due to have been executed
+    deadline: DateTime<Utc>,
+}
+
+/// Implement a set of resubmission helper methods.
+impl Resubmission {
+    /// Starts watching the transaction with the given hash, which was just published.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How long the transaction is given to be executed, and what's done about it if it isn't
+    /// * `hash` - The hash of the transaction
+    /// * `now` - The current time
+    pub fn new(policy: ResubmissionPolicy, hash: Hash, now: DateTime<Utc>) -> Self {
+        let deadline = now + Duration::seconds(policy.window_secs as i64);
+
+        Self {
+            policy,
+            hashes: vec![hash],
+            resubmissions: 0,
+            deadline,
+        }
+    }
+
+    /// Gets the hash of each of the transactions submitted so far, the original first.
+    pub fn hashes(&self) -> &[Hash] {
+        &self.hashes
+    }
+
+    /// Gets the hash of the latest transaction submitted.
+    pub fn latest(&self) -> Hash {
+        self.hashes[self.hashes.len() - 1]
+    }
+
+    /// Works out what should be done about the transaction, given that none of its submissions have been executed.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    pub fn next_action(&self, now: DateTime<Utc>) -> ResubmissionAction {
+        if now < self.deadline {
+            ResubmissionAction::Wait {
+                deadline: self.deadline,
+            }
+        } else if self.resubmissions < self.policy.max_resubmissions {
+            ResubmissionAction::Resubmit {
+                resubmission: self.resubmissions + 1,
+                strategy: self.policy.strategy,
+            }
+        } else {
+            ResubmissionAction::GiveUp
+        }
+    }
+
+    /// Records that the transaction has been resubmitted as the transaction with the given hash (the same hash, if it
+    /// was regossiped), giving it another window in which to be executed.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction that was submitted
+    /// * `now` - The current time
+    pub fn resubmitted(&mut self, hash: Hash, now: DateTime<Utc>) {
+        if !self.hashes.contains(&hash) {
+            self.hashes.push(hash);
+        }
+
+        self.resubmissions += 1;
+        self.deadline = now + Duration::seconds(self.policy.window_secs as i64);
+    }
+
+    /// Describes how the submission turned out, given the hash of the submitted transaction that was executed, if
+    /// any was.
+    ///
+    /// # Arguments
+    ///
+    /// * `executed` - The hash of the transaction that was executed, if one was
+    pub fn outcome(&self, executed: Option<Hash>) -> ResubmissionOutcome {
+        match executed {
+            Some(hash) => ResubmissionOutcome::Executed {
+                hash,
+                resubmissions: self.resubmissions,
+            },
+            None => ResubmissionOutcome::Abandoned {
+                hashes: self.hashes.clone(),
+                resubmissions: self.resubmissions,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::crypto::blake3; // Import the blake3 hashing module
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_resubmission() {
+        let now = Utc::now();
+        let (original, rebuilt) = (blake3::hash_slice(b"a"), blake3::hash_slice(b"b"));
+        let policy = ResubmissionPolicy {
+            window_secs: 30,
+            max_resubmissions: 2,
+            strategy: "rebuild".parse().unwrap(),
+        };
+        let mut resubmission = Resubmission::new(policy, original, now);
+
+        // Each submission is given its window before anything is done about it
+        assert_eq!(
+            resubmission.next_action(now + Duration::seconds(29)),
+            ResubmissionAction::Wait {
+                deadline: now + Duration::seconds(30)
+            }
+        );
+        assert_eq!(
+            resubmission.next_action(now + Duration::seconds(30)),
+            ResubmissionAction::Resubmit {
+                resubmission: 1,
+                strategy: ResubmissionStrategy::Rebuild
+            }
+        );
+
+        resubmission.resubmitted(rebuilt, now + Duration::seconds(30));
+        resubmission.resubmitted(rebuilt, now + Duration::seconds(60));
+        assert_eq!(resubmission.hashes(), &[original, rebuilt]);
+        assert_eq!(resubmission.latest(), rebuilt);
+
+        // Once the resubmissions run out, the transaction is given up on
+        assert_eq!(
+            resubmission.next_action(now + Duration::seconds(90)),
+            ResubmissionAction::GiveUp
+        );
+        assert_eq!(
+            resubmission.outcome(None),
+            ResubmissionOutcome::Abandoned {
+                hashes: vec![original, rebuilt],
+                resubmissions: 2
+            }
+        );
+
+        // The original may still be executed after it has been replaced
+        assert_eq!(
+            resubmission.outcome(Some(original)),
+            ResubmissionOutcome::Executed {
+                hash: original,
+                resubmissions: 2
+            }
+        );
+        assert!("resend".parse::<ResubmissionStrategy>().is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use super::{
+    super::{common::address::Address, crypto::blake3},
+    account::{self, Account},
+}; // Import the account types
+
+/// The service name under which keystore passwords are filed in the OS keychain.
+pub const KEYCHAIN_SERVICE: &str = "summercash-keystore";
+
+/// An error encountered while reading from, or writing to, a secret store.
+#[derive(Debug, Fail)]
+pub enum SecretStoreError {
+    #[fail(
+        display = "unknown secret store backend: {} (expected one of none, keychain)",
+        backend
+    )]
+    UnknownBackend {
+        backend: String, // The backend that was asked for
+    },
+    #[fail(
+        display = "the {} secret store isn't available in this build; rebuild with --features keychain",
+        backend
+    )]
+    Unavailable {
+        backend: String, // The backend that was asked for
+    },
+    #[fail(display = "the secret store failed: {}", error)]
+    BackendFailure {
+        error: String, // The underlying error
+    },
+}
+
+/// A place to keep the passwords that keystore accounts are locked with, so that a node can unlock its accounts
+/// without anyone typing them in.
+pub trait SecretStore {
+    /// Gets the password that the account with the given address is locked with, if one has been stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the locked account
+    fn get(&self, address: &Address) -> Result<Option<String>, SecretStoreError>;
+
+    /// Stores the password that the account with the given address is locked with, replacing any existing password.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the locked account
+    /// * `secret` - The password that the account is locked with
+    fn set(&self, address: &Address, secret: &str) -> Result<(), SecretStoreError>;
+
+    /// Removes the password stored for the account with the given address, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the account whose password should be forgotten
+    fn delete(&self, address: &Address) -> Result<(), SecretStoreError>;
+}
+
+/// A secret store backed by the operating system's keychain: the Keychain on macOS, the Credential Manager on
+/// Windows, or the Secret Service on Linux.
+#[cfg(feature = "keychain")]
+pub struct KeychainSecretStore {
+    /// The service name that passwords are filed under
+    service: String,
+}
+
+#[cfg(feature = "keychain")]
+impl KeychainSecretStore {
+    /// Initializes a new keychain secret store, filing passwords under the given service name.
+    ///
+    /// # Arguments
+    ///
+    /// * `service` - The service name that passwords should be filed under
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.to_owned(),
+        }
+    }
+}
+
+#[cfg(feature = "keychain")]
+impl SecretStore for KeychainSecretStore {
+    /// Gets the password that the account with the given address is locked with, if one has been stored.
+    fn get(&self, address: &Address) -> Result<Option<String>, SecretStoreError> {
+        let username = address.to_str();
+
+        match keyring::Keyring::new(&self.service, &username).get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::KeyringError::NoPasswordFound) => Ok(None),
+            Err(e) => Err(SecretStoreError::BackendFailure {
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    /// Stores the password that the account with the given address is locked with.
+    fn set(&self, address: &Address, secret: &str) -> Result<(), SecretStoreError> {
+        let username = address.to_str();
+
+        keyring::Keyring::new(&self.service, &username)
+            .set_password(secret)
+            .map_err(|e| SecretStoreError::BackendFailure {
+                error: e.to_string(),
+            })
+    }
+
+    /// Removes the password stored for the account with the given address.
+    fn delete(&self, address: &Address) -> Result<(), SecretStoreError> {
+        let username = address.to_str();
+
+        match keyring::Keyring::new(&self.service, &username).delete_password() {
+            Ok(_) | Err(keyring::KeyringError::NoPasswordFound) => Ok(()),
+            Err(e) => Err(SecretStoreError::BackendFailure {
+                error: e.to_string(),
+            }),
+        }
+    }
+}
+
+/// Opens the secret store with the given name: "none" or "keychain". Returns None if the user doesn't want to use a
+/// secret store at all. Passwords are never written to disk in the clear, so there's no file-backed store.
+///
+/// # Arguments
+///
+/// * `backend` - The name of the secret store that should be opened
+pub fn open(backend: &str) -> Result<Option<Box<dyn SecretStore>>, SecretStoreError> {
+    match backend {
+        "none" => Ok(None),
+        #[cfg(feature = "keychain")]
+        "keychain" => Ok(Some(Box::new(KeychainSecretStore::new(KEYCHAIN_SERVICE)))),
+        #[cfg(not(feature = "keychain"))]
+        "keychain" => Err(SecretStoreError::Unavailable {
+            backend: backend.to_owned(),
+        }),
+        _ => Err(SecretStoreError::UnknownBackend {
+            backend: backend.to_owned(),
+        }),
+    }
+}
+
+/// Unlocks, in memory, each of the locked accounts in the given data directory whose password is held by the given
+/// secret store. The keystore files themselves stay locked.
+///
+/// # Arguments
+///
+/// * `store` - The secret store holding the accounts' passwords
+/// * `data_dir` - The data directory containing the keystore
+pub fn unlock_accounts(store: &dyn SecretStore, data_dir: &str) -> Vec<Account> {
+    let mut accounts: Vec<Account> = vec![]; // The accounts that we managed to unlock
+
+    for address in account::get_all_unlocked_accounts_in_data_directory(data_dir) {
+        // Accounts that aren't locked don't need a password
+        if Account::read_from_disk_at_data_directory(address, data_dir).is_ok()
+            || address == blake3::hash_slice(b"p2p_identity")
+        {
+            continue;
+        }
+
+        match store.get(&address) {
+            Ok(Some(secret)) => {
+                match Account::read_locked_from_disk_at_data_directory(address, data_dir, &secret) {
+                    Ok(acc) => accounts.push(acc),
+                    Err(_) => warn!(
+                        "The stored password for account {} doesn't unlock it",
+                        address
+                    ),
+                }
+            }
+            Ok(None) => debug!("No password stored for locked account {}", address),
+            Err(e) => warn!("Failed to read the password for account {}: {}", address, e),
+        }
+    }
+
+    accounts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    use std::{cell::RefCell, collections::HashMap, fs};
+
+    /// A secret store that only keeps its passwords in memory.
+    #[derive(Default)]
+    struct MemorySecretStore {
+        /// The stored passwords
+        secrets: RefCell<HashMap<Address, String>>,
+    }
+
+    impl SecretStore for MemorySecretStore {
+        fn get(&self, address: &Address) -> Result<Option<String>, SecretStoreError> {
+            Ok(self.secrets.borrow().get(address).cloned())
+        }
+
+        fn set(&self, address: &Address, secret: &str) -> Result<(), SecretStoreError> {
+            self.secrets
+                .borrow_mut()
+                .insert(*address, secret.to_owned());
+
+            Ok(())
+        }
+
+        fn delete(&self, address: &Address) -> Result<(), SecretStoreError> {
+            self.secrets.borrow_mut().remove(address);
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_open() {
+        assert!(open("none").unwrap().is_none());
+        assert!(open("file").is_err()); // Passwords never go to disk in the clear
+        assert!(open("fridge").is_err());
+    }
+
+    #[test]
+    fn test_unlock_accounts() {
+        let data_dir = format!(".tests/secrets/{}", rand::random::<u32>()); // Make a data directory
+        let store = MemorySecretStore::default();
+
+        let acc = Account::new(); // Make an account
+        let address = acc.address().unwrap();
+        acc.write_to_disk_at_data_directory(&data_dir).unwrap();
+
+        // Lock the account
+        let contents =
+            fs::read(format!("{}/keystore/{}.json", data_dir, address.to_str())).unwrap();
+        fs::write(
+            format!("{}/keystore/{}.json", data_dir, address.to_str()),
+            account::encrypt_keystore(&contents, "hunter2").unwrap(),
+        )
+        .unwrap();
+
+        assert!(unlock_accounts(&store, &data_dir).is_empty()); // We don't know the password yet
+
+        store.set(&address, "hunter2").unwrap(); // Remember the password
+        let unlocked = unlock_accounts(&store, &data_dir);
+        assert_eq!(unlocked.len(), 1);
+        assert_eq!(unlocked[0].address().unwrap(), address); // Ensure the account was unlocked
+
+        assert!(Account::read_from_disk_at_data_directory(address, &data_dir).is_err());
+        // The file should stay locked
+    }
+}
@@ -0,0 +1,686 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    time::Duration,
+}; // Import the filesystem & io libraries
+
+use chrono::Utc; // Import time utilities
+
+use rand::{rngs::OsRng, RngCore}; // Import the os's rng
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::{
+    super::{
+        common::address::Address,
+        core::{
+            sys::vote::Vote,
+            types::{
+                signature::Signature,
+                transaction::{self, Transaction},
+            },
+        },
+        crypto::{
+            blake3,
+            hash::{Hash, HASH_SIZE},
+        },
+        p2p::envelope,
+    },
+    account::Account,
+    policy::{SpendingLog, SpendingPolicy},
+}; // Import the account, vote, transaction & envelope types
+
+/// The name of the socket that the signer listens on, in its data directory, unless told otherwise.
+pub const DEFAULT_SOCKET_FILE: &str = "signer.sock";
+
+/// The name of the file in the signer's data directory holding the token that callers must present. Only the node
+/// should be given a copy of it.
+pub const TOKEN_FILE: &str = "signer.token";
+
+/// The number of seconds that the node waits on the signer before giving up on a request.
+pub const SIGNER_TIMEOUT_SECS: u64 = 5;
+
+/// The largest request, in bytes, that the signer will read.
+pub const MAX_REQUEST_SIZE: u64 = 1024 * 1024;
+
+/// An error encountered while asking a remote signer to sign something.
+#[derive(Debug, Fail, PartialEq)]
+pub enum SignerError {
+    #[fail(display = "the signer at {} couldn't be reached: {}", socket, error)]
+    Unreachable {
+        socket: String, // The path of the signer's socket
+        error: String,  // The underlying error
+    },
+    #[fail(display = "the signer refused the request: {}", reason)]
+    Refused {
+        reason: String, // Why the signer refused
+    },
+    #[fail(display = "the signer sent back a malformed response: {}", error)]
+    MalformedResponse {
+        error: String, // The underlying error
+    },
+    #[fail(display = "remote signers are only supported over unix domain sockets")]
+    Unsupported,
+}
+
+/// A request made to a remote signer. The signer only ever signs votes and transactions that it has seen in full, so
+/// that it can't be turned into an oracle for arbitrary messages.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum SignerRequest {
+    /// Lists the accounts that the signer holds the keys to
+    ListAccounts,
+
+    /// Casts a vote on a proposal with one of the signer's accounts, and seals it for gossip on the given topic
+    CastVote {
+        account: Address,
+        proposal_id: Hash,
+        in_favor: bool,
+        topic: String,
+    },
+
+    /// Signs a transaction sent by one of the signer's accounts, or by one of their sub-addresses
+    SignTransaction {
+        tx: Transaction,
+        confirmation: Option<String>,
+    },
+}
+
+/// A response sent back by a remote signer.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "result", content = "value", rename_all = "snake_case")]
+pub enum SignerResponse {
+    /// The addresses of each of the signer's accounts
+    Accounts(Vec<Address>),
+
+    /// A cast vote, along with the same vote sealed for gossip
+    Vote { vote: Vote, sealed: Vec<u8> },
+
+    /// The signature of a transaction
+    Signature(Signature),
+
+    /// The reason that the signer refused the request
+    Refused(String),
+}
+
+/// A request, along with the token proving that the caller may make it.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AuthenticatedRequest {
+    /// The token held in the signer's token file
+    pub token: String,
+
+    /// The request being made
+    pub request: SignerRequest,
+}
+
+/// Reads the token that callers of the signer with the given data directory must present, generating one if it
+/// doesn't exist yet.
+///
+/// # Arguments
+///
+/// * `data_dir` - The signer's data directory
+pub fn read_or_generate_token(data_dir: &str) -> io::Result<String> {
+    let path = format!("{}/{}", data_dir, TOKEN_FILE);
+
+    match fs::read_to_string(&path) {
+        Ok(token) => return Ok(token.trim().to_owned()),
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => (),
+    }
+
+    let mut bytes = [0u8; HASH_SIZE];
+    OsRng.fill_bytes(&mut bytes);
+    let token = Hash::new(bytes.to_vec()).to_str();
+
+    fs::create_dir_all(data_dir)?; // Make the data directory, if it doesn't exist
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    // Nobody but the signer's user should be able to read the token
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    options.open(&path)?.write_all(token.as_bytes())?;
+
+    Ok(token)
+}
+
+/// A client for a signer running in a separate process, which holds the node's keys so that they never enter the
+/// address space of the network-facing daemon.
+#[derive(Clone)]
+pub struct RemoteSigner {
+    /// The path of the socket that the signer listens on
+    pub socket: String,
+
+    /// The token proving that the node may use the signer
+    token: String,
+}
+
+/// Implement a set of remote signer helper methods.
+impl RemoteSigner {
+    /// Initializes a new client for the signer listening on the given socket.
+    ///
+    /// # Arguments
+    ///
+    /// * `socket` - The path of the signer's socket
+    /// * `token` - The token held in the signer's token file
+    pub fn new(socket: &str, token: &str) -> Self {
+        Self {
+            socket: socket.to_owned(),
+            token: token.trim().to_owned(),
+        }
+    }
+
+    /// Initializes a new client for the signer listening on the given socket, reading its token from the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `socket` - The path of the signer's socket
+    /// * `token_file` - The path of a copy of the signer's token file
+    pub fn open(socket: &str, token_file: &str) -> io::Result<Self> {
+        Ok(Self::new(socket, &fs::read_to_string(token_file)?))
+    }
+
+    /// Gets the addresses of each of the accounts that the signer holds the keys to.
+    pub fn list_accounts(&self) -> Result<Vec<Address>, SignerError> {
+        match self.call(SignerRequest::ListAccounts)? {
+            SignerResponse::Accounts(accounts) => Ok(accounts),
+            _ => Err(unexpected_response()),
+        }
+    }
+
+    /// Has the signer cast a vote on the given proposal with one of its accounts. Results in the vote, along with the
+    /// same vote sealed for gossip.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The address of the account that should vote
+    /// * `proposal_id` - The hash of the proposal being voted on
+    /// * `in_favor` - Whether or not the vote is in favor of the proposal
+    /// * `topic` - The topic that the vote will be published to
+    pub fn cast_vote(
+        &self,
+        account: Address,
+        proposal_id: Hash,
+        in_favor: bool,
+        topic: &str,
+    ) -> Result<(Vote, Vec<u8>), SignerError> {
+        match self.call(SignerRequest::CastVote {
+            account,
+            proposal_id,
+            in_favor,
+            topic: topic.to_owned(),
+        })? {
+            SignerResponse::Vote { vote, sealed } => Ok((vote, sealed)),
+            _ => Err(unexpected_response()),
+        }
+    }
+
+    /// Has the signer sign the given transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that should be signed
+    /// * `confirmation` - The confirmation code written to the signer's log, if the signer's spending policy asks for
+    /// one
+    pub fn sign_transaction(
+        &self,
+        tx: &Transaction,
+        confirmation: Option<String>,
+    ) -> Result<Signature, SignerError> {
+        match self.call(SignerRequest::SignTransaction {
+            tx: tx.clone(),
+            confirmation,
+        })? {
+            SignerResponse::Signature(signature) => Ok(signature),
+            _ => Err(unexpected_response()),
+        }
+    }
+
+    /// Sends the given request to the signer, and waits on its response.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request that should be made
+    fn call(&self, request: SignerRequest) -> Result<SignerResponse, SignerError> {
+        let request = serde_json::to_vec(&AuthenticatedRequest {
+            token: self.token.clone(),
+            request,
+        })
+        .map_err(|e| SignerError::MalformedResponse {
+            error: e.to_string(),
+        })?;
+
+        let response = self
+            .round_trip(&request)
+            .map_err(|e| SignerError::Unreachable {
+                socket: self.socket.clone(),
+                error: e.to_string(),
+            })?;
+
+        match serde_json::from_slice(&response) {
+            Ok(SignerResponse::Refused(reason)) => Err(SignerError::Refused { reason }),
+            Ok(response) => Ok(response),
+            Err(e) => Err(SignerError::MalformedResponse {
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    /// Writes the given serialized request to the signer's socket, and reads back a single line in response.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The serialized request
+    #[cfg(unix)]
+    fn round_trip(&self, request: &[u8]) -> io::Result<Vec<u8>> {
+        let mut stream = std::os::unix::net::UnixStream::connect(&self.socket)?;
+        stream.set_read_timeout(Some(Duration::from_secs(SIGNER_TIMEOUT_SECS)))?;
+        stream.set_write_timeout(Some(Duration::from_secs(SIGNER_TIMEOUT_SECS)))?;
+
+        stream.write_all(request)?;
+        stream.write_all(b"\n")?;
+
+        let mut response = Vec::new();
+        BufReader::new(stream.take(MAX_REQUEST_SIZE)).read_until(b'\n', &mut response)?;
+
+        Ok(response)
+    }
+
+    /// Remote signers are only supported over unix domain sockets.
+    #[cfg(not(unix))]
+    fn round_trip(&self, _request: &[u8]) -> io::Result<Vec<u8>> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            SignerError::Unsupported.to_string(),
+        ))
+    }
+}
+
+/// Builds the error returned when the signer answers a request with the wrong kind of response.
+fn unexpected_response() -> SignerError {
+    SignerError::MalformedResponse {
+        error: "the response doesn't match the request".to_owned(),
+    }
+}
+
+/// The signing half of a remote signer, holding each of the unlocked accounts that it signs with.
+pub struct SignerService {
+    /// The accounts that the signer signs with
+    accounts: Vec<Account>,
+
+    /// The digest of the token that callers must present
+    token_digest: Hash,
+
+    /// The data directory holding the signer's spending policy and spending log
+    data_dir: String,
+}
+
+/// Implement a set of signer service helper methods.
+impl SignerService {
+    /// Initializes a new signer service signing with the given accounts.
+    ///
+    /// # Arguments
+    ///
+    /// * `accounts` - The unlocked accounts that the signer should sign with
+    /// * `token` - The token that callers must present
+    /// * `data_dir` - The signer's data directory, holding its spending policy
+    pub fn new(accounts: Vec<Account>, token: &str, data_dir: &str) -> Self {
+        Self {
+            accounts,
+            token_digest: blake3::hash_slice(token.trim().as_bytes()),
+            data_dir: data_dir.to_owned(),
+        }
+    }
+
+    /// Gets the addresses of each of the accounts that the signer signs with.
+    pub fn addresses(&self) -> Vec<Address> {
+        self.accounts
+            .iter()
+            .filter_map(|acc| acc.address().ok())
+            .collect()
+    }
+
+    /// Answers a single request, refusing it if the given token is wrong.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request that was made, along with the caller's token
+    pub fn handle(&self, request: AuthenticatedRequest) -> SignerResponse {
+        // Compare digests, rather than the tokens themselves, so that the time taken doesn't give the token away
+        if blake3::hash_slice(request.token.trim().as_bytes()) != self.token_digest {
+            warn!("Refusing a request made with the wrong token");
+
+            return SignerResponse::Refused("the token is wrong".to_owned());
+        }
+
+        match request.request {
+            SignerRequest::ListAccounts => SignerResponse::Accounts(self.addresses()),
+            SignerRequest::CastVote {
+                account,
+                proposal_id,
+                in_favor,
+                topic,
+            } => self.cast_vote(account, proposal_id, in_favor, &topic),
+            SignerRequest::SignTransaction { tx, confirmation } => {
+                self.sign_transaction(tx, confirmation.as_deref())
+            }
+        }
+    }
+
+    /// Casts a vote on the given proposal with one of the signer's accounts, and seals it for gossip.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The address of the account that should vote
+    /// * `proposal_id` - The hash of the proposal being voted on
+    /// * `in_favor` - Whether or not the vote is in favor of the proposal
+    /// * `topic` - The topic that the vote will be published to
+    fn cast_vote(
+        &self,
+        account: Address,
+        proposal_id: Hash,
+        in_favor: bool,
+        topic: &str,
+    ) -> SignerResponse {
+        let acc = match self
+            .accounts
+            .iter()
+            .find(|acc| acc.address().ok() == Some(account))
+        {
+            Some(acc) => acc,
+            None => return SignerResponse::Refused(format!("no such account: {}", account)),
+        };
+
+        let (keypair, sealing_keypair) = match (acc.keypair(), acc.keypair()) {
+            (Ok(k), Ok(s)) => (k, s),
+            _ => return SignerResponse::Refused("the account's keypair is corrupt".to_owned()),
+        };
+
+        let vote = Vote::new(proposal_id, in_favor, keypair);
+        match envelope::seal(topic, &vote, &sealing_keypair) {
+            Ok(sealed) => {
+                info!(
+                    "Voted {} proposal {} with account {}",
+                    if in_favor { "for" } else { "against" },
+                    proposal_id,
+                    account
+                );
+
+                SignerResponse::Vote { vote, sealed }
+            }
+            Err(e) => SignerResponse::Refused(format!("failed to seal the vote: {}", e)),
+        }
+    }
+
+    /// Signs the given transaction with the signer's account that sent it, or that derived its sender, as long as the
+    /// signer's own spending policy allows it.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that should be signed
+    /// * `confirmation` - The confirmation code given by the caller, if any
+    fn sign_transaction(&self, mut tx: Transaction, confirmation: Option<&str>) -> SignerResponse {
+        // Make sure that the hash being signed actually covers what we were shown
+        let hash = tx.transaction_data.hash();
+        if hash != tx.hash {
+            return SignerResponse::Refused(
+                "the transaction's hash doesn't match its contents".to_owned(),
+            );
+        }
+
+        let sender = tx.transaction_data.sender;
+        let acc = match self.accounts.iter().find(|acc| {
+            acc.address().map_or(false, |address| {
+                address == sender || sender.is_sub_address_of(&address).is_some()
+            })
+        }) {
+            Some(acc) => acc,
+            None => return SignerResponse::Refused(format!("no account can sign for {}", sender)),
+        };
+        let (signer, keypair) = match (acc.address(), acc.keypair()) {
+            (Ok(address), Ok(keypair)) => (address, keypair),
+            _ => return SignerResponse::Refused("the account's keypair is corrupt".to_owned()),
+        };
+
+        // Hold the transaction to the signer's own spending policy, regardless of what the node has checked
+        if let Err(e) = self.check_spending_policy(&signer, &tx, confirmation) {
+            warn!("Refusing to sign transaction {}: {}", tx.hash, e);
+
+            return SignerResponse::Refused(e);
+        }
+
+        match transaction::sign_transaction(keypair, &mut tx) {
+            Ok(_) => match tx.signature {
+                Some(signature) => {
+                    info!("Signed transaction {} with account {}", tx.hash, signer);
+
+                    SignerResponse::Signature(signature)
+                }
+                None => SignerResponse::Refused("the transaction wasn't signed".to_owned()),
+            },
+            Err(e) => SignerResponse::Refused(e.to_string()),
+        }
+    }
+
+    /// Checks the given transaction against the signer's spending policy, recording it as spent if it's allowed.
+    ///
+    /// # Arguments
+    ///
+    /// * `signer` - The address of the account signing the transaction
+    /// * `tx` - The transaction being signed
+    /// * `confirmation` - The confirmation code given by the caller, if any
+    fn check_spending_policy(
+        &self,
+        signer: &Address,
+        tx: &Transaction,
+        confirmation: Option<&str>,
+    ) -> Result<(), String> {
+        let policy = SpendingPolicy::read_from_disk_at_data_directory(&self.data_dir)
+            .map_err(|e| format!("failed to read the spending policy: {}", e))?;
+        let mut log = SpendingLog::read_from_disk_at_data_directory(&self.data_dir)
+            .map_err(|e| format!("failed to read the spending log: {}", e))?;
+
+        let result = policy.authorize(&mut log, signer, tx, confirmation, Utc::now());
+
+        log.write_to_disk_at_data_directory(&self.data_dir)
+            .map_err(|e| format!("failed to write the spending log: {}", e))?;
+
+        result.map_err(|e| e.to_string())
+    }
+
+    /// Reads a single request from the given stream, and writes back the response.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The connection that the request was made over
+    pub fn answer<S: Read + Write>(&self, stream: &mut S) -> io::Result<()> {
+        let mut request = Vec::new();
+        BufReader::new(stream.by_ref().take(MAX_REQUEST_SIZE)).read_until(b'\n', &mut request)?;
+
+        let response = match serde_json::from_slice(&request) {
+            Ok(request) => self.handle(request),
+            Err(e) => SignerResponse::Refused(format!("the request is malformed: {}", e)),
+        };
+
+        stream.write_all(&serde_json::to_vec(&response)?)?;
+        stream.write_all(b"\n")
+    }
+
+    /// Answers requests made over the unix domain socket at the given path, one at a time, until the process exits.
+    /// Answering one request at a time keeps the spending log consistent.
+    ///
+    /// # Arguments
+    ///
+    /// * `socket` - The path that the socket should be bound to
+    #[cfg(unix)]
+    pub fn serve(&self, socket: &str) -> io::Result<()> {
+        use std::os::unix::{fs::PermissionsExt, net::UnixListener};
+
+        // Clear out the socket left behind by any earlier signer
+        match fs::remove_file(socket) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => (),
+        }
+
+        let listener = UnixListener::bind(socket)?;
+
+        // Nobody but the signer's user should be able to connect, on top of needing the token
+        fs::set_permissions(socket, fs::Permissions::from_mode(0o600))?;
+
+        info!(
+            "Signing with {} account(s) on {}",
+            self.accounts.len(),
+            socket
+        );
+
+        for stream in listener.incoming() {
+            let result = stream.and_then(|mut stream| {
+                stream.set_read_timeout(Some(Duration::from_secs(SIGNER_TIMEOUT_SECS)))?;
+                stream.set_write_timeout(Some(Duration::from_secs(SIGNER_TIMEOUT_SECS)))?;
+
+                self.answer(&mut stream)
+            });
+
+            if let Err(e) = result {
+                warn!("Failed to answer a request: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Remote signers are only supported over unix domain sockets.
+    #[cfg(not(unix))]
+    pub fn serve(&self, _socket: &str) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            SignerError::Unsupported.to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    use num::BigUint; // Add support for large unsigned integers
+
+    /// A connection whose request has been written in advance, and whose response is collected.
+    struct Connection {
+        request: io::Cursor<Vec<u8>>,
+        response: Vec<u8>,
+    }
+
+    impl Read for Connection {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.request.read(buf)
+        }
+    }
+
+    impl Write for Connection {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.response.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Sends the given request to the service, and gets its response.
+    fn ask(service: &SignerService, token: &str, request: SignerRequest) -> SignerResponse {
+        let mut request = serde_json::to_vec(&AuthenticatedRequest {
+            token: token.to_owned(),
+            request,
+        })
+        .unwrap();
+        request.push(b'\n');
+
+        let mut connection = Connection {
+            request: io::Cursor::new(request),
+            response: Vec::new(),
+        };
+        service.answer(&mut connection).unwrap();
+
+        serde_json::from_slice(&connection.response).unwrap()
+    }
+
+    #[test]
+    fn test_answer() {
+        let data_dir = format!(
+            "{}/.tests/signer/{}",
+            super::super::super::common::io::data_dir(),
+            rand::random::<u32>()
+        );
+        let token = read_or_generate_token(&data_dir).unwrap();
+        assert_eq!(read_or_generate_token(&data_dir).unwrap(), token);
+
+        let acc = Account::new();
+        let address = acc.address().unwrap();
+        let service = SignerService::new(vec![acc], &token, &data_dir);
+
+        // Callers without the token get nothing
+        assert!(matches!(
+            ask(&service, "guess", SignerRequest::ListAccounts),
+            SignerResponse::Refused(_)
+        ));
+        assert!(matches!(
+            ask(&service, &token, SignerRequest::ListAccounts),
+            SignerResponse::Accounts(ref accounts) if *accounts == vec![address]
+        ));
+
+        // Votes come back signed by the account, and sealed by it too
+        let proposal_id = blake3::hash_slice(b"proposal");
+        match ask(
+            &service,
+            &token,
+            SignerRequest::CastVote {
+                account: address,
+                proposal_id,
+                in_favor: true,
+                topic: "andromeda/votes".to_owned(),
+            },
+        ) {
+            SignerResponse::Vote { vote, sealed } => {
+                assert!(vote.valid());
+                assert_eq!(
+                    envelope::Envelope::from_bytes(&sealed).unwrap().signer(),
+                    Some(address)
+                );
+            }
+            _ => panic!("expected a vote"),
+        }
+
+        // Transactions are only signed if their hash matches what the signer was shown
+        let mut tx = Transaction::new(0, address, address, BigUint::from(1 as u8), b"", vec![]);
+        match ask(
+            &service,
+            &token,
+            SignerRequest::SignTransaction {
+                tx: tx.clone(),
+                confirmation: None,
+            },
+        ) {
+            SignerResponse::Signature(signature) => {
+                tx.signature = Some(signature);
+                assert!(tx.verify_signature());
+            }
+            _ => panic!("expected a signature"),
+        }
+
+        tx.transaction_data.value = BigUint::from(1000 as u16);
+        assert!(matches!(
+            ask(
+                &service,
+                &token,
+                SignerRequest::SignTransaction {
+                    tx,
+                    confirmation: None
+                }
+            ),
+            SignerResponse::Refused(_)
+        ));
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap; // Import the map type
+
+use chrono::{DateTime, Duration, Utc}; // Import time utilities
+
+use rand::{rngs::OsRng, RngCore}; // Import the os's rng
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use zeroize::{Zeroize, Zeroizing}; // Import helpers for wiping secrets from memory
+
+use super::{
+    super::{
+        common::address::Address,
+        crypto::{blake3, hash::Hash},
+    },
+    account::{self, Account},
+}; // Import the address, hash & account types
+
+/// The number of minutes that an account stays unlocked for, unless told otherwise.
+pub const DEFAULT_UNLOCK_MINUTES: u64 = 5;
+
+/// The greatest number of minutes that an account may stay unlocked for.
+pub const MAX_UNLOCK_MINUTES: u64 = 24 * 60;
+
+/// An error encountered while opening, or using, an unlock session.
+#[derive(Debug, Fail, PartialEq)]
+pub enum UnlockError {
+    #[fail(display = "no such unlock session is open (it may have expired)")]
+    NoSuchSession,
+    #[fail(
+        display = "accounts can't be unlocked for {} minutes (the most is {})",
+        minutes, max_minutes
+    )]
+    TooLong {
+        minutes: u64,     // The number of minutes asked for
+        max_minutes: u64, // The greatest number of minutes allowed
+    },
+    #[fail(
+        display = "the unlock session doesn't hold account {} in the given data directory",
+        address
+    )]
+    WrongAccount {
+        address: String, // The address of the account that was asked for
+    },
+    #[fail(display = "the unlocked account couldn't be sealed in memory")]
+    SealingFailed,
+}
+
+/// A handle to an account unlocked for a limited time, handed out to the caller that unlocked it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct UnlockSession {
+    /// The secret handle that must accompany each request made with the unlocked account
+    pub handle: String,
+
+    /// The address of the unlocked account
+    pub address: Address,
+
+    /// When the account locks itself again
+    pub expires_at: DateTime<Utc>,
+}
+
+/// An account unlocked in memory. The account's keystore is sealed under the session's handle, which the node
+/// doesn't keep, so the key material can't be read back out of memory without the caller.
+struct UnlockedAccount {
+    /// The address of the account
+    address: Address,
+
+    /// The data directory that the account was unlocked from
+    data_dir: String,
+
+    /// The account's keystore, encrypted with the session's handle
+    sealed: Vec<u8>,
+
+    /// When the account locks itself again
+    expires_at: DateTime<Utc>,
+}
+
+/// Each of the accounts unlocked for a limited time. Unlike unlock_account, unlocking an account this way never
+/// rewrites its keystore on the disk.
+#[derive(Default)]
+pub struct UnlockSessions {
+    /// Each of the unlocked accounts, by a digest of its session's handle
+    sessions: HashMap<Hash, UnlockedAccount>,
+}
+
+/// Implement a set of unlock session helper methods.
+impl UnlockSessions {
+    /// Unlocks the given account for the given number of minutes, and gets a handle to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `acc` - The decrypted account
+    /// * `data_dir` - The data directory that the account was read from
+    /// * `minutes` - The number of minutes that the account should stay unlocked for
+    pub fn open(
+        &mut self,
+        acc: &Account,
+        data_dir: &str,
+        minutes: u64,
+    ) -> Result<UnlockSession, UnlockError> {
+        if minutes > MAX_UNLOCK_MINUTES {
+            return Err(UnlockError::TooLong {
+                minutes,
+                max_minutes: MAX_UNLOCK_MINUTES,
+            });
+        }
+
+        self.expire();
+
+        let address = acc.address().map_err(|_| UnlockError::SealingFailed)?;
+
+        // The handle is the only copy of the key that the account is sealed under
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+        let handle = bs58::encode(secret).into_string();
+        secret.zeroize();
+
+        let sealed = serde_json::to_vec(acc)
+            .ok()
+            .map(Zeroizing::new)
+            .and_then(|contents| account::encrypt_keystore(&contents, &handle))
+            .ok_or(UnlockError::SealingFailed)?;
+
+        let expires_at = Utc::now() + Duration::minutes(minutes as i64);
+        self.sessions.insert(
+            Self::digest(&handle),
+            UnlockedAccount {
+                address,
+                data_dir: data_dir.to_owned(),
+                sealed,
+                expires_at,
+            },
+        );
+
+        Ok(UnlockSession {
+            handle,
+            address,
+            expires_at,
+        })
+    }
+
+    /// Gets the address of the account held by the session with the given handle.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The session's handle
+    pub fn address(&mut self, handle: &str) -> Result<Address, UnlockError> {
+        self.expire();
+
+        self.sessions
+            .get(&Self::digest(handle))
+            .map(|unlocked| unlocked.address)
+            .ok_or(UnlockError::NoSuchSession)
+    }
+
+    /// Gets the account held by the session with the given handle, as long as it is the account with the given
+    /// address, unlocked from the given data directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The session's handle
+    /// * `address` - The address of the account that the caller expects the session to hold
+    /// * `data_dir` - The data directory that the caller expects the account to have been unlocked from
+    pub fn account(
+        &mut self,
+        handle: &str,
+        address: &Address,
+        data_dir: &str,
+    ) -> Result<Account, UnlockError> {
+        self.expire();
+
+        let unlocked = self
+            .sessions
+            .get(&Self::digest(handle))
+            .ok_or(UnlockError::NoSuchSession)?;
+        if unlocked.address != *address || unlocked.data_dir != data_dir {
+            return Err(UnlockError::WrongAccount {
+                address: address.to_str(),
+            });
+        }
+
+        account::decrypt_keystore(&unlocked.sealed, handle)
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+            .ok_or(UnlockError::NoSuchSession)
+    }
+
+    /// Locks the account held by the session with the given handle straight away.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The session's handle
+    pub fn close(&mut self, handle: &str) -> Result<(), UnlockError> {
+        self.expire();
+
+        self.sessions
+            .remove(&Self::digest(handle))
+            .map(|_| ())
+            .ok_or(UnlockError::NoSuchSession)
+    }
+
+    /// Checks whether or not any accounts are unlocked.
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Locks each of the accounts whose sessions have run out.
+    fn expire(&mut self) {
+        let now = Utc::now();
+
+        self.sessions
+            .retain(|_, unlocked| unlocked.expires_at > now);
+    }
+
+    /// Digests the given handle, so that sessions can be looked up without keeping the key that they're sealed
+    /// under.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The session's handle
+    fn digest(handle: &str) -> Hash {
+        blake3::hash_slice(format!("unlock_session:{}", handle).as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_unlock_sessions() {
+        let acc = Account::new();
+        let address = acc.address().unwrap();
+        let mut sessions = UnlockSessions::default();
+
+        assert_eq!(
+            sessions.open(&acc, "data", MAX_UNLOCK_MINUTES + 1).err(),
+            Some(UnlockError::TooLong {
+                minutes: MAX_UNLOCK_MINUTES + 1,
+                max_minutes: MAX_UNLOCK_MINUTES,
+            })
+        );
+
+        let session = sessions.open(&acc, "data", DEFAULT_UNLOCK_MINUTES).unwrap();
+        assert_eq!(session.address, address);
+        assert_eq!(sessions.address(&session.handle), Ok(address));
+
+        // The session only hands out the account that it holds, from the directory it was unlocked in
+        assert_eq!(
+            sessions
+                .account(&session.handle, &address, "data")
+                .unwrap()
+                .keypair()
+                .unwrap()
+                .to_bytes()[..],
+            acc.keypair().unwrap().to_bytes()[..]
+        );
+        assert!(sessions
+            .account(&session.handle, &address, "elsewhere")
+            .is_err());
+        assert_eq!(
+            sessions.account("forged", &address, "data").err(),
+            Some(UnlockError::NoSuchSession)
+        );
+
+        // Sessions that have run out are locked, as are those that are closed
+        let expired = sessions.open(&acc, "data", 0).unwrap();
+        assert_eq!(
+            sessions.account(&expired.handle, &address, "data").err(),
+            Some(UnlockError::NoSuchSession)
+        );
+        sessions.close(&session.handle).unwrap();
+        assert!(sessions.is_empty());
+    }
+}
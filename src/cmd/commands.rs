@@ -3,10 +3,49 @@ use clap::Clap;
 #[derive(Clap, Clone)]
 pub enum Create {
     /// Creates a new account.
-    Account,
+    Account(NewAccount),
+
+    /// Recovers one or more accounts from the mnemonic phrase that they were derived from.
+    RecoveredAccount(MnemonicRecovery),
 
     /// Creates a new transaction.
     Transaction(Transaction),
+
+    /// Creates a single transaction paying each of a set of recipients, debiting the sender once.
+    BatchTransaction(BatchTransaction),
+
+    /// Creates a set of transactions moving the entire balance of one or more accounts to a destination.
+    Sweep(Sweep),
+
+    /// Creates a transaction sending funds that the recipient can't spend until a time or milestone.
+    LockedTransaction(LockedTransaction),
+
+    /// Attaches a local note to a transaction hash or address.
+    Note(Note),
+
+    /// Adds a named contact to the local address book, so that it can be paid by name.
+    Contact(Contact),
+
+    /// Creates a transaction registering the guardians able to recover an account.
+    Guardians(GuardianSet),
+
+    /// Creates a transaction, sent by a guardian, beginning the recovery of a lost account.
+    Recovery(Recovery),
+
+    /// Creates a transaction, sent by a guardian, approving the pending recovery of an account.
+    RecoveryApproval(Recovery),
+
+    /// Creates a transaction, sent by a guardian, reassigning an approved recovery's account to its new owner.
+    RecoveryExecution(RecoveryExecution),
+
+    /// Creates a transaction, sent by a voting account to itself, claiming each of the rewards it has earned.
+    RewardsClaim(Account),
+
+    /// Pins a known-good transaction as a checkpoint that any synchronized history must agree with.
+    Checkpoint(Checkpoint),
+
+    /// Starts voting with an account on the running node, without restarting it.
+    VotingAccount(VotingAccount),
 }
 
 #[derive(Clap, Clone)]
@@ -17,29 +56,121 @@ pub enum Get {
     /// Gets the balance of a particular account.
     Balance(Account),
 
+    /// Gets how much of an account's balance is locked, and how much may be spent.
+    LockedBalance(Account),
+
     /// Gets a list of nodes contained in the working dag.
     Dag(UnitObject),
 
     /// Gets a list of transactions contained in the transaction cache.
     TransactionMemory(UnitObject),
+
+    /// Gets the local note attached to a transaction hash or address.
+    Note(HashableObject),
+
+    /// Gets the contact with the given name from the local address book.
+    Contact(ContactName),
+
+    /// Gets the transactions that produced, or cited, the state entry with the given hash.
+    StateOrigin(HashableObject),
+
+    /// Gets the state of the ledger as it stood after the transaction with the given hash (archive nodes only).
+    StateAt(HashableObject),
+
+    /// Gets the balance of an account as it stood after the transaction with the given hash (archive nodes only).
+    BalanceAt(HistoricalBalance),
+
+    /// Gets the guardians registered by an account, along with any recovery in progress.
+    Guardians(Account),
+
+    /// Gets the rewards that a voting account has earned but not yet claimed.
+    Rewards(Account),
+
+    /// Gets the network activity observed by the node, hour by hour.
+    NetworkStats(UnitObject),
+
+    /// Gets how synchronization with each of the node's peers is going.
+    SyncPeers(UnitObject),
+
+    /// Gets the network's supply, and the schedule that new finks are minted on.
+    Supply(UnitObject),
+
+    /// Gets the number of finks burned so far, and the address that finks are burned by sending them to.
+    BurnedSupply(UnitObject),
+
+    /// Gets the accounts designated a role (e.g. the faucet, or the treasury) in the genesis.
+    GenesisRoles(UnitObject),
+
+    /// Gets what would happen if the pending proposal with the given hash were executed right now.
+    ProposalSimulation(HashableObject),
+
+    /// Gets the spending policy of an account, along with how much it has spent over the last day.
+    SpendingStatus(Account),
+
+    /// Gets the most recent proposals executed by the node, along with what came of them.
+    ExecutedProposals(ExecutedProposalQuery),
+
+    /// Gets the most recent votes that the node has cast with an account, along with why each was cast that way.
+    VoteJournal(VoteJournalQuery),
+
+    /// Gets how far along the node is in synchronizing its DAG with the network.
+    SyncStatus(SyncStatusQuery),
+
+    /// Gets the accounts holding funds that haven't sent a transaction in a while, along with how much they hold.
+    DormantAccounts(DormancyQuery),
+
+    /// Gets the most recent competing transactions spending the same nonce seen by the node.
+    DoubleSpends(DoubleSpendQuery),
+
+    /// Gets the size of the node's database, the room left on its disk, and whether the node is running out.
+    StorageStatus(UnitObject),
+
+    /// Gets anything keeping an account's transactions from being executed (e.g. a skipped nonce), and what to do
+    /// about it.
+    AccountDiagnosis(AccountDiagnosisQuery),
+
+    /// Gets a proof of an account's balance against the header of a node in the DAG, and checks it, so that the
+    /// balance can be trusted without trusting the node that proved it.
+    BalanceProof(BalanceProofQuery),
 }
 
 #[derive(Clap, Clone)]
 pub enum Lock {
     /// Locks a particular account with the given address.
     Account(CryptoAccount),
+
+    /// Locks the account held by an unlock session, before the session runs out.
+    Session(UnlockSessionHandle),
 }
 
 #[derive(Clap, Clone)]
 pub enum Unlock {
     /// Unlocks a particular account with the given address.
     Account(CryptoAccount),
+
+    /// Unlocks a particular account in the node's memory for a limited time, leaving its keystore locked on the disk.
+    Session(TimedUnlock),
 }
 
 #[derive(Clap, Clone)]
 pub enum Delete {
     /// Deletes an account with the given address.
     Account(Account),
+
+    /// Deletes the local note attached to a transaction hash or address.
+    Note(HashableObject),
+
+    /// Deletes the contact with the given name from the local address book.
+    Contact(ContactName),
+
+    /// Creates a transaction, sent by the account itself, calling off the pending recovery of an account.
+    Recovery(Account),
+
+    /// Unpins the checkpoint at a particular index in the DAG.
+    Checkpoint(CheckpointIndex),
+
+    /// Stops voting with an account on the running node, without restarting it.
+    VotingAccount(Account),
 }
 
 #[derive(Clap, Clone)]
@@ -52,17 +183,129 @@ pub enum List {
 
     /// Gets a list of pending proposals held in the working runtime.
     Proposals(UnitObject),
+
+    /// Gets a list of the local notes attached to transaction hashes and addresses.
+    Notes(UnitObject),
+
+    /// Gets a list of the contacts in the local address book.
+    Contacts(UnitObject),
+
+    /// Gets a list of the checkpoints pinned by the node.
+    Checkpoints(UnitObject),
+
+    /// Gets a list of the accounts that the node is currently voting with.
+    VotingAccounts(UnitObject),
+}
+
+#[derive(Clap, Clone)]
+pub enum Debug {
+    /// Re-executes the node's entire DAG from its genesis, and reports the first transaction citing a parent state
+    /// that couldn't be reproduced.
+    Replay(UnitObject),
+
+    /// Reports who's holding, or waiting on, the node's runtime lock, and which callers have waited on it longest.
+    Locks(UnitObject),
+
+    /// Re-executes a transaction in the DAG, and prints each step taken while executing it.
+    Trace(TraceableTransaction),
+
+    /// Recomputes the state resulting from a node in the DAG from the genesis, and checks it against the state hash
+    /// that the node recorded, even if the state itself has been purged.
+    State(HashableObject),
+}
+
+#[derive(Clap, Clone)]
+pub struct TraceableTransaction {
+    /// A hex-encoded string representing the hash of the transaction, or its short id (e.g. its first 8 characters)
+    pub hash: String,
+
+    /// How much detail to print: minimal (only the steps that changed something), steps, or full (with balances)
+    #[clap(long = "level", default_value = "steps")]
+    pub level: String,
+}
+
+#[derive(Clap, Clone)]
+pub enum Backup {
+    /// Bundles the keystore, address book, and network config of the data directory into a single password-protected
+    /// file.
+    Export(BackupExport),
+
+    /// Restores the keystore, address book, and network config held in a backup file to the data directory.
+    Import(BackupImport),
+}
+
+#[derive(Clap, Clone)]
+pub enum Export {
+    /// Writes each of the transactions in the node's DAG, along with their receipts and the balances they left
+    /// behind, to a file, one JSON object per line.
+    History(HistoryExport),
+}
+
+#[derive(Clap, Clone)]
+pub struct HistoryExport {
+    /// The file that the history should be written to
+    pub path: String,
+
+    /// The number of transactions to fetch from the node at once
+    #[clap(long = "page-size", default_value = "100")]
+    pub page_size: usize,
+
+    /// Picks an interrupted export with the given id back up, appending to the file from the last page that the node
+    /// saw acknowledged
+    #[clap(long = "resume")]
+    pub resume: Option<String>,
 }
 
 #[derive(Clap, Clone)]
 pub enum Sign {
     /// Signs the provided transaction with a given account
-    Transaction(HashableObject),
+    Transaction(SignableTransaction),
+}
+
+#[derive(Clap, Clone)]
+pub struct SignableTransaction {
+    /// A hex-encoded string representing the hash of the transaction
+    pub hash: String,
+
+    /// The confirmation code written to the node's log, for transactions above the account's confirmation threshold
+    #[clap(long = "confirmation")]
+    pub confirmation: Option<String>,
+
+    /// Signs with the account held by the unlock session with the given handle (see unlock session)
+    #[clap(long = "unlock-session")]
+    pub unlock_session: Option<String>,
 }
 
 #[derive(Clap, Clone)]
 pub enum Publish {
-    Transaction(HashableObject),
+    Transaction(PublishableTransaction),
+}
+
+#[derive(Clap, Clone)]
+pub struct PublishableTransaction {
+    /// A hex-encoded string representing the hash of the transaction
+    pub hash: String,
+
+    /// Publishes the transaction without asking for confirmation, as long as the pre-flight checks pass
+    #[clap(short = "y", long = "yes")]
+    pub yes: bool,
+
+    /// Waits for the transaction to be executed, resubmitting it each time it isn't executed within this many seconds
+    #[clap(long = "deadline")]
+    pub deadline: Option<u64>,
+
+    /// The number of times that the transaction is resubmitted before it's given up on (see --deadline)
+    #[clap(long = "max-resubmissions", default_value = "3")]
+    pub max_resubmissions: u32,
+
+    /// How a transaction that misses its deadline is resubmitted: regossip (send the same transaction again), or
+    /// rebuild (sign a replacement built on top of the DAG's new head, spending the same nonce)
+    #[clap(long = "resubmit-with", default_value = "regossip")]
+    pub resubmit_with: String,
+
+    /// Signs rebuilt transactions with the account held by the unlock session with the given handle
+    #[clap(long = "unlock-session")]
+    pub unlock_session: Option<String>,
 }
 
 #[derive(Clap, Clone)]
@@ -71,6 +314,78 @@ pub struct Account {
     pub address: String,
 }
 
+#[derive(Clap, Clone)]
+pub struct AccountDiagnosisQuery {
+    /// The address of the account
+    pub address: String,
+
+    /// The number of seconds that the transaction spending the account's next nonce may wait before it's stuck
+    #[clap(long = "stuck-after")]
+    pub stuck_after_secs: Option<u64>,
+}
+
+#[derive(Clap, Clone)]
+pub struct BalanceProofQuery {
+    /// The address of the account
+    pub address: String,
+
+    /// The hash, or short id, of the node whose resulting state should hold the balance; the executed head, if none
+    /// is given
+    #[clap(long = "at")]
+    pub at: Option<String>,
+
+    /// The RPC address of another node (e.g. a light client) that should check the proof against its own copy of the
+    /// header. May be given more than once.
+    #[clap(long = "verify-with")]
+    pub verify_with: Vec<String>,
+}
+
+#[derive(Clap, Clone)]
+pub struct NewAccount {
+    /// Derives the account from a newly generated mnemonic phrase, which is printed so that it can be written down.
+    /// The account is generated locally, rather than by the node.
+    #[clap(long = "mnemonic")]
+    pub mnemonic: bool,
+
+    /// The number of words in the mnemonic phrase (12, 15, 18, 21, or 24)
+    #[clap(long = "words", default_value = "24")]
+    pub words: usize,
+
+    /// An optional passphrase that the mnemonic phrase's seed should be salted with
+    #[clap(long = "passphrase", default_value = "")]
+    pub passphrase: String,
+
+    /// The index of the account among those derived from the phrase, used in the default derivation path
+    #[clap(long = "index", default_value = "0")]
+    pub index: u32,
+
+    /// A derivation path to use instead of the default one (e.g. m/44'/21315'/0'/0'/0')
+    #[clap(long = "derivation-path")]
+    pub derivation_path: Option<String>,
+}
+
+#[derive(Clap, Clone)]
+pub struct MnemonicRecovery {
+    /// The mnemonic phrase that the accounts were derived from, in quotes
+    pub phrase: String,
+
+    /// The passphrase that the mnemonic phrase's seed was salted with, if any
+    #[clap(long = "passphrase", default_value = "")]
+    pub passphrase: String,
+
+    /// The index of the first account that should be recovered, used in the default derivation path
+    #[clap(long = "index", default_value = "0")]
+    pub index: u32,
+
+    /// The number of consecutive accounts that should be recovered, starting at the given index
+    #[clap(long = "count", default_value = "1")]
+    pub count: u32,
+
+    /// The derivation path of a single account to recover, instead of the default ones
+    #[clap(long = "derivation-path")]
+    pub derivation_path: Option<String>,
+}
+
 #[derive(Clap, Clone)]
 pub struct CryptoAccount {
     /// The address of the account
@@ -78,17 +393,75 @@ pub struct CryptoAccount {
 
     /// The encryption / decryption key used to unlock or lock the account
     pub key: String,
+
+    /// Remembers the key in the given secret store (none or keychain) when locking, so that smcd can unlock the
+    /// account on its own; forgets it again when unlocking
+    #[clap(long = "secret-store", default_value = "none")]
+    pub secret_store: String,
+}
+
+#[derive(Clap, Clone)]
+pub struct TimedUnlock {
+    /// The address of the account
+    pub address: String,
+
+    /// The key that the account was locked with
+    pub key: String,
+
+    /// The number of minutes that the account should stay unlocked for
+    #[clap(long = "minutes", default_value = "5")]
+    pub minutes: u64,
+}
+
+#[derive(Clap, Clone)]
+pub struct UnlockSessionHandle {
+    /// The handle of the unlock session
+    pub handle: String,
 }
 
 #[derive(Clap, Clone)]
 pub struct UnitObject {}
 
+#[derive(Clap, Clone)]
+pub struct Consolidate {
+    /// A hex-encoded string representing the address of the account that each of the balances should be merged
+    /// into, or the name of a contact
+    pub primary: String,
+
+    /// Hex-encoded strings representing the addresses of the accounts that should be merged; every account in the
+    /// keystore, if none are given
+    pub sources: Vec<String>,
+
+    /// Carries out the plan without asking for confirmation, as long as the pre-flight checks pass
+    #[clap(short = "y", long = "yes")]
+    pub yes: bool,
+
+    /// Signs with the account held by the unlock session with the given handle (see unlock session)
+    #[clap(long = "unlock-session")]
+    pub unlock_session: Option<String>,
+}
+
+#[derive(Clap, Clone)]
+pub struct Clean {
+    /// Lists what would be cleaned up, without touching anything
+    #[clap(long = "dry-run")]
+    pub dry_run: bool,
+
+    /// Also moves keystore entries whose addresses never appear on the ledger into keystore.orphaned
+    #[clap(long = "keystore")]
+    pub keystore: bool,
+
+    /// How long an artifact must have gone untouched before it is cleaned up, in hours
+    #[clap(long = "min-age-hours", default_value = "24")]
+    pub min_age_hours: u64,
+}
+
 #[derive(Clap, Clone)]
 pub struct Transaction {
     /// A hex-encoded string representing the address of the sender of the transaction
     pub sender: String,
 
-    /// A hex-encoded string representing the address of the recipient of the transaction
+    /// A hex-encoded string representing the address of the recipient of the transaction, or the name of a contact
     pub recipient: String,
 
     /// The number of finks sent through the transaction
@@ -96,10 +469,239 @@ pub struct Transaction {
 
     /// A UTF-8-encoded payload sent along with the transaction
     pub payload: String,
+
+    /// The number of finks paid for each unit of gas that the transaction uses. Transactions without a gas price are
+    /// free.
+    #[clap(long = "gas-price")]
+    pub gas_price: Option<u128>,
+
+    /// The most gas that the transaction may use, if it sets a gas price. Defaults to exactly the gas that it uses.
+    #[clap(long = "gas-limit")]
+    pub gas_limit: Option<u64>,
+}
+
+#[derive(Clap, Clone)]
+pub struct BatchTransaction {
+    /// A hex-encoded string representing the address of the sender of the batch
+    pub sender: String,
+
+    /// Each of the payments made by the batch, written as address=finks (or contact=finks)
+    pub transfers: Vec<String>,
+
+    /// A CSV file containing further payments, one address,finks (or contact,finks) pair per line
+    #[clap(long = "csv")]
+    pub csv: Option<String>,
+}
+
+#[derive(Clap, Clone)]
+pub struct LockedTransaction {
+    /// A hex-encoded string representing the address of the sender of the transaction
+    pub sender: String,
+
+    /// A hex-encoded string representing the address of the recipient of the transaction, or the name of a contact
+    pub recipient: String,
+
+    /// The number of finks sent, and locked, through the transaction
+    pub amount: u128,
+
+    /// The UNIX timestamp at which the funds unlock
+    #[clap(long = "unlocks-at")]
+    pub unlocks_at: Option<i64>,
+
+    /// The number of transactions deep that the ledger must be before the funds unlock
+    #[clap(long = "milestone")]
+    pub milestone: Option<u64>,
+}
+
+#[derive(Clap, Clone)]
+pub struct Sweep {
+    /// A hex-encoded string representing the address that each of the balances should be moved to, or the name of a
+    /// contact
+    pub destination: String,
+
+    /// Hex-encoded strings representing the addresses of the accounts that should be emptied
+    pub sources: Vec<String>,
+}
+
+#[derive(Clap, Clone)]
+pub struct Note {
+    /// A hex-encoded string representing the transaction hash or address that the note describes
+    pub subject: String,
+
+    /// A short, human-readable name for the subject (e.g. "rent payment")
+    pub label: String,
+
+    /// Any longer-form text that should be kept alongside the label
+    #[clap(default_value = "")]
+    pub memo: String,
+}
+
+#[derive(Clap, Clone)]
+pub struct Contact {
+    /// A short name for the contact, without any whitespace (e.g. "landlord")
+    pub name: String,
+
+    /// A hex-encoded string representing the address of the contact's account
+    pub address: String,
+
+    /// Any text that should be kept alongside the contact
+    #[clap(default_value = "")]
+    pub memo: String,
+}
+
+#[derive(Clap, Clone)]
+pub struct BackupExport {
+    /// The file that the backup should be written to
+    pub path: String,
+
+    /// The password that the backup should be protected with
+    pub password: String,
+}
+
+#[derive(Clap, Clone)]
+pub struct BackupImport {
+    /// The backup file that should be restored
+    pub path: String,
+
+    /// The password that the backup was protected with
+    pub password: String,
+
+    /// Replaces any keystore, config, or contact entries that differ from those in the backup, rather than refusing
+    /// to restore it
+    #[clap(long = "overwrite")]
+    pub overwrite: bool,
+}
+
+#[derive(Clap, Clone)]
+pub struct ContactName {
+    /// The name of the contact
+    pub name: String,
+}
+
+#[derive(Clap, Clone)]
+pub struct GuardianSet {
+    /// A hex-encoded string representing the address of the account registering the guardians
+    pub account: String,
+
+    /// The number of guardians that must approve a recovery
+    pub threshold: u32,
+
+    /// Hex-encoded strings representing the addresses of the guardians
+    pub guardians: Vec<String>,
+
+    /// The number of seconds that a recovery must wait after being initiated (one week, by default)
+    #[clap(long = "timelock", default_value = "604800")]
+    pub timelock_secs: u64,
+}
+
+#[derive(Clap, Clone)]
+pub struct Recovery {
+    /// A hex-encoded string representing the address of the guardian sending the transaction
+    pub guardian: String,
+
+    /// A hex-encoded string representing the address of the account being recovered
+    pub account: String,
+
+    /// A hex-encoded string representing the address that the account should be reassigned to
+    pub new_owner: String,
+}
+
+#[derive(Clap, Clone)]
+pub struct RecoveryExecution {
+    /// A hex-encoded string representing the address of the guardian sending the transaction
+    pub guardian: String,
+
+    /// A hex-encoded string representing the address of the account being recovered
+    pub account: String,
+}
+
+#[derive(Clap, Clone)]
+pub struct Checkpoint {
+    /// The index of the transaction in the DAG
+    pub index: usize,
+
+    /// A hex-encoded string representing the hash of the transaction, obtained from a source you trust
+    pub hash: String,
+}
+
+#[derive(Clap, Clone)]
+pub struct VotingAccount {
+    /// The address of the account
+    pub address: String,
+
+    /// The key that the account is locked with, if it's locked. The account stays locked on the disk.
+    #[clap(long = "key")]
+    pub key: Option<String>,
+}
+
+#[derive(Clap, Clone)]
+pub struct CheckpointIndex {
+    /// The index in the DAG that the checkpoint was pinned at
+    pub index: usize,
 }
 
 #[derive(Clap, Clone)]
 pub struct HashableObject {
-    /// A hex-encoded string representing the hash of the object
+    /// A hex-encoded string representing the hash of the object, or the short id (e.g. the first 8 characters) of a
+    /// transaction or proposal's hash
     pub hash: String,
 }
+
+#[derive(Clap, Clone)]
+pub struct DormancyQuery {
+    /// The number of days that an account must have gone without sending anything to count as dormant
+    #[clap(long = "idle-days", default_value = "365")]
+    pub idle_days: u64,
+
+    /// The greatest number of accounts to print, richest first
+    #[clap(long = "limit", default_value = "20")]
+    pub limit: usize,
+}
+
+#[derive(Clap, Clone)]
+pub struct DoubleSpendQuery {
+    /// The greatest number of double spends to print, newest first
+    #[clap(long = "limit", default_value = "20")]
+    pub limit: usize,
+}
+
+#[derive(Clap, Clone)]
+pub struct SyncStatusQuery {
+    /// Keeps reporting the node's progress until it has synchronized its DAG
+    #[clap(long = "watch")]
+    pub watch: bool,
+}
+
+#[derive(Clap, Clone)]
+pub struct ExecutedProposalQuery {
+    /// The greatest number of proposals to print
+    #[clap(long = "limit", default_value = "20")]
+    pub limit: usize,
+
+    /// Only prints the proposals touching the given param (e.g. config::network_name)
+    #[clap(long = "param")]
+    pub param: Option<String>,
+}
+
+#[derive(Clap, Clone)]
+pub struct VoteJournalQuery {
+    /// The address of the account (or the name of a contact)
+    pub address: String,
+
+    /// The greatest number of votes to print
+    #[clap(long = "limit", default_value = "20")]
+    pub limit: usize,
+
+    /// Only prints the votes older than the one with the given number, so that older votes can be paged through
+    #[clap(long = "before")]
+    pub before: Option<u64>,
+}
+
+#[derive(Clap, Clone)]
+pub struct HistoricalBalance {
+    /// The address of the account
+    pub address: String,
+
+    /// The hash of the transaction after which the balance should be read
+    pub node_hash: String,
+}
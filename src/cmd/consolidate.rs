@@ -0,0 +1,216 @@
+use num::{BigUint, Zero}; // Add support for large unsigned integers
+
+use std::collections::HashSet; // Import the set type
+
+use super::super::{
+    common::{address::Address, fink},
+    core::types::vesting::LockedBalance,
+}; // Import the address & locked balance types
+
+/// A transfer moving everything that a source account may spend into the primary account.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlannedTransfer {
+    /// The account that the funds are moved out of
+    pub source: Address,
+
+    /// The number of finks moved
+    pub value: BigUint,
+
+    /// The number of finks left behind, since they're still locked
+    pub locked: BigUint,
+}
+
+/// Why an account won't be merged into the primary account.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SkipReason {
+    /// The account is the primary account itself
+    Primary,
+
+    /// The account was listed more than once
+    Duplicate,
+
+    /// The account doesn't hold anything
+    Empty,
+
+    /// Each of the account's funds are still locked
+    Locked,
+}
+
+/// An account that won't be merged into the primary account.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SkippedAccount {
+    /// The address of the account
+    pub address: Address,
+
+    /// Why the account is left alone
+    pub reason: SkipReason,
+}
+
+/// The transfers merging the balances of a set of accounts into a single primary account. The transfers don't set a
+/// gas price, so they're free, and each moves the source's entire spendable balance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsolidationPlan {
+    /// The account that each of the balances are merged into
+    pub primary: Address,
+
+    /// Each of the transfers that should be made, in the order given
+    pub transfers: Vec<PlannedTransfer>,
+
+    /// Each of the accounts that are left alone
+    pub skipped: Vec<SkippedAccount>,
+}
+
+/// Plans the transfers merging each of the given accounts into the primary account.
+///
+/// # Arguments
+///
+/// * `primary` - The account that the balances should be merged into
+/// * `sources` - Each of the accounts that should be merged, along with how much of its balance may be spent
+pub fn plan(primary: Address, sources: &[(Address, LockedBalance)]) -> ConsolidationPlan {
+    let mut seen: HashSet<Address> = HashSet::new();
+    let mut transfers = Vec::new();
+    let mut skipped = Vec::new();
+
+    for (address, balance) in sources {
+        let reason = if *address == primary {
+            Some(SkipReason::Primary)
+        } else if !seen.insert(*address) {
+            Some(SkipReason::Duplicate)
+        } else if balance.balance.is_zero() {
+            Some(SkipReason::Empty)
+        } else if balance.spendable.is_zero() {
+            Some(SkipReason::Locked)
+        } else {
+            None
+        };
+
+        match reason {
+            Some(reason) => skipped.push(SkippedAccount {
+                address: *address,
+                reason,
+            }),
+            None => transfers.push(PlannedTransfer {
+                source: *address,
+                value: balance.spendable.clone(),
+                locked: balance.locked.clone(),
+            }),
+        }
+    }
+
+    ConsolidationPlan {
+        primary,
+        transfers,
+        skipped,
+    }
+}
+
+/// Implement a set of consolidation plan helper methods.
+impl ConsolidationPlan {
+    /// Gets the number of finks that the plan moves into the primary account.
+    pub fn total(&self) -> BigUint {
+        self.transfers
+            .iter()
+            .fold(BigUint::zero(), |total, transfer| total + &transfer.value)
+    }
+
+    /// Describes the plan in a few human-readable lines.
+    pub fn summarize(&self) -> String {
+        let mut lines = vec![format!(
+            "Merging {} accounts into {} ({} SMC in total)",
+            self.transfers.len(),
+            self.primary,
+            smc(&self.total())
+        )];
+
+        for transfer in &self.transfers {
+            lines.push(if transfer.locked.is_zero() {
+                format!("  {} -> {} SMC", transfer.source, smc(&transfer.value))
+            } else {
+                format!(
+                    "  {} -> {} SMC ({} SMC stays behind until it unlocks)",
+                    transfer.source,
+                    smc(&transfer.value),
+                    smc(&transfer.locked)
+                )
+            });
+        }
+
+        for account in &self.skipped {
+            lines.push(format!(
+                "  {} (skipped: {:?})",
+                account.address, account.reason
+            ));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Formats the given number of finks as a number of SMC.
+fn smc(finks: &BigUint) -> String {
+    fink::convert_finks_to_smc(finks.clone()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::crypto::blake3; // Import the blake3 hashing module
+    use super::*; // Import names from our parent module
+
+    /// Makes a balance with the given number of finks, some of which are locked.
+    fn balance(total: u64, locked: u64) -> LockedBalance {
+        LockedBalance {
+            balance: BigUint::from(total),
+            locked: BigUint::from(locked),
+            spendable: BigUint::from(total - locked),
+            locks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_plan() {
+        let primary = blake3::hash_slice(b"primary");
+        let (voter_a, voter_b, empty, vesting) = (
+            blake3::hash_slice(b"voter a"),
+            blake3::hash_slice(b"voter b"),
+            blake3::hash_slice(b"empty"),
+            blake3::hash_slice(b"vesting"),
+        );
+
+        let plan = plan(
+            primary,
+            &[
+                (voter_a, balance(10, 0)),
+                (primary, balance(100, 0)),
+                (voter_b, balance(5, 2)),
+                (voter_a, balance(10, 0)),
+                (empty, balance(0, 0)),
+                (vesting, balance(7, 7)),
+            ],
+        );
+
+        // Locked funds stay behind, and everything else is merged
+        assert_eq!(
+            plan.transfers
+                .iter()
+                .map(|transfer| (transfer.source, transfer.value.clone()))
+                .collect::<Vec<(Address, BigUint)>>(),
+            vec![
+                (voter_a, BigUint::from(10u8)),
+                (voter_b, BigUint::from(3u8))
+            ]
+        );
+        assert_eq!(plan.total(), BigUint::from(13u8));
+        assert_eq!(
+            plan.skipped
+                .iter()
+                .map(|account| account.reason)
+                .collect::<Vec<SkipReason>>(),
+            vec![
+                SkipReason::Primary,
+                SkipReason::Duplicate,
+                SkipReason::Empty,
+                SkipReason::Locked
+            ]
+        );
+    }
+}
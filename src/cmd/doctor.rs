@@ -0,0 +1,530 @@
+use libp2p::{multiaddr::Protocol, Multiaddr, PeerId}; // Import the peer addressing types
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::{
+    fmt, fs,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+}; // Import the networking & filesystem libraries
+
+use super::super::{
+    accounts::{account::Account, keystore},
+    common::isolation,
+    core::{
+        sys::{
+            consistency,
+            storage::{StorageMonitor, StoragePressure, StorageQuota},
+            system::System,
+        },
+        types::graph::Graph,
+    },
+    crypto::hash::Hash,
+}; // Import the keystore, storage, ledger & consistency types
+
+/// The number of seconds waited for each bootstrap peer to accept a connection before it's considered unreachable.
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 5;
+
+/// The outcome of a single self-test.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    /// Nothing is wrong
+    Pass,
+
+    /// The node will run, but not as well as it could
+    Warn,
+
+    /// The node won't run, or won't be able to do its job
+    Fail,
+}
+
+impl fmt::Display for Status {
+    /// Formats the status as a fixed-width tag.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Pass => "[ ok ]",
+                Self::Warn => "[warn]",
+                Self::Fail => "[FAIL]",
+            }
+        )
+    }
+}
+
+/// The result of a single self-test, along with what the operator can do about it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Finding {
+    /// The name of the self-test
+    pub check: String,
+
+    /// How the self-test went
+    pub status: Status,
+
+    /// What the self-test found
+    pub detail: String,
+
+    /// What the operator can do to fix the problem, if there is one
+    pub fix: Option<String>,
+}
+
+/// Implement a set of finding helper methods.
+impl Finding {
+    /// Records a self-test that found nothing wrong.
+    ///
+    /// # Arguments
+    ///
+    /// * `check` - The name of the self-test
+    /// * `detail` - What the self-test found
+    pub fn pass(check: &str, detail: String) -> Self {
+        Self {
+            check: check.to_owned(),
+            status: Status::Pass,
+            detail,
+            fix: None,
+        }
+    }
+
+    /// Records a self-test that found a problem that the node can run with.
+    ///
+    /// # Arguments
+    ///
+    /// * `check` - The name of the self-test
+    /// * `detail` - What the self-test found
+    /// * `fix` - What the operator can do about it
+    pub fn warn(check: &str, detail: String, fix: &str) -> Self {
+        Self {
+            status: Status::Warn,
+            fix: Some(fix.to_owned()),
+            ..Self::pass(check, detail)
+        }
+    }
+
+    /// Records a self-test that found a problem that the node can't run with.
+    ///
+    /// # Arguments
+    ///
+    /// * `check` - The name of the self-test
+    /// * `detail` - What the self-test found
+    /// * `fix` - What the operator can do about it
+    pub fn fail(check: &str, detail: String, fix: &str) -> Self {
+        Self {
+            status: Status::Fail,
+            ..Self::warn(check, detail, fix)
+        }
+    }
+}
+
+/// The findings of each of the self-tests run by `smcd doctor`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Report {
+    /// The finding of each self-test, in the order that they were run
+    pub findings: Vec<Finding>,
+}
+
+/// Implement a set of report helper methods.
+impl Report {
+    /// Adds the finding of a self-test to the report.
+    ///
+    /// # Arguments
+    ///
+    /// * `finding` - The finding of the self-test
+    pub fn push(&mut self, finding: Finding) {
+        self.findings.push(finding);
+    }
+
+    /// Gets the number of self-tests that went the given way.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - How the self-tests went
+    pub fn count(&self, status: Status) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.status == status)
+            .count()
+    }
+
+    /// Checks whether or not the node can be started as it is.
+    pub fn is_healthy(&self) -> bool {
+        self.count(Status::Fail) == 0
+    }
+}
+
+impl fmt::Display for Report {
+    /// Formats the report as a list of findings, each followed by its fix, if it has one.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for finding in &self.findings {
+            writeln!(
+                f,
+                "{} {}: {}",
+                finding.status, finding.check, finding.detail
+            )?;
+
+            if let Some(fix) = &finding.fix {
+                writeln!(f, "       fix: {}", fix)?;
+            }
+        }
+
+        write!(
+            f,
+            "{} passed, {} warning(s), {} failed",
+            self.count(Status::Pass),
+            self.count(Status::Warn),
+            self.count(Status::Fail)
+        )
+    }
+}
+
+/// Gets each of the socket addresses that the given multi-address points to, if it points to a TCP port at all.
+///
+/// # Arguments
+///
+/// * `addr` - The multi-address of a peer
+fn socket_addrs(addr: &Multiaddr) -> Vec<SocketAddr> {
+    let mut host: Option<String> = None;
+
+    for protocol in addr.iter() {
+        match protocol {
+            Protocol::Ip4(ip) => host = Some(ip.to_string()),
+            Protocol::Ip6(ip) => host = Some(ip.to_string()),
+            Protocol::Dns4(name) | Protocol::Dns6(name) => host = Some(name.into_owned()),
+            Protocol::Tcp(port) => {
+                return host
+                    .and_then(|host| (host.as_str(), port).to_socket_addrs().ok())
+                    .map(|addrs| addrs.collect())
+                    .unwrap_or_default();
+            }
+            _ => (),
+        }
+    }
+
+    Vec::new()
+}
+
+/// Makes sure that the node can reach its bootstrap peers, by opening a TCP connection to each of them.
+///
+/// # Arguments
+///
+/// * `peers` - The bootstrap peers that the node would connect to
+/// * `timeout` - How long each peer is given to accept the connection
+pub fn check_bootstrap_peers(peers: &[(PeerId, Multiaddr)], timeout: Duration) -> Finding {
+    const CHECK: &str = "bootstrap peers";
+
+    if peers.is_empty() {
+        return Finding::warn(
+            CHECK,
+            "no bootstrap peers are configured, so the node will only hear from peers that dial it".to_owned(),
+            "pass --bootstrap-peer-id & --bootstrap-peer-addr, or add bootstrap peers for the network to the registry",
+        );
+    }
+
+    let unreachable: Vec<String> = peers
+        .iter()
+        .filter(|(_, addr)| {
+            !socket_addrs(addr)
+                .iter()
+                .any(|socket| TcpStream::connect_timeout(socket, timeout).is_ok())
+        })
+        .map(|(id, addr)| format!("{} ({})", addr, id))
+        .collect();
+
+    if unreachable.is_empty() {
+        Finding::pass(
+            CHECK,
+            format!("all {} bootstrap peer(s) are reachable", peers.len()),
+        )
+    } else if unreachable.len() < peers.len() {
+        Finding::warn(
+            CHECK,
+            format!(
+                "{} of {} bootstrap peer(s) are unreachable: {}",
+                unreachable.len(),
+                peers.len(),
+                unreachable.join(", ")
+            ),
+            "the node can join through the others, but report the unreachable peers to the network's operators",
+        )
+    } else {
+        Finding::fail(
+            CHECK,
+            format!("none of the bootstrap peers are reachable: {}", unreachable.join(", ")),
+            "make sure that this machine is online, and that outgoing TCP connections aren't blocked by a firewall",
+        )
+    }
+}
+
+/// Makes sure that the node could listen on the given interface & port.
+///
+/// # Arguments
+///
+/// * `name` - The name of the service that would listen on the port
+/// * `host` - The interface that the service would listen on
+/// * `port` - The port that the service would listen on
+pub fn check_port(name: &str, host: &str, port: u16) -> Finding {
+    match TcpListener::bind((host, port)) {
+        Ok(_) => Finding::pass(name, format!("{}:{} is free", host, port)),
+        Err(e) => Finding::fail(
+            name,
+            format!("{}:{} can't be listened on: {}", host, port, e),
+            "stop whatever is using the port (another smcd, perhaps), or choose another port or interface",
+        ),
+    }
+}
+
+/// Makes sure that the disk holding the data directory has room to spare, per the given quota.
+///
+/// # Arguments
+///
+/// * `data_dir` - The data directory
+/// * `quota` - How much room the node's database may take up, and how much it must leave
+pub fn check_disk_space(data_dir: &str, quota: StorageQuota) -> Finding {
+    const CHECK: &str = "disk space";
+
+    // A fresh node's data directory doesn't exist yet, but it will live on the same disk as its parent
+    let path = if fs::metadata(data_dir).is_ok() {
+        data_dir
+    } else {
+        "."
+    };
+
+    let mut monitor = StorageMonitor::new(quota);
+    monitor.measure(path);
+
+    let status = monitor.status();
+    let available = match status.available_bytes {
+        Some(available) => available,
+        None => {
+            return Finding::warn(
+                CHECK,
+                format!("the free space around {} couldn't be measured", path),
+                "make sure that the data directory is readable, and check the free space on its disk by hand",
+            )
+        }
+    };
+
+    let detail = format!(
+        "{} MiB free, {} MiB used by the data directory",
+        available >> 20,
+        status.db_bytes >> 20
+    );
+    match status.pressure {
+        StoragePressure::Normal => Finding::pass(CHECK, detail),
+        StoragePressure::Low => Finding::warn(
+            CHECK,
+            detail,
+            "free up some space, or move --data-dir to a bigger disk; the node will prune what it can until then",
+        ),
+        StoragePressure::Critical => Finding::fail(
+            CHECK,
+            detail,
+            "free up some space, move --data-dir to a bigger disk, or run with --storage-mode pruned",
+        ),
+    }
+}
+
+/// Makes sure that each of the files in the data directory's keystore can be decoded. Locked files can't be decoded
+/// without their passwords, so they're only checked for the shape of a locked file.
+///
+/// # Arguments
+///
+/// * `data_dir` - The data directory holding the keystore
+pub fn check_keystore(data_dir: &str) -> Finding {
+    const CHECK: &str = "keystore";
+
+    let keystore_dir = format!("{}/keystore", data_dir);
+    let entries = match fs::read_dir(&keystore_dir) {
+        Ok(entries) => entries,
+        Err(_) => {
+            return Finding::pass(
+                CHECK,
+                format!(
+                    "{} doesn't exist yet, and will be made on startup",
+                    keystore_dir
+                ),
+            )
+        }
+    };
+
+    let (mut unlocked, mut locked, mut broken) = (0, 0, Vec::new());
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if path
+            .extension()
+            .map_or(true, |extension| extension != "json")
+        {
+            continue;
+        }
+
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                broken.push(format!("{} ({})", path.display(), e));
+
+                continue;
+            }
+        };
+
+        if keystore::is_locked(&contents) {
+            locked += 1;
+
+            continue;
+        }
+
+        match serde_json::from_slice::<Account>(&contents) {
+            Ok(acc) if acc.keypair().is_ok() && acc.p2p_keypair().is_ok() => unlocked += 1,
+            Ok(_) => broken.push(format!("{} (holds an invalid keypair)", path.display())),
+            Err(e) => broken.push(format!("{} ({})", path.display(), e)),
+        }
+    }
+
+    if broken.is_empty() {
+        Finding::pass(
+            CHECK,
+            format!(
+                "{} unlocked & {} locked account(s) decode",
+                unlocked, locked
+            ),
+        )
+    } else {
+        Finding::fail(
+            CHECK,
+            format!("{} keystore file(s) can't be decoded: {}", broken.len(), broken.join(", ")),
+            "restore the files from a backup (smcli backup import), or move them out of the keystore",
+        )
+    }
+}
+
+/// Makes sure that each of the nodes in the ledger can be read back from the database intact.
+///
+/// # Arguments
+///
+/// * `ledger` - The node's ledger
+pub fn check_ledger(ledger: &Graph) -> Finding {
+    const CHECK: &str = "database";
+    const FIX: &str = "the database is damaged; move the ledger's db directory aside, and let the node resynchronize \
+                       (--fast-sync makes this quicker)";
+
+    for index in 0..ledger.nodes.len() {
+        match ledger.get_pure(index) {
+            Ok(Some(node)) if node.verify_contents() => (),
+            Ok(_) => {
+                return Finding::fail(
+                    CHECK,
+                    format!("the node at index {} doesn't match its hash", index),
+                    FIX,
+                )
+            }
+            Err(e) => {
+                return Finding::fail(
+                    CHECK,
+                    format!("the node at index {} couldn't be read: {}", index, e),
+                    FIX,
+                )
+            }
+        }
+    }
+
+    Finding::pass(
+        CHECK,
+        format!(
+            "read each of the ledger's {} node(s) back (height {})",
+            ledger.nodes.len(),
+            ledger.height()
+        ),
+    )
+}
+
+/// Makes sure that the runtime's config, ledger & keystore all belong to the network that the node would be started
+/// for, and that the ledger descends from the expected genesis.
+///
+/// # Arguments
+///
+/// * `system` - The runtime whose config & ledger should be checked
+/// * `network` - The name of the network that the node would be started for
+/// * `data_dir` - The data directory
+/// * `expected_genesis` - The genesis that the ledger must descend from, if one has been pinned
+pub fn check_consistency(
+    system: &System,
+    network: &str,
+    data_dir: &str,
+    expected_genesis: Option<Hash>,
+) -> Finding {
+    const CHECK: &str = "genesis & ledger";
+
+    // Each of these errors explains how to fix it
+    let failed = |e: &dyn fmt::Display| {
+        let message = e.to_string();
+        let mut parts = message.splitn(2, "; ");
+
+        Finding::fail(
+            CHECK,
+            parts.next().unwrap_or_default().to_owned(),
+            parts.next().unwrap_or("see the logs above"),
+        )
+    };
+
+    if let Err(e) = isolation::check(data_dir, network) {
+        return failed(&e);
+    }
+    if let Err(e) = consistency::check(system, network, data_dir, expected_genesis) {
+        return failed(&e);
+    }
+
+    Finding::pass(
+        CHECK,
+        match system.ledger.root_hash() {
+            Some(genesis) => format!(
+                "the ledger belongs to {}, and descends from genesis {}",
+                network, genesis
+            ),
+            None => format!("the ledger belongs to {}, and has no genesis yet", network),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::common::io; // Import the io module
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_doctor() {
+        let data_dir = format!("{}/.tests/doctor", io::data_dir()); // Get a test directory
+        let _ = fs::remove_dir_all(&data_dir);
+
+        // Missing & intact keystores pass, but undecodable files are reported
+        let mut report = Report::default();
+        report.push(check_keystore(&data_dir));
+        Account::new()
+            .write_to_disk_at_data_directory(&data_dir)
+            .unwrap();
+        report.push(check_keystore(&data_dir));
+        assert!(report.is_healthy());
+
+        fs::write(
+            format!("{}/keystore/broken.json", data_dir),
+            b"{\"keypair\":",
+        )
+        .unwrap();
+        let broken = check_keystore(&data_dir);
+        assert_eq!(broken.status, Status::Fail);
+        assert!(broken.detail.contains("broken.json"));
+
+        // Ports that are already taken are reported
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        report.push(check_port("RPC API", "127.0.0.1", port));
+        assert!(!report.is_healthy());
+        assert!(report.to_string().contains("fix: "));
+
+        // Nodes without any bootstrap peers are warned
+        assert_eq!(
+            check_bootstrap_peers(&[], Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS)).status,
+            Status::Warn
+        );
+    }
+}
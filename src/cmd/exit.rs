@@ -0,0 +1,319 @@
+use std::{io, str::FromStr}; // Import the IO & string parsing libraries
+
+use serde::Serialize; // Import serde serialization
+
+use super::{
+    super::{
+        accounts::contacts::ContactError,
+        common::{address::AddressError, isolation::IsolationError},
+        p2p::rpc::client::{ClientError, ServerErrorKind},
+    },
+    preflight::PreflightError,
+}; // Import each of the error types that the CLI classifies
+
+/// The stable exit code of a command-line tool, telling scripts why a command failed without having to parse its
+/// output. These codes are part of the CLI's interface, so they may be added to, but never renumbered.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitCode {
+    /// The command succeeded
+    Success,
+
+    /// The command failed for a reason that doesn't fit any of the other codes
+    Failure,
+
+    /// The command was given an argument, file, or option that it couldn't make sense of
+    InvalidInput,
+
+    /// None of the nodes that the command was pointed at answered
+    NodeUnreachable,
+
+    /// The node refused the command, since the caller isn't allowed to run it
+    Unauthorized,
+
+    /// The account, transaction, or other object that the command named doesn't exist
+    NotFound,
+
+    /// The command was given an address (or contact name) that doesn't decode to a full address
+    InvalidAddress,
+
+    /// The command would spend more than the sender holds
+    InsufficientBalance,
+
+    /// The node refused the command, since its rules don't allow it
+    Rejected,
+
+    /// The node was too busy to serve the command, and it may be retried
+    NodeBusy,
+
+    /// The node, or the CLI, failed to read from or write to its disk or database
+    Storage,
+
+    /// The node failed to encrypt, decrypt, or sign something
+    Crypto,
+
+    /// The data directory, node, or address that the command was given belongs to a different network than the one
+    /// it was pointed at
+    WrongNetwork,
+}
+
+/// Implement a set of exit code helper methods.
+impl ExitCode {
+    /// Gets the number that the process exits with.
+    pub fn code(self) -> i32 {
+        match self {
+            Self::Success => 0,
+            Self::Failure => 1,
+            Self::InvalidInput => 2,
+            Self::NodeUnreachable => 3,
+            Self::Unauthorized => 4,
+            Self::NotFound => 5,
+            Self::InvalidAddress => 6,
+            Self::InsufficientBalance => 7,
+            Self::Rejected => 8,
+            Self::NodeBusy => 9,
+            Self::Storage => 10,
+            Self::Crypto => 11,
+            Self::WrongNetwork => 12,
+        }
+    }
+
+    /// Classifies the given error by the first error in its chain of causes that the CLI knows how to classify.
+    ///
+    /// # Arguments
+    ///
+    /// * `e` - The error that the command failed with
+    pub fn classify(e: &failure::Error) -> Self {
+        e.iter_chain()
+            .find_map(|cause| {
+                if let Some(e) = cause.downcast_ref::<ClientError>() {
+                    Some(Self::from(e))
+                } else if let Some(e) = cause.downcast_ref::<PreflightError>() {
+                    Some(Self::from(e))
+                } else if let Some(e) = cause.downcast_ref::<ContactError>() {
+                    Some(Self::from(e))
+                } else if let Some(e) = cause.downcast_ref::<IsolationError>() {
+                    Some(Self::from(e))
+                } else if let Some(e) = cause.downcast_ref::<AddressError>() {
+                    Some(Self::from(e))
+                } else if let Some(e) = cause.downcast_ref::<io::Error>() {
+                    Some(Self::from(e))
+                } else if cause.downcast_ref::<serde_json::Error>().is_some() {
+                    Some(Self::InvalidInput)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(Self::Failure)
+    }
+}
+
+impl From<&ClientError> for ExitCode {
+    /// Classifies a failed request by the reason that the node gave for refusing it, if it answered at all.
+    fn from(e: &ClientError) -> Self {
+        match e {
+            ClientError::Unreachable { .. } => Self::NodeUnreachable,
+            ClientError::Server { kind, .. } => match kind {
+                ServerErrorKind::Unauthorized => Self::Unauthorized,
+                ServerErrorKind::NotFound => Self::NotFound,
+                ServerErrorKind::Rejected => Self::Rejected,
+                ServerErrorKind::Crypto => Self::Crypto,
+                ServerErrorKind::Storage => Self::Storage,
+                ServerErrorKind::Busy => Self::NodeBusy,
+                ServerErrorKind::Protocol | ServerErrorKind::Unknown => Self::Failure,
+            },
+            _ => Self::Failure,
+        }
+    }
+}
+
+impl From<&PreflightError> for ExitCode {
+    /// Classifies a mistake spotted in a transaction before it was published.
+    fn from(e: &PreflightError) -> Self {
+        match e {
+            PreflightError::MalformedRecipient => Self::InvalidAddress,
+            PreflightError::InsufficientBalance { .. } => Self::InsufficientBalance,
+            PreflightError::NonceAlreadyUsed { .. } | PreflightError::CheckFailed { .. } => {
+                Self::Rejected
+            }
+        }
+    }
+}
+
+impl From<&ContactError> for ExitCode {
+    /// Classifies a failed lookup in, or edit of, the address book.
+    fn from(e: &ContactError) -> Self {
+        match e {
+            ContactError::InvalidAddress { .. } | ContactError::NoSuchContact { .. } => {
+                Self::InvalidAddress
+            }
+            ContactError::InvalidName { .. } => Self::InvalidInput,
+        }
+    }
+}
+
+impl From<&IsolationError> for ExitCode {
+    /// Classifies a data directory or node found to belong to a different network.
+    fn from(e: &IsolationError) -> Self {
+        match e {
+            IsolationError::Io { .. } => Self::Storage,
+            _ => Self::WrongNetwork,
+        }
+    }
+}
+
+impl From<&AddressError> for ExitCode {
+    /// Classifies a typed address that couldn't be used.
+    fn from(e: &AddressError) -> Self {
+        match e {
+            AddressError::WrongNetwork { .. } => Self::WrongNetwork,
+            _ => Self::InvalidAddress,
+        }
+    }
+}
+
+impl From<&io::Error> for ExitCode {
+    /// Classifies a failed read from, or write to, the disk.
+    fn from(e: &io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::NotFound => Self::NotFound,
+            io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => Self::InvalidInput,
+            _ => Self::Storage,
+        }
+    }
+}
+
+/// The format that a command-line tool prints its errors in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// Errors are logged for a person to read
+    Text,
+
+    /// Errors are printed to stderr as an ErrorReport, for scripts to parse
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    /// Parses an output format from its name ("text" or "json").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "unknown output format '{}' (expected text or json)",
+                s
+            )),
+        }
+    }
+}
+
+/// A failed command, as printed to stderr with `--output json`:
+///
+/// `{"error": {"code": 3, "kind": "node_unreachable", "message": "Failed to load the DAG: none of the RPC ..."}}`
+///
+/// `code` is the number that the process exits with, and `kind` names it; both are stable. `message` is meant for
+/// people, and may change between versions.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ErrorReport {
+    /// The number that the process exits with
+    pub code: i32,
+
+    /// The name of the exit code
+    pub kind: ExitCode,
+
+    /// What went wrong, including each of the error's causes
+    pub message: String,
+}
+
+impl From<&failure::Error> for ErrorReport {
+    /// Describes the given error, classifying it by its chain of causes.
+    fn from(e: &failure::Error) -> Self {
+        let kind = ExitCode::classify(e);
+
+        Self {
+            code: kind.code(),
+            kind,
+            message: e
+                .iter_chain()
+                .map(|cause| cause.to_string())
+                .collect::<Vec<String>>()
+                .join(": "),
+        }
+    }
+}
+
+/// Implement a set of error report helper methods.
+impl ErrorReport {
+    /// Encodes the report in the documented JSON error shape.
+    pub fn to_json(&self) -> String {
+        serde_json::json!({ "error": self }).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use failure::Fail; // Allow errors to be given context
+
+    use super::super::super::p2p::rpc::failover::FailoverError; // Import the failover error type
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_classify() {
+        let unreachable: failure::Error = ClientError::Unreachable {
+            error: FailoverError::NoEndpoints,
+        }
+        .context("Failed to load the DAG")
+        .into();
+        let report = ErrorReport::from(&unreachable);
+        assert_eq!(report.kind, ExitCode::NodeUnreachable);
+        assert_eq!(report.code, 3);
+        assert_eq!(
+            report.message,
+            "Failed to load the DAG: no RPC endpoints were given"
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&report.to_json()).unwrap()["error"]["kind"],
+            "node_unreachable"
+        );
+
+        // Scripts can tell a typo in an address from an account that can't afford a transaction
+        assert_eq!(
+            ExitCode::classify(
+                &ContactError::NoSuchContact {
+                    name: "bob".to_owned()
+                }
+                .into()
+            ),
+            ExitCode::InvalidAddress
+        );
+        assert_eq!(
+            ExitCode::classify(
+                &PreflightError::InsufficientBalance {
+                    value: "2".to_owned(),
+                    balance: "1".to_owned()
+                }
+                .into()
+            ),
+            ExitCode::InsufficientBalance
+        );
+        assert_eq!(
+            ExitCode::classify(
+                &IsolationError::NodeNetworkMismatch {
+                    url: "http://127.0.0.1:8080".to_owned(),
+                    node_network: "andromeda".to_owned(),
+                    network: "testnet".to_owned()
+                }
+                .into()
+            )
+            .code(),
+            12
+        );
+        assert_eq!(
+            ExitCode::classify(&failure::err_msg("something else")),
+            ExitCode::Failure
+        );
+        assert_eq!("json".parse(), Ok(OutputFormat::Json));
+    }
+}
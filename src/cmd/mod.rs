@@ -1 +1,6 @@
 pub mod commands;
+pub mod consolidate;
+pub mod doctor;
+pub mod exit;
+pub mod preflight;
+pub mod progress;
@@ -0,0 +1,180 @@
+use num::BigUint; // Add support for large unsigned integers
+
+use super::super::{
+    common::{address::Address, fink},
+    core::types::{gas, transaction::Transaction},
+    validator::{Check, ValidationReport},
+}; // Import the transaction & validator types
+
+/// A mistake spotted in a transaction before it was published.
+#[derive(Debug, Fail, PartialEq)]
+pub enum PreflightError {
+    #[fail(
+        display = "the recipient address is malformed (it doesn't decode to a 32-byte address), so the funds would be burned"
+    )]
+    MalformedRecipient,
+    #[fail(
+        display = "the transaction spends up to {} SMC, but the sender only has {} SMC",
+        value, balance
+    )]
+    InsufficientBalance {
+        value: String,   // The amount sent by the transaction, along with its max fee, in SMC
+        balance: String, // The balance of the sender, in SMC
+    },
+    #[fail(
+        display = "the sender has already used nonce {}; the next unused nonce is {}",
+        nonce, expected
+    )]
+    NonceAlreadyUsed {
+        nonce: u64,    // The nonce of the transaction
+        expected: u64, // The nonce that the node expects next
+    },
+    #[fail(display = "the node's {} check failed: {}", check, reason)]
+    CheckFailed {
+        check: String,  // The name of the failed check
+        reason: String, // Why the check failed
+    },
+}
+
+/// Checks the given transaction for common mistakes, considering the node's validation report for it, and returns
+/// each of the mistakes found.
+///
+/// # Arguments
+///
+/// * `tx` - The transaction that is about to be published
+/// * `report` - The node's validation report for the transaction
+pub fn check(tx: &Transaction, report: &ValidationReport) -> Vec<PreflightError> {
+    let mut errors = Vec::new(); // Each of the mistakes that we've found
+
+    // A recipient that couldn't be decoded ends up as the zero address
+    if tx.transaction_data.recipient == Address::default() {
+        errors.push(PreflightError::MalformedRecipient);
+    }
+
+    let cost = gas::max_cost(&tx.transaction_data);
+    if cost > report.sender_balance {
+        errors.push(PreflightError::InsufficientBalance {
+            value: smc(&cost),
+            balance: smc(&report.sender_balance),
+        });
+    }
+
+    if tx.transaction_data.nonce < report.expected_nonce {
+        errors.push(PreflightError::NonceAlreadyUsed {
+            nonce: tx.transaction_data.nonce,
+            expected: report.expected_nonce,
+        });
+    }
+
+    // Report anything else that the node's validator didn't like, leaving out what we've already explained
+    let explained = |check: Check| {
+        errors.iter().any(|e| match e {
+            PreflightError::InsufficientBalance { .. } => check == Check::SenderBalance,
+            PreflightError::NonceAlreadyUsed { .. } => check == Check::Nonce,
+            _ => false,
+        })
+    };
+    let failed: Vec<PreflightError> = report
+        .checks
+        .iter()
+        .filter(|res| !res.passed && !explained(res.check))
+        .map(|res| PreflightError::CheckFailed {
+            check: format!("{:?}", res.check),
+            reason: res.reason.clone().unwrap_or_default(),
+        })
+        .collect();
+    errors.extend(failed);
+
+    errors
+}
+
+/// Describes the given transaction in a few human-readable lines.
+///
+/// # Arguments
+///
+/// * `tx` - The transaction that is about to be published
+/// * `report` - The node's validation report for the transaction
+pub fn summarize(tx: &Transaction, report: &ValidationReport) -> String {
+    format!(
+        "Transaction: {}\nSender:      {} (balance: {} SMC)\nRecipient:   {}\nAmount:      {} SMC ({} finks)\nMax fee:     {} finks ({} gas at {} finks)\nNonce:       {}\nPayload:     {} bytes",
+        tx.hash,
+        tx.transaction_data.sender,
+        smc(&report.sender_balance),
+        tx.transaction_data.recipient,
+        smc(&tx.transaction_data.value),
+        tx.transaction_data.value,
+        gas::max_fee(&tx.transaction_data),
+        tx.transaction_data.gas_limit,
+        tx.transaction_data.gas_price,
+        tx.transaction_data.nonce,
+        tx.transaction_data.payload.len()
+    )
+}
+
+/// Formats the given number of finks as a number of SMC.
+fn smc(finks: &BigUint) -> String {
+    fink::convert_finks_to_smc(finks.clone()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{crypto::blake3, validator::CheckResult};
+    use super::*; // Import names from our parent module
+
+    /// Makes a validation report for the given transaction, as the node would for the given balance and nonce.
+    fn report(tx: &Transaction, sender_balance: u64, expected_nonce: u64) -> ValidationReport {
+        ValidationReport {
+            tx_hash: tx.hash,
+            valid: true,
+            checks: vec![CheckResult {
+                check: Check::Nonce,
+                passed: expected_nonce <= tx.transaction_data.nonce,
+                reason: None,
+            }],
+            expected_nonce,
+            expected_parent_state_hash: None,
+            sender_balance: BigUint::from(sender_balance),
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let tx = Transaction::new(
+            2,
+            blake3::hash_slice(b"sender"),
+            blake3::hash_slice(b"recipient"),
+            BigUint::from(10 as u8),
+            b"test",
+            vec![],
+        ); // Make a transaction
+
+        assert!(check(&tx, &report(&tx, 10, 2)).is_empty()); // Nothing is wrong with it
+
+        assert_eq!(
+            check(&tx, &report(&tx, 9, 3)),
+            vec![
+                PreflightError::InsufficientBalance {
+                    value: "1/100000000000000000".to_owned(),
+                    balance: "9/1000000000000000000".to_owned(),
+                },
+                PreflightError::NonceAlreadyUsed {
+                    nonce: 2,
+                    expected: 3
+                }
+            ]
+        ); // The sender is too poor, and has already used the nonce
+
+        let typo = Transaction::new(
+            2,
+            blake3::hash_slice(b"sender"),
+            Address::from("not an address"),
+            BigUint::from(10 as u8),
+            b"test",
+            vec![],
+        ); // Make a transaction to a mistyped address
+        assert_eq!(
+            check(&typo, &report(&typo, 10, 2)),
+            vec![PreflightError::MalformedRecipient]
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use console::Term; // Import the terminal handle
+
+use std::time::{Duration, Instant}; // Import the time library
+
+/// The number of characters in a progress bar.
+pub const BAR_WIDTH: usize = 30;
+
+/// How often progress is logged when it can't be drawn (e.g. when stderr is redirected to a file).
+pub const LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Reports the progress of a long-running command: as a bar redrawn in place when stderr is a terminal, or as a log
+/// line every few seconds when it isn't, so that logs of unattended runs aren't flooded with redraws.
+pub struct Progress {
+    /// What is being done (e.g. "Downloading the DAG")
+    label: String,
+
+    /// The position at which the work is done, if it is known
+    total: Option<u64>,
+
+    /// How much of the work has been done
+    position: u64,
+
+    /// When the work began
+    started: Instant,
+
+    /// When progress was last logged, if it isn't being drawn
+    last_logged: Option<Instant>,
+
+    /// The terminal that the bar is drawn on, if stderr is one
+    term: Option<Term>,
+
+    /// Whether or not anything should be reported at all
+    enabled: bool,
+}
+
+/// Implement a set of progress helper methods.
+impl Progress {
+    /// Begins reporting the progress of some work. Nothing is reported if info logging is turned off (e.g. with
+    /// --silent).
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - What is being done
+    /// * `total` - The position at which the work is done, if it is known
+    pub fn new(label: &str, total: Option<u64>) -> Self {
+        let term = Term::stderr();
+
+        Self {
+            label: label.to_owned(),
+            total,
+            position: 0,
+            started: Instant::now(),
+            last_logged: None,
+            term: if term.is_term() { Some(term) } else { None },
+            enabled: log_enabled!(log::Level::Info),
+        }
+    }
+
+    /// Changes what is being done, reporting the change right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - What is being done now
+    pub fn set_label(&mut self, label: &str) {
+        self.label = label.to_owned();
+        self.last_logged = None;
+
+        self.report();
+    }
+
+    /// Moves the progress to the given position.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - How much of the work has been done
+    pub fn set(&mut self, position: u64) {
+        self.position = position;
+
+        self.report();
+    }
+
+    /// Moves the progress along by the given amount.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - How much more of the work has been done
+    pub fn inc(&mut self, delta: u64) {
+        self.set(self.position + delta);
+    }
+
+    /// Stops reporting progress, clearing the bar, so that whatever is printed next starts on a clean line.
+    pub fn finish(&mut self) {
+        if let (true, Some(term)) = (self.enabled, &self.term) {
+            let _ = term.clear_line();
+        }
+    }
+
+    /// Draws the bar, or logs the progress if it has been a while since it was last logged.
+    fn report(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        let line = render(
+            &self.label,
+            self.position,
+            self.total,
+            self.started.elapsed(),
+        );
+
+        match &self.term {
+            Some(term) => {
+                let _ = term
+                    .clear_line()
+                    .and_then(|_| term.write_str(&line))
+                    .and_then(|_| term.flush());
+            }
+            None => {
+                let done = self.total.map_or(false, |total| self.position >= total);
+                if done
+                    || self
+                        .last_logged
+                        .map_or(true, |logged| logged.elapsed() >= LOG_INTERVAL)
+                {
+                    info!("{}", line);
+                    self.last_logged = Some(Instant::now());
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Progress {
+    /// Clears the bar, in case the work was abandoned part way through (e.g. because of an error).
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Writes out the given progress as a single line: a bar, with an estimate of the time remaining, if the total is
+/// known, or a running count otherwise.
+///
+/// # Arguments
+///
+/// * `label` - What is being done
+/// * `position` - How much of the work has been done
+/// * `total` - The position at which the work is done, if it is known
+/// * `elapsed` - How long the work has been going on for
+pub fn render(label: &str, position: u64, total: Option<u64>, elapsed: Duration) -> String {
+    let rate = if elapsed.as_secs_f64() > 0.0 {
+        position as f64 / elapsed.as_secs_f64()
+    } else {
+        0.0
+    };
+
+    match total {
+        Some(total) if total > 0 => {
+            let fraction = (position as f64 / total as f64).min(1.0);
+            let filled = (fraction * BAR_WIDTH as f64) as usize;
+
+            let eta = if rate > 0.0 && position < total {
+                format!(", ETA {}s", ((total - position) as f64 / rate).ceil())
+            } else {
+                String::new()
+            };
+
+            format!(
+                "{} [{}{}] {}/{} ({}%){}",
+                label,
+                "#".repeat(filled),
+                "-".repeat(BAR_WIDTH - filled),
+                position,
+                total,
+                (fraction * 100.0) as u64,
+                eta
+            )
+        }
+        _ => format!("{}: {} ({:.1}/s)", label, position, rate),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render("Exporting", 15, Some(60), Duration::from_secs(3)),
+            format!(
+                "Exporting [{}{}] 15/60 (25%), ETA 9s",
+                "#".repeat(7),
+                "-".repeat(BAR_WIDTH - 7)
+            )
+        );
+
+        // Work that has run over its estimate is never drawn past the end of the bar
+        assert!(
+            render("Exporting", 90, Some(60), Duration::from_secs(3)).starts_with(&format!(
+                "Exporting [{}] 90/60 (100%)",
+                "#".repeat(BAR_WIDTH)
+            ))
+        );
+
+        assert_eq!(
+            render("Downloading the DAG", 25, None, Duration::from_secs(2)),
+            "Downloading the DAG: 25 (12.5/s)"
+        );
+    }
+}
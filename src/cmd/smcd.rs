@@ -10,21 +10,67 @@ extern crate tokio;
 use failure::Error;
 use libp2p::{Multiaddr, PeerId};
 use summercash::{
-    core::{sys::system::System, types::genesis::Config},
+    accounts::{
+        secrets,
+        signer::{self, RemoteSigner},
+    },
+    cmd::doctor::{self, Finding, Report},
+    common::{
+        address::{self, Address},
+        diversity::DiversityPolicy,
+        isolation,
+        lock::InstrumentedRwLock,
+    },
+    core::{
+        sys::{checkpoints::Checkpoint, consistency, storage::StorageQuota, system::System},
+        types::{
+            chainfile::{self, ChainReader},
+            genesis::Config,
+            graph::{ResolutionLimits, StorageMode},
+            receipt::ExecutionClock,
+        },
+    },
+    crypto::hash::Hash,
     p2p::{
+        attestation::AttestationPolicy,
         client::Client,
-        network, peers,
-        rpc::{accounts::AccountsImpl, dag::DagImpl},
+        dht::DhtConfig,
+        psk::SwarmKey,
+        publisher,
+        registry::{NetworkInfo, NetworkRegistry},
+        relay,
+        rpc::{
+            access::ReadOnlyGate,
+            accounts::AccountsImpl,
+            auth::{self, AdminGate, AuthImpl, RpcHandler, SessionTable},
+            batch::BatchLimit,
+            contacts::ContactsImpl,
+            dag::DagImpl,
+            events::EventsImpl,
+            guardians::GuardiansImpl,
+            notes::NotesImpl,
+            peers::PeersImpl,
+            rewards::RewardsImpl,
+            runtime::RuntimeImpl,
+            subscriptions::SubscriptionsImpl,
+            tenants::{TenantGate, TenantTable},
+            vesting::VestingImpl,
+        },
+        updater::{Updater, UpdaterConfig},
+        webhook::{WebhookConfig, WebhookPolicy},
     },
 };
 
 use std::{
+    fs, io,
     sync::{Arc, RwLock},
     thread,
+    time::Duration,
 };
 
-use jsonrpc_core::IoHandler;
-use jsonrpc_http_server::ServerBuilder;
+use jsonrpc_http_server::{hyper, ServerBuilder};
+use jsonrpc_pubsub::Session as PubSubSession;
+use jsonrpc_ws_server::RequestContext;
 
 use clap::Clap;
 
@@ -52,14 +98,52 @@ struct Opts {
     #[clap(short = "p", long = "node-port", default_value = "0")]
     node_port: u16,
 
+    /// Listens for peers on the given multi-address (e.g. /ip6/::/tcp/2048), instead of on every IPv4 and IPv6
+    /// interface at the node port. May be given more than once.
+    #[clap(long = "listen-addr")]
+    listen_addrs: Vec<String>,
+
+    /// Advertises the given multi-address to peers, in addition to those that the node is listening on (e.g. a
+    /// public address in front of a NAT). May be given more than once.
+    #[clap(long = "external-addr")]
+    external_addrs: Vec<String>,
+
     /// Signals to the local node that it should listen on the givn port for all RPC API communications.
     #[clap(short = "P", long = "rpc-port", default_value = "8080")]
     api_port: u16,
 
-    /// Ensures that the node will connect to the given network
+    /// The interface that the full RPC API, wallet & admin methods included, is served on. Use 127.0.0.1 to keep
+    /// account manipulation off the network.
+    #[clap(long = "rpc-host", default_value = "0.0.0.0")]
+    api_host: String,
+
+    /// Serves only the read-only RPC methods (balances, transactions, receipts, and the like) on the given port, in
+    /// addition to the full API. Safe to expose publicly. Disabled unless given.
+    #[clap(long = "rpc-read-port", default_value = "0")]
+    read_api_port: u16,
+
+    /// The interface that the read-only RPC API is served on.
+    #[clap(long = "rpc-read-host", default_value = "0.0.0.0")]
+    read_api_host: String,
+
+    /// Serves the read-only RPC methods over WebSocket on the given port, along with subscriptions pushing new
+    /// transactions, proposals & balance changes to wallets as they happen. Disabled unless given.
+    #[clap(long = "rpc-ws-port", default_value = "0")]
+    ws_api_port: u16,
+
+    /// The interface that the WebSocket RPC API is served on.
+    #[clap(long = "rpc-ws-host", default_value = "0.0.0.0")]
+    ws_api_host: String,
+
+    /// Ensures that the node will connect to the given network, as described by the network registry
     #[clap(long = "network", default_value = "andromeda")]
     network: String,
 
+    /// Downloads a network registry from the given URL before starting, adding its networks to those in the
+    /// config/networks.json file in the data directory.
+    #[clap(long = "network-registry-url", default_value = "none")]
+    network_registry_url: String,
+
     /// Changes the directory that node data will be stored in
     #[clap(long = "data-dir", default_value = "data")]
     data_dir: String,
@@ -68,6 +152,19 @@ struct Opts {
     #[clap(long = "genesis-file", default_value = "none")]
     genesis_file: String,
 
+    /// Imports the history held in the given chain file into a fresh node before starting, re-executing each of its
+    /// transactions. Much faster than synchronizing a long history from the network.
+    #[clap(long = "import-chain", default_value = "none")]
+    import_chain: String,
+
+    /// Exports the node's entire history to the given chain file, and exits without starting the node.
+    #[clap(long = "export-chain", default_value = "none")]
+    export_chain: String,
+
+    /// Refuses to start unless the ledger descends from the genesis transaction with the given hash.
+    #[clap(long = "expected-genesis", default_value = "none")]
+    expected_genesis: String,
+
     /// Uses a bootstrap peer with the given ID to connect to the network.
     #[clap(long = "bootstrap-peer-id", default_value = "net_bps")]
     bootstrap_peer: String,
@@ -75,6 +172,250 @@ struct Opts {
     /// Uses a bootstrap peer with the given multi-address to connect to the network.
     #[clap(long = "bootstrap-peer-addr", default_value = "net_bps")]
     bootstrap_peer_addr: String,
+
+    /// Limits the number of proposals and votes relayed from a single sender per window, if the node doesn't vote.
+    #[clap(long = "relay-rate-limit", default_value = "16")]
+    relay_rate_limit: u32,
+
+    /// The length of the relay rate-limiting window, in seconds.
+    #[clap(long = "relay-window", default_value = "60")]
+    relay_window: u64,
+
+    /// The minimum gas price, in finks, that a transaction must pay in order to be relayed, if the node doesn't vote.
+    #[clap(long = "relay-gas-price-floor", default_value = "0")]
+    relay_gas_price_floor: u64,
+
+    /// Keeps the state resulting from every transaction in the DAG, so that historical states and balances can be
+    /// served through the RPC API. Shorthand for --storage-mode archive.
+    #[clap(long = "archive")]
+    archive: bool,
+
+    /// How much of the ledger's history the node keeps around: archive, pruned (the states of recent transactions
+    /// are kept in memory, and older ones paged out to the disk), or light (only the executed head's state is kept in
+    /// memory). Uses the mode in the network's config unless given.
+    #[clap(long = "storage-mode", default_value = "config")]
+    storage_mode: String,
+
+    /// The number of recent transactions whose states a pruned node keeps in memory. Uses the number in the
+    /// network's config unless given.
+    #[clap(long = "recent-nodes", default_value = "0")]
+    recent_nodes: usize,
+
+    /// Picks an empty ledger up from the network's latest snapshot, only replaying the transactions after it, rather
+    /// than replaying the ledger from its genesis. The history before the snapshot is taken on trust, so its anchor has
+    /// to be pinned as a checkpoint, or be the head that enough of the network's stake vouches for.
+    #[clap(long = "fast-sync")]
+    fast_sync: bool,
+
+    /// Runs the node as a light client: only the DAG's headers are synchronized, and balances proven by full nodes are
+    /// checked against them, rather than the DAG being executed.
+    #[clap(long = "light")]
+    light: bool,
+
+    /// The number of transactions added to the ledger between the snapshots that the node publishes for others to
+    /// fast-sync from. Snapshots aren't published if this is zero.
+    #[clap(long = "snapshot-interval", default_value = "4096")]
+    snapshot_interval: u64,
+
+    /// Gives up on resolving a transaction whose unexecuted ancestors are nested more than the given number of
+    /// generations deep.
+    #[clap(long = "max-resolution-depth", default_value = "4096")]
+    max_resolution_depth: usize,
+
+    /// Gives up on resolving a transaction whose parents would require more than the given number of unexecuted
+    /// transactions to be executed.
+    #[clap(long = "max-resolution-nodes", default_value = "65536")]
+    max_resolution_nodes: usize,
+
+    /// The number of peers that each DHT record is replicated to.
+    #[clap(long = "dht-replication-factor", default_value = "20")]
+    dht_replication_factor: usize,
+
+    /// The number of peers asked at once while looking up a DHT record.
+    #[clap(long = "dht-parallelism", default_value = "3")]
+    dht_parallelism: usize,
+
+    /// The number of seconds that a DHT record lives for before it expires, unless it is republished. Records never
+    /// expire if this is zero.
+    #[clap(long = "dht-record-ttl", default_value = "604800")]
+    dht_record_ttl: u64,
+
+    /// The number of seconds between republications of the DHT records published by the node. Must be shorter than
+    /// the lifetime of a record.
+    #[clap(long = "dht-republish-interval", default_value = "43200")]
+    dht_republish_interval: u64,
+
+    /// The number of DHT records that the node keeps for the network.
+    #[clap(long = "dht-max-records", default_value = "1048576")]
+    dht_max_records: usize,
+
+    /// Refuses gossiped proposals and votes sent more than the given number of seconds ago.
+    #[clap(long = "gossip-max-age", default_value = "600")]
+    gossip_max_age: u64,
+
+    /// The number of distinct publishers holding stake that must vouch for a head before the node synchronizes
+    /// towards it.
+    #[clap(long = "min-head-attesters", default_value = "2")]
+    min_head_attesters: usize,
+
+    /// The number of finks that the publishers vouching for a head must hold between them before the node
+    /// synchronizes towards it.
+    #[clap(long = "min-head-stake", default_value = "0")]
+    min_head_stake: u64,
+
+    /// The number of distinct peers that must have published the votes on a proposal, or the attestations of a head,
+    /// before the node executes the proposal or synchronizes towards the head. The node's own votes don't count.
+    #[clap(long = "min-distinct-peers", default_value = "0")]
+    min_distinct_peers: usize,
+
+    /// The number of distinct subnets (/24 for IPv4, /48 for IPv6) that those peers must be reachable in.
+    #[clap(long = "min-distinct-subnets", default_value = "0")]
+    min_distinct_subnets: usize,
+
+    /// POSTs a JSON alert to the given URL whenever competing transactions spending the same nonce are seen, so that
+    /// payments made by either can be held until the ledger settles on one.
+    #[clap(long = "double-spend-webhook", default_value = "none")]
+    double_spend_webhook: String,
+
+    /// POSTs a JSON notification to the given URL whenever a proposal is executed, a transaction touching a watched
+    /// account is executed, the node falls behind the network (or catches back up), or a double spend is seen. May
+    /// be given more than once.
+    #[clap(long = "webhook")]
+    webhooks: Vec<String>,
+
+    /// Tells the webhooks about each executed transaction sent by, or paying, the given account. May be given more
+    /// than once.
+    #[clap(long = "webhook-watch")]
+    webhook_watched: Vec<String>,
+
+    /// Signs each webhook notification with the secret held in the given file, sending the HMAC-SHA256 of the body in
+    /// the X-SummerCash-Signature header.
+    #[clap(long = "webhook-secret-file", default_value = "none")]
+    webhook_secret_file: String,
+
+    /// The number of times that delivering a webhook notification is attempted before it is given up on.
+    #[clap(long = "webhook-max-attempts", default_value = "5")]
+    webhook_max_attempts: u32,
+
+    /// The number of milliseconds waited before retrying a failed webhook delivery. The wait doubles with each
+    /// attempt after the first.
+    #[clap(long = "webhook-backoff-ms", default_value = "500")]
+    webhook_backoff_ms: u64,
+
+    /// The number of transactions that the node may trail the network by before the webhooks are told that it has
+    /// fallen behind.
+    #[clap(long = "webhook-behind-by", default_value = "32")]
+    webhook_behind_by: u64,
+
+    /// Unlocks any locked voting accounts with passwords kept in the given secret store (none or keychain).
+    #[clap(long = "secret-store", default_value = "none")]
+    secret_store: String,
+
+    /// Only lets callers who prove that they hold one of the given accounts use the admin RPC methods. May be given
+    /// more than once.
+    #[clap(long = "admin-account")]
+    admin_accounts: Vec<String>,
+
+    /// Serves a downstream application holding the given API key, written as namespace:api-key, confining its
+    /// accounts, transactions, notes and contacts to a directory of its own. May be given more than once.
+    #[clap(long = "tenant")]
+    tenants: Vec<String>,
+
+    /// Refuses any synchronized history that disagrees with the given checkpoint, written as index:hash. May be given
+    /// more than once. Checkpoints can also be pinned in config/checkpoints.json, or through the RPC API.
+    #[clap(long = "checkpoint")]
+    checkpoints: Vec<String>,
+
+    /// Refuses JSON-RPC batches holding more than the given number of calls.
+    #[clap(long = "rpc-batch-limit", default_value = "100")]
+    rpc_batch_limit: usize,
+
+    /// The greatest number of bytes that the node's database may take up before writes that consensus can do without
+    /// are paused. The database is unbounded if this is zero.
+    #[clap(long = "max-db-bytes", default_value = "0")]
+    max_db_bytes: u64,
+
+    /// The number of free bytes on the database's disk below which the node prunes whatever it can rebuild later.
+    #[clap(long = "low-free-bytes", default_value = "4294967296")]
+    low_free_bytes: u64,
+
+    /// The number of free bytes on the database's disk below which the node pauses writes that consensus can do
+    /// without.
+    #[clap(long = "min-free-bytes", default_value = "1073741824")]
+    min_free_bytes: u64,
+
+    /// How the execution of each transaction is timestamped in its receipt: off, wall-clock, or monotonic (the node's
+    /// clock, but never earlier than the execution before).
+    #[clap(long = "execution-timestamps", default_value = "monotonic")]
+    execution_timestamps: String,
+
+    /// The number of outgoing gossip messages that may wait to be published at once.
+    #[clap(long = "publish-queue-size", default_value = "256")]
+    publish_queue_size: usize,
+
+    /// The number of gossip messages published each second.
+    #[clap(long = "publish-rate", default_value = "20")]
+    publish_rate: u32,
+
+    /// What happens to gossip messages published while the queue is full (drop-oldest or drop-newest).
+    #[clap(long = "publish-overflow", default_value = "drop-oldest")]
+    publish_overflow: String,
+
+    /// Delegates all signing to the smsigner process listening on the given socket, so that no private keys need to
+    /// be held by the node.
+    #[clap(long = "remote-signer", default_value = "none")]
+    remote_signer: String,
+
+    /// The file holding a copy of the remote signer's token. Defaults to the signer.token file next to its socket.
+    #[clap(long = "remote-signer-token", default_value = "none")]
+    remote_signer_token: String,
+
+    /// The number of seconds that an interrupted export of the DAG is kept by the node, waiting to be resumed.
+    #[clap(long = "export-ttl", default_value = "3600")]
+    export_ttl: u64,
+
+    /// The greatest number of locally submitted transactions held in the mempool, waiting to be published and
+    /// executed. Once it's full, the transactions paying the lowest gas prices are dropped first.
+    #[clap(long = "mempool-size", default_value = "4096")]
+    mempool_size: usize,
+
+    /// The number of seconds that a transaction may wait in the mempool before it is dropped.
+    #[clap(long = "mempool-ttl", default_value = "3600")]
+    mempool_ttl: u64,
+
+    /// How far ahead of its sender's next nonce a transaction submitted to the mempool may be.
+    #[clap(long = "max-nonce-gap", default_value = "16")]
+    max_nonce_gap: u64,
+
+    /// Checks the signed release manifest at the given URL every so often, reporting newer releases of the node
+    /// software in the log and the sync status. Requires --update-signer.
+    #[clap(long = "update-manifest-url", default_value = "none")]
+    update_manifest_url: String,
+
+    /// The address of the account that release manifests must be signed by.
+    #[clap(long = "update-signer", default_value = "none")]
+    update_signer: String,
+
+    /// The number of seconds between checks of the release manifest.
+    #[clap(long = "update-check-interval", default_value = "21600")]
+    update_check_interval: u64,
+
+    /// Downloads the build of each newer release to the given directory, verifying it against the manifest. Nothing
+    /// is installed; the operator still upgrades by hand.
+    #[clap(long = "update-download-dir", default_value = "none")]
+    update_download_dir: String,
+
+    #[clap(subcommand)]
+    command: Option<NodeCommand>,
+}
+
+#[derive(Clap)]
+enum NodeCommand {
+    /// Checks that the node is ready to be started with the given options: that its bootstrap peers are reachable,
+    /// its RPC ports are free, its disk has room, and its keystore, database & ledger are intact. Prints a report
+    /// with a fix for each problem found, and exits without starting the node.
+    #[clap(name = "doctor")]
+    Doctor,
 }
 
 /// Starts the SMCd node daemon.
@@ -83,11 +424,152 @@ async fn main() -> Result<(), Error> {
     // Get any flags issued by the user
     let opts: Opts = use_options(Opts::parse())?;
 
+    // Look up the network that the user wants to join
+    let network_info = use_network_registry(&opts).await?;
+
     // Use the options
-    let (bootstrap_nodes, opts) = use_bootstrap_peers(&opts.network.clone(), opts)?;
+    let (bootstrap_nodes, opts) = use_bootstrap_peers(&network_info, opts)?;
+
+    // If the user wants to check the node over, do that instead of starting it
+    if let Some(NodeCommand::Doctor) = opts.command {
+        return run_doctor(&opts, &network_info, &bootstrap_nodes);
+    }
 
     // Get a client for the network that the user specified
     let mut c = Client::new(opts.network.clone().into(), &opts.data_dir)?;
+    c.protocol_prefix = network_info.protocol_prefix.clone();
+
+    // Use the relay thresholds that the user specified
+    c.relay_policy = relay::PolicyConfig {
+        messages_per_window: opts.relay_rate_limit,
+        window_secs: opts.relay_window,
+        gas_price_floor: opts.relay_gas_price_floor.into(),
+    };
+
+    // Refuse proposals and votes that have been held onto for too long
+    c.gossip_max_age_secs = opts.gossip_max_age;
+
+    // Tune the DHT as the user asked, making sure that records outlive the time between republications
+    c.dht = DhtConfig {
+        replication_factor: opts.dht_replication_factor,
+        parallelism: opts.dht_parallelism,
+        record_ttl_secs: opts.dht_record_ttl,
+        republish_interval_secs: opts.dht_republish_interval,
+        max_records: opts.dht_max_records,
+    };
+    c.dht.kademlia_config(Vec::new())?;
+
+    // Only synchronize towards heads with as much backing as the user asked for
+    c.head_attestation_policy = AttestationPolicy {
+        min_publishers: opts.min_head_attesters,
+        min_stake: opts.min_head_stake.into(),
+        diversity: diversity_policy(&opts),
+    };
+
+    // Pick the ledger up from a snapshot if asked to, and publish snapshots as often as asked to
+    c.fast_sync = opts.fast_sync;
+    c.snapshot_interval = opts.snapshot_interval;
+
+    // Only follow the DAG's headers, if asked to
+    c.light = opts.light;
+
+    // Alert the operator's systems to double spends, if they asked
+    if opts.double_spend_webhook != "none" {
+        c.double_spend_webhook = Some(opts.double_spend_webhook.clone());
+    }
+
+    // Notify the operator's systems of anything else that they asked to hear about
+    c.webhooks = WebhookConfig {
+        urls: opts.webhooks.clone(),
+        policy: WebhookPolicy {
+            secret: if opts.webhook_secret_file != "none" {
+                Some(
+                    fs::read_to_string(&opts.webhook_secret_file)?
+                        .trim()
+                        .to_owned(),
+                )
+            } else {
+                None
+            },
+            max_attempts: opts.webhook_max_attempts,
+            initial_backoff_ms: opts.webhook_backoff_ms,
+        },
+        watched: opts
+            .webhook_watched
+            .iter()
+            .map(|addr| address::parse(addr, &opts.network))
+            .collect::<Result<Vec<Address>, _>>()?,
+        behind_by: opts.webhook_behind_by,
+    };
+
+    // If the network is private, refuse to start without a usable key, rather than failing every handshake later
+    c.psk = SwarmKey::read_from_disk_at_data_directory(&opts.data_dir, &opts.network)?;
+    if let Some(key) = &c.psk {
+        info!(
+            "Joining the private network {} with the pre-shared key {}",
+            opts.network,
+            key.fingerprint()
+        );
+    }
+
+    // Listen on, and advertise, the addresses that the user specified
+    c.listen_addrs = opts
+        .listen_addrs
+        .iter()
+        .map(|addr| addr.parse::<Multiaddr>())
+        .collect::<Result<Vec<Multiaddr>, _>>()?;
+    c.external_addrs = opts
+        .external_addrs
+        .iter()
+        .map(|addr| addr.parse::<Multiaddr>())
+        .collect::<Result<Vec<Multiaddr>, _>>()?;
+
+    // Hold outgoing gossip to the limits that the user specified
+    c.publisher = publisher::PublisherConfig {
+        capacity: opts.publish_queue_size,
+        messages_per_sec: opts.publish_rate,
+        overflow: opts.publish_overflow.parse()?,
+    };
+
+    // Unlock any voting accounts whose passwords the secret store is holding onto
+    use_secret_store(&mut c, &opts)?;
+
+    // Hand signing off to a separate process, if the user wants the node to hold no keys
+    use_remote_signer(&mut c, &opts)?;
+
+    if let Ok(mut rt) = c.runtime.write() {
+        // Keep as much of the ledger's history around as the user asked, or as the network's config calls for
+        let mut storage_mode = if opts.archive {
+            StorageMode::Archive
+        } else if opts.storage_mode == "config" {
+            rt.config.storage_mode
+        } else {
+            opts.storage_mode.parse()?
+        };
+        if let StorageMode::Pruned { recent_nodes } = &mut storage_mode {
+            if opts.recent_nodes != 0 {
+                *recent_nodes = opts.recent_nodes;
+            }
+        }
+        rt.ledger.set_storage_mode(storage_mode)?;
+
+        // Don't let peers make us walk an arbitrarily deep unexecuted history
+        rt.ledger.set_resolution_limits(ResolutionLimits {
+            max_depth: opts.max_resolution_depth,
+            max_nodes: opts.max_resolution_nodes,
+        });
+
+        // Hold onto interrupted exports for as long as the user asked
+        rt.exports.ttl_secs = opts.export_ttl;
+
+        // Hold the mempool to the limits that the user specified
+        rt.mempool.max_transactions = opts.mempool_size;
+        rt.mempool.ttl_secs = opts.mempool_ttl;
+        rt.mempool.max_nonce_gap = opts.max_nonce_gap;
+
+        // Only execute proposals voted on by as many distinct peers as the user asked for
+        rt.diversity_policy = diversity_policy(&opts);
+    }
 
     // Convert the client into its string representation
     let c_str: String = (&c).into();
@@ -95,60 +577,121 @@ async fn main() -> Result<(), Error> {
     // Log the initialized client, as well as the network name
     info!("Initiated network client ({}): \n{}", opts.network, c_str);
 
+    // Pin any checkpoints that the user gave us
+    use_checkpoints(&c, &opts)?;
+    use_storage_quota(&c, &opts);
+    use_execution_clock(&c, &opts)?;
+
+    // Make sure that the config, ledger, and database all agree with each other before we touch any of them
+    if let Err(e) = check_consistency(&c, &opts, &network_info) {
+        error!("Refusing to start: {}", e);
+
+        std::process::exit(1);
+    }
+
     // If the user wants to make a genesis state, let's do it.
     if opts.genesis_file != "none" {
         // Construct the genesis state
         use_genesis_file(&mut c, &opts.genesis_file, &opts.network)?;
     }
 
+    // If the user wants to export the node's history, do that instead of starting the node
+    if opts.export_chain != "none" {
+        return export_chain(&c, &opts);
+    }
+
+    // If the user gave us a history to start from, import it
+    if opts.import_chain != "none" {
+        import_chain(&c, &opts)?;
+
+        // Make sure that the imported history is the one that the user expected
+        if let Err(e) = check_consistency(&c, &opts, &network_info) {
+            error!("Refusing to start: {}", e);
+
+            std::process::exit(1);
+        }
+    }
+
     // If the user wants to activate the SummerCash RPC API, let's do it.
     if !opts.disable_api {
-        // Initialize a server for the RPC API
-        let mut io = IoHandler::new();
-
-        // Register the accounts API
-        AccountsImpl::register(&mut io, c.runtime.clone());
-        DagImpl::register(&mut io, c.runtime.clone());
-
-        // Create an HTTP server for the RPC API
-        let server = ServerBuilder::new(io)
-            .start_http(
-                &format!("0.0.0.0:{}", opts.api_port)
-                    .parse()
-                    .expect("Node was unable to parsed the given API port."),
-            )
-            .expect("Node was unable to create an HTTP server for the SummerCash RPC API.");
+        // Only the admin accounts that the user named may open admin sessions
+        let sessions = Arc::new(RwLock::new(SessionTable::new(
+            opts.admin_accounts
+                .iter()
+                .map(|addr| address::parse(addr, &opts.network))
+                .collect::<Result<_, _>>()?,
+        )));
+        if opts.admin_accounts.is_empty() {
+            warn!("No admin accounts were given; the admin RPC methods are open to anyone who can reach the API");
+        }
 
-        info!(
-            "Starting an HTTP server for the SummerCash RPC API on port {}",
-            opts.api_port
-        );
+        // Each of the tenants is confined to a namespace of its own
+        let tenants = Arc::new(TenantTable::parse(&opts.data_dir, &opts.tenants)?);
+        if tenants.is_enforced() && opts.admin_accounts.is_empty() {
+            warn!("No admin accounts were given; the node's own keystore is unreachable through the RPC API while it serves tenants");
+        }
+
+        // Serve the full API where the user asked
+        serve_rpc(
+            rpc_handler(&c, &opts, ReadOnlyGate::open(), &sessions, &tenants),
+            &opts.api_host,
+            opts.api_port,
+            "RPC API",
+        )?;
+
+        // Serve the read-only API alongside it, if the user asked for one
+        if opts.read_api_port != 0 {
+            if !is_loopback(&opts.api_host) {
+                warn!(
+                    "The full RPC API is served on {}, not just the read-only API; consider --rpc-host 127.0.0.1",
+                    opts.api_host
+                );
+            }
+
+            serve_rpc(
+                rpc_handler(&c, &opts, ReadOnlyGate::read_only(), &sessions, &tenants),
+                &opts.read_api_host,
+                opts.read_api_port,
+                "read-only RPC API",
+            )?;
+        }
+
+        // Push events to subscribers over WebSocket, if the user asked
+        if opts.ws_api_port != 0 {
+            let mut io = rpc_handler(&c, &opts, ReadOnlyGate::read_only(), &sessions, &tenants);
+            SubscriptionsImpl::register(&mut io, c.runtime.clone());
 
-        // Start listening in a different thread
-        thread::spawn(move || server.wait());
+            serve_ws(io, &opts.ws_api_host, opts.ws_api_port, "WebSocket RPC API")?;
+        }
     }
 
     // Get a reference to the client's runtime so that we can stop all operations on ^c if necessary
-    let runtime_ctx: Arc<RwLock<System>> = c.runtime.clone();
+    let runtime_ctx: Arc<InstrumentedRwLock<System>> = c.runtime.clone();
     let persistence_dir = opts.data_dir.clone();
 
     ctrlc::set_handler(move || {
         // Get a runtime that we can actually close
         if let Ok(rt) = runtime_ctx.write() {
-            // Save everything up
-            rt.config
-                .write_to_disk_at_data_directory(&persistence_dir)
-                .expect("Error writing the runtime config to the disk");
-            rt.ledger
-                .write_to_disk()
-                .expect("Error writing the ledger to the disk");
+            // Save everything up, if the disk still takes writes
+            let mut saved = true;
+            if let Err(e) = rt.config.write_to_disk_at_data_directory(&persistence_dir) {
+                error!("Error writing the runtime config to the disk: {}", e);
+                saved = false;
+            }
+            if let Err(e) = rt.ledger.write_to_disk() {
+                error!("Error writing the ledger to the disk: {}", e);
+                saved = false;
+            }
 
             // Stop running
-            std::process::exit(0);
+            std::process::exit(if saved { 0 } else { 1 });
         }
     })
     .expect("Error setting Ctrl-C handler");
 
+    // Keep an eye out for releases of the node software, if the user asked
+    use_updater(&c, &opts)?;
+
     // Start the client
     c.start(bootstrap_nodes, opts.node_port).await?;
 
@@ -156,9 +699,185 @@ async fn main() -> Result<(), Error> {
     Ok(())
 }
 
+/// Builds an RPC handler serving each of the node's APIs, guarded by the given gate.
+///
+/// # Arguments
+///
+/// * `client` - The client whose runtime the APIs serve
+/// * `opts` - The options that the node was started with
+/// * `gate` - Whether or not the handler only serves read-only methods
+/// * `sessions` - The node's admin challenges and sessions
+/// * `tenants` - Each of the node's tenants
+fn rpc_handler(
+    client: &Client,
+    opts: &Opts,
+    gate: ReadOnlyGate,
+    sessions: &Arc<RwLock<SessionTable>>,
+    tenants: &Arc<TenantTable>,
+) -> RpcHandler {
+    // Refuse writes while the node's disk isn't taking them
+    let gate = match client.runtime.read() {
+        Ok(rt) => gate.watching_disk(rt.storage.read_only_flag()),
+        Err(_) => gate,
+    };
+
+    let mut io = RpcHandler::with_middleware((
+        gate,
+        TenantGate {
+            tenants: tenants.clone(),
+            sessions: sessions.clone(),
+        },
+        AdminGate {
+            sessions: sessions.clone(),
+        },
+        BatchLimit::new(opts.rpc_batch_limit),
+    ));
+
+    // Register each of the APIs
+    AuthImpl::register(&mut io, sessions.clone());
+    AccountsImpl::register(&mut io, client.runtime.clone());
+    DagImpl::register(&mut io, client.runtime.clone());
+    RuntimeImpl::register(&mut io, client.runtime.clone());
+    NotesImpl::register(&mut io, client.runtime.clone());
+    ContactsImpl::register(&mut io);
+    GuardiansImpl::register(&mut io, client.runtime.clone());
+    PeersImpl::register(
+        &mut io,
+        client.sync_peers.clone(),
+        client.connections.clone(),
+        client.runtime.clone(),
+    );
+    VestingImpl::register(&mut io, client.runtime.clone());
+    RewardsImpl::register(&mut io, client.runtime.clone());
+    EventsImpl::register(&mut io, client.runtime.clone());
+
+    io
+}
+
+/// Starts an HTTP server for the given RPC handler in a thread of its own.
+///
+/// # Arguments
+///
+/// * `io` - The handler to serve
+/// * `host` - The interface to listen on
+/// * `port` - The port to listen on
+/// * `name` - What to call the API in the log
+fn serve_rpc(io: RpcHandler, host: &str, port: u16, name: &str) -> Result<(), Error> {
+    let addr = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+
+    // Create an HTTP server for the RPC API
+    let server = ServerBuilder::with_meta_extractor(io, |req: &hyper::Request<hyper::Body>| {
+        // Pick up the admin session or API key that the request was made with, if any
+        auth::Session::from_authorization_header(
+            req.headers()
+                .get(hyper::header::AUTHORIZATION)
+                .and_then(|header| header.to_str().ok()),
+        )
+    })
+    .start_http(&addr.parse()?)?;
+
+    info!(
+        "Starting an HTTP server for the SummerCash {} on {}",
+        name, addr
+    );
+
+    // Start listening in a different thread
+    thread::spawn(move || server.wait());
+
+    Ok(())
+}
+
+/// Starts a WebSocket server for the given RPC handler in a thread of its own. Requests made over it carry no
+/// Authorization header, so only the methods open to anyone are reachable.
+///
+/// # Arguments
+///
+/// * `io` - The handler to serve
+/// * `host` - The interface to listen on
+/// * `port` - The port to listen on
+/// * `name` - What to call the API in the log
+fn serve_ws(io: RpcHandler, host: &str, port: u16, name: &str) -> Result<(), Error> {
+    let addr = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+
+    // Create a WebSocket server for the RPC API, pushing subscription notifications down each connection
+    let server =
+        jsonrpc_ws_server::ServerBuilder::with_meta_extractor(io, |ctx: &RequestContext| {
+            auth::Session {
+                token: None,
+                tenant: None,
+                pubsub: Some(Arc::new(PubSubSession::new(ctx.sender()))),
+            }
+        })
+        .start(&addr.parse()?)?;
+
+    info!(
+        "Starting a WebSocket server for the SummerCash {} on {}",
+        name, addr
+    );
+
+    // Start listening in a different thread
+    thread::spawn(move || server.wait());
+
+    Ok(())
+}
+
+/// Checks whether or not the given interface is only reachable from the local machine.
+///
+/// # Arguments
+///
+/// * `host` - The interface
+fn is_loopback(host: &str) -> bool {
+    host == "localhost"
+        || host
+            .parse::<std::net::IpAddr>()
+            .map_or(false, |ip| ip.is_loopback())
+}
+
+/// Gets the network that the node was started for from the network registry, downloading any registry that the user
+/// pointed us to first.
+async fn use_network_registry(opts: &Opts) -> Result<NetworkInfo, Error> {
+    if opts.network_registry_url != "none" {
+        let downloaded = NetworkRegistry::download(&opts.network_registry_url).await?;
+        info!(
+            "Downloaded {} network(s) from {}",
+            downloaded.networks().len(),
+            opts.network_registry_url
+        );
+
+        // Keep the downloaded networks alongside any that the user added by hand
+        let mut overrides =
+            NetworkRegistry::read_overrides_from_disk_at_data_directory(&opts.data_dir)?;
+        overrides.merge(downloaded);
+        overrides.write_to_disk_at_data_directory(&opts.data_dir)?;
+    }
+
+    let registry = NetworkRegistry::read_from_disk_at_data_directory(&opts.data_dir)?;
+
+    Ok(match registry.get(&opts.network) {
+        Some(info) => info.clone(),
+        None => {
+            warn!(
+                "The {} network isn't in the network registry ({}); joining it without any bootstrap peers",
+                opts.network,
+                NetworkRegistry::path(&opts.data_dir)
+            );
+
+            NetworkInfo::unlisted(&opts.network)
+        }
+    })
+}
+
 /// Gets the network bootstrap peers from the configuration struct.
 fn use_bootstrap_peers(
-    network: &str,
+    network: &NetworkInfo,
     opts: Opts,
 ) -> Result<(Vec<(PeerId, Multiaddr)>, Opts), Error> {
     // If the user has provided a custom bootstrap peer, use that.
@@ -174,14 +893,274 @@ fn use_bootstrap_peers(
         // The user has explicitly requested that they not connect to any bootstrap peers. Follow this wish.
         Ok((vec![], opts))
     } else {
-        // Otherwise, just use the hard-coded bootstrap node for the active network
+        // Otherwise, just use the bootstrap nodes listed in the registry for the active network
         Ok((
-            peers::get_network_bootstrap_peers(network::Network::from(network)),
+            network
+                .resolve_bootstrap_peers()
+                .map_err(|e| failure::err_msg(format!("network {}: {}", network.name, e)))?,
             opts,
         ))
     }
 }
 
+/// Gets the genesis that the ledger must descend from: the one that the operator pinned, if they pinned one, or the
+/// one listed in the registry otherwise.
+fn expected_genesis(opts: &Opts, network: &NetworkInfo) -> Option<Hash> {
+    if opts.expected_genesis != "none" {
+        Some(Hash::from(opts.expected_genesis.as_str()))
+    } else {
+        network.genesis_hash
+    }
+}
+
+/// Checks that the client's config, ledger, and database all belong to the network that the node was started for.
+fn check_consistency(client: &Client, opts: &Opts, network: &NetworkInfo) -> Result<(), Error> {
+    if let Ok(rt) = client.runtime.read() {
+        consistency::check(
+            &rt,
+            &opts.network,
+            &opts.data_dir,
+            expected_genesis(opts, network),
+        )?;
+    }
+
+    // The node votes with the keys in its keystore, so they mustn't be shared with another network's node
+    isolation::bind_keystore(&opts.data_dir, &opts.network)?;
+
+    Ok(())
+}
+
+/// Runs each of the node's self-tests against the options that it would be started with, and prints a report of
+/// their findings. Results in an error if the node can't be started as it is.
+fn run_doctor(
+    opts: &Opts,
+    network: &NetworkInfo,
+    bootstrap_nodes: &[(PeerId, Multiaddr)],
+) -> Result<(), Error> {
+    let mut report = Report::default();
+
+    report.push(doctor::check_bootstrap_peers(
+        bootstrap_nodes,
+        Duration::from_secs(doctor::DEFAULT_CONNECT_TIMEOUT_SECS),
+    ));
+
+    // Only the APIs that would be served need free ports
+    if !opts.disable_api {
+        report.push(doctor::check_port("RPC API", &opts.api_host, opts.api_port));
+
+        if opts.read_api_port != 0 {
+            report.push(doctor::check_port(
+                "read-only RPC API",
+                &opts.read_api_host,
+                opts.read_api_port,
+            ));
+        }
+        if opts.ws_api_port != 0 {
+            report.push(doctor::check_port(
+                "WebSocket RPC API",
+                &opts.ws_api_host,
+                opts.ws_api_port,
+            ));
+        }
+    }
+
+    report.push(doctor::check_disk_space(
+        &opts.data_dir,
+        StorageQuota {
+            max_db_bytes: opts.max_db_bytes,
+            low_free_bytes: opts.low_free_bytes,
+            min_free_bytes: opts.min_free_bytes,
+            ..Default::default()
+        },
+    ));
+    report.push(doctor::check_keystore(&opts.data_dir));
+
+    // The database is opened just as it would be on startup
+    match Client::new(opts.network.clone().into(), &opts.data_dir) {
+        Ok(c) => {
+            if let Ok(rt) = c.runtime.read() {
+                report.push(doctor::check_ledger(&rt.ledger));
+                report.push(doctor::check_consistency(
+                    &rt,
+                    &opts.network,
+                    &opts.data_dir,
+                    expected_genesis(opts, network),
+                ));
+            }
+        }
+        Err(e) => report.push(Finding::fail(
+            "database",
+            format!("the node's data couldn't be opened: {}", e),
+            "make sure that no other smcd is running with this --data-dir; if none is, move the ledger's db directory \
+             aside, and let the node resynchronize",
+        )),
+    }
+
+    println!("{}", report);
+
+    if report.is_healthy() {
+        Ok(())
+    } else {
+        Err(failure::err_msg(format!(
+            "{} of the node's self-tests failed",
+            report.count(doctor::Status::Fail)
+        )))
+    }
+}
+
+/// Pins each of the checkpoints given on the command line, for as long as the node runs.
+fn use_checkpoints(client: &Client, opts: &Opts) -> Result<(), Error> {
+    if let Ok(mut rt) = client.runtime.write() {
+        for checkpoint in &opts.checkpoints {
+            rt.checkpoints.pin(checkpoint.parse::<Checkpoint>()?);
+        }
+
+        if !rt.checkpoints.checkpoints.is_empty() {
+            info!(
+                "Pinned {} checkpoint(s); conflicting history will be refused",
+                rt.checkpoints.checkpoints.len()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets how many distinct peers, and subnets, the user asked for before the node acts on votes or attestations.
+fn diversity_policy(opts: &Opts) -> DiversityPolicy {
+    DiversityPolicy {
+        min_peers: opts.min_distinct_peers,
+        min_subnets: opts.min_distinct_subnets,
+    }
+}
+
+/// Holds the node's database to the quota given on the command line, measuring it straight away.
+fn use_storage_quota(client: &Client, opts: &Opts) {
+    if let Ok(mut rt) = client.runtime.write() {
+        rt.storage.quota = StorageQuota {
+            max_db_bytes: opts.max_db_bytes,
+            low_free_bytes: opts.low_free_bytes,
+            min_free_bytes: opts.min_free_bytes,
+            ..Default::default()
+        };
+        rt.check_storage();
+    }
+}
+
+/// Timestamps executions the way that the user asked.
+fn use_execution_clock(client: &Client, opts: &Opts) -> Result<(), Error> {
+    let clock: ExecutionClock = opts.execution_timestamps.parse()?;
+
+    if let Ok(mut rt) = client.runtime.write() {
+        rt.execution_clock = clock;
+    }
+
+    Ok(())
+}
+
+/// Checks for releases of the node software in the background, if the user gave us a release manifest to watch.
+fn use_updater(client: &Client, opts: &Opts) -> Result<(), Error> {
+    if opts.update_manifest_url == "none" {
+        return Ok(());
+    }
+
+    // Only trust releases signed by the account that the user named
+    if opts.update_signer == "none" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--update-manifest-url requires --update-signer",
+        )
+        .into());
+    }
+
+    let updater = Updater::new(
+        UpdaterConfig {
+            manifest_url: opts.update_manifest_url.clone(),
+            signer: address::parse(&opts.update_signer, &opts.network)?,
+            interval_secs: opts.update_check_interval,
+            download_dir: if opts.update_download_dir != "none" {
+                Some(opts.update_download_dir.clone())
+            } else {
+                None
+            },
+        },
+        client.sync_peers.clone(),
+    );
+
+    info!(
+        "Checking {} for releases of the node software every {} second(s)",
+        opts.update_manifest_url, opts.update_check_interval
+    );
+
+    tokio::spawn(updater.run());
+
+    Ok(())
+}
+
+/// Adds each of the locked accounts whose password is held by the secret store that the user chose to the client's
+/// voting accounts.
+fn use_secret_store(client: &mut Client, opts: &Opts) -> Result<(), Error> {
+    if let Some(store) = secrets::open(&opts.secret_store)? {
+        let unlocked = secrets::unlock_accounts(&*store, &opts.data_dir);
+
+        info!(
+            "Unlocked {} voting account(s) with the {} secret store",
+            unlocked.len(),
+            opts.secret_store
+        );
+
+        client
+            .voting_accounts
+            .get_or_insert_with(Vec::new)
+            .extend(unlocked);
+    }
+
+    Ok(())
+}
+
+/// Delegates all signing to the remote signer that the user chose, making sure that it can be reached first.
+fn use_remote_signer(client: &mut Client, opts: &Opts) -> Result<(), Error> {
+    if opts.remote_signer == "none" {
+        return Ok(());
+    }
+
+    // The signer keeps its token next to its socket, unless the user has copied it somewhere else
+    let token_file = if opts.remote_signer_token == "none" {
+        std::path::Path::new(&opts.remote_signer)
+            .with_file_name(signer::TOKEN_FILE)
+            .to_string_lossy()
+            .into_owned()
+    } else {
+        opts.remote_signer_token.clone()
+    };
+
+    let remote_signer = RemoteSigner::open(&opts.remote_signer, &token_file)?;
+    let accounts = remote_signer.list_accounts()?;
+
+    info!(
+        "Delegating signing to the remote signer at {}, holding {} account(s)",
+        opts.remote_signer,
+        accounts.len()
+    );
+
+    // Keys held by the node itself would defeat the purpose of the signer, so let go of them
+    if let Some(local) = client.voting_accounts.replace(Vec::new()) {
+        if !local.is_empty() {
+            warn!(
+                "Not voting with the {} account(s) unlocked in the node's own keystore; consider moving them to the \
+                 signer",
+                local.len()
+            );
+        }
+    }
+
+    if let Ok(mut rt) = client.runtime.write() {
+        rt.remote_signer = Some(remote_signer);
+    }
+
+    Ok(())
+}
+
 /// Constructs a new genesis for the network, considering a given genesis file.
 fn use_genesis_file(client: &mut Client, file: &str, network: &str) -> Result<(), Error> {
     // Log the pending gen op
@@ -193,10 +1172,60 @@ fn use_genesis_file(client: &mut Client, file: &str, network: &str) -> Result<()
     // Make the genesis state for the network
     client.construct_genesis(Config::read_from_file(file)?)?;
 
+    // Remember the genesis, so that the ledger can't be mixed with another network's history later on
+    if let Ok(rt) = client.runtime.read() {
+        consistency::stamp(&rt.ledger, network)?;
+    }
+
     // All done!
     Ok(())
 }
 
+/// Writes the client's entire history to the chain file that the user named.
+fn export_chain(client: &Client, opts: &Opts) -> Result<(), Error> {
+    let mut file = io::BufWriter::new(fs::File::create(&opts.export_chain)?);
+
+    if let Ok(rt) = client.runtime.read() {
+        let header = chainfile::export(&rt.ledger, &opts.network, &mut file)?;
+
+        info!(
+            "Exported {} transactions descending from {} to {}",
+            header.transactions, header.genesis, opts.export_chain
+        );
+    }
+
+    Ok(())
+}
+
+/// Imports the history held in the chain file that the user named into the client's empty ledger.
+fn import_chain(client: &Client, opts: &Opts) -> Result<(), Error> {
+    let chain = ChainReader::open(io::BufReader::new(fs::File::open(&opts.import_chain)?))?;
+
+    // Don't mix another network's history into this one
+    if chain.header.network != opts.network {
+        return Err(failure::err_msg(format!(
+            "the chain file belongs to the {} network, not {}",
+            chain.header.network, opts.network
+        )));
+    }
+
+    info!(
+        "Importing {} transactions from {}; this may take a while",
+        chain.header.transactions, opts.import_chain
+    );
+
+    if let Ok(mut rt) = client.runtime.write() {
+        let imported = rt.import_chain(chain)?;
+
+        // Remember the genesis, so that the ledger can't be mixed with another network's history later on
+        consistency::stamp(&rt.ledger, &opts.network)?;
+
+        info!("Imported {} transactions", imported);
+    }
+
+    Ok(())
+}
+
 /// Applies the given options.
 fn use_options(mut opts: Opts) -> Result<Opts, Error> {
     // Configure the logger
@@ -6,13 +6,53 @@ extern crate log;
 use clap::Clap;
 
 use summercash::{
-    cmd::commands::*,
-    crypto::hash::Hash,
-    p2p::rpc::{accounts, dag, runtime},
+    accounts::{
+        account,
+        backup::{BackupArchive, BackupContents},
+        contacts::AddressBook,
+        kdf::KdfParams,
+        mnemonic::DerivationPath,
+        resubmission::{
+            Resubmission, ResubmissionAction, ResubmissionOutcome, ResubmissionPolicy,
+            ResubmissionStrategy, POLL_INTERVAL_SECS,
+        },
+        secrets,
+    },
+    cmd::{
+        commands::*,
+        consolidate,
+        exit::{ErrorReport, OutputFormat},
+        preflight,
+        progress::Progress,
+    },
+    common::{
+        address::{self, Address, TYPED_ADDRESS_SEPARATOR},
+        isolation::{self, IsolationError},
+    },
+    core::{
+        sys::{history::ExecutionOutcome, journal::VoteOrigin, maintenance},
+        types::{light, transaction},
+    },
+    crypto::{hash::Hash, short_id},
+    p2p::{
+        reconnect::Connectivity,
+        rpc::{
+            accounts, auth, client::ClientError, contacts, dag, error, events,
+            failover::RetryPolicy, guardians, notes, peers, rewards, runtime, vesting,
+        },
+    },
 };
 
+use chrono::Utc;
 use console::Emoji;
-use std::clone::Clone;
+use failure::Fail;
+use num::ToPrimitive;
+use std::{
+    clone::Clone,
+    fs,
+    io::{self, Write},
+    time::Duration,
+};
 
 /// The SummerCash command-line interface.
 #[derive(Clap)]
@@ -30,7 +70,18 @@ struct Opts {
     #[clap(long = "data-dir", default_value = "data")]
     data_dir: String,
 
-    /// Signals to the SummerCash command-line utility that it should connect to the given node.
+    /// Refuses to act on a data directory, node, or typed address belonging to any network other than the given one,
+    /// and binds newly created accounts' keystore to it.
+    #[clap(long = "network")]
+    network: Option<String>,
+
+    /// The format that errors are printed in: "text", or "json" for a stable {"error": {"code", "kind", "message"}}
+    /// object printed to stderr. Either way, the process exits with a stable code telling why the command failed.
+    #[clap(long = "output", default_value = "text")]
+    output: String,
+
+    /// Signals to the SummerCash command-line utility that it should connect to the given node. A comma-separated
+    /// list of nodes may be given, in which case requests fail over to the next node whenever one is unreachable.
     #[clap(
         short = "r",
         long = "remote-host-url",
@@ -38,6 +89,32 @@ struct Opts {
     )]
     rpc_host_url: String,
 
+    /// The number of times to try each of the nodes before giving up on a request.
+    #[clap(long = "rpc-attempts", default_value = "3")]
+    rpc_attempts: u32,
+
+    /// How long to wait, in milliseconds, before trying the nodes again; the wait doubles after each attempt.
+    #[clap(long = "rpc-backoff-ms", default_value = "250")]
+    rpc_backoff_ms: u64,
+
+    /// How hard to try to reach the nodes, built from the two options above
+    #[clap(skip)]
+    retry_policy: RetryPolicy,
+
+    /// Proves to the node that the user holds the given local account, and makes any admin requests under the
+    /// resulting session.
+    #[clap(long = "admin-account", default_value = "none")]
+    admin_account: String,
+
+    /// Makes each request with the given API key, confining it to the key's namespace on a node hosting several
+    /// applications. Ignored if an admin account is given.
+    #[clap(long = "api-key", default_value = "none")]
+    api_key: String,
+
+    /// The token of the admin session opened with the admin account, or the API key, if any
+    #[clap(skip)]
+    session: Option<String>,
+
     #[clap(subcommand)]
     subcmd: SubCommand,
 }
@@ -75,12 +152,61 @@ enum SubCommand {
     /// Publishes a SummerCash object of a given type using a provided hash.
     #[clap(name = "publish")]
     Publish(Publish),
+
+    /// Merges the balances of several local accounts (e.g. auto-generated voting accounts) into a single account.
+    #[clap(name = "consolidate")]
+    Consolidate(Consolidate),
+
+    /// Cleans up test databases, stale lock files, and (optionally) orphaned keystore entries in the data directory.
+    #[clap(name = "clean")]
+    Clean(Clean),
+
+    /// Runs a diagnostic of a given type against the node.
+    #[clap(name = "debug")]
+    Debug(Debug),
+
+    /// Exports or imports a password-protected backup of the wallet in the data directory.
+    #[clap(name = "backup")]
+    Backup(Backup),
+
+    /// Exports the node's data of a given type to a file.
+    #[clap(name = "export")]
+    Export(Export),
 }
 
 #[tokio::main]
-async fn main() -> Result<(), failure::Error> {
+async fn main() {
+    let opts = Opts::parse();
+
+    // Work out how to report a failure before anything can fail, so that scripts get the shape they asked for
+    let format = opts.output.parse().unwrap_or(OutputFormat::Text);
+
+    if let Err(e) = run(opts).await {
+        let report = ErrorReport::from(&e);
+
+        match format {
+            OutputFormat::Json => eprintln!("{}", report.to_json()),
+            OutputFormat::Text => error!("{}", report.message),
+        }
+
+        std::process::exit(report.code);
+    }
+}
+
+/// Runs the command that the user asked for.
+async fn run(opts: Opts) -> Result<(), failure::Error> {
     // Get the options that the user passed to the program
-    let opts: Opts = use_options(Opts::parse())?;
+    let mut opts: Opts = use_options(opts)?;
+
+    // Open an admin session, if the user wants one, or use their API key
+    if opts.admin_account != "none" {
+        opts.session = Some(open_admin_session(&opts).await?);
+    } else if opts.api_key != "none" {
+        opts.session = Some(opts.api_key.clone());
+    }
+
+    // Make sure that nothing we're about to touch belongs to another network
+    check_network(&opts).await?;
 
     match opts.subcmd.clone() {
         SubCommand::Create(c) => create(opts, c).await,
@@ -91,15 +217,51 @@ async fn main() -> Result<(), failure::Error> {
         SubCommand::List(l) => list(opts, l).await,
         SubCommand::Sign(s) => sign(opts, s).await,
         SubCommand::Publish(p) => publish(opts, p).await,
+        SubCommand::Consolidate(c) => consolidate(opts, c).await,
+        SubCommand::Clean(c) => clean(opts, c).await,
+        SubCommand::Debug(d) => debug(opts, d).await,
+        SubCommand::Backup(b) => backup(opts, b).await,
+        SubCommand::Export(e) => export(opts, e).await,
     }
 }
 
 /// Creates the object from the given options.
 async fn create(opts: Opts, c: Create) -> Result<(), failure::Error> {
     match c {
-        Create::Account => {
+        Create::Account(new_account) if new_account.mnemonic => {
+            let path = match &new_account.derivation_path {
+                Some(path) => path.parse()?,
+                None => DerivationPath::for_account(new_account.index),
+            };
+
+            // Generate the account here, so that its phrase never leaves this machine
+            let acc =
+                account::Account::with_mnemonic(new_account.words, &new_account.passphrase, &path)?;
+            acc.write_to_disk_at_data_directory(&opts.data_dir)?;
+
+            info!(
+                "{}Successfully generated account: {}",
+                Emoji::new("💳 ", ""),
+                acc
+            );
+            warn!(
+                "Write down the following phrase, and keep it somewhere safe. Anyone holding it can spend from the \
+                 account, which can be recovered from it (along with its passphrase, if any) using `smcli create \
+                 recovered-account` and the derivation path {}:",
+                path
+            );
+            println!("{}", acc.mnemonic().unwrap_or_default());
+
+            // Keep the keystore from being used with any other network from now on
+            if let Some(network) = &opts.network {
+                isolation::bind_keystore(&opts.data_dir, network)?;
+            }
+        }
+        Create::Account(_) => {
             // Make a client for the accounts API
-            let client = accounts::Client::new(&opts.rpc_host_url);
+            let client = accounts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
 
             // Generate the account
             match client.generate(&opts.data_dir).await {
@@ -108,20 +270,63 @@ async fn create(opts: Opts, c: Create) -> Result<(), failure::Error> {
                     Emoji::new("💳 ", ""),
                     acc
                 ),
-                Err(e) => error!("Failed to generate account: {}", e),
+                Err(e) => return Err(e.context("Failed to generate account").into()),
+            }
+
+            // Keep the keystore from being used with any other network from now on
+            if let Some(network) = &opts.network {
+                isolation::bind_keystore(&opts.data_dir, network)?;
+            }
+        }
+        Create::RecoveredAccount(recovery) => {
+            let paths = match &recovery.derivation_path {
+                Some(path) => vec![path.parse::<DerivationPath>()?],
+                None => (recovery.index..recovery.index.saturating_add(recovery.count))
+                    .map(DerivationPath::for_account)
+                    .collect(),
+            };
+
+            for path in paths {
+                let acc =
+                    account::Account::from_mnemonic(&recovery.phrase, &recovery.passphrase, &path)?;
+                acc.write_to_disk_at_data_directory(&opts.data_dir)?;
+
+                info!(
+                    "{}Recovered account {} (at {})",
+                    Emoji::new("🔑 ", ""),
+                    acc,
+                    path
+                );
+            }
+
+            // Keep the keystore from being used with any other network from now on
+            if let Some(network) = &opts.network {
+                isolation::bind_keystore(&opts.data_dir, network)?;
             }
         }
         Create::Transaction(transaction) => {
             // Make a client for the DAG API
-            let client = dag::Client::new(&opts.rpc_host_url);
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            // Pay contacts by name
+            let recipient = resolve(
+                &opts,
+                &load_address_book(&opts).await?,
+                &transaction.recipient,
+            )
+            .await?;
 
             // Generate the account
             match client
                 .create_tx(
                     transaction.sender,
-                    transaction.recipient,
+                    recipient.to_str(),
                     transaction.amount,
                     transaction.payload,
+                    transaction.gas_price,
+                    transaction.gas_limit,
                 )
                 .await
             {
@@ -134,7 +339,261 @@ async fn create(opts: Opts, c: Create) -> Result<(), failure::Error> {
                         serde_json::to_string_pretty(&tx)?
                     );
                 }
-                Err(e) => error!("Failed to create transaction: {}", e),
+                Err(e) => return Err(e.context("Failed to create transaction").into()),
+            }
+        }
+        Create::BatchTransaction(batch) => {
+            // Make a client for the DAG API
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            // Collect the payments from the command line, and from the CSV file, if there is one, paying contacts by
+            // name
+            let book = load_address_book(&opts).await?;
+            let mut transfers = Vec::new();
+            for (recipient, value) in read_transfers(&batch)? {
+                transfers.push((resolve(&opts, &book, &recipient).await?.to_str(), value));
+            }
+            let n_transfers = transfers.len();
+
+            match client.create_batch_tx(batch.sender, transfers).await {
+                Ok(tx) => {
+                    // Persist the tx first
+                    tx.to_disk_at_data_directory(&opts.data_dir)?;
+
+                    info!(
+                        "{}Successfully created batch transaction paying {} recipients (use publish command to add to DAG): {}",
+                        Emoji::new("📋 ", ""),
+                        n_transfers,
+                        serde_json::to_string_pretty(&tx)?
+                    );
+                }
+                Err(e) => return Err(e.context("Failed to create batch transaction").into()),
+            }
+        }
+        Create::Sweep(sweep) => {
+            // Make a client for the DAG API
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            // Sweep into a contact's account by name
+            let destination =
+                resolve(&opts, &load_address_book(&opts).await?, &sweep.destination).await?;
+
+            // Generate the sweeping transactions
+            match client.sweep(sweep.sources, destination.to_str()).await {
+                Ok(txs) => {
+                    // Persist each of the txs, so that they can be signed (potentially offline) and published
+                    for tx in &txs {
+                        tx.to_disk_at_data_directory(&opts.data_dir)?;
+                    }
+
+                    info!(
+                        "{}Successfully created {} sweeping transactions (sign and publish each to add to DAG): {}",
+                        Emoji::new("🧹 ", ""),
+                        txs.len(),
+                        serde_json::to_string_pretty(
+                            &txs.iter().map(|tx| tx.hash).collect::<Vec<Hash>>()
+                        )?
+                    );
+                }
+                Err(e) => return Err(e.context("Failed to create sweeping transactions").into()),
+            }
+        }
+        Create::LockedTransaction(locked) => {
+            // Make a client for the time-locked balance API
+            let client = vesting::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            // Pay contacts by name
+            let recipient =
+                resolve(&opts, &load_address_book(&opts).await?, &locked.recipient).await?;
+
+            match client
+                .create_locked_tx(
+                    locked.sender,
+                    recipient.to_str(),
+                    locked.amount,
+                    locked.unlocks_at,
+                    locked.milestone,
+                )
+                .await
+            {
+                Ok(tx) => {
+                    // Persist the tx first
+                    tx.to_disk_at_data_directory(&opts.data_dir)?;
+
+                    info!(
+                        "{}Successfully created locked transaction (use publish command to add to DAG): {}",
+                        Emoji::new("🔒 ", ""),
+                        serde_json::to_string_pretty(&tx)?
+                    );
+                }
+                Err(e) => return Err(e.context("Failed to create locked transaction").into()),
+            }
+        }
+        Create::Note(note) => {
+            // Make a client for the notes API
+            let client = notes::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            // Attach the note
+            match client
+                .set(
+                    resolve_hash(&opts, &note.subject).await?,
+                    note.label,
+                    note.memo,
+                    &opts.data_dir,
+                )
+                .await
+            {
+                Ok(n) => info!(
+                    "{}Labeled '{}' as '{}'",
+                    Emoji::new("🏷️ ", ""),
+                    note.subject,
+                    n.label
+                ),
+                Err(e) => return Err(e.context("Failed to attach note").into()),
+            }
+        }
+        Create::Contact(contact) => {
+            // Make a client for the address book API
+            let client = contacts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client
+                .add(
+                    contact.name.clone(),
+                    contact.address,
+                    contact.memo,
+                    &opts.data_dir,
+                )
+                .await
+            {
+                Ok(c) => info!(
+                    "{}Added '{}' to the address book as {}",
+                    Emoji::new("📇 ", ""),
+                    contact.name,
+                    c.address
+                ),
+                Err(e) => return Err(e.context("Failed to add contact").into()),
+            }
+        }
+        Create::Guardians(set) => {
+            // Make a client for the account recovery API
+            let client = guardians::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            persist_recovery_tx(
+                &opts.data_dir,
+                client
+                    .register_guardians(
+                        set.account,
+                        set.guardians,
+                        set.threshold,
+                        set.timelock_secs,
+                    )
+                    .await,
+                "register guardians",
+            )?;
+        }
+        Create::Recovery(recovery) => {
+            // Make a client for the account recovery API
+            let client = guardians::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            persist_recovery_tx(
+                &opts.data_dir,
+                client
+                    .initiate_recovery(recovery.guardian, recovery.account, recovery.new_owner)
+                    .await,
+                "initiate recovery",
+            )?;
+        }
+        Create::RecoveryApproval(recovery) => {
+            // Make a client for the account recovery API
+            let client = guardians::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            persist_recovery_tx(
+                &opts.data_dir,
+                client
+                    .approve_recovery(recovery.guardian, recovery.account, recovery.new_owner)
+                    .await,
+                "approve recovery",
+            )?;
+        }
+        Create::RecoveryExecution(execution) => {
+            // Make a client for the account recovery API
+            let client = guardians::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            persist_recovery_tx(
+                &opts.data_dir,
+                client
+                    .execute_recovery(execution.guardian, execution.account)
+                    .await,
+                "execute recovery",
+            )?;
+        }
+        Create::RewardsClaim(acc) => {
+            // Make a client for the validator rewards API
+            let client = rewards::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            persist_recovery_tx(
+                &opts.data_dir,
+                client.claim_rewards(acc.address).await,
+                "claim rewards",
+            )?;
+        }
+        Create::Checkpoint(checkpoint) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client
+                .pin_checkpoint(
+                    checkpoint.index,
+                    checkpoint.hash.clone(),
+                    opts.data_dir.clone(),
+                )
+                .await
+            {
+                Ok(_) => info!(
+                    "{}Pinned checkpoint {} at index {}",
+                    Emoji::new("📌 ", ""),
+                    checkpoint.hash,
+                    checkpoint.index
+                ),
+                Err(e) => return Err(e.context("Failed to pin checkpoint").into()),
+            }
+        }
+        Create::VotingAccount(voter) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client
+                .add_voting_account(voter.address.clone(), voter.key, opts.data_dir.clone())
+                .await
+            {
+                Ok(_) => info!(
+                    "{}Now voting with account {}",
+                    Emoji::new("🗳️ ", ""),
+                    voter.address
+                ),
+                Err(e) => {
+                    return Err(e
+                        .context(format!("Failed to add voting account '{}'", voter.address))
+                        .into())
+                }
             }
         }
     };
@@ -142,36 +601,269 @@ async fn create(opts: Opts, c: Create) -> Result<(), failure::Error> {
     Ok(())
 }
 
+/// Persists a transaction built by the account recovery API, so that it can be signed and published.
+///
+/// # Arguments
+///
+/// * `data_dir` - The directory that the transaction should be persisted to
+/// * `res` - The transaction returned by the API
+/// * `action` - A description of what the transaction does
+fn persist_recovery_tx(
+    data_dir: &str,
+    res: Result<transaction::Transaction, ClientError>,
+    action: &str,
+) -> Result<(), failure::Error> {
+    match res {
+        Ok(tx) => {
+            // Persist the tx first
+            tx.to_disk_at_data_directory(data_dir)?;
+
+            info!(
+                "{}Successfully created transaction to {} (sign and publish it to add to DAG): {}",
+                Emoji::new("🛟 ", ""),
+                action,
+                tx.hash
+            );
+        }
+        Err(e) => {
+            return Err(e
+                .context(format!("Failed to create transaction to {}", action))
+                .into())
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses each of the payments that the given batch should make, written either as address=finks on the command
+/// line, or as address,finks lines in a CSV file.
+///
+/// # Arguments
+///
+/// * `batch` - The batch command given by the user
+fn read_transfers(batch: &BatchTransaction) -> Result<Vec<(String, u128)>, failure::Error> {
+    let mut lines: Vec<(String, char)> = batch
+        .transfers
+        .iter()
+        .map(|transfer| (transfer.clone(), '='))
+        .collect();
+
+    if let Some(csv) = &batch.csv {
+        lines.extend(
+            std::fs::read_to_string(csv)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| (line.to_owned(), ',')),
+        );
+    }
+
+    lines
+        .iter()
+        .map(|(line, separator)| {
+            let mut fields = line.splitn(2, *separator);
+
+            match (
+                fields.next(),
+                fields.next().map(|value| value.trim().parse()),
+            ) {
+                (Some(recipient), Some(Ok(value))) => Ok((recipient.trim().to_owned(), value)),
+                _ => Err(failure::err_msg(format!(
+                    "invalid transfer '{}' (expected address{}finks)",
+                    line, separator
+                ))),
+            }
+        })
+        .collect()
+}
+
+/// Loads the address book kept by the node in the data directory, so that contacts can be paid by name.
+async fn load_address_book(opts: &Opts) -> Result<AddressBook, failure::Error> {
+    let client = contacts::Client::new(&opts.rpc_host_url)
+        .with_retry_policy(opts.retry_policy.clone())
+        .with_session(opts.session.clone());
+
+    Ok(AddressBook {
+        contacts: client.list(&opts.data_dir).await?,
+    })
+}
+
+/// Resolves the given contact name or address, refusing typed addresses meant for a network other than the one that
+/// the user pointed us at (or, if they didn't, the one that the node serves).
+///
+/// # Arguments
+///
+/// * `opts` - The options that the CLI was started with
+/// * `book` - The address book that names are looked up in
+/// * `name_or_address` - The name of a contact, a bare address, or a typed address
+async fn resolve(
+    opts: &Opts,
+    book: &AddressBook,
+    name_or_address: &str,
+) -> Result<Address, failure::Error> {
+    if name_or_address.contains(TYPED_ADDRESS_SEPARATOR) {
+        return parse_address(opts, name_or_address).await;
+    }
+
+    Ok(book.resolve(name_or_address)?)
+}
+
+/// Parses the given bare or typed address, refusing typed addresses meant for a network other than the one that the
+/// user pointed us at (or, if they didn't, the one that the node serves).
+///
+/// # Arguments
+///
+/// * `opts` - The options that the CLI was started with
+/// * `address` - The bare or typed address
+async fn parse_address(opts: &Opts, address: &str) -> Result<Address, failure::Error> {
+    // Bare addresses don't name a network, so there's nothing to check them against
+    let network = if address.contains(TYPED_ADDRESS_SEPARATOR) {
+        address_network(opts).await?
+    } else {
+        String::new()
+    };
+
+    Ok(address::parse(address, &network)?)
+}
+
+/// Gets the network that typed addresses given to the CLI have to belong to: the one that the user pointed us at, or
+/// else the one that the node serves.
+async fn address_network(opts: &Opts) -> Result<String, failure::Error> {
+    if let Some(network) = &opts.network {
+        return Ok(network.clone());
+    }
+
+    let client = runtime::Client::new(&opts.rpc_host_url)
+        .with_retry_policy(opts.retry_policy.clone())
+        .with_session(opts.session.clone());
+
+    match client.get_network().await {
+        Ok(network) => Ok(network),
+        Err(e) => Err(e
+            .context("Failed to ask the node which network typed addresses belong to (try passing --network)")
+            .into()),
+    }
+}
+
+/// Resolves the given full hash, or short id, to a full hash. Short ids are resolved against the node's DAG.
+///
+/// # Arguments
+///
+/// * `opts` - The options that the CLI was started with
+/// * `id` - The full hash, or short id (e.g. the first 8 characters of a transaction's hash)
+async fn resolve_hash(opts: &Opts, id: &str) -> Result<Hash, failure::Error> {
+    // Full hashes don't need to be looked up
+    if let Ok(hash) = short_id::resolve(id, &[]) {
+        return Ok(hash);
+    }
+
+    let client = dag::Client::new(&opts.rpc_host_url)
+        .with_retry_policy(opts.retry_policy.clone())
+        .with_session(opts.session.clone());
+
+    match client.resolve_hash(id).await {
+        Ok(hash) => Ok(hash),
+        Err(e) => Err(e.context(format!("Failed to resolve '{}'", id)).into()),
+    }
+}
+
+/// Makes sure that the data directory, and the node, belong to the network that the user pointed us at, if they did.
+/// A node that can't be reached is left for the command itself to report.
+async fn check_network(opts: &Opts) -> Result<(), failure::Error> {
+    let network = match &opts.network {
+        Some(network) => network,
+        None => return Ok(()),
+    };
+
+    isolation::check(&opts.data_dir, network)?;
+
+    let client = runtime::Client::new(&opts.rpc_host_url)
+        .with_retry_policy(opts.retry_policy.clone())
+        .with_session(opts.session.clone());
+
+    match client.get_network().await {
+        Ok(node_network) if node_network != *network => Err(IsolationError::NodeNetworkMismatch {
+            url: opts.rpc_host_url.clone(),
+            node_network,
+            network: network.clone(),
+        }
+        .into()),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            debug!("Couldn't ask the node which network it serves: {}", e);
+
+            Ok(())
+        }
+    }
+}
+
 /// Gets the object with matching criteria.
 async fn get(opts: Opts, g: Get) -> Result<(), failure::Error> {
     match g {
         Get::Account(acc) => {
             // Make a client for the accounts API
-            let client = accounts::Client::new(&opts.rpc_host_url);
+            let client = accounts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
 
             // Get the account
-            match client.get(Hash::from(acc.address), &opts.data_dir).await {
+            match client
+                .get(parse_address(&opts, &acc.address).await?, &opts.data_dir)
+                .await
+            {
                 Ok(acc) => info!("{}Found account: {}", Emoji::new("📒 ", ""), acc),
-                Err(e) => error!("Failed to load the account: {}", e),
+                Err(e) => return Err(e.context("Failed to load the account").into()),
             }
         }
         Get::Balance(acc) => {
             // Make a client for the accounts API
-            let client = accounts::Client::new(&opts.rpc_host_url);
+            let client = accounts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
 
             // Get the account
-            match client.balance(Hash::from(acc.address)).await {
+            match client
+                .balance(parse_address(&opts, &acc.address).await?)
+                .await
+            {
                 Ok(balance) => info!(
                     "{}Balance: {} SMC",
                     Emoji::new("💵 ", ""),
                     summercash::common::fink::convert_finks_to_smc(balance),
                 ),
-                Err(e) => error!("Failed to calculate the account's balance: {}", e),
+                Err(e) => {
+                    return Err(e
+                        .context("Failed to calculate the account's balance")
+                        .into())
+                }
+            }
+        }
+        Get::LockedBalance(acc) => {
+            // Make a client for the time-locked balance API
+            let client = vesting::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            match client.get_locked_balance(acc.address).await {
+                Ok(funds) => info!(
+                    "{}Balance: {} SMC ({} SMC locked, {} SMC spendable): {}",
+                    Emoji::new("🔒 ", ""),
+                    summercash::common::fink::convert_finks_to_smc(funds.balance.clone()),
+                    summercash::common::fink::convert_finks_to_smc(funds.locked.clone()),
+                    summercash::common::fink::convert_finks_to_smc(funds.spendable.clone()),
+                    serde_json::to_string_pretty(&funds.locks)?
+                ),
+                Err(e) => {
+                    return Err(e
+                        .context("Failed to load the account's locked balance")
+                        .into())
+                }
             }
         }
         Get::Dag(_) => {
             // Make a client for the DAG API
-            let client = dag::Client::new(&opts.rpc_host_url);
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
 
             match client.get().await {
                 Ok(nodes) => {
@@ -186,12 +878,14 @@ async fn get(opts: Opts, g: Get) -> Result<(), failure::Error> {
                         info!("{}It's empty tho...", Emoji::new("🤔 ", ""));
                     }
                 }
-                Err(e) => error!("Failed to load the DAG: {}", e),
+                Err(e) => return Err(e.context("Failed to load the DAG").into()),
             }
         }
         Get::TransactionMemory(_) => {
             // Make a client for the DAG API
-            let client = dag::Client::new(&opts.rpc_host_url);
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
 
             match client.get_mem_txs(opts.data_dir).await {
                 Ok(txs) => info!(
@@ -199,95 +893,911 @@ async fn get(opts: Opts, g: Get) -> Result<(), failure::Error> {
                     txs.iter()
                         .fold(String::new(), |acc, &arg| acc + &arg.to_str())
                 ),
-                Err(e) => error!("Failed to load transactions stored in memory: {}", e),
+                Err(e) => {
+                    return Err(e
+                        .context("Failed to load transactions stored in memory")
+                        .into())
+                }
             }
         }
-    };
-
-    Ok(())
-}
-
-/// Locks the object with matching constraints.
-async fn lock(opts: Opts, l: Lock) -> Result<(), failure::Error> {
-    match l {
-        Lock::Account(acc) => {
-            // Make a client for the accounts API
-            let client = accounts::Client::new(&opts.rpc_host_url);
-
-            // Lock the account
-            match client
-                .lock(Hash::from(&*acc.address), &acc.key, &opts.data_dir)
-                .await
-            {
-                Ok(_) => info!(
-                    "{}Locked account '{}' successfully",
-                    Emoji::new("🔒 ", ""),
-                    acc.address
+        Get::Note(subject) => {
+            // Make a client for the notes API
+            let client = notes::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            let hash = resolve_hash(&opts, &subject.hash).await?;
+
+            match client.get(hash, &opts.data_dir).await {
+                Ok(note) => info!(
+                    "{}{}{}",
+                    Emoji::new("🏷️ ", ""),
+                    note.label,
+                    if note.memo.is_empty() {
+                        String::new()
+                    } else {
+                        format!(": {}", note.memo)
+                    }
                 ),
-                Err(e) => error!("Failed to lock the account: {}", e),
+                Err(e) => return Err(e.context("Failed to load the note").into()),
             }
         }
-    };
-
-    Ok(())
-}
-
-/// Locks the object with matching constraints.
-async fn unlock(opts: Opts, u: Unlock) -> Result<(), failure::Error> {
-    match u {
-        Unlock::Account(acc) => {
-            // Make a client for the accounts API
-            let client = accounts::Client::new(&opts.rpc_host_url);
-
-            // Lock the account
-            match client
-                .unlock(Hash::from(acc.address), &acc.key, &opts.data_dir)
-                .await
-            {
-                Ok(acc) => info!(
-                    "{}Unlocked account successfully: {}",
-                    Emoji::new("🔓 ", ""),
-                    acc
+        Get::Contact(contact) => {
+            // Make a client for the address book API
+            let client = contacts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client.get(contact.name.clone(), &opts.data_dir).await {
+                Ok(c) => info!(
+                    "{}{}: {}{}",
+                    Emoji::new("📇 ", ""),
+                    contact.name,
+                    c.address,
+                    if c.memo.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" ({})", c.memo)
+                    }
                 ),
-                Err(e) => error!("Failed to lock the account: {}", e),
+                Err(e) => return Err(e.context("Failed to load the contact").into()),
             }
         }
-    };
-
-    Ok(())
-}
-
-/// Deletes the object with matching constraints.
-async fn delete(opts: Opts, d: Delete) -> Result<(), failure::Error> {
-    match d {
-        Delete::Account(acc) => {
-            // Make a client for the accounts API
-            let client = accounts::Client::new(&opts.rpc_host_url);
+        Get::StateOrigin(state) => {
+            // Make a client for the DAG API
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client.get_state_origin(state.hash).await {
+                Ok(origin) => info!(
+                    "{}Found state origin: {}",
+                    Emoji::new("🧭 ", ""),
+                    serde_json::to_string_pretty(&origin)?
+                ),
+                Err(e) => return Err(e.context("Failed to locate the origin of the state").into()),
+            }
+        }
+        Get::StateAt(node) => {
+            // Make a client for the DAG API
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client.get_state_at(node.hash).await {
+                Ok(entry) => info!(
+                    "{}Found historical state: {}",
+                    Emoji::new("🗄️ ", ""),
+                    serde_json::to_string_pretty(&entry)?
+                ),
+                Err(e) => return Err(e.context("Failed to load the historical state").into()),
+            }
+        }
+        Get::BalanceAt(historical) => {
+            // Make a client for the DAG API
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
 
-            // Delete the account
             match client
-                .delete(Hash::from(&*acc.address), &opts.data_dir)
+                .get_balance_at(
+                    parse_address(&opts, &historical.address).await?,
+                    historical.node_hash,
+                )
                 .await
             {
-                Ok(_) => info!(
-                    "{}Deleted account '{}' successfully",
-                    Emoji::new("🗑️ ", ""),
-                    acc.address
+                Ok(balance) => info!(
+                    "{}Balance: {} SMC",
+                    Emoji::new("💵 ", ""),
+                    summercash::common::fink::convert_finks_to_smc(balance),
                 ),
-                Err(e) => error!("Failed to delete account '{}': {}", acc.address, e),
+                Err(e) => {
+                    return Err(e
+                        .context("Failed to calculate the account's historical balance")
+                        .into())
+                }
             }
         }
-    };
-
-    Ok(())
-}
+        Get::Guardians(acc) => {
+            // Make a client for the account recovery API
+            let client = guardians::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            match client.get(acc.address).await {
+                Ok(record) => info!(
+                    "{}Found guardians: {}",
+                    Emoji::new("🛡️ ", ""),
+                    serde_json::to_string_pretty(&record)?
+                ),
+                Err(e) => return Err(e.context("Failed to load the account's guardians").into()),
+            }
+        }
+        Get::Rewards(acc) => {
+            // Make a client for the validator rewards API
+            let client = rewards::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            match client.get_rewards(acc.address).await {
+                Ok(report) => info!(
+                    "{}Unclaimed rewards: {} SMC ({} votes counted towards the current epoch so far)",
+                    Emoji::new("🏅 ", ""),
+                    summercash::common::fink::convert_finks_to_smc(report.unclaimed),
+                    report.votes_this_epoch,
+                ),
+                Err(e) => return Err(e.context("Failed to load the account's rewards").into()),
+            }
+        }
+        Get::NetworkStats(_) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            match client.get_network_stats().await {
+                Ok(series) => {
+                    if series.is_empty() {
+                        info!(
+                            "{}No network activity has been recorded yet",
+                            Emoji::new("🤔 ", "")
+                        );
+                    }
 
-/// Lists the objects with the given type.
-async fn list(opts: Opts, l: List) -> Result<(), failure::Error> {
-    match l {
-        List::Accounts(_) => {
-            // Make a client for the accounts API
-            let client = accounts::Client::new(&opts.rpc_host_url);
+                    // Print out each of the samples
+                    for sample in series {
+                        println!("{}: {}", sample.start, sample.describe());
+                    }
+                }
+                Err(e) => return Err(e.context("Failed to load the network's stats").into()),
+            }
+        }
+        Get::Supply(_) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            match client.get_supply().await {
+                Ok(supply) => info!(
+                    "{}Supply: {} SMC ({} SMC allocated in the genesis, {} SMC minted over {} epochs, {} SMC burned); {} SMC will be minted for epoch {}",
+                    Emoji::new("🏦 ", ""),
+                    summercash::common::fink::convert_finks_to_smc(supply.total.clone()),
+                    summercash::common::fink::convert_finks_to_smc(supply.genesis.clone()),
+                    summercash::common::fink::convert_finks_to_smc(supply.minted.clone()),
+                    supply.mints.len(),
+                    summercash::common::fink::convert_finks_to_smc(supply.burned.clone()),
+                    summercash::common::fink::convert_finks_to_smc(supply.next_reward.clone()),
+                    supply.current_epoch,
+                ),
+                Err(e) => return Err(e.context("Failed to load the network's supply").into()),
+            }
+        }
+        Get::BurnedSupply(_) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            match client.get_burned_supply().await {
+                Ok(report) => info!(
+                    "{}{} SMC have been burned by sending them to {}",
+                    Emoji::new("🔥 ", ""),
+                    summercash::common::fink::convert_finks_to_smc(report.burned.clone()),
+                    report.address
+                ),
+                Err(e) => return Err(e.context("Failed to load the burned supply").into()),
+            }
+        }
+        Get::GenesisRoles(_) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            match client.get_genesis_roles().await {
+                Ok(assignments) if assignments.is_empty() => {
+                    info!("The genesis didn't designate any accounts a role")
+                }
+                Ok(assignments) => {
+                    for assignment in assignments {
+                        info!(
+                            "{}{} is the network's {} (allocated {} SMC in the genesis)",
+                            Emoji::new("🏛  ", ""),
+                            assignment.address,
+                            assignment.role,
+                            summercash::common::fink::convert_finks_to_smc(assignment.allocated)
+                        );
+                    }
+                }
+                Err(e) => return Err(e.context("Failed to load the genesis roles").into()),
+            }
+        }
+        Get::ProposalSimulation(proposal) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            match client.simulate_proposal(proposal.hash).await {
+                Ok(simulation) => {
+                    match simulation.failure() {
+                        Some(failure) => info!(
+                            "{}Executing the proposal would fail: {}",
+                            Emoji::new("⚠️ ", ""),
+                            failure
+                        ),
+                        None => info!(
+                            "{}Executing the proposal would succeed",
+                            Emoji::new("✅ ", "")
+                        ),
+                    }
+
+                    println!("{}", serde_json::to_string_pretty(&simulation)?);
+                }
+                Err(e) => return Err(e.context("Failed to simulate the proposal").into()),
+            }
+        }
+        Get::SpendingStatus(acc) => {
+            // Make a client for the accounts API
+            let client = accounts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client
+                .get_spending_status(parse_address(&opts, &acc.address).await?, &opts.data_dir)
+                .await
+            {
+                Ok(status) => {
+                    info!(
+                        "{}Spent {} SMC over the last day",
+                        Emoji::new("🧾 ", ""),
+                        summercash::common::fink::convert_finks_to_smc(status.spent_today.clone()),
+                    );
+
+                    println!("{}", serde_json::to_string_pretty(&status)?);
+                }
+                Err(e) => {
+                    return Err(e
+                        .context("Failed to load the account's spending status")
+                        .into())
+                }
+            }
+        }
+        Get::ExecutedProposals(query) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            match client
+                .list_executed_proposals(Some(query.limit), query.param.as_deref())
+                .await
+            {
+                Ok(executed) => {
+                    if executed.is_empty() {
+                        info!(
+                            "{}The node hasn't executed any matching proposals yet",
+                            Emoji::new("🤔 ", "")
+                        );
+                    }
+
+                    // Print out each of the proposals, the newest first
+                    for proposal in executed {
+                        println!(
+                            "{} {} ({}: {} {}): {}",
+                            proposal.executed_at,
+                            proposal.proposal_id,
+                            proposal.proposal_name,
+                            proposal.operation,
+                            proposal.params.join(", "),
+                            match proposal.outcome {
+                                ExecutionOutcome::Applied => "applied".to_owned(),
+                                ExecutionOutcome::Failed { error } => format!("failed ({})", error),
+                            }
+                        );
+                    }
+                }
+                Err(e) => return Err(e.context("Failed to load the executed proposals").into()),
+            }
+        }
+        Get::VoteJournal(query) => {
+            let address = resolve(&opts, &load_address_book(&opts).await?, &query.address).await?;
+
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client
+                .get_vote_journal(address, query.before, Some(query.limit))
+                .await
+            {
+                Ok(votes) => {
+                    if votes.is_empty() {
+                        info!(
+                            "{}The node hasn't voted with {} yet",
+                            Emoji::new("🤔 ", ""),
+                            address
+                        );
+                    }
+
+                    // Print out each of the votes, the newest first
+                    for vote in votes {
+                        println!(
+                            "#{} {} voted {} proposal {} ({}{}) {}{}: {}",
+                            vote.seq,
+                            vote.voted_at,
+                            if vote.in_favor { "for" } else { "against" },
+                            vote.proposal_id,
+                            vote.param,
+                            vote.transaction
+                                .map_or_else(String::new, |tx| format!(", transaction {}", tx)),
+                            match vote.origin {
+                                VoteOrigin::Automatic => "automatically",
+                                VoteOrigin::Confirmed => "as confirmed by the operator",
+                            },
+                            if vote.remote {
+                                " via the remote signer"
+                            } else {
+                                ""
+                            },
+                            vote.reason
+                        );
+                    }
+                }
+                Err(e) => {
+                    return Err(e
+                        .context("Failed to load the account's vote journal")
+                        .into())
+                }
+            }
+        }
+        Get::DormantAccounts(query) => {
+            // Make a client for the accounts API
+            let client = accounts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client
+                .get_dormant_accounts(query.idle_days, Some(query.limit))
+                .await
+            {
+                Ok(report) => {
+                    info!(
+                        "{}{} accounts haven't sent anything in {} days, holding {} of {} SMC",
+                        Emoji::new("💤 ", ""),
+                        report.dormant_accounts,
+                        query.idle_days,
+                        summercash::common::fink::convert_finks_to_smc(
+                            report.dormant_balance.clone()
+                        ),
+                        summercash::common::fink::convert_finks_to_smc(
+                            report.total_balance.clone()
+                        )
+                    );
+
+                    // Print out each of the accounts, the richest first
+                    for account in report.accounts {
+                        println!(
+                            "{}: {} SMC, last sent {}",
+                            account.address,
+                            summercash::common::fink::convert_finks_to_smc(account.balance),
+                            account
+                                .last_sent
+                                .map_or("never".to_owned(), |last_sent| last_sent.to_string())
+                        );
+                    }
+                }
+                Err(e) => return Err(e.context("Failed to load the dormant accounts").into()),
+            }
+        }
+        Get::DoubleSpends(query) => {
+            // Make a client for the events API
+            let client = events::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            match client.get_double_spends(Some(query.limit)).await {
+                Ok(alerts) if alerts.is_empty() => {
+                    info!("{}No double spends have been seen", Emoji::new("✅ ", ""))
+                }
+                Ok(alerts) => {
+                    // Print out each of the competing spends, so that the payments they make can be held
+                    for alert in alerts {
+                        println!(
+                            "{}Nonce {} of {} is spent by {} transactions (seen {}):",
+                            Emoji::new("⚠️ ", ""),
+                            alert.nonce,
+                            alert.sender,
+                            alert.spends.len(),
+                            alert.detected_at
+                        );
+
+                        for spend in alert.spends {
+                            println!(
+                                "    {}: {} SMC to {}",
+                                spend.hash,
+                                summercash::common::fink::convert_finks_to_smc(spend.value),
+                                spend.recipient
+                            );
+                        }
+                    }
+                }
+                Err(e) => return Err(e.context("Failed to load the double spends").into()),
+            }
+        }
+        Get::StorageStatus(_) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            match client.get_storage_status().await {
+                Ok(status) => {
+                    // A node that can't write to its disk keeps serving reads, but doesn't vote or publish
+                    if status.read_only {
+                        warn!(
+                            "{}The node has been read-only since {}, as its disk isn't taking writes: {}",
+                            Emoji::new("🔒 ", ""),
+                            status
+                                .read_only_since
+                                .map_or("startup".to_owned(), |since| since.to_string()),
+                            status.write_failure.as_deref().unwrap_or("unknown error")
+                        );
+                    }
+
+                    match status.checked_at {
+                    Some(checked_at) => info!(
+                        "{}The database takes up {} bytes, with {} bytes free on its disk (pressure: {}, checked at \
+                         {}, {} writes refused)",
+                        Emoji::new("💾 ", ""),
+                        status.db_bytes,
+                        status
+                            .available_bytes
+                            .map_or("an unknown number of".to_owned(), |free| free.to_string()),
+                        format!("{:?}", status.pressure).to_lowercase(),
+                        checked_at,
+                        status.refused_writes
+                    ),
+                    None => info!("The node hasn't measured its storage yet"),
+                    }
+                }
+                Err(e) => return Err(e.context("Failed to load the storage status").into()),
+            }
+        }
+        Get::AccountDiagnosis(query) => {
+            // Make a client for the DAG API
+            let client =
+                dag::Client::new(&opts.rpc_host_url).with_retry_policy(opts.retry_policy.clone());
+
+            match client
+                .diagnose_account(&query.address, query.stuck_after_secs)
+                .await
+            {
+                Ok(diagnosis) if diagnosis.is_healthy() => info!(
+                    "{}Nothing is holding back the account's transactions ({} waiting, next nonce: {})",
+                    Emoji::new("✅ ", ""),
+                    diagnosis.waiting.len(),
+                    diagnosis.next_nonce
+                ),
+                Ok(diagnosis) => {
+                    warn!(
+                        "{}Found {} issue(s) holding back the account's transactions; recommended actions follow",
+                        Emoji::new("🩺 ", ""),
+                        diagnosis.issues.len()
+                    );
+
+                    println!("{}", serde_json::to_string_pretty(&diagnosis)?);
+                }
+                Err(e) => return Err(e.context("Failed to diagnose the account").into()),
+            }
+        }
+        Get::BalanceProof(query) => {
+            // Make a client for the DAG API
+            let client =
+                dag::Client::new(&opts.rpc_host_url).with_retry_policy(opts.retry_policy.clone());
+
+            let proven = client
+                .get_balance_proof(&query.address, query.at.as_deref())
+                .await
+                .map_err(|e| e.context("Failed to prove the account's balance"))?;
+
+            // The proof has to hold up against the header that it came with, at the very least
+            let balance = light::verify_balance(&proven.header, &proven.proof)?;
+
+            // A header is only as trustworthy as whoever serves it, so have the other nodes check it against theirs
+            for url in &query.verify_with {
+                let verifier = dag::Client::new(url).with_retry_policy(opts.retry_policy.clone());
+
+                match verifier.verify_balance_proof(&proven).await {
+                    Ok(verified) if verified == balance => {
+                        info!("{}{} agrees with the proof", Emoji::new("🤝 ", ""), url)
+                    }
+                    Ok(_) => {
+                        return Err(failure::err_msg(format!(
+                            "{} disagrees with the proof",
+                            url
+                        )))
+                    }
+                    Err(e) => {
+                        return Err(e
+                            .context(format!("{} couldn't verify the proof", url))
+                            .into())
+                    }
+                }
+            }
+
+            info!(
+                "{}Account {} held {} finks as of transaction {} (height {}), checked against {} node(s)",
+                Emoji::new("🔐 ", ""),
+                proven.proof.address,
+                balance,
+                proven.header.hash,
+                proven.header.height,
+                query.verify_with.len() + 1
+            );
+
+            println!("{}", serde_json::to_string_pretty(&proven)?);
+        }
+        Get::SyncStatus(query) => {
+            // Make a client for the peers API
+            let client =
+                peers::Client::new(&opts.rpc_host_url).with_retry_policy(opts.retry_policy.clone());
+
+            // The total isn't known until the node has caught up, so just count the transactions downloaded
+            let mut progress = if query.watch {
+                Some(Progress::new("Downloading the DAG", None))
+            } else {
+                None
+            };
+
+            loop {
+                let status = match client.get_sync_status().await {
+                    Ok(status) => status,
+                    Err(e) => {
+                        drop(progress);
+
+                        return Err(e.context("Failed to load the node's sync status").into());
+                    }
+                };
+
+                match progress.as_mut() {
+                    Some(progress) if !status.synchronized => {
+                        progress.set(status.height as u64);
+                        tokio::time::delay_for(Duration::from_secs(1)).await;
+
+                        continue;
+                    }
+                    Some(progress) => progress.finish(),
+                    None => (),
+                }
+
+                if status.connectivity == Connectivity::Isolated {
+                    warn!(
+                        "{}Isolated: the node isn't connected to any peers, and is retrying its bootstrap nodes",
+                        Emoji::new("🔌 ", "")
+                    );
+                } else if status.synchronized {
+                    info!(
+                        "{}Synchronized: the DAG holds {} transactions",
+                        Emoji::new("✅ ", ""),
+                        status.height
+                    );
+                } else {
+                    info!(
+                        "{}Synchronizing: the DAG holds {} transactions so far, and {} of {} peers have published more",
+                        Emoji::new("⏳ ", ""),
+                        status.height,
+                        status.peers_ahead,
+                        status.peers
+                    );
+                }
+
+                // Point out releases that the node has fallen behind on
+                if let Some(update) = status
+                    .update
+                    .as_ref()
+                    .filter(|update| update.update_available)
+                {
+                    warn!(
+                        "{}Release {} of the node software is out (running {}){}",
+                        Emoji::new("⬆️ ", ""),
+                        update.latest_version.as_deref().unwrap_or_default(),
+                        update.running_version,
+                        if update.consensus_critical {
+                            "; it is consensus-critical, so upgrade soon"
+                        } else {
+                            ""
+                        }
+                    );
+                }
+
+                println!("{}", serde_json::to_string_pretty(&status)?);
+
+                break;
+            }
+        }
+        Get::SyncPeers(_) => {
+            // Make a client for the peers API
+            let client =
+                peers::Client::new(&opts.rpc_host_url).with_retry_policy(opts.retry_policy.clone());
+
+            match client.get_sync_peers().await {
+                Ok(sync_peers) => {
+                    if sync_peers.is_empty() {
+                        info!(
+                            "{}The node hasn't heard from any peers yet",
+                            Emoji::new("🤔 ", "")
+                        );
+                    }
+
+                    // Print out each of the peers, the worst first
+                    for peer in sync_peers {
+                        println!(
+                            "{} ({}): head {}, {} records fetched ({} rejected, {} fabricated), RTT {}, \
+                             {} failed pings",
+                            peer.peer_id,
+                            peer.agent_version.as_ref().map(String::as_str).unwrap_or("unidentified"),
+                            peer.advertised_head.map_or_else(|| "unknown".to_owned(), |head| head.to_str()),
+                            peer.records_fetched,
+                            peer.records_rejected,
+                            peer.invariant_violations,
+                            peer.rtt_ms.map_or_else(|| "unknown".to_owned(), |rtt| format!("{}ms", rtt)),
+                            peer.ping_failures
+                        );
+                    }
+                }
+                Err(e) => return Err(e.context("Failed to load the node's sync peers").into()),
+            }
+        }
+    };
+
+    Ok(())
+}
+
+/// Locks the object with matching constraints.
+async fn lock(opts: Opts, l: Lock) -> Result<(), failure::Error> {
+    match l {
+        Lock::Account(acc) => {
+            // Make a client for the accounts API
+            let client = accounts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            // Lock the account
+            match client
+                .lock(
+                    parse_address(&opts, &acc.address).await?,
+                    &acc.key,
+                    &opts.data_dir,
+                )
+                .await
+            {
+                Ok(_) => {
+                    info!(
+                        "{}Locked account '{}' successfully",
+                        Emoji::new("🔒 ", ""),
+                        acc.address
+                    );
+
+                    // Hand the key to the secret store, if the user asked us to
+                    if let Some(store) = secrets::open(&acc.secret_store)? {
+                        store.set(&parse_address(&opts, &acc.address).await?, &acc.key)?;
+
+                        info!(
+                            "Stored the account's key in the {} secret store",
+                            acc.secret_store
+                        );
+                    }
+                }
+                Err(e) => return Err(e.context("Failed to lock the account").into()),
+            }
+        }
+        Lock::Session(session) => {
+            // Make a client for the accounts API
+            let client = accounts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            // Close the session, locking its account
+            match client.close_unlock_session(&session.handle).await {
+                Ok(_) => info!("{}Locked the session's account", Emoji::new("🔒 ", "")),
+                Err(e) => return Err(e.context("Failed to close the unlock session").into()),
+            }
+        }
+    };
+
+    Ok(())
+}
+
+/// Locks the object with matching constraints.
+async fn unlock(opts: Opts, u: Unlock) -> Result<(), failure::Error> {
+    match u {
+        Unlock::Account(acc) => {
+            // Make a client for the accounts API
+            let client = accounts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            // Lock the account
+            match client
+                .unlock(
+                    parse_address(&opts, &acc.address).await?,
+                    &acc.key,
+                    &opts.data_dir,
+                )
+                .await
+            {
+                Ok(unlocked) => {
+                    info!(
+                        "{}Unlocked account successfully: {}",
+                        Emoji::new("🔓 ", ""),
+                        unlocked
+                    );
+
+                    // The key no longer protects anything
+                    if let Some(store) = secrets::open(&acc.secret_store)? {
+                        store.delete(&parse_address(&opts, &acc.address).await?)?;
+                    }
+                }
+                Err(e) => return Err(e.context("Failed to lock the account").into()),
+            }
+        }
+        Unlock::Session(timed) => {
+            // Make a client for the accounts API
+            let client = accounts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            // Unlock the account in the node's memory, leaving its keystore locked
+            match client
+                .open_unlock_session(
+                    parse_address(&opts, &timed.address).await?,
+                    &timed.key,
+                    &opts.data_dir,
+                    Some(timed.minutes),
+                )
+                .await
+            {
+                Ok(session) => info!(
+                    "{}Unlocked account '{}' until {} (sign with --unlock-session {})",
+                    Emoji::new("🔓 ", ""),
+                    session.address,
+                    session.expires_at,
+                    session.handle
+                ),
+                Err(e) => return Err(e.context("Failed to unlock the account").into()),
+            }
+        }
+    };
+
+    Ok(())
+}
+
+/// Deletes the object with matching constraints.
+async fn delete(opts: Opts, d: Delete) -> Result<(), failure::Error> {
+    match d {
+        Delete::Account(acc) => {
+            // Make a client for the accounts API
+            let client = accounts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            // Delete the account
+            match client
+                .delete(parse_address(&opts, &acc.address).await?, &opts.data_dir)
+                .await
+            {
+                Ok(_) => info!(
+                    "{}Deleted account '{}' successfully",
+                    Emoji::new("🗑️ ", ""),
+                    acc.address
+                ),
+                Err(e) => {
+                    return Err(e
+                        .context(format!("Failed to delete account '{}'", acc.address))
+                        .into())
+                }
+            }
+        }
+        Delete::Note(subject) => {
+            // Make a client for the notes API
+            let client = notes::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            // Delete the note
+            let hash = resolve_hash(&opts, &subject.hash).await?;
+
+            match client.delete(hash, &opts.data_dir).await {
+                Ok(_) => info!(
+                    "{}Deleted note on '{}' successfully",
+                    Emoji::new("🗑️ ", ""),
+                    subject.hash
+                ),
+                Err(e) => {
+                    return Err(e
+                        .context(format!("Failed to delete note on '{}'", subject.hash))
+                        .into())
+                }
+            }
+        }
+        Delete::Contact(contact) => {
+            // Make a client for the address book API
+            let client = contacts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client.delete(contact.name.clone(), &opts.data_dir).await {
+                Ok(_) => info!(
+                    "{}Deleted contact '{}' successfully",
+                    Emoji::new("🗑️ ", ""),
+                    contact.name
+                ),
+                Err(e) => {
+                    return Err(e
+                        .context(format!("Failed to delete contact '{}'", contact.name))
+                        .into())
+                }
+            }
+        }
+        Delete::Checkpoint(checkpoint) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client
+                .unpin_checkpoint(checkpoint.index, opts.data_dir.clone())
+                .await
+            {
+                Ok(Some(unpinned)) => info!(
+                    "{}Unpinned checkpoint {} at index {}",
+                    Emoji::new("🗑️ ", ""),
+                    unpinned.hash,
+                    unpinned.index
+                ),
+                Ok(None) => warn!("No checkpoint is pinned at index {}", checkpoint.index),
+                Err(e) => return Err(e.context("Failed to unpin checkpoint").into()),
+            }
+        }
+        Delete::VotingAccount(acc) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client
+                .remove_voting_account(acc.address.clone(), opts.data_dir.clone())
+                .await
+            {
+                Ok(_) => info!(
+                    "{}No longer voting with account {}",
+                    Emoji::new("🗑️ ", ""),
+                    acc.address
+                ),
+                Err(e) => {
+                    return Err(e
+                        .context(format!("Failed to remove voting account '{}'", acc.address))
+                        .into())
+                }
+            }
+        }
+        Delete::Recovery(acc) => {
+            // Make a client for the account recovery API
+            let client = guardians::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone());
+
+            persist_recovery_tx(
+                &opts.data_dir,
+                client.cancel_recovery(acc.address).await,
+                "cancel recovery",
+            )?;
+        }
+    };
+
+    Ok(())
+}
+
+/// Lists the objects with the given type.
+async fn list(opts: Opts, l: List) -> Result<(), failure::Error> {
+    match l {
+        List::Accounts(_) => {
+            // Make a client for the accounts API
+            let client = accounts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
 
             // List all of the accounts on the disk
             match client.list(&opts.data_dir).await {
@@ -320,33 +1830,38 @@ async fn list(opts: Opts, l: List) -> Result<(), failure::Error> {
                 }
 
                 // Log the error
-                Err(e) => error!("Failed to locate all of the accounts in dir: {}", e),
+                Err(e) => {
+                    return Err(e
+                        .context("Failed to locate all of the accounts in dir")
+                        .into())
+                }
             }
         }
         List::Transactions(_) => {
-            // Make a client for the DAG API
-            let client = dag::Client::new(&opts.rpc_host_url);
+            // Make a client for the notes API, which pairs each transaction with its labels
+            let client = notes::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
 
             // List all of the transactions on the disk
-            match client.list().await {
+            match client.list_annotated_txs(&opts.data_dir).await {
                 Ok(transactions) => {
                     // The collective hashes of each transaction, in one string
                     let mut transactions_string = String::new();
 
-                    // The current index in the hash collection process
-                    let mut i = 0;
-
-                    // Put each of the hashes into the overall string
-                    let _: Vec<()> = transactions
-                        .iter()
-                        .map(|hash| {
-                            // Append the hash to the overall string (+ a separator, if need be)
-                            transactions_string +=
-                                &format!("{}{}", if i > 0 { ", " } else { "" }, hash.to_str());
-
-                            i += 1;
-                        })
-                        .collect();
+                    // Put each of the hashes into the overall string, along with any label the user has given it
+                    for (i, tx) in transactions.iter().enumerate() {
+                        transactions_string += &format!(
+                            "{}{}{}",
+                            if i > 0 { ", " } else { "" },
+                            tx.hash.to_str(),
+                            if let Some(note) = &tx.note {
+                                format!(" ({})", note.label)
+                            } else {
+                                String::new()
+                            }
+                        );
+                    }
 
                     info!(
                         "{}Found transactions: {}",
@@ -356,12 +1871,18 @@ async fn list(opts: Opts, l: List) -> Result<(), failure::Error> {
                 }
 
                 // Log the error
-                Err(e) => error!("Failed to locate all of the transactions in the DAG: {}", e),
+                Err(e) => {
+                    return Err(e
+                        .context("Failed to locate all of the transactions in the DAG")
+                        .into())
+                }
             }
         }
         List::Proposals(_) => {
             // Make a client for the runtime API
-            let client = runtime::Client::new(&opts.rpc_host_url);
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
 
             // List all of the pending proposals on the disk
             match client.list_pending_proposals().await {
@@ -375,10 +1896,9 @@ async fn list(opts: Opts, l: List) -> Result<(), failure::Error> {
                         .iter()
                         .map(|prop| {
                             proposals_string += &format!(
-                                "{}Proposal to '{}' '{}': '{}' ({})",
+                                "{}Proposal to {}: '{}' ({})",
                                 if i > 1 { "\n" } else { "" },
-                                prop.proposal_data.operation,
-                                prop.proposal_data.param_name,
+                                prop.proposal_data,
                                 prop.proposal_name,
                                 prop.proposal_id
                             );
@@ -395,10 +1915,99 @@ async fn list(opts: Opts, l: List) -> Result<(), failure::Error> {
                 }
 
                 // Log the error
-                Err(e) => error!(
-                    "Failed to locate all of the proposals in the runtime: {}",
-                    e
-                ),
+                Err(e) => {
+                    return Err(e
+                        .context("Failed to locate all of the proposals in the runtime")
+                        .into())
+                }
+            }
+        }
+        List::Checkpoints(_) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client.list_checkpoints().await {
+                Ok(set) => {
+                    info!(
+                        "{}Found {} pinned checkpoints",
+                        Emoji::new("🔎 ", ""),
+                        set.checkpoints.len()
+                    );
+
+                    // Print out each of the checkpoints
+                    for checkpoint in set.checkpoints {
+                        println!("{}: {}", checkpoint.index, checkpoint.hash);
+                    }
+                }
+
+                // Log the error
+                Err(e) => return Err(e.context("Failed to load the pinned checkpoints").into()),
+            }
+        }
+        List::VotingAccounts(_) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client.list_voting_accounts().await {
+                Ok(voters) => {
+                    info!(
+                        "{}Voting with {} accounts",
+                        Emoji::new("🔎 ", ""),
+                        voters.len()
+                    );
+
+                    // Print out each of the voting accounts
+                    for address in voters {
+                        println!("{}", address);
+                    }
+                }
+
+                // Log the error
+                Err(e) => return Err(e.context("Failed to load the voting accounts").into()),
+            }
+        }
+        List::Notes(_) => {
+            // Make a client for the notes API
+            let client = notes::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client.list(&opts.data_dir).await {
+                Ok(notes) => {
+                    info!("{}Found {} notes", Emoji::new("🔎 ", ""), notes.len());
+
+                    // Print out each of the notes
+                    for (subject, note) in notes {
+                        println!("{}: {} {}", subject.to_str(), note.label, note.memo);
+                    }
+                }
+
+                // Log the error
+                Err(e) => return Err(e.context("Failed to load the notes in dir").into()),
+            }
+        }
+        List::Contacts(_) => {
+            // Make a client for the address book API
+            let client = contacts::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            match client.list(&opts.data_dir).await {
+                Ok(contacts) => {
+                    info!("{}Found {} contacts", Emoji::new("🔎 ", ""), contacts.len());
+
+                    // Print out each of the contacts
+                    for (name, contact) in contacts {
+                        println!("{}: {} {}", name, contact.address, contact.memo);
+                    }
+                }
+
+                // Log the error
+                Err(e) => return Err(e.context("Failed to load the contacts in dir").into()),
             }
         }
     }
@@ -411,16 +2020,36 @@ async fn sign(opts: Opts, s: Sign) -> Result<(), failure::Error> {
     match s {
         Sign::Transaction(signable) => {
             // Make a client for the DAG API
-            let client = dag::Client::new(&opts.rpc_host_url);
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
 
             // Sign the transaction
-            match client.sign_tx(signable.hash, opts.data_dir).await {
+            match client
+                .sign_tx(
+                    signable.hash,
+                    opts.data_dir,
+                    signable.confirmation,
+                    signable.unlock_session,
+                )
+                .await
+            {
                 Ok(signature) => info!(
                     "{}Signed tx (publish with publish command): {}",
                     Emoji::new("✍️ ", ""),
                     serde_json::to_string(&signature)?
                 ),
-                Err(e) => error!("Failed to sign tx: {}", e),
+                Err(e) => {
+                    let context = match &e {
+                        ClientError::Server { code, .. } if *code == error::ERROR_CONFIRMATION_REQUIRED => {
+                            "Failed to sign tx (it must be confirmed with the code written to the node's log; use \
+                             --confirmation)"
+                        }
+                        _ => "Failed to sign tx",
+                    };
+
+                    return Err(e.context(context).into());
+                }
             }
         }
     }
@@ -433,11 +2062,800 @@ async fn publish(opts: Opts, p: Publish) -> Result<(), failure::Error> {
     match p {
         Publish::Transaction(publishable) => {
             // Make a client for the DAG API
-            let client = dag::Client::new(&opts.rpc_host_url);
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
 
-            match client.publish_tx(publishable.hash, opts.data_dir).await {
+            // Make sure the user knows what they're about to send
+            if !preflight_tx(&client, &opts, &publishable).await? {
+                info!("{}Not publishing the transaction", Emoji("✋ ", ""));
+
+                return Ok(());
+            }
+
+            match client
+                .publish_tx(publishable.hash.clone(), opts.data_dir.clone())
+                .await
+            {
                 Ok(_) => info!("{}Publishing transaction!", Emoji("🚚 ", "")),
-                Err(e) => error!("Failed to publish tx: {}", e),
+                Err(e) => return Err(e.context("Failed to publish tx").into()),
+            }
+
+            // Keep an eye on the transaction until it's executed, if the user gave it a deadline
+            if let Some(window_secs) = publishable.deadline {
+                let policy = ResubmissionPolicy {
+                    window_secs,
+                    max_resubmissions: publishable.max_resubmissions,
+                    strategy: publishable.resubmit_with.parse()?,
+                };
+
+                watch_submission(&client, &opts, &publishable, policy).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Waits for the published transaction to be executed, resubmitting it each time that it misses its deadline, and
+/// reports how its submission turned out.
+async fn watch_submission(
+    client: &dag::Client,
+    opts: &Opts,
+    publishable: &PublishableTransaction,
+    policy: ResubmissionPolicy,
+) -> Result<(), failure::Error> {
+    let mut resubmission =
+        Resubmission::new(policy, Hash::from(publishable.hash.as_str()), Utc::now());
+
+    let executed = loop {
+        // Any of the transactions submitted so far may have been executed, since they all spend the same nonce
+        let hashes: Vec<String> = resubmission.hashes().iter().map(Hash::to_str).collect();
+        let executed = client
+            .get_receipts(&hashes)
+            .await?
+            .iter()
+            .zip(resubmission.hashes())
+            .find(|(receipt, _)| receipt.is_ok())
+            .map(|(_, hash)| *hash);
+        if executed.is_some() {
+            break executed;
+        }
+
+        let (n, strategy) = match resubmission.next_action(Utc::now()) {
+            ResubmissionAction::Wait { .. } => {
+                tokio::time::delay_for(Duration::from_secs(POLL_INTERVAL_SECS)).await;
+
+                continue;
+            }
+            ResubmissionAction::Resubmit {
+                resubmission: n,
+                strategy,
+            } => (n, strategy),
+            ResubmissionAction::GiveUp => break None,
+        };
+
+        let latest = resubmission.latest().to_str();
+        let hash = match strategy {
+            ResubmissionStrategy::Regossip => {
+                client
+                    .resubmit_tx(&latest, &opts.data_dir, None)
+                    .await
+                    .map_err(|e| e.context("Failed to resubmit tx"))?;
+
+                resubmission.latest()
+            }
+            ResubmissionStrategy::Rebuild => {
+                let rebuilt = client
+                    .rebuild_tx(&latest, &opts.data_dir)
+                    .await
+                    .map_err(|e| e.context("Failed to rebuild tx"))?;
+                rebuilt.to_disk_at_data_directory(&opts.data_dir)?;
+
+                // The replacement needs a signature of its own
+                client
+                    .sign_tx(
+                        rebuilt.hash.to_str(),
+                        opts.data_dir.clone(),
+                        None,
+                        publishable.unlock_session.clone(),
+                    )
+                    .await
+                    .map_err(|e| e.context("Failed to sign rebuilt tx"))?;
+                client
+                    .resubmit_tx(&rebuilt.hash.to_str(), &opts.data_dir, Some(&latest))
+                    .await
+                    .map_err(|e| e.context("Failed to resubmit tx"))?;
+
+                rebuilt.hash
+            }
+        };
+
+        info!(
+            "{}Transaction {} wasn't executed in time; resubmitted it as {} ({} of {})",
+            Emoji::new("🔁 ", ""),
+            latest,
+            hash,
+            n,
+            resubmission.policy.max_resubmissions
+        );
+        resubmission.resubmitted(hash, Utc::now());
+    };
+
+    match resubmission.outcome(executed) {
+        ResubmissionOutcome::Executed {
+            hash,
+            resubmissions,
+        } => {
+            info!(
+                "{}Transaction {} was executed after {} resubmissions",
+                Emoji::new("✅ ", ""),
+                hash,
+                resubmissions
+            );
+
+            Ok(())
+        }
+        ResubmissionOutcome::Abandoned {
+            hashes,
+            resubmissions,
+        } => Err(failure::err_msg(format!(
+            "none of the submitted transactions ({}) were executed after {} resubmissions",
+            hashes
+                .iter()
+                .map(Hash::to_str)
+                .collect::<Vec<String>>()
+                .join(", "),
+            resubmissions
+        ))),
+    }
+}
+
+/// Runs the node's validator and a few local sanity checks against the transaction that is about to be published,
+/// summarizes the transaction, and asks the user to confirm it. Returns whether or not the transaction should be
+/// published.
+async fn preflight_tx(
+    client: &dag::Client,
+    opts: &Opts,
+    publishable: &PublishableTransaction,
+) -> Result<bool, failure::Error> {
+    // Read the transaction that we're about to publish
+    let tx = transaction::Transaction::from_disk_at_data_directory(
+        &opts.data_dir,
+        Hash::from(publishable.hash.as_str()),
+    )?;
+
+    let report = client.validate_tx(&tx).await?; // Ask the node what it thinks of the transaction
+    let mut errors = preflight::check(&tx, &report);
+
+    println!("{}", preflight::summarize(&tx, &report));
+    for e in &errors {
+        warn!("{}{}", Emoji::new("⚠️ ", ""), e);
+    }
+
+    // Don't skip the prompt if something looks off
+    if publishable.yes {
+        if !errors.is_empty() {
+            // Fail with the first mistake found, so that scripts can tell why the transaction wasn't published
+            return Err(errors
+                .remove(0)
+                .context(
+                    "Refusing to publish without confirmation, since the pre-flight checks failed",
+                )
+                .into());
+        }
+
+        return Ok(true);
+    }
+
+    print!("Publish this transaction? [y/N] ");
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    Ok(["y", "yes"].contains(&answer.trim().to_lowercase().as_str()))
+}
+
+/// Plans the transfers merging each of the given accounts into the primary account, prints out the plan, and carries
+/// it out once the user confirms it.
+async fn consolidate(opts: Opts, c: Consolidate) -> Result<(), failure::Error> {
+    // Make a client for each of the APIs that the plan is drawn up & carried out with
+    let accounts_client = accounts::Client::new(&opts.rpc_host_url)
+        .with_retry_policy(opts.retry_policy.clone())
+        .with_session(opts.session.clone());
+    let vesting_client =
+        vesting::Client::new(&opts.rpc_host_url).with_retry_policy(opts.retry_policy.clone());
+    let dag_client = dag::Client::new(&opts.rpc_host_url)
+        .with_retry_policy(opts.retry_policy.clone())
+        .with_session(opts.session.clone());
+
+    // Merge into a contact's account by name
+    let primary = resolve(&opts, &load_address_book(&opts).await?, &c.primary).await?;
+
+    // Merge every account in the keystore, unless the user picked some
+    let sources: Vec<Address> = if c.sources.is_empty() {
+        accounts_client
+            .list(&opts.data_dir)
+            .await
+            .map_err(|e| e.context("Failed to locate the accounts in the keystore"))?
+    } else {
+        let mut sources = Vec::new();
+        for source in &c.sources {
+            sources.push(parse_address(&opts, source).await?);
+        }
+
+        sources
+    };
+
+    // Find out how much of each account may be spent
+    let mut balances = Vec::new();
+    for source in sources {
+        let balance = vesting_client
+            .get_locked_balance(source.to_str())
+            .await
+            .map_err(|e| e.context(format!("Failed to load the balance of {}", source)))?;
+
+        balances.push((source, balance));
+    }
+
+    let plan = consolidate::plan(primary, &balances);
+    println!("{}", plan.summarize());
+
+    if plan.transfers.is_empty() {
+        info!("{}Nothing to merge", Emoji::new("✨ ", ""));
+
+        return Ok(());
+    }
+
+    if !c.yes {
+        print!("Carry out this plan? [y/N] ");
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+
+        if !["y", "yes"].contains(&answer.trim().to_lowercase().as_str()) {
+            info!("{}Not merging any accounts", Emoji("✋ ", ""));
+
+            return Ok(());
+        }
+    }
+
+    // Carry out each transfer in turn. Each source spends from its own nonce, so one failing doesn't hold up the rest.
+    let mut failures: Vec<failure::Error> = Vec::new();
+    for transfer in &plan.transfers {
+        match consolidate_transfer(&dag_client, &opts, &c, primary, transfer).await {
+            Ok(hash) => info!(
+                "{}Merged {} into {} (transaction {})",
+                Emoji::new("🚚 ", ""),
+                transfer.source,
+                primary,
+                hash
+            ),
+            Err(e) => {
+                warn!("Failed to merge {}: {}", transfer.source, e);
+
+                failures.push(e);
+            }
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        let n_failed = failures.len();
+
+        Err(failures
+            .remove(0)
+            .context(format!(
+                "Failed to merge {} of {} accounts",
+                n_failed,
+                plan.transfers.len()
+            ))
+            .into())
+    }
+}
+
+/// Creates, checks, signs, and publishes a single transfer of a consolidation plan. Returns the hash of the published
+/// transaction.
+async fn consolidate_transfer(
+    client: &dag::Client,
+    opts: &Opts,
+    c: &Consolidate,
+    primary: Address,
+    transfer: &consolidate::PlannedTransfer,
+) -> Result<Hash, failure::Error> {
+    let value = transfer.value.to_u128().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the balance is too large to move in one transaction",
+        )
+    })?;
+
+    // The node picks the nonce following the source's last transaction
+    let tx = client
+        .create_tx(
+            transfer.source.to_str(),
+            primary.to_str(),
+            value,
+            String::new(),
+            None,
+            None,
+        )
+        .await?;
+    tx.to_disk_at_data_directory(&opts.data_dir)?;
+
+    // Don't publish anything that the node would refuse (e.g. if the source has a transaction in flight already)
+    let report = client.validate_tx(&tx).await?;
+    if let Some(e) = preflight::check(&tx, &report).into_iter().next() {
+        return Err(e.into());
+    }
+
+    client
+        .sign_tx(
+            tx.hash.to_str(),
+            opts.data_dir.clone(),
+            None,
+            c.unlock_session.clone(),
+        )
+        .await?;
+    client
+        .publish_tx(tx.hash.to_str(), opts.data_dir.clone())
+        .await?;
+
+    Ok(tx.hash)
+}
+
+/// Cleans up the garbage that has accumulated in the data directory.
+async fn clean(opts: Opts, c: Clean) -> Result<(), failure::Error> {
+    let options = maintenance::SweepOptions {
+        min_age: Duration::from_secs(c.min_age_hours * 60 * 60),
+        include_keystore: c.keystore,
+    };
+
+    // Ask the node for the ledger, so that we know which keystore entries are in use
+    let ledger_addresses = if c.keystore {
+        let client = dag::Client::new(&opts.rpc_host_url)
+            .with_retry_policy(opts.retry_policy.clone())
+            .with_session(opts.session.clone());
+
+        match client.get().await {
+            Ok(nodes) => Some(maintenance::active_addresses(&nodes)),
+            Err(e) => {
+                error!(
+                    "Failed to load the DAG, so the keystore will be left alone: {}",
+                    e
+                );
+
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let garbage = maintenance::find_garbage(&opts.data_dir, ledger_addresses.as_ref(), &options);
+    if garbage.is_empty() {
+        info!("{}Nothing to clean up", Emoji::new("✨ ", ""));
+
+        return Ok(());
+    }
+
+    // Print out each of the artifacts
+    for artifact in &garbage {
+        println!(
+            "{:?}: {} ({} bytes)",
+            artifact.kind, artifact.path, artifact.bytes
+        );
+    }
+
+    if c.dry_run {
+        info!(
+            "{}Found {} artifacts to clean up (dry run; nothing was touched)",
+            Emoji::new("🔍 ", ""),
+            garbage.len()
+        );
+
+        return Ok(());
+    }
+
+    let freed = maintenance::remove(&opts.data_dir, &garbage)?;
+    info!(
+        "{}Cleaned up {} artifacts, freeing {} bytes",
+        Emoji::new("🧹 ", ""),
+        garbage.len(),
+        freed
+    );
+
+    Ok(())
+}
+
+/// Exports or imports a backup of the wallet in the data directory. Backups never touch the node, so they work whether
+/// or not it's running.
+async fn backup(opts: Opts, b: Backup) -> Result<(), failure::Error> {
+    match b {
+        Backup::Export(export) => {
+            let mut progress = Progress::new("Gathering the wallet", Some(3));
+            progress.set(0);
+
+            let contents = BackupContents::collect(&opts.data_dir)?;
+            progress.set(1);
+
+            // Stretching the password is what takes the longest
+            progress.set_label("Encrypting the backup");
+            let archive = BackupArchive::seal(&contents, &export.password, KdfParams::default())?
+                .to_bytes()?;
+            progress.set(2);
+
+            progress.set_label("Writing the backup");
+            fs::write(&export.path, &archive)?;
+            progress.set(3);
+            progress.finish();
+
+            info!(
+                "{}Backed up {} to {} ({} bytes)",
+                Emoji::new("💾 ", ""),
+                opts.data_dir,
+                export.path,
+                archive.len()
+            );
+        }
+        Backup::Import(import) => {
+            let mut progress = Progress::new("Reading the backup", Some(3));
+            progress.set(0);
+
+            let archive = BackupArchive::from_bytes(&fs::read(&import.path)?)?;
+            progress.set(1);
+
+            // Stretching the password is what takes the longest
+            progress.set_label("Decrypting the backup");
+            let contents = archive.open(&import.password)?;
+            progress.set(2);
+
+            progress.set_label("Restoring the wallet");
+            let summary = contents.restore(&opts.data_dir, import.overwrite)?;
+            progress.set(3);
+            progress.finish();
+
+            info!(
+                "{}Restored {} keystore files, {} config files, and {} contacts to {}",
+                Emoji::new("📦 ", ""),
+                summary.keystore_files,
+                summary.config_files,
+                summary.contacts,
+                opts.data_dir
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Exports the node's data to a file, reporting progress along the way.
+async fn export(opts: Opts, e: Export) -> Result<(), failure::Error> {
+    match e {
+        Export::History(history) => {
+            // Make a client for the DAG API
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            // The size of the DAG is only used to draw the progress bar, so it's fine to go without it
+            let height = peers::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .get_sync_status()
+                .await
+                .ok()
+                .map(|status| status.height as u64);
+
+            // Pick an interrupted export back up where the node saw it acknowledged, or start a fresh one
+            let (session, file) = match &history.resume {
+                Some(id) => {
+                    let session = match client.get_export(id).await {
+                        Ok(session) => session,
+                        Err(e) => return Err(e.context("Failed to resume the export").into()),
+                    };
+
+                    (
+                        session.clone(),
+                        truncate_lines(&history.path, session.exported)?,
+                    )
+                }
+                None => match client.open_export(None).await {
+                    Ok(session) => (session, fs::File::create(&history.path)?),
+                    Err(e) => return Err(e.context("Failed to open an export").into()),
+                },
+            };
+
+            let mut file = io::BufWriter::new(file);
+            let mut progress = Progress::new("Exporting the DAG", height);
+            let (mut exported, mut ack) = (session.exported, None);
+            progress.set(exported);
+
+            loop {
+                let page = match client
+                    .next_export_page(&session.id, ack.take(), Some(history.page_size))
+                    .await
+                {
+                    Ok(export) => export.page,
+                    Err(e) => {
+                        progress.finish();
+
+                        return Err(e
+                            .context(format!(
+                                "Failed to export the DAG after {} transactions (pick it back up with --resume {})",
+                                exported, session.id
+                            ))
+                            .into());
+                    }
+                };
+
+                for event in page.events.iter() {
+                    serde_json::to_writer(&mut file, event)?;
+                    file.write_all(b"\n")?;
+                }
+
+                // Only acknowledge the page once it's safely on the disk
+                file.flush()?;
+                file.get_ref().sync_data()?;
+
+                exported += page.events.len() as u64;
+                progress.set(exported);
+
+                // The cursor is handed out even once the replay has caught up, so stop at the first short page
+                match page.next_cursor {
+                    Some(cursor) if page.events.len() >= history.page_size => ack = Some(cursor),
+                    _ => break,
+                }
+            }
+
+            progress.finish();
+
+            // The node would drop the export eventually, but there's no reason to keep it around
+            if let Err(e) = client.close_export(&session.id).await {
+                warn!("Failed to close export {}: {}", session.id, e);
+            }
+
+            info!(
+                "{}Exported {} transactions to {}",
+                Emoji::new("📤 ", ""),
+                exported,
+                history.path
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Opens the file at the given path for appending, cutting it down to the given number of lines first, so that a
+/// resumed export doesn't repeat any transaction written after the node last saw a page acknowledged.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file
+/// * `lines` - The number of lines to keep
+fn truncate_lines(path: &str, lines: u64) -> Result<fs::File, failure::Error> {
+    let mut reader = io::BufReader::new(fs::File::open(path)?);
+    let (mut kept, mut len) = (0, 0);
+
+    while kept < lines {
+        let mut line = Vec::new();
+        let read = io::BufRead::read_until(&mut reader, b'\n', &mut line)?;
+
+        // The file is missing transactions that the node saw acknowledged, so it can't be resumed
+        if read == 0 || line.last() != Some(&b'\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} holds only {} of the {} transactions exported so far",
+                    path, kept, lines
+                ),
+            )
+            .into());
+        }
+
+        kept += 1;
+        len += read as u64;
+    }
+
+    let file = fs::OpenOptions::new().append(true).open(path)?;
+    file.set_len(len)?;
+
+    Ok(file)
+}
+
+/// Runs a diagnostic against the node.
+async fn debug(opts: Opts, d: Debug) -> Result<(), failure::Error> {
+    match d {
+        Debug::Replay(_) => {
+            // Make a client for the DAG API
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            let report = match client.verify().await {
+                Ok(report) => report,
+                Err(e) => return Err(e.context("Failed to re-execute the DAG").into()),
+            };
+
+            let divergence = match report.divergence {
+                Some(divergence) => divergence,
+                None => {
+                    info!(
+                        "{}Re-executed {} transactions; each of the {} parent states cited was reproduced",
+                        Emoji::new("✅ ", ""),
+                        report.executed,
+                        report.citations_checked
+                    );
+
+                    return Ok(());
+                }
+            };
+
+            error!(
+                "{}Transaction {} (index {}) cites parent state {}, but re-executing the DAG gives {}",
+                Emoji::new("🔀 ", ""),
+                divergence.tx_hash,
+                divergence.index,
+                divergence.cited_state_hash,
+                divergence.recomputed_state_hash
+            );
+
+            match divergence.stored_state_hash {
+                Some(stored) if divergence.diff.is_empty() => info!(
+                    "The states stored alongside the transaction's parents merge to {}, agreeing with the re-execution",
+                    stored
+                ),
+                Some(stored) => {
+                    info!(
+                        "The states stored alongside the transaction's parents merge to {}, which differs from the re-execution on {} accounts:",
+                        stored,
+                        divergence.diff.len()
+                    );
+
+                    // Print out each of the accounts that the states disagree on
+                    for diff in divergence.diff {
+                        println!(
+                            "{}: balance {} (stored) vs {} (recomputed), nonce {} (stored) vs {} (recomputed)",
+                            diff.address,
+                            diff.stored_balance
+                                .map_or("none".to_owned(), |balance| balance.to_string()),
+                            diff.recomputed_balance
+                                .map_or("none".to_owned(), |balance| balance.to_string()),
+                            diff.stored_nonce
+                                .map_or("none".to_owned(), |nonce| nonce.to_string()),
+                            diff.recomputed_nonce
+                                .map_or("none".to_owned(), |nonce| nonce.to_string())
+                        );
+                    }
+                }
+                None => info!("Some of the transaction's parents have no stored state to compare against"),
+            }
+        }
+        Debug::Locks(_) => {
+            // Make a client for the runtime API
+            let client = runtime::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            let report = match client.get_lock_metrics().await {
+                Ok(report) => report,
+                Err(e) => return Err(e.context("Failed to get the node's lock metrics").into()),
+            };
+
+            if report.holders.is_empty() {
+                info!(
+                    "{}Nobody is holding the {} lock",
+                    Emoji::new("🔓 ", ""),
+                    report.name
+                );
+            }
+
+            // Print out each of the callers holding, or stuck waiting on, the lock
+            for holder in report.holders {
+                println!(
+                    "{} {:?} lock for {}ms at {}",
+                    if holder.held { "holding" } else { "waiting on" },
+                    holder.mode,
+                    holder.for_ms,
+                    holder.caller
+                );
+            }
+
+            // Print out the callers that have waited on the lock longest
+            for metrics in report.metrics.iter().take(10) {
+                println!(
+                    "{} ({:?}): {} acquisitions, waited {}us in total ({}us at most), held {}us in total ({}us at most)",
+                    metrics.caller,
+                    metrics.mode,
+                    metrics.acquisitions,
+                    metrics.total_wait_us,
+                    metrics.max_wait_us,
+                    metrics.total_hold_us,
+                    metrics.max_hold_us
+                );
+            }
+        }
+        Debug::Trace(traceable) => {
+            // Make a client for the DAG API
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            let trace = match client
+                .trace(traceable.hash, Some(traceable.level.parse()?))
+                .await
+            {
+                Ok(trace) => trace,
+                Err(e) => return Err(e.context("Failed to trace the transaction").into()),
+            };
+
+            info!(
+                "{}Executed transaction {} on top of state {}: {:?}",
+                Emoji::new("🔬 ", ""),
+                trace.tx,
+                trace.parent_state_hash,
+                trace.status
+            );
+
+            // Print out each of the steps, along with any balances that they changed
+            for step in trace.steps {
+                println!(
+                    "#{} {}{}",
+                    step.index,
+                    step.op,
+                    if step.changed_state {
+                        ""
+                    } else {
+                        " (no change)"
+                    }
+                );
+
+                for change in step.balance_changes {
+                    println!(
+                        "    {}: {} -> {}",
+                        change.account, change.before, change.after
+                    );
+                }
+            }
+
+            info!("Resulting state: {}", trace.state_hash);
+        }
+        Debug::State(node) => {
+            // Make a client for the DAG API
+            let client = dag::Client::new(&opts.rpc_host_url)
+                .with_retry_policy(opts.retry_policy.clone())
+                .with_session(opts.session.clone());
+
+            let proof = match client.verify_state_at(&node.hash).await {
+                Ok(proof) => proof,
+                Err(e) => return Err(e.context("Failed to verify the node's state").into()),
+            };
+
+            match proof.recorded_state_hash {
+                Some(recorded) if proof.verified => info!(
+                    "{}Re-executed {} transactions; node {} (index {}) left behind state {}, as recorded",
+                    Emoji::new("✅ ", ""),
+                    proof.executed,
+                    proof.tx_hash,
+                    proof.index,
+                    recorded
+                ),
+                Some(recorded) => error!(
+                    "{}Node {} (index {}) recorded state {}, but re-executing its {} ancestors gives {}",
+                    Emoji::new("🔀 ", ""),
+                    proof.tx_hash,
+                    proof.index,
+                    recorded,
+                    proof.executed - 1,
+                    proof.recomputed_state_hash
+                ),
+                None => info!(
+                    "Node {} (index {}) has no recorded state to check; re-executing it gives {}",
+                    proof.tx_hash, proof.index, proof.recomputed_state_hash
+                ),
             }
         }
     }
@@ -445,6 +2863,27 @@ async fn publish(opts: Opts, p: Publish) -> Result<(), failure::Error> {
     Ok(())
 }
 
+/// Opens an admin session by signing the node's challenge with the admin account, and returns the session's token.
+async fn open_admin_session(opts: &Opts) -> Result<String, failure::Error> {
+    // The admin account has to be available locally, since we need to sign with it
+    let admin = account::Account::read_from_disk_at_data_directory(
+        parse_address(opts, &opts.admin_account).await?,
+        &opts.data_dir,
+    )?;
+
+    let session = auth::Client::new(&opts.rpc_host_url)
+        .with_retry_policy(opts.retry_policy.clone())
+        .login(&admin)
+        .await?;
+
+    debug!(
+        "Opened an admin session as '{}', valid until {}",
+        opts.admin_account, session.expires_at
+    );
+
+    Ok(session.token)
+}
+
 /// Applies the given options.
 fn use_options(mut opts: Opts) -> Result<Opts, failure::Error> {
     // Configure the logger
@@ -462,6 +2901,18 @@ fn use_options(mut opts: Opts) -> Result<Opts, failure::Error> {
         }
     }
 
+    // Make sure that errors can be printed in the format that the user asked for
+    opts.output
+        .parse::<OutputFormat>()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    // Use the retry settings that the user chose for each request
+    opts.retry_policy = RetryPolicy {
+        attempts: opts.rpc_attempts.max(1),
+        initial_backoff: Duration::from_millis(opts.rpc_backoff_ms),
+        ..Default::default()
+    };
+
     // If the user has chosen the default data dir, normalize it
     if opts.data_dir == "data" {
         // Normalize the data directory, and put it back in the config
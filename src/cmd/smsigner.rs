@@ -0,0 +1,97 @@
+/// SMSigner is the SummerCash remote signer: it holds the keys that a node votes and signs transactions with, so that
+/// they never enter the address space of the network-facing daemon.
+extern crate clap;
+
+#[macro_use]
+extern crate log;
+
+extern crate env_logger;
+
+use failure::Error;
+use summercash::accounts::{
+    account::{self, Account},
+    secrets, signer,
+};
+
+use clap::Clap;
+
+/// The SummerCash remote signer.
+#[derive(Clap)]
+#[clap(version = "1.0", author = "Dowland A.")]
+struct Opts {
+    /// Print debug info
+    #[clap(short = "d", long = "debug")]
+    debug: bool,
+
+    /// Prevents any non-critical information from being printed to the console
+    #[clap(short = "s", long = "silent")]
+    silent: bool,
+
+    /// The directory holding the keystore that the signer signs with, its token, and its spending policy
+    #[clap(long = "data-dir", default_value = "data")]
+    data_dir: String,
+
+    /// Listens on the unix domain socket at the given path. Defaults to signer.sock in the data directory.
+    #[clap(long = "socket", default_value = "none")]
+    socket: String,
+
+    /// Unlocks any locked accounts with passwords kept in the given secret store (none or keychain).
+    #[clap(long = "secret-store", default_value = "none")]
+    secret_store: String,
+}
+
+/// Starts the SMSigner remote signer.
+fn main() -> Result<(), Error> {
+    // Get any flags issued by the user
+    let opts: Opts = use_options(Opts::parse());
+
+    // Sign with each of the unlocked accounts, along with any that the secret store can unlock
+    let mut accounts: Vec<Account> =
+        account::get_all_unlocked_accounts_in_data_directory(&opts.data_dir)
+            .into_iter()
+            .filter_map(|address| {
+                Account::read_from_disk_at_data_directory(address, &opts.data_dir).ok()
+            })
+            .collect();
+    if let Some(store) = secrets::open(&opts.secret_store)? {
+        accounts.extend(secrets::unlock_accounts(&*store, &opts.data_dir));
+    }
+
+    // Nodes must present the token in order to use the signer
+    let token = signer::read_or_generate_token(&opts.data_dir)?;
+    info!(
+        "Nodes must be given a copy of {}/{} (pass it to smcd with --remote-signer-token)",
+        opts.data_dir,
+        signer::TOKEN_FILE
+    );
+
+    signer::SignerService::new(accounts, &token, &opts.data_dir).serve(&opts.socket)?;
+
+    Ok(())
+}
+
+/// Applies the given options, configuring the logger, and filling in the default paths.
+fn use_options(mut opts: Opts) -> Opts {
+    // Configure the logger
+    if !opts.silent {
+        env_logger::builder()
+            .filter_level(if opts.debug {
+                log::LevelFilter::Debug
+            } else {
+                log::LevelFilter::Info
+            })
+            .init();
+    }
+
+    // If the user has chosen the default data dir, normalize it
+    if opts.data_dir == "data" {
+        opts.data_dir = summercash::common::io::data_dir();
+    }
+
+    // Listen in the data directory, unless told otherwise
+    if opts.socket == "none" {
+        opts.socket = format!("{}/{}", opts.data_dir, signer::DEFAULT_SOCKET_FILE);
+    }
+
+    opts
+}
@@ -1,14 +1,200 @@
 use ed25519_dalek::{Keypair, PublicKey}; // Import the edwards25519 digital signature library
 
+use std::{fmt, str::FromStr}; // Import the formatting & string parsing libraries
+
 use super::super::crypto::blake3;
 use super::super::crypto::hash; // Import the hash library // Import the blake3 hashing library
 
 /// The length of a standard address (32 bytes).
 pub const ADDRESS_SIZE: usize = 32;
 
+/// The number of trailing bytes in a sub-address that store the sub-address's index.
+pub const SUB_ADDRESS_INDEX_SIZE: usize = 8;
+
 /// A standard 32-byte blake3 hash of an account's public key.
 pub type Address = hash::Hash;
 
+/// The separator between the parts of an address written in its typed form.
+pub const TYPED_ADDRESS_SEPARATOR: char = ':';
+
+/// What sits behind an address.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AddressKind {
+    /// An account held by a single keypair
+    User,
+
+    /// A contract deployed to the ledger
+    Contract,
+
+    /// An account held jointly by several keypairs
+    Multisig,
+
+    /// An address that nobody holds the key to, where funds are sent to be destroyed
+    Burn,
+}
+
+impl fmt::Display for AddressKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::User => "user",
+                Self::Contract => "contract",
+                Self::Multisig => "multisig",
+                Self::Burn => "burn",
+            }
+        )
+    }
+}
+
+impl FromStr for AddressKind {
+    type Err = AddressError;
+
+    /// Parses an address kind from its name (e.g. "contract").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "user" => Ok(Self::User),
+            "contract" => Ok(Self::Contract),
+            "multisig" => Ok(Self::Multisig),
+            "burn" => Ok(Self::Burn),
+            _ => Err(AddressError::UnknownKind { kind: s.to_owned() }),
+        }
+    }
+}
+
+/// An error encountered while parsing an address written in its typed form.
+#[derive(Debug, Fail, PartialEq)]
+pub enum AddressError {
+    #[fail(
+        display = "{} is not a typed address (expected network{}kind{}address)",
+        address, TYPED_ADDRESS_SEPARATOR, TYPED_ADDRESS_SEPARATOR
+    )]
+    Malformed {
+        address: String, // The address that was given
+    },
+    #[fail(
+        display = "unknown address kind '{}' (expected user, contract, multisig, or burn)",
+        kind
+    )]
+    UnknownKind {
+        kind: String, // The kind that was given
+    },
+    #[fail(
+        display = "{} doesn't decode to a full {}-byte address",
+        address, ADDRESS_SIZE
+    )]
+    InvalidAddress {
+        address: String, // The base58-encoded address that was given
+    },
+    #[fail(display = "the address belongs to network {}, not {}", found, expected)]
+    WrongNetwork {
+        expected: String, // The network that the address was meant for
+        found: String,    // The network that the address names
+    },
+}
+
+/// An address, along with the network that it belongs to and what sits behind it. Written as network:kind:address
+/// (e.g. andromeda:user:FVPfbg9bK7mj7jnaSRXhuVcVakkXcjMPgSwxmauUofYf), so that an address meant for one network, or a
+/// contract, isn't mistaken for another. The ledger only ever holds the bare address.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedAddress {
+    /// The name of the network that the address belongs to
+    pub network: String,
+
+    /// What sits behind the address
+    pub kind: AddressKind,
+
+    /// The bare address
+    pub address: Address,
+}
+
+/// Implement a set of typed address helper methods.
+impl TypedAddress {
+    /// Makes sure that the address belongs to the given network. Results in the bare address if it does.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The name of the network that the address is meant for
+    pub fn on_network(&self, network: &str) -> Result<Address, AddressError> {
+        if self.network != network {
+            return Err(AddressError::WrongNetwork {
+                expected: network.to_owned(),
+                found: self.network.clone(),
+            });
+        }
+
+        Ok(self.address)
+    }
+}
+
+impl fmt::Display for TypedAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}{}",
+            self.network, TYPED_ADDRESS_SEPARATOR, self.kind, TYPED_ADDRESS_SEPARATOR, self.address
+        )
+    }
+}
+
+impl FromStr for TypedAddress {
+    type Err = AddressError;
+
+    /// Parses an address written in its typed form (network:kind:address).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.trim().split(TYPED_ADDRESS_SEPARATOR).collect();
+        if parts.len() != 3 || parts[0].is_empty() {
+            return Err(AddressError::Malformed {
+                address: s.to_owned(),
+            });
+        }
+
+        // Don't let a malformed address decode to the zero address, where funds would be lost
+        let decoded = bs58::decode(parts[2]).into_vec().unwrap_or_default();
+        if decoded.len() != ADDRESS_SIZE {
+            return Err(AddressError::InvalidAddress {
+                address: parts[2].to_owned(),
+            });
+        }
+
+        Ok(Self {
+            network: parts[0].to_owned(),
+            kind: parts[1].parse()?,
+            address: Address::new(decoded),
+        })
+    }
+}
+
+impl From<TypedAddress> for Address {
+    /// Strips the network & kind from the typed address.
+    fn from(typed: TypedAddress) -> Self {
+        typed.address
+    }
+}
+
+/// Decodes an address given either bare, or in its typed form. Typed addresses meant for a network other than the
+/// given one are refused, as is anything that doesn't decode to a full address.
+///
+/// # Arguments
+///
+/// * `address` - The bare, or typed, address
+/// * `network` - The name of the network that the address is meant for
+pub fn parse(address: &str, network: &str) -> Result<Address, AddressError> {
+    if address.contains(TYPED_ADDRESS_SEPARATOR) {
+        return address.parse::<TypedAddress>()?.on_network(network);
+    }
+
+    let decoded = bs58::decode(address.trim()).into_vec().unwrap_or_default();
+    if decoded.len() != ADDRESS_SIZE {
+        return Err(AddressError::InvalidAddress {
+            address: address.to_owned(),
+        });
+    }
+
+    Ok(Address::new(decoded))
+}
+
 /* BEGIN EXPORTED METHODS */
 
 impl Address {
@@ -67,6 +253,75 @@ impl Address {
     pub fn from_key_pair(key_pair: &Keypair) -> Address {
         blake3::hash_slice(&key_pair.public.to_bytes()) // Return hashed public key
     }
+
+    /// Derive a deposit sub-address from a parent address and an index. The first 24 bytes of
+    /// the sub-address are a tag derived from the parent address, and the last 8 bytes store the
+    /// index, so that the index can be recovered from the sub-address alone. Transactions sent from
+    /// a sub-address may be signed with the parent account's keypair.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the sub-address
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use summercash::common::address; // Import the address utility
+    ///
+    /// let parent = address::Address::from("FVPfbg9bK7mj7jnaSRXhuVcVakkXcjMPgSwxmauUofYf"); // Get a parent address
+    ///
+    /// let deposit_address = parent.derive_sub_address(42); // Derive a sub-address
+    /// assert_eq!(deposit_address.sub_address_index(), 42); // The index can be recovered
+    /// ```
+    pub fn derive_sub_address(&self, index: u64) -> Address {
+        let tag = blake3::hash_slice(&[b"sub_address".as_ref(), &self[..]].concat()); // Tag the parent
+
+        let mut sub_address = tag[..ADDRESS_SIZE - SUB_ADDRESS_INDEX_SIZE].to_vec(); // Keep the tag prefix
+        sub_address.extend_from_slice(&index.to_be_bytes()); // Append the index
+
+        Address::new(sub_address) // Return the sub-address
+    }
+
+    /// Get the index stored in the trailing bytes of a sub-address. Note: every address has an
+    /// index, so is_sub_address_of() should be used to check that an address is actually a
+    /// sub-address of some parent.
+    pub fn sub_address_index(&self) -> u64 {
+        let mut index_bytes = [0u8; SUB_ADDRESS_INDEX_SIZE]; // Initialize a buffer to store the index in
+        index_bytes.copy_from_slice(&self[ADDRESS_SIZE - SUB_ADDRESS_INDEX_SIZE..]);
+
+        u64::from_be_bytes(index_bytes) // Return the index
+    }
+
+    /// Writes the address in its typed form, naming the network that it belongs to and what sits behind it.
+    ///
+    /// # Arguments
+    ///
+    /// * `network` - The name of the network that the address belongs to
+    /// * `kind` - What sits behind the address
+    pub fn typed(&self, network: &str, kind: AddressKind) -> TypedAddress {
+        TypedAddress {
+            network: network.to_owned(),
+            kind,
+            address: *self,
+        }
+    }
+
+    /// Check whether or not the address is a sub-address of the given parent address. Returns the
+    /// index of the sub-address if it is.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The address of the account that may have derived this address
+    pub fn is_sub_address_of(&self, parent: &Address) -> Option<u64> {
+        let index = self.sub_address_index(); // Recover the index of the address
+
+        // Make sure the parent would actually derive this address at the index
+        if parent.derive_sub_address(index) == *self {
+            Some(index)
+        } else {
+            None
+        }
+    }
 }
 
 /* END EXPORTED METHODS */
@@ -113,6 +368,32 @@ mod tests {
         ); // Ensure our original input was preserved
     }
 
+    #[test]
+    fn test_typed_address() {
+        let address = Address::from("FVPfbg9bK7mj7jnaSRXhuVcVakkXcjMPgSwxmauUofYf");
+        let typed = address.typed("andromeda", AddressKind::Contract);
+
+        assert_eq!(
+            typed.to_string(),
+            "andromeda:contract:FVPfbg9bK7mj7jnaSRXhuVcVakkXcjMPgSwxmauUofYf"
+        ); // Ensure the network & kind are written out
+        assert_eq!(typed.to_string().parse::<TypedAddress>(), Ok(typed.clone())); // Ensure the typed form round-trips
+        assert_eq!(typed.on_network("andromeda"), Ok(address)); // Ensure the network is checked
+        assert!(typed.on_network("vela").is_err());
+
+        assert_eq!(parse(&typed.to_string(), "andromeda"), Ok(address)); // Ensure typed forms stand in for bare ones
+        assert_eq!(parse(&address.to_str(), "vela"), Ok(address));
+        assert!(parse(&typed.to_string(), "vela").is_err()); // Ensure typed forms are held to their network
+        assert!(parse("FVPfbg9b", "andromeda").is_err()); // Ensure truncated bare addresses are refused
+
+        assert!(
+            "andromeda:wallet:FVPfbg9bK7mj7jnaSRXhuVcVakkXcjMPgSwxmauUofYf"
+                .parse::<TypedAddress>()
+                .is_err()
+        ); // Ensure unknown kinds are refused
+        assert!("andromeda:user:FVPfbg9b".parse::<TypedAddress>().is_err()); // Ensure truncated addresses are refused
+    }
+
     #[test]
     fn test_from_public_key() {
         let mut csprng = OsRng {}; // Generate source of randomness
@@ -138,4 +419,18 @@ mod tests {
             blake3::hash_slice(&keypair.public.to_bytes()).to_str()
         ); // Ensure address properly derived
     }
+
+    #[test]
+    fn test_derive_sub_address() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+        let parent = Address::from_key_pair(&Keypair::generate(&mut csprng)); // Derive a parent address
+        let other = Address::from_key_pair(&Keypair::generate(&mut csprng)); // Derive another address
+
+        let sub_address = parent.derive_sub_address(1337); // Derive a sub-address
+
+        assert_eq!(sub_address.sub_address_index(), 1337); // Ensure the index is recoverable
+        assert_eq!(sub_address.is_sub_address_of(&parent), Some(1337)); // Ensure maps back to the parent
+        assert_eq!(sub_address.is_sub_address_of(&other), None); // Ensure isn't owned by another account
+        assert_ne!(parent.derive_sub_address(1338), sub_address); // Ensure indexes are distinct
+    }
 }
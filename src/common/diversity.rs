@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::{
+    collections::HashSet,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+}; // Import the set & IP address types
+
+/// The number of leading bits that IPv4 addresses in the same subnet share.
+pub const IPV4_SUBNET_BITS: u8 = 24;
+
+/// The number of leading bits that IPv6 addresses in the same subnet share.
+pub const IPV6_SUBNET_BITS: u8 = 48;
+
+/// How many distinct peers (and subnets) must have relayed the votes or records behind a decision before the node acts
+/// on it, so that a single host running many peers can't decide for it. Neither is required by default.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct DiversityPolicy {
+    /// The number of distinct peer identities that must have relayed the votes or records, besides the node itself
+    pub min_peers: usize,
+
+    /// The number of distinct subnets (/24 for IPv4, /48 for IPv6) that those peers must be reachable in
+    pub min_subnets: usize,
+}
+
+/// Implement a set of diversity policy helper methods.
+impl DiversityPolicy {
+    /// Checks whether or not the given witnesses are diverse enough to act on.
+    ///
+    /// # Arguments
+    ///
+    /// * `witnesses` - The peers that relayed the votes or records behind the decision
+    pub fn admits(&self, witnesses: &Witnesses) -> bool {
+        witnesses.peers() >= self.min_peers && witnesses.subnets() >= self.min_subnets
+    }
+}
+
+/// A peer that relayed a vote or record, along with the subnet that it's reachable in, if we know an address for it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Witness {
+    /// The identity of the peer
+    pub peer: String,
+
+    /// The subnet that the peer is reachable in, if we know an address for it
+    pub subnet: Option<String>,
+}
+
+/// Implement a set of witness helper methods.
+impl Witness {
+    /// Makes a witness for the peer with the given identity, reachable at the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The identity of the peer
+    /// * `ip` - The address that the peer is reachable at, if we know one
+    pub fn new(peer: String, ip: Option<IpAddr>) -> Self {
+        Self {
+            peer,
+            subnet: ip.as_ref().map(subnet_of),
+        }
+    }
+}
+
+/// The distinct peers, and subnets, that relayed the votes or records behind a decision.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Witnesses {
+    /// The identity of each peer
+    peers: HashSet<String>,
+
+    /// Each subnet that the peers are reachable in
+    subnets: HashSet<String>,
+}
+
+/// Implement a set of witness set helper methods.
+impl Witnesses {
+    /// Counts the given peer as a witness. Counting the same peer twice is harmless.
+    ///
+    /// # Arguments
+    ///
+    /// * `witness` - The peer
+    pub fn add(&mut self, witness: Witness) {
+        if let Some(subnet) = witness.subnet {
+            self.subnets.insert(subnet);
+        }
+
+        self.peers.insert(witness.peer);
+    }
+
+    /// Gets the number of distinct peers counted.
+    pub fn peers(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Gets the number of distinct subnets that the peers counted are reachable in.
+    pub fn subnets(&self) -> usize {
+        self.subnets.len()
+    }
+}
+
+/// Gets the subnet that the given address belongs to (e.g. 203.0.113.0/24).
+///
+/// # Arguments
+///
+/// * `ip` - The address
+pub fn subnet_of(ip: &IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => {
+            let mask = u32::MAX << (32 - IPV4_SUBNET_BITS as u32);
+
+            format!(
+                "{}/{}",
+                Ipv4Addr::from(u32::from(*ip) & mask),
+                IPV4_SUBNET_BITS
+            )
+        }
+        IpAddr::V6(ip) => {
+            let mask = u128::MAX << (128 - IPV6_SUBNET_BITS as u32);
+
+            format!(
+                "{}/{}",
+                Ipv6Addr::from(u128::from(*ip) & mask),
+                IPV6_SUBNET_BITS
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_admits() {
+        let policy = DiversityPolicy {
+            min_peers: 3,
+            min_subnets: 2,
+        };
+        let mut witnesses = Witnesses::default();
+
+        // Many peers on the same host (or /24) only count as one subnet
+        for (peer, ip) in &[
+            ("a", "203.0.113.7"),
+            ("b", "203.0.113.8"),
+            ("a", "203.0.113.7"),
+        ] {
+            witnesses.add(Witness::new(peer.to_string(), ip.parse().ok()));
+        }
+        assert_eq!((witnesses.peers(), witnesses.subnets()), (2, 1));
+        assert!(!policy.admits(&witnesses));
+
+        // Peers whose addresses we don't know count towards the peers, but not the subnets
+        witnesses.add(Witness::new("c".to_owned(), None));
+        assert!(!policy.admits(&witnesses));
+
+        witnesses.add(Witness::new("d".to_owned(), "2001:db8:1::1".parse().ok()));
+        assert!(policy.admits(&witnesses));
+        assert!(DiversityPolicy::default().admits(&Witnesses::default()));
+
+        assert_eq!(
+            subnet_of(&"2001:db8:1:2::1".parse().unwrap()),
+            "2001:db8:1::/48"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use std::{fs, io}; // Import the filesystem libraries
+
+/// The name of the file in a data directory's config folder naming the network that its keystore belongs to.
+pub const KEYSTORE_NETWORK_FILE: &str = "keystore_network";
+
+/// An error encountered while making sure that a data directory belongs to the network that a tool was pointed at.
+#[derive(Debug, Fail, PartialEq)]
+pub enum IsolationError {
+    #[fail(
+        display = "the keystore in {} belongs to network {}, not {}; pass --network {}, or choose a different --data-dir",
+        data_dir, bound, network, bound
+    )]
+    KeystoreNetworkMismatch {
+        data_dir: String, // The data directory holding the keystore
+        bound: String,    // The network that the keystore belongs to
+        network: String,  // The network that the tool was pointed at
+    },
+    #[fail(
+        display = "the data directory {} only holds ledgers for {}, not {}; pass --network with one of those, or choose a different --data-dir",
+        data_dir, found, network
+    )]
+    LedgerNetworkMismatch {
+        data_dir: String, // The data directory holding the ledgers
+        found: String,    // The networks whose ledgers the data directory holds
+        network: String,  // The network that the tool was pointed at
+    },
+    #[fail(
+        display = "the node at {} serves network {}, not {}; point --remote-host-url at a {} node",
+        url, node_network, network, network
+    )]
+    NodeNetworkMismatch {
+        url: String,          // The node that was asked
+        node_network: String, // The network that the node serves
+        network: String,      // The network that the tool was pointed at
+    },
+    #[fail(display = "failed to read the network of {}: {}", path, error)]
+    Io {
+        path: String,  // The file or directory that couldn't be read
+        error: String, // The underlying error
+    },
+}
+
+/// Gets the network that the keystore in the given data directory belongs to, if it has been bound to one.
+///
+/// # Arguments
+///
+/// * `data_dir` - The data directory holding the keystore
+pub fn keystore_network(data_dir: &str) -> Result<Option<String>, IsolationError> {
+    let path = format!("{}/config/{}", data_dir, KEYSTORE_NETWORK_FILE);
+
+    match fs::read_to_string(&path) {
+        Ok(network) => Ok(Some(network.trim().to_owned())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(IsolationError::Io {
+            path,
+            error: e.to_string(),
+        }),
+    }
+}
+
+/// Binds the keystore in the given data directory to the given network, unless it already belongs to one. Results in
+/// an error if it belongs to a different network.
+///
+/// # Arguments
+///
+/// * `data_dir` - The data directory holding the keystore
+/// * `network` - The network that the keystore should belong to
+pub fn bind_keystore(data_dir: &str, network: &str) -> Result<(), IsolationError> {
+    if keystore_network(data_dir)?.is_some() {
+        return check_keystore(data_dir, network);
+    }
+
+    let failed = |path: String, e: io::Error| IsolationError::Io {
+        path,
+        error: e.to_string(),
+    };
+
+    let config_dir = format!("{}/config", data_dir);
+    fs::create_dir_all(&config_dir).map_err(|e| failed(config_dir.clone(), e))?;
+
+    let path = format!("{}/{}", config_dir, KEYSTORE_NETWORK_FILE);
+    fs::write(&path, network).map_err(|e| failed(path.clone(), e))
+}
+
+/// Makes sure that the keystore in the given data directory doesn't belong to a network other than the given one.
+///
+/// # Arguments
+///
+/// * `data_dir` - The data directory holding the keystore
+/// * `network` - The network that the tool was pointed at
+pub fn check_keystore(data_dir: &str, network: &str) -> Result<(), IsolationError> {
+    match keystore_network(data_dir)? {
+        Some(bound) if bound != network => Err(IsolationError::KeystoreNetworkMismatch {
+            data_dir: data_dir.to_owned(),
+            bound,
+            network: network.to_owned(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Gets the networks whose ledgers are held in the given data directory, in alphabetical order.
+///
+/// # Arguments
+///
+/// * `data_dir` - The data directory holding the ledgers
+pub fn ledger_networks(data_dir: &str) -> Result<Vec<String>, IsolationError> {
+    let db_dir = format!("{}/db", data_dir);
+
+    let entries = match fs::read_dir(&db_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(IsolationError::Io {
+                path: db_dir,
+                error: e.to_string(),
+            })
+        }
+    };
+
+    let mut networks: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_owned))
+        .collect();
+    networks.sort();
+
+    Ok(networks)
+}
+
+/// Makes sure that neither the keystore nor the ledgers in the given data directory belong to a network other than the
+/// given one. A data directory that doesn't hold anything yet belongs to every network.
+///
+/// # Arguments
+///
+/// * `data_dir` - The data directory
+/// * `network` - The network that the tool was pointed at
+pub fn check(data_dir: &str, network: &str) -> Result<(), IsolationError> {
+    check_keystore(data_dir, network)?;
+
+    let networks = ledger_networks(data_dir)?;
+    if !networks.is_empty() && !networks.iter().any(|found| found == network) {
+        return Err(IsolationError::LedgerNetworkMismatch {
+            data_dir: data_dir.to_owned(),
+            found: networks.join(", "),
+            network: network.to_owned(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::io; // Import the io module
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_check() {
+        let data_dir = format!("{}/.tests/isolation", io::data_dir()); // Get a test directory
+        let _ = fs::remove_dir_all(&data_dir);
+
+        // Fresh data directories belong to every network
+        assert!(check(&data_dir, "andromeda").is_ok());
+
+        // Once bound, the keystore refuses tools pointed at any other network
+        bind_keystore(&data_dir, "andromeda").unwrap();
+        assert!(bind_keystore(&data_dir, "andromeda").is_ok());
+        assert_eq!(
+            check(&data_dir, "testnet"),
+            Err(IsolationError::KeystoreNetworkMismatch {
+                data_dir: data_dir.clone(),
+                bound: "andromeda".to_owned(),
+                network: "testnet".to_owned(),
+            })
+        );
+
+        // So does a data directory holding only other networks' ledgers
+        fs::create_dir_all(format!("{}/db/cassiopeia", data_dir)).unwrap();
+        match check(&data_dir, "andromeda") {
+            Err(IsolationError::LedgerNetworkMismatch { found, .. }) => {
+                assert_eq!(found, "cassiopeia")
+            }
+            other => panic!("expected a ledger mismatch, got {:?}", other),
+        }
+        fs::create_dir_all(format!("{}/db/andromeda", data_dir)).unwrap();
+        assert!(check(&data_dir, "andromeda").is_ok());
+    }
+}
@@ -0,0 +1,383 @@
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    panic::Location,
+    sync::{LockResult, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    time::{Duration, Instant},
+}; // Import the synchronization & time libraries
+
+/// The number of milliseconds that a lock may be waited on, or held, before a warning is logged.
+pub const SLOW_LOCK_WARNING_MS: u64 = 1000;
+
+/// Whether a lock is shared with other readers, or held exclusively.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LockMode {
+    /// The lock is shared with any other readers
+    Read,
+
+    /// The lock is held exclusively
+    Write,
+}
+
+/// A caller that is holding, or waiting on, a lock.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LockHolder {
+    /// The place in the source that the lock was asked for at, written as file:line:column
+    pub caller: String,
+
+    /// Whether the caller asked to read or to write
+    pub mode: LockMode,
+
+    /// Whether the caller has the lock yet, or is still waiting on it
+    pub held: bool,
+
+    /// The number of milliseconds that the caller has been holding, or waiting on, the lock
+    pub for_ms: u64,
+}
+
+/// How long a single caller has spent waiting on, and holding, a lock.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CallerMetrics {
+    /// The place in the source that the lock was asked for at, written as file:line:column
+    pub caller: String,
+
+    /// Whether the caller asked to read or to write
+    pub mode: LockMode,
+
+    /// The number of times that the caller has acquired the lock
+    pub acquisitions: u64,
+
+    /// The total number of microseconds that the caller has spent waiting on the lock
+    pub total_wait_us: u64,
+
+    /// The greatest number of microseconds that the caller has waited on the lock at once
+    pub max_wait_us: u64,
+
+    /// The total number of microseconds that the caller has held the lock for
+    pub total_hold_us: u64,
+
+    /// The greatest number of microseconds that the caller has held the lock for at once
+    pub max_hold_us: u64,
+}
+
+/// A snapshot of who's using a lock, and how contended it has been.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LockReport {
+    /// The name of the lock
+    pub name: String,
+
+    /// Each of the callers holding, or waiting on, the lock right now, those that have been at it longest first
+    pub holders: Vec<LockHolder>,
+
+    /// How long each of the callers that have ever acquired the lock spent waiting on, and holding, it, those that
+    /// have waited longest in total first
+    pub metrics: Vec<CallerMetrics>,
+}
+
+/// A caller that is holding, or waiting on, a lock.
+struct Activity {
+    /// The place in the source that the lock was asked for at
+    caller: &'static Location<'static>,
+
+    /// Whether the caller asked to read or to write
+    mode: LockMode,
+
+    /// The time at which the caller asked for, or acquired, the lock
+    since: Instant,
+
+    /// Whether the caller has the lock yet
+    held: bool,
+}
+
+/// What's known about the use of a lock so far.
+#[derive(Default)]
+struct Instruments {
+    /// The identifier that will be given to the next acquisition
+    next_id: u64,
+
+    /// Each of the callers holding, or waiting on, the lock, by the identifier of their acquisition
+    active: HashMap<u64, Activity>,
+
+    /// How long each caller has spent waiting on, and holding, the lock
+    metrics: HashMap<(&'static Location<'static>, LockMode), CallerMetrics>,
+}
+
+/// A reader-writer lock that keeps track of who's holding, or waiting on, it, and how long they spent doing so, so
+/// that stalls can be traced back to the caller responsible. Callers are identified by the place in the source that
+/// they asked for the lock at.
+pub struct InstrumentedRwLock<T> {
+    /// The name that the lock is reported under
+    name: &'static str,
+
+    /// The underlying lock
+    inner: RwLock<T>,
+
+    /// What's known about the use of the lock so far. This is never held while waiting on the lock itself, so it can
+    /// always be inspected, even while the lock is stalled.
+    instruments: Mutex<Instruments>,
+}
+
+/// A guard releasing an instrumented lock, and recording how long it was held for, once dropped.
+pub struct InstrumentedGuard<'a, T, G> {
+    /// The guard of the underlying lock
+    guard: G,
+
+    /// The lock that the guard releases
+    lock: &'a InstrumentedRwLock<T>,
+
+    /// The identifier of the acquisition
+    id: u64,
+}
+
+/// A guard sharing an instrumented lock with any other readers.
+pub type InstrumentedReadGuard<'a, T> = InstrumentedGuard<'a, T, RwLockReadGuard<'a, T>>;
+
+/// A guard holding an instrumented lock exclusively.
+pub type InstrumentedWriteGuard<'a, T> = InstrumentedGuard<'a, T, RwLockWriteGuard<'a, T>>;
+
+impl<'a, T, G: Deref<Target = T>> Deref for InstrumentedGuard<'a, T, G> {
+    type Target = T;
+
+    /// Gets a reference to the value behind the lock.
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<'a, T, G: DerefMut<Target = T>> DerefMut for InstrumentedGuard<'a, T, G> {
+    /// Gets a mutable reference to the value behind the lock.
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<'a, T, G> Drop for InstrumentedGuard<'a, T, G> {
+    /// Records how long the lock was held for. The underlying guard is dropped right after.
+    fn drop(&mut self) {
+        self.lock.released(self.id);
+    }
+}
+
+/// Implement a set of instrumented lock helper methods.
+impl<T> InstrumentedRwLock<T> {
+    /// Initializes a new instrumented lock around the given value.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name that the lock is reported under
+    /// * `value` - The value that the lock guards
+    pub fn new(name: &'static str, value: T) -> Self {
+        Self {
+            name,
+            inner: RwLock::new(value),
+            instruments: Mutex::new(Instruments::default()),
+        }
+    }
+
+    /// Acquires the lock, shared with any other readers, blocking until it's available.
+    #[track_caller]
+    pub fn read(&self) -> LockResult<InstrumentedReadGuard<'_, T>> {
+        let id = self.waiting(Location::caller(), LockMode::Read);
+
+        self.wrap(id, self.inner.read())
+    }
+
+    /// Acquires the lock exclusively, blocking until it's available.
+    #[track_caller]
+    pub fn write(&self) -> LockResult<InstrumentedWriteGuard<'_, T>> {
+        let id = self.waiting(Location::caller(), LockMode::Write);
+
+        self.wrap(id, self.inner.write())
+    }
+
+    /// Takes a snapshot of who's using the lock, and how contended it has been.
+    pub fn report(&self) -> LockReport {
+        let instruments = self.instruments();
+
+        let mut holders: Vec<LockHolder> = instruments
+            .active
+            .values()
+            .map(|activity| LockHolder {
+                caller: activity.caller.to_string(),
+                mode: activity.mode,
+                held: activity.held,
+                for_ms: millis(activity.since.elapsed()),
+            })
+            .collect();
+        holders.sort_by(|a, b| b.for_ms.cmp(&a.for_ms));
+
+        let mut metrics: Vec<CallerMetrics> = instruments.metrics.values().cloned().collect();
+        metrics.sort_by(|a, b| b.total_wait_us.cmp(&a.total_wait_us));
+
+        LockReport {
+            name: self.name.to_owned(),
+            holders,
+            metrics,
+        }
+    }
+
+    /// Locks what's known about the use of the lock. A panic while it was held can't leave it in a state worth
+    /// refusing to read, so poisoning is ignored.
+    fn instruments(&self) -> MutexGuard<'_, Instruments> {
+        match self.instruments.lock() {
+            Ok(instruments) => instruments,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Records that the given caller has started waiting on the lock, and gets the identifier of its acquisition.
+    fn waiting(&self, caller: &'static Location<'static>, mode: LockMode) -> u64 {
+        let mut instruments = self.instruments();
+
+        let id = instruments.next_id;
+        instruments.next_id = id.wrapping_add(1);
+        instruments.active.insert(
+            id,
+            Activity {
+                caller,
+                mode,
+                since: Instant::now(),
+                held: false,
+            },
+        );
+
+        id
+    }
+
+    /// Records that the acquisition with the given identifier has the lock, and wraps its guard.
+    fn wrap<G>(&self, id: u64, result: LockResult<G>) -> LockResult<InstrumentedGuard<'_, T, G>> {
+        self.acquired(id);
+
+        match result {
+            Ok(guard) => Ok(InstrumentedGuard {
+                guard,
+                lock: self,
+                id,
+            }),
+            Err(poisoned) => Err(PoisonError::new(InstrumentedGuard {
+                guard: poisoned.into_inner(),
+                lock: self,
+                id,
+            })),
+        }
+    }
+
+    /// Records how long the acquisition with the given identifier waited on the lock.
+    fn acquired(&self, id: u64) {
+        let mut instruments = self.instruments();
+
+        let (caller, mode, waited) = match instruments.active.get_mut(&id) {
+            Some(activity) => {
+                let waited = activity.since.elapsed();
+                activity.since = Instant::now();
+                activity.held = true;
+
+                (activity.caller, activity.mode, waited)
+            }
+            None => return,
+        };
+
+        if millis(waited) >= SLOW_LOCK_WARNING_MS {
+            warn!(
+                "Waited {}ms for the {} ({:?}) at {}",
+                millis(waited),
+                self.name,
+                mode,
+                caller
+            );
+        }
+
+        let metrics = instruments
+            .metrics
+            .entry((caller, mode))
+            .or_insert_with(|| CallerMetrics {
+                caller: caller.to_string(),
+                mode,
+                acquisitions: 0,
+                total_wait_us: 0,
+                max_wait_us: 0,
+                total_hold_us: 0,
+                max_hold_us: 0,
+            });
+        metrics.acquisitions += 1;
+        metrics.total_wait_us = metrics.total_wait_us.saturating_add(micros(waited));
+        metrics.max_wait_us = metrics.max_wait_us.max(micros(waited));
+    }
+
+    /// Records how long the acquisition with the given identifier held the lock, and forgets it.
+    fn released(&self, id: u64) {
+        let mut instruments = self.instruments();
+
+        let activity = match instruments.active.remove(&id) {
+            Some(activity) => activity,
+            None => return,
+        };
+        let held = activity.since.elapsed();
+
+        if millis(held) >= SLOW_LOCK_WARNING_MS {
+            warn!(
+                "Held the {} ({:?}) for {}ms at {}",
+                self.name,
+                activity.mode,
+                millis(held),
+                activity.caller
+            );
+        }
+
+        if let Some(metrics) = instruments
+            .metrics
+            .get_mut(&(activity.caller, activity.mode))
+        {
+            metrics.total_hold_us = metrics.total_hold_us.saturating_add(micros(held));
+            metrics.max_hold_us = metrics.max_hold_us.max(micros(held));
+        }
+    }
+}
+
+/// Converts the given duration into a whole number of milliseconds.
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+/// Converts the given duration into a whole number of microseconds.
+fn micros(duration: Duration) -> u64 {
+    duration.as_micros() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_report() {
+        let lock = InstrumentedRwLock::new("test lock", 0);
+
+        {
+            let mut guard = lock.write().unwrap();
+            *guard += 1;
+
+            // The writer shows up as holding the lock until it lets go
+            let report = lock.report();
+            assert_eq!(report.name, "test lock");
+            assert_eq!(report.holders.len(), 1);
+            assert_eq!(report.holders[0].mode, LockMode::Write);
+            assert!(report.holders[0].held);
+            assert!(report.holders[0].caller.contains("lock.rs"));
+        }
+
+        assert_eq!(*lock.read().unwrap(), 1);
+        assert_eq!(*lock.read().unwrap(), 1);
+
+        // Each call site is tracked on its own
+        let report = lock.report();
+        assert!(report.holders.is_empty());
+        assert_eq!(report.metrics.len(), 3);
+        assert!(report
+            .metrics
+            .iter()
+            .all(|metrics| metrics.acquisitions == 1));
+    }
+}
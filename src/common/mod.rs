@@ -1,3 +1,7 @@
 pub mod address; // Export the address types & utilities module
+pub mod diversity; // Export the peer diversity safeguards
 pub mod fink; // Export the fink unit conversion utilities module
 pub mod io; // Export the io definitions module
+pub mod isolation; // Export the per-network data directory checks
+pub mod lock; // Export the instrumented lock module
+pub mod notes; // Export the local notes store module
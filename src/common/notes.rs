@@ -0,0 +1,113 @@
+use std::{collections::HashMap, fs, io, io::Write}; // Import the filesystem library
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::super::crypto::hash::Hash; // Import the hash type
+
+/// The name of the file in a data directory that local notes are stored in.
+pub const NOTES_FILE: &str = "notes.json";
+
+/// A local annotation attached to a transaction hash or an address. Notes never leave the node, and have no bearing
+/// on consensus.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Note {
+    /// A short, human-readable name for the subject (e.g. "rent payment")
+    pub label: String,
+
+    /// Any longer-form text the user wants to keep alongside the label
+    #[serde(default)]
+    pub memo: String,
+}
+
+/// A set of notes, keyed by the transaction hash or address they describe.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct NoteStore {
+    /// Each of the notes in the store
+    #[serde(default)]
+    pub notes: HashMap<Hash, Note>,
+}
+
+/// Implement a set of note store helper methods.
+impl NoteStore {
+    /// Gets the note attached to the given transaction hash or address, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `subject` - The transaction hash or address that the note describes
+    pub fn get(&self, subject: &Hash) -> Option<&Note> {
+        self.notes.get(subject)
+    }
+
+    /// Attaches a note to the given transaction hash or address, replacing any existing note.
+    ///
+    /// # Arguments
+    ///
+    /// * `subject` - The transaction hash or address that the note describes
+    /// * `note` - The note that should be attached to the subject
+    pub fn set(&mut self, subject: Hash, note: Note) {
+        self.notes.insert(subject, note);
+    }
+
+    /// Removes the note attached to the given transaction hash or address, and returns it.
+    ///
+    /// # Arguments
+    ///
+    /// * `subject` - The transaction hash or address that the note describes
+    pub fn remove(&mut self, subject: &Hash) -> Option<Note> {
+        self.notes.remove(subject)
+    }
+
+    /// Persist the store to the given data directory.
+    pub fn write_to_disk_at_data_directory(&self, data_dir: &str) -> io::Result<()> {
+        fs::create_dir_all(data_dir)?; // Make the data directory, if it doesn't exist
+
+        let mut file = fs::File::create(format!("{}/{}", data_dir, NOTES_FILE))?; // Initialize file
+
+        file.write_all(serde_json::to_vec_pretty(self)?.as_slice())?; // Serialize
+
+        Ok(()) // All good!
+    }
+
+    /// Read the store persisted in the given data directory. If no notes have been saved yet, an empty store is
+    /// returned.
+    pub fn read_from_disk_at_data_directory(data_dir: &str) -> io::Result<Self> {
+        // Open the notes file, or start fresh if there isn't one
+        let file = match fs::File::open(format!("{}/{}", data_dir, NOTES_FILE)) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+
+        Ok(serde_json::from_reader(file)?) // Return read store
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::io; // Import the io module
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_read_from_disk() {
+        let data_dir = format!("{}/.tests/notes", io::data_dir()); // Get a test directory
+
+        let subject = Hash::new(vec![1; 32]); // Get a subject to annotate
+
+        let mut store = NoteStore::default(); // Make an empty store
+        store.set(
+            subject,
+            Note {
+                label: "rent payment".to_owned(),
+                memo: "March".to_owned(),
+            },
+        ); // Label the subject
+        store.write_to_disk_at_data_directory(&data_dir).unwrap(); // Write the store
+
+        let mut read_store = NoteStore::read_from_disk_at_data_directory(&data_dir).unwrap(); // Read it back
+
+        assert_eq!(read_store, store); // Ensure the store survived
+        assert_eq!(read_store.get(&subject).unwrap().label, "rent payment"); // Ensure the label survived
+        assert!(read_store.remove(&subject).is_some()); // Ensure the note can be removed
+        assert!(read_store.get(&subject).is_none()); // Ensure the note is gone
+    }
+}
@@ -0,0 +1,234 @@
+use std::{fs, io, io::Write, str::FromStr}; // Import the filesystem library
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::super::{super::crypto::hash::Hash, types::graph::Graph}; // Import the hash & ledger types
+
+/// The name of the file in a data directory's config folder that pinned checkpoints are stored in.
+pub const CHECKPOINTS_FILE: &str = "checkpoints.json";
+
+/// An error encountered while pinning, parsing, or enforcing a checkpoint.
+#[derive(Debug, Fail, PartialEq)]
+pub enum CheckpointError {
+    #[fail(
+        display = "transaction {} at index {} conflicts with the pinned checkpoint {}",
+        found, index, pinned
+    )]
+    Conflict {
+        index: usize, // The index in the DAG that the checkpoint was pinned at
+        pinned: Hash, // The hash that was pinned
+        found: Hash,  // The hash that was found at the index instead
+    },
+    #[fail(display = "invalid checkpoint {} (expected index:hash)", checkpoint)]
+    Malformed {
+        checkpoint: String, // The checkpoint that couldn't be parsed
+    },
+}
+
+/// A known-good transaction, obtained from some source outside of the network (e.g. a block explorer or a trusted
+/// operator), at a particular index in the DAG.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Checkpoint {
+    /// The index of the transaction in the DAG
+    pub index: usize,
+
+    /// The hash of the transaction
+    pub hash: Hash,
+}
+
+impl FromStr for Checkpoint {
+    type Err = CheckpointError;
+
+    /// Parses a checkpoint written as index:hash.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || CheckpointError::Malformed {
+            checkpoint: s.to_owned(),
+        };
+
+        let mut parts = s.splitn(2, ':');
+        let index = parts
+            .next()
+            .and_then(|index| index.trim().parse::<usize>().ok())
+            .ok_or_else(malformed)?;
+        let hash = Hash::from(parts.next().ok_or_else(malformed)?.trim());
+
+        // Hashes that can't be decoded come out as zero
+        if hash == Hash::default() {
+            return Err(malformed());
+        }
+
+        Ok(Self { index, hash })
+    }
+}
+
+/// The checkpoints pinned by the operator. Any history that disagrees with one of them is refused, so that a fresh
+/// node can't be fed a fake history that forks off before the checkpoint.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct CheckpointSet {
+    /// Each of the pinned checkpoints, ordered by index
+    #[serde(default)]
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+/// Implement a set of checkpoint helper methods.
+impl CheckpointSet {
+    /// Pins the given checkpoint, replacing any checkpoint already pinned at the same index.
+    ///
+    /// # Arguments
+    ///
+    /// * `checkpoint` - The checkpoint that should be pinned
+    pub fn pin(&mut self, checkpoint: Checkpoint) {
+        self.unpin(checkpoint.index);
+
+        // Keep the checkpoints in order
+        let pos = self
+            .checkpoints
+            .iter()
+            .position(|existing| existing.index > checkpoint.index)
+            .unwrap_or_else(|| self.checkpoints.len());
+        self.checkpoints.insert(pos, checkpoint);
+    }
+
+    /// Removes the checkpoint pinned at the given index, returning it if there was one.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index that the checkpoint was pinned at
+    pub fn unpin(&mut self, index: usize) -> Option<Checkpoint> {
+        let pos = self.checkpoints.iter().position(|c| c.index == index)?;
+
+        Some(self.checkpoints.remove(pos))
+    }
+
+    /// Gets the hash pinned at the given index, if one has been pinned.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index in the DAG
+    pub fn get(&self, index: usize) -> Option<Hash> {
+        self.checkpoints
+            .iter()
+            .find(|c| c.index == index)
+            .map(|c| c.hash)
+    }
+
+    /// Makes sure that the transaction with the given hash may sit at the given index in the DAG.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index in the DAG that the transaction would sit at
+    /// * `hash` - The hash of the transaction
+    pub fn check(&self, index: usize, hash: Hash) -> Result<(), CheckpointError> {
+        match self.get(index) {
+            Some(pinned) if pinned != hash => Err(CheckpointError::Conflict {
+                index,
+                pinned,
+                found: hash,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Makes sure that none of the transactions already in the given ledger conflict with a pinned checkpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `ledger` - The ledger that should be checked
+    pub fn verify(&self, ledger: &Graph) -> Result<(), CheckpointError> {
+        for checkpoint in &self.checkpoints {
+            // Checkpoints pinned before the snapshot that the ledger was picked up from, if any, can't be checked
+            let local = match checkpoint.index.checked_sub(ledger.base_height() as usize) {
+                Some(local) => local,
+                None => continue,
+            };
+
+            if let Some(node) = ledger.nodes.get(local) {
+                self.check(checkpoint.index, node.hash)?;
+            }
+        }
+
+        Ok(()) // All good!
+    }
+
+    /// Persist the checkpoints to the config folder in the given data directory.
+    pub fn write_to_disk_at_data_directory(&self, data_dir: &str) -> io::Result<()> {
+        // Make the config folder
+        fs::create_dir_all(format!("{}/config", data_dir))?;
+
+        let mut file = fs::File::create(format!("{}/config/{}", data_dir, CHECKPOINTS_FILE))?; // Initialize file
+
+        file.write_all(serde_json::to_vec_pretty(self)?.as_slice())?; // Serialize
+
+        Ok(()) // All good!
+    }
+
+    /// Read the persisted checkpoints from the config folder in the given data directory.
+    pub fn read_from_disk_at_data_directory(data_dir: &str) -> io::Result<Self> {
+        let file = fs::File::open(format!("{}/config/{}", data_dir, CHECKPOINTS_FILE))?; // Open checkpoints file
+
+        Ok(serde_json::from_reader(file)?) // Return read checkpoints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::{common::io, crypto::blake3}; // Import the io & hashing modules
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_check() {
+        let mut set = CheckpointSet::default();
+        set.pin(Checkpoint {
+            index: 4,
+            hash: blake3::hash_slice(b"good"),
+        }); // Pin a checkpoint
+
+        assert!(set.check(4, blake3::hash_slice(b"good")).is_ok());
+        assert!(set.check(5, blake3::hash_slice(b"anything")).is_ok()); // Nothing is pinned here
+        assert_eq!(
+            set.check(4, blake3::hash_slice(b"evil")),
+            Err(CheckpointError::Conflict {
+                index: 4,
+                pinned: blake3::hash_slice(b"good"),
+                found: blake3::hash_slice(b"evil"),
+            })
+        );
+
+        assert!(set.unpin(4).is_some()); // Unpin it again
+        assert!(set.check(4, blake3::hash_slice(b"evil")).is_ok());
+    }
+
+    #[test]
+    fn test_from_str() {
+        let hash = blake3::hash_slice(b"good");
+
+        assert_eq!(
+            format!("12:{}", hash).parse::<Checkpoint>(),
+            Ok(Checkpoint { index: 12, hash })
+        );
+        assert!("12".parse::<Checkpoint>().is_err());
+        assert!("twelve:abc".parse::<Checkpoint>().is_err());
+        assert!("12:not a hash".parse::<Checkpoint>().is_err());
+    }
+
+    #[test]
+    fn test_read_from_disk() {
+        let data_dir = format!("{}/.tests/checkpoints", io::data_dir()); // Get a test directory
+
+        let mut set = CheckpointSet::default();
+        set.pin(Checkpoint {
+            index: 9,
+            hash: blake3::hash_slice(b"nine"),
+        });
+        set.pin(Checkpoint {
+            index: 3,
+            hash: blake3::hash_slice(b"three"),
+        }); // Pin a couple of checkpoints, out of order
+        set.write_to_disk_at_data_directory(&data_dir).unwrap();
+
+        let read_set = CheckpointSet::read_from_disk_at_data_directory(&data_dir).unwrap(); // Read them back
+
+        assert_eq!(read_set, set); // Ensure the checkpoints survived
+        assert_eq!(read_set.checkpoints[0].index, 3); // Ensure they're kept in order
+    }
+}
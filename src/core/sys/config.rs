@@ -4,7 +4,14 @@ use std::{fs, io, io::Write}; // Import the filesystem library
 
 use serde::{Deserialize, Serialize}; // Import serde serialization
 
-use super::super::super::common; // Import the io module
+use super::{
+    super::{
+        super::common,
+        types::{graph::StorageMode, issuance::IssuanceSchedule},
+    },
+    consensus::ConsensusThresholds,
+    council::Council,
+}; // Import the io module, storage mode, issuance schedule, vote thresholds & council
 
 /// The current version of rust-summercash.
 pub const NODE_VERSION: &str = "v0.1.0";
@@ -20,6 +27,28 @@ pub struct Config {
 
     /// The name of the network
     pub network_name: String,
+
+    /// How many finks are minted at the end of each epoch, and how the reward shrinks over time
+    #[serde(default)]
+    pub issuance: IssuanceSchedule,
+
+    /// How much of the issuance must vote for or against a proposal, and for how long, before it is decided
+    #[serde(default)]
+    pub consensus: ConsensusThresholds,
+
+    /// The keys that must sign off on changes to critical parameters, on top of the votes cast by stakeholders
+    #[serde(default)]
+    pub council: Council,
+
+    /// How much of the ledger's history the node keeps around: everything (archive), the states of recent
+    /// transactions (pruned), or just the executed head's state (light)
+    #[serde(default)]
+    pub storage_mode: StorageMode,
+
+    /// The number of times that the config has been changed by governance, which orders the versions of the config
+    /// published on the network
+    #[serde(default)]
+    pub sequence: u64,
 }
 
 /// Implement a set of config helper methods.
@@ -62,6 +91,16 @@ impl Config {
 
         Ok(serde_json::from_reader(file)?) // Return read config
     }
+
+    /// Namespaces the given pubsub topic by the name of the network, so that gossip from
+    /// different networks never crosses over (e.g. "andromeda/proposals").
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The un-namespaced name of the topic
+    pub fn namespaced_topic(&self, topic: &str) -> String {
+        format!("{}/{}", self.network_name, topic) // Return the namespaced topic
+    }
 }
 
 /// Checks whether or not the two clients are within an acceptable version range of each other in
@@ -103,6 +142,11 @@ mod tests {
         let config = Config {
             reward_per_gas: BigUint::from_str("10000000000000000000000000000000000000000").unwrap(), // Venezuela style
             network_name: "olympia1".to_owned(),
+            issuance: Default::default(),
+            consensus: Default::default(),
+            council: Default::default(),
+            storage_mode: Default::default(),
+            sequence: 0,
         }; // Initialize network config
 
         config.write_to_disk().unwrap(); // Panic if not Ok()
@@ -116,6 +160,11 @@ mod tests {
         let config = Config {
             reward_per_gas: BigUint::from_str("10000000000000000000000000000000000000000").unwrap(), // Venezuela style
             network_name: "olympia".to_owned(),
+            issuance: Default::default(),
+            consensus: Default::default(),
+            council: Default::default(),
+            storage_mode: Default::default(),
+            sequence: 0,
         }; // Initialize network config
 
         config.write_to_disk().unwrap(); // Panic if not Ok()
@@ -127,4 +176,20 @@ mod tests {
         // Delete the test config file
         fs::remove_file(common::io::format_config_dir("network_olympia.json")).unwrap();
     }
+
+    #[test]
+    fn test_namespaced_topic() {
+        let config = Config {
+            reward_per_gas: BigUint::from(DEFAULT_REWARD_PER_GAS),
+            network_name: "andromeda".to_owned(),
+            issuance: Default::default(),
+            consensus: Default::default(),
+            council: Default::default(),
+            storage_mode: Default::default(),
+            sequence: 0,
+        }; // Initialize network config
+
+        assert_eq!(config.namespaced_topic("proposals"), "andromeda/proposals");
+        // Ensure is prefixed by the network
+    }
 }
@@ -0,0 +1,438 @@
+use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::HashSet; // Import the set type
+
+use super::{
+    super::{
+        super::{
+            common::address::Address,
+            crypto::{blake3, hash::Hash},
+        },
+        types::signature::Signature,
+    },
+    config::Config,
+    consensus::ConsensusThresholds,
+    proposal::{Proposal, ProposalBody},
+    system::config_after,
+}; // Import the address, hashing, signature, config, vote threshold & proposal types
+
+/// An error encountered while checking a config record published by a peer.
+#[derive(Debug, Fail, PartialEq)]
+pub enum ConfigSyncError {
+    #[fail(
+        display = "config record {} is no newer than the local config, at {}",
+        sequence, local
+    )]
+    Stale {
+        sequence: u64, // The sequence number of the record
+        local: u64,    // The sequence number of the local config
+    },
+    #[fail(
+        display = "config record {} skips past the version after the local config, at {}",
+        sequence, local
+    )]
+    OutOfOrder {
+        sequence: u64, // The sequence number of the record
+        local: u64,    // The sequence number of the local config
+    },
+    #[fail(
+        display = "config record {} was not produced by its proposal: {}",
+        sequence, error
+    )]
+    Unproducible {
+        sequence: u64, // The sequence number of the record
+        error: String, // Why the proposal doesn't yield the record's config
+    },
+    #[fail(
+        display = "config record {} is not authorized by the council: {}",
+        sequence, error
+    )]
+    Unauthorized {
+        sequence: u64, // The sequence number of the record
+        error: String, // The underlying error
+    },
+    #[fail(
+        display = "config record {} is backed by {} finks, but needs {}",
+        sequence, found, required
+    )]
+    InsufficientBacking {
+        sequence: u64,    // The sequence number of the record
+        found: String,    // The number of finks held by the record's signers
+        required: String, // The number of finks that must back the record
+    },
+    #[fail(display = "the config record could not be decoded: {}", error)]
+    Malformed {
+        error: String, // The underlying error
+    },
+}
+
+/// A version of the network's config, produced by the execution of a governance proposal, and signed by the voting
+/// accounts of the nodes that executed it. Only the settings that every node on the network must agree on are
+/// carried; settings local to a node (e.g. how much of the ledger it keeps) are left out.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConfigRecord {
+    /// The config, stripped of its node-level settings
+    pub config: Config,
+
+    /// The executed proposal that produced the config from the version before it
+    pub proposal: Proposal,
+
+    /// The signatures of each of the accounts vouching for the config
+    pub signatures: Vec<Signature>,
+}
+
+/// Strips the node-level settings from the given config, leaving only those that every node must agree on.
+///
+/// # Arguments
+///
+/// * `config` - The config that should be stripped
+pub fn consensus_view(config: &Config) -> Config {
+    Config {
+        storage_mode: Default::default(),
+        ..config.clone()
+    }
+}
+
+/// Implement a set of config record helper methods.
+impl ConfigRecord {
+    /// Builds an unsigned record of the given config.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The config that the record should carry
+    /// * `proposal` - The executed proposal that produced the config
+    pub fn new(config: &Config, proposal: &Proposal) -> Self {
+        Self {
+            config: consensus_view(config),
+            proposal: proposal.clone(),
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Gets the sequence number of the config carried in the record.
+    pub fn sequence(&self) -> u64 {
+        self.config.sequence
+    }
+
+    /// Gets the hash of the config carried in the record and of the proposal that produced it, which is what its
+    /// signers sign.
+    pub fn digest(&self) -> Hash {
+        blake3::hash_slice(
+            &bincode::serialize(&(consensus_view(&self.config), &self.proposal))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Checks whether or not the record carries the given config, ignoring its node-level settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The config that the record should be compared to
+    pub fn describes(&self, config: &Config) -> bool {
+        bincode::serialize(&consensus_view(&self.config)).ok()
+            == bincode::serialize(&consensus_view(config)).ok()
+    }
+
+    /// Gets the address of each of the distinct accounts that have validly signed the record.
+    pub fn signers(&self) -> Vec<Address> {
+        let digest = self.digest();
+
+        let mut seen = HashSet::new();
+        self.signatures
+            .iter()
+            .filter(|signature| signature.verify(&*digest))
+            .filter_map(|signature| signature.address().ok())
+            .filter(|signer| seen.insert(*signer))
+            .collect()
+    }
+
+    /// Signs the record with the given keypair, unless the keypair's account has signed it already. Results in
+    /// whether or not a signature was added.
+    ///
+    /// # Arguments
+    ///
+    /// * `keypair` - The keypair of the account vouching for the config
+    pub fn sign(&mut self, keypair: &ed25519_dalek::Keypair) -> bool {
+        if self.signers().contains(&Address::from_key_pair(keypair)) {
+            return false;
+        }
+
+        self.signatures
+            .push(Signature::new(keypair, &*self.digest()));
+
+        true
+    }
+
+    /// Adds each of the valid signatures carried by another copy of the same record that this one doesn't already
+    /// carry, so that signatures gathered by different nodes add up. Results in the number of signatures added.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Another record, which is ignored unless it carries the same config
+    pub fn merge(&mut self, other: ConfigRecord) -> usize {
+        if other.digest() != self.digest() {
+            return 0;
+        }
+
+        let (digest, known) = (self.digest(), self.signers());
+
+        let mut added = 0;
+        for signature in other.signatures {
+            match signature.address() {
+                Ok(signer) if signature.verify(&*digest) && !known.contains(&signer) => {
+                    self.signatures.push(signature);
+                    added += 1;
+                }
+                _ => (),
+            }
+        }
+
+        added
+    }
+
+    /// Derives the config that should replace the given local config, by executing the record's proposal on top of
+    /// it. The record must be the very next version of the local config, its proposal must be authorized by the local
+    /// council and yield exactly the config carried in the record, and its signers must hold enough of the issuance
+    /// between them, per the local config's quorum. The record's own thresholds don't count, since they're what's
+    /// being vouched for. The local config's node-level settings are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `local` - The config that the node is using at the moment
+    /// * `height` - The depth of the node's executed ledger state, which scheduled changes must have reached
+    /// * `backing` - The number of finks held by the record's signers
+    /// * `issuance` - The overall issuance of the network
+    pub fn apply_to(
+        &self,
+        local: &Config,
+        height: u64,
+        backing: &BigUint,
+        issuance: &BigUint,
+    ) -> Result<Config, ConfigSyncError> {
+        let sequence = self.sequence();
+        if sequence <= local.sequence {
+            return Err(ConfigSyncError::Stale {
+                sequence,
+                local: local.sequence,
+            });
+        }
+
+        // Each version may only be derived from the one right before it
+        if sequence != local.sequence + 1 {
+            return Err(ConfigSyncError::OutOfOrder {
+                sequence,
+                local: local.sequence,
+            });
+        }
+
+        // The council signs the proposal's ID, so the ID must commit to what the proposal actually does
+        let proposal = &self.proposal;
+        let unproducible = |error: String| ConfigSyncError::Unproducible { sequence, error };
+        if Proposal::new(
+            proposal.proposal_name.clone(),
+            proposal.proposal_data.clone(),
+        )
+        .proposal_id
+            != proposal.proposal_id
+        {
+            return Err(unproducible(
+                "the proposal's ID doesn't match its contents".to_owned(),
+            ));
+        }
+
+        // Critical parameters can't be changed on the strength of the stake alone
+        local
+            .council
+            .authorize(proposal)
+            .map_err(|e| ConfigSyncError::Unauthorized {
+                sequence,
+                error: e.to_string(),
+            })?;
+
+        let change = match proposal.proposal_data.body() {
+            Ok(ProposalBody::Scheduled(scheduled)) => {
+                if height < scheduled.activation_height {
+                    return Err(unproducible(format!(
+                        "the change activates at ledger height {}, but the local ledger is at {}",
+                        scheduled.activation_height, height
+                    )));
+                }
+
+                scheduled.change
+            }
+            _ => proposal.proposal_data.clone(),
+        };
+
+        let config = config_after(local, &change).map_err(|e| unproducible(e.to_string()))?;
+        if !self.describes(&config) {
+            return Err(unproducible(
+                "the proposal yields a different config".to_owned(),
+            ));
+        }
+
+        let required = ConsensusThresholds::required_weight(issuance, local.consensus.quorum_bps);
+        if *backing < required || required.is_zero() {
+            return Err(ConfigSyncError::InsufficientBacking {
+                sequence,
+                found: backing.to_string(),
+                required: required.to_string(),
+            });
+        }
+
+        Ok(config) // All good!
+    }
+
+    /// Serializes the record into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap_or_default()
+    }
+
+    /// Deserializes a record from the given bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `b` - The encoded record
+    pub fn from_bytes(b: &[u8]) -> Result<Self, ConfigSyncError> {
+        bincode::deserialize(b).map_err(|e| ConfigSyncError::Malformed {
+            error: e.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        super::types::graph::StorageMode,
+        proposal::{Operation, ProposalData},
+    }; // Import the storage modes & proposal types
+    use super::*; // Import names from our parent module
+
+    use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    use rand::rngs::OsRng; // Import the os's rng
+
+    #[test]
+    fn test_config_records() {
+        let mut csprng = OsRng {};
+        let (alice, bob) = (
+            Keypair::generate(&mut csprng),
+            Keypair::generate(&mut csprng),
+        );
+
+        let local = Config {
+            reward_per_gas: BigUint::from(1 as u8),
+            network_name: "andromeda".to_owned(),
+            issuance: Default::default(),
+            consensus: Default::default(),
+            council: Default::default(),
+            storage_mode: StorageMode::Archive,
+            sequence: 0,
+        };
+        let amend = |param: &str, value: Vec<u8>| {
+            ProposalData::new(
+                param.to_owned(),
+                Operation::Amend {
+                    amended_value: value,
+                },
+            )
+        };
+        let proposal = Proposal::new(
+            "double_rewards".to_owned(),
+            amend("config::reward_per_gas", vec![2]),
+        );
+        let governed = Config {
+            storage_mode: StorageMode::Light,
+            ..config_after(&local, &proposal.proposal_data).unwrap()
+        };
+
+        // Signatures gathered by different nodes over the same config add up, whatever their storage modes
+        let mut record = ConfigRecord::new(&governed, &proposal);
+        assert!(record.sign(&alice));
+        assert!(!record.sign(&alice));
+
+        let mut other = ConfigRecord::new(
+            &Config {
+                storage_mode: StorageMode::default(),
+                ..governed.clone()
+            },
+            &proposal,
+        );
+        other.sign(&bob);
+        assert_eq!(record.merge(other.clone()), 1);
+        assert_eq!(record.merge(other), 0);
+        assert_eq!(record.signers().len(), 2);
+        assert!(record.describes(&governed));
+
+        // Records need the backing of a quorum of the issuance, per the local thresholds
+        let issuance = BigUint::from(1_000 as u16);
+        let required = ConsensusThresholds::required_weight(&issuance, local.consensus.quorum_bps);
+        assert!(record
+            .apply_to(&local, 0, &(&required - BigUint::from(1 as u8)), &issuance)
+            .is_err());
+
+        // Node-level settings survive the update, and the same record isn't applied twice
+        let applied = ConfigRecord::from_bytes(&record.to_bytes())
+            .unwrap()
+            .apply_to(&local, 0, &required, &issuance)
+            .unwrap();
+        assert_eq!(applied.reward_per_gas, BigUint::from(2 as u8));
+        assert_eq!(applied.storage_mode, StorageMode::Archive);
+        assert_eq!(
+            record.apply_to(&applied, 0, &required, &issuance).err(),
+            Some(ConfigSyncError::Stale {
+                sequence: 1,
+                local: 1
+            })
+        );
+
+        // Versions can't be skipped
+        let skipping = ConfigRecord::new(
+            &config_after(&applied, &proposal.proposal_data).unwrap(),
+            &proposal,
+        );
+        assert_eq!(
+            skipping.apply_to(&local, 0, &required, &issuance).err(),
+            Some(ConfigSyncError::OutOfOrder {
+                sequence: 2,
+                local: 0
+            })
+        );
+
+        // The config must be the one that the proposal produces
+        let forged = ConfigRecord::new(
+            &Config {
+                reward_per_gas: BigUint::from(3 as u8),
+                ..governed.clone()
+            },
+            &proposal,
+        );
+        assert!(matches!(
+            forged.apply_to(&local, 0, &required, &issuance),
+            Err(ConfigSyncError::Unproducible { .. })
+        ));
+
+        // Critical parameters still need the council's signatures
+        let renaming = Proposal::new(
+            "rename".to_owned(),
+            amend("config::network_name", b"triangulum".to_vec()),
+        );
+        let renamed = ConfigRecord::new(
+            &config_after(&local, &renaming.proposal_data).unwrap(),
+            &renaming,
+        );
+        assert!(matches!(
+            renamed.apply_to(&local, 0, &required, &issuance),
+            Err(ConfigSyncError::Unauthorized { .. })
+        ));
+
+        // Scheduled changes only apply once the local ledger has reached their activation height
+        let scheduling = Proposal::new(
+            "double_rewards_later".to_owned(),
+            ProposalData::scheduled(5, proposal.proposal_data.clone()),
+        );
+        let scheduled = ConfigRecord::new(&governed, &scheduling);
+        assert!(scheduled.apply_to(&local, 4, &required, &issuance).is_err());
+        assert!(scheduled.apply_to(&local, 5, &required, &issuance).is_ok());
+    }
+}
@@ -0,0 +1,205 @@
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use num::BigUint; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::{HashMap, VecDeque}; // Import the map & queue types
+
+use super::super::{
+    super::{common::address::Address, crypto::hash::Hash},
+    types::transaction::Transaction,
+}; // Import the address, hash & transaction types
+
+/// The number of (sender, nonce) pairs that the tracker remembers the spends of, by default.
+pub const DEFAULT_TRACKED_SPENDS: usize = 65536;
+
+/// The number of double spends that the tracker holds onto for operators to look over.
+pub const MAX_RECENT_DOUBLE_SPENDS: usize = 256;
+
+/// A single transaction competing to spend a sender's nonce.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConflictingSpend {
+    /// The hash of the transaction
+    pub hash: Hash,
+
+    /// The account that the transaction pays
+    pub recipient: Address,
+
+    /// The number of finks that the transaction sends
+    pub value: BigUint,
+}
+
+/// Two or more distinct transactions sent by the same account with the same nonce, at most one of which can ever
+/// execute. Merchants being paid by any of them should hold off on fulfillment until the ledger settles on one.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DoubleSpendAlert {
+    /// The account that sent each of the competing transactions
+    pub sender: Address,
+
+    /// The nonce that each of the competing transactions claims
+    pub nonce: u64,
+
+    /// Each of the competing transactions, in the order that they were seen
+    pub spends: Vec<ConflictingSpend>,
+
+    /// When the latest competing transaction was seen
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Remembers the transactions spending each recently seen (sender, nonce) pair, so that competing spends can be
+/// flagged as soon as the second one shows up.
+pub struct ConflictTracker {
+    /// The greatest number of (sender, nonce) pairs to remember at once
+    capacity: usize,
+
+    /// The transactions seen spending each (sender, nonce) pair
+    spends: HashMap<(Address, u64), Vec<ConflictingSpend>>,
+
+    /// Each of the remembered (sender, nonce) pairs, oldest first
+    order: VecDeque<(Address, u64)>,
+
+    /// The most recent double spends, oldest first
+    recent: VecDeque<DoubleSpendAlert>,
+}
+
+impl Default for ConflictTracker {
+    /// Remembers the default number of spends.
+    fn default() -> Self {
+        Self::new(DEFAULT_TRACKED_SPENDS)
+    }
+}
+
+/// Implement a set of conflict tracker helper methods.
+impl ConflictTracker {
+    /// Initializes a new, empty tracker.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The greatest number of (sender, nonce) pairs to remember at once
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            spends: HashMap::new(),
+            order: VecDeque::new(),
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Remembers the given transaction, and raises an alert if it competes with another transaction seen spending
+    /// the same nonce. Transactions that have already been seen are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that was seen
+    pub fn observe(&mut self, tx: &Transaction) -> Option<DoubleSpendAlert> {
+        let key = (tx.transaction_data.sender, tx.transaction_data.nonce);
+
+        if !self.spends.contains_key(&key) {
+            // Forget the oldest spends, if need be
+            while self.order.len() >= self.capacity.max(1) {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.spends.remove(&oldest);
+                }
+            }
+
+            self.order.push_back(key);
+        }
+
+        let spends = self.spends.entry(key).or_insert_with(Vec::new);
+        if spends.iter().any(|spend| spend.hash == tx.hash) {
+            return None;
+        }
+
+        spends.push(ConflictingSpend {
+            hash: tx.hash,
+            recipient: tx.transaction_data.recipient,
+            value: tx.transaction_data.value.clone(),
+        });
+
+        if spends.len() < 2 {
+            return None;
+        }
+
+        let alert = DoubleSpendAlert {
+            sender: key.0,
+            nonce: key.1,
+            spends: spends.clone(),
+            detected_at: Utc::now(),
+        };
+
+        if self.recent.len() >= MAX_RECENT_DOUBLE_SPENDS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(alert.clone());
+
+        Some(alert)
+    }
+
+    /// Gets the most recent double spends, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The greatest number of double spends to get
+    pub fn recent(&self, limit: usize) -> Vec<DoubleSpendAlert> {
+        self.recent.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::crypto::blake3; // Import the blake3 hashing module
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_conflict_tracker() {
+        let (alice, bob, carol) = (
+            blake3::hash_slice(b"alice"),
+            blake3::hash_slice(b"bob"),
+            blake3::hash_slice(b"carol"),
+        );
+        let mut tracker = ConflictTracker::new(2);
+
+        let to_bob = Transaction::new(0, alice, bob, BigUint::from(5 as u8), b"", vec![]);
+        let to_carol = Transaction::new(0, alice, carol, BigUint::from(5 as u8), b"", vec![]);
+
+        // Seeing the same transaction twice isn't a double spend
+        assert_eq!(tracker.observe(&to_bob), None);
+        assert_eq!(tracker.observe(&to_bob), None);
+
+        let alert = tracker.observe(&to_carol).unwrap();
+        assert_eq!((alert.sender, alert.nonce), (alice, 0));
+        assert_eq!(
+            alert
+                .spends
+                .iter()
+                .map(|spend| spend.hash)
+                .collect::<Vec<Hash>>(),
+            vec![to_bob.hash, to_carol.hash]
+        );
+        assert_eq!(tracker.recent(10), vec![alert]);
+
+        // Old spends are forgotten once the tracker is full
+        for nonce in 1..3 {
+            tracker.observe(&Transaction::new(
+                nonce,
+                alice,
+                bob,
+                BigUint::from(1 as u8),
+                b"",
+                vec![],
+            ));
+        }
+        assert_eq!(
+            tracker.observe(&Transaction::new(
+                0,
+                alice,
+                bob,
+                BigUint::from(9 as u8),
+                b"",
+                vec![]
+            )),
+            None
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use chrono::Duration; // Import time utilities
+
+use num::bigint::{BigInt, BigUint, Sign}; // Add support for large integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+/// The number of basis points making up the whole of the issuance.
+pub const BASIS_POINTS: u64 = 10_000;
+
+/// The share of the issuance, in basis points, that must be cast in favor of (or against) a proposal by default: a
+/// simple majority.
+pub const DEFAULT_THRESHOLD_BPS: u64 = 5_000;
+
+/// The rules deciding when a proposal has gathered enough votes to be executed or rejected.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ConsensusThresholds {
+    /// The share of the overall issuance, in basis points, that the net weight of the votes cast in favor of a
+    /// proposal must reach before the proposal is executed
+    pub quorum_bps: u64,
+
+    /// The share of the overall issuance, in basis points, that the net weight of the votes cast against a proposal
+    /// must reach before the proposal is rejected
+    pub rejection_bps: u64,
+
+    /// The number of seconds that must pass after a proposal is first seen before it may be executed or rejected
+    pub min_voting_period_secs: u64,
+}
+
+/// Where a proposal stands, considering the votes cast on it so far.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verdict {
+    /// Neither threshold has been reached
+    Undecided,
+
+    /// A threshold has been reached, but the minimum voting period hasn't passed yet
+    Premature,
+
+    /// Enough weight has been cast in favor of the proposal to execute it
+    Accepted,
+
+    /// Enough weight has been cast in favor of the proposal, but the votes were relayed by too few distinct peers (or
+    /// subnets) to trust that they don't all come from the same host
+    Undiverse,
+
+    /// Enough weight has been cast against the proposal to reject it
+    Rejected,
+}
+
+impl Default for ConsensusThresholds {
+    /// Executes or rejects proposals as soon as a simple majority of the issuance has been cast either way.
+    fn default() -> Self {
+        Self {
+            quorum_bps: DEFAULT_THRESHOLD_BPS,
+            rejection_bps: DEFAULT_THRESHOLD_BPS,
+            min_voting_period_secs: 0,
+        }
+    }
+}
+
+/// Implement a set of consensus threshold helper methods.
+impl ConsensusThresholds {
+    /// Gets the weight that must be cast in order to reach the given share of the issuance.
+    ///
+    /// # Arguments
+    ///
+    /// * `issuance` - The overall issuance of the network
+    /// * `bps` - The share of the issuance, in basis points
+    pub fn required_weight(issuance: &BigUint, bps: u64) -> BigUint {
+        issuance * BigUint::from(bps) / BigUint::from(BASIS_POINTS)
+    }
+
+    /// Decides where a proposal stands.
+    ///
+    /// # Arguments
+    ///
+    /// * `net_weight` - The weight of the votes cast in favor of the proposal, less those cast against it
+    /// * `issuance` - The overall issuance of the network
+    /// * `open_for` - How long the proposal has been open for voting
+    pub fn tally(&self, net_weight: &BigInt, issuance: &BigUint, open_for: Duration) -> Verdict {
+        let (sign, magnitude) = (net_weight.sign(), net_weight.magnitude());
+
+        let verdict = if sign != Sign::Minus
+            && *magnitude >= Self::required_weight(issuance, self.quorum_bps)
+        {
+            Verdict::Accepted
+        } else if sign != Sign::Plus
+            && *magnitude >= Self::required_weight(issuance, self.rejection_bps)
+        {
+            Verdict::Rejected
+        } else {
+            return Verdict::Undecided;
+        };
+
+        if open_for < Duration::seconds(self.min_voting_period_secs as i64) {
+            return Verdict::Premature;
+        }
+
+        verdict
+    }
+
+    /// Checks that each of the thresholds is a share of the issuance that can actually be reached.
+    pub fn is_valid(&self) -> bool {
+        (1..=BASIS_POINTS).contains(&self.quorum_bps)
+            && (1..=BASIS_POINTS).contains(&self.rejection_bps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_tally() {
+        let issuance = BigUint::from(1000 as u16);
+        let thresholds = ConsensusThresholds {
+            quorum_bps: 6_667,
+            rejection_bps: 3_334,
+            min_voting_period_secs: 60,
+        };
+        let open = Duration::seconds(60);
+
+        // The quorum is reached exactly at the threshold, and not a fink before it
+        assert_eq!(
+            thresholds.tally(&BigInt::from(666), &issuance, open),
+            Verdict::Accepted
+        );
+        assert_eq!(
+            thresholds.tally(&BigInt::from(665), &issuance, open),
+            Verdict::Undecided
+        );
+
+        // The same goes for rejections
+        assert_eq!(
+            thresholds.tally(&BigInt::from(-333), &issuance, open),
+            Verdict::Rejected
+        );
+        assert_eq!(
+            thresholds.tally(&BigInt::from(-332), &issuance, open),
+            Verdict::Undecided
+        );
+
+        // Nothing is decided until the voting period has passed
+        assert_eq!(
+            thresholds.tally(&BigInt::from(1000), &issuance, Duration::seconds(59)),
+            Verdict::Premature
+        );
+        assert_eq!(
+            thresholds.tally(&BigInt::from(10), &issuance, Duration::seconds(0)),
+            Verdict::Undecided
+        );
+    }
+
+    #[test]
+    fn test_default() {
+        let thresholds = ConsensusThresholds::default();
+        let issuance = BigUint::from(101 as u8);
+
+        // A simple majority (rounded down) settles a proposal right away
+        assert_eq!(
+            thresholds.tally(&BigInt::from(50), &issuance, Duration::zero()),
+            Verdict::Accepted
+        );
+        assert_eq!(
+            thresholds.tally(&BigInt::from(-50), &issuance, Duration::zero()),
+            Verdict::Rejected
+        );
+        assert_eq!(
+            thresholds.tally(&BigInt::from(49), &issuance, Duration::zero()),
+            Verdict::Undecided
+        );
+
+        // Before anything has been issued, any proposal without opposition passes
+        assert_eq!(
+            thresholds.tally(&BigInt::from(0), &BigUint::from(0 as u8), Duration::zero()),
+            Verdict::Accepted
+        );
+
+        assert!(thresholds.is_valid());
+        assert!(!ConsensusThresholds {
+            quorum_bps: 0,
+            ..thresholds
+        }
+        .is_valid());
+        assert!(!ConsensusThresholds {
+            rejection_bps: BASIS_POINTS + 1,
+            ..thresholds
+        }
+        .is_valid());
+    }
+}
@@ -0,0 +1,330 @@
+use std::{convert::TryInto, error::Error}; // Import the error library
+
+use super::{
+    super::{
+        super::crypto::hash::Hash,
+        types::{
+            graph::Graph,
+            migrations::{SCHEMA_VERSION, SCHEMA_VERSION_KEY},
+        },
+    },
+    checkpoints::CheckpointError,
+    system::System,
+}; // Import the system & ledger types
+
+/// The metadata key under which the name of the network that the ledger belongs to is stored.
+pub const NETWORK_NAME_KEY: &str = "network_name";
+
+/// The metadata key under which the hash of the ledger's genesis is stored.
+pub const GENESIS_HASH_KEY: &str = "genesis_hash";
+
+/// An error encountered while checking that the node's config, ledger, and database agree with each other.
+#[derive(Debug, Fail)]
+pub enum ConsistencyError {
+    #[fail(
+        display = "the node was started for network {}, but its config names network {}; start with --network {}, or fix the network_name field in the network config",
+        network, config_network, config_network
+    )]
+    ConfigNetworkMismatch {
+        network: String,        // The network the node was started for
+        config_network: String, // The network named in the config
+    },
+    #[fail(
+        display = "the ledger in {} belongs to network {}, not {}; start with --network {}, or choose a different --data-dir",
+        db_dir, recorded_network, network, recorded_network
+    )]
+    LedgerNetworkMismatch {
+        db_dir: String,           // The directory containing the ledger
+        recorded_network: String, // The network the ledger was created for
+        network: String,          // The network the node was started for
+    },
+    #[fail(
+        display = "the ledger in {} descends from genesis {}, but genesis {} was expected; move {} aside and let the node resynchronize",
+        db_dir, found, expected, db_dir
+    )]
+    GenesisMismatch {
+        db_dir: String,   // The directory containing the ledger
+        found: String,    // The genesis of the ledger on disk
+        expected: String, // The genesis the ledger should have
+    },
+    #[fail(
+        display = "the ledger in {} uses schema version {}, but this node only supports up to version {}; upgrade smcd, or move {} aside and let the node resynchronize",
+        db_dir, found, supported, db_dir
+    )]
+    UnsupportedSchema {
+        db_dir: String, // The directory containing the ledger
+        found: u32,     // The schema version of the ledger on disk
+        supported: u32, // The newest schema version this node understands
+    },
+    #[fail(
+        display = "the ledger in {} uses schema version {}, but version {} is required, and the migration to it failed (see the logs above); fix the reported problem and restart, or move {} aside and let the node resynchronize",
+        db_dir, found, required, db_dir
+    )]
+    OutdatedSchema {
+        db_dir: String, // The directory containing the ledger
+        found: u32,     // The schema version of the ledger on disk
+        required: u32,  // The schema version this node reads and writes
+    },
+    #[fail(
+        display = "the ledger in {} holds transaction {} at index {}, but checkpoint {} is pinned there; move {} aside and let the node resynchronize, or unpin the checkpoint",
+        db_dir, found, index, pinned, db_dir
+    )]
+    CheckpointConflict {
+        db_dir: String, // The directory containing the ledger
+        index: usize,   // The index that the checkpoint was pinned at
+        pinned: String, // The hash of the pinned checkpoint
+        found: String,  // The hash of the transaction in the ledger
+    },
+    #[fail(display = "failed to read the ledger's metadata: {}", error)]
+    StorageFailure {
+        error: String, // The underlying error
+    },
+}
+
+impl From<sled::Error> for ConsistencyError {
+    /// Converts the given sled error into a ConsistencyError.
+    fn from(e: sled::Error) -> Self {
+        Self::StorageFailure {
+            error: e.description().to_owned(),
+        }
+    }
+}
+
+/// Makes sure that the given runtime's config, ledger, and database all describe the same network, so that histories
+/// from different networks are never mixed. Any metadata that hasn't been recorded yet is recorded once the checks pass.
+///
+/// # Arguments
+///
+/// * `system` - The runtime whose config and ledger should be checked
+/// * `network` - The name of the network that the node was started for
+/// * `data_dir` - The data directory that the node was started with
+/// * `expected_genesis` - The genesis that the ledger must descend from, if the operator has pinned one
+pub fn check(
+    system: &System,
+    network: &str,
+    data_dir: &str,
+    expected_genesis: Option<Hash>,
+) -> Result<(), ConsistencyError> {
+    let db_dir = format!("{}/db/{}", data_dir, system.config.network_name); // The directory containing the ledger
+
+    // The config decides which ledger is opened, so it must name the network we were asked to join
+    if system.config.network_name != network {
+        return Err(ConsistencyError::ConfigNetworkMismatch {
+            network: network.to_owned(),
+            config_network: system.config.network_name.clone(),
+        });
+    }
+
+    // Make sure we know how to read the database
+    if let Some(version) = schema_version(&system.ledger)? {
+        if version > SCHEMA_VERSION {
+            return Err(ConsistencyError::UnsupportedSchema {
+                db_dir,
+                found: version,
+                supported: SCHEMA_VERSION,
+            });
+        }
+
+        // The database is migrated as it's opened, so an old version means that a migration failed
+        if version < SCHEMA_VERSION {
+            return Err(ConsistencyError::OutdatedSchema {
+                db_dir,
+                found: version,
+                required: SCHEMA_VERSION,
+            });
+        }
+    }
+
+    // Make sure the ledger was made for this network
+    if let Some(recorded) = system.ledger.get_meta(NETWORK_NAME_KEY)? {
+        let recorded_network = String::from_utf8_lossy(&recorded).into_owned();
+
+        if recorded_network != network {
+            return Err(ConsistencyError::LedgerNetworkMismatch {
+                db_dir,
+                recorded_network,
+                network: network.to_owned(),
+            });
+        }
+    }
+
+    // Make sure the ledger descends from the right genesis: either the one the operator pinned, or the one that the
+    // ledger started out with
+    let expected = match expected_genesis {
+        Some(genesis) => Some(genesis),
+        None => system.ledger.get_meta(GENESIS_HASH_KEY)?.map(Hash::new),
+    };
+    if let (Some(expected), Some(found)) = (expected, system.ledger.root_hash()) {
+        if expected != found {
+            return Err(ConsistencyError::GenesisMismatch {
+                db_dir,
+                found: found.to_str(),
+                expected: expected.to_str(),
+            });
+        }
+    }
+
+    // Make sure that nothing we've already synchronized disagrees with the checkpoints that the operator pinned
+    if let Err(CheckpointError::Conflict {
+        index,
+        pinned,
+        found,
+    }) = system.checkpoints.verify(&system.ledger)
+    {
+        return Err(ConsistencyError::CheckpointConflict {
+            db_dir,
+            index,
+            pinned: pinned.to_str(),
+            found: found.to_str(),
+        });
+    }
+
+    stamp(&system.ledger, network) // Remember everything we've just verified
+}
+
+/// Records the schema version, network name, and genesis of the given ledger, if they haven't been recorded already.
+///
+/// # Arguments
+///
+/// * `ledger` - The ledger that should be stamped
+/// * `network` - The name of the network that the ledger belongs to
+pub fn stamp(ledger: &Graph, network: &str) -> Result<(), ConsistencyError> {
+    if schema_version(ledger)?.is_none() {
+        ledger.set_meta(SCHEMA_VERSION_KEY, &SCHEMA_VERSION.to_be_bytes())?;
+    }
+
+    if ledger.get_meta(NETWORK_NAME_KEY)?.is_none() {
+        ledger.set_meta(NETWORK_NAME_KEY, network.as_bytes())?;
+    }
+
+    // The genesis can only be recorded once the ledger actually has one
+    if let (None, Some(root)) = (ledger.get_meta(GENESIS_HASH_KEY)?, ledger.root_hash()) {
+        ledger.set_meta(GENESIS_HASH_KEY, &*root)?;
+    }
+
+    Ok(()) // Done!
+}
+
+/// Gets the schema version recorded in the given ledger's database, if one has been recorded.
+///
+/// # Arguments
+///
+/// * `ledger` - The ledger whose schema version should be read
+pub fn schema_version(ledger: &Graph) -> Result<Option<u32>, ConsistencyError> {
+    match ledger.get_meta(SCHEMA_VERSION_KEY)? {
+        Some(encoded) => {
+            // Decode the big-endian version number
+            let bytes: [u8; 4] =
+                encoded
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| ConsistencyError::StorageFailure {
+                        error: "malformed schema version".to_owned(),
+                    })?;
+
+            Ok(Some(u32::from_be_bytes(bytes)))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::{
+        common::{address::Address, io},
+        core::types::transaction::Transaction,
+    };
+    use super::super::{checkpoints::Checkpoint, config::Config};
+    use super::*; // Import names from our parent module
+
+    use num::BigUint; // Add support for large unsigned integers
+
+    /// Makes a runtime for the given network, with a fresh ledger stored in a test directory.
+    fn test_system(network: &str) -> System {
+        let mut root = Transaction::new(
+            0,
+            Address::default(),
+            Address::default(),
+            BigUint::from(0 as u8),
+            b"genesis",
+            vec![],
+        ); // Make a root transaction
+        root.genesis = true;
+
+        let ledger = Graph::new_with_db_path(
+            root,
+            &format!(
+                "{}/.tests/consistency/{}",
+                io::db_dir(),
+                rand::random::<u32>()
+            ),
+        ); // Make a ledger
+
+        System::with_ledger(
+            Config {
+                reward_per_gas: BigUint::from(0 as u8),
+                network_name: network.to_owned(),
+                issuance: Default::default(),
+                consensus: Default::default(),
+                council: Default::default(),
+                storage_mode: Default::default(),
+                sequence: 0,
+            },
+            ledger,
+        )
+    }
+
+    #[test]
+    fn test_check() {
+        let system = test_system("olympia");
+
+        assert!(check(&system, "olympia", "data", None).is_ok()); // A fresh ledger should pass, and be stamped
+        assert_eq!(
+            schema_version(&system.ledger).unwrap(),
+            Some(SCHEMA_VERSION)
+        ); // Ensure the version was stamped
+        assert!(check(&system, "olympia", "data", None).is_ok()); // The stamped ledger should still pass
+
+        assert!(check(&system, "andromeda", "data", None).is_err()); // The config names a different network
+        assert!(check(&system, "olympia", "data", Some(Hash::default())).is_err());
+        // The genesis doesn't match
+    }
+
+    #[test]
+    fn test_check_unsupported_schema() {
+        let system = test_system("olympia");
+
+        system
+            .ledger
+            .set_meta(SCHEMA_VERSION_KEY, &(SCHEMA_VERSION + 1).to_be_bytes())
+            .unwrap(); // Pretend a newer node wrote the ledger
+
+        match check(&system, "olympia", "data", None) {
+            Err(ConsistencyError::UnsupportedSchema { found, .. }) => {
+                assert_eq!(found, SCHEMA_VERSION + 1)
+            }
+            _ => panic!("expected the schema version to be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_check_checkpoints() {
+        let mut system = test_system("olympia");
+        let root = system.ledger.nodes[0].hash;
+
+        system.checkpoints.pin(Checkpoint {
+            index: 0,
+            hash: root,
+        }); // Pin the ledger's own root
+        assert!(check(&system, "olympia", "data", None).is_ok());
+
+        system.checkpoints.pin(Checkpoint {
+            index: 0,
+            hash: Hash::default(),
+        }); // Pin a different root
+        match check(&system, "olympia", "data", None) {
+            Err(ConsistencyError::CheckpointConflict { index, .. }) => assert_eq!(index, 0),
+            _ => panic!("expected the conflicting ledger to be rejected"),
+        }
+    }
+}
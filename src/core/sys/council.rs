@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::HashSet; // Import the set type
+
+use super::{
+    super::super::common::address::Address,
+    proposal::{Proposal, ProposalData},
+}; // Import the address & proposal types
+
+/// The parameters that only the council may change by default, since changing them splits the network.
+pub const DEFAULT_CRITICAL_PARAMS: [&str; 1] = ["config::network_name"];
+
+/// An error encountered while checking that a proposal has been endorsed by the council.
+#[derive(Debug, Fail, PartialEq)]
+pub enum CouncilError {
+    #[fail(
+        display = "proposal changes critical parameter {}, but carries only {} of the {} council signatures required",
+        param, found, required
+    )]
+    InsufficientSignatures {
+        param: String,   // The first critical parameter that the proposal changes
+        found: usize,    // The number of distinct council members that signed the proposal
+        required: usize, // The number of council members that must sign the proposal
+    },
+    #[fail(
+        display = "proposal changes critical parameter {}, but no council has been configured",
+        param
+    )]
+    NoCouncil {
+        param: String, // The first critical parameter that the proposal changes
+    },
+}
+
+/// A set of keys that must additionally sign off on changes to critical config parameters, on top of the votes cast
+/// by stakeholders. This keeps a transient majority of the stake from renaming, or otherwise bricking, the network.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Council {
+    /// The addresses of the accounts belonging to each member of the council
+    pub members: Vec<Address>,
+
+    /// The number of distinct members that must sign a proposal changing a critical parameter
+    pub threshold: usize,
+
+    /// The parameters that can't be changed without the council's signatures (e.g. config::network_name)
+    pub critical_params: Vec<String>,
+}
+
+impl Default for Council {
+    /// Protects the default critical parameters, but leaves the council empty, so that they can't be changed at all
+    /// until one is configured.
+    fn default() -> Self {
+        Self {
+            members: Vec::new(),
+            threshold: 0,
+            critical_params: DEFAULT_CRITICAL_PARAMS
+                .iter()
+                .map(|param| (*param).to_owned())
+                .collect(),
+        }
+    }
+}
+
+/// Implement a set of council helper methods.
+impl Council {
+    /// Gets the first of the critical parameters that the given proposal data would change, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_data` - The change that a proposal would make
+    pub fn critical_param(&self, proposal_data: &ProposalData) -> Option<String> {
+        proposal_data
+            .affected_params()
+            .into_iter()
+            .find(|param| self.critical_params.contains(param))
+    }
+
+    /// Counts the distinct members of the council that have signed the given proposal.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal whose signatures should be counted
+    pub fn count_signers(&self, proposal: &Proposal) -> usize {
+        proposal
+            .council_signatures
+            .iter()
+            .filter(|signature| signature.verify(&*proposal.proposal_id))
+            .filter_map(|signature| signature.address().ok())
+            .filter(|signer| self.members.contains(signer))
+            .collect::<HashSet<Address>>()
+            .len()
+    }
+
+    /// Makes sure that the given proposal carries enough council signatures to be executed. Proposals that don't
+    /// change any critical parameters never need any.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal that is about to be executed
+    pub fn authorize(&self, proposal: &Proposal) -> Result<(), CouncilError> {
+        let param = match self.critical_param(&proposal.proposal_data) {
+            Some(param) => param,
+            None => return Ok(()),
+        };
+
+        // A council without any members (or a threshold of zero) would let anyone through
+        if self.members.is_empty() || self.threshold == 0 {
+            return Err(CouncilError::NoCouncil { param });
+        }
+
+        let found = self.count_signers(proposal);
+        if found < self.threshold {
+            return Err(CouncilError::InsufficientSignatures {
+                param,
+                found,
+                required: self.threshold,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::proposal::Operation; // Import the proposal operation type
+    use super::*; // Import names from our parent module
+
+    use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    use rand::rngs::OsRng; // Import the os's rng
+
+    #[test]
+    fn test_authorize() {
+        let mut csprng = OsRng {};
+        let members: Vec<Keypair> = (0..3).map(|_| Keypair::generate(&mut csprng)).collect();
+        let outsider = Keypair::generate(&mut csprng);
+
+        let council = Council {
+            members: members.iter().map(Address::from_key_pair).collect(),
+            threshold: 2,
+            ..Default::default()
+        };
+
+        let mut rename = Proposal::new(
+            "rename".to_owned(),
+            ProposalData::new(
+                "config::network_name".to_owned(),
+                Operation::Amend {
+                    amended_value: b"andromeda".to_vec(),
+                },
+            ),
+        );
+
+        // Signatures from outsiders, or the same member twice, don't count
+        rename.sign_as_council(&members[0]);
+        rename.sign_as_council(&members[0]);
+        rename.sign_as_council(&outsider);
+        assert_eq!(council.count_signers(&rename), 1);
+        assert!(council.authorize(&rename).is_err());
+
+        rename.sign_as_council(&members[2]);
+        assert_eq!(council.authorize(&rename), Ok(()));
+
+        // The same change can't go through without a council at all
+        assert!(Council::default().authorize(&rename).is_err());
+
+        // Nothing else needs the council's signatures
+        let signal = Proposal::new("hi".to_owned(), ProposalData::signal("hello"));
+        assert_eq!(Council::default().authorize(&signal), Ok(()));
+    }
+}
@@ -0,0 +1,312 @@
+use chrono::{DateTime, Duration, Utc}; // Import time utilities
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::BTreeMap; // Import the ordered map type
+
+use super::super::super::{common::address::Address, crypto::hash::Hash}; // Import the address & hash types
+
+/// The number of seconds that the transaction spending an account's next nonce may wait to be executed before it's
+/// considered stuck, by default.
+pub const DEFAULT_STUCK_AFTER_SECS: u64 = 600;
+
+/// The greatest number of missing nonces reported for a single account, so that a transaction with a wildly high
+/// nonce can't blow up the report.
+pub const MAX_REPORTED_MISSING_NONCES: usize = 64;
+
+/// Where a transaction waiting to be executed is held.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionLocation {
+    /// The transaction has made it into the DAG, but hasn't been executed
+    Dag,
+
+    /// The transaction is waiting in the node's mempool to be published
+    Mempool,
+}
+
+/// A transaction sent by an account that hasn't been executed yet.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WaitingTransaction {
+    /// The hash of the transaction
+    pub hash: Hash,
+
+    /// The nonce spent by the transaction
+    pub nonce: u64,
+
+    /// Where the transaction is held
+    pub location: TransactionLocation,
+
+    /// When the transaction started waiting: when it was admitted to the mempool, or when it was signed, if it's in
+    /// the DAG
+    pub since: DateTime<Utc>,
+
+    /// Whether or not the transaction has been gossiped to the network
+    pub published: bool,
+}
+
+/// Something keeping an account's transactions from being executed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NonceIssue {
+    /// No transaction spends the given nonces, holding back each of the account's transactions that follow them
+    Gap { missing: Vec<u64> },
+
+    /// The transaction spending the account's next nonce hasn't been executed in time
+    Stuck {
+        hash: Hash,
+        nonce: u64,
+        waiting_secs: u64,
+    },
+
+    /// Several transactions spend the same nonce, so that only one of them can ever be executed
+    Duplicate { nonce: u64, hashes: Vec<Hash> },
+
+    /// The transaction spends a nonce that has already been spent by an executed transaction (e.g. the original of a
+    /// replacement, or a replacement of an executed original), so that it never will be executed
+    Orphaned { hash: Hash, nonce: u64 },
+}
+
+/// Something that the account's owner can do to get its transactions executed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RepairAction {
+    /// Sign & submit a new transaction spending the missing nonce. Transactions created through the node spend the
+    /// first missing nonce on their own.
+    ResubmitMissingNonce { nonce: u64 },
+
+    /// Rebuild the transaction on top of the DAG's head (rebuild_transaction), sign the copy, and gossip it in the
+    /// original's place (resubmit_transaction)
+    Rebuild { hash: Hash },
+
+    /// Stop resubmitting the transaction, since it can't be executed
+    Cancel { hash: Hash },
+}
+
+/// Why an account's transactions aren't being executed, if they aren't, and what can be done about it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AccountDiagnosis {
+    /// The address of the account
+    pub address: Address,
+
+    /// The nonce that the account's next executed transaction must spend
+    pub executed_nonce: u64,
+
+    /// The nonce that a new transaction sent by the account through the node would spend
+    pub next_nonce: u64,
+
+    /// Each of the account's transactions that haven't been executed, lowest nonce first
+    pub waiting: Vec<WaitingTransaction>,
+
+    /// Each of the issues found with the account's transactions
+    pub issues: Vec<NonceIssue>,
+
+    /// What can be done about the issues, in the order that it should be done in
+    pub actions: Vec<RepairAction>,
+}
+
+/// Implement a set of account diagnosis helper methods.
+impl AccountDiagnosis {
+    /// Checks whether or not nothing is keeping the account's transactions from being executed.
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Inspects the transactions sent by an account that haven't been executed yet for nonce gaps, stuck transactions,
+/// and transactions that can never be executed, recommending what to do about each.
+///
+/// # Arguments
+///
+/// * `address` - The address of the account
+/// * `executed_nonce` - The nonce following the account's last executed transaction
+/// * `next_nonce` - The nonce that a new transaction sent by the account through the node would spend
+/// * `waiting` - Each of the account's transactions in the DAG or mempool that haven't been executed
+/// * `stuck_after` - How long the transaction spending the account's next nonce may wait before it's stuck
+/// * `now` - The current time
+pub fn diagnose(
+    address: Address,
+    executed_nonce: u64,
+    next_nonce: u64,
+    mut waiting: Vec<WaitingTransaction>,
+    stuck_after: Duration,
+    now: DateTime<Utc>,
+) -> AccountDiagnosis {
+    waiting.sort_by(|a, b| {
+        (a.nonce, a.location, a.since, a.hash.as_ref()).cmp(&(
+            b.nonce,
+            b.location,
+            b.since,
+            b.hash.as_ref(),
+        ))
+    });
+
+    let (mut issues, mut actions) = (Vec::new(), Vec::new());
+
+    // Transactions spending nonces that have been spent already can't be executed
+    for tx in waiting.iter().filter(|tx| tx.nonce < executed_nonce) {
+        issues.push(NonceIssue::Orphaned {
+            hash: tx.hash,
+            nonce: tx.nonce,
+        });
+        actions.push(RepairAction::Cancel { hash: tx.hash });
+    }
+
+    // Only one of the transactions spending each nonce can be executed. The one furthest along (in the DAG, and
+    // waiting the longest) is kept, since it's the closest to being executed.
+    let mut by_nonce: BTreeMap<u64, Vec<&WaitingTransaction>> = BTreeMap::new();
+    for tx in waiting.iter().filter(|tx| tx.nonce >= executed_nonce) {
+        by_nonce.entry(tx.nonce).or_default().push(tx);
+    }
+    for (nonce, txs) in by_nonce.iter().filter(|(_, txs)| txs.len() > 1) {
+        issues.push(NonceIssue::Duplicate {
+            nonce: *nonce,
+            hashes: txs.iter().map(|tx| tx.hash).collect(),
+        });
+        actions.extend(
+            txs.iter()
+                .skip(1)
+                .map(|tx| RepairAction::Cancel { hash: tx.hash }),
+        );
+    }
+
+    // Each nonce between the account's last executed transaction and its highest waiting one has to be spent
+    if let Some(highest) = by_nonce.keys().next_back() {
+        let missing: Vec<u64> = (executed_nonce..*highest)
+            .filter(|nonce| !by_nonce.contains_key(nonce))
+            .take(MAX_REPORTED_MISSING_NONCES)
+            .collect();
+
+        if !missing.is_empty() {
+            actions.extend(
+                missing
+                    .iter()
+                    .map(|nonce| RepairAction::ResubmitMissingNonce { nonce: *nonce }),
+            );
+            issues.push(NonceIssue::Gap { missing });
+        }
+    }
+
+    // Everything else waits on the transaction spending the next nonce
+    if let Some(next) = by_nonce.get(&executed_nonce).and_then(|txs| txs.first()) {
+        let waited = now - next.since;
+
+        if waited >= stuck_after {
+            issues.push(NonceIssue::Stuck {
+                hash: next.hash,
+                nonce: next.nonce,
+                waiting_secs: waited.num_seconds().max(0) as u64,
+            });
+            actions.push(RepairAction::Rebuild { hash: next.hash });
+        }
+    }
+
+    AccountDiagnosis {
+        address,
+        executed_nonce,
+        next_nonce,
+        waiting,
+        issues,
+        actions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::crypto::blake3; // Import the blake3 hashing module
+    use super::*; // Import names from our parent module
+
+    /// Makes a waiting transaction spending the given nonce, that started waiting the given number of seconds ago.
+    fn waiting(
+        name: &[u8],
+        nonce: u64,
+        location: TransactionLocation,
+        secs_ago: i64,
+        now: DateTime<Utc>,
+    ) -> WaitingTransaction {
+        WaitingTransaction {
+            hash: blake3::hash_slice(name),
+            nonce,
+            location,
+            since: now - Duration::seconds(secs_ago),
+            published: true,
+        }
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let now = Utc::now();
+        let alice = blake3::hash_slice(b"alice");
+        let stuck_after = Duration::seconds(DEFAULT_STUCK_AFTER_SECS as i64);
+
+        // Nothing's wrong with transactions that follow on from each other, and haven't waited long
+        let healthy = diagnose(
+            alice,
+            2,
+            4,
+            vec![
+                waiting(b"b", 3, TransactionLocation::Mempool, 10, now),
+                waiting(b"a", 2, TransactionLocation::Dag, 10, now),
+            ],
+            stuck_after,
+            now,
+        );
+        assert!(healthy.is_healthy());
+        assert_eq!(healthy.waiting[0].nonce, 2);
+
+        // Gaps, duplicates, orphans & stuck transactions are each reported, along with what to do about them
+        let sick = diagnose(
+            alice,
+            2,
+            3,
+            vec![
+                waiting(b"orphan", 1, TransactionLocation::Mempool, 10, now),
+                waiting(b"original", 2, TransactionLocation::Dag, 3600, now),
+                waiting(b"replacement", 2, TransactionLocation::Mempool, 60, now),
+                waiting(b"held back", 5, TransactionLocation::Mempool, 60, now),
+            ],
+            stuck_after,
+            now,
+        );
+        assert_eq!(
+            sick.issues,
+            vec![
+                NonceIssue::Orphaned {
+                    hash: blake3::hash_slice(b"orphan"),
+                    nonce: 1
+                },
+                NonceIssue::Duplicate {
+                    nonce: 2,
+                    hashes: vec![
+                        blake3::hash_slice(b"original"),
+                        blake3::hash_slice(b"replacement")
+                    ]
+                },
+                NonceIssue::Gap {
+                    missing: vec![3, 4]
+                },
+                NonceIssue::Stuck {
+                    hash: blake3::hash_slice(b"original"),
+                    nonce: 2,
+                    waiting_secs: 3600
+                },
+            ]
+        );
+        assert_eq!(
+            sick.actions,
+            vec![
+                RepairAction::Cancel {
+                    hash: blake3::hash_slice(b"orphan")
+                },
+                RepairAction::Cancel {
+                    hash: blake3::hash_slice(b"replacement")
+                },
+                RepairAction::ResubmitMissingNonce { nonce: 3 },
+                RepairAction::ResubmitMissingNonce { nonce: 4 },
+                RepairAction::Rebuild {
+                    hash: blake3::hash_slice(b"original")
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,576 @@
+use chrono::{DateTime, Duration, Utc}; // Import time utilities
+
+use num::BigUint; // Add support for large unsigned integers
+
+use rand::RngCore; // Import the random number generator
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::{HashMap, VecDeque}; // Import the map & queue types
+
+use super::{
+    super::{
+        super::{common::address::Address, crypto::hash::Hash},
+        types::transaction::Transaction,
+    },
+    conflicts::DoubleSpendAlert,
+    proposal::{Operation, Proposal},
+    vote::Vote,
+}; // Import the address, proposal, vote, double spend & transaction types
+
+/// The number of events that the feed holds onto for subscribers that haven't polled them yet.
+pub const DEFAULT_FEED_CAPACITY: usize = 4096;
+
+/// The number of seconds that a subscription may go without being polled before it is dropped.
+pub const SUBSCRIPTION_IDLE_SECS: i64 = 300;
+
+/// The greatest number of subscriptions that the feed keeps open at once.
+pub const MAX_SUBSCRIPTIONS: usize = 256;
+
+/// The greatest number of events handed out by a single poll.
+pub const MAX_POLL_SIZE: usize = 512;
+
+/// The name of the param that transactions are appended to the ledger through.
+pub const TRANSACTIONS_PARAM: &str = "ledger::transactions";
+
+/// An error encountered while subscribing to, or polling, the event feed.
+#[derive(Debug, Fail, PartialEq)]
+pub enum EventError {
+    #[fail(
+        display = "no subscription with id {} is open (it may have expired)",
+        id
+    )]
+    NoSuchSubscription {
+        id: String, // The id of the subscription
+    },
+    #[fail(display = "the node already has {} subscriptions open", open)]
+    TooManySubscriptions {
+        open: usize, // The number of open subscriptions
+    },
+}
+
+/// The kinds of events that the node publishes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A proposal that the node hadn't seen before
+    Proposal,
+
+    /// A vote counted towards a pending proposal
+    Vote,
+
+    /// A transaction proposed for the ledger
+    Transaction,
+
+    /// Competing transactions spending the same nonce
+    DoubleSpend,
+
+    /// A change to an account's balance made by an executed transaction
+    BalanceChange,
+}
+
+/// Something observed by the node that subscribers may want to hear about.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Event {
+    /// A proposal that the node hadn't seen before
+    Proposal { proposal: Proposal },
+
+    /// A vote counted towards a pending proposal
+    Vote { vote: Vote },
+
+    /// A transaction proposed for the ledger
+    Transaction { tx: Transaction },
+
+    /// Competing transactions spending the same nonce
+    DoubleSpend { alert: DoubleSpendAlert },
+
+    /// A change to an account's balance made by an executed transaction
+    BalanceChange { change: BalanceChange },
+}
+
+/// A change to an account's balance made by an executed transaction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BalanceChange {
+    /// The address of the account
+    pub address: String,
+
+    /// The hash of the transaction that changed the balance
+    pub tx_hash: Hash,
+
+    /// The balance of the account before the transaction was executed
+    pub before: BigUint,
+
+    /// The balance of the account once the transaction was executed
+    pub after: BigUint,
+}
+
+/// Implement a set of event helper methods.
+impl Event {
+    /// Gets the kind of the event.
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::Proposal { .. } => EventKind::Proposal,
+            Self::Vote { .. } => EventKind::Vote,
+            Self::Transaction { .. } => EventKind::Transaction,
+            Self::DoubleSpend { .. } => EventKind::DoubleSpend,
+            Self::BalanceChange { .. } => EventKind::BalanceChange,
+        }
+    }
+}
+
+/// The events that a subscriber wants to hear about. Each criterion only narrows down the kind of event that it
+/// concerns (e.g. min_value has no bearing on votes), so kinds should be given to drop the other kinds altogether.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct EventFilter {
+    /// Only events of the given kinds, or every kind if none are given
+    #[serde(default)]
+    pub kinds: Option<Vec<EventKind>>,
+
+    /// Only proposals touching the given param (e.g. ledger::transactions)
+    #[serde(default)]
+    pub param: Option<String>,
+
+    /// Only votes for, and proposals with, the given proposal id
+    #[serde(default)]
+    pub proposal_id: Option<Hash>,
+
+    /// Only transactions (or double spends involving a transaction) sending at least the given number of finks
+    #[serde(default)]
+    pub min_value: Option<BigUint>,
+
+    /// Only transactions sent from or to, and balance changes of, any of the given accounts
+    #[serde(default)]
+    pub addresses: Option<Vec<Address>>,
+}
+
+/// Implement a set of filter helper methods.
+impl EventFilter {
+    /// Checks whether or not the given event passes the filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event that should be checked
+    pub fn matches(&self, event: &Event) -> bool {
+        if let Some(kinds) = &self.kinds {
+            if !kinds.contains(&event.kind()) {
+                return false;
+            }
+        }
+
+        match event {
+            Event::Proposal { proposal } => {
+                self.proposal_id
+                    .map_or(true, |id| id == proposal.proposal_id)
+                    && self.param.as_ref().map_or(true, |param| {
+                        proposal.proposal_data.affected_params().contains(param)
+                    })
+            }
+            Event::Vote { vote } => self
+                .proposal_id
+                .map_or(true, |id| id == vote.target_proposal),
+            Event::Transaction { tx } => {
+                self.min_value
+                    .as_ref()
+                    .map_or(true, |min| tx.transaction_data.value >= *min)
+                    && self.addresses.as_ref().map_or(true, |addresses| {
+                        addresses.contains(&tx.transaction_data.sender)
+                            || addresses.contains(&tx.transaction_data.recipient)
+                    })
+            }
+            Event::DoubleSpend { alert } => self.min_value.as_ref().map_or(true, |min| {
+                alert.spends.iter().any(|spend| spend.value >= *min)
+            }),
+            Event::BalanceChange { change } => self.addresses.as_ref().map_or(true, |addresses| {
+                addresses
+                    .iter()
+                    .any(|address| address.to_str() == change.address)
+            }),
+        }
+    }
+}
+
+/// An event, along with its position in the feed.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SequencedEvent {
+    /// The position of the event in the feed, counting every event ever published
+    pub seq: u64,
+
+    /// The event itself
+    pub event: Event,
+}
+
+/// The events handed out by a single poll of a subscription.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PolledEvents {
+    /// Each of the matching events published since the last poll, in order
+    pub events: Vec<SequencedEvent>,
+
+    /// The number of events that fell out of the feed before the subscription could be checked against them
+    pub missed: u64,
+}
+
+/// A subscriber's place in the feed.
+struct Subscription {
+    /// The events that the subscriber wants to hear about
+    filter: EventFilter,
+
+    /// The sequence number of the next event to check
+    cursor: u64,
+
+    /// When the subscription was last polled
+    last_polled: DateTime<Utc>,
+}
+
+/// A bounded feed of the proposals, votes & transactions seen by the node, which subscribers poll through filters of
+/// their choosing, so that only matching events ever leave the node.
+pub struct EventFeed {
+    /// The most recent events, oldest first
+    events: VecDeque<SequencedEvent>,
+
+    /// The sequence number that the next event will be published with
+    next_seq: u64,
+
+    /// The greatest number of events held onto at once
+    capacity: usize,
+
+    /// Each of the open subscriptions, by id
+    subscriptions: HashMap<String, Subscription>,
+}
+
+impl Default for EventFeed {
+    /// Holds onto the default number of events.
+    fn default() -> Self {
+        Self::new(DEFAULT_FEED_CAPACITY)
+    }
+}
+
+/// Implement a set of event feed helper methods.
+impl EventFeed {
+    /// Initializes a new, empty event feed.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The greatest number of events to hold onto at once
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::new(),
+            next_seq: 0,
+            capacity,
+            subscriptions: HashMap::new(),
+        }
+    }
+
+    /// Publishes the given event to each of the subscribers, dropping the oldest event if the feed is full.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event that should be published
+    pub fn publish(&mut self, event: Event) {
+        // Nobody is listening, so there's no sense in holding onto it
+        if self.subscriptions.is_empty() {
+            self.next_seq += 1;
+
+            return;
+        }
+
+        if self.events.len() >= self.capacity {
+            self.events.pop_front();
+        }
+
+        self.events.push_back(SequencedEvent {
+            seq: self.next_seq,
+            event,
+        });
+        self.next_seq += 1;
+    }
+
+    /// Publishes the given proposal, along with the transaction that it carries, if it appends one to the ledger.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal that should be published
+    pub fn publish_proposal(&mut self, proposal: &Proposal) {
+        let tx = proposed_transaction(proposal);
+
+        self.publish(Event::Proposal {
+            proposal: proposal.clone(),
+        });
+
+        if let Some(tx) = tx {
+            self.publish(Event::Transaction { tx });
+        }
+    }
+
+    /// Publishes a balance change for each of the accounts whose balance differs between the given balances, in order
+    /// of address.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - The hash of the executed transaction
+    /// * `before` - The balance of each account before the transaction was executed
+    /// * `after` - The balance of each account once the transaction was executed
+    pub fn publish_balance_changes(
+        &mut self,
+        tx_hash: Hash,
+        before: &HashMap<String, BigUint>,
+        after: &HashMap<String, BigUint>,
+    ) {
+        // Nobody is listening, so there's no sense in working out what changed
+        if self.subscriptions.is_empty() {
+            return;
+        }
+
+        let mut addresses: Vec<&String> = before.keys().chain(after.keys()).collect();
+        addresses.sort();
+        addresses.dedup();
+
+        for address in addresses {
+            let (old, new) = (before.get(address), after.get(address));
+            if old == new {
+                continue;
+            }
+
+            self.publish(Event::BalanceChange {
+                change: BalanceChange {
+                    address: address.clone(),
+                    tx_hash,
+                    before: old.cloned().unwrap_or_default(),
+                    after: new.cloned().unwrap_or_default(),
+                },
+            });
+        }
+    }
+
+    /// Opens a new subscription to the events published from now on that pass the given filter, and gets its id.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The events that the subscriber wants to hear about
+    pub fn subscribe(&mut self, filter: EventFilter) -> Result<String, EventError> {
+        self.expire_idle();
+
+        if self.subscriptions.len() >= MAX_SUBSCRIPTIONS {
+            return Err(EventError::TooManySubscriptions {
+                open: self.subscriptions.len(),
+            });
+        }
+
+        let mut id = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut id);
+        let id = bs58::encode(id).into_string();
+
+        self.subscriptions.insert(
+            id.clone(),
+            Subscription {
+                filter,
+                cursor: self.next_seq,
+                last_polled: Utc::now(),
+            },
+        );
+
+        Ok(id)
+    }
+
+    /// Gets the matching events published since the subscription was last polled, up to the given limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the subscription
+    /// * `limit` - The greatest number of events to hand out
+    pub fn poll(&mut self, id: &str, limit: usize) -> Result<PolledEvents, EventError> {
+        self.expire_idle();
+
+        let sub = self
+            .subscriptions
+            .get_mut(id)
+            .ok_or_else(|| EventError::NoSuchSubscription { id: id.to_owned() })?;
+        sub.last_polled = Utc::now();
+
+        // Any events older than the oldest one held onto can no longer be checked
+        let oldest = self.events.front().map_or(self.next_seq, |event| event.seq);
+        let missed = oldest.saturating_sub(sub.cursor);
+        sub.cursor = sub.cursor.max(oldest);
+
+        let mut events = Vec::new();
+        for event in self.events.iter().skip((sub.cursor - oldest) as usize) {
+            if events.len() >= limit.min(MAX_POLL_SIZE) {
+                break;
+            }
+
+            sub.cursor = event.seq + 1;
+
+            if sub.filter.matches(&event.event) {
+                events.push(event.clone());
+            }
+        }
+
+        // Nothing else was published, so the subscription has caught up
+        if events.len() < limit.min(MAX_POLL_SIZE) {
+            sub.cursor = self.next_seq;
+        }
+
+        self.trim();
+
+        Ok(PolledEvents { events, missed })
+    }
+
+    /// Closes the given subscription.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the subscription
+    pub fn unsubscribe(&mut self, id: &str) -> Result<(), EventError> {
+        self.subscriptions
+            .remove(id)
+            .map(|_| self.trim())
+            .ok_or_else(|| EventError::NoSuchSubscription { id: id.to_owned() })
+    }
+
+    /// Drops each of the subscriptions that haven't been polled in a while.
+    fn expire_idle(&mut self) {
+        let cutoff = Utc::now() - Duration::seconds(SUBSCRIPTION_IDLE_SECS);
+        self.subscriptions.retain(|_, sub| sub.last_polled > cutoff);
+    }
+
+    /// Drops each of the events that every subscriber has already been checked against.
+    fn trim(&mut self) {
+        let earliest = self
+            .subscriptions
+            .values()
+            .map(|sub| sub.cursor)
+            .min()
+            .unwrap_or(self.next_seq);
+
+        while self
+            .events
+            .front()
+            .map_or(false, |event| event.seq < earliest)
+        {
+            self.events.pop_front();
+        }
+    }
+}
+
+/// Gets the transaction that the given proposal appends to the ledger, if it appends one.
+///
+/// # Arguments
+///
+/// * `proposal` - The proposal
+pub fn proposed_transaction(proposal: &Proposal) -> Option<Transaction> {
+    match &proposal.proposal_data.operation {
+        Operation::Append { value_to_append }
+            if proposal.proposal_data.param_name == TRANSACTIONS_PARAM =>
+        {
+            bincode::deserialize::<Transaction>(value_to_append).ok()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::{common::address::Address, crypto::blake3}; // Import the address & hashing types
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_event_feed() {
+        let mut feed = EventFeed::new(8);
+
+        let tx = |value: u8| {
+            Transaction::new(
+                value as u64,
+                Address::default(),
+                Address::default(),
+                BigUint::from(value),
+                b"",
+                vec![],
+            )
+        };
+
+        // Only transactions worth at least 10 finks should be handed out
+        let large = feed
+            .subscribe(EventFilter {
+                kinds: Some(vec![EventKind::Transaction]),
+                min_value: Some(BigUint::from(10 as u8)),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // Only proposals touching the ledger's transactions should be handed out
+        let ledger = feed
+            .subscribe(EventFilter {
+                kinds: Some(vec![EventKind::Proposal]),
+                param: Some(TRANSACTIONS_PARAM.to_owned()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        for value in &[5, 50] {
+            feed.publish_proposal(&Proposal::for_transaction(&tx(*value)));
+        }
+        feed.publish(Event::Vote {
+            vote: Vote {
+                target_proposal: blake3::hash_slice(b"proposal"),
+                in_favor: true,
+                signature: None,
+            },
+        });
+
+        let polled = feed.poll(&large, 10).unwrap();
+        assert_eq!(polled.missed, 0);
+        assert_eq!(polled.events.len(), 1);
+        match &polled.events[0].event {
+            Event::Transaction { tx } => {
+                assert_eq!(tx.transaction_data.value, BigUint::from(50 as u8))
+            }
+            _ => panic!("expected a transaction"),
+        }
+        assert!(feed.poll(&large, 10).unwrap().events.is_empty()); // Events are only handed out once
+
+        // A subscriber that falls behind is told how much it missed
+        for _ in 0..10 {
+            feed.publish_proposal(&Proposal::for_transaction(&tx(1)));
+        }
+        let polled = feed.poll(&ledger, 10).unwrap();
+        assert_eq!(polled.missed, 17);
+        assert!(polled
+            .events
+            .iter()
+            .all(|event| event.event.kind() == EventKind::Proposal));
+
+        feed.unsubscribe(&large).unwrap();
+        assert_eq!(
+            feed.poll(&large, 10).err(),
+            Some(EventError::NoSuchSubscription { id: large })
+        );
+
+        // Balance changes are only handed out for the accounts asked about
+        let (alice, bob) = (blake3::hash_slice(b"alice"), blake3::hash_slice(b"bob"));
+        let wallet = feed
+            .subscribe(EventFilter {
+                kinds: Some(vec![EventKind::BalanceChange]),
+                addresses: Some(vec![bob]),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let mut before = HashMap::new();
+        before.insert(alice.to_str(), BigUint::from(10 as u8));
+        let mut after = before.clone();
+        after.insert(alice.to_str(), BigUint::from(7 as u8));
+        after.insert(bob.to_str(), BigUint::from(3 as u8));
+        feed.publish_balance_changes(blake3::hash_slice(b"tx"), &before, &after);
+
+        let polled = feed.poll(&wallet, 10).unwrap();
+        assert_eq!(polled.events.len(), 1);
+        match &polled.events[0].event {
+            Event::BalanceChange { change } => {
+                assert_eq!(change.address, bob.to_str());
+                assert_eq!(change.before, BigUint::from(0 as u8));
+                assert_eq!(change.after, BigUint::from(3 as u8));
+            }
+            _ => panic!("expected a balance change"),
+        }
+    }
+}
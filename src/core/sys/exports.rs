@@ -0,0 +1,374 @@
+use chrono::{DateTime, Duration, Utc}; // Import time utilities
+
+use rand::RngCore; // Import the random number generator
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::HashMap; // Import the map type
+
+use super::super::types::replay::{ReplayPage, ReplayStart}; // Import the replay types
+
+/// The name of the tree in which open exports are persisted, so that they outlive a restart of the node.
+pub const EXPORTS_TREE: &str = "exports";
+
+/// The number of seconds that an export may go without being resumed before it is dropped, by default.
+pub const DEFAULT_EXPORT_TTL_SECS: u64 = 3600;
+
+/// The greatest number of exports that the node keeps open at once.
+pub const MAX_EXPORTS: usize = 64;
+
+/// An error encountered while opening, resuming, or acknowledging an export.
+#[derive(Debug, Fail, PartialEq)]
+pub enum ExportError {
+    #[fail(display = "no export with id {} is open (it may have expired)", id)]
+    NoSuchExport {
+        id: String, // The id of the export
+    },
+    #[fail(display = "the node already has {} exports open", open)]
+    TooManyExports {
+        open: usize, // The number of open exports
+    },
+    #[fail(
+        display = "cursor {} wasn't handed out with the last page of export {}",
+        cursor, id
+    )]
+    UnexpectedAck {
+        id: String,     // The id of the export
+        cursor: String, // The cursor that was acknowledged
+    },
+}
+
+/// A long export of the ledger, whose progress is kept by the node so that a consumer cut off partway through can
+/// resume from the last page it acknowledged, rather than starting over.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExportSession {
+    /// The id of the export
+    pub id: String,
+
+    /// Where the export began, or None if it began at the genesis
+    pub start: Option<ReplayStart>,
+
+    /// The cursor handed out with the last page that the consumer acknowledged, if it has acknowledged any
+    pub acknowledged: Option<String>,
+
+    /// The number of transactions in the pages acknowledged so far
+    pub exported: u64,
+
+    /// The cursor handed out with the last page, if the consumer hasn't acknowledged it yet
+    pub pending: Option<String>,
+
+    /// The number of transactions in the last page, if the consumer hasn't acknowledged it yet
+    pub pending_events: u64,
+
+    /// When the export is dropped, unless it is resumed before then
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Implement a set of export session helper methods.
+impl ExportSession {
+    /// Gets where the next page of the export begins: just after the last page acknowledged, or where the export
+    /// began if nothing has been acknowledged yet.
+    pub fn resume_from(&self) -> Option<ReplayStart> {
+        match &self.acknowledged {
+            Some(cursor) => Some(ReplayStart::Cursor(cursor.clone())),
+            None => self.start.clone(),
+        }
+    }
+}
+
+/// A page of an export, along with where the export stands once the page was handed out.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ExportPage {
+    /// The export
+    pub session: ExportSession,
+
+    /// The page
+    pub page: ReplayPage,
+}
+
+/// Each of the exports that the node has open, persisted alongside the ledger.
+pub struct ExportSessions {
+    /// Each of the open exports, by id
+    sessions: HashMap<String, ExportSession>,
+
+    /// The tree in which open exports are persisted, if it could be opened
+    store: Option<sled::Tree>,
+
+    /// The number of seconds that an export may go without being resumed before it is dropped
+    pub ttl_secs: u64,
+}
+
+impl Default for ExportSessions {
+    /// Keeps exports in memory alone, for the default amount of time.
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+/// Implement a set of export session table helper methods.
+impl ExportSessions {
+    /// Makes a table persisting exports to the given tree, restoring any that were open before the last shutdown.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The tree in which open exports are persisted, if any
+    pub fn new(store: Option<sled::Tree>) -> Self {
+        let sessions = store
+            .iter()
+            .flat_map(|tree| tree.iter().values())
+            .filter_map(|value| bincode::deserialize::<ExportSession>(&value.ok()?).ok())
+            .map(|session| (session.id.clone(), session))
+            .collect();
+
+        Self {
+            sessions,
+            store,
+            ttl_secs: DEFAULT_EXPORT_TTL_SECS,
+        }
+    }
+
+    /// Opens an export beginning at the given point, and gets it.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Where the export should begin, or None to begin at the genesis
+    /// * `now` - The current time
+    pub fn open(
+        &mut self,
+        start: Option<ReplayStart>,
+        now: DateTime<Utc>,
+    ) -> Result<ExportSession, ExportError> {
+        self.expire(now);
+
+        if self.sessions.len() >= MAX_EXPORTS {
+            return Err(ExportError::TooManyExports {
+                open: self.sessions.len(),
+            });
+        }
+
+        let mut id = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut id);
+
+        let session = ExportSession {
+            id: bs58::encode(id).into_string(),
+            start,
+            acknowledged: None,
+            exported: 0,
+            pending: None,
+            pending_events: 0,
+            expires_at: self.deadline(now),
+        };
+        self.save(session.clone());
+
+        Ok(session)
+    }
+
+    /// Gets the export with the given id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the export
+    /// * `now` - The current time
+    pub fn get(&mut self, id: &str, now: DateTime<Utc>) -> Result<ExportSession, ExportError> {
+        self.expire(now);
+
+        self.sessions
+            .get(id)
+            .cloned()
+            .ok_or_else(|| ExportError::NoSuchExport { id: id.to_owned() })
+    }
+
+    /// Records that the consumer has safely stored the last page handed out, if a cursor is given, and keeps the
+    /// export open for a while longer. Acknowledging the same page twice is harmless. Results in the export, which
+    /// resumes just after the last page acknowledged.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the export
+    /// * `cursor` - The cursor handed out with the page being acknowledged, if any
+    /// * `now` - The current time
+    pub fn acknowledge(
+        &mut self,
+        id: &str,
+        cursor: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<ExportSession, ExportError> {
+        let mut session = self.get(id, now)?;
+
+        if let Some(cursor) = cursor {
+            if session.pending.as_deref() == Some(cursor) {
+                session.acknowledged = session.pending.take();
+                session.exported += session.pending_events;
+                session.pending_events = 0;
+            } else if session.acknowledged.as_deref() != Some(cursor) {
+                return Err(ExportError::UnexpectedAck {
+                    id: id.to_owned(),
+                    cursor: cursor.to_owned(),
+                });
+            }
+        }
+
+        session.expires_at = self.deadline(now);
+        self.save(session.clone());
+
+        Ok(session)
+    }
+
+    /// Records the page just handed out for the given export, so that it can be acknowledged. Results in the export.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the export
+    /// * `page` - The page handed out
+    pub fn handed_out(
+        &mut self,
+        id: &str,
+        page: &ReplayPage,
+    ) -> Result<ExportSession, ExportError> {
+        let mut session = self
+            .sessions
+            .get(id)
+            .cloned()
+            .ok_or_else(|| ExportError::NoSuchExport { id: id.to_owned() })?;
+
+        session.pending = page.next_cursor.clone();
+        session.pending_events = page.events.len() as u64;
+        self.save(session.clone());
+
+        Ok(session)
+    }
+
+    /// Closes the export with the given id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the export
+    pub fn close(&mut self, id: &str) -> Result<(), ExportError> {
+        if self.sessions.remove(id).is_none() {
+            return Err(ExportError::NoSuchExport { id: id.to_owned() });
+        }
+
+        self.forget(id);
+
+        Ok(())
+    }
+
+    /// Gets the time at which an export resumed at the given time is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    fn deadline(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now + Duration::seconds(self.ttl_secs as i64)
+    }
+
+    /// Drops each of the exports that haven't been resumed in time.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    fn expire(&mut self, now: DateTime<Utc>) {
+        let expired: Vec<String> = self
+            .sessions
+            .values()
+            .filter(|session| session.expires_at <= now)
+            .map(|session| session.id.clone())
+            .collect();
+
+        for id in expired {
+            self.sessions.remove(&id);
+            self.forget(&id);
+        }
+    }
+
+    /// Keeps the given export, persisting it if possible.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The export
+    fn save(&mut self, session: ExportSession) {
+        if let Some(tree) = &self.store {
+            let persisted = bincode::serialize(&session)
+                .map_err(|e| e.to_string())
+                .and_then(|encoded| {
+                    tree.insert(session.id.as_bytes(), encoded)
+                        .map_err(|e| e.to_string())
+                });
+            if let Err(e) = persisted {
+                warn!("Failed to persist export {}: {}", session.id, e);
+            }
+        }
+
+        self.sessions.insert(session.id.clone(), session);
+    }
+
+    /// Removes the export with the given id from the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the export
+    fn forget(&self, id: &str) {
+        if let Some(tree) = &self.store {
+            if let Err(e) = tree.remove(id.as_bytes()) {
+                warn!("Failed to forget export {}: {}", id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    /// Makes an empty page ending at the given cursor.
+    fn page(cursor: &str) -> ReplayPage {
+        ReplayPage {
+            events: Vec::new(),
+            next_cursor: Some(cursor.to_owned()),
+        }
+    }
+
+    #[test]
+    fn test_export_sessions() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let now = Utc::now();
+        let mut exports = ExportSessions::new(db.open_tree(EXPORTS_TREE).ok());
+
+        let id = exports.open(None, now).unwrap().id;
+        exports.handed_out(&id, &page("99:a")).unwrap();
+
+        // The consumer was cut off before storing the page, so the export resumes from the start
+        assert_eq!(
+            exports.acknowledge(&id, None, now).unwrap().resume_from(),
+            None
+        );
+
+        // Once a page is acknowledged, the export resumes just after it, even after the node restarts
+        exports.handed_out(&id, &page("99:a")).unwrap();
+        exports.acknowledge(&id, Some("99:a"), now).unwrap();
+        assert!(exports.acknowledge(&id, Some("99:a"), now).is_ok());
+        assert_eq!(
+            exports.acknowledge(&id, Some("199:b"), now),
+            Err(ExportError::UnexpectedAck {
+                id: id.clone(),
+                cursor: "199:b".to_owned()
+            })
+        );
+
+        let mut restarted = ExportSessions::new(db.open_tree(EXPORTS_TREE).ok());
+        assert_eq!(
+            restarted.get(&id, now).unwrap().resume_from(),
+            Some(ReplayStart::Cursor("99:a".to_owned()))
+        );
+
+        // Exports left alone for too long are dropped
+        let later = now + Duration::seconds(DEFAULT_EXPORT_TTL_SECS as i64);
+        assert_eq!(
+            restarted.get(&id, later),
+            Err(ExportError::NoSuchExport { id: id.clone() })
+        );
+        assert!(ExportSessions::new(db.open_tree(EXPORTS_TREE).ok())
+            .sessions
+            .is_empty());
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::VecDeque; // Import the queue type
+
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::{super::super::crypto::hash::Hash, proposal::Proposal}; // Import the hash & proposal types
+
+/// The name of the tree in the ledger's database in which the history of executed proposals is persisted.
+pub const HISTORY_TREE: &str = "executed";
+
+/// The number of executed proposals remembered before the oldest are forgotten.
+pub const RETAINED_EXECUTIONS: usize = 1024;
+
+/// What came of executing a proposal.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum ExecutionOutcome {
+    /// The proposal was applied in full
+    Applied,
+
+    /// The proposal passed, but couldn't be applied
+    Failed { error: String },
+}
+
+/// A proposal executed by the node, along with what came of it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExecutedProposal {
+    /// The position of the execution in the node's history, counting every proposal ever executed
+    pub seq: u64,
+
+    /// The hash of the proposal
+    pub proposal_id: Hash,
+
+    /// The name of the proposal
+    pub proposal_name: String,
+
+    /// Each of the params that the proposal touched
+    pub params: Vec<String>,
+
+    /// The operation carried out on the params (e.g. amend, append)
+    pub operation: String,
+
+    /// What came of executing the proposal
+    pub outcome: ExecutionOutcome,
+
+    /// When the proposal was executed
+    pub executed_at: DateTime<Utc>,
+
+    /// The hash of the state that the proposal left behind: the config's, for config changes, or the appended
+    /// transaction's, for changes to the ledger
+    pub state_hash: Option<Hash>,
+}
+
+/// A rolling history of the most recent proposals executed by the node.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionHistory {
+    /// The most recent executions, oldest first
+    entries: VecDeque<ExecutedProposal>,
+}
+
+/// Implement a set of execution history helper methods.
+impl ExecutionHistory {
+    /// Rebuilds a history from the given persisted executions, keeping only the most recent.
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - The executions that were persisted, in any order
+    pub fn from_entries(entries: impl Iterator<Item = ExecutedProposal>) -> Self {
+        let mut entries: Vec<ExecutedProposal> = entries.collect();
+        entries.sort_by_key(|entry| entry.seq);
+
+        let skip = entries.len().saturating_sub(RETAINED_EXECUTIONS);
+
+        Self {
+            entries: entries.into_iter().skip(skip).collect(),
+        }
+    }
+
+    /// Remembers the execution of the given proposal, forgetting the oldest execution if the history is full. Results
+    /// in the new entry, along with the sequence numbers of any entries that were forgotten.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal that was executed
+    /// * `outcome` - What came of executing the proposal
+    /// * `state_hash` - The hash of the state that the proposal left behind, if any
+    pub fn record(
+        &mut self,
+        proposal: &Proposal,
+        outcome: ExecutionOutcome,
+        state_hash: Option<Hash>,
+    ) -> (ExecutedProposal, Vec<u64>) {
+        let entry = ExecutedProposal {
+            seq: self.entries.back().map_or(0, |last| last.seq + 1),
+            proposal_id: proposal.proposal_id,
+            proposal_name: proposal.proposal_name.clone(),
+            params: proposal.proposal_data.affected_params(),
+            operation: proposal.proposal_data.operation.to_string(),
+            outcome,
+            executed_at: Utc::now(),
+            state_hash,
+        };
+        self.entries.push_back(entry.clone());
+
+        let mut pruned = Vec::new();
+        while self.entries.len() > RETAINED_EXECUTIONS {
+            if let Some(oldest) = self.entries.pop_front() {
+                pruned.push(oldest.seq);
+            }
+        }
+
+        (entry, pruned)
+    }
+
+    /// Gets the most recent executions, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The greatest number of executions to get
+    /// * `param` - Only get the executions of proposals touching the given param, if one is given
+    pub fn recent(&self, limit: usize, param: Option<&str>) -> Vec<ExecutedProposal> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| param.map_or(true, |param| entry.params.iter().any(|p| p == param)))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::proposal::{Operation, ProposalData}; // Import the proposal body types
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_execution_history() {
+        let rename = Proposal::new(
+            "rename".to_owned(),
+            ProposalData::new(
+                "config::network_name".to_owned(),
+                Operation::Amend {
+                    amended_value: b"andromeda".to_vec(),
+                },
+            ),
+        );
+        let append = Proposal::new(
+            "append".to_owned(),
+            ProposalData::new(
+                "ledger::transactions".to_owned(),
+                Operation::Append {
+                    value_to_append: vec![],
+                },
+            ),
+        );
+
+        let mut history = ExecutionHistory::default();
+        history.record(&rename, ExecutionOutcome::Applied, None);
+
+        // Fill the history up, so that the rename is forgotten
+        let mut pruned = Vec::new();
+        for _ in 0..RETAINED_EXECUTIONS {
+            pruned.extend(
+                history
+                    .record(
+                        &append,
+                        ExecutionOutcome::Failed {
+                            error: "invalid transaction".to_owned(),
+                        },
+                        None,
+                    )
+                    .1,
+            );
+        }
+        assert_eq!(pruned, vec![0]);
+        assert!(history.recent(10, Some("config::network_name")).is_empty());
+
+        let (entry, _) = history.record(&rename, ExecutionOutcome::Applied, None);
+        assert_eq!(entry.seq, RETAINED_EXECUTIONS as u64 + 1);
+        assert_eq!(entry.params, vec!["config::network_name".to_owned()]);
+        assert_eq!(history.recent(1, None), vec![entry.clone()]);
+        assert_eq!(
+            history.recent(10, Some("config::network_name")),
+            vec![entry.clone()]
+        );
+
+        // The history survives being persisted & reloaded, whatever order it comes back in
+        let reloaded = ExecutionHistory::from_entries(history.entries.iter().rev().cloned());
+        assert_eq!(reloaded.recent(1, None), vec![entry]);
+        assert_eq!(reloaded.entries.len(), RETAINED_EXECUTIONS);
+    }
+}
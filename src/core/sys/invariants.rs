@@ -0,0 +1,157 @@
+use std::collections::HashMap; // Import the map type
+
+use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+use super::super::types::issuance::IssuanceSchedule; // Import the issuance schedule type
+
+/// The number of transactions synchronized from the network between checks of the ledger's invariants, by default.
+pub const DEFAULT_AUDIT_INTERVAL: usize = 64;
+
+/// A rule that every state of the ledger must follow, broken by a state.
+#[derive(Debug, Fail, PartialEq)]
+pub enum InvariantViolation {
+    #[fail(
+        display = "the ledger holds {} finks, more than the {} finks that can have been issued",
+        total, ceiling
+    )]
+    SupplyExceeded {
+        total: String,   // The number of finks held between each of the accounts in the state
+        ceiling: String, // The greatest number of finks that can have been issued
+    },
+    #[fail(
+        display = "account {} holds {} finks, more than the {} finks that can have been issued",
+        account, balance, ceiling
+    )]
+    BalanceExceeded {
+        account: String, // The address of the account
+        balance: String, // The number of finks held by the account
+        ceiling: String, // The greatest number of finks that can have been issued
+    },
+}
+
+/// Gets the greatest number of finks that can have been issued by the time the ledger reaches the given height: those
+/// allocated in the genesis, plus whichever is greater of those recorded as minted and those that the issuance
+/// schedule allows to have been minted by now.
+///
+/// # Arguments
+///
+/// * `genesis` - The number of finks allocated in the genesis
+/// * `minted` - The number of finks recorded as minted since the genesis
+/// * `schedule` - The schedule that mints follow
+/// * `height` - The number of transactions in the ledger
+pub fn supply_ceiling(
+    genesis: &BigUint,
+    minted: &BigUint,
+    schedule: &IssuanceSchedule,
+    height: u64,
+) -> BigUint {
+    let mut scheduled = BigUint::zero();
+
+    if schedule.is_enabled() {
+        for epoch in 0..schedule.epoch_at(height) {
+            let reward = schedule.reward_for_epoch(epoch);
+
+            // Nothing more is minted once the reward has halved away
+            if reward.is_zero() {
+                break;
+            }
+
+            scheduled += reward;
+        }
+    }
+
+    genesis + minted.max(&scheduled)
+}
+
+/// Makes sure that the given balances hold no more finks than can have been issued. Balances are unsigned, so an
+/// account overdrawn by a fabricated state shows up as finks appearing from nowhere, which this catches. Results in
+/// the number of finks held between each of the accounts.
+///
+/// # Arguments
+///
+/// * `balances` - The balance of each account in the state
+/// * `ceiling` - The greatest number of finks that can have been issued
+pub fn check_supply(
+    balances: &HashMap<String, BigUint>,
+    ceiling: &BigUint,
+) -> Result<BigUint, InvariantViolation> {
+    let mut total = BigUint::zero();
+
+    for (account, balance) in balances {
+        if balance > ceiling {
+            return Err(InvariantViolation::BalanceExceeded {
+                account: account.clone(),
+                balance: balance.to_string(),
+                ceiling: ceiling.to_string(),
+            });
+        }
+
+        total += balance;
+    }
+
+    if &total > ceiling {
+        return Err(InvariantViolation::SupplyExceeded {
+            total: total.to_string(),
+            ceiling: ceiling.to_string(),
+        });
+    }
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_check_supply() {
+        let schedule = IssuanceSchedule {
+            per_epoch: BigUint::from(8 as u8),
+            epoch_length: 10,
+            halving_epochs: 1,
+        };
+
+        // Three epochs are over by height 35, paying out 8 + 4 + 2
+        let ceiling = supply_ceiling(
+            &BigUint::from(100 as u8),
+            &BigUint::from(12 as u8),
+            &schedule,
+            35,
+        );
+        assert_eq!(ceiling, BigUint::from(114 as u8));
+
+        // Mints recorded beyond the schedule (e.g. under an earlier one) still count
+        assert_eq!(
+            supply_ceiling(
+                &BigUint::from(100 as u8),
+                &BigUint::from(50 as u8),
+                &schedule,
+                35
+            ),
+            BigUint::from(150 as u8)
+        );
+
+        let mut balances = HashMap::new();
+        balances.insert("alice".to_owned(), BigUint::from(60 as u8));
+        balances.insert("bob".to_owned(), BigUint::from(54 as u8));
+        assert_eq!(
+            check_supply(&balances, &ceiling),
+            Ok(BigUint::from(114 as u8))
+        );
+
+        balances.insert("mallory".to_owned(), BigUint::from(1 as u8));
+        assert_eq!(
+            check_supply(&balances, &ceiling),
+            Err(InvariantViolation::SupplyExceeded {
+                total: "115".to_owned(),
+                ceiling: "114".to_owned(),
+            })
+        );
+
+        balances.insert("mallory".to_owned(), BigUint::from(200 as u8));
+        assert!(match check_supply(&balances, &ceiling) {
+            Err(InvariantViolation::BalanceExceeded { account, .. }) => account == "mallory",
+            _ => false,
+        });
+    }
+}
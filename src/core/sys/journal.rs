@@ -0,0 +1,227 @@
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::HashMap; // Import the map type
+
+use super::super::super::{common::address::Address, crypto::hash::Hash}; // Import the address & hash types
+
+/// The name of the tree in the ledger's database in which the votes cast with each of the node's accounts are
+/// journaled.
+pub const VOTE_JOURNAL_TREE: &str = "vote_journal";
+
+/// The number of votes remembered for each account before the oldest are forgotten.
+pub const RETAINED_VOTES_PER_ACCOUNT: u64 = 4096;
+
+/// The number of journaled votes handed out at once, by default.
+pub const DEFAULT_JOURNAL_PAGE: usize = 100;
+
+/// How a journaled vote came to be cast.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteOrigin {
+    /// The node judged the proposal itself, and voted according to its validator's verdict
+    Automatic,
+
+    /// The node's operator confirmed the proposal by hand
+    Confirmed,
+}
+
+/// A receipt for a vote cast with one of the node's accounts, kept so that the account's owner can audit what their
+/// key has been signing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JournaledVote {
+    /// The position of the vote in the voter's journal, counting every vote ever cast with the account
+    pub seq: u64,
+
+    /// The account that cast the vote
+    pub voter: Address,
+
+    /// The hash of the vote itself, as gossiped to the network
+    pub vote_hash: Hash,
+
+    /// The hash of the proposal voted on
+    pub proposal_id: Hash,
+
+    /// The parameter that the proposal would change
+    pub param: String,
+
+    /// The hash of the transaction that the proposal would append, if it proposes one
+    pub transaction: Option<Hash>,
+
+    /// Whether the vote was in favor of the proposal
+    pub in_favor: bool,
+
+    /// Why the vote was cast the way it was (e.g. the validator's reason for rejecting a transaction)
+    pub reason: String,
+
+    /// How the vote came to be cast
+    pub origin: VoteOrigin,
+
+    /// Whether the vote was signed by the node's remote signer, rather than with a key held by the node
+    pub remote: bool,
+
+    /// When the vote was cast
+    pub voted_at: DateTime<Utc>,
+}
+
+/// The votes cast with each of the node's accounts, persisted alongside the ledger.
+#[derive(Default)]
+pub struct VoteJournal {
+    /// The tree in which journaled votes are persisted, if it could be opened
+    store: Option<sled::Tree>,
+
+    /// The seq of the next vote journaled for each account
+    next_seqs: HashMap<Address, u64>,
+}
+
+/// Implement a set of vote journal helper methods.
+impl VoteJournal {
+    /// Makes a journal persisting votes to the given tree, picking up where the votes journaled before the last
+    /// shutdown left off.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - The tree in which journaled votes are persisted, if any
+    pub fn new(store: Option<sled::Tree>) -> Self {
+        let mut next_seqs: HashMap<Address, u64> = HashMap::new();
+
+        for vote in store
+            .iter()
+            .flat_map(|tree| tree.iter().values())
+            .filter_map(|value| bincode::deserialize::<JournaledVote>(&value.ok()?).ok())
+        {
+            let next = next_seqs.entry(vote.voter).or_default();
+            *next = (*next).max(vote.seq + 1);
+        }
+
+        Self { store, next_seqs }
+    }
+
+    /// Journals the given vote for its voter, forgetting the voter's oldest vote if their journal is full. Results in
+    /// the journaled vote.
+    ///
+    /// # Arguments
+    ///
+    /// * `vote` - The vote, without its seq
+    pub fn record(&mut self, mut vote: JournaledVote) -> JournaledVote {
+        let next = self.next_seqs.entry(vote.voter).or_default();
+        vote.seq = *next;
+        *next += 1;
+
+        if let Some(tree) = &self.store {
+            let persisted = bincode::serialize(&vote)
+                .map_err(|e| e.to_string())
+                .and_then(|encoded| {
+                    tree.insert(key(&vote.voter, vote.seq), encoded)
+                        .map_err(|e| e.to_string())
+                })
+                .and_then(|_| {
+                    // Forget the vote that has aged out, if any
+                    if vote.seq >= RETAINED_VOTES_PER_ACCOUNT {
+                        tree.remove(key(&vote.voter, vote.seq - RETAINED_VOTES_PER_ACCOUNT))
+                            .map_err(|e| e.to_string())?;
+                    }
+
+                    Ok(())
+                });
+
+            if let Err(e) = persisted {
+                warn!(
+                    "Failed to journal the vote of {} on proposal {}: {}",
+                    vote.voter, vote.proposal_id, e
+                );
+            }
+        }
+
+        vote
+    }
+
+    /// Gets the most recent votes cast with the given account, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `voter` - The account whose votes should be listed
+    /// * `before` - Only votes with a seq lower than this one are listed, if given, so that older pages can be read
+    /// * `limit` - The greatest number of votes that should be listed
+    pub fn list(&self, voter: &Address, before: Option<u64>, limit: usize) -> Vec<JournaledVote> {
+        let tree = match &self.store {
+            Some(tree) => tree,
+            None => return Vec::new(),
+        };
+
+        tree.range(key(voter, 0)..key(voter, before.unwrap_or(u64::MAX)))
+            .values()
+            .rev()
+            .filter_map(|value| bincode::deserialize(&value.ok()?).ok())
+            .take(limit)
+            .collect()
+    }
+}
+
+/// Gets the key under which the vote with the given seq is journaled for the given voter.
+///
+/// # Arguments
+///
+/// * `voter` - The account that cast the vote
+/// * `seq` - The position of the vote in the voter's journal
+fn key(voter: &Address, seq: u64) -> Vec<u8> {
+    let mut key = voter.to_vec();
+    key.extend_from_slice(&seq.to_be_bytes());
+
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::crypto::blake3; // Import the blake3 hashing module
+    use super::*; // Import names from our parent module
+
+    /// Makes an automatic vote on the given proposal.
+    fn vote(voter: Address, proposal: &[u8], in_favor: bool, reason: &str) -> JournaledVote {
+        JournaledVote {
+            seq: 0,
+            voter,
+            vote_hash: blake3::hash_slice(reason.as_bytes()),
+            proposal_id: blake3::hash_slice(proposal),
+            param: "ledger::transactions".to_owned(),
+            transaction: None,
+            in_favor,
+            reason: reason.to_owned(),
+            origin: VoteOrigin::Automatic,
+            remote: false,
+            voted_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_vote_journal() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let (alice, bob) = (blake3::hash_slice(b"alice"), blake3::hash_slice(b"bob"));
+        let mut journal = VoteJournal::new(db.open_tree(VOTE_JOURNAL_TREE).ok());
+
+        journal.record(vote(alice, b"a", true, "transaction is valid"));
+        journal.record(vote(bob, b"a", true, "transaction is valid"));
+        journal.record(vote(alice, b"b", false, "insufficient balance"));
+
+        // Each account's journal holds only its own votes, newest first
+        let votes = journal.list(&alice, None, DEFAULT_JOURNAL_PAGE);
+        assert_eq!(
+            votes
+                .iter()
+                .map(|vote| (vote.seq, vote.in_favor, vote.reason.as_str()))
+                .collect::<Vec<(u64, bool, &str)>>(),
+            vec![
+                (1, false, "insufficient balance"),
+                (0, true, "transaction is valid")
+            ]
+        );
+        assert_eq!(journal.list(&alice, Some(1), DEFAULT_JOURNAL_PAGE).len(), 1);
+        assert_eq!(journal.list(&bob, None, DEFAULT_JOURNAL_PAGE).len(), 1);
+
+        // The journal outlives a restart of the node, and picks up counting where it left off
+        let mut restarted = VoteJournal::new(db.open_tree(VOTE_JOURNAL_TREE).ok());
+        assert_eq!(restarted.record(vote(alice, b"c", true, "ok")).seq, 2);
+        assert_eq!(restarted.list(&alice, None, 1)[0].reason, "ok");
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+}; // Import the filesystem library
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use walkdir::WalkDir; // Import the directory walker
+
+use super::{
+    super::{
+        super::{common::address::Address, crypto::blake3},
+        types::graph::Node,
+    },
+    voting_policy::VotingPolicy,
+}; // Import the address & ledger types
+
+/// The name of the directories that tests write their databases and config files into.
+pub const TEST_ARTIFACTS_DIR: &str = ".tests";
+
+/// The extension of lock files.
+pub const LOCK_FILE_EXTENSION: &str = "lock";
+
+/// The name of the folder in a data directory that orphaned keystore entries are moved to, rather than deleted, in
+/// case one of them turns out to be needed after all.
+pub const ORPHANED_KEYSTORE_DIR: &str = "keystore.orphaned";
+
+/// An error encountered while cleaning up a data directory.
+#[derive(Debug, Fail)]
+pub enum MaintenanceError {
+    #[fail(display = "failed to remove {}: {}", path, error)]
+    RemovalFailed {
+        path: String,  // The path of the artifact that couldn't be removed
+        error: String, // The underlying error
+    },
+}
+
+/// The reason that a file or directory is considered garbage.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ArtifactKind {
+    /// A database or config file left behind by a test
+    TestArtifact,
+
+    /// A lock file that hasn't been touched in a long time, and was likely left behind by a process that crashed
+    StaleLock,
+
+    /// A keystore entry whose address never appears on the ledger
+    OrphanedKey,
+}
+
+/// A file or directory that can be cleaned up.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Artifact {
+    /// The path of the file or directory
+    pub path: String,
+
+    /// Why the artifact is considered garbage
+    pub kind: ArtifactKind,
+
+    /// The number of bytes that the artifact takes up on the disk
+    pub bytes: u64,
+}
+
+/// What a sweep of a data directory should look for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepOptions {
+    /// How long an artifact must have gone untouched before it is considered garbage, so that the files of running
+    /// tests and nodes are left alone
+    pub min_age: Duration,
+
+    /// Whether or not keystore entries whose addresses never appear on the ledger should be swept up
+    pub include_keystore: bool,
+}
+
+impl Default for SweepOptions {
+    /// Builds options sweeping up test artifacts and lock files untouched for a day, but leaving the keystore alone.
+    fn default() -> Self {
+        Self {
+            min_age: Duration::from_secs(24 * 60 * 60),
+            include_keystore: false,
+        }
+    }
+}
+
+/// Gets each of the addresses that appear in the given ledger, either as a sender or as a recipient.
+///
+/// # Arguments
+///
+/// * `nodes` - Each of the nodes in the ledger
+pub fn active_addresses(nodes: &[Node]) -> HashSet<Address> {
+    nodes
+        .iter()
+        .flat_map(|node| {
+            vec![
+                node.transaction.transaction_data.sender,
+                node.transaction.transaction_data.recipient,
+            ]
+        })
+        .collect()
+}
+
+/// Finds each of the artifacts in the given data directory that can be cleaned up. Keystore entries are only
+/// considered if the options ask for them, and the addresses appearing on the ledger are given.
+///
+/// # Arguments
+///
+/// * `data_dir` - The data directory that should be swept
+/// * `ledger_addresses` - Each of the addresses that appear on the ledger, if they're known
+/// * `options` - What the sweep should look for
+pub fn find_garbage(
+    data_dir: &str,
+    ledger_addresses: Option<&HashSet<Address>>,
+    options: &SweepOptions,
+) -> Vec<Artifact> {
+    let mut garbage = Vec::new(); // Each of the artifacts that we've found
+
+    // Each of the databases and config files left behind by tests lives in a .tests directory
+    for tests_dir in &[
+        format!("{}/{}", data_dir, TEST_ARTIFACTS_DIR),
+        format!("{}/db/{}", data_dir, TEST_ARTIFACTS_DIR),
+    ] {
+        for entry in fs::read_dir(tests_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+        {
+            if is_stale(&entry.path(), options.min_age) {
+                garbage.push(Artifact {
+                    path: entry.path().to_string_lossy().into_owned(),
+                    kind: ArtifactKind::TestArtifact,
+                    bytes: size_of(&entry.path()),
+                });
+            }
+        }
+    }
+
+    // Look for lock files anywhere else in the data directory
+    for entry in WalkDir::new(data_dir)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != TEST_ARTIFACTS_DIR)
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        if entry
+            .path()
+            .extension()
+            .map_or(false, |ext| ext == LOCK_FILE_EXTENSION)
+            && is_stale(entry.path(), options.min_age)
+        {
+            garbage.push(Artifact {
+                path: entry.path().to_string_lossy().into_owned(),
+                kind: ArtifactKind::StaleLock,
+                bytes: size_of(entry.path()),
+            });
+        }
+    }
+
+    if let (true, Some(ledger_addresses)) = (options.include_keystore, ledger_addresses) {
+        garbage.extend(find_orphaned_keys(
+            data_dir,
+            ledger_addresses,
+            options.min_age,
+        ));
+    }
+
+    garbage
+}
+
+/// Finds each of the keystore entries in the given data directory whose addresses never appear on the ledger. The
+/// node's p2p identity, and any account named in the voting policy, are never considered orphaned.
+///
+/// # Arguments
+///
+/// * `data_dir` - The data directory containing the keystore
+/// * `ledger_addresses` - Each of the addresses that appear on the ledger
+/// * `min_age` - How long an entry must have gone untouched before it is considered orphaned
+fn find_orphaned_keys(
+    data_dir: &str,
+    ledger_addresses: &HashSet<Address>,
+    min_age: Duration,
+) -> Vec<Artifact> {
+    let identity = blake3::hash_slice(b"p2p_identity");
+    let voters = VotingPolicy::read_from_disk_at_data_directory(data_dir)
+        .map(|policy| policy.accounts)
+        .unwrap_or_default();
+
+    fs::read_dir(format!("{}/keystore", data_dir))
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().map_or(true, |ext| ext != "json") {
+                return None;
+            }
+
+            // Keystore entries are named after the address of the account
+            let address = Address::from(path.file_stem()?.to_str()?);
+            if address == Address::default()
+                || address == identity
+                || voters.contains(&address)
+                || ledger_addresses.contains(&address)
+                || !is_stale(&path, min_age)
+            {
+                return None;
+            }
+
+            Some(Artifact {
+                path: path.to_string_lossy().into_owned(),
+                kind: ArtifactKind::OrphanedKey,
+                bytes: size_of(&path),
+            })
+        })
+        .collect()
+}
+
+/// Cleans up each of the given artifacts, returning the number of bytes freed. Orphaned keystore entries are moved to
+/// the data directory's keystore.orphaned folder; everything else is deleted.
+///
+/// # Arguments
+///
+/// * `data_dir` - The data directory that the artifacts were found in
+/// * `artifacts` - The artifacts that should be cleaned up
+pub fn remove(data_dir: &str, artifacts: &[Artifact]) -> Result<u64, MaintenanceError> {
+    let mut freed = 0;
+
+    for artifact in artifacts {
+        let path = Path::new(&artifact.path);
+        let failed = |e: std::io::Error| MaintenanceError::RemovalFailed {
+            path: artifact.path.clone(),
+            error: e.to_string(),
+        };
+
+        match artifact.kind {
+            ArtifactKind::OrphanedKey => {
+                let archive = format!("{}/{}", data_dir, ORPHANED_KEYSTORE_DIR);
+                fs::create_dir_all(&archive).map_err(failed)?;
+
+                if let Some(name) = path.file_name() {
+                    fs::rename(path, Path::new(&archive).join(name)).map_err(failed)?;
+                }
+            }
+            _ if path.is_dir() => fs::remove_dir_all(path).map_err(failed)?,
+            _ => fs::remove_file(path).map_err(failed)?,
+        }
+
+        freed += artifact.bytes;
+    }
+
+    Ok(freed)
+}
+
+/// Checks whether or not the file or directory at the given path has gone untouched for at least the given duration.
+fn is_stale(path: &Path, min_age: Duration) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .map_or(false, |age| age >= min_age)
+}
+
+/// Gets the number of bytes taken up by the file or directory at the given path.
+fn size_of(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::common::io; // Import the io module
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_find_garbage() {
+        let data_dir = format!(
+            "{}/.tests/maintenance/{}",
+            io::data_dir(),
+            rand::random::<u32>()
+        ); // Make a data directory to sweep
+
+        let used = blake3::hash_slice(b"used");
+        let unused = blake3::hash_slice(b"unused");
+
+        fs::create_dir_all(format!("{}/.tests/old_test", data_dir)).unwrap();
+        fs::create_dir_all(format!("{}/keystore", data_dir)).unwrap();
+        fs::write(format!("{}/.tests/old_test/db", data_dir), b"test").unwrap();
+        fs::write(format!("{}/node.lock", data_dir), b"").unwrap();
+        fs::write(format!("{}/keystore/{}.json", data_dir, used), b"{}").unwrap();
+        fs::write(format!("{}/keystore/{}.json", data_dir, unused), b"{}").unwrap();
+
+        let options = SweepOptions {
+            min_age: Duration::from_secs(0),
+            include_keystore: true,
+        };
+        let ledger_addresses: HashSet<Address> = vec![used].into_iter().collect();
+
+        let garbage = find_garbage(&data_dir, Some(&ledger_addresses), &options);
+        let kinds: Vec<ArtifactKind> = garbage.iter().map(|artifact| artifact.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ArtifactKind::TestArtifact,
+                ArtifactKind::StaleLock,
+                ArtifactKind::OrphanedKey
+            ]
+        );
+        assert!(garbage[2].path.contains(&unused.to_str())); // Only the unused key is orphaned
+
+        // Nothing is garbage if it was touched recently
+        assert!(
+            find_garbage(&data_dir, Some(&ledger_addresses), &SweepOptions::default()).is_empty()
+        );
+
+        remove(&data_dir, &garbage).unwrap();
+        assert!(find_garbage(&data_dir, Some(&ledger_addresses), &options).is_empty());
+        assert!(Path::new(&format!(
+            "{}/{}/{}.json",
+            data_dir, ORPHANED_KEYSTORE_DIR, unused
+        ))
+        .exists()); // The orphaned key should have been kept aside
+    }
+}
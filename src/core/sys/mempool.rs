@@ -0,0 +1,604 @@
+use chrono::{DateTime, Duration, Utc}; // Import time utilities
+
+use num::bigint::BigUint; // Add support for large unsigned integers
+
+use std::collections::{BTreeMap, HashMap}; // Import the map types
+
+use super::{
+    super::{
+        super::{common::address::Address, crypto::hash::Hash},
+        types::transaction::Transaction,
+    },
+    diagnosis::{TransactionLocation, WaitingTransaction},
+}; // Import the address, hash, transaction & diagnosis types
+
+/// The greatest number of transactions held in the mempool at once, by default.
+pub const DEFAULT_MAX_MEMPOOL_TRANSACTIONS: usize = 4096;
+
+/// The number of seconds that a transaction may wait in the mempool before it is dropped, by default.
+pub const DEFAULT_MEMPOOL_TTL_SECS: u64 = 3600;
+
+/// How far ahead of a sender's next nonce a transaction may be, by default, while it waits for the transactions
+/// before it.
+pub const DEFAULT_MAX_NONCE_GAP: u64 = 16;
+
+/// An error encountered while admitting a transaction to the mempool.
+#[derive(Debug, Fail, PartialEq)]
+pub enum MempoolError {
+    #[fail(display = "transaction {} is already in the mempool", hash)]
+    AlreadyKnown {
+        hash: Hash, // The hash of the transaction
+    },
+    #[fail(
+        display = "{} has already used nonce {}; its next nonce is {}",
+        sender, nonce, expected
+    )]
+    StaleNonce {
+        sender: Address, // The sender of the transaction
+        nonce: u64,      // The nonce of the transaction
+        expected: u64,   // The nonce that the sender's next transaction must have
+    },
+    #[fail(
+        display = "nonce {} of {} is too far ahead of its next nonce, {} (at most {} may be skipped)",
+        nonce, sender, expected, max_gap
+    )]
+    NonceGap {
+        sender: Address, // The sender of the transaction
+        nonce: u64,      // The nonce of the transaction
+        expected: u64,   // The nonce that the sender's next transaction must have
+        max_gap: u64,    // The number of nonces that may be skipped
+    },
+    #[fail(
+        display = "{} already has a transaction with nonce {} waiting; replace it with a higher gas price",
+        sender, nonce
+    )]
+    Underpriced {
+        sender: Address, // The sender of the transaction
+        nonce: u64,      // The nonce of the transaction
+    },
+    #[fail(
+        display = "the mempool holds {} transactions, all paying at least as much",
+        max
+    )]
+    Full {
+        max: usize, // The greatest number of transactions held at once
+    },
+    #[fail(
+        display = "transaction {} can't replace {}, since it doesn't spend the same nonce of the same sender",
+        hash, replaced
+    )]
+    MismatchedReplacement {
+        hash: Hash,     // The hash of the replacement
+        replaced: Hash, // The hash of the transaction that it would replace
+    },
+}
+
+/// A transaction waiting in the mempool.
+struct PendingTransaction {
+    /// The transaction
+    tx: Transaction,
+
+    /// When the transaction was admitted to the mempool
+    admitted_at: DateTime<Utc>,
+
+    /// Whether or not the transaction has been handed out for publishing
+    published: bool,
+}
+
+/// Implement a set of pending transaction helper methods.
+impl PendingTransaction {
+    /// Gets how urgently the transaction should be published, compared to others: transactions paying the highest gas
+    /// price go first, then those that have waited the longest.
+    fn priority(&self) -> (&BigUint, std::cmp::Reverse<DateTime<Utc>>) {
+        (
+            &self.tx.transaction_data.gas_price,
+            std::cmp::Reverse(self.admitted_at),
+        )
+    }
+}
+
+/// Transactions submitted to the node that haven't been executed yet, published in order of the fees that they pay,
+/// without ever publishing a transaction before those of its sender with lower nonces.
+pub struct Mempool {
+    /// Each of the waiting transactions, by hash
+    pending: HashMap<Hash, PendingTransaction>,
+
+    /// The hash of each of the waiting transactions, by sender & nonce
+    by_sender: HashMap<Address, BTreeMap<u64, Hash>>,
+
+    /// The greatest number of transactions held at once
+    pub max_transactions: usize,
+
+    /// The number of seconds that a transaction may wait before it is dropped
+    pub ttl_secs: u64,
+
+    /// How far ahead of its sender's next nonce a transaction may be
+    pub max_nonce_gap: u64,
+
+    /// Whether or not the last call to take_ready left transactions that were ready behind
+    held_back: bool,
+}
+
+impl Default for Mempool {
+    /// Makes an empty mempool with the default limits.
+    fn default() -> Self {
+        Self {
+            pending: HashMap::new(),
+            by_sender: HashMap::new(),
+            max_transactions: DEFAULT_MAX_MEMPOOL_TRANSACTIONS,
+            ttl_secs: DEFAULT_MEMPOOL_TTL_SECS,
+            max_nonce_gap: DEFAULT_MAX_NONCE_GAP,
+            held_back: false,
+        }
+    }
+}
+
+/// Implement a set of mempool helper methods.
+impl Mempool {
+    /// Gets the number of transactions waiting in the mempool.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Checks whether or not the mempool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Checks whether or not the transaction with the given hash is waiting in the mempool.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction
+    pub fn contains(&self, hash: &Hash) -> bool {
+        self.pending.contains_key(hash)
+    }
+
+    /// Gets the nonce that the given sender's next transaction should have: the one following the last of the
+    /// sender's transactions waiting in the mempool, or the given executed nonce if none follow on from it.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The sender of the transaction
+    /// * `expected` - The nonce following the sender's last executed transaction
+    pub fn next_nonce(&self, sender: &Address, expected: u64) -> u64 {
+        let mut next = expected;
+
+        if let Some(nonces) = self.by_sender.get(sender) {
+            while nonces.contains_key(&next) {
+                next += 1;
+            }
+        }
+
+        next
+    }
+
+    /// Gets each of the given sender's transactions waiting in the mempool, lowest nonce first.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The sender of the transactions
+    pub fn waiting_from(&self, sender: &Address) -> Vec<WaitingTransaction> {
+        self.by_sender
+            .get(sender)
+            .map(|nonces| {
+                nonces
+                    .iter()
+                    .filter_map(|(nonce, hash)| {
+                        let pending = self.pending.get(hash)?;
+
+                        Some(WaitingTransaction {
+                            hash: *hash,
+                            nonce: *nonce,
+                            location: TransactionLocation::Mempool,
+                            since: pending.admitted_at,
+                            published: pending.published,
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Admits the given transaction to the mempool, dropping the least urgent transaction waiting if the mempool is
+    /// full and the new one pays more. A waiting transaction that hasn't been published yet may be replaced by one
+    /// with the same nonce that pays a higher gas price.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction
+    /// * `expected` - The nonce following the last executed transaction of the transaction's sender
+    /// * `now` - The current time
+    pub fn insert(
+        &mut self,
+        tx: Transaction,
+        expected: u64,
+        now: DateTime<Utc>,
+    ) -> Result<(), MempoolError> {
+        self.prune(now);
+
+        let (sender, nonce) = (tx.transaction_data.sender, tx.transaction_data.nonce);
+
+        if self.pending.contains_key(&tx.hash) {
+            return Err(MempoolError::AlreadyKnown { hash: tx.hash });
+        }
+
+        if nonce < expected {
+            return Err(MempoolError::StaleNonce {
+                sender,
+                nonce,
+                expected,
+            });
+        }
+
+        let next = self.next_nonce(&sender, expected);
+        if nonce > next + self.max_nonce_gap {
+            return Err(MempoolError::NonceGap {
+                sender,
+                nonce,
+                expected: next,
+                max_gap: self.max_nonce_gap,
+            });
+        }
+
+        // Transactions may only be replaced before they've been published, and only by paying more
+        let replaced = self
+            .by_sender
+            .get(&sender)
+            .and_then(|nonces| nonces.get(&nonce))
+            .copied();
+        if let Some(replaced) = replaced {
+            match self.pending.get(&replaced) {
+                Some(existing)
+                    if !existing.published
+                        && tx.transaction_data.gas_price
+                            > existing.tx.transaction_data.gas_price => {}
+                _ => return Err(MempoolError::Underpriced { sender, nonce }),
+            }
+
+            self.remove(&replaced);
+        }
+
+        let admitted = PendingTransaction {
+            tx,
+            admitted_at: now,
+            published: false,
+        };
+
+        // Make room by dropping the least urgent transaction that hasn't gone out yet, if the new one is more urgent
+        if self.pending.len() >= self.max_transactions {
+            let evicted = self
+                .pending
+                .iter()
+                .filter(|(_, pending)| !pending.published)
+                .min_by(|a, b| a.1.priority().cmp(&b.1.priority()))
+                .filter(|(_, least)| least.priority() < admitted.priority())
+                .map(|(hash, _)| *hash);
+
+            match evicted {
+                Some(hash) => {
+                    self.remove(&hash);
+                }
+                None => {
+                    return Err(MempoolError::Full {
+                        max: self.max_transactions,
+                    })
+                }
+            }
+        }
+
+        self.by_sender
+            .entry(sender)
+            .or_default()
+            .insert(nonce, admitted.tx.hash);
+        self.pending.insert(admitted.tx.hash, admitted);
+
+        Ok(())
+    }
+
+    /// Hands out up to the given number of transactions that are ready to be published, most urgent first, and marks
+    /// them as published. A transaction is only ready once each of its sender's transactions with lower nonces has
+    /// been executed, or is waiting in the mempool ahead of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The greatest number of transactions that should be handed out
+    /// * `nonces` - The nonce of each sender's last executed transaction, as recorded in the ledger's state
+    /// * `now` - The current time
+    pub fn take_ready(
+        &mut self,
+        limit: usize,
+        nonces: &HashMap<String, u64>,
+        now: DateTime<Utc>,
+    ) -> Vec<Transaction> {
+        self.prune(now);
+
+        // Line up each sender's unpublished transactions that don't follow a gap, lowest nonce first
+        let mut queues: Vec<Vec<Hash>> = self
+            .by_sender
+            .iter()
+            .map(|(sender, waiting)| {
+                let mut next = nonces.get(&sender.to_str()).map_or(0, |last| last + 1);
+
+                let mut queue: Vec<Hash> = waiting
+                    .range(next..)
+                    .take_while(|(nonce, _)| {
+                        let follows = **nonce == next;
+                        next += 1;
+
+                        follows
+                    })
+                    .map(|(_, hash)| *hash)
+                    .filter(|hash| !self.pending[hash].published)
+                    .collect();
+
+                // Keep the lowest nonce at the back, where it's popped from
+                queue.reverse();
+
+                queue
+            })
+            .filter(|queue: &Vec<Hash>| !queue.is_empty())
+            .collect();
+
+        // Repeatedly take the most urgent transaction at the front of any sender's queue
+        let mut ready = Vec::new();
+        while ready.len() < limit {
+            let most_urgent = queues
+                .iter()
+                .enumerate()
+                .filter_map(|(i, queue)| Some((i, &self.pending[queue.last()?])))
+                .max_by(|a, b| a.1.priority().cmp(&b.1.priority()))
+                .map(|(i, _)| i);
+
+            let hash = match most_urgent.and_then(|i| queues[i].pop()) {
+                Some(hash) => hash,
+                None => break,
+            };
+
+            if let Some(pending) = self.pending.get_mut(&hash) {
+                pending.published = true;
+                ready.push(pending.tx.clone());
+            }
+        }
+        self.held_back = queues.iter().any(|queue| !queue.is_empty());
+
+        ready
+    }
+
+    /// Marks the transaction with the given hash as not yet published, so that it's handed out for publishing again
+    /// once it's ready. Results in whether or not the transaction was waiting in the mempool.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction
+    pub fn requeue(&mut self, hash: &Hash) -> bool {
+        match self.pending.get_mut(hash) {
+            Some(pending) => {
+                pending.published = false;
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Swaps the waiting transaction with the given hash for the given transaction, which must spend the same nonce of
+    /// the same sender. Unlike an ordinary replacement, the waiting transaction may already have been published, and
+    /// the replacement needn't pay more.
+    ///
+    /// # Arguments
+    ///
+    /// * `replaced` - The hash of the waiting transaction
+    /// * `tx` - The replacement
+    /// * `expected` - The nonce following the last executed transaction of the transaction's sender
+    /// * `now` - The current time
+    pub fn replace(
+        &mut self,
+        replaced: &Hash,
+        tx: Transaction,
+        expected: u64,
+        now: DateTime<Utc>,
+    ) -> Result<(), MempoolError> {
+        if let Some(existing) = self.pending.get(replaced) {
+            let (old, new) = (&existing.tx.transaction_data, &tx.transaction_data);
+
+            if old.sender != new.sender || old.nonce != new.nonce {
+                return Err(MempoolError::MismatchedReplacement {
+                    hash: tx.hash,
+                    replaced: *replaced,
+                });
+            }
+
+            self.remove(replaced);
+        }
+
+        self.insert(tx, expected, now)
+    }
+
+    /// Removes the transaction with the given hash from the mempool (e.g. once it has been executed). Results in the
+    /// transaction, if it was waiting.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction
+    pub fn remove(&mut self, hash: &Hash) -> Option<Transaction> {
+        let removed = self.pending.remove(hash)?;
+        let sender = removed.tx.transaction_data.sender;
+
+        if let Some(nonces) = self.by_sender.get_mut(&sender) {
+            nonces.remove(&removed.tx.transaction_data.nonce);
+
+            if nonces.is_empty() {
+                self.by_sender.remove(&sender);
+            }
+        }
+
+        Some(removed.tx)
+    }
+
+    /// Removes the given executed transaction from the mempool, along with any other transaction of its sender that
+    /// has been left with a nonce that's already used. Results in whether or not the sender has any transactions left
+    /// waiting, which may have become ready.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that was executed
+    pub fn confirm(&mut self, tx: &Transaction) -> bool {
+        let sender = tx.transaction_data.sender;
+
+        let stale: Vec<Hash> = self
+            .by_sender
+            .get(&sender)
+            .map(|nonces| {
+                nonces
+                    .range(..=tx.transaction_data.nonce)
+                    .map(|(_, hash)| *hash)
+                    .collect()
+            })
+            .unwrap_or_default();
+        for hash in stale.iter().chain(std::iter::once(&tx.hash)) {
+            self.remove(hash);
+        }
+
+        self.by_sender.contains_key(&sender)
+    }
+
+    /// Checks whether or not the last call to take_ready left transactions that were ready behind, since it couldn't
+    /// hand out any more.
+    pub fn held_back(&self) -> bool {
+        self.held_back
+    }
+
+    /// Drops each of the transactions that have waited in the mempool for longer than its TTL. Results in the number of
+    /// transactions dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    pub fn prune(&mut self, now: DateTime<Utc>) -> usize {
+        let cutoff = now - Duration::seconds(self.ttl_secs as i64);
+
+        let expired: Vec<Hash> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| pending.admitted_at < cutoff)
+            .map(|(hash, _)| *hash)
+            .collect();
+
+        for hash in &expired {
+            self.remove(hash);
+        }
+
+        expired.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::crypto::blake3; // Import the blake3 hashing module
+    use super::*; // Import names from our parent module
+
+    /// Makes a transaction from the given sender with the given nonce, paying the given gas price.
+    fn tx(sender: &[u8], nonce: u64, gas_price: u64) -> Transaction {
+        Transaction::new(
+            nonce,
+            blake3::hash_slice(sender),
+            blake3::hash_slice(b"recipient"),
+            BigUint::from(1 as u8),
+            b"",
+            vec![],
+        )
+        .with_gas(2_000, BigUint::from(gas_price))
+    }
+
+    #[test]
+    fn test_mempool() {
+        let now = Utc::now();
+        let (alice, bob) = (blake3::hash_slice(b"alice"), blake3::hash_slice(b"bob"));
+        let mut mempool = Mempool {
+            max_transactions: 4,
+            max_nonce_gap: 1,
+            ..Default::default()
+        };
+
+        mempool.insert(tx(b"alice", 1, 50), 0, now).unwrap();
+        mempool.insert(tx(b"alice", 0, 1), 0, now).unwrap();
+        mempool.insert(tx(b"bob", 0, 10), 0, now).unwrap();
+        assert_eq!(mempool.next_nonce(&alice, 0), 2);
+        assert_eq!(
+            mempool.insert(tx(b"bob", 3, 10), 0, now),
+            Err(MempoolError::NonceGap {
+                sender: bob,
+                nonce: 3,
+                expected: 1,
+                max_gap: 1
+            })
+        );
+
+        // A full mempool drops the cheapest transaction for one that pays more, but not for one that pays less
+        mempool.insert(tx(b"carol", 0, 5), 0, now).unwrap();
+        assert_eq!(
+            mempool.insert(tx(b"dave", 0, 0), 0, now),
+            Err(MempoolError::Full { max: 4 })
+        );
+        mempool.insert(tx(b"dave", 0, 20), 0, now).unwrap();
+        assert_eq!(mempool.next_nonce(&alice, 0), 0);
+
+        // The highest gas price goes first, but alice's second transaction has to wait for her first
+        let order = |txs: Vec<Transaction>| -> Vec<String> {
+            txs.iter()
+                .map(|tx| tx.transaction_data.gas_price.to_string())
+                .collect()
+        };
+        assert_eq!(
+            order(mempool.take_ready(10, &HashMap::new(), now)),
+            vec!["20", "10", "5"]
+        );
+        assert!(mempool.take_ready(10, &HashMap::new(), now).is_empty());
+
+        // Published transactions can't be replaced
+        assert_eq!(
+            mempool.insert(tx(b"bob", 0, 99), 0, now),
+            Err(MempoolError::Underpriced {
+                sender: bob,
+                nonce: 0
+            })
+        );
+
+        // Once alice's first transaction has been executed, her second is ready
+        let mut nonces = HashMap::new();
+        nonces.insert(alice.to_str(), 0);
+        assert_eq!(order(mempool.take_ready(10, &nonces, now)), vec!["50"]);
+        assert!(!mempool.held_back());
+
+        // Stuck transactions can be gossiped again, or swapped for a rebuilt copy spending the same nonce
+        assert!(mempool.requeue(&tx(b"bob", 0, 10).hash));
+        assert_eq!(
+            order(mempool.take_ready(10, &HashMap::new(), now)),
+            vec!["10"]
+        );
+        assert_eq!(
+            mempool.replace(&tx(b"bob", 0, 10).hash, tx(b"bob", 1, 10), 0, now),
+            Err(MempoolError::MismatchedReplacement {
+                hash: tx(b"bob", 1, 10).hash,
+                replaced: tx(b"bob", 0, 10).hash
+            })
+        );
+        mempool
+            .replace(&tx(b"bob", 0, 10).hash, tx(b"bob", 0, 9), 0, now)
+            .unwrap();
+        assert_eq!(
+            order(mempool.take_ready(10, &HashMap::new(), now)),
+            vec!["9"]
+        );
+
+        // Executing a transaction drops it, along with anything else left spending its nonce
+        let executed = tx(b"carol", 0, 7);
+        assert!(!mempool.confirm(&executed));
+        assert_eq!(mempool.len(), 3);
+
+        // Transactions that wait too long are dropped
+        assert_eq!(mempool.prune(now + Duration::hours(2)), 3);
+        assert!(mempool.is_empty());
+    }
+}
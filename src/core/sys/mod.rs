@@ -1,4 +1,22 @@
+pub mod checkpoints; // Export the pinned checkpoints
 pub mod config; // Export the config module
+pub mod config_sync; // Export the versioned config records synchronized from the network
+pub mod conflicts; // Export the double spend tracker
+pub mod consensus; // Export the vote thresholds
+pub mod consistency; // Export the startup consistency checks
+pub mod council; // Export the council guarding critical parameters
+pub mod diagnosis; // Export the account nonce diagnostics
+pub mod events; // Export the event feed
+pub mod exports; // Export the resumable ledger exports
+pub mod history; // Export the history of executed proposals
+pub mod invariants; // Export the ledger's supply invariants
+pub mod journal; // Export the per-account voting journal
+pub mod maintenance; // Export the data directory cleanup utilities
+pub mod mempool; // Export the pending transaction pool
 pub mod proposal; // Export the proposal type
+pub mod simulation; // Export the sandboxed proposal simulations
+pub mod stats; // Export the network statistics collector
+pub mod storage; // Export the disk pressure safeguards
 pub mod system; // Export the system type
 pub mod vote; // Export the vote type
+pub mod voting_policy; // Export the voting policy type
@@ -1,9 +1,78 @@
 use super::super::super::crypto::{blake3, hash}; // Import the blake3 hashing module
+use super::super::types::{
+    issuance::{Mint, ISSUANCE_PARAM},
+    signature::Signature,
+    transaction::Transaction,
+}; // Import the transaction, signature & mint types
 
 use serde::{Deserialize, Serialize}; // Import serde serialization
 
 use std::fmt;
 
+/// The parameter targeted by a text-only signaling proposal, which changes nothing when executed.
+pub const SIGNAL_PARAM: &str = "governance::signal";
+
+/// The parameter targeted by a proposal bundling several config changes that are applied atomically.
+pub const BUNDLE_PARAM: &str = "governance::bundle";
+
+/// The parameter targeted by a proposal that schedules a config change for a later ledger height.
+pub const SCHEDULED_PARAM: &str = "governance::scheduled";
+
+/// Each of the config parameters that a proposal may edit.
+pub const CONFIG_PARAMS: [&str; 8] = [
+    "config::reward_per_gas",
+    "config::network_name",
+    "config::issuance_per_epoch",
+    "config::issuance_epoch_length",
+    "config::issuance_halving_epochs",
+    "config::consensus_quorum_bps",
+    "config::consensus_rejection_bps",
+    "config::consensus_min_voting_period",
+];
+
+/// An error encountered while checking that a proposal is well-formed.
+#[derive(Debug, Fail)]
+pub enum ProposalError {
+    #[fail(display = "unknown proposal parameter {}", param)]
+    UnknownParam { param: String },
+    #[fail(
+        display = "operation {} cannot be completed on param {}",
+        operation, param
+    )]
+    InvalidOperation { operation: String, param: String },
+    #[fail(display = "the value proposed for param {} is malformed", param)]
+    MalformedValue { param: String },
+    #[fail(display = "a bundle must contain at least one change")]
+    EmptyBundle,
+    #[fail(
+        display = "param {} cannot be changed as part of a bundle or schedule",
+        param
+    )]
+    NotBundleable { param: String },
+}
+
+/// A config change that is applied once the ledger reaches a given height.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScheduledChange {
+    /// The number of transactions deep that the ledger's executed state must be before the change is applied
+    pub activation_height: u64,
+
+    /// The change to apply (either a single config edit, or a bundle of them)
+    pub change: ProposalData,
+}
+
+/// The decoded form of a proposal's body.
+pub enum ProposalBody {
+    /// A raw edit to a single parameter
+    Edit(ProposalData),
+    /// A text-only signaling proposal
+    Signal(String),
+    /// A set of config edits applied atomically
+    Bundle(Vec<ProposalData>),
+    /// A change applied at a later ledger height
+    Scheduled(ScheduledChange),
+}
+
 /// A proposal regarding a network-wide action.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Proposal {
@@ -15,10 +84,15 @@ pub struct Proposal {
 
     /// The hash of the proposal
     pub proposal_id: hash::Hash,
+
+    /// Signatures over the proposal's hash from members of the council, which are required to change critical config
+    /// parameters
+    #[serde(default)]
+    pub council_signatures: Vec<Signature>,
 }
 
 /// The body of a proposal.
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub struct ProposalData {
     /// The name of the system parameter to modify
     pub param_name: String,
@@ -28,7 +102,7 @@ pub struct ProposalData {
 }
 
 /// The manner in which a particular atomic event should be treated.
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub enum Operation {
     /// Make a minor change, or revision to a particular attribute or event
     Amend { amended_value: Vec<u8> },
@@ -82,6 +156,7 @@ impl Proposal {
             proposal_name,                                          // Set proposal name
             proposal_data,                                          // Set proposal data
             proposal_id: hash::Hash::new(vec![0; hash::HASH_SIZE]), // Set id to empty hash
+            council_signatures: Vec::new(),                         // Not signed by the council yet
         }; // Initialize proposal
 
         proposal.proposal_id = blake3::hash_slice(
@@ -93,6 +168,71 @@ impl Proposal {
         proposal // Return proposal
     }
 
+    /// Wraps the given transaction in a proposal to append it to the ledger. The proposal's ID is derived from the
+    /// transaction alone, so every node wrapping the same transaction arrives at the same proposal.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that should be appended to the ledger
+    pub fn for_transaction(tx: &Transaction) -> Proposal {
+        Proposal::new(
+            format!("new_tx({})", tx.hash.to_str()),
+            ProposalData::new(
+                "ledger::transactions".to_owned(),
+                Operation::Append {
+                    value_to_append: tx.to_bytes(),
+                },
+            ),
+        )
+    }
+
+    /// Wraps the given mint transaction in a proposal to append it to the ledger once the network agrees that the
+    /// epoch's reward is due.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction carrying the mint
+    pub fn for_mint(tx: &Transaction) -> Proposal {
+        Proposal::new(
+            format!("mint({})", tx.hash.to_str()),
+            ProposalData::new(
+                ISSUANCE_PARAM.to_owned(),
+                Operation::Append {
+                    value_to_append: tx.to_bytes(),
+                },
+            ),
+        )
+    }
+
+    /// Signs the proposal on behalf of the council member holding the given keypair. Since the proposal's hash is
+    /// derived from its data alone, signing it doesn't change its hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `keypair` - The keypair of the council member
+    pub fn sign_as_council(&mut self, keypair: &ed25519_dalek::Keypair) {
+        let signature = Signature::new(keypair, &*self.proposal_id);
+        self.council_signatures.push(signature);
+    }
+
+    /// Adds each of the council signatures carried by the given copy of the proposal that this one doesn't already
+    /// carry, so that signatures gathered by different nodes add up.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Another copy of the same proposal
+    pub fn merge_council_signatures(&mut self, other: Proposal) {
+        for signature in other.council_signatures {
+            if !self
+                .council_signatures
+                .iter()
+                .any(|known| known.signature_bytes == signature.signature_bytes)
+            {
+                self.council_signatures.push(signature);
+            }
+        }
+    }
+
     /// Encode &self to a byte vector via serde_json.
     pub fn to_bytes(&self) -> Vec<u8> {
         serde_json::to_vec_pretty(self).unwrap() // Return serialized
@@ -122,6 +262,231 @@ impl ProposalData {
             operation,  // Set operation
         } // Return initialized proposal data
     }
+
+    /// Initialize a text-only signaling proposal, which changes nothing when executed.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text of the signal
+    pub fn signal(text: &str) -> ProposalData {
+        ProposalData::new(
+            SIGNAL_PARAM.to_owned(),
+            Operation::Append {
+                value_to_append: text.as_bytes().to_vec(),
+            },
+        ) // Return the signal
+    }
+
+    /// Initialize a proposal bundling several config changes, which are applied all at once or
+    /// not at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `changes` - The config changes that should be applied together
+    pub fn bundle(changes: Vec<ProposalData>) -> ProposalData {
+        ProposalData::new(
+            BUNDLE_PARAM.to_owned(),
+            Operation::Append {
+                value_to_append: bincode::serialize(&changes).unwrap_or_default(),
+            },
+        ) // Return the bundle
+    }
+
+    /// Initialize a proposal that applies a config change (or bundle) once the ledger reaches the
+    /// given height.
+    ///
+    /// # Arguments
+    ///
+    /// * `activation_height` - The number of transactions deep that the ledger's executed state must be first
+    /// * `change` - The change that should be applied
+    pub fn scheduled(activation_height: u64, change: ProposalData) -> ProposalData {
+        ProposalData::new(
+            SCHEDULED_PARAM.to_owned(),
+            Operation::Append {
+                value_to_append: bincode::serialize(&ScheduledChange {
+                    activation_height,
+                    change,
+                })
+                .unwrap_or_default(),
+            },
+        ) // Return the scheduled change
+    }
+
+    /// Decodes the body of the proposal according to the parameter that it targets.
+    pub fn body(&self) -> Result<ProposalBody, ProposalError> {
+        // Structured proposals are always appends of some encoded value
+        let structured_value = || {
+            if let Operation::Append { value_to_append } = &self.operation {
+                Ok(value_to_append)
+            } else {
+                Err(ProposalError::InvalidOperation {
+                    operation: self.operation.to_string(),
+                    param: self.param_name.clone(),
+                })
+            }
+        };
+        let malformed = || ProposalError::MalformedValue {
+            param: self.param_name.clone(),
+        };
+
+        match self.param_name.as_str() {
+            SIGNAL_PARAM => Ok(ProposalBody::Signal(
+                String::from_utf8(structured_value()?.clone()).map_err(|_| malformed())?,
+            )),
+            BUNDLE_PARAM => Ok(ProposalBody::Bundle(
+                bincode::deserialize(structured_value()?).map_err(|_| malformed())?,
+            )),
+            SCHEDULED_PARAM => Ok(ProposalBody::Scheduled(
+                bincode::deserialize(structured_value()?).map_err(|_| malformed())?,
+            )),
+            _ => Ok(ProposalBody::Edit(self.clone())),
+        }
+    }
+
+    /// Checks that the proposal is well-formed: that it targets a known parameter with an
+    /// operation that the parameter supports, and that its value can be decoded.
+    pub fn validate(&self) -> Result<(), ProposalError> {
+        match self.body()? {
+            ProposalBody::Edit(edit) => match edit.param_name.as_str() {
+                "ledger::transactions" | ISSUANCE_PARAM => {
+                    // Transactions (mints included) can only ever be appended to the ledger
+                    if let Operation::Append { value_to_append } = &edit.operation {
+                        bincode::deserialize::<Transaction>(value_to_append)
+                            .map(|_| ())
+                            .map_err(|_| ProposalError::MalformedValue {
+                                param: edit.param_name.clone(),
+                            })
+                    } else {
+                        Err(ProposalError::InvalidOperation {
+                            operation: edit.operation.to_string(),
+                            param: edit.param_name.clone(),
+                        })
+                    }
+                }
+                param if CONFIG_PARAMS.contains(&param) => Ok(()),
+                param => Err(ProposalError::UnknownParam {
+                    param: param.to_owned(),
+                }),
+            },
+            ProposalBody::Signal(_) => Ok(()),
+            ProposalBody::Bundle(changes) => {
+                // An empty bundle would pass without doing anything
+                if changes.is_empty() {
+                    return Err(ProposalError::EmptyBundle);
+                }
+
+                // Only config edits can be applied atomically
+                for change in changes {
+                    if !CONFIG_PARAMS.contains(&change.param_name.as_str()) {
+                        return Err(ProposalError::NotBundleable {
+                            param: change.param_name,
+                        });
+                    }
+                }
+
+                Ok(())
+            }
+            ProposalBody::Scheduled(scheduled) => {
+                // Only config edits and bundles of them can be scheduled
+                if scheduled.change.param_name != BUNDLE_PARAM
+                    && !CONFIG_PARAMS.contains(&scheduled.change.param_name.as_str())
+                {
+                    return Err(ProposalError::NotBundleable {
+                        param: scheduled.change.param_name,
+                    });
+                }
+
+                scheduled.change.validate()
+            }
+        }
+    }
+
+    /// Gets the name of each of the parameters that the proposal would eventually change.
+    pub fn affected_params(&self) -> Vec<String> {
+        match self.body() {
+            Ok(ProposalBody::Bundle(changes)) => changes
+                .into_iter()
+                .map(|change| change.param_name)
+                .collect(),
+            Ok(ProposalBody::Scheduled(scheduled)) => scheduled.change.affected_params(),
+            _ => vec![self.param_name.clone()],
+        }
+    }
+}
+
+impl fmt::Display for ProposalData {
+    /// Renders the proposal in a human-readable form (e.g. "amend 'config::network_name' to 'x'").
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.body() {
+            Ok(ProposalBody::Signal(text)) => write!(f, "signal: \"{}\"", text),
+            Ok(ProposalBody::Bundle(changes)) => {
+                write!(f, "apply {} changes at once: [", changes.len())?;
+
+                for (i, change) in changes.iter().enumerate() {
+                    write!(f, "{}{}", if i > 0 { "; " } else { "" }, change)?;
+                }
+
+                write!(f, "]")
+            }
+            Ok(ProposalBody::Scheduled(scheduled)) => write!(
+                f,
+                "at ledger height {}, {}",
+                scheduled.activation_height, scheduled.change
+            ),
+            _ => match &self.operation {
+                Operation::Remove => write!(f, "remove '{}'", self.param_name),
+                Operation::Amend { amended_value } => write!(
+                    f,
+                    "amend '{}' to {}",
+                    self.param_name,
+                    render_value(&self.param_name, amended_value)
+                ),
+                Operation::Append { value_to_append } => write!(
+                    f,
+                    "append {} to '{}'",
+                    render_value(&self.param_name, value_to_append),
+                    self.param_name
+                ),
+            },
+        }
+    }
+}
+
+/// Renders a raw proposed value according to the parameter that it is destined for.
+///
+/// # Arguments
+///
+/// * `param_name` - The name of the parameter that the value is destined for
+/// * `value` - The raw value
+fn render_value(param_name: &str, value: &[u8]) -> String {
+    match param_name {
+        "config::reward_per_gas" | "config::issuance_per_epoch" => {
+            num::BigUint::from_bytes_le(value).to_string()
+        }
+        "config::issuance_epoch_length"
+        | "config::issuance_halving_epochs"
+        | "config::consensus_quorum_bps"
+        | "config::consensus_rejection_bps"
+        | "config::consensus_min_voting_period" => {
+            let mut bytes = [0; 8];
+            bytes[..value.len().min(8)].copy_from_slice(&value[..value.len().min(8)]);
+
+            u64::from_le_bytes(bytes).to_string()
+        }
+        "config::network_name" => format!("'{}'", String::from_utf8_lossy(value)),
+        "ledger::transactions" => bincode::deserialize::<Transaction>(value).map_or_else(
+            |_| "a malformed transaction".to_owned(),
+            |tx| format!("transaction {}", tx.hash),
+        ),
+        ISSUANCE_PARAM => bincode::deserialize::<Transaction>(value)
+            .ok()
+            .and_then(|tx| Mint::from_payload(&tx.transaction_data.payload).ok()?)
+            .map_or_else(
+                || "a malformed mint".to_owned(),
+                |mint| format!("{} finks minted for epoch {}", mint.total(), mint.epoch),
+            ),
+        _ => format!("{} bytes", value.len()),
+    }
 }
 
 /// Implement a serialization for the proposal list type.
@@ -131,3 +496,141 @@ impl ProposalList {
         serde_json::to_vec_pretty(self).unwrap() // Return serialized
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::common::address::Address;
+    use super::super::super::types::{batch::Transfer, issuance::issuance_address};
+    use super::*; // Import names from our parent module
+
+    use num::BigUint; // Add support for large unsigned integers
+
+    #[test]
+    fn test_for_transaction() {
+        let tx = Transaction::new(
+            0,
+            Address::default(),
+            Address::default(),
+            BigUint::from(1 as u8),
+            b"test",
+            vec![],
+        ); // Make a transaction
+
+        let proposal = Proposal::for_transaction(&tx); // Wrap the transaction
+
+        assert_eq!(
+            proposal.proposal_id,
+            Proposal::for_transaction(&tx).proposal_id
+        ); // Every node should arrive at the same proposal
+        assert!(proposal.proposal_data.validate().is_ok()); // The proposal should be well-formed
+    }
+
+    #[test]
+    fn test_validate() {
+        let rename = ProposalData::new(
+            "config::network_name".to_owned(),
+            Operation::Amend {
+                amended_value: b"andromeda".to_vec(),
+            },
+        ); // Make a simple config edit
+
+        assert!(rename.validate().is_ok()); // Config edits are well-formed
+        assert!(ProposalData::signal("hello").validate().is_ok()); // Signals are well-formed
+        assert!(ProposalData::bundle(vec![rename.clone()])
+            .validate()
+            .is_ok()); // Bundles of config edits are fine
+        assert!(ProposalData::scheduled(10, rename.clone())
+            .validate()
+            .is_ok()); // So are scheduled edits
+
+        assert!(ProposalData::bundle(vec![]).validate().is_err()); // Empty bundles aren't allowed
+        assert!(ProposalData::bundle(vec![ProposalData::signal("hi")])
+            .validate()
+            .is_err()); // Only config edits can be bundled
+        assert!(
+            ProposalData::new("config::unknown".to_owned(), Operation::Remove)
+                .validate()
+                .is_err()
+        ); // Unknown params aren't allowed
+        assert!(
+            ProposalData::new("ledger::transactions".to_owned(), Operation::Remove)
+                .validate()
+                .is_err()
+        ); // Txs can't be removed
+    }
+
+    #[test]
+    fn test_affected_params() {
+        let rename = ProposalData::new(
+            "config::network_name".to_owned(),
+            Operation::Amend {
+                amended_value: b"andromeda".to_vec(),
+            },
+        ); // Make a simple config edit
+
+        assert_eq!(
+            ProposalData::scheduled(10, ProposalData::bundle(vec![rename])).affected_params(),
+            vec!["config::network_name".to_owned()]
+        ); // Nested changes should be seen through
+    }
+
+    #[test]
+    fn test_display() {
+        let rename = ProposalData::new(
+            "config::network_name".to_owned(),
+            Operation::Amend {
+                amended_value: b"andromeda".to_vec(),
+            },
+        ); // Make a simple config edit
+
+        assert_eq!(
+            ProposalData::scheduled(10, rename).to_string(),
+            "at ledger height 10, amend 'config::network_name' to 'andromeda'"
+        ); // Ensure renders properly
+
+        let lengthen = ProposalData::new(
+            "config::issuance_epoch_length".to_owned(),
+            Operation::Amend {
+                amended_value: 5000u64.to_le_bytes().to_vec(),
+            },
+        );
+        assert_eq!(
+            lengthen.to_string(),
+            "amend 'config::issuance_epoch_length' to 5000"
+        );
+
+        let supermajority = ProposalData::new(
+            "config::consensus_quorum_bps".to_owned(),
+            Operation::Amend {
+                amended_value: 6667u64.to_le_bytes().to_vec(),
+            },
+        );
+        assert!(supermajority.validate().is_ok());
+        assert_eq!(
+            supermajority.to_string(),
+            "amend 'config::consensus_quorum_bps' to 6667"
+        );
+
+        let mint = Mint {
+            epoch: 2,
+            transfers: vec![Transfer {
+                recipient: Address::default(),
+                value: BigUint::from(10 as u8),
+            }],
+            votes: Vec::new(),
+        };
+        let proposal = Proposal::for_mint(&Transaction::new(
+            0,
+            issuance_address(),
+            issuance_address(),
+            BigUint::from(0 as u8),
+            &mint.to_payload(),
+            vec![],
+        ));
+        assert!(proposal.proposal_data.validate().is_ok());
+        assert_eq!(
+            proposal.proposal_data.to_string(),
+            "append 10 finks minted for epoch 2 to 'ledger::issuance'"
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use num::BigUint; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::BTreeSet; // Import the ordered set type
+
+use super::{
+    super::{
+        super::crypto::hash::Hash,
+        types::{receipt::ExecutionStatus, state::Entry, trace::diff_balance_maps},
+    },
+    config::Config,
+}; // Import the config, state & hash types
+
+/// A field of the config that a proposal would change.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConfigChange {
+    /// The path of the field in the config, with each level separated by a dot (e.g. issuance.per_epoch)
+    pub field: String,
+
+    /// The value of the field right now
+    pub before: serde_json::Value,
+
+    /// The value that the field would hold once the proposal is executed
+    pub after: serde_json::Value,
+}
+
+/// An account whose balance a proposal would change.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BalanceChange {
+    /// The address of the account
+    pub account: String,
+
+    /// The balance of the account in the state that the proposal's transaction would be executed on top of
+    pub before: BigUint,
+
+    /// The balance of the account once the proposal's transaction is executed
+    pub after: BigUint,
+}
+
+/// What would happen if a pending proposal were executed right now. Simulations are run against copies of the config
+/// and ledger, so nothing is ever committed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProposalSimulation {
+    /// The hash of the proposal that was simulated
+    pub proposal_id: Hash,
+
+    /// Why executing the proposal would fail, if it would
+    pub error: Option<String>,
+
+    /// The number of transactions deep that the ledger's executed state must be before the proposal's change is
+    /// applied, if it is scheduled
+    pub activation_height: Option<u64>,
+
+    /// Each of the config fields that the proposal would change
+    pub config_changes: Vec<ConfigChange>,
+
+    /// The outcome of executing the proposal's transaction, if it carries one
+    pub status: Option<ExecutionStatus>,
+
+    /// The hash of the state that executing the proposal's transaction would result in, if it carries one
+    pub state_hash: Option<Hash>,
+
+    /// Each of the accounts whose balance the proposal's transaction would change
+    pub balance_changes: Vec<BalanceChange>,
+}
+
+/// Implement a set of simulation helper methods.
+impl ProposalSimulation {
+    /// Initializes a new simulation of the proposal with the given hash, in which nothing changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_id` - The hash of the proposal being simulated
+    pub fn new(proposal_id: Hash) -> Self {
+        Self {
+            proposal_id,
+            error: None,
+            activation_height: None,
+            config_changes: Vec::new(),
+            status: None,
+            state_hash: None,
+            balance_changes: Vec::new(),
+        }
+    }
+
+    /// Gets the reason that executing the proposal would fail, or None if it would succeed. A transaction that would
+    /// be admitted to the ledger, but wouldn't move any funds, counts as a failure.
+    pub fn failure(&self) -> Option<String> {
+        if let Some(error) = &self.error {
+            return Some(error.clone());
+        }
+
+        match self.status {
+            Some(ExecutionStatus::Success) | None => None,
+            Some(status) => Some(format!("the transaction would not execute: {:?}", status)),
+        }
+    }
+
+    /// Checks whether or not executing the proposal would succeed.
+    pub fn succeeds(&self) -> bool {
+        self.failure().is_none()
+    }
+}
+
+/// Gets each of the fields that differ between the given configs.
+///
+/// # Arguments
+///
+/// * `before` - The config as it is now
+/// * `after` - The config as it would be once a proposal is executed
+pub fn diff_configs(before: &Config, after: &Config) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+
+    if let (Ok(before), Ok(after)) = (serde_json::to_value(before), serde_json::to_value(after)) {
+        diff_values("", &before, &after, &mut changes);
+    }
+
+    changes
+}
+
+/// Records each of the leaves that differ between the given JSON values, descending into objects.
+///
+/// # Arguments
+///
+/// * `path` - The path of the values in the config
+/// * `before` - The value as it is now
+/// * `after` - The value as it would be once a proposal is executed
+/// * `changes` - The list that each difference should be added to
+fn diff_values(
+    path: &str,
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+    changes: &mut Vec<ConfigChange>,
+) {
+    if before == after {
+        return;
+    }
+
+    if let (Some(before), Some(after)) = (before.as_object(), after.as_object()) {
+        let fields: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+
+        for field in fields {
+            let child = if path.is_empty() {
+                field.clone()
+            } else {
+                format!("{}.{}", path, field)
+            };
+
+            diff_values(
+                &child,
+                before.get(field).unwrap_or(&serde_json::Value::Null),
+                after.get(field).unwrap_or(&serde_json::Value::Null),
+                changes,
+            );
+        }
+
+        return;
+    }
+
+    changes.push(ConfigChange {
+        field: path.to_owned(),
+        before: before.clone(),
+        after: after.clone(),
+    });
+}
+
+/// Gets each of the accounts whose balances differ between the given states, in order of address.
+///
+/// # Arguments
+///
+/// * `before` - The state that a transaction would be executed on top of
+/// * `after` - The state that executing the transaction would result in
+pub fn diff_balances(before: &Entry, after: &Entry) -> Vec<BalanceChange> {
+    diff_balance_maps(&before.data.balances, &after.data.balances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    use num::Zero; // Import the zero trait
+
+    #[test]
+    fn test_diff() {
+        let before = Config {
+            reward_per_gas: BigUint::from(1 as u8),
+            network_name: "olympia".to_owned(),
+            issuance: Default::default(),
+            consensus: Default::default(),
+            council: Default::default(),
+            storage_mode: Default::default(),
+            sequence: 0,
+        };
+        let mut after = before.clone();
+        after.consensus.quorum_bps += 1;
+
+        // Only the nested field that was edited shows up
+        let changes = diff_configs(&before, &after);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].field, "consensus.quorum_bps");
+        assert!(diff_configs(&before, &before).is_empty());
+
+        let mut parent = Entry::default();
+        parent
+            .data
+            .balances
+            .insert("alice".to_owned(), BigUint::from(10 as u8));
+        parent
+            .data
+            .balances
+            .insert("carol".to_owned(), BigUint::from(7 as u8));
+        let mut child = parent.clone();
+        child
+            .data
+            .balances
+            .insert("alice".to_owned(), BigUint::from(4 as u8));
+        child
+            .data
+            .balances
+            .insert("bob".to_owned(), BigUint::from(6 as u8));
+
+        let changes = diff_balances(&parent, &child);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| change.account.as_str())
+                .collect::<Vec<_>>(),
+            vec!["alice", "bob"]
+        );
+        assert_eq!(changes[1].before, BigUint::zero());
+
+        // A reverted transaction would never pass for a successful one
+        let mut simulation = ProposalSimulation::new(Hash::default());
+        assert!(simulation.succeeds());
+        simulation.status = Some(ExecutionStatus::InsufficientFunds);
+        assert!(!simulation.succeeds());
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::{BTreeMap, HashSet}; // Import collections
+
+use chrono::{DateTime, TimeZone, Utc}; // Import time utilities
+
+use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::super::{super::common::address::Address, types::transaction::Transaction}; // Import the address & transaction types
+
+/// The name of the tree in the ledger's database in which network statistics are persisted.
+pub const STATS_TREE: &str = "stats";
+
+/// The number of seconds of network activity summarized by each sample.
+pub const BUCKET_SECS: i64 = 3600;
+
+/// The number of samples kept before the oldest are discarded (thirty days' worth).
+pub const RETAINED_BUCKETS: usize = 24 * 30;
+
+/// The network activity observed in a single window of time, as persisted.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Bucket {
+    /// The UNIX timestamp at which the window starts
+    pub start: i64,
+
+    /// The number of transactions executed successfully in the window
+    pub transactions: u64,
+
+    /// The number of finks moved by those transactions
+    pub volume: BigUint,
+
+    /// The number of finks paid in fees by those transactions
+    pub fees: BigUint,
+
+    /// Each of the addresses that sent or received funds in the window
+    pub active_addresses: HashSet<Address>,
+}
+
+/// A summary of the network activity observed in a window of time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Sample {
+    /// The time at which the window starts
+    pub start: DateTime<Utc>,
+
+    /// The length of the window, in seconds
+    pub duration_secs: i64,
+
+    /// The number of transactions executed successfully in the window
+    pub transactions: u64,
+
+    /// The average number of transactions executed per second
+    pub transactions_per_sec: f64,
+
+    /// The number of distinct addresses that sent or received funds
+    pub active_addresses: usize,
+
+    /// The number of finks moved, a measure of SMC velocity
+    pub volume: BigUint,
+
+    /// The average number of finks paid in fees by each transaction
+    pub average_fee: BigUint,
+}
+
+/// A time series of the activity on the network, kept by each node so that proposals tuning the network's config
+/// (e.g. reward_per_gas) can be weighed against real usage.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NetworkStats {
+    /// Each of the retained windows, keyed by the time at which they start
+    buckets: BTreeMap<i64, Bucket>,
+}
+
+/// Implement a set of network stats helper methods.
+impl NetworkStats {
+    /// Initializes a new set of statistics from the given persisted windows.
+    ///
+    /// # Arguments
+    ///
+    /// * `buckets` - Each of the windows, in any order
+    pub fn from_buckets<I: IntoIterator<Item = Bucket>>(buckets: I) -> Self {
+        let mut stats = Self {
+            buckets: buckets
+                .into_iter()
+                .map(|bucket| (bucket.start, bucket))
+                .collect(),
+        };
+        stats.prune();
+
+        stats
+    }
+
+    /// Records the execution of the given transaction, returning the window that it was counted in (which should be
+    /// persisted), along with the start of each window discarded to make room for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that was executed successfully
+    /// * `fee` - The number of finks that the transaction paid in fees
+    pub fn record(&mut self, tx: &Transaction, fee: &BigUint) -> (Option<Bucket>, Vec<i64>) {
+        let timestamp = tx.transaction_data.timestamp.timestamp();
+        let start = timestamp - timestamp.rem_euclid(BUCKET_SECS);
+
+        let bucket = self.buckets.entry(start).or_insert_with(|| Bucket {
+            start,
+            ..Default::default()
+        });
+        bucket.transactions += 1;
+        bucket.volume += &tx.transaction_data.value;
+        bucket.fees += fee;
+        bucket.active_addresses.insert(tx.transaction_data.sender);
+        bucket
+            .active_addresses
+            .insert(tx.transaction_data.recipient);
+
+        let pruned = self.prune();
+
+        (self.buckets.get(&start).cloned(), pruned)
+    }
+
+    /// Discards the oldest windows beyond RETAINED_BUCKETS, and returns the start of each.
+    fn prune(&mut self) -> Vec<i64> {
+        let excess = self.buckets.len().saturating_sub(RETAINED_BUCKETS);
+        let pruned: Vec<i64> = self.buckets.keys().take(excess).cloned().collect();
+
+        for start in &pruned {
+            self.buckets.remove(start);
+        }
+
+        pruned
+    }
+
+    /// Gets a sample of each of the retained windows, oldest first.
+    pub fn series(&self) -> Vec<Sample> {
+        self.buckets
+            .values()
+            .map(|bucket| summarize(&[bucket]))
+            .collect()
+    }
+
+    /// Gets a single sample summarizing the most recent windows, if any activity has been recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `windows` - The number of most recent windows that should be summarized
+    pub fn recent(&self, windows: usize) -> Option<Sample> {
+        let recent: Vec<&Bucket> = self.buckets.values().rev().take(windows).collect();
+
+        if recent.is_empty() {
+            None
+        } else {
+            Some(summarize(&recent))
+        }
+    }
+}
+
+/// Summarizes the activity in the given windows, as if they were a single window.
+///
+/// # Arguments
+///
+/// * `buckets` - The windows that should be summarized
+fn summarize(buckets: &[&Bucket]) -> Sample {
+    let start = buckets.iter().map(|bucket| bucket.start).min().unwrap_or(0);
+    let duration_secs = BUCKET_SECS * buckets.len() as i64;
+    let transactions: u64 = buckets.iter().map(|bucket| bucket.transactions).sum();
+    let fees = buckets
+        .iter()
+        .fold(BigUint::zero(), |total, bucket| total + &bucket.fees);
+
+    Sample {
+        start: Utc.timestamp(start, 0),
+        duration_secs,
+        transactions,
+        transactions_per_sec: transactions as f64 / duration_secs as f64,
+        active_addresses: buckets
+            .iter()
+            .flat_map(|bucket| bucket.active_addresses.iter())
+            .collect::<HashSet<_>>()
+            .len(),
+        volume: buckets
+            .iter()
+            .fold(BigUint::zero(), |total, bucket| total + &bucket.volume),
+        average_fee: if transactions == 0 {
+            BigUint::zero()
+        } else {
+            fees / BigUint::from(transactions)
+        },
+    }
+}
+
+/// Implement a set of sample helper methods.
+impl Sample {
+    /// Describes the sample in a single human-readable line.
+    pub fn describe(&self) -> String {
+        format!(
+            "{} txs ({:.4}/s) between {} active addresses over {}h, moving {} finks with an average fee of {} finks",
+            self.transactions,
+            self.transactions_per_sec,
+            self.active_addresses,
+            self.duration_secs / 3600,
+            self.volume,
+            self.average_fee
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::crypto::blake3; // Import the hashing module
+    use super::*; // Import names from our parent module
+
+    /// Makes a transaction sending the given value at the given UNIX timestamp.
+    fn tx_at(sender: &str, recipient: &str, value: u64, timestamp: i64) -> Transaction {
+        let mut tx = Transaction::new(
+            0,
+            blake3::hash_slice(sender.as_bytes()),
+            blake3::hash_slice(recipient.as_bytes()),
+            BigUint::from(value),
+            b"test",
+            vec![],
+        );
+        tx.transaction_data.timestamp = Utc.timestamp(timestamp, 0);
+
+        tx
+    }
+
+    #[test]
+    fn test_record() {
+        let mut stats = NetworkStats::default();
+
+        stats.record(&tx_at("alice", "bob", 5, 10), &BigUint::from(2 as u8));
+        stats.record(&tx_at("bob", "carol", 7, 20), &BigUint::from(4 as u8));
+        let (bucket, pruned) =
+            stats.record(&tx_at("alice", "bob", 1, BUCKET_SECS + 1), &BigUint::zero());
+
+        assert_eq!(bucket.unwrap().start, BUCKET_SECS); // The last tx starts a new window
+        assert!(pruned.is_empty());
+
+        let series = stats.series();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].transactions, 2);
+        assert_eq!(series[0].active_addresses, 3);
+        assert_eq!(series[0].volume, BigUint::from(12 as u8));
+        assert_eq!(series[0].average_fee, BigUint::from(3 as u8));
+
+        let recent = stats.recent(24).unwrap(); // Summarize both windows
+        assert_eq!(recent.transactions, 3);
+        assert_eq!(recent.active_addresses, 3); // Addresses are only counted once
+        assert_eq!(recent.duration_secs, 2 * BUCKET_SECS);
+    }
+
+    #[test]
+    fn test_prune() {
+        let stats = NetworkStats::from_buckets((0..RETAINED_BUCKETS as i64 + 5).map(|i| Bucket {
+            start: i * BUCKET_SECS,
+            ..Default::default()
+        }));
+
+        let series = stats.series();
+        assert_eq!(series.len(), RETAINED_BUCKETS);
+        assert_eq!(series[0].start, Utc.timestamp(5 * BUCKET_SECS, 0)); // The oldest windows are discarded
+    }
+}
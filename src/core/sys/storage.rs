@@ -0,0 +1,406 @@
+use chrono::{DateTime, Duration, Utc}; // Import time utilities
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::{
+    fs, io,
+    io::Write,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+}; // Import the filesystem & shared flag types
+
+use walkdir::WalkDir; // Import the directory walker
+
+/// The number of free bytes below which the node starts pruning what it can, by default (4 GiB).
+pub const DEFAULT_LOW_FREE_BYTES: u64 = 4 << 30;
+
+/// The number of free bytes below which the node refuses any writes that consensus doesn't depend on, by default
+/// (1 GiB).
+pub const DEFAULT_MIN_FREE_BYTES: u64 = 1 << 30;
+
+/// The number of seconds between measurements of the database & the disk that it lives on, by default.
+pub const DEFAULT_CHECK_INTERVAL_SECS: u64 = 60;
+
+/// The share of the database quota, in percent, past which the node starts pruning what it can.
+pub const LOW_QUOTA_PERCENT: u64 = 90;
+
+/// The name of the file written & removed next to the database to check that the disk still takes writes.
+const WRITE_PROBE_FILE: &str = ".write_probe";
+
+/// An error encountered while writing under disk pressure.
+#[derive(Debug, Fail, PartialEq)]
+pub enum StorageError {
+    #[fail(
+        display = "refusing to write while storage is exhausted (database: {} bytes, free: {} bytes)",
+        db_bytes, available_bytes
+    )]
+    Exhausted {
+        db_bytes: u64,        // The number of bytes taken up by the database
+        available_bytes: u64, // The number of bytes left on the disk
+    },
+    #[fail(display = "refusing to write while the disk is read-only: {}", error)]
+    ReadOnly {
+        error: String, // The write failure that put the node in read-only mode
+    },
+}
+
+/// How close the node is to running out of room.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum StoragePressure {
+    /// There's plenty of room
+    Normal,
+
+    /// Room is running low, so anything that can be rebuilt later is pruned
+    Low,
+
+    /// Room has run out, so only writes that consensus depends on are made
+    Critical,
+}
+
+impl Default for StoragePressure {
+    /// Assumes that there's plenty of room until the disk has been measured.
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// How much room the node's database may take up, and how much room it must leave on the disk.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StorageQuota {
+    /// The greatest number of bytes that the database may take up. The database is unbounded if this is zero.
+    pub max_db_bytes: u64,
+
+    /// The number of free bytes below which the node starts pruning what it can
+    pub low_free_bytes: u64,
+
+    /// The number of free bytes below which the node refuses any writes that consensus doesn't depend on
+    pub min_free_bytes: u64,
+
+    /// The number of seconds between measurements of the database & the disk that it lives on
+    pub check_interval_secs: u64,
+}
+
+impl Default for StorageQuota {
+    /// Builds a quota leaving the database unbounded, but keeping at least a gigabyte free on the disk.
+    fn default() -> Self {
+        Self {
+            max_db_bytes: 0,
+            low_free_bytes: DEFAULT_LOW_FREE_BYTES,
+            min_free_bytes: DEFAULT_MIN_FREE_BYTES,
+            check_interval_secs: DEFAULT_CHECK_INTERVAL_SECS,
+        }
+    }
+}
+
+/// Implement a set of storage quota helper methods.
+impl StorageQuota {
+    /// Decides how close the node is to running out of room.
+    ///
+    /// # Arguments
+    ///
+    /// * `db_bytes` - The number of bytes taken up by the database
+    /// * `available_bytes` - The number of bytes left on the disk, if it could be measured
+    pub fn assess(&self, db_bytes: u64, available_bytes: Option<u64>) -> StoragePressure {
+        let free_below = |threshold: u64| available_bytes.map_or(false, |free| free < threshold);
+        let quota_past = |percent: u64| {
+            self.max_db_bytes != 0
+                && db_bytes.saturating_mul(100) >= self.max_db_bytes.saturating_mul(percent)
+        };
+
+        if quota_past(100) || free_below(self.min_free_bytes) {
+            StoragePressure::Critical
+        } else if quota_past(LOW_QUOTA_PERCENT) || free_below(self.low_free_bytes) {
+            StoragePressure::Low
+        } else {
+            StoragePressure::Normal
+        }
+    }
+}
+
+/// The latest measurement of the node's database & the disk that it lives on.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct StorageStatus {
+    /// The number of bytes taken up by the database
+    pub db_bytes: u64,
+
+    /// The number of bytes left on the disk, if it could be measured
+    pub available_bytes: Option<u64>,
+
+    /// How close the node is to running out of room
+    pub pressure: StoragePressure,
+
+    /// When the database & disk were last measured
+    pub checked_at: Option<DateTime<Utc>>,
+
+    /// When the node last pruned what it could to make room
+    pub relieved_at: Option<DateTime<Utc>>,
+
+    /// The number of writes refused since the node started, for want of room
+    pub refused_writes: u64,
+
+    /// Whether or not writes to the disk are failing (e.g. since it was remounted read-only), leaving the node to
+    /// serve reads without voting or publishing
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// When the node last found that it couldn't write to the disk, if it's in read-only mode
+    #[serde(default)]
+    pub read_only_since: Option<DateTime<Utc>>,
+
+    /// The write failure that put the node in read-only mode, if it's in read-only mode
+    #[serde(default)]
+    pub write_failure: Option<String>,
+}
+
+/// Keeps an eye on the size of the node's database and the room left on its disk, so that the node can make room (or
+/// stop writing what it can do without) before sled starts failing writes in the middle of consensus.
+pub struct StorageMonitor {
+    /// How much room the database may take up, and how much it must leave
+    pub quota: StorageQuota,
+
+    /// The latest measurement
+    status: StorageStatus,
+
+    /// Whether or not the node is in read-only mode, shared with anything refusing writes on the node's behalf
+    read_only: Arc<AtomicBool>,
+}
+
+/// Implement a set of storage monitor helper methods.
+impl StorageMonitor {
+    /// Makes a monitor holding the node to the given quota.
+    ///
+    /// # Arguments
+    ///
+    /// * `quota` - How much room the database may take up, and how much it must leave
+    pub fn new(quota: StorageQuota) -> Self {
+        Self {
+            quota,
+            status: StorageStatus::default(),
+            read_only: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Gets a flag that is raised for as long as the node is in read-only mode.
+    pub fn read_only_flag(&self) -> Arc<AtomicBool> {
+        self.read_only.clone()
+    }
+
+    /// Checks whether or not the node is in read-only mode, since writes to its disk are failing.
+    pub fn is_read_only(&self) -> bool {
+        self.status.read_only
+    }
+
+    /// Records that a write to the disk failed, putting the node in read-only mode. Results in whether or not the node
+    /// was writing normally until now.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error that the write failed with
+    pub fn record_write_failure(&mut self, error: &str) -> bool {
+        let entered = !self.status.read_only;
+
+        self.status.read_only = true;
+        self.status.write_failure = Some(error.to_owned());
+        if entered {
+            self.status.read_only_since = Some(Utc::now());
+        }
+        self.read_only.store(true, Ordering::SeqCst);
+
+        entered
+    }
+
+    /// Records that a write to the disk succeeded, taking the node out of read-only mode. Results in whether or not
+    /// the node was in read-only mode until now.
+    pub fn record_write_success(&mut self) -> bool {
+        let recovered = self.status.read_only;
+
+        self.status.read_only = false;
+        self.status.read_only_since = None;
+        self.status.write_failure = None;
+        self.read_only.store(false, Ordering::SeqCst);
+
+        recovered
+    }
+
+    /// Gets the latest measurement.
+    pub fn status(&self) -> &StorageStatus {
+        &self.status
+    }
+
+    /// Checks whether or not the database & disk are due to be measured again.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    pub fn due(&self, now: DateTime<Utc>) -> bool {
+        self.status.checked_at.map_or(true, |checked_at| {
+            now - checked_at >= Duration::seconds(self.quota.check_interval_secs as i64)
+        })
+    }
+
+    /// Measures the database in the given directory, and the disk that it lives on. Results in the pressure that the
+    /// node was under before the measurement.
+    ///
+    /// # Arguments
+    ///
+    /// * `db_path` - The directory holding the database
+    pub fn measure(&mut self, db_path: &str) -> StoragePressure {
+        let db_bytes = WalkDir::new(db_path)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| entry.metadata().ok())
+            .filter(|metadata| metadata.is_file())
+            .map(|metadata| metadata.len())
+            .sum();
+
+        let available_bytes = match fs2::available_space(db_path) {
+            Ok(available) => Some(available),
+            Err(e) => {
+                debug!("Failed to measure the free space around {}: {}", db_path, e);
+
+                None
+            }
+        };
+
+        self.record(db_bytes, available_bytes, Utc::now())
+    }
+
+    /// Records a measurement of the database & disk. Results in the pressure that the node was under before the
+    /// measurement.
+    ///
+    /// # Arguments
+    ///
+    /// * `db_bytes` - The number of bytes taken up by the database
+    /// * `available_bytes` - The number of bytes left on the disk, if it could be measured
+    /// * `now` - The time of the measurement
+    pub fn record(
+        &mut self,
+        db_bytes: u64,
+        available_bytes: Option<u64>,
+        now: DateTime<Utc>,
+    ) -> StoragePressure {
+        let previous = self.status.pressure;
+
+        self.status.db_bytes = db_bytes;
+        self.status.available_bytes = available_bytes;
+        self.status.pressure = self.quota.assess(db_bytes, available_bytes);
+        self.status.checked_at = Some(now);
+
+        previous
+    }
+
+    /// Records that the node pruned what it could to make room.
+    pub fn record_relieved(&mut self) {
+        self.status.relieved_at = Some(Utc::now());
+    }
+
+    /// Makes sure that there's room for a write that consensus doesn't depend on, and that the disk takes writes at
+    /// all, counting the write as refused if it doesn't.
+    pub fn admit_noncritical(&mut self) -> Result<(), StorageError> {
+        if let Some(error) = self.status.write_failure.clone() {
+            self.status.refused_writes += 1;
+
+            return Err(StorageError::ReadOnly { error });
+        }
+
+        if self.status.pressure < StoragePressure::Critical {
+            return Ok(());
+        }
+
+        self.status.refused_writes += 1;
+
+        Err(StorageError::Exhausted {
+            db_bytes: self.status.db_bytes,
+            available_bytes: self.status.available_bytes.unwrap_or_default(),
+        })
+    }
+}
+
+impl Default for StorageMonitor {
+    /// Makes a monitor holding the node to the default quota.
+    fn default() -> Self {
+        Self::new(StorageQuota::default())
+    }
+}
+
+/// Checks that the disk holding the given directory still takes writes, by writing, syncing & removing a small file
+/// inside of it.
+///
+/// # Arguments
+///
+/// * `dir` - The directory that should be written to
+pub fn probe_writable(dir: &str) -> io::Result<()> {
+    let path = Path::new(dir).join(WRITE_PROBE_FILE);
+
+    let mut file = fs::File::create(&path)?;
+    file.write_all(b"ok")?;
+    file.sync_all()?;
+
+    fs::remove_file(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_storage_monitor() {
+        let mut monitor = StorageMonitor::new(StorageQuota {
+            max_db_bytes: 1000,
+            low_free_bytes: 500,
+            min_free_bytes: 100,
+            check_interval_secs: 60,
+        });
+        let now = Utc::now();
+        assert!(monitor.due(now));
+
+        // Running low on either the quota or the disk makes the node prune
+        monitor.record(100, Some(10_000), now);
+        assert_eq!(monitor.status().pressure, StoragePressure::Normal);
+        assert!(!monitor.due(now + Duration::seconds(30)));
+        assert!(monitor.due(now + Duration::seconds(60)));
+        assert_eq!(
+            monitor.quota.assess(900, Some(10_000)),
+            StoragePressure::Low
+        );
+        assert_eq!(monitor.quota.assess(100, Some(400)), StoragePressure::Low);
+
+        // Running out of either refuses writes that consensus can do without
+        assert!(monitor.admit_noncritical().is_ok());
+        assert_eq!(monitor.record(1000, None, now), StoragePressure::Normal);
+        assert_eq!(
+            monitor.admit_noncritical(),
+            Err(StorageError::Exhausted {
+                db_bytes: 1000,
+                available_bytes: 0,
+            })
+        );
+        assert_eq!(
+            monitor.quota.assess(100, Some(50)),
+            StoragePressure::Critical
+        );
+        assert_eq!(monitor.status().refused_writes, 1);
+
+        // Disks that can't be measured only count against the database quota
+        assert_eq!(
+            StorageQuota::default().assess(u64::max_value(), None),
+            StoragePressure::Normal
+        );
+
+        // Failed writes put the node in read-only mode until the disk takes writes again
+        let mut monitor = StorageMonitor::default();
+        let flag = monitor.read_only_flag();
+        assert!(monitor.record_write_failure("Read-only file system"));
+        assert!(!monitor.record_write_failure("Read-only file system"));
+        assert!(monitor.is_read_only() && flag.load(Ordering::SeqCst));
+        assert!(monitor.admit_noncritical().is_err());
+        assert!(monitor.record_write_success());
+        assert!(!flag.load(Ordering::SeqCst));
+        assert!(monitor.admit_noncritical().is_ok());
+        assert!(probe_writable(&std::env::temp_dir().to_string_lossy()).is_ok());
+    }
+}
@@ -1,31 +1,92 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    convert::TryInto,
     error::Error,
+    io::Read,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
 }; // Import collections
 
+use chrono::{DateTime, Utc}; // Import time utilities
+
 use num::{
     bigint::{self, BigInt, BigUint, ToBigInt},
-    Zero,
+    CheckedSub, Zero,
 }; // Add support for large unsigned integers
 
 use super::{
     super::{
         super::{
-            common::address::Address,
-            crypto::hash::Hash,
+            accounts::{signer::RemoteSigner, unlock::UnlockSessions},
+            common::{
+                address::Address,
+                diversity::{DiversityPolicy, Witness, Witnesses},
+            },
+            crypto::{
+                blake3,
+                hash::Hash,
+                short_id::{self, ShortIdError},
+            },
             validator::{GraphBoundValidator, Validator},
         },
-        types::{graph::Graph, transaction::Transaction},
+        types::{
+            chainfile::{ChainFileError, ChainReader},
+            gas,
+            graph::{Graph, ResolutionError, DEFAULT_TIPS_PER_TRANSACTION},
+            issuance::{
+                self, issuance_address, IssuanceError, Mint, SupplyReport, DEFAULT_EPOCH_LENGTH,
+                ISSUANCE_PARAM,
+            },
+            light::{self, HeaderChain, LightClientError, LightHeader, ProvenBalance},
+            receipt::{self, ExecutionClock, ExecutionStatus, Receipt},
+            rewards::{self, RewardsReport},
+            transaction::Transaction,
+        },
     },
+    checkpoints::CheckpointSet,
     config,
-    proposal::{Operation, Proposal},
-    vote::Vote,
+    config_sync::{ConfigRecord, ConfigSyncError},
+    conflicts::{ConflictTracker, DoubleSpendAlert, MAX_RECENT_DOUBLE_SPENDS},
+    consensus::{Verdict, BASIS_POINTS, DEFAULT_THRESHOLD_BPS},
+    diagnosis::{self, AccountDiagnosis, TransactionLocation, WaitingTransaction},
+    events::{self, Event, EventFeed},
+    exports::{ExportSessions, EXPORTS_TREE},
+    history::{
+        ExecutedProposal, ExecutionHistory, ExecutionOutcome, HISTORY_TREE, RETAINED_EXECUTIONS,
+    },
+    invariants::{self, InvariantViolation},
+    journal::{JournaledVote, VoteJournal, VoteOrigin, VOTE_JOURNAL_TREE},
+    mempool::{Mempool, MempoolError},
+    proposal::{
+        Operation, Proposal, ProposalBody, ProposalData, ProposalError, ScheduledChange,
+        CONFIG_PARAMS,
+    },
+    simulation::{diff_balances, diff_configs, ProposalSimulation},
+    stats::{Bucket, NetworkStats, STATS_TREE},
+    storage::{self, StorageMonitor, StoragePressure},
+    vote::{Tally, Vote},
+    voting_policy::{VotingAccountChange, VotingPolicy},
 }; // Import hash types
 
+/// The name of the tree in the ledger's database in which vote tallies are persisted.
+pub const TALLIES_TREE: &str = "votes";
+
+/// The name of the tree in the ledger's database in which pending proposals are persisted, alongside their tallies.
+pub const PROPOSALS_TREE: &str = "proposals";
+
+/// The name of the tree in the ledger's database in which the proposals scheduling config changes are persisted.
+pub const SCHEDULED_CHANGES_TREE: &str = "scheduled";
+
+/// The name of the tree in the ledger's database in which the proposal that produced the current version of the
+/// config is persisted.
+pub const CONFIG_PROPOSAL_TREE: &str = "config_proposal";
+
+/// The name of the tree in the ledger's database in which the signed votes cast during each issuance epoch are kept
+/// until the epoch's reward is minted.
+pub const PARTICIPATION_TREE: &str = "participation";
+
 /// An error encountered while executing a proposal.
 #[derive(Debug, Fail)]
 pub enum ExecutionError {
@@ -51,6 +112,26 @@ pub enum ExecutionError {
     },
 }
 
+impl From<ResolutionError> for ExecutionError {
+    /// Converts the given parent resolution error into an ExecutionError.
+    fn from(e: ResolutionError) -> Self {
+        // Return a miscellaneous error
+        Self::Miscellaneous {
+            error: e.to_string(),
+        }
+    }
+}
+
+impl From<ConfigSyncError> for ExecutionError {
+    /// Converts the given config record error into an ExecutionError.
+    fn from(e: ConfigSyncError) -> Self {
+        // Return a miscellaneous error
+        Self::Miscellaneous {
+            error: e.to_string(),
+        }
+    }
+}
+
 impl From<sled::Error> for ExecutionError {
     /// Converts the given sled error into an ExecutionError.
     fn from(e: sled::Error) -> Self {
@@ -75,11 +156,116 @@ pub struct System {
     /// The ledger
     pub ledger: Graph,
 
-    /// The total weight of each proposed vote
-    votes: HashMap<Hash, BigInt>,
+    /// The votes cast on each proposal
+    tallies: HashMap<Hash, Tally>,
+
+    /// The tree in which vote tallies are persisted, if it could be opened
+    tally_store: Option<sled::Tree>,
+
+    /// The tree in which pending proposals are persisted, if it could be opened
+    proposal_store: Option<sled::Tree>,
+
+    /// When each of the pending proposals was first seen by the node
+    opened_at: HashMap<Hash, DateTime<Utc>>,
+
+    /// Proposals that have gathered enough votes to be decided, but whose minimum voting period hasn't passed yet
+    maturing: HashSet<Hash>,
+
+    /// The distinct peers that relayed the votes cast on each pending proposal
+    vote_witnesses: HashMap<Hash, Witnesses>,
+
+    /// How many distinct peers must have relayed the votes on a proposal before it may be executed
+    pub diversity_policy: DiversityPolicy,
+
+    /// The proposals scheduling config changes that have passed, but that are waiting for the ledger to reach their
+    /// activation height
+    pub scheduled_changes: Vec<Proposal>,
+
+    /// The tree in which scheduled changes are persisted, if it could be opened
+    scheduled_store: Option<sled::Tree>,
+
+    /// The executed proposal that produced the current version of the config, which is published alongside it
+    pub config_proposal: Option<Proposal>,
+
+    /// The tree in which the proposal behind the current version of the config is persisted, if it could be opened
+    config_proposal_store: Option<sled::Tree>,
+
+    /// The activity observed on the network over time
+    pub stats: NetworkStats,
 
-    /// Whether or not a user has voted for a particular proposal
-    voted: HashMap<Hash, HashMap<Address, bool>>,
+    /// The tree in which network statistics are persisted, if it could be opened
+    stats_store: Option<sled::Tree>,
+
+    /// The most recent proposals executed by the node, along with what came of them
+    pub history: ExecutionHistory,
+
+    /// The tree in which the history of executed proposals is persisted, if it could be opened
+    history_store: Option<sled::Tree>,
+
+    /// The votes that the node has seen cast during each issuance epoch that hasn't been paid out yet
+    participation: HashMap<u64, Vec<Vote>>,
+
+    /// The tree in which vote participation is persisted, if it could be opened
+    participation_store: Option<sled::Tree>,
+
+    /// The rules dictating how the node votes on incoming proposals
+    pub voting_policy: VotingPolicy,
+
+    /// The checkpoints that any synchronized history must agree with
+    pub checkpoints: CheckpointSet,
+
+    /// Proposals that the voting policy requires the operator to vote on manually
+    pub awaiting_confirmation: HashSet<Hash>,
+
+    /// The addresses of the accounts that the node is currently voting with
+    pub active_voters: Vec<Address>,
+
+    /// Changes to the node's voting accounts that have been requested, but not yet applied
+    voting_account_changes: Vec<VotingAccountChange>,
+
+    /// Votes cast locally that have not yet been published, each already sealed for gossip by its voter
+    localized_votes: Vec<Vec<u8>>,
+
+    /// Signed transactions submitted locally that have not yet been executed, published in order of their fees
+    pub mempool: Mempool,
+
+    /// The separate process that votes and transactions are signed by, if the node doesn't hold any keys itself
+    pub remote_signer: Option<RemoteSigner>,
+
+    /// The accounts unlocked over RPC for a limited time, each sealed under a handle held by its caller
+    pub unlock_sessions: UnlockSessions,
+
+    /// The proposals, votes & transactions seen by the node, for subscribers to poll
+    pub events: EventFeed,
+
+    /// The long exports of the ledger that consumers may resume
+    pub exports: ExportSessions,
+
+    /// The votes cast with each of the node's accounts, so that their owners can audit what their keys have signed
+    pub vote_journal: VoteJournal,
+
+    /// The transactions recently seen spending each sender's nonces, so that competing spends can be flagged
+    pub conflicts: ConflictTracker,
+
+    /// Double spends that have been detected, but not yet handed to the network client to be delivered
+    pending_double_spends: Vec<DoubleSpendAlert>,
+
+    /// Proposals that have been executed, along with the transactions that they appended (if any), but not yet handed
+    /// to the network client to be delivered
+    pending_executions: Vec<(ExecutedProposal, Option<Transaction>)>,
+
+    /// The size of the ledger's database, and the room left on its disk
+    pub storage: StorageMonitor,
+
+    /// The headers synchronized by the node, if it runs as a light client, following the ledger's headers rather than
+    /// executing its transactions
+    pub header_chain: Option<HeaderChain>,
+
+    /// How the execution of each transaction is timestamped in its receipt
+    pub execution_clock: ExecutionClock,
+
+    /// The time that the last execution was stamped with, if any
+    last_executed_at: Option<DateTime<Utc>>,
 
     /// Whether or not new proposals have been added to the system
     new_tx_ctx: Arc<AtomicBool>,
@@ -92,15 +278,8 @@ impl System {
         // Copy the network name, since we'll have to move the configuration into the system
         let network_name = &config.network_name.clone();
 
-        System {
-            config,                                              // Set config
-            pending_proposals: HashMap::new(), // set pending proposals to empty initialized hash map
-            localized_proposals: HashMap::new(), // a set of proposals that have been registered, but not yet published
-            ledger: Graph::read_partial_from_disk(network_name), // Set ledger
-            new_tx_ctx: Arc::new(AtomicBool::new(false)),
-            voted: HashMap::new(),
-            votes: HashMap::new(),
-        } // Return initialized system
+        System::with_ledger(config, Graph::read_partial_from_disk(network_name))
+        // Return initialized system
     }
 
     /// Initialize a new proposal execution system with the given data directory.
@@ -108,19 +287,215 @@ impl System {
         // Copy the network name, sine we'll have to move the configuration into the system
         let network_name = &config.network_name.clone();
 
+        let mut system = System::with_ledger(
+            config,
+            Graph::read_partial_from_disk_with_data_dir(data_dir, network_name),
+        );
+
+        // Use the voting policy stored in the data directory, if there is one
+        if let Ok(policy) = VotingPolicy::read_from_disk_at_data_directory(data_dir) {
+            system.voting_policy = policy;
+        }
+
+        // Use the checkpoints pinned in the data directory, if there are any
+        if let Ok(checkpoints) = CheckpointSet::read_from_disk_at_data_directory(data_dir) {
+            system.checkpoints = checkpoints;
+        }
+
+        system // Return initialized system
+    }
+
+    /// Initialize a new proposal execution system around the given ledger, restoring any vote tallies persisted alongside it.
+    pub(crate) fn with_ledger(config: config::Config, ledger: Graph) -> Self {
+        // Open the tree that vote tallies are kept in
+        let tally_store = match ledger.open_tree(TALLIES_TREE) {
+            Ok(tree) => Some(tree),
+            Err(e) => {
+                warn!(
+                    "Failed to open the vote tally store; tallies will not persist: {}",
+                    e
+                );
+
+                None
+            }
+        };
+
+        // Reload each of the proposals that were still pending before the last shutdown
+        let proposal_store = ledger.open_tree(PROPOSALS_TREE).ok();
+        let pending_proposals: HashMap<Hash, Proposal> = proposal_store
+            .iter()
+            .flat_map(|tree| tree.iter().values())
+            .filter_map(|value| bincode::deserialize::<Proposal>(&value.ok()?).ok())
+            .map(|proposal| (proposal.proposal_id, proposal))
+            .collect();
+
+        // Reload each of the tallies recorded before the last shutdown, discarding those whose proposal is gone
+        let mut tallies: HashMap<Hash, Tally> = HashMap::new();
+        if let Some(tree) = &tally_store {
+            for (key, value) in tree.iter().filter_map(|pair| pair.ok()) {
+                let proposal = Hash::new(key.to_vec());
+
+                if !pending_proposals.contains_key(&proposal) {
+                    if let Err(e) = tree.remove(&key) {
+                        warn!(
+                            "Failed to discard the orphaned vote tally for proposal {}: {}",
+                            proposal, e
+                        );
+                    }
+
+                    continue;
+                }
+
+                if let Ok(tally) = bincode::deserialize::<Tally>(&value) {
+                    tallies.insert(proposal, tally);
+                }
+            }
+        }
+
+        // We can't know when the reloaded proposals were first seen, so their voting periods start over
+        let opened_at: HashMap<Hash, DateTime<Utc>> = pending_proposals
+            .keys()
+            .map(|proposal| (*proposal, Utc::now()))
+            .collect();
+
+        // Reload each of the changes that were still waiting to be activated
+        let scheduled_store = ledger.open_tree(SCHEDULED_CHANGES_TREE).ok();
+        let scheduled_changes: Vec<Proposal> = scheduled_store
+            .iter()
+            .flat_map(|tree| tree.iter())
+            .filter_map(|pair| {
+                let (key, value) = pair.ok()?;
+                if let Some(proposal) = bincode::deserialize::<Proposal>(&value)
+                    .ok()
+                    .filter(|proposal| scheduled_change(proposal).is_some())
+                {
+                    return Some(proposal);
+                }
+
+                // Changes scheduled before their proposals were kept are re-filed under a proposal of their own
+                let legacy: ScheduledChange = bincode::deserialize(&value).ok()?;
+                let proposal = Proposal::new(
+                    format!(
+                        "scheduled change at ledger height {}",
+                        legacy.activation_height
+                    ),
+                    ProposalData::scheduled(legacy.activation_height, legacy.change),
+                );
+
+                if let Some(tree) = &scheduled_store {
+                    let refiled = bincode::serialize(&proposal)
+                        .map_err(|e| e.to_string())
+                        .and_then(|encoded| {
+                            tree.insert(proposal.proposal_id, encoded)
+                                .and_then(|_| tree.remove(key))
+                                .map_err(|e| e.to_string())
+                        });
+                    if let Err(e) = refiled {
+                        warn!("Failed to re-file scheduled change: {}", e);
+                    }
+                }
+
+                Some(proposal)
+            })
+            .collect();
+
+        // Reload the proposal behind the current version of the config
+        let config_proposal_store = ledger.open_tree(CONFIG_PROPOSAL_TREE).ok();
+        let config_proposal: Option<Proposal> = config_proposal_store
+            .as_ref()
+            .and_then(|tree| tree.get(CONFIG_PROPOSAL_TREE).ok()?)
+            .and_then(|value| bincode::deserialize(&value).ok());
+
+        // Reload the network activity recorded before the last shutdown
+        let stats_store = ledger.open_tree(STATS_TREE).ok();
+        let stats = NetworkStats::from_buckets(
+            stats_store
+                .iter()
+                .flat_map(|tree| tree.iter().values())
+                .filter_map(|value| bincode::deserialize::<Bucket>(&value.ok()?).ok()),
+        );
+
+        // Reload the proposals executed before the last shutdown
+        let history_store = ledger.open_tree(HISTORY_TREE).ok();
+        let history = ExecutionHistory::from_entries(
+            history_store
+                .iter()
+                .flat_map(|tree| tree.iter().values())
+                .filter_map(|value| bincode::deserialize(&value.ok()?).ok()),
+        );
+
+        // Reload the participation counted in each epoch that hasn't been paid out yet
+        let participation_store = ledger.open_tree(PARTICIPATION_TREE).ok();
+        let participation: HashMap<u64, Vec<Vote>> = participation_store
+            .iter()
+            .flat_map(|tree| tree.iter())
+            .filter_map(|pair| {
+                let (key, value) = pair.ok()?;
+                let mut epoch = [0; 8];
+                epoch.copy_from_slice(key.get(..8)?);
+
+                Some((
+                    u64::from_be_bytes(epoch),
+                    bincode::deserialize(&value).ok()?,
+                ))
+            })
+            .collect();
+
+        // Reload the exports that were still open before the last shutdown
+        let exports = ExportSessions::new(ledger.open_tree(EXPORTS_TREE).ok());
+
+        // Pick up the journal of the votes cast with the node's accounts
+        let vote_journal = VoteJournal::new(ledger.open_tree(VOTE_JOURNAL_TREE).ok());
+
         System {
             config,
-            pending_proposals: HashMap::new(),
-            localized_proposals: HashMap::new(),
-            ledger: Graph::read_partial_from_disk_with_data_dir(data_dir, network_name),
+            pending_proposals,
+            localized_proposals: HashMap::new(), // a set of proposals that have been registered, but not yet published
+            ledger,
             new_tx_ctx: Arc::new(AtomicBool::new(false)),
-            voted: HashMap::new(),
-            votes: HashMap::new(),
+            tallies,
+            tally_store,
+            proposal_store,
+            opened_at,
+            maturing: HashSet::new(),
+            vote_witnesses: HashMap::new(),
+            diversity_policy: DiversityPolicy::default(),
+            scheduled_changes,
+            scheduled_store,
+            config_proposal,
+            config_proposal_store,
+            stats,
+            stats_store,
+            history,
+            history_store,
+            participation,
+            participation_store,
+            voting_policy: VotingPolicy::default(),
+            checkpoints: CheckpointSet::default(),
+            awaiting_confirmation: HashSet::new(),
+            active_voters: Vec::new(),
+            voting_account_changes: Vec::new(),
+            localized_votes: Vec::new(),
+            mempool: Mempool::default(),
+            remote_signer: None,
+            unlock_sessions: UnlockSessions::default(),
+            events: EventFeed::default(),
+            exports,
+            vote_journal,
+            conflicts: ConflictTracker::default(),
+            pending_double_spends: Vec::new(),
+            pending_executions: Vec::new(),
+            storage: StorageMonitor::default(),
+            header_chain: None,
+            execution_clock: ExecutionClock::default(),
+            last_executed_at: None,
         }
     }
 
-    /// Add a given proposal to the system's localized proposals list.
-    pub fn register_proposal(&mut self, proposal: Proposal) {
+    /// Add a given proposal to the system's localized proposals list, so long as it is well-formed.
+    pub fn register_proposal(&mut self, proposal: Proposal) -> Result<(), ProposalError> {
+        proposal.proposal_data.validate()?; // Make sure the proposal makes sense
+
         // Check proposal not already registered
         self.localized_proposals
             .entry(proposal.proposal_id)
@@ -128,13 +503,33 @@ impl System {
 
         // Make sure that we're going to broadcast the new proposals
         self.new_tx_ctx.store(true, Ordering::SeqCst);
+
+        Ok(())
     }
 
-    /// Add a given proposal to the system's pending proposals list without alerting any listeners to this change.
-    pub fn push_proposal(&mut self, proposal: Proposal) {
+    /// Add a given proposal to the system's pending proposals list without alerting any listeners to this change, so
+    /// long as it is well-formed.
+    pub fn push_proposal(&mut self, proposal: Proposal) -> Result<(), ProposalError> {
+        proposal.proposal_data.validate()?; // Make sure the proposal makes sense
+
         // Register the proposal without altering the new_tx context
-        self.pending_proposals
-            .insert(proposal.proposal_id, proposal);
+        self.opened_at
+            .entry(proposal.proposal_id)
+            .or_insert_with(Utc::now);
+
+        // Copies of a proposal that we already know about may carry council signatures that ours doesn't
+        let proposal_id = proposal.proposal_id;
+        match self.pending_proposals.get_mut(&proposal_id) {
+            Some(known) => known.merge_council_signatures(proposal),
+            None => {
+                self.announce_proposal(&proposal);
+                self.pending_proposals.insert(proposal_id, proposal);
+            }
+        }
+
+        self.persist_proposal(&proposal_id);
+
+        Ok(())
     }
 
     /// Clears the list of localized proposals contained inside the system.
@@ -152,7 +547,10 @@ impl System {
     pub fn propose_proposal(&mut self, proposal_id: &Hash) -> Result<(), ExecutionError> {
         // Ensure that the proposal exists. Otherwise, return a suitable error
         if let Some(prop) = self.localized_proposals.remove(proposal_id) {
+            self.opened_at.entry(*proposal_id).or_insert_with(Utc::now);
+            self.announce_proposal(&prop);
             self.pending_proposals.insert(*proposal_id, prop);
+            self.persist_proposal(proposal_id);
 
             Ok(())
         } else {
@@ -162,284 +560,2102 @@ impl System {
         }
     }
 
-    /// Gets an atomic reference to the system's current new_tx state variable.
-    pub(crate) fn get_state_ref(&self) -> Arc<AtomicBool> {
-        // Clone the system's new_tx ctx reference variable
-        self.new_tx_ctx.clone()
-    }
-
-    /// Registers a provided vote as in favor or against a particular proposal.
+    /// Registers a vote cast by one of the node's own accounts, and queues it to be published.
     ///
     /// # Arguments
     ///
-    /// * `proposal_id` - The hash of the proposal that the vote is targeting
-    /// * `vote` - The vote primitive instance
-    pub fn register_vote_for_proposal(
+    /// * `vote` - The vote that should be registered
+    /// * `sealed` - The vote, sealed for gossip by its voter, as it should be published
+    pub fn register_local_vote(
         &mut self,
-        proposal_id: Hash,
-        vote: &Vote,
+        vote: Vote,
+        sealed: Vec<u8>,
     ) -> Result<(), ExecutionError> {
-        // Ensure that the proposal exists in the runtime
-        if self.pending_proposals.contains_key(&proposal_id) {
-            // The vote must have a signature in order to be registered
-            let sig = if let Some(sig) = vote.clone().signature.take() {
-                sig
-            } else {
-                return Err(ExecutionError::Miscellaneous {
-                    error: "vote signature is nil".to_owned(),
-                });
-            };
+        self.register_vote_for_proposal(vote.target_proposal, &vote)?; // Count the vote
 
-            // Try to get a public key from the vote's signature. If there is no public key
-            // associated with the signature, it must be invalid.
-            if let Ok(public_key) = sig.public_key() {
-                // The signature must be valid
-                if !sig.verify(&*vote.hash()) {
-                    return Err(ExecutionError::Miscellaneous {
-                        error: "vote signature is invalid".to_owned(),
-                    });
-                }
+        // The operator has made up their mind about the proposal
+        self.awaiting_confirmation.remove(&vote.target_proposal);
 
-                // Get the set of users that have voted for the proposal so that we can ensure
-                // this person isn't voting twice
-                let voters = self.voted.entry(proposal_id).or_insert_with(HashMap::new);
+        self.localized_votes.push(sealed); // Queue the vote for publishing
 
-                // Ensure the voter is unique
-                let has_voted = voters
-                    .entry(Address::from_public_key(&public_key))
-                    .or_insert(false);
-
-                if !*has_voted {
-                    // We've voted now
-                    *has_voted = true;
-
-                    // Calculate the weight of the vote
-                    let vote_value =
-                        if let Some(bigint) = self.determine_vote_weight(vote).to_bigint() {
-                            bigint
-                        } else {
-                            BigInt::zero()
-                        };
-
-                    *self.votes.entry(proposal_id).or_insert_with(BigInt::zero) += if vote.in_favor
-                    {
-                        vote_value
-                    } else {
-                        -vote_value
-                    };
-                }
-            } else {
-                return Err(ExecutionError::Miscellaneous {
-                    error: "vote signature public key is nil".to_owned(),
-                });
-            }
+        // Make sure that we're going to broadcast the new vote
+        self.new_tx_ctx.store(true, Ordering::SeqCst);
 
-            Ok(())
-        } else {
-            Err(ExecutionError::ProposalDoesNotExist {
-                proposal_id: proposal_id.to_str(),
-            })
-        }
+        Ok(())
     }
 
-    /// Execute a proposal in the pending proposals set with the given hash.
-    pub fn execute_proposal(&mut self, proposal_id: Hash) -> Result<(), ExecutionError> {
-        // Try to pull the proposal out of the proposal queue. If this fails, that means that the proposal doesn't actually exist
-        let target_proposal = if let Some(prop) = self.pending_proposals.remove(&proposal_id) {
-            prop
-        } else {
-            return Err(ExecutionError::ProposalDoesNotExist {
-                proposal_id: proposal_id.to_str(),
-            }); // Return error
-        };
-
-        // Handle different target system parameters
-        match target_proposal.proposal_data.param_name.as_str() {
-            // Proposal is targeting the reward_per_gas config field
-            "config::reward_per_gas" => {
-                // Handle different operations
-                match target_proposal.proposal_data.operation {
-                    // Is updating reward_per_gas
-                    Operation::Amend { amended_value } => {
-                        self.config.reward_per_gas = bigint::BigUint::from_bytes_le(&amended_value)
-                    } // Set reward_per_gas
-                    // Is setting reward_per_gas to zero
-                    Operation::Remove => {
-                        self.config.reward_per_gas = bigint::BigUint::from(0 as u16)
-                    }
-                    // Is adding a value to the reward_per_gas
-                    Operation::Append { value_to_append } => {
-                        self.config.reward_per_gas = self.config.reward_per_gas.clone()
-                            + bigint::BigUint::from_bytes_le(&value_to_append)
-                    } // Add to reward_per_gas
-                }
+    /// Journals the given vote, cast with one of the node's accounts, so that the account's owner can later audit it.
+    /// Must be called before the proposal voted on is executed. Results in the journaled vote, if the vote is signed.
+    ///
+    /// # Arguments
+    ///
+    /// * `vote` - The vote that was cast
+    /// * `reason` - Why the vote was cast the way it was
+    /// * `origin` - How the vote came to be cast
+    /// * `remote` - Whether the vote was signed by the node's remote signer
+    pub fn journal_vote(
+        &mut self,
+        vote: &Vote,
+        reason: &str,
+        origin: VoteOrigin,
+        remote: bool,
+    ) -> Option<JournaledVote> {
+        let voter = vote.voter_address()?;
+        let proposal = self.pending_proposals.get(&vote.target_proposal);
 
-                let operation_result = self.config.write_to_disk(); // Write config to disk
-                                                                    // Check for errors
-                if let Err(e) = operation_result {
-                    Err(ExecutionError::Miscellaneous {
-                        error: e.to_string(),
-                    })
-                } else {
-                    Ok(()) // Mhm
-                }
+        // Mints & ordinary transactions both append a transaction, which the owner will want to look up
+        let transaction = proposal.and_then(|proposal| match &proposal.proposal_data.operation {
+            Operation::Append { value_to_append }
+                if proposal.proposal_data.param_name == events::TRANSACTIONS_PARAM
+                    || proposal.proposal_data.param_name == ISSUANCE_PARAM =>
+            {
+                bincode::deserialize::<Transaction>(value_to_append)
+                    .ok()
+                    .map(|tx| tx.hash)
             }
-            // Proposal is targeting the network_name config field
-            "config::network_name" => {
-                // Handle different operations
-                match target_proposal.proposal_data.operation {
-                    // Is updating network_name
-                    Operation::Amend { amended_value } => {
-                        self.config.network_name =
-                            String::from_utf8_lossy(&amended_value).into_owned()
-                    } // Set network_name
-                    // Is setting network_name to ""
-                    Operation::Remove => self.config.network_name = "".to_owned(), // Set network_name to empty string
-                    // Is appending a substring to the network_name
-                    Operation::Append { value_to_append } => {
-                        self.config.network_name = format!(
-                            "{}{}",
-                            self.config.network_name,
-                            String::from_utf8_lossy(&value_to_append).into_owned()
-                        )
-                    } // Append to network_name
-                }
+            _ => None,
+        });
 
-                let operation_result = self.config.write_to_disk(); // Write config to disk
-                if let Err(e) = operation_result {
-                    // Check for errors
-                    Err(ExecutionError::Miscellaneous {
-                        error: e.to_string(),
-                    }) // Return error
-                } else {
-                    Ok(()) // Mhm
-                }
-            }
-            // Proposal is targeting the ledger
-            "ledger::transactions" => {
-                // Handle different operations
-                match target_proposal.proposal_data.operation {
-                    // Targeted amend, despite the fact that ledger operations cannot be reverted
-                    Operation::Amend { .. } => Err(ExecutionError::InvalidOperation {
-                        operation: "amend".to_owned(),
-                        proposal_param: "ledger::transactions".to_owned(),
-                    }),
-                    // Targeted remove, despite the fact that ledger operations cannot be reverted
-                    Operation::Remove => Err(ExecutionError::InvalidOperation {
-                        operation: "remove".to_owned(),
-                        proposal_param: "ledger::transactions".to_owned(),
-                    }),
-                    // Is appending a transaction to the network ledger
-                    Operation::Append { value_to_append } => {
-                        let tx = Transaction::from_bytes(&value_to_append); // Deserialize transaction
-
-                        // Get the index of the submitted transaction entry
-                        let entry_index = self.ledger.push(tx.clone(), None);
-
-                        // Execute the parent transactions, get the overall hash
-                        let parent_tx_state = self.ledger.execute_parent_nodes(entry_index)?;
-
-                        // Get the hash of the parent state that the transaction THINKS is right
-                        let asserted_parent_state_hash = if let Some(parent_state_hash) =
-                            tx.transaction_data.parent_state_hash
-                        {
-                            parent_state_hash
-                        } else {
-                            // Remove the head tx, since it's invalid
-                            self.ledger.rollback_head();
-
-                            // Return the error
-                            return Err(ExecutionError::Miscellaneous {
-                                error: "Invalid transaction: must have parent state hash."
-                                    .to_owned(),
-                            });
-                        };
-
-                        // UWU WHAT'S THIS I SEE?
-                        if parent_tx_state.hash != asserted_parent_state_hash {
-                            // Remove the head tx, since it's invalid
-                            self.ledger.rollback_head();
-
-                            // Return the error
-                            return Err(ExecutionError::Miscellaneous{error: format!("Invalid transaction: merged parent states must have a hash matching that which is asserted by the transaction (found {}, tx asserted {}).", parent_tx_state.hash, asserted_parent_state_hash)});
-                        };
-
-                        //if let Ok(prev_state_entry) = self
-                        //    .ledger
-                        //    .execute_parent_nodes(self.ledger.nodes.len() - 1)
-                        //{
-                        //    let index = self.ledger.nodes.len() - 1; // Get index of pushed tx
-
-                        // Get previous state entry
-                        //self.ledger.nodes[index].state_entry =
-                        //Some(tx.execute(Some(prev_state_entry))); // Set node state entry
-                        //}
-
-                        Ok(())
-                    }
-                }
-            }
-            _ => Err(ExecutionError::InvalidTargetProposalParam {
-                proposal_param: target_proposal.proposal_data.param_name,
+        Some(self.vote_journal.record(JournaledVote {
+            seq: 0,
+            voter,
+            vote_hash: vote.hash(),
+            proposal_id: vote.target_proposal,
+            param: proposal.map_or_else(String::new, |proposal| {
+                proposal.proposal_data.param_name.clone()
             }),
-        }
+            transaction,
+            in_favor: vote.in_favor,
+            reason: reason.to_owned(),
+            origin,
+            remote,
+            voted_at: Utc::now(),
+        }))
     }
 
-    /// Determines the number of coins associated with a particular voter.
+    /// Takes up to the given number of locally cast votes that have not yet been published, oldest first, each sealed
+    /// for gossip.
     ///
     /// # Arguments
     ///
-    /// * `vote` - The vote that the weight should be determined of
-    pub fn determine_vote_weight(&self, vote: &Vote) -> BigUint {
-        // Determine the address of the account submitting the vote
-        let voter = if let Some(addr) = vote.voter_address() {
-            addr
-        } else {
-            // Since the vote doesn't have a voter, the vote can't have any weight
-            return BigUint::zero();
-        };
+    /// * `limit` - The greatest number of votes that should be taken
+    pub fn take_localized_votes(&mut self, limit: usize) -> Vec<Vec<u8>> {
+        let n = limit.min(self.localized_votes.len());
 
-        // Get the balance of the voter
-        self.ledger.get_balance_of_account(&voter)
+        self.localized_votes.drain(..n).collect() // Return the queued votes
     }
 
-    /// Determins the number of coins from voters in favor of the proposal.
+    /// Admits the given signed transaction to the mempool, from which it will be gossiped to the network as-is once
+    /// its sender's earlier transactions have gone out, leaving it to each node to wrap the transaction in a proposal
+    /// of its own.
     ///
     /// # Arguments
     ///
-    /// * `proposal` - The proposal that the weight should be determined of
-    pub fn get_coins_in_support_of(&self, proposal: &Hash) -> BigInt {
-        self.votes.get(proposal).unwrap_or(&BigInt::zero()).clone()
+    /// * `tx` - The transaction that should be gossiped
+    pub fn register_local_transaction(&mut self, tx: Transaction) -> Result<(), MempoolError> {
+        let expected = self.executed_nonce(&tx.transaction_data.sender);
+        self.mempool.insert(tx, expected, Utc::now())?; // Queue the transaction for publishing
+
+        // Make sure that we're going to broadcast the new transaction
+        self.new_tx_ctx.store(true, Ordering::SeqCst);
+
+        Ok(())
     }
 
-    /// Determines whether or not a particular proposal is valid.
+    /// Gossips the given signed transaction to the network again, since it hasn't been executed in time. If the
+    /// transaction was rebuilt on top of the DAG's new head, it takes the place of the transaction that it replaces,
+    /// which must spend the same nonce. Transactions that have since dropped out of the mempool are admitted again.
     ///
     /// # Arguments
     ///
-    /// * `proposal` - The proposal that should be validated
-    pub fn validate_proposal(&self, proposal: &Hash) -> bool {
-        // Get the proposal's metadata
-        if let Some(prop) = self.pending_proposals.get(proposal) {
-            // If the proposal is a transaction, validate it
-            return if prop.proposal_data.param_name.as_str() == "ledger::transactions" {
-                // The proposal must be an append op if it is proposing a TX
-                if let Operation::Append { value_to_append } = &prop.proposal_data.operation {
-                    // Try to deserialize the transaction
-                    if let Ok(tx) = bincode::deserialize::<Transaction>(&value_to_append) {
-                        // Validate the transaction
-                        let validator = GraphBoundValidator::new(&self.ledger);
-                        validator.transaction_is_valid(&tx).is_ok()
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
-            } else {
-                true
-            };
+    /// * `tx` - The transaction that should be gossiped again
+    /// * `replaces` - The hash of the transaction that the given one was rebuilt from, if it was rebuilt
+    pub fn resubmit_local_transaction(
+        &mut self,
+        tx: Transaction,
+        replaces: Option<Hash>,
+    ) -> Result<(), MempoolError> {
+        let expected = self.executed_nonce(&tx.transaction_data.sender);
+
+        match replaces {
+            Some(replaced) if replaced != tx.hash => {
+                self.mempool.replace(&replaced, tx, expected, Utc::now())?
+            }
+            _ if self.mempool.requeue(&tx.hash) => (),
+            _ => self.mempool.insert(tx, expected, Utc::now())?,
         }
 
-        false
+        // Make sure that we're going to broadcast the transaction again
+        self.new_tx_ctx.store(true, Ordering::SeqCst);
+
+        Ok(())
     }
+
+    /// Takes up to the given number of locally submitted transactions that are ready to be gossiped, paying the
+    /// highest gas prices first.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The greatest number of transactions that should be taken
+    pub fn take_localized_transactions(&mut self, limit: usize) -> Vec<Transaction> {
+        let nonces = self
+            .ledger
+            .obtain_executed_head()
+            .and_then(|head| head.state_entry)
+            .map(|entry| entry.data.nonces)
+            .unwrap_or_default();
+
+        self.mempool.take_ready(limit, &nonces, Utc::now()) // Return the queued transactions
+    }
+
+    /// Gets the number of local proposals, votes, and transactions that are still waiting to be published.
+    pub fn localized_backlog(&self) -> usize {
+        self.localized_proposals.len()
+            + self.localized_votes.len()
+            + self.mempool.held_back() as usize
+    }
+
+    /// Gets the nonce following the last executed transaction of the given account, or zero if it hasn't sent any.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The address of the account
+    pub fn executed_nonce(&self, sender: &Address) -> u64 {
+        self.ledger
+            .obtain_executed_head()
+            .and_then(|head| head.state_entry)
+            .and_then(|entry| entry.data.nonces.get(&sender.to_str()).map(|last| last + 1))
+            .unwrap_or(0)
+    }
+
+    /// Gets the nonce that the given account's next transaction should have, following any of its transactions still
+    /// waiting in the mempool.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The address of the account
+    pub fn next_nonce(&self, sender: &Address) -> u64 {
+        self.mempool.next_nonce(sender, self.executed_nonce(sender))
+    }
+
+    /// Inspects the DAG & mempool for each of the given account's transactions that haven't been executed, looking for
+    /// nonce gaps, stuck transactions, and transactions that can never be executed.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the account
+    /// * `stuck_after_secs` - The number of seconds that the transaction spending the account's next nonce may wait
+    pub fn diagnose_account(&self, address: &Address, stuck_after_secs: u64) -> AccountDiagnosis {
+        let mut waiting = self.mempool.waiting_from(address);
+
+        // Transactions without a receipt haven't been executed
+        for node in self.ledger.nodes.iter().filter(|node| {
+            !node.transaction.genesis && node.transaction.transaction_data.sender == *address
+        }) {
+            if let Ok(Some(_)) = self.ledger.get_receipt(node.hash) {
+                continue;
+            }
+
+            // Transactions published from the mempool may have made it into the DAG already
+            waiting.retain(|tx| tx.hash != node.hash);
+            waiting.push(WaitingTransaction {
+                hash: node.hash,
+                nonce: node.transaction.transaction_data.nonce,
+                location: TransactionLocation::Dag,
+                since: node.transaction.transaction_data.timestamp,
+                published: true,
+            });
+        }
+
+        diagnosis::diagnose(
+            *address,
+            self.executed_nonce(address),
+            self.next_nonce(address),
+            waiting,
+            chrono::Duration::seconds(stuck_after_secs as i64),
+            Utc::now(),
+        )
+    }
+
+    /// Checks whether or not the node runs as a light client, only following the ledger's headers.
+    pub fn is_light(&self) -> bool {
+        self.header_chain.is_some()
+    }
+
+    /// Gets a page of the headers that light clients follow: those of the ledger's nodes in ledger order, or those
+    /// synchronized so far, if the node is a light client itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The number of headers that should be skipped
+    /// * `limit` - The largest number of headers that should be listed
+    pub fn light_headers(&self, offset: usize, limit: Option<usize>) -> Vec<LightHeader> {
+        if let Some(chain) = &self.header_chain {
+            return chain.page(offset, limit);
+        }
+
+        (offset..self.ledger.nodes.len())
+            .take(light::clamp_page_size(limit))
+            .filter_map(|index| self.light_header_at(index))
+            .collect()
+    }
+
+    /// Gets the header of the executed head, which light clients check balances against.
+    pub fn executed_light_header(&self) -> Option<LightHeader> {
+        if let Some(chain) = &self.header_chain {
+            return chain.executed_head().cloned();
+        }
+
+        let head = self.ledger.obtain_executed_head()?;
+        let index = *self.ledger.hash_routes.get(&head.hash)?;
+
+        Some(LightHeader::from_node(&head, self.height_at(index)))
+    }
+
+    /// Proves the balance held by an account in the state resulting from the node with the given hash (or from the
+    /// executed head, if no hash is given), so that a light client can check it against the node's header.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the account
+    /// * `at` - The hash of the node whose resulting state should hold the balance
+    pub fn prove_balance(
+        &self,
+        address: &Address,
+        at: Option<Hash>,
+    ) -> Result<ProvenBalance, LightClientError> {
+        let unknown = || LightClientError::UnknownHeader {
+            hash: at.unwrap_or_default().to_str(),
+        };
+
+        let index = match at {
+            Some(hash) => self.ledger.hash_routes.get(&hash).copied(),
+            None => self
+                .ledger
+                .obtain_executed_head()
+                .and_then(|head| self.ledger.hash_routes.get(&head.hash).copied()),
+        }
+        .ok_or_else(unknown)?;
+
+        let node = match self.ledger.get_pure(index) {
+            Ok(Some(node)) => node,
+            _ => return Err(unknown()),
+        };
+        let proof = node
+            .state_entry
+            .as_ref()
+            .ok_or_else(|| LightClientError::MissingState {
+                hash: node.hash.to_str(),
+            })?
+            .prove_balance(&address.to_str())
+            .ok_or_else(|| LightClientError::UnknownAccount {
+                address: address.to_str(),
+            })?;
+
+        Ok(ProvenBalance {
+            header: LightHeader::from_node(&node, self.height_at(index)),
+            proof,
+        })
+    }
+
+    /// Checks a balance proven by another node against the node's own copy of the header that it was proven against.
+    /// Results in the proven balance.
+    ///
+    /// # Arguments
+    ///
+    /// * `proven` - The balance, and the header that it was proven against
+    pub fn verify_balance_proof(
+        &self,
+        proven: &ProvenBalance,
+    ) -> Result<BigUint, LightClientError> {
+        if let Some(chain) = &self.header_chain {
+            return chain.verify(proven);
+        }
+
+        let header = self
+            .ledger
+            .hash_routes
+            .get(&proven.header.hash)
+            .and_then(|index| self.light_header_at(*index))
+            .ok_or_else(|| LightClientError::UnknownHeader {
+                hash: proven.header.hash.to_str(),
+            })?;
+
+        light::verify_balance(&header, &proven.proof)
+    }
+
+    /// Derives the header of the node at the given index in the ledger, committing to the node's state if it's known.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the node
+    fn light_header_at(&self, index: usize) -> Option<LightHeader> {
+        match self.ledger.get_pure(index) {
+            Ok(Some(node)) => Some(LightHeader::from_node(&node, self.height_at(index))),
+            _ => None,
+        }
+    }
+
+    /// Gets the number of transactions in the ledger up to & including the node at the given index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the node
+    fn height_at(&self, index: usize) -> u64 {
+        self.ledger.base_height() + index as u64 + 1
+    }
+
+    /// Queues the given change to the node's voting accounts, to be applied by the network client.
+    ///
+    /// # Arguments
+    ///
+    /// * `change` - The account that should be added or removed
+    pub fn queue_voting_account_change(&mut self, change: VotingAccountChange) {
+        self.voting_account_changes.push(change);
+
+        // Make sure that the client picks up the change
+        self.new_tx_ctx.store(true, Ordering::SeqCst);
+    }
+
+    /// Takes each of the requested changes to the node's voting accounts that have not yet been applied.
+    pub fn take_voting_account_changes(&mut self) -> Vec<VotingAccountChange> {
+        self.voting_account_changes.drain(..).collect() // Return the queued changes
+    }
+
+    /// Takes each of the double spends detected since the last time they were taken, oldest first.
+    pub fn take_double_spend_alerts(&mut self) -> Vec<DoubleSpendAlert> {
+        self.pending_double_spends.drain(..).collect()
+    }
+
+    /// Takes each of the proposals executed since the last time they were taken, oldest first, along with the
+    /// transactions that they appended to the ledger.
+    pub fn take_executions(&mut self) -> Vec<(ExecutedProposal, Option<Transaction>)> {
+        self.pending_executions.drain(..).collect()
+    }
+
+    /// Publishes the given proposal to subscribers, and checks the transaction that it carries, if any, against the
+    /// other transactions spending the same nonce.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal that the node hadn't seen before
+    fn announce_proposal(&mut self, proposal: &Proposal) {
+        self.events.publish_proposal(proposal);
+
+        let alert = match events::proposed_transaction(proposal)
+            .and_then(|tx| self.conflicts.observe(&tx))
+        {
+            Some(alert) => alert,
+            None => return,
+        };
+
+        warn!(
+            "Detected a double spend of nonce {} by {}: {}",
+            alert.nonce,
+            alert.sender,
+            alert
+                .spends
+                .iter()
+                .map(|spend| format!(
+                    "{} ({} finks to {})",
+                    spend.hash, spend.value, spend.recipient
+                ))
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+
+        // Nobody may be collecting the alerts, so only hold onto the latest
+        if self.pending_double_spends.len() >= MAX_RECENT_DOUBLE_SPENDS {
+            self.pending_double_spends.remove(0);
+        }
+        self.pending_double_spends.push(alert.clone());
+
+        self.events.publish(Event::DoubleSpend { alert });
+    }
+
+    /// Has the remote signer vote on the given proposal with each of its accounts that the voting policy allows.
+    /// Results in each of the cast votes, along with the same vote sealed for gossip. Nothing is cast if the node
+    /// doesn't use a remote signer.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_id` - The hash of the proposal being voted on
+    /// * `in_favor` - Whether or not the votes are in favor of the proposal
+    /// * `topic` - The topic that the votes will be published to
+    pub fn cast_remote_votes(
+        &self,
+        proposal_id: Hash,
+        in_favor: bool,
+        topic: &str,
+    ) -> Vec<(Vote, Vec<u8>)> {
+        let signer = match &self.remote_signer {
+            Some(signer) => signer,
+            None => return Vec::new(),
+        };
+
+        let accounts = match signer.list_accounts() {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                warn!("Failed to list the remote signer's accounts: {}", e);
+
+                return Vec::new();
+            }
+        };
+
+        accounts
+            .into_iter()
+            .filter(|address| self.voting_policy.permits(address))
+            .filter_map(
+                |address| match signer.cast_vote(address, proposal_id, in_favor, topic) {
+                    Ok(vote) => Some(vote),
+                    Err(e) => {
+                        warn!("The remote signer failed to vote with {}: {}", address, e);
+
+                        None
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// Gets an atomic reference to the system's current new_tx state variable.
+    pub(crate) fn get_state_ref(&self) -> Arc<AtomicBool> {
+        // Clone the system's new_tx ctx reference variable
+        self.new_tx_ctx.clone()
+    }
+
+    /// Registers a provided vote as in favor or against a particular proposal.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_id` - The hash of the proposal that the vote is targeting
+    /// * `vote` - The vote primitive instance
+    pub fn register_vote_for_proposal(
+        &mut self,
+        proposal_id: Hash,
+        vote: &Vote,
+    ) -> Result<(), ExecutionError> {
+        // Ensure that the proposal exists in the runtime
+        if self.pending_proposals.contains_key(&proposal_id) {
+            // The vote must have a signature in order to be registered
+            let sig = if let Some(sig) = vote.clone().signature.take() {
+                sig
+            } else {
+                return Err(ExecutionError::Miscellaneous {
+                    error: "vote signature is nil".to_owned(),
+                });
+            };
+
+            // Try to get a public key from the vote's signature. If there is no public key
+            // associated with the signature, it must be invalid.
+            if let Ok(public_key) = sig.public_key() {
+                // The signature must be valid
+                if !sig.verify(&*vote.hash()) {
+                    return Err(ExecutionError::Miscellaneous {
+                        error: "vote signature is invalid".to_owned(),
+                    });
+                }
+
+                let voter = Address::from_public_key(&public_key);
+
+                // Ensure the voter is unique
+                if self
+                    .tallies
+                    .get(&proposal_id)
+                    .map_or(false, |tally| tally.voters.contains(&voter))
+                {
+                    return Ok(());
+                }
+
+                // Calculate the weight of the vote
+                let vote_value = if let Some(bigint) = self.determine_vote_weight(vote).to_bigint()
+                {
+                    bigint
+                } else {
+                    BigInt::zero()
+                };
+
+                // Record the vote
+                let tally = self.tallies.entry(proposal_id).or_default();
+                tally.voters.insert(voter);
+                tally.weight += if vote.in_favor {
+                    vote_value
+                } else {
+                    -vote_value
+                };
+
+                // Persist the updated tally, so that it survives a restart
+                if let Some(tree) = &self.tally_store {
+                    let encoded =
+                        bincode::serialize(&*tally).map_err(|e| ExecutionError::Miscellaneous {
+                            error: e.to_string(),
+                        })?;
+
+                    tree.insert(proposal_id, encoded)?;
+                    tree.flush()?;
+                }
+
+                // Count the vote towards the voter's share of the epoch's reward
+                self.record_participation(vote)?;
+
+                self.events.publish(Event::Vote { vote: vote.clone() });
+            } else {
+                return Err(ExecutionError::Miscellaneous {
+                    error: "vote signature public key is nil".to_owned(),
+                });
+            }
+
+            Ok(())
+        } else {
+            Err(ExecutionError::ProposalDoesNotExist {
+                proposal_id: proposal_id.to_str(),
+            })
+        }
+    }
+
+    /// Execute a proposal in the pending proposals set with the given hash, and remember what came of it.
+    pub fn execute_proposal(&mut self, proposal_id: Hash) -> Result<(), ExecutionError> {
+        // Try to pull the proposal out of the proposal queue. If this fails, that means that the proposal doesn't actually exist
+        let target_proposal = if let Some(prop) = self.resolve_proposal(&proposal_id) {
+            prop
+        } else {
+            return Err(ExecutionError::ProposalDoesNotExist {
+                proposal_id: proposal_id.to_str(),
+            }); // Return error
+        };
+
+        let result = self.apply_proposal(target_proposal.clone());
+        self.record_execution(&target_proposal, &result);
+
+        result
+    }
+
+    /// Applies the given proposal, which has already been pulled out of the pending proposals set.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_proposal` - The proposal that should be applied
+    fn apply_proposal(&mut self, target_proposal: Proposal) -> Result<(), ExecutionError> {
+        let proposal_id = target_proposal.proposal_id;
+
+        // Critical parameters can't be changed on the strength of the stake alone
+        self.config
+            .council
+            .authorize(&target_proposal)
+            .map_err(|e| ExecutionError::Miscellaneous {
+                error: e.to_string(),
+            })?;
+
+        // Structured proposals are handled on their own
+        match target_proposal.proposal_data.body() {
+            Ok(ProposalBody::Signal(text)) => {
+                info!("Signaling proposal {} passed: \"{}\"", proposal_id, text);
+
+                return Ok(());
+            }
+            Ok(ProposalBody::Bundle(_)) => {
+                // Every change is applied at once, or not at all
+                let config = config_after(&self.config, &target_proposal.proposal_data)?;
+
+                return self.adopt_config(config, target_proposal);
+            }
+            Ok(ProposalBody::Scheduled(_)) => {
+                // Hold on to the change until the ledger is tall enough
+                self.schedule_change(target_proposal)?;
+
+                return self.activate_scheduled_changes();
+            }
+            Ok(ProposalBody::Edit(_)) => (),
+            Err(e) => {
+                return Err(ExecutionError::Miscellaneous {
+                    error: e.to_string(),
+                })
+            }
+        }
+
+        // Handle different target system parameters
+        match target_proposal.proposal_data.param_name.as_str() {
+            // Proposal is targeting a config field
+            param if CONFIG_PARAMS.contains(&param) => {
+                let config = config_after(&self.config, &target_proposal.proposal_data)?;
+
+                self.adopt_config(config, target_proposal)
+            }
+            // Proposal is targeting the ledger
+            "ledger::transactions" => {
+                // Handle different operations
+                match target_proposal.proposal_data.operation {
+                    // Targeted amend, despite the fact that ledger operations cannot be reverted
+                    Operation::Amend { .. } => Err(ExecutionError::InvalidOperation {
+                        operation: "amend".to_owned(),
+                        proposal_param: "ledger::transactions".to_owned(),
+                    }),
+                    // Targeted remove, despite the fact that ledger operations cannot be reverted
+                    Operation::Remove => Err(ExecutionError::InvalidOperation {
+                        operation: "remove".to_owned(),
+                        proposal_param: "ledger::transactions".to_owned(),
+                    }),
+                    // Is appending a transaction to the network ledger
+                    Operation::Append { value_to_append } => {
+                        self.append_transaction(Transaction::from_bytes(&value_to_append))
+                    }
+                }
+            }
+            // Proposal is minting an epoch's reward
+            ISSUANCE_PARAM => {
+                let value_to_append = match target_proposal.proposal_data.operation {
+                    Operation::Append { value_to_append } => value_to_append,
+                    op => {
+                        return Err(ExecutionError::InvalidOperation {
+                            operation: op.to_string(),
+                            proposal_param: ISSUANCE_PARAM.to_owned(),
+                        })
+                    }
+                };
+                // Deserialize the mint, refusing anything that isn't a transaction
+                let tx: Transaction = bincode::deserialize(&value_to_append).map_err(|e| {
+                    ExecutionError::Miscellaneous {
+                        error: format!("failed to decode mint: {}", e),
+                    }
+                })?;
+
+                // Another mint for the same epoch may have passed in the meantime
+                self.check_mint(&tx)
+                    .map_err(|e| ExecutionError::Miscellaneous {
+                        error: e.to_string(),
+                    })?;
+
+                self.append_transaction(tx)
+            }
+            _ => Err(ExecutionError::InvalidTargetProposalParam {
+                proposal_param: target_proposal.proposal_data.param_name,
+            }),
+        }
+    }
+
+    /// Remembers the execution of the given proposal, persisting it alongside the ledger.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal that was executed
+    /// * `result` - The result of executing the proposal
+    fn record_execution(&mut self, proposal: &Proposal, result: &Result<(), ExecutionError>) {
+        let outcome = match result {
+            Ok(()) => ExecutionOutcome::Applied,
+            Err(e) => ExecutionOutcome::Failed {
+                error: e.to_string(),
+            },
+        };
+        let state_hash = self.resulting_state_hash(proposal);
+
+        let (entry, pruned) = self.history.record(proposal, outcome, state_hash);
+
+        // Nobody may be collecting the executions, so only hold onto as many as the history does
+        if self.pending_executions.len() >= RETAINED_EXECUTIONS {
+            self.pending_executions.remove(0);
+        }
+        self.pending_executions
+            .push((entry.clone(), events::proposed_transaction(proposal)));
+
+        if let Some(tree) = &self.history_store {
+            let persisted = bincode::serialize(&entry)
+                .map_err(|e| e.to_string())
+                .and_then(|encoded| {
+                    tree.insert(entry.seq.to_be_bytes(), encoded)
+                        .map_err(|e| e.to_string())
+                })
+                .and_then(|_| {
+                    // Forget the executions that have aged out
+                    for seq in pruned {
+                        tree.remove(seq.to_be_bytes()).map_err(|e| e.to_string())?;
+                    }
+
+                    Ok(())
+                });
+
+            if let Err(e) = persisted {
+                warn!(
+                    "Failed to persist the execution of proposal {}: {}",
+                    proposal.proposal_id, e
+                );
+            }
+        }
+    }
+
+    /// Gets the hash of the state that the given proposal left behind after being executed: the config's, for config
+    /// changes, or the appended transaction's, for changes to the ledger.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal that was executed
+    fn resulting_state_hash(&self, proposal: &Proposal) -> Option<Hash> {
+        let config_hash = || {
+            bincode::serialize(&self.config)
+                .ok()
+                .map(|encoded| blake3::hash_slice(&encoded))
+        };
+
+        match proposal.proposal_data.body() {
+            Ok(ProposalBody::Bundle(_)) => config_hash(),
+            Ok(ProposalBody::Edit(_)) => match (
+                proposal.proposal_data.param_name.as_str(),
+                &proposal.proposal_data.operation,
+            ) {
+                (param, _) if CONFIG_PARAMS.contains(&param) => config_hash(),
+                ("ledger::transactions", Operation::Append { value_to_append })
+                | (ISSUANCE_PARAM, Operation::Append { value_to_append }) => {
+                    let tx: Transaction = bincode::deserialize(value_to_append).ok()?;
+
+                    Some(self.ledger.get_receipt(tx.hash).ok()??.state_hash)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Appends the given transaction to the ledger, executes it on top of its parents, and records what happened to
+    /// it.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that should be appended
+    fn append_transaction(&mut self, tx: Transaction) -> Result<(), ExecutionError> {
+        // Get the index of the submitted transaction entry
+        let entry_index = self.ledger.push(tx.clone(), None);
+
+        // Note when the transaction was executed, regardless of what the sender claims
+        let executed_at = self
+            .execution_clock
+            .stamp(self.last_executed_at, Utc::now());
+        if executed_at.is_some() {
+            self.last_executed_at = executed_at;
+        }
+
+        // Execute the parent transactions, get the overall hash
+        let parent_tx_state = self.ledger.execute_parent_nodes(entry_index)?;
+
+        // Remember that the merged parent state came from the transaction's parents, so that it can be traced back
+        // later
+        self.ledger
+            .index_state(parent_tx_state.hash, &tx.transaction_data.parents);
+
+        // Get the hash of the parent state that the transaction THINKS is right
+        let asserted_parent_state_hash =
+            if let Some(parent_state_hash) = tx.transaction_data.parent_state_hash {
+                parent_state_hash
+            } else {
+                // Remove the head tx, since it's invalid
+                self.ledger.rollback_head();
+                self.ledger.record_receipt(
+                    tx.hash,
+                    &Receipt {
+                        state_hash: parent_tx_state.hash,
+                        logs: Vec::new(),
+                        status: Some(ExecutionStatus::Reverted),
+                        items: Vec::new(),
+                        executed_at,
+                    },
+                )?;
+
+                // Return the error
+                return Err(ExecutionError::Miscellaneous {
+                    error: "Invalid transaction: must have parent state hash.".to_owned(),
+                });
+            };
+
+        // UWU WHAT'S THIS I SEE?
+        if parent_tx_state.hash != asserted_parent_state_hash {
+            // Remove the head tx, since it's invalid
+            self.ledger.rollback_head();
+            self.ledger.record_receipt(
+                tx.hash,
+                &Receipt {
+                    state_hash: parent_tx_state.hash,
+                    logs: Vec::new(),
+                    status: Some(ExecutionStatus::Reverted),
+                    items: Vec::new(),
+                    executed_at,
+                },
+            )?;
+
+            // Return the error
+            return Err(ExecutionError::Miscellaneous{error: format!("Invalid transaction: merged parent states must have a hash matching that which is asserted by the transaction (found {}, tx asserted {}).", parent_tx_state.hash, asserted_parent_state_hash)});
+        };
+
+        // Record what happened to the transaction, so that its sender can find out
+        let status = tx.execution_status(&parent_tx_state);
+        let balances_before = parent_tx_state.data.balances.clone();
+        let resulting_state = tx.execute(Some(parent_tx_state));
+        let resulting_state_hash = resulting_state.hash;
+        self.ledger.index_state(resulting_state_hash, &[tx.hash]);
+        self.ledger.record_receipt(
+            tx.hash,
+            &Receipt {
+                state_hash: resulting_state_hash,
+                logs: Vec::new(),
+                status: Some(status),
+                items: receipt::itemize(&balances_before, &resulting_state.data.balances),
+                executed_at,
+            },
+        )?;
+
+        // Let any wallets listening know how the transaction moved their funds
+        self.events.publish_balance_changes(
+            tx.hash,
+            &balances_before,
+            &resulting_state.data.balances,
+        );
+
+        // The transaction no longer needs publishing, and any of its sender's that follow it may now be ready
+        if self.mempool.confirm(&tx) {
+            self.new_tx_ctx.store(true, Ordering::SeqCst);
+        }
+
+        // Keep track of the network's activity
+        if status == ExecutionStatus::Success {
+            self.record_stats(&tx)?;
+        }
+
+        // A mint pays out whatever its nonce, so make sure that its epoch is never paid out again
+        self.ledger.apply_mint(&tx)?;
+
+        // The ledger has grown, so some scheduled changes, or an epoch's reward, may now be due
+        self.activate_scheduled_changes()?;
+        self.propose_due_mints()?;
+
+        // Page out any states that the ledger's storage mode doesn't keep in memory; they can be loaded back in later
+        if let Err(e) = self.ledger.prune() {
+            warn!("Failed to page out the ledger's older states: {}", e);
+        }
+
+        //if let Ok(prev_state_entry) = self
+        //    .ledger
+        //    .execute_parent_nodes(self.ledger.nodes.len() - 1)
+        //{
+        //    let index = self.ledger.nodes.len() - 1; // Get index of pushed tx
+
+        // Get previous state entry
+        //self.ledger.nodes[index].state_entry =
+        //Some(tx.execute(Some(prev_state_entry))); // Set node state entry
+        //}
+
+        Ok(())
+    }
+
+    /// Resolves the given full hash, or short id, to the hash of the one pending proposal that it begins. Full hashes
+    /// are returned as-is, even if no such proposal is pending.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The full hash, or short id, of the proposal
+    pub fn resolve_proposal_id(&self, id: &str) -> Result<Hash, ShortIdError> {
+        short_id::resolve(id, self.pending_proposals.keys())
+    }
+
+    /// Works out what would happen if the pending proposal with the given hash were executed right now, without
+    /// committing anything. Config changes are applied to a copy of the config, and transactions are executed on top
+    /// of their parents' states without being added to the ledger. Results in an error only if the proposal isn't
+    /// pending; a proposal whose execution would fail is reported as such in the simulation.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_id` - The hash of the proposal that should be simulated
+    pub fn simulate_proposal(
+        &self,
+        proposal_id: &Hash,
+    ) -> Result<ProposalSimulation, ExecutionError> {
+        let proposal = if let Some(prop) = self.pending_proposals.get(proposal_id) {
+            prop
+        } else {
+            return Err(ExecutionError::ProposalDoesNotExist {
+                proposal_id: proposal_id.to_str(),
+            });
+        };
+
+        let mut simulation = ProposalSimulation::new(*proposal_id);
+        if let Err(e) = self.config.council.authorize(proposal) {
+            simulation.error = Some(e.to_string());
+        } else if let Err(e) = self.simulate_proposal_data(&proposal.proposal_data, &mut simulation)
+        {
+            simulation.error = Some(e.to_string());
+        }
+
+        Ok(simulation)
+    }
+
+    /// Applies the given proposal data to copies of the config and ledger, recording what changed in the given
+    /// simulation.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_data` - The change that the proposal would make
+    /// * `simulation` - The simulation that the outcome should be recorded in
+    fn simulate_proposal_data(
+        &self,
+        proposal_data: &ProposalData,
+        simulation: &mut ProposalSimulation,
+    ) -> Result<(), ExecutionError> {
+        // Bundles and scheduled changes are made up of config edits, which are all applied to the same copy
+        let changes = match proposal_data.body() {
+            Ok(ProposalBody::Signal(_)) => return Ok(()),
+            Ok(ProposalBody::Bundle(changes)) => changes,
+            Ok(ProposalBody::Scheduled(scheduled)) => {
+                simulation.activation_height = Some(scheduled.activation_height);
+
+                match scheduled.change.body() {
+                    Ok(ProposalBody::Bundle(changes)) => changes,
+                    _ => vec![scheduled.change],
+                }
+            }
+            Ok(ProposalBody::Edit(_))
+                if CONFIG_PARAMS.contains(&proposal_data.param_name.as_str()) =>
+            {
+                vec![proposal_data.clone()]
+            }
+            Ok(ProposalBody::Edit(_)) => return self.simulate_append(proposal_data, simulation),
+            Err(e) => {
+                return Err(ExecutionError::Miscellaneous {
+                    error: e.to_string(),
+                })
+            }
+        };
+
+        let mut config = self.config.clone();
+        for change in changes {
+            apply_config_change(&mut config, change)?;
+        }
+
+        simulation.config_changes = diff_configs(&self.config, &config);
+
+        Ok(())
+    }
+
+    /// Executes the transaction that the given proposal data would append to the ledger, recording its outcome in the
+    /// given simulation.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_data` - The change that the proposal would make
+    /// * `simulation` - The simulation that the outcome should be recorded in
+    fn simulate_append(
+        &self,
+        proposal_data: &ProposalData,
+        simulation: &mut ProposalSimulation,
+    ) -> Result<(), ExecutionError> {
+        let value_to_append = match (&proposal_data.param_name[..], &proposal_data.operation) {
+            ("ledger::transactions", Operation::Append { value_to_append })
+            | (ISSUANCE_PARAM, Operation::Append { value_to_append }) => value_to_append,
+            ("ledger::transactions", op) | (ISSUANCE_PARAM, op) => {
+                return Err(ExecutionError::InvalidOperation {
+                    operation: op.to_string(),
+                    proposal_param: proposal_data.param_name.clone(),
+                })
+            }
+            (param, _) => {
+                return Err(ExecutionError::InvalidTargetProposalParam {
+                    proposal_param: param.to_owned(),
+                })
+            }
+        };
+        let tx: Transaction =
+            bincode::deserialize(value_to_append).map_err(|e| ExecutionError::Miscellaneous {
+                error: e.to_string(),
+            })?;
+
+        if proposal_data.param_name == ISSUANCE_PARAM {
+            self.check_mint(&tx)
+                .map_err(|e| ExecutionError::Miscellaneous {
+                    error: e.to_string(),
+                })?;
+        }
+
+        self.simulate_transaction(&tx, simulation)
+    }
+
+    /// Executes the given transaction on top of the merged states of its parents, without adding it to the ledger,
+    /// recording its outcome in the given simulation.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that should be executed
+    /// * `simulation` - The simulation that the outcome should be recorded in
+    fn simulate_transaction(
+        &self,
+        tx: &Transaction,
+        simulation: &mut ProposalSimulation,
+    ) -> Result<(), ExecutionError> {
+        // Resolving the parents never touches the ledger itself
+        let (parent_state, _) = self
+            .ledger
+            .resolve_parent_nodes(tx.transaction_data.parents.clone())?;
+
+        match tx.transaction_data.parent_state_hash {
+            Some(asserted) if asserted == parent_state.hash => (),
+            Some(asserted) => {
+                return Err(ExecutionError::Miscellaneous {
+                    error: format!(
+                        "Invalid transaction: merged parent states have hash {}, but the tx asserted {}.",
+                        parent_state.hash, asserted
+                    ),
+                })
+            }
+            None => {
+                return Err(ExecutionError::Miscellaneous {
+                    error: "Invalid transaction: must have parent state hash.".to_owned(),
+                })
+            }
+        }
+
+        simulation.status = Some(tx.execution_status(&parent_state));
+
+        let resulting_state = tx.execute(Some(parent_state.clone()));
+        simulation.state_hash = Some(resulting_state.hash);
+        simulation.balance_changes = diff_balances(&parent_state, &resulting_state);
+
+        Ok(())
+    }
+
+    /// Determines the number of coins associated with a particular voter.
+    ///
+    /// # Arguments
+    ///
+    /// * `vote` - The vote that the weight should be determined of
+    pub fn determine_vote_weight(&self, vote: &Vote) -> BigUint {
+        // Determine the address of the account submitting the vote
+        let voter = if let Some(addr) = vote.voter_address() {
+            addr
+        } else {
+            // Since the vote doesn't have a voter, the vote can't have any weight
+            return BigUint::zero();
+        };
+
+        // Get the balance of the voter
+        self.ledger.get_balance_of_account(&voter)
+    }
+
+    /// Determins the number of coins from voters in favor of the proposal.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal that the weight should be determined of
+    pub fn get_coins_in_support_of(&self, proposal: &Hash) -> BigInt {
+        self.tallies
+            .get(proposal)
+            .map_or_else(BigInt::zero, |tally| tally.weight.clone())
+    }
+
+    /// Decides where the proposal with the given hash stands, according to the network's consensus thresholds. A
+    /// proposal that has gathered enough votes too early is remembered, so that it can be decided once its minimum
+    /// voting period has passed.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The hash of the proposal that should be tallied
+    pub fn tally_proposal(&mut self, proposal: &Hash) -> Verdict {
+        let open_for = self
+            .opened_at
+            .get(proposal)
+            .map_or_else(chrono::Duration::max_value, |opened_at| {
+                Utc::now().signed_duration_since(*opened_at)
+            });
+
+        let verdict = self.config.consensus.tally(
+            &self.get_coins_in_support_of(proposal),
+            &self.ledger.overall_issuance(),
+            open_for,
+        );
+
+        // A single host running many peers mustn't be able to push a proposal through on its own
+        let verdict = if verdict == Verdict::Accepted
+            && !self.diversity_policy.admits(
+                self.vote_witnesses
+                    .get(proposal)
+                    .unwrap_or(&Witnesses::default()),
+            ) {
+            Verdict::Undiverse
+        } else {
+            verdict
+        };
+
+        if verdict == Verdict::Premature {
+            self.maturing.insert(*proposal);
+        } else {
+            self.maturing.remove(proposal);
+        }
+
+        verdict
+    }
+
+    /// Counts the peer that relayed a vote on the given pending proposal towards the proposal's witnesses, so that the
+    /// proposal can be held back until votes have arrived from enough distinct peers.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The hash of the proposal voted on
+    /// * `witness` - The peer that relayed the vote
+    pub fn witness_vote(&mut self, proposal: &Hash, witness: Witness) {
+        if self.pending_proposals.contains_key(proposal) {
+            self.vote_witnesses
+                .entry(*proposal)
+                .or_default()
+                .add(witness);
+        }
+    }
+
+    /// Gets each of the proposals that gathered enough votes before their minimum voting period had passed, and whose
+    /// period has passed since. Each of them should be tallied again.
+    pub fn matured_proposals(&self) -> Vec<Hash> {
+        let period = chrono::Duration::seconds(self.config.consensus.min_voting_period_secs as i64);
+
+        self.maturing
+            .iter()
+            .filter(|proposal| {
+                self.opened_at
+                    .get(proposal)
+                    .map_or(true, |opened_at| Utc::now() >= *opened_at + period)
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Removes the proposal with the given hash from the set of pending proposals, and discards the votes cast on it,
+    /// since they will never be needed again.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The hash of the proposal that has been resolved
+    pub fn resolve_proposal(&mut self, proposal: &Hash) -> Option<Proposal> {
+        // Forget the votes cast on the proposal
+        self.tallies.remove(proposal);
+        self.awaiting_confirmation.remove(proposal);
+        self.opened_at.remove(proposal);
+        self.maturing.remove(proposal);
+        self.vote_witnesses.remove(proposal);
+
+        if let Some(tree) = &self.tally_store {
+            if let Err(e) = tree.remove(proposal) {
+                warn!(
+                    "Failed to discard the vote tally for proposal {}: {}",
+                    proposal, e
+                );
+            }
+        }
+
+        if let Some(tree) = &self.proposal_store {
+            if let Err(e) = tree.remove(proposal) {
+                warn!("Failed to discard the proposal {}: {}", proposal, e);
+            }
+        }
+
+        self.pending_proposals.remove(proposal) // Remove the proposal itself
+    }
+
+    /// Persists the pending proposal with the given hash, so that it survives a restart along with its tally.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The hash of the pending proposal that should be persisted
+    fn persist_proposal(&self, proposal: &Hash) {
+        if let (Some(tree), Some(prop)) =
+            (&self.proposal_store, self.pending_proposals.get(proposal))
+        {
+            if let Err(e) = bincode::serialize(prop)
+                .map_err(|e| e.to_string())
+                .and_then(|encoded| tree.insert(proposal, encoded).map_err(|e| e.to_string()))
+            {
+                warn!("Failed to persist the proposal {}: {}", proposal, e);
+            }
+        }
+    }
+
+    /// Determines whether or not a particular proposal is valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal that should be validated
+    pub fn validate_proposal(&self, proposal: &Hash) -> bool {
+        // Get the proposal's metadata
+        if let Some(prop) = self.pending_proposals.get(proposal) {
+            // If the proposal is a transaction, validate it
+            return if prop.proposal_data.param_name.as_str() == "ledger::transactions" {
+                // The proposal must be an append op if it is proposing a TX
+                if let Operation::Append { value_to_append } = &prop.proposal_data.operation {
+                    // Try to deserialize the transaction
+                    if let Ok(tx) = bincode::deserialize::<Transaction>(&value_to_append) {
+                        // Validate the transaction
+                        let validator = GraphBoundValidator::new(&self.ledger);
+                        validator.transaction_is_valid(&tx).is_ok()
+                    } else {
+                        false
+                    }
+                } else {
+                    false
+                }
+            } else if prop.proposal_data.param_name == ISSUANCE_PARAM {
+                // The proposal must be minting a reward that is actually due
+                if let Operation::Append { value_to_append } = &prop.proposal_data.operation {
+                    bincode::deserialize::<Transaction>(&value_to_append)
+                        .map_or(false, |tx| self.check_mint(&tx).is_ok())
+                } else {
+                    false
+                }
+            } else {
+                true
+            };
+        }
+
+        false
+    }
+
+    /// Makes sure that the given transaction carries a mint that the current issuance schedule allows, that pays out
+    /// exactly what the votes that it carries earn, and that hasn't already been executed.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction carrying the mint
+    pub fn check_mint(&self, tx: &Transaction) -> Result<Mint, IssuanceError> {
+        // Find out whether or not the mint's epoch has already been paid out
+        let already_minted = match Mint::from_payload(&tx.transaction_data.payload)? {
+            Some(mint) => self
+                .ledger
+                .get_mint(mint.epoch)
+                .map_err(|_| IssuanceError::Malformed)?
+                .is_some(),
+            None => false,
+        };
+
+        issuance::check(
+            &tx.transaction_data,
+            &self.config.issuance,
+            self.ledger.height(),
+            already_minted,
+            &self.counted_votes(),
+        )
+    }
+
+    /// Gets the voter & proposal of each of the votes counted by the mints executed so far, so that no vote is paid
+    /// for twice. Mints whose transactions the ledger doesn't hold (e.g. those preceding a snapshot) can't be
+    /// accounted for.
+    fn counted_votes(&self) -> HashSet<(Address, Hash)> {
+        self.ledger
+            .get_mints()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|record| self.ledger.get_with_hash(record.tx_hash).ok())
+            .filter_map(|node| {
+                Mint::from_payload(&node.transaction.transaction_data.payload).ok()?
+            })
+            .flat_map(|mint| mint.votes)
+            .filter_map(|vote| issuance::vote_key(&vote))
+            .collect()
+    }
+
+    /// Gets an account of the network's supply: what was allocated in the genesis, what has been minted and burned
+    /// since, and what will be minted next.
+    pub fn supply(&self) -> SupplyReport {
+        let genesis = self.ledger.genesis_issuance();
+        let mints = self.ledger.get_mints().unwrap_or_default();
+        let minted = mints
+            .iter()
+            .fold(BigUint::zero(), |minted, record| minted + &record.amount);
+        let burned = self.ledger.burned_supply();
+
+        let schedule = self.config.issuance.clone();
+        let current_epoch = schedule.epoch_at(self.ledger.height());
+
+        SupplyReport {
+            total: (&genesis + &minted)
+                .checked_sub(&burned)
+                .unwrap_or_else(BigUint::zero),
+            genesis,
+            minted,
+            burned,
+            next_reward: if schedule.is_enabled() {
+                schedule.reward_for_epoch(current_epoch)
+            } else {
+                BigUint::zero()
+            },
+            schedule,
+            current_epoch,
+            mints,
+        }
+    }
+
+    /// Makes sure that the state at the executed head of the ledger holds no more finks than can have been issued by
+    /// now, guarding against fabricated state synchronized from the network. Results in the number of finks held
+    /// between each of the accounts.
+    pub fn audit_supply(&self) -> Result<BigUint, InvariantViolation> {
+        let balances = match self
+            .ledger
+            .obtain_executed_head()
+            .and_then(|head| head.state_entry)
+        {
+            Some(state) => state.data.balances,
+            None => return Ok(BigUint::zero()),
+        };
+
+        let ceiling = invariants::supply_ceiling(
+            &self.ledger.genesis_issuance(),
+            &self.ledger.minted_supply(),
+            &self.config.issuance,
+            self.ledger.height(),
+        );
+
+        invariants::check_supply(&balances, &ceiling)
+    }
+
+    /// Discards each of the transactions added to the ledger after it held the given number of transactions, newest
+    /// first. Results in the number of transactions discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - The number of transactions that the ledger should be left holding
+    pub fn discard_since(&mut self, height: usize) -> usize {
+        let mut discarded = 0;
+
+        while self.ledger.nodes.len() > height.max(1) {
+            self.ledger.rollback_head();
+            discarded += 1;
+        }
+
+        discarded
+    }
+
+    /// Gets an account of the rewards earned by the given voting account: those that have been minted but not yet
+    /// claimed, and the votes counted towards the current epoch's reward so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The address of the voting account
+    pub fn rewards_report(&self, account: Address) -> RewardsReport {
+        let unclaimed = self
+            .ledger
+            .obtain_executed_head()
+            .and_then(|head| head.state_entry)
+            .map(|state| rewards::unclaimed(&state.data.balances, account))
+            .unwrap_or_default();
+
+        let current_epoch = self.config.issuance.epoch_at(self.ledger.height());
+
+        RewardsReport {
+            account,
+            escrow: rewards::escrow_address(account),
+            unclaimed,
+            votes_this_epoch: self
+                .participation
+                .get(&current_epoch)
+                .and_then(|votes| issuance::participation(votes).get(&account).copied())
+                .unwrap_or(0),
+        }
+    }
+
+    /// Appends each of the transactions in the given chain file to the ledger, re-executing each one and checking the
+    /// parent state that it cites, just as though it had been synchronized from the network. Only a fresh node, whose
+    /// ledger is still empty, may import a chain. Returns the number of transactions imported.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain` - The chain file that should be imported
+    pub fn import_chain<R: Read>(&mut self, chain: ChainReader<R>) -> Result<u64, ChainFileError> {
+        // Make sure that we're really starting at the beginning
+        if !self.ledger.nodes.is_empty() {
+            return Err(ChainFileError::NotFresh);
+        }
+
+        let mut imported = 0;
+
+        for tx in chain {
+            let tx = tx?;
+            let hash = tx.hash;
+
+            if self.ledger.nodes.is_empty() {
+                // The genesis doesn't cite a parent state, so there's nothing to check it against
+                let state = tx.execute(None);
+                self.ledger.push(tx, Some(state));
+            } else {
+                self.append_transaction(tx)
+                    .map_err(|e| ChainFileError::Rejected {
+                        hash: hash.to_str(),
+                        error: e.to_string(),
+                    })?;
+            }
+
+            imported += 1;
+        }
+
+        self.ledger.write_to_disk()?; // Persist the imported history
+
+        Ok(imported)
+    }
+
+    /// Persists the system's config to the disk.
+    fn persist_config(&self) -> Result<(), ExecutionError> {
+        // Write config to disk
+        self.config
+            .write_to_disk()
+            .map_err(|e| ExecutionError::Miscellaneous {
+                error: e.to_string(),
+            })
+    }
+
+    /// Replaces the settings that every node must agree on with those carried in the given config record, if the
+    /// record is the next version of the node's config, its proposal is authorized and produces exactly that
+    /// version, and its signers hold enough of the issuance between them. The node's own settings (e.g. its storage
+    /// mode) are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The config record published by a peer
+    pub fn apply_config_record(&mut self, record: &ConfigRecord) -> Result<(), ExecutionError> {
+        let backing = record
+            .signers()
+            .iter()
+            .fold(bigint::BigUint::zero(), |backing, signer| {
+                backing + self.ledger.get_balance_of_account(signer)
+            });
+        let config = record.apply_to(
+            &self.config,
+            self.executed_height(),
+            &backing,
+            &self.ledger.overall_issuance(),
+        )?;
+
+        info!(
+            "Updating the config from version {} to version {}, vouched for by {} account(s)",
+            self.config.sequence,
+            record.sequence(),
+            record.signers().len()
+        );
+
+        // A change synchronized ahead of its activation here mustn't be applied a second time
+        self.unschedule_change(&record.proposal.proposal_id)?;
+
+        self.adopt_config(config, record.proposal.clone())
+    }
+
+    /// Switches over to the given version of the config, remembering the proposal that produced it so that it can
+    /// be published alongside it.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The new version of the config
+    /// * `proposal` - The executed proposal that produced it
+    fn adopt_config(
+        &mut self,
+        config: config::Config,
+        proposal: Proposal,
+    ) -> Result<(), ExecutionError> {
+        if let Some(tree) = &self.config_proposal_store {
+            let encoded =
+                bincode::serialize(&proposal).map_err(|e| ExecutionError::Miscellaneous {
+                    error: e.to_string(),
+                })?;
+
+            tree.insert(CONFIG_PROPOSAL_TREE, encoded)?;
+            tree.flush()?;
+        }
+
+        self.config = config;
+        self.config_proposal = Some(proposal);
+
+        self.persist_config()
+    }
+
+    /// Records a proposal scheduling a config change that should be applied once the ledger reaches a particular
+    /// height.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal carrying the change, and the height at which it should be applied
+    fn schedule_change(&mut self, proposal: Proposal) -> Result<(), ExecutionError> {
+        // Persist the change, so that it isn't forgotten if the node restarts before it activates
+        if let Some(tree) = &self.scheduled_store {
+            let encoded =
+                bincode::serialize(&proposal).map_err(|e| ExecutionError::Miscellaneous {
+                    error: e.to_string(),
+                })?;
+
+            tree.insert(proposal.proposal_id, encoded)?;
+            tree.flush()?;
+        }
+
+        self.scheduled_changes.push(proposal);
+
+        Ok(())
+    }
+
+    /// Measures the ledger's database and the room left on its disk, if they're due to be measured. Anything that can
+    /// be rebuilt later is pruned once room runs low, and writes that consensus can do without are paused once it
+    /// runs out, so that writes needed to execute proposals don't fail for want of room. Disks that stop taking
+    /// writes altogether put the node in read-only mode until they take writes again.
+    pub fn check_storage(&mut self) {
+        if !self.storage.due(Utc::now()) {
+            return;
+        }
+
+        let db_path = match self.ledger.db_path() {
+            Some(path) => path.to_owned(),
+            None => return,
+        };
+
+        let previous = self.storage.measure(&db_path);
+        match storage::probe_writable(&db_path) {
+            Ok(_) => {
+                if self.storage.record_write_success() {
+                    info!("The disk is taking writes again; leaving read-only mode");
+                }
+            }
+            Err(e) => self.record_write_failure(&e.to_string()),
+        }
+
+        let status = self.storage.status().clone();
+        self.ledger
+            .set_pressured(status.pressure == StoragePressure::Critical || status.read_only);
+
+        if status.pressure == previous {
+            return;
+        }
+
+        let free = status
+            .available_bytes
+            .map_or("unknown".to_owned(), |free| free.to_string());
+
+        match status.pressure {
+            StoragePressure::Normal => info!(
+                "Storage has recovered (database: {} bytes, free: {} bytes); resuming all writes",
+                status.db_bytes, free
+            ),
+            StoragePressure::Low => warn!(
+                "Storage is running low (database: {} bytes, free: {} bytes); pruning what can be rebuilt",
+                status.db_bytes, free
+            ),
+            StoragePressure::Critical => warn!(
+                "Storage has run out (database: {} bytes, free: {} bytes); pausing writes that consensus can do \
+                 without",
+                status.db_bytes, free
+            ),
+        }
+
+        // Make what room we can whenever things get worse
+        if status.pressure > previous {
+            match self.ledger.relieve_storage() {
+                Ok(_) => self.storage.record_relieved(),
+                Err(e) => warn!("Failed to make room in the database: {}", e),
+            }
+        }
+    }
+
+    /// Records that a write to the disk failed, putting the node in read-only mode: it keeps serving reads, but stops
+    /// voting, publishing, and making writes that the ledger can do without, rather than crashing.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error that the write failed with
+    pub fn record_write_failure(&mut self, error: &str) {
+        if self.storage.record_write_failure(error) {
+            error!(
+                "Failed to write to the disk ({}); entering read-only mode until it takes writes again",
+                error
+            );
+        }
+
+        self.ledger.set_pressured(true);
+    }
+
+    /// Checks whether or not the node is in read-only mode, since writes to its disk are failing.
+    pub fn is_read_only(&self) -> bool {
+        self.storage.is_read_only()
+    }
+
+    /// Counts the given transaction towards the network's activity, and persists the window that it fell in.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that was executed successfully
+    fn record_stats(&mut self, tx: &Transaction) -> Result<(), ExecutionError> {
+        let (bucket, pruned) = self.stats.record(tx, &gas::fee(&tx.transaction_data));
+
+        // The statistics can go unpersisted for a while, but the transaction can't go unexecuted
+        if self.storage.admit_noncritical().is_err() {
+            return Ok(());
+        }
+
+        let encoded = match bucket {
+            Some(bucket) => Some((
+                bucket.start,
+                bincode::serialize(&bucket).map_err(|e| ExecutionError::Miscellaneous {
+                    error: e.to_string(),
+                })?,
+            )),
+            None => None,
+        };
+
+        if let Some(tree) = &self.stats_store {
+            // Forget the windows that have aged out once the latest one has been saved
+            let written = encoded
+                .map_or(Ok(None), |(start, encoded)| {
+                    tree.insert(start.to_be_bytes(), encoded)
+                })
+                .and_then(|_| {
+                    pruned
+                        .iter()
+                        .try_for_each(|start| tree.remove(start.to_be_bytes()).map(|_| ()))
+                });
+
+            // A disk that stops taking writes shouldn't stop the transaction from being executed
+            if let Err(e) = written {
+                self.record_write_failure(&e.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keeps the given vote, so that it counts towards its voter's share of the current epoch's reward, and persists
+    /// the epoch's participation. Nothing is counted while issuance is disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `vote` - The signed vote
+    fn record_participation(&mut self, vote: &Vote) -> Result<(), ExecutionError> {
+        if !self.config.issuance.is_enabled() {
+            return Ok(());
+        }
+
+        let epoch = self.config.issuance.epoch_at(self.ledger.height());
+        let votes = self.participation.entry(epoch).or_default();
+        votes.push(vote.clone());
+
+        if let Some(tree) = &self.participation_store {
+            let encoded =
+                bincode::serialize(&*votes).map_err(|e| ExecutionError::Miscellaneous {
+                    error: e.to_string(),
+                })?;
+
+            tree.insert(epoch.to_be_bytes(), encoded)?;
+        }
+
+        Ok(())
+    }
+
+    /// Forgets the participation counted in the given epoch, since its reward won't be paid out again.
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - The epoch that has been paid out
+    fn forget_participation(&mut self, epoch: u64) -> Result<(), ExecutionError> {
+        self.participation.remove(&epoch);
+
+        if let Some(tree) = &self.participation_store {
+            tree.remove(epoch.to_be_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Proposes to mint the reward for the oldest finished epoch that hasn't been paid out yet, splitting it between
+    /// the accounts that the node saw vote during the epoch, and carrying the votes along so that every node can
+    /// check the split. Votes already counted by an executed mint are left out. The epoch's most active participant is
+    /// left to make the proposal, unless it hasn't done so within an epoch. Only one mint is proposed at a time, so
+    /// that each is built on the issuance address's latest nonce.
+    fn propose_due_mints(&mut self) -> Result<(), ExecutionError> {
+        let schedule = self.config.issuance.clone();
+        if !schedule.is_enabled() {
+            return Ok(());
+        }
+
+        // Wait for any mint that's already in flight to be decided
+        if self
+            .pending_proposals
+            .values()
+            .chain(self.localized_proposals.values())
+            .any(|prop| prop.proposal_data.param_name == ISSUANCE_PARAM)
+        {
+            return Ok(());
+        }
+
+        let current = schedule.epoch_at(self.ledger.height());
+        let mut finished: Vec<u64> = self
+            .participation
+            .keys()
+            .filter(|epoch| **epoch < current)
+            .cloned()
+            .collect();
+        finished.sort();
+
+        let counted = self.counted_votes();
+        for epoch in finished {
+            let votes: Vec<Vote> = self.participation[&epoch]
+                .iter()
+                .filter(|vote| {
+                    issuance::vote_key(vote).map_or(false, |key| !counted.contains(&key))
+                })
+                .cloned()
+                .collect();
+            let transfers = issuance::distribute(
+                &schedule.reward_for_epoch(epoch),
+                &issuance::participation(&votes),
+            );
+
+            // Nothing more will come of epochs that have been paid out, or that have nothing left to pay
+            if transfers.is_empty() || self.ledger.get_mint(epoch)?.is_some() {
+                self.forget_participation(epoch)?;
+
+                continue;
+            }
+
+            if !self.active_voters.contains(&transfers[0].recipient) && current <= epoch + 1 {
+                continue;
+            }
+
+            // Each participant's share accrues until the participant claims it
+            let tx = self.build_mint(Mint {
+                epoch,
+                transfers: rewards::accrue(transfers),
+                votes,
+            })?;
+            info!(
+                "Proposing to mint the reward for epoch {} in transaction {}",
+                epoch, tx.hash
+            );
+
+            return self
+                .register_proposal(Proposal::for_mint(&tx))
+                .map_err(|e| ExecutionError::Miscellaneous {
+                    error: e.to_string(),
+                });
+        }
+
+        Ok(())
+    }
+
+    /// Builds a transaction carrying the given mint on top of a few unresolved tips of the ledger.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - The reward that should be minted
+    fn build_mint(&self, mint: Mint) -> Result<Transaction, ExecutionError> {
+        // Use the nonce directly following the issuance address's last mint
+        let nonce = self
+            .ledger
+            .obtain_executed_head()
+            .and_then(|head| head.state_entry)
+            .and_then(|state| {
+                state
+                    .data
+                    .nonces
+                    .get(&issuance_address().to_str())
+                    .map(|last_nonce| last_nonce + 1)
+            })
+            .unwrap_or(0);
+
+        let mut tx = Transaction::new(
+            nonce,
+            issuance_address(),
+            issuance_address(),
+            BigUint::zero(),
+            &mint.to_payload(),
+            self.ledger.select_tips(DEFAULT_TIPS_PER_TRANSACTION, true),
+        );
+
+        // Prove that we've executed the mint's parents
+        let (merged_state_entry, parent_entries) = self
+            .ledger
+            .resolve_parent_nodes(tx.transaction_data.parents.clone())?;
+        tx.register_parental_state(merged_state_entry, parent_entries);
+
+        Ok(tx)
+    }
+
+    /// Gets the depth of the executed state, which follows from the transactions leading up to it, rather than how
+    /// many transactions this node happens to hold.
+    fn executed_height(&self) -> u64 {
+        self.ledger
+            .obtain_executed_head()
+            .and_then(|head| head.state_entry)
+            .map_or(0, |state| state.data.height)
+    }
+
+    /// Forgets about the scheduled change carried by the proposal with the given hash, if it's still waiting.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_id` - The hash of the proposal that scheduled the change
+    fn unschedule_change(&mut self, proposal_id: &Hash) -> Result<(), ExecutionError> {
+        self.scheduled_changes
+            .retain(|proposal| proposal.proposal_id != *proposal_id);
+
+        if let Some(tree) = &self.scheduled_store {
+            tree.remove(*proposal_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies each of the scheduled changes whose activation height the ledger has reached. What came of each
+    /// activation is remembered alongside the executed proposals, so that one that fails doesn't go unnoticed.
+    fn activate_scheduled_changes(&mut self) -> Result<(), ExecutionError> {
+        let height = self.executed_height();
+
+        // Split out the changes that are due
+        let (due, waiting): (Vec<Proposal>, Vec<Proposal>) =
+            self.scheduled_changes.drain(..).partition(|proposal| {
+                scheduled_change(proposal)
+                    .map_or(true, |scheduled| scheduled.activation_height <= height)
+            });
+        self.scheduled_changes = waiting;
+
+        for proposal in due {
+            // Forget about the change, since it is about to be applied
+            self.unschedule_change(&proposal.proposal_id)?;
+
+            let scheduled = match scheduled_change(&proposal) {
+                Some(scheduled) => scheduled,
+                None => continue,
+            };
+
+            info!(
+                "Activating scheduled change at ledger height {}: {}",
+                height, scheduled.change
+            );
+
+            // A failure leaves the config untouched
+            let applied = config_after(&self.config, &scheduled.change)
+                .and_then(|config| self.adopt_config(config, proposal.clone()));
+            if let Err(e) = &applied {
+                warn!("Failed to activate scheduled change: {}", e);
+            }
+
+            self.record_execution(
+                &Proposal::new(
+                    format!(
+                        "scheduled change at ledger height {}",
+                        scheduled.activation_height
+                    ),
+                    scheduled.change,
+                ),
+                &applied,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes the config change scheduled by the given proposal, if it schedules one.
+///
+/// # Arguments
+///
+/// * `proposal` - The proposal that scheduled the change
+fn scheduled_change(proposal: &Proposal) -> Option<ScheduledChange> {
+    match proposal.proposal_data.body() {
+        Ok(ProposalBody::Scheduled(scheduled)) => Some(scheduled),
+        _ => None,
+    }
+}
+
+/// Applies the given config change (either a single edit, or a bundle of them) to a copy of the given config,
+/// superseding the version of the config published on the network. Results in an error, rather than a partially
+/// edited config, if any of the edits fail.
+///
+/// # Arguments
+///
+/// * `config` - The config that the change should be applied to
+/// * `change` - The change that should be applied
+pub(crate) fn config_after(
+    config: &config::Config,
+    change: &ProposalData,
+) -> Result<config::Config, ExecutionError> {
+    let changes = match change.body() {
+        Ok(ProposalBody::Bundle(changes)) => changes,
+        _ => vec![change.clone()],
+    };
+
+    let mut config = config.clone();
+    for change in changes {
+        apply_config_change(&mut config, change)?;
+    }
+    config.sequence += 1; // Supersede the last version of the config published on the network
+
+    Ok(config)
+}
+
+/// Applies a single edit to one of the fields of the given config.
+///
+/// # Arguments
+///
+/// * `config` - The config that should be edited
+/// * `change` - The edit that should be applied
+fn apply_config_change(
+    config: &mut config::Config,
+    change: ProposalData,
+) -> Result<(), ExecutionError> {
+    match change.param_name.as_str() {
+        // Proposal is targeting the reward_per_gas config field
+        "config::reward_per_gas" => {
+            // Handle different operations
+            match change.operation {
+                // Is updating reward_per_gas
+                Operation::Amend { amended_value } => {
+                    config.reward_per_gas = bigint::BigUint::from_bytes_le(&amended_value)
+                } // Set reward_per_gas
+                // Is setting reward_per_gas to zero
+                Operation::Remove => config.reward_per_gas = bigint::BigUint::from(0 as u16),
+                // Is adding a value to the reward_per_gas
+                Operation::Append { value_to_append } => {
+                    config.reward_per_gas = config.reward_per_gas.clone()
+                        + bigint::BigUint::from_bytes_le(&value_to_append)
+                } // Add to reward_per_gas
+            }
+
+            Ok(())
+        }
+        // Proposal is targeting the network_name config field
+        "config::network_name" => {
+            // Handle different operations
+            match change.operation {
+                // Is updating network_name
+                Operation::Amend { amended_value } => {
+                    config.network_name = String::from_utf8_lossy(&amended_value).into_owned()
+                } // Set network_name
+                // Is setting network_name to ""
+                Operation::Remove => config.network_name = "".to_owned(), // Set network_name to empty string
+                // Is appending a substring to the network_name
+                Operation::Append { value_to_append } => {
+                    config.network_name = format!(
+                        "{}{}",
+                        config.network_name,
+                        String::from_utf8_lossy(&value_to_append).into_owned()
+                    )
+                } // Append to network_name
+            }
+
+            Ok(())
+        }
+        // Proposal is targeting the number of finks minted each epoch
+        "config::issuance_per_epoch" => {
+            match change.operation {
+                Operation::Amend { amended_value } => {
+                    config.issuance.per_epoch = bigint::BigUint::from_bytes_le(&amended_value)
+                }
+                // Is turning issuance off
+                Operation::Remove => config.issuance.per_epoch = bigint::BigUint::zero(),
+                Operation::Append { value_to_append } => {
+                    config.issuance.per_epoch += bigint::BigUint::from_bytes_le(&value_to_append)
+                }
+            }
+
+            Ok(())
+        }
+        // Proposal is targeting the length of each issuance epoch
+        "config::issuance_epoch_length" => {
+            let length = match &change.operation {
+                Operation::Amend { amended_value } => decode_u64(&change, amended_value)?,
+                Operation::Remove => DEFAULT_EPOCH_LENGTH,
+                Operation::Append { value_to_append } => config
+                    .issuance
+                    .epoch_length
+                    .saturating_add(decode_u64(&change, value_to_append)?),
+            };
+
+            // Every transaction would otherwise start an epoch of its own
+            if length == 0 {
+                return Err(ExecutionError::InvalidOperation {
+                    operation: change.operation.to_string(),
+                    proposal_param: change.param_name,
+                });
+            }
+
+            config.issuance.epoch_length = length;
+
+            Ok(())
+        }
+        // Proposal is targeting how often the issuance reward is halved
+        "config::issuance_halving_epochs" => {
+            config.issuance.halving_epochs = match &change.operation {
+                Operation::Amend { amended_value } => decode_u64(&change, amended_value)?,
+                // Is making the reward stay the same forever
+                Operation::Remove => 0,
+                Operation::Append { value_to_append } => config
+                    .issuance
+                    .halving_epochs
+                    .saturating_add(decode_u64(&change, value_to_append)?),
+            };
+
+            Ok(())
+        }
+        // Proposal is targeting the share of the issuance that must vote for a proposal to execute it
+        "config::consensus_quorum_bps" => {
+            config.consensus.quorum_bps = decode_bps(&change, config.consensus.quorum_bps)?;
+
+            Ok(())
+        }
+        // Proposal is targeting the share of the issuance that must vote against a proposal to reject it
+        "config::consensus_rejection_bps" => {
+            config.consensus.rejection_bps = decode_bps(&change, config.consensus.rejection_bps)?;
+
+            Ok(())
+        }
+        // Proposal is targeting how long proposals must stay open for voting
+        "config::consensus_min_voting_period" => {
+            config.consensus.min_voting_period_secs = match &change.operation {
+                Operation::Amend { amended_value } => decode_u64(&change, amended_value)?,
+                // Is letting proposals be decided as soon as a threshold is reached
+                Operation::Remove => 0,
+                Operation::Append { value_to_append } => config
+                    .consensus
+                    .min_voting_period_secs
+                    .saturating_add(decode_u64(&change, value_to_append)?),
+            };
+
+            Ok(())
+        }
+        _ => Err(ExecutionError::InvalidTargetProposalParam {
+            proposal_param: change.param_name,
+        }),
+    }
+}
+
+/// Applies the given edit to a consensus threshold, expressed in basis points of the issuance. Thresholds that could
+/// never be reached, or that would be reached without a single vote, are refused.
+///
+/// # Arguments
+///
+/// * `change` - The edit that should be applied
+/// * `current` - The current value of the threshold
+fn decode_bps(change: &ProposalData, current: u64) -> Result<u64, ExecutionError> {
+    let bps = match &change.operation {
+        Operation::Amend { amended_value } => decode_u64(change, amended_value)?,
+        // Is going back to a simple majority
+        Operation::Remove => DEFAULT_THRESHOLD_BPS,
+        Operation::Append { value_to_append } => {
+            current.saturating_add(decode_u64(change, value_to_append)?)
+        }
+    };
+
+    if bps == 0 || bps > BASIS_POINTS {
+        return Err(ExecutionError::InvalidOperation {
+            operation: change.operation.to_string(),
+            proposal_param: change.param_name.clone(),
+        });
+    }
+
+    Ok(bps)
+}
+
+/// Decodes a little-endian, 8-byte unsigned integer proposed as the value of a config field.
+///
+/// # Arguments
+///
+/// * `change` - The edit carrying the value
+/// * `value` - The raw value
+fn decode_u64(change: &ProposalData, value: &[u8]) -> Result<u64, ExecutionError> {
+    value
+        .try_into()
+        .map(u64::from_le_bytes)
+        .map_err(|_| ExecutionError::InvalidOperation {
+            operation: change.operation.to_string(),
+            proposal_param: change.param_name.clone(),
+        })
 }
@@ -12,10 +12,12 @@ use super::super::super::{
 }; // Import the hash primitive
 use super::super::types::signature; // Import the signature primitive
 
-use std::fmt;
+use num::bigint::BigInt; // Add support for large signed integers
+
+use std::{collections::HashSet, fmt};
 
 /// A binary, signed vote regarding a particular proposal.
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Vote {
     /// The hash of the target proposal
     pub target_proposal: hash::Hash,
@@ -25,6 +27,15 @@ pub struct Vote {
     pub signature: Option<signature::Signature>,
 }
 
+/// The running total of the votes cast on a particular proposal.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Tally {
+    /// The net weight of the votes cast (those in favor, less those in opposition)
+    pub weight: BigInt,
+    /// Each of the addresses that have voted on the proposal
+    pub voters: HashSet<Address>,
+}
+
 /// Implement a set of voting helper methods.
 impl Vote {
     /// Initialize and sign a new vote instance.
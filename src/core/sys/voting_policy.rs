@@ -0,0 +1,222 @@
+use std::{fs, io, io::Write}; // Import the filesystem library
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::super::super::{accounts::account::Account, common::address::Address}; // Import the account & address types
+use super::proposal::ProposalData; // Import the proposal body type
+
+/// The name of the file in a data directory's config folder that the voting policy is stored in.
+pub const VOTING_POLICY_FILE: &str = "voting_policy.json";
+
+/// What a node should do when it receives a particular proposal.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Decision {
+    /// Vote on the proposal automatically, if the node knows how to evaluate it
+    Automatic,
+
+    /// Don't vote on the proposal at all
+    Abstain,
+
+    /// Hold off on voting until the operator confirms a vote via the RPC API
+    AwaitConfirmation,
+}
+
+/// A change to the set of accounts that a running node votes with, requested by the operator.
+pub enum VotingAccountChange {
+    /// Start voting with the given unlocked account
+    Add(Account),
+
+    /// Stop voting with the account with the given address
+    Remove(Address),
+}
+
+/// A node-local set of rules dictating how (and whether) the node votes on incoming proposals.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct VotingPolicy {
+    /// The accounts that the node may vote with. If empty, every unlocked account is used.
+    #[serde(default)]
+    pub accounts: Vec<Address>,
+
+    /// Prefixes of the parameter names (e.g. "config::") of proposals that should never be voted on
+    #[serde(default)]
+    pub abstain_on: Vec<String>,
+
+    /// Prefixes of the parameter names of proposals that require a manual vote
+    #[serde(default = "default_confirm_manually")]
+    pub confirm_manually: Vec<String>,
+}
+
+/// Gets the parameter prefixes that require manual confirmation by default: anything that changes the network's config.
+fn default_confirm_manually() -> Vec<String> {
+    vec!["config::".to_owned()]
+}
+
+impl Default for VotingPolicy {
+    /// Builds a policy that votes with every account, and only requires confirmation for config changes.
+    fn default() -> Self {
+        Self {
+            accounts: Vec::new(),
+            abstain_on: Vec::new(),
+            confirm_manually: default_confirm_manually(),
+        }
+    }
+}
+
+/// Implement a set of voting policy helper methods.
+impl VotingPolicy {
+    /// Decides what the node should do with a proposal, considering each of the parameters that it
+    /// would change. Abstention takes precedence over manual confirmation.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_data` - The body of the proposal
+    pub fn decide(&self, proposal_data: &ProposalData) -> Decision {
+        let params = proposal_data.affected_params(); // Get each of the params the proposal touches
+
+        // Checks whether or not any of the params start with one of the given prefixes
+        let matches_any = |prefixes: &[String]| {
+            params.iter().any(|param| {
+                prefixes
+                    .iter()
+                    .any(|prefix| param.starts_with(prefix.as_str()))
+            })
+        };
+
+        if matches_any(&self.abstain_on) {
+            Decision::Abstain
+        } else if matches_any(&self.confirm_manually) {
+            Decision::AwaitConfirmation
+        } else {
+            Decision::Automatic
+        }
+    }
+
+    /// Checks whether or not the node may vote with the account with the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The address of the account
+    pub fn permits(&self, account: &Address) -> bool {
+        self.accounts.is_empty() || self.accounts.contains(account)
+    }
+
+    /// Makes sure that the policy lets the node vote with the account with the given address. A policy that permits
+    /// every account is left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The address of the account
+    pub fn admit(&mut self, account: Address) {
+        if !self.permits(&account) {
+            self.accounts.push(account);
+        }
+    }
+
+    /// Stops the policy from naming the account with the given address. The account is kept if it's the only one
+    /// named, since an empty list would let the node vote with every account instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The address of the account
+    pub fn dismiss(&mut self, account: &Address) {
+        if self.accounts.len() > 1 {
+            self.accounts.retain(|existing| existing != account);
+        }
+    }
+
+    /// Persist the policy to the config folder in the given data directory.
+    pub fn write_to_disk_at_data_directory(&self, data_dir: &str) -> io::Result<()> {
+        // Make the config folder
+        fs::create_dir_all(format!("{}/config", data_dir))?;
+
+        let mut file = fs::File::create(format!("{}/config/{}", data_dir, VOTING_POLICY_FILE))?; // Initialize file
+
+        file.write_all(serde_json::to_vec_pretty(self)?.as_slice())?; // Serialize
+
+        Ok(()) // All good!
+    }
+
+    /// Read a persisted policy from the config folder in the given data directory.
+    pub fn read_from_disk_at_data_directory(data_dir: &str) -> io::Result<Self> {
+        let file = fs::File::open(format!("{}/config/{}", data_dir, VOTING_POLICY_FILE))?; // Open policy file
+
+        Ok(serde_json::from_reader(file)?) // Return read policy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::{common::io, crypto::blake3}; // Import the io & hashing modules
+    use super::super::proposal::Operation; // Import the operation type
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_decide() {
+        let policy = VotingPolicy {
+            abstain_on: vec!["config::reward_per_gas".to_owned()],
+            ..Default::default()
+        }; // Make a policy that abstains on reward changes
+
+        let rename = ProposalData::new(
+            "config::network_name".to_owned(),
+            Operation::Amend {
+                amended_value: b"andromeda".to_vec(),
+            },
+        ); // Make a config change
+        let reward_change =
+            ProposalData::new("config::reward_per_gas".to_owned(), Operation::Remove);
+
+        assert_eq!(
+            policy.decide(&ProposalData::signal("hello")),
+            Decision::Automatic
+        ); // Signals should be voted on automatically
+        assert_eq!(
+            policy.decide(&ProposalData::bundle(vec![rename.clone()])),
+            Decision::AwaitConfirmation
+        ); // Bundled config changes should be confirmed
+        assert_eq!(
+            policy.decide(&ProposalData::bundle(vec![rename, reward_change])),
+            Decision::Abstain
+        ); // Abstention should take precedence
+    }
+
+    #[test]
+    fn test_admit_dismiss() {
+        let first = Address::default();
+        let second = blake3::hash_slice(b"second");
+
+        let mut open = VotingPolicy::default();
+        open.admit(second);
+        assert!(open.accounts.is_empty()); // Every account is already permitted
+
+        let mut policy = VotingPolicy {
+            accounts: vec![first],
+            ..Default::default()
+        }; // Make a policy that only votes with one account
+
+        policy.admit(second);
+        assert!(policy.permits(&second)); // Ensure the new account is allowed
+
+        policy.dismiss(&first);
+        assert_eq!(policy.accounts, vec![second]);
+
+        policy.dismiss(&second);
+        assert_eq!(policy.accounts, vec![second]); // Dismissing the last account must not permit every account
+    }
+
+    #[test]
+    fn test_read_from_disk() {
+        let data_dir = format!("{}/.tests/voting_policy", io::data_dir()); // Get a test directory
+
+        let policy = VotingPolicy {
+            accounts: vec![Address::default()],
+            ..Default::default()
+        }; // Make a policy that only votes with one account
+        policy.write_to_disk_at_data_directory(&data_dir).unwrap(); // Write the policy
+
+        let read_policy = VotingPolicy::read_from_disk_at_data_directory(&data_dir).unwrap(); // Read it back
+
+        assert_eq!(read_policy, policy); // Ensure the policy survived
+        assert!(read_policy.permits(&Address::default())); // Ensure the account is allowed
+    }
+}
@@ -0,0 +1,253 @@
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::HashMap; // Import the map type
+
+use super::{burn, issuance, transaction::TransactionData}; // Import the system addresses & transaction type
+
+use super::super::super::common::address::Address; // Import the address type
+
+/// The activity of a single account, as seen in the transactions that it sent and received.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AccountActivity {
+    /// The number of transactions that the account has sent
+    pub sent: u64,
+
+    /// The number of transactions that the account has received
+    pub received: u64,
+
+    /// When the account last sent a transaction, if it ever has
+    pub last_sent: Option<DateTime<Utc>>,
+
+    /// When the account last received a transaction, if it ever has
+    pub last_received: Option<DateTime<Utc>>,
+}
+
+/// An account holding funds that it hasn't touched in a while.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DormantAccount {
+    /// The address of the account
+    pub address: String,
+
+    /// The balance of the account
+    pub balance: BigUint,
+
+    /// When the account last sent a transaction, if it ever has
+    pub last_sent: Option<DateTime<Utc>>,
+}
+
+/// Each of the accounts that haven't sent anything since a point in time, along with how much they hold between them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DormancyReport {
+    /// Accounts that haven't sent anything since this point in time are dormant
+    pub cutoff: DateTime<Utc>,
+
+    /// The number of dormant accounts
+    pub dormant_accounts: usize,
+
+    /// The number of finks held by the dormant accounts
+    pub dormant_balance: BigUint,
+
+    /// The number of finks held by every account, dormant or not
+    pub total_balance: BigUint,
+
+    /// The dormant accounts holding the most, richest first
+    pub accounts: Vec<DormantAccount>,
+}
+
+/// An index of the activity of each account that has sent or received a transaction, kept alongside the ledger, so
+/// that questions about dormancy can be answered without walking the entire DAG.
+#[derive(Clone)]
+pub struct ActivityIndex {
+    /// The tree in which the activity of each account is kept, by address
+    tree: sled::Tree,
+}
+
+impl From<sled::Tree> for ActivityIndex {
+    /// Keeps the index in the given tree.
+    fn from(tree: sled::Tree) -> Self {
+        Self { tree }
+    }
+}
+
+/// Implement a set of activity index helper methods.
+impl ActivityIndex {
+    /// Gets the activity of the account with the given address, if it has ever sent or received anything.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The address of the account
+    pub fn get(&self, account: &Address) -> Result<Option<AccountActivity>, sled::Error> {
+        match self.tree.get(account)? {
+            Some(encoded) => Ok(Some(
+                bincode::deserialize(&encoded)
+                    .map_err(|e| sled::Error::Unsupported(e.to_string()))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Checks whether or not anything has been indexed yet.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Forgets the activity of every account.
+    pub fn clear(&self) -> Result<(), sled::Error> {
+        self.tree.clear()
+    }
+
+    /// Records the given transaction against its sender & recipient. Transactions that were later refused still
+    /// count, since only the holder of the sender's key could have signed them.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The data of the transaction
+    pub fn record(&self, tx: &TransactionData) -> Result<(), sled::Error> {
+        self.update(&tx.sender, |activity| {
+            activity.sent += 1;
+            activity.last_sent = activity.last_sent.max(Some(tx.timestamp));
+        })?;
+
+        self.update(&tx.recipient, |activity| {
+            activity.received += 1;
+            activity.last_received = activity.last_received.max(Some(tx.timestamp));
+        })
+    }
+
+    /// Reports each of the accounts holding funds that haven't sent anything since the given point in time. The burn &
+    /// issuance addresses are left out, since nobody can send from them.
+    ///
+    /// # Arguments
+    ///
+    /// * `balances` - The balance of each account
+    /// * `cutoff` - Accounts that haven't sent anything since this point in time are dormant
+    /// * `limit` - The greatest number of dormant accounts to list
+    pub fn dormancy_report(
+        &self,
+        balances: &HashMap<String, BigUint>,
+        cutoff: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<DormancyReport, sled::Error> {
+        let system_addresses = [
+            burn::burn_address().to_str(),
+            issuance::issuance_address().to_str(),
+        ];
+
+        let mut accounts = Vec::new();
+        let mut total_balance = BigUint::zero();
+
+        for (address, balance) in balances {
+            if balance.is_zero() || system_addresses.contains(address) {
+                continue;
+            }
+
+            total_balance += balance;
+
+            let last_sent = self
+                .get(&Address::from(address.as_str()))?
+                .and_then(|activity| activity.last_sent);
+            if last_sent.map_or(true, |last_sent| last_sent < cutoff) {
+                accounts.push(DormantAccount {
+                    address: address.clone(),
+                    balance: balance.clone(),
+                    last_sent,
+                });
+            }
+        }
+
+        // List the richest accounts first, since they matter most to the supply
+        accounts.sort_by(|a, b| b.balance.cmp(&a.balance).then(a.address.cmp(&b.address)));
+
+        Ok(DormancyReport {
+            cutoff,
+            dormant_accounts: accounts.len(),
+            dormant_balance: accounts.iter().map(|account| &account.balance).sum(),
+            total_balance,
+            accounts: accounts.into_iter().take(limit).collect(),
+        })
+    }
+
+    /// Applies the given change to the activity of the account with the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The address of the account
+    /// * `change` - The change that should be made to the account's activity
+    fn update(
+        &self,
+        account: &Address,
+        change: impl FnOnce(&mut AccountActivity),
+    ) -> Result<(), sled::Error> {
+        let mut activity = self.get(account)?.unwrap_or_default();
+        change(&mut activity);
+
+        let encoded =
+            bincode::serialize(&activity).map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+        self.tree.insert(account, encoded)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{super::super::common::io, transaction::Transaction}; // Import the io & transaction modules
+    use super::*; // Import names from our parent module
+
+    use super::super::super::super::crypto::blake3; // Import the blake3 hashing module
+
+    use chrono::Duration;
+
+    #[test]
+    fn test_dormancy_report() {
+        let db = sled::open(format!(
+            "{}/.tests/activity/{}",
+            io::db_dir(),
+            rand::random::<u32>()
+        ))
+        .unwrap();
+        let index = ActivityIndex::from(db.open_tree("activity").unwrap());
+        assert!(index.is_empty());
+
+        let (alice, bob, carol) = (
+            blake3::hash_slice(b"alice"),
+            blake3::hash_slice(b"bob"),
+            blake3::hash_slice(b"carol"),
+        );
+
+        // Alice paid bob a long time ago, and bob has only just paid carol
+        let mut old = Transaction::new(0, alice, bob, BigUint::from(1 as u8), b"", vec![]);
+        old.transaction_data.timestamp = Utc::now() - Duration::days(400);
+        index.record(&old.transaction_data).unwrap();
+        index
+            .record(
+                &Transaction::new(0, bob, carol, BigUint::from(1 as u8), b"", vec![])
+                    .transaction_data,
+            )
+            .unwrap();
+
+        assert_eq!(index.get(&bob).unwrap().unwrap().sent, 1);
+        assert_eq!(index.get(&bob).unwrap().unwrap().received, 1);
+
+        let mut balances = HashMap::new();
+        for (account, balance) in &[(alice, 5), (bob, 7), (carol, 9)] {
+            balances.insert(account.to_str(), BigUint::from(*balance as u8));
+        }
+        balances.insert(burn::burn_address().to_str(), BigUint::from(100 as u8));
+
+        // Alice hasn't sent anything in a year, and carol never has
+        let report = index
+            .dormancy_report(&balances, Utc::now() - Duration::days(365), 1)
+            .unwrap();
+        assert_eq!(report.dormant_accounts, 2);
+        assert_eq!(report.dormant_balance, BigUint::from(14 as u8));
+        assert_eq!(report.total_balance, BigUint::from(21 as u8));
+        assert_eq!(report.accounts.len(), 1);
+        assert_eq!(report.accounts[0].address, carol.to_str());
+        assert_eq!(report.accounts[0].last_sent, None);
+    }
+}
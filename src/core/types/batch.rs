@@ -0,0 +1,295 @@
+use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::HashMap;
+
+use super::transaction::TransactionData; // Import the transaction type
+
+use super::super::super::{common::address::Address, crypto::blake3}; // Import the address & hashing modules
+
+/// The prefix marking a transaction payload as a batch of transfers.
+pub const BATCH_PAYLOAD_PREFIX: &[u8] = b"batch:";
+
+/// The greatest number of recipients that a single batch may pay.
+pub const MAX_BATCH_RECIPIENTS: usize = 256;
+
+/// A single payment made as part of a batch.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Transfer {
+    /// The address being paid
+    pub recipient: Address,
+
+    /// The number of finks paid to the recipient
+    pub value: BigUint,
+}
+
+/// A set of payments made by a single transaction, which debits the sender once for the sum of the payments.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BatchTransfer {
+    /// Each of the payments, in the order in which they are credited
+    pub transfers: Vec<Transfer>,
+}
+
+/// An error encountered while checking a batch transfer against the rules of the ledger.
+#[derive(Debug, Fail, PartialEq)]
+pub enum BatchError {
+    #[fail(display = "the batch transfer could not be decoded")]
+    Malformed,
+    #[fail(display = "batch transfers must be sent to the batch address")]
+    WrongRecipient,
+    #[fail(display = "only batch transfers may be sent to the batch address")]
+    NotABatch,
+    #[fail(
+        display = "a batch must pay between 1 and {} recipients, but pays {}",
+        max, found
+    )]
+    InvalidRecipientCount { found: usize, max: usize },
+    #[fail(display = "the batch pays {} more than once", recipient)]
+    DuplicateRecipient { recipient: Address },
+    #[fail(display = "the batch may not pay the batch address itself")]
+    PaysBatchAddress,
+    #[fail(display = "the batch pays nothing to {}", recipient)]
+    EmptyTransfer { recipient: Address },
+    #[fail(
+        display = "the transaction sends {} finks, but the batch pays out {} finks",
+        value, total
+    )]
+    ValueMismatch { value: BigUint, total: BigUint },
+}
+
+/// Gets the address that each batch transfer is sent to. Funds only pass through this address while a batch is
+/// executed; nobody holds its key.
+pub fn batch_address() -> Address {
+    blake3::hash_slice(b"batch_transfer")
+}
+
+/// Implement a set of batch transfer helper methods.
+impl BatchTransfer {
+    /// Initializes a new batch paying each of the given recipients.
+    ///
+    /// # Arguments
+    ///
+    /// * `transfers` - Each of the payments that the batch should make
+    pub fn new(transfers: Vec<Transfer>) -> Self {
+        Self { transfers }
+    }
+
+    /// Gets the total number of finks paid out by the batch, which is what the transaction carrying it must send.
+    pub fn total(&self) -> BigUint {
+        self.transfers
+            .iter()
+            .fold(BigUint::zero(), |total, transfer| total + &transfer.value)
+    }
+
+    /// Encodes the batch as a transaction payload.
+    pub fn to_payload(&self) -> Vec<u8> {
+        [
+            BATCH_PAYLOAD_PREFIX,
+            &bincode::serialize(self).unwrap_or_default()[..],
+        ]
+        .concat()
+    }
+
+    /// Decodes the batch carried in the given transaction payload. Payloads that aren't marked as batches result in
+    /// None.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload of the transaction
+    pub fn from_payload(payload: &[u8]) -> Result<Option<Self>, BatchError> {
+        if !payload.starts_with(BATCH_PAYLOAD_PREFIX) {
+            return Ok(None);
+        }
+
+        bincode::deserialize(&payload[BATCH_PAYLOAD_PREFIX.len()..])
+            .map(Some)
+            .map_err(|_| BatchError::Malformed)
+    }
+}
+
+/// Makes sure that the given transaction data either carries a well-formed batch sent to the batch address, or has
+/// nothing to do with batches at all.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction that should be checked
+pub fn check(tx: &TransactionData) -> Result<(), BatchError> {
+    let batch = match BatchTransfer::from_payload(&tx.payload)? {
+        Some(batch) => batch,
+        None if tx.recipient == batch_address() => return Err(BatchError::NotABatch),
+        None => return Ok(()),
+    };
+
+    if tx.recipient != batch_address() {
+        return Err(BatchError::WrongRecipient);
+    }
+
+    if batch.transfers.is_empty() || batch.transfers.len() > MAX_BATCH_RECIPIENTS {
+        return Err(BatchError::InvalidRecipientCount {
+            found: batch.transfers.len(),
+            max: MAX_BATCH_RECIPIENTS,
+        });
+    }
+
+    // Each recipient should be paid exactly once, and actually be paid something
+    let mut seen = Vec::with_capacity(batch.transfers.len());
+    for transfer in &batch.transfers {
+        if transfer.recipient == batch_address() {
+            return Err(BatchError::PaysBatchAddress);
+        }
+
+        if seen.contains(&transfer.recipient) {
+            return Err(BatchError::DuplicateRecipient {
+                recipient: transfer.recipient,
+            });
+        }
+        seen.push(transfer.recipient);
+
+        if transfer.value.is_zero() {
+            return Err(BatchError::EmptyTransfer {
+                recipient: transfer.recipient,
+            });
+        }
+    }
+
+    // The sender is debited the transaction's value, so it has to cover the batch exactly
+    let total = batch.total();
+    if total != tx.value {
+        return Err(BatchError::ValueMismatch {
+            value: tx.value.clone(),
+            total,
+        });
+    }
+
+    Ok(())
+}
+
+/// Pays out the batch carried in the given transaction data, if there is one, from the funds that the transaction
+/// credited to the batch address. Batches that the batch address can't cover are left alone.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction being executed
+/// * `balances` - The balances of each account, which should be updated
+pub fn execute_batch(tx: &TransactionData, balances: &mut HashMap<String, BigUint>) {
+    let batch = match BatchTransfer::from_payload(&tx.payload) {
+        Ok(Some(batch)) if tx.recipient == batch_address() => batch,
+        _ => return,
+    };
+
+    // Take the batch's funds back out of the batch address
+    let escrow = balances
+        .get(&batch_address().to_str())
+        .cloned()
+        .unwrap_or_default();
+    let total = batch.total();
+    if escrow < total {
+        return;
+    }
+    if escrow == total {
+        balances.remove(&batch_address().to_str());
+    } else {
+        balances.insert(batch_address().to_str(), escrow - &total);
+    }
+
+    // Credit each of the recipients
+    for transfer in batch.transfers {
+        *balances
+            .entry(transfer.recipient.to_str())
+            .or_insert_with(BigUint::zero) += transfer.value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::transaction::Transaction;
+    use super::*; // Import names from our parent module
+
+    /// Makes a batch transaction paying each of the given recipients the given amount.
+    fn batch_tx(payments: &[(&str, u64)], value: u64) -> Transaction {
+        let batch = BatchTransfer::new(
+            payments
+                .iter()
+                .map(|(recipient, value)| Transfer {
+                    recipient: blake3::hash_slice(recipient.as_bytes()),
+                    value: BigUint::from(*value),
+                })
+                .collect(),
+        );
+
+        Transaction::new(
+            0,
+            blake3::hash_slice(b"payroll"),
+            batch_address(),
+            BigUint::from(value),
+            &batch.to_payload(),
+            vec![],
+        )
+    }
+
+    #[test]
+    fn test_check() {
+        assert!(check(&batch_tx(&[("alice", 5), ("bob", 10)], 15).transaction_data).is_ok());
+
+        assert_eq!(
+            check(&batch_tx(&[("alice", 5), ("bob", 10)], 20).transaction_data),
+            Err(BatchError::ValueMismatch {
+                value: BigUint::from(20 as u8),
+                total: BigUint::from(15 as u8),
+            })
+        ); // The batch has to add up to the transaction's value
+        assert_eq!(
+            check(&batch_tx(&[("alice", 5), ("alice", 10)], 15).transaction_data),
+            Err(BatchError::DuplicateRecipient {
+                recipient: blake3::hash_slice(b"alice")
+            })
+        );
+        assert_eq!(
+            check(&batch_tx(&[("alice", 0)], 0).transaction_data),
+            Err(BatchError::EmptyTransfer {
+                recipient: blake3::hash_slice(b"alice")
+            })
+        );
+        assert!(check(&batch_tx(&[], 0).transaction_data).is_err()); // A batch has to pay someone
+
+        // Plain transfers into the batch address would be stuck there
+        let stuck = Transaction::new(
+            0,
+            blake3::hash_slice(b"payroll"),
+            batch_address(),
+            BigUint::from(1 as u8),
+            b"",
+            vec![],
+        );
+        assert_eq!(check(&stuck.transaction_data), Err(BatchError::NotABatch));
+    }
+
+    #[test]
+    fn test_execute() {
+        let mut genesis = Transaction::new(
+            0,
+            Address::default(),
+            blake3::hash_slice(b"payroll"),
+            BigUint::from(100 as u8),
+            b"genesis",
+            vec![],
+        ); // Fund the sender
+        genesis.genesis = true;
+
+        let state = batch_tx(&[("alice", 5), ("bob", 10)], 15).execute(Some(genesis.execute(None)));
+
+        let balance = |name: &[u8]| {
+            state
+                .data
+                .balances
+                .get(&blake3::hash_slice(name).to_str())
+                .cloned()
+                .unwrap_or_default()
+        };
+        assert_eq!(balance(b"payroll"), BigUint::from(85 as u8)); // The sender is debited once
+        assert_eq!(balance(b"alice"), BigUint::from(5 as u8));
+        assert_eq!(balance(b"bob"), BigUint::from(10 as u8));
+        assert_eq!(state.data.balances.get(&batch_address().to_str()), None); // Nothing is left in the batch address
+    }
+}
@@ -0,0 +1,99 @@
+use num::bigint::BigUint; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::transaction::TransactionData; // Import the transaction type
+
+use super::super::super::{common::address::Address, crypto::blake3}; // Import the address & hashing modules
+
+/// How many finks have been destroyed, and where to send finks to destroy them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BurnReport {
+    /// The address that finks are burned by sending them to
+    pub address: Address,
+
+    /// The number of finks burned so far
+    pub burned: BigUint,
+}
+
+/// An error encountered while checking a transaction against the burn rules.
+#[derive(Debug, Fail, PartialEq)]
+pub enum BurnError {
+    #[fail(display = "burned funds can't be spent")]
+    SpendsBurnedFunds,
+}
+
+/// Gets the address that finks are burned by sending them to. Nobody holds its key, and the ledger refuses any
+/// transaction sent from it, so whatever it holds is out of circulation for good; its balance is the number of finks
+/// burned so far.
+pub fn burn_address() -> Address {
+    blake3::hash_slice(b"burn")
+}
+
+/// Checks whether or not the given transaction data would spend burned funds.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction that should be checked
+pub fn check(tx: &TransactionData) -> Result<(), BurnError> {
+    if tx.sender == burn_address() {
+        return Err(BurnError::SpendsBurnedFunds);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::transaction::Transaction;
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_burn() {
+        let mut genesis = Transaction::new(
+            0,
+            Address::default(),
+            blake3::hash_slice(b"alice"),
+            BigUint::from(100 as u8),
+            b"genesis",
+            vec![],
+        ); // Fund alice
+        genesis.genesis = true;
+
+        let burn = Transaction::new(
+            0,
+            blake3::hash_slice(b"alice"),
+            burn_address(),
+            BigUint::from(40 as u8),
+            b"",
+            vec![],
+        );
+        assert!(check(&burn.transaction_data).is_ok()); // Anyone may burn their own funds
+
+        let state = burn.execute(Some(genesis.execute(None)));
+        assert_eq!(
+            state.data.balances.get(&burn_address().to_str()),
+            Some(&BigUint::from(40 as u8))
+        );
+
+        let theft = Transaction::new(
+            0,
+            burn_address(),
+            blake3::hash_slice(b"alice"),
+            BigUint::from(40 as u8),
+            b"",
+            vec![],
+        );
+        assert_eq!(
+            check(&theft.transaction_data),
+            Err(BurnError::SpendsBurnedFunds)
+        );
+
+        // Even if the transaction made it into the ledger, the burned funds would stay put
+        let state = theft.execute(Some(state));
+        assert_eq!(
+            state.data.balances.get(&burn_address().to_str()),
+            Some(&BigUint::from(40 as u8))
+        );
+    }
+}
@@ -0,0 +1,469 @@
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io::{self, Read, Write},
+}; // Import the collections & io libraries
+
+use super::{graph::Graph, transaction::Transaction}; // Import the ledger types
+
+use super::super::super::crypto::hash::Hash; // Import the hash type
+
+/// The bytes that every chain file begins with.
+pub const CHAIN_FILE_MAGIC: &[u8; 8] = b"SMCCHAIN";
+
+/// The version of the chain file format written by this build. Files of any other version are refused.
+pub const CHAIN_FILE_VERSION: u32 = 1;
+
+/// The largest header or transaction record, in bytes, that a chain file may hold. Anything larger is refused, so
+/// that a corrupt length prefix can't exhaust the memory of whoever reads the file.
+pub const MAX_RECORD_SIZE: u32 = 16 * 1024 * 1024;
+
+/// An error encountered while exporting or reading a chain file.
+#[derive(Debug, Fail)]
+pub enum ChainFileError {
+    #[fail(
+        display = "not a SummerCash chain file (the file doesn't begin with the chain file header)"
+    )]
+    NotAChainFile,
+    #[fail(
+        display = "unsupported chain file version {} (this build reads version {})",
+        version, CHAIN_FILE_VERSION
+    )]
+    UnsupportedVersion {
+        version: u32, // The version of the file
+    },
+    #[fail(display = "the chain file is malformed: {}", error)]
+    Malformed {
+        error: String, // The underlying error
+    },
+    #[fail(
+        display = "a record of {} bytes is larger than any chain file may hold",
+        size
+    )]
+    RecordTooLarge {
+        size: u32, // The size given by the record's length prefix
+    },
+    #[fail(
+        display = "transaction {} doesn't match its hash; the file has been tampered with",
+        hash
+    )]
+    TamperedTransaction {
+        hash: String, // The hash that the transaction claims
+    },
+    #[fail(
+        display = "transaction {} appears before its parent {}, or its parent is missing",
+        hash, parent
+    )]
+    MissingParent {
+        hash: String,   // The hash of the transaction
+        parent: String, // The hash of the parent that hadn't been seen yet
+    },
+    #[fail(
+        display = "the chain file begins with {}, rather than the genesis {} named in its header",
+        found, expected
+    )]
+    GenesisMismatch {
+        expected: String, // The genesis named in the header
+        found: String,    // The first transaction in the file
+    },
+    #[fail(
+        display = "the chain file holds {} transactions, but its header promises {}",
+        found, expected
+    )]
+    CountMismatch {
+        expected: u64, // The number of transactions named in the header
+        found: u64,    // The number of transactions actually read
+    },
+    #[fail(display = "the ledger is not empty; chains can only be imported into a fresh node")]
+    NotFresh,
+    #[fail(display = "transaction {} could not be imported: {}", hash, error)]
+    Rejected {
+        hash: String,  // The hash of the transaction
+        error: String, // Why the transaction couldn't be appended to the ledger
+    },
+    #[fail(display = "failed to read or write the chain file: {}", error)]
+    Io {
+        error: String, // The underlying error
+    },
+}
+
+impl From<io::Error> for ChainFileError {
+    /// Converts the given io error into a ChainFileError.
+    fn from(e: io::Error) -> Self {
+        Self::Io {
+            error: e.to_string(),
+        }
+    }
+}
+
+impl From<bincode::Error> for ChainFileError {
+    /// Converts the given serialization error into a ChainFileError.
+    fn from(e: bincode::Error) -> Self {
+        Self::Malformed {
+            error: e.to_string(),
+        }
+    }
+}
+
+impl From<sled::Error> for ChainFileError {
+    /// Converts the given sled error into a ChainFileError.
+    fn from(e: sled::Error) -> Self {
+        Self::Io {
+            error: e.to_string(),
+        }
+    }
+}
+
+/// Describes the history held in a chain file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChainHeader {
+    /// The name of the network that the history belongs to
+    pub network: String,
+
+    /// The hash of the genesis transaction, which is always the first in the file
+    pub genesis: Hash,
+
+    /// The number of transactions held in the file
+    pub transactions: u64,
+
+    /// The time at which the file was exported
+    pub exported_at: DateTime<Utc>,
+}
+
+/// Gets the index of each of the transactions in the given ledger, ordered such that every transaction comes after
+/// each of its parents. Ledger order is kept wherever it already satisfies this, which it usually does.
+///
+/// # Arguments
+///
+/// * `ledger` - The ledger whose transactions should be ordered
+pub fn topological_order(ledger: &Graph) -> Vec<usize> {
+    let mut order = Vec::with_capacity(ledger.nodes.len());
+    let mut placed: HashSet<Hash> = HashSet::new();
+
+    // The transactions waiting on each parent that hasn't been placed yet
+    let mut waiting: HashMap<Hash, Vec<usize>> = HashMap::new();
+
+    for index in 0..ledger.nodes.len() {
+        let mut ready = VecDeque::new();
+        ready.push_back(index);
+
+        while let Some(index) = ready.pop_front() {
+            let node = &ledger.nodes[index];
+
+            // Wait on the first parent that hasn't been placed, if any. Parents that aren't in the ledger at all
+            // can never be placed, so they're ignored.
+            if let Some(parent) = node
+                .transaction
+                .transaction_data
+                .parents
+                .iter()
+                .find(|parent| {
+                    !placed.contains(*parent) && ledger.hash_routes.contains_key(*parent)
+                })
+            {
+                waiting.entry(*parent).or_insert_with(Vec::new).push(index);
+
+                continue;
+            }
+
+            order.push(index);
+            placed.insert(node.hash);
+
+            // Anything waiting on this transaction may now be ready
+            if let Some(children) = waiting.remove(&node.hash) {
+                ready.extend(children);
+            }
+        }
+    }
+
+    order
+}
+
+/// Writes the given ledger to the given writer as a chain file: a header naming the network, genesis and number of
+/// transactions, followed by each transaction, parents first, each prefixed by its length. States are left out, since
+/// they're recomputed on import.
+///
+/// # Arguments
+///
+/// * `ledger` - The ledger that should be exported
+/// * `network` - The name of the network that the ledger belongs to
+/// * `writer` - Where the chain file should be written
+pub fn export<W: Write>(
+    ledger: &Graph,
+    network: &str,
+    writer: &mut W,
+) -> Result<ChainHeader, ChainFileError> {
+    let order = topological_order(ledger);
+
+    // The genesis has no parents, so it always comes first
+    let genesis = match order.first() {
+        Some(index) => ledger.nodes[*index].hash,
+        None => {
+            return Err(ChainFileError::Malformed {
+                error: "the ledger has no genesis".to_owned(),
+            })
+        }
+    };
+
+    let header = ChainHeader {
+        network: network.to_owned(),
+        genesis,
+        transactions: order.len() as u64,
+        exported_at: Utc::now(),
+    };
+
+    writer.write_all(CHAIN_FILE_MAGIC)?;
+    writer.write_all(&CHAIN_FILE_VERSION.to_le_bytes())?;
+    write_record(writer, &bincode::serialize(&header)?)?;
+
+    for index in order {
+        write_record(writer, &ledger.nodes[index].transaction.to_bytes())?;
+    }
+
+    writer.flush()?;
+
+    Ok(header)
+}
+
+/// Writes the given bytes, prefixed by their length.
+fn write_record<W: Write>(writer: &mut W, bytes: &[u8]) -> Result<(), ChainFileError> {
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(bytes)?;
+
+    Ok(())
+}
+
+/// Reads the transactions in a chain file one at a time, checking that each matches its hash and comes after each of
+/// its parents, that the file begins with the genesis named in its header, and that it holds as many transactions as
+/// its header promises.
+pub struct ChainReader<R: Read> {
+    /// The header at the start of the file
+    pub header: ChainHeader,
+
+    /// Where the rest of the file is read from
+    reader: R,
+
+    /// The hash of each of the transactions read so far
+    seen: HashSet<Hash>,
+
+    /// Whether or not the reader has stopped, either at the end of the file or at an error
+    done: bool,
+}
+
+impl<R: Read> ChainReader<R> {
+    /// Reads the header of a chain file, leaving its transactions to be read through the iterator.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - Where the chain file should be read from
+    pub fn open(mut reader: R) -> Result<Self, ChainFileError> {
+        let mut magic = [0u8; 8];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|_| ChainFileError::NotAChainFile)?;
+        if &magic != CHAIN_FILE_MAGIC {
+            return Err(ChainFileError::NotAChainFile);
+        }
+
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_le_bytes(version);
+        if version != CHAIN_FILE_VERSION {
+            return Err(ChainFileError::UnsupportedVersion { version });
+        }
+
+        let header = match read_record(&mut reader)? {
+            Some(bytes) => bincode::deserialize(&bytes)?,
+            None => {
+                return Err(ChainFileError::Malformed {
+                    error: "the file ends before its header".to_owned(),
+                })
+            }
+        };
+
+        Ok(Self {
+            header,
+            reader,
+            seen: HashSet::new(),
+            done: false,
+        })
+    }
+
+    /// Reads the next transaction in the file, and checks that it fits in with the transactions before it.
+    fn next_transaction(&mut self) -> Result<Option<Transaction>, ChainFileError> {
+        let read = self.seen.len() as u64;
+
+        let bytes = match read_record(&mut self.reader)? {
+            Some(bytes) => bytes,
+            None if read == self.header.transactions => return Ok(None),
+            None => {
+                return Err(ChainFileError::CountMismatch {
+                    expected: self.header.transactions,
+                    found: read,
+                })
+            }
+        };
+        if read == self.header.transactions {
+            return Err(ChainFileError::CountMismatch {
+                expected: self.header.transactions,
+                found: read + 1,
+            });
+        }
+
+        let tx: Transaction = bincode::deserialize(&bytes)?;
+
+        if tx.hash != tx.transaction_data.hash() {
+            return Err(ChainFileError::TamperedTransaction {
+                hash: tx.hash.to_str(),
+            });
+        }
+
+        if read == 0 && tx.hash != self.header.genesis {
+            return Err(ChainFileError::GenesisMismatch {
+                expected: self.header.genesis.to_str(),
+                found: tx.hash.to_str(),
+            });
+        }
+
+        if let Some(parent) = tx
+            .transaction_data
+            .parents
+            .iter()
+            .find(|parent| !self.seen.contains(*parent))
+        {
+            return Err(ChainFileError::MissingParent {
+                hash: tx.hash.to_str(),
+                parent: parent.to_str(),
+            });
+        }
+
+        self.seen.insert(tx.hash);
+
+        Ok(Some(tx))
+    }
+}
+
+impl<R: Read> Iterator for ChainReader<R> {
+    type Item = Result<Transaction, ChainFileError>;
+
+    /// Reads the next transaction in the file. The reader stops at the first error.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.next_transaction() {
+            Ok(Some(tx)) => Some(Ok(tx)),
+            Ok(None) => {
+                self.done = true;
+
+                None
+            }
+            Err(e) => {
+                self.done = true;
+
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Reads a length-prefixed record, or None if the reader is already at the end of the file.
+fn read_record<R: Read>(reader: &mut R) -> Result<Option<Vec<u8>>, ChainFileError> {
+    let mut len = [0u8; 4];
+
+    // Tell a clean end of the file apart from one that cuts a length prefix short
+    let mut filled = 0;
+    while filled < len.len() {
+        match reader.read(&mut len[filled..])? {
+            0 if filled == 0 => return Ok(None),
+            0 => {
+                return Err(ChainFileError::Malformed {
+                    error: "the file ends partway through a record".to_owned(),
+                })
+            }
+            n => filled += n,
+        }
+    }
+
+    let len = u32::from_le_bytes(len);
+    if len > MAX_RECORD_SIZE {
+        return Err(ChainFileError::RecordTooLarge { size: len });
+    }
+
+    let mut bytes = vec![0u8; len as usize];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|_| ChainFileError::Malformed {
+            error: "the file ends partway through a record".to_owned(),
+        })?;
+
+    Ok(Some(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::{common::io as data_io, crypto::hash}; // Import the io & hash modules
+    use super::*; // Import names from our parent module
+
+    use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+    use rand::Rng; // Import the rng
+
+    #[test]
+    fn test_export() {
+        let root = Transaction::new(
+            0,
+            Default::default(),
+            Default::default(),
+            BigUint::zero(),
+            b"genesis",
+            vec![],
+        );
+        let root_hash = root.hash;
+        let child = Transaction::new(
+            1,
+            Default::default(),
+            Default::default(),
+            BigUint::zero(),
+            b"child",
+            vec![root_hash],
+        );
+        let child_hash = child.hash;
+
+        let mut ledger = Graph::new_with_db_path(
+            root,
+            &format!(
+                "{}/.tests/chainfile_{}",
+                data_io::db_dir(),
+                rand::thread_rng().gen::<u16>()
+            ),
+        );
+        ledger.push(child, None);
+
+        let mut file = Vec::new();
+        let header = export(&ledger, "olympia", &mut file).unwrap();
+        assert_eq!(header.genesis, root_hash);
+        assert_eq!(header.transactions, 2);
+
+        // The file can be read back, parents first
+        let reader = ChainReader::open(&file[..]).unwrap();
+        assert_eq!(reader.header, header);
+        assert_eq!(
+            reader
+                .map(|tx| tx.unwrap().hash)
+                .collect::<Vec<hash::Hash>>(),
+            vec![root_hash, child_hash]
+        );
+
+        // A truncated file falls short of its header's promise
+        let truncated = &file[..file.len() - 1];
+        assert!(ChainReader::open(truncated).unwrap().any(|tx| tx.is_err()));
+
+        // Anything that isn't a chain file is refused outright
+        assert!(ChainReader::open(&b"SMCBAK"[..]).is_err());
+    }
+}
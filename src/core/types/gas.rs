@@ -0,0 +1,259 @@
+use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+use std::collections::HashMap;
+
+use super::batch::BatchTransfer; // Import the multi-recipient batch transfer type
+use super::issuance::{self, Mint}; // Import the issuance address & mints
+use super::transaction::TransactionData; // Import the transaction type
+
+use super::super::super::{common::address::Address, crypto::blake3}; // Import the address & hashing modules
+
+/// The gas used by every transaction, however little it does.
+pub const BASE_GAS: u64 = 1_000;
+
+/// The gas used by each byte of a transaction's payload.
+pub const PAYLOAD_BYTE_GAS: u64 = 10;
+
+/// The gas used by each recipient of a batch transfer, on top of the transaction's base gas.
+pub const BATCH_RECIPIENT_GAS: u64 = 500;
+
+/// An error encountered while checking a transaction's gas limit and price.
+#[derive(Debug, Fail, PartialEq)]
+pub enum GasError {
+    #[fail(
+        display = "the transaction needs {} gas, but its gas limit is only {}",
+        required, limit
+    )]
+    LimitTooLow {
+        required: u64, // The gas used by executing the transaction
+        limit: u64,    // The most gas that the transaction may use
+    },
+    #[fail(display = "transactions sent from {} can't pay fees", sender)]
+    ExemptSender {
+        sender: Address, // The address that the transaction was sent from
+    },
+}
+
+/// Gets the address that fees are collected in until the next mint pays them out to the network's voting accounts.
+/// Nobody holds its key; its balance only ever leaves it along with a mint.
+pub fn fee_pool_address() -> Address {
+    blake3::hash_slice(b"fee_pool")
+}
+
+/// Gets the gas used by executing the given transaction data.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction
+pub fn required(tx: &TransactionData) -> u64 {
+    let recipients = match BatchTransfer::from_payload(&tx.payload) {
+        Ok(Some(batch)) => batch.transfers.len() as u64,
+        _ => 0,
+    };
+
+    BASE_GAS
+        .saturating_add((tx.payload.len() as u64).saturating_mul(PAYLOAD_BYTE_GAS))
+        .saturating_add(recipients.saturating_mul(BATCH_RECIPIENT_GAS))
+}
+
+/// Gets the number of finks that the given transaction data pays in fees: the gas it uses, at its gas price.
+/// Transactions without a gas price are free.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction
+pub fn fee(tx: &TransactionData) -> BigUint {
+    BigUint::from(required(tx).min(tx.gas_limit)) * &tx.gas_price
+}
+
+/// Gets the most that the given transaction data could pay in fees: its gas limit, at its gas price.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction
+pub fn max_fee(tx: &TransactionData) -> BigUint {
+    BigUint::from(tx.gas_limit) * &tx.gas_price
+}
+
+/// Gets the most that sending the given transaction data could take from its sender: its value, and its max fee.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction
+pub fn max_cost(tx: &TransactionData) -> BigUint {
+    &tx.value + max_fee(tx)
+}
+
+/// Makes sure that the gas limit of the given transaction data covers the gas that it uses, if it pays for its gas at
+/// all. Mints can't pay fees, since the fee pool is emptied by them.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction that should be checked
+pub fn check(tx: &TransactionData) -> Result<(), GasError> {
+    if tx.gas_price.is_zero() {
+        return Ok(());
+    }
+
+    if tx.sender == issuance::issuance_address() || tx.sender == fee_pool_address() {
+        return Err(GasError::ExemptSender { sender: tx.sender });
+    }
+
+    let required = required(tx);
+    if tx.gas_limit < required {
+        return Err(GasError::LimitTooLow {
+            required,
+            limit: tx.gas_limit,
+        });
+    }
+
+    Ok(())
+}
+
+/// Moves the fee paid by the given transaction data from its sender into the fee pool, if the sender can afford it.
+/// Results in whether or not the fee was paid.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction being executed
+/// * `balances` - The balances of each account, which should be updated
+pub fn execute_fee(tx: &TransactionData, balances: &mut HashMap<String, BigUint>) -> bool {
+    let fee = fee(tx);
+    if fee.is_zero() {
+        return true;
+    }
+
+    let sender = tx.sender.to_str();
+    let balance = balances.get(&sender).cloned().unwrap_or_default();
+    if balance < fee {
+        return false;
+    }
+
+    balances.insert(sender, balance - &fee);
+    *balances
+        .entry(fee_pool_address().to_str())
+        .or_insert_with(BigUint::zero) += fee;
+
+    true
+}
+
+/// Pays the fees collected in the fee pool out to the recipients of the mint carried in the given transaction data,
+/// if there is one and it was sent from the issuance address, in proportion to what the mint pays each of them.
+/// Whatever can't be split evenly goes to the mint's first recipient.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction being executed
+/// * `balances` - The balances of each account, which should be updated
+pub fn execute_payout(tx: &TransactionData, balances: &mut HashMap<String, BigUint>) {
+    if tx.sender != issuance::issuance_address() {
+        return;
+    }
+
+    let mint = match Mint::from_payload(&tx.payload) {
+        Ok(Some(mint)) if !mint.transfers.is_empty() => mint,
+        _ => return,
+    };
+
+    let minted = mint.total();
+    if minted.is_zero() {
+        return;
+    }
+
+    let pool = match balances.remove(&fee_pool_address().to_str()) {
+        Some(pool) => pool,
+        None => return,
+    };
+
+    let mut paid = BigUint::zero();
+    for transfer in &mint.transfers {
+        let share = &pool * &transfer.value / &minted;
+        paid += &share;
+
+        *balances
+            .entry(transfer.recipient.to_str())
+            .or_insert_with(BigUint::zero) += share;
+    }
+
+    *balances
+        .entry(mint.transfers[0].recipient.to_str())
+        .or_insert_with(BigUint::zero) += pool - paid;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{batch::Transfer, transaction::Transaction};
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_fees() {
+        let (alice, bob) = (blake3::hash_slice(b"alice"), blake3::hash_slice(b"bob"));
+
+        let mut balances = HashMap::new();
+        balances.insert(alice.to_str(), BigUint::from(100_000 as u32));
+
+        // Transactions without a gas price are free, whatever their gas limit
+        let free = Transaction::new(0, alice, bob, BigUint::from(1 as u8), b"", vec![]);
+        assert_eq!(check(&free.transaction_data), Ok(()));
+        assert!(execute_fee(&free.transaction_data, &mut balances));
+        assert_eq!(balances[&alice.to_str()], BigUint::from(100_000 as u32));
+
+        // Paid transactions need a gas limit covering what they use, but only pay for what they use
+        let paid = Transaction::new(0, alice, bob, BigUint::from(1 as u8), b"memo", vec![])
+            .with_gas(2_000, BigUint::from(10 as u8));
+        assert_eq!(required(&paid.transaction_data), 1_040);
+        assert_eq!(check(&paid.transaction_data), Ok(()));
+        assert_eq!(fee(&paid.transaction_data), BigUint::from(10_400 as u32));
+        assert_eq!(
+            max_cost(&paid.transaction_data),
+            BigUint::from(20_001 as u32)
+        );
+        assert_eq!(
+            check(
+                &paid
+                    .clone()
+                    .with_gas(1_000, BigUint::from(10 as u8))
+                    .transaction_data
+            ),
+            Err(GasError::LimitTooLow {
+                required: 1_040,
+                limit: 1_000
+            })
+        );
+
+        assert!(execute_fee(&paid.transaction_data, &mut balances));
+        assert_eq!(balances[&alice.to_str()], BigUint::from(89_600 as u32));
+        assert_eq!(
+            balances[&fee_pool_address().to_str()],
+            BigUint::from(10_400 as u32)
+        );
+
+        // The next mint pays the pool out alongside the epoch's reward
+        let mint = Mint {
+            epoch: 0,
+            transfers: vec![
+                Transfer {
+                    recipient: bob,
+                    value: BigUint::from(2 as u8),
+                },
+                Transfer {
+                    recipient: alice,
+                    value: BigUint::from(1 as u8),
+                },
+            ],
+            votes: Vec::new(),
+        };
+        let mint_tx = Transaction::new(
+            0,
+            issuance::issuance_address(),
+            issuance::issuance_address(),
+            BigUint::zero(),
+            &mint.to_payload(),
+            vec![],
+        );
+        execute_payout(&mint_tx.transaction_data, &mut balances);
+        assert_eq!(balances[&bob.to_str()], BigUint::from(6_934 as u32));
+        assert_eq!(balances[&alice.to_str()], BigUint::from(93_066 as u32));
+        assert!(!balances.contains_key(&fee_pool_address().to_str()));
+    }
+}
@@ -1,7 +1,154 @@
 use super::super::super::common::address::Address;
+use super::vesting::{TimeLock, Unlock};
+use chrono::{TimeZone, Utc};
 use num::{BigUint, FromPrimitive, Zero};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, default::Default, fs::File, io::BufReader};
+use std::{collections::HashMap, default::Default, fmt, fs::File, io::BufReader, str::FromStr};
+
+/// The bytes that the payload of a genesis child sent to the holder of a role begins with, followed by the role's
+/// name.
+pub const GENESIS_ROLE_PAYLOAD_PREFIX: &[u8] = b"genesis_role:";
+
+/// The payload of the transaction wrapping up the genesis.
+pub const GENESIS_FINALIZATION_PAYLOAD: &[u8] = b"genesis_finalization";
+
+/// An error encountered while building a genesis configuration.
+#[derive(Debug, Fail)]
+pub enum GenesisError {
+    #[fail(
+        display = "the locks on {} add up to {} finks, but only {} finks are allocated to it",
+        address, locked, allocated
+    )]
+    LockExceedsAllocation {
+        address: Address,   // The address whose allocation is over-locked
+        locked: BigUint,    // The total amount locked
+        allocated: BigUint, // The amount allocated to the address
+    },
+    #[fail(
+        display = "each lock on {} must name exactly one of unlocks_at or milestone",
+        address
+    )]
+    AmbiguousUnlock {
+        address: Address, // The address that the lock applies to
+    },
+    #[fail(display = "{} is allocated to more than once", address)]
+    DuplicateAllocation {
+        address: Address, // The address allocated to more than once
+    },
+    #[fail(
+        display = "{} is designated the {}, but isn't allocated anything",
+        address, role
+    )]
+    UnallocatedRole {
+        address: Address,  // The address designated the role
+        role: GenesisRole, // The role
+    },
+    #[fail(
+        display = "both {} and {} are designated the {}; only one account may be",
+        first, second, role
+    )]
+    DuplicateRole {
+        role: GenesisRole, // The role
+        first: Address,    // The address designated the role first
+        second: Address,   // The address designated the role second
+    },
+    #[fail(
+        display = "the {} {} can't have any of its allocation locked",
+        role, address
+    )]
+    LockedRole {
+        address: Address,  // The address designated the role
+        role: GenesisRole, // The role, which must be able to spend its allocation right away
+    },
+    #[fail(
+        display = "unknown genesis role {} (expected faucet or treasury)",
+        role
+    )]
+    UnknownRole {
+        role: String, // The role that was asked for
+    },
+}
+
+/// A part that an account allocated to in the genesis is designated to play in the network.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum GenesisRole {
+    /// Hands out small amounts to whoever asks (e.g. on a test network). Its allocation can't be locked.
+    Faucet,
+
+    /// Holds the network's shared funds, to be spent as governance sees fit
+    Treasury,
+}
+
+/// Implement a set of genesis role helper methods.
+impl GenesisRole {
+    /// Gets the name of the role.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Faucet => "faucet",
+            Self::Treasury => "treasury",
+        }
+    }
+
+    /// Checks whether or not an account designated the role must be able to spend its allocation right away.
+    pub fn forbids_locks(self) -> bool {
+        self == Self::Faucet
+    }
+
+    /// Encodes the role as the payload of the genesis child sent to the account designated it.
+    pub fn to_payload(self) -> Vec<u8> {
+        [GENESIS_ROLE_PAYLOAD_PREFIX, self.as_str().as_bytes()].concat()
+    }
+
+    /// Decodes the role carried in the given genesis child payload, if it carries one.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload of the genesis child
+    pub fn from_payload(payload: &[u8]) -> Option<Self> {
+        if !payload.starts_with(GENESIS_ROLE_PAYLOAD_PREFIX) {
+            return None;
+        }
+
+        std::str::from_utf8(&payload[GENESIS_ROLE_PAYLOAD_PREFIX.len()..])
+            .ok()?
+            .parse()
+            .ok()
+    }
+}
+
+impl fmt::Display for GenesisRole {
+    /// Writes the name of the role.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GenesisRole {
+    type Err = GenesisError;
+
+    /// Parses a role written as faucet or treasury.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "faucet" => Ok(Self::Faucet),
+            "treasury" => Ok(Self::Treasury),
+            _ => Err(GenesisError::UnknownRole { role: s.to_owned() }),
+        }
+    }
+}
+
+/// An account designated a role in the genesis, as recorded in the ledger.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RoleAssignment {
+    /// The address of the account
+    pub address: Address,
+
+    /// The role that the account was designated
+    pub role: GenesisRole,
+
+    /// The number of finks allocated to the account
+    pub allocated: BigUint,
+}
 
 /// The configuration for the network's genesis.
 #[derive(Serialize, Deserialize, Default)]
@@ -9,6 +156,14 @@ pub struct Config {
     /// The capital allocated to each user
     pub(crate) alloc: HashMap<Address, BigUint>,
 
+    /// The portions of each user's allocation that can't be spent right away
+    #[serde(default)]
+    pub(crate) locks: HashMap<Address, Vec<TimeLock>>,
+
+    /// The role that each of the designated users plays in the network
+    #[serde(default)]
+    pub(crate) roles: HashMap<Address, GenesisRole>,
+
     /// The total value of the genesis
     total_value: BigUint,
 }
@@ -24,8 +179,155 @@ impl Config {
         // Increment the total value of the allocation
         self.total_value += amount.clone();
 
-        // Put the amount in the alloc map
-        self.alloc.insert(address, amount);
+        // Put the amount in the alloc map, replacing anything allocated to the address before
+        if let Some(replaced) = self.alloc.insert(address, amount) {
+            self.total_value -= replaced;
+        }
+    }
+
+    /// Locks part of the amount allocated to a particular address until the given condition is met.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address whose allocation should be locked
+    /// * `lock` - The amount that should be locked, and until when
+    pub fn lock_allocation(
+        &mut self,
+        address: Address,
+        lock: TimeLock,
+    ) -> Result<(), GenesisError> {
+        let allocated = self.amount_allocated_for_address(address);
+        let locked = self
+            .locks
+            .get(&address)
+            .into_iter()
+            .flatten()
+            .fold(lock.amount.clone(), |locked, lock| locked + &lock.amount);
+
+        // Nobody can be locked out of more than they were given
+        if locked > allocated {
+            return Err(GenesisError::LockExceedsAllocation {
+                address,
+                locked,
+                allocated,
+            });
+        }
+
+        // Some roles need to be able to spend their allocations right away
+        if let Some(role) = self.role_of(address).filter(|role| role.forbids_locks()) {
+            return Err(GenesisError::LockedRole { address, role });
+        }
+
+        self.locks.entry(address).or_default().push(lock);
+
+        Ok(())
+    }
+
+    /// Designates the given address to play the given role in the network. Only one address may be designated each
+    /// role, and it must be allocated something.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address that should be designated the role
+    /// * `role` - The role that the address should play
+    pub fn assign_role(&mut self, address: Address, role: GenesisRole) -> Result<(), GenesisError> {
+        if self.amount_allocated_for_address(address).is_zero() {
+            return Err(GenesisError::UnallocatedRole { address, role });
+        }
+
+        if let Some(holder) = self.holder_of(role).filter(|holder| *holder != address) {
+            return Err(GenesisError::DuplicateRole {
+                role,
+                first: holder,
+                second: address,
+            });
+        }
+
+        if role.forbids_locks() && !self.locks_for_address(address).is_empty() {
+            return Err(GenesisError::LockedRole { address, role });
+        }
+
+        self.roles.insert(address, role);
+
+        Ok(())
+    }
+
+    /// Gets the role that the given address is designated to play, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address whose role should be returned
+    pub fn role_of(&self, address: Address) -> Option<GenesisRole> {
+        self.roles.get(&address).copied()
+    }
+
+    /// Gets the address designated the given role, if any is.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - The role whose holder should be returned
+    pub fn holder_of(&self, role: GenesisRole) -> Option<Address> {
+        self.roles
+            .iter()
+            .find(|(_, held)| **held == role)
+            .map(|(address, _)| *address)
+    }
+
+    /// Makes sure that the configuration is consistent with itself: no allocation is locked beyond its amount, and
+    /// each role is held by a single address that is allocated something, and may spend it as the role requires.
+    /// Configurations that weren't built up through the checked helpers (e.g. deserialized ones) should be validated
+    /// before use.
+    pub fn validate(&self) -> Result<(), GenesisError> {
+        for (address, locks) in self.locks.iter() {
+            let allocated = self.amount_allocated_for_address(*address);
+            let locked = locks
+                .iter()
+                .fold(BigUint::zero(), |locked, lock| locked + &lock.amount);
+
+            if locked > allocated {
+                return Err(GenesisError::LockExceedsAllocation {
+                    address: *address,
+                    locked,
+                    allocated,
+                });
+            }
+        }
+
+        let mut holders: HashMap<GenesisRole, Address> = HashMap::new();
+        for (address, role) in self.roles.iter() {
+            if self.amount_allocated_for_address(*address).is_zero() {
+                return Err(GenesisError::UnallocatedRole {
+                    address: *address,
+                    role: *role,
+                });
+            }
+
+            if role.forbids_locks() && !self.locks_for_address(*address).is_empty() {
+                return Err(GenesisError::LockedRole {
+                    address: *address,
+                    role: *role,
+                });
+            }
+
+            if let Some(first) = holders.insert(*role, *address) {
+                return Err(GenesisError::DuplicateRole {
+                    role: *role,
+                    first,
+                    second: *address,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets each of the locks placed on the allocation of the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address whose locks should be returned
+    pub fn locks_for_address(&self, address: Address) -> &[TimeLock] {
+        self.locks.get(&address).map_or(&[][..], Vec::as_slice)
     }
 
     /// Identifies the amount of coins allocated to the given address.
@@ -47,9 +349,36 @@ impl Config {
     /// Reads a genesis configuration from the given genesis file in a given data dir.
     pub fn read_from_file(file_name: &str) -> Result<Self, failure::Error> {
         /// The raw configuration stored on disk, in JSON format with hex addresses, rather than inline vecs.
+        /// Allocations may be given as flat address to amount pairs, or as entries carrying their own vesting
+        /// schedules and roles.
         #[derive(Deserialize)]
         struct RawConfig {
+            #[serde(default)]
             alloc: HashMap<String, u128>,
+            #[serde(default)]
+            vesting: HashMap<String, Vec<RawLock>>,
+            #[serde(default)]
+            allocations: Vec<RawAllocation>,
+        };
+
+        /// An allocation to an address, along with the locks on it and the role that the address plays, if any.
+        #[derive(Deserialize)]
+        struct RawAllocation {
+            address: String,
+            amount: u128,
+            #[serde(default)]
+            vesting: Vec<RawLock>,
+            #[serde(default)]
+            role: Option<GenesisRole>,
+        };
+
+        /// A lock on part of an allocation, unlocking either at a UNIX timestamp, or once the ledger is a number of
+        /// transactions deep.
+        #[derive(Deserialize)]
+        struct RawLock {
+            amount: u128,
+            unlocks_at: Option<i64>,
+            milestone: Option<u64>,
         };
 
         // Open the genesis configuration file
@@ -73,7 +402,94 @@ impl Config {
             );
         }
 
+        // Each of the allocation entries brings its own locks & role along with it
+        let mut vesting: Vec<(Address, &Vec<RawLock>)> = raw_cfg
+            .vesting
+            .iter()
+            .map(|(address, locks)| (Address::from(&address[..]), locks))
+            .collect();
+        let mut roles: Vec<(Address, GenesisRole)> = Vec::new();
+        for allocation in raw_cfg.allocations.iter() {
+            let address = Address::from(&allocation.address[..]);
+
+            // An address allocated to twice would have one of its allocations silently dropped
+            if final_cfg.alloc.contains_key(&address) {
+                return Err(GenesisError::DuplicateAllocation { address }.into());
+            }
+
+            final_cfg.allocate_to_address(
+                address,
+                BigUint::from_u128(allocation.amount).unwrap_or_default(),
+            );
+            vesting.push((address, &allocation.vesting));
+            roles.extend(allocation.role.map(|role| (address, role)));
+        }
+
+        // Lock up any of the allocations that vest over time
+        for (address, locks) in vesting {
+            for lock in locks {
+                let unlock = match (lock.unlocks_at, lock.milestone) {
+                    (Some(unlocks_at), None) => Unlock::At(Utc.timestamp(unlocks_at, 0)),
+                    (None, Some(milestone)) => Unlock::Milestone(milestone),
+                    _ => return Err(GenesisError::AmbiguousUnlock { address }.into()),
+                };
+
+                final_cfg.lock_allocation(
+                    address,
+                    TimeLock {
+                        amount: BigUint::from_u128(lock.amount).unwrap_or_default(),
+                        unlock,
+                    },
+                )?;
+            }
+        }
+
+        // Designate the accounts playing special roles, now that their allocations are known
+        for (address, role) in roles {
+            final_cfg.assign_role(address, role)?;
+        }
+
         // Return the final configuration instance
         Ok(final_cfg)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_roles() {
+        let (faucet, treasury) = (Address::new(vec![1; 32]), Address::new(vec![2; 32]));
+
+        let mut cfg = Config::default();
+        cfg.allocate_to_address(faucet, BigUint::from(100 as u8));
+        cfg.allocate_to_address(treasury, BigUint::from(50 as u8));
+        cfg.allocate_to_address(treasury, BigUint::from(200 as u8));
+        assert_eq!(cfg.issuance(), BigUint::from(300 as u16));
+
+        // Roles go to accounts that were allocated something, one account apiece
+        assert!(cfg
+            .assign_role(Address::new(vec![3; 32]), GenesisRole::Treasury)
+            .is_err());
+        cfg.assign_role(faucet, GenesisRole::Faucet).unwrap();
+        cfg.assign_role(treasury, GenesisRole::Treasury).unwrap();
+        assert!(cfg.assign_role(faucet, GenesisRole::Treasury).is_err());
+        assert_eq!(cfg.holder_of(GenesisRole::Treasury), Some(treasury));
+
+        // The faucet needs to be able to hand its allocation out right away
+        let lock = TimeLock {
+            amount: BigUint::from(10 as u8),
+            unlock: Unlock::Milestone(10),
+        };
+        assert!(cfg.lock_allocation(faucet, lock.clone()).is_err());
+        cfg.lock_allocation(treasury, lock).unwrap();
+        assert!(cfg.validate().is_ok());
+
+        assert_eq!(
+            GenesisRole::from_payload(&GenesisRole::Treasury.to_payload()),
+            Some(GenesisRole::Treasury)
+        );
+        assert_eq!(GenesisRole::from_payload(b"genesis_child"), None);
+    }
+}
@@ -1,16 +1,82 @@
+use super::activity::{ActivityIndex, DormancyReport}; // Import the account activity index
+use super::burn; // Import the burn address
+use super::genesis::{GenesisRole, RoleAssignment, GENESIS_FINALIZATION_PAYLOAD}; // Import the genesis roles
+use super::guardians::GuardianRecord; // Import the account recovery guardians
+use super::issuance::{self, Mint, MintRecord}; // Import the issuance types
+use super::migrations; // Import the database migrations
+use super::payloads::PayloadStore; // Import the deduplicated payload store
+use super::receipt::Receipt; // Import the receipt type
+use super::snapshot::{
+    Snapshot, SnapshotError, SnapshotGenesis, SnapshotTree, BASE_HEIGHT_META, LATEST_SNAPSHOT_META,
+    SNAPSHOTS_TREE, SNAPSHOT_GENESIS_META, SNAPSHOT_TREES,
+}; // Import the ledger snapshots
 use super::state::{self, Entry}; // Import state module
 use super::transaction; // Import transaction types
+use super::vesting::{self, LockedBalance, VestingSchedule}; // Import the time-locked balance types
+
+use chrono::{DateTime, Utc}; // Import time library
 
 use num::{bigint::BigUint, Zero};
-use std::collections; // Import collections, io modules
+use rand::Rng; // Import the rand library
+use std::{collections, str::FromStr, sync::Arc}; // Import collections, sync modules
 
 use serde::{Deserialize, Serialize}; // Import serde serialization
 
 use super::super::super::{
-    common::{address::Address, io},
-    crypto::hash::{self, Hash},
+    common::{
+        address::{Address, AddressKind},
+        io,
+    },
+    crypto::{
+        hash::{self, Hash},
+        short_id::{self, ShortIdError},
+    },
 }; // Import address, hash types
 
+/// The name of the tree in the graph's database in which the activity of each account is indexed.
+pub const ACTIVITY_TREE: &str = "activity";
+
+/// The metadata key marking that every transaction in the graph has been recorded in the account activity index.
+pub const ACTIVITY_INDEXED_META: &str = "activity_indexed";
+
+/// The name of the tree in the graph's database in which transaction receipts are stored.
+pub const RECEIPTS_TREE: &str = "receipts";
+
+/// The name of the tree in the graph's database in which the origins of each known state entry are indexed.
+pub const STATE_ORIGINS_TREE: &str = "state_origins";
+
+/// The name of the tree in the graph's database in which each executed mint is recorded, by epoch.
+pub const MINTS_TREE: &str = "mints";
+
+/// The name of the tree in the graph's database in which an archive graph keeps the state resulting from each of its
+/// nodes, and a pruned graph the states that it has paged out, by transaction hash.
+pub const STATES_TREE: &str = "states";
+
+/// The name of the tree in the graph's database in which the hash of the state resulting from each node is kept once
+/// the state itself has been purged from memory, by transaction hash.
+pub const STATE_HASHES_TREE: &str = "state_hashes";
+
+/// The name of the tree in the graph's database in which the payload of each transaction is stored, by its hash, so
+/// that repeated payloads are only stored once.
+pub const PAYLOADS_TREE: &str = "payloads";
+
+/// The name of the tree in the graph's database in which metadata describing the database itself is stored.
+pub const META_TREE: &str = "meta";
+
+/// The number of tips that a new transaction should reference as its parents, by default.
+pub const DEFAULT_TIPS_PER_TRANSACTION: usize = 2;
+
+/// The greatest number of generations of unexecuted ancestors that may be walked while resolving a transaction's
+/// parents, by default.
+pub const DEFAULT_MAX_RESOLUTION_DEPTH: usize = 4096;
+
+/// The greatest number of unexecuted ancestors that may be executed while resolving a transaction's parents, by
+/// default.
+pub const DEFAULT_MAX_RESOLUTION_NODES: usize = 65536;
+
+/// The number of recent nodes whose states a pruned graph keeps in memory, by default.
+pub const DEFAULT_RECENT_NODES: usize = 1024;
+
 /// An error encountered while signing a tx.
 #[derive(Debug, Fail)]
 pub enum OperationError {
@@ -31,6 +97,114 @@ pub enum OperationError {
     },
 }
 
+/// An error encountered while resolving the states of a transaction's parents.
+#[derive(Debug, Fail)]
+pub enum ResolutionError {
+    #[fail(
+        display = "refusing to resolve transaction {}; its unexecuted ancestors are nested more than {} deep",
+        transaction_hash, max_depth
+    )]
+    TooDeep {
+        transaction_hash: String, // The hash of the transaction past the depth limit
+        max_depth: usize,         // The greatest depth that may be walked
+    },
+    #[fail(
+        display = "refusing to resolve parents requiring more than {} unexecuted transactions to be executed",
+        max_nodes
+    )]
+    TooManyNodes {
+        max_nodes: usize, // The greatest number of transactions that may be executed
+    },
+    #[fail(
+        display = "encountered a storage error while resolving parents: {}",
+        error
+    )]
+    Storage {
+        error: String, // The error
+    },
+}
+
+impl From<sled::Error> for ResolutionError {
+    /// Converts the given sled error into a ResolutionError.
+    fn from(e: sled::Error) -> Self {
+        Self::Storage {
+            error: e.to_string(),
+        }
+    }
+}
+
+/// The limits placed on the work done resolving the states of a transaction's parents, so that a peer can't make the
+/// node walk (or execute) an arbitrarily large unexecuted history.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResolutionLimits {
+    /// The greatest number of generations of unexecuted ancestors that may be walked
+    pub max_depth: usize,
+    /// The greatest number of unexecuted ancestors that may be executed
+    pub max_nodes: usize,
+}
+
+impl Default for ResolutionLimits {
+    /// Gets the default resolution limits.
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_RESOLUTION_DEPTH,
+            max_nodes: DEFAULT_MAX_RESOLUTION_NODES,
+        }
+    }
+}
+
+/// An error encountered while parsing a storage mode.
+#[derive(Debug, Fail, PartialEq)]
+pub enum StorageModeError {
+    #[fail(
+        display = "unknown storage mode {} (expected archive, pruned, or light)",
+        mode
+    )]
+    UnknownMode {
+        mode: String, // The mode that was asked for
+    },
+}
+
+/// How much of the ledger's history a graph keeps around. Whatever the mode, the header of each node is held in memory;
+/// the modes differ in which states are.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageMode {
+    /// Keep every state that has been loaded in memory, and the state resulting from each node on the disk, so that
+    /// the state (and balances) of the ledger as of any node can be looked up later on
+    Archive,
+
+    /// Keep the states of the executed head and the given number of the most recent nodes in memory, paging older
+    /// states out to the disk, and back in as they're needed
+    Pruned { recent_nodes: usize },
+
+    /// Keep only the state of the executed head in memory, paging every other state out to the disk
+    Light,
+}
+
+impl Default for StorageMode {
+    /// Gets the default storage mode: pruned, keeping the default number of recent nodes in memory.
+    fn default() -> Self {
+        Self::Pruned {
+            recent_nodes: DEFAULT_RECENT_NODES,
+        }
+    }
+}
+
+impl FromStr for StorageMode {
+    type Err = StorageModeError;
+
+    /// Parses a mode written as archive, pruned (keeping the default number of recent nodes), or light.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "archive" => Ok(Self::Archive),
+            "pruned" => Ok(Self::default()),
+            "light" => Ok(Self::Light),
+            _ => Err(StorageModeError::UnknownMode { mode: s.to_owned() }),
+        }
+    }
+}
+
 /// A node in any particular state-entry/transaction-based DAG.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Node {
@@ -42,17 +216,53 @@ pub struct Node {
     pub hash: hash::Hash,
 }
 
+/// The transactions known to have produced, or cited, a particular state entry.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct StateOrigin {
+    /// The transactions whose execution resulted in the state. A merged parental state is produced by each of the
+    /// parents that were merged together.
+    pub produced_by: Vec<Hash>,
+    /// The transactions that cite the state as their parent state
+    pub cited_by: Vec<Hash>,
+}
+
 /// A generic DAG used to store state entries, as well as transactions.
-#[derive(Clone)]
+///
+/// The node list and its indices are shared between a graph and any of its snapshots. Mutations
+/// are copy-on-write, so taking a snapshot is cheap, and a graph with no outstanding snapshots
+/// is never copied.
 pub struct Graph {
     /// A list of nodes in the graph
-    pub nodes: Vec<Node>,
+    pub nodes: Arc<Vec<Node>>,
     /// A list of routes to addresses in the graph (by usize index)
-    pub hash_routes: collections::HashMap<hash::Hash, usize>,
+    pub hash_routes: Arc<collections::HashMap<hash::Hash, usize>>,
     /// A list of children for a given node in the graph
-    pub node_children: collections::HashMap<hash::Hash, Vec<hash::Hash>>,
+    pub node_children: Arc<collections::HashMap<hash::Hash, Vec<hash::Hash>>>,
+    /// The addresses of the contracts deployed by transactions in the graph
+    contracts: Arc<collections::HashSet<Address>>,
     /// A persisted database instance
     db: Option<sled::Db>,
+    /// The directory holding the database, if there is one
+    db_path: Option<String>,
+    /// Whether or not the disk is too full for writes that the ledger can do without (e.g. indices & archives)
+    pressured: bool,
+    /// Whether or not the graph is a read-only view of another graph
+    snapshot: bool,
+    /// How much of the ledger's history the graph keeps around
+    storage_mode: StorageMode,
+    /// The number of transactions preceding the graph's first node, if the graph was installed from a snapshot
+    base_height: u64,
+    /// The limits placed on the work done resolving the states of a transaction's parents
+    resolution_limits: ResolutionLimits,
+}
+
+/// Gets the address of the contract that the given transaction deploys, if it deploys one.
+fn deployed_contract(transaction: &transaction::Transaction) -> Option<Address> {
+    if transaction.contract_creation {
+        transaction.deployed_contract_address
+    } else {
+        None
+    }
 }
 
 /// Implement a set of node helper methods.
@@ -179,12 +389,55 @@ impl Node {
     }
 }
 
+/// Gets the key under which the node at the given index is stored in a graph's database. Keys are big-endian, so
+/// iterating over the database visits nodes in the order that they were added to the graph.
+///
+/// # Arguments
+///
+/// * `index` - The index of the node in the graph
+pub fn node_key(index: usize) -> [u8; 8] {
+    (index as u64).to_be_bytes()
+}
+
+/// Applies the given modification to the origin of the state entry with the given hash, stored in the given tree.
+///
+/// # Arguments
+///
+/// * `tree` - The tree in which state origins are indexed
+/// * `state_hash` - The hash of the state entry
+/// * `modify` - The change that should be made to the state's origin
+pub(crate) fn update_state_origin<F: FnOnce(&mut StateOrigin)>(
+    tree: &sled::Tree,
+    state_hash: Hash,
+    modify: F,
+) -> Result<(), sled::Error> {
+    // Get the existing origin of the state, if there is one
+    let mut origin: StateOrigin = match tree.get(state_hash)? {
+        Some(encoded) => bincode::deserialize(&encoded).unwrap_or_default(),
+        None => StateOrigin::default(),
+    };
+
+    modify(&mut origin); // Make the change
+
+    // Serialize the origin so that we can put it back in the db
+    let encoded =
+        bincode::serialize(&origin).map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+    tree.insert(state_hash, encoded)?;
+
+    Ok(()) // Done!
+}
+
 /// We'll want to make sure everything has been closed before the Graph can be deallocated.
 impl Drop for Graph {
     /// Deallocates the graph.
     fn drop(&mut self) {
-        // Save the graph
-        self.write_to_disk().unwrap();
+        // Save the graph, unless it's a snapshot of one that will save itself. The graph can't be saved if the disk
+        // has stopped taking writes, but that's no reason to panic on the way out.
+        if !self.snapshot {
+            if let Err(e) = self.write_to_disk() {
+                error!("Failed to persist the ledger on the way out: {}", e);
+            }
+        }
 
         // Deallocate the db
         self.db.take();
@@ -270,442 +523,1378 @@ impl Graph {
         let mut hash_routes = collections::HashMap::new(); // Initialize address routes map
         hash_routes.insert(root_transaction_hash, 0); // Set root transaction route
 
-        Graph {
-            nodes: vec![Node {
+        let db = sled::open(db_path).unwrap(); // Open database
+
+        // Make sure the database is marked as using the current layout
+        if let Err(e) = migrations::migrate(&db) {
+            error!("Failed to migrate the database in {}: {}", db_path, e);
+        }
+
+        let root_state_hash = root_transaction_state_entry.hash; // Clone root state hash
+        let contracts = deployed_contract(&root_transaction).into_iter().collect(); // Index the root's contract
+
+        let graph = Graph {
+            nodes: Arc::new(vec![Node {
                 transaction: root_transaction,                   // Set transaction
                 state_entry: Some(root_transaction_state_entry), // Set state entry
                 hash: root_transaction_hash,                     // Set hash
-            }], // Set nodes
-            hash_routes,                                // Set address routes
-            node_children: collections::HashMap::new(), // Set node children
-            db: Some(sled::open(db_path).unwrap()),     // Set db
-        } // Return initialized dag
+            }]), // Set nodes
+            hash_routes: Arc::new(hash_routes), // Set address routes
+            node_children: Arc::new(collections::HashMap::new()), // Set node children
+            contracts: Arc::new(contracts),     // Set deployed contracts
+            db: Some(db),                       // Set db
+            db_path: Some(db_path.to_owned()),  // Remember where the db lives
+            pressured: false,                   // Assume there's room until told otherwise
+            snapshot: false,                    // Not a snapshot
+            storage_mode: Default::default(),   // Prune, unless asked not to
+            base_height: 0,                     // Start from the genesis
+            resolution_limits: Default::default(), // Use the default resolution limits
+        }; // Initialize dag
+
+        graph.index_state(root_state_hash, &[root_transaction_hash]); // Remember where the root state came from
+
+        graph // Return initialized dag
     }
 
-    /// Push a new item to the graph.
-    ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// extern crate num; // Link num library
-    /// extern crate rand; // Link rand library
-    ///
-    /// use num::traits::FromPrimitive; // Allow overloading of from_i64()
-    /// use num::bigint::BigUint; // Add support for large unsigned integers
-    ///
-    /// use rand::rngs::OsRng; // Import the os's rng
-    ///
-    /// use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
-    ///
-    /// use summercash::core::types::{transaction, graph}; // Import the transaction, graph libraries
-    /// use summercash::{common::address, crypto::hash}; // Import the address, hash libraries
-    ///
-    /// let mut csprng = OsRng{}; // Generate source of randomness
-    ///
-    /// let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
-    /// let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
-    ///
-    /// let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
-    /// let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
-    ///
-    /// let tx = transaction::Transaction::new(0, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize transaction
-    /// let tx2 = transaction::Transaction::new(1, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize second transaction
-    ///
-    /// let mut dag = graph::Graph::new(tx, "olympia"); // Initialize graph
-    /// let index_of_transaction = dag.push(tx2, None); // Add transaction to DAG
-    ///
-    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
-    /// ```
-    pub fn push(
-        &mut self,
-        transaction: transaction::Transaction,
-        state_entry: Option<state::Entry>,
-    ) -> usize {
-        let transaction_hash = transaction.hash; // Clone transaction hash value
-        let transaction_parents = transaction.transaction_data.parents.clone(); // Clone transaction parents
+    /// Takes a read-only snapshot of the graph. The snapshot shares its nodes and indices with the
+    /// graph it was taken from, rather than copying them; any later changes to the original graph
+    /// will not be reflected in the snapshot. Snapshots are never written to the disk.
+    pub fn snapshot(&self) -> Graph {
+        Graph {
+            nodes: Arc::clone(&self.nodes),                 // Share nodes
+            hash_routes: Arc::clone(&self.hash_routes),     // Share address routes
+            node_children: Arc::clone(&self.node_children), // Share node children
+            contracts: Arc::clone(&self.contracts),         // Share deployed contracts
+            db: self.db.clone(),                            // Share db handle
+            db_path: self.db_path.clone(),                  // Share db path
+            pressured: self.pressured,                      // Share disk pressure
+            snapshot: true,                                 // Mark as snapshot
+            storage_mode: self.storage_mode,                // Share storage mode
+            base_height: self.base_height,                  // Share base height
+            resolution_limits: self.resolution_limits,      // Share resolution limits
+        } // Return snapshot
+    }
 
-        self.nodes.push(Node::new(transaction, state_entry)); // Push node to graph
-        self.hash_routes
-            .insert(transaction_hash, self.nodes.len() - 1); // Set route to node
+    /// Checks whether or not the graph is a snapshot of another graph.
+    pub fn is_snapshot(&self) -> bool {
+        self.snapshot
+    }
 
-        for parent in transaction_parents {
-            self.node_children
-                .entry(parent)
-                .or_insert_with(Vec::new)
-                .push(transaction_hash); // Add transaction as child of parent in graph
-        }
+    /// Checks whether or not the graph keeps the state resulting from each of its nodes.
+    pub fn is_archive(&self) -> bool {
+        self.storage_mode == StorageMode::Archive
+    }
 
-        self.nodes.len() - 1 // Return index of transaction
+    /// Gets how much of the ledger's history the graph keeps around.
+    pub fn storage_mode(&self) -> StorageMode {
+        self.storage_mode
     }
 
-    /// Push a new node to the graph.
+    /// Keeps as much of the ledger's history around as the given mode calls for from now on. Any states that the mode
+    /// doesn't keep in memory are paged out straight away.
     ///
     /// # Arguments
     ///
-    /// * `node` - The node to push to the graph
-    pub fn add(&mut self, node: Node) -> usize {
-        // Put the node in the graph
-        self.push(node.transaction, node.state_entry)
+    /// * `mode` - How much of the ledger's history should be kept around
+    pub fn set_storage_mode(&mut self, mode: StorageMode) -> Result<(), sled::Error> {
+        if mode == StorageMode::Archive {
+            return self.enable_archive();
+        }
+
+        self.storage_mode = mode;
+        self.prune()?;
+
+        Ok(()) // Done!
     }
 
-    /// Purges the contents of each of the nodes in the in-memory graph.
-    pub fn purge(&mut self) {
-        // Go through each of the nodes & manually purge
+    /// Makes the graph keep the state resulting from each of its nodes from now on, so that the state (and balances)
+    /// of the ledger as of any of them can be looked up later on. Each node that has already been executed is
+    /// archived straight away.
+    pub fn enable_archive(&mut self) -> Result<(), sled::Error> {
+        self.storage_mode = StorageMode::Archive;
+
+        let tree = self.open_tree(STATES_TREE)?;
+
         for i in 0..self.nodes.len() {
-            // Reset the state contents of the nodes
-            self.nodes[i].state_entry = None;
+            // Don't bother loading the states of nodes that have already been archived
+            if tree.contains_key(self.nodes[i].hash)? {
+                continue;
+            }
+
+            if let Some(Node {
+                hash,
+                state_entry: Some(entry),
+                ..
+            }) = self.get_pure(i)?
+            {
+                self.put_state(&tree, hash, &entry)?;
+            }
         }
+
+        Ok(()) // Done!
     }
 
-    /// Update an item in the graph.
+    /// Gets the state of the ledger as it stood after the execution of the node with the given hash, if the node is
+    /// in the graph and its state is known.
     ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// extern crate num; // Link num library
-    /// extern crate rand; // Link rand library
-    ///
-    /// use num::traits::FromPrimitive; // Allow overloading of from_i64()
-    /// use num::bigint::BigUint; // Add support for large unsigned integers
-    ///
-    /// use rand::rngs::OsRng; // Import the os's rng
-    ///
-    /// use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
-    ///
-    /// use summercash::core::types::{transaction, graph}; // Import the transaction, graph libraries
-    /// use summercash::{common::address, crypto::hash}; // Import the address, hash libraries
+    /// # Arguments
     ///
-    /// let mut csprng = OsRng{}; // Generate source of randomness
+    /// * `node_hash` - The hash of the node
+    pub fn get_state_at(&self, node_hash: Hash) -> Result<Option<state::Entry>, sled::Error> {
+        // States belonging to nodes that have since been rolled back don't describe the ledger
+        let index = match self.hash_routes.get(&node_hash) {
+            Some(index) => *index,
+            None => return Ok(None),
+        };
+
+        // Look up the archived state, and deserialize it
+        if let Some(encoded) = self.open_tree(STATES_TREE)?.get(node_hash)? {
+            return Ok(Some(
+                bincode::deserialize(&encoded)
+                    .map_err(|e| sled::Error::Unsupported(e.to_string()))?,
+            ));
+        }
+
+        // The node may have been executed before the graph became an archive
+        Ok(self.get_pure(index)?.and_then(|node| node.state_entry))
+    }
+
+    /// Keeps the state resulting from the node with the given hash, if the graph is an archive. Failures are logged,
+    /// rather than returned, since the state can still be recomputed from the DAG.
     ///
-    /// let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
-    /// let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+    /// # Arguments
     ///
-    /// let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
-    /// let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
+    /// * `node_hash` - The hash of the node
+    /// * `entry` - The state resulting from the node
+    pub fn archive_state(&self, node_hash: Hash, entry: &state::Entry) {
+        if !self.is_archive() {
+            return;
+        }
+
+        // The state can be recomputed once there's room for it again
+        if self.pressured {
+            debug!(
+                "Not archiving the state of node {}, since the disk is nearly full",
+                node_hash
+            );
+
+            return;
+        }
+
+        if let Err(e) = self
+            .open_tree(STATES_TREE)
+            .and_then(|tree| self.put_state(&tree, node_hash, entry))
+        {
+            debug!("Failed to archive the state of node {}: {}", node_hash, e);
+        }
+    }
+
+    /// Gets the hash of the state resulting from the node with the given hash, if the node is in the graph and its
+    /// state is known, or was known before being purged.
     ///
-    /// let tx = transaction::Transaction::new(0, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize transaction
-    /// let tx2 = transaction::Transaction::new(1, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize second transaction
+    /// # Arguments
     ///
-    /// let mut dag = graph::Graph::new(tx, "olympia"); // Initialize graph
-    /// dag.update(0, tx2, None); // Update transaction in DAG
-    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
-    /// ```
-    pub fn update(
-        &mut self,
-        index: usize,
-        transaction: transaction::Transaction,
-        state_entry: Option<state::Entry>,
-    ) {
-        self.nodes[index] = Node::new(transaction, state_entry); // Set node in graph
+    /// * `node_hash` - The hash of the node
+    pub fn get_state_hash_at(&self, node_hash: Hash) -> Result<Option<Hash>, sled::Error> {
+        // States belonging to nodes that have since been rolled back don't describe the ledger
+        let index = match self.hash_routes.get(&node_hash) {
+            Some(index) => *index,
+            None => return Ok(None),
+        };
+
+        if let Some(entry) = &self.nodes[index].state_entry {
+            return Ok(Some(entry.hash));
+        }
+
+        // Look up the hash kept when the state was purged
+        if let Ok(tree) = self.open_tree(STATE_HASHES_TREE) {
+            if let Some(retained) = tree.get(node_hash)? {
+                return Ok(Some(Hash::from(&retained[..])));
+            }
+        }
+
+        Ok(self
+            .get_pure(index)?
+            .and_then(|node| node.state_entry)
+            .map(|entry| entry.hash))
     }
 
-    /// Gets a copy of the node at a given index without modifying the graph's state.
-    pub fn get_pure(&self, index: usize) -> Result<Option<Node>, sled::Error> {
-        // Get a copy of the node
-        let mut node = self.nodes[index].clone();
+    /// Pages the states that the graph's storage mode doesn't keep in memory out to the disk, from where they're loaded
+    /// back in as they're needed. The state of the executed head is always kept. Results in the number of states that
+    /// were paged out.
+    pub fn prune(&mut self) -> Result<usize, sled::Error> {
+        let recent_nodes = match self.storage_mode {
+            StorageMode::Archive => return Ok(0),
+            StorageMode::Pruned { recent_nodes } => recent_nodes,
+            StorageMode::Light => 0,
+        };
+
+        // Snapshots share their nodes with the graph that they were taken from, which pages them out itself. States
+        // can't be paged out while there's no room for them on the disk.
+        if self.snapshot || self.pressured {
+            return Ok(0);
+        }
 
-        // Fill the node's state
-        match node.state_entry {
-            Some(_) => Ok(Some(node)),
-            None => {
-                // Check db opened
-                if let Some(db) = &self.db {
-                    let node_query_result = db.get(index.to_string().as_bytes())?; // Query db for node
-
-                    // Handle different result types
-                    match node_query_result {
-                        // Success!
-                        Some(bytes_encoded_node) => {
-                            let deserialized_node: Node =
-                                Node::from_bytes(&bytes_encoded_node.to_vec()[..]); // Deserialize node
-                            node.state_entry = deserialized_node.state_entry; // Set state entry
-
-                            return Ok(Some(node)); // Return deserialized node
-                        }
-                        // Couldn't find node in db
-                        None => return Ok(Some(node)),
-                    };
+        let head = (0..self.nodes.len())
+            .rev()
+            .find(|i| self.nodes[*i].state_entry.is_some());
+        let paged: Vec<usize> = (0..self.nodes.len().saturating_sub(recent_nodes))
+            .filter(|i| Some(*i) != head && self.nodes[*i].state_entry.is_some())
+            .collect();
+        if paged.is_empty() {
+            return Ok(0);
+        }
+
+        // Make sure that each of the states can be loaded back in before letting go of it
+        let tree = self.open_tree(STATES_TREE)?;
+        for i in &paged {
+            let node = &self.nodes[*i];
+
+            if let Some(entry) = &node.state_entry {
+                if !tree.contains_key(node.hash)? {
+                    self.put_state(&tree, node.hash, entry)?;
                 }
+            }
+        }
+
+        let nodes = self.nodes_mut();
+        for i in &paged {
+            nodes[*i].state_entry = None;
+        }
+
+        Ok(paged.len())
+    }
+
+    /// Keeps the hash of the state held in memory for each of the nodes in the graph. Failures are logged, rather than
+    /// returned, since the states can still be recomputed from the DAG; they just can't be checked.
+    fn retain_state_hashes(&self) {
+        // Snapshots are never written to the disk, and the hashes can be kept once there's room for them again
+        if self.snapshot || self.pressured {
+            return;
+        }
 
-                Ok(Some(node)) // Return node, since we can't do a full load anyway
+        if let Err(e) = self.open_tree(STATE_HASHES_TREE).and_then(|tree| {
+            for node in self.nodes.iter() {
+                if let Some(entry) = &node.state_entry {
+                    tree.insert(node.hash, entry.hash.as_ref())?;
+                }
             }
+
+            Ok(())
+        }) {
+            debug!("Failed to keep the hashes of purged states: {}", e);
         }
     }
 
-    /// Get a reference to the node at a given index.
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// extern crate num; // Link num library
-    /// extern crate rand; // Link rand library
-    ///
-    /// use num::traits::FromPrimitive; // Allow overloading of from_i64()
-    /// use num::bigint::BigUint; // Add support for large unsigned integers
-    ///
-    /// use rand::rngs::OsRng; // Import the os's rng
-    ///
-    /// use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    /// Gets the number of transactions in the ledger, counting those preceding the snapshot that the graph was
+    /// installed from, if it was installed from one.
+    pub fn height(&self) -> u64 {
+        self.base_height + self.nodes.len() as u64
+    }
+
+    /// Gets the number of transactions preceding the graph's first node: zero, unless the graph was installed from a
+    /// snapshot.
+    pub fn base_height(&self) -> u64 {
+        self.base_height
+    }
+
+    /// Takes a snapshot of the ledger as of its executed head. Results in None if nothing has been executed yet.
+    pub fn take_snapshot(&self) -> Result<Option<Snapshot>, sled::Error> {
+        let head = match self.obtain_executed_head() {
+            Some(head) => head,
+            None => return Ok(None),
+        };
+        let (index, state) = match (self.hash_routes.get(&head.hash), head.state_entry) {
+            (Some(index), Some(state)) => (*index, state),
+            _ => return Ok(None),
+        };
+
+        let mut trees = Vec::with_capacity(SNAPSHOT_TREES.len());
+        for name in SNAPSHOT_TREES.iter() {
+            let mut entries = Vec::new();
+            for entry in self.open_tree(name)?.iter() {
+                let (key, value) = entry?;
+                entries.push((key.to_vec(), value.to_vec()));
+            }
+
+            trees.push(SnapshotTree {
+                name: (*name).to_owned(),
+                entries,
+            });
+        }
+
+        Ok(Some(Snapshot {
+            height: self.base_height + index as u64 + 1,
+            transaction: head.transaction,
+            state,
+            genesis: SnapshotGenesis {
+                hash: self.root_hash().unwrap_or_default(),
+                issuance: self.genesis_issuance(),
+                roles: self.genesis_roles(),
+            },
+            trees,
+        }))
+    }
+
+    /// Keeps the given snapshot as the graph's latest, in place of any it had before. Results in the snapshot's id.
     ///
-    /// use summercash::core::types::{transaction, graph}; // Import the transaction, graph libraries
-    /// use summercash::{common::address, crypto::hash}; // Import the address, hash libraries
+    /// # Arguments
     ///
-    /// let mut csprng = OsRng{}; // Generate source of randomness
+    /// * `snapshot` - The snapshot that should be kept
+    pub fn store_snapshot(&self, snapshot: &Snapshot) -> Result<Hash, sled::Error> {
+        let id = snapshot.id();
+
+        let tree = self.open_tree(SNAPSHOTS_TREE)?;
+        tree.clear()?;
+        tree.insert(id, snapshot.to_bytes())?;
+        self.set_meta(LATEST_SNAPSHOT_META, &*id)?;
+
+        Ok(id)
+    }
+
+    /// Gets the latest snapshot that the graph has kept, if it has kept one.
+    pub fn latest_snapshot(&self) -> Result<Option<Snapshot>, sled::Error> {
+        let id = match self.get_meta(LATEST_SNAPSHOT_META)? {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        match self.open_tree(SNAPSHOTS_TREE)?.get(id)? {
+            Some(encoded) => Snapshot::from_bytes(&encoded)
+                .map(Some)
+                .map_err(|e| sled::Error::Unsupported(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Picks the ledger up from the given snapshot, rather than from its genesis: the snapshot's anchor becomes the
+    /// graph's first node, executed to the snapshot's state. The snapshot should have been verified already. Only an
+    /// empty graph can be installed from a snapshot.
     ///
-    /// let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
-    /// let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+    /// # Arguments
     ///
-    /// let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
-    /// let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
-    ///
-    /// let tx = transaction::Transaction::new(0, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize transaction
-    /// let tx2 = transaction::Transaction::new(1, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize second transaction
-    ///
-    /// let mut dag = graph::Graph::new(tx, "olympia"); // Initialize graph
-    ///
-    /// let index_of_transaction = dag.push(tx2, None); // Add transaction to DAG
-    /// let node = dag.get(index_of_transaction); // Get a reference to the corresponding node
-    ///
-    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
-    /// ```
-    pub fn get(&mut self, index: usize) -> Result<Option<&Node>, sled::Error> {
-        let node = &mut self.nodes[index]; // Get ref to node
+    /// * `snapshot` - The snapshot that the ledger should be picked up from
+    pub fn install_snapshot(&mut self, snapshot: Snapshot) -> Result<(), SnapshotError> {
+        if !self.nodes.is_empty() {
+            return Err(SnapshotError::NotEmpty);
+        }
 
-        // Check was partially or fully loaded
-        match node.state_entry {
-            // Loaded fully
-            Some(_) => Ok(Some(node)),
-            // Loaded partially
-            None => {
-                // Check db opened
-                if let Some(db) = &self.db {
-                    let node_query_result = db.get(index.to_string().as_bytes())?; // Query db for node
-
-                    // Handle different result types
-                    match node_query_result {
-                        // Success!
-                        Some(bytes_encoded_node) => {
-                            let deserialized_node: Node =
-                                Node::from_bytes(&bytes_encoded_node.to_vec()[..]); // Deserialize node
-                            node.state_entry = deserialized_node.state_entry; // Set state entry
-
-                            return Ok(Some(node)); // Return deserialized node
-                        }
-                        // Couldn't find node in db
-                        None => return Ok(Some(node)),
-                    };
-                }
+        // Only the trees derived from the ledger may be overwritten
+        snapshot.check_trees()?;
+
+        for tree in &snapshot.trees {
+            let restored = self.open_tree(&tree.name)?;
+            restored.clear()?;
 
-                Ok(Some(node)) // Return node, since we can't do a full load anyway
+            for (key, value) in &tree.entries {
+                restored.insert(key.as_slice(), value.as_slice())?;
             }
         }
+
+        let base_height = snapshot.height.saturating_sub(1);
+        self.set_meta(
+            BASE_HEIGHT_META,
+            &bincode::serialize(&base_height).unwrap_or_default(),
+        )?;
+        self.set_meta(
+            SNAPSHOT_GENESIS_META,
+            &bincode::serialize(&snapshot.genesis).unwrap_or_default(),
+        )?;
+        self.base_height = base_height;
+
+        // Keep the snapshot around, so that it can be handed on to other nodes
+        self.store_snapshot(&snapshot)?;
+
+        self.push(snapshot.transaction, Some(snapshot.state));
+
+        Ok(()) // Done!
     }
 
-    /// Get a reference to a node with the given hash.
-    ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// extern crate num; // Link num library
-    /// extern crate rand; // Link rand library
-    ///
-    /// use num::traits::FromPrimitive; // Allow overloading of from_i64()
-    /// use num::bigint::BigUint; // Add support for large unsigned integers
-    ///
-    /// use rand::rngs::OsRng; // Import the os's rng
-    ///
-    /// use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
-    ///
-    /// use summercash::core::types::{transaction, graph}; // Import the transaction, graph libraries
-    /// use summercash::{common::address, crypto::hash}; // Import the address, hash libraries
-    ///
-    /// let mut csprng = OsRng{}; // Generate source of randomness
-    ///
-    /// let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
-    /// let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
-    ///
-    /// let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
-    /// let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
-    ///
-    /// let tx = transaction::Transaction::new(0, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize transaction
+    /// Gets the genesis described by the snapshot that the graph was installed from, if it was installed from one.
+    fn snapshot_genesis(&self) -> Option<SnapshotGenesis> {
+        if self.base_height == 0 {
+            return None;
+        }
+
+        self.get_meta(SNAPSHOT_GENESIS_META)
+            .ok()
+            .flatten()
+            .and_then(|encoded| bincode::deserialize(&encoded).ok())
+    }
+
+    /// Gets the directory holding the graph's database, if it has one.
+    pub fn db_path(&self) -> Option<&str> {
+        self.db_path.as_deref()
+    }
+
+    /// Checks whether or not the disk is too full for writes that the ledger can do without.
+    pub fn is_pressured(&self) -> bool {
+        self.pressured
+    }
+
+    /// Sets whether or not the disk is too full for writes that the ledger can do without. While it is, states aren't
+    /// archived, and the account activity index isn't kept up to date.
     ///
-    /// let tx2 = transaction::Transaction::new(1, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize second transaction
-    /// let tx2_hash = tx2.hash.clone(); // Clone transaction 2 hash
+    /// # Arguments
     ///
-    /// let mut dag = graph::Graph::new(tx, "olympia"); // Initialize graph
+    /// * `pressured` - Whether or not the disk is too full
+    pub fn set_pressured(&mut self, pressured: bool) {
+        self.pressured = pressured;
+    }
+
+    /// Makes room on the disk by dropping the account activity index, which is rebuilt from the DAG the next time
+    /// that it's needed, and flushing the database.
+    pub fn relieve_storage(&self) -> Result<(), sled::Error> {
+        if self.snapshot {
+            return Ok(());
+        }
+
+        self.open_tree(META_TREE)?
+            .remove(ACTIVITY_INDEXED_META.as_bytes())?;
+        self.activity_index()?.clear()?;
+
+        if let Some(db) = &self.db {
+            db.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Opens a named tree in the graph's database, which may be used to persist auxiliary data alongside the graph.
     ///
-    /// let index_of_transaction = dag.push(tx2, None); // Add transaction to DAG
-    /// let node = dag.get_with_hash(tx2_hash); // Get a reference to the corresponding node
+    /// # Arguments
     ///
-    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
-    /// ```
-    pub fn get_with_hash(&self, hash: hash::Hash) -> Result<&Node, OperationError> {
-        if self.hash_routes.contains_key(&hash) {
-            // Check hash route to node with hash
-            Ok(&self.nodes[*self.hash_routes.get(&hash).unwrap()]) // Return node
+    /// * `name` - The name of the tree that should be opened
+    pub fn open_tree(&self, name: &str) -> Result<sled::Tree, sled::Error> {
+        if let Some(db) = &self.db {
+            db.open_tree(name) // Open the tree
         } else {
-            Err(OperationError::NoLookupResults {
-                key: hash.to_str(),                         // Set key
-                error: "no route to node found".to_owned(), // Set error
-            }) // Return error in result
+            Err(sled::Error::Unsupported(
+                "could not open database".to_owned(),
+            )) // Return error
         }
     }
 
-    /// Get the last recorded balance of the provided account.
+    /// Opens the store in which the payloads of the graph's transactions are kept.
+    pub fn payload_store(&self) -> Result<PayloadStore, sled::Error> {
+        Ok(PayloadStore::from(self.open_tree(PAYLOADS_TREE)?))
+    }
+
+    /// Opens the index of the activity of each account that has sent or received a transaction in the graph.
+    pub fn activity_index(&self) -> Result<ActivityIndex, sled::Error> {
+        Ok(ActivityIndex::from(self.open_tree(ACTIVITY_TREE)?))
+    }
+
+    /// Records the given transaction in the account activity index. Failures are logged, rather than returned, since
+    /// the index is only used for analytics. Snapshots leave the index alone.
     ///
     /// # Arguments
     ///
-    /// * `account` - The account of which the balance should be determined
-    pub fn get_balance_of_account(&self, account: &Address) -> BigUint {
-        // Get a non-empty state in which the account's balance might reside
-        if let Some(head) = self.obtain_executed_head() {
-            // The state must have a state entry, since it was marked as "executed"
-            if let Some(state) = head.state_entry {
-                // Get the balance of the account
-                return state
-                    .data
-                    .balances
-                    .get(&account.to_str())
-                    .unwrap_or(&BigUint::zero())
-                    .clone();
-            }
+    /// * `tx` - The transaction that should be indexed
+    pub fn index_activity(&self, tx: &transaction::Transaction) {
+        if self.snapshot {
+            return;
         }
 
-        // The account must have a balance of zero, since it hasn't been included in the DAG yet
-        BigUint::zero()
+        // The index was dropped when the disk filled up, and is rebuilt from the DAG once it's needed again
+        if self.pressured {
+            return;
+        }
+
+        if let Err(e) = self
+            .activity_index()
+            .and_then(|index| index.record(&tx.transaction_data))
+        {
+            debug!(
+                "Failed to index the activity of transaction {}: {}",
+                tx.hash, e
+            );
+        }
     }
 
-    /// Gets the number of finks issued in the genesis block.
-    pub fn overall_issuance(&self) -> BigUint {
-        // Get the genesis transaction
-        if let Ok(Some(genesis)) = self.get_pure(0) {
-            // Try to get the state, and, by extension, all of the balances at the genesis
-            // transaction
-            if let Some(state) = genesis.state_entry {
-                // We're going to sum up the coins issued to each address; make a var to store this
-                let mut total_issuance = BigUint::zero();
+    /// Reports each of the accounts holding funds at the executed head that haven't sent anything since the given
+    /// point in time. Ledgers written before the activity index existed are indexed in full first.
+    ///
+    /// # Arguments
+    ///
+    /// * `cutoff` - Accounts that haven't sent anything since this point in time are dormant
+    /// * `limit` - The greatest number of dormant accounts to list
+    pub fn dormancy_report(
+        &self,
+        cutoff: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<DormancyReport, sled::Error> {
+        let index = self.activity_index()?;
+
+        // Start the index over from the genesis, so that transactions added before it existed are counted too
+        if self.get_meta(ACTIVITY_INDEXED_META)?.is_none() {
+            if self.pressured {
+                return Err(sled::Error::Unsupported(
+                    "the disk is too full to rebuild the activity index".to_owned(),
+                ));
+            }
 
-                // Collect the balances of each address
-                for balance in state.data.balances.values().into_iter() {
-                    total_issuance += balance;
-                }
+            index.clear()?;
 
-                return total_issuance;
+            for i in 0..self.nodes.len() {
+                if let Some(node) = self.get_pure(i)? {
+                    index.record(&node.transaction.transaction_data)?;
+                }
             }
+
+            self.set_meta(ACTIVITY_INDEXED_META, &[1])?;
         }
 
-        BigUint::zero()
-    }
+        let balances = self
+            .obtain_executed_head()
+            .and_then(|head| head.state_entry)
+            .map(|state| state.data.balances)
+            .unwrap_or_default();
 
-    /// Read the entirety of a persisted graph, or just state entry headers.
-    fn read_some_from_disk(read_all: bool, network: &str) -> Self {
-        // Read the database
-        Self::read_some_from_disk_with_data_dir(read_all, &io::format_db_dir(network))
+        index.dormancy_report(&balances, cutoff, limit)
     }
 
-    /// Read the entirety of a persisted graph, or just state entry headers.
-    fn read_some_from_disk_with_data_dir(read_all: bool, directory: &str) -> Graph {
-        let db = sled::open(directory).unwrap(); // Open database
+    /// Persists a receipt describing the execution of the transaction with the given hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - The hash of the transaction that the receipt belongs to
+    /// * `receipt` - The receipt that should be recorded
+    pub fn record_receipt(&self, tx_hash: Hash, receipt: &Receipt) -> Result<(), sled::Error> {
+        // Serialize the receipt so that we can put it in the db
+        let encoded =
+            bincode::serialize(receipt).map_err(|e| sled::Error::Unsupported(e.to_string()))?;
 
-        let mut nodes: Vec<Node> = vec![]; // Empty vector
-        let mut hash_routes: collections::hash_map::HashMap<hash::Hash, usize> =
-            collections::hash_map::HashMap::new(); // Initialize hash routes map buffer
-        let mut node_children: collections::hash_map::HashMap<hash::Hash, Vec<hash::Hash>> =
-            collections::hash_map::HashMap::new(); // Initialize child routes map buffer
+        self.open_tree(RECEIPTS_TREE)?.insert(tx_hash, encoded)?; // Record the receipt
 
-        let iter = db.iter(); // Get iterator (start at genesis transaction)
+        Ok(()) // Done!
+    }
 
-        iter.for_each(|key_val_pair| {
-            if let Ok(val) = key_val_pair {
-                let mut current_node: Node = Node::from_bytes(&val.1.to_vec()[..]); // Deserialize node
+    /// Gets the receipt recorded for the transaction with the given hash, if one exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx_hash` - The hash of the transaction that the receipt belongs to
+    pub fn get_receipt(&self, tx_hash: Hash) -> Result<Option<Receipt>, sled::Error> {
+        // Look up the receipt, and deserialize it
+        match self.open_tree(RECEIPTS_TREE)?.get(tx_hash)? {
+            Some(encoded) => Ok(Some(
+                bincode::deserialize(&encoded)
+                    .map_err(|e| sled::Error::Unsupported(e.to_string()))?,
+            )),
+            None => Ok(None),
+        }
+    }
 
-                if !read_all {
-                    // Check should disregard state data
-                    current_node.state_entry = None; // Set state entry to nil
-                }
+    /// Gets the metadata value stored under the given key, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the metadata value
+    pub fn get_meta(&self, key: &str) -> Result<Option<Vec<u8>>, sled::Error> {
+        Ok(self
+            .open_tree(META_TREE)?
+            .get(key.as_bytes())?
+            .map(|value| value.to_vec()))
+    }
 
-                hash_routes.insert(current_node.hash.clone(), nodes.len()); // Insert route to node
+    /// Stores a metadata value under the given key, replacing any existing value.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the metadata value
+    /// * `value` - The value that should be stored
+    pub fn set_meta(&self, key: &str, value: &[u8]) -> Result<(), sled::Error> {
+        self.open_tree(META_TREE)?.insert(key.as_bytes(), value)?; // Store the value
 
-                for parent in current_node.transaction.transaction_data.clone().parents {
-                    node_children
-                        .entry(parent)
-                        .or_insert_with(|| vec![])
-                        .push(current_node.hash.clone());
-                }
+        Ok(()) // Done!
+    }
 
-                nodes.push(current_node); // Add current node to nodes list
-            }
-        }); // Add nodes to graph vars
+    /// Gets the hash of the graph's genesis: the root transaction from which every other transaction descends.
+    pub fn root_hash(&self) -> Option<Hash> {
+        // A graph installed from a snapshot doesn't hold its genesis, but knows what it was
+        if let Some(genesis) = self.snapshot_genesis() {
+            return Some(genesis.hash);
+        }
 
-        Graph {
-            nodes,         // Set nodes
-            hash_routes,   // Set address routes
-            node_children, // Set node children
-            db: Some(db),  // Set db to none until we initialize our graph
-        } // Return initialized graph
+        // Prefer the transaction marked as the genesis, but fall back to any transaction without parents
+        self.nodes
+            .iter()
+            .find(|node| node.transaction.genesis)
+            .or_else(|| {
+                self.nodes
+                    .iter()
+                    .find(|node| node.transaction.transaction_data.parents.is_empty())
+            })
+            .map(|node| node.hash)
     }
 
-    /// Read the transactions--but not state data--in a graph from the disk.
+    /// Records that the state entry with the given hash was produced by the given transactions. Failures are logged,
+    /// rather than returned, since the index is only used for debugging.
     ///
-    /// # Example
-    ///
-    /// ```ignore
-    /// use summercash::core::types::graph; // Import the graph module
+    /// # Arguments
     ///
-    /// let dag: graph::Graph = graph::Graph::read_partial_from_disk(); // Read txs, but not state data from disk
-    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
-    /// ```
-    pub fn read_partial_from_disk(network_name: &str) -> Graph {
-        Graph::read_some_from_disk(false, network_name) // Read just transaction headers
+    /// * `state_hash` - The hash of the state entry
+    /// * `producers` - The hashes of the transactions whose execution resulted in the state
+    pub fn index_state(&self, state_hash: Hash, producers: &[Hash]) {
+        if let Err(e) = self.update_state_origin(state_hash, |origin| {
+            for producer in producers {
+                if !origin.produced_by.contains(producer) {
+                    origin.produced_by.push(*producer);
+                }
+            }
+        }) {
+            debug!("Failed to index the origin of state {}: {}", state_hash, e);
+        }
     }
 
-    /// Read the transactions--but not state data--in a graph from the disk, where the database is located in the given data_dir.
-    pub fn read_partial_from_disk_with_data_dir(data_dir: &str, network_name: &str) -> Self {
-        // Read just transaction headers
-        Graph::read_some_from_disk_with_data_dir(
-            false,
-            &format!("{}/db/{}", data_dir, network_name),
-        )
+    /// Records that the transaction with the given hash cites the state entry with the given hash as its parent
+    /// state. Failures are logged, rather than returned, since the index is only used for debugging.
+    ///
+    /// # Arguments
+    ///
+    /// * `state_hash` - The hash of the cited state entry
+    /// * `tx_hash` - The hash of the transaction citing the state
+    pub fn index_citation(&self, state_hash: Hash, tx_hash: Hash) {
+        if let Err(e) = self.update_state_origin(state_hash, |origin| {
+            if !origin.cited_by.contains(&tx_hash) {
+                origin.cited_by.push(tx_hash);
+            }
+        }) {
+            debug!(
+                "Failed to index the citation of state {}: {}",
+                state_hash, e
+            );
+        }
     }
 
-    /// Read a graph instance from the disk.
-    ///
-    /// # Example
+    /// Gets each of the transactions known to have produced or cited the state entry with the given hash.
     ///
-    /// ```ignore
-    /// use summercash::core::types::graph; // Import the graph module
+    /// # Arguments
     ///
-    /// let dag: graph::Graph = graph::Graph::read_from_disk(); // Read graph from disk
-    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
-    /// ```
-    pub fn read_from_disk(network_name: &str) -> Graph {
-        Graph::read_some_from_disk(true, network_name) // Read entirety of graph
+    /// * `state_hash` - The hash of the state entry
+    pub fn get_state_origin(&self, state_hash: Hash) -> Result<Option<StateOrigin>, sled::Error> {
+        // Look up the origin, and deserialize it
+        match self.open_tree(STATE_ORIGINS_TREE)?.get(state_hash)? {
+            Some(encoded) => Ok(Some(
+                bincode::deserialize(&encoded)
+                    .map_err(|e| sled::Error::Unsupported(e.to_string()))?,
+            )),
+            None => Ok(None),
+        }
     }
 
-    /// Write a graph instance to the disk, and close the associated database instance.
-    ///
-    /// # Example
+    /// Gets the guardians registered by the account with the given address as of the executed head of the graph,
+    /// along with any recovery in progress.
     ///
-    /// ```ignore
-    /// extern crate num; // Link num library
-    /// extern crate rand; // Link rand library
+    /// # Arguments
     ///
-    /// use num::traits::FromPrimitive; // Allow overloading of from_i64()
-    /// use num::bigint::BigUint; // Add support for large unsigned integers
+    /// * `account` - The address of the account
+    pub fn get_guardians(&self, account: Address) -> Option<GuardianRecord> {
+        self.obtain_executed_head()
+            .and_then(|head| head.state_entry)
+            .and_then(|state| state.data.guardians.get(&account.to_str()).cloned())
+    }
+
+    /// Gets each of the amounts locked in the account with the given address as of the executed head of the graph,
+    /// including any that have since expired but haven't been pruned yet.
     ///
-    /// use rand::rngs::OsRng; // Import the os's rng
+    /// # Arguments
     ///
-    /// use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    /// * `account` - The address of the account
+    pub fn get_vesting(&self, account: Address) -> Option<VestingSchedule> {
+        self.obtain_executed_head()
+            .and_then(|head| head.state_entry)
+            .and_then(|state| state.data.locks.get(&account.to_str()).cloned())
+    }
+
+    /// Describes how much of the balance of the account with the given address is locked as of the executed head of
+    /// the graph, for a transaction made at the given time, and how much may be spent.
     ///
-    /// use summercash::core::types::{transaction, graph}; // Import the transaction, graph libraries
-    /// use summercash::{common::address, crypto::hash}; // Import the address, hash libraries
+    /// # Arguments
     ///
-    /// let mut csprng = OsRng{}; // Generate source of randomness
+    /// * `account` - The address of the account
+    /// * `now` - The time at which the locks should be evaluated, bounded by the clock of the head's state
+    pub fn locked_balance(&self, account: Address, now: DateTime<Utc>) -> LockedBalance {
+        let state = self
+            .obtain_executed_head()
+            .and_then(|head| head.state_entry)
+            .unwrap_or_default();
+
+        vesting::funds(&state.data, &account.to_str(), now)
+    }
+
+    /// Gets the record of the mint executed for the given epoch, if the epoch's reward has been minted.
     ///
-    /// let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
-    /// let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+    /// # Arguments
     ///
-    /// let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+    /// * `epoch` - The epoch that the reward was for
+    pub fn get_mint(&self, epoch: u64) -> Result<Option<MintRecord>, sled::Error> {
+        // Look up the record, and deserialize it
+        match self.open_tree(MINTS_TREE)?.get(epoch.to_be_bytes())? {
+            Some(encoded) => Ok(Some(
+                bincode::deserialize(&encoded)
+                    .map_err(|e| sled::Error::Unsupported(e.to_string()))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Gets the record of each executed mint, oldest epoch first.
+    pub fn get_mints(&self) -> Result<Vec<MintRecord>, sled::Error> {
+        self.open_tree(MINTS_TREE)?
+            .iter()
+            .values()
+            .map(|encoded| {
+                bincode::deserialize(&encoded?).map_err(|e| sled::Error::Unsupported(e.to_string()))
+            })
+            .collect()
+    }
+
+    /// Records the mint carried by the given executed transaction, if it carries one.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that was executed
+    pub fn apply_mint(&self, tx: &transaction::Transaction) -> Result<(), sled::Error> {
+        if tx.transaction_data.sender != issuance::issuance_address() {
+            return Ok(());
+        }
+
+        let mint = match Mint::from_payload(&tx.transaction_data.payload) {
+            Ok(Some(mint)) => mint,
+            _ => return Ok(()),
+        };
+
+        let record = MintRecord {
+            epoch: mint.epoch,
+            tx_hash: tx.hash,
+            amount: mint.total(),
+            recipients: mint.transfers.len(),
+        };
+
+        self.open_tree(MINTS_TREE)?.insert(
+            mint.epoch.to_be_bytes(),
+            bincode::serialize(&record).map_err(|e| sled::Error::Unsupported(e.to_string()))?,
+        )?;
+
+        Ok(()) // Done!
+    }
+
+    /// Gets the number of finks minted since the genesis.
+    pub fn minted_supply(&self) -> BigUint {
+        self.get_mints()
+            .unwrap_or_default()
+            .iter()
+            .fold(BigUint::zero(), |minted, record| minted + &record.amount)
+    }
+
+    /// Applies the given modification to the indexed origin of the state entry with the given hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `state_hash` - The hash of the state entry
+    /// * `modify` - The change that should be made to the state's origin
+    fn update_state_origin<F: FnOnce(&mut StateOrigin)>(
+        &self,
+        state_hash: Hash,
+        modify: F,
+    ) -> Result<(), sled::Error> {
+        update_state_origin(&self.open_tree(STATE_ORIGINS_TREE)?, state_hash, modify)
+    }
+
+    /// Stores the state resulting from the node with the given hash in the given tree.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The tree in which archived states are stored
+    /// * `node_hash` - The hash of the node
+    /// * `entry` - The state resulting from the node
+    fn put_state(
+        &self,
+        tree: &sled::Tree,
+        node_hash: Hash,
+        entry: &state::Entry,
+    ) -> Result<(), sled::Error> {
+        // Serialize the state so that we can put it in the db
+        let encoded =
+            bincode::serialize(entry).map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+        tree.insert(node_hash, encoded)?;
+
+        Ok(()) // Done!
+    }
+
+    /// Gets a mutable reference to the nodes in the graph, copying them only if a snapshot still refers to them.
+    fn nodes_mut(&mut self) -> &mut Vec<Node> {
+        Arc::make_mut(&mut self.nodes)
+    }
+
+    /// Push a new item to the graph.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// extern crate num; // Link num library
+    /// extern crate rand; // Link rand library
+    ///
+    /// use num::traits::FromPrimitive; // Allow overloading of from_i64()
+    /// use num::bigint::BigUint; // Add support for large unsigned integers
+    ///
+    /// use rand::rngs::OsRng; // Import the os's rng
+    ///
+    /// use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    ///
+    /// use summercash::core::types::{transaction, graph}; // Import the transaction, graph libraries
+    /// use summercash::{common::address, crypto::hash}; // Import the address, hash libraries
+    ///
+    /// let mut csprng = OsRng{}; // Generate source of randomness
+    ///
+    /// let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+    /// let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+    ///
+    /// let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+    /// let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
+    ///
+    /// let tx = transaction::Transaction::new(0, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize transaction
+    /// let tx2 = transaction::Transaction::new(1, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize second transaction
+    ///
+    /// let mut dag = graph::Graph::new(tx, "olympia"); // Initialize graph
+    /// let index_of_transaction = dag.push(tx2, None); // Add transaction to DAG
+    ///
+    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    /// ```
+    pub fn push(
+        &mut self,
+        transaction: transaction::Transaction,
+        state_entry: Option<state::Entry>,
+    ) -> usize {
+        let transaction_hash = transaction.hash; // Clone transaction hash value
+        let transaction_parents = transaction.transaction_data.parents.clone(); // Clone transaction parents
+
+        // Remember which state the transaction cites, and which state it produced, if we know
+        if let Some(parent_state_hash) = transaction.transaction_data.parent_state_hash {
+            self.index_citation(parent_state_hash, transaction_hash);
+        }
+        if let Some(entry) = &state_entry {
+            self.index_state(entry.hash, &[transaction_hash]);
+            self.archive_state(transaction_hash, entry);
+        }
+        self.index_activity(&transaction);
+        if let Some(contract) = deployed_contract(&transaction) {
+            Arc::make_mut(&mut self.contracts).insert(contract); // Remember the contract it deploys
+        }
+
+        self.nodes_mut().push(Node::new(transaction, state_entry)); // Push node to graph
+        let index = self.nodes.len() - 1; // Get index of pushed node
+        Arc::make_mut(&mut self.hash_routes).insert(transaction_hash, index); // Set route to node
+
+        let node_children = Arc::make_mut(&mut self.node_children); // Get mutable ref to children
+
+        for parent in transaction_parents {
+            node_children
+                .entry(parent)
+                .or_insert_with(Vec::new)
+                .push(transaction_hash); // Add transaction as child of parent in graph
+        }
+
+        index // Return index of transaction
+    }
+
+    /// Push a new node to the graph.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node to push to the graph
+    pub fn add(&mut self, node: Node) -> usize {
+        // Put the node in the graph
+        self.push(node.transaction, node.state_entry)
+    }
+
+    /// Purges the contents of each of the nodes in the in-memory graph. The hash of each purged state is kept, so that
+    /// the state can still be recomputed and checked against it later on.
+    pub fn purge(&mut self) {
+        self.retain_state_hashes();
+
+        // Go through each of the nodes & manually purge
+        for node in self.nodes_mut().iter_mut() {
+            // Reset the state contents of the nodes
+            node.state_entry = None;
+        }
+    }
+
+    /// Update an item in the graph.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// extern crate num; // Link num library
+    /// extern crate rand; // Link rand library
+    ///
+    /// use num::traits::FromPrimitive; // Allow overloading of from_i64()
+    /// use num::bigint::BigUint; // Add support for large unsigned integers
+    ///
+    /// use rand::rngs::OsRng; // Import the os's rng
+    ///
+    /// use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    ///
+    /// use summercash::core::types::{transaction, graph}; // Import the transaction, graph libraries
+    /// use summercash::{common::address, crypto::hash}; // Import the address, hash libraries
+    ///
+    /// let mut csprng = OsRng{}; // Generate source of randomness
+    ///
+    /// let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+    /// let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+    ///
+    /// let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+    /// let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
+    ///
+    /// let tx = transaction::Transaction::new(0, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize transaction
+    /// let tx2 = transaction::Transaction::new(1, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize second transaction
+    ///
+    /// let mut dag = graph::Graph::new(tx, "olympia"); // Initialize graph
+    /// dag.update(0, tx2, None); // Update transaction in DAG
+    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    /// ```
+    pub fn update(
+        &mut self,
+        index: usize,
+        transaction: transaction::Transaction,
+        state_entry: Option<state::Entry>,
+    ) {
+        if let Some(entry) = &state_entry {
+            self.archive_state(transaction.hash, entry);
+        }
+
+        self.nodes_mut()[index] = Node::new(transaction, state_entry); // Set node in graph
+    }
+
+    /// Gets a copy of the node at a given index without modifying the graph's state.
+    pub fn get_pure(&self, index: usize) -> Result<Option<Node>, sled::Error> {
+        // Get a copy of the node
+        let mut node = self.nodes[index].clone();
+
+        // Fill the node's state
+        if node.state_entry.is_none() {
+            node.state_entry = self.load_state(index)?;
+        }
+
+        Ok(Some(node)) // Return the node, loaded as fully as we can
+    }
+
+    /// Loads the state resulting from the node at the given index from the disk, if it was persisted along with the
+    /// node, or paged out (or archived) on its own.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the node
+    fn load_state(&self, index: usize) -> Result<Option<state::Entry>, sled::Error> {
+        // Check db opened
+        let db = match &self.db {
+            Some(db) => db,
+            None => return Ok(None),
+        };
+
+        // Look for the state in the persisted copy of the node
+        if let Some(bytes_encoded_node) = db.get(node_key(index))? {
+            if let Some(entry) = Node::from_bytes(&bytes_encoded_node.to_vec()[..]).state_entry {
+                return Ok(Some(entry));
+            }
+        }
+
+        // The state may have been paged out since the node was persisted
+        match db.open_tree(STATES_TREE)?.get(self.nodes[index].hash)? {
+            Some(encoded) => bincode::deserialize(&encoded)
+                .map(Some)
+                .map_err(|e| sled::Error::Unsupported(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Get a reference to the node at a given index.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate num; // Link num library
+    /// extern crate rand; // Link rand library
+    ///
+    /// use num::traits::FromPrimitive; // Allow overloading of from_i64()
+    /// use num::bigint::BigUint; // Add support for large unsigned integers
+    ///
+    /// use rand::rngs::OsRng; // Import the os's rng
+    ///
+    /// use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    ///
+    /// use summercash::core::types::{transaction, graph}; // Import the transaction, graph libraries
+    /// use summercash::{common::address, crypto::hash}; // Import the address, hash libraries
+    ///
+    /// let mut csprng = OsRng{}; // Generate source of randomness
+    ///
+    /// let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+    /// let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+    ///
+    /// let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+    /// let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
+    ///
+    /// let tx = transaction::Transaction::new(0, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize transaction
+    /// let tx2 = transaction::Transaction::new(1, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize second transaction
+    ///
+    /// let mut dag = graph::Graph::new(tx, "olympia"); // Initialize graph
+    ///
+    /// let index_of_transaction = dag.push(tx2, None); // Add transaction to DAG
+    /// let node = dag.get(index_of_transaction); // Get a reference to the corresponding node
+    ///
+    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    /// ```
+    pub fn get(&mut self, index: usize) -> Result<Option<&Node>, sled::Error> {
+        // Check was partially or fully loaded
+        match self.nodes[index].state_entry {
+            // Loaded fully
+            Some(_) => Ok(Some(&self.nodes[index])),
+            // Loaded partially
+            None => {
+                // Page the state back in, if it can be found
+                if let Some(state_entry) = self.load_state(index)? {
+                    self.nodes_mut()[index].state_entry = Some(state_entry); // Set state entry
+                }
+
+                Ok(Some(&self.nodes[index])) // Return node, loaded as fully as we can
+            }
+        }
+    }
+
+    /// Get a reference to a node with the given hash.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// extern crate num; // Link num library
+    /// extern crate rand; // Link rand library
+    ///
+    /// use num::traits::FromPrimitive; // Allow overloading of from_i64()
+    /// use num::bigint::BigUint; // Add support for large unsigned integers
+    ///
+    /// use rand::rngs::OsRng; // Import the os's rng
+    ///
+    /// use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    ///
+    /// use summercash::core::types::{transaction, graph}; // Import the transaction, graph libraries
+    /// use summercash::{common::address, crypto::hash}; // Import the address, hash libraries
+    ///
+    /// let mut csprng = OsRng{}; // Generate source of randomness
+    ///
+    /// let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+    /// let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+    ///
+    /// let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+    /// let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
+    ///
+    /// let tx = transaction::Transaction::new(0, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize transaction
+    ///
+    /// let tx2 = transaction::Transaction::new(1, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize second transaction
+    /// let tx2_hash = tx2.hash.clone(); // Clone transaction 2 hash
+    ///
+    /// let mut dag = graph::Graph::new(tx, "olympia"); // Initialize graph
+    ///
+    /// let index_of_transaction = dag.push(tx2, None); // Add transaction to DAG
+    /// let node = dag.get_with_hash(tx2_hash); // Get a reference to the corresponding node
+    ///
+    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    /// ```
+    pub fn get_with_hash(&self, hash: hash::Hash) -> Result<&Node, OperationError> {
+        if self.hash_routes.contains_key(&hash) {
+            // Check hash route to node with hash
+            Ok(&self.nodes[*self.hash_routes.get(&hash).unwrap()]) // Return node
+        } else {
+            Err(OperationError::NoLookupResults {
+                key: hash.to_str(),                         // Set key
+                error: "no route to node found".to_owned(), // Set error
+            }) // Return error in result
+        }
+    }
+
+    /// Resolves the given full hash, or short id, to the hash of the one transaction in the DAG that it begins. Full
+    /// hashes are returned as-is, even if the DAG doesn't hold them.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The full hash, or short id, of the transaction
+    pub fn resolve_hash(&self, id: &str) -> Result<Hash, ShortIdError> {
+        short_id::resolve(id, self.hash_routes.keys())
+    }
+
+    /// Get the last recorded balance of the provided account.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account of which the balance should be determined
+    pub fn get_balance_of_account(&self, account: &Address) -> BigUint {
+        // Get a non-empty state in which the account's balance might reside
+        if let Some(head) = self.obtain_executed_head() {
+            // The state must have a state entry, since it was marked as "executed"
+            if let Some(state) = head.state_entry {
+                // Get the balance of the account
+                return state
+                    .data
+                    .balances
+                    .get(&account.to_str())
+                    .unwrap_or(&BigUint::zero())
+                    .clone();
+            }
+        }
+
+        // The account must have a balance of zero, since it hasn't been included in the DAG yet
+        BigUint::zero()
+    }
+
+    /// Gets the total number of finks in circulation: those issued in the genesis block, and those minted since,
+    /// less those that have been burned.
+    pub fn overall_issuance(&self) -> BigUint {
+        let issued = self.genesis_issuance() + self.minted_supply();
+        let burned = self.burned_supply();
+
+        if burned > issued {
+            return BigUint::zero();
+        }
+
+        issued - burned
+    }
+
+    /// Gets the number of finks burned so far: everything held by the burn address at the executed head.
+    pub fn burned_supply(&self) -> BigUint {
+        self.get_balance_of_account(&burn::burn_address())
+    }
+
+    /// Gets what sits behind the given address, as far as the ledger knows: the burn address, a contract deployed by
+    /// a transaction in the graph, or otherwise a user's account. Multisig accounts aren't recorded on the ledger, so
+    /// they're only told apart by their typed form.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address that should be looked up
+    pub fn address_kind(&self, address: &Address) -> AddressKind {
+        if *address == burn::burn_address() {
+            return AddressKind::Burn;
+        }
+
+        if self.contracts.contains(address) {
+            return AddressKind::Contract;
+        }
+
+        AddressKind::User
+    }
+
+    /// Gets the number of finks issued in the genesis block.
+    pub fn genesis_issuance(&self) -> BigUint {
+        if let Some(genesis) = self.snapshot_genesis() {
+            return genesis.issuance;
+        }
+
+        // Get the genesis transaction
+        if let Ok(Some(genesis)) = self.get_pure(0) {
+            // Try to get the state, and, by extension, all of the balances at the genesis
+            // transaction
+            if let Some(state) = genesis.state_entry {
+                // We're going to sum up the coins issued to each address; make a var to store this
+                let mut total_issuance = BigUint::zero();
+
+                // Collect the balances of each address
+                for balance in state.data.balances.values().into_iter() {
+                    total_issuance += balance;
+                }
+
+                return total_issuance;
+            }
+        }
+
+        BigUint::zero()
+    }
+
+    /// Gets each of the accounts designated a role in the genesis, in the order that they were allocated to. Only the
+    /// genesis children, sent from the genesis account before the genesis was wrapped up, can designate roles.
+    pub fn genesis_roles(&self) -> Vec<RoleAssignment> {
+        if let Some(genesis) = self.snapshot_genesis() {
+            return genesis.roles;
+        }
+
+        let genesis_account = match self.nodes.first() {
+            Some(root) => root.transaction.transaction_data.recipient,
+            None => return Vec::new(),
+        };
+
+        let children: Vec<&transaction::TransactionData> = self
+            .nodes
+            .iter()
+            .skip(1)
+            .map(|node| &node.transaction.transaction_data)
+            .take_while(|tx| {
+                tx.sender == genesis_account && tx.payload != GENESIS_FINALIZATION_PAYLOAD
+            })
+            .collect();
+
+        children
+            .iter()
+            .filter_map(|tx| {
+                let role = GenesisRole::from_payload(&tx.payload)?;
+
+                // Any locked tranches sent to the account count towards its allocation too
+                Some(RoleAssignment {
+                    address: tx.recipient,
+                    role,
+                    allocated: children
+                        .iter()
+                        .filter(|child| child.recipient == tx.recipient)
+                        .fold(BigUint::zero(), |allocated, child| allocated + &child.value),
+                })
+            })
+            .collect()
+    }
+
+    /// Read the entirety of a persisted graph, or just state entry headers.
+    fn read_some_from_disk(read_all: bool, network: &str) -> Self {
+        // Read the database
+        Self::read_some_from_disk_with_data_dir(read_all, &io::format_db_dir(network))
+    }
+
+    /// Read the entirety of a persisted graph, or just state entry headers.
+    fn read_some_from_disk_with_data_dir(read_all: bool, directory: &str) -> Graph {
+        let db = sled::open(directory).unwrap(); // Open database
+
+        // Bring the database up to date before reading anything from it. If this fails, the startup consistency
+        // check will refuse to use the outdated database.
+        if let Err(e) = migrations::migrate(&db) {
+            error!("Failed to migrate the database in {}: {}", directory, e);
+        }
+
+        // Graphs installed from a snapshot don't hold the transactions preceding it
+        let base_height = db
+            .open_tree(META_TREE)
+            .and_then(|tree| tree.get(BASE_HEIGHT_META.as_bytes()))
+            .ok()
+            .flatten()
+            .and_then(|encoded| bincode::deserialize(&encoded).ok())
+            .unwrap_or(0);
+
+        let mut nodes: Vec<Node> = vec![]; // Empty vector
+        let mut hash_routes: collections::hash_map::HashMap<hash::Hash, usize> =
+            collections::hash_map::HashMap::new(); // Initialize hash routes map buffer
+        let mut node_children: collections::hash_map::HashMap<hash::Hash, Vec<hash::Hash>> =
+            collections::hash_map::HashMap::new(); // Initialize child routes map buffer
+        let mut contracts: collections::HashSet<Address> = collections::HashSet::new(); // Initialize contracts buffer
+
+        // Payloads are stored on their own, and need to be put back into each of the transactions that carry them
+        let payloads = db.open_tree(PAYLOADS_TREE).map(PayloadStore::from);
+
+        let iter = db.iter(); // Get iterator (start at genesis transaction)
+
+        iter.for_each(|key_val_pair| {
+            if let Ok(val) = key_val_pair {
+                let mut current_node: Node = Node::from_bytes(&val.1.to_vec()[..]); // Deserialize node
+
+                if let Ok(payloads) = &payloads {
+                    if let Err(e) = payloads.inflate(&mut current_node.transaction) {
+                        error!(
+                            "Failed to inflate the payload of {}: {}",
+                            current_node.hash, e
+                        );
+                    }
+                }
+
+                if !read_all {
+                    // Check should disregard state data
+                    current_node.state_entry = None; // Set state entry to nil
+                }
+
+                hash_routes.insert(current_node.hash.clone(), nodes.len()); // Insert route to node
+                contracts.extend(deployed_contract(&current_node.transaction)); // Remember any deployed contract
+
+                for parent in current_node.transaction.transaction_data.clone().parents {
+                    node_children
+                        .entry(parent)
+                        .or_insert_with(|| vec![])
+                        .push(current_node.hash.clone());
+                }
+
+                nodes.push(current_node); // Add current node to nodes list
+            }
+        }); // Add nodes to graph vars
+
+        Graph {
+            nodes: Arc::new(nodes),                 // Set nodes
+            hash_routes: Arc::new(hash_routes),     // Set address routes
+            node_children: Arc::new(node_children), // Set node children
+            contracts: Arc::new(contracts),         // Set deployed contracts
+            db: Some(db),                           // Set db
+            db_path: Some(directory.to_owned()),    // Remember where the db lives
+            pressured: false,                       // Assume there's room until told otherwise
+            snapshot: false,                        // Not a snapshot
+            storage_mode: Default::default(),       // Prune, unless asked not to
+            base_height, // Pick up where the snapshot left off, if there was one
+            resolution_limits: Default::default(), // Use the default resolution limits
+        } // Return initialized graph
+    }
+
+    /// Read the transactions--but not state data--in a graph from the disk.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use summercash::core::types::graph; // Import the graph module
+    ///
+    /// let dag: graph::Graph = graph::Graph::read_partial_from_disk(); // Read txs, but not state data from disk
+    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    /// ```
+    pub fn read_partial_from_disk(network_name: &str) -> Graph {
+        Graph::read_some_from_disk(false, network_name) // Read just transaction headers
+    }
+
+    /// Read the transactions--but not state data--in a graph from the disk, where the database is located in the given data_dir.
+    pub fn read_partial_from_disk_with_data_dir(data_dir: &str, network_name: &str) -> Self {
+        // Read just transaction headers
+        Graph::read_some_from_disk_with_data_dir(
+            false,
+            &format!("{}/db/{}", data_dir, network_name),
+        )
+    }
+
+    /// Read a graph instance from the disk.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use summercash::core::types::graph; // Import the graph module
+    ///
+    /// let dag: graph::Graph = graph::Graph::read_from_disk(); // Read graph from disk
+    /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    /// ```
+    pub fn read_from_disk(network_name: &str) -> Graph {
+        Graph::read_some_from_disk(true, network_name) // Read entirety of graph
+    }
+
+    /// Write a graph instance to the disk, and close the associated database instance.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// extern crate num; // Link num library
+    /// extern crate rand; // Link rand library
+    ///
+    /// use num::traits::FromPrimitive; // Allow overloading of from_i64()
+    /// use num::bigint::BigUint; // Add support for large unsigned integers
+    ///
+    /// use rand::rngs::OsRng; // Import the os's rng
+    ///
+    /// use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    ///
+    /// use summercash::core::types::{transaction, graph}; // Import the transaction, graph libraries
+    /// use summercash::{common::address, crypto::hash}; // Import the address, hash libraries
+    ///
+    /// let mut csprng = OsRng{}; // Generate source of randomness
+    ///
+    /// let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+    /// let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+    ///
+    /// let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
     /// let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
     ///
     /// let tx = transaction::Transaction::new(0, sender, recipient, BigUint::from_i64(0).unwrap(), b"test transaction payload", vec![hash::Hash::new(vec![0; hash::HASH_SIZE])]); // Initialize transaction
@@ -714,18 +1903,32 @@ impl Graph {
     /// assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
     /// ```
     pub fn write_to_disk(&self) -> Result<(), sled::Error> {
+        // Snapshots are read-only; the graph they were taken from owns persistence
+        if self.snapshot {
+            return Err(sled::Error::Unsupported(
+                "cannot persist a graph snapshot".to_owned(),
+            )); // Return error
+        }
+
         // Get database instance
         if let Some(db) = &self.db {
-            // Iterate through nodes
-            for (i, node) in self.nodes.iter().rev().enumerate() {
+            let payloads = PayloadStore::from(db.open_tree(PAYLOADS_TREE)?);
+
+            // Iterate through nodes, starting with the newest
+            for i in (0..self.nodes.len()).rev() {
                 // Only continue with the persistence process if the nodes haven't already been
                 // saved to the database
-                if db.contains_key(i.to_string().as_bytes())? {
+                if db.contains_key(node_key(i))? {
                     break;
                 }
 
-                // Save the node in the database
-                db.insert(i.to_string().as_bytes(), node.to_bytes())?;
+                // Save the node in the database, keeping its payload in the payload store so that repeated payloads are
+                // only stored once
+                let mut node = self.nodes[i].clone();
+                payloads
+                    .deflate(&mut node.transaction)
+                    .map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+                db.insert(node_key(i), node.to_bytes())?;
             }
 
             db.flush()?; // Close db
@@ -756,171 +1959,616 @@ impl Graph {
         None
     }
 
+    /// Selects up to n distinct tips (nodes without any children) to use as the parents of a new transaction by way of a
+    /// weighted random walk. Each walk begins at the executed head of the graph, and moves to a child with probability
+    /// proportional to the child's weight (the size of its future cone), until a tip is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The maximum number of tips to select
+    /// * `unresolved_only` - Whether or not tips that have already been executed should be passed over
+    pub fn select_tips(&self, n: usize, unresolved_only: bool) -> Vec<Hash> {
+        // Nothing to walk over
+        if self.nodes.is_empty() || n == 0 {
+            return Vec::new();
+        }
+
+        // Children are always added after their parents, so walking backwards lets us calculate the weight of each node
+        // from those of its children. Nodes reachable along more than one path are counted once per path.
+        let mut weights: Vec<u64> = vec![1; self.nodes.len()];
+        for i in (0..self.nodes.len()).rev() {
+            if let Some(children) = self.node_children.get(&self.nodes[i].hash) {
+                for child in children {
+                    if let Some(child_index) = self.hash_routes.get(child) {
+                        // Only count children that come after the node, in case the graph is malformed
+                        if *child_index > i {
+                            weights[i] = weights[i].saturating_add(weights[*child_index]);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Start each walk from the most recently executed node, or the root if nothing has been executed
+        let start = self
+            .obtain_executed_head()
+            .and_then(|head| self.hash_routes.get(&head.hash).copied())
+            .unwrap_or(0);
+
+        let mut rng = rand::thread_rng(); // Get a source of randomness
+        let mut tips: Vec<Hash> = Vec::new(); // The tips that we've selected
+
+        // Walk a few more times than the number of tips we want, since walks can land on the same tip
+        for _ in 0..n * 4 {
+            let mut current = start;
+
+            // Keep moving forward until we can't
+            loop {
+                // Collect the children that we know of, along with their weights
+                let children: Vec<(usize, u64)> = self
+                    .node_children
+                    .get(&self.nodes[current].hash)
+                    .map(|children| {
+                        children
+                            .iter()
+                            .filter_map(|child| self.hash_routes.get(child))
+                            .filter(|child_index| **child_index > current)
+                            .map(|child_index| (*child_index, weights[*child_index]))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                // We've reached a tip
+                if children.is_empty() {
+                    break;
+                }
+
+                // Pick a child, favoring those with more weight
+                let total: u64 = children
+                    .iter()
+                    .fold(0u64, |acc, (_, weight)| acc.saturating_add(*weight));
+                let mut target = rng.gen_range(0, total);
+
+                for (child_index, weight) in children {
+                    if target < weight {
+                        current = child_index;
+
+                        break;
+                    }
+
+                    target -= weight;
+                }
+            }
+
+            let tip = self.nodes[current].hash; // Get the hash of the tip we landed on
+
+            // Skip any tips that we've already picked
+            if tips.contains(&tip) {
+                continue;
+            }
+
+            // Skip the tip if it's been executed, and we only want unresolved tips
+            if unresolved_only {
+                if let Ok(Some(node)) = self.get_pure(current) {
+                    if node.state_entry.is_some() {
+                        continue;
+                    }
+                }
+            }
+
+            tips.push(tip);
+
+            // We've got enough tips
+            if tips.len() == n {
+                break;
+            }
+        }
+
+        tips // Return the selected tips
+    }
+
     /// Removes the head transaction, and rolls back its direct parents. If there is no head, no computation occurs.
     pub fn rollback_head(&mut self) {
         // Remove the head from the nodes list
-        if let Some(removed_node) = self.nodes.pop() {
+        if let Some(removed_node) = self.nodes_mut().pop() {
             // Remove the route to the transaction by its hash
-            self.hash_routes.remove(&removed_node.hash);
+            Arc::make_mut(&mut self.hash_routes).remove(&removed_node.hash);
+
+            // Forget the contract that the transaction deployed, if it deployed one
+            if let Some(contract) = deployed_contract(&removed_node.transaction) {
+                Arc::make_mut(&mut self.contracts).remove(&contract);
+            }
 
             // Remove the child from each parent
             for parent in removed_node.transaction.transaction_data.parents {
                 // Remove the child from the parent, if it has any children it can remember
-                if let Some(children) = self.node_children.get_mut(&parent) {
+                if let Some(children) = Arc::make_mut(&mut self.node_children).get_mut(&parent) {
                     // Remove the child from the parent's memory
                     children.pop();
                 }
 
                 // If the parent exists, remove the state, since we gotta roll back
-                if let Some(parent_node) = self.hash_routes.get(&parent) {
+                if let Some(parent_node) = self.hash_routes.get(&parent).copied() {
                     // Reset the node's state
-                    self.nodes[*parent_node].state_entry = None;
+                    self.nodes_mut()[parent_node].state_entry = None;
                 }
             }
         }
     }
 
-    /// Executes each of the parent nodes with the provided hashes, merging their resultant states. NOTE: This method is pure.
+    /// Executes each of the parent nodes with the provided hashes, merging their resultant states. Ancestors are
+    /// resolved with a worklist, rather than recursively, so that deep unexecuted chains received from the network
+    /// can't exhaust the stack; the graph's resolution limits are enforced instead. NOTE: This method is pure.
+    ///
+    /// # Arguments
+    ///
+    /// * `parents` - The hashes of the nodes whose states should be merged
     pub fn resolve_parent_nodes(
         &self,
         parents: Vec<Hash>,
-    ) -> Result<(Entry, Vec<(Hash, Entry)>), sled::Error> {
-        // The set of states resulting from the execution of each of the provided nodes
-        let mut merged_state: Entry = Default::default();
-        let mut individual_states: Vec<(Hash, Entry)> = Vec::new();
+    ) -> Result<(Entry, Vec<(Hash, Entry)>), ResolutionError> {
+        // The state resulting from each node resolved so far, or None if the node couldn't be found
+        let mut resolved: collections::HashMap<Hash, Option<Entry>> = collections::HashMap::new();
+        let mut executed = 0; // The number of unexecuted nodes that had to be executed
+
+        // Work out the state resulting from each of the provided nodes, and from any of their unexecuted ancestors
+        for node_hash in &parents {
+            let mut work: Vec<(Hash, usize, bool)> = vec![(*node_hash, 1, false)];
+
+            while let Some((hash, depth, expanded)) = work.pop() {
+                // Diamonds in the graph mean that a node may be reached more than once
+                if resolved.contains_key(&hash) {
+                    continue;
+                }
 
-        // Execute each of the provided nodes, and collect a state entry describing such an execution
-        for node_hash in parents {
-            // Get the index of this node
-            let index = if let Some(i) = self.hash_routes.get(&node_hash) {
-                *i
-            } else {
-                continue;
-            };
+                // Skip any nodes that we don't have
+                let node = match self.hash_routes.get(&hash).map(|i| self.get_pure(*i)) {
+                    Some(Ok(Some(n))) => n,
+                    _ => {
+                        resolved.insert(hash, None);
 
-            // Get the actual node at the index
-            let node = if let Ok(Some(n)) = self.get_pure(index) {
-                n
-            } else {
-                continue;
-            };
+                        continue;
+                    }
+                };
 
-            // If the node already has a state entry, we should be able to continue on without executing it
-            if let Some(entry) = node.state_entry.clone() {
-                // Just use the entry that the node already has
-                individual_states.push((node_hash, entry.clone()));
-                merged_state = state::merge_entries(vec![merged_state, entry]);
+                // If the node already has a state entry, we should be able to continue on without executing it
+                if let Some(entry) = node.state_entry {
+                    resolved.insert(hash, Some(entry));
 
-                continue;
-            }
+                    continue;
+                }
 
-            // If the transaction doesn't have any parents, we can just execute it without any params
-            if node.transaction.transaction_data.parents.is_empty() {
-                // Execute the transaction
-                individual_states.push((node_hash, node.transaction.execute(None)));
-                merged_state = state::merge_entries(vec![
-                    merged_state,
-                    individual_states[individual_states.len() - 1].1.clone(),
-                ]);
+                let node_parents = &node.transaction.transaction_data.parents;
 
-                continue;
-            }
+                // If the transaction doesn't have any parents, we can just execute it without any params. Otherwise,
+                // it can be executed once each of its parents has been resolved.
+                if node_parents.is_empty() {
+                    resolved.insert(hash, Some(node.transaction.execute(None)));
 
-            // Try to execute the parents of this transaction. If this succeeds, we can resolve the immediate state.
-            if let Ok(prev_state) =
-                self.resolve_parent_nodes(node.transaction.transaction_data.parents.clone())
-            {
-                // Execute the transaction, and merge it back on to the overall state
-                individual_states.push((node_hash, node.transaction.execute(Some(prev_state.0))));
-                merged_state = state::merge_entries(vec![
-                    merged_state,
-                    individual_states[individual_states.len() - 1].1.clone(),
-                ]);
+                    continue;
+                } else if expanded {
+                    let (prev_state, _) = Self::merge_resolved_states(node_parents, &resolved);
+                    resolved.insert(hash, Some(node.transaction.execute(Some(prev_state))));
+
+                    continue;
+                }
+
+                self.check_resolution_limits(hash, depth, &mut executed)?;
+
+                // Come back to the node once each of its parents has been resolved
+                work.push((hash, depth, true));
+                work.extend(
+                    node_parents
+                        .iter()
+                        .filter(|parent| !resolved.contains_key(parent))
+                        .map(|parent| (*parent, depth + 1, false)),
+                );
             }
         }
 
         // Combine each of the states that the parent nodes produced into one gigantic state entry
-        Ok((merged_state, individual_states))
+        Ok(Self::merge_resolved_states(&parents, &resolved))
     }
 
-    /// Resolve states for all parent nodes, direct or indirect. NOTE: This method is not pure.
+    /// Resolve states for all parent nodes, direct or indirect. Like resolve_parent_nodes, ancestors are resolved
+    /// with a worklist, and the graph's resolution limits are enforced. NOTE: This method is not pure.
+    ///
+    /// # Arguments
+    ///
+    /// * `child_index` - The index of the node whose parents should be executed
     pub fn execute_parent_nodes(
         &mut self,
         child_index: usize,
-    ) -> Result<state::Entry, sled::Error> {
+    ) -> Result<state::Entry, ResolutionError> {
         // Get node
-        if let Some(node) = self.get(child_index)? {
-            let mut parent_entries: Vec<state::Entry> = vec![]; // Initialize parent entries vec
-
-            for parent in node.transaction.transaction_data.parents.clone() {
-                // Iterate through node parents
-                if let Some(index) = self.clone().hash_routes.get(&parent) {
-                    // Get index of parent
-                    if let Some(node) = self.nodes.get(*index) {
-                        // If the node has already been executed, we don't need to do the heavy lifting
-                        if let Some(state_entry) = node.state_entry.clone() {
-                            // Check already has state entry
-                            parent_entries.push(state_entry); // Add state entry to parent entries vec
-
-                            continue; // Continue
-                        }
-                    }
+        let parents = if let Some(node) = self.get(child_index)? {
+            node.transaction.transaction_data.parents.clone()
+        } else {
+            return Err(sled::Error::CollectionNotFound((&[child_index as u8]).into()).into());
+            // Return error
+        };
+
+        let mut executed = 0; // The number of unexecuted nodes that have been executed
+
+        // Execute each of the parents, along with any of their unexecuted ancestors
+        let mut work: Vec<(Hash, usize, bool)> = parents
+            .iter()
+            .rev()
+            .map(|parent| (*parent, 1, false))
+            .collect();
+
+        while let Some((hash, depth, expanded)) = work.pop() {
+            // Skip any nodes that we don't have
+            let index = if let Some(index) = self.hash_routes.get(&hash).copied() {
+                index
+            } else {
+                continue;
+            };
 
-                    if self.nodes[*index]
-                        .transaction
-                        .transaction_data
-                        .parents
-                        .is_empty()
-                    {
-                        // Execute the node, without any prior metadata, since the transaction is the root
-                        let executed = self.nodes[*index].transaction.execute(None);
+            // If the node has already been executed, we don't need to do the heavy lifting
+            if let Some(node) = self.get(index)? {
+                if node.state_entry.is_some() {
+                    continue;
+                }
+            }
 
-                        // Keep the executed state in mind for later computation
-                        self.nodes[*index].state_entry = Some(executed.clone());
-                        parent_entries.push(executed);
+            let node_parents = self.nodes[index]
+                .transaction
+                .transaction_data
+                .parents
+                .clone();
+
+            // Execute the node once each of its parents has been executed, or without any prior metadata, if the
+            // transaction is the root
+            if node_parents.is_empty() || expanded {
+                let prev_state_entry = if node_parents.is_empty() {
+                    None
+                } else {
+                    Some(state::merge_entries(self.executed_states_of(&node_parents)))
+                };
+                let executed_state = self.nodes[index].transaction.execute(prev_state_entry);
+
+                // Keep the executed state in mind for later computation
+                self.index_state(executed_state.hash, &[hash]);
+                self.archive_state(hash, &executed_state);
+                self.nodes_mut()[index].state_entry = Some(executed_state);
 
-                        continue;
-                    }
+                continue;
+            }
 
-                    if let Ok(prev_state_entry) = self.execute_parent_nodes(*index) {
-                        // Execute the node with the given prior metadata / state
-                        let executed = self.nodes[*index]
-                            .transaction
-                            .execute(Some(prev_state_entry));
+            self.check_resolution_limits(hash, depth, &mut executed)?;
 
-                        // Store the calculated state in the tree, and in the collection vector
-                        self.nodes[*index].state_entry = Some(executed.clone());
-                        parent_entries.push(self.nodes[*index].state_entry.clone().unwrap());
-                    }
-                }
-            }
+            // Come back to the node once each of its parents has been executed
+            work.push((hash, depth, true));
+            work.extend(
+                node_parents
+                    .iter()
+                    .rev()
+                    .map(|parent| (*parent, depth + 1, false)),
+            );
+        }
 
-            Ok(state::merge_entries(parent_entries)) // Return merged entries
-        } else {
-            Err(sled::Error::CollectionNotFound(
-                (&[child_index as u8]).into(),
-            )) // Return error
+        Ok(state::merge_entries(self.executed_states_of(&parents))) // Return merged entries
+    }
+
+    /// Gets the limits placed on the work done resolving the states of a transaction's parents.
+    pub fn resolution_limits(&self) -> ResolutionLimits {
+        self.resolution_limits
+    }
+
+    /// Places the given limits on the work done resolving the states of a transaction's parents from now on.
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - How deep, and how much, resolving a transaction's parents may go before it is given up on
+    pub fn set_resolution_limits(&mut self, limits: ResolutionLimits) {
+        self.resolution_limits = limits;
+    }
+
+    /// Makes sure that expanding the unexecuted node with the given hash keeps a resolution within the graph's
+    /// limits, counting it against the number of nodes executed so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the node about to be expanded
+    /// * `depth` - How far the node is from the transaction whose parents are being resolved
+    /// * `executed` - The number of unexecuted nodes expanded so far
+    fn check_resolution_limits(
+        &self,
+        hash: Hash,
+        depth: usize,
+        executed: &mut usize,
+    ) -> Result<(), ResolutionError> {
+        if depth > self.resolution_limits.max_depth {
+            return Err(ResolutionError::TooDeep {
+                transaction_hash: hash.to_str(),
+                max_depth: self.resolution_limits.max_depth,
+            });
+        }
+
+        *executed += 1;
+        if *executed > self.resolution_limits.max_nodes {
+            return Err(ResolutionError::TooManyNodes {
+                max_nodes: self.resolution_limits.max_nodes,
+            });
         }
+
+        Ok(())
+    }
+
+    /// Gets the states resulting from each of the nodes with the given hashes that have been executed, in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `hashes` - The hashes of the nodes
+    fn executed_states_of(&self, hashes: &[Hash]) -> Vec<Entry> {
+        hashes
+            .iter()
+            .filter_map(|hash| self.hash_routes.get(hash))
+            .filter_map(|index| self.nodes[*index].state_entry.clone())
+            .collect()
+    }
+
+    /// Merges the resolved states of each of the nodes with the given hashes, skipping any that couldn't be resolved.
+    /// Results in the merged state, along with the state of each node that it was merged from.
+    ///
+    /// # Arguments
+    ///
+    /// * `hashes` - The hashes of the nodes whose states should be merged
+    /// * `resolved` - The state resulting from each node resolved so far
+    fn merge_resolved_states(
+        hashes: &[Hash],
+        resolved: &collections::HashMap<Hash, Option<Entry>>,
+    ) -> (Entry, Vec<(Hash, Entry)>) {
+        let mut merged_state: Entry = Default::default();
+        let mut individual_states: Vec<(Hash, Entry)> = Vec::new();
+
+        for hash in hashes {
+            if let Some(Some(entry)) = resolved.get(hash) {
+                individual_states.push((*hash, entry.clone()));
+                merged_state = state::merge_entries(vec![merged_state, entry.clone()]);
+            }
+        }
+
+        (merged_state, individual_states)
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::rand::Rng; // Import rand
-    use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
-    use num::bigint::BigUint; // Add support for large unsigned integers
-    use num::traits::FromPrimitive; // Allow overloading of from_i64()
-    use rand; // Import the rand module
-    use rand::rngs::OsRng; // Import the os's rng
+#[cfg(test)]
+mod tests {
+    use crate::rand::Rng; // Import rand
+    use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    use num::bigint::BigUint; // Add support for large unsigned integers
+    use num::traits::FromPrimitive; // Allow overloading of from_i64()
+    use rand; // Import the rand module
+    use rand::rngs::OsRng; // Import the os's rng
+
+    use path_clean; // Import path clean module
+
+    use super::super::super::super::common::address; // Import address module
+    use super::super::super::super::crypto::blake3; // Import the blake3 hashing module
+    use super::super::batch::Transfer; // Import the single payment type
+    use super::super::vesting::Unlock; // Import the time lock conditions
+
+    use super::*; // Import names from parent module
+
+    #[test]
+    fn test_new() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+        let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
+
+        let rand: u16 = rng.gen(); // Generate random number
+
+        let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+        let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+
+        let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+        let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
+
+        let root_tx = transaction::Transaction::new(
+            0,
+            sender,
+            recipient,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+        ); // Initialize root transaction
+
+        let dag: Graph = Graph::new_with_db_path(
+            root_tx,
+            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
+        ); // Initialize graph
+
+        assert_eq!(
+            dag.nodes[0].transaction.transaction_data.payload,
+            b"test transaction payload"
+        ); // Ensure transaction payload retained
+
+        assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    }
+
+    #[test]
+    fn test_address_kind() {
+        let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
+        let rand: u16 = rng.gen(); // Generate random number
+
+        let (deployer, contract) = (
+            blake3::hash_slice(b"deployer"),
+            blake3::hash_slice(b"contract"),
+        );
+
+        let mut root_tx = transaction::Transaction::new(
+            0,
+            deployer,
+            Address::default(),
+            BigUint::zero(),
+            b"contract bytecode",
+            vec![],
+        ); // Deploy a contract
+        root_tx.contract_creation = true;
+        root_tx.deployed_contract_address = Some(contract);
+
+        let mut dag: Graph = Graph::new_with_db_path(
+            root_tx,
+            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
+        ); // Initialize graph
+
+        assert_eq!(dag.address_kind(&contract), AddressKind::Contract); // Ensure deployed contracts are recognized
+        assert_eq!(dag.address_kind(&burn::burn_address()), AddressKind::Burn);
+        assert_eq!(dag.address_kind(&deployer), AddressKind::User); // Ensure everything else is a user's account
+
+        let other_contract = blake3::hash_slice(b"other contract");
+        let mut tx = transaction::Transaction::new(
+            1,
+            deployer,
+            Address::default(),
+            BigUint::zero(),
+            b"other contract bytecode",
+            vec![dag.nodes[0].hash],
+        ); // Deploy another contract
+        tx.contract_creation = true;
+        tx.deployed_contract_address = Some(other_contract);
+
+        dag.push(tx, None);
+        assert_eq!(dag.address_kind(&other_contract), AddressKind::Contract); // Ensure pushed deployments are indexed
+
+        dag.rollback_head();
+        assert_eq!(dag.address_kind(&other_contract), AddressKind::User); // Ensure rolled back deployments are dropped
+
+        assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    }
+
+    #[test]
+    fn test_push() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+        let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
+
+        let rand: u16 = rng.gen(); // Generate random number
+
+        let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+        let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+
+        let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+        let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
+
+        let root_tx = transaction::Transaction::new(
+            0,
+            sender,
+            recipient,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+        ); // Initialize root transaction
+        let tx_2 = transaction::Transaction::new(
+            1,
+            sender,
+            recipient,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+        ); // Initialize second transaction
+
+        let mut dag: Graph = Graph::new_with_db_path(
+            root_tx,
+            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
+        ); // Initialize graph
+
+        let node_index: usize = dag.push(tx_2, None); // Push second transaction
+
+        assert_eq!(node_index, 1); // Ensure is second transaction in DAG
+
+        assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    }
+
+    #[test]
+    fn test_update() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+        let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
+
+        let rand: u16 = rng.gen(); // Generate random number
+
+        let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+        let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+
+        let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+        let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
+
+        let root_tx = transaction::Transaction::new(
+            0,
+            sender,
+            recipient,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+        ); // Initialize root transaction
+        let tx_2 = transaction::Transaction::new(
+            1,
+            sender,
+            recipient,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload 2",
+            vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+        ); // Initialize second transaction
+
+        let mut dag: Graph = Graph::new_with_db_path(
+            root_tx,
+            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
+        ); // Initialize graph
+
+        dag.update(0, tx_2, None); // Update root transaction
+
+        assert_eq!(
+            dag.get(0)
+                .unwrap()
+                .unwrap()
+                .transaction
+                .transaction_data
+                .payload,
+            b"test transaction payload 2"
+        ); // Ensure has updated transaction
+
+        assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    }
+
+    #[test]
+    fn test_get() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+        let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
+
+        let rand: u16 = rng.gen(); // Generate random number
+
+        let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+        let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+
+        let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+        let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
+
+        let root_tx = transaction::Transaction::new(
+            0,
+            sender,
+            recipient,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+        ); // Initialize root transaction
 
-    use path_clean; // Import path clean module
+        let mut dag: Graph = Graph::new_with_db_path(
+            root_tx,
+            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
+        ); // Initialize graph
 
-    use super::super::super::super::common::address; // Import address module
+        let found_root_tx = dag.get(0).unwrap().unwrap(); // Get root tx
 
-    use super::*; // Import names from parent module
+        assert_eq!(
+            found_root_tx.transaction.transaction_data.payload,
+            b"test transaction payload"
+        ); // Ensure is same transaction
+
+        assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    }
 
     #[test]
-    fn test_new() {
+    fn test_get_with_hash() {
         let mut csprng = OsRng {}; // Generate source of randomness
         let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
 
@@ -940,22 +2588,25 @@ mod tests {
             b"test transaction payload",
             vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
         ); // Initialize root transaction
+        let root_tx_hash = root_tx.hash.clone(); // Clone root tx hash
 
         let dag: Graph = Graph::new_with_db_path(
             root_tx,
             &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
         ); // Initialize graph
 
+        let found_root_tx = dag.get_with_hash(root_tx_hash).unwrap(); // Get root tx
+
         assert_eq!(
-            dag.nodes[0].transaction.transaction_data.payload,
+            found_root_tx.transaction.transaction_data.payload,
             b"test transaction payload"
-        ); // Ensure transaction payload retained
+        ); // Ensure is same transaction
 
         assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
     }
 
     #[test]
-    fn test_push() {
+    fn test_select_tips() {
         let mut csprng = OsRng {}; // Generate source of randomness
         let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
 
@@ -973,31 +2624,37 @@ mod tests {
             recipient,
             BigUint::from_i64(0).unwrap(),
             b"test transaction payload",
-            vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+            vec![],
         ); // Initialize root transaction
+        let root_tx_hash = root_tx.hash; // Copy root tx hash
+
+        let mut dag: Graph = Graph::new_with_db_path(
+            root_tx,
+            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
+        ); // Initialize graph
+
+        assert_eq!(dag.select_tips(2, false), vec![root_tx_hash]); // The root is the only tip
+        assert!(dag.select_tips(2, true).is_empty()); // The root has already been executed
+
         let tx_2 = transaction::Transaction::new(
             1,
             sender,
             recipient,
             BigUint::from_i64(0).unwrap(),
             b"test transaction payload",
-            vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+            vec![root_tx_hash],
         ); // Initialize second transaction
+        let tx_2_hash = tx_2.hash; // Copy second tx hash
 
-        let mut dag: Graph = Graph::new_with_db_path(
-            root_tx,
-            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
-        ); // Initialize graph
-
-        let node_index: usize = dag.push(tx_2, None); // Push second transaction
+        dag.push(tx_2, None); // Push second transaction
 
-        assert_eq!(node_index, 1); // Ensure is second transaction in DAG
+        assert_eq!(dag.select_tips(2, true), vec![tx_2_hash]); // The second transaction is now the only tip
 
         assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
     }
 
     #[test]
-    fn test_update() {
+    fn test_snapshot() {
         let mut csprng = OsRng {}; // Generate source of randomness
         let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
 
@@ -1022,7 +2679,7 @@ mod tests {
             sender,
             recipient,
             BigUint::from_i64(0).unwrap(),
-            b"test transaction payload 2",
+            b"test transaction payload",
             vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
         ); // Initialize second transaction
 
@@ -1031,92 +2688,520 @@ mod tests {
             &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
         ); // Initialize graph
 
-        dag.update(0, tx_2, None); // Update root transaction
+        let snapshot = dag.snapshot(); // Take a snapshot of the graph
 
-        assert_eq!(
-            dag.get(0)
-                .unwrap()
-                .unwrap()
-                .transaction
-                .transaction_data
-                .payload,
-            b"test transaction payload 2"
-        ); // Ensure has updated transaction
+        assert!(snapshot.is_snapshot()); // Ensure is marked as a snapshot
+        assert!(Arc::ptr_eq(&dag.nodes, &snapshot.nodes)); // Ensure nodes weren't copied
+        assert!(snapshot.write_to_disk().is_err()); // Ensure snapshots can't be persisted
+
+        dag.push(tx_2, None); // Push second transaction
+
+        assert_eq!(dag.nodes.len(), 2); // Ensure graph has new node
+        assert_eq!(snapshot.nodes.len(), 1); // Ensure snapshot is unaffected
 
         assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
     }
 
     #[test]
-    fn test_get() {
+    fn test_state_origin() {
         let mut csprng = OsRng {}; // Generate source of randomness
         let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
 
         let rand: u16 = rng.gen(); // Generate random number
 
         let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
-        let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+        let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+
+        let root_tx = transaction::Transaction::new(
+            0,
+            sender,
+            sender,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            vec![],
+        ); // Initialize root transaction
+        let root_hash = root_tx.hash; // Get the hash of the root transaction
+
+        let mut dag: Graph = Graph::new_with_db_path(
+            root_tx,
+            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
+        ); // Initialize graph
+
+        let root_state = dag.nodes[0].state_entry.clone().unwrap(); // Get the state of the root node
+
+        let mut tx_2 = transaction::Transaction::new(
+            1,
+            sender,
+            sender,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            vec![root_hash],
+        ); // Initialize second transaction
+        let (merged, entries) = dag.resolve_parent_nodes(vec![root_hash]).unwrap(); // Resolve the parent state
+        tx_2.register_parental_state(merged.clone(), entries); // Cite the parent state
+        let tx_2_hash = tx_2.hash; // Get the hash of the second transaction
 
+        dag.push(tx_2, None); // Push second transaction
+
+        let origin = dag.get_state_origin(root_state.hash).unwrap().unwrap(); // Trace the root state
+
+        assert_eq!(merged.hash, root_state.hash); // A lone parent's state should be cited as-is
+        assert_eq!(origin.produced_by, vec![root_hash]); // The root produced the state
+        assert_eq!(origin.cited_by, vec![tx_2_hash]); // The second transaction cited the state
+
+        assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    }
+
+    #[test]
+    fn test_resolution_limits() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+        let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
+
+        let rand: u16 = rng.gen(); // Generate random number
+
+        let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
         let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
-        let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
 
         let root_tx = transaction::Transaction::new(
             0,
             sender,
-            recipient,
+            sender,
             BigUint::from_i64(0).unwrap(),
             b"test transaction payload",
-            vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+            vec![],
+        ); // Initialize root transaction
+
+        let mut dag: Graph = Graph::new_with_db_path(
+            root_tx.clone(),
+            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
+        ); // Initialize graph
+
+        // Build a long chain of transactions that haven't been executed yet
+        let mut expected_state = dag.nodes[0].state_entry.clone().unwrap();
+        let mut head = root_tx.hash;
+        for nonce in 1..=64 {
+            let tx = transaction::Transaction::new(
+                nonce,
+                sender,
+                sender,
+                BigUint::from_i64(0).unwrap(),
+                b"test transaction payload",
+                vec![head],
+            );
+            head = tx.hash;
+            expected_state = tx.execute(Some(state::merge_entries(vec![
+                Default::default(),
+                expected_state,
+            ])));
+
+            dag.push(tx, None);
+        }
+
+        // Walking the chain takes more generations, and more executions, than we're willing to spend
+        dag.set_resolution_limits(ResolutionLimits {
+            max_depth: 16,
+            max_nodes: DEFAULT_MAX_RESOLUTION_NODES,
+        });
+        match dag.resolve_parent_nodes(vec![head]) {
+            Err(ResolutionError::TooDeep { max_depth, .. }) => assert_eq!(max_depth, 16),
+            _ => panic!("expected the resolution to be too deep"),
+        }
+
+        dag.set_resolution_limits(ResolutionLimits {
+            max_depth: DEFAULT_MAX_RESOLUTION_DEPTH,
+            max_nodes: 16,
+        });
+        match dag.execute_parent_nodes(dag.nodes.len() - 1) {
+            Err(ResolutionError::TooManyNodes { max_nodes }) => assert_eq!(max_nodes, 16),
+            _ => panic!("expected the resolution to execute too many nodes"),
+        }
+
+        // With enough room, the chain resolves to the same state that it would have if it had been executed in order
+        dag.set_resolution_limits(ResolutionLimits::default());
+        let (merged, entries) = dag.resolve_parent_nodes(vec![head]).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].1.hash, expected_state.hash);
+        assert_eq!(
+            merged.hash,
+            state::merge_entries(vec![Default::default(), expected_state.clone()]).hash
+        );
+
+        // Executing the chain leaves every ancestor executed
+        let child = dag.push(
+            transaction::Transaction::new(
+                65,
+                sender,
+                sender,
+                BigUint::from_i64(0).unwrap(),
+                b"test transaction payload",
+                vec![head],
+            ),
+            None,
+        );
+        dag.execute_parent_nodes(child).unwrap();
+        assert_eq!(
+            dag.nodes[child - 1].state_entry.as_ref().unwrap().hash,
+            expected_state.hash
+        );
+        assert!(dag.nodes[..child]
+            .iter()
+            .all(|node| node.state_entry.is_some()));
+
+        assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    }
+
+    #[test]
+    fn test_archive() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+        let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
+
+        let rand: u16 = rng.gen(); // Generate random number
+
+        let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+        let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+
+        let root_tx = transaction::Transaction::new(
+            0,
+            sender,
+            sender,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            vec![],
         ); // Initialize root transaction
+        let root_hash = root_tx.hash; // Get the hash of the root transaction
 
         let mut dag: Graph = Graph::new_with_db_path(
             root_tx,
             &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
         ); // Initialize graph
+        assert!(!dag.is_archive());
 
-        let found_root_tx = dag.get(0).unwrap().unwrap(); // Get root tx
+        let root_state = dag.nodes[0].state_entry.clone().unwrap(); // Get the state of the root node
 
+        // Nodes executed before the graph became an archive are archived straight away
+        dag.enable_archive().unwrap();
+        dag.purge();
         assert_eq!(
-            found_root_tx.transaction.transaction_data.payload,
-            b"test transaction payload"
-        ); // Ensure is same transaction
+            dag.get_state_at(root_hash).unwrap().unwrap().hash,
+            root_state.hash
+        );
+
+        let tx_2 = transaction::Transaction::new(
+            1,
+            sender,
+            sender,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            vec![root_hash],
+        ); // Initialize second transaction
+        let tx_2_hash = tx_2.hash; // Get the hash of the second transaction
+        let tx_2_state = tx_2.execute(Some(root_state)); // Execute the second transaction
+
+        dag.push(tx_2, Some(tx_2_state.clone())); // Push second transaction
+        dag.purge();
+        assert_eq!(
+            dag.get_state_at(tx_2_hash).unwrap().unwrap().hash,
+            tx_2_state.hash
+        );
+
+        // The states of nodes that have been rolled back are forgotten
+        dag.rollback_head();
+        assert!(dag.get_state_at(tx_2_hash).unwrap().is_none());
 
         assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
     }
 
     #[test]
-    fn test_get_with_hash() {
+    fn test_storage_modes() {
         let mut csprng = OsRng {}; // Generate source of randomness
         let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
 
         let rand: u16 = rng.gen(); // Generate random number
 
         let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
-        let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+        let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+
+        let root_tx = transaction::Transaction::new(
+            0,
+            sender,
+            sender,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            vec![],
+        ); // Initialize root transaction
+
+        let mut dag: Graph = Graph::new_with_db_path(
+            root_tx,
+            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
+        ); // Initialize graph
+        assert_eq!(dag.storage_mode(), StorageMode::default());
+
+        // Build a chain of executed transactions on top of the root
+        for nonce in 1..4 {
+            let parent = dag.nodes[dag.nodes.len() - 1].clone();
+            let tx = transaction::Transaction::new(
+                nonce,
+                sender,
+                sender,
+                BigUint::from_i64(0).unwrap(),
+                b"test transaction payload",
+                vec![parent.hash],
+            ); // Initialize transaction
+            let state = tx.execute(parent.state_entry);
+
+            dag.push(tx, Some(state));
+        }
+
+        // Pruned graphs keep the states of their most recent nodes in memory
+        dag.set_storage_mode(StorageMode::Pruned { recent_nodes: 2 })
+            .unwrap();
+        let in_memory = |dag: &Graph| -> Vec<bool> {
+            dag.nodes
+                .iter()
+                .map(|node| node.state_entry.is_some())
+                .collect()
+        };
+        assert_eq!(in_memory(&dag), vec![false, false, true, true]);
+
+        // Light graphs only keep the state of the executed head
+        dag.set_storage_mode("light".parse().unwrap()).unwrap();
+        assert_eq!(in_memory(&dag), vec![false, false, false, true]);
+        assert_eq!(dag.prune().unwrap(), 0);
+
+        // Paged out states are loaded back in as they're needed
+        let root_state_hash = dag.get_pure(0).unwrap().unwrap().state_entry.unwrap().hash;
+        assert!(dag.nodes[0].state_entry.is_none());
+        assert_eq!(
+            dag.get(0)
+                .unwrap()
+                .unwrap()
+                .state_entry
+                .as_ref()
+                .unwrap()
+                .hash,
+            root_state_hash
+        );
+        assert!(!dag.is_archive());
+
+        assert_eq!(
+            "everything".parse::<StorageMode>(),
+            Err(StorageModeError::UnknownMode {
+                mode: "everything".to_owned()
+            })
+        );
+
+        assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    }
+
+    #[test]
+    fn test_install_snapshot() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+        let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
 
+        let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
         let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
-        let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
 
         let root_tx = transaction::Transaction::new(
             0,
             sender,
-            recipient,
+            sender,
             BigUint::from_i64(0).unwrap(),
             b"test transaction payload",
-            vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+            vec![],
         ); // Initialize root transaction
-        let root_tx_hash = root_tx.hash.clone(); // Clone root tx hash
 
-        let dag: Graph = Graph::new_with_db_path(
+        let mut dag: Graph = Graph::new_with_db_path(
+            root_tx,
+            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rng.gen::<u16>())),
+        ); // Initialize graph
+
+        // Build a chain of executed transactions on top of the root
+        for nonce in 1..4 {
+            let parent = dag.nodes[dag.nodes.len() - 1].clone();
+            let tx = transaction::Transaction::new(
+                nonce,
+                sender,
+                sender,
+                BigUint::from_i64(0).unwrap(),
+                b"test transaction payload",
+                vec![parent.hash],
+            ); // Initialize transaction
+            let state = tx.execute(parent.state_entry);
+
+            dag.push(tx, Some(state));
+        }
+
+        // Snapshots are taken at the executed head, and kept until the next one is taken
+        let snapshot = dag.take_snapshot().unwrap().unwrap();
+        assert_eq!(snapshot.height, 4);
+        assert_eq!(snapshot.verify(snapshot.id()), Ok(()));
+        dag.store_snapshot(&snapshot).unwrap();
+        assert_eq!(
+            dag.latest_snapshot().unwrap().map(|kept| kept.id()),
+            Some(snapshot.id())
+        );
+
+        // An empty ledger picks up where the snapshot left off, without the transactions before it
+        let data_dir = path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rng.gen::<u16>()));
+        let mut installed = Graph::read_partial_from_disk_with_data_dir(&data_dir, "snapshot");
+        installed.install_snapshot(snapshot.clone()).unwrap();
+        assert_eq!(installed.nodes.len(), 1);
+        assert_eq!(installed.height(), dag.height());
+        assert_eq!(installed.root_hash(), dag.root_hash());
+        assert_eq!(
+            installed
+                .obtain_executed_head()
+                .unwrap()
+                .state_entry
+                .unwrap()
+                .hash,
+            snapshot.state.hash
+        );
+        assert_eq!(
+            installed.install_snapshot(snapshot),
+            Err(SnapshotError::NotEmpty)
+        );
+
+        // The base of the ledger survives a restart
+        assert_eq!(installed.write_to_disk(), Ok(()));
+        drop(installed);
+        assert_eq!(
+            Graph::read_partial_from_disk_with_data_dir(&data_dir, "snapshot").base_height(),
+            3
+        );
+
+        assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    }
+
+    #[test]
+    fn test_vesting() {
+        let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
+
+        let rand: u16 = rng.gen(); // Generate random number
+
+        let (alice, bob, carol) = (
+            address::Address::new(vec![1; 32]),
+            address::Address::new(vec![2; 32]),
+            address::Address::new(vec![3; 32]),
+        );
+
+        let root_tx = transaction::Transaction::new(
+            0,
+            alice,
+            alice,
+            BigUint::from_i64(100).unwrap(),
+            b"test transaction payload",
+            vec![],
+        ); // Give alice 100 finks to begin with
+        let root_hash = root_tx.hash; // Get the hash of the root transaction
+
+        let mut dag: Graph = Graph::new_with_db_path(
             root_tx,
             &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
         ); // Initialize graph
 
-        let found_root_tx = dag.get_with_hash(root_tx_hash).unwrap(); // Get root tx
+        // Lock 60 of alice's finks in bob's account until they're spent from a state 3 transactions deep
+        let lock_tx = transaction::Transaction::new(
+            1,
+            alice,
+            bob,
+            BigUint::from_i64(60).unwrap(),
+            &Unlock::Milestone(3).to_payload(),
+            vec![root_hash],
+        );
+        let lock_state = lock_tx.execute(dag.nodes[0].state_entry.clone());
+        dag.push(lock_tx.clone(), Some(lock_state.clone()));
+
+        let bob_funds = dag.locked_balance(bob, Utc::now());
+        assert_eq!(bob_funds.locked, BigUint::from_i64(60).unwrap());
+        assert_eq!(bob_funds.spendable, BigUint::zero());
+        assert_eq!(dag.get_vesting(bob).unwrap().locks.len(), 1);
+
+        // Bob can't send the locked funds on until the ledger is deep enough
+        let spend_tx = transaction::Transaction::new(
+            0,
+            bob,
+            carol,
+            BigUint::from_i64(60).unwrap(),
+            b"test transaction payload",
+            vec![lock_tx.hash],
+        );
+        let refused = spend_tx.execute(Some(lock_state.clone()));
+        assert!(refused.data.balances.get(&carol.to_str()).is_none());
+
+        let filler_tx = transaction::Transaction::new(
+            2,
+            alice,
+            alice,
+            BigUint::zero(),
+            b"test transaction payload",
+            vec![lock_tx.hash],
+        );
+        let spent = spend_tx.execute(Some(filler_tx.execute(Some(lock_state))));
+        assert_eq!(
+            spent.data.balances[&carol.to_str()],
+            BigUint::from_i64(60).unwrap()
+        );
+
+        assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    }
+
+    #[test]
+    fn test_mints() {
+        let mut rng: rand::prelude::ThreadRng = rand::thread_rng(); // Generate source of randomness
 
+        let rand: u16 = rng.gen(); // Generate random number
+
+        let alice = address::Address::new(vec![1; 32]);
+
+        let dag: Graph = Graph::new_with_db_path(
+            transaction::Transaction::new(
+                0,
+                alice,
+                alice,
+                BigUint::from_i64(0).unwrap(),
+                b"test transaction payload",
+                vec![],
+            ),
+            &path_clean::clean(&format!("{}/.tests/{}", io::db_dir(), rand.to_string())),
+        ); // Initialize graph
+
+        // Ordinary transactions don't mint anything
+        dag.apply_mint(&transaction::Transaction::new(
+            0,
+            alice,
+            issuance::issuance_address(),
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            vec![],
+        ))
+        .unwrap();
+        assert!(dag.get_mint(0).unwrap().is_none());
+
+        let mint = Mint {
+            epoch: 3,
+            transfers: vec![Transfer {
+                recipient: alice,
+                value: BigUint::from_i64(50).unwrap(),
+            }],
+            votes: Vec::new(),
+        };
+        dag.apply_mint(&transaction::Transaction::new(
+            0,
+            issuance::issuance_address(),
+            issuance::issuance_address(),
+            BigUint::from_i64(0).unwrap(),
+            &mint.to_payload(),
+            vec![],
+        ))
+        .unwrap();
+
+        assert_eq!(dag.get_mint(3).unwrap().unwrap().recipients, 1);
+        assert_eq!(dag.minted_supply(), BigUint::from_i64(50).unwrap());
         assert_eq!(
-            found_root_tx.transaction.transaction_data.payload,
-            b"test transaction payload"
-        ); // Ensure is same transaction
+            dag.overall_issuance(),
+            dag.genesis_issuance() + BigUint::from_i64(50).unwrap()
+        );
 
         assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
     }
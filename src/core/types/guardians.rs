@@ -0,0 +1,815 @@
+use chrono::{DateTime, Duration, Utc}; // Import time library
+
+use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::{HashMap, HashSet};
+
+use super::transaction::Transaction; // Import the transaction type
+use super::vesting::VestingSchedule; // Import the time-locked balance types
+
+use super::super::super::{common::address::Address, crypto::blake3}; // Import the address & hashing modules
+
+/// The prefix marking a transaction payload as a guardian instruction.
+pub const GUARDIAN_PAYLOAD_PREFIX: &[u8] = b"guardians:";
+
+/// The greatest number of guardians that an account may register.
+pub const MAX_GUARDIANS: usize = 16;
+
+/// The shortest timelock that an account may register, in seconds (one day).
+pub const MIN_TIMELOCK_SECS: u64 = 60 * 60 * 24;
+
+/// The timelock suggested to accounts registering guardians, in seconds (one week).
+pub const DEFAULT_TIMELOCK_SECS: u64 = 7 * MIN_TIMELOCK_SECS;
+
+/// How far, in seconds, the timestamp of a transaction initiating or executing a recovery may stray from the clock of
+/// the node accepting it. The timelock is measured between the two timestamps, so neither may be picked freely.
+pub const MAX_TIMESTAMP_SKEW_SECS: i64 = 10 * 60;
+
+/// An operation on an account's guardians, carried in the payload of a zero-value transaction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum GuardianInstruction {
+    /// Sent by an account to register (or replace) the guardians able to recover it
+    Register {
+        guardians: Vec<Address>, // The addresses of the guardians
+        threshold: u32,          // The number of guardians that must approve a recovery
+        timelock_secs: u64,      // How long a recovery must wait after being initiated
+    },
+
+    /// Sent by a guardian to begin recovering a lost account to a new owner. Counts as the guardian's approval.
+    InitiateRecovery {
+        account: Address,   // The account that should be recovered
+        new_owner: Address, // The address that the account should be reassigned to
+    },
+
+    /// Sent by a guardian to approve a pending recovery
+    ApproveRecovery {
+        account: Address,   // The account being recovered
+        new_owner: Address, // The new owner that the guardian approves of
+    },
+
+    /// Sent by an account that hasn't actually lost its key to call off a pending recovery
+    CancelRecovery,
+
+    /// Sent by a guardian once a recovery has been approved and its timelock has passed, moving the account's
+    /// balance and guardians to the new owner
+    ExecuteRecovery {
+        account: Address,   // The account being recovered
+        new_owner: Address, // The new owner of the account
+    },
+}
+
+/// The guardians registered by an account.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GuardianSet {
+    /// The addresses of the guardians
+    pub guardians: Vec<Address>,
+
+    /// The number of guardians that must approve a recovery
+    pub threshold: u32,
+
+    /// How long a recovery must wait after being initiated, in seconds
+    pub timelock_secs: u64,
+}
+
+/// A recovery that has been initiated, but not yet executed or cancelled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingRecovery {
+    /// The address that the account will be reassigned to
+    pub new_owner: Address,
+
+    /// The timestamp of the transaction that initiated the recovery
+    pub initiated_at: DateTime<Utc>,
+
+    /// The guardians that have approved the recovery
+    pub approvals: Vec<Address>,
+}
+
+/// Everything the ledger knows about an account's guardians.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GuardianRecord {
+    /// The account's guardians
+    pub set: GuardianSet,
+
+    /// The recovery in progress, if there is one
+    pub recovery: Option<PendingRecovery>,
+}
+
+/// An error encountered while checking a guardian instruction against the rules of the ledger.
+#[derive(Debug, Fail, PartialEq)]
+pub enum GuardianError {
+    #[fail(display = "the guardian instruction could not be decoded")]
+    Malformed,
+    #[fail(display = "guardian instructions must be sent to the guardian registry address")]
+    WrongRecipient,
+    #[fail(display = "guardian instructions may not transfer any finks")]
+    CarriesValue,
+    #[fail(display = "the guardian set is invalid: {}", reason)]
+    InvalidGuardianSet { reason: String },
+    #[fail(display = "account {} hasn't registered any guardians", account)]
+    NotRegistered { account: Address },
+    #[fail(display = "{} is not a guardian of account {}", guardian, account)]
+    NotAGuardian { guardian: Address, account: Address },
+    #[fail(display = "a recovery of account {} is already in progress", account)]
+    RecoveryPending { account: Address },
+    #[fail(display = "no recovery of account {} is in progress", account)]
+    NoRecoveryPending { account: Address },
+    #[fail(
+        display = "the pending recovery reassigns the account to {}, not {}",
+        expected, found
+    )]
+    NewOwnerMismatch { expected: Address, found: Address },
+    #[fail(display = "guardian {} has already approved the recovery", guardian)]
+    AlreadyApproved { guardian: Address },
+    #[fail(
+        display = "the recovery has {} of the {} approvals it requires",
+        approvals, threshold
+    )]
+    InsufficientApprovals { approvals: usize, threshold: u32 },
+    #[fail(
+        display = "the recovery's timelock doesn't expire until {}",
+        unlocks_at
+    )]
+    TimelockActive { unlocks_at: DateTime<Utc> },
+    #[fail(
+        display = "the instruction is timestamped {}, too far from the current time ({})",
+        timestamp, now
+    )]
+    UntimelyInstruction {
+        timestamp: DateTime<Utc>,
+        now: DateTime<Utc>,
+    },
+}
+
+/// Gets the address that each guardian instruction is sent to. Nobody holds the key to this address.
+pub fn registry_address() -> Address {
+    blake3::hash_slice(b"guardian_registry")
+}
+
+/// Implement a set of guardian instruction helper methods.
+impl GuardianInstruction {
+    /// Encodes the instruction as a transaction payload.
+    pub fn to_payload(&self) -> Vec<u8> {
+        [
+            GUARDIAN_PAYLOAD_PREFIX,
+            &bincode::serialize(self).unwrap_or_default()[..],
+        ]
+        .concat()
+    }
+
+    /// Decodes the guardian instruction carried in the given transaction payload. Payloads that aren't marked as
+    /// guardian instructions result in None.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload of the transaction
+    pub fn from_payload(payload: &[u8]) -> Result<Option<Self>, GuardianError> {
+        if !payload.starts_with(GUARDIAN_PAYLOAD_PREFIX) {
+            return Ok(None);
+        }
+
+        bincode::deserialize(&payload[GUARDIAN_PAYLOAD_PREFIX.len()..])
+            .map(Some)
+            .map_err(|_| GuardianError::Malformed)
+    }
+
+    /// Gets the account whose guardians the instruction concerns.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The sender of the transaction carrying the instruction
+    pub fn account(&self, sender: Address) -> Address {
+        match self {
+            Self::Register { .. } | Self::CancelRecovery => sender,
+            Self::InitiateRecovery { account, .. }
+            | Self::ApproveRecovery { account, .. }
+            | Self::ExecuteRecovery { account, .. } => *account,
+        }
+    }
+}
+
+/// Implement a set of guardian record helper methods.
+impl GuardianRecord {
+    /// Makes sure that the given address is one of the account's guardians.
+    ///
+    /// # Arguments
+    ///
+    /// * `guardian` - The address claiming to be a guardian
+    /// * `account` - The account that the record belongs to
+    fn ensure_guardian(&self, guardian: Address, account: Address) -> Result<(), GuardianError> {
+        if self.set.guardians.contains(&guardian) {
+            Ok(())
+        } else {
+            Err(GuardianError::NotAGuardian { guardian, account })
+        }
+    }
+
+    /// Gets the pending recovery, and makes sure that it reassigns the account to the given owner.
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - The account that the record belongs to
+    /// * `new_owner` - The owner that the recovery should reassign the account to
+    fn pending_recovery(
+        &self,
+        account: Address,
+        new_owner: Address,
+    ) -> Result<&PendingRecovery, GuardianError> {
+        let recovery = self
+            .recovery
+            .as_ref()
+            .ok_or(GuardianError::NoRecoveryPending { account })?;
+
+        if recovery.new_owner != new_owner {
+            return Err(GuardianError::NewOwnerMismatch {
+                expected: recovery.new_owner,
+                found: new_owner,
+            });
+        }
+
+        Ok(recovery)
+    }
+}
+
+/// Makes sure that the given transaction may carry out the given guardian instruction, considering the guardians
+/// currently registered for the account that the instruction concerns.
+///
+/// # Arguments
+///
+/// * `record` - The guardians registered for the account that the instruction concerns, if any
+/// * `tx` - The transaction carrying the instruction
+/// * `instruction` - The instruction carried by the transaction
+pub fn check(
+    record: Option<&GuardianRecord>,
+    tx: &Transaction,
+    instruction: &GuardianInstruction,
+) -> Result<(), GuardianError> {
+    let sender = tx.transaction_data.sender;
+    let account = instruction.account(sender);
+
+    // Guardian instructions never move any funds themselves
+    if tx.transaction_data.recipient != registry_address() {
+        return Err(GuardianError::WrongRecipient);
+    }
+    if !tx.transaction_data.value.is_zero() {
+        return Err(GuardianError::CarriesValue);
+    }
+
+    // Registrations are the only instruction that doesn't need existing guardians
+    if let GuardianInstruction::Register {
+        guardians,
+        threshold,
+        timelock_secs,
+    } = instruction
+    {
+        // The guardians can't be swapped out from under a recovery
+        if record.map_or(false, |record| record.recovery.is_some()) {
+            return Err(GuardianError::RecoveryPending { account });
+        }
+
+        return check_set(account, guardians, *threshold, *timelock_secs);
+    }
+    let record = record.ok_or(GuardianError::NotRegistered { account })?;
+
+    match instruction {
+        GuardianInstruction::Register { .. } => Ok(()),
+        GuardianInstruction::InitiateRecovery { .. } => {
+            record.ensure_guardian(sender, account)?;
+
+            if record.recovery.is_some() {
+                return Err(GuardianError::RecoveryPending { account });
+            }
+
+            Ok(())
+        }
+        GuardianInstruction::ApproveRecovery { new_owner, .. } => {
+            record.ensure_guardian(sender, account)?;
+
+            if record
+                .pending_recovery(account, *new_owner)?
+                .approvals
+                .contains(&sender)
+            {
+                return Err(GuardianError::AlreadyApproved { guardian: sender });
+            }
+
+            Ok(())
+        }
+        GuardianInstruction::CancelRecovery => {
+            // Only the account itself can call off a recovery, proving that its key isn't lost after all
+            if record.recovery.is_none() {
+                return Err(GuardianError::NoRecoveryPending { account });
+            }
+
+            Ok(())
+        }
+        GuardianInstruction::ExecuteRecovery { new_owner, .. } => {
+            record.ensure_guardian(sender, account)?;
+
+            let recovery = record.pending_recovery(account, *new_owner)?;
+
+            // Make sure enough guardians have signed off on the recovery
+            if recovery.approvals.len() < record.set.threshold as usize {
+                return Err(GuardianError::InsufficientApprovals {
+                    approvals: recovery.approvals.len(),
+                    threshold: record.set.threshold,
+                });
+            }
+
+            // Give the owner a chance to cancel the recovery
+            let unlocks_at =
+                recovery.initiated_at + Duration::seconds(record.set.timelock_secs as i64);
+            if tx.transaction_data.timestamp < unlocks_at {
+                return Err(GuardianError::TimelockActive { unlocks_at });
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Makes sure that a transaction initiating or executing a recovery was timestamped at around the given time, so that
+/// a guardian can't backdate a recovery's start, or postdate its execution, to get around its timelock. Unlike `check`,
+/// this depends on the clock of the node doing the checking, so it's only run when a transaction is first accepted.
+///
+/// # Arguments
+///
+/// * `tx` - The transaction carrying the instruction
+/// * `instruction` - The instruction carried by the transaction
+/// * `now` - The current time
+pub fn check_timestamp(
+    tx: &Transaction,
+    instruction: &GuardianInstruction,
+    now: DateTime<Utc>,
+) -> Result<(), GuardianError> {
+    match instruction {
+        GuardianInstruction::InitiateRecovery { .. }
+        | GuardianInstruction::ExecuteRecovery { .. } => {
+            let timestamp = tx.transaction_data.timestamp;
+
+            if (timestamp - now).num_seconds().abs() > MAX_TIMESTAMP_SKEW_SECS {
+                return Err(GuardianError::UntimelyInstruction { timestamp, now });
+            }
+
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Makes sure that the given guardian set is one that an account may register.
+///
+/// # Arguments
+///
+/// * `account` - The account registering the guardians
+/// * `guardians` - The addresses of the guardians
+/// * `threshold` - The number of guardians that must approve a recovery
+/// * `timelock_secs` - How long a recovery must wait after being initiated
+fn check_set(
+    account: Address,
+    guardians: &[Address],
+    threshold: u32,
+    timelock_secs: u64,
+) -> Result<(), GuardianError> {
+    let invalid = |reason: &str| {
+        Err(GuardianError::InvalidGuardianSet {
+            reason: reason.to_owned(),
+        })
+    };
+
+    if guardians.is_empty() || guardians.len() > MAX_GUARDIANS {
+        return invalid(&format!(
+            "between 1 and {} guardians must be registered",
+            MAX_GUARDIANS
+        ));
+    }
+    if guardians.iter().collect::<HashSet<_>>().len() != guardians.len() {
+        return invalid("each guardian may only be registered once");
+    }
+    if guardians.contains(&account) {
+        return invalid("an account cannot be its own guardian");
+    }
+    if threshold == 0 || threshold as usize > guardians.len() {
+        return invalid("the threshold must be between 1 and the number of guardians");
+    }
+    if timelock_secs < MIN_TIMELOCK_SECS {
+        return invalid(&format!(
+            "the timelock must be at least {} seconds",
+            MIN_TIMELOCK_SECS
+        ));
+    }
+
+    Ok(())
+}
+
+/// Determines how the guardian records of each affected account change once the given transaction is executed. The
+/// instruction is assumed to have passed `check`. A None record means that the account's record should be removed.
+///
+/// # Arguments
+///
+/// * `record` - The guardians registered for the account that the instruction concerns, if any
+/// * `tx` - The transaction carrying the instruction
+/// * `instruction` - The instruction carried by the transaction
+pub fn apply(
+    record: Option<GuardianRecord>,
+    tx: &Transaction,
+    instruction: &GuardianInstruction,
+) -> Vec<(Address, Option<GuardianRecord>)> {
+    let sender = tx.transaction_data.sender;
+    let account = instruction.account(sender);
+
+    match (instruction, record) {
+        (
+            GuardianInstruction::Register {
+                guardians,
+                threshold,
+                timelock_secs,
+            },
+            _,
+        ) => vec![(
+            account,
+            Some(GuardianRecord {
+                set: GuardianSet {
+                    guardians: guardians.clone(),
+                    threshold: *threshold,
+                    timelock_secs: *timelock_secs,
+                },
+                recovery: None,
+            }),
+        )],
+        (GuardianInstruction::InitiateRecovery { new_owner, .. }, Some(mut record)) => {
+            record.recovery = Some(PendingRecovery {
+                new_owner: *new_owner,
+                initiated_at: tx.transaction_data.timestamp,
+                approvals: vec![sender],
+            });
+
+            vec![(account, Some(record))]
+        }
+        (GuardianInstruction::ApproveRecovery { .. }, Some(mut record)) => {
+            if let Some(recovery) = record.recovery.as_mut() {
+                recovery.approvals.push(sender);
+            }
+
+            vec![(account, Some(record))]
+        }
+        (GuardianInstruction::CancelRecovery, Some(mut record)) => {
+            record.recovery = None;
+
+            vec![(account, Some(record))]
+        }
+        (GuardianInstruction::ExecuteRecovery { new_owner, .. }, Some(mut record)) => {
+            record.recovery = None;
+
+            // The guardians keep watching over the account under its new owner
+            vec![(account, None), (*new_owner, Some(record))]
+        }
+        (_, None) => Vec::new(),
+    }
+}
+
+/// Carries out the guardian instruction carried by the given transaction, if it carries one that the guardian records
+/// of the state being executed on allow: the records are updated, and the entire balance of a recovered account is
+/// moved to its new owner, along with any of its locks.
+///
+/// # Arguments
+///
+/// * `tx` - The transaction being executed
+/// * `records` - The guardian records of each account, which will be updated in place
+/// * `balances` - The balances of each account, which will be updated in place
+/// * `locks` - The funds locked in each account, which will be updated in place
+pub fn execute(
+    tx: &Transaction,
+    records: &mut HashMap<String, GuardianRecord>,
+    balances: &mut HashMap<String, BigUint>,
+    locks: &mut HashMap<String, VestingSchedule>,
+) {
+    let instruction = match GuardianInstruction::from_payload(&tx.transaction_data.payload) {
+        Ok(Some(instruction)) => instruction,
+        _ => return,
+    };
+
+    let record = records
+        .get(&instruction.account(tx.transaction_data.sender).to_str())
+        .cloned();
+    if check(record.as_ref(), tx, &instruction).is_err() {
+        return;
+    }
+
+    // Store each of the updated records
+    for (address, record) in apply(record, tx, &instruction) {
+        match record {
+            Some(record) => records.insert(address.to_str(), record),
+            None => records.remove(&address.to_str()),
+        };
+    }
+
+    // Take the lost account's balance, and give it to the new owner
+    if let GuardianInstruction::ExecuteRecovery { account, new_owner } = instruction {
+        if let Some(balance) = balances.remove(&account.to_str()) {
+            *balances
+                .entry(new_owner.to_str())
+                .or_insert_with(BigUint::zero) += balance;
+        }
+
+        // Don't let a recovery be used to get around a lock
+        if let Some(moved) = locks.remove(&account.to_str()) {
+            locks
+                .entry(new_owner.to_str())
+                .or_default()
+                .locks
+                .extend(moved.locks);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::vesting::{TimeLock, Unlock}; // Import the time lock types
+    use super::*; // Import names from our parent module
+
+    /// Makes a guardian transaction sent by the given address, at the given offset from now.
+    fn instruction_tx(
+        sender: Address,
+        instruction: &GuardianInstruction,
+        offset_secs: i64,
+    ) -> Transaction {
+        let mut tx = Transaction::new(
+            0,
+            sender,
+            registry_address(),
+            BigUint::zero(),
+            &instruction.to_payload(),
+            vec![],
+        ); // Make the transaction
+        tx.transaction_data.timestamp = Utc::now() + Duration::seconds(offset_secs);
+
+        tx
+    }
+
+    /// Checks the given instruction, and applies it to the given records if it passes.
+    fn run(
+        records: &mut HashMap<Address, GuardianRecord>,
+        sender: Address,
+        instruction: GuardianInstruction,
+        offset_secs: i64,
+    ) -> Result<(), GuardianError> {
+        let tx = instruction_tx(sender, &instruction, offset_secs);
+        let account = instruction.account(sender);
+
+        check(records.get(&account), &tx, &instruction)?;
+
+        for (address, record) in apply(records.get(&account).cloned(), &tx, &instruction) {
+            match record {
+                Some(record) => records.insert(address, record),
+                None => records.remove(&address),
+            };
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_payload() {
+        let instruction = GuardianInstruction::CancelRecovery;
+
+        assert_eq!(
+            GuardianInstruction::from_payload(&instruction.to_payload()),
+            Ok(Some(instruction))
+        ); // Ensure the instruction survives encoding
+        assert_eq!(GuardianInstruction::from_payload(b"hello"), Ok(None)); // Ordinary payloads aren't instructions
+        assert_eq!(
+            GuardianInstruction::from_payload(b"guardians:?"),
+            Err(GuardianError::Malformed)
+        ); // Garbage should be rejected
+    }
+
+    #[test]
+    fn test_recovery() {
+        let (account, new_owner) = (Address::new(vec![1; 32]), Address::new(vec![2; 32]));
+        let guardians = vec![
+            Address::new(vec![3; 32]),
+            Address::new(vec![4; 32]),
+            Address::new(vec![5; 32]),
+        ];
+
+        let mut records = HashMap::new(); // The guardians of each account
+
+        // Nobody can be recovered before registering
+        assert!(run(
+            &mut records,
+            guardians[0],
+            GuardianInstruction::InitiateRecovery { account, new_owner },
+            0
+        )
+        .is_err());
+
+        // Register 2-of-3 guardians
+        assert!(run(
+            &mut records,
+            account,
+            GuardianInstruction::Register {
+                guardians: guardians.clone(),
+                threshold: 2,
+                timelock_secs: MIN_TIMELOCK_SECS,
+            },
+            0
+        )
+        .is_ok());
+
+        // Strangers can't start a recovery
+        assert!(run(
+            &mut records,
+            new_owner,
+            GuardianInstruction::InitiateRecovery { account, new_owner },
+            0
+        )
+        .is_err());
+
+        assert!(run(
+            &mut records,
+            guardians[0],
+            GuardianInstruction::InitiateRecovery { account, new_owner },
+            0
+        )
+        .is_ok());
+
+        // A single approval isn't enough
+        assert_eq!(
+            run(
+                &mut records,
+                guardians[0],
+                GuardianInstruction::ExecuteRecovery { account, new_owner },
+                MIN_TIMELOCK_SECS as i64 + 1
+            ),
+            Err(GuardianError::InsufficientApprovals {
+                approvals: 1,
+                threshold: 2
+            })
+        );
+
+        assert!(run(
+            &mut records,
+            guardians[1],
+            GuardianInstruction::ApproveRecovery { account, new_owner },
+            0
+        )
+        .is_ok());
+
+        // The timelock must pass first
+        assert!(run(
+            &mut records,
+            guardians[1],
+            GuardianInstruction::ExecuteRecovery { account, new_owner },
+            60
+        )
+        .is_err());
+
+        assert!(run(
+            &mut records,
+            guardians[1],
+            GuardianInstruction::ExecuteRecovery { account, new_owner },
+            MIN_TIMELOCK_SECS as i64 + 1
+        )
+        .is_ok());
+
+        assert!(records.get(&account).is_none()); // The old account should be forgotten
+        assert_eq!(records.get(&new_owner).unwrap().set.guardians, guardians); // The guardians should carry over
+    }
+
+    #[test]
+    fn test_cancel() {
+        let (account, new_owner, guardian) = (
+            Address::new(vec![1; 32]),
+            Address::new(vec![2; 32]),
+            Address::new(vec![3; 32]),
+        );
+
+        let mut records = HashMap::new(); // The guardians of each account
+
+        assert!(run(
+            &mut records,
+            account,
+            GuardianInstruction::Register {
+                guardians: vec![guardian],
+                threshold: 1,
+                timelock_secs: MIN_TIMELOCK_SECS,
+            },
+            0
+        )
+        .is_ok());
+        assert!(run(
+            &mut records,
+            guardian,
+            GuardianInstruction::InitiateRecovery { account, new_owner },
+            0
+        )
+        .is_ok());
+        assert!(run(
+            &mut records,
+            account,
+            GuardianInstruction::CancelRecovery,
+            0
+        )
+        .is_ok()); // The owner still has the key
+
+        assert!(records.get(&account).unwrap().recovery.is_none()); // The recovery should be gone
+    }
+
+    #[test]
+    fn test_check_timestamp() {
+        let (account, new_owner) = (Address::new(vec![1; 32]), Address::new(vec![2; 32]));
+        let initiate = GuardianInstruction::InitiateRecovery { account, new_owner };
+
+        let now = Utc::now();
+        assert!(check_timestamp(&instruction_tx(new_owner, &initiate, 0), &initiate, now).is_ok());
+
+        // A recovery can't be backdated to get through its timelock sooner
+        let backdated = instruction_tx(new_owner, &initiate, -(MIN_TIMELOCK_SECS as i64));
+        assert_eq!(
+            check_timestamp(&backdated, &initiate, now),
+            Err(GuardianError::UntimelyInstruction {
+                timestamp: backdated.transaction_data.timestamp,
+                now
+            })
+        );
+
+        // Nor can it be executed ahead of time
+        let execute = GuardianInstruction::ExecuteRecovery { account, new_owner };
+        let postdated = instruction_tx(new_owner, &execute, MIN_TIMELOCK_SECS as i64);
+        assert!(check_timestamp(&postdated, &execute, now).is_err());
+
+        // Everything else happens whenever it happens
+        let cancel = GuardianInstruction::CancelRecovery;
+        let late = instruction_tx(account, &cancel, -(MIN_TIMELOCK_SECS as i64));
+        assert!(check_timestamp(&late, &cancel, now).is_ok());
+    }
+
+    #[test]
+    fn test_execute() {
+        let (account, new_owner, guardian) = (
+            Address::new(vec![1; 32]),
+            Address::new(vec![2; 32]),
+            Address::new(vec![3; 32]),
+        );
+
+        let mut balances = HashMap::new();
+        balances.insert(account.to_str(), BigUint::from(10 as u8));
+        balances.insert(new_owner.to_str(), BigUint::from(1 as u8));
+
+        let mut records = HashMap::new(); // The guardians of each account, as kept in the state
+        let mut locks = HashMap::new();
+        locks.insert(
+            account.to_str(),
+            VestingSchedule {
+                locks: vec![TimeLock {
+                    amount: BigUint::from(5 as u8),
+                    unlock: Unlock::Milestone(100),
+                }],
+            },
+        );
+        let execute_recovery = GuardianInstruction::ExecuteRecovery { account, new_owner };
+
+        // Nothing happens until a recovery has been approved, and its timelock has passed
+        execute(
+            &instruction_tx(guardian, &execute_recovery, 0),
+            &mut records,
+            &mut balances,
+            &mut locks,
+        );
+        assert_eq!(balances[&account.to_str()], BigUint::from(10 as u8));
+
+        for (sender, instruction, offset_secs) in vec![
+            (
+                account,
+                GuardianInstruction::Register {
+                    guardians: vec![guardian],
+                    threshold: 1,
+                    timelock_secs: MIN_TIMELOCK_SECS,
+                },
+                0,
+            ),
+            (
+                guardian,
+                GuardianInstruction::InitiateRecovery { account, new_owner },
+                0,
+            ),
+            (guardian, execute_recovery, MIN_TIMELOCK_SECS as i64 + 1),
+        ] {
+            execute(
+                &instruction_tx(sender, &instruction, offset_secs),
+                &mut records,
+                &mut balances,
+                &mut locks,
+            );
+        }
+
+        assert!(balances.get(&account.to_str()).is_none()); // The balance should have moved
+        assert_eq!(balances[&new_owner.to_str()], BigUint::from(11 as u8));
+        assert!(records.get(&account.to_str()).is_none()); // The guardians should follow the account
+        assert_eq!(records[&new_owner.to_str()].set.guardians, vec![guardian]);
+        assert!(locks.get(&account.to_str()).is_none()); // So should the locks
+        assert_eq!(locks[&new_owner.to_str()].locks.len(), 1);
+    }
+}
@@ -0,0 +1,726 @@
+use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::{HashMap, HashSet};
+
+use super::batch::Transfer; // Import the single payment type
+use super::rewards; // Import the validator rewards accounting
+use super::transaction::TransactionData; // Import the transaction type
+
+use super::super::super::{
+    common::address::Address,
+    core::sys::vote::Vote,
+    crypto::{blake3, hash::Hash},
+}; // Import the address, vote & hashing modules
+
+/// The prefix marking a transaction payload as the minting of an epoch's reward.
+pub const MINT_PAYLOAD_PREFIX: &[u8] = b"mint:";
+
+/// The name of the proposal parameter that mints carry.
+pub const ISSUANCE_PARAM: &str = "ledger::issuance";
+
+/// The default number of transactions in each issuance epoch.
+pub const DEFAULT_EPOCH_LENGTH: u64 = 1000;
+
+/// How many finks are minted, and when. Nothing is minted unless per_epoch is set to something other than zero.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IssuanceSchedule {
+    /// The number of finks minted at the end of each epoch, before any halvings
+    pub per_epoch: BigUint,
+
+    /// The number of transactions in each epoch
+    pub epoch_length: u64,
+
+    /// The number of epochs after which the reward is halved, or zero if it never is
+    pub halving_epochs: u64,
+}
+
+/// The reward for a single epoch, paid out to those that voted during it in proportion to how often they voted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Mint {
+    /// The epoch that the reward is for
+    pub epoch: u64,
+
+    /// Each of the payments, in the order in which they are credited
+    pub transfers: Vec<Transfer>,
+
+    /// Each of the signed votes cast during the epoch, which every node derives the payments from
+    pub votes: Vec<Vote>,
+}
+
+/// A mint, as laid out before mints carried the votes that they pay out for.
+#[derive(Deserialize)]
+struct LegacyMint {
+    /// The epoch that the reward is for
+    epoch: u64,
+
+    /// Each of the payments, in the order in which they are credited
+    transfers: Vec<Transfer>,
+}
+
+/// A mint that has been executed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MintRecord {
+    /// The epoch that the reward was for
+    pub epoch: u64,
+
+    /// The hash of the transaction carrying the mint
+    pub tx_hash: Hash,
+
+    /// The number of finks minted
+    pub amount: BigUint,
+
+    /// The number of accounts that were paid
+    pub recipients: usize,
+}
+
+/// An account of the network's supply.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SupplyReport {
+    /// The number of finks allocated in the genesis
+    pub genesis: BigUint,
+
+    /// The number of finks minted since the genesis
+    pub minted: BigUint,
+
+    /// The number of finks burned since the genesis
+    pub burned: BigUint,
+
+    /// The total number of finks in circulation
+    pub total: BigUint,
+
+    /// The schedule that mints currently follow
+    pub schedule: IssuanceSchedule,
+
+    /// The epoch that the ledger is currently in
+    pub current_epoch: u64,
+
+    /// The number of finks that will be minted for the current epoch once it's over
+    pub next_reward: BigUint,
+
+    /// Each of the mints executed so far, oldest epoch first
+    pub mints: Vec<MintRecord>,
+}
+
+/// An error encountered while checking a mint against the rules of the ledger.
+#[derive(Debug, Fail, PartialEq)]
+pub enum IssuanceError {
+    #[fail(display = "the mint could not be decoded")]
+    Malformed,
+    #[fail(display = "the transaction does not carry a mint")]
+    NotAMint,
+    #[fail(display = "mints must be sent from and to the issuance address")]
+    WrongAddress,
+    #[fail(display = "mints may not send any value of their own")]
+    CarriesValue,
+    #[fail(display = "issuance is disabled")]
+    Disabled,
+    #[fail(
+        display = "epoch {} isn't over yet (the ledger is in epoch {})",
+        epoch, current
+    )]
+    EpochNotOver {
+        epoch: u64,   // The epoch that the mint is for
+        current: u64, // The epoch that the ledger is in
+    },
+    #[fail(display = "the reward for epoch {} has already been minted", epoch)]
+    AlreadyMinted {
+        epoch: u64, // The epoch that the mint is for
+    },
+    #[fail(
+        display = "the reward for the epoch is {} finks, but the mint pays out {} finks",
+        expected, found
+    )]
+    RewardMismatch {
+        expected: BigUint, // The reward dictated by the schedule
+        found: BigUint,    // The total paid out by the mint
+    },
+    #[fail(display = "the mint pays {} more than once", recipient)]
+    DuplicateRecipient {
+        recipient: Address, // The recipient paid more than once
+    },
+    #[fail(display = "the mint pays nothing to {}", recipient)]
+    EmptyTransfer {
+        recipient: Address, // The recipient paid nothing
+    },
+    #[fail(display = "the mint counts a vote that isn't signed by its voter")]
+    InvalidVote,
+    #[fail(
+        display = "the vote cast by {} on proposal {} has already been counted",
+        voter, proposal
+    )]
+    VoteAlreadyCounted {
+        voter: Address, // The account that cast the vote
+        proposal: Hash, // The proposal that the vote was cast on
+    },
+    #[fail(
+        display = "the mint pays {} something other than the votes that it carries entitle it to",
+        recipient
+    )]
+    UnearnedTransfer {
+        recipient: Address, // The recipient paid the wrong amount
+    },
+}
+
+/// Gets the address that mints are sent from. Nobody holds its key; mints are only ever executed once the network
+/// has voted them through.
+pub fn issuance_address() -> Address {
+    blake3::hash_slice(b"issuance")
+}
+
+impl Default for IssuanceSchedule {
+    /// Builds a schedule that never mints anything.
+    fn default() -> Self {
+        Self {
+            per_epoch: BigUint::zero(),
+            epoch_length: DEFAULT_EPOCH_LENGTH,
+            halving_epochs: 0,
+        }
+    }
+}
+
+/// Implement a set of issuance schedule helper methods.
+impl IssuanceSchedule {
+    /// Checks whether or not the schedule mints anything at all.
+    pub fn is_enabled(&self) -> bool {
+        !self.per_epoch.is_zero() && self.epoch_length > 0
+    }
+
+    /// Gets the epoch that a ledger holding the given number of transactions is in.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - The number of transactions in the ledger
+    pub fn epoch_at(&self, height: u64) -> u64 {
+        height / self.epoch_length.max(1)
+    }
+
+    /// Gets the number of finks minted for the given epoch.
+    ///
+    /// # Arguments
+    ///
+    /// * `epoch` - The epoch that the reward is for
+    pub fn reward_for_epoch(&self, epoch: u64) -> BigUint {
+        if self.halving_epochs == 0 {
+            return self.per_epoch.clone();
+        }
+
+        let halvings = epoch / self.halving_epochs;
+        if halvings >= self.per_epoch.bits() as u64 {
+            return BigUint::zero();
+        }
+
+        &self.per_epoch >> halvings as usize
+    }
+}
+
+/// Implement a set of mint helper methods.
+impl Mint {
+    /// Gets the total number of finks minted.
+    pub fn total(&self) -> BigUint {
+        self.transfers
+            .iter()
+            .fold(BigUint::zero(), |total, transfer| total + &transfer.value)
+    }
+
+    /// Encodes the mint as a transaction payload.
+    pub fn to_payload(&self) -> Vec<u8> {
+        [
+            MINT_PAYLOAD_PREFIX,
+            &bincode::serialize(self).unwrap_or_default()[..],
+        ]
+        .concat()
+    }
+
+    /// Decodes the mint carried in the given transaction payload. Payloads that aren't marked as mints result in
+    /// None.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload of the transaction
+    pub fn from_payload(payload: &[u8]) -> Result<Option<Self>, IssuanceError> {
+        if !payload.starts_with(MINT_PAYLOAD_PREFIX) {
+            return Ok(None);
+        }
+
+        let encoded = &payload[MINT_PAYLOAD_PREFIX.len()..];
+
+        // Mints made before they carried their votes don't have any
+        bincode::deserialize(encoded)
+            .or_else(|_| {
+                bincode::deserialize(encoded).map(|legacy: LegacyMint| Self {
+                    epoch: legacy.epoch,
+                    transfers: legacy.transfers,
+                    votes: Vec::new(),
+                })
+            })
+            .map(Some)
+            .map_err(|_| IssuanceError::Malformed)
+    }
+}
+
+/// Gets the account that cast the given vote, and the proposal that it was cast on, if the vote is signed. Each
+/// account's vote on a proposal only counts towards a single mint.
+///
+/// # Arguments
+///
+/// * `vote` - The signed vote
+pub fn vote_key(vote: &Vote) -> Option<(Address, Hash)> {
+    if !vote.valid() {
+        return None;
+    }
+
+    Some((vote.voter_address()?, vote.target_proposal))
+}
+
+/// Counts the number of votes cast by each account among the given signed votes. Unsigned votes aren't counted, and
+/// neither is more than one vote by the same account on the same proposal.
+///
+/// # Arguments
+///
+/// * `votes` - The votes that should be counted
+pub fn participation(votes: &[Vote]) -> HashMap<Address, u64> {
+    let mut seen = HashSet::new();
+    let mut counts = HashMap::new();
+
+    for (voter, proposal) in votes.iter().filter_map(vote_key) {
+        if seen.insert((voter, proposal)) {
+            *counts.entry(voter).or_insert(0) += 1;
+        }
+    }
+
+    counts
+}
+
+/// Splits the given reward between each of the given participants in proportion to the number of votes that they
+/// cast. Whatever can't be split evenly goes to the most active participant, so every node derives the same
+/// transfers from the same participation.
+///
+/// # Arguments
+///
+/// * `reward` - The number of finks that should be paid out
+/// * `participation` - The number of votes cast by each participant
+pub fn distribute(reward: &BigUint, participation: &HashMap<Address, u64>) -> Vec<Transfer> {
+    // Order the participants deterministically, most active first
+    let mut participants: Vec<(&Address, &u64)> = participation
+        .iter()
+        .filter(|(_, votes)| **votes > 0)
+        .collect();
+    participants.sort_by(|a, b| b.1.cmp(a.1).then((*a.0).cmp(&*b.0)));
+
+    let total_votes: u64 = participants.iter().map(|(_, votes)| **votes).sum();
+    if total_votes == 0 || reward.is_zero() {
+        return Vec::new();
+    }
+
+    let mut transfers: Vec<Transfer> = participants
+        .iter()
+        .map(|(address, votes)| Transfer {
+            recipient: **address,
+            value: reward * BigUint::from(**votes) / BigUint::from(total_votes),
+        })
+        .collect();
+
+    // Hand the remainder to the most active participant
+    let paid = transfers
+        .iter()
+        .fold(BigUint::zero(), |paid, transfer| paid + &transfer.value);
+    transfers[0].value += reward - paid;
+
+    transfers.retain(|transfer| !transfer.value.is_zero());
+
+    transfers
+}
+
+/// Makes sure that the given transaction data carries a mint that the given schedule allows at the given height, and
+/// that each of its payments is exactly what the votes that it carries entitle the payee to, so that every node
+/// arrives at the same payments regardless of which votes it saw itself.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction that should be checked
+/// * `schedule` - The schedule that the mint should follow
+/// * `height` - The number of transactions in the ledger
+/// * `already_minted` - Whether or not the reward for the mint's epoch has already been minted
+/// * `counted` - The voter & proposal of each of the votes counted by the mints executed so far
+pub fn check(
+    tx: &TransactionData,
+    schedule: &IssuanceSchedule,
+    height: u64,
+    already_minted: bool,
+    counted: &HashSet<(Address, Hash)>,
+) -> Result<Mint, IssuanceError> {
+    if tx.sender != issuance_address() || tx.recipient != issuance_address() {
+        return Err(IssuanceError::WrongAddress);
+    }
+
+    if !tx.value.is_zero() {
+        return Err(IssuanceError::CarriesValue);
+    }
+
+    let mint = Mint::from_payload(&tx.payload)?.ok_or(IssuanceError::NotAMint)?;
+
+    if !schedule.is_enabled() {
+        return Err(IssuanceError::Disabled);
+    }
+
+    // Rewards are only minted once everyone has had the chance to vote during the epoch
+    let current = schedule.epoch_at(height);
+    if mint.epoch >= current {
+        return Err(IssuanceError::EpochNotOver {
+            epoch: mint.epoch,
+            current,
+        });
+    }
+
+    if already_minted {
+        return Err(IssuanceError::AlreadyMinted { epoch: mint.epoch });
+    }
+
+    let expected = schedule.reward_for_epoch(mint.epoch);
+    let found = mint.total();
+    if expected != found {
+        return Err(IssuanceError::RewardMismatch { expected, found });
+    }
+
+    // Each recipient should be paid exactly once, and actually be paid something
+    let mut seen = Vec::with_capacity(mint.transfers.len());
+    for transfer in &mint.transfers {
+        if seen.contains(&transfer.recipient) {
+            return Err(IssuanceError::DuplicateRecipient {
+                recipient: transfer.recipient,
+            });
+        }
+        seen.push(transfer.recipient);
+
+        if transfer.value.is_zero() || transfer.recipient == issuance_address() {
+            return Err(IssuanceError::EmptyTransfer {
+                recipient: transfer.recipient,
+            });
+        }
+    }
+
+    // Each vote must be signed, and counted once
+    let mut seen = HashSet::with_capacity(mint.votes.len());
+    for vote in &mint.votes {
+        let (voter, proposal) = vote_key(vote).ok_or(IssuanceError::InvalidVote)?;
+
+        if counted.contains(&(voter, proposal)) || !seen.insert((voter, proposal)) {
+            return Err(IssuanceError::VoteAlreadyCounted { voter, proposal });
+        }
+    }
+
+    // Each payment must be the share of the reward that the votes earn its payee, accruing until it's claimed
+    let earned = rewards::accrue(distribute(&expected, &participation(&mint.votes)));
+    for (i, transfer) in mint.transfers.iter().enumerate() {
+        if earned.get(i) != Some(transfer) {
+            return Err(IssuanceError::UnearnedTransfer {
+                recipient: transfer.recipient,
+            });
+        }
+    }
+
+    Ok(mint)
+}
+
+/// Credits each of the recipients of the mint carried in the given transaction data, if there is one and it was
+/// sent from the issuance address.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction being executed
+/// * `balances` - The balances of each account, which should be updated
+pub fn execute_mint(tx: &TransactionData, balances: &mut HashMap<String, BigUint>) {
+    if tx.sender != issuance_address() {
+        return;
+    }
+
+    let mint = match Mint::from_payload(&tx.payload) {
+        Ok(Some(mint)) => mint,
+        _ => return,
+    };
+
+    for transfer in mint.transfers {
+        *balances
+            .entry(transfer.recipient.to_str())
+            .or_insert_with(BigUint::zero) += transfer.value;
+    }
+
+    // The issuance address never holds anything, so don't let it clutter the state
+    if balances
+        .get(&issuance_address().to_str())
+        .map_or(false, Zero::is_zero)
+    {
+        balances.remove(&issuance_address().to_str());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::transaction::Transaction;
+    use super::*; // Import names from our parent module
+
+    use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    use rand::rngs::OsRng; // Import the os's rng
+
+    /// Makes a mint transaction for the given epoch paying each of the given recipients the given amount.
+    fn mint_tx(epoch: u64, payments: &[(&str, u64)]) -> Transaction {
+        carrying(&Mint {
+            epoch,
+            transfers: payments
+                .iter()
+                .map(|(recipient, value)| Transfer {
+                    recipient: blake3::hash_slice(recipient.as_bytes()),
+                    value: BigUint::from(*value),
+                })
+                .collect(),
+            votes: Vec::new(),
+        })
+    }
+
+    /// Makes a transaction carrying the given mint.
+    fn carrying(mint: &Mint) -> Transaction {
+        Transaction::new(
+            0,
+            issuance_address(),
+            issuance_address(),
+            BigUint::zero(),
+            &mint.to_payload(),
+            vec![],
+        )
+    }
+
+    /// Signs a vote in favor of each of the given proposals with the given keypair.
+    fn votes(keypair: &Keypair, proposals: &[&[u8]]) -> Vec<Vote> {
+        proposals
+            .iter()
+            .map(|proposal| {
+                Vote::new(
+                    blake3::hash_slice(proposal),
+                    true,
+                    Keypair::from_bytes(&keypair.to_bytes()).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reward_for_epoch() {
+        let schedule = IssuanceSchedule {
+            per_epoch: BigUint::from(100 as u8),
+            epoch_length: 10,
+            halving_epochs: 2,
+        };
+
+        assert_eq!(schedule.epoch_at(25), 2);
+        assert_eq!(schedule.reward_for_epoch(1), BigUint::from(100 as u8));
+        assert_eq!(schedule.reward_for_epoch(2), BigUint::from(50 as u8)); // Halved after two epochs
+        assert_eq!(schedule.reward_for_epoch(200), BigUint::zero()); // Eventually nothing is left to mint
+        assert!(!IssuanceSchedule::default().is_enabled()); // Supply is fixed unless governance says otherwise
+    }
+
+    #[test]
+    fn test_distribute() {
+        let mut participation = HashMap::new();
+        participation.insert(blake3::hash_slice(b"alice"), 2);
+        participation.insert(blake3::hash_slice(b"bob"), 1);
+
+        let transfers = distribute(&BigUint::from(100 as u8), &participation);
+        assert_eq!(transfers[0].recipient, blake3::hash_slice(b"alice")); // The most active participant comes first
+        assert_eq!(transfers[0].value, BigUint::from(67 as u8)); // ...and gets the remainder
+        assert_eq!(transfers[1].value, BigUint::from(33 as u8));
+
+        assert!(distribute(&BigUint::from(100 as u8), &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_check() {
+        let schedule = IssuanceSchedule {
+            per_epoch: BigUint::from(100 as u8),
+            epoch_length: 10,
+            halving_epochs: 0,
+        };
+
+        // Alice voted on three proposals during the epoch, and bob on two
+        let (alice, bob) = (
+            Keypair::generate(&mut OsRng {}),
+            Keypair::generate(&mut OsRng {}),
+        );
+        let mut cast = votes(&alice, &[b"a", b"b", b"c"]);
+        cast.extend(votes(&bob, &[b"a", b"b"]));
+
+        let mint = Mint {
+            epoch: 0,
+            transfers: rewards::accrue(distribute(
+                &BigUint::from(100 as u8),
+                &participation(&cast),
+            )),
+            votes: cast.clone(),
+        };
+        let tx = carrying(&mint);
+        let counted = HashSet::new();
+        assert_eq!(
+            check(&tx.transaction_data, &schedule, 10, false, &counted),
+            Ok(mint.clone())
+        );
+        assert_eq!(
+            mint.transfers[0],
+            Transfer {
+                recipient: rewards::escrow_address(Address::from_key_pair(&alice)),
+                value: BigUint::from(60 as u8),
+            }
+        ); // Alice's share accrues in her escrow
+
+        assert_eq!(
+            check(&tx.transaction_data, &schedule, 5, false, &counted),
+            Err(IssuanceError::EpochNotOver {
+                epoch: 0,
+                current: 0
+            })
+        ); // The epoch has to be over
+        assert_eq!(
+            check(&tx.transaction_data, &schedule, 10, true, &counted),
+            Err(IssuanceError::AlreadyMinted { epoch: 0 })
+        );
+        assert_eq!(
+            check(
+                &mint_tx(0, &[("alice", 60)]).transaction_data,
+                &schedule,
+                10,
+                false,
+                &counted
+            ),
+            Err(IssuanceError::RewardMismatch {
+                expected: BigUint::from(100 as u8),
+                found: BigUint::from(60 as u8),
+            })
+        );
+        assert_eq!(
+            check(
+                &tx.transaction_data,
+                &IssuanceSchedule::default(),
+                10_000,
+                false,
+                &counted
+            ),
+            Err(IssuanceError::Disabled)
+        );
+
+        // The payments have to follow from the votes
+        let mut swapped = mint.clone();
+        swapped.transfers[0].value = BigUint::from(40 as u8);
+        swapped.transfers[1].value = BigUint::from(60 as u8);
+        assert_eq!(
+            check(
+                &carrying(&swapped).transaction_data,
+                &schedule,
+                10,
+                false,
+                &counted
+            ),
+            Err(IssuanceError::UnearnedTransfer {
+                recipient: mint.transfers[0].recipient
+            })
+        );
+        assert_eq!(
+            check(
+                &mint_tx(0, &[("alice", 100)]).transaction_data,
+                &schedule,
+                10,
+                false,
+                &counted
+            ),
+            Err(IssuanceError::UnearnedTransfer {
+                recipient: blake3::hash_slice(b"alice")
+            })
+        ); // Nobody earns anything without any votes
+
+        // ...and each vote has to be signed by its voter, and only counted once
+        let mut forged = mint.clone();
+        forged.votes[0].in_favor = false;
+        assert_eq!(
+            check(
+                &carrying(&forged).transaction_data,
+                &schedule,
+                10,
+                false,
+                &counted
+            ),
+            Err(IssuanceError::InvalidVote)
+        );
+
+        let mut repeated = mint.clone();
+        repeated.votes.push(cast[4].clone());
+        let bobs_vote = (Address::from_key_pair(&bob), blake3::hash_slice(b"b"));
+        assert_eq!(
+            check(
+                &carrying(&repeated).transaction_data,
+                &schedule,
+                10,
+                false,
+                &counted
+            ),
+            Err(IssuanceError::VoteAlreadyCounted {
+                voter: bobs_vote.0,
+                proposal: bobs_vote.1
+            })
+        );
+        assert_eq!(
+            check(
+                &tx.transaction_data,
+                &schedule,
+                10,
+                false,
+                &vec![bobs_vote].into_iter().collect()
+            ),
+            Err(IssuanceError::VoteAlreadyCounted {
+                voter: bobs_vote.0,
+                proposal: bobs_vote.1
+            })
+        );
+
+        // Mints made before they carried their votes can still be decoded
+        let legacy = [
+            MINT_PAYLOAD_PREFIX,
+            &bincode::serialize(&(7 as u64, &mint.transfers)).unwrap()[..],
+        ]
+        .concat();
+        assert_eq!(
+            Mint::from_payload(&legacy),
+            Ok(Some(Mint {
+                epoch: 7,
+                transfers: mint.transfers.clone(),
+                votes: Vec::new(),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_execute() {
+        let mut genesis = Transaction::new(
+            0,
+            Address::default(),
+            blake3::hash_slice(b"alice"),
+            BigUint::from(100 as u8),
+            b"genesis",
+            vec![],
+        ); // Fund someone, so that the state isn't empty
+        genesis.genesis = true;
+
+        let state = mint_tx(0, &[("alice", 60), ("bob", 40)]).execute(Some(genesis.execute(None)));
+
+        let balance = |name: &[u8]| {
+            state
+                .data
+                .balances
+                .get(&blake3::hash_slice(name).to_str())
+                .cloned()
+                .unwrap_or_default()
+        };
+        assert_eq!(balance(b"alice"), BigUint::from(160 as u8));
+        assert_eq!(balance(b"bob"), BigUint::from(40 as u8));
+        assert_eq!(state.data.balances.get(&issuance_address().to_str()), None);
+        // Nothing is left in the issuance address
+    }
+}
@@ -0,0 +1,508 @@
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use num::bigint::BigUint; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::HashMap; // Import the hash map type
+
+use super::{
+    super::super::{common::address::Address, crypto::hash::Hash},
+    graph::Node,
+    state::BalanceProof,
+}; // Import the node, address & hash types
+
+/// The number of headers listed in a page, unless another page size is asked for.
+pub const DEFAULT_HEADER_PAGE_SIZE: usize = 100;
+
+/// The largest number of headers that can be listed in a single page.
+pub const MAX_HEADER_PAGE_SIZE: usize = 1000;
+
+/// An error encountered while following the ledger's headers, or checking a balance proof against them.
+#[derive(Debug, Fail, PartialEq)]
+pub enum LightClientError {
+    #[fail(
+        display = "the header of transaction {} cites parent {}, which hasn't been synchronized",
+        hash, parent
+    )]
+    UnknownParent {
+        hash: String,   // The hash of the transaction
+        parent: String, // The hash of the missing parent
+    },
+    #[fail(
+        display = "the header of transaction {} is at height {}, rather than {}",
+        hash, height, expected
+    )]
+    OutOfOrder {
+        hash: String,  // The hash of the transaction
+        height: u64,   // The height claimed by the header
+        expected: u64, // The height of the next header in the chain
+    },
+    #[fail(
+        display = "the header of transaction {} disagrees with the copy already synchronized",
+        hash
+    )]
+    ConflictingHeader {
+        hash: String, // The hash of the transaction
+    },
+    #[fail(display = "no header is known for transaction {}", hash)]
+    UnknownHeader {
+        hash: String, // The hash of the transaction
+    },
+    #[fail(display = "the state resulting from transaction {} isn't known", hash)]
+    MissingState {
+        hash: String, // The hash of the transaction
+    },
+    #[fail(
+        display = "not enough full nodes vouch for the state resulting from transaction {}",
+        hash
+    )]
+    UnattestedState {
+        hash: String, // The hash of the transaction
+    },
+    #[fail(display = "the state doesn't hold a balance for account {}", address)]
+    UnknownAccount {
+        address: String, // The address of the account
+    },
+    #[fail(
+        display = "the balance proof for account {} doesn't match the state resulting from transaction {}",
+        address, hash
+    )]
+    InvalidProof {
+        address: String, // The address of the account
+        hash: String,    // The hash of the transaction
+    },
+    #[fail(display = "the header could not be decoded: {}", error)]
+    Serialization {
+        error: String, // The underlying error
+    },
+}
+
+/// What a light client keeps of a single transaction in the ledger: enough to follow the DAG, and to check balances
+/// against the state that the transaction resulted in, without its payload or the state itself.
+///
+/// The balance root isn't part of the state hash that transactions cite, so a header's state commitments are only as
+/// trustworthy as the full node serving it. Light clients only check balances against the roots that enough full
+/// nodes vouch for in their head attestations.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LightHeader {
+    /// The number of transactions in the ledger up to & including this one
+    pub height: u64,
+
+    /// The hash of the transaction
+    pub hash: Hash,
+
+    /// The sender of the transaction
+    pub sender: Address,
+
+    /// The nonce spent by the transaction
+    pub nonce: u64,
+
+    /// The hashes of the transaction's parents
+    pub parents: Vec<Hash>,
+
+    /// The hash of the combined parent state cited by the transaction
+    pub parent_state_hash: Option<Hash>,
+
+    /// The transaction's timestamp
+    pub timestamp: DateTime<Utc>,
+
+    /// The hash of the state resulting from the transaction, if it has been executed
+    pub state_hash: Option<Hash>,
+
+    /// The root of the merkle tree of the balances in the state resulting from the transaction, if the state is known
+    pub balance_root: Option<Hash>,
+}
+
+/// Implement a set of light header helper methods.
+impl LightHeader {
+    /// Derives the header of the given node. The node's state, if it's loaded, is committed to by the header's state
+    /// hash & balance root.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node whose header should be derived
+    /// * `height` - The number of transactions in the ledger up to & including the node
+    pub fn from_node(node: &Node, height: u64) -> Self {
+        let data = &node.transaction.transaction_data;
+
+        Self {
+            height,
+            hash: node.hash,
+            sender: data.sender,
+            nonce: data.nonce,
+            parents: data.parents.clone(),
+            parent_state_hash: data.parent_state_hash,
+            timestamp: data.timestamp,
+            state_hash: node.state_entry.as_ref().map(|entry| entry.hash),
+            balance_root: node.state_entry.as_ref().map(|entry| entry.balance_root()),
+        }
+    }
+
+    /// Serializes the header into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap_or_default()
+    }
+
+    /// Deserializes a header from the given bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `b` - The serialized header
+    pub fn from_bytes(b: &[u8]) -> Result<Self, LightClientError> {
+        bincode::deserialize(b).map_err(|e| LightClientError::Serialization {
+            error: e.to_string(),
+        })
+    }
+
+    /// Gets a copy of the header without its state commitments, as published before the transaction was executed.
+    fn without_state(&self) -> Self {
+        Self {
+            state_hash: None,
+            balance_root: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// A balance proven against the state resulting from a transaction, along with the header of the transaction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProvenBalance {
+    /// The header of the transaction whose resulting state holds the balance
+    pub header: LightHeader,
+
+    /// The proof of the balance against the header's balance root
+    pub proof: BalanceProof,
+}
+
+/// Checks a balance proof against the balance root committed to by the given header. Results in the proven balance.
+///
+/// # Arguments
+///
+/// * `header` - The header of the transaction whose resulting state the proof should be checked against
+/// * `proof` - The proof of the balance
+pub fn verify_balance(
+    header: &LightHeader,
+    proof: &BalanceProof,
+) -> Result<BigUint, LightClientError> {
+    let root = header
+        .balance_root
+        .ok_or_else(|| LightClientError::MissingState {
+            hash: header.hash.to_str(),
+        })?;
+
+    if proof.root() != root {
+        return Err(LightClientError::InvalidProof {
+            address: proof.address.clone(),
+            hash: header.hash.to_str(),
+        });
+    }
+
+    Ok(proof.balance.clone())
+}
+
+/// The headers synchronized by a light client, in the order that they were synchronized.
+#[derive(Default)]
+pub struct HeaderChain {
+    /// Each of the synchronized headers
+    headers: Vec<LightHeader>,
+
+    /// The index of each header, by the hash of its transaction
+    routes: HashMap<Hash, usize>,
+
+    /// The balance roots that enough full nodes vouch for, by the hash of the transaction whose resulting state they
+    /// commit to
+    attested: HashMap<Hash, Hash>,
+}
+
+/// Implement a set of header chain helper methods.
+impl HeaderChain {
+    /// Adds a header to the chain. Every header but the first has to directly follow the chain's head, and cite
+    /// parents that are already in the chain. A header that's already in the chain may only be replaced with a copy
+    /// that adds the state commitments that it was published without. Headers committing to a state other than the
+    /// one that full nodes vouch for are refused. Results in whether or not the chain changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The header that should be added
+    pub fn insert(&mut self, header: LightHeader) -> Result<bool, LightClientError> {
+        if header.balance_root.is_some() && !self.agrees(&header) {
+            return Err(LightClientError::ConflictingHeader {
+                hash: header.hash.to_str(),
+            });
+        }
+
+        if let Some(index) = self.routes.get(&header.hash).copied() {
+            let known = &self.headers[index];
+
+            if *known == header || (header.state_hash.is_none() && known.without_state() == header)
+            {
+                return Ok(false);
+            }
+
+            // A header that has since been executed may fill in its state
+            if known.state_hash.is_none() && *known == header.without_state() {
+                self.headers[index] = header;
+
+                return Ok(true);
+            }
+
+            return Err(LightClientError::ConflictingHeader {
+                hash: header.hash.to_str(),
+            });
+        }
+
+        if let Some(head) = self.head() {
+            if let Some(parent) = header
+                .parents
+                .iter()
+                .find(|parent| !self.routes.contains_key(parent))
+            {
+                return Err(LightClientError::UnknownParent {
+                    hash: header.hash.to_str(),
+                    parent: parent.to_str(),
+                });
+            }
+
+            if header.height != head.height + 1 {
+                return Err(LightClientError::OutOfOrder {
+                    hash: header.hash.to_str(),
+                    height: header.height,
+                    expected: head.height + 1,
+                });
+            }
+        }
+
+        self.routes.insert(header.hash, self.headers.len());
+        self.headers.push(header);
+
+        Ok(true)
+    }
+
+    /// Gets the header of the transaction with the given hash, if it has been synchronized.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction
+    pub fn get(&self, hash: &Hash) -> Option<&LightHeader> {
+        self.routes.get(hash).map(|index| &self.headers[*index])
+    }
+
+    /// Gets the last header to be synchronized.
+    pub fn head(&self) -> Option<&LightHeader> {
+        self.headers.last()
+    }
+
+    /// Gets the last synchronized header whose state is known, and vouched for by enough full nodes.
+    pub fn executed_head(&self) -> Option<&LightHeader> {
+        self.headers.iter().rev().find(|header| {
+            header.balance_root.is_some() && self.attested.contains_key(&header.hash)
+        })
+    }
+
+    /// Notes the balance root that enough full nodes vouch for as that of the state resulting from the transaction
+    /// with the given hash, so that balances can be checked against it. A synchronized header committing to another
+    /// state loses its state commitments, so that an honest copy can fill them back in.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction
+    /// * `balance_root` - The root of the merkle tree of the balances in the state resulting from the transaction
+    pub fn attest(&mut self, hash: Hash, balance_root: Hash) {
+        self.attested.insert(hash, balance_root);
+
+        if let Some(index) = self.routes.get(&hash).copied() {
+            if !self.agrees(&self.headers[index]) {
+                self.headers[index] = self.headers[index].without_state();
+            }
+        }
+    }
+
+    /// Checks whether or not the given header's balance root agrees with the one vouched for by full nodes, if any
+    /// has been.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The header
+    fn agrees(&self, header: &LightHeader) -> bool {
+        self.attested
+            .get(&header.hash)
+            .map_or(true, |root| header.balance_root == Some(*root))
+    }
+
+    /// Gets the number of synchronized headers.
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Checks whether or not no headers have been synchronized.
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    /// Gets a page of the synchronized headers, in the order that they were synchronized.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The number of headers that should be skipped
+    /// * `limit` - The largest number of headers that should be listed (between 1 and MAX_HEADER_PAGE_SIZE)
+    pub fn page(&self, offset: usize, limit: Option<usize>) -> Vec<LightHeader> {
+        self.headers
+            .iter()
+            .skip(offset)
+            .take(clamp_page_size(limit))
+            .cloned()
+            .collect()
+    }
+
+    /// Checks a balance proven by a full node against the balance root that enough full nodes vouch for, for a header
+    /// in the chain, so that a full node can't vouch for a header (or a state) of its own making. Results in the proven
+    /// balance.
+    ///
+    /// # Arguments
+    ///
+    /// * `proven` - The balance, and the header that it was proven against
+    pub fn verify(&self, proven: &ProvenBalance) -> Result<BigUint, LightClientError> {
+        let header =
+            self.get(&proven.header.hash)
+                .ok_or_else(|| LightClientError::UnknownHeader {
+                    hash: proven.header.hash.to_str(),
+                })?;
+
+        let root =
+            self.attested
+                .get(&header.hash)
+                .ok_or_else(|| LightClientError::UnattestedState {
+                    hash: header.hash.to_str(),
+                })?;
+
+        if header.balance_root.map_or(false, |known| known != *root)
+            || proven.header.balance_root != Some(*root)
+        {
+            return Err(LightClientError::ConflictingHeader {
+                hash: header.hash.to_str(),
+            });
+        }
+
+        verify_balance(
+            &LightHeader {
+                balance_root: Some(*root),
+                ..header.clone()
+            },
+            &proven.proof,
+        )
+    }
+}
+
+/// Clamps the number of headers asked for in a page to the number that may be listed at once.
+///
+/// # Arguments
+///
+/// * `limit` - The number of headers asked for, if any
+pub fn clamp_page_size(limit: Option<usize>) -> usize {
+    limit
+        .unwrap_or(DEFAULT_HEADER_PAGE_SIZE)
+        .min(MAX_HEADER_PAGE_SIZE)
+        .max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::crypto::blake3; // Import the blake3 hashing module
+    use super::super::{state::Entry, transaction::Transaction}; // Import the state & transaction types
+    use super::*; // Import names from our parent module
+
+    use num::FromPrimitive; // Let the bigint library implement from_i64
+
+    /// Makes a node spending the given nonce on top of the given parents, resulting in the given state.
+    fn node(nonce: u64, parents: Vec<Hash>, state_entry: Option<Entry>) -> Node {
+        let sender = blake3::hash_slice(b"sender");
+        let tx = Transaction::new(
+            nonce,
+            sender,
+            sender,
+            BigUint::from_i64(0).unwrap(),
+            b"test transaction payload",
+            parents,
+        );
+
+        Node::new(tx, state_entry)
+    }
+
+    #[test]
+    fn test_header_chain() {
+        let mut balances = HashMap::new();
+        balances.insert("alice".to_owned(), BigUint::from_i64(10).unwrap());
+        balances.insert("bob".to_owned(), BigUint::from_i64(20).unwrap());
+        let state = Entry::new(HashMap::new(), balances);
+
+        let root = node(0, vec![Hash::default()], Some(state.clone()));
+        let child = node(1, vec![root.hash], Some(state.clone()));
+        let orphan = node(2, vec![blake3::hash_slice(b"unknown")], None);
+
+        // Headers are only followed once their parents have been
+        let mut chain = HeaderChain::default();
+        assert_eq!(chain.insert(LightHeader::from_node(&root, 1)), Ok(true));
+        assert!(chain.insert(LightHeader::from_node(&orphan, 2)).is_err());
+        assert!(chain.insert(LightHeader::from_node(&child, 3)).is_err());
+
+        // A header published before its transaction was executed picks up its state later, but can't be rewritten
+        let executed = LightHeader::from_node(&child, 2);
+        assert_eq!(chain.insert(executed.without_state()), Ok(true));
+        assert_eq!(chain.executed_head().map(|header| header.height), Some(1));
+        assert_eq!(chain.insert(executed.clone()), Ok(true));
+        assert_eq!(chain.insert(executed.without_state()), Ok(false));
+        let mut rewritten = executed.clone();
+        rewritten.balance_root = Some(Hash::default());
+        assert!(chain.insert(rewritten.clone()).is_err());
+        assert_eq!(chain.page(1, None), vec![executed.clone()]);
+
+        // Balances are only checked against the roots that full nodes vouch for, not whichever one a header carries
+        let proven = ProvenBalance {
+            header: executed.clone(),
+            proof: state.prove_balance("bob").unwrap(),
+        };
+        assert_eq!(
+            chain.verify(&proven),
+            Err(LightClientError::UnattestedState {
+                hash: executed.hash.to_str()
+            })
+        );
+        assert_eq!(chain.executed_head(), None);
+        chain.attest(executed.hash, state.balance_root());
+        assert_eq!(chain.executed_head(), Some(&executed));
+        assert_eq!(chain.verify(&proven), Ok(BigUint::from_i64(20).unwrap()));
+
+        let mut forged = proven.clone();
+        forged.proof.balance = BigUint::from_i64(2000).unwrap();
+        assert!(chain.verify(&forged).is_err());
+        assert!(chain
+            .verify(&ProvenBalance {
+                header: rewritten,
+                ..proven
+            })
+            .is_err());
+
+        // A header served with a state of some full node's own making is stripped of it once the real one is vouched
+        // for, and only the real one can take its place
+        let mut honest = HashMap::new();
+        honest.insert("alice".to_owned(), BigUint::from_i64(30).unwrap());
+        let honest = Entry::new(HashMap::new(), honest);
+        chain.attest(root.hash, honest.balance_root());
+        assert_eq!(chain.get(&root.hash).unwrap().balance_root, None);
+        assert!(chain.insert(LightHeader::from_node(&root, 1)).is_err());
+        let restored = LightHeader {
+            state_hash: Some(honest.hash),
+            balance_root: Some(honest.balance_root()),
+            ..LightHeader::from_node(&root, 1)
+        };
+        assert_eq!(chain.insert(restored), Ok(true));
+        assert_eq!(
+            chain.verify(&ProvenBalance {
+                header: chain.get(&root.hash).unwrap().clone(),
+                proof: honest.prove_balance("alice").unwrap(),
+            }),
+            Ok(BigUint::from_i64(30).unwrap())
+        );
+    }
+}
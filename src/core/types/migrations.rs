@@ -0,0 +1,717 @@
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+    error::Error,
+}; // Import collections
+
+use num::bigint::BigUint; // Add support for large unsigned integers
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize}; // Import serde serialization
+
+use super::{
+    graph::{self, Node, META_TREE, STATES_TREE, STATE_ORIGINS_TREE},
+    snapshot::{LATEST_SNAPSHOT_META, SNAPSHOTS_TREE},
+    state::{self, Entry},
+    transaction::{LegacyTransaction, Transaction},
+}; // Import the graph, snapshot, state & transaction types
+
+use super::super::super::crypto::hash::Hash; // Import the hash type
+
+/// The version of the layout of the ledger's database that this node reads and writes.
+pub const SCHEMA_VERSION: u32 = 6;
+
+/// The schema version assumed for databases written before versions were recorded.
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+/// The metadata key under which the ledger's schema version is stored.
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// A single step in the evolution of the ledger's database layout.
+pub struct Migration {
+    /// The schema version that the database is at once the migration has been applied
+    pub version: u32,
+
+    /// A human-readable description of the migration
+    pub description: &'static str,
+
+    /// Applies the migration to the given database
+    pub apply: fn(&sled::Db) -> Result<(), sled::Error>,
+}
+
+/// The name of the tree in which the guardians registered by each account were kept before schema version 6, when
+/// they moved into the state.
+const LEGACY_GUARDIANS_TREE: &str = "guardians";
+
+/// The name of the tree in which the funds locked in each account were kept before schema version 6, when they moved
+/// into the state.
+const LEGACY_VESTING_TREE: &str = "vesting";
+
+/// A state entry, as laid out before schema version 6, when states only carried balances & nonces.
+#[derive(Serialize, Deserialize, Clone)]
+struct LegacyEntry {
+    /// Body of the state entry
+    data: LegacyEntryData,
+    /// Hash of the state entry
+    hash: Hash,
+}
+
+/// The body of a state entry, as laid out before schema version 6.
+#[derive(Serialize, Deserialize, Clone)]
+struct LegacyEntryData {
+    /// Balances of every account at a certain point in time
+    balances: HashMap<String, BigUint>,
+    /// The last recorded index of each account
+    nonces: HashMap<String, u64>,
+}
+
+impl From<LegacyEntry> for Entry {
+    /// Upgrades the given state entry to the current layout. States in the old layout don't carry any guardians or
+    /// locks, so they keep their hashes.
+    fn from(entry: LegacyEntry) -> Self {
+        Self::new(entry.data.nonces, entry.data.balances)
+    }
+}
+
+/// A graph node, as laid out before schema version 5, when its transaction was laid out by version 1 of the
+/// transaction format.
+#[derive(Serialize, Deserialize, Clone)]
+struct LegacyNode {
+    /// The transaction associated with the node
+    transaction: LegacyTransaction,
+    /// The state entry associated with the node
+    state_entry: Option<LegacyEntry>,
+    /// The hash of the transaction associated with the node
+    hash: Hash,
+}
+
+/// A graph node, as laid out by schema version 5, before its state entry carried the guardians & locks of each
+/// account.
+#[derive(Serialize, Deserialize, Clone)]
+struct UnextendedNode {
+    /// The transaction associated with the node
+    transaction: Transaction,
+    /// The state entry associated with the node
+    state_entry: Option<LegacyEntry>,
+    /// The hash of the transaction associated with the node
+    hash: Hash,
+}
+
+impl From<LegacyNode> for UnextendedNode {
+    /// Upgrades the given node's transaction to the current layout.
+    fn from(node: LegacyNode) -> Self {
+        Self {
+            transaction: Transaction::from(node.transaction),
+            state_entry: node.state_entry,
+            hash: node.hash,
+        }
+    }
+}
+
+/// Decodes a node laid out before schema version 5.
+///
+/// # Arguments
+///
+/// * `encoded` - The encoded node
+fn decode_legacy_node(encoded: &[u8]) -> Result<LegacyNode, sled::Error> {
+    bincode::deserialize(encoded).map_err(|e| sled::Error::Unsupported(e.to_string()))
+}
+
+/// Encodes a node in the layout used before schema version 5.
+///
+/// # Arguments
+///
+/// * `node` - The node that should be encoded
+fn encode_legacy_node(node: &LegacyNode) -> Result<Vec<u8>, sled::Error> {
+    bincode::serialize(node).map_err(|e| sled::Error::Unsupported(e.to_string()))
+}
+
+/// Decodes a value persisted in the given layout.
+///
+/// # Arguments
+///
+/// * `encoded` - The encoded value
+fn decode<T: DeserializeOwned>(encoded: &[u8]) -> Result<T, sled::Error> {
+    bincode::deserialize(encoded).map_err(|e| sled::Error::Unsupported(e.to_string()))
+}
+
+/// Each of the migrations, in the order in which they must be applied.
+pub const MIGRATIONS: [Migration; 5] = [
+    Migration {
+        version: 2,
+        description: "re-key nodes by their big-endian position in the graph",
+        apply: rekey_nodes,
+    },
+    Migration {
+        version: 3,
+        description: "rehash persisted state entries",
+        apply: rehash_state_entries,
+    },
+    Migration {
+        version: 4,
+        description: "index the origins of persisted state entries",
+        apply: index_state_origins,
+    },
+    Migration {
+        version: 5,
+        description: "re-encode persisted nodes in version 2 of the transaction format",
+        apply: reencode_nodes,
+    },
+    Migration {
+        version: 6,
+        description: "rebuild persisted states to carry the guardians & locks of each account",
+        apply: rebuild_states,
+    },
+];
+
+/// An error encountered while migrating a database.
+#[derive(Debug, Fail)]
+pub enum MigrationError {
+    #[fail(display = "failed to read the database's schema version: {}", error)]
+    UnreadableVersion {
+        error: String, // The underlying error
+    },
+    #[fail(
+        display = "migration to schema version {} ({}) failed: {}",
+        version, description, error
+    )]
+    MigrationFailed {
+        version: u32,              // The version being migrated to
+        description: &'static str, // What the migration does
+        error: String,             // The underlying error
+    },
+}
+
+impl From<sled::Error> for MigrationError {
+    /// Converts the given sled error into a MigrationError.
+    fn from(e: sled::Error) -> Self {
+        Self::UnreadableVersion {
+            error: e.description().to_owned(),
+        }
+    }
+}
+
+/// Gets the schema version recorded in the given database, if one has been recorded.
+///
+/// # Arguments
+///
+/// * `db` - The database whose schema version should be read
+pub fn schema_version(db: &sled::Db) -> Result<Option<u32>, MigrationError> {
+    match db
+        .open_tree(META_TREE)?
+        .get(SCHEMA_VERSION_KEY.as_bytes())?
+    {
+        Some(encoded) => {
+            // Decode the big-endian version number
+            let bytes: [u8; 4] =
+                encoded
+                    .as_ref()
+                    .try_into()
+                    .map_err(|_| MigrationError::UnreadableVersion {
+                        error: "malformed schema version".to_owned(),
+                    })?;
+
+            Ok(Some(u32::from_be_bytes(bytes)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Records the given schema version in the given database.
+///
+/// # Arguments
+///
+/// * `db` - The database whose schema version should be recorded
+/// * `version` - The schema version of the database
+pub fn stamp_schema_version(db: &sled::Db, version: u32) -> Result<(), sled::Error> {
+    db.open_tree(META_TREE)?
+        .insert(SCHEMA_VERSION_KEY.as_bytes(), &version.to_be_bytes()[..])?;
+
+    Ok(()) // Done!
+}
+
+/// Applies each of the migrations that the given database hasn't had applied to it yet, in order, and returns the
+/// resulting schema version. A database without a recorded version is considered brand new if it is empty, and
+/// assumed to use the legacy layout otherwise. Databases written by a newer node are left untouched.
+///
+/// # Arguments
+///
+/// * `db` - The database that should be migrated
+pub fn migrate(db: &sled::Db) -> Result<u32, MigrationError> {
+    let mut version = match schema_version(db)? {
+        Some(version) => version,
+        None if db.is_empty() => {
+            // Nothing to migrate; just mark the database as up to date
+            stamp_schema_version(db, SCHEMA_VERSION)?;
+
+            return Ok(SCHEMA_VERSION);
+        }
+        None => LEGACY_SCHEMA_VERSION,
+    };
+
+    // Apply each of the outstanding migrations, recording our progress after each one so that an interrupted
+    // migration picks up where it left off
+    for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+        info!(
+            "Migrating the database to schema version {}: {}",
+            migration.version, migration.description
+        );
+
+        (migration.apply)(db)
+            .and_then(|_| stamp_schema_version(db, migration.version))
+            .and_then(|_| db.flush().map(|_| ()))
+            .map_err(|e| MigrationError::MigrationFailed {
+                version: migration.version,
+                description: migration.description,
+                error: e.description().to_owned(),
+            })?;
+
+        version = migration.version;
+    }
+
+    Ok(version)
+}
+
+/// Moves each of the nodes stored under a decimal index string to a big-endian key. Since the legacy layout didn't
+/// preserve the order of the nodes, they are re-ordered such that each node comes after its parents.
+///
+/// # Arguments
+///
+/// * `db` - The database that should be migrated
+fn rekey_nodes(db: &sled::Db) -> Result<(), sled::Error> {
+    let mut legacy: Vec<(usize, sled::IVec, LegacyNode)> = Vec::new(); // Each of the nodes stored under a legacy key
+
+    // Collect each of the nodes stored under a decimal key
+    for pair in db.iter() {
+        let (key, value) = pair?;
+
+        if let Some(index) = std::str::from_utf8(&key)
+            .ok()
+            .and_then(|key| key.parse::<usize>().ok())
+        {
+            legacy.push((index, key, decode_legacy_node(&value)?));
+        }
+    }
+    legacy.sort_by_key(|(index, _, _)| *index);
+
+    // Parents that aren't in the graph at all can't hold up their children
+    let known: HashSet<_> = legacy.iter().map(|(_, _, node)| node.hash).collect();
+
+    let mut placed = HashSet::new(); // The hashes of the nodes that have been given a position
+    let mut ordered: Vec<&LegacyNode> = Vec::new(); // The nodes, in their new order
+    let mut remaining: Vec<&LegacyNode> = legacy.iter().map(|(_, _, node)| node).collect();
+
+    // Keep placing each node whose parents have all been placed
+    while !remaining.is_empty() {
+        let before = remaining.len();
+
+        remaining.retain(|node| {
+            let ready = node
+                .transaction
+                .transaction_data
+                .parents
+                .iter()
+                .all(|parent| placed.contains(parent) || !known.contains(parent));
+
+            if ready {
+                placed.insert(node.hash);
+                ordered.push(*node);
+            }
+
+            !ready
+        });
+
+        // The remaining nodes refer to each other in a cycle; keep them in their original order
+        if remaining.len() == before {
+            ordered.append(&mut remaining);
+        }
+    }
+
+    // Swap the old keys for the new ones all at once
+    let mut batch = sled::Batch::default();
+    for (_, key, _) in &legacy {
+        batch.remove(key.clone());
+    }
+    for (i, node) in ordered.iter().enumerate() {
+        batch.insert(&graph::node_key(i)[..], encode_legacy_node(node)?);
+    }
+
+    db.apply_batch(batch)
+}
+
+/// Recomputes the hash of each persisted state entry, since older nodes hashed every state to the same value.
+///
+/// # Arguments
+///
+/// * `db` - The database that should be migrated
+fn rehash_state_entries(db: &sled::Db) -> Result<(), sled::Error> {
+    for pair in db.iter() {
+        let (key, value) = pair?;
+
+        let mut node = decode_legacy_node(&value)?;
+
+        // Rebuild the entry, which recalculates its hash
+        if let Some(mut entry) = node.state_entry.take() {
+            entry.hash = Entry::from(entry.clone()).hash;
+            node.state_entry = Some(entry);
+
+            db.insert(key, encode_legacy_node(&node)?)?;
+        }
+    }
+
+    Ok(()) // Done!
+}
+
+/// Indexes each of the persisted state entries by the transaction that produced it, and each of the cited parent
+/// states by the transaction citing it.
+///
+/// # Arguments
+///
+/// * `db` - The database that should be migrated
+fn index_state_origins(db: &sled::Db) -> Result<(), sled::Error> {
+    let tree = db.open_tree(STATE_ORIGINS_TREE)?; // Open the index
+
+    for pair in db.iter() {
+        let (_, value) = pair?;
+
+        let node = decode_legacy_node(&value)?;
+
+        // Index the state that the node produced
+        if let Some(entry) = &node.state_entry {
+            graph::update_state_origin(&tree, entry.hash, |origin| {
+                if !origin.produced_by.contains(&node.hash) {
+                    origin.produced_by.push(node.hash);
+                }
+            })?;
+        }
+
+        // Index the state that the node cites
+        if let Some(parent_state_hash) = node.transaction.transaction_data.parent_state_hash {
+            graph::update_state_origin(&tree, parent_state_hash, |origin| {
+                if !origin.cited_by.contains(&node.hash) {
+                    origin.cited_by.push(node.hash);
+                }
+            })?;
+        }
+    }
+
+    Ok(()) // Done!
+}
+
+/// Re-encodes each persisted node in version 2 of the transaction format. Transactions laid out by version 1 of the
+/// transaction format don't use any of the fields added since, so they keep their hashes, and their children keep
+/// referring to them.
+///
+/// # Arguments
+///
+/// * `db` - The database that should be migrated
+fn reencode_nodes(db: &sled::Db) -> Result<(), sled::Error> {
+    let mut batch = sled::Batch::default();
+
+    for pair in db.iter() {
+        let (key, value) = pair?;
+
+        let node = UnextendedNode::from(decode_legacy_node(&value)?);
+        batch.insert(
+            key,
+            bincode::serialize(&node).map_err(|e| sled::Error::Unsupported(e.to_string()))?,
+        );
+    }
+
+    db.apply_batch(batch)
+}
+
+/// Rebuilds each persisted state by executing the ledger over again, so that the guardians registered by each account
+/// and the funds locked in it are carried in the states that they were registered or locked in, rather than in trees
+/// of their own. States in which nobody has registered any guardians or locked any funds keep their hashes;
+/// transactions already citing the old hash of a state that changed can't be updated to follow it. A node whose
+/// parents aren't in the database (e.g. the anchor of a snapshot) keeps the balances & nonces that it was stored
+/// with. The latest snapshot carries a state in the old layout, so it's dropped; a new one is taken in due course.
+///
+/// # Arguments
+///
+/// * `db` - The database that should be migrated
+fn rebuild_states(db: &sled::Db) -> Result<(), sled::Error> {
+    let archive = db.open_tree(STATES_TREE)?;
+    let origins = db.open_tree(STATE_ORIGINS_TREE)?;
+
+    let mut states: HashMap<Hash, Entry> = HashMap::new(); // The rebuilt state left behind by each executed node
+    let mut batch = sled::Batch::default();
+
+    // Nodes are stored after their parents, so each node's parents have been rebuilt by the time it's reached
+    for pair in db.iter() {
+        let (key, value) = pair?;
+        let node: UnextendedNode = decode(&value)?;
+
+        // Nodes that were executed may have had their states paged out
+        let archived = archive.get(node.hash)?;
+        let stored = match (&node.state_entry, &archived) {
+            (Some(entry), _) => Some(entry.clone()),
+            (None, Some(encoded)) => Some(decode::<LegacyEntry>(encoded)?),
+            (None, None) => None,
+        };
+
+        let rebuilt = stored.map(|stored| {
+            let parents = &node.transaction.transaction_data.parents;
+
+            if parents.is_empty() {
+                node.transaction.execute(None)
+            } else if parents.iter().all(|parent| states.contains_key(parent)) {
+                let merged = parents.iter().fold(Entry::default(), |merged, parent| {
+                    state::merge_entries(vec![merged, states[parent].clone()])
+                });
+
+                node.transaction.execute(Some(merged))
+            } else {
+                Entry::from(stored)
+            }
+        });
+
+        if let Some(state) = &rebuilt {
+            if archived.is_some() {
+                archive.insert(
+                    node.hash,
+                    bincode::serialize(state)
+                        .map_err(|e| sled::Error::Unsupported(e.to_string()))?,
+                )?;
+            }
+
+            graph::update_state_origin(&origins, state.hash, |origin| {
+                if !origin.produced_by.contains(&node.hash) {
+                    origin.produced_by.push(node.hash);
+                }
+            })?;
+
+            states.insert(node.hash, state.clone());
+        }
+
+        batch.insert(
+            key,
+            Node {
+                state_entry: rebuilt.filter(|_| node.state_entry.is_some()),
+                transaction: node.transaction,
+                hash: node.hash,
+            }
+            .to_bytes(),
+        );
+    }
+
+    db.apply_batch(batch)?;
+
+    // The guardians & locks now live in the states, and the snapshot would have to be taken again to carry them
+    db.drop_tree(LEGACY_GUARDIANS_TREE)?;
+    db.drop_tree(LEGACY_VESTING_TREE)?;
+    db.drop_tree(SNAPSHOTS_TREE)?;
+    db.open_tree(META_TREE)?
+        .remove(LATEST_SNAPSHOT_META.as_bytes())?;
+
+    Ok(()) // Done!
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::{common::address::Address, common::io};
+    use super::super::{
+        guardians::{self, GuardianInstruction, MIN_TIMELOCK_SECS},
+        receipt::{Receipt, ReceiptMap},
+        transaction::LegacyTransactionData,
+    };
+    use super::*; // Import names from our parent module
+
+    /// Lays out the given state entry the way that state entries were laid out before schema version 6.
+    fn legacy_entry(entry: Entry) -> LegacyEntry {
+        LegacyEntry {
+            data: LegacyEntryData {
+                balances: entry.data.balances,
+                nonces: entry.data.nonces,
+            },
+            hash: entry.hash,
+        }
+    }
+
+    /// Encodes a node holding the given transaction the way that nodes were encoded before schema version 5.
+    fn legacy_node(tx: Transaction, state_entry: Option<Entry>) -> Vec<u8> {
+        encode_legacy_node(&LegacyNode {
+            hash: tx.hash,
+            transaction: LegacyTransaction {
+                transaction_data: LegacyTransactionData::from(&tx.transaction_data),
+                hash: tx.hash,
+                signature: tx.signature,
+                deployed_contract_address: tx.deployed_contract_address,
+                contract_creation: tx.contract_creation,
+                genesis: tx.genesis,
+            },
+            state_entry: state_entry.map(legacy_entry),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_migrate_legacy() {
+        let db = sled::open(format!(
+            "{}/.tests/migrations/{}",
+            io::db_dir(),
+            rand::random::<u32>()
+        ))
+        .unwrap(); // Open a test database
+
+        let root = Transaction::new(
+            0,
+            Address::default(),
+            Address::default(),
+            BigUint::from(1 as u8),
+            b"root",
+            vec![],
+        ); // Make a root transaction
+        let mut child = Transaction::new(
+            1,
+            Address::default(),
+            Address::default(),
+            BigUint::from(0 as u8),
+            b"child",
+            vec![root.hash],
+        ); // Make a child of the root
+
+        let root_state = root.execute(None); // Execute the root
+
+        // Have the child cite the root's receipt
+        child.transaction_data.parent_receipts = Some(ReceiptMap {
+            associated_transactions: vec![root.hash],
+            receipts: vec![Receipt {
+                state_hash: root_state.hash,
+                ..Default::default()
+            }],
+        });
+        child.hash = child.transaction_data.hash();
+
+        let (root_hash, child_hash) = (root.hash, child.hash);
+
+        // Store the nodes the way that older nodes did: in reverse, under decimal keys
+        db.insert("0", legacy_node(child, None)).unwrap();
+        db.insert("1", legacy_node(root, Some(root_state.clone())))
+            .unwrap();
+
+        assert_eq!(migrate(&db).unwrap(), SCHEMA_VERSION); // Migrate the database
+        assert_eq!(schema_version(&db).unwrap(), Some(SCHEMA_VERSION)); // Ensure the version was recorded
+
+        // The root should now come first, followed by its child
+        let first: Node =
+            bincode::deserialize(&db.get(graph::node_key(0)).unwrap().unwrap()).unwrap();
+        let second: Node =
+            bincode::deserialize(&db.get(graph::node_key(1)).unwrap().unwrap()).unwrap();
+        assert_eq!(first.hash, root_hash);
+        assert_eq!(second.hash, child_hash);
+        assert!(db.get("0").unwrap().is_none()); // Ensure the legacy keys are gone
+
+        // Upgrading the layout of the transactions mustn't change their hashes
+        assert_eq!(first.transaction.transaction_data.hash(), root_hash);
+        assert_eq!(second.transaction.transaction_data.hash(), child_hash);
+
+        // The root's state should have been indexed
+        let origin: graph::StateOrigin = bincode::deserialize(
+            &db.open_tree(STATE_ORIGINS_TREE)
+                .unwrap()
+                .get(first.state_entry.unwrap().hash)
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(origin.produced_by, vec![root_hash]);
+
+        assert_eq!(migrate(&db).unwrap(), SCHEMA_VERSION); // Migrating again should be a no-op
+    }
+
+    #[test]
+    fn test_migrate_empty() {
+        let db = sled::open(format!(
+            "{}/.tests/migrations/{}",
+            io::db_dir(),
+            rand::random::<u32>()
+        ))
+        .unwrap(); // Open a test database
+
+        assert_eq!(migrate(&db).unwrap(), SCHEMA_VERSION); // A new database needs no migrations
+        assert_eq!(schema_version(&db).unwrap(), Some(SCHEMA_VERSION)); // Ensure the version was recorded
+    }
+
+    #[test]
+    fn test_rebuild_states() {
+        let db = sled::open(format!(
+            "{}/.tests/migrations/{}",
+            io::db_dir(),
+            rand::random::<u32>()
+        ))
+        .unwrap(); // Open a test database
+
+        let (alice, guardian) = (Address::new(vec![1; 32]), Address::new(vec![2; 32]));
+
+        let root = Transaction::new(
+            0,
+            Address::default(),
+            alice,
+            BigUint::from(1 as u8),
+            b"root",
+            vec![],
+        ); // Make a root transaction
+        let register = Transaction::new(
+            0,
+            alice,
+            guardians::registry_address(),
+            BigUint::from(0 as u8),
+            &GuardianInstruction::Register {
+                guardians: vec![guardian],
+                threshold: 1,
+                timelock_secs: MIN_TIMELOCK_SECS,
+            }
+            .to_payload(),
+            vec![root.hash],
+        ); // Have alice register a guardian
+
+        // Store the nodes the way that nodes were stored by schema version 5, with the guardians & locks kept on the
+        // side
+        let root_state = root.execute(None);
+        let stale_state = Entry::new(HashMap::new(), root_state.data.balances.clone());
+        for (i, (tx, state)) in vec![(root, root_state.clone()), (register, stale_state)]
+            .into_iter()
+            .enumerate()
+        {
+            let node = UnextendedNode {
+                hash: tx.hash,
+                transaction: tx,
+                state_entry: Some(legacy_entry(state)),
+            };
+            db.insert(graph::node_key(i), bincode::serialize(&node).unwrap())
+                .unwrap();
+        }
+        db.open_tree(LEGACY_GUARDIANS_TREE)
+            .unwrap()
+            .insert(alice, vec![0])
+            .unwrap();
+        db.open_tree(LEGACY_VESTING_TREE)
+            .unwrap()
+            .insert(alice, vec![0])
+            .unwrap();
+        stamp_schema_version(&db, 5).unwrap();
+
+        assert_eq!(migrate(&db).unwrap(), SCHEMA_VERSION); // Migrate the database
+
+        let first: Node =
+            bincode::deserialize(&db.get(graph::node_key(0)).unwrap().unwrap()).unwrap();
+        let second: Node =
+            bincode::deserialize(&db.get(graph::node_key(1)).unwrap().unwrap()).unwrap();
+
+        // The root's state has no guardians in it, so it should keep its hash
+        assert_eq!(first.state_entry.unwrap().hash, root_state.hash);
+
+        // Alice's guardian should now be carried in the state left behind by her registration
+        let state = second.state_entry.unwrap();
+        assert_eq!(
+            state.data.guardians[&alice.to_str()].set.guardians,
+            vec![guardian]
+        );
+        assert_eq!(state.hash, state.data.hash());
+        assert_eq!(state.data.height, 2); // The registration sits on top of the root
+        for tree in &[LEGACY_GUARDIANS_TREE, LEGACY_VESTING_TREE] {
+            assert!(!db.tree_names().contains(&sled::IVec::from(*tree)));
+        }
+    }
+}
@@ -1,7 +1,23 @@
+pub mod activity; // Export the account activity index
+pub mod batch; // Export the multi-recipient batch transfer types
+pub mod burn; // Export the burn address
+pub mod chainfile; // Export the chain interchange format
+pub mod gas; // Export the gas & fee accounting
 pub mod genesis;
 pub mod graph; // Export the graph module
+pub mod guardians; // Export the account recovery guardians
+pub mod issuance; // Export the issuance schedule & mints
+pub mod light; // Export the light client headers & balance proofs
 pub mod logs; // Export the log types
+pub mod migrations; // Export the database migrations
+pub mod payloads; // Export the deduplicated payload store
+pub mod query; // Export the transaction query API
 pub mod receipt; // Export the receipt types
+pub mod replay; // Export the ledger replay API
+pub mod rewards; // Export the validator rewards accounting
 pub mod signature; // Export the signature type
+pub mod snapshot; // Export the ledger snapshots
 pub mod state; // Export the state module
+pub mod trace; // Export the execution tracer
 pub mod transaction; // Export the transaction type
+pub mod vesting; // Export the time-locked balance types
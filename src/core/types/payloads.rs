@@ -0,0 +1,243 @@
+use super::transaction::Transaction; // Import the transaction type
+
+use super::super::super::crypto::{
+    blake3,
+    hash::{Hash, HASH_SIZE},
+}; // Import the hashing modules
+
+/// The bytes that a deflated payload begins with, followed by the hash of the payload that it stands in for. The
+/// leading NUL keeps ordinary text memos from ever being mistaken for a reference.
+pub const REFERENCE_PREFIX: &[u8] = b"\0payload:blake3:";
+
+/// The number of bytes in a deflated payload.
+pub const REFERENCE_SIZE: usize = REFERENCE_PREFIX.len() + HASH_SIZE;
+
+/// An error encountered while storing, or looking up, a transaction's payload.
+#[derive(Debug, Fail, PartialEq)]
+pub enum PayloadError {
+    #[fail(display = "no payload with hash {} has been stored", hash)]
+    Missing {
+        hash: String, // The hash of the missing payload
+    },
+    #[fail(display = "failed to access the payload store: {}", error)]
+    StorageFailure {
+        error: String, // The underlying error
+    },
+}
+
+impl From<sled::Error> for PayloadError {
+    /// Converts the given sled error into a PayloadError.
+    fn from(e: sled::Error) -> Self {
+        Self::StorageFailure {
+            error: e.to_string(),
+        }
+    }
+}
+
+/// Builds the reference that stands in for the payload with the given hash.
+///
+/// # Arguments
+///
+/// * `hash` - The hash of the payload
+pub fn reference(hash: &Hash) -> Vec<u8> {
+    let mut reference = REFERENCE_PREFIX.to_vec();
+    reference.extend_from_slice(&**hash);
+
+    reference
+}
+
+/// Gets the hash of the payload that the given payload stands in for, if it is a reference to one.
+///
+/// # Arguments
+///
+/// * `payload` - The payload of a transaction
+pub fn referenced_hash(payload: &[u8]) -> Option<Hash> {
+    if payload.len() != REFERENCE_SIZE || !payload.starts_with(REFERENCE_PREFIX) {
+        return None;
+    }
+
+    Some(Hash::new(payload[REFERENCE_PREFIX.len()..].to_vec()))
+}
+
+/// Checks whether or not the given payload should be stored on its own. Payloads that are no longer than a reference
+/// aren't worth it, unless they look like a reference themselves, in which case they must be stored so that they
+/// aren't inflated into something else later on.
+///
+/// # Arguments
+///
+/// * `payload` - The payload of a transaction
+pub fn worth_deflating(payload: &[u8]) -> bool {
+    payload.len() > REFERENCE_SIZE || referenced_hash(payload).is_some()
+}
+
+/// A content-addressed store of transaction payloads, so that each distinct payload is only ever kept once, however
+/// many transactions carry it. Transactions keep a short reference to their payload in its place.
+#[derive(Clone)]
+pub struct PayloadStore {
+    /// The tree in which each payload is kept, by its hash
+    tree: sled::Tree,
+}
+
+impl From<sled::Tree> for PayloadStore {
+    /// Keeps payloads in the given tree.
+    fn from(tree: sled::Tree) -> Self {
+        Self { tree }
+    }
+}
+
+/// Implement a set of payload store helper methods.
+impl PayloadStore {
+    /// Stores the given payload, if it isn't already, and gets its hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload that should be stored
+    pub fn put(&self, payload: &[u8]) -> Result<Hash, PayloadError> {
+        let hash = blake3::hash_slice(payload);
+
+        if !self.tree.contains_key(hash)? {
+            self.tree.insert(hash, payload)?;
+        }
+
+        Ok(hash)
+    }
+
+    /// Gets the payload with the given hash, if it has been stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the payload
+    pub fn get(&self, hash: &Hash) -> Result<Option<Vec<u8>>, PayloadError> {
+        Ok(self.tree.get(hash)?.map(|payload| payload.to_vec()))
+    }
+
+    /// Checks whether or not the payload with the given hash has been stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the payload
+    pub fn contains(&self, hash: &Hash) -> Result<bool, PayloadError> {
+        Ok(self.tree.contains_key(hash)?)
+    }
+
+    /// Moves the payload of the given transaction into the store, leaving a reference in its place. The transaction's
+    /// hash, which is derived from its full payload, is left alone. Results in whether or not the payload was moved.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction whose payload should be deflated
+    pub fn deflate(&self, tx: &mut Transaction) -> Result<bool, PayloadError> {
+        let payload = &tx.transaction_data.payload;
+        if !worth_deflating(payload) {
+            return Ok(false);
+        }
+
+        let hash = self.put(payload)?;
+        tx.transaction_data.payload = reference(&hash);
+
+        Ok(true)
+    }
+
+    /// Replaces the payload of the given transaction with a reference, but only if the payload has been stored
+    /// already. Used to shrink gossip, since peers holding the same ledger will have stored it too. Results in whether
+    /// or not the payload was replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction whose payload should be deflated
+    pub fn deflate_known(&self, tx: &mut Transaction) -> Result<bool, PayloadError> {
+        let payload = &tx.transaction_data.payload;
+        if payload.len() <= REFERENCE_SIZE {
+            return Ok(false);
+        }
+
+        let hash = blake3::hash_slice(payload);
+        if !self.contains(&hash)? {
+            return Ok(false);
+        }
+
+        tx.transaction_data.payload = reference(&hash);
+
+        Ok(true)
+    }
+
+    /// Puts back the full payload of the given transaction, if it was deflated. Transactions whose payloads were never
+    /// deflated are left alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction whose payload should be inflated
+    pub fn inflate(&self, tx: &mut Transaction) -> Result<(), PayloadError> {
+        let hash = match referenced_hash(&tx.transaction_data.payload) {
+            Some(hash) => hash,
+            None => return Ok(()),
+        };
+
+        tx.transaction_data.payload = self.get(&hash)?.ok_or_else(|| PayloadError::Missing {
+            hash: hash.to_str(),
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::common::{address::Address, io}; // Import the address & io modules
+    use super::*; // Import names from our parent module
+
+    use num::BigUint; // Add support for large unsigned integers
+
+    #[test]
+    fn test_deflate() {
+        let db = sled::open(format!(
+            "{}/.tests/payloads/{}",
+            io::db_dir(),
+            rand::random::<u32>()
+        ))
+        .unwrap();
+        let store = PayloadStore::from(db.open_tree("payloads").unwrap());
+
+        let memo = b"monthly rent for the apartment on fifth street, thanks!";
+        let tx = |nonce, payload: &[u8]| {
+            Transaction::new(
+                nonce,
+                Address::default(),
+                Address::default(),
+                BigUint::from(1 as u8),
+                payload,
+                vec![],
+            )
+        };
+
+        // Peers that haven't seen the payload yet need it in full
+        let mut gossiped = tx(0, memo);
+        assert_eq!(store.deflate_known(&mut gossiped), Ok(false));
+
+        // Both transactions share the one stored payload, but keep their own hashes
+        let (mut first, mut second) = (tx(0, memo), tx(1, memo));
+        let hashes = (first.hash, second.hash);
+        assert_eq!(store.deflate(&mut first), Ok(true));
+        assert_eq!(store.deflate(&mut second), Ok(true));
+        assert_eq!(first.transaction_data.payload.len(), REFERENCE_SIZE);
+        assert_eq!(
+            first.transaction_data.payload,
+            second.transaction_data.payload
+        );
+        assert_eq!((first.hash, second.hash), hashes);
+
+        store.inflate(&mut first).unwrap();
+        assert_eq!(first.transaction_data.payload, memo.to_vec());
+        assert_eq!(store.deflate_known(&mut gossiped), Ok(true));
+
+        // Short payloads stay where they are
+        let mut short = tx(2, b"hi");
+        assert_eq!(store.deflate(&mut short), Ok(false));
+        store.inflate(&mut short).unwrap();
+        assert_eq!(short.transaction_data.payload, b"hi".to_vec());
+
+        // A reference to a payload that was never stored can't be inflated
+        let mut dangling = tx(3, &reference(&blake3::hash_slice(b"unknown")));
+        assert!(store.inflate(&mut dangling).is_err());
+    }
+}
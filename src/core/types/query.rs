@@ -0,0 +1,370 @@
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use num::bigint::BigUint; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::graph::{Graph, Node}; // Import the ledger types
+
+use super::super::super::{common::address::Address, crypto::hash::Hash}; // Import the address & hash types
+
+/// An error encountered while querying the transactions in the ledger.
+#[derive(Debug, Fail, PartialEq)]
+pub enum QueryError {
+    #[fail(
+        display = "the {} range is empty: its lower bound exceeds its upper bound",
+        field
+    )]
+    EmptyRange {
+        field: String, // The name of the field whose bounds are inverted
+    },
+    #[fail(display = "failed to read the ledger: {}", error)]
+    StorageFailure {
+        error: String, // The underlying error
+    },
+}
+
+impl From<sled::Error> for QueryError {
+    /// Converts the given sled error into a QueryError.
+    fn from(e: sled::Error) -> Self {
+        Self::StorageFailure {
+            error: e.to_string(),
+        }
+    }
+}
+
+/// The order in which matching transactions are returned.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    /// The order in which the transactions were added to the ledger
+    Ledger,
+
+    /// The time at which each transaction was made, as claimed by its sender
+    Timestamp,
+
+    /// The time at which the node executed each transaction. Transactions that haven't been executed, or whose
+    /// execution wasn't timestamped, come last.
+    Execution,
+
+    /// The number of finks sent by each transaction
+    Value,
+}
+
+impl Default for SortBy {
+    /// Sorts transactions in ledger order, unless told otherwise.
+    fn default() -> Self {
+        Self::Ledger
+    }
+}
+
+/// A set of conditions that each of the transactions returned by a query must meet, along with the order and page in
+/// which they should be returned. Each condition that is left out matches every transaction.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct TransactionQuery {
+    /// The address that the transactions must be sent by
+    pub sender: Option<Address>,
+
+    /// The address that the transactions must be sent to
+    pub recipient: Option<Address>,
+
+    /// The least number of finks that the transactions may send
+    pub min_value: Option<BigUint>,
+
+    /// The greatest number of finks that the transactions may send
+    pub max_value: Option<BigUint>,
+
+    /// The earliest UNIX timestamp at which the transactions may have been made, inclusive
+    pub since: Option<i64>,
+
+    /// The latest UNIX timestamp at which the transactions may have been made, inclusive
+    pub until: Option<i64>,
+
+    /// The earliest UNIX timestamp at which the transactions may have been executed by the node, inclusive
+    pub executed_since: Option<i64>,
+
+    /// The latest UNIX timestamp at which the transactions may have been executed by the node, inclusive
+    pub executed_until: Option<i64>,
+
+    /// The text that the payloads of the transactions must begin with
+    pub payload_prefix: Option<String>,
+
+    /// Whether or not to leave out transactions that haven't been executed yet
+    pub executed_only: bool,
+
+    /// The order in which the transactions should be returned
+    pub sort_by: SortBy,
+
+    /// Whether or not to reverse the order in which the transactions are returned
+    pub descending: bool,
+
+    /// The number of matching transactions to skip over
+    pub offset: usize,
+
+    /// The greatest number of transactions to return, or None to return every match
+    pub limit: Option<usize>,
+}
+
+impl TransactionQuery {
+    /// Makes sure that each of the ranges in the query could match at least one transaction.
+    fn validate(&self) -> Result<(), QueryError> {
+        if let (Some(min), Some(max)) = (&self.min_value, &self.max_value) {
+            if min > max {
+                return Err(QueryError::EmptyRange {
+                    field: "value".to_owned(),
+                });
+            }
+        }
+
+        if let (Some(since), Some(until)) = (self.since, self.until) {
+            if since > until {
+                return Err(QueryError::EmptyRange {
+                    field: "time".to_owned(),
+                });
+            }
+        }
+
+        if let (Some(since), Some(until)) = (self.executed_since, self.executed_until) {
+            if since > until {
+                return Err(QueryError::EmptyRange {
+                    field: "execution time".to_owned(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether or not the transaction held by the given node meets each of the query's conditions. Conditions
+    /// on the transaction itself are checked first, so that the state of the node is only read from the disk when
+    /// it's actually needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `ledger` - The ledger holding the node
+    /// * `index` - The index of the node in the ledger
+    /// * `node` - The node holding the transaction
+    fn matches(&self, ledger: &Graph, index: usize, node: &Node) -> Result<bool, QueryError> {
+        let data = &node.transaction.transaction_data;
+        let timestamp = data.timestamp.timestamp();
+
+        if self.sender.map_or(false, |sender| sender != data.sender)
+            || self
+                .recipient
+                .map_or(false, |recipient| recipient != data.recipient)
+            || self
+                .min_value
+                .as_ref()
+                .map_or(false, |min| data.value < *min)
+            || self
+                .max_value
+                .as_ref()
+                .map_or(false, |max| data.value > *max)
+            || self.since.map_or(false, |since| timestamp < since)
+            || self.until.map_or(false, |until| timestamp > until)
+            || self
+                .payload_prefix
+                .as_ref()
+                .map_or(false, |prefix| !data.payload.starts_with(prefix.as_bytes()))
+        {
+            return Ok(false);
+        }
+
+        // Only read the transaction's receipt if the query cares when it was executed
+        if self.executed_since.is_some() || self.executed_until.is_some() {
+            let executed_at = match executed_at(ledger, node)? {
+                Some(executed_at) => executed_at.timestamp(),
+                None => return Ok(false),
+            };
+
+            if self
+                .executed_since
+                .map_or(false, |since| executed_at < since)
+                || self
+                    .executed_until
+                    .map_or(false, |until| executed_at > until)
+            {
+                return Ok(false);
+            }
+        }
+
+        // Partially loaded ledgers only keep the transactions in memory, so the state may be on the disk
+        if self.executed_only && node.state_entry.is_none() {
+            return Ok(ledger
+                .get_pure(index)?
+                .map_or(false, |node| node.state_entry.is_some()));
+        }
+
+        Ok(true)
+    }
+}
+
+/// Gets the time at which the node executed the transaction held by the given node, if its execution was
+/// timestamped.
+///
+/// # Arguments
+///
+/// * `ledger` - The ledger holding the node
+/// * `node` - The node holding the transaction
+fn executed_at(ledger: &Graph, node: &Node) -> Result<Option<DateTime<Utc>>, QueryError> {
+    Ok(ledger
+        .get_receipt(node.hash)?
+        .and_then(|receipt| receipt.executed_at))
+}
+
+/// Gets the hashes of each of the transactions in the given ledger meeting the conditions of the given query, sorted
+/// and paginated as the query asks.
+///
+/// # Arguments
+///
+/// * `ledger` - The ledger that should be queried
+/// * `query` - The conditions, order, and page of the transactions that should be returned
+pub fn run(ledger: &Graph, query: &TransactionQuery) -> Result<Vec<Hash>, QueryError> {
+    query.validate()?;
+
+    let mut matches: Vec<&Node> = Vec::new();
+    for (index, node) in ledger.nodes.iter().enumerate() {
+        if query.matches(ledger, index, node)? {
+            matches.push(node);
+        }
+    }
+
+    // Ties keep their ledger order, since the sort is stable
+    match query.sort_by {
+        SortBy::Ledger => (),
+        SortBy::Timestamp => {
+            matches.sort_by_key(|node| node.transaction.transaction_data.timestamp)
+        }
+        SortBy::Execution => {
+            let mut keyed = Vec::with_capacity(matches.len());
+            for node in matches {
+                keyed.push((executed_at(ledger, node)?, node));
+            }
+
+            keyed.sort_by_key(|(executed_at, _)| (executed_at.is_none(), *executed_at));
+            matches = keyed.into_iter().map(|(_, node)| node).collect();
+        }
+        SortBy::Value => matches.sort_by(|a, b| {
+            a.transaction
+                .transaction_data
+                .value
+                .cmp(&b.transaction.transaction_data.value)
+        }),
+    }
+    if query.descending {
+        matches.reverse();
+    }
+
+    Ok(matches
+        .into_iter()
+        .skip(query.offset)
+        .take(query.limit.unwrap_or(usize::MAX))
+        .map(|node| node.hash)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::{common::io, crypto::blake3}; // Import the io & hashing modules
+    use super::super::{receipt::Receipt, transaction::Transaction}; // Import the receipt & transaction types
+    use super::*;
+    use chrono::TimeZone; // Import the time zone trait // Import names from our parent module
+
+    #[test]
+    fn test_run() {
+        let alice = blake3::hash_slice(b"alice");
+        let bob = blake3::hash_slice(b"bob");
+
+        let mut root = Transaction::new(
+            0,
+            Address::default(),
+            alice,
+            BigUint::from(100 as u8),
+            b"genesis",
+            vec![],
+        ); // Make a root transaction
+        root.genesis = true;
+
+        let mut ledger = Graph::new_with_db_path(
+            root,
+            &format!("{}/.tests/query/{}", io::db_dir(), rand::random::<u32>()),
+        ); // Make a ledger
+
+        let mut hashes = Vec::new();
+        for nonce in 0..4 {
+            let tx = Transaction::new(
+                nonce,
+                alice,
+                bob,
+                BigUint::from(10 - nonce as u8),
+                if nonce % 2 == 0 { b"rent" } else { b"food" },
+                vec![],
+            );
+            hashes.push(tx.hash);
+            ledger.push(tx, None);
+        }
+
+        let query = TransactionQuery {
+            sender: Some(alice),
+            payload_prefix: Some("re".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(run(&ledger, &query), Ok(vec![hashes[0], hashes[2]]));
+
+        // Sort by value, smallest first, and page through the matches
+        let query = TransactionQuery {
+            recipient: Some(bob),
+            max_value: Some(BigUint::from(9 as u8)),
+            sort_by: SortBy::Value,
+            offset: 1,
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(run(&ledger, &query), Ok(vec![hashes[2]]));
+
+        // None of the transactions have been executed
+        let query = TransactionQuery {
+            sender: Some(alice),
+            executed_only: true,
+            ..Default::default()
+        };
+        assert_eq!(run(&ledger, &query), Ok(vec![]));
+
+        let query = TransactionQuery {
+            since: Some(10),
+            until: Some(5),
+            ..Default::default()
+        };
+        assert!(run(&ledger, &query).is_err());
+
+        // Order by when the node executed the transactions, rather than by when their senders claim to have made them
+        for (i, hash) in hashes.iter().enumerate().take(2) {
+            ledger
+                .record_receipt(
+                    *hash,
+                    &Receipt {
+                        executed_at: Some(Utc.timestamp(100 - i as i64, 0)),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+        }
+        let query = TransactionQuery {
+            sender: Some(alice),
+            sort_by: SortBy::Execution,
+            limit: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            run(&ledger, &query),
+            Ok(vec![hashes[1], hashes[0], hashes[2]])
+        );
+
+        let query = TransactionQuery {
+            executed_since: Some(100),
+            ..Default::default()
+        };
+        assert_eq!(run(&ledger, &query), Ok(vec![hashes[0]]));
+    }
+}
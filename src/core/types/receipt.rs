@@ -1,9 +1,118 @@
-use super::logs; // Import the logs module
+use super::{burn, gas, logs, trace}; // Import the burn, gas, logs & trace modules
+
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use num::bigint::BigUint; // Add support for large unsigned integers
 
 use serde::{Deserialize, Serialize}; // Import serde serialization
 
+use std::{collections::HashMap, str::FromStr}; // Import the map & parsing types
+
 use super::super::super::crypto::hash::Hash; // Import the address utility
 
+/// The outcome of a transaction's execution.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ExecutionStatus {
+    /// The transaction was executed successfully
+    Success,
+    /// The sender of the transaction couldn't afford to send its value; no funds were moved
+    InsufficientFunds,
+    /// The transaction's nonce didn't directly follow that of the sender's last transaction
+    InvalidNonce,
+    /// The transaction's gas limit didn't cover the gas that it used; its fee was paid, but no funds were moved
+    OutOfGas,
+    /// The transaction was admitted to the DAG, but was rolled back
+    Reverted,
+}
+
+/// An error encountered while choosing how executions are timestamped.
+#[derive(Debug, Fail, PartialEq)]
+pub enum ExecutionClockError {
+    #[fail(
+        display = "unknown execution timestamp mode {} (expected off, wall-clock, or monotonic)",
+        mode
+    )]
+    UnknownMode {
+        mode: String, // The mode that was given
+    },
+}
+
+/// How the node timestamps the execution of each transaction in its receipt.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionClock {
+    /// Executions aren't timestamped
+    Off,
+    /// Executions are stamped with the node's clock as is
+    WallClock,
+    /// Executions are stamped with the node's clock, but never earlier than the execution before them, so that
+    /// ordering by execution time always agrees with the order in which transactions were executed
+    Monotonic,
+}
+
+impl Default for ExecutionClock {
+    /// Keeps execution timestamps in execution order, unless told otherwise.
+    fn default() -> Self {
+        Self::Monotonic
+    }
+}
+
+impl FromStr for ExecutionClock {
+    type Err = ExecutionClockError;
+
+    /// Parses an execution timestamp mode: off, wall-clock, or monotonic.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "wall-clock" => Ok(Self::WallClock),
+            "monotonic" => Ok(Self::Monotonic),
+            _ => Err(ExecutionClockError::UnknownMode { mode: s.to_owned() }),
+        }
+    }
+}
+
+/// Implement a set of execution clock helper methods.
+impl ExecutionClock {
+    /// Gets the time that an execution happening now should be stamped with, if executions are stamped at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `last` - The time that the previous execution was stamped with, if any
+    /// * `now` - The current time
+    pub fn stamp(self, last: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Off => None,
+            Self::WallClock => Some(now),
+            Self::Monotonic => Some(last.map_or(now, |last| last.max(now))),
+        }
+    }
+}
+
+/// Where a part of the value moved by a transaction went.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueFlow {
+    /// Taken from an account (e.g. the sender)
+    Debit,
+    /// Credited to an account (e.g. the recipient, or one of a batch's recipients)
+    Credit,
+    /// Paid into the fee pool, which the next mint pays out to the network's voting accounts
+    Fee,
+    /// Sent to the burn address, and taken out of circulation for good
+    Burn,
+}
+
+/// A single movement of value made by a transaction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReceiptItem {
+    /// Where the value went
+    pub flow: ValueFlow,
+    /// The address of the account that the value was taken from, or given to
+    pub account: String,
+    /// The number of finks moved
+    pub amount: BigUint,
+}
+
 /// A receipt of a transaction's execution.
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct Receipt {
@@ -11,6 +120,97 @@ pub struct Receipt {
     pub state_hash: Hash,
     /// Logs emitted at run time
     pub logs: Vec<logs::Log>,
+    /// The outcome of the transaction's execution, if the receipt was recorded as the transaction was executed
+    pub status: Option<ExecutionStatus>,
+    /// Each movement of value made by the transaction, if the receipt was recorded as the transaction was executed
+    #[serde(default)]
+    pub items: Vec<ReceiptItem>,
+    /// When the node executed (confirmed) the transaction, if it timestamps executions. Unlike the transaction's own
+    /// timestamp, this isn't up to the sender.
+    pub executed_at: Option<DateTime<Utc>>,
+}
+
+/// A receipt, as laid out by version 1 of the transaction format, before receipts recorded the outcome of a
+/// transaction's execution.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct LegacyReceipt {
+    /// Hash of state at transaction
+    pub state_hash: Hash,
+    /// Logs emitted at run time
+    pub logs: Vec<logs::Log>,
+}
+
+/// Implement a set of receipt helper methods.
+impl Receipt {
+    /// Determines whether or not the receipt can be laid out by version 1 of the transaction format, which is the
+    /// case for any receipt that doesn't record the outcome or the time of an execution.
+    pub fn is_legacy(&self) -> bool {
+        self.status.is_none() && self.items.is_empty() && self.executed_at.is_none()
+    }
+}
+
+impl From<&Receipt> for LegacyReceipt {
+    /// Lays out the given receipt in version 1 of the transaction format, dropping any fields added since.
+    fn from(receipt: &Receipt) -> Self {
+        Self {
+            state_hash: receipt.state_hash,
+            logs: receipt.logs.clone(),
+        }
+    }
+}
+
+impl From<LegacyReceipt> for Receipt {
+    /// Upgrades the given receipt from version 1 of the transaction format.
+    fn from(receipt: LegacyReceipt) -> Self {
+        Self {
+            state_hash: receipt.state_hash,
+            logs: receipt.logs,
+            ..Default::default()
+        }
+    }
+}
+
+/// Itemizes each movement of value between the given balances, debits first, so that accounting systems can reconcile
+/// balances without having to diff states themselves.
+///
+/// # Arguments
+///
+/// * `before` - The balances that the transaction was executed on top of
+/// * `after` - The balances that executing the transaction resulted in
+pub fn itemize(
+    before: &HashMap<String, BigUint>,
+    after: &HashMap<String, BigUint>,
+) -> Vec<ReceiptItem> {
+    let burn_address = burn::burn_address().to_str();
+    let fee_pool_address = gas::fee_pool_address().to_str();
+
+    let mut items: Vec<ReceiptItem> = trace::diff_balance_maps(before, after)
+        .into_iter()
+        .map(|change| {
+            if change.after < change.before {
+                ReceiptItem {
+                    flow: ValueFlow::Debit,
+                    amount: change.before - change.after,
+                    account: change.account,
+                }
+            } else {
+                ReceiptItem {
+                    flow: if change.account == burn_address {
+                        ValueFlow::Burn
+                    } else if change.account == fee_pool_address {
+                        ValueFlow::Fee
+                    } else {
+                        ValueFlow::Credit
+                    },
+                    amount: change.after - change.before,
+                    account: change.account,
+                }
+            }
+        })
+        .collect();
+    items.sort_by(|a, b| a.flow.cmp(&b.flow).then(a.account.cmp(&b.account)));
+
+    items
 }
 
 /// A mapping between a set of tx hashes and transaction receipts.
@@ -22,7 +222,42 @@ pub struct ReceiptMap {
     pub receipts: Vec<Receipt>,
 }
 
+/// A mapping between a set of tx hashes and transaction receipts, as laid out by version 1 of the transaction format.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct LegacyReceiptMap {
+    /// All transactions affected by the grouped state change
+    pub associated_transactions: Vec<Hash>,
+    /// All of the corresponding receipts
+    pub receipts: Vec<LegacyReceipt>,
+}
+
+impl From<&ReceiptMap> for LegacyReceiptMap {
+    /// Lays out the given receipts in version 1 of the transaction format.
+    fn from(map: &ReceiptMap) -> Self {
+        Self {
+            associated_transactions: map.associated_transactions.clone(),
+            receipts: map.receipts.iter().map(LegacyReceipt::from).collect(),
+        }
+    }
+}
+
+impl From<LegacyReceiptMap> for ReceiptMap {
+    /// Upgrades the given receipts from version 1 of the transaction format.
+    fn from(map: LegacyReceiptMap) -> Self {
+        Self {
+            associated_transactions: map.associated_transactions,
+            receipts: map.receipts.into_iter().map(Receipt::from).collect(),
+        }
+    }
+}
+
 impl ReceiptMap {
+    /// Determines whether or not each of the receipts in the map can be laid out by version 1 of the transaction
+    /// format.
+    pub fn is_legacy(&self) -> bool {
+        self.receipts.iter().all(Receipt::is_legacy)
+    }
+
     /// Gets a receipt stored in the receiptmap by its hash.
     ///
     /// # Arguments
@@ -56,3 +291,88 @@ impl ReceiptMap {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::{state::Entry, transaction::Transaction}; // Import the state & transaction types
+    use super::*; // Import names from our parent module
+
+    use super::super::super::super::crypto::blake3; // Import the blake3 hashing module
+
+    #[test]
+    fn test_itemize() {
+        let (alice, bob) = (blake3::hash_slice(b"alice"), blake3::hash_slice(b"bob"));
+
+        let mut balances = HashMap::new();
+        balances.insert(alice.to_str(), BigUint::from(10 as u8));
+        let parent = Entry::new(HashMap::new(), balances.clone());
+
+        let paid = Transaction::new(0, alice, bob, BigUint::from(4 as u8), b"", vec![])
+            .execute(Some(parent.clone()));
+        assert_eq!(
+            itemize(&balances, &paid.data.balances),
+            vec![
+                ReceiptItem {
+                    flow: ValueFlow::Debit,
+                    account: alice.to_str(),
+                    amount: BigUint::from(4 as u8),
+                },
+                ReceiptItem {
+                    flow: ValueFlow::Credit,
+                    account: bob.to_str(),
+                    amount: BigUint::from(4 as u8),
+                },
+            ]
+        );
+
+        // Value sent to the burn address is itemized as burned, rather than credited
+        let burned = Transaction::new(
+            0,
+            alice,
+            burn::burn_address(),
+            BigUint::from(3 as u8),
+            b"",
+            vec![],
+        )
+        .execute(Some(parent));
+        let items = itemize(&balances, &burned.data.balances);
+        assert_eq!(items[1].flow, ValueFlow::Burn);
+        assert_eq!(items[1].amount, BigUint::from(3 as u8));
+    }
+
+    #[test]
+    fn test_execution_clock() {
+        let now = Utc::now();
+        let later = now + chrono::Duration::seconds(5);
+
+        assert_eq!(ExecutionClock::Off.stamp(Some(now), now), None);
+        assert_eq!(ExecutionClock::WallClock.stamp(Some(later), now), Some(now));
+
+        // A clock stepping backwards never puts an execution before the one preceding it
+        assert_eq!(
+            ExecutionClock::Monotonic.stamp(Some(later), now),
+            Some(later)
+        );
+        assert_eq!(ExecutionClock::Monotonic.stamp(None, now), Some(now));
+
+        assert_eq!("wall-clock".parse(), Ok(ExecutionClock::WallClock));
+        assert!("sundial".parse::<ExecutionClock>().is_err());
+    }
+
+    #[test]
+    fn test_is_legacy() {
+        assert!(Receipt::default().is_legacy());
+
+        // Receipts stamped with an execution time can't be laid out without it
+        let stamped = Receipt {
+            executed_at: Some(Utc::now()),
+            ..Default::default()
+        };
+        assert!(!stamped.is_legacy());
+        assert!(!ReceiptMap {
+            associated_transactions: vec![Hash::default()],
+            receipts: vec![stamped],
+        }
+        .is_legacy());
+    }
+}
@@ -0,0 +1,745 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    str::FromStr,
+}; // Import the collections & formatting libraries
+
+use num::bigint::BigUint; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::{
+    batch::BatchTransfer,
+    graph::Graph,
+    receipt::Receipt,
+    state::{self, Entry},
+    transaction::Transaction,
+}; // Import the ledger types
+
+use super::super::super::{common::address::Address, crypto::hash::Hash}; // Import the address & hash types
+
+/// The number of events returned in a single page, unless a different limit is asked for.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// The greatest number of events that may be returned in a single page.
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// An error encountered while replaying the ledger.
+#[derive(Debug, Fail, PartialEq)]
+pub enum ReplayError {
+    #[fail(display = "invalid replay cursor {} (expected index:hash)", cursor)]
+    MalformedCursor {
+        cursor: String, // The cursor that couldn't be parsed
+    },
+    #[fail(
+        display = "the ledger no longer holds transaction {} at index {}; restart the replay from an earlier point",
+        hash, index
+    )]
+    CursorInvalidated {
+        index: usize, // The index that the cursor points after
+        hash: String, // The hash of the last transaction that was replayed
+    },
+    #[fail(display = "no transaction with the hash {} exists in the ledger", hash)]
+    NoSuchTransaction {
+        hash: String, // The hash that was asked for
+    },
+    #[fail(display = "failed to read the ledger: {}", error)]
+    StorageFailure {
+        error: String, // The underlying error
+    },
+}
+
+impl From<sled::Error> for ReplayError {
+    /// Converts the given sled error into a ReplayError.
+    fn from(e: sled::Error) -> Self {
+        Self::StorageFailure {
+            error: e.to_string(),
+        }
+    }
+}
+
+/// A position in the replay, just after the last transaction delivered. The hash of that transaction is kept, so that
+/// a cursor is refused, rather than silently skipping or repeating transactions, if the ledger's head was rolled back
+/// since the cursor was handed out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cursor {
+    /// The index of the last transaction delivered
+    pub index: usize,
+
+    /// The hash of the last transaction delivered
+    pub hash: Hash,
+}
+
+impl fmt::Display for Cursor {
+    /// Writes the cursor as index:hash.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.index, self.hash)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = ReplayError;
+
+    /// Parses a cursor written as index:hash.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || ReplayError::MalformedCursor {
+            cursor: s.to_owned(),
+        };
+
+        let mut parts = s.splitn(2, ':');
+        let index = parts
+            .next()
+            .and_then(|index| index.parse::<usize>().ok())
+            .ok_or_else(malformed)?;
+        let hash = Hash::from(parts.next().ok_or_else(malformed)?);
+
+        // Hashes that can't be decoded come out as zero
+        if hash == Hash::default() {
+            return Err(malformed());
+        }
+
+        Ok(Self { index, hash })
+    }
+}
+
+/// Where a replay should begin.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplayStart {
+    /// Start just after the position described by a cursor handed out with an earlier page
+    Cursor(String),
+
+    /// Start at the transaction with the given hash, inclusive
+    Hash(String),
+
+    /// Start at the first transaction in the ledger stamped at or after the given UNIX timestamp
+    Timestamp(i64),
+}
+
+/// The balance of an account touched by a transaction, once the transaction was executed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BalanceChange {
+    /// The address of the account
+    pub address: Address,
+
+    /// The balance of the account after the transaction
+    pub balance: BigUint,
+}
+
+/// A transaction in the ledger, along with what its execution did.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReplayEvent {
+    /// The index of the transaction in the ledger
+    pub index: usize,
+
+    /// The transaction
+    pub transaction: Transaction,
+
+    /// The receipt recorded for the transaction, if it was executed by this node
+    pub receipt: Option<Receipt>,
+
+    /// The hash of the state resulting from the transaction, if it has been executed
+    pub state_hash: Option<Hash>,
+
+    /// The balance of each of the accounts that the transaction touched, once it was executed
+    pub changes: Vec<BalanceChange>,
+}
+
+/// A page of the replay.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReplayPage {
+    /// Each of the events in the page, in ledger order
+    pub events: Vec<ReplayEvent>,
+
+    /// The cursor that the next page should be requested with. Once the replay has caught up with the ledger's head,
+    /// the cursor is still handed out, so that the consumer can poll for new transactions with it.
+    pub next_cursor: Option<String>,
+}
+
+/// An account whose balance or nonce differs between the state recomputed by a replay, and the state stored in the
+/// ledger.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AccountDiff {
+    /// The address of the account
+    pub address: String,
+
+    /// The balance of the account in the stored state, if it has one there
+    pub stored_balance: Option<BigUint>,
+
+    /// The balance of the account in the recomputed state, if it has one there
+    pub recomputed_balance: Option<BigUint>,
+
+    /// The nonce of the account in the stored state, if it has one there
+    pub stored_nonce: Option<u64>,
+
+    /// The nonce of the account in the recomputed state, if it has one there
+    pub recomputed_nonce: Option<u64>,
+}
+
+/// The first transaction in the ledger citing a parent state that couldn't be reproduced by re-executing the ledger
+/// from its genesis.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Divergence {
+    /// The index of the transaction in the ledger
+    pub index: usize,
+
+    /// The hash of the transaction
+    pub tx_hash: Hash,
+
+    /// The hash of the parent state cited by the transaction
+    pub cited_state_hash: Hash,
+
+    /// The hash of the parent state recomputed by the replay
+    pub recomputed_state_hash: Hash,
+
+    /// The hash of the parent state built from the states stored alongside the transaction's parents, if each of them
+    /// has one
+    pub stored_state_hash: Option<Hash>,
+
+    /// Each of the accounts on which the stored and recomputed parent states disagree
+    pub diff: Vec<AccountDiff>,
+}
+
+/// The outcome of re-executing the entire ledger from its genesis.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct VerificationReport {
+    /// The number of transactions that were re-executed
+    pub executed: usize,
+
+    /// The number of parent state citations that were checked against the recomputed states
+    pub citations_checked: usize,
+
+    /// The first transaction whose citation couldn't be reproduced, if any
+    pub divergence: Option<Divergence>,
+}
+
+/// The outcome of recomputing the state resulting from a single node, and comparing it against the hash recorded for
+/// the node's state, which is kept even once the state itself has been purged.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StateProof {
+    /// The index of the node in the ledger
+    pub index: usize,
+
+    /// The hash of the node's transaction
+    pub tx_hash: Hash,
+
+    /// The hash of the state recorded for the node, if the node has been executed
+    pub recorded_state_hash: Option<Hash>,
+
+    /// The hash of the state recomputed by re-executing the node's ancestors from the genesis
+    pub recomputed_state_hash: Hash,
+
+    /// The number of transactions that were re-executed
+    pub executed: usize,
+
+    /// Whether or not the recorded state hash matches the recomputed one
+    pub verified: bool,
+}
+
+/// Replays the transactions in the given ledger, in ledger order, from the given starting point. Every node that has
+/// synchronized the same ledger replays it in the same order.
+///
+/// # Arguments
+///
+/// * `ledger` - The ledger that should be replayed
+/// * `start` - Where the replay should begin, or None to start at the genesis
+/// * `limit` - The greatest number of events that should be returned (capped at MAX_PAGE_SIZE)
+pub fn replay(
+    ledger: &Graph,
+    start: Option<&ReplayStart>,
+    limit: Option<usize>,
+) -> Result<ReplayPage, ReplayError> {
+    let first = match start {
+        None => 0,
+        Some(ReplayStart::Cursor(cursor)) => {
+            let cursor = cursor.parse::<Cursor>()?;
+
+            // Make sure the transaction we stopped at is still where we left it
+            match ledger.nodes.get(cursor.index) {
+                Some(node) if node.hash == cursor.hash => cursor.index + 1,
+                _ => {
+                    return Err(ReplayError::CursorInvalidated {
+                        index: cursor.index,
+                        hash: cursor.hash.to_str(),
+                    })
+                }
+            }
+        }
+        Some(ReplayStart::Hash(hash)) => ledger
+            .nodes
+            .iter()
+            .position(|node| node.hash == Hash::from(hash.as_str()))
+            .ok_or_else(|| ReplayError::NoSuchTransaction { hash: hash.clone() })?,
+        Some(ReplayStart::Timestamp(timestamp)) => ledger
+            .nodes
+            .iter()
+            .position(|node| node.transaction.transaction_data.timestamp.timestamp() >= *timestamp)
+            .unwrap_or_else(|| ledger.nodes.len()),
+    };
+
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+    let last = ledger.nodes.len().min(first.saturating_add(limit));
+
+    let mut events = Vec::with_capacity(last.saturating_sub(first));
+    for index in first..last {
+        let node = match ledger.get_pure(index)? {
+            Some(node) => node,
+            None => continue,
+        };
+
+        let changes = match &node.state_entry {
+            Some(state) => touched_accounts(&node.transaction)
+                .into_iter()
+                .map(|address| BalanceChange {
+                    address,
+                    balance: state
+                        .data
+                        .balances
+                        .get(&address.to_str())
+                        .cloned()
+                        .unwrap_or_default(),
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        events.push(ReplayEvent {
+            index,
+            receipt: ledger.get_receipt(node.hash)?,
+            state_hash: node.state_entry.as_ref().map(|state| state.hash),
+            transaction: node.transaction,
+            changes,
+        });
+    }
+
+    // Point the next page just past the last event, or wherever this page would have started
+    let next_cursor = match events.last() {
+        Some(event) => Some(Cursor {
+            index: event.index,
+            hash: event.transaction.hash,
+        }),
+        None if first > 0 => ledger.nodes.get(first - 1).map(|node| Cursor {
+            index: first - 1,
+            hash: node.hash,
+        }),
+        None => None,
+    };
+
+    Ok(ReplayPage {
+        events,
+        next_cursor: next_cursor.map(|cursor| cursor.to_string()),
+    })
+}
+
+/// Re-executes each of the transactions in the given ledger, in ledger order, starting from the genesis, and compares
+/// the parent state recomputed for each transaction against the parent state hash that the transaction cites. Every
+/// state is kept to the replay; nothing in the ledger is touched, so the ledger may be a snapshot of a live one.
+/// Stops at the first citation that can't be reproduced, and describes how the recomputed state differs from the one
+/// stored in the ledger.
+///
+/// # Arguments
+///
+/// * `ledger` - The ledger that should be re-executed
+pub fn verify(ledger: &Graph) -> Result<VerificationReport, ReplayError> {
+    let mut recomputed: HashMap<Hash, Entry> = HashMap::new(); // The state left behind by each transaction
+    let mut citations_checked = 0;
+
+    for index in 0..ledger.nodes.len() {
+        let node = match ledger.get_pure(index)? {
+            Some(node) => node,
+            None => continue,
+        };
+        let tx_data = &node.transaction.transaction_data;
+
+        // Roots are executed on their own
+        if tx_data.parents.is_empty() {
+            recomputed.insert(node.hash, node.transaction.execute(None));
+
+            continue;
+        }
+
+        // Merge the parents' states in the same order that the ledger does when the transaction is made
+        let parent_state = tx_data
+            .parents
+            .iter()
+            .filter_map(|parent| recomputed.get(parent))
+            .fold(Entry::default(), |merged, entry| {
+                state::merge_entries(vec![merged, entry.clone()])
+            });
+
+        if let Some(cited) = tx_data.parent_state_hash {
+            citations_checked += 1;
+
+            if cited != parent_state.hash {
+                let stored = stored_parent_state(ledger, &tx_data.parents)?;
+
+                return Ok(VerificationReport {
+                    executed: recomputed.len(),
+                    citations_checked,
+                    divergence: Some(Divergence {
+                        index,
+                        tx_hash: node.hash,
+                        cited_state_hash: cited,
+                        recomputed_state_hash: parent_state.hash,
+                        stored_state_hash: stored.as_ref().map(|stored| stored.hash),
+                        diff: stored
+                            .map(|stored| diff_states(&stored, &parent_state))
+                            .unwrap_or_default(),
+                    }),
+                });
+            }
+        }
+
+        recomputed.insert(node.hash, node.transaction.execute(Some(parent_state)));
+    }
+
+    Ok(VerificationReport {
+        executed: recomputed.len(),
+        citations_checked,
+        divergence: None,
+    })
+}
+
+/// Recomputes the state resulting from the node with the given hash by re-executing it, and each of its ancestors, in
+/// ledger order from the genesis, and compares it against the state hash recorded for the node. None of the states
+/// stored in the ledger are trusted along the way, so a node whose state has been purged can still be checked.
+///
+/// # Arguments
+///
+/// * `ledger` - The ledger holding the node
+/// * `node_hash` - The hash of the node whose state should be checked
+pub fn verify_state(ledger: &Graph, node_hash: Hash) -> Result<StateProof, ReplayError> {
+    let target = match ledger.hash_routes.get(&node_hash) {
+        Some(index) => *index,
+        None => {
+            return Err(ReplayError::NoSuchTransaction {
+                hash: node_hash.to_str(),
+            })
+        }
+    };
+
+    // Only the node's ancestors have any bearing on its state
+    let mut ancestors: BTreeSet<usize> = BTreeSet::new();
+    let mut work: Vec<usize> = vec![target];
+    while let Some(index) = work.pop() {
+        if !ancestors.insert(index) {
+            continue;
+        }
+
+        work.extend(
+            ledger.nodes[index]
+                .transaction
+                .transaction_data
+                .parents
+                .iter()
+                .filter_map(|parent| ledger.hash_routes.get(parent).copied()),
+        );
+    }
+
+    let mut recomputed: HashMap<Hash, Entry> = HashMap::new(); // The state left behind by each ancestor
+
+    // Parents always come before their children, so executing in ledger order resolves each parent first
+    for index in ancestors {
+        let tx = &ledger.nodes[index].transaction;
+        let parents = &tx.transaction_data.parents;
+
+        let entry = if parents.is_empty() {
+            tx.execute(None)
+        } else {
+            tx.execute(Some(
+                parents
+                    .iter()
+                    .filter_map(|parent| recomputed.get(parent))
+                    .fold(Entry::default(), |merged, entry| {
+                        state::merge_entries(vec![merged, entry.clone()])
+                    }),
+            ))
+        };
+
+        recomputed.insert(tx.hash, entry);
+    }
+
+    let recomputed_state_hash = recomputed[&node_hash].hash;
+    let recorded_state_hash = ledger.get_state_hash_at(node_hash)?;
+
+    Ok(StateProof {
+        index: target,
+        tx_hash: node_hash,
+        recorded_state_hash,
+        recomputed_state_hash,
+        executed: recomputed.len(),
+        verified: recorded_state_hash == Some(recomputed_state_hash),
+    })
+}
+
+/// Merges the states stored in the ledger alongside each of the given parents. Results in None if any of the parents
+/// is missing, or hasn't been executed.
+///
+/// # Arguments
+///
+/// * `ledger` - The ledger holding the parents
+/// * `parents` - The hashes of each of the parents
+fn stored_parent_state(ledger: &Graph, parents: &[Hash]) -> Result<Option<Entry>, ReplayError> {
+    let mut merged = Entry::default();
+
+    for parent in parents {
+        let stored = match ledger.hash_routes.get(parent) {
+            Some(index) => ledger.get_pure(*index)?.and_then(|node| node.state_entry),
+            None => None,
+        };
+
+        match stored {
+            Some(entry) => merged = state::merge_entries(vec![merged, entry]),
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(merged))
+}
+
+/// Lists each of the accounts whose balance or nonce differs between the given states, sorted by address.
+///
+/// # Arguments
+///
+/// * `stored` - The state stored in the ledger
+/// * `recomputed` - The state recomputed by the replay
+pub fn diff_states(stored: &Entry, recomputed: &Entry) -> Vec<AccountDiff> {
+    let addresses: BTreeSet<&String> = stored
+        .data
+        .balances
+        .keys()
+        .chain(stored.data.nonces.keys())
+        .chain(recomputed.data.balances.keys())
+        .chain(recomputed.data.nonces.keys())
+        .collect();
+
+    addresses
+        .into_iter()
+        .map(|address| AccountDiff {
+            address: address.clone(),
+            stored_balance: stored.data.balances.get(address).cloned(),
+            recomputed_balance: recomputed.data.balances.get(address).cloned(),
+            stored_nonce: stored.data.nonces.get(address).copied(),
+            recomputed_nonce: recomputed.data.nonces.get(address).copied(),
+        })
+        .filter(|diff| {
+            diff.stored_balance != diff.recomputed_balance
+                || diff.stored_nonce != diff.recomputed_nonce
+        })
+        .collect()
+}
+
+/// Gets each of the accounts whose balance the given transaction may change: its sender, its recipient, and the
+/// recipients of any batch that it carries.
+///
+/// # Arguments
+///
+/// * `tx` - The transaction
+fn touched_accounts(tx: &Transaction) -> Vec<Address> {
+    let mut touched = vec![tx.transaction_data.sender, tx.transaction_data.recipient];
+
+    if let Ok(Some(batch)) = BatchTransfer::from_payload(&tx.transaction_data.payload) {
+        touched.extend(batch.transfers.iter().map(|transfer| transfer.recipient));
+    }
+
+    touched.dedup();
+
+    touched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::{common::io, crypto::blake3}; // Import the io & hashing modules
+    use super::*; // Import names from our parent module
+
+    /// Makes a ledger holding a genesis and the given number of transactions.
+    fn test_ledger(n: u64) -> Graph {
+        let mut root = Transaction::new(
+            0,
+            Address::default(),
+            blake3::hash_slice(b"alice"),
+            BigUint::from(100 as u8),
+            b"genesis",
+            vec![],
+        ); // Make a root transaction
+        root.genesis = true;
+
+        let mut ledger = Graph::new_with_db_path(
+            root,
+            &format!("{}/.tests/replay/{}", io::db_dir(), rand::random::<u32>()),
+        ); // Make a ledger
+
+        for nonce in 0..n {
+            ledger.push(
+                Transaction::new(
+                    nonce,
+                    blake3::hash_slice(b"alice"),
+                    blake3::hash_slice(b"bob"),
+                    BigUint::from(1 as u8),
+                    b"test",
+                    vec![],
+                ),
+                None,
+            );
+        }
+
+        ledger
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut ledger = test_ledger(4);
+
+        let first = replay(&ledger, None, Some(2)).unwrap();
+        assert_eq!(
+            first.events.iter().map(|e| e.index).collect::<Vec<usize>>(),
+            vec![0, 1]
+        );
+        assert_eq!(first.events[0].changes[1].balance, BigUint::from(100 as u8)); // The genesis funded alice
+
+        // Resume from the cursor
+        let cursor = ReplayStart::Cursor(first.next_cursor.clone().unwrap());
+        let second = replay(&ledger, Some(&cursor), Some(10)).unwrap();
+        assert_eq!(
+            second
+                .events
+                .iter()
+                .map(|e| e.index)
+                .collect::<Vec<usize>>(),
+            vec![2, 3, 4]
+        );
+
+        // A caught-up consumer gets nothing new, but keeps its place
+        let caught_up = ReplayStart::Cursor(second.next_cursor.clone().unwrap());
+        let third = replay(&ledger, Some(&caught_up), None).unwrap();
+        assert!(third.events.is_empty());
+        assert_eq!(third.next_cursor, second.next_cursor);
+
+        // Starting from a hash includes the transaction itself
+        let from_hash = ReplayStart::Hash(ledger.nodes[3].hash.to_str());
+        assert_eq!(
+            replay(&ledger, Some(&from_hash), None).unwrap().events[0].index,
+            3
+        );
+
+        // Cursors are refused once the transaction they point after is rolled back
+        ledger.rollback_head();
+        assert!(replay(&ledger, Some(&caught_up), None).is_err());
+        assert!(replay(
+            &ledger,
+            Some(&ReplayStart::Cursor("nonsense".to_owned())),
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut ledger = test_ledger(0);
+        let genesis = ledger.nodes[0].hash;
+        let genesis_state = ledger.nodes[0].state_entry.clone().unwrap();
+
+        // Build a transaction on the genesis, citing the state that the genesis left behind
+        let mut tx = Transaction::new(
+            0,
+            blake3::hash_slice(b"alice"),
+            blake3::hash_slice(b"bob"),
+            BigUint::from(10 as u8),
+            b"test",
+            vec![genesis],
+        );
+        tx.transaction_data.parent_state_hash = Some(genesis_state.hash);
+        let state = tx.execute(Some(genesis_state.clone()));
+        ledger.push(tx, Some(state));
+
+        let report = verify(&ledger).unwrap();
+        assert_eq!(report.executed, 2);
+        assert_eq!(report.citations_checked, 1);
+        assert_eq!(report.divergence, None);
+
+        // A transaction citing a state in which alice held more than she did diverges
+        let mut forged = genesis_state.clone();
+        forged.data.balances.insert(
+            blake3::hash_slice(b"alice").to_str(),
+            BigUint::from(200 as u8),
+        );
+        let forged = Entry::new(forged.data.nonces, forged.data.balances);
+
+        let mut tx = Transaction::new(
+            1,
+            blake3::hash_slice(b"alice"),
+            blake3::hash_slice(b"bob"),
+            BigUint::from(150 as u8),
+            b"test",
+            vec![genesis],
+        );
+        tx.transaction_data.parent_state_hash = Some(forged.hash);
+        ledger.push(tx, None);
+
+        let divergence = verify(&ledger).unwrap().divergence.unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.cited_state_hash, forged.hash);
+        assert_eq!(divergence.recomputed_state_hash, genesis_state.hash);
+        assert_eq!(divergence.stored_state_hash, Some(genesis_state.hash));
+        assert!(divergence.diff.is_empty()); // The stored state agrees with the replay; the citation is at fault
+
+        assert_eq!(
+            diff_states(&forged, &genesis_state),
+            vec![AccountDiff {
+                address: blake3::hash_slice(b"alice").to_str(),
+                stored_balance: Some(BigUint::from(200 as u8)),
+                recomputed_balance: Some(BigUint::from(100 as u8)),
+                stored_nonce: None,
+                recomputed_nonce: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_verify_state() {
+        let mut ledger = test_ledger(0);
+        let genesis = ledger.nodes[0].hash;
+        let genesis_state = ledger.nodes[0].state_entry.clone().unwrap();
+
+        let tx = Transaction::new(
+            0,
+            blake3::hash_slice(b"alice"),
+            blake3::hash_slice(b"bob"),
+            BigUint::from(10 as u8),
+            b"test",
+            vec![genesis],
+        );
+        let tx_hash = tx.hash;
+        let state = tx.execute(Some(genesis_state.clone()));
+        ledger.push(tx, Some(state.clone()));
+
+        // A node stored alongside a state that its ancestors can't have produced
+        let bad = Transaction::new(
+            1,
+            blake3::hash_slice(b"alice"),
+            blake3::hash_slice(b"bob"),
+            BigUint::from(10 as u8),
+            b"test",
+            vec![tx_hash],
+        );
+        let bad_hash = bad.hash;
+        ledger.push(bad, Some(genesis_state));
+
+        // Purged states are still checked against the hashes kept for them
+        ledger.purge();
+        assert!(ledger.nodes[1].state_entry.is_none());
+
+        let proof = verify_state(&ledger, tx_hash).unwrap();
+        assert_eq!(proof.index, 1);
+        assert_eq!(proof.executed, 2);
+        assert_eq!(proof.recorded_state_hash, Some(state.hash));
+        assert!(proof.verified);
+
+        let proof = verify_state(&ledger, bad_hash).unwrap();
+        assert_eq!(proof.executed, 3);
+        assert_ne!(proof.recorded_state_hash, Some(proof.recomputed_state_hash));
+        assert!(!proof.verified);
+
+        assert!(verify_state(&ledger, blake3::hash_slice(b"nonsense")).is_err());
+    }
+}
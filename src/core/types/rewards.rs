@@ -0,0 +1,136 @@
+use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::HashMap;
+
+use super::batch::Transfer; // Import the single payment type
+use super::transaction::TransactionData; // Import the transaction type
+
+use super::super::super::{common::address::Address, crypto::blake3}; // Import the address & hashing modules
+
+/// The prefix from which the address holding an account's unclaimed rewards is derived.
+pub const ESCROW_PREFIX: &[u8] = b"rewards:";
+
+/// The payload marking a transaction as the claim of its sender's unclaimed rewards.
+pub const CLAIM_PAYLOAD: &[u8] = b"claim_rewards";
+
+/// An account of the rewards earned by a voting account.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RewardsReport {
+    /// The address of the voting account
+    pub account: Address,
+
+    /// The address holding the account's rewards until they're claimed
+    pub escrow: Address,
+
+    /// The number of finks that the account has earned, but not yet claimed
+    pub unclaimed: BigUint,
+
+    /// The number of the account's votes counted towards the current epoch's reward so far
+    pub votes_this_epoch: u64,
+}
+
+/// Gets the address that the rewards earned by the given account are held in until the account claims them. Nobody
+/// holds its key; the rewards can only ever be moved by a claim sent by the account itself.
+///
+/// # Arguments
+///
+/// * `account` - The address of the voting account
+pub fn escrow_address(account: Address) -> Address {
+    blake3::hash_slice(&[ESCROW_PREFIX, &*account].concat())
+}
+
+/// Redirects each of the given reward payments to the escrow of its recipient, so that the rewards accrue until
+/// they're claimed, rather than being paid out straight away.
+///
+/// # Arguments
+///
+/// * `transfers` - The reward paid to each participant
+pub fn accrue(transfers: Vec<Transfer>) -> Vec<Transfer> {
+    transfers
+        .into_iter()
+        .map(|transfer| Transfer {
+            recipient: escrow_address(transfer.recipient),
+            value: transfer.value,
+        })
+        .collect()
+}
+
+/// Checks whether or not the given transaction data claims its sender's unclaimed rewards. Claims are sent by an
+/// account to itself, carrying no value of their own.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction
+pub fn is_claim(tx: &TransactionData) -> bool {
+    tx.payload == CLAIM_PAYLOAD && tx.sender == tx.recipient && tx.value.is_zero()
+}
+
+/// Gets the number of finks that the given account has earned, but not yet claimed, in the given balances.
+///
+/// # Arguments
+///
+/// * `balances` - The balances of each account
+/// * `account` - The address of the voting account
+pub fn unclaimed(balances: &HashMap<String, BigUint>, account: Address) -> BigUint {
+    balances
+        .get(&escrow_address(account).to_str())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Moves each of the finks held in the sender's escrow into its balance, if the given transaction data is a claim.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction being executed
+/// * `balances` - The balances of each account, which should be updated
+pub fn execute_claim(tx: &TransactionData, balances: &mut HashMap<String, BigUint>) {
+    if !is_claim(tx) {
+        return;
+    }
+
+    // Empty the escrow entirely, so that it doesn't clutter the state
+    if let Some(rewards) = balances.remove(&escrow_address(tx.sender).to_str()) {
+        *balances
+            .entry(tx.sender.to_str())
+            .or_insert_with(BigUint::zero) += rewards;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::transaction::Transaction;
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_execute_claim() {
+        let alice = blake3::hash_slice(b"alice");
+        let bob = blake3::hash_slice(b"bob");
+
+        // Rewards accrue in the escrow, rather than the account itself
+        let transfers = accrue(vec![Transfer {
+            recipient: alice,
+            value: BigUint::from(10 as u8),
+        }]);
+        assert_eq!(transfers[0].recipient, escrow_address(alice));
+        assert_ne!(escrow_address(alice), escrow_address(bob));
+
+        let mut balances = HashMap::new();
+        balances.insert(alice.to_str(), BigUint::from(5 as u8));
+        balances.insert(escrow_address(alice).to_str(), BigUint::from(10 as u8));
+        assert_eq!(unclaimed(&balances, alice), BigUint::from(10 as u8));
+
+        // Nobody but the account itself can claim its rewards
+        let not_a_claim = Transaction::new(0, bob, alice, BigUint::zero(), CLAIM_PAYLOAD, vec![]);
+        execute_claim(&not_a_claim.transaction_data, &mut balances);
+        assert_eq!(unclaimed(&balances, alice), BigUint::from(10 as u8));
+
+        let claim = Transaction::new(0, alice, alice, BigUint::zero(), CLAIM_PAYLOAD, vec![]);
+        execute_claim(&claim.transaction_data, &mut balances);
+        assert_eq!(balances[&alice.to_str()], BigUint::from(15 as u8));
+        assert!(unclaimed(&balances, alice).is_zero());
+        assert!(!balances.contains_key(&escrow_address(alice).to_str()));
+    }
+}
@@ -5,7 +5,7 @@ use super::transaction::Transaction;
 use serde::{Deserialize, Serialize}; // Import serde serialization
 
 /// An edwards25519 signature.
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Signature {
     /// The public key corresponding to a transaction sender address
     pub(crate) public_key_bytes: Vec<u8>,
@@ -15,6 +15,19 @@ pub struct Signature {
 
 /// Implement a set of signature helper methods.
 impl Signature {
+    /// Signs the given message with the given keypair.
+    ///
+    /// # Arguments
+    ///
+    /// * `keypair` - The keypair that the message should be signed with
+    /// * `message` - The message that should be signed
+    pub fn new(keypair: &ed25519_dalek::Keypair, message: &[u8]) -> Signature {
+        Signature {
+            public_key_bytes: bincode::serialize(&keypair.public).unwrap_or_default(),
+            signature_bytes: bincode::serialize(&keypair.sign(message)).unwrap_or_default(),
+        }
+    }
+
     /// Verify the signature (self).
     pub fn verify(&self, message: &[u8]) -> bool {
         // Get the signature's public key
@@ -38,8 +51,16 @@ impl Signature {
     pub fn verify_tx(&self, transaction: &Transaction) -> bool {
         // Get the public key of the sender of the transaction
         if let Ok(sender_kp) = self.public_key() {
-            // Make sure that the sender has the same keypair as that in the tx
-            transaction.transaction_data.sender == Address::from_public_key(&sender_kp)
+            let signer = Address::from_public_key(&sender_kp); // Get the address of the signer
+
+            // Make sure that the sender has the same keypair as that in the tx, or that the sender
+            // is a sub-address derived from the signer's address
+            (transaction.transaction_data.sender == signer
+                || transaction
+                    .transaction_data
+                    .sender
+                    .is_sub_address_of(&signer)
+                    .is_some())
                 && self.verify(&*transaction.hash)
         } else {
             // If the sender doesn't have a keypair, the tx can't be valid
@@ -0,0 +1,453 @@
+use num::bigint::BigUint; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::genesis::RoleAssignment; // Import the genesis role assignments
+use super::graph::MINTS_TREE; // Import the name of the ledger-derived mints tree
+use super::state::Entry; // Import the state entry type
+use super::transaction::Transaction; // Import the transaction type
+
+use super::super::super::crypto::{blake3, hash::Hash}; // Import the hashing modules
+
+/// The name of the tree in which the graph's latest snapshot is kept.
+pub const SNAPSHOTS_TREE: &str = "snapshots";
+
+/// The metadata key under which the id of the graph's latest snapshot is kept.
+pub const LATEST_SNAPSHOT_META: &str = "latest_snapshot";
+
+/// The metadata key under which the number of transactions preceding the graph's first node is kept, for graphs
+/// installed from a snapshot.
+pub const BASE_HEIGHT_META: &str = "base_height";
+
+/// The metadata key under which the genesis described by the snapshot that a graph was installed from is kept.
+pub const SNAPSHOT_GENESIS_META: &str = "snapshot_genesis";
+
+/// The number of transactions added to the ledger between snapshots, by default.
+pub const DEFAULT_SNAPSHOT_INTERVAL: u64 = 4096;
+
+/// The most bytes of an encoded snapshot published in a single DHT record. Records are capped well above this, but
+/// the envelope that they're sealed in needs some room of its own.
+pub const SNAPSHOT_CHUNK_SIZE: usize = 32 * 1024;
+
+/// The largest number of chunks that a snapshot may be split into, so that a manifest can't have a node set aside
+/// room for (or ask the network for) an endless download.
+pub const MAX_SNAPSHOT_CHUNKS: usize = 8192;
+
+/// The trees derived from the ledger's history that a snapshot carries along with the state, since they can't be
+/// rebuilt without the transactions preceding it.
+pub const SNAPSHOT_TREES: [&str; 1] = [MINTS_TREE];
+
+/// An error encountered while building, verifying, or installing a snapshot.
+#[derive(Debug, Fail, PartialEq)]
+pub enum SnapshotError {
+    #[fail(display = "the snapshot could not be decoded: {}", error)]
+    Malformed {
+        error: String, // The underlying error
+    },
+    #[fail(display = "the snapshot has id {}, not {}", found, expected)]
+    MismatchedId {
+        expected: String, // The id that the snapshot was published under
+        found: String,    // The id of the snapshot's contents
+    },
+    #[fail(display = "the snapshot's state doesn't hash to {}", state_hash)]
+    MismatchedState {
+        state_hash: String, // The hash that the snapshot claims for its state
+    },
+    #[fail(display = "the snapshot's anchor transaction doesn't hash to {}", hash)]
+    MismatchedTransaction {
+        hash: String, // The hash that the snapshot claims for its anchor
+    },
+    #[fail(
+        display = "the snapshot carries tree {}, which isn't derived from the ledger",
+        name
+    )]
+    UnknownTree {
+        name: String, // The name of the tree
+    },
+    #[fail(
+        display = "the snapshot is split into {} chunks, more than the {} allowed",
+        chunks, MAX_SNAPSHOT_CHUNKS
+    )]
+    TooLarge {
+        chunks: usize, // The number of chunks that the snapshot is split into
+    },
+    #[fail(display = "chunk {} of the snapshot was not asked for", hash)]
+    UnexpectedChunk {
+        hash: String, // The hash of the chunk
+    },
+    #[fail(display = "a snapshot can only be installed in an empty ledger")]
+    NotEmpty,
+    #[fail(display = "failed to access the snapshot store: {}", error)]
+    StorageFailure {
+        error: String, // The underlying error
+    },
+}
+
+impl From<sled::Error> for SnapshotError {
+    /// Converts the given sled error into a SnapshotError.
+    fn from(e: sled::Error) -> Self {
+        Self::StorageFailure {
+            error: e.to_string(),
+        }
+    }
+}
+
+/// The contents of one of the trees carried in a snapshot.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SnapshotTree {
+    /// The name of the tree
+    pub name: String,
+
+    /// Each of the tree's keys & values, ordered by key
+    pub entries: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+/// The genesis of the ledger that a snapshot was taken from, which can't be recovered from the transactions after it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct SnapshotGenesis {
+    /// The hash of the genesis transaction
+    pub hash: Hash,
+
+    /// The number of finks issued in the genesis
+    pub issuance: BigUint,
+
+    /// Each of the accounts designated a role in the genesis
+    pub roles: Vec<RoleAssignment>,
+}
+
+/// The state of the ledger as of its executed head, from which a node can pick up without replaying the transactions
+/// before it. Snapshots are content-addressed: their id is derived from everything they vouch for.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Snapshot {
+    /// The number of transactions in the ledger, up to & including the anchor
+    pub height: u64,
+
+    /// The transaction that the snapshot was taken at
+    pub transaction: Transaction,
+
+    /// The balances & nonces of each account after the execution of the anchor
+    pub state: Entry,
+
+    /// The genesis of the ledger that the snapshot was taken from
+    pub genesis: SnapshotGenesis,
+
+    /// The contents of each of the ledger-derived trees
+    pub trees: Vec<SnapshotTree>,
+}
+
+/// A description of a snapshot, and of the chunks that it was split into to be published.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SnapshotManifest {
+    /// The id of the snapshot
+    pub id: Hash,
+
+    /// The number of transactions in the ledger, up to & including the snapshot's anchor
+    pub height: u64,
+
+    /// The hash of the snapshot's anchor transaction
+    pub anchor: Hash,
+
+    /// The hash of each of the snapshot's chunks, in order
+    pub chunks: Vec<Hash>,
+}
+
+/// A snapshot being downloaded a chunk at a time.
+#[derive(Clone, Debug)]
+pub struct SnapshotAssembly {
+    /// The snapshot being downloaded
+    pub manifest: SnapshotManifest,
+
+    /// Each of the chunks received so far, in the order in which they belong
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+/// Implement a set of snapshot helper methods.
+impl Snapshot {
+    /// Gets the id of the snapshot: the hash of its height, anchor, state, genesis, and trees.
+    pub fn id(&self) -> Hash {
+        blake3::hash_slice(
+            &bincode::serialize(&(
+                self.height,
+                self.transaction.hash,
+                self.state.hash,
+                &self.genesis,
+                &self.trees,
+            ))
+            .unwrap_or_default(),
+        )
+    }
+
+    /// Makes sure that the snapshot is the one with the given id, and that its state & anchor are what they claim to
+    /// be.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id that the snapshot was published under
+    pub fn verify(&self, id: Hash) -> Result<(), SnapshotError> {
+        let found = self.id();
+        if found != id {
+            return Err(SnapshotError::MismatchedId {
+                expected: id.to_str(),
+                found: found.to_str(),
+            });
+        }
+
+        // The id only covers the hash of the state, so the state itself has to be checked against it
+        if self.state.data.hash() != self.state.hash {
+            return Err(SnapshotError::MismatchedState {
+                state_hash: self.state.hash.to_str(),
+            });
+        }
+
+        let tx_hash = self.transaction.transaction_data.hash();
+        if tx_hash != self.transaction.hash {
+            return Err(SnapshotError::MismatchedTransaction {
+                hash: self.transaction.hash.to_str(),
+            });
+        }
+
+        self.check_trees()
+    }
+
+    /// Makes sure that each of the trees carried by the snapshot is one derived from the ledger, so that installing
+    /// the snapshot can't overwrite any of the node's other trees.
+    pub fn check_trees(&self) -> Result<(), SnapshotError> {
+        match self
+            .trees
+            .iter()
+            .find(|tree| !SNAPSHOT_TREES.contains(&tree.name.as_str()))
+        {
+            Some(tree) => Err(SnapshotError::UnknownTree {
+                name: tree.name.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Describes the snapshot, splitting it into chunks small enough to be published on their own. Results in the
+    /// manifest, and each of the chunks that it lists.
+    pub fn chunk(&self) -> (SnapshotManifest, Vec<Vec<u8>>) {
+        let chunks: Vec<Vec<u8>> = self
+            .to_bytes()
+            .chunks(SNAPSHOT_CHUNK_SIZE)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+
+        (
+            SnapshotManifest {
+                id: self.id(),
+                height: self.height,
+                anchor: self.transaction.hash,
+                chunks: chunks
+                    .iter()
+                    .map(|chunk| blake3::hash_slice(chunk))
+                    .collect(),
+            },
+            chunks,
+        )
+    }
+
+    /// Serializes the snapshot into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap_or_default()
+    }
+
+    /// Deserializes a snapshot from the given bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `b` - The encoded snapshot
+    pub fn from_bytes(b: &[u8]) -> Result<Self, SnapshotError> {
+        bincode::deserialize(b).map_err(|e| SnapshotError::Malformed {
+            error: e.to_string(),
+        })
+    }
+}
+
+/// Implement a set of snapshot manifest helper methods.
+impl SnapshotManifest {
+    /// Makes sure that the snapshot isn't split into more than MAX_SNAPSHOT_CHUNKS chunks.
+    pub fn check_size(&self) -> Result<(), SnapshotError> {
+        if self.chunks.len() > MAX_SNAPSHOT_CHUNKS {
+            return Err(SnapshotError::TooLarge {
+                chunks: self.chunks.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Serializes the manifest into a byte vector.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap_or_default()
+    }
+
+    /// Deserializes a manifest from the given bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `b` - The encoded manifest
+    pub fn from_bytes(b: &[u8]) -> Result<Self, SnapshotError> {
+        bincode::deserialize(b).map_err(|e| SnapshotError::Malformed {
+            error: e.to_string(),
+        })
+    }
+}
+
+/// Implement a set of snapshot assembly helper methods.
+impl SnapshotAssembly {
+    /// Starts downloading the snapshot described by the given manifest.
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest` - The manifest of the snapshot
+    pub fn new(manifest: SnapshotManifest) -> Self {
+        let chunks = vec![None; manifest.chunks.len()];
+
+        Self { manifest, chunks }
+    }
+
+    /// Puts the given chunk in its place. Results in whether or not the chunk hadn't been received yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk` - The contents of the chunk
+    pub fn insert(&mut self, chunk: Vec<u8>) -> Result<bool, SnapshotError> {
+        let hash = blake3::hash_slice(&chunk);
+
+        let mut inserted = false;
+        for (i, expected) in self.manifest.chunks.iter().enumerate() {
+            // The same chunk may appear more than once in a snapshot
+            if *expected == hash && self.chunks[i].is_none() {
+                self.chunks[i] = Some(chunk.clone());
+                inserted = true;
+            }
+        }
+
+        if !inserted && !self.manifest.chunks.contains(&hash) {
+            return Err(SnapshotError::UnexpectedChunk {
+                hash: hash.to_str(),
+            });
+        }
+
+        Ok(inserted)
+    }
+
+    /// Gets the hash of each of the chunks that haven't been received yet.
+    pub fn missing(&self) -> Vec<Hash> {
+        self.manifest
+            .chunks
+            .iter()
+            .zip(self.chunks.iter())
+            .filter(|(_, chunk)| chunk.is_none())
+            .map(|(hash, _)| *hash)
+            .collect()
+    }
+
+    /// Checks whether or not each of the snapshot's chunks has been received.
+    pub fn is_complete(&self) -> bool {
+        self.chunks.iter().all(Option::is_some)
+    }
+
+    /// Puts the snapshot back together from its chunks, and makes sure that it's the snapshot that the manifest
+    /// describes.
+    pub fn assemble(&self) -> Result<Snapshot, SnapshotError> {
+        let encoded: Vec<u8> = self.chunks.iter().flatten().flatten().copied().collect();
+
+        let snapshot = Snapshot::from_bytes(&encoded)?;
+        snapshot.verify(self.manifest.id)?;
+
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    use num::Zero;
+    use std::collections::HashMap;
+
+    /// Builds a snapshot of a ledger holding the given number of transactions, anchored at a transaction carrying the
+    /// given payload.
+    fn snapshot(height: u64, payload: &[u8]) -> Snapshot {
+        let (alice, bob) = (blake3::hash_slice(b"alice"), blake3::hash_slice(b"bob"));
+
+        let mut balances = HashMap::new();
+        balances.insert(alice.to_str(), BigUint::from(100 as u8));
+        let mut nonces = HashMap::new();
+        nonces.insert(alice.to_str(), 3);
+
+        Snapshot {
+            height,
+            transaction: Transaction::new(3, alice, bob, BigUint::zero(), payload, vec![]),
+            state: Entry::new(nonces, balances),
+            genesis: SnapshotGenesis::default(),
+            trees: vec![SnapshotTree {
+                name: MINTS_TREE.to_owned(),
+                entries: vec![(b"0".to_vec(), b"mint".to_vec())],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        // Snapshots are content-addressed
+        let taken = snapshot(4, b"memo");
+        assert_eq!(taken.id(), snapshot(4, b"memo").id());
+        assert_ne!(taken.id(), snapshot(5, b"memo").id());
+        assert_eq!(taken.verify(taken.id()), Ok(()));
+
+        // Tampering with the state is caught, even though only its hash is covered by the id
+        let mut tampered = taken.clone();
+        tampered.state.data.balances.insert(
+            blake3::hash_slice(b"mallory").to_str(),
+            BigUint::from(1 as u8),
+        );
+        assert_eq!(
+            tampered.verify(taken.id()),
+            Err(SnapshotError::MismatchedState {
+                state_hash: taken.state.hash.to_str()
+            })
+        );
+
+        // Snapshots can only carry the trees derived from the ledger
+        let mut intrusive = taken.clone();
+        intrusive.trees.push(SnapshotTree {
+            name: "votes".to_owned(),
+            entries: vec![],
+        });
+        assert_eq!(
+            intrusive.verify(intrusive.id()),
+            Err(SnapshotError::UnknownTree {
+                name: "votes".to_owned()
+            })
+        );
+
+        // A snapshot too large for a single record is put back together from its chunks, in any order
+        let large = snapshot(4, &vec![7; SNAPSHOT_CHUNK_SIZE * 2]);
+        let (manifest, chunks) = large.chunk();
+        assert_eq!(manifest.chunks.len(), 3);
+        assert_eq!(
+            SnapshotManifest::from_bytes(&manifest.to_bytes()),
+            Ok(manifest.clone())
+        );
+
+        assert_eq!(manifest.check_size(), Ok(()));
+        let mut oversized = manifest.clone();
+        oversized.chunks = vec![Hash::default(); MAX_SNAPSHOT_CHUNKS + 1];
+        assert!(oversized.check_size().is_err());
+
+        let mut assembly = SnapshotAssembly::new(manifest);
+        for chunk in chunks.iter().rev() {
+            assert!(!assembly.is_complete());
+            assert_eq!(assembly.insert(chunk.clone()), Ok(true));
+        }
+        assert_eq!(assembly.insert(chunks[0].clone()), Ok(false));
+        assert!(assembly.missing().is_empty());
+        assert_eq!(assembly.assemble().unwrap().id(), large.id());
+
+        // Chunks that the manifest doesn't list are refused
+        assert!(assembly.insert(b"junk".to_vec()).is_err());
+    }
+}
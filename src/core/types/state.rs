@@ -1,11 +1,23 @@
 use std::collections; // Import the stdlib collections library
 
+use chrono::{DateTime, Utc}; // Import time library
+
 use serde::{Deserialize, Serialize}; // Import serde serialization
 
 use super::super::super::{crypto::blake3, crypto::hash}; // Import the hash modules
 
+use super::guardians::GuardianRecord; // Import the account recovery guardians
+use super::vesting::VestingSchedule; // Import the time-locked balance types
+
 use num::bigint::BigUint; // Add support for large unsigned integers
 
+/// The byte prefixed to each leaf of the merkle tree of a state's balances, so that a leaf can't pass for one of the
+/// nodes above it.
+const BALANCE_LEAF_PREFIX: u8 = 0;
+
+/// The byte prefixed to each interior node of the merkle tree of a state's balances.
+const BALANCE_NODE_PREFIX: u8 = 1;
+
 /// The state at a particular point in time.
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Entry {
@@ -23,6 +35,73 @@ pub struct EntryData {
 
     /// The last recorded index of each account
     pub nonces: collections::HashMap<String, u64>,
+
+    /// The guardians registered by each account, along with any recovery in progress
+    #[serde(default)]
+    pub guardians: collections::HashMap<String, GuardianRecord>,
+
+    /// The funds locked in each account
+    #[serde(default)]
+    pub locks: collections::HashMap<String, VestingSchedule>,
+
+    /// The number of transactions along the longest chain of parents leading up to the state, including its own
+    #[serde(default)]
+    pub height: u64,
+
+    /// The latest timestamp of any of the transactions leading up to the state
+    #[serde(default)]
+    pub time: Option<DateTime<Utc>>,
+}
+
+/// Which side of its parent a sibling in a balance proof sits on.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    /// The sibling is hashed before the node on the path
+    Left,
+
+    /// The sibling is hashed after the node on the path
+    Right,
+}
+
+/// A sibling of one of the nodes on the path from an account's leaf up to the root of a balance tree.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProofStep {
+    /// Which side of the parent the sibling sits on
+    pub side: Side,
+
+    /// The hash of the sibling
+    pub hash: hash::Hash,
+}
+
+/// A proof that an account held a particular balance in a state: the account's leaf in the merkle tree of the state's
+/// balances, along with the siblings needed to hash the leaf up to the tree's root.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BalanceProof {
+    /// The address of the account, as the state keys it
+    pub address: String,
+
+    /// The balance held by the account
+    pub balance: BigUint,
+
+    /// The sibling of each node on the path from the account's leaf up to the root, from the leaf up. Nodes without a
+    /// sibling are carried up as-is, and have no step.
+    pub path: Vec<ProofStep>,
+}
+
+/// Implement a set of balance proof helper methods.
+impl BalanceProof {
+    /// Computes the root of the balance tree that the proof leads up to. The proof holds if this is the balance root
+    /// of the state that it's checked against.
+    pub fn root(&self) -> hash::Hash {
+        self.path.iter().fold(
+            balance_leaf(&self.address, &self.balance),
+            |node, step| match step.side {
+                Side::Left => balance_node(&step.hash, &node),
+                Side::Right => balance_node(&node, &step.hash),
+            },
+        )
+    }
 }
 
 /// Implement a set of state entry serialization helper methods.
@@ -31,6 +110,42 @@ impl EntryData {
     pub fn to_bytes(&self) -> Vec<u8> {
         serde_json::to_vec(self).unwrap() // Serialize
     }
+
+    /// Produces a reproducible hash of the state. States in which no account has registered any guardians or locked
+    /// any funds hash the same as they did before either were kept in the state. The height & time of a state follow
+    /// from the transactions leading up to it, so they're only hashed alongside the locks that they're needed for.
+    pub fn hash(&self) -> hash::Hash {
+        // Sort each of the nonces and balances by address, so that the hash of the state doesn't depend on the order
+        // in which the maps happen to be iterated
+        let mut sorted_nonces: Vec<(&String, &u64)> = self.nonces.iter().collect();
+        sorted_nonces.sort();
+        let mut sorted_balances: Vec<(&String, &BigUint)> = self.balances.iter().collect();
+        sorted_balances.sort();
+
+        let mut parts = vec![
+            bincode::serialize(&sorted_nonces).unwrap_or_default(),
+            bincode::serialize(&sorted_balances).unwrap_or_default(),
+        ];
+
+        if !self.guardians.is_empty() || !self.locks.is_empty() {
+            let mut sorted_guardians: Vec<(&String, &GuardianRecord)> =
+                self.guardians.iter().collect();
+            sorted_guardians.sort_by(|a, b| a.0.cmp(b.0));
+
+            parts.push(bincode::serialize(&sorted_guardians).unwrap_or_default());
+        }
+
+        if !self.locks.is_empty() {
+            let mut sorted_locks: Vec<(&String, &VestingSchedule)> = self.locks.iter().collect();
+            sorted_locks.sort_by(|a, b| a.0.cmp(b.0));
+
+            parts.push(
+                bincode::serialize(&(sorted_locks, self.height, self.time)).unwrap_or_default(),
+            );
+        }
+
+        blake3::hash_slice(&bincode::serialize(&parts).unwrap_or_default())
+    }
 }
 
 /// Implement a set of state helper methods.
@@ -40,37 +155,140 @@ impl Entry {
         nonces: collections::HashMap<String, u64>,
         balances: collections::HashMap<String, BigUint>,
     ) -> Entry {
-        // Produce a reproducible hash of the state
-        let state_hash = blake3::hash_slice(
-            &bincode::serialize(&vec![
-                bincode::serialize(&nonces.keys().collect::<Vec<&String>>().sort())
-                    .unwrap_or_default(),
-                bincode::serialize(&nonces.values().collect::<Vec<&u64>>().sort())
-                    .unwrap_or_default(),
-                bincode::serialize(&balances.keys().collect::<Vec<&String>>().sort())
-                    .unwrap_or_default(),
-                bincode::serialize(&balances.values().collect::<Vec<&BigUint>>().sort())
-                    .unwrap_or_default(),
-            ])
-            .unwrap_or_default(),
-        );
-
-        let entry_data: EntryData = EntryData {
+        Self::from_data(EntryData {
             balances, // Set balances
             nonces,   // Set nonces
-        }; // Initialize entry data
+            ..Default::default()
+        })
+    }
 
+    /// Initialize a new Entry instance around the given state, hashing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The body of the state entry
+    pub fn from_data(data: EntryData) -> Entry {
         Entry {
-            data: entry_data, // Set data
-            hash: state_hash, // Set hash
+            hash: data.hash(), // Set hash
+            data,              // Set data
+        }
+    }
+
+    /// Computes the root of the merkle tree of the state's balances, ordered by address, so that a single account's
+    /// balance can be checked without the rest of the state. An empty state has a root of all zeroes.
+    pub fn balance_root(&self) -> hash::Hash {
+        self.balance_levels()
+            .last()
+            .and_then(|level| level.first())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Proves the balance held by an account in the state against the state's balance root. Results in None if the
+    /// state doesn't hold a balance for the account.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the account, as the state keys it
+    pub fn prove_balance(&self, address: &str) -> Option<BalanceProof> {
+        let balance = self.data.balances.get(address)?.clone();
+
+        let mut addresses: Vec<&String> = self.data.balances.keys().collect();
+        addresses.sort();
+        let mut index = addresses
+            .binary_search_by(|candidate| candidate.as_str().cmp(address))
+            .ok()?;
+
+        let levels = self.balance_levels();
+        let mut path = Vec::new();
+        for level in &levels[..levels.len() - 1] {
+            let sibling = index ^ 1;
+
+            // The last node of a level with an odd number of nodes is carried up as-is
+            if let Some(hash) = level.get(sibling) {
+                path.push(ProofStep {
+                    side: if sibling < index {
+                        Side::Left
+                    } else {
+                        Side::Right
+                    },
+                    hash: *hash,
+                });
+            }
+
+            index /= 2;
         }
+
+        Some(BalanceProof {
+            address: address.to_owned(),
+            balance,
+            path,
+        })
+    }
+
+    /// Hashes each level of the merkle tree of the state's balances, from the leaves (ordered by address) up to the
+    /// root. An empty state has no levels.
+    fn balance_levels(&self) -> Vec<Vec<hash::Hash>> {
+        let mut sorted_balances: Vec<(&String, &BigUint)> = self.data.balances.iter().collect();
+        sorted_balances.sort();
+
+        let mut levels = vec![sorted_balances
+            .into_iter()
+            .map(|(address, balance)| balance_leaf(address, balance))
+            .collect::<Vec<hash::Hash>>()];
+
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => balance_node(left, right),
+                    _ => pair[0],
+                })
+                .collect();
+
+            levels.push(next);
+        }
+
+        levels.retain(|level| !level.is_empty());
+
+        levels
     }
 }
 
+/// Hashes the leaf of a balance tree holding the given account's balance.
+///
+/// # Arguments
+///
+/// * `address` - The address of the account
+/// * `balance` - The balance held by the account
+fn balance_leaf(address: &str, balance: &BigUint) -> hash::Hash {
+    let mut leaf = vec![BALANCE_LEAF_PREFIX];
+    leaf.extend(bincode::serialize(&(address, balance)).unwrap_or_default());
+
+    blake3::hash_slice(&leaf)
+}
+
+/// Hashes the parent of the given two nodes of a balance tree.
+///
+/// # Arguments
+///
+/// * `left` - The hash of the left child
+/// * `right` - The hash of the right child
+fn balance_node(left: &hash::Hash, right: &hash::Hash) -> hash::Hash {
+    let mut node = vec![BALANCE_NODE_PREFIX];
+    node.extend_from_slice(left.as_ref());
+    node.extend_from_slice(right.as_ref());
+
+    blake3::hash_slice(&node)
+}
+
 /// Merge multiple state entires into one batch state entry.
 pub fn merge_entries(entries: Vec<Entry>) -> Entry {
     let mut balances: collections::HashMap<String, BigUint> = collections::HashMap::new(); // Initialize balances map
     let mut nonces: collections::HashMap<String, u64> = collections::HashMap::new(); // Initialize a collections map
+    let mut guardians: collections::HashMap<String, GuardianRecord> = collections::HashMap::new();
+    let mut locks: collections::HashMap<String, VestingSchedule> = collections::HashMap::new();
+    let (mut height, mut time) = (0, None);
 
     for entry in entries {
         // Iterate through entries
@@ -95,9 +313,24 @@ pub fn merge_entries(entries: Vec<Entry>) -> Entry {
                 nonces.insert(nonce_addr.clone(), *nonce);
             }
         }
+
+        // Later entries' guardian records & locks take precedence
+        guardians.extend(entry.data.guardians);
+        locks.extend(entry.data.locks);
+
+        // The merged state is as deep, and as late, as the deepest & latest of the entries
+        height = height.max(entry.data.height);
+        time = time.max(entry.data.time);
     }
 
-    Entry::new(nonces, balances) // Return initialized state entry
+    Entry::from_data(EntryData {
+        balances,
+        nonces,
+        guardians,
+        locks,
+        height,
+        time,
+    }) // Return initialized state entry
 }
 
 #[cfg(test)]
@@ -105,6 +338,8 @@ mod tests {
     use super::*; // Import names from parent module
 
     use super::super::super::super::common::address; // Import the hash & address modules
+    use super::super::guardians::GuardianSet; // Import the guardian set type
+    use super::super::vesting::{TimeLock, Unlock}; // Import the time lock types
 
     use crate::num::FromPrimitive; // Let the bigint library implement from_i64
 
@@ -129,4 +364,88 @@ mod tests {
             BigUint::from_i64(1).unwrap()
         ); // Ensure balance entry correctly written to state entry
     }
+
+    #[test]
+    pub fn test_hash() {
+        let mut balances: collections::HashMap<String, BigUint> = collections::HashMap::new(); // Initialize balances hash map
+        balances.insert("a".to_owned(), BigUint::from_i64(1).unwrap()); // Balance of 1 fink
+        balances.insert("b".to_owned(), BigUint::from_i64(2).unwrap()); // Balance of 2 finks
+
+        let entry = Entry::new(collections::HashMap::new(), balances.clone()); // Initialize state entry
+
+        assert_eq!(
+            entry.hash,
+            Entry::new(collections::HashMap::new(), balances.clone()).hash
+        ); // Ensure the hash is reproducible
+
+        balances.insert("b".to_owned(), BigUint::from_i64(3).unwrap()); // Change one of the balances
+
+        assert_ne!(
+            entry.hash,
+            Entry::new(collections::HashMap::new(), balances).hash
+        ); // Ensure the hash reflects the contents of the state
+
+        // Registering guardians changes the hash of the state
+        let mut data = entry.data.clone();
+        data.guardians.insert(
+            "a".to_owned(),
+            GuardianRecord {
+                set: GuardianSet {
+                    guardians: vec![address::Address::default()],
+                    threshold: 1,
+                    timelock_secs: 0,
+                },
+                recovery: None,
+            },
+        );
+        assert_ne!(Entry::from_data(data).hash, entry.hash);
+        assert_eq!(Entry::from_data(entry.data.clone()).hash, entry.hash);
+
+        // So does locking funds, but the height of a state without any locks isn't hashed
+        let mut data = entry.data.clone();
+        data.height = 42;
+        assert_eq!(Entry::from_data(data.clone()).hash, entry.hash);
+        data.locks.insert(
+            "a".to_owned(),
+            VestingSchedule {
+                locks: vec![TimeLock {
+                    amount: BigUint::from_i64(1).unwrap(),
+                    unlock: Unlock::Milestone(100),
+                }],
+            },
+        );
+        assert_ne!(Entry::from_data(data).hash, entry.hash);
+    }
+
+    #[test]
+    pub fn test_prove_balance() {
+        let mut balances: collections::HashMap<String, BigUint> = collections::HashMap::new(); // Initialize balances hash map
+        for (i, address) in ["a", "b", "c", "d", "e"].iter().enumerate() {
+            balances.insert((*address).to_owned(), BigUint::from_usize(i + 1).unwrap());
+        }
+
+        let entry = Entry::new(collections::HashMap::new(), balances.clone()); // Initialize state entry
+        let root = entry.balance_root();
+
+        // Each balance, including the one carried up without a sibling, proves out against the root
+        for address in balances.keys() {
+            let proof = entry.prove_balance(address).unwrap();
+            assert_eq!(proof.balance, balances[address]);
+            assert_eq!(proof.root(), root);
+        }
+
+        // A proof claiming a different balance doesn't, and accounts without a balance can't be proven
+        let mut forged = entry.prove_balance("c").unwrap();
+        forged.balance = BigUint::from_i64(1000).unwrap();
+        assert_ne!(forged.root(), root);
+        assert!(entry.prove_balance("z").is_none());
+
+        // The root reflects the balances of the state
+        balances.insert("e".to_owned(), BigUint::from_i64(6).unwrap());
+        assert_ne!(
+            Entry::new(collections::HashMap::new(), balances).balance_root(),
+            root
+        );
+        assert_eq!(Entry::default().balance_root(), hash::Hash::default());
+    }
 }
@@ -0,0 +1,261 @@
+use num::{BigUint, Zero}; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    str::FromStr,
+}; // Import the map & set types
+
+use super::{
+    super::{super::crypto::hash::Hash, sys::simulation::BalanceChange},
+    receipt::ExecutionStatus,
+}; // Import the receipt & hash types
+
+/// How much detail an execution trace records.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceLevel {
+    /// Only the steps that changed a balance or nonce
+    Minimal,
+
+    /// Every step taken while executing the transaction, whether or not it changed anything
+    Steps,
+
+    /// Every step, along with the balances that each step changed
+    Full,
+}
+
+impl Default for TraceLevel {
+    /// Traces every step, without the balances.
+    fn default() -> Self {
+        Self::Steps
+    }
+}
+
+impl FromStr for TraceLevel {
+    type Err = failure::Error;
+
+    /// Parses a trace level from one of minimal, steps, or full.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minimal" => Ok(Self::Minimal),
+            "steps" => Ok(Self::Steps),
+            "full" => Ok(Self::Full),
+            _ => Err(failure::err_msg(format!(
+                "unknown trace level: {} (expected one of minimal, steps, full)",
+                s
+            ))),
+        }
+    }
+}
+
+/// A single step taken while executing a transaction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TraceStep {
+    /// The position of the step in the execution
+    pub index: usize,
+
+    /// The operation carried out by the step (e.g. transfer, batch, mint)
+    pub op: String,
+
+    /// Whether or not the step changed any balance or nonce
+    pub changed_state: bool,
+
+    /// The balances that the step changed, if the trace is full
+    pub balance_changes: Vec<BalanceChange>,
+}
+
+/// A record of each of the steps taken while executing a transaction on top of its parent state.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExecutionTrace {
+    /// The hash of the transaction
+    pub tx: Hash,
+
+    /// The detail that the trace was recorded at
+    pub level: TraceLevel,
+
+    /// The hash of the state that the transaction was executed on top of
+    pub parent_state_hash: Hash,
+
+    /// The outcome of the transaction
+    pub status: ExecutionStatus,
+
+    /// The steps taken while executing the transaction
+    pub steps: Vec<TraceStep>,
+
+    /// The hash of the state that executing the transaction resulted in
+    pub state_hash: Hash,
+}
+
+/// Records each step of a transaction's execution. A disabled tracer records nothing, and costs next to nothing.
+pub struct Tracer {
+    /// The detail to record at, or None if the tracer is disabled
+    level: Option<TraceLevel>,
+
+    /// The steps recorded so far
+    steps: Vec<TraceStep>,
+
+    /// The number of steps taken so far, including any that weren't recorded
+    taken: usize,
+
+    /// The balances & nonces as they stood after the last recorded step
+    last: (HashMap<String, BigUint>, HashMap<String, u64>),
+}
+
+/// Implement a set of tracer helper methods.
+impl Tracer {
+    /// Initializes a new tracer recording at the given level.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - How much detail to record
+    pub fn new(level: TraceLevel) -> Self {
+        Self {
+            level: Some(level),
+            steps: Vec::new(),
+            taken: 0,
+            last: (HashMap::new(), HashMap::new()),
+        }
+    }
+
+    /// Initializes a new tracer that records nothing.
+    pub fn disabled() -> Self {
+        Self {
+            level: None,
+            steps: Vec::new(),
+            taken: 0,
+            last: (HashMap::new(), HashMap::new()),
+        }
+    }
+
+    /// Remembers the balances & nonces that execution begins with, so that the first step can be compared to them.
+    ///
+    /// # Arguments
+    ///
+    /// * `balances` - The balances before execution
+    /// * `nonces` - The nonces before execution
+    pub fn begin(&mut self, balances: &HashMap<String, BigUint>, nonces: &HashMap<String, u64>) {
+        if self.level.is_some() {
+            self.last = (balances.clone(), nonces.clone());
+        }
+    }
+
+    /// Records a step of the execution, comparing the balances & nonces that it left behind to those left by the last
+    /// step.
+    ///
+    /// # Arguments
+    ///
+    /// * `op` - The operation carried out by the step
+    /// * `balances` - The balances after the step
+    /// * `nonces` - The nonces after the step
+    pub fn record(
+        &mut self,
+        op: &str,
+        balances: &HashMap<String, BigUint>,
+        nonces: &HashMap<String, u64>,
+    ) {
+        let level = match self.level {
+            Some(level) => level,
+            None => return,
+        };
+
+        let balance_changes = diff_balance_maps(&self.last.0, balances);
+        let changed_state = !balance_changes.is_empty() || self.last.1 != *nonces;
+        self.taken += 1;
+
+        if changed_state || level >= TraceLevel::Steps {
+            self.steps.push(TraceStep {
+                index: self.taken - 1,
+                op: op.to_owned(),
+                changed_state,
+                balance_changes: if level == TraceLevel::Full {
+                    balance_changes
+                } else {
+                    Vec::new()
+                },
+            });
+        }
+
+        self.last = (balances.clone(), nonces.clone());
+    }
+
+    /// Gets each of the steps recorded so far.
+    pub fn finish(self) -> Vec<TraceStep> {
+        self.steps
+    }
+}
+
+/// Gets each of the accounts whose balances differ between the given balance maps, in order of address.
+///
+/// # Arguments
+///
+/// * `before` - The balances before a change
+/// * `after` - The balances after the change
+pub fn diff_balance_maps(
+    before: &HashMap<String, BigUint>,
+    after: &HashMap<String, BigUint>,
+) -> Vec<BalanceChange> {
+    let accounts: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+
+    accounts
+        .into_iter()
+        .filter_map(|account| {
+            let balance_before = before.get(account).cloned().unwrap_or_else(BigUint::zero);
+            let balance_after = after.get(account).cloned().unwrap_or_else(BigUint::zero);
+
+            if balance_before == balance_after {
+                None
+            } else {
+                Some(BalanceChange {
+                    account: account.clone(),
+                    before: balance_before,
+                    after: balance_after,
+                })
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{state::Entry, transaction::Transaction}; // Import the state & transaction types
+    use super::*; // Import names from our parent module
+
+    use super::super::super::super::crypto::blake3; // Import the blake3 hashing module
+
+    #[test]
+    fn test_trace() {
+        let (alice, bob) = (blake3::hash_slice(b"alice"), blake3::hash_slice(b"bob"));
+
+        let mut balances = HashMap::new();
+        balances.insert(alice.to_str(), BigUint::from(10 as u8));
+        let parent = Entry::new(HashMap::new(), balances);
+
+        let tx = Transaction::new(0, alice, bob, BigUint::from(4 as u8), b"", vec![]);
+
+        // Tracing never changes the outcome of the execution
+        let trace = tx.trace(parent.clone(), TraceLevel::Full);
+        assert_eq!(trace.state_hash, tx.execute(Some(parent.clone())).hash);
+        assert_eq!(trace.status, ExecutionStatus::Success);
+
+        // Only the transfer moved any funds, and each step keeps its place in the execution
+        let transfer = &trace.steps[0];
+        assert_eq!(transfer.op, "transfer");
+        assert_eq!(transfer.balance_changes.len(), 2);
+        assert!(trace.steps[1..]
+            .iter()
+            .all(|step| step.op == "nonce" || !step.changed_state));
+
+        let minimal = tx.trace(parent, TraceLevel::Minimal);
+        assert_eq!(
+            minimal
+                .steps
+                .iter()
+                .map(|step| (step.index, step.op.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(0, "transfer"), (trace.steps.len() - 1, "nonce")]
+        );
+        assert!(minimal.steps[0].balance_changes.is_empty());
+    }
+}
@@ -8,15 +8,23 @@ use std::{
 
 use chrono; // Import time library
 
-use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+use num::{bigint::BigUint, CheckedSub, Zero}; // Add support for large unsigned integers
 
 use bincode;
 use serde::{Deserialize, Serialize}; // Import serde serialization
 use serde_json; // Import serde json // Import serde bincode
 
-use super::receipt::{self, Receipt, ReceiptMap}; // Import receipt types
+use super::batch; // Import the multi-recipient batch transfer types
+use super::burn; // Import the burn address
+use super::gas; // Import the gas & fee accounting
+use super::guardians; // Import the account recovery guardians
+use super::issuance; // Import the issuance schedule
+use super::receipt::{self, ExecutionStatus, LegacyReceiptMap, Receipt, ReceiptMap}; // Import receipt types
+use super::rewards; // Import the validator rewards accounting
 use super::signature; // Import signature type
 use super::state::{self, Entry}; // Import the state entry types
+use super::trace::{ExecutionTrace, TraceLevel, Tracer}; // Import the execution tracer
+use super::vesting; // Import the time-locked balance types
 
 use super::super::super::{
     common::address,
@@ -76,6 +84,99 @@ pub struct TransactionData {
     pub parent_state_hash: Option<hash::Hash>,
     /// The transaction's timestamp
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// The most gas that the transaction may use
+    #[serde(default)]
+    pub gas_limit: u64,
+    /// The number of finks paid for each unit of gas used by the transaction, or zero if the transaction is free
+    #[serde(default)]
+    pub gas_price: BigUint,
+}
+
+/// A transaction, as laid out by version 1 of the transaction format.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LegacyTransaction {
+    /// The contents of the transaction
+    pub transaction_data: LegacyTransactionData,
+    /// The hash of the transaction
+    pub hash: hash::Hash,
+    /// The transaction's signature
+    pub signature: Option<signature::Signature>,
+    /// The address of the deployed contract (if applicable)
+    pub deployed_contract_address: Option<address::Address>,
+    /// Whether or not this transaction creates a contract
+    pub contract_creation: bool,
+    /// Whether or not this transaction is the network genesis
+    pub genesis: bool,
+}
+
+/// The contents of a transaction, as laid out by version 1 of the transaction format, before transactions carried gas
+/// and receipts recorded the outcome of an execution.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LegacyTransactionData {
+    /// The index of the transaction in the sender's set of txs
+    pub nonce: u64,
+    /// The sender of the transaction
+    pub sender: address::Address,
+    /// The recipient of the transaction
+    pub recipient: address::Address,
+    /// The amount of finks sent along with the Transaction
+    pub value: BigUint,
+    /// The data sent to the transaction recipient (i.e. contract call bytecode)
+    pub payload: Vec<u8>,
+    /// The hashes of the transaction's parents
+    pub parents: Vec<hash::Hash>,
+    /// The list of resolved parent receipts
+    pub parent_receipts: Option<LegacyReceiptMap>,
+    /// The hash of the combined parent state
+    pub parent_state_hash: Option<hash::Hash>,
+    /// The transaction's timestamp
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&TransactionData> for LegacyTransactionData {
+    /// Lays out the given transaction data in version 1 of the transaction format, dropping any fields added since.
+    fn from(data: &TransactionData) -> Self {
+        Self {
+            nonce: data.nonce,
+            sender: data.sender,
+            recipient: data.recipient,
+            value: data.value.clone(),
+            payload: data.payload.clone(),
+            parents: data.parents.clone(),
+            parent_receipts: data.parent_receipts.as_ref().map(LegacyReceiptMap::from),
+            parent_state_hash: data.parent_state_hash,
+            timestamp: data.timestamp,
+        }
+    }
+}
+
+impl From<LegacyTransaction> for Transaction {
+    /// Upgrades the given transaction from version 1 of the transaction format. Since the upgraded transaction
+    /// doesn't use any of the fields added since, its hash stays the same.
+    fn from(tx: LegacyTransaction) -> Self {
+        let data = tx.transaction_data;
+
+        Self {
+            transaction_data: TransactionData {
+                nonce: data.nonce,
+                sender: data.sender,
+                recipient: data.recipient,
+                value: data.value,
+                payload: data.payload,
+                parents: data.parents,
+                parent_receipts: data.parent_receipts.map(ReceiptMap::from),
+                parent_state_hash: data.parent_state_hash,
+                timestamp: data.timestamp,
+                gas_limit: 0,
+                gas_price: BigUint::zero(),
+            },
+            hash: tx.hash,
+            signature: tx.signature,
+            deployed_contract_address: tx.deployed_contract_address,
+            contract_creation: tx.contract_creation,
+            genesis: tx.genesis,
+        }
+    }
 }
 
 /* BEGIN EXPORTED METHODS */
@@ -85,6 +186,33 @@ impl TransactionData {
     pub fn to_bytes(&self) -> Vec<u8> {
         serde_json::to_vec(self).unwrap() // Serialize
     }
+
+    /// Gets the oldest version of the transaction format that can lay out the transaction's contents. Transactions
+    /// that don't pay for gas, and whose parent receipts don't record the outcome of an execution, fit in version 1.
+    pub fn format_version(&self) -> u8 {
+        if self.gas_limit == 0
+            && self.gas_price.is_zero()
+            && self
+                .parent_receipts
+                .as_ref()
+                .map_or(true, ReceiptMap::is_legacy)
+        {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Hashes the contents of the transaction, laid out in the oldest version of the transaction format that fits
+    /// them, so that the hashes of transactions made before the format changed stay the same.
+    pub fn hash(&self) -> hash::Hash {
+        let encoded = match self.format_version() {
+            1 => bincode::serialize(&LegacyTransactionData::from(self)),
+            _ => bincode::serialize(self),
+        };
+
+        blake3::hash_slice(&encoded.unwrap_or_default())
+    }
 }
 
 /// Implement a set of transaction helper methods.
@@ -135,10 +263,12 @@ impl Transaction {
             parent_receipts: None,         // Set parent receipts
             parent_state_hash: None,       // Set parent state hash
             timestamp: chrono::Utc::now(), // Set timestamp
+            gas_limit: 0,                  // Set gas limit
+            gas_price: BigUint::zero(),    // Set gas price
         }; // Initialize transaction data
 
         Transaction {
-            hash: blake3::hash_slice(&bincode::serialize(&transaction_data).unwrap_or_default()),
+            hash: transaction_data.hash(),
             transaction_data, // Set transaction data
             signature: None,  // Set signature
             deployed_contract_address: None,
@@ -147,6 +277,22 @@ impl Transaction {
         }
     }
 
+    /// Sets the gas limit and gas price of the transaction, rehashing it. Any signature attached to the transaction
+    /// must be made again.
+    ///
+    /// # Arguments
+    ///
+    /// * `gas_limit` - The most gas that the transaction may use
+    /// * `gas_price` - The number of finks paid for each unit of gas used by the transaction
+    pub fn with_gas(mut self, gas_limit: u64, gas_price: BigUint) -> Self {
+        self.transaction_data.gas_limit = gas_limit;
+        self.transaction_data.gas_price = gas_price;
+
+        self.hash = self.transaction_data.hash();
+
+        self
+    }
+
     /// Verify the signature attached to a transaction.
     ///
     /// # Example
@@ -215,59 +361,212 @@ impl Transaction {
     /// let resulting_state_entry = tx.execute(None); // Must specify a previous state entry if this is not the genesis transaction
     /// ```
     pub fn execute(&self, prev_entry: Option<state::Entry>) -> state::Entry {
+        self.execute_traced(prev_entry, &mut Tracer::disabled())
+    }
+
+    /// Executes the transaction on top of the given state, like execute, recording each step with the given tracer.
+    ///
+    /// # Arguments
+    ///
+    /// * `prev_entry` - The state that the transaction should be executed on top of, if any
+    /// * `tracer` - The tracer that each step should be recorded with
+    pub fn execute_traced(
+        &self,
+        prev_entry: Option<state::Entry>,
+        tracer: &mut Tracer,
+    ) -> state::Entry {
         match prev_entry {
             Some(entry) => {
                 // Execute the transaction, but with no entry data, since there isn't anything in the entry in the first place
                 if entry.data.balances.is_empty() {
-                    return self.execute(None);
+                    return self.execute_traced(None, tracer);
                 }
 
+                // Locks are evaluated on the clock of the state that the transaction is executed on top of
+                let (now, height) = vesting::clock(&entry.data, self.transaction_data.timestamp);
+                let spendable =
+                    vesting::sender_funds(&entry.data, &self.transaction_data).spendable;
+
                 let mut balances: collections::HashMap<String, BigUint> = entry.data.balances; // Initialize balances map
                 let mut nonces: collections::HashMap<String, u64> = entry.data.nonces;
+                let mut guardians = entry.data.guardians;
+                let mut locks = entry.data.locks;
+                tracer.begin(&balances, &nonces);
+
+                // Only move any funds if the sender can afford to send them along with the fee without touching any
+                // of its locked funds, they haven't been burned, and the transaction doesn't run out of gas
+                let transferred = if let Some(sender_balance) = balances
+                    .get(&self.transaction_data.sender.to_str())
+                    .unwrap_or(&BigUint::zero())
+                    .checked_sub(&self.transaction_data.value)
+                    .filter(|remaining| *remaining >= gas::fee(&self.transaction_data))
+                    .filter(|_| {
+                        spendable >= &self.transaction_data.value + gas::fee(&self.transaction_data)
+                    })
+                    .filter(|_| gas::check(&self.transaction_data).is_ok())
+                    .filter(|_| burn::check(&self.transaction_data).is_ok())
+                {
+                    balances.insert(self.transaction_data.sender.to_str(), sender_balance); // Subtract transaction value from sender balance
+                    balances.insert(
+                        self.transaction_data.recipient.to_str(),
+                        balances
+                            .get(&self.transaction_data.recipient.to_str())
+                            .unwrap_or(&BigUint::zero())
+                            + self.transaction_data.value.clone(),
+                    ); // Add transaction value to recipient balance
+                    tracer.record("transfer", &balances, &nonces);
+
+                    // Pass the value along to each of the batch's recipients, if this is a batch
+                    batch::execute_batch(&self.transaction_data, &mut balances);
+                    tracer.record("batch", &balances, &nonces);
+
+                    true
+                } else {
+                    tracer.record("transfer_refused", &balances, &nonces);
+
+                    false
+                };
+
+                // Collect the transaction's fee. Transactions that run out of gas still pay for the gas they used.
+                gas::execute_fee(&self.transaction_data, &mut balances);
+                tracer.record("fee", &balances, &nonces);
+
+                // Lock the value sent up, if it vests, and carry out any guardian instruction, handing a recovered
+                // account's balance over to its new owner
+                if transferred {
+                    vesting::execute(&self.transaction_data, &mut locks, now, height);
+                    guardians::execute(self, &mut guardians, &mut balances, &mut locks);
+                }
+                tracer.record("recovery", &balances, &nonces);
 
-                balances.insert(
-                    self.transaction_data.sender.to_str(),
-                    balances
-                        .get(&self.transaction_data.sender.to_str())
-                        .unwrap_or(&BigUint::zero())
-                        - self.transaction_data.value.clone(),
-                ); // Subtract transaction value from sender balance
-                balances.insert(
-                    self.transaction_data.recipient.to_str(),
-                    balances
-                        .get(&self.transaction_data.recipient.to_str())
-                        .unwrap_or(&BigUint::zero())
-                        + self.transaction_data.value.clone(),
-                ); // Add transaction value to recipient balance
+                // Pay out an epoch's reward, if this is a mint
+                issuance::execute_mint(&self.transaction_data, &mut balances);
+                tracer.record("mint", &balances, &nonces);
+
+                // Pay the fees collected since the last mint out along with it, if this is a mint
+                gas::execute_payout(&self.transaction_data, &mut balances);
+                tracer.record("fee_payout", &balances, &nonces);
+
+                // Pay a voting account the rewards it has earned, if this is a claim
+                rewards::execute_claim(&self.transaction_data, &mut balances);
+                tracer.record("claim", &balances, &nonces);
 
                 // Put the new nonce into the transaction's state
                 nonces.insert(
                     self.transaction_data.sender.to_str(),
                     self.transaction_data.nonce,
                 );
-
-                state::Entry::new(nonces, balances) // Return state entry
+                tracer.record("nonce", &balances, &nonces);
+
+                // Return state entry
+                state::Entry::from_data(state::EntryData {
+                    balances,
+                    nonces,
+                    guardians,
+                    locks,
+                    height: entry.data.height + 1,
+                    time: entry.data.time.max(Some(self.transaction_data.timestamp)),
+                })
             }
             None => {
                 let mut balances: collections::HashMap<String, BigUint> =
                     collections::HashMap::new(); // Initialize balance map
                 let mut nonces: collections::HashMap<String, u64> = collections::HashMap::new();
+                tracer.begin(&balances, &nonces);
 
                 balances.insert(
                     self.transaction_data.recipient.to_str(),
                     self.transaction_data.value.clone(),
                 ); // Set recipient balance to tx value
+                tracer.record("genesis_allocation", &balances, &nonces);
 
                 nonces.insert(
                     self.transaction_data.sender.to_str(),
                     self.transaction_data.nonce,
                 );
+                tracer.record("nonce", &balances, &nonces);
+
+                // Lock the allocation up, if it vests
+                let mut locks = collections::HashMap::new();
+                vesting::execute(
+                    &self.transaction_data,
+                    &mut locks,
+                    self.transaction_data.timestamp,
+                    0,
+                );
 
-                state::Entry::new(nonces, balances) // Return state entry
+                // Return state entry
+                state::Entry::from_data(state::EntryData {
+                    balances,
+                    nonces,
+                    locks,
+                    height: 1,
+                    time: Some(self.transaction_data.timestamp),
+                    ..Default::default()
+                })
             }
         }
     }
 
+    /// Executes the transaction on top of the given parent state without committing anything, and records each step
+    /// taken along the way.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent_state` - The state that the transaction should be executed on top of
+    /// * `level` - How much detail to record
+    pub fn trace(&self, parent_state: state::Entry, level: TraceLevel) -> ExecutionTrace {
+        let status = self.execution_status(&parent_state);
+        let parent_state_hash = parent_state.hash;
+
+        let mut tracer = Tracer::new(level);
+        let resulting_state = self.execute_traced(Some(parent_state), &mut tracer);
+
+        ExecutionTrace {
+            tx: self.hash,
+            level,
+            parent_state_hash,
+            status,
+            steps: tracer.finish(),
+            state_hash: resulting_state.hash,
+        }
+    }
+
+    /// Determines the outcome of executing the transaction on top of the given state, without executing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `prev_entry` - The state that the transaction would be executed on top of
+    pub fn execution_status(&self, prev_entry: &state::Entry) -> ExecutionStatus {
+        let sender = self.transaction_data.sender.to_str(); // Get the sender's key in the state
+
+        // The nonce must directly follow the sender's last nonce, or be zero if the sender hasn't sent anything yet
+        let expected_nonce = prev_entry
+            .data
+            .nonces
+            .get(&sender)
+            .map_or(0, |last_nonce| last_nonce + 1);
+
+        if self.transaction_data.nonce != expected_nonce {
+            return ExecutionStatus::InvalidNonce;
+        }
+
+        if gas::check(&self.transaction_data).is_err() {
+            return ExecutionStatus::OutOfGas;
+        }
+
+        // Burned funds can't be spent, and the sender must be able to afford the transaction, along with its fee,
+        // without touching any of its locked funds
+        if burn::check(&self.transaction_data).is_err()
+            || vesting::sender_funds(&prev_entry.data, &self.transaction_data).spendable
+                < &self.transaction_data.value + gas::fee(&self.transaction_data)
+        {
+            return ExecutionStatus::InsufficientFunds;
+        }
+
+        ExecutionStatus::Success
+    }
+
     /// Registers the provided state entry as a parental state for the transaction.
     ///
     /// # Arguments
@@ -291,6 +590,9 @@ impl Transaction {
             receipts.receipts.push(Receipt {
                 state_hash: entry.hash,
                 logs: Vec::new(),
+                status: None,
+                items: Vec::new(),
+                executed_at: None,
             });
         }
 
@@ -298,8 +600,7 @@ impl Transaction {
         self.transaction_data.parent_receipts = Some(receipts);
 
         // Rehash the transaction
-        self.hash =
-            blake3::hash_slice(&bincode::serialize(&self.transaction_data).unwrap_or_default());
+        self.hash = self.transaction_data.hash();
     }
 
     /// Persists the transaction to a mem dir in the given data directory.
@@ -431,6 +732,124 @@ mod tests {
         ); // Ensure payload intact
     }
 
+    #[test]
+    fn test_format_version() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+
+        let sender = address::Address::from_key_pair(&Keypair::generate(&mut csprng)); // Derive sender address
+        let recipient = address::Address::from_key_pair(&Keypair::generate(&mut csprng)); // Derive recipient address
+
+        let mut tx = Transaction::new(0, sender, recipient, BigUint::from(5 as u8), b"", vec![]);
+        tx.transaction_data.parent_receipts = Some(ReceiptMap {
+            associated_transactions: vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+            receipts: vec![Receipt::default()],
+        }); // Cite a receipt that doesn't record an execution
+
+        // Transactions that fit in the first version of the format keep the hash that they had in it
+        assert_eq!(tx.transaction_data.format_version(), 1);
+        assert_eq!(
+            tx.transaction_data.hash(),
+            blake3::hash_slice(
+                &bincode::serialize(&LegacyTransactionData::from(&tx.transaction_data)).unwrap()
+            )
+        );
+
+        // Receipts recording an execution need the second version
+        let mut itemized = tx.clone();
+        itemized.transaction_data.parent_receipts = Some(ReceiptMap {
+            associated_transactions: vec![hash::Hash::new(vec![0; hash::HASH_SIZE])],
+            receipts: vec![Receipt {
+                status: Some(ExecutionStatus::Success),
+                ..Default::default()
+            }],
+        });
+        assert_eq!(itemized.transaction_data.format_version(), 2);
+        assert_ne!(itemized.transaction_data.hash(), tx.transaction_data.hash()); // Ensure the status is covered
+
+        // So do transactions paying for gas
+        let paying = tx.clone().with_gas(21_000, BigUint::from(1 as u8));
+        assert_eq!(paying.transaction_data.format_version(), 2);
+        assert_ne!(paying.hash, tx.transaction_data.hash()); // Ensure the gas is covered
+    }
+
+    #[test]
+    fn test_execution_status() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+
+        let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+        let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+
+        let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+        let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
+
+        let mut balances = collections::HashMap::new(); // Initialize balances map
+        balances.insert(sender.to_str(), BigUint::from(10 as u8)); // Give the sender some finks
+
+        let prev_entry = state::Entry::new(collections::HashMap::new(), balances); // Initialize the previous state
+
+        let affordable =
+            Transaction::new(0, sender, recipient, BigUint::from(5 as u8), b"", vec![]); // Initialize transaction
+        let unaffordable =
+            Transaction::new(0, sender, recipient, BigUint::from(50 as u8), b"", vec![]); // Initialize transaction
+        let out_of_order =
+            Transaction::new(1, sender, recipient, BigUint::from(5 as u8), b"", vec![]); // Initialize transaction
+
+        assert_eq!(
+            affordable.execution_status(&prev_entry),
+            ExecutionStatus::Success
+        ); // Ensure can be executed
+        assert_eq!(
+            unaffordable.execution_status(&prev_entry),
+            ExecutionStatus::InsufficientFunds
+        ); // Ensure sender can't afford
+        assert_eq!(
+            out_of_order.execution_status(&prev_entry),
+            ExecutionStatus::InvalidNonce
+        ); // Ensure nonce is wrong
+    }
+
+    #[test]
+    fn test_execute_insufficient_funds() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+
+        let sender_keypair: Keypair = Keypair::generate(&mut csprng); // Generate sender key pair
+        let recipient_keypair: Keypair = Keypair::generate(&mut csprng); // Generate recipient key pair
+
+        let sender = address::Address::from_key_pair(&sender_keypair); // Derive sender from sender key pair
+        let recipient = address::Address::from_key_pair(&recipient_keypair); // Derive recipient from recipient key pair
+
+        let mut balances = collections::HashMap::new(); // Initialize balances map
+        balances.insert(sender.to_str(), BigUint::from(10 as u8)); // Give the sender some finks
+
+        let prev_entry = state::Entry::new(collections::HashMap::new(), balances); // Initialize the previous state
+
+        let unaffordable =
+            Transaction::new(0, sender, recipient, BigUint::from(50 as u8), b"", vec![]); // Initialize transaction
+
+        let underpriced =
+            Transaction::new(0, sender, recipient, BigUint::from(5 as u8), b"", vec![])
+                .with_gas(1, BigUint::from(1 as u8)); // Initialize a transaction that runs out of gas
+        assert_eq!(
+            underpriced.execution_status(&prev_entry),
+            ExecutionStatus::OutOfGas
+        ); // Ensure the gas limit is too low
+
+        let entry = underpriced.execute(Some(prev_entry.clone())); // Execute the transaction that runs out of gas
+        assert_eq!(
+            entry.data.balances[&sender.to_str()],
+            BigUint::from(9 as u8)
+        ); // Ensure the gas used was paid for, but nothing was sent
+
+        let entry = unaffordable.execute(Some(prev_entry)); // Execute the unaffordable transaction
+
+        assert_eq!(
+            entry.data.balances[&sender.to_str()],
+            BigUint::from(10 as u8)
+        ); // Ensure no funds were taken from the sender
+        assert_eq!(entry.data.balances.get(&recipient.to_str()), None); // Ensure no funds were given to the recipient
+        assert_eq!(entry.data.nonces[&sender.to_str()], 0); // Ensure the nonce was still used up
+    }
+
     #[test]
     fn test_sign_transaction() {
         let mut csprng = OsRng {}; // Generate source of randomness
@@ -0,0 +1,404 @@
+use chrono::{DateTime, Utc}; // Import time library
+
+use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::HashMap;
+
+use super::state::EntryData; // Import the state entry type
+use super::transaction::TransactionData; // Import the transaction type
+
+/// The prefix marking a transaction payload as a time lock on the value that it sends.
+pub const VESTING_PAYLOAD_PREFIX: &[u8] = b"vesting:";
+
+/// How far, in seconds, a transaction may be timestamped ahead of the clock of the node accepting it. Each state keeps
+/// the latest timestamp leading up to it, which time locks are evaluated against, so it mustn't be pushed ahead freely.
+pub const MAX_FUTURE_SKEW_SECS: i64 = 10 * 60;
+
+/// The condition that must be met before locked funds may be spent.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Unlock {
+    /// The funds unlock once the given time has passed
+    At(DateTime<Utc>),
+
+    /// The funds unlock once the state being spent from is at least the given number of transactions deep
+    Milestone(u64),
+}
+
+/// An amount held in an account that can't be spent until it unlocks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TimeLock {
+    /// The number of finks locked
+    pub amount: BigUint,
+
+    /// When the finks unlock
+    pub unlock: Unlock,
+}
+
+/// Each of the amounts locked in an account, which the state keeps alongside the account's balance.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct VestingSchedule {
+    /// Each of the locks that haven't been pruned yet, in the order in which they were made
+    pub locks: Vec<TimeLock>,
+}
+
+/// How much of an account's balance is locked, and how much may be spent.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LockedBalance {
+    /// The account's balance, including any locked funds
+    pub balance: BigUint,
+
+    /// The number of finks that can't be spent yet
+    pub locked: BigUint,
+
+    /// The number of finks that may be spent right away
+    pub spendable: BigUint,
+
+    /// Each of the locks that haven't expired yet
+    pub locks: Vec<TimeLock>,
+}
+
+/// An error encountered while checking a time lock against the rules of the ledger.
+#[derive(Debug, Fail, PartialEq)]
+pub enum VestingError {
+    #[fail(display = "the time lock could not be decoded")]
+    Malformed,
+    #[fail(display = "a time lock must lock at least one fink")]
+    CarriesNoValue,
+    #[fail(
+        display = "transaction timestamped {} is from the future (it is now {})",
+        timestamp, now
+    )]
+    FromTheFuture {
+        timestamp: DateTime<Utc>, // The time at which the transaction claims to have been made
+        now: DateTime<Utc>,       // The time at which the transaction was checked
+    },
+}
+
+/// Implement a set of unlock condition helper methods.
+impl Unlock {
+    /// Checks whether or not the condition has been met.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time at which the condition is evaluated
+    /// * `height` - The depth of the state that the condition is evaluated against
+    pub fn is_met(&self, now: DateTime<Utc>, height: u64) -> bool {
+        match self {
+            Self::At(unlocks_at) => now >= *unlocks_at,
+            Self::Milestone(milestone) => height >= *milestone,
+        }
+    }
+
+    /// Encodes the condition as a transaction payload, locking the value sent by the transaction until it is met.
+    pub fn to_payload(&self) -> Vec<u8> {
+        [
+            VESTING_PAYLOAD_PREFIX,
+            &bincode::serialize(self).unwrap_or_default()[..],
+        ]
+        .concat()
+    }
+
+    /// Decodes the unlock condition carried in the given transaction payload. Payloads that aren't marked as time
+    /// locks result in None.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - The payload of the transaction
+    pub fn from_payload(payload: &[u8]) -> Result<Option<Self>, VestingError> {
+        if !payload.starts_with(VESTING_PAYLOAD_PREFIX) {
+            return Ok(None);
+        }
+
+        bincode::deserialize(&payload[VESTING_PAYLOAD_PREFIX.len()..])
+            .map(Some)
+            .map_err(|_| VestingError::Malformed)
+    }
+}
+
+/// Implement a set of vesting schedule helper methods.
+impl VestingSchedule {
+    /// Gets the number of finks that are still locked.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time at which the locks are evaluated
+    /// * `height` - The depth of the state that the schedule belongs to
+    pub fn locked(&self, now: DateTime<Utc>, height: u64) -> BigUint {
+        self.locks
+            .iter()
+            .filter(|lock| !lock.unlock.is_met(now, height))
+            .fold(BigUint::zero(), |locked, lock| locked + &lock.amount)
+    }
+
+    /// Forgets each of the locks that have expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time at which the locks are evaluated
+    /// * `height` - The depth of the state that the schedule belongs to
+    pub fn prune(&mut self, now: DateTime<Utc>, height: u64) {
+        self.locks.retain(|lock| !lock.unlock.is_met(now, height));
+    }
+
+    /// Describes how much of the given balance is locked by the schedule, and how much may be spent.
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - The balance of the account that the schedule belongs to
+    /// * `now` - The time at which the locks are evaluated
+    /// * `height` - The depth of the state that the schedule belongs to
+    pub fn describe(&self, balance: BigUint, now: DateTime<Utc>, height: u64) -> LockedBalance {
+        let mut active = self.clone();
+        active.prune(now, height);
+
+        // Locked funds may have been moved out by a recovery, so never lock more than the account holds
+        let locked = active.locked(now, height).min(balance.clone());
+
+        LockedBalance {
+            spendable: &balance - &locked,
+            balance,
+            locked,
+            locks: active.locks,
+        }
+    }
+}
+
+/// Makes sure that the given transaction data either carries a well-formed time lock on the value that it sends, or
+/// has nothing to do with time locks at all.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction that should be checked
+pub fn check(tx: &TransactionData) -> Result<(), VestingError> {
+    if Unlock::from_payload(&tx.payload)?.is_some() && tx.value.is_zero() {
+        return Err(VestingError::CarriesNoValue);
+    }
+
+    Ok(())
+}
+
+/// Makes sure that the given transaction data isn't timestamped too far ahead of the given time.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction that should be checked
+/// * `now` - The current time
+pub fn check_timestamp(tx: &TransactionData, now: DateTime<Utc>) -> Result<(), VestingError> {
+    if (tx.timestamp - now).num_seconds() > MAX_FUTURE_SKEW_SECS {
+        return Err(VestingError::FromTheFuture {
+            timestamp: tx.timestamp,
+            now,
+        });
+    }
+
+    Ok(())
+}
+
+/// Gets the time and height at which the locks of the given state are evaluated for a transaction made at the given
+/// time. The time is never later than the latest transaction leading up to the state, so that a transaction can't
+/// unlock funds early by claiming to be from later than it is.
+///
+/// # Arguments
+///
+/// * `state` - The state that the transaction is executed on top of
+/// * `timestamp` - The time at which the transaction claims to have been made
+pub fn clock(state: &EntryData, timestamp: DateTime<Utc>) -> (DateTime<Utc>, u64) {
+    (
+        state.time.map_or(timestamp, |time| timestamp.min(time)),
+        state.height,
+    )
+}
+
+/// Describes how much of the balance of the account with the given address is locked in the given state, for a
+/// transaction made at the given time, and how much may be spent.
+///
+/// # Arguments
+///
+/// * `state` - The state holding the account's balance & locks
+/// * `account` - The address of the account, as the state keys it
+/// * `timestamp` - The time at which the locks should be evaluated, bounded by the state's clock
+pub fn funds(state: &EntryData, account: &str, timestamp: DateTime<Utc>) -> LockedBalance {
+    let (now, height) = clock(state, timestamp);
+
+    state
+        .locks
+        .get(account)
+        .cloned()
+        .unwrap_or_default()
+        .describe(
+            state.balances.get(account).cloned().unwrap_or_default(),
+            now,
+            height,
+        )
+}
+
+/// Describes how much of the sender's balance is locked when the given transaction data is executed on top of the
+/// given state, and how much it may spend.
+///
+/// # Arguments
+///
+/// * `state` - The state that the transaction is executed on top of
+/// * `tx` - The data of the transaction
+pub fn sender_funds(state: &EntryData, tx: &TransactionData) -> LockedBalance {
+    funds(state, &tx.sender.to_str(), tx.timestamp)
+}
+
+/// Locks the value sent by the given transaction data in its recipient's account, if it carries a time lock,
+/// forgetting any of the recipient's locks that have expired along the way.
+///
+/// # Arguments
+///
+/// * `tx` - The data of the transaction being executed
+/// * `locks` - The funds locked in each account, which will be updated in place
+/// * `now` - The time at which expired locks should be pruned
+/// * `height` - The depth of the state that the transaction is executed on top of
+pub fn execute(
+    tx: &TransactionData,
+    locks: &mut HashMap<String, VestingSchedule>,
+    now: DateTime<Utc>,
+    height: u64,
+) {
+    let unlock = match Unlock::from_payload(&tx.payload) {
+        Ok(Some(unlock)) if !tx.value.is_zero() => unlock,
+        _ => return,
+    };
+
+    let schedule = locks.entry(tx.recipient.to_str()).or_default();
+    schedule.prune(now, height);
+    schedule.locks.push(TimeLock {
+        amount: tx.value.clone(),
+        unlock,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::super::{common::address::Address, crypto::blake3}; // Import the address & hashing modules
+    use super::super::transaction::Transaction;
+    use super::*; // Import names from our parent module
+
+    use chrono::Duration;
+
+    #[test]
+    fn test_payload() {
+        let unlock = Unlock::Milestone(42);
+
+        assert_eq!(Unlock::from_payload(&unlock.to_payload()), Ok(Some(unlock))); // Ensure the lock survives encoding
+        assert_eq!(Unlock::from_payload(b"hello"), Ok(None)); // Ordinary payloads aren't locks
+        assert_eq!(
+            Unlock::from_payload(b"vesting:?"),
+            Err(VestingError::Malformed)
+        );
+
+        let empty = Transaction::new(
+            0,
+            Address::default(),
+            blake3::hash_slice(b"alice"),
+            BigUint::zero(),
+            &unlock.to_payload(),
+            vec![],
+        );
+        assert_eq!(
+            check(&empty.transaction_data),
+            Err(VestingError::CarriesNoValue)
+        ); // Locking nothing is pointless
+    }
+
+    #[test]
+    fn test_describe() {
+        let now = Utc::now();
+        let schedule = VestingSchedule {
+            locks: vec![
+                TimeLock {
+                    amount: BigUint::from(30 as u8),
+                    unlock: Unlock::At(now + Duration::days(30)),
+                },
+                TimeLock {
+                    amount: BigUint::from(20 as u8),
+                    unlock: Unlock::Milestone(10),
+                },
+                TimeLock {
+                    amount: BigUint::from(10 as u8),
+                    unlock: Unlock::At(now - Duration::days(1)),
+                },
+            ],
+        };
+
+        let before = schedule.describe(BigUint::from(100 as u8), now, 5);
+        assert_eq!(before.locked, BigUint::from(50 as u8)); // The expired lock doesn't count
+        assert_eq!(before.spendable, BigUint::from(50 as u8));
+        assert_eq!(before.locks.len(), 2);
+
+        let after_milestone = schedule.describe(BigUint::from(100 as u8), now, 10);
+        assert_eq!(after_milestone.spendable, BigUint::from(70 as u8));
+
+        // Nothing is spendable if the account holds less than is locked
+        let drained = schedule.describe(BigUint::from(40 as u8), now, 5);
+        assert_eq!(drained.locked, BigUint::from(40 as u8));
+        assert_eq!(drained.spendable, BigUint::zero());
+    }
+
+    #[test]
+    fn test_execute() {
+        let now = Utc::now();
+        let (alice, bob) = (blake3::hash_slice(b"alice"), blake3::hash_slice(b"bob"));
+
+        let mut state = EntryData::default();
+        state
+            .balances
+            .insert(bob.to_str(), BigUint::from(100 as u8));
+        state.height = 5;
+        state.time = Some(now);
+
+        // Lock 30 of the finks sent to bob for a day, and another 20 until the state is 10 transactions deep
+        for (amount, unlock) in vec![
+            (30 as u8, Unlock::At(now + Duration::days(1))),
+            (20 as u8, Unlock::Milestone(10)),
+            (0 as u8, Unlock::Milestone(10)),
+        ] {
+            let tx = Transaction::new(
+                0,
+                alice,
+                bob,
+                BigUint::from(amount),
+                &unlock.to_payload(),
+                vec![],
+            );
+            execute(&tx.transaction_data, &mut state.locks, now, state.height);
+        }
+        assert_eq!(state.locks[&bob.to_str()].locks.len(), 2); // Locking nothing doesn't do anything
+
+        // Claiming to be from later than the state doesn't unlock anything
+        let mut spend = Transaction::new(0, bob, alice, BigUint::from(60 as u8), b"", vec![]);
+        spend.transaction_data.timestamp = now + Duration::days(2);
+        assert_eq!(
+            sender_funds(&state, &spend.transaction_data).spendable,
+            BigUint::from(50 as u8)
+        );
+
+        // Once the state's clock & height catch up, though, the funds unlock
+        state.time = Some(now + Duration::days(2));
+        assert_eq!(
+            sender_funds(&state, &spend.transaction_data).spendable,
+            BigUint::from(80 as u8)
+        );
+        state.height = 10;
+        assert_eq!(
+            sender_funds(&state, &spend.transaction_data).locked,
+            BigUint::zero()
+        );
+
+        // Transactions can't push the clock far ahead of everyone else's
+        assert!(check_timestamp(&spend.transaction_data, now + Duration::days(2)).is_ok());
+        assert_eq!(
+            check_timestamp(&spend.transaction_data, now),
+            Err(VestingError::FromTheFuture {
+                timestamp: spend.transaction_data.timestamp,
+                now
+            })
+        );
+    }
+}
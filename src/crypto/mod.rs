@@ -1,2 +1,3 @@
 pub mod blake3; // Export the blake3 hashing module
 pub mod hash; // Export the hash type module
+pub mod short_id; // Export the short hash id module
@@ -0,0 +1,149 @@
+use super::hash::{Hash, HASH_SIZE}; // Import the hash type
+
+/// The number of leading base58 characters of a hash shown as its short id.
+pub const SHORT_ID_LEN: usize = 8;
+
+/// The fewest characters that a short id may be given with.
+pub const MIN_SHORT_ID_LEN: usize = 4;
+
+/// The greatest number of candidates listed for an ambiguous short id.
+pub const MAX_LISTED_CANDIDATES: usize = 10;
+
+/// An error encountered while resolving a short id to a full hash.
+#[derive(Debug, Fail, PartialEq)]
+pub enum ShortIdError {
+    #[fail(display = "{} isn't a base58-encoded hash, or a prefix of one", id)]
+    Malformed {
+        id: String, // The short id
+    },
+    #[fail(
+        display = "short id {} is too short; give at least {} characters",
+        id, min
+    )]
+    TooShort {
+        id: String, // The short id
+        min: usize, // The fewest characters that a short id may be given with
+    },
+    #[fail(display = "no known hash begins with {}", id)]
+    Unknown {
+        id: String, // The short id
+    },
+    #[fail(
+        display = "short id {} is ambiguous; it could be any of {}",
+        id, candidates
+    )]
+    Ambiguous {
+        id: String,         // The short id
+        candidates: String, // The hashes beginning with the short id, comma-separated in alphabetical order
+    },
+}
+
+/// Implement a set of hash helper methods concerning short ids.
+impl Hash {
+    /// Gets the short id of the hash: its first few base58 characters, which are enough for a person to tell it apart
+    /// from the other hashes in a DAG.
+    pub fn short_id(&self) -> String {
+        self.to_str().chars().take(SHORT_ID_LEN).collect()
+    }
+}
+
+/// Resolves the given full hash, or short id (a prefix of a base58-encoded hash), to the one hash among the given
+/// candidates that it begins. Full hashes are returned as-is, whether or not they are among the candidates.
+///
+/// # Arguments
+///
+/// * `id` - The full hash, or short id
+/// * `candidates` - Each of the hashes that the short id may refer to
+pub fn resolve<'a>(
+    id: &str,
+    candidates: impl IntoIterator<Item = &'a Hash>,
+) -> Result<Hash, ShortIdError> {
+    let id = id.trim();
+
+    match bs58::decode(id).into_vec() {
+        Ok(decoded) if decoded.len() == HASH_SIZE => return Ok(Hash::new(decoded)),
+        Err(_) => return Err(ShortIdError::Malformed { id: id.to_owned() }),
+        Ok(_) => (),
+    }
+
+    if id.len() < MIN_SHORT_ID_LEN {
+        return Err(ShortIdError::TooShort {
+            id: id.to_owned(),
+            min: MIN_SHORT_ID_LEN,
+        });
+    }
+
+    let mut matches: Vec<String> = candidates
+        .into_iter()
+        .map(Hash::to_str)
+        .filter(|candidate| candidate.starts_with(id))
+        .collect();
+    matches.sort();
+    matches.dedup();
+
+    match matches.len() {
+        0 => Err(ShortIdError::Unknown { id: id.to_owned() }),
+        1 => Ok(Hash::from(matches[0].as_str())),
+        _ => {
+            matches.truncate(MAX_LISTED_CANDIDATES);
+
+            Err(ShortIdError::Ambiguous {
+                id: id.to_owned(),
+                candidates: matches.join(", "),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::blake3; // Import the blake3 hashing module
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_resolve() {
+        let hashes: Vec<Hash> = (0..64u8).map(|i| blake3::hash_slice(&[i])).collect();
+        let hash = hashes[7];
+
+        // Full hashes & short ids both resolve
+        assert_eq!(resolve(&hash.to_str(), &hashes), Ok(hash));
+        assert_eq!(resolve(&hash.short_id(), &hashes), Ok(hash));
+        assert_eq!(hash.short_id().len(), SHORT_ID_LEN);
+
+        // A character or two could begin any number of hashes
+        assert_eq!(
+            resolve("FV", &hashes),
+            Err(ShortIdError::TooShort {
+                id: "FV".to_owned(),
+                min: MIN_SHORT_ID_LEN
+            })
+        );
+
+        // Short ids that begin more than one hash list each of them
+        let twins = [
+            Hash::from("FVPfbg9bK7mj7jnaSRXhuVcVakkXcjMPgSwxmauUofYf"),
+            Hash::from("FVPfbg9bK7mj7jnaSRXhuVcVakkXcjMPgSwxmauUofYg"),
+        ];
+        assert_eq!(
+            resolve("FVPfbg9b", &twins),
+            Err(ShortIdError::Ambiguous {
+                id: "FVPfbg9b".to_owned(),
+                candidates: format!("{}, {}", twins[0], twins[1]),
+            })
+        );
+        assert_eq!(resolve("FVPfbg9b", &twins[1..]), Ok(twins[1]));
+
+        assert_eq!(
+            resolve("zzzzzzzz", &twins),
+            Err(ShortIdError::Unknown {
+                id: "zzzzzzzz".to_owned()
+            })
+        );
+        assert_eq!(
+            resolve("0OIl", &twins),
+            Err(ShortIdError::Malformed {
+                id: "0OIl".to_owned()
+            })
+        );
+    }
+}
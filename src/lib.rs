@@ -18,6 +18,7 @@ extern crate ed25519_dalek;
 extern crate log;
 
 extern crate libp2p;
+extern crate libp2p_pnet;
 
 extern crate crypto as cryptolib;
 extern crate num;
@@ -38,3 +39,6 @@ extern crate reqwest;
 extern crate ctrlc;
 
 extern crate bs58;
+
+#[cfg(feature = "keychain")]
+extern crate keyring;
@@ -0,0 +1,530 @@
+use std::collections::HashMap; // Import the map type
+
+use num::{bigint::BigUint, Zero}; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::super::{
+    common::{
+        address::Address,
+        diversity::{DiversityPolicy, Witness, Witnesses},
+    },
+    core::types::signature::Signature,
+    crypto::{blake3, hash::Hash},
+}; // Import the address, signature & hash types
+
+/// The number of distinct publishers that must vouch for a head before it is synchronized towards, by default.
+pub const DEFAULT_MIN_ATTESTERS: usize = 2;
+
+/// An error encountered while weighing a head attestation.
+#[derive(Debug, Fail)]
+pub enum AttestationError {
+    #[fail(
+        display = "the attestation of head {} claims to be published by {}, but isn't signed by it",
+        head, publisher
+    )]
+    InvalidSignature {
+        head: String,      // The hash of the attested head
+        publisher: String, // The address that the attestation claims to be published by
+    },
+    #[fail(
+        display = "refusing the attestation of head {} by {}, since it holds no stake",
+        head, publisher
+    )]
+    NoStake {
+        head: String,      // The hash of the attested head
+        publisher: String, // The address of the publisher
+    },
+}
+
+/// The state resulting from a transaction, as committed to by the root of the merkle tree of its balances.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct AttestedState {
+    /// The hash of the transaction
+    pub hash: Hash,
+
+    /// The root of the merkle tree of the balances in the state resulting from the transaction
+    pub balance_root: Hash,
+}
+
+/// A signed claim, published under HEAD_TRANSACTION_KEY, that a particular transaction is at the head of the DAG.
+/// Publishers also vouch for the state resulting from their executed head, which is what light clients check
+/// balances against.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HeadAttestation {
+    /// The hash of the last transaction in the publisher's DAG
+    pub head: Hash,
+
+    /// The number of transactions in the publisher's DAG
+    pub height: u64,
+
+    /// The address of the account that vouches for the head
+    pub publisher: Address,
+
+    /// The publisher's signature of the attestation
+    pub signature: Option<Signature>,
+
+    /// The state resulting from the last transaction that the publisher has executed, if it has executed any
+    pub state: Option<AttestedState>,
+}
+
+/// A head attestation, as published before attestations vouched for the publisher's executed state.
+#[derive(Serialize, Deserialize)]
+struct LegacyHeadAttestation {
+    head: Hash,
+    height: u64,
+    publisher: Address,
+    signature: Option<Signature>,
+}
+
+/// Implement a set of head attestation helper methods.
+impl HeadAttestation {
+    /// Makes an attestation of the given head, signed with the given keypair.
+    ///
+    /// # Arguments
+    ///
+    /// * `head` - The hash of the last transaction in the DAG
+    /// * `height` - The number of transactions in the DAG
+    /// * `state` - The state resulting from the last executed transaction in the DAG, if any
+    /// * `keypair` - The keypair of the account vouching for the head
+    pub fn new(
+        head: Hash,
+        height: u64,
+        state: Option<AttestedState>,
+        keypair: &ed25519_dalek::Keypair,
+    ) -> Self {
+        let mut attestation = Self {
+            head,
+            height,
+            publisher: Address::from_public_key(&keypair.public),
+            signature: None,
+            state,
+        };
+        attestation.signature = Some(Signature::new(keypair, &*attestation.hash()));
+
+        attestation
+    }
+
+    /// Hashes the contents of the attestation, excluding its signature. Attestations that don't vouch for a state
+    /// hash the same as they did before they could.
+    pub fn hash(&self) -> Hash {
+        let mut to_be_hashed = self.clone();
+        to_be_hashed.signature = None;
+
+        let serialized = match to_be_hashed.state {
+            Some(_) => bincode::serialize(&to_be_hashed),
+            None => bincode::serialize(&LegacyHeadAttestation {
+                head: to_be_hashed.head,
+                height: to_be_hashed.height,
+                publisher: to_be_hashed.publisher,
+                signature: None,
+            }),
+        };
+
+        blake3::hash_slice(&serialized.unwrap_or_default())
+    }
+
+    /// Ensures that the attestation was signed by the publisher that it names.
+    pub fn valid(&self) -> bool {
+        match &self.signature {
+            Some(signature) => {
+                signature.address().ok() == Some(self.publisher) && signature.verify(&*self.hash())
+            }
+            None => false,
+        }
+    }
+
+    /// Serializes the attestation into a vector of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).unwrap_or_default()
+    }
+
+    /// Deserializes an attestation from the given bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `b` - The serialized attestation
+    pub fn from_bytes(b: &[u8]) -> Result<Self, bincode::Error> {
+        bincode::deserialize(b).or_else(|e| {
+            // Fall back to the layout used before attestations vouched for a state
+            let legacy: LegacyHeadAttestation = bincode::deserialize(b).map_err(|_| e)?;
+
+            Ok(Self {
+                head: legacy.head,
+                height: legacy.height,
+                publisher: legacy.publisher,
+                signature: legacy.signature,
+                state: None,
+            })
+        })
+    }
+}
+
+/// How much backing a head needs before the node will synchronize towards it.
+#[derive(Clone, Debug)]
+pub struct AttestationPolicy {
+    /// The number of distinct publishers holding stake that must vouch for the head
+    pub min_publishers: usize,
+
+    /// The number of finks that the publishers must hold between them
+    pub min_stake: BigUint,
+
+    /// How many distinct peers (and subnets) the attestations must have been fetched from, so that a single host
+    /// can't vouch for a head with many accounts
+    pub diversity: DiversityPolicy,
+}
+
+impl Default for AttestationPolicy {
+    /// Gets the default attestation policy.
+    fn default() -> Self {
+        Self {
+            min_publishers: DEFAULT_MIN_ATTESTERS,
+            min_stake: BigUint::zero(),
+            diversity: DiversityPolicy::default(),
+        }
+    }
+}
+
+/// A head vouched for by enough publishers to be synchronized towards.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AttestedHead {
+    /// The hash of the head
+    pub head: Hash,
+
+    /// The number of transactions in the DAG, as of the head
+    pub height: u64,
+
+    /// The number of distinct publishers vouching for the head
+    pub publishers: usize,
+
+    /// The number of finks held by the publishers vouching for the head
+    pub stake: BigUint,
+
+    /// The number of distinct peers that the attestations of the head were fetched from
+    #[serde(default)]
+    pub peers: usize,
+
+    /// The number of distinct subnets that those peers are reachable in
+    #[serde(default)]
+    pub subnets: usize,
+}
+
+/// The latest head vouched for by each publisher, weighed by the publisher's stake, so that a single peer (or a
+/// swarm of peers holding nothing) can't point the node at a history of its choosing.
+pub struct HeadAttestations {
+    /// How much backing a head needs before it is synchronized towards
+    policy: AttestationPolicy,
+
+    /// The latest attestation by each publisher, along with the publisher's stake and the peer that the attestation
+    /// was fetched from, if it's known
+    latest: HashMap<Address, (HeadAttestation, BigUint, Option<Witness>)>,
+}
+
+/// Implement a set of head attestation tally helper methods.
+impl HeadAttestations {
+    /// Makes an empty tally, holding heads to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How much backing a head needs before it is synchronized towards
+    pub fn new(policy: AttestationPolicy) -> Self {
+        Self {
+            policy,
+            latest: HashMap::new(),
+        }
+    }
+
+    /// Counts the given attestation towards its head, replacing any earlier attestation by the same publisher.
+    /// Attestations of a lower height than the publisher's latest are ignored, so that old records can't be replayed
+    /// to drag the node backwards. Light clients don't hold the state, so they can't weigh publishers by their stake;
+    /// the attestations that they count carry no stake, and can't meet a policy's minimum stake.
+    ///
+    /// # Arguments
+    ///
+    /// * `attestation` - The attestation
+    /// * `stake` - The number of finks held by the publisher, if the node can tell
+    /// * `witness` - The peer that the attestation was fetched from, if it's known
+    pub fn record(
+        &mut self,
+        attestation: &HeadAttestation,
+        stake: Option<BigUint>,
+        witness: Option<Witness>,
+    ) -> Result<(), AttestationError> {
+        if !attestation.valid() {
+            return Err(AttestationError::InvalidSignature {
+                head: attestation.head.to_str(),
+                publisher: attestation.publisher.to_str(),
+            });
+        }
+
+        let stake = match stake {
+            Some(stake) if stake.is_zero() => {
+                return Err(AttestationError::NoStake {
+                    head: attestation.head.to_str(),
+                    publisher: attestation.publisher.to_str(),
+                });
+            }
+            Some(stake) => stake,
+            None => BigUint::zero(),
+        };
+
+        match self.latest.get(&attestation.publisher) {
+            Some((latest, _, _)) if latest.height > attestation.height => (),
+            _ => {
+                self.latest
+                    .insert(attestation.publisher, (attestation.clone(), stake, witness));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the balance root that enough publishers vouch for as that of the state resulting from the transaction with
+    /// the given hash, if there is one. Publishers only count towards the state that their latest attestation vouches
+    /// for.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction
+    pub fn attested_root(&self, hash: &Hash) -> Option<Hash> {
+        let mut roots: HashMap<Hash, (usize, BigUint, Witnesses)> = HashMap::new();
+
+        for (attestation, stake, witness) in self.latest.values() {
+            let state = match &attestation.state {
+                Some(state) if state.hash == *hash => state,
+                _ => continue,
+            };
+
+            let (publishers, backing, witnesses) = roots
+                .entry(state.balance_root)
+                .or_insert_with(|| (0, BigUint::zero(), Witnesses::default()));
+            *publishers += 1;
+            *backing += stake;
+
+            if let Some(witness) = witness {
+                witnesses.add(witness.clone());
+            }
+        }
+
+        roots
+            .into_iter()
+            .filter(|(_, (publishers, backing, witnesses))| {
+                *publishers >= self.policy.min_publishers
+                    && *backing >= self.policy.min_stake
+                    && self.policy.diversity.admits(witnesses)
+            })
+            .max_by(|(a_root, (_, a, _)), (b_root, (_, b, _))| {
+                a.cmp(b).then((**b_root).cmp(&**a_root))
+            })
+            .map(|(root, _)| root)
+    }
+
+    /// Gets the highest head with enough backing to be synchronized towards, if there is one. Ties are broken in
+    /// favor of the head with the most stake behind it.
+    pub fn target(&self) -> Option<AttestedHead> {
+        let mut heads: HashMap<(Hash, u64), (AttestedHead, Witnesses)> = HashMap::new();
+
+        for (attestation, stake, witness) in self.latest.values() {
+            let (head, height) = (attestation.head, attestation.height);
+            let (attested, witnesses) = heads.entry((head, height)).or_insert_with(|| {
+                (
+                    AttestedHead {
+                        head,
+                        height,
+                        publishers: 0,
+                        stake: BigUint::zero(),
+                        peers: 0,
+                        subnets: 0,
+                    },
+                    Witnesses::default(),
+                )
+            });
+            attested.publishers += 1;
+            attested.stake += stake;
+
+            if let Some(witness) = witness {
+                witnesses.add(witness.clone());
+            }
+        }
+
+        heads
+            .into_iter()
+            .filter(|(_, (attested, witnesses))| {
+                attested.publishers >= self.policy.min_publishers
+                    && attested.stake >= self.policy.min_stake
+                    && self.policy.diversity.admits(witnesses)
+            })
+            .map(|(_, (mut attested, witnesses))| {
+                attested.peers = witnesses.peers();
+                attested.subnets = witnesses.subnets();
+
+                attested
+            })
+            .max_by(|a, b| {
+                a.height
+                    .cmp(&b.height)
+                    .then(a.stake.cmp(&b.stake))
+                    .then((*b.head).cmp(&*a.head))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    use rand::rngs::OsRng; // Import the os's rng
+
+    #[test]
+    fn test_head_attestations() {
+        let mut csprng = OsRng {};
+        let publishers: Vec<ed25519_dalek::Keypair> = (0..3)
+            .map(|_| ed25519_dalek::Keypair::generate(&mut csprng))
+            .collect();
+
+        let (old_head, new_head) = (blake3::hash_slice(b"old"), blake3::hash_slice(b"new"));
+
+        // Attestations only count if they're signed by the publisher that they name
+        let mut forged = HeadAttestation::new(new_head, 10, None, &publishers[0]);
+        assert!(forged.valid());
+        forged.publisher = Address::from_public_key(&publishers[1].public);
+        assert!(!forged.valid());
+
+        let mut attestations = HeadAttestations::new(AttestationPolicy::default());
+        assert!(attestations
+            .record(&forged, Some(BigUint::from(1 as u8)), None)
+            .is_err());
+
+        // A single publisher isn't enough, nor are publishers that hold nothing
+        let attestation = HeadAttestation::from_bytes(
+            &HeadAttestation::new(new_head, 10, None, &publishers[0]).to_bytes(),
+        )
+        .unwrap();
+        let host = |peer: &str| Some(Witness::new(peer.to_owned(), "203.0.113.7".parse().ok()));
+        attestations
+            .record(&attestation, Some(BigUint::from(5 as u8)), host("a"))
+            .unwrap();
+        assert!(attestations
+            .record(
+                &HeadAttestation::new(new_head, 10, None, &publishers[1]),
+                Some(BigUint::zero()),
+                None
+            )
+            .is_err());
+        assert_eq!(attestations.target(), None);
+
+        attestations
+            .record(
+                &HeadAttestation::new(new_head, 10, None, &publishers[1]),
+                Some(BigUint::from(7 as u8)),
+                host("b"),
+            )
+            .unwrap();
+        attestations
+            .record(
+                &HeadAttestation::new(old_head, 9, None, &publishers[2]),
+                Some(BigUint::from(100 as u8)),
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            attestations.target(),
+            Some(AttestedHead {
+                head: new_head,
+                height: 10,
+                publishers: 2,
+                stake: BigUint::from(12 as u8),
+                peers: 2,
+                subnets: 1,
+            })
+        );
+
+        // Replaying an older attestation doesn't take a publisher's vote away from its latest head
+        attestations
+            .record(
+                &HeadAttestation::new(old_head, 9, None, &publishers[1]),
+                Some(BigUint::from(7 as u8)),
+                host("b"),
+            )
+            .unwrap();
+        assert_eq!(attestations.target().unwrap().head, new_head);
+
+        // Heads can be held to a minimum stake, too
+        let mut strict = HeadAttestations::new(AttestationPolicy {
+            min_publishers: 2,
+            min_stake: BigUint::from(13 as u8),
+            diversity: DiversityPolicy::default(),
+        });
+        strict.latest = attestations.latest.clone();
+        assert_eq!(strict.target(), None);
+
+        // And to a number of distinct subnets, so that one host running many peers can't vouch for a head alone
+        let mut diverse = HeadAttestations::new(AttestationPolicy {
+            diversity: DiversityPolicy {
+                min_peers: 2,
+                min_subnets: 2,
+            },
+            ..AttestationPolicy::default()
+        });
+        diverse.latest = attestations.latest.clone();
+        assert_eq!(diverse.target(), None);
+    }
+
+    #[test]
+    fn test_attested_root() {
+        let mut csprng = OsRng {};
+        let publishers: Vec<ed25519_dalek::Keypair> = (0..3)
+            .map(|_| ed25519_dalek::Keypair::generate(&mut csprng))
+            .collect();
+
+        let (head, executed) = (blake3::hash_slice(b"head"), blake3::hash_slice(b"executed"));
+        let state = |root: &[u8]| {
+            Some(AttestedState {
+                hash: executed,
+                balance_root: blake3::hash_slice(root),
+            })
+        };
+
+        // Attestations that don't vouch for a state keep the layout (and signature) that they had before they could
+        let attestation = HeadAttestation::new(head, 10, None, &publishers[0]);
+        let legacy = bincode::serialize(&LegacyHeadAttestation {
+            head,
+            height: 10,
+            publisher: attestation.publisher,
+            signature: attestation.signature.clone(),
+        })
+        .unwrap();
+        assert!(HeadAttestation::from_bytes(&legacy).unwrap().valid());
+
+        // A light client can't weigh publishers by their stake, but still needs enough of them to agree on a root
+        let mut attestations = HeadAttestations::new(AttestationPolicy::default());
+        for (publisher, root) in publishers
+            .iter()
+            .zip(&[&b"honest"[..], b"honest", b"forged"])
+        {
+            let attestation = HeadAttestation::from_bytes(
+                &HeadAttestation::new(head, 10, state(root), publisher).to_bytes(),
+            )
+            .unwrap();
+            assert!(attestation.valid());
+
+            attestations.record(&attestation, None, None).unwrap();
+            if publisher.public == publishers[0].public {
+                assert_eq!(attestations.attested_root(&executed), None);
+            }
+        }
+        assert_eq!(
+            attestations.attested_root(&executed),
+            Some(blake3::hash_slice(b"honest"))
+        );
+        assert_eq!(attestations.attested_root(&head), None);
+
+        // Publishers holding no known stake can't meet a minimum stake
+        let mut strict = HeadAttestations::new(AttestationPolicy {
+            min_stake: BigUint::from(1 as u8),
+            ..AttestationPolicy::default()
+        });
+        strict.latest = attestations.latest.clone();
+        assert_eq!(strict.attested_root(&executed), None);
+    }
+}
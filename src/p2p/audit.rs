@@ -0,0 +1,108 @@
+use libp2p::PeerId; // Import the libp2p peer identity type
+
+use super::super::core::sys::invariants::DEFAULT_AUDIT_INTERVAL; // Import the default audit interval
+
+/// Keeps track of the transactions synchronized from the network since the ledger's invariants were last found to
+/// hold, along with the peers that supplied them, so that a branch breaking the invariants can be discarded as a
+/// whole and its suppliers flagged.
+pub struct SyncAudit {
+    /// The number of synchronized transactions between checks of the ledger's invariants
+    interval: usize,
+
+    /// The number of transactions that the ledger held when the invariants were last found to hold, if any
+    /// transactions have been synchronized since
+    verified_height: Option<usize>,
+
+    /// The number of transactions synchronized since the invariants were last found to hold
+    pending: usize,
+
+    /// Each of the peers that supplied a transaction synchronized since the invariants were last found to hold
+    suppliers: Vec<PeerId>,
+}
+
+impl Default for SyncAudit {
+    /// Checks the ledger's invariants every DEFAULT_AUDIT_INTERVAL synchronized transactions.
+    fn default() -> Self {
+        Self::new(DEFAULT_AUDIT_INTERVAL)
+    }
+}
+
+/// Implement a set of sync audit helper methods.
+impl SyncAudit {
+    /// Makes an audit checking the ledger's invariants every given number of synchronized transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The number of synchronized transactions between checks
+    pub fn new(interval: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            verified_height: None,
+            pending: 0,
+            suppliers: Vec::new(),
+        }
+    }
+
+    /// Records a transaction synchronized from the network. Results in whether or not the ledger's invariants are due
+    /// to be checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_before` - The number of transactions that the ledger held before the transaction was added
+    /// * `supplier` - The peer that supplied the transaction, if it's known
+    pub fn record(&mut self, height_before: usize, supplier: Option<&PeerId>) -> bool {
+        if self.verified_height.is_none() {
+            self.verified_height = Some(height_before);
+        }
+
+        if let Some(peer) = supplier {
+            if !self.suppliers.contains(peer) {
+                self.suppliers.push(peer.clone());
+            }
+        }
+
+        self.pending += 1;
+
+        self.pending >= self.interval
+    }
+
+    /// Records that the ledger's invariants were found to hold.
+    pub fn passed(&mut self) {
+        self.verified_height = None;
+        self.pending = 0;
+        self.suppliers.clear();
+    }
+
+    /// Records that the ledger's invariants were found to be broken. Results in the number of transactions that the
+    /// ledger held when they last held, along with each of the peers that supplied a transaction since.
+    pub fn failed(&mut self) -> (usize, Vec<PeerId>) {
+        let verified_height = self.verified_height.take().unwrap_or_default();
+        self.pending = 0;
+
+        (verified_height, self.suppliers.drain(..).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_sync_audit() {
+        let (honest, liar) = (PeerId::random(), PeerId::random());
+        let mut audit = SyncAudit::new(2);
+
+        assert!(!audit.record(10, Some(&honest)));
+        assert!(audit.record(11, Some(&honest)));
+        audit.passed();
+
+        // A broken branch is traced back to the last height that was found to hold, and to everyone who supplied it
+        assert!(!audit.record(12, Some(&honest)));
+        assert!(audit.record(13, Some(&liar)));
+        assert_eq!(audit.failed(), (12, vec![honest, liar]));
+
+        // The next branch starts afresh
+        assert!(!audit.record(12, None));
+        assert_eq!(audit.failed(), (12, Vec::new()));
+    }
+}
@@ -1,24 +1,47 @@
 use super::super::core::{
     sys::{
         config::{self, Config},
+        config_sync::ConfigRecord,
         system::{self, System},
+        voting_policy::VotingAccountChange,
     },
     types::{
         genesis,
+        issuance::ISSUANCE_PARAM,
+        light::{HeaderChain, LightHeader},
         receipt::{Receipt, ReceiptMap},
+        snapshot::{self, SnapshotAssembly},
         transaction::Transaction,
     },
 }; // Import the system module
 use super::super::crypto::blake3; // Import the blake3 hashing module
 use super::super::{
     accounts::account::{self, Account},
-    common::address::Address,
+    common::{address::Address, diversity::Witness, lock::InstrumentedRwLock},
 };
 use super::{
+    attestation::{AttestationPolicy, AttestedState, HeadAttestation, HeadAttestations},
+    audit::SyncAudit,
+    compression,
+    connections::{ConnectionTable, ConnectionTracker},
+    dht::DhtConfig,
+    envelope::{self, ReplayGuard},
     floodsub,
+    latency::LatencyTable,
+    listen,
     network::{self, Network},
+    parents::ParentFetcher,
+    psk::{self, SwarmKey},
+    publisher::{self, PublishQueue},
+    pubsub::GatedFloodsub,
+    reconnect::{self, ReconnectAction, Reconnector},
+    relay::{self, RelayPolicy},
     sync,
+    sync_peers::SyncPeerTable,
+    wal::WriteAheadLog,
+    webhook::{Notification, Webhook, WebhookConfig, WebhookDispatcher},
 };
+use chrono::Utc;
 use num::Zero;
 use std::{
     convert::TryInto,
@@ -29,16 +52,16 @@ use std::{
         Arc, RwLock,
     },
     task::{Context, Poll},
+    time::{Duration, Instant},
 }; // Allow libp2p to implement the write() helper method.
 
 use libp2p::{
-    floodsub::{Floodsub, Topic},
     futures::StreamExt,
     identify::Identify,
     identity, kad,
     kad::{
         record::{store::MemoryStore, Key},
-        Kademlia, KademliaConfig, Quorum, Record,
+        Kademlia, Quorum, Record,
     },
     mdns::Mdns,
     ping::{Ping, PingConfig},
@@ -169,7 +192,7 @@ impl<T> From<std::sync::PoisonError<T>> for CommunicationError {
 #[derive(NetworkBehaviour)]
 pub struct ClientBehavior {
     /// Some pubsub mechanism bound to the above transport
-    pub(crate) gossipsub: Floodsub,
+    pub(crate) gossipsub: GatedFloodsub,
 
     /// Some mDNS service bound to the above transport
     pub(crate) mdns: Mdns,
@@ -183,9 +206,12 @@ pub struct ClientBehavior {
     /// Allow the client to inform its peers of its identity
     pub(crate) identification: Identify,
 
+    /// Keep track of the client's open connections, so that they can be listed over RPC
+    pub(crate) connections: ConnectionTracker,
+
     /// Allow for a state to be maintained inside the client behavior
     #[behaviour(ignore)]
-    pub(crate) runtime: Arc<RwLock<System>>,
+    pub(crate) runtime: Arc<InstrumentedRwLock<System>>,
 
     /// The accounts that the client will use to vote on proposals
     #[behaviour(ignore)]
@@ -199,6 +225,10 @@ pub struct ClientBehavior {
     #[behaviour(ignore)]
     pub(crate) network: Network,
 
+    /// The protocol name that members of the network identify themselves with
+    #[behaviour(ignore)]
+    pub(crate) protocol_prefix: String,
+
     /// Whether or not the transaction queue contains proposals that have not yet been evaluated or
     /// published
     #[behaviour(ignore)]
@@ -207,6 +237,90 @@ pub struct ClientBehavior {
     /// The index of the last published node
     #[behaviour(ignore)]
     last_published_tx: usize,
+
+    /// Whether or not an empty ledger should be picked up from the network's latest snapshot, rather than from its
+    /// genesis
+    #[behaviour(ignore)]
+    pub(crate) fast_sync: bool,
+
+    /// The snapshot being downloaded to pick the ledger up from, if any
+    #[behaviour(ignore)]
+    pub(crate) snapshot_download: Option<SnapshotAssembly>,
+
+    /// The number of transactions added to the ledger between the snapshots that the client publishes, or zero if it
+    /// doesn't publish any
+    #[behaviour(ignore)]
+    snapshot_interval: u64,
+
+    /// The height of the ledger when the client last published a snapshot of it
+    #[behaviour(ignore)]
+    last_snapshot_height: u64,
+
+    /// The last time that the client polled the network for headers past its head, if it's a light client
+    #[behaviour(ignore)]
+    last_header_poll: Instant,
+
+    /// The latest version of the network's config that the client knows of, and the signatures vouching for it
+    #[behaviour(ignore)]
+    pub(crate) config_record: Option<ConfigRecord>,
+
+    /// A log of incoming gossip messages that have not yet been fully processed
+    #[behaviour(ignore)]
+    pub(crate) wal: Option<WriteAheadLog>,
+
+    /// The pubsub topics for the network that the behavior is listening on
+    #[behaviour(ignore)]
+    pub(crate) topics: floodsub::Topics,
+
+    /// The policy used to filter the proposals and votes that pass through the client when it
+    /// doesn't vote itself
+    #[behaviour(ignore)]
+    pub(crate) relay_policy: RelayPolicy,
+
+    /// The measured round-trip times of the client's peers, used to leave slow peers out of quorums, and to handle
+    /// the records published by fast peers first
+    #[behaviour(ignore)]
+    pub(crate) latencies: LatencyTable,
+
+    /// The queue that outgoing gossip waits in, so that it goes out at a steady rate
+    #[behaviour(ignore)]
+    pub(crate) publisher: PublishQueue,
+
+    /// What we know about how synchronization with each of the client's peers is going
+    #[behaviour(ignore)]
+    pub(crate) sync_peers: Arc<RwLock<SyncPeerTable>>,
+
+    /// The keypair that the client seals the proposals it publishes with
+    #[behaviour(ignore)]
+    pub(crate) envelope_keypair: ed25519_dalek::Keypair,
+
+    /// The stale and replayed proposals and votes that the client refuses
+    #[behaviour(ignore)]
+    pub(crate) replay_guard: ReplayGuard,
+
+    /// The latest head vouched for by each publisher on the network
+    #[behaviour(ignore)]
+    pub(crate) head_attestations: HeadAttestations,
+
+    /// The transactions synchronized since the ledger's supply invariants were last checked, and who supplied them
+    #[behaviour(ignore)]
+    pub(crate) sync_audit: SyncAudit,
+
+    /// The votes held off until the parents of the transactions being voted on have been fetched from peers
+    #[behaviour(ignore)]
+    pub(crate) parent_fetcher: ParentFetcher,
+
+    /// The bootstrap peers that are dialed again, backing off between rounds, while the node has no peers
+    #[behaviour(ignore)]
+    pub(crate) reconnector: Reconnector,
+
+    /// The webhook that detected double spends are POSTed to, if the operator gave one
+    #[behaviour(ignore)]
+    pub(crate) double_spend_webhook: Option<Webhook>,
+
+    /// The webhooks that executions, changes in synchronization & double spends are POSTed to
+    #[behaviour(ignore)]
+    pub(crate) webhooks: WebhookDispatcher,
 }
 
 impl ClientBehavior {
@@ -231,10 +345,60 @@ impl ClientBehavior {
         self.kad_dht.kbuckets_entries().size_hint().0
     }
 
-    /// Gets a quorum for an acceptable majority of the active subset of the network.
+    /// Describes the given peer as a witness to the votes or records that it relays, along with the subnet that it's
+    /// reachable in, if the DHT knows an address for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer
+    pub(crate) fn witness(&mut self, peer: &PeerId) -> Witness {
+        let ip = libp2p::swarm::NetworkBehaviour::addresses_of_peer(&mut self.kad_dht, peer)
+            .iter()
+            .find_map(listen::ip);
+
+        Witness::new(peer.to_base58(), ip)
+    }
+
+    /// Gets the number of connected peers that answer pings quickly enough to be worth waiting on.
+    pub fn responsive_peers(&mut self) -> usize {
+        let latencies = &self.latencies;
+
+        // Leave out any peers that are consistently slow or unresponsive
+        self.kad_dht
+            .kbuckets_entries()
+            .filter(|key| latencies.is_responsive(key.preimage()))
+            .count()
+    }
+
+    /// Orders the given records so that those published by the fastest peers come first, so that synchronization
+    /// follows low-latency peers wherever several peers answer with a record. Records from unresponsive or unknown
+    /// publishers keep their order, after the rest.
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - The records that should be ordered
+    pub fn rank_records(&self, records: &mut [Record]) {
+        let ranked = self.latencies.rank(
+            records
+                .iter()
+                .filter_map(|record| record.publisher.as_ref()),
+        );
+
+        records.sort_by_key(|record| {
+            record
+                .publisher
+                .as_ref()
+                .and_then(|publisher| ranked.iter().position(|peer| peer == publisher))
+                .unwrap_or(ranked.len())
+        });
+    }
+
+    /// Gets a quorum for an acceptable majority of the active subset of the network. Only
+    /// responsive peers are counted, so that record fetches complete once the fast peers have
+    /// answered, rather than waiting on peers that are unlikely to answer in time.
     pub fn active_subset_quorum(&mut self) -> Quorum {
-        // Get the number of active peers in the network
-        let n_peers = self.active_peers();
+        // Get the number of responsive peers in the network
+        let n_peers = self.responsive_peers();
 
         // Construct a quorum for at least 1/2 of the network
         Quorum::N(
@@ -243,13 +407,151 @@ impl ClientBehavior {
         )
     }
 
+    /// Decides each of the proposals that gathered enough votes before their minimum voting period had passed, now
+    /// that their period has passed.
+    pub fn clear_matured_proposals(&mut self) {
+        let matured = if let Ok(rt) = self.runtime.read() {
+            rt.matured_proposals()
+        } else {
+            return;
+        };
+
+        for proposal in matured {
+            if let Ok(rt) = self.runtime.write() {
+                if floodsub::potentially_clear_proposal(rt, &proposal) {
+                    info!("Successfully cleared proposal {}!", proposal);
+
+                    self.publish_dag();
+                }
+            }
+        }
+    }
+
+    /// Measures the ledger's database and the room left on its disk, making room (or pausing writes that consensus
+    /// can do without) if need be.
+    pub fn check_storage(&mut self) {
+        if let Ok(mut rt) = self.runtime.write() {
+            rt.check_storage();
+        }
+    }
+
+    /// Keeps track of how well the node is connected to the network, bootstrapping the DHT again once connectivity is
+    /// regained after the node was left without any peers. Results in whether or not the bootstrap peers should be
+    /// dialed again.
+    ///
+    /// # Arguments
+    ///
+    /// * `connected_peers` - The number of peers that the node is connected to
+    pub fn maintain_connectivity(&mut self, connected_peers: usize) -> bool {
+        let synchronized = match (self.sync_peers.read(), self.runtime.read()) {
+            (Ok(sync_peers), Ok(rt)) => {
+                sync_peers
+                    .status(
+                        rt.ledger.nodes.len(),
+                        rt.ledger.nodes.last().map(|node| node.hash),
+                        |hash| rt.ledger.hash_routes.contains_key(hash),
+                    )
+                    .synchronized
+            }
+            _ => return false,
+        };
+
+        let before = self.reconnector.status();
+        let action = self
+            .reconnector
+            .tick(connected_peers, synchronized, Utc::now());
+        let after = self.reconnector.status();
+
+        if before != after {
+            info!(
+                "Network connectivity changed from {:?} to {:?}",
+                before, after
+            );
+
+            if let Ok(mut sync_peers) = self.sync_peers.write() {
+                sync_peers.record_connectivity(after);
+            }
+        }
+
+        match action {
+            ReconnectAction::Redial => {
+                info!("Not connected to any peers; dialing the bootstrap nodes again...");
+
+                true
+            }
+            ReconnectAction::Rebootstrap => {
+                info!("Regained connectivity; bootstrapping the network DHT again");
+
+                self.kad_dht.bootstrap();
+                self.synchronize_dag();
+                self.synchronize_config();
+
+                false
+            }
+            ReconnectAction::Wait => false,
+        }
+    }
+
+    /// Hands each of the double spends detected & proposals executed by the runtime to the operator's webhooks, along
+    /// with any change in whether or not the node has fallen behind the network.
+    pub fn forward_notifications(&mut self) {
+        let mut rt = if let Ok(rt) = self.runtime.write() {
+            rt
+        } else {
+            return;
+        };
+
+        let alerts = rt.take_double_spend_alerts();
+        let executions = rt.take_executions();
+
+        if let Some(webhook) = &self.double_spend_webhook {
+            for alert in &alerts {
+                webhook.notify(alert);
+            }
+        }
+
+        if self.webhooks.is_empty() {
+            return;
+        }
+
+        for (execution, tx) in executions {
+            // Itemize the transaction's movements of value, so that watched accounts paid by a batch are caught too
+            let items = tx
+                .as_ref()
+                .and_then(|tx| rt.ledger.get_receipt(tx.hash).ok().flatten())
+                .map_or_else(Vec::new, |receipt| receipt.items);
+
+            for notification in self.webhooks.executed(&execution, tx.as_ref(), items) {
+                self.webhooks.dispatch(&notification);
+            }
+        }
+
+        for alert in alerts {
+            self.webhooks.dispatch(&Notification::DoubleSpend { alert });
+        }
+
+        let height = rt.ledger.height();
+        drop(rt); // Let go of the runtime before touching the sync peers
+
+        let target = if let Ok(sync_peers) = self.sync_peers.read() {
+            sync_peers.target_head().cloned()
+        } else {
+            return;
+        };
+
+        if let Some(notification) = self.webhooks.sync_progress(height, target.as_ref()) {
+            self.webhooks.dispatch(&notification);
+        }
+    }
+
     /// Checks whether or not the proposal queue contains any new unpublished proposals.
     pub fn transaction_queue_is_empty(&self) -> bool {
         !self.proposal_queue_full.load(Ordering::SeqCst)
     }
 
-    /// Checks the transaction queue for any unpublished proposals, and publishes any applicable
-    /// proposals.
+    /// Checks the transaction queue for any unpublished proposals, votes, or transactions, and
+    /// queues as many of them for publishing as the publishing queue has room for. Anything that
+    /// doesn't fit is left in the runtime until the queue drains.
     pub fn clear_transaction_queue(&mut self) {
         // We should only go through with publishing the items contained in the transaction queue
         // if the queue actually contains something
@@ -265,63 +567,281 @@ impl ClientBehavior {
             return;
         };
 
-        // Alert the user of the new proposals
-        info!(
-            "Publishing {} new proposals...",
-            rt.localized_proposals.len()
-        );
+        // Start or stop voting with any accounts that the operator has added or removed through the RPC API
+        for change in rt.take_voting_account_changes() {
+            match change {
+                VotingAccountChange::Add(acc) => {
+                    let address = acc.address().ok();
+
+                    if !self
+                        .voting_accounts
+                        .iter()
+                        .any(|existing| existing.address().ok() == address)
+                    {
+                        self.voting_accounts.push(acc);
+                    }
+                }
+                VotingAccountChange::Remove(address) => self
+                    .voting_accounts
+                    .retain(|existing| existing.address().ok() != Some(address)),
+            }
+
+            rt.active_voters = self
+                .voting_accounts
+                .iter()
+                .filter_map(|acc| acc.address().ok())
+                .collect();
+            info!("Now voting with {} accounts", rt.active_voters.len());
+        }
+
+        // Hold on to the proposals until the disk takes writes again, since they couldn't be executed anyway
+        if rt.is_read_only() {
+            return;
+        }
+
+        // Get the list of proposals that haven't been published yet, leaving behind any that don't fit in the queue
+        let unpublished_proposals: Vec<_> = rt
+            .localized_proposals
+            .iter()
+            .take(self.publisher.remaining())
+            .map(|(id, prop)| (*id, prop.clone()))
+            .collect();
+
+        if !unpublished_proposals.is_empty() {
+            // Alert the user of the new proposals
+            info!(
+                "Publishing {} new proposals...",
+                unpublished_proposals.len()
+            );
+        }
 
-        // Get the list of proposals that haven't been published yet
-        let unpublished_proposals = rt.localized_proposals.clone();
+        // Our own mints go unheard by our own floodsub listener, so they have to be voted on here
+        let mut mints = Vec::new();
 
-        // Publish each proposal
+        // Queue each proposal for publishing
         for (i, (id, prop)) in unpublished_proposals.into_iter().enumerate() {
-            // Try to serialize the proposal. If this succeeds, we can try to publish the
+            // Try to seal the proposal. If this succeeds, we can try to publish the
             // proposal.
-            if let Ok(ser) = bincode::serialize(&prop) {
+            if let Ok(ser) =
+                envelope::seal(self.topics.proposals.id(), &prop, &self.envelope_keypair)
+            {
                 // We've got a serialized proposal; publish it
-                self.gossipsub
-                    .publish(Topic::new(floodsub::PROPOSALS_TOPIC.to_owned()), ser);
+                self.publisher
+                    .push(self.topics.proposals.clone(), compression::seal(&ser));
 
                 // Propose the proposal
                 match rt.propose_proposal(&id) {
                     Ok(_) => info!("Successfully proposed proposal {}: {}", i, prop.proposal_id),
                     Err(e) => warn!("Failed to propose proposal {}: {}", prop.proposal_id, e),
                 }
+
+                if prop.proposal_data.param_name == ISSUANCE_PARAM {
+                    mints.push(prop);
+                }
             } else {
                 warn!(
                     "Failed to serialize proposal with hash: {}",
                     prop.proposal_id
                 );
+
+                // Don't try to publish the proposal again
+                rt.localized_proposals.remove(&id);
             }
         }
 
-        // Clear the runtime of all pending local proposals
-        rt.clear_localized_proposals();
+        // Publish each of the votes that the operator has cast through the RPC API
+        let votes = rt.take_localized_votes(self.publisher.remaining());
+        if !votes.is_empty() {
+            info!("Publishing {} new votes...", votes.len());
+
+            floodsub::publish_votes(votes, &mut self.publisher, &self.topics.votes);
+        }
+
+        // Take each of the raw transactions submitted through the RPC API that fit in the queue
+        let txs = rt.take_localized_transactions(self.publisher.remaining());
+
+        // Only stop checking the runtime once everything has made it into the queue
+        match rt.localized_backlog() {
+            0 => rt.clear_localized_proposals(),
+            held_back => debug!(
+                "Holding back {} messages until the publishing queue drains",
+                held_back
+            ),
+        }
+
+        // Payloads that we've already stored can be left out of the gossip, since peers holding the same ledger will
+        // have stored them too
+        let payloads = rt.ledger.payload_store().ok();
+
+        // Let go of the runtime so that the transactions can be handled like any other incoming transaction
+        drop(rt);
+
+        if !txs.is_empty() {
+            info!("Publishing {} new transactions...", txs.len());
+        }
+
+        // Gossip each transaction, and wrap it in a proposal of our own
+        for tx in txs {
+            let mut gossiped = tx.clone();
+            if let Some(payloads) = &payloads {
+                if let Err(e) = payloads.deflate_known(&mut gossiped) {
+                    warn!("Failed to deflate the payload of {}: {}", tx.hash, e);
+                }
+            }
+
+            match bincode::serialize(&gossiped) {
+                Ok(ser) => {
+                    self.publisher
+                        .push(self.topics.transactions.clone(), compression::seal(&ser));
+                }
+                Err(e) => {
+                    warn!("Failed to serialize transaction {}: {}", tx.hash, e);
+
+                    continue;
+                }
+            }
+
+            self.handle_transaction(tx);
+        }
+
+        // Vote on each of the mints that we've just proposed
+        for mint in mints {
+            self.handle_proposal(mint);
+        }
+    }
+
+    /// Publishes each of the messages in the publishing queue that the publishing rate allows,
+    /// and makes sure that the swarm is polled again once more may be published.
+    ///
+    /// # Arguments
+    ///
+    /// * `cx` - The context of the task polling the swarm
+    pub fn flush_publish_queue(&mut self, cx: &mut Context) {
+        for msg in self.publisher.poll_ready(cx) {
+            self.gossipsub.publish(msg.topic, msg.payload);
+        }
+    }
+
+    /// Publishes the latest version of the network's config, if governance has changed it, vouching for it with each
+    /// of the client's voting accounts.
+    pub fn publish_config(&mut self) {
+        // The config that the network started out with is in everyone's genesis already, so there's nothing to
+        // publish until a proposal has changed it
+        let (config, proposal) = match self.runtime.read() {
+            Ok(rt) if !rt.is_read_only() && rt.config.sequence != 0 => {
+                match rt.config_proposal.clone() {
+                    Some(proposal) => (rt.config.clone(), proposal),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+
+        // Start over once the config has moved on, keeping any signatures gathered for the current version so far
+        let mut record = match self.config_record.take() {
+            Some(record)
+                if record.describes(&config)
+                    && record.proposal.proposal_id == proposal.proposal_id =>
+            {
+                record
+            }
+            _ => ConfigRecord::new(&config, &proposal),
+        };
+
+        let mut signed = false;
+        for keypair in self
+            .voting_accounts
+            .iter()
+            .filter_map(|acc| acc.keypair().ok())
+        {
+            signed |= record.sign(&keypair);
+        }
+
+        self.config_record = Some(record);
+
+        // Only bother the network if we've got something to add
+        if signed {
+            self.put_config_record();
+        }
+    }
+
+    /// Puts the latest version of the network's config that the client knows of in the DHT, along with each of the
+    /// signatures vouching for it.
+    pub fn put_config_record(&mut self) {
+        let q: Quorum = self.active_subset_quorum();
+
+        if let Some(record) = &self.config_record {
+            debug!(
+                "Publishing version {} of the config, vouched for by {} account(s)",
+                record.sequence(),
+                record.signers().len()
+            );
+
+            self.kad_dht.put_record(
+                Record::new(
+                    Key::new(&sync::CONFIG_KEY),
+                    compression::seal(&record.to_bytes()),
+                ),
+                q,
+            );
+        }
     }
 
     /// Publishes a copy of the DAG to the remote.
     pub fn publish_dag(&mut self) {
+        // Let the network know about any changes that governance has made to the config along the way
+        self.publish_config();
+
         // Get a quorum for at least 1/2 of the network
         let q: Quorum = self.active_subset_quorum();
 
         // Try to get a lock on the runtime ref that we generated earlier, so we can kick off synchronization
         if let Ok(runtime) = self.runtime.read() {
-            // If the DAG is empty, we can't really publish anything
-            if runtime.ledger.nodes.is_empty() {
+            // If the DAG is empty, we can't really publish anything, and a node that can't write to its disk
+            // shouldn't be vouching for its copy of the DAG
+            if runtime.ledger.nodes.is_empty() || runtime.is_read_only() {
                 return;
             }
 
-            debug!("Broadcasting root transaction");
+            // A ledger picked up from a snapshot doesn't hold the root transaction, so it can't hand it on
+            if runtime.ledger.base_height() == 0 {
+                debug!("Broadcasting root transaction");
 
-            // Broadcast the local node's current root transaction to the network
-            self.kad_dht.put_record(
-                Record::new(
-                    Key::new(&sync::ROOT_TRANSACTION_KEY),
-                    runtime.ledger.nodes[0].hash.to_vec(),
-                ),
-                q,
-            );
+                // Broadcast the local node's current root transaction to the network
+                self.kad_dht.put_record(
+                    Record::new(
+                        Key::new(&sync::ROOT_TRANSACTION_KEY),
+                        compression::seal(&*runtime.ledger.nodes[0].hash),
+                    ),
+                    q,
+                );
+            }
+
+            // Vouch for our head with the first of our voting accounts, so that peers can weigh it by our stake.
+            // Nodes that don't vote hold no stake worth counting, so they don't bother. Light clients check balances
+            // against the state that we vouch for alongside it.
+            if let Some(Ok(keypair)) = self.voting_accounts.first().map(|acc| acc.keypair()) {
+                let state = runtime.ledger.obtain_executed_head().and_then(|head| {
+                    head.state_entry.as_ref().map(|entry| AttestedState {
+                        hash: head.hash,
+                        balance_root: entry.balance_root(),
+                    })
+                });
+                let attestation = HeadAttestation::new(
+                    runtime.ledger.nodes[runtime.ledger.nodes.len() - 1].hash,
+                    runtime.ledger.height(),
+                    state,
+                    &keypair,
+                );
+
+                self.kad_dht.put_record(
+                    Record::new(
+                        Key::new(&sync::HEAD_TRANSACTION_KEY),
+                        compression::seal(&attestation.to_bytes()),
+                    ),
+                    q,
+                );
+            }
 
             // Make sure the network has a full copy of the entire transaction history
             for i in self.last_published_tx..runtime.ledger.nodes.len() {
@@ -331,7 +851,7 @@ impl ClientBehavior {
                     self.kad_dht.put_record(
                         Record::new(
                             Key::new(&sync::next_transaction_key(runtime.ledger.nodes[i].hash)),
-                            runtime.ledger.nodes[i + 1].hash.to_vec(),
+                            compression::seal(&*runtime.ledger.nodes[i + 1].hash),
                         ),
                         q,
                     );
@@ -345,18 +865,114 @@ impl ClientBehavior {
                             Key::new(&sync::transaction_with_hash_key(
                                 runtime.ledger.nodes[i].hash,
                             )),
-                            node.transaction.to_bytes(),
+                            compression::seal(&node.transaction.to_bytes()),
                         ),
                         q,
                     );
+
+                    // Light clients only need the node's header
+                    let header =
+                        LightHeader::from_node(&node, runtime.ledger.base_height() + i as u64 + 1);
+                    self.kad_dht.put_record(
+                        Record::new(
+                            Key::new(&sync::light_header_key(header.hash)),
+                            compression::seal(&header.to_bytes()),
+                        ),
+                        q,
+                    );
+                }
+            }
+
+            // Headers published before their transactions were executed don't commit to any state, so hand light
+            // clients the state of the executed head
+            if let Some(header) = runtime.executed_light_header() {
+                for key in &[
+                    Key::new(&sync::LIGHT_HEAD_KEY),
+                    Key::new(&sync::light_header_key(header.hash)),
+                ] {
+                    self.kad_dht.put_record(
+                        Record::new(key.clone(), compression::seal(&header.to_bytes())),
+                        q,
+                    );
                 }
             }
 
             // Move the published head to the last published tx
             self.last_published_tx = runtime.ledger.nodes.len();
+
+            // Every so often, publish a snapshot of the ledger, so that new nodes can pick up from it rather than
+            // replaying everything before it
+            if self.snapshot_interval == 0
+                || runtime.ledger.height() < self.last_snapshot_height + self.snapshot_interval
+            {
+                return;
+            }
+
+            let taken = runtime.ledger.take_snapshot().and_then(|taken| {
+                if let Some(snapshot) = &taken {
+                    runtime.ledger.store_snapshot(snapshot)?;
+                }
+
+                Ok(taken)
+            });
+
+            match taken {
+                Ok(Some(snapshot)) => {
+                    let (manifest, chunks) = snapshot.chunk();
+
+                    info!(
+                        "Publishing snapshot {} of the ledger at height {}",
+                        manifest.id, manifest.height
+                    );
+
+                    // Publish the chunks before the manifest pointing to them
+                    for chunk in chunks {
+                        self.kad_dht.put_record(
+                            Record::new(
+                                Key::new(&sync::snapshot_chunk_key(blake3::hash_slice(&chunk))),
+                                compression::seal(&chunk),
+                            ),
+                            q,
+                        );
+                    }
+
+                    self.kad_dht.put_record(
+                        Record::new(
+                            Key::new(&sync::LATEST_SNAPSHOT_KEY),
+                            compression::seal(&manifest.to_bytes()),
+                        ),
+                        q,
+                    );
+
+                    self.last_snapshot_height = runtime.ledger.height();
+                }
+                Ok(None) => (),
+                Err(e) => warn!("Failed to take a snapshot of the ledger: {}", e),
+            }
         }
     }
 
+    /// Downloads the latest version of the network's config that the network knows of. It's only applied if it's
+    /// newer than the local config, and enough of the network's stake vouches for it.
+    pub fn synchronize_config(&mut self) {
+        let q: Quorum = self.active_subset_quorum();
+
+        self.kad_dht.get_record(&Key::new(&sync::CONFIG_KEY), q);
+    }
+
+    /// Polls the network for headers past the client's head every so often, if it's a light client.
+    pub fn poll_light_headers(&mut self) {
+        if self.last_header_poll.elapsed()
+            < Duration::from_secs(sync::LIGHT_HEADER_POLL_INTERVAL_SECS)
+            || !self.is_light()
+        {
+            return;
+        }
+
+        self.last_header_poll = Instant::now();
+        self.synchronize_dag();
+    }
+
     /// Downloads a copy of the remote DAG.
     pub fn synchronize_dag(&mut self) {
         // Get a quorum for at least 1/2 of the network
@@ -364,14 +980,61 @@ impl ClientBehavior {
 
         // Try to get a lock on the runtime ref that we generated earlier, so we can kick off synchronization
         if let Ok(runtime) = self.runtime.read() {
+            // Light clients only follow the DAG's headers, starting with the root's
+            if let Some(chain) = &runtime.header_chain {
+                // Balances are only checked against the states that full nodes vouch for in their attestations
+                self.kad_dht
+                    .get_record(&Key::new(&sync::HEAD_TRANSACTION_KEY), q);
+
+                match chain.head() {
+                    Some(head) => {
+                        self.kad_dht.get_record(&Key::new(&sync::LIGHT_HEAD_KEY), q);
+                        self.kad_dht
+                            .get_record(&Key::new(&sync::next_transaction_key(head.hash)), q);
+                    }
+                    None => {
+                        info!("Synchronizing root transaction header");
+
+                        self.kad_dht
+                            .get_record(&Key::new(&sync::ROOT_TRANSACTION_KEY), q);
+                    }
+                }
+
+                return;
+            }
+
             // If there aren't any nodes in the runtime's ledger instance, we'll have to start synchronizing from the very beginning
             if runtime.ledger.nodes.is_empty() {
+                // Carry on downloading the snapshot that the ledger is being picked up from, if there is one
+                if let Some(download) = &self.snapshot_download {
+                    for chunk in download.missing() {
+                        self.kad_dht
+                            .get_record(&Key::new(&sync::snapshot_chunk_key(chunk)), q);
+                    }
+
+                    return;
+                }
+
+                // Skip straight to the network's latest snapshot, if asked to
+                if self.fast_sync {
+                    info!("Looking for a snapshot of the ledger to synchronize from");
+
+                    self.kad_dht
+                        .get_record(&Key::new(&sync::LATEST_SNAPSHOT_KEY), q);
+
+                    return;
+                }
+
                 info!("Synchronizing root transaction");
 
                 // Fetch the hash of the first node from the network
                 self.kad_dht
                     .get_record(&Key::new(&sync::ROOT_TRANSACTION_KEY), q);
             } else {
+                // Find out which head the network vouches for, so that we know how far we have to go
+                self.kad_dht
+                    .get_record(&Key::new(&sync::HEAD_TRANSACTION_KEY), q);
+
                 // Start synchronizing from the last transaction that we got
                 self.kad_dht.get_record(
                     &Key::new(&sync::next_transaction_key(
@@ -387,7 +1050,7 @@ impl ClientBehavior {
 /// A network client.
 pub struct Client {
     /// The active SummerCash runtime environment
-    pub runtime: Arc<RwLock<system::System>>,
+    pub runtime: Arc<InstrumentedRwLock<system::System>>,
 
     /// The list of accounts used to vote on proposals
     pub voting_accounts: Option<Vec<account::Account>>,
@@ -400,6 +1063,61 @@ pub struct Client {
 
     /// The network that the client should connect to
     network: Network,
+
+    /// The protocol name that members of the network identify themselves with, and segregate their DHT by
+    pub protocol_prefix: String,
+
+    /// The thresholds used to filter relayed proposals and votes if the client has no voting accounts
+    pub relay_policy: relay::PolicyConfig,
+
+    /// The limits that outgoing gossip is held to
+    pub publisher: publisher::PublisherConfig,
+
+    /// The number of seconds after which gossiped proposals and votes are refused as stale
+    pub gossip_max_age_secs: u64,
+
+    /// The addresses that the client listens on. If there aren't any, the client listens on every IPv4 and IPv6
+    /// interface.
+    pub listen_addrs: Vec<Multiaddr>,
+
+    /// The addresses that peers should reach the client at, in addition to those it's listening on
+    pub external_addrs: Vec<Multiaddr>,
+
+    /// The key shared by each of the members of the network, if the network is private
+    pub psk: Option<SwarmKey>,
+
+    /// What we know about how synchronization with each of the client's peers is going
+    pub sync_peers: Arc<RwLock<SyncPeerTable>>,
+
+    /// The client's open connections, along with what its peers have told us about themselves
+    pub connections: Arc<RwLock<ConnectionTable>>,
+
+    /// How much backing a head published by the client's peers needs before the client synchronizes towards it
+    pub head_attestation_policy: AttestationPolicy,
+
+    /// How the client's Kademlia DHT is tuned
+    pub dht: DhtConfig,
+
+    /// The URL that detected double spends should be POSTed to, if any
+    pub double_spend_webhook: Option<String>,
+
+    /// The webhooks that executions, changes in synchronization & double spends should be POSTed to
+    pub webhooks: WebhookConfig,
+
+    /// Whether or not an empty ledger should be picked up from the network's latest snapshot, rather than replayed
+    /// from its genesis. The history before the snapshot is taken on trust from whoever published it.
+    pub fast_sync: bool,
+
+    /// The number of transactions added to the ledger between the snapshots that the client publishes, or zero if it
+    /// shouldn't publish any
+    pub snapshot_interval: u64,
+
+    /// Whether or not the client should run as a light client, only synchronizing the DAG's headers, and checking
+    /// balances proven by full nodes against them, rather than executing the DAG itself
+    pub light: bool,
+
+    /// The directory in which the client's data is stored
+    data_dir: String,
 }
 
 impl Into<String> for &Client {
@@ -450,35 +1168,25 @@ impl Client {
             }
         } else {
             let p2p_account = account::Account::new(); // Generate p2p account
-                                                       // Write p2p account to disk
 
-            match p2p_account.write_to_disk_with_name_at_data_directory("p2p_identity", data_dir) {
-                Ok(_) => {
-                    // Save the keypair as a normal account as well
-                    p2p_account.write_to_disk_at_data_directory(data_dir)?;
-
-                    // Check has valid p2p keypair
-                    if let Ok(p2p_keypair) = p2p_account.p2p_keypair() {
-                        Client::with_peer_id(
-                            network,
-                            identity::Keypair::Ed25519(p2p_keypair),
-                            data_dir,
-                        )
-                    // Return initialized client
-                    } else {
-                        Err(ConstructionError::InvalidPeerIdentity) // Return error
-                    }
-                }
-                _ => {
-                    // Check could get account address
-                    if let Ok(address) = p2p_account.address() {
-                        Err(ConstructionError::AccountIOFailure {
-                            address_hex: address.to_str(),
-                        }) // Return error
-                    } else {
-                        Err(ConstructionError::InvalidPeerIdentity) // Return error
-                    }
-                }
+            // Write p2p account to disk, saving the keypair as a normal account as well. A node whose disk doesn't take
+            // writes can still run, but under a new identity each time that it starts.
+            if let Err(e) = p2p_account
+                .write_to_disk_with_name_at_data_directory("p2p_identity", data_dir)
+                .and_then(|_| p2p_account.write_to_disk_at_data_directory(data_dir))
+            {
+                warn!(
+                    "Failed to save the peer identity ({}); running under a temporary identity",
+                    e
+                );
+            }
+
+            // Check has valid p2p keypair
+            if let Ok(p2p_keypair) = p2p_account.p2p_keypair() {
+                Client::with_peer_id(network, identity::Keypair::Ed25519(p2p_keypair), data_dir)
+            // Return initialized client
+            } else {
+                Err(ConstructionError::InvalidPeerIdentity) // Return error
             }
         }
     }
@@ -490,12 +1198,17 @@ impl Client {
         data_dir: &str,
     ) -> Result<Client, ConstructionError> {
         // Check for errors while reading config
-        if let Ok(read_config) = config::Config::read_from_disk(network.into()) {
+        if let Ok(read_config) = config::Config::read_from_disk(network.name()) {
             Ok(Client::with_config(keypair, read_config, data_dir)) // Return initialized client
         } else {
             let config = Config {
                 reward_per_gas: config::DEFAULT_REWARD_PER_GAS.into(),
                 network_name: network.into(),
+                issuance: Default::default(),
+                consensus: Default::default(),
+                council: Default::default(),
+                storage_mode: Default::default(),
+                sequence: 0,
             };
 
             Ok(Client::with_config(keypair, config, data_dir)) // Return initialized client
@@ -511,10 +1224,12 @@ impl Client {
             // Generate a new account to use for voting
             let acc: Account = Account::new();
 
-            // Save the voting account
-            if acc.write_to_disk_at_data_directory(data_dir).is_ok() {
-                voting_accounts.push(acc);
-            };
+            // Save the voting account. An account that can't be saved can't be voted with, since its votes couldn't
+            // be accounted for after a restart.
+            match acc.write_to_disk_at_data_directory(data_dir) {
+                Ok(_) => voting_accounts.push(acc),
+                Err(e) => warn!("Failed to save a new voting account; not voting: {}", e),
+            }
         }
 
         // Initialize a client with the given keypair, configuration & voting accounts
@@ -531,11 +1246,43 @@ impl Client {
         // Return the initialized client inside a result
         Client {
             network: Network::from(&*cfg.network_name),
-            runtime: Arc::new(RwLock::new(system::System::with_data_dir(cfg, data_dir))), // Set runtime
-            voting_accounts: Some(voting_accounts), // Set voters
+            protocol_prefix: cfg.network_name.clone(), // Identify by name, unless the registry says otherwise
+            runtime: Arc::new(InstrumentedRwLock::new(
+                "runtime",
+                system::System::with_data_dir(cfg, data_dir),
+            )), // Set runtime
+            voting_accounts: Some(voting_accounts),    // Set voters
             peer_id: PeerId::from_public_key(keypair.public()), // Set peer id
             keypair,
+            relay_policy: relay::PolicyConfig::default(), // Use the default relay thresholds
+            publisher: publisher::PublisherConfig::default(), // Use the default publishing limits
+            gossip_max_age_secs: envelope::DEFAULT_MAX_AGE_SECS,
+            listen_addrs: Vec::new(), // Listen on both address families
+            external_addrs: Vec::new(),
+            psk: None, // Networks are public, unless a key is given
+            sync_peers: Arc::new(RwLock::new(SyncPeerTable::default())),
+            connections: Arc::new(RwLock::new(ConnectionTable::default())),
+            head_attestation_policy: AttestationPolicy::default(), // Require the default backing for heads
+            dht: DhtConfig::default(), // Keep records alive until they're republished
+            double_spend_webhook: None, // Only report double spends through the event feed
+            webhooks: WebhookConfig::default(), // Don't notify any webhooks
+            fast_sync: false,          // Replay the ledger from its genesis
+            snapshot_interval: snapshot::DEFAULT_SNAPSHOT_INTERVAL,
+            light: false,                  // Execute the DAG
+            data_dir: data_dir.to_owned(), // Set data dir
+        }
+    }
+
+    /// Gets the keypair that the client seals the proposals it publishes with: its peer identity, if it's an
+    /// ed25519 identity, or a fresh keypair otherwise.
+    fn envelope_keypair(&self) -> ed25519_dalek::Keypair {
+        if let identity::Keypair::Ed25519(keypair) = &self.keypair {
+            if let Ok(keypair) = ed25519_dalek::Keypair::from_bytes(&keypair.encode()) {
+                return keypair;
+            }
         }
+
+        ed25519_dalek::Keypair::generate(&mut rand::rngs::OsRng {})
     }
 
     /// Constructs a new graph according to an inputted genesis configuration file.
@@ -545,6 +1292,9 @@ impl Client {
     /// * `genesis` - The configuration for the genesis dag
     /// * `data_dir` - The directory in which genesis dag data will be stored
     pub fn construct_genesis(&mut self, genesis: genesis::Config) -> Result<(), failure::Error> {
+        // Don't build a genesis that contradicts itself
+        genesis.validate()?;
+
         // Generate an account to which all of the genesis funds will be transferred
         let genesis_account = Account::new();
 
@@ -607,50 +1357,73 @@ impl Client {
                 address
             );
 
-            // Make a transaction worth the value allocated to the address
-            let mut tx = Transaction::new(
-                (i as i64).try_into().unwrap(),
-                genesis_account.address()?,
-                *address,
-                value.clone(),
-                b"genesis_child",
-                vec![last_hash],
-            );
-
-            // We should be mentioning the last state hash in this tx, since we know it already
-            tx.transaction_data.parent_state_hash = Some(last_state_hash);
-            tx.transaction_data.parent_receipts = Some(ReceiptMap {
-                associated_transactions: vec![last_hash],
-                receipts: vec![Receipt {
-                    state_hash: last_state_hash,
-                    logs: Vec::new(),
-                }],
-            });
-            tx.hash =
-                blake3::hash_slice(&bincode::serialize(&tx.transaction_data).unwrap_or_default());
-
-            // Execute the transaction, and collect its state
-            let state = tx.execute(runtime.ledger.get(i - 1)?.unwrap().state_entry.clone());
-
-            // Since we might need to make more transactions, we'll want to keep them as children of this transaction.
-            // Update the last_hash & last_state_hash to reflect this.
-            last_hash = tx.hash;
-            last_state_hash = state.hash;
+            // Each locked portion of the allocation is sent on its own, carrying its lock, and the rest is sent as a
+            // plain genesis child, or one recording the role that the account plays
+            let locks = genesis.locks_for_address(*address);
+            let role = genesis.role_of(*address);
+            let mut tranches: Vec<(num::BigUint, Vec<u8>)> = locks
+                .iter()
+                .map(|lock| (lock.amount.clone(), lock.unlock.to_payload()))
+                .collect();
+            let unlocked = locks
+                .iter()
+                .fold(value.clone(), |unlocked, lock| unlocked - &lock.amount);
+            if !unlocked.is_zero() || tranches.is_empty() || role.is_some() {
+                tranches.push((
+                    unlocked,
+                    role.map_or(b"genesis_child".to_vec(), |role| role.to_payload()),
+                ));
+            }
 
-            // Instantly resolve the state for this transaction, since we'll add a finalizing tx next
-            runtime.ledger.push(tx, Some(state));
+            for (value, payload) in tranches {
+                // Make a transaction worth the tranche. Since each genesis child is resolved as soon as it's made, the
+                // only tip will be the last child.
+                let mut tx = Transaction::new(
+                    ((i - 1) as i64).try_into().unwrap(),
+                    genesis_account.address()?,
+                    *address,
+                    value,
+                    &payload,
+                    runtime.ledger.select_tips(1, false),
+                );
 
-            i += 1;
+                // We should be mentioning the last state hash in this tx, since we know it already
+                tx.transaction_data.parent_state_hash = Some(last_state_hash);
+                tx.transaction_data.parent_receipts = Some(ReceiptMap {
+                    associated_transactions: vec![last_hash],
+                    receipts: vec![Receipt {
+                        state_hash: last_state_hash,
+                        logs: Vec::new(),
+                        status: None,
+                        items: Vec::new(),
+                        executed_at: None,
+                    }],
+                });
+                tx.hash = tx.transaction_data.hash();
+
+                // Execute the transaction, and collect its state
+                let state = tx.execute(runtime.ledger.get(i - 1)?.unwrap().state_entry.clone());
+
+                // Since we might need to make more transactions, we'll want to keep them as children of this
+                // transaction. Update the last_hash & last_state_hash to reflect this.
+                last_hash = tx.hash;
+                last_state_hash = state.hash;
+
+                // Instantly resolve the state for this transaction, since we'll add a finalizing tx next
+                runtime.ledger.push(tx, Some(state));
+
+                i += 1;
+            }
         }
 
         // Make a transaction to wrap up the genesis creation process
         let mut finalization = Transaction::new(
-            ((i + 1) as i64).try_into().unwrap(),
+            ((i - 1) as i64).try_into().unwrap(),
             genesis_account.address()?,
             Address::default(),
             num::BigUint::zero(),
-            b"genesis_finalization",
-            vec![last_hash],
+            genesis::GENESIS_FINALIZATION_PAYLOAD,
+            runtime.ledger.select_tips(1, false),
         );
         finalization.transaction_data.parent_state_hash = Some(last_state_hash);
 
@@ -670,11 +1443,23 @@ impl Client {
         bootstrap_addresses: Vec<(PeerId, Multiaddr)>,
         port: u16,
     ) -> Result<(), failure::Error> {
-        let store = kad::record::store::MemoryStore::new(self.peer_id.clone()); // Initialize a memory store to store peer information in
+        // Initialize a memory store to store peer information in, with enough room for the whole ledger
+        let store = kad::record::store::MemoryStore::with_config(
+            self.peer_id.clone(),
+            self.dht.store_config(),
+        );
+
+        // Derive the topics for the network from its configuration
+        let topics = if let Ok(rt) = self.runtime.read() {
+            floodsub::Topics::from_config(&rt.config)
+        } else {
+            return Err(CommunicationError::MutexFailure.into());
+        };
 
-        let mut sub = Floodsub::new(self.peer_id.clone());
-        sub.subscribe(Topic::new(floodsub::PROPOSALS_TOPIC.to_owned()));
-        sub.subscribe(Topic::new(floodsub::VOTES_TOPIC.to_owned()));
+        let mut sub = GatedFloodsub::new(self.peer_id.clone());
+        sub.subscribe(topics.proposals.clone());
+        sub.subscribe(topics.votes.clone());
+        sub.subscribe(topics.transactions.clone());
 
         // Move the accounts stored in the client into the ClientBehavior
         let accounts = if let Some(taken_accounts) = self.voting_accounts.take() {
@@ -683,24 +1468,45 @@ impl Client {
             Vec::new()
         };
 
-        // Generate an empty configuration for the kademlia DHT that we'll use to bootstrap network consensus with.
-        // We're going to segregate the network's KAD DHT from all the other DHTs to prevent poisoning.
-        let mut kad_dht_cfg: KademliaConfig = Default::default();
-        kad_dht_cfg.set_protocol_name(<Network as Into<String>>::into(self.network).into_bytes());
+        // Let the RPC API know which accounts we're voting with
+        if let Ok(mut rt) = self.runtime.write() {
+            rt.active_voters = accounts
+                .iter()
+                .filter_map(|acc| acc.address().ok())
+                .collect();
+
+            // Light clients follow the DAG's headers, rather than the DAG itself
+            if self.light && !rt.is_light() {
+                rt.header_chain = Some(HeaderChain::default());
+            }
+        }
+
+        // Each bootstrap peer may be reachable over more than one address
+        let bootstrap_peers = listen::group_by_peer(bootstrap_addresses);
+
+        // Generate a configuration for the kademlia DHT that we'll use to bootstrap network consensus with, tuned as
+        // the user asked. We're going to segregate the network's KAD DHT from all the other DHTs to prevent poisoning,
+        // and from the nodes that don't seal record values in envelopes.
+        let protocol_prefix = compression::versioned(&self.protocol_prefix);
+        let kad_dht_cfg = self
+            .dht
+            .kademlia_config(protocol_prefix.clone().into_bytes())?;
 
         // Initialize a new behavior for a client that we will generate in the not-so-distant future with the given peerId, alongside
         // an mDNS service handler as well as a gossipsub instance targeted at the given peer
         let behavior = ClientBehavior {
             gossipsub: sub,
             mdns: Mdns::new()?,
-            kad_dht: Kademlia::new(self.peer_id.clone(), store),
+            kad_dht: Kademlia::with_config(self.peer_id.clone(), store, kad_dht_cfg),
             identification: Identify::new(
-                format!("{}", self.network),
+                protocol_prefix.clone(),
                 config::NODE_VERSION.to_owned(),
                 self.keypair.public(),
             ),
             pinger: Ping::new(PingConfig::new()),
-            network: self.network,
+            connections: ConnectionTracker::new(self.connections.clone()),
+            network: self.network.clone(),
+            protocol_prefix,
             runtime: self.runtime.clone(),
             voting_accounts: accounts,
             should_broadcast_dag: false,
@@ -710,31 +1516,127 @@ impl Client {
                 Arc::new(AtomicBool::new(false))
             },
             last_published_tx: 0,
+            fast_sync: self.fast_sync,
+            snapshot_download: None,
+            snapshot_interval: self.snapshot_interval,
+            last_snapshot_height: 0,
+            last_header_poll: Instant::now(),
+            config_record: None,
+            wal: match WriteAheadLog::open(&self.data_dir, &format!("{}", self.network)) {
+                Ok(wal) => Some(wal),
+                Err(e) => {
+                    warn!("Failed to open the write-ahead log; incoming messages will not be journaled: {}", e);
+
+                    None
+                }
+            },
+            topics,
+            relay_policy: RelayPolicy::new(self.relay_policy.clone()),
+            latencies: LatencyTable::default(),
+            publisher: PublishQueue::new(self.publisher.clone()),
+            sync_peers: self.sync_peers.clone(),
+            envelope_keypair: self.envelope_keypair(),
+            replay_guard: ReplayGuard::new(self.gossip_max_age_secs),
+            head_attestations: HeadAttestations::new(self.head_attestation_policy.clone()),
+            sync_audit: SyncAudit::default(),
+            parent_fetcher: ParentFetcher::default(),
+            reconnector: Reconnector::new(
+                bootstrap_peers.clone(),
+                reconnect::DEFAULT_RECONNECT_BASE_SECS,
+                reconnect::DEFAULT_RECONNECT_MAX_SECS,
+            ),
+            double_spend_webhook: match &self.double_spend_webhook {
+                Some(url) => Some(Webhook::with_policy(url, self.webhooks.policy.clone())?),
+                None => None,
+            },
+            webhooks: WebhookDispatcher::new(&self.webhooks)?,
         };
 
-        let mut swarm = Swarm::new(
-            libp2p::build_tcp_ws_secio_mplex_yamux(self.keypair.clone())?,
-            behavior,
-            self.peer_id.clone(),
-        ); // Initialize a swarm
+        // Initialize a swarm, cutting off any peer without the network's key if the network is private
+        let mut swarm = if let Some(key) = &self.psk {
+            Swarm::new(
+                psk::build_transport(self.keypair.clone(), key)?,
+                behavior,
+                self.peer_id.clone(),
+            )
+        } else {
+            Swarm::new(
+                libp2p::build_tcp_ws_secio_mplex_yamux(self.keypair.clone())?,
+                behavior,
+                self.peer_id.clone(),
+            )
+        };
+
+        // Finish processing any messages that we received before the last shutdown
+        swarm.replay_wal();
+
+        // Listen on each of the configured addresses, keeping track of which address families we could bind to, so
+        // that hosts lacking one of the families can still join the network over the other
+        let mut listeners = 0;
+        let mut reachable: Vec<listen::AddressFamily> = Vec::new();
+        for addr in listen::listen_addrs(&self.listen_addrs, port) {
+            match Swarm::listen_on(&mut swarm, addr.clone()) {
+                Ok(_) => {
+                    // Print the address we'll be listening on
+                    info!("Swarm listening on addr {}; ready for connections", addr);
+
+                    listeners += 1;
+                    reachable.extend(listen::family(&addr));
+                }
+                Err(e) => warn!("Swarm failed to bind to listening address {}: {}", addr, e),
+            }
+        }
+
+        // We can't do anything without at least one listener
+        if listeners == 0 {
+            // Log the error
+            error!("Swarm failed to bind to any listening address");
+
+            // Convert the error into an IO error
+            let e: std::io::Error = io::ErrorKind::AddrNotAvailable.into();
+
+            // Return an error that says we can't listen on this address
+            return Err(e.into());
+        }
+
+        // Advertise any addresses that peers should reach us at, but that we can't see ourselves (e.g. behind a NAT)
+        for addr in self.external_addrs.iter() {
+            Swarm::add_external_address(&mut swarm, addr.clone());
+        }
 
         // Log the pending bootstrap operation
         info!("Bootstrapping a network DHT & behavior to existing bootstrap nodes...");
 
-        // Iterate through bootstrap addresses
-        for (i, bootstrap_peer) in bootstrap_addresses.into_iter().enumerate() {
-            // Log the pending connection op
-            info!(
-                "Connecting to bootstrap node {} ({})...",
-                i, bootstrap_peer.1
-            );
+        // Iterate through bootstrap peers, each of which may be reachable over more than one address
+        for (i, (bootstrap_peer, addrs)) in bootstrap_peers.into_iter().enumerate() {
+            // Add each of the bootstrap peer's addresses to the DHT
+            for addr in addrs.iter() {
+                swarm.add_address(bootstrap_peer.clone(), addr.clone());
+            }
+
+            // Connect to the peer, falling back on its other addresses if one can't be dialed
+            let mut connected = false;
+            for addr in listen::dial_order(addrs, &reachable) {
+                // Log the pending connection op
+                info!("Connecting to bootstrap node {} ({})...", i, addr);
 
-            swarm.add_address(bootstrap_peer.0, bootstrap_peer.1.clone()); // Add the bootstrap peer to the DHT
+                match Swarm::dial_addr(&mut swarm, addr) {
+                    Ok(_) => {
+                        info!("Connected to bootstrap node {} successfully!", i);
 
-            // Connect to the peer
-            match Swarm::dial_addr(&mut swarm, bootstrap_peer.1) {
-                Ok(_) => info!("Connected to bootstrap node {} successfully!", i),
-                Err(e) => warn!("Failed to connect to bootstrap node {}: {}", i, e),
+                        connected = true;
+
+                        break;
+                    }
+                    Err(e) => warn!("Failed to connect to bootstrap node {}: {}", i, e),
+                }
+            }
+
+            if !connected {
+                warn!(
+                    "Failed to connect to bootstrap node {} over any of its addresses",
+                    i
+                );
             }
         }
 
@@ -744,88 +1646,97 @@ impl Client {
         // Bootstrap the behavior's DHT
         swarm.kad_dht.bootstrap();
 
-        // Try to get the address we'll listen on
-        match format!("/ip4/0.0.0.0/tcp/{}", port).parse::<Multiaddr>() {
-            Ok(addr) => {
-                // Try to tell the swarm to listen on this address, return an error if this doesn't work
-                if let Err(e) = Swarm::listen_on(&mut swarm, addr.clone()) {
-                    // Log the error
-                    error!("Swarm failed to bind to listening address {}: {}", addr, e);
+        // If none of the bootstrap peers answer, try them again later, rather than running isolated for good
+        swarm.reconnector.dialed(Utc::now());
 
-                    // Convert the addr err into an io error
-                    let e: std::io::Error = io::ErrorKind::AddrNotAvailable.into();
+        // We'll want to remember whether or not we have begun listening so that we can print out debug info
+        let mut listening = false;
 
-                    // Return an error
-                    return Err(e.into());
-                };
+        // Tell the network what we know about the DAG
+        swarm.publish_dag();
 
-                // Print the address we'll be listening on
-                info!("Swarm listening on addr {}; ready for connections", addr);
+        // Get some information about what our peers know
+        swarm.synchronize_dag();
+        swarm.synchronize_config();
 
-                // We'll want to remember whether or not we have begun listening so that we can print out debug info
-                let mut listening = false;
+        task::block_on(future::poll_fn(move |cx: &mut Context| {
+            loop {
+                // if we haven't completely publicized the DAG info, start publishing
+                if swarm.should_broadcast_dag {
+                    swarm.should_broadcast_dag = false;
 
-                // Tell the network what we know about the DAG
-                swarm.publish_dag();
+                    debug!("Broadcasting a copy of the DAG to the network...");
 
-                // Get some information about what our peers know
-                swarm.synchronize_dag();
+                    swarm.publish_dag();
+                }
 
-                task::block_on(future::poll_fn(move |cx: &mut Context| {
-                    loop {
-                        // if we haven't completely publicized the DAG info, start publishing
-                        if swarm.should_broadcast_dag {
-                            swarm.should_broadcast_dag = false;
+                // If there are transactions that we should be publishing, queue them up
+                if !swarm.transaction_queue_is_empty() {
+                    swarm.clear_transaction_queue();
+                }
 
-                            debug!("Broadcasting a copy of the DAG to the network...");
+                // Decide any proposals that were waiting out their voting period
+                swarm.clear_matured_proposals();
 
-                            swarm.publish_dag();
-                        }
+                // Stop waiting on parents that haven't shown up in time
+                swarm.vote_on_expired_deferrals();
 
-                        // If there are transactions that we should be publishing, do just that
-                        if !swarm.transaction_queue_is_empty() {
-                            swarm.clear_transaction_queue();
-                        }
+                // Make sure that the ledger doesn't run out of room mid-execution
+                swarm.check_storage();
 
-                        // Poll the swarm
-                        match swarm.poll_next_unpin(cx) {
-                            Poll::Ready(Some(e)) => debug!("{:?}", e),
-                            Poll::Ready(None) => return Poll::Ready(Ok(())),
-                            Poll::Pending => {
-                                if !listening {
-                                    for addr in Swarm::listeners(&swarm) {
-                                        // Print out that we're listening on the address
-                                        info!("Assigned to new address; listening on {} now", addr);
-                                    }
-
-                                    // We're listening now
-                                    listening = true;
-                                }
-
-                                break;
+                // Keep up with the network's headers, if we're a light client
+                swarm.poll_light_headers();
+
+                // Let the operator know about any executions, competing spends, or trouble keeping up
+                swarm.forward_notifications();
+
+                // Dial the bootstrap peers again if we've lost touch with the rest of the network
+                let connected_peers = Swarm::network_info(&swarm).num_peers;
+                if swarm.maintain_connectivity(connected_peers) {
+                    for (bootstrap_peer, addrs) in swarm.reconnector.bootstrap_peers().to_vec() {
+                        for addr in listen::dial_order(addrs, &reachable) {
+                            match Swarm::dial_addr(&mut swarm, addr) {
+                                Ok(_) => break,
+                                Err(e) => debug!(
+                                    "Failed to redial bootstrap node {}: {}",
+                                    bootstrap_peer, e
+                                ),
                             }
-                        };
+                        }
                     }
-                    Poll::Pending
-                }))
-            }
-            _ => {
-                // Log the error
-                error!("Swarm failed to bind to listening address");
+                }
+
+                // Publish as much of the queue as the publishing rate allows
+                swarm.flush_publish_queue(cx);
+
+                // Poll the swarm
+                match swarm.poll_next_unpin(cx) {
+                    Poll::Ready(Some(e)) => debug!("{:?}", e),
+                    Poll::Ready(None) => return Poll::Ready(Ok(())),
+                    Poll::Pending => {
+                        if !listening {
+                            for addr in Swarm::listeners(&swarm) {
+                                // Print out that we're listening on the address
+                                info!("Assigned to new address; listening on {} now", addr);
+                            }
 
-                // Convert the error into an IO error
-                let e: std::io::Error = io::ErrorKind::AddrNotAvailable.into();
+                            // We're listening now
+                            listening = true;
+                        }
 
-                // Return an error that says we can't listen on this address
-                Err(e.into())
+                        break;
+                    }
+                };
             }
-        }
+            Poll::Pending
+        }))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use num::bigint::BigUint; // Add support for large unsigned integers
+    use rand::Rng; // Import rand
 
     use std::str::FromStr; // Allow overriding of from_str() helper method.
 
@@ -836,6 +1747,11 @@ mod tests {
         let config = config::Config {
             reward_per_gas: BigUint::from_str("10000000000000000000000000000000000000000").unwrap(), // Venezuela style
             network_name: "olympia".to_owned(),
+            issuance: Default::default(),
+            consensus: Default::default(),
+            council: Default::default(),
+            storage_mode: Default::default(),
+            sequence: 0,
         }; // Initialize config
 
         config.write_to_disk().unwrap(); // Write config to disk
@@ -850,6 +1766,38 @@ mod tests {
             "olympia"
         ); // Ensure client has correct net
     }
+
+    #[test]
+    fn test_construct_genesis() {
+        let rand: u16 = rand::thread_rng().gen(); // Generate random number
+
+        let mut client = Client::new(
+            network::Network::LocalTestNetwork,
+            &format!(
+                "{}/.tests/{}",
+                super::super::super::common::io::DATA_DIR,
+                rand
+            ),
+        )
+        .unwrap(); // Initialize client with an empty DAG
+
+        let mut genesis = genesis::Config::default(); // Initialize genesis config
+
+        // Allocate some finks to a couple of accounts
+        for value in &[10 as u8, 20] {
+            genesis.allocate_to_address(Account::new().address().unwrap(), BigUint::from(*value));
+        }
+
+        client.construct_genesis(genesis).unwrap(); // Construct the genesis
+
+        // The genesis account hasn't sent anything before its children, so they must start at nonce 0
+        let nonces: Vec<u64> = client.runtime.read().unwrap().ledger.nodes[1..]
+            .iter()
+            .map(|node| node.transaction.transaction_data.nonce)
+            .collect();
+
+        assert_eq!(nonces, vec![0, 1, 2]); // Ensure the children & the finalization are numbered consecutively
+    }
 }
 
 /*
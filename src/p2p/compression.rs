@@ -0,0 +1,141 @@
+use std::io::{self, Read}; // Import the io library
+
+/// Messages at least this large (in bytes) are compressed before being sent.
+pub const COMPRESSION_THRESHOLD: usize = 512;
+
+/// The largest size (in bytes) that a compressed message may expand to.
+pub const MAX_DECOMPRESSED_SIZE: u64 = 16 * 1024 * 1024;
+
+/// The zstd compression level used for outgoing messages.
+pub const COMPRESSION_LEVEL: i32 = 3;
+
+/// The envelope flag for a message that is sent as-is.
+pub const FLAG_UNCOMPRESSED: u8 = 0;
+
+/// The envelope flag for a message that has been compressed with zstd.
+pub const FLAG_ZSTD: u8 = 1;
+
+/// The version of the wire format in which each pubsub message & DHT record value is sealed in an envelope. Nodes
+/// that predate the envelope would read its flag byte as part of the message, so the version is folded into the
+/// protocol names & pubsub topics that nodes use, and the two never exchange messages.
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// An error encountered while opening a message envelope.
+#[derive(Debug, Fail)]
+pub enum CompressionError {
+    #[fail(display = "the envelope is empty")]
+    EmptyEnvelope,
+    #[fail(display = "the envelope has an unknown compression flag: {}", flag)]
+    UnknownFlag { flag: u8 },
+    #[fail(display = "the message expands beyond the {} byte limit", limit)]
+    TooLarge { limit: u64 },
+    #[fail(display = "failed to decompress the message: {}", error)]
+    DecompressionFailure { error: String },
+}
+
+impl From<io::Error> for CompressionError {
+    /// Converts the given IO error into a CompressionError.
+    fn from(e: io::Error) -> Self {
+        Self::DecompressionFailure {
+            error: e.to_string(),
+        }
+    }
+}
+
+/// Wraps the given message in an envelope, compressing it if it is large enough to be worth it.
+/// The first byte of the envelope signals to the receiver whether or not the rest of the envelope
+/// is compressed.
+///
+/// # Arguments
+///
+/// * `data` - The raw message that should be sent
+pub fn seal(data: &[u8]) -> Vec<u8> {
+    // Only bother compressing large messages, and only keep the compressed form if it's smaller
+    if data.len() >= COMPRESSION_THRESHOLD {
+        if let Ok(compressed) = zstd::encode_all(data, COMPRESSION_LEVEL) {
+            if compressed.len() < data.len() {
+                return [&[FLAG_ZSTD][..], &compressed[..]].concat();
+            }
+        }
+    }
+
+    [&[FLAG_UNCOMPRESSED][..], data].concat() // Send the message as-is
+}
+
+/// Tags the given protocol name or pubsub topic with the envelope version (e.g. "olympia" becomes "olympia/env1"),
+/// so that only nodes sealing their messages in envelopes agree on it.
+///
+/// # Arguments
+///
+/// * `name` - The protocol name or topic
+pub fn versioned(name: &str) -> String {
+    format!("{}/env{}", name, ENVELOPE_VERSION)
+}
+
+/// Opens an envelope produced by seal(), and returns the raw message.
+///
+/// # Arguments
+///
+/// * `envelope` - The envelope that was received
+pub fn open(envelope: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    // Split the flag from the contents of the envelope
+    let (flag, contents) = envelope
+        .split_first()
+        .ok_or(CompressionError::EmptyEnvelope)?;
+
+    match *flag {
+        FLAG_UNCOMPRESSED => Ok(contents.to_vec()),
+        FLAG_ZSTD => {
+            let mut data = Vec::new(); // Initialize a buffer to decompress the message into
+
+            // Decompress the message, reading at most one byte past the limit so we can tell if it
+            // has been exceeded
+            zstd::stream::Decoder::new(contents)?
+                .take(MAX_DECOMPRESSED_SIZE + 1)
+                .read_to_end(&mut data)?;
+
+            // Don't let a tiny message fill up our memory
+            if data.len() as u64 > MAX_DECOMPRESSED_SIZE {
+                return Err(CompressionError::TooLarge {
+                    limit: MAX_DECOMPRESSED_SIZE,
+                });
+            }
+
+            Ok(data)
+        }
+        _ => Err(CompressionError::UnknownFlag { flag: *flag }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from parent module
+
+    #[test]
+    fn test_seal_open() {
+        let small = b"a small message".to_vec(); // Make a message below the threshold
+        let large = vec![7u8; COMPRESSION_THRESHOLD * 4]; // Make a compressible message
+
+        let sealed_small = seal(&small); // Seal the small message
+        let sealed_large = seal(&large); // Seal the large message
+
+        assert_eq!(sealed_small[0], FLAG_UNCOMPRESSED); // Small messages aren't compressed
+        assert_eq!(sealed_large[0], FLAG_ZSTD); // Large messages are compressed
+        assert!(sealed_large.len() < large.len()); // Compression should shrink the message
+
+        assert_eq!(open(&sealed_small).unwrap(), small); // Ensure the small message survives
+        assert_eq!(open(&sealed_large).unwrap(), large); // Ensure the large message survives
+    }
+
+    #[test]
+    fn test_versioned() {
+        assert_eq!(versioned("olympia"), "olympia/env1"); // Versions are appended to the name
+        assert_ne!(versioned("olympia"), "olympia"); // Nodes without envelopes don't share the name
+    }
+
+    #[test]
+    fn test_open_invalid() {
+        assert!(open(&[]).is_err()); // Empty envelopes are invalid
+        assert!(open(&[42, 1, 2, 3]).is_err()); // Unknown flags are invalid
+    }
+}
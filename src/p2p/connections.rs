@@ -0,0 +1,355 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    task::{Context, Poll},
+    time::Duration,
+}; // Import the collections, locking & polling types
+
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use libp2p::{
+    core::ConnectedPoint,
+    swarm::{
+        protocols_handler::DummyProtocolsHandler, NetworkBehaviour, NetworkBehaviourAction,
+        NetworkBehaviourEventProcess, PollParameters,
+    },
+    Multiaddr, PeerId,
+}; // Import the libp2p swarm types
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use void::Void; // Import the uninhabited event type
+
+use super::client::ClientBehavior; // Import the client behavior
+
+/// The number of peers listed in a page, unless another page size is asked for.
+pub const DEFAULT_PEER_PAGE_SIZE: usize = 50;
+
+/// The largest number of peers that can be listed in a single page.
+pub const MAX_PEER_PAGE_SIZE: usize = 500;
+
+/// Which side of a connection opened it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// The peer dialed us
+    Inbound,
+
+    /// We dialed the peer
+    Outbound,
+}
+
+/// What we know about an open connection to a single peer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConnectedPeer {
+    /// The base58-encoded ID of the peer
+    pub peer_id: String,
+
+    /// The address that the peer is connected over
+    pub address: String,
+
+    /// Which side of the connection opened it
+    pub direction: Direction,
+
+    /// The protocols that the peer identified itself as supporting
+    pub protocols: Vec<String>,
+
+    /// The version of SummerCash that the peer identified itself as running
+    pub agent_version: Option<String>,
+
+    /// The network protocol that the peer identified itself as speaking
+    pub protocol_version: Option<String>,
+
+    /// The time at which the connection was opened
+    pub connected_at: DateTime<Utc>,
+
+    /// The number of seconds that the connection has been open for, as of the listing
+    pub age_secs: u64,
+
+    /// The last round-trip time to the peer, in milliseconds, if it has answered a ping yet
+    pub rtt_ms: Option<u64>,
+}
+
+/// A page of the client's connected peers, oldest connections first.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PeerPage {
+    /// The overall number of connected peers
+    pub total: usize,
+
+    /// The offset of the first peer in the page
+    pub offset: usize,
+
+    /// The offset of the first peer in the next page, if there are any peers left
+    pub next_offset: Option<usize>,
+
+    /// The peers in the page
+    pub peers: Vec<ConnectedPeer>,
+}
+
+/// The open connections of the client, shared between its swarm & its RPC server.
+#[derive(Default)]
+pub struct ConnectionTable {
+    /// Each connected peer, by its ID
+    peers: HashMap<PeerId, ConnectedPeer>,
+}
+
+/// Implement a set of connection table helper methods.
+impl ConnectionTable {
+    /// Records that a connection to the given peer was opened, replacing any earlier connection to the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The ID of the peer
+    /// * `address` - The address that the peer is connected over
+    /// * `direction` - Which side of the connection opened it
+    /// * `now` - The current time
+    pub fn record_connected(
+        &mut self,
+        peer: PeerId,
+        address: &Multiaddr,
+        direction: Direction,
+        now: DateTime<Utc>,
+    ) {
+        self.peers.insert(
+            peer.clone(),
+            ConnectedPeer {
+                peer_id: peer.to_base58(),
+                address: address.to_string(),
+                direction,
+                protocols: Vec::new(),
+                agent_version: None,
+                protocol_version: None,
+                connected_at: now,
+                age_secs: 0,
+                rtt_ms: None,
+            },
+        );
+    }
+
+    /// Records the identity that a connected peer announced. Peers that aren't connected are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The ID of the peer
+    /// * `agent_version` - The version of SummerCash that the peer is running
+    /// * `protocol_version` - The network protocol that the peer speaks
+    /// * `protocols` - The protocols that the peer supports
+    pub fn record_identity(
+        &mut self,
+        peer: &PeerId,
+        agent_version: &str,
+        protocol_version: &str,
+        protocols: &[String],
+    ) {
+        if let Some(connected) = self.peers.get_mut(peer) {
+            connected.agent_version = Some(agent_version.to_owned());
+            connected.protocol_version = Some(protocol_version.to_owned());
+            connected.protocols = protocols.to_vec();
+        }
+    }
+
+    /// Records the round-trip time of a ping answered by a connected peer. Peers that aren't connected are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The ID of the peer
+    /// * `rtt` - The time that the peer took to answer
+    pub fn record_rtt(&mut self, peer: &PeerId, rtt: Duration) {
+        if let Some(connected) = self.peers.get_mut(peer) {
+            connected.rtt_ms = Some(rtt.as_millis() as u64);
+        }
+    }
+
+    /// Forgets about the connection to the given peer, if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The ID of the peer
+    pub fn forget(&mut self, peer: &PeerId) {
+        self.peers.remove(peer);
+    }
+
+    /// Gets the number of connected peers.
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Checks whether or not there are no connected peers.
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    /// Gets a page of the connected peers, ordered from the oldest connection to the newest.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The number of peers that should be skipped
+    /// * `limit` - The largest number of peers that should be listed (between 1 and MAX_PEER_PAGE_SIZE)
+    /// * `now` - The current time, which connection ages are measured against
+    pub fn page(&self, offset: usize, limit: Option<usize>, now: DateTime<Utc>) -> PeerPage {
+        let limit = limit
+            .unwrap_or(DEFAULT_PEER_PAGE_SIZE)
+            .min(MAX_PEER_PAGE_SIZE)
+            .max(1);
+
+        let mut peers = self.peers.values().collect::<Vec<&ConnectedPeer>>();
+        peers.sort_by(|a, b| {
+            a.connected_at
+                .cmp(&b.connected_at)
+                .then_with(|| a.peer_id.cmp(&b.peer_id))
+        });
+
+        let page = peers
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|connected| ConnectedPeer {
+                age_secs: (now - connected.connected_at).num_seconds().max(0) as u64,
+                ..connected.clone()
+            })
+            .collect::<Vec<ConnectedPeer>>();
+
+        PeerPage {
+            total: self.peers.len(),
+            offset,
+            next_offset: Some(offset + page.len()).filter(|next| *next < self.peers.len()),
+            peers: page,
+        }
+    }
+}
+
+/// A network behaviour keeping a connection table up to date with the connections opened & closed by the swarm. It
+/// speaks no protocol of its own.
+pub struct ConnectionTracker {
+    /// The table that connections are recorded in
+    pub(crate) table: Arc<RwLock<ConnectionTable>>,
+}
+
+/// Implement a set of connection tracker helper methods.
+impl ConnectionTracker {
+    /// Initializes a new tracker recording connections in the given table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table that connections should be recorded in
+    pub fn new(table: Arc<RwLock<ConnectionTable>>) -> Self {
+        Self { table }
+    }
+
+    /// Records a connection opened over the given endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_id` - The ID of the peer
+    /// * `endpoint` - The endpoint that the connection was opened over
+    fn record(&mut self, peer_id: PeerId, endpoint: &ConnectedPoint) {
+        let (address, direction) = match endpoint {
+            ConnectedPoint::Dialer { address } => (address, Direction::Outbound),
+            ConnectedPoint::Listener { send_back_addr, .. } => (send_back_addr, Direction::Inbound),
+        };
+
+        if let Ok(mut table) = self.table.write() {
+            table.record_connected(peer_id, address, direction, Utc::now());
+        }
+    }
+}
+
+impl NetworkBehaviour for ConnectionTracker {
+    type ProtocolsHandler = DummyProtocolsHandler;
+    type OutEvent = Void;
+
+    fn new_handler(&mut self) -> Self::ProtocolsHandler {
+        DummyProtocolsHandler::default()
+    }
+
+    fn addresses_of_peer(&mut self, _peer_id: &PeerId) -> Vec<Multiaddr> {
+        Vec::new()
+    }
+
+    fn inject_connected(&mut self, peer_id: PeerId, endpoint: ConnectedPoint) {
+        self.record(peer_id, &endpoint);
+    }
+
+    fn inject_disconnected(&mut self, peer_id: &PeerId, _endpoint: ConnectedPoint) {
+        if let Ok(mut table) = self.table.write() {
+            table.forget(peer_id);
+        }
+    }
+
+    fn inject_replaced(
+        &mut self,
+        peer_id: PeerId,
+        _closed: ConnectedPoint,
+        opened: ConnectedPoint,
+    ) {
+        self.record(peer_id, &opened);
+    }
+
+    fn inject_node_event(&mut self, _peer_id: PeerId, event: Void) {
+        void::unreachable(event)
+    }
+
+    fn poll(
+        &mut self,
+        _cx: &mut Context,
+        _params: &mut impl PollParameters,
+    ) -> Poll<NetworkBehaviourAction<Void, Void>> {
+        Poll::Pending
+    }
+}
+
+/// The connection tracker never emits any events.
+impl NetworkBehaviourEventProcess<Void> for ClientBehavior {
+    fn inject_event(&mut self, event: Void) {
+        void::unreachable(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_connection_table() {
+        let mut table = ConnectionTable::default();
+        let now = Utc::now();
+        let address: Multiaddr = "/ip4/127.0.0.1/tcp/2048".parse().unwrap();
+
+        // Connections are listed oldest first, with their identities & latencies
+        let peers: Vec<PeerId> = (0..3).map(|_| PeerId::random()).collect();
+        for (i, peer) in peers.iter().enumerate() {
+            table.record_connected(
+                peer.clone(),
+                &address,
+                Direction::Inbound,
+                now - chrono::Duration::seconds(30 - i as i64),
+            );
+        }
+        table.record_identity(&peers[0], "summercash/1.0.0", "/smc/1.0.0", &[]);
+        table.record_rtt(&peers[0], Duration::from_millis(42));
+
+        let first = table.page(0, Some(2), now);
+        assert_eq!(first.total, 3);
+        assert_eq!(first.next_offset, Some(2));
+        assert_eq!(first.peers[0].peer_id, peers[0].to_base58());
+        assert_eq!(first.peers[0].age_secs, 30);
+        assert_eq!(first.peers[0].rtt_ms, Some(42));
+        assert_eq!(
+            first.peers[0].agent_version,
+            Some("summercash/1.0.0".to_owned())
+        );
+
+        let last = table.page(2, Some(2), now);
+        assert_eq!(last.peers.len(), 1);
+        assert_eq!(last.next_offset, None);
+
+        // Peers that disconnect are left out, and nothing is recorded for peers that aren't connected
+        table.forget(&peers[0]);
+        table.record_rtt(&peers[0], Duration::from_millis(42));
+        assert_eq!(table.len(), 2);
+        assert_eq!(
+            table.page(0, None, now).peers[0].peer_id,
+            peers[1].to_base58()
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::{num::NonZeroUsize, time::Duration}; // Import the non-zero integer & time types
+
+use libp2p::kad::{record::store::MemoryStoreConfig, KademliaConfig}; // Import the kademlia config types
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+/// The number of peers that each record is replicated to, by default.
+pub const DEFAULT_REPLICATION_FACTOR: usize = 20;
+
+/// The number of peers asked at once while looking up a record, by default.
+pub const DEFAULT_PARALLELISM: usize = 3;
+
+/// The number of seconds that a record lives for before it expires, unless it is republished, by default (a week).
+pub const DEFAULT_RECORD_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// The number of seconds between republications of the records published by the node, by default (12 hours).
+pub const DEFAULT_REPUBLISH_INTERVAL_SECS: u64 = 12 * 60 * 60;
+
+/// The number of records that the node keeps for the network, by default. Each transaction in the DAG takes up two
+/// records, so this has to grow with the ledger.
+pub const DEFAULT_MAX_RECORDS: usize = 1 << 20;
+
+/// An error encountered while configuring the DHT.
+#[derive(Debug, Fail, PartialEq)]
+pub enum DhtConfigError {
+    #[fail(display = "the DHT's {} must be greater than zero", param)]
+    Zero {
+        param: String, // The name of the parameter that was zero
+    },
+    #[fail(
+        display = "records would expire after {}s, before being republished every {}s",
+        record_ttl_secs, republish_interval_secs
+    )]
+    ExpiresBeforeRepublished {
+        record_ttl_secs: u64,         // The lifetime of a record
+        republish_interval_secs: u64, // The time between republications
+    },
+}
+
+/// How the node's Kademlia DHT is tuned. The libp2p defaults let records expire sooner than a quiet network gets
+/// around to republishing them, so the ledger is republished well within the lifetime of its records.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DhtConfig {
+    /// The number of peers that each record is replicated to
+    pub replication_factor: usize,
+
+    /// The number of peers asked at once while looking up a record
+    pub parallelism: usize,
+
+    /// The number of seconds that a record lives for before it expires, unless it is republished. Records never
+    /// expire if this is zero.
+    pub record_ttl_secs: u64,
+
+    /// The number of seconds between republications of the records published by the node. Records are never
+    /// republished if this is zero.
+    pub republish_interval_secs: u64,
+
+    /// The number of records that the node keeps for the network
+    pub max_records: usize,
+}
+
+impl Default for DhtConfig {
+    /// Builds a config that republishes each record twice a day, keeping it alive for a week.
+    fn default() -> Self {
+        Self {
+            replication_factor: DEFAULT_REPLICATION_FACTOR,
+            parallelism: DEFAULT_PARALLELISM,
+            record_ttl_secs: DEFAULT_RECORD_TTL_SECS,
+            republish_interval_secs: DEFAULT_REPUBLISH_INTERVAL_SECS,
+            max_records: DEFAULT_MAX_RECORDS,
+        }
+    }
+}
+
+/// Implement a set of DHT config helper methods.
+impl DhtConfig {
+    /// Builds a Kademlia config following this one, speaking the given protocol.
+    ///
+    /// # Arguments
+    ///
+    /// * `protocol_name` - The name of the protocol spoken by the DHT, which keeps it apart from other networks' DHTs
+    pub fn kademlia_config(
+        &self,
+        protocol_name: Vec<u8>,
+    ) -> Result<KademliaConfig, DhtConfigError> {
+        let nonzero = |value: usize, param: &str| {
+            NonZeroUsize::new(value).ok_or_else(|| DhtConfigError::Zero {
+                param: param.to_owned(),
+            })
+        };
+
+        // A record that expires before it is republished silently vanishes from the network
+        if self.record_ttl_secs != 0
+            && (self.republish_interval_secs == 0
+                || self.republish_interval_secs >= self.record_ttl_secs)
+        {
+            return Err(DhtConfigError::ExpiresBeforeRepublished {
+                record_ttl_secs: self.record_ttl_secs,
+                republish_interval_secs: self.republish_interval_secs,
+            });
+        }
+
+        let mut cfg: KademliaConfig = Default::default();
+        cfg.set_protocol_name(protocol_name);
+        cfg.set_replication_factor(nonzero(self.replication_factor, "replication factor")?);
+        cfg.set_parallelism(nonzero(self.parallelism, "parallelism")?);
+        cfg.set_record_ttl(Self::secs(self.record_ttl_secs));
+        cfg.set_publication_interval(Self::secs(self.republish_interval_secs));
+
+        Ok(cfg)
+    }
+
+    /// Builds a config for the store that the node keeps the network's records in.
+    pub fn store_config(&self) -> MemoryStoreConfig {
+        MemoryStoreConfig {
+            max_records: self.max_records,
+            ..Default::default()
+        }
+    }
+
+    /// Converts the given number of seconds into a duration, if it isn't zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `secs` - The number of seconds
+    fn secs(secs: u64) -> Option<Duration> {
+        if secs == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(secs))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_kademlia_config() {
+        assert!(DhtConfig::default()
+            .kademlia_config(b"andromeda".to_vec())
+            .is_ok());
+
+        assert_eq!(
+            DhtConfig {
+                parallelism: 0,
+                ..Default::default()
+            }
+            .kademlia_config(b"andromeda".to_vec())
+            .err(),
+            Some(DhtConfigError::Zero {
+                param: "parallelism".to_owned()
+            })
+        );
+
+        // Records must be republished before they expire, unless they never expire
+        assert!(DhtConfig {
+            record_ttl_secs: 60,
+            republish_interval_secs: 60,
+            ..Default::default()
+        }
+        .kademlia_config(b"andromeda".to_vec())
+        .is_err());
+        assert!(DhtConfig {
+            record_ttl_secs: 0,
+            republish_interval_secs: 0,
+            ..Default::default()
+        }
+        .kademlia_config(b"andromeda".to_vec())
+        .is_ok());
+
+        assert_eq!(
+            DhtConfig::default().store_config().max_records,
+            DEFAULT_MAX_RECORDS
+        );
+    }
+}
@@ -0,0 +1,388 @@
+use chrono::{DateTime, Duration, Utc}; // Import time utilities
+
+use ed25519_dalek; // Import the edwards25519 digital signature library
+
+use rand::RngCore; // Import the rng used to generate nonces
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::super::{
+    common::address::Address,
+    core::types::signature::Signature,
+    crypto::{blake3, hash::Hash},
+}; // Import the address, signature & hashing modules
+
+/// The default number of seconds after which a gossiped envelope is considered stale, and refused.
+pub const DEFAULT_MAX_AGE_SECS: u64 = 600;
+
+/// The number of seconds that an envelope may appear to have been sent in the future, to allow for clock drift
+/// between nodes.
+pub const MAX_CLOCK_SKEW_SECS: i64 = 60;
+
+/// The number of fresh envelopes that a replay guard remembers at most. Once it's full, the envelopes received the
+/// longest ago are forgotten first.
+pub const MAX_REMEMBERED_ENVELOPES: usize = 131_072;
+
+/// The number of fresh envelopes that a replay guard remembers from any one sender. Any more envelopes from the sender
+/// are refused until its older envelopes go stale, so that no single sender can push everyone else's out.
+pub const MAX_ENVELOPES_PER_SENDER: usize = 4096;
+
+/// An error encountered while admitting a gossiped envelope.
+#[derive(Debug, Fail, PartialEq)]
+pub enum EnvelopeError {
+    #[fail(display = "the envelope is malformed: {}", error)]
+    Malformed {
+        error: String, // The underlying error
+    },
+    #[fail(
+        display = "the envelope was sealed for topic {}, but received on another",
+        topic
+    )]
+    WrongTopic {
+        topic: String, // The topic that the envelope was sealed for
+    },
+    #[fail(display = "the envelope's signature is missing or invalid")]
+    InvalidSignature,
+    #[fail(
+        display = "the envelope was sent {} seconds ago, which is too long ago",
+        age
+    )]
+    Stale {
+        age: i64, // The age of the envelope, in seconds
+    },
+    #[fail(display = "the envelope was sent {} seconds in the future", ahead)]
+    FromTheFuture {
+        ahead: i64, // How far ahead of our clock the envelope is, in seconds
+    },
+    #[fail(display = "the envelope has already been received once")]
+    Replayed,
+    #[fail(
+        display = "the envelope's sender has already sent {} envelopes that are still fresh",
+        sent
+    )]
+    RateLimited {
+        sent: usize, // The number of fresh envelopes already received from the sender
+    },
+    #[fail(display = "the envelope wasn't sealed by the author of its contents")]
+    SignerMismatch,
+}
+
+impl From<bincode::Error> for EnvelopeError {
+    /// Converts the given serialization error into an EnvelopeError.
+    fn from(e: bincode::Error) -> Self {
+        Self::Malformed {
+            error: e.to_string(),
+        }
+    }
+}
+
+/// A signed, timestamped wrapper around a gossiped proposal or vote. The signature covers the topic, nonce and time
+/// of sending, so that a captured message can't be passed along again once it has been seen, or once it has gone
+/// stale, nor moved to another topic.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Envelope {
+    /// The topic that the envelope was sealed for
+    pub topic: String,
+
+    /// A random number distinguishing the envelope from any other sealed by the same sender
+    pub nonce: u64,
+
+    /// The time at which the envelope was sealed
+    pub sent_at: DateTime<Utc>,
+
+    /// The serialized proposal or vote
+    pub payload: Vec<u8>,
+
+    /// The sender's signature of the envelope
+    pub signature: Option<Signature>,
+}
+
+/// Implement a set of envelope helper methods.
+impl Envelope {
+    /// Wraps the given payload in an envelope, and signs it with the given keypair.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic that the envelope will be published to
+    /// * `payload` - The serialized proposal or vote
+    /// * `keypair` - The keypair of the sender
+    pub fn seal(topic: &str, payload: Vec<u8>, keypair: &ed25519_dalek::Keypair) -> Self {
+        let mut envelope = Self {
+            topic: topic.to_owned(),
+            nonce: rand::thread_rng().next_u64(),
+            sent_at: Utc::now(),
+            payload,
+            signature: None,
+        };
+        envelope.signature = Some(Signature::new(keypair, &*envelope.hash()));
+
+        envelope
+    }
+
+    /// Hashes the contents of the envelope, excluding any signature.
+    pub fn hash(&self) -> Hash {
+        let mut to_be_hashed = self.clone();
+        to_be_hashed.signature = None;
+
+        blake3::hash_slice(&bincode::serialize(&to_be_hashed).unwrap_or_default())
+    }
+
+    /// Gets the address of the sender that signed the envelope, if the signature is authentic.
+    pub fn signer(&self) -> Option<Address> {
+        self.signature
+            .as_ref()
+            .filter(|sig| sig.verify(&*self.hash()))
+            .and_then(|sig| sig.address().ok())
+    }
+
+    /// Serializes the envelope.
+    pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
+        bincode::serialize(self)
+    }
+
+    /// Deserializes an envelope, without checking its signature.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The serialized envelope
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EnvelopeError> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+/// Serializes the given value, wraps it in an envelope for the given topic signed with the given keypair, and
+/// serializes the envelope.
+///
+/// # Arguments
+///
+/// * `topic` - The topic that the envelope will be published to
+/// * `value` - The proposal or vote that should be sent
+/// * `keypair` - The keypair of the sender
+pub fn seal<T: Serialize>(
+    topic: &str,
+    value: &T,
+    keypair: &ed25519_dalek::Keypair,
+) -> bincode::Result<Vec<u8>> {
+    Envelope::seal(topic, bincode::serialize(value)?, keypair).to_bytes()
+}
+
+/// Refuses envelopes that are stale, or that have already been received. Each sender's nonces are remembered for as
+/// long as their envelopes are fresh; after that, the envelopes are refused for being stale instead.
+pub struct ReplayGuard {
+    /// The age after which an envelope is refused
+    pub max_age: Duration,
+
+    /// The number of fresh envelopes remembered at most, past which the envelopes received the longest ago are
+    /// forgotten
+    pub max_remembered: usize,
+
+    /// The number of fresh envelopes remembered from any one sender, past which the sender's envelopes are refused
+    pub max_per_sender: usize,
+
+    /// The sender & nonce of each of the fresh envelopes received so far
+    seen: HashSet<(Address, u64)>,
+
+    /// The number of fresh envelopes received so far from each sender
+    per_sender: HashMap<Address, usize>,
+
+    /// The sender & nonce of each of the fresh envelopes received so far, along with the time at which they were
+    /// sent, in the order in which they were received
+    received: VecDeque<(DateTime<Utc>, (Address, u64))>,
+}
+
+/// Implement a set of replay guard helper methods.
+impl ReplayGuard {
+    /// Initializes a new replay guard refusing envelopes older than the given number of seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_age_secs` - The age, in seconds, after which an envelope is refused
+    pub fn new(max_age_secs: u64) -> Self {
+        Self {
+            max_age: Duration::seconds(max_age_secs as i64),
+            max_remembered: MAX_REMEMBERED_ENVELOPES,
+            max_per_sender: MAX_ENVELOPES_PER_SENDER,
+            seen: HashSet::new(),
+            per_sender: HashMap::new(),
+            received: VecDeque::new(),
+        }
+    }
+
+    /// Checks that the given envelope was received on the topic that it was sealed for, is authentic & fresh, hasn't
+    /// been received before, and that its sender hasn't sent too many fresh envelopes already. If so, the envelope is
+    /// remembered, and its sender is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `envelope` - The envelope that was received
+    /// * `topic` - The topic that the envelope was received on
+    /// * `now` - The current time
+    pub fn admit(
+        &mut self,
+        envelope: &Envelope,
+        topic: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Address, EnvelopeError> {
+        if envelope.topic != topic {
+            return Err(EnvelopeError::WrongTopic {
+                topic: envelope.topic.clone(),
+            });
+        }
+
+        let sender = envelope.signer().ok_or(EnvelopeError::InvalidSignature)?;
+
+        let age = now.signed_duration_since(envelope.sent_at);
+        if age > self.max_age {
+            return Err(EnvelopeError::Stale {
+                age: age.num_seconds(),
+            });
+        }
+        if age < -Duration::seconds(MAX_CLOCK_SKEW_SECS) {
+            return Err(EnvelopeError::FromTheFuture {
+                ahead: -age.num_seconds(),
+            });
+        }
+
+        self.forget_stale(now); // Stale envelopes are refused without needing to be remembered
+
+        let key = (sender, envelope.nonce);
+        if self.seen.contains(&key) {
+            return Err(EnvelopeError::Replayed);
+        }
+
+        let sent = self.per_sender.get(&sender).copied().unwrap_or(0);
+        if sent >= self.max_per_sender {
+            return Err(EnvelopeError::RateLimited { sent });
+        }
+
+        // Make room for the envelope, forgetting the envelopes received the longest ago first
+        while self.received.len() >= self.max_remembered.max(1) {
+            self.forget_oldest();
+        }
+
+        self.seen.insert(key);
+        self.received.push_back((envelope.sent_at, key));
+        *self.per_sender.entry(sender).or_insert(0) += 1;
+
+        Ok(sender)
+    }
+
+    /// Forgets each of the envelopes that would now be refused for being stale.
+    fn forget_stale(&mut self, now: DateTime<Utc>) {
+        while let Some((sent_at, _)) = self.received.front() {
+            if now.signed_duration_since(*sent_at) <= self.max_age {
+                break;
+            }
+
+            self.forget_oldest();
+        }
+    }
+
+    /// Forgets the envelope received the longest ago, if any envelope is remembered.
+    fn forget_oldest(&mut self) {
+        if let Some((_, key)) = self.received.pop_front() {
+            self.seen.remove(&key);
+
+            // Stop keeping count of the sender once none of its envelopes are remembered
+            if let Some(sent) = self.per_sender.get_mut(&key.0) {
+                *sent -= 1;
+
+                if *sent == 0 {
+                    self.per_sender.remove(&key.0);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::accounts::account::Account; // Import the account type
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_admit() {
+        let sender = Account::new();
+        let mut guard = ReplayGuard::new(DEFAULT_MAX_AGE_SECS);
+
+        let envelope = Envelope::seal(
+            "olympia/votes",
+            b"vote".to_vec(),
+            &sender.keypair().unwrap(),
+        );
+        let now = envelope.sent_at;
+
+        assert_eq!(
+            guard.admit(&envelope, "olympia/votes", now),
+            Ok(sender.address().unwrap())
+        );
+
+        // The same envelope can't be passed along twice, nor on another topic
+        assert_eq!(
+            guard.admit(&envelope, "olympia/votes", now),
+            Err(EnvelopeError::Replayed)
+        );
+        assert!(guard.admit(&envelope, "olympia/proposals", now).is_err());
+
+        // Once the envelope has gone stale, it's refused for that reason instead
+        let later = now + Duration::seconds(DEFAULT_MAX_AGE_SECS as i64 + 1);
+        assert_eq!(
+            guard.admit(&envelope, "olympia/votes", later),
+            Err(EnvelopeError::Stale {
+                age: DEFAULT_MAX_AGE_SECS as i64 + 1
+            })
+        );
+        assert!(guard
+            .admit(&envelope, "olympia/votes", now - Duration::seconds(3600))
+            .is_err());
+
+        // Tampering with the envelope breaks its signature
+        let mut forged = Envelope::seal(
+            "olympia/votes",
+            b"vote".to_vec(),
+            &sender.keypair().unwrap(),
+        );
+        forged.sent_at = forged.sent_at + Duration::seconds(1);
+        assert_eq!(
+            guard.admit(&forged, "olympia/votes", now),
+            Err(EnvelopeError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_admit_bounded() {
+        let (chatty, quiet) = (Account::new(), Account::new());
+        let mut guard = ReplayGuard::new(DEFAULT_MAX_AGE_SECS);
+        guard.max_remembered = 3;
+        guard.max_per_sender = 2;
+
+        let seal = |account: &Account| {
+            Envelope::seal(
+                "olympia/votes",
+                b"vote".to_vec(),
+                &account.keypair().unwrap(),
+            )
+        };
+        let first = seal(&chatty);
+        let now = first.sent_at;
+
+        // Senders can't have more than their share of fresh envelopes remembered
+        assert!(guard.admit(&first, "olympia/votes", now).is_ok());
+        assert!(guard.admit(&seal(&chatty), "olympia/votes", now).is_ok());
+        assert_eq!(
+            guard.admit(&seal(&chatty), "olympia/votes", now),
+            Err(EnvelopeError::RateLimited { sent: 2 })
+        );
+
+        // Once full, the guard forgets the envelopes received the longest ago first
+        assert!(guard.admit(&seal(&quiet), "olympia/votes", now).is_ok());
+        assert!(guard.admit(&seal(&quiet), "olympia/votes", now).is_ok());
+        assert_eq!(guard.received.len(), 3);
+        assert!(!guard
+            .seen
+            .contains(&(chatty.address().unwrap(), first.nonce)));
+        assert!(guard.admit(&seal(&chatty), "olympia/votes", now).is_ok()); // The sender has room again
+    }
+}
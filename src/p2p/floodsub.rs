@@ -1,21 +1,34 @@
 use super::{
     super::{
-        core::sys::{
-            proposal::{Operation, Proposal},
-            system::System,
-            vote::Vote,
+        common::lock::InstrumentedWriteGuard,
+        core::{
+            sys::{
+                config::Config,
+                consensus::Verdict,
+                journal::VoteOrigin,
+                proposal::{Operation, Proposal},
+                system::System,
+                vote::Vote,
+                voting_policy::Decision,
+            },
+            types::{issuance::ISSUANCE_PARAM, payloads::PayloadError, transaction::Transaction},
         },
         crypto::hash::Hash,
         validator::{GraphBoundValidator, Validator},
     },
     client::ClientBehavior,
+    compression,
+    envelope::{self, Envelope, EnvelopeError},
+    publisher::PublishQueue,
+    sync,
+    wal::MAX_REPLAY_ATTEMPTS,
 };
+use chrono::Utc;
 use libp2p::{
-    floodsub::{Floodsub, FloodsubEvent, Topic},
+    floodsub::{FloodsubEvent, Topic},
     swarm::NetworkBehaviourEventProcess,
+    PeerId,
 };
-use num::{bigint::BigUint, CheckedDiv, Zero};
-use std::sync::RwLockWriteGuard;
 
 /// A topic for all proposals in a network.
 pub const PROPOSALS_TOPIC: &str = "proposals";
@@ -23,6 +36,42 @@ pub const PROPOSALS_TOPIC: &str = "proposals";
 /// A topic for all votes in a network.
 pub const VOTES_TOPIC: &str = "votes";
 
+/// A topic for signed transactions gossiped on their own, rather than wrapped in a proposal.
+pub const TRANSACTIONS_TOPIC: &str = "transactions";
+
+/// The network-specific pubsub topics that a client subscribes and publishes to.
+#[derive(Clone)]
+pub struct Topics {
+    /// The topic for all proposals in the network
+    pub proposals: Topic,
+
+    /// The topic for all votes in the network
+    pub votes: Topic,
+
+    /// The topic for signed transactions in the network
+    pub transactions: Topic,
+}
+
+impl Topics {
+    /// Derives each of the pubsub topics for the network described by the given config, tagged with the version of
+    /// the envelope that messages are sealed in.
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - The configuration of the network that the topics should belong to
+    pub fn from_config(cfg: &Config) -> Self {
+        Self {
+            proposals: Topic::new(compression::versioned(
+                &cfg.namespaced_topic(PROPOSALS_TOPIC),
+            )),
+            votes: Topic::new(compression::versioned(&cfg.namespaced_topic(VOTES_TOPIC))),
+            transactions: Topic::new(compression::versioned(
+                &cfg.namespaced_topic(TRANSACTIONS_TOPIC),
+            )),
+        }
+    }
+}
+
 impl NetworkBehaviourEventProcess<FloodsubEvent> for ClientBehavior {
     /// Wait for an incoming gossipsub message from a known peer. Handle it somehow.
     fn inject_event(&mut self, message: FloodsubEvent) {
@@ -35,209 +84,716 @@ impl NetworkBehaviourEventProcess<FloodsubEvent> for ClientBehavior {
                 return;
             }
 
-            // If the message is a proposal message, handle it as such
-            if message.topics[0].id() == PROPOSALS_TOPIC {
-                debug!("Message is a proposal message; handling it as such");
+            // Take the message out of its envelope
+            let data = match compression::open(&message.data) {
+                Ok(data) => data,
+                Err(e) => {
+                    warn!("Failed to open message received via pubsub: {}", e);
 
-                // Try to deserialize a proposal from the provided message data. If this fails, we'll want to print the error to stderr.
-                let proposal: Proposal = match bincode::deserialize(&message.data) {
-                    Ok(deserialized) => deserialized,
-                    Err(e) => {
-                        warn!("Failed to deserialize proposal received via pubsub: {}", e);
+                    return;
+                }
+            };
 
-                        return;
-                    }
-                };
+            // Refuse any stale, replayed, or forged proposals and votes before they're journaled
+            let data = match self.admit_envelope(message.topics[0].id(), data) {
+                Ok(data) => data,
+                Err(e) => {
+                    warn!("Refusing message received via pubsub: {}", e);
+
+                    return;
+                }
+            };
 
-                // Get a writing lock on the client's runtime so that we can add the proposal
-                let mut rt = match self.runtime.write() {
-                    Ok(runtime) => runtime,
+            // Record the message before doing anything with it, so that it can be replayed if we crash
+            let seq = if let Some(wal) = &self.wal {
+                match wal.append(message.topics[0].id(), &data) {
+                    Ok(seq) => Some(seq),
                     Err(e) => {
-                        warn!(
-                            "Failed to obtain a writing lock on the client's runtime: {}",
-                            e
-                        );
+                        warn!("Failed to append message to the write-ahead log: {}", e);
 
-                        return;
+                        None
                     }
-                };
-                // Print out the proposal's details
-                info!(
-                    "Received new proposal to '{}' '{}': '{}' ({})",
-                    proposal.proposal_data.operation,
-                    proposal.proposal_data.param_name,
-                    proposal.proposal_name,
-                    proposal.proposal_id
-                );
+                }
+            } else {
+                None
+            };
 
-                // Copy the name of the parameter that the proposal will be changing so that we can vote on it.
-                let param_name = proposal.proposal_data.param_name.clone();
-                let id = proposal.proposal_id;
-                let data = proposal.proposal_data.clone();
-
-                // Add the proposal to the runtime
-                rt.push_proposal(proposal);
-
-                // If this is a proposal that we can automatically vote on, do it.
-                if param_name == "ledger::transactions" {
-                    // Get the data stored in the proposal
-                    let tx_bytes = if let Operation::Append { value_to_append } = data.operation {
-                        value_to_append
-                    } else {
-                        return;
-                    };
-
-                    // Derive a transaction from the data
-                    let tx = if let Ok(deserialized) = bincode::deserialize(&tx_bytes) {
-                        deserialized
-                    } else {
-                        return;
-                    };
-
-                    // The votes that we've generated for the proposal from each votinig account
-                    let mut resultant_votes: Vec<Vote> = Vec::new();
-
-                    // Print out the beginning voting process
-                    info!("Automatically verifying, and voting in accordance to the result of the output of the chosen validator with {} accounts", self.voting_accounts.len());
-
-                    // Vote for the proposal with each voting account
-                    for i in 0..self.voting_accounts.len() {
-                        // Try to get a keypair for the account that we can use to vote with
-                        if let Ok(keypair) = self.voting_accounts[i].keypair() {
-                            // Make a validator for the transaction
-                            let validator = GraphBoundValidator::new(&rt.ledger);
-
-                            // See if the transaction is valid or not
-                            let reason = validator.transaction_is_valid(&tx);
-
-                            // Make the vote
-                            let vote = Vote::new(id, reason.is_ok(), keypair);
-
-                            // Save the vote for later so we can publish it
-                            resultant_votes.push(vote.clone());
-
-                            // Register the vote
-                            match rt.register_vote_for_proposal(id, &vote) {
-                                Ok(_) => {
-                                    info!(
-                                        "Successfully submitted vote for proposal {}: {} because {}",
-                                        id, vote.in_favor, if let Some(e) = reason.err() {format!("{}", e)} else {"transaction is valid".to_owned()});
-                                }
-                                Err(e) => warn!("Failed to vote for proposal {}: {}", id, e),
-                            }
-                        }
-                    }
+            // Process the message, and only then pass it along, if it's met our relay policy
+            if self.handle_message(message.topics[0].id(), &data, Some(&message.source)) {
+                self.gossipsub.forward(&message);
+            }
 
-                    // Publish each of the votes that we collected from the unlocked
-                    // accounts
-                    if let Err(e) = publish_votes(resultant_votes, &mut self.gossipsub) {
-                        warn!("Failed to publish votes: {}", e);
-                    }
+            // The message has been handled, so it no longer needs to be kept around
+            if let (Some(wal), Some(seq)) = (&self.wal, seq) {
+                if let Err(e) = wal.complete(seq) {
+                    warn!(
+                        "Failed to remove message {} from the write-ahead log: {}",
+                        seq, e
+                    );
+                }
+            }
+        }
+    }
+}
 
-                    // Try to clear the proposal
-                    if potentially_clear_proposal(rt, &id) {
-                        info!("Successfully cleared proposal {}!", id);
+impl ClientBehavior {
+    /// Checks whether or not the client votes on proposals, either with accounts of its own, or through a remote
+    /// signer.
+    fn votes(&self) -> bool {
+        !self.voting_accounts.is_empty()
+            || self
+                .runtime
+                .read()
+                .map_or(false, |rt| rt.remote_signer.is_some())
+    }
 
-                        self.publish_dag();
-                    } else {
-                        debug!("Proposal {} is not mature enough...", id);
-                    }
+    /// Replays each of the messages left in the write-ahead log by a previous run of the client.
+    pub(crate) fn replay_wal(&mut self) {
+        // Collect the messages that were never fully processed
+        let pending = if let Some(wal) = &self.wal {
+            wal.pending()
+        } else {
+            return;
+        };
+
+        // Nothing to do if we shut down cleanly
+        if pending.is_empty() {
+            return;
+        }
+
+        info!(
+            "Replaying {} unprocessed messages from the write-ahead log",
+            pending.len()
+        );
+
+        // Process each message as if we just received it
+        for (seq, entry) in pending {
+            // Note the attempt before replaying the message, so that a message that crashes the client is dropped
+            // after a few tries, rather than crashing the client every time it starts
+            match self.wal.as_ref().map(|wal| wal.attempt(seq)) {
+                Some(Ok(attempts)) if attempts <= MAX_REPLAY_ATTEMPTS => {
+                    // Whoever relayed the message has been forgotten, so it can't count towards any proposal's
+                    // witnesses
+                    self.handle_message(&entry.topic, &entry.data, None);
                 }
-            } else if message.topics[0].id() == VOTES_TOPIC {
-                debug!("Message is a vote message; handling it as such");
+                Some(Ok(_)) => warn!(
+                    "Dropping message {} from the write-ahead log after {} failed replays",
+                    seq, MAX_REPLAY_ATTEMPTS
+                ),
+                Some(Err(e)) => warn!(
+                    "Dropping message {} from the write-ahead log, since replaying it couldn't be recorded: {}",
+                    seq, e
+                ),
+                None => return,
+            }
 
-                // Deserialize the vote that was sent to us via pubsub, encoded with bincode
-                let vote: Vote = match bincode::deserialize(&message.data) {
-                    Ok(v) => v,
-                    Err(e) => {
-                        warn!("Failed to deserialize vote received via pubsub: {}", e);
+            // Remove the message from the log
+            if let Some(wal) = &self.wal {
+                if let Err(e) = wal.complete(seq) {
+                    warn!(
+                        "Failed to remove message {} from the write-ahead log: {}",
+                        seq, e
+                    );
+                }
+            }
+        }
+    }
 
-                        return;
-                    }
-                };
+    /// Checks the signed envelope that proposals and votes are gossiped in, and takes the message out of it. Votes must
+    /// be sealed by their own voter, so that a captured vote can't be passed along again in a fresh envelope. Messages
+    /// on any other topic are returned as-is.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic that the message was received on
+    /// * `data` - The contents of the message
+    pub(crate) fn admit_envelope(
+        &mut self,
+        topic: &str,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, EnvelopeError> {
+        if topic != self.topics.proposals.id() && topic != self.topics.votes.id() {
+            return Ok(data);
+        }
 
-                // If the vote isn't valid, we must not continue
-                if !vote.valid() {
-                    warn!("Received invalid vote: {}", vote.hash());
+        let envelope = Envelope::from_bytes(&data)?;
+        let sender = self.replay_guard.admit(&envelope, topic, Utc::now())?;
 
-                    return;
+        if topic == self.topics.votes.id() {
+            let vote: Vote = bincode::deserialize(&envelope.payload)?;
+
+            if vote.voter_address() != Some(sender) {
+                return Err(EnvelopeError::SignerMismatch);
+            }
+        }
+
+        Ok(envelope.payload)
+    }
+
+    /// Handles a raw gossip message received on the given topic. Results in whether or not the message is worth
+    /// relaying to the rest of our peers.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic that the message was received on
+    /// * `data` - The raw contents of the message
+    /// * `source` - The peer that published the message, if it's known
+    pub(crate) fn handle_message(
+        &mut self,
+        topic: &str,
+        data: &[u8],
+        source: Option<&PeerId>,
+    ) -> bool {
+        // Light clients don't execute anything, so they've no use for proposals, votes or transactions
+        if self.runtime.read().map(|rt| rt.is_light()).unwrap_or(false) {
+            return false;
+        }
+
+        // If the message is a proposal message, handle it as such
+        if topic == self.topics.proposals.id() {
+            debug!("Message is a proposal message; handling it as such");
+
+            // Try to deserialize a proposal from the provided message data. If this fails, we'll want to print the error to stderr.
+            let proposal: Proposal = match bincode::deserialize(data) {
+                Ok(deserialized) => deserialized,
+                Err(e) => {
+                    warn!("Failed to deserialize proposal received via pubsub: {}", e);
+
+                    return false;
                 }
+            };
 
-                // Eventually, we'll want to register this vote in the runtime
-                let mut rt = if let Ok(rt) = self.runtime.write() {
-                    rt
-                } else {
-                    warn!("Failed to obtain a lock on the client's runtime. Aborting incoming vote registration process.");
+            // Nodes that don't vote can't validate what they pass along, so hold the proposal to
+            // the relay policy instead
+            if !self.votes() {
+                if let Err(e) = self.relay_policy.admit_proposal(&proposal) {
+                    debug!("Not relaying proposal {}: {}", proposal.proposal_id, e);
 
-                    return;
-                };
+                    return false;
+                }
+            }
 
-                // The proposal that the vote is in favor or against should exist in the runtime.
-                // Otherwise, it is invalid.
-                if !rt.pending_proposals.contains_key(&vote.target_proposal) {
+            self.handle_proposal(proposal)
+        } else if topic == self.topics.transactions.id() {
+            debug!("Message is a transaction message; handling it as such");
+
+            // Deserialize the signed transaction that was gossiped to us
+            let mut tx: Transaction = match bincode::deserialize(data) {
+                Ok(deserialized) => deserialized,
+                Err(e) => {
                     warn!(
-                        "Received invalid vote: {} (targets an unknown proposal)",
-                        vote.hash()
+                        "Failed to deserialize transaction received via pubsub: {}",
+                        e
                     );
 
-                    return;
+                    return false;
                 }
+            };
+
+            // The payload may have been left out in favor of a reference to one that we've already stored
+            let inflated = match self.runtime.read() {
+                Ok(rt) => rt
+                    .ledger
+                    .payload_store()
+                    .map_err(PayloadError::from)
+                    .and_then(|payloads| payloads.inflate(&mut tx)),
+                Err(_) => {
+                    warn!("Failed to obtain a reading lock on the client's runtime");
+
+                    return false;
+                }
+            };
+            if let Err(e) = inflated {
+                warn!(
+                    "Dropping transaction {} received via pubsub: {}",
+                    tx.hash, e
+                );
 
-                // Collect metadata regarding the vote so that we can alert the user of the vote in
-                // the future
-                let vote_value: String = format!("{}", vote);
-                let vote_hash = vote.hash();
+                return false;
+            }
 
-                // Register the vote, and log any errors that come up along the way
-                if let Err(e) = rt.register_vote_for_proposal(vote.target_proposal, &vote) {
-                    warn!("Failed to register vote {}: {}", vote_hash, e);
+            self.handle_transaction(tx)
+        } else if topic == self.topics.votes.id() {
+            debug!("Message is a vote message; handling it as such");
 
-                    return;
+            // Deserialize the vote that was sent to us via pubsub, encoded with bincode
+            let vote: Vote = match bincode::deserialize(data) {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!("Failed to deserialize vote received via pubsub: {}", e);
+
+                    return false;
+                }
+            };
+
+            // If the vote isn't valid, we must not continue
+            if !vote.valid() {
+                warn!("Received invalid vote: {}", vote.hash());
+
+                return false;
+            }
+
+            // Hold the vote to the relay policy if we don't vote ourselves
+            if !self.votes() {
+                if let Err(e) = self.relay_policy.admit_vote(&vote) {
+                    debug!("Not relaying vote {}: {}", vote.hash(), e);
+
+                    return false;
+                }
+            }
+
+            // Note who published the vote, so that proposals can be held back until enough distinct peers have voted
+            let witness = source.map(|peer| self.witness(peer));
+
+            // Eventually, we'll want to register this vote in the runtime
+            let mut rt = if let Ok(rt) = self.runtime.write() {
+                rt
+            } else {
+                warn!("Failed to obtain a lock on the client's runtime. Aborting incoming vote registration process.");
+
+                return false;
+            };
+
+            // The proposal that the vote is in favor or against should exist in the runtime.
+            // Otherwise, it is invalid.
+            if !rt.pending_proposals.contains_key(&vote.target_proposal) {
+                warn!(
+                    "Received invalid vote: {} (targets an unknown proposal)",
+                    vote.hash()
+                );
+
+                return false;
+            }
+
+            // Collect metadata regarding the vote so that we can alert the user of the vote in
+            // the future
+            let vote_value: String = format!("{}", vote);
+            let vote_hash = vote.hash();
+
+            // Register the vote, and log any errors that come up along the way
+            if let Err(e) = rt.register_vote_for_proposal(vote.target_proposal, &vote) {
+                warn!("Failed to register vote {}: {}", vote_hash, e);
+
+                return false;
+            }
+            if let Some(witness) = witness {
+                rt.witness_vote(&vote.target_proposal, witness);
+            }
+
+            // Log the success!
+            info!(
+                "Received a new vote: {}; registered it with the runtime successfully",
+                vote_value
+            );
+
+            // Try to clear the proposal
+            if potentially_clear_proposal(rt, &vote.target_proposal) {
+                info!(
+                    "Successfully cleared proposal {}!",
+                    vote.target_proposal.short_id()
+                );
+
+                self.publish_dag();
+            } else {
+                debug!("Proposal {} is not mature enough...", vote.target_proposal);
+            }
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Validates a signed transaction gossiped on its own, and wraps it in a proposal to append it to the ledger.
+    /// Every node derives the same proposal from the same transaction, so the votes cast by each of them line up
+    /// without the proposal itself ever having to be published. Results in whether or not the transaction is new,
+    /// and valid as far as we can tell.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that was received
+    pub(crate) fn handle_transaction(&mut self, tx: Transaction) -> bool {
+        // An unsigned or forged transaction can't be attributed to its sender, so it isn't worth relaying
+        if !tx.verify_signature() {
+            warn!(
+                "Received transaction with an invalid signature: {}",
+                tx.hash
+            );
+
+            return false;
+        }
+
+        // Nodes that don't vote hold transactions to the relay policy, just like proposals
+        if !self.votes() {
+            if let Err(e) = self.relay_policy.admit_transaction(&tx) {
+                debug!("Not relaying transaction {}: {}", tx.hash, e);
+
+                return false;
+            }
+        }
+
+        let proposal = Proposal::for_transaction(&tx); // Wrap the transaction
+
+        // Make sure we haven't already seen the transaction, and that it's valid
+        if let Ok(rt) = self.runtime.read() {
+            if rt.ledger.hash_routes.contains_key(&tx.hash)
+                || rt.pending_proposals.contains_key(&proposal.proposal_id)
+            {
+                debug!("Ignoring transaction {}; it is already known", tx.hash);
+
+                return false;
+            }
+
+            let validator = GraphBoundValidator::new(&rt.ledger);
+
+            // Transactions citing parents that we haven't caught up to yet are judged once the parents arrive
+            if validator.missing_parents(&tx).is_empty() {
+                if let Err(e) = validator.transaction_is_valid(&tx) {
+                    info!("Dropping invalid transaction {}: {}", tx.hash, e);
+
+                    return false;
                 }
+            }
+        } else {
+            warn!("Failed to obtain a reading lock on the client's runtime");
+
+            return false;
+        }
+
+        info!("Received new transaction {}", tx.hash);
+
+        self.handle_proposal(proposal) // Vote on the transaction like any other proposal
+    }
+
+    /// Adds the given proposal to the runtime, and votes on it if the node's voting policy allows it. Results in
+    /// whether or not the proposal was added.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal that was received
+    pub(crate) fn handle_proposal(&mut self, proposal: Proposal) -> bool {
+        // Get a writing lock on the client's runtime so that we can add the proposal
+        let mut rt = match self.runtime.write() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                warn!(
+                    "Failed to obtain a writing lock on the client's runtime: {}",
+                    e
+                );
+
+                return false;
+            }
+        };
+        // Print out the proposal's details
+        info!(
+            "Received new proposal to {}: '{}' ({})",
+            proposal.proposal_data, proposal.proposal_name, proposal.proposal_id
+        );
 
-                // Log the success!
+        let id = proposal.proposal_id;
+        let proposal_data = proposal.proposal_data.clone();
+
+        // Add the proposal to the runtime
+        if let Err(e) = rt.push_proposal(proposal) {
+            warn!("Ignoring malformed proposal {}: {}", id, e);
+
+            return false;
+        }
+
+        // Check what the node's voting policy has to say about the proposal
+        match rt.voting_policy.decide(&proposal_data) {
+            Decision::Abstain => {
                 info!(
-                    "Received a new vote: {}; registered it with the runtime successfully",
-                    vote_value
+                    "Abstaining from proposal {} per the voting policy",
+                    id.short_id()
                 );
 
-                // Try to clear the proposal
-                if potentially_clear_proposal(rt, &vote.target_proposal) {
-                    info!("Successfully cleared proposal {}!", vote.target_proposal);
+                return true;
+            }
+            Decision::AwaitConfirmation => {
+                info!(
+                    "Proposal {} requires a manual vote (use the confirm_proposal RPC method)",
+                    id
+                );
 
-                    self.publish_dag();
-                } else {
-                    debug!("Proposal {} is not mature enough...", vote.target_proposal);
+                // Show the operator how busy the network has been, so that changes to fees can be weighed
+                if proposal_data
+                    .affected_params()
+                    .iter()
+                    .any(|param| param == "config::reward_per_gas")
+                {
+                    match rt.stats.recent(24) {
+                        Some(sample) => {
+                            info!("Network activity over the last day: {}", sample.describe())
+                        }
+                        None => info!("No network activity has been recorded yet"),
+                    }
                 }
+
+                rt.awaiting_confirmation.insert(id);
+
+                return true;
             }
+            Decision::Automatic => (),
         }
+
+        // Release the runtime, so that the vote can be cast
+        drop(rt);
+
+        self.vote_on_proposal(id, true);
+
+        true
     }
-}
 
-/// Publishes each of the provided votes via pubsub, using the provided floodsub adapter.
-/// This helper method terminates execution on the first error.
-///
-/// # Arguments
-///
-/// * `votes` - The votes that should be published
-/// * `adapter` - The floodsub instance that the votes will be published with
-pub(crate) fn publish_votes(votes: Vec<Vote>, adapter: &mut Floodsub) -> bincode::Result<()> {
-    for vote in votes.iter() {
-        match bincode::serialize(vote) {
-            Ok(serialized) => adapter.publish(Topic::new(VOTES_TOPIC), serialized),
+    /// Validates the pending proposal with the given ID, and votes on it with each of the node's voting accounts, if
+    /// it's a proposal that can be judged automatically. Votes on transactions citing parents that the ledger doesn't
+    /// hold yet can be held off until the parents have been fetched from peers.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the proposal
+    /// * `await_parents` - Whether or not to hold off on the vote if the transaction's parents are missing
+    pub(crate) fn vote_on_proposal(&mut self, id: Hash, await_parents: bool) {
+        let mut rt = match self.runtime.write() {
+            Ok(runtime) => runtime,
             Err(e) => {
                 warn!(
-                    "Failed to serialize and publish vote {}: {}",
-                    vote.hash(),
+                    "Failed to obtain a writing lock on the client's runtime: {}",
                     e
                 );
 
-                return Err(e);
+                return;
             }
+        };
+
+        // Votes couldn't be persisted alongside the transactions that they decide
+        if rt.is_read_only() {
+            debug!("Not voting on proposal {}, since the node is read-only", id);
+
+            return;
+        }
+
+        // Copy the name of the parameter that the proposal will be changing so that we can vote on it.
+        let proposal_data = match rt.pending_proposals.get(&id) {
+            Some(proposal) => proposal.proposal_data.clone(),
+            None => return,
+        };
+        let param_name = proposal_data.param_name.clone();
+
+        // If this is a proposal that we can automatically vote on, do it.
+        if param_name == "ledger::transactions" || param_name == ISSUANCE_PARAM {
+            // Get the data stored in the proposal
+            let tx_bytes = if let Operation::Append { value_to_append } = proposal_data.operation {
+                value_to_append
+            } else {
+                return;
+            };
+
+            // Derive a transaction from the data
+            let tx: Transaction = if let Ok(deserialized) = bincode::deserialize(&tx_bytes) {
+                deserialized
+            } else {
+                return;
+            };
+
+            // Hold off on judging transactions whose parents we haven't caught up to yet, and go get the parents
+            if param_name == "ledger::transactions" && await_parents {
+                let missing = GraphBoundValidator::new(&rt.ledger).missing_parents(&tx);
+
+                if !missing.is_empty() {
+                    match self.parent_fetcher.defer(id, missing, Utc::now()) {
+                        Some(to_request) => {
+                            info!(
+                                "Holding off on voting for transaction {} until its parents arrive",
+                                tx.hash.short_id()
+                            );
+
+                            // Release the runtime, so that the parents can be requested
+                            drop(rt);
+                            self.request_parents(to_request);
+
+                            return;
+                        }
+                        None => warn!(
+                            "Too many transactions are waiting on their parents; judging {} without them",
+                            tx.hash
+                        ),
+                    }
+                }
+            }
+
+            // Mints are held to the issuance schedule, rather than to the rules for ordinary transactions
+            let reason: Result<(), failure::Error> = if param_name == ISSUANCE_PARAM {
+                rt.check_mint(&tx).map(|_| ()).map_err(failure::Error::from)
+            } else {
+                GraphBoundValidator::new(&rt.ledger).transaction_is_valid(&tx)
+            };
+
+            // A transaction can be valid on its own, yet still fail once executed on top of its parents, so try it out
+            // against the current state before deciding
+            let reason = reason.and_then(|_| {
+                match rt
+                    .simulate_proposal(&id)
+                    .map(|simulation| simulation.failure())
+                {
+                    Ok(None) => Ok(()),
+                    Ok(Some(why)) => Err(failure::err_msg(why)),
+                    Err(e) => Err(e.into()),
+                }
+            });
+
+            // Why we're voting the way we are, as journaled for each voter
+            let verdict = match &reason {
+                Ok(_) => "transaction is valid".to_owned(),
+                Err(e) => e.to_string(),
+            };
+
+            // The votes that we've generated for the proposal from each votinig account, sealed for gossip
+            let mut resultant_votes: Vec<Vec<u8>> = Vec::new();
+
+            // Print out the beginning voting process
+            info!("Automatically verifying, and voting in accordance to the result of the output of the chosen validator with {} accounts", self.voting_accounts.len());
+
+            // Vote for the proposal with each voting account
+            for i in 0..self.voting_accounts.len() {
+                // Only vote with the accounts that the voting policy allows
+                if !self.voting_accounts[i]
+                    .address()
+                    .map_or(false, |address| rt.voting_policy.permits(&address))
+                {
+                    continue;
+                }
+
+                // Try to get a keypair for the account that we can use to vote with
+                if let (Ok(keypair), Ok(sealing_keypair)) = (
+                    self.voting_accounts[i].keypair(),
+                    self.voting_accounts[i].keypair(),
+                ) {
+                    // Make the vote
+                    let vote = Vote::new(id, reason.is_ok(), keypair);
+
+                    // Save the vote for later so we can publish it, sealed under the voter's own key
+                    match envelope::seal(self.topics.votes.id(), &vote, &sealing_keypair) {
+                        Ok(sealed) => resultant_votes.push(sealed),
+                        Err(e) => warn!("Failed to seal vote {}: {}", vote.hash(), e),
+                    }
+
+                    // Register the vote
+                    match rt.register_vote_for_proposal(id, &vote) {
+                        Ok(_) => {
+                            info!(
+                                "Successfully submitted vote for proposal {}: {} because {}",
+                                id.short_id(),
+                                vote.in_favor,
+                                verdict
+                            );
+
+                            rt.journal_vote(&vote, &verdict, VoteOrigin::Automatic, false);
+                        }
+                        Err(e) => warn!("Failed to vote for proposal {}: {}", id, e),
+                    }
+                }
+            }
+
+            // Have the remote signer vote with each of its accounts, if the node delegates its signing
+            for (vote, sealed) in rt.cast_remote_votes(id, reason.is_ok(), self.topics.votes.id()) {
+                resultant_votes.push(sealed);
+
+                match rt.register_vote_for_proposal(id, &vote) {
+                    Ok(_) => {
+                        info!(
+                            "Successfully submitted remotely signed vote for proposal {}: {}",
+                            id.short_id(),
+                            vote.in_favor
+                        );
+
+                        rt.journal_vote(&vote, &verdict, VoteOrigin::Automatic, true);
+                    }
+                    Err(e) => warn!("Failed to vote for proposal {}: {}", id, e),
+                }
+            }
+
+            // Publish each of the votes that we collected from the unlocked
+            // accounts
+            publish_votes(resultant_votes, &mut self.publisher, &self.topics.votes);
+
+            // Try to clear the proposal
+            if potentially_clear_proposal(rt, &id) {
+                info!("Successfully cleared proposal {}!", id);
+
+                self.publish_dag();
+            } else {
+                debug!("Proposal {} is not mature enough...", id);
+            }
+        }
+    }
+}
+
+/// Implement a set of helper methods for fetching the parents that votes are held off on.
+impl ClientBehavior {
+    /// Requests each of the given transactions from peers, so that the votes waiting on them can be cast.
+    ///
+    /// # Arguments
+    ///
+    /// * `parents` - The hashes of the transactions to request
+    pub(crate) fn request_parents(&mut self, parents: Vec<Hash>) {
+        for parent in parents {
+            debug!(
+                "Requesting missing parent transaction {} from peers",
+                parent
+            );
+
+            // Get a quorum to poll at least 50% of the network
+            let q = self.active_subset_quorum();
+
+            self.kad_dht
+                .get_record(&sync::transaction_with_hash_key(parent), q);
+        }
+    }
+
+    /// Votes on each of the proposals that were waiting on the given transaction, now that it has arrived.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The hash of the transaction that arrived
+    pub(crate) fn vote_on_arrived_parent(&mut self, parent: Hash) {
+        for id in self.parent_fetcher.arrived(parent) {
+            self.vote_on_proposal(id, true);
         }
     }
 
-    Ok(())
+    /// Votes on each of the proposals that have waited on their parents for too long, without them.
+    pub fn vote_on_expired_deferrals(&mut self) {
+        let expired = self.parent_fetcher.expired(Utc::now());
+        if !expired.is_empty() {
+            warn!(
+                "Gave up on the parents of {} transaction(s); voting without them",
+                expired.len()
+            );
+        }
+
+        for id in expired {
+            self.vote_on_proposal(id, false);
+        }
+    }
+}
+
+/// Queues each of the provided votes to be published via pubsub.
+///
+/// # Arguments
+///
+/// * `votes` - The votes that should be published, each sealed for gossip by its voter
+/// * `queue` - The publishing queue that the votes will wait in
+/// * `topic` - The topic that the votes should be published to
+pub(crate) fn publish_votes(votes: Vec<Vec<u8>>, queue: &mut PublishQueue, topic: &Topic) {
+    for sealed in votes.iter() {
+        if queue
+            .push(topic.clone(), compression::seal(sealed))
+            .is_some()
+        {
+            warn!("The publishing queue is full; dropped a message to make room for a vote");
+        }
+    }
 }
 
 /// Attempts to execute the given proposal, on the condition that it has enough vote weight to
@@ -249,50 +805,46 @@ pub(crate) fn publish_votes(votes: Vec<Vote>, adapter: &mut Floodsub) -> bincode
 /// may be executed
 /// * `proposal` - The proposal that should be executed
 pub(crate) fn potentially_clear_proposal(
-    mut runtime: RwLockWriteGuard<System>,
+    mut runtime: InstrumentedWriteGuard<System>,
     proposal: &Hash,
 ) -> bool {
-    // Get the # of coins that the proposer must have, at least, in order to execute it
-    let acceptable_majority = if let Some(maj) = runtime
-        .ledger
-        .overall_issuance()
-        .checked_div(&BigUint::from(2 as u8))
-    {
-        maj
-    } else {
-        return false;
-    };
-
-    // In order to execute the proposal, we must have a 1/2 majority
-    if runtime
-        .get_coins_in_support_of(proposal)
-        .to_biguint()
-        .unwrap_or_else(BigUint::zero)
-        >= acceptable_majority
-    {
-        // If the proposal is invalid, don't execute it, but clear it, nonetheless
-        if !runtime.validate_proposal(proposal) {
-            runtime.pending_proposals.remove(proposal);
-
-            return false;
-        }
+    // Weigh the votes cast so far against the network's consensus thresholds
+    match runtime.tally_proposal(proposal) {
+        Verdict::Accepted => {
+            // If the proposal is invalid, don't execute it, but clear it, nonetheless
+            if !runtime.validate_proposal(proposal) {
+                runtime.resolve_proposal(proposal);
+
+                return false;
+            }
 
-        // Execute the proposal
-        return match runtime.execute_proposal(*proposal) {
-            Ok(()) => true,
-            Err(e) => {
-                warn!("Failed to execute proposal {}: {}", proposal, e);
+            // Execute the proposal
+            match runtime.execute_proposal(*proposal) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("Failed to execute proposal {}: {}", proposal, e);
 
-                false
+                    false
+                }
             }
-        };
-    } else if (-runtime.get_coins_in_support_of(proposal))
-        .to_biguint()
-        .unwrap_or_else(BigUint::zero)
-        >= acceptable_majority
-    {
-        return runtime.pending_proposals.remove(proposal).is_some();
-    }
+        }
+        Verdict::Rejected => runtime.resolve_proposal(proposal).is_some(),
+        Verdict::Premature => {
+            debug!(
+                "Proposal {} has enough votes, but its voting period hasn't passed yet",
+                proposal
+            );
+
+            false
+        }
+        Verdict::Undiverse => {
+            debug!(
+                "Proposal {} has enough votes, but from too few distinct peers",
+                proposal
+            );
 
-    false
+            false
+        }
+        Verdict::Undecided => false,
+    }
 }
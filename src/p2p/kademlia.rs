@@ -1,21 +1,31 @@
 use super::{
     super::{
         core::{
-            sys::proposal::{Operation, Proposal, ProposalData},
-            types::transaction::Transaction,
+            sys::{
+                config_sync::ConfigRecord,
+                proposal::{Operation, Proposal, ProposalData},
+                system::System,
+            },
+            types::{
+                light::{LightClientError, LightHeader},
+                snapshot::{SnapshotAssembly, SnapshotManifest},
+                transaction::Transaction,
+            },
         },
         crypto::hash::Hash,
     },
+    attestation::HeadAttestation,
     client::ClientBehavior,
-    sync,
+    compression, sync,
 };
 
 use libp2p::{
     kad::{
         record::{Key, Record},
-        KademliaEvent, Quorum,
+        GetRecordError, KademliaEvent, Quorum,
     },
     swarm::NetworkBehaviourEventProcess,
+    PeerId,
 };
 
 /// Network synchronization via KAD DHT events.
@@ -26,7 +36,33 @@ impl NetworkBehaviourEventProcess<KademliaEvent> for ClientBehavior {
         match event {
             // The record was found successfully; print it
             KademliaEvent::GetRecordResult(Ok(result)) => {
-                for Record { key, value, .. } in result.records {
+                // Whether or not a snapshot was passed over, since nothing we trust vouches for its anchor
+                let mut unvouched_snapshot = false;
+
+                // Handle the records published by the fastest peers first
+                let mut records = result.records;
+                self.rank_records(&mut records);
+
+                for Record {
+                    key,
+                    value,
+                    publisher,
+                    ..
+                } in records
+                {
+                    self.note_record(publisher.as_ref(), RecordOutcome::Fetched);
+
+                    // Take the record's value out of its envelope
+                    let value = match compression::open(&value) {
+                        Ok(value) => value,
+                        Err(e) => {
+                            debug!("Failed to open record value: {}", e);
+                            self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                            continue;
+                        }
+                    };
+
                     // Handle different key types
                     match key.as_ref() {
                         b"ledger::transactions::root" => {
@@ -38,37 +74,278 @@ impl NetworkBehaviourEventProcess<KademliaEvent> for ClientBehavior {
                                 "Received the root transaction hash for the network: {}",
                                 root_hash
                             );
+
+                            // Don't follow a root that the operator has ruled out
+                            if self.conflicts_with_checkpoint(root_hash) {
+                                self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                                continue;
+                            }
+                            self.note_record(publisher.as_ref(), RecordOutcome::Head(root_hash));
+
                             let q: Quorum = self.active_subset_quorum();
 
-                            // Get the actual root transaction, not just the hash, from the network
+                            // Get the actual root transaction (or just its header, if we're a light client), not just
+                            // the hash, from the network
                             self.kad_dht.get_record(
-                                &Key::new(&sync::transaction_with_hash_key(root_hash)),
+                                &Key::new(&if self.is_light() {
+                                    sync::light_header_key(root_hash)
+                                } else {
+                                    sync::transaction_with_hash_key(root_hash)
+                                }),
                                 q,
                             );
                         }
 
+                        b"ledger::light::head" => {
+                            let header = match LightHeader::from_bytes(&value) {
+                                Ok(header) => header,
+                                Err(e) => {
+                                    debug!("Failed to decode light head: {}", e);
+                                    self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                                    continue;
+                                }
+                            };
+
+                            // Only light clients follow headers
+                            if self.is_light() {
+                                self.follow_header(header, publisher.as_ref());
+                            }
+                        }
+
+                        b"ledger::transactions::head" => {
+                            // Only signed attestations count; raw hashes say nothing about who vouches for them
+                            let attestation = match HeadAttestation::from_bytes(&value) {
+                                Ok(attestation) => attestation,
+                                Err(e) => {
+                                    debug!("Failed to decode head attestation: {}", e);
+                                    self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                                    continue;
+                                }
+                            };
+
+                            // Weigh the attestation by the publisher's stake, as far as our ledger knows. Light clients
+                            // don't hold the state, so they can't.
+                            let stake = if let Ok(rt) = self.runtime.read() {
+                                if rt.is_light() {
+                                    None
+                                } else {
+                                    Some(rt.ledger.get_balance_of_account(&attestation.publisher))
+                                }
+                            } else {
+                                continue;
+                            };
+
+                            // Note which peer the attestation came from, so that one host can't vouch for a head alone
+                            let witness = publisher.as_ref().map(|peer| self.witness(peer));
+
+                            if let Err(e) =
+                                self.head_attestations.record(&attestation, stake, witness)
+                            {
+                                debug!("Refusing head attestation: {}", e);
+                                self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                                continue;
+                            }
+                            self.note_record(
+                                publisher.as_ref(),
+                                RecordOutcome::Head(attestation.head),
+                            );
+
+                            // Light clients don't follow the DAG itself, only the states vouched for alongside it
+                            if self.is_light() {
+                                if let Some(state) = attestation.state {
+                                    self.adopt_attested_state(state.hash);
+                                }
+
+                                continue;
+                            }
+
+                            // Synchronize towards the head once enough of the network's stake vouches for it
+                            let mut retargeted = false;
+                            if let (Some(target), Ok(mut sync_peers)) =
+                                (self.head_attestations.target(), self.sync_peers.write())
+                            {
+                                if sync_peers.record_target_head(target.clone()) {
+                                    info!(
+                                        "Synchronizing towards head {} at height {}, vouched for by {} publishers",
+                                        target.head, target.height, target.publishers
+                                    );
+
+                                    retargeted = true;
+                                }
+                            }
+
+                            // Pick up where synchronization stopped waiting for the attestations
+                            if retargeted {
+                                self.synchronize_dag();
+                            }
+                        }
+
+                        b"config" => {
+                            let record = match ConfigRecord::from_bytes(&value) {
+                                Ok(record) => record,
+                                Err(e) => {
+                                    debug!("Failed to decode config record: {}", e);
+                                    self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                                    continue;
+                                }
+                            };
+
+                            self.handle_config_record(record, publisher.as_ref());
+                        }
+
+                        b"ledger::snapshots::latest" => {
+                            // Only one snapshot is downloaded at once, and only to pick up an empty ledger
+                            if !self.fast_sync || self.snapshot_download.is_some() {
+                                continue;
+                            }
+
+                            let manifest = match SnapshotManifest::from_bytes(&value) {
+                                Ok(manifest) if !manifest.chunks.is_empty() => manifest,
+                                Ok(_) => {
+                                    self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                                    continue;
+                                }
+                                Err(e) => {
+                                    debug!("Failed to decode snapshot manifest: {}", e);
+                                    self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                                    continue;
+                                }
+                            };
+
+                            // Don't pick up from an anchor that the operator has ruled out
+                            if self.refuses_snapshot(&manifest) {
+                                self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                                continue;
+                            }
+
+                            // The snapshot's state is taken on trust, so its anchor has to be vouched for first
+                            if !self.vouches_for_snapshot(&manifest) {
+                                debug!(
+                                    "Passing over snapshot {}, since its anchor {} is neither pinned nor attested",
+                                    manifest.id, manifest.anchor
+                                );
+                                unvouched_snapshot = true;
+
+                                continue;
+                            }
+                            self.note_record(
+                                publisher.as_ref(),
+                                RecordOutcome::Head(manifest.anchor),
+                            );
+
+                            info!(
+                                "Downloading snapshot {} of the ledger at height {}, in {} chunk(s)",
+                                manifest.id,
+                                manifest.height,
+                                manifest.chunks.len()
+                            );
+
+                            let download = SnapshotAssembly::new(manifest);
+                            let q: Quorum = self.active_subset_quorum();
+
+                            // Fetch each of the snapshot's chunks
+                            for chunk in download.missing() {
+                                self.kad_dht
+                                    .get_record(&Key::new(&sync::snapshot_chunk_key(chunk)), q);
+                            }
+
+                            self.snapshot_download = Some(download);
+                        }
+
+                        _ if String::from_utf8_lossy(key.as_ref())
+                            .contains("ledger::light::header") =>
+                        {
+                            let header = match LightHeader::from_bytes(&value) {
+                                Ok(header) => header,
+                                Err(e) => {
+                                    debug!("Failed to decode light header: {}", e);
+                                    self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                                    continue;
+                                }
+                            };
+                            let hash = header.hash;
+
+                            // Carry on with the header that follows this one
+                            if self.is_light() && self.follow_header(header, publisher.as_ref()) {
+                                let q: Quorum = self.active_subset_quorum();
+
+                                self.kad_dht
+                                    .get_record(&Key::new(&sync::next_transaction_key(hash)), q);
+                            }
+                        }
+
+                        _ if String::from_utf8_lossy(key.as_ref())
+                            .contains("ledger::snapshots::chunk") =>
+                        {
+                            let complete = match &mut self.snapshot_download {
+                                Some(download) => match download.insert(value) {
+                                    Ok(_) => download.is_complete(),
+                                    Err(e) => {
+                                        debug!("Refusing snapshot chunk: {}", e);
+                                        self.note_record(
+                                            publisher.as_ref(),
+                                            RecordOutcome::Rejected,
+                                        );
+
+                                        continue;
+                                    }
+                                },
+                                None => continue,
+                            };
+
+                            if complete {
+                                self.install_downloaded_snapshot();
+                            }
+                        }
+
                         _ => {
                             // If the response is a transaction response, try deserializing the transaction, and doing something with it
                             if String::from_utf8_lossy(key.as_ref())
                                 .contains("ledger::transactions::tx")
                             {
                                 // Deserialize the transaction that the peer responded with
-                                let tx: Transaction =
-                                    if let Ok(val) = bincode::deserialize::<Transaction>(&value) {
-                                        // Alert the user that we've obtained a copy of the tx
-                                        info!(
-                                            "Obtained a copy of a transaction with the hash: {}",
-                                            val.hash.clone()
-                                        );
+                                let tx: Transaction = if let Ok(val) =
+                                    bincode::deserialize::<Transaction>(&value)
+                                {
+                                    // Alert the user that we've obtained a copy of the tx
+                                    info!(
+                                        "Obtained a copy of a transaction with the hash: {}",
+                                        val.hash.clone()
+                                    );
 
-                                        val
-                                    } else {
-                                        return;
-                                    };
+                                    val
+                                } else {
+                                    self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                                    return;
+                                };
                                 let hash = tx.hash;
 
+                                // Whether or not the transaction made it into the DAG
+                                let mut accepted = true;
+
                                 // Try to get a lock on the runtime so we can put the tx in the database
                                 if let Ok(mut rt) = self.runtime.write() {
+                                    // Refuse any transaction that disagrees with a pinned checkpoint, and stop
+                                    // following the history that it belongs to
+                                    if refuses_history(&rt, hash) {
+                                        self.note_record(
+                                            publisher.as_ref(),
+                                            RecordOutcome::Rejected,
+                                        );
+
+                                        continue;
+                                    }
+
                                     // If we haven't a single node in the graph, we'll just treat this node as the root
                                     if rt.ledger.nodes.is_empty() {
                                         // Just push the root node onto the graph
@@ -89,21 +366,86 @@ impl NetworkBehaviourEventProcess<KademliaEvent> for ClientBehavior {
                                         let id = proposal.proposal_id;
 
                                         // Put the proposal in the system, so we can execute it
-                                        rt.push_proposal(proposal);
+                                        if let Err(e) = rt.push_proposal(proposal) {
+                                            warn!("Failed to register transaction {}: {}", hash, e);
+                                            self.note_record(
+                                                publisher.as_ref(),
+                                                RecordOutcome::Rejected,
+                                            );
+
+                                            return;
+                                        }
+
+                                        let height_before = rt.ledger.nodes.len();
 
                                         // Execute the proposal so it gets added to the dag
                                         match rt.execute_proposal(id) {
                                             Ok(_) => {
-                                                info!("Successfully executed transaction {}", id)
+                                                info!("Successfully executed transaction {}", id);
+
+                                                // Every so often, make sure that the branch we're following hasn't
+                                                // conjured finks out of nowhere
+                                                if self
+                                                    .sync_audit
+                                                    .record(height_before, publisher.as_ref())
+                                                {
+                                                    match rt.audit_supply() {
+                                                        Ok(_) => self.sync_audit.passed(),
+                                                        Err(e) => {
+                                                            let (verified_height, suppliers) =
+                                                                self.sync_audit.failed();
+                                                            let discarded =
+                                                                rt.discard_since(verified_height);
+
+                                                            warn!(
+                                                                "Discarded {} synchronized transaction(s), since {}; \
+                                                                 flagging {} supplying peer(s)",
+                                                                discarded,
+                                                                e,
+                                                                suppliers.len()
+                                                            );
+
+                                                            if let Ok(mut sync_peers) =
+                                                                self.sync_peers.write()
+                                                            {
+                                                                for peer in &suppliers {
+                                                                    sync_peers
+                                                                        .record_violation(peer);
+                                                                }
+                                                            }
+
+                                                            // Stop following the branch
+                                                            continue;
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                            Err(e) => {
+                                                warn!(
+                                                    "Failed to execute transaction {}: {}",
+                                                    hash, e
+                                                );
+
+                                                accepted = false;
                                             }
-                                            Err(e) => warn!(
-                                                "Failed to execute transaction {}: {}",
-                                                hash, e
-                                            ),
                                         }
                                     }
                                 }
 
+                                self.note_record(
+                                    publisher.as_ref(),
+                                    if accepted {
+                                        RecordOutcome::Head(hash)
+                                    } else {
+                                        RecordOutcome::Rejected
+                                    },
+                                );
+
+                                // Vote on any transactions that were waiting on this one
+                                if accepted {
+                                    self.vote_on_arrived_parent(hash);
+                                }
+
                                 // Get a quorum to poll at least 50% of the network
                                 let q: Quorum = self.active_subset_quorum();
 
@@ -120,22 +462,72 @@ impl NetworkBehaviourEventProcess<KademliaEvent> for ClientBehavior {
 
                                 info!("Determined the next hash in the remote DAG: {}", hash);
 
+                                // Don't bother downloading a transaction that we'd refuse anyway
+                                if self.conflicts_with_checkpoint(hash) {
+                                    self.note_record(publisher.as_ref(), RecordOutcome::Rejected);
+
+                                    continue;
+                                }
+
+                                // Only follow the DAG as far as the head that the network's stake vouches for
+                                if !self.is_light() && !self.vouched_for(hash) {
+                                    debug!(
+                                        "Not following transaction {} until enough stake vouches for a head past it",
+                                        hash
+                                    );
+
+                                    continue;
+                                }
+                                self.note_record(publisher.as_ref(), RecordOutcome::Head(hash));
+
                                 // Get a quorum to poll at least 50% of the network
                                 let q: Quorum = self.active_subset_quorum();
 
-                                // Get the actual transaction corresponding to what we now know is the hash of such a transaction
+                                // Get the actual transaction (or just its header, if we're a light client)
+                                // corresponding to what we now know is the hash of such a transaction
                                 self.kad_dht.get_record(
-                                    &Key::new(&sync::transaction_with_hash_key(hash)),
+                                    &Key::new(&if self.is_light() {
+                                        sync::light_header_key(hash)
+                                    } else {
+                                        sync::transaction_with_hash_key(hash)
+                                    }),
                                     q,
                                 );
                             }
                         }
                     }
                 }
+
+                // Without a snapshot that we can trust, the ledger has to be replayed from its genesis
+                if unvouched_snapshot && self.fast_sync && self.snapshot_download.is_none() {
+                    info!("No trusted snapshot of the ledger has been published; synchronizing from the genesis");
+
+                    self.fast_sync = false;
+                    self.synchronize_dag();
+                }
             }
 
             // An error occurred while fetching the record; print it
             KademliaEvent::GetRecordResult(Err(e)) => {
+                // Nobody has published anything after our head, so we hold everything there is for now
+                if let GetRecordError::NotFound { key, .. } = &e {
+                    if String::from_utf8_lossy(key.as_ref()).contains("ledger::transactions::next")
+                    {
+                        if let Ok(mut sync_peers) = self.sync_peers.write() {
+                            sync_peers.record_caught_up();
+                        }
+                    }
+
+                    // Nobody has published a snapshot yet, so the ledger has to be replayed from its genesis
+                    if key.as_ref() == sync::LATEST_SNAPSHOT_KEY && self.snapshot_download.is_none()
+                    {
+                        info!("No snapshot of the ledger has been published; synchronizing from the genesis");
+
+                        self.fast_sync = false;
+                        self.synchronize_dag();
+                    }
+                }
+
                 debug!("Failed to load record: {:?}", e);
             }
 
@@ -158,3 +550,326 @@ impl NetworkBehaviourEventProcess<KademliaEvent> for ClientBehavior {
         }
     }
 }
+
+/// What became of a DHT record fetched from a peer, as far as the peer's sync diagnostics are concerned.
+enum RecordOutcome {
+    /// The record was received
+    Fetched,
+
+    /// The record was refused
+    Rejected,
+
+    /// The record pointed us to the transaction with the given hash
+    Head(Hash),
+}
+
+impl ClientBehavior {
+    /// Updates the sync diagnostics of the peer that published a record, if the record names its publisher.
+    ///
+    /// # Arguments
+    ///
+    /// * `publisher` - The peer that published the record
+    /// * `outcome` - What became of the record
+    fn note_record(&self, publisher: Option<&PeerId>, outcome: RecordOutcome) {
+        if let (Some(peer), Ok(mut sync_peers)) = (publisher, self.sync_peers.write()) {
+            match outcome {
+                RecordOutcome::Fetched => sync_peers.record_fetched(peer),
+                RecordOutcome::Rejected => sync_peers.record_rejected(peer),
+                RecordOutcome::Head(hash) => sync_peers.record_head(peer, hash),
+            }
+        }
+    }
+
+    /// Checks whether or not the transaction with the given hash, if it were the next transaction synchronized,
+    /// would conflict with one of the checkpoints pinned by the operator.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction
+    fn conflicts_with_checkpoint(&self, hash: Hash) -> bool {
+        self.runtime
+            .read()
+            .map(|rt| refuses_history(&rt, hash))
+            .unwrap_or(true)
+    }
+
+    /// Checks whether or not synchronization may carry on to the transaction with the given hash, which a peer claims
+    /// follows the latest one that we hold. The DAG is only downloaded up to the head that enough of the network's
+    /// stake vouches for, and no further until the attestations catch up.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction
+    fn vouched_for(&self, hash: Hash) -> bool {
+        let target = match self.head_attestations.target() {
+            Some(target) => target,
+            None => return false,
+        };
+
+        if target.head == hash {
+            return true;
+        }
+
+        self.runtime
+            .read()
+            .map(|rt| {
+                !rt.ledger.hash_routes.contains_key(&target.head)
+                    && (rt.ledger.nodes.len() as u64) < target.height
+            })
+            .unwrap_or(false)
+    }
+
+    /// Lets a light client check balances against the state resulting from the transaction with the given hash, once
+    /// enough publishers vouch for the same balance root for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction
+    fn adopt_attested_state(&mut self, hash: Hash) {
+        let root = match self.head_attestations.attested_root(&hash) {
+            Some(root) => root,
+            None => return,
+        };
+
+        if let Ok(mut rt) = self.runtime.write() {
+            if let Some(chain) = rt.header_chain.as_mut() {
+                chain.attest(hash, root);
+            }
+        }
+    }
+
+    /// Checks whether or not the client runs as a light client, only following the DAG's headers.
+    pub(crate) fn is_light(&self) -> bool {
+        self.runtime.read().map(|rt| rt.is_light()).unwrap_or(false)
+    }
+
+    /// Adds a header published by a peer to the headers followed by the client. Results in whether or not the header
+    /// is now among them.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The header
+    /// * `publisher` - The peer that published the header
+    fn follow_header(&mut self, header: LightHeader, publisher: Option<&PeerId>) -> bool {
+        let hash = header.hash;
+
+        let followed = match self.runtime.write() {
+            Ok(mut rt) => {
+                let next = match &rt.header_chain {
+                    Some(chain) => {
+                        chain.get(&hash).is_none()
+                            && chain
+                                .head()
+                                .map_or(true, |head| head.height + 1 == header.height)
+                    }
+                    None => return false,
+                };
+
+                // New headers are held to the operator's checkpoints, just like transactions
+                if next && refuses_history(&rt, hash) {
+                    Err(LightClientError::ConflictingHeader {
+                        hash: hash.to_str(),
+                    })
+                } else {
+                    rt.header_chain
+                        .as_mut()
+                        .map_or(Ok(false), |chain| chain.insert(header))
+                }
+            }
+            Err(_) => return false,
+        };
+
+        match followed {
+            Ok(_) => {
+                self.note_record(publisher, RecordOutcome::Head(hash));
+
+                true
+            }
+
+            // A head that we haven't caught up to yet isn't the publisher's fault
+            Err(LightClientError::UnknownParent { .. })
+            | Err(LightClientError::OutOfOrder { .. }) => false,
+            Err(e) => {
+                debug!("Refusing light header: {}", e);
+                self.note_record(publisher, RecordOutcome::Rejected);
+
+                false
+            }
+        }
+    }
+
+    /// Handles a version of the network's config published by a peer. Signatures gathered for the version that the
+    /// client is on are added to its own, and handed back to the network; the next version is applied if its
+    /// proposal produces it, and enough of the network's stake vouches for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The config record
+    /// * `publisher` - The peer that published the record
+    fn handle_config_record(&mut self, record: ConfigRecord, publisher: Option<&PeerId>) {
+        let local = match self.runtime.read() {
+            Ok(rt) => rt.config.clone(),
+            Err(_) => return,
+        };
+
+        // Signatures gathered by others for the version that we're on add up with our own
+        if record.describes(&local) {
+            let merged = match &mut self.config_record {
+                Some(ours) if ours.describes(&local) => ours.merge(record) > 0,
+                _ => {
+                    self.config_record = Some(record);
+
+                    false
+                }
+            };
+
+            if merged {
+                self.put_config_record();
+            }
+
+            return;
+        }
+
+        let applied = match self.runtime.write() {
+            Ok(mut rt) => rt.apply_config_record(&record),
+            Err(_) => return,
+        };
+
+        match applied {
+            Ok(_) => self.config_record = Some(record),
+            Err(e) => {
+                debug!("Refusing config record: {}", e);
+
+                // Versions that we've moved past, or that we can't reach yet, aren't the publisher's fault
+                if record.sequence() == local.sequence + 1 {
+                    self.note_record(publisher, RecordOutcome::Rejected);
+                }
+            }
+        }
+    }
+
+    /// Checks whether or not the snapshot described by the given manifest should be refused, since the ledger isn't
+    /// empty anymore, the snapshot is split into too many chunks, or the snapshot's anchor conflicts with one of the
+    /// checkpoints pinned by the operator.
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest` - The manifest of the snapshot
+    fn refuses_snapshot(&self, manifest: &SnapshotManifest) -> bool {
+        let rt = match self.runtime.read() {
+            Ok(rt) => rt,
+            Err(_) => return true,
+        };
+
+        if !rt.ledger.nodes.is_empty() {
+            return true;
+        }
+
+        if let Err(e) = manifest.check_size() {
+            warn!("Refusing snapshot {}: {}", manifest.id, e);
+
+            return true;
+        }
+
+        match rt
+            .checkpoints
+            .check(manifest.height.saturating_sub(1) as usize, manifest.anchor)
+        {
+            Ok(_) => false,
+            Err(e) => {
+                warn!("Refusing snapshot {}: {}", manifest.id, e);
+
+                true
+            }
+        }
+    }
+
+    /// Checks whether or not the anchor of the snapshot described by the given manifest is one that we trust: either
+    /// pinned by the operator at the snapshot's height, or the head that enough of the network's stake vouches for.
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest` - The manifest of the snapshot
+    fn vouches_for_snapshot(&self, manifest: &SnapshotManifest) -> bool {
+        let pinned = self
+            .runtime
+            .read()
+            .map(|rt| {
+                rt.checkpoints
+                    .get(manifest.height.saturating_sub(1) as usize)
+                    .map_or(false, |hash| hash == manifest.anchor)
+            })
+            .unwrap_or(false);
+
+        pinned
+            || self.head_attestations.target().map_or(false, |target| {
+                target.head == manifest.anchor && target.height == manifest.height
+            })
+    }
+
+    /// Picks the ledger up from the snapshot that has just been downloaded, and carries on synchronizing from its
+    /// anchor. If the snapshot can't be installed, the ledger is replayed from its genesis instead.
+    fn install_downloaded_snapshot(&mut self) {
+        let download = match self.snapshot_download.take() {
+            Some(download) => download,
+            None => return,
+        };
+        let (id, anchor) = (download.manifest.id, download.manifest.anchor);
+
+        let installed = match self.runtime.write() {
+            Ok(mut rt) => download
+                .assemble()
+                .and_then(|snapshot| rt.ledger.install_snapshot(snapshot)),
+            Err(_) => return,
+        };
+
+        match installed {
+            Ok(_) => {
+                info!(
+                    "Picked the ledger up from snapshot {} at height {}",
+                    id, download.manifest.height
+                );
+
+                let q: Quorum = self.active_subset_quorum();
+
+                // Only the transactions after the snapshot's anchor have to be replayed
+                self.kad_dht
+                    .get_record(&Key::new(&sync::next_transaction_key(anchor)), q);
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to install snapshot {}; synchronizing from the genesis instead: {}",
+                    id, e
+                );
+
+                self.fast_sync = false;
+                self.synchronize_dag();
+            }
+        }
+    }
+}
+
+/// Checks whether or not the given runtime should refuse the transaction with the given hash as the next entry in its
+/// ledger, since it disagrees with a pinned checkpoint. Conflicts are logged, since they mean that some peers are
+/// serving a history other than the one the operator trusts.
+///
+/// # Arguments
+///
+/// * `rt` - The runtime that the transaction would be added to
+/// * `hash` - The hash of the transaction
+fn refuses_history(rt: &System, hash: Hash) -> bool {
+    // Light clients are only as far along as the headers that they've followed
+    let height = match &rt.header_chain {
+        Some(chain) => chain.len(),
+        None => rt.ledger.height() as usize,
+    };
+
+    match rt.checkpoints.check(height, hash) {
+        Ok(_) => false,
+        Err(e) => {
+            warn!("Refusing remote history: {}", e);
+
+            true
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use libp2p::PeerId; // Import the libp2p peer identity type
+
+use std::{collections::HashMap, time::Duration};
+
+/// The smoothed round-trip time above which a peer is considered too slow to count towards a quorum.
+pub const SLOW_PEER_RTT_MS: u64 = 2000;
+
+/// The number of consecutive failed pings after which a peer is considered unresponsive.
+pub const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// The weight given to the latest round-trip time measurement, as a fraction of 1 / RTT_SMOOTHING.
+pub const RTT_SMOOTHING: u32 = 8;
+
+/// What we've measured about the connection to a single peer.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PeerLatency {
+    /// The exponentially smoothed round-trip time to the peer, if it has answered a ping yet
+    pub rtt: Option<Duration>,
+
+    /// The number of pings to the peer that have failed since it last answered one
+    pub failures: u32,
+}
+
+impl PeerLatency {
+    /// Checks whether or not the peer answers pings, and does so quickly enough to be worth waiting on.
+    pub fn is_responsive(&self) -> bool {
+        self.failures < MAX_CONSECUTIVE_FAILURES
+            && self
+                .rtt
+                .map_or(true, |rtt| rtt <= Duration::from_millis(SLOW_PEER_RTT_MS))
+    }
+}
+
+/// The measured latency of each peer that the client has pinged.
+#[derive(Default)]
+pub struct LatencyTable {
+    /// The latency of each peer
+    peers: HashMap<PeerId, PeerLatency>,
+}
+
+/// Implement a set of latency table helper methods.
+impl LatencyTable {
+    /// Records a successful ping of the given peer, folding the round-trip time into its smoothed RTT.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer that answered the ping
+    /// * `rtt` - The round-trip time of the ping
+    pub fn record_rtt(&mut self, peer: PeerId, rtt: Duration) {
+        let latency = self.peers.entry(peer).or_default();
+
+        // Smooth out the measurement, so that a single hiccup doesn't mark the peer as slow
+        latency.rtt = Some(match latency.rtt {
+            Some(smoothed) => smoothed - smoothed / RTT_SMOOTHING + rtt / RTT_SMOOTHING,
+            None => rtt,
+        });
+        latency.failures = 0; // The peer is alive
+    }
+
+    /// Records a failed ping of the given peer, and returns whether or not the peer is still considered responsive.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer that failed to answer the ping
+    pub fn record_failure(&mut self, peer: PeerId) -> bool {
+        let latency = self.peers.entry(peer).or_default();
+        latency.failures += 1;
+
+        latency.is_responsive()
+    }
+
+    /// Gets what we've measured about the given peer, if anything.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer whose latency should be obtained
+    pub fn get(&self, peer: &PeerId) -> Option<&PeerLatency> {
+        self.peers.get(peer)
+    }
+
+    /// Checks whether or not the given peer should count towards a quorum. Peers that haven't been pinged yet are
+    /// given the benefit of the doubt.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer that should be checked
+    pub fn is_responsive(&self, peer: &PeerId) -> bool {
+        self.peers
+            .get(peer)
+            .map_or(true, |latency| latency.is_responsive())
+    }
+
+    /// Forgets everything measured about the given peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer that should be forgotten
+    pub fn forget(&mut self, peer: &PeerId) {
+        self.peers.remove(peer);
+    }
+
+    /// Orders the responsive peers among the given peers from fastest to slowest, leaving out any unresponsive
+    /// peers. Peers that haven't been measured yet come last.
+    ///
+    /// # Arguments
+    ///
+    /// * `peers` - The peers that should be ranked
+    pub fn rank<'a>(&self, peers: impl Iterator<Item = &'a PeerId>) -> Vec<PeerId> {
+        let mut ranked: Vec<(Option<Duration>, PeerId)> = peers
+            .filter(|peer| self.is_responsive(peer))
+            .map(|peer| (self.peers.get(peer).and_then(|l| l.rtt), peer.clone()))
+            .collect();
+
+        // Sort by RTT, putting the unmeasured peers at the back
+        ranked.sort_by_key(|(rtt, _)| (rtt.is_none(), *rtt));
+
+        ranked.into_iter().map(|(_, peer)| peer).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from parent module
+
+    #[test]
+    fn test_is_responsive() {
+        let mut table = LatencyTable::default(); // Make an empty table
+
+        let (fast, slow, dead, unknown) = (
+            PeerId::random(),
+            PeerId::random(),
+            PeerId::random(),
+            PeerId::random(),
+        ); // Make a few peers
+
+        table.record_rtt(fast.clone(), Duration::from_millis(20));
+        table.record_rtt(slow.clone(), Duration::from_millis(400));
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            table.record_failure(dead.clone());
+        } // Kill one of the peers
+
+        assert!(table.is_responsive(&fast)); // Fast peers should count
+        assert!(table.is_responsive(&slow)); // Peers below the RTT limit should count
+        assert!(!table.is_responsive(&dead)); // The dead peer shouldn't count
+        assert!(table.is_responsive(&unknown)); // Unmeasured peers should count
+
+        assert_eq!(
+            table.rank(vec![&unknown, &dead, &slow, &fast].into_iter()),
+            vec![fast, slow, unknown]
+        ); // Ensure the peers are ranked by latency
+    }
+
+    #[test]
+    fn test_record_rtt() {
+        let mut table = LatencyTable::default(); // Make an empty table
+
+        let peer = PeerId::random(); // Make a peer
+
+        table.record_rtt(peer.clone(), Duration::from_millis(100));
+        table.record_rtt(peer.clone(), Duration::from_millis(10_000)); // A single slow ping
+
+        assert!(table.is_responsive(&peer)); // A single hiccup shouldn't exclude the peer
+
+        for _ in 0..8 {
+            table.record_rtt(peer.clone(), Duration::from_millis(10_000));
+        } // The peer is consistently slow
+
+        assert!(!table.is_responsive(&peer)); // The peer should now be excluded
+    }
+}
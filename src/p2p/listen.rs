@@ -0,0 +1,142 @@
+use libp2p::{multiaddr::Protocol, Multiaddr, PeerId}; // Import the libp2p address types
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr}; // Import the IP address types
+
+/// The version of the internet protocol that a multi-address is reached over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressFamily {
+    /// An IPv4 address, or a DNS name resolved to one
+    V4,
+
+    /// An IPv6 address, or a DNS name resolved to one
+    V6,
+}
+
+/// Gets the family of the given multi-address, if it names one.
+///
+/// # Arguments
+///
+/// * `addr` - The multi-address
+pub fn family(addr: &Multiaddr) -> Option<AddressFamily> {
+    match addr.iter().next() {
+        Some(Protocol::Ip4(_)) | Some(Protocol::Dns4(_)) => Some(AddressFamily::V4),
+        Some(Protocol::Ip6(_)) | Some(Protocol::Dns6(_)) => Some(AddressFamily::V6),
+        _ => None,
+    }
+}
+
+/// Gets the IP address that the given multi-address reaches, if it names one directly, rather than by DNS name.
+///
+/// # Arguments
+///
+/// * `addr` - The multi-address
+pub fn ip(addr: &Multiaddr) -> Option<IpAddr> {
+    addr.iter().find_map(|protocol| match protocol {
+        Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+        Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+        _ => None,
+    })
+}
+
+/// Gets the addresses that a node listens on when none are configured: every IPv4 and every IPv6 interface, on the
+/// given port.
+///
+/// # Arguments
+///
+/// * `port` - The TCP port to listen on, or 0 to let the OS pick one
+pub fn default_listen_addrs(port: u16) -> Vec<Multiaddr> {
+    vec![
+        Multiaddr::empty()
+            .with(Protocol::Ip4(Ipv4Addr::UNSPECIFIED))
+            .with(Protocol::Tcp(port)),
+        Multiaddr::empty()
+            .with(Protocol::Ip6(Ipv6Addr::UNSPECIFIED))
+            .with(Protocol::Tcp(port)),
+    ]
+}
+
+/// Gets the addresses that a node should listen on: the configured addresses, or, if there aren't any, both address
+/// families on the given port.
+///
+/// # Arguments
+///
+/// * `configured` - The listen addresses given by the operator
+/// * `port` - The TCP port to listen on by default
+pub fn listen_addrs(configured: &[Multiaddr], port: u16) -> Vec<Multiaddr> {
+    if configured.is_empty() {
+        default_listen_addrs(port)
+    } else {
+        configured.to_vec()
+    }
+}
+
+/// Gathers each of the addresses given for the same peer, keeping the peers in the order that they were first given.
+///
+/// # Arguments
+///
+/// * `peers` - Each peer, paired with one of its addresses
+pub fn group_by_peer(peers: Vec<(PeerId, Multiaddr)>) -> Vec<(PeerId, Vec<Multiaddr>)> {
+    let mut grouped: Vec<(PeerId, Vec<Multiaddr>)> = Vec::new();
+
+    for (peer, addr) in peers {
+        match grouped.iter_mut().find(|(known, _)| *known == peer) {
+            Some((_, addrs)) => addrs.push(addr),
+            None => grouped.push((peer, vec![addr])),
+        }
+    }
+
+    grouped
+}
+
+/// Orders the given addresses of a peer so that those in a family that the node is listening on, and so can most
+/// likely reach, are dialed first. The rest are kept to fall back on, in the order that they were given.
+///
+/// # Arguments
+///
+/// * `addrs` - The addresses of the peer
+/// * `reachable` - Each of the address families that the node is listening on
+pub fn dial_order(mut addrs: Vec<Multiaddr>, reachable: &[AddressFamily]) -> Vec<Multiaddr> {
+    addrs.sort_by_key(|addr| match family(addr) {
+        Some(family) => !reachable.contains(&family),
+        None => false, // Names resolving to either family might be reachable
+    });
+
+    addrs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_dial_order() {
+        let defaults = default_listen_addrs(2048);
+        assert_eq!(defaults[0], "/ip4/0.0.0.0/tcp/2048".parse().unwrap());
+        assert_eq!(defaults[1], "/ip6/::/tcp/2048".parse().unwrap());
+        assert_eq!(
+            defaults.iter().map(family).collect::<Vec<_>>(),
+            vec![Some(AddressFamily::V4), Some(AddressFamily::V6)]
+        );
+
+        let v4: Multiaddr = "/ip4/108.41.124.60/tcp/4096".parse().unwrap();
+        let v6: Multiaddr = "/ip6/2001:db8::1/tcp/4096".parse().unwrap();
+        let dns: Multiaddr = "/dns4/node1.summer.cash/tcp/2048".parse().unwrap();
+
+        // An IPv6-only node tries the peer's IPv6 address first, but keeps the rest to fall back on
+        assert_eq!(
+            dial_order(vec![v4.clone(), v6.clone()], &[AddressFamily::V6]),
+            vec![v6.clone(), v4.clone()]
+        );
+        assert_eq!(
+            dial_order(
+                vec![v4.clone(), dns.clone(), v6.clone()],
+                &[AddressFamily::V4, AddressFamily::V6]
+            ),
+            vec![v4.clone(), dns, v6.clone()]
+        );
+
+        let peer = PeerId::random();
+        let grouped = group_by_peer(vec![(peer.clone(), v4.clone()), (peer.clone(), v6.clone())]);
+        assert_eq!(grouped, vec![(peer, vec![v4, v6])]);
+    }
+}
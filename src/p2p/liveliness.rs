@@ -1,5 +1,9 @@
-use super::{super::core::sys::config, client::ClientBehavior, network::Network};
-use libp2p::{identify::IdentifyEvent, ping::PingEvent, swarm::NetworkBehaviourEventProcess};
+use super::{super::core::sys::config, client::ClientBehavior};
+use libp2p::{
+    identify::IdentifyEvent,
+    ping::{PingEvent, PingSuccess},
+    swarm::NetworkBehaviourEventProcess,
+};
 
 /// Network liveliness enforcement via identification swarm services.
 impl NetworkBehaviourEventProcess<IdentifyEvent> for ClientBehavior {
@@ -13,17 +17,37 @@ impl NetworkBehaviourEventProcess<IdentifyEvent> for ClientBehavior {
             } => {
                 // If this peer is from a different network, or they're too old, but they're still trying to connect to
                 // us, ban them.
-                if <Network as From<String>>::from(info.protocol_version) != self.network
+                if info.protocol_version != self.protocol_prefix
                     || !config::is_compatible_with_client(&info.agent_version)
                 {
                     debug!("Peer {} is incompatible; removing from the Swarm", peer_id);
 
                     // Remove the peer from the client's perspective
                     self.remove_address(&peer_id);
+                    self.latencies.forget(&peer_id);
+                    if let Ok(mut sync_peers) = self.sync_peers.write() {
+                        sync_peers.forget(&peer_id);
+                    }
+                    if let Ok(mut connections) = self.connections.table.write() {
+                        connections.forget(&peer_id);
+                    }
 
                     return;
                 }
 
+                // Remember which version the peer is running, in case it turns out to be feeding us bad data
+                if let Ok(mut sync_peers) = self.sync_peers.write() {
+                    sync_peers.record_identity(&peer_id, &info.agent_version);
+                }
+                if let Ok(mut connections) = self.connections.table.write() {
+                    connections.record_identity(
+                        &peer_id,
+                        &info.agent_version,
+                        &info.protocol_version,
+                        &info.protocols,
+                    );
+                }
+
                 debug!("Discovered a new peer to add to the swarm: {}", peer_id);
 
                 // Since the node is compatible, we can register them in each of our local views of
@@ -38,13 +62,35 @@ impl NetworkBehaviourEventProcess<IdentifyEvent> for ClientBehavior {
 impl NetworkBehaviourEventProcess<PingEvent> for ClientBehavior {
     fn inject_event(&mut self, event: PingEvent) {
         match event.result {
-            // Since our connection to the peer has basically cut out, we can remove them from our
-            // view of the network
+            // Keep track of how quickly the peer answers, so that slow peers can be left out of
+            // quorums
+            Ok(PingSuccess::Ping { rtt }) => {
+                debug!("Peer {} answered a ping in {:?}", event.peer, rtt);
+
+                self.latencies.record_rtt(event.peer.clone(), rtt);
+                if let Ok(mut connections) = self.connections.table.write() {
+                    connections.record_rtt(&event.peer, rtt);
+                }
+            }
+            // A single failed ping doesn't mean much, but if the peer keeps failing them, our
+            // connection to the peer has basically cut out, and we can remove them from our view
+            // of the network
             Err(e) => {
-                info!("Removing peer {}: {}", event.peer, e);
-                self.remove_address(&event.peer);
+                if !self.latencies.record_failure(event.peer.clone()) {
+                    info!("Removing peer {}: {}", event.peer, e);
+                    self.remove_address(&event.peer);
+                } else {
+                    debug!("Peer {} failed to answer a ping: {}", event.peer, e);
+                }
             }
             _ => debug!("Received ping event: {:?}", event),
         }
+
+        // Keep the peer's sync diagnostics up to date, whatever the outcome of the ping
+        if let (Some(latency), Ok(mut sync_peers)) =
+            (self.latencies.get(&event.peer), self.sync_peers.write())
+        {
+            sync_peers.record_latency(&event.peer, latency);
+        }
     }
 }
@@ -1,10 +1,29 @@
+pub mod attestation; // Export the head attestation module
+pub mod audit; // Export the sync invariant audit
 pub mod client; // Export the client module
+pub mod compression; // Export the message compression module
+pub mod connections; // Export the peer connection tracking module
+pub mod dht; // Export the DHT tuning module
+pub mod envelope; // Export the gossip envelope module
 mod floodsub;
 mod kademlia;
+pub mod latency; // Export the peer latency module
+pub mod listen; // Export the listen address module
 mod liveliness;
 mod mdns;
 pub mod message; // Export the message module
 pub mod network; // Export the network module
+pub mod parents; // Export the missing parent fetching module
 pub mod peers; // Export the peer discovery module
+pub mod psk; // Export the private network key module
+pub mod publisher; // Export the gossip publishing queue
+pub mod pubsub; // Export the relay-gated pubsub behavior
+pub mod reconnect; // Export the bootstrap reconnection module
+pub mod registry; // Export the network registry module
+pub mod relay; // Export the relay policy module
 pub mod rpc;
 pub mod sync;
+pub mod sync_peers; // Export the peer sync diagnostics module
+pub mod updater; // Export the node software update checker
+pub mod wal; // Export the write-ahead log module
+pub mod webhook; // Export the webhook notification module
@@ -14,7 +14,7 @@ pub static DEV_TEST_NETWORK_NAME: &str = "virgo";
 pub static LOCAL_TEST_NETWORK_NAME: &str = "olympia";
 
 /// A SummerCash network.
-#[derive(Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum Network {
     /// The main SummerCash network.
     MainNetwork,
@@ -27,33 +27,28 @@ pub enum Network {
 
     /// The local SummerCash test network name.
     LocalTestNetwork,
+
+    /// A network that isn't built into the crate, but may be described by the network registry.
+    Community(String),
 }
 
 /// Implement a set of network enum helper methods.
 impl Network {
-    /// Get the string representation of a particular network.
-    fn to_str(self) -> &'static str {
+    /// Get the name of a particular network.
+    pub fn name(&self) -> &str {
         match self {
             Network::MainNetwork => MAIN_NETWORK_NAME,
             Network::PublicTestNetwork => PUBLIC_TEST_NETWORK_NAME,
             Network::DevTestNetwork => DEV_TEST_NETWORK_NAME,
             Network::LocalTestNetwork => LOCAL_TEST_NETWORK_NAME,
+            Network::Community(name) => name,
         } // Handle different networks
     }
 }
 
 impl fmt::Display for Network {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", <Self as Into<String>>::into(*self))
-    }
-}
-
-/// Implement conversions from a network primitive to a referenced string.
-impl Into<&str> for Network {
-    /// Converts the network primitive identiofier to a referenced string.
-    fn into(self) -> &'static str {
-        // Return the string version of the network identifier
-        self.to_str()
+        write!(f, "{}", self.name())
     }
 }
 
@@ -61,8 +56,10 @@ impl Into<&str> for Network {
 impl Into<String> for Network {
     /// Converts the network primitive identifier into an owned string.
     fn into(self) -> String {
-        // Convert the network to an owned string through the existing to_str impl
-        self.to_str().to_owned()
+        match self {
+            Network::Community(name) => name,
+            _ => self.name().to_owned(),
+        }
     }
 }
 
@@ -92,8 +89,8 @@ impl From<&str> for Network {
             // A local test net
             "olympia" => Network::LocalTestNetwork,
 
-            // Let's assume this is a local test net, since it doesn't have a proper name
-            _ => Network::LocalTestNetwork,
+            // Any other network has to be looked up in the registry
+            _ => Network::Community(s.to_owned()),
         }
     }
 }
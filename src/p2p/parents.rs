@@ -0,0 +1,206 @@
+use chrono::{DateTime, Duration, Utc}; // Import time utilities
+
+use std::collections::HashSet; // Import the set type
+
+use super::super::crypto::hash::Hash; // Import the hash type
+
+/// The number of transactions that may wait on missing parents at once, by default.
+pub const DEFAULT_MAX_DEFERRED: usize = 256;
+
+/// The number of seconds that a transaction waits on its missing parents before it is voted on without them, by
+/// default.
+pub const DEFAULT_FETCH_TIMEOUT_SECS: u64 = 30;
+
+/// A vote held off until each of the parents of the transaction being voted on arrives.
+struct DeferredVote {
+    /// The ID of the proposal wrapping the transaction
+    proposal_id: Hash,
+
+    /// Each of the transaction's parents that the ledger doesn't hold yet
+    missing: Vec<Hash>,
+
+    /// When the vote was held off
+    deferred_at: DateTime<Utc>,
+}
+
+/// Keeps track of the transactions that cite parents the ledger hasn't caught up to yet, so that they can be voted on
+/// once their parents have been fetched from peers, rather than voted against for being out of reach during a partial
+/// sync.
+pub struct ParentFetcher {
+    /// The greatest number of votes that may be held off at once
+    capacity: usize,
+
+    /// The number of seconds that a vote may be held off for
+    timeout_secs: u64,
+
+    /// Each of the votes being held off, oldest first
+    deferred: Vec<DeferredVote>,
+
+    /// Each of the parents that have been requested from peers, but haven't arrived yet
+    requested: HashSet<Hash>,
+}
+
+impl Default for ParentFetcher {
+    /// Holds off on the default number of votes, for the default amount of time.
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_DEFERRED, DEFAULT_FETCH_TIMEOUT_SECS)
+    }
+}
+
+/// Implement a set of parent fetcher helper methods.
+impl ParentFetcher {
+    /// Makes a fetcher holding off on at most the given number of votes, for at most the given amount of time.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The greatest number of votes that may be held off at once
+    /// * `timeout_secs` - The number of seconds that a vote may be held off for
+    pub fn new(capacity: usize, timeout_secs: u64) -> Self {
+        Self {
+            capacity,
+            timeout_secs,
+            deferred: Vec::new(),
+            requested: HashSet::new(),
+        }
+    }
+
+    /// Holds off on voting for the given proposal until each of the given parents arrives. Results in the parents that
+    /// should be requested from peers (those that haven't been requested already), or None if too many votes are
+    /// already being held off.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_id` - The ID of the proposal wrapping the transaction
+    /// * `missing` - Each of the transaction's parents that the ledger doesn't hold yet
+    /// * `now` - The current time
+    pub fn defer(
+        &mut self,
+        proposal_id: Hash,
+        missing: Vec<Hash>,
+        now: DateTime<Utc>,
+    ) -> Option<Vec<Hash>> {
+        if self.is_deferred(&proposal_id) {
+            return Some(Vec::new());
+        }
+
+        if self.deferred.len() >= self.capacity {
+            return None;
+        }
+
+        let to_request = missing
+            .iter()
+            .filter(|parent| self.requested.insert(**parent))
+            .copied()
+            .collect();
+
+        self.deferred.push(DeferredVote {
+            proposal_id,
+            missing,
+            deferred_at: now,
+        });
+
+        Some(to_request)
+    }
+
+    /// Checks whether or not the vote on the given proposal is being held off.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal_id` - The ID of the proposal
+    pub fn is_deferred(&self, proposal_id: &Hash) -> bool {
+        self.deferred
+            .iter()
+            .any(|vote| vote.proposal_id == *proposal_id)
+    }
+
+    /// Records that the given parent has made it into the ledger. Results in the IDs of the proposals that no longer
+    /// have any missing parents, and can be voted on.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The hash of the transaction that arrived
+    pub fn arrived(&mut self, parent: Hash) -> Vec<Hash> {
+        if !self.requested.remove(&parent) {
+            return Vec::new();
+        }
+
+        for vote in self.deferred.iter_mut() {
+            vote.missing.retain(|missing| *missing != parent);
+        }
+
+        self.release(|vote| vote.missing.is_empty())
+    }
+
+    /// Gives up on the parents of each vote that has been held off for too long. Results in the IDs of the proposals
+    /// that should be voted on without them.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    pub fn expired(&mut self, now: DateTime<Utc>) -> Vec<Hash> {
+        let timeout = Duration::seconds(self.timeout_secs as i64);
+        let expired = self.release(|vote| now - vote.deferred_at >= timeout);
+
+        // Stop waiting on parents that nobody needs anymore
+        let deferred = &self.deferred;
+        self.requested
+            .retain(|parent| deferred.iter().any(|vote| vote.missing.contains(parent)));
+
+        expired
+    }
+
+    /// Stops holding off on each vote matching the given predicate. Results in the IDs of their proposals.
+    ///
+    /// # Arguments
+    ///
+    /// * `done` - Whether or not a vote should no longer be held off
+    fn release<F: Fn(&DeferredVote) -> bool>(&mut self, done: F) -> Vec<Hash> {
+        let (released, deferred): (Vec<DeferredVote>, Vec<DeferredVote>) =
+            self.deferred.drain(..).partition(|vote| done(vote));
+        self.deferred = deferred;
+
+        released.into_iter().map(|vote| vote.proposal_id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::crypto::blake3; // Import the blake3 hashing module
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_parent_fetcher() {
+        let (a, b, parent_a, parent_b) = (
+            blake3::hash_slice(b"a"),
+            blake3::hash_slice(b"b"),
+            blake3::hash_slice(b"parent a"),
+            blake3::hash_slice(b"parent b"),
+        );
+        let now = Utc::now();
+        let mut fetcher = ParentFetcher::new(2, 30);
+
+        // Parents are only requested once, no matter how many transactions are waiting on them
+        assert_eq!(
+            fetcher.defer(a, vec![parent_a, parent_b], now),
+            Some(vec![parent_a, parent_b])
+        );
+        assert_eq!(fetcher.defer(b, vec![parent_b], now), Some(Vec::new()));
+        assert_eq!(
+            fetcher.defer(blake3::hash_slice(b"c"), vec![parent_a], now),
+            None
+        );
+
+        // Transactions are voted on as soon as each of their parents is in
+        assert_eq!(fetcher.arrived(parent_b), vec![b]);
+        assert!(fetcher.is_deferred(&a));
+        assert_eq!(fetcher.arrived(parent_b), Vec::<Hash>::new());
+
+        // ...or once the parents have taken too long to show up
+        assert_eq!(
+            fetcher.expired(now + Duration::seconds(10)),
+            Vec::<Hash>::new()
+        );
+        assert_eq!(fetcher.expired(now + Duration::seconds(30)), vec![a]);
+        assert_eq!(fetcher.arrived(parent_a), Vec::<Hash>::new());
+    }
+}
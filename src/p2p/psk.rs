@@ -0,0 +1,214 @@
+use std::{fmt, fs, io, str::FromStr, time::Duration}; // Import the filesystem & formatting libraries
+
+use libp2p::{
+    core::{
+        muxing::StreamMuxerBox,
+        transport::boxed::Boxed,
+        upgrade::{self, SelectUpgrade},
+    },
+    dns::DnsConfig,
+    identity,
+    mplex::MplexConfig,
+    secio::SecioConfig,
+    tcp::TcpConfig,
+    websocket::WsConfig,
+    yamux, PeerId, Transport,
+}; // Import the libp2p transport types
+use libp2p_pnet::{PnetConfig, PreSharedKey}; // Import the private network handshake
+
+use super::super::crypto::blake3; // Import the blake3 hashing module
+
+/// The number of bytes in a pre-shared key.
+pub const KEY_SIZE: usize = 32;
+
+/// The first line of a key file, naming the version of the private network protocol that the key is for.
+pub const KEY_FILE_HEADER: &str = "/key/swarm/psk/1.0.0/";
+
+/// The second line of a key file, naming the encoding of the key.
+pub const KEY_FILE_ENCODING: &str = "/base16/";
+
+/// An error encountered while loading a pre-shared key.
+#[derive(Debug, Fail, PartialEq)]
+pub enum PskError {
+    #[fail(display = "the pre-shared key file is malformed: {}", reason)]
+    Malformed {
+        reason: String, // What's wrong with the file
+    },
+    #[fail(display = "failed to read the pre-shared key file: {}", error)]
+    Io {
+        error: String, // The underlying error
+    },
+}
+
+impl From<io::Error> for PskError {
+    /// Converts the given IO error into a PskError.
+    fn from(e: io::Error) -> Self {
+        Self::Io {
+            error: e.to_string(),
+        }
+    }
+}
+
+/// A key shared by each of the members of a private network. Peers that don't hold the key can't open a transport
+/// connection to a member at all, let alone gossip with it.
+#[derive(Clone, Copy, PartialEq)]
+pub struct SwarmKey([u8; KEY_SIZE]);
+
+impl FromStr for SwarmKey {
+    type Err = PskError;
+
+    /// Parses a key file, written in the same format as those used by other libp2p private networks: the protocol
+    /// header, the encoding, and the key in hex, each on a line of its own.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = |reason: &str| PskError::Malformed {
+            reason: reason.to_owned(),
+        };
+
+        let mut lines = s.lines().map(str::trim);
+        if lines.next() != Some(KEY_FILE_HEADER) {
+            return Err(malformed("expected a /key/swarm/psk/1.0.0/ header"));
+        }
+        if lines.next() != Some(KEY_FILE_ENCODING) {
+            return Err(malformed("only /base16/ encoded keys are supported"));
+        }
+
+        let hex = lines.next().unwrap_or_default();
+        if hex.len() != KEY_SIZE * 2 {
+            return Err(malformed("the key must be 64 hex characters long"));
+        }
+
+        let mut key = [0; KEY_SIZE];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = hex
+                .get(i * 2..i * 2 + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| malformed("the key holds a character that isn't a hex digit"))?;
+        }
+
+        // A key of all zeroes is almost certainly a placeholder that was never filled in
+        if key.iter().all(|byte| *byte == 0) {
+            return Err(malformed("the key is all zeroes"));
+        }
+
+        Ok(Self(key))
+    }
+}
+
+impl fmt::Debug for SwarmKey {
+    /// Writes the fingerprint of the key, rather than the key itself, so that it never ends up in a log.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SwarmKey({})", self.fingerprint())
+    }
+}
+
+/// Implement a set of pre-shared key helper methods.
+impl SwarmKey {
+    /// Gets a short digest of the key, which operators can compare to make sure that their nodes hold the same key
+    /// without revealing it.
+    pub fn fingerprint(&self) -> String {
+        blake3::hash_slice(&self.0)[..8]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Gets the path of the file that the key for the given network is kept in.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The data directory of the node
+    /// * `network_name` - The name of the private network
+    pub fn path(data_dir: &str, network_name: &str) -> String {
+        format!("{}/config/swarm_{}.key", data_dir, network_name)
+    }
+
+    /// Reads the key for the given network from the config folder in the given data directory. Results in None if the
+    /// network isn't private, and an error if the key is there, but can't be used.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The data directory of the node
+    /// * `network_name` - The name of the network
+    pub fn read_from_disk_at_data_directory(
+        data_dir: &str,
+        network_name: &str,
+    ) -> Result<Option<Self>, PskError> {
+        match fs::read_to_string(Self::path(data_dir, network_name)) {
+            Ok(contents) => Ok(Some(contents.parse()?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Builds the same TCP & websocket transport as a public node, but runs the private network handshake on every
+/// connection before anything else, so that peers without the key are cut off before they can even negotiate a
+/// protocol.
+///
+/// # Arguments
+///
+/// * `keypair` - The libp2p identity of the node
+/// * `key` - The key shared by each of the members of the private network
+pub fn build_transport(
+    keypair: identity::Keypair,
+    key: &SwarmKey,
+) -> io::Result<Boxed<(PeerId, StreamMuxerBox), io::Error>> {
+    let psk = PreSharedKey::new(key.0);
+
+    let tcp = DnsConfig::new(TcpConfig::new().nodelay(true))?;
+    let transport = tcp.clone().or_transport(WsConfig::new(tcp));
+
+    Ok(transport
+        .and_then(move |socket, _| PnetConfig::new(psk).handshake(socket))
+        .upgrade(upgrade::Version::V1)
+        .authenticate(SecioConfig::new(keypair))
+        .multiplex(SelectUpgrade::new(
+            yamux::Config::default(),
+            MplexConfig::new(),
+        ))
+        .map(|(peer, muxer), _| (peer, StreamMuxerBox::new(muxer)))
+        .timeout(Duration::from_secs(20))
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+        .boxed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_from_str() {
+        let hex = "8a".repeat(KEY_SIZE);
+        let key: SwarmKey = format!("{}\n{}\n{}\n", KEY_FILE_HEADER, KEY_FILE_ENCODING, hex)
+            .parse()
+            .unwrap();
+        assert_eq!(key, SwarmKey([0x8a; KEY_SIZE]));
+        assert!(!format!("{:?}", key).contains(&hex)); // The key itself never gets printed
+
+        // Keys of the wrong length, encoding, or contents are refused at startup, rather than failing every handshake
+        assert!(
+            format!("{}\n{}\n{}\n", KEY_FILE_HEADER, KEY_FILE_ENCODING, "8a")
+                .parse::<SwarmKey>()
+                .is_err()
+        );
+        assert!(format!("{}\n/base64/\n{}\n", KEY_FILE_HEADER, hex)
+            .parse::<SwarmKey>()
+            .is_err());
+        assert!(format!(
+            "{}\n{}\n{}\n",
+            KEY_FILE_HEADER,
+            KEY_FILE_ENCODING,
+            "zz".repeat(KEY_SIZE)
+        )
+        .parse::<SwarmKey>()
+        .is_err());
+        assert!(format!(
+            "{}\n{}\n{}\n",
+            KEY_FILE_HEADER,
+            KEY_FILE_ENCODING,
+            "00".repeat(KEY_SIZE)
+        )
+        .parse::<SwarmKey>()
+        .is_err());
+    }
+}
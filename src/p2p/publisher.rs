@@ -0,0 +1,328 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+}; // Import the collections & time libraries
+
+use async_std::task; // Import the async-std timer
+
+use libp2p::floodsub::Topic; // Import the pubsub topic type
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+/// The default number of messages that may wait in the publishing queue at once.
+pub const DEFAULT_QUEUE_CAPACITY: usize = 256;
+
+/// The default number of messages published each second.
+pub const DEFAULT_MESSAGES_PER_SEC: u32 = 20;
+
+/// An error encountered while configuring the publishing queue.
+#[derive(Debug, Fail, PartialEq)]
+pub enum PublisherError {
+    #[fail(
+        display = "unknown overflow policy {} (expected drop-oldest or drop-newest)",
+        policy
+    )]
+    UnknownOverflowPolicy {
+        policy: String, // The policy that was asked for
+    },
+}
+
+/// What happens to a message pushed onto a full publishing queue.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum OverflowPolicy {
+    /// Discard the message that has waited the longest, making room for the new one
+    DropOldest,
+
+    /// Discard the new message, leaving the queue as it is
+    DropNewest,
+}
+
+impl FromStr for OverflowPolicy {
+    type Err = PublisherError;
+
+    /// Parses an overflow policy written as drop-oldest or drop-newest.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop-oldest" => Ok(Self::DropOldest),
+            "drop-newest" => Ok(Self::DropNewest),
+            _ => Err(PublisherError::UnknownOverflowPolicy {
+                policy: s.to_owned(),
+            }),
+        }
+    }
+}
+
+/// The limits that the publishing queue holds outgoing gossip to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PublisherConfig {
+    /// The number of messages that may wait in the queue at once
+    pub capacity: usize,
+
+    /// The number of messages published each second. Up to a second's worth of messages may go out at once after the
+    /// queue has sat idle.
+    pub messages_per_sec: u32,
+
+    /// What happens to messages pushed onto a full queue
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for PublisherConfig {
+    /// Builds a config with the default capacity and rate, which drops the oldest messages on overflow.
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_QUEUE_CAPACITY,
+            messages_per_sec: DEFAULT_MESSAGES_PER_SEC,
+            overflow: OverflowPolicy::DropOldest,
+        }
+    }
+}
+
+/// A message waiting to be published.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outgoing {
+    /// The topic that the message should be published to
+    pub topic: Topic,
+
+    /// The sealed contents of the message
+    pub payload: Vec<u8>,
+}
+
+/// A bounded queue of gossip messages, released at a steady rate so that a burst of local proposals doesn't flood
+/// the swarm, or hold up the loop polling it.
+pub struct PublishQueue {
+    /// The limits that the queue holds messages to
+    pub config: PublisherConfig,
+
+    /// Each of the messages waiting to be published, oldest first
+    queue: VecDeque<Outgoing>,
+
+    /// The number of messages that may be published right now
+    allowance: f64,
+
+    /// The last time that the allowance was topped up
+    last_refill: Instant,
+
+    /// The number of messages discarded because the queue was full
+    dropped: u64,
+
+    /// A timer waking the poller once more messages may be published
+    timer: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+}
+
+/// Implement a set of publishing queue helper methods.
+impl PublishQueue {
+    /// Initializes a new, empty publishing queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The limits that the queue should hold messages to
+    pub fn new(config: PublisherConfig) -> Self {
+        Self {
+            allowance: f64::from(config.messages_per_sec.max(1)),
+            config,
+            queue: VecDeque::new(),
+            last_refill: Instant::now(),
+            dropped: 0,
+            timer: None,
+        }
+    }
+
+    /// Gets the number of messages waiting to be published.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Checks whether or not any messages are waiting to be published.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Gets the number of messages that may be pushed before the queue overflows.
+    pub fn remaining(&self) -> usize {
+        self.config.capacity.saturating_sub(self.queue.len())
+    }
+
+    /// Gets the number of messages discarded so far because the queue was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Puts the given message at the back of the queue. If the queue is full, a message is discarded according to the
+    /// overflow policy, and returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic that the message should be published to
+    /// * `payload` - The sealed contents of the message
+    pub fn push(&mut self, topic: Topic, payload: Vec<u8>) -> Option<Outgoing> {
+        let msg = Outgoing { topic, payload };
+
+        if self.remaining() > 0 {
+            self.queue.push_back(msg);
+
+            return None;
+        }
+
+        self.dropped += 1;
+
+        match self.config.overflow {
+            OverflowPolicy::DropOldest => {
+                let discarded = self.queue.pop_front();
+                self.queue.push_back(msg);
+
+                discarded
+            }
+            OverflowPolicy::DropNewest => Some(msg),
+        }
+    }
+
+    /// Takes each of the messages that the rate allows to be published at the given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    pub fn take_ready(&mut self, now: Instant) -> Vec<Outgoing> {
+        let rate = f64::from(self.config.messages_per_sec.max(1));
+
+        // Top up the allowance for the time that has passed, but never beyond a second's worth of messages
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.allowance = (self.allowance + elapsed.as_secs_f64() * rate).min(rate);
+        self.last_refill = now;
+
+        let n = (self.allowance as usize).min(self.queue.len());
+        self.allowance -= n as f64;
+
+        self.queue.drain(..n).collect()
+    }
+
+    /// Gets how long it will be, from the given time, before another message may be published, if any are waiting.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    pub fn next_ready_in(&self, now: Instant) -> Option<Duration> {
+        if self.queue.is_empty() {
+            return None;
+        }
+
+        let rate = f64::from(self.config.messages_per_sec.max(1));
+        let allowance = self.allowance
+            + now
+                .saturating_duration_since(self.last_refill)
+                .as_secs_f64()
+                * rate;
+
+        Some(Duration::from_secs_f64(((1.0 - allowance) / rate).max(0.0)))
+    }
+
+    /// Takes each of the messages that may be published now, and makes sure that the current task is woken once more
+    /// may be published.
+    ///
+    /// # Arguments
+    ///
+    /// * `cx` - The context of the task polling the queue
+    pub fn poll_ready(&mut self, cx: &mut Context) -> Vec<Outgoing> {
+        let ready = self.take_ready(Instant::now());
+
+        match self.next_ready_in(Instant::now()) {
+            Some(wait) => {
+                let timer = self
+                    .timer
+                    .get_or_insert_with(|| Box::pin(task::sleep(wait)));
+
+                // Poll the queue again right away if the timer has already gone off
+                if let Poll::Ready(()) = timer.as_mut().poll(cx) {
+                    self.timer = None;
+
+                    cx.waker().wake_by_ref();
+                }
+            }
+            None => self.timer = None,
+        }
+
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    /// Makes a queue holding the given number of messages.
+    fn queue_with(config: PublisherConfig, n: u8) -> PublishQueue {
+        let mut queue = PublishQueue::new(config);
+        for i in 0..n {
+            queue.push(Topic::new("test".to_owned()), vec![i]);
+        }
+
+        queue
+    }
+
+    #[test]
+    fn test_take_ready() {
+        let mut queue = queue_with(
+            PublisherConfig {
+                capacity: 16,
+                messages_per_sec: 4,
+                overflow: OverflowPolicy::DropOldest,
+            },
+            10,
+        );
+        let start = queue.last_refill;
+
+        assert_eq!(queue.take_ready(start).len(), 4); // A second's worth of messages may go out at once
+        assert!(queue.take_ready(start).is_empty());
+        assert_eq!(queue.next_ready_in(start), Some(Duration::from_millis(250)));
+
+        let later = start + Duration::from_millis(500);
+        let ready = queue.take_ready(later);
+        assert_eq!(ready.len(), 2);
+        assert_eq!(ready[0].payload, vec![4]); // Messages go out in order
+        assert_eq!(queue.len(), 4);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let config = PublisherConfig {
+            capacity: 2,
+            messages_per_sec: 1,
+            overflow: OverflowPolicy::DropOldest,
+        };
+
+        let mut oldest = queue_with(config.clone(), 2);
+        assert_eq!(
+            oldest
+                .push(Topic::new("test".to_owned()), vec![2])
+                .unwrap()
+                .payload,
+            vec![0]
+        );
+        assert_eq!(oldest.dropped(), 1);
+
+        let mut newest = queue_with(
+            PublisherConfig {
+                overflow: OverflowPolicy::DropNewest,
+                ..config
+            },
+            2,
+        );
+        assert_eq!(
+            newest
+                .push(Topic::new("test".to_owned()), vec![2])
+                .unwrap()
+                .payload,
+            vec![2]
+        );
+        assert_eq!(newest.remaining(), 0);
+
+        assert_eq!(
+            "drop-newest".parse::<OverflowPolicy>(),
+            Ok(OverflowPolicy::DropNewest)
+        );
+        assert!("drop-everything".parse::<OverflowPolicy>().is_err());
+    }
+}
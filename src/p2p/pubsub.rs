@@ -0,0 +1,402 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    task::{Context, Poll},
+}; // Import the collections & polling types
+
+use libp2p::{
+    core::ConnectedPoint,
+    floodsub::{
+        protocol::{FloodsubConfig, FloodsubSubscription, FloodsubSubscriptionAction},
+        FloodsubEvent, FloodsubMessage, FloodsubRpc, Topic, TopicHash,
+    },
+    swarm::{
+        protocols_handler::OneShotHandler, NetworkBehaviour, NetworkBehaviourAction, PollParameters,
+    },
+    Multiaddr, PeerId,
+}; // Import the floodsub protocol & libp2p swarm types
+
+/// The number of messages remembered as seen, so that each one is only handed to the client once.
+pub const MAX_REMEMBERED_MESSAGES: usize = 4096;
+
+/// What the handler of a connection reports back to the behavior.
+pub enum InnerMessage {
+    /// An RPC was received from the peer
+    Rx(FloodsubRpc),
+
+    /// An RPC was sent to the peer
+    Sent,
+}
+
+impl From<FloodsubRpc> for InnerMessage {
+    fn from(rpc: FloodsubRpc) -> Self {
+        InnerMessage::Rx(rpc)
+    }
+}
+
+impl From<()> for InnerMessage {
+    fn from(_: ()) -> Self {
+        InnerMessage::Sent
+    }
+}
+
+/// A floodsub behavior that speaks the same protocol as libp2p's, but never passes a message along on its own.
+/// Received messages are handed to the client, which forwards the ones that it's willing to relay (e.g. that meet
+/// its relay policy) to the rest of its peers itself.
+pub struct GatedFloodsub {
+    /// The actions waiting to be handed to the swarm
+    events: VecDeque<NetworkBehaviourAction<FloodsubRpc, FloodsubEvent>>,
+
+    /// The ID of the local peer, which messages published by the client are attributed to
+    local_peer_id: PeerId,
+
+    /// The peers that we should stay connected to, and tell about our subscriptions
+    target_peers: HashSet<PeerId>,
+
+    /// The topics that each connected peer has subscribed to
+    connected_peers: HashMap<PeerId, Vec<TopicHash>>,
+
+    /// The topics that the client has subscribed to
+    subscribed_topics: Vec<Topic>,
+
+    /// The source & sequence number of each of the most recently seen messages, oldest first
+    received: VecDeque<(PeerId, Vec<u8>)>,
+
+    /// The same messages as above, for quick lookups
+    received_set: HashSet<(PeerId, Vec<u8>)>,
+}
+
+/// Implement a set of gated floodsub helper methods.
+impl GatedFloodsub {
+    /// Builds a behavior that publishes messages as the given peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `local_peer_id` - The ID of the local peer
+    pub fn new(local_peer_id: PeerId) -> Self {
+        Self {
+            events: VecDeque::new(),
+            local_peer_id,
+            target_peers: HashSet::new(),
+            connected_peers: HashMap::new(),
+            subscribed_topics: Vec::new(),
+            received: VecDeque::new(),
+            received_set: HashSet::new(),
+        }
+    }
+
+    /// Adds the given peer to the peers that messages are exchanged with, dialing it if we aren't connected to it yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_id` - The ID of the peer
+    pub fn add_node_to_partial_view(&mut self, peer_id: PeerId) {
+        // Let the peer know what we're interested in
+        if self.connected_peers.contains_key(&peer_id) {
+            for topic in self.subscribed_topics.iter() {
+                self.events.push_back(NetworkBehaviourAction::SendEvent {
+                    peer_id: peer_id.clone(),
+                    event: subscription_rpc(topic, FloodsubSubscriptionAction::Subscribe),
+                });
+            }
+        }
+
+        if self.target_peers.insert(peer_id.clone()) && !self.connected_peers.contains_key(&peer_id)
+        {
+            self.events
+                .push_back(NetworkBehaviourAction::DialPeer { peer_id });
+        }
+    }
+
+    /// Stops exchanging messages with the given peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer_id` - The ID of the peer
+    pub fn remove_node_from_partial_view(&mut self, peer_id: &PeerId) {
+        self.target_peers.remove(peer_id);
+    }
+
+    /// Subscribes to the given topic, letting each of our peers know. Results in whether or not we weren't
+    /// subscribed to it already.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic that should be subscribed to
+    pub fn subscribe(&mut self, topic: Topic) -> bool {
+        if self
+            .subscribed_topics
+            .iter()
+            .any(|subscribed| subscribed.hash() == topic.hash())
+        {
+            return false;
+        }
+
+        for peer_id in self.connected_peers.keys() {
+            self.events.push_back(NetworkBehaviourAction::SendEvent {
+                peer_id: peer_id.clone(),
+                event: subscription_rpc(&topic, FloodsubSubscriptionAction::Subscribe),
+            });
+        }
+
+        self.subscribed_topics.push(topic);
+
+        true
+    }
+
+    /// Publishes a message with the given contents to each of the peers subscribed to the given topic.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic that the message should be published to
+    /// * `data` - The contents of the message
+    pub fn publish(&mut self, topic: impl Into<Topic>, data: impl Into<Vec<u8>>) {
+        let message = FloodsubMessage {
+            source: self.local_peer_id.clone(),
+            data: data.into(),
+            // Sequence numbers only have to be unique per source
+            sequence_number: rand::random::<[u8; 20]>().to_vec(),
+            topics: vec![topic.into().hash().clone()],
+        };
+
+        // Our own message shouldn't be handed back to us by a peer
+        self.remember(&message);
+
+        self.send(&message, None);
+    }
+
+    /// Passes a message received from a peer along to each of the other peers subscribed to any of its topics. Called
+    /// by the client once it has decided that the message is worth relaying.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message that should be relayed
+    pub fn forward(&mut self, message: &FloodsubMessage) {
+        self.send(message, Some(&message.source));
+    }
+
+    /// Sends the given message to each of the connected peers subscribed to any of its topics.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message that should be sent
+    /// * `except` - A peer that the message shouldn't be sent back to
+    fn send(&mut self, message: &FloodsubMessage, except: Option<&PeerId>) {
+        for (peer_id, topics) in self.connected_peers.iter() {
+            if Some(peer_id) == except || !topics.iter().any(|topic| message.topics.contains(topic))
+            {
+                continue;
+            }
+
+            self.events.push_back(NetworkBehaviourAction::SendEvent {
+                peer_id: peer_id.clone(),
+                event: FloodsubRpc {
+                    subscriptions: Vec::new(),
+                    messages: vec![message.clone()],
+                },
+            });
+        }
+    }
+
+    /// Remembers that the given message has been seen. Results in whether or not it hadn't been seen before.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message that was seen
+    fn remember(&mut self, message: &FloodsubMessage) -> bool {
+        let id = (message.source.clone(), message.sequence_number.clone());
+        if !self.received_set.insert(id.clone()) {
+            return false;
+        }
+
+        self.received.push_back(id);
+
+        // Forget the oldest messages once there are too many to keep track of
+        while self.received.len() > MAX_REMEMBERED_MESSAGES {
+            if let Some(oldest) = self.received.pop_front() {
+                self.received_set.remove(&oldest);
+            }
+        }
+
+        true
+    }
+}
+
+/// Builds an RPC telling a peer that we've subscribed to, or unsubscribed from, the given topic.
+///
+/// # Arguments
+///
+/// * `topic` - The topic
+/// * `action` - Whether we've subscribed or unsubscribed
+fn subscription_rpc(topic: &Topic, action: FloodsubSubscriptionAction) -> FloodsubRpc {
+    FloodsubRpc {
+        messages: Vec::new(),
+        subscriptions: vec![FloodsubSubscription {
+            topic: topic.hash().clone(),
+            action,
+        }],
+    }
+}
+
+impl NetworkBehaviour for GatedFloodsub {
+    type ProtocolsHandler = OneShotHandler<FloodsubConfig, FloodsubRpc, InnerMessage>;
+    type OutEvent = FloodsubEvent;
+
+    fn new_handler(&mut self) -> Self::ProtocolsHandler {
+        Default::default()
+    }
+
+    fn addresses_of_peer(&mut self, _peer_id: &PeerId) -> Vec<Multiaddr> {
+        Vec::new()
+    }
+
+    fn inject_connected(&mut self, peer_id: PeerId, _endpoint: ConnectedPoint) {
+        // Let the peer know what we're interested in
+        if self.target_peers.contains(&peer_id) {
+            for topic in self.subscribed_topics.iter() {
+                self.events.push_back(NetworkBehaviourAction::SendEvent {
+                    peer_id: peer_id.clone(),
+                    event: subscription_rpc(topic, FloodsubSubscriptionAction::Subscribe),
+                });
+            }
+        }
+
+        self.connected_peers.insert(peer_id, Vec::new());
+    }
+
+    fn inject_disconnected(&mut self, peer_id: &PeerId, _endpoint: ConnectedPoint) {
+        self.connected_peers.remove(peer_id);
+
+        // Peers drop idle connections, so keep on reconnecting to the ones that we care about
+        if self.target_peers.contains(peer_id) {
+            self.events.push_back(NetworkBehaviourAction::DialPeer {
+                peer_id: peer_id.clone(),
+            });
+        }
+    }
+
+    fn inject_node_event(&mut self, peer_id: PeerId, event: InnerMessage) {
+        let rpc = match event {
+            InnerMessage::Rx(rpc) => rpc,
+            InnerMessage::Sent => return,
+        };
+
+        for subscription in rpc.subscriptions {
+            let topics = self
+                .connected_peers
+                .entry(peer_id.clone())
+                .or_insert_with(Vec::new);
+
+            match subscription.action {
+                FloodsubSubscriptionAction::Subscribe => {
+                    if !topics.contains(&subscription.topic) {
+                        topics.push(subscription.topic.clone());
+                    }
+
+                    self.events.push_back(NetworkBehaviourAction::GenerateEvent(
+                        FloodsubEvent::Subscribed {
+                            peer_id: peer_id.clone(),
+                            topic: subscription.topic,
+                        },
+                    ));
+                }
+                FloodsubSubscriptionAction::Unsubscribe => {
+                    topics.retain(|topic| *topic != subscription.topic);
+
+                    self.events.push_back(NetworkBehaviourAction::GenerateEvent(
+                        FloodsubEvent::Unsubscribed {
+                            peer_id: peer_id.clone(),
+                            topic: subscription.topic,
+                        },
+                    ));
+                }
+            }
+        }
+
+        for message in rpc.messages {
+            // Nothing is passed along here; that's up to the client
+            if !self.remember(&message)
+                || !self
+                    .subscribed_topics
+                    .iter()
+                    .any(|topic| message.topics.contains(topic.hash()))
+            {
+                continue;
+            }
+
+            self.events.push_back(NetworkBehaviourAction::GenerateEvent(
+                FloodsubEvent::Message(message),
+            ));
+        }
+    }
+
+    fn poll(
+        &mut self,
+        _cx: &mut Context,
+        _params: &mut impl PollParameters,
+    ) -> Poll<NetworkBehaviourAction<FloodsubRpc, FloodsubEvent>> {
+        match self.events.pop_front() {
+            Some(event) => Poll::Ready(event),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_gated_floodsub() {
+        let (alice, bob) = (PeerId::random(), PeerId::random());
+        let topic = Topic::new("votes".to_owned());
+
+        let mut sub = GatedFloodsub::new(PeerId::random());
+        sub.subscribe(topic.clone());
+
+        for peer in &[&alice, &bob] {
+            sub.inject_connected(
+                (*peer).clone(),
+                ConnectedPoint::Dialer {
+                    address: "/ip4/127.0.0.1/tcp/2048".parse().unwrap(),
+                },
+            );
+            sub.inject_node_event(
+                (*peer).clone(),
+                subscription_rpc(&topic, FloodsubSubscriptionAction::Subscribe).into(),
+            );
+        }
+        sub.events.clear();
+
+        // Messages from peers are handed to the client, but not passed along
+        let message = FloodsubMessage {
+            source: alice.clone(),
+            data: b"vote".to_vec(),
+            sequence_number: vec![1],
+            topics: vec![topic.hash().clone()],
+        };
+        let rpc = || FloodsubRpc {
+            subscriptions: Vec::new(),
+            messages: vec![message.clone()],
+        };
+        sub.inject_node_event(alice.clone(), rpc().into());
+        assert_eq!(sub.events.len(), 1);
+        assert!(matches!(
+            sub.events.pop_front(),
+            Some(NetworkBehaviourAction::GenerateEvent(
+                FloodsubEvent::Message(_)
+            ))
+        ));
+
+        // The same message isn't handed over twice
+        sub.inject_node_event(bob.clone(), rpc().into());
+        assert!(sub.events.is_empty());
+
+        // Once the client relays the message, it goes to everyone but its source
+        sub.forward(&message);
+        assert_eq!(sub.events.len(), 1);
+        assert!(matches!(
+            sub.events.pop_front(),
+            Some(NetworkBehaviourAction::SendEvent { peer_id, .. }) if peer_id == bob
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::{DateTime, Duration, Utc}; // Import time utilities
+
+use libp2p::{Multiaddr, PeerId}; // Import the libp2p peer identity & address types
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+/// The number of seconds waited before bootstrap peers are dialed again, after the first failed round, by default.
+pub const DEFAULT_RECONNECT_BASE_SECS: u64 = 2;
+
+/// The greatest number of seconds waited between rounds of dialing the bootstrap peers, by default.
+pub const DEFAULT_RECONNECT_MAX_SECS: u64 = 300;
+
+/// How well the node is connected to the rest of the network.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Connectivity {
+    /// The node isn't connected to any peer, and is retrying its bootstrap peers
+    Isolated,
+
+    /// The node is connected to at least one peer, but hasn't caught up with the network yet
+    Connecting,
+
+    /// The node is connected to the network, and holds everything that its peers have published
+    Synced,
+}
+
+impl Default for Connectivity {
+    /// Nodes start out without any peers.
+    fn default() -> Self {
+        Self::Isolated
+    }
+}
+
+/// What the client should do to keep in touch with the network.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReconnectAction {
+    /// Nothing needs doing
+    Wait,
+
+    /// Dial each of the bootstrap peers again
+    Redial,
+
+    /// Connectivity has been regained, so the DHT should be bootstrapped again
+    Rebootstrap,
+}
+
+/// Keeps dialing the bootstrap peers, backing off between rounds, while the node has no peers, so that a node that
+/// couldn't reach the network at startup (or that lost touch with it later on) doesn't stay isolated for good.
+pub struct Reconnector {
+    /// Each of the bootstrap peers, along with the addresses it can be reached at
+    bootstrap_peers: Vec<(PeerId, Vec<Multiaddr>)>,
+
+    /// The number of seconds waited before dialing again, after the first failed round
+    base_secs: u64,
+
+    /// The greatest number of seconds waited between rounds
+    max_secs: u64,
+
+    /// How well the node was connected to the network when last checked
+    status: Connectivity,
+
+    /// The number of rounds of dialing since the node was last connected to a peer
+    rounds: u32,
+
+    /// When the bootstrap peers may next be dialed
+    next_attempt: Option<DateTime<Utc>>,
+}
+
+/// Implement a set of reconnector helper methods.
+impl Reconnector {
+    /// Makes a reconnector dialing the given bootstrap peers, waiting twice as long after each failed round.
+    ///
+    /// # Arguments
+    ///
+    /// * `bootstrap_peers` - Each of the bootstrap peers, along with the addresses it can be reached at
+    /// * `base_secs` - The number of seconds waited before dialing again, after the first failed round
+    /// * `max_secs` - The greatest number of seconds waited between rounds
+    pub fn new(
+        bootstrap_peers: Vec<(PeerId, Vec<Multiaddr>)>,
+        base_secs: u64,
+        max_secs: u64,
+    ) -> Self {
+        Self {
+            bootstrap_peers,
+            base_secs,
+            max_secs: max_secs.max(base_secs),
+            status: Connectivity::default(),
+            rounds: 0,
+            next_attempt: None,
+        }
+    }
+
+    /// Gets each of the bootstrap peers, along with the addresses it can be reached at.
+    pub fn bootstrap_peers(&self) -> &[(PeerId, Vec<Multiaddr>)] {
+        &self.bootstrap_peers
+    }
+
+    /// Gets how well the node was connected to the network when last checked.
+    pub fn status(&self) -> Connectivity {
+        self.status
+    }
+
+    /// Records a round of dialing the bootstrap peers, putting off the next round for longer than the last.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time
+    pub fn dialed(&mut self, now: DateTime<Utc>) {
+        self.rounds = self.rounds.saturating_add(1);
+
+        let factor = 1u64.checked_shl(self.rounds - 1).unwrap_or(std::u64::MAX);
+        let backoff = self.base_secs.saturating_mul(factor).min(self.max_secs);
+
+        self.next_attempt = Some(now + Duration::seconds(backoff as i64));
+    }
+
+    /// Works out what the client should do to keep in touch with the network, given how connected it is now.
+    ///
+    /// # Arguments
+    ///
+    /// * `connected_peers` - The number of peers that the node is connected to
+    /// * `synchronized` - Whether or not the node holds everything that its peers have published
+    /// * `now` - The current time
+    pub fn tick(
+        &mut self,
+        connected_peers: usize,
+        synchronized: bool,
+        now: DateTime<Utc>,
+    ) -> ReconnectAction {
+        if connected_peers == 0 {
+            self.status = Connectivity::Isolated;
+
+            // There's nobody to dial, or we've dialed them too recently
+            if self.bootstrap_peers.is_empty() || self.next_attempt.map_or(false, |at| now < at) {
+                return ReconnectAction::Wait;
+            }
+
+            self.dialed(now);
+
+            return ReconnectAction::Redial;
+        }
+
+        let regained = self.status == Connectivity::Isolated;
+
+        self.status = if synchronized {
+            Connectivity::Synced
+        } else {
+            Connectivity::Connecting
+        };
+        self.rounds = 0;
+        self.next_attempt = None;
+
+        if regained {
+            ReconnectAction::Rebootstrap
+        } else {
+            ReconnectAction::Wait
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_reconnector() {
+        let bootstrap = vec![(
+            PeerId::random(),
+            vec!["/ip4/10.0.0.1/tcp/2048".parse().unwrap()],
+        )];
+        let now = Utc::now();
+        let mut reconnector = Reconnector::new(bootstrap, 2, 5);
+
+        // The bootstrap peers were dialed at startup, but nobody answered
+        reconnector.dialed(now);
+        assert_eq!(reconnector.tick(0, false, now), ReconnectAction::Wait);
+        assert_eq!(reconnector.status(), Connectivity::Isolated);
+
+        // Each round waits twice as long as the last, up to the limit
+        let mut at = now + Duration::seconds(2);
+        assert_eq!(reconnector.tick(0, false, at), ReconnectAction::Redial);
+        assert_eq!(
+            reconnector.tick(0, false, at + Duration::seconds(3)),
+            ReconnectAction::Wait
+        );
+        at = at + Duration::seconds(4);
+        assert_eq!(reconnector.tick(0, false, at), ReconnectAction::Redial);
+        assert_eq!(
+            reconnector.tick(0, false, at + Duration::seconds(4)),
+            ReconnectAction::Wait
+        );
+        at = at + Duration::seconds(5);
+        assert_eq!(reconnector.tick(0, false, at), ReconnectAction::Redial);
+
+        // Once a peer answers, the DHT is bootstrapped again, and we catch up with the network
+        assert_eq!(reconnector.tick(1, false, at), ReconnectAction::Rebootstrap);
+        assert_eq!(reconnector.status(), Connectivity::Connecting);
+        assert_eq!(reconnector.tick(1, true, at), ReconnectAction::Wait);
+        assert_eq!(reconnector.status(), Connectivity::Synced);
+
+        // Losing every peer starts the retries afresh, right away
+        assert_eq!(reconnector.tick(0, true, at), ReconnectAction::Redial);
+        assert_eq!(reconnector.status(), Connectivity::Isolated);
+
+        // Nodes without any bootstrap peers have nobody to retry
+        let mut lonely = Reconnector::new(Vec::new(), 2, 5);
+        assert_eq!(lonely.tick(0, false, now), ReconnectAction::Wait);
+    }
+}
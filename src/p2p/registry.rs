@@ -0,0 +1,328 @@
+use std::{fs, io}; // Import the filesystem libraries
+
+use libp2p::{Multiaddr, PeerId}; // Import the libp2p peer identity & address types
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::super::crypto::hash::Hash; // Import the hash type
+
+/// The name of the file in a node's config folder that adds to, or overrides, the bundled network registry.
+pub const REGISTRY_FILE: &str = "networks.json";
+
+/// The registry of networks shipped with the crate.
+pub const BUNDLED_REGISTRY: &str = include_str!("networks.json");
+
+/// An error encountered while loading the network registry.
+#[derive(Debug, Fail, PartialEq)]
+pub enum RegistryError {
+    #[fail(
+        display = "the network registry at {} is malformed: {}",
+        source, reason
+    )]
+    Malformed {
+        source: String, // Where the registry came from
+        reason: String, // What's wrong with the registry
+    },
+    #[fail(display = "failed to read the network registry at {}: {}", path, error)]
+    Io {
+        path: String,  // The path of the registry file
+        error: String, // The underlying error
+    },
+    #[fail(
+        display = "failed to download the network registry from {}: {}",
+        url, error
+    )]
+    Download {
+        url: String,   // The URL that the registry was requested from
+        error: String, // The underlying error
+    },
+}
+
+/// A peer that nodes joining a network connect to first.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BootstrapPeer {
+    /// The base58-encoded identity of the peer
+    pub peer_id: String,
+
+    /// The multi-address that the peer listens on
+    pub address: String,
+}
+
+/// Everything that a node needs to know to join a network.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NetworkInfo {
+    /// The name of the network (e.g. "andromeda")
+    pub name: String,
+
+    /// The hash of the network's genesis transaction, if it's known ahead of time
+    #[serde(default)]
+    pub genesis_hash: Option<Hash>,
+
+    /// The protocol name that members of the network identify themselves with, and segregate their DHT by
+    pub protocol_prefix: String,
+
+    /// The peers that nodes joining the network connect to first
+    #[serde(default)]
+    pub bootstrap_peers: Vec<BootstrapPeer>,
+}
+
+/// Implement a set of network info helper methods.
+impl NetworkInfo {
+    /// Describes a network that the registry knows nothing about: one without any bootstrap peers, identified by its
+    /// name alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the network
+    pub fn unlisted(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            genesis_hash: None,
+            protocol_prefix: name.to_owned(),
+            bootstrap_peers: Vec::new(),
+        }
+    }
+
+    /// Parses the identity & address of each of the network's bootstrap peers.
+    pub fn resolve_bootstrap_peers(&self) -> Result<Vec<(PeerId, Multiaddr)>, String> {
+        self.bootstrap_peers
+            .iter()
+            .map(|peer| {
+                let peer_id = peer
+                    .peer_id
+                    .parse::<PeerId>()
+                    .map_err(|_| format!("{} is not a valid peer ID", peer.peer_id))?;
+                let address = peer
+                    .address
+                    .parse::<Multiaddr>()
+                    .map_err(|e| format!("{} is not a valid multi-address: {}", peer.address, e))?;
+
+                Ok((peer_id, address))
+            })
+            .collect()
+    }
+}
+
+/// Each of the networks that a node knows how to join. The registry bundled with the crate can be added to, or
+/// overridden, by a networks.json file in the node's config folder, so that community networks can be joined without
+/// recompiling.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(transparent)]
+pub struct NetworkRegistry {
+    /// Each of the networks in the registry
+    networks: Vec<NetworkInfo>,
+}
+
+/// Implement a set of network registry helper methods.
+impl NetworkRegistry {
+    /// Gets the registry shipped with the crate.
+    pub fn bundled() -> Self {
+        Self::parse("the bundled registry", BUNDLED_REGISTRY).unwrap_or_default()
+    }
+
+    /// Parses a registry, making sure that each of its networks can actually be joined.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Where the registry came from, for use in errors
+    /// * `contents` - The JSON-encoded registry
+    pub fn parse(source: &str, contents: &str) -> Result<Self, RegistryError> {
+        let malformed = |reason: String| RegistryError::Malformed {
+            source: source.to_owned(),
+            reason,
+        };
+
+        let registry: Self =
+            serde_json::from_str(contents).map_err(|e| malformed(e.to_string()))?;
+
+        for network in &registry.networks {
+            if network.name.is_empty() || network.protocol_prefix.is_empty() {
+                return Err(malformed(
+                    "each network needs a name & protocol prefix".to_owned(),
+                ));
+            }
+
+            network
+                .resolve_bootstrap_peers()
+                .map_err(|e| malformed(format!("network {}: {}", network.name, e)))?;
+        }
+
+        Ok(registry)
+    }
+
+    /// Gets the path of the file that adds to the bundled registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The data directory of the node
+    pub fn path(data_dir: &str) -> String {
+        format!("{}/config/{}", data_dir, REGISTRY_FILE)
+    }
+
+    /// Reads the networks added by the operator to the config folder in the given data directory, without the
+    /// bundled registry. Results in an empty registry if the operator hasn't added any.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The data directory of the node
+    pub fn read_overrides_from_disk_at_data_directory(
+        data_dir: &str,
+    ) -> Result<Self, RegistryError> {
+        let path = Self::path(data_dir);
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&path, &contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(RegistryError::Io {
+                path,
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    /// Reads the full registry: the bundled networks, plus any added by the operator to the config folder in the
+    /// given data directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The data directory of the node
+    pub fn read_from_disk_at_data_directory(data_dir: &str) -> Result<Self, RegistryError> {
+        let mut registry = Self::bundled();
+        registry.merge(Self::read_overrides_from_disk_at_data_directory(data_dir)?);
+
+        Ok(registry)
+    }
+
+    /// Writes the registry to the config folder in the given data directory, where it adds to the bundled registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The data directory of the node
+    pub fn write_to_disk_at_data_directory(&self, data_dir: &str) -> io::Result<()> {
+        fs::create_dir_all(format!("{}/config", data_dir))?; // Make the config directory, if it doesn't exist
+
+        fs::write(Self::path(data_dir), serde_json::to_vec_pretty(self)?)
+    }
+
+    /// Downloads a registry published at the given URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - Where the registry is published
+    pub async fn download(url: &str) -> Result<Self, RegistryError> {
+        let failed = |error: String| RegistryError::Download {
+            url: url.to_owned(),
+            error,
+        };
+
+        let contents = reqwest::get(url)
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .map_err(|e| failed(e.to_string()))?
+            .text()
+            .await
+            .map_err(|e| failed(e.to_string()))?;
+
+        Self::parse(url, &contents)
+    }
+
+    /// Adds each of the networks in the given registry, replacing any network of the same name.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The registry to take networks from
+    pub fn merge(&mut self, other: Self) {
+        for network in other.networks {
+            match self.networks.iter_mut().find(|n| n.name == network.name) {
+                Some(existing) => *existing = network,
+                None => self.networks.push(network),
+            }
+        }
+    }
+
+    /// Gets the network with the given name, if the registry lists it.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the network
+    pub fn get(&self, name: &str) -> Option<&NetworkInfo> {
+        self.networks.iter().find(|network| network.name == name)
+    }
+
+    /// Gets each of the networks in the registry.
+    pub fn networks(&self) -> &[NetworkInfo] {
+        &self.networks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_registry() {
+        let mut registry = NetworkRegistry::bundled();
+        assert_eq!(
+            registry
+                .networks()
+                .iter()
+                .map(|network| network.name.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["andromeda", "vela", "virgo", "olympia"]
+        );
+        assert_eq!(
+            registry
+                .get("andromeda")
+                .unwrap()
+                .resolve_bootstrap_peers()
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // Operators can join new networks, and repoint the bundled ones
+        registry.merge(
+            NetworkRegistry::parse(
+                "test",
+                r#"[
+                    {"name": "olympia", "protocol_prefix": "olympia/2"},
+                    {
+                        "name": "lyra",
+                        "protocol_prefix": "lyra",
+                        "bootstrap_peers": [{
+                            "peer_id": "QmUGGG7T5AicmMUjUTay3wT7jRPwfhrjM1e8W8i1DZXjBg",
+                            "address": "/ip4/10.0.0.1/tcp/2048"
+                        }]
+                    }
+                ]"#,
+            )
+            .unwrap(),
+        );
+        assert_eq!(registry.networks().len(), 5);
+        assert_eq!(
+            registry.get("olympia").unwrap().protocol_prefix,
+            "olympia/2"
+        );
+        assert_eq!(
+            registry
+                .get("lyra")
+                .unwrap()
+                .resolve_bootstrap_peers()
+                .unwrap()[0]
+                .1,
+            "/ip4/10.0.0.1/tcp/2048".parse::<Multiaddr>().unwrap()
+        );
+        assert_eq!(registry.get("orion"), None);
+
+        // Registries listing peers that can't be dialed are refused up front
+        assert!(NetworkRegistry::parse(
+            "test",
+            r#"[{
+                "name": "lyra",
+                "protocol_prefix": "lyra",
+                "bootstrap_peers": [{"peer_id": "nope", "address": "/ip4/10.0.0.1/tcp/2048"}]
+            }]"#
+        )
+        .is_err());
+    }
+}
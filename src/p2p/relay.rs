@@ -0,0 +1,270 @@
+use super::super::{
+    common::address::Address,
+    core::{
+        sys::{
+            proposal::{Operation, Proposal},
+            vote::Vote,
+        },
+        types::transaction::Transaction,
+    },
+};
+
+use num::bigint::BigUint; // Add support for large unsigned integers
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// The default number of messages that a single sender may have relayed in each window.
+pub const DEFAULT_MESSAGES_PER_WINDOW: u32 = 16;
+
+/// The default length of a rate-limiting window, in seconds.
+pub const DEFAULT_WINDOW_SECS: u64 = 60;
+
+/// An error encountered while deciding whether or not to relay a message.
+#[derive(Debug, Fail, PartialEq)]
+pub enum RelayRejection {
+    #[fail(
+        display = "sender {} has exceeded its limit of {} messages per window",
+        sender, limit
+    )]
+    RateLimited { sender: String, limit: u32 },
+    #[fail(
+        display = "transaction gas price {} is below the relay floor of {} finks",
+        gas_price, floor
+    )]
+    BelowGasPriceFloor { gas_price: BigUint, floor: BigUint },
+    #[fail(display = "the message could not be attributed to a sender")]
+    Unattributable,
+}
+
+/// The thresholds that a non-voting node applies to the messages it relays.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PolicyConfig {
+    /// The number of messages that a single sender may have relayed in each window
+    pub messages_per_window: u32,
+
+    /// The length of a rate-limiting window, in seconds
+    pub window_secs: u64,
+
+    /// The minimum number of finks that a transaction must pay for each unit of gas in order to be relayed
+    pub gas_price_floor: BigUint,
+}
+
+impl Default for PolicyConfig {
+    /// Builds a policy with the default rate limit and no gas price floor.
+    fn default() -> Self {
+        Self {
+            messages_per_window: DEFAULT_MESSAGES_PER_WINDOW,
+            window_secs: DEFAULT_WINDOW_SECS,
+            gas_price_floor: BigUint::default(),
+        }
+    }
+}
+
+/// Decides which proposals, transactions, and votes a non-voting node admits and passes along to the rest of the
+/// network, keeping a fixed window of message counts for each sender.
+pub struct RelayPolicy {
+    /// The thresholds that each message is held to
+    pub config: PolicyConfig,
+
+    /// The start of the current window, and the number of messages relayed in it, for each sender
+    windows: HashMap<Address, (Instant, u32)>,
+}
+
+/// Implement a set of relay policy helper methods.
+impl RelayPolicy {
+    /// Initializes a new relay policy with the given thresholds.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The thresholds that each relayed message should be held to
+    pub fn new(config: PolicyConfig) -> Self {
+        Self {
+            config,
+            windows: HashMap::new(),
+        }
+    }
+
+    /// Checks whether or not the given proposal should be relayed. Transaction proposals are
+    /// attributed to the transaction's sender, and must meet the gas price floor. Any other proposal
+    /// is admitted without being counted against a sender.
+    ///
+    /// # Arguments
+    ///
+    /// * `proposal` - The proposal that was received
+    pub fn admit_proposal(&mut self, proposal: &Proposal) -> Result<(), RelayRejection> {
+        // Only transactions can be attributed to a sender
+        if proposal.proposal_data.param_name != "ledger::transactions" {
+            return Ok(());
+        }
+
+        // Get the transaction that the proposal wants to append
+        let tx_bytes =
+            if let Operation::Append { value_to_append } = &proposal.proposal_data.operation {
+                value_to_append
+            } else {
+                return Err(RelayRejection::Unattributable);
+            };
+        let tx: Transaction =
+            bincode::deserialize(tx_bytes).map_err(|_| RelayRejection::Unattributable)?;
+
+        self.admit_transaction(&tx)
+    }
+
+    /// Checks whether or not the given raw transaction should be relayed. Transactions are
+    /// attributed to their sender, and must meet the gas price floor.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that was received
+    pub fn admit_transaction(&mut self, tx: &Transaction) -> Result<(), RelayRejection> {
+        // Make sure the transaction pays enough for its gas to be worth passing along
+        if tx.transaction_data.gas_price < self.config.gas_price_floor {
+            return Err(RelayRejection::BelowGasPriceFloor {
+                gas_price: tx.transaction_data.gas_price.clone(),
+                floor: self.config.gas_price_floor.clone(),
+            });
+        }
+
+        // Count the message against the sender
+        self.record(tx.transaction_data.sender, Instant::now())
+    }
+
+    /// Checks whether or not the given vote should be relayed. Votes are attributed to the voter.
+    ///
+    /// # Arguments
+    ///
+    /// * `vote` - The vote that was received
+    pub fn admit_vote(&mut self, vote: &Vote) -> Result<(), RelayRejection> {
+        // Get the address of the voter
+        let voter = vote.voter_address().ok_or(RelayRejection::Unattributable)?;
+
+        self.record(voter, Instant::now()) // Count the message against the voter
+    }
+
+    /// Counts a message against the given sender, returning an error if the sender has already
+    /// used up its allowance for the current window.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The address that the message is attributed to
+    /// * `now` - The time at which the message was received
+    fn record(&mut self, sender: Address, now: Instant) -> Result<(), RelayRejection> {
+        let window = Duration::from_secs(self.config.window_secs);
+        let limit = self.config.messages_per_window;
+
+        // Forget about any windows that have already closed, so that the map doesn't grow forever
+        self.windows
+            .retain(|_, (started, _)| now.duration_since(*started) < window);
+
+        let (_, count) = self.windows.entry(sender).or_insert((now, 0)); // Get the sender's window
+
+        // Make sure the sender hasn't already had its fill
+        if *count >= limit {
+            return Err(RelayRejection::RateLimited {
+                sender: sender.to_str(),
+                limit,
+            });
+        }
+
+        *count += 1; // Count the message
+
+        Ok(()) // Done!
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::{accounts::account::Account, core::sys::proposal::ProposalData};
+    use super::*; // Import names from parent module
+
+    /// Builds a proposal to append a transaction paying the given gas price.
+    fn tx_proposal(sender: Address, gas_price: u64) -> Proposal {
+        let tx = Transaction::new(
+            0,
+            sender,
+            Address::default(),
+            BigUint::from(1 as u8),
+            b"test",
+            vec![],
+        )
+        .with_gas(21_000, BigUint::from(gas_price)); // Make a transaction
+
+        Proposal::new(
+            "test".to_owned(),
+            ProposalData::new(
+                "ledger::transactions".to_owned(),
+                Operation::Append {
+                    value_to_append: tx.to_bytes(),
+                },
+            ),
+        ) // Wrap the transaction in a proposal
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut policy = RelayPolicy::new(PolicyConfig {
+            messages_per_window: 2,
+            ..Default::default()
+        }); // Make a policy that only allows two messages
+
+        let sender = Account::new().address().unwrap(); // Get a sender
+
+        assert!(policy.admit_proposal(&tx_proposal(sender, 1)).is_ok()); // First message should pass
+        assert!(policy.admit_proposal(&tx_proposal(sender, 2)).is_ok()); // Second message should pass
+        assert!(policy.admit_proposal(&tx_proposal(sender, 3)).is_err()); // Third should be limited
+
+        let other = Account::new().address().unwrap(); // Get another sender
+
+        assert!(policy.admit_proposal(&tx_proposal(other, 1)).is_ok()); // Limits are per-sender
+    }
+
+    #[test]
+    fn test_admit_transaction() {
+        let mut policy = RelayPolicy::new(PolicyConfig {
+            messages_per_window: 1,
+            gas_price_floor: BigUint::from(10 as u8),
+            ..Default::default()
+        }); // Make a policy with a floor that only allows one message
+
+        let sender = Account::new().address().unwrap(); // Get a sender
+        let tx = |gas_price: u64| {
+            Transaction::new(
+                0,
+                sender,
+                Address::default(),
+                BigUint::from(1 as u8),
+                b"test",
+                vec![],
+            )
+            .with_gas(21_000, BigUint::from(gas_price))
+        }; // Make transactions from the sender
+
+        assert!(policy.admit_transaction(&tx(5)).is_err()); // Should be below the floor
+        assert!(policy.admit_transaction(&tx(10)).is_ok()); // Should meet the floor
+        assert!(policy.admit_proposal(&tx_proposal(sender, 10)).is_err()); // Raw and wrapped txs share a limit
+    }
+
+    #[test]
+    fn test_gas_price_floor() {
+        let mut policy = RelayPolicy::new(PolicyConfig {
+            gas_price_floor: BigUint::from(10 as u8),
+            ..Default::default()
+        }); // Make a policy with a floor
+
+        let sender = Account::new().address().unwrap(); // Get a sender
+
+        assert_eq!(
+            policy.admit_proposal(&tx_proposal(sender, 5)),
+            Err(RelayRejection::BelowGasPriceFloor {
+                gas_price: BigUint::from(5 as u8),
+                floor: BigUint::from(10 as u8)
+            })
+        ); // Should be rejected
+        assert!(policy.admit_proposal(&tx_proposal(sender, 10)).is_ok()); // Should meet the floor
+    }
+}
@@ -0,0 +1,166 @@
+use jsonrpc_core::{
+    futures::{
+        future::{self, Either},
+        Future,
+    },
+    middleware::{NoopCallFuture, NoopFuture},
+    response::Output,
+    Call, Error, ErrorCode, Metadata, Middleware,
+};
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+}; // Import the shared flag types
+
+use super::error;
+
+/// The RPC methods that only read from the ledger & network, and don't touch any account on the node. These are the
+/// only methods served on a read-only API, which can be exposed to the public like an explorer.
+pub const READ_ONLY_METHODS: [&str; 43] = [
+    "get_account_balance",
+    "get_balance_at",
+    "get_burned_supply",
+    "get_dag",
+    "get_dormant_accounts",
+    "get_genesis_roles",
+    "get_double_spends",
+    "get_guardians",
+    "get_locked_balance",
+    "get_network",
+    "get_network_stats",
+    "get_peers",
+    "get_receipt",
+    "get_rewards",
+    "get_state_at",
+    "get_state_origin",
+    "get_supply",
+    "get_sync_status",
+    "list_executed_proposals",
+    "list_pending_proposals",
+    "list_transactions",
+    "validate_transaction",
+    "debug_trace_transaction",
+    "resolve_hash",
+    "diagnose_account",
+    "get_light_headers",
+    "get_light_head",
+    "get_balance_proof",
+    "verify_balance_proof",
+    "replay_transactions",
+    "open_export",
+    "next_export_page",
+    "get_export",
+    "close_export",
+    "subscribe",
+    "poll_subscription",
+    "unsubscribe",
+    "subscribe_new_transactions",
+    "unsubscribe_new_transactions",
+    "subscribe_proposals",
+    "unsubscribe_proposals",
+    "subscribe_balance_changes",
+    "unsubscribe_balance_changes",
+];
+
+/// Checks whether or not the given RPC method only reads from the ledger & network.
+///
+/// # Arguments
+///
+/// * `method` - The name of the method
+pub fn is_read_only(method: &str) -> bool {
+    READ_ONLY_METHODS.contains(&method)
+}
+
+/// A middleware rejecting any call to a method that isn't read-only, if the API it guards is read-only. Wallet &
+/// admin methods are left to an API served elsewhere (e.g. only on localhost). Every API turns read-only while the
+/// node's disk isn't taking writes.
+pub struct ReadOnlyGate {
+    /// Whether or not the API only serves read-only methods
+    pub enforced: bool,
+
+    /// A flag raised while the node's disk isn't taking writes
+    pub disk_read_only: Arc<AtomicBool>,
+}
+
+/// Implement a set of read-only gate helper methods.
+impl ReadOnlyGate {
+    /// Makes a gate for an API serving each of the node's methods.
+    pub fn open() -> Self {
+        Self {
+            enforced: false,
+            disk_read_only: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Makes a gate for an API serving only read-only methods.
+    pub fn read_only() -> Self {
+        Self {
+            enforced: true,
+            ..Self::open()
+        }
+    }
+
+    /// Refuses calls to methods that aren't read-only while the given flag is raised.
+    ///
+    /// # Arguments
+    ///
+    /// * `disk_read_only` - A flag raised while the node's disk isn't taking writes
+    pub fn watching_disk(mut self, disk_read_only: Arc<AtomicBool>) -> Self {
+        self.disk_read_only = disk_read_only;
+
+        self
+    }
+}
+
+impl<M: Metadata> Middleware<M> for ReadOnlyGate {
+    type Future = NoopFuture;
+    type CallFuture = NoopCallFuture;
+
+    /// Rejects the call if it is made to a method that isn't read-only, through a read-only API.
+    fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, M) -> X + Send + Sync,
+        X: Future<Item = Option<Output>, Error = ()> + Send + 'static,
+    {
+        if let Call::MethodCall(ref method_call) = call {
+            let refusal = if is_read_only(&method_call.method) {
+                None
+            } else if self.enforced {
+                Some(error::ERROR_METHOD_NOT_EXPOSED)
+            } else if self.disk_read_only.load(Ordering::SeqCst) {
+                Some(error::ERROR_DISK_READ_ONLY)
+            } else {
+                None
+            };
+
+            if let Some(code) = refusal {
+                return Either::A(Box::new(future::ok(Some(Output::from(
+                    Err(Error::new(ErrorCode::from(code))),
+                    method_call.id.clone(),
+                    method_call.jsonrpc,
+                )))));
+            }
+        }
+
+        Either::B(next(call, meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{auth::ADMIN_METHODS, tenants::SCOPED_METHODS}; // Import the admin & tenant method lists
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_is_read_only() {
+        assert!(is_read_only("get_account_balance"));
+        assert!(!is_read_only("create_transaction"));
+
+        // Nothing touching the node's accounts, or a tenant's namespace, is ever served on a read-only API
+        assert!(ADMIN_METHODS.iter().all(|method| !is_read_only(method)));
+        assert!(SCOPED_METHODS
+            .iter()
+            .all(|(method, _)| !is_read_only(method)));
+    }
+}
@@ -1,55 +1,58 @@
-use jsonrpc_core::{response::Output, Error, ErrorCode, IoHandler, Result};
+use jsonrpc_core::{Error, ErrorCode, Result};
 use jsonrpc_derive::rpc;
 
-use cryptolib::{
-    aes::{self, KeySize},
-    blockmodes,
-    buffer::{self, BufferResult, ReadBuffer, WriteBuffer},
-};
-
-use serde::Deserialize;
-
-use rand::{rngs::StdRng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 use super::{
     super::super::{
-        accounts::account::{self, Account},
-        common::address::Address,
-        core::sys::system::System,
-        crypto::blake3,
+        accounts::{
+            account::{self, Account, PublicAccount},
+            keystore::{self, KeystoreError},
+            policy::{SpendingLog, SpendingPolicy, SpendingStatus},
+            unlock::{UnlockError, UnlockSession, DEFAULT_UNLOCK_MINUTES},
+        },
+        common::{address::Address, lock::InstrumentedRwLock},
+        core::{sys::system::System, types::activity::DormancyReport},
     },
+    auth::RpcHandler,
+    client::{ClientError, RpcClient},
     error,
+    failover::RetryPolicy,
 };
 
-use std::{
-    collections::HashMap,
-    fs,
-    io::{Read, Seek, SeekFrom, Write},
-    sync::{Arc, RwLock},
-};
+use std::{fs, sync::Arc};
+
+/// A deposit sub-address, along with the parent account that derived it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SubAccount {
+    /// The address of the account that derived the sub-address
+    pub parent: Address,
+    /// The index of the sub-address
+    pub index: u64,
+}
 
 /// Defines the standard SummerCash accounts RPC API.
 #[rpc]
 pub trait Accounts {
-    /// Generates a new account and returns the account's address and private key. Note: this method also writes the
-    /// new account to the given data directory.
+    /// Generates a new account and returns the account's address and public key. Note: this method also writes the
+    /// new account to the given data directory, which is the only place that its private key is kept.
     #[rpc(name = "new_account")]
-    fn generate(&self, data_dir: String) -> Result<Account>;
+    fn generate(&self, data_dir: String) -> Result<PublicAccount>;
 
-    /// Reads an account with the given address from the disk, and returns its details. If the account is locked,
-    /// an error will be returned.
+    /// Reads an account with the given address from the disk, and returns its public details. If the account is
+    /// locked, an error will be returned.
     #[rpc(name = "get_account")]
-    fn get(&self, address: Address, data_dir: String) -> Result<Account>;
+    fn get(&self, address: Address, data_dir: String) -> Result<PublicAccount>;
 
     /// Locks the account with the corresponding address in the given data directory. If the account is already locked,
     /// an error is returned.
     #[rpc(name = "lock_account")]
     fn lock(&self, address: Address, enc_key: String, data_dir: String) -> Result<()>;
 
-    /// Unlocks the account with the corresponding address in the given data directory, and returns the account if the operation
-    /// was successful. If the account is already unlocked, an error is returned.
+    /// Unlocks the account with the corresponding address in the given data directory, and returns the account's public
+    /// details if the operation was successful. If the account is already unlocked, an error is returned.
     #[rpc(name = "unlock_account")]
-    fn unlock(&self, address: Address, dec_key: String, data_dir: String) -> Result<Account>;
+    fn unlock(&self, address: Address, dec_key: String, data_dir: String) -> Result<PublicAccount>;
 
     /// Deletes the account with the corresponding address.
     #[rpc(name = "delete_account")]
@@ -62,34 +65,67 @@ pub trait Accounts {
     /// Gets the balance of an account with the given address.
     #[rpc(name = "get_account_balance")]
     fn balance(&self, address: Address) -> Result<num::BigUint>;
+
+    /// Derives the deposit sub-address of the given parent account at the provided index.
+    #[rpc(name = "derive_sub_address")]
+    fn derive_sub_address(&self, parent: Address, index: u64) -> Result<Address>;
+
+    /// Maps a deposit sub-address back to the account in the given data directory that derived it.
+    #[rpc(name = "get_parent_account")]
+    fn get_parent(&self, address: Address, data_dir: String) -> Result<SubAccount>;
+
+    /// Gets the spending policy of the account in the given data directory, along with how much it has spent over the
+    /// last day. The policy can only be changed on the node's disk.
+    #[rpc(name = "get_spending_status")]
+    fn get_spending_status(&self, address: Address, data_dir: String) -> Result<SpendingStatus>;
+
+    /// Gets each of the accounts holding funds that haven't sent a transaction in the given number of days, along with
+    /// how much they hold between them. Only the richest are listed if a limit is given.
+    #[rpc(name = "get_dormant_accounts")]
+    fn get_dormant_accounts(&self, idle_days: u64, limit: Option<usize>) -> Result<DormancyReport>;
+
+    /// Unlocks the locked account with the given address in memory for the given number of minutes (or five), leaving
+    /// its keystore locked on the disk. The returned handle must accompany each transaction signed with the account.
+    #[rpc(name = "open_unlock_session")]
+    fn open_unlock_session(
+        &self,
+        address: Address,
+        dec_key: String,
+        data_dir: String,
+        minutes: Option<u64>,
+    ) -> Result<UnlockSession>;
+
+    /// Locks the account held by the unlock session with the given handle, before the session runs out.
+    #[rpc(name = "close_unlock_session")]
+    fn close_unlock_session(&self, handle: String) -> Result<()>;
 }
 
 /// An implementation of the accounts API.
 pub struct AccountsImpl {
-    pub(crate) runtime: Arc<RwLock<System>>,
+    pub(crate) runtime: Arc<InstrumentedRwLock<System>>,
 }
 
 impl Accounts for AccountsImpl {
-    /// Generates a new account and returns the account's address and private key
-    fn generate(&self, data_dir: String) -> Result<Account> {
+    /// Generates a new account and returns the account's address and public key
+    fn generate(&self, data_dir: String) -> Result<PublicAccount> {
         // Generate an account
         let acc: Account = Account::new();
 
-        // Persist the account to the local disk + return it
+        // Persist the account to the local disk + return its public details
         match acc.write_to_disk_at_data_directory(&data_dir) {
-            Ok(_) => Ok(acc),
+            Ok(_) => public_details(&acc),
             Err(_) => Err(Error::new(ErrorCode::from(
                 error::ERROR_UNABLE_TO_WRITE_ACCOUNT,
             ))),
         }
     }
 
-    /// Reads an account with the given address from the disk, and returns its details. If the account is locked,
-    /// an error will be returned.
-    fn get(&self, address: Address, data_dir: String) -> Result<Account> {
+    /// Reads an account with the given address from the disk, and returns its public details. If the account is
+    /// locked, an error will be returned.
+    fn get(&self, address: Address, data_dir: String) -> Result<PublicAccount> {
         // Convert the IO error into a suitable JSONRPC error, if need be
         match Account::read_from_disk_at_data_directory(address, &data_dir) {
-            Ok(acc) => Ok(acc),
+            Ok(acc) => public_details(&acc),
             Err(_) => Err(Error::new(ErrorCode::ServerError(
                 error::ERROR_UNABLE_TO_OPEN_ACCOUNT,
             ))),
@@ -99,211 +135,16 @@ impl Accounts for AccountsImpl {
     /// Locks the account with the corresponding address in the given data directory. If the account is already locked,
     /// an error is returned.
     fn lock(&self, address: Address, enc_key: String, data_dir: String) -> Result<()> {
-        // We'll need to generate an IV to do encryption properly
-        let mut iv: [u8; 16] = [0; 16];
-
-        // Make a random number generator for the pwd
-        let mut rng: StdRng = SeedableRng::from_seed(*blake3::hash_slice(enc_key.as_bytes()));
-
-        // Generate an IV
-        rng.fill_bytes(&mut iv);
-
-        // Make an instance of the encryption helper for the file
-        let mut enc = aes::cbc_encryptor(
-            KeySize::KeySize128,
-            &*blake3::hash_slice(enc_key.as_bytes()),
-            &iv,
-            blockmodes::PkcsPadding,
-        );
-
-        // Open the file that the account is stored in
-        let mut f = if let Ok(f) = fs::OpenOptions::new().read(true).write(true).open(format!(
-            "{}/keystore/{}.json",
-            data_dir,
-            address.to_str()
-        )) {
-            f
-        } else {
-            // Return an error
-            return Err(Error::new(ErrorCode::ServerError(
-                error::ERROR_UNABLE_TO_OPEN_ACCOUNT,
-            )));
-        };
-
-        // The contents of the file. We'll read the file into this buffer later.
-        let mut contents = Vec::new();
-
-        // Read into the buffer from the file.
-        match f.read_to_end(&mut contents) {
-            Ok(_) => {}
-            Err(_) => {
-                return Err(Error::new(ErrorCode::ServerError(
-                    error::ERROR_UNABLE_TO_OPEN_ACCOUNT,
-                )));
-            }
-        }
-
-        // Idk some crypto stuff
-        let mut read_buffer = buffer::RefReadBuffer::new(&contents);
-
-        // Make a buffer to store the final encrypted data in
-        let mut final_result = Vec::<u8>::new();
-
-        // More crypto garbage
-        let mut buffer = [0; 2048];
-
-        // Even more crypto stuff
-        let mut write_buffer = buffer::RefWriteBuffer::new(&mut buffer);
-
-        // Encrypt the stuff...
-        loop {
-            // Depersonalization isn't fun, but encryption is! Encrypt! Encrypt! Encrypt!
-            let result = if let Ok(res) = enc.encrypt(&mut read_buffer, &mut write_buffer, true) {
-                // We successfully encrypted the block. Cool.
-                res
-            } else {
-                // Return an error!
-                return Err(Error::new(ErrorCode::from(error::ERROR_ENCRYPTION_FAILED)));
-            };
-
-            final_result.extend(
-                write_buffer
-                    .take_read_buffer()
-                    .take_remaining()
-                    .iter()
-                    .copied(),
-            );
-
-            // Handle some bad stuff...
-            match result {
-                BufferResult::BufferUnderflow => break,
-                BufferResult::BufferOverflow => {}
-            }
-        }
-
-        // Reset the writer to the beginning of the file
-        if f.seek(SeekFrom::Start(0)).is_err() {
-            // Return an error
-            return Err(Error::new(ErrorCode::from(
-                error::ERROR_UNABLE_TO_WRITE_ACCOUNT,
-            )));
-        }
-
-        // Put the encrypted data in the file
-        match f.write_all(&final_result) {
-            Ok(_) => Ok(()),
-            Err(_) => Err(Error::new(ErrorCode::from(
-                error::ERROR_UNABLE_TO_WRITE_ACCOUNT,
-            ))),
-        }
+        keystore::lock_account(&address, &data_dir, &enc_key).map_err(keystore_error)
     }
 
     /// Unlocks the account with the corresponding address in the given data directory. If the account is already unlocked,
     /// an error is returned.
-    fn unlock(&self, address: Address, dec_key: String, data_dir: String) -> Result<Account> {
-        // We'll need to generate an IV to do encryption properly
-        let mut iv: [u8; 16] = [0; 16];
-
-        // Make a random number generator for the pwd
-        let mut rng: StdRng = SeedableRng::from_seed(*blake3::hash_slice(dec_key.as_bytes()));
-
-        // Generate an IV
-        rng.fill_bytes(&mut iv);
-
-        // Make an instance of the decryption helper for the file
-        let mut dec = aes::cbc_decryptor(
-            KeySize::KeySize128,
-            &*blake3::hash_slice(dec_key.as_bytes()),
-            &iv,
-            blockmodes::PkcsPadding,
-        );
-
-        // Open the file that the account is stored in
-        let mut f = if let Ok(f) = fs::OpenOptions::new().read(true).write(true).open(format!(
-            "{}/keystore/{}.json",
-            data_dir,
-            address.to_str()
-        )) {
-            f
-        } else {
-            // Return an error
-            return Err(Error::new(ErrorCode::ServerError(
-                error::ERROR_UNABLE_TO_OPEN_ACCOUNT,
-            )));
-        };
-
-        // The contents of the file. We'll have to decrypt this in a second.
-        let mut contents = Vec::new();
-
-        // Read into the buffer from the file.
-        match f.read_to_end(&mut contents) {
-            Ok(_) => {}
-            Err(_) => {
-                return Err(Error::new(ErrorCode::ServerError(
-                    error::ERROR_UNABLE_TO_OPEN_ACCOUNT,
-                )));
-            }
-        }
-
-        // Generate a few buffers, set the encoder to read from the file's contents
-        let mut final_result = Vec::<u8>::new();
-        let mut read_buffer = buffer::RefReadBuffer::new(&contents);
-        let mut buffer = [0; 2048];
-        let mut write_buffer = buffer::RefWriteBuffer::new(&mut buffer);
+    fn unlock(&self, address: Address, dec_key: String, data_dir: String) -> Result<PublicAccount> {
+        let acc =
+            keystore::unlock_account(&address, &data_dir, &dec_key).map_err(keystore_error)?;
 
-        loop {
-            // Try to decrypt the data in the file; return an error if this fails
-            let result = if let Ok(res) = dec.decrypt(&mut read_buffer, &mut write_buffer, true) {
-                res
-            } else {
-                // Return an error
-                return Err(Error::new(ErrorCode::from(error::ERROR_DECRYPTION_FAILED)));
-            };
-
-            // Put the decrypted data block into the overall buffer
-            final_result.extend(
-                write_buffer
-                    .take_read_buffer()
-                    .take_remaining()
-                    .iter()
-                    .copied(),
-            );
-
-            match result {
-                BufferResult::BufferUnderflow => break,
-                BufferResult::BufferOverflow => {}
-            }
-        }
-
-        // Deserialize the account
-        match serde_json::from_slice(final_result.as_slice()) {
-            Ok(acc) => {
-                // Re-open the file, but with permissions that delete what was previously in the file
-                f = if let Ok(opened_file) = fs::OpenOptions::new()
-                    .truncate(true)
-                    .write(true)
-                    .open(format!("{}/keystore/{}.json", data_dir, address.to_str()))
-                {
-                    opened_file
-                } else {
-                    // Return an error
-                    return Err(Error::new(ErrorCode::from(
-                        error::ERROR_UNABLE_TO_WRITE_ACCOUNT,
-                    )));
-                };
-
-                // Now that we've deserialized the account, let's write it back to the original file
-                match serde_json::to_writer(f, &acc) {
-                    Ok(_) => Ok(acc),
-                    Err(_) => Err(Error::new(ErrorCode::from(
-                        error::ERROR_UNABLE_TO_WRITE_ACCOUNT,
-                    ))),
-                }
-            }
-            Err(_) => Err(Error::new(ErrorCode::ServerError(
-                error::ERROR_UNABLE_TO_READ_ACCOUNT,
-            ))),
-        }
+        public_details(&acc)
     }
 
     /// Deletes the account with the corresponding address in the given data directory.
@@ -368,11 +209,135 @@ impl Accounts for AccountsImpl {
             }
         }
     }
+
+    /// Derives the deposit sub-address of the given parent account at the provided index.
+    fn derive_sub_address(&self, parent: Address, index: u64) -> Result<Address> {
+        Ok(parent.derive_sub_address(index)) // Return the derived sub-address
+    }
+
+    /// Maps a deposit sub-address back to the account in the given data directory that derived it.
+    fn get_parent(&self, address: Address, data_dir: String) -> Result<SubAccount> {
+        // Find the local account that would derive the sub-address at its stored index
+        account::get_all_unlocked_accounts_in_data_directory(&data_dir)
+            .into_iter()
+            .find_map(|parent| {
+                address
+                    .is_sub_address_of(&parent)
+                    .map(|index| SubAccount { parent, index })
+            })
+            .ok_or_else(|| Error::new(ErrorCode::from(error::ERROR_NO_SUCH_PARENT_ACCOUNT)))
+    }
+
+    /// Gets the spending policy of the account in the given data directory, and how much it has spent recently.
+    fn get_spending_status(&self, address: Address, data_dir: String) -> Result<SpendingStatus> {
+        let storage_error = |_| {
+            Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_ACCESS_SPENDING_POLICY,
+            ))
+        };
+
+        let policy =
+            SpendingPolicy::read_from_disk_at_data_directory(&data_dir).map_err(storage_error)?;
+        let mut log =
+            SpendingLog::read_from_disk_at_data_directory(&data_dir).map_err(storage_error)?;
+        log.prune(chrono::Utc::now());
+
+        Ok(log.status(&policy, &address))
+    }
+
+    /// Gets each of the accounts holding funds that haven't sent a transaction in the given number of days.
+    fn get_dormant_accounts(&self, idle_days: u64, limit: Option<usize>) -> Result<DormancyReport> {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(idle_days as i64);
+
+        if let Ok(rt) = self.runtime.read() {
+            rt.ledger
+                .dormancy_report(cutoff, limit.unwrap_or(usize::MAX))
+                .map_err(|_| Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_READ_DAG)))
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Unlocks the locked account with the given address in memory for a limited time.
+    fn open_unlock_session(
+        &self,
+        address: Address,
+        dec_key: String,
+        data_dir: String,
+        minutes: Option<u64>,
+    ) -> Result<UnlockSession> {
+        let acc =
+            keystore::read_locked_account(&address, &data_dir, &dec_key).map_err(keystore_error)?;
+
+        if let Ok(mut rt) = self.runtime.write() {
+            rt.unlock_sessions
+                .open(&acc, &data_dir, minutes.unwrap_or(DEFAULT_UNLOCK_MINUTES))
+                .map_err(unlock_error)
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Locks the account held by the unlock session with the given handle.
+    fn close_unlock_session(&self, handle: String) -> Result<()> {
+        if let Ok(mut rt) = self.runtime.write() {
+            rt.unlock_sessions.close(&handle).map_err(unlock_error)
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+}
+
+/// Gets the public details of the given account, which are all that the accounts API ever hands out.
+///
+/// # Arguments
+///
+/// * `acc` - The account
+fn public_details(acc: &Account) -> Result<PublicAccount> {
+    acc.public()
+        .map_err(|_| Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_READ_ACCOUNT)))
+}
+
+/// Converts the given unlock session error into a suitable JSON-RPC error.
+///
+/// # Arguments
+///
+/// * `e` - The unlock session error
+pub(crate) fn unlock_error(e: UnlockError) -> Error {
+    Error::new(ErrorCode::from(match e {
+        UnlockError::NoSuchSession | UnlockError::WrongAccount { .. } => {
+            error::ERROR_NO_SUCH_UNLOCK_SESSION
+        }
+        UnlockError::TooLong { .. } => error::ERROR_INVALID_UNLOCK_DURATION,
+        UnlockError::SealingFailed => error::ERROR_ENCRYPTION_FAILED,
+    }))
+}
+
+/// Converts the given keystore error into a suitable JSON-RPC error.
+///
+/// # Arguments
+///
+/// * `e` - The keystore error
+fn keystore_error(e: KeystoreError) -> Error {
+    Error::new(ErrorCode::from(match e {
+        KeystoreError::WrongPassword
+        | KeystoreError::UnsupportedVersion { .. }
+        | KeystoreError::Malformed { .. } => error::ERROR_DECRYPTION_FAILED,
+        KeystoreError::AlreadyLocked => error::ERROR_ACCOUNT_ALREADY_LOCKED,
+        KeystoreError::NotLocked => error::ERROR_ACCOUNT_NOT_LOCKED,
+        KeystoreError::Io { .. } => error::ERROR_UNABLE_TO_OPEN_ACCOUNT,
+    }))
 }
 
 impl AccountsImpl {
-    /// Registers the accounts service on the given IoHandler server.
-    pub fn register(io: &mut IoHandler, runtime: Arc<RwLock<System>>) {
+    /// Registers the accounts service on the given RPC handler.
+    pub fn register(io: &mut RpcHandler, runtime: Arc<InstrumentedRwLock<System>>) {
         // Register this service on the IO handler
         io.extend_with(Self { runtime }.to_delegate());
     }
@@ -380,64 +345,54 @@ impl AccountsImpl {
 
 /// A client for the SummerCash accounts API.
 pub struct Client {
-    /// The address for the server hosting the APi
-    pub server: String,
+    /// The underlying JSON-RPC client
+    rpc: RpcClient,
+}
 
-    /// An HTTP client
-    client: reqwest::Client,
+impl From<RpcClient> for Client {
+    /// Builds a client on top of the given JSON-RPC client, sharing its connections.
+    fn from(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
 }
 
 impl Client {
-    /// Initializes a new Client with the given remote URL.
+    /// Initializes a new Client with the given remote URL, or comma-separated list of URLs for redundant nodes.
     pub fn new(server_addr: &str) -> Self {
-        // Initialize and return the client
         Self {
-            server: server_addr.trim_end_matches('/').to_owned(),
-            client: reqwest::Client::new(),
+            rpc: RpcClient::new(server_addr),
         }
     }
 
-    /// Performs a request considering the given method, and returns the response.
-    async fn do_request<T>(
-        &self,
-        method: &str,
-        params: &str,
-    ) -> std::result::Result<T, failure::Error>
-    where
-        T: for<'de> Deserialize<'de>,
-    {
-        // Make a hashmap to store the body of the request in
-        let mut json_body: HashMap<&str, serde_json::Value> = HashMap::new();
-        json_body.insert("jsonrpc", serde_json::Value::String("2.0".to_owned()));
-        json_body.insert("method", serde_json::Value::String(method.to_owned()));
-        json_body.insert("id", serde_json::Value::String("".to_owned()));
-        json_body.insert("params", serde_json::from_str(params)?);
-
-        // Send a request to the endpoint, and pass the given parameters along with the request
-        let res = self
-            .client
-            .post(&self.server)
-            .json(&json_body)
-            .send()
-            .await?
-            .json::<Output>()
-            .await?;
-
-        // Some type conversion black magic fuckery
-        match res {
-            Output::Success(s) => match serde_json::from_value(s.result) {
-                Ok(res) => Ok(res),
-                Err(e) => Err(e.into()),
-            },
-            Output::Failure(e) => Err(e.error.into()),
-        }
+    /// Makes each of the client's requests under the admin session with the given token.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The token of the admin session, if there is one
+    pub fn with_session(mut self, session: Option<String>) -> Self {
+        self.rpc = self.rpc.with_session(session);
+
+        self
     }
 
-    /// Generates and returns a new account.
-    pub async fn generate(&self, data_dir: &str) -> std::result::Result<Account, failure::Error> {
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.rpc = self.rpc.with_retry_policy(policy);
+
+        self
+    }
+
+    /// Generates a new account, and returns its public details.
+    pub async fn generate(
+        &self,
+        data_dir: &str,
+    ) -> std::result::Result<PublicAccount, ClientError> {
         // Generate the account and return it
-        self.do_request::<Account>("new_account", &format!("[\"{}\"]", data_dir))
-            .await
+        self.rpc.call("new_account", (data_dir,)).await
     }
 
     /// Reads an account with the given address from the disk, and returns its details. If the account is locked,
@@ -446,12 +401,8 @@ impl Client {
         &self,
         address: Address,
         data_dir: &str,
-    ) -> std::result::Result<Account, failure::Error> {
-        self.do_request::<Account>(
-            "get_account",
-            &format!("[{}, \"{}\"]", serde_json::to_string(&address)?, data_dir),
-        )
-        .await
+    ) -> std::result::Result<PublicAccount, ClientError> {
+        self.rpc.call("get_account", (&address, data_dir)).await
     }
 
     /// Encrypts an account with the giiven address. If the account is already locked, an error is returned.
@@ -460,17 +411,10 @@ impl Client {
         address: Address,
         enc_key: &str,
         data_dir: &str,
-    ) -> std::result::Result<(), failure::Error> {
-        self.do_request::<()>(
-            "lock_account",
-            &format!(
-                "[{}, \"{}\", \"{}\"]",
-                serde_json::to_string(&address)?,
-                enc_key,
-                data_dir
-            ),
-        )
-        .await
+    ) -> std::result::Result<(), ClientError> {
+        self.rpc
+            .call("lock_account", (&address, enc_key, data_dir))
+            .await
     }
 
     /// Decrypts an account with the given address. If the account is already unlocked, an error is returned.
@@ -479,17 +423,10 @@ impl Client {
         address: Address,
         dec_key: &str,
         data_dir: &str,
-    ) -> std::result::Result<Account, failure::Error> {
-        self.do_request::<Account>(
-            "unlock_account",
-            &format!(
-                "[{}, \"{}\", \"{}\"]",
-                serde_json::to_string(&address)?,
-                dec_key,
-                data_dir
-            ),
-        )
-        .await
+    ) -> std::result::Result<PublicAccount, ClientError> {
+        self.rpc
+            .call("unlock_account", (&address, dec_key, data_dir))
+            .await
     }
 
     /// Deletes an account with the given address.
@@ -497,29 +434,100 @@ impl Client {
         &self,
         address: Address,
         data_dir: &str,
-    ) -> std::result::Result<(), failure::Error> {
-        self.do_request::<()>(
-            "delete_account",
-            &format!("[{}, \"{}\"]", serde_json::to_string(&address)?, data_dir),
-        )
-        .await
+    ) -> std::result::Result<(), ClientError> {
+        self.rpc.call("delete_account", (&address, data_dir)).await
     }
 
     /// Gets a list of accounts stored on the disk in a given directory.
-    pub async fn list(&self, data_dir: &str) -> std::result::Result<Vec<Address>, failure::Error> {
-        self.do_request::<Vec<Address>>("list_accounts", &format!("[\"{}\"]", data_dir))
-            .await
+    pub async fn list(&self, data_dir: &str) -> std::result::Result<Vec<Address>, ClientError> {
+        self.rpc.call("list_accounts", (data_dir,)).await
     }
 
     /// Gets the balance of a particular account.
     pub async fn balance(
         &self,
         address: Address,
-    ) -> std::result::Result<num::BigUint, failure::Error> {
-        self.do_request::<num::BigUint>(
-            "get_account_balance",
-            &format!("[{}]", serde_json::to_string(&address)?),
-        )
-        .await
+    ) -> std::result::Result<num::BigUint, ClientError> {
+        self.rpc.call("get_account_balance", (&address,)).await
+    }
+
+    /// Gets the balance of each of the given accounts in a single request, in the order given.
+    ///
+    /// # Arguments
+    ///
+    /// * `addresses` - The addresses of each of the accounts
+    pub async fn balances(
+        &self,
+        addresses: &[Address],
+    ) -> std::result::Result<Vec<std::result::Result<num::BigUint, ClientError>>, ClientError> {
+        let mut batch = self.rpc.batch();
+        for address in addresses {
+            batch.queue("get_account_balance", (address,))?;
+        }
+
+        batch.flush().await
+    }
+
+    /// Derives the deposit sub-address of the given parent account at the provided index.
+    pub async fn derive_sub_address(
+        &self,
+        parent: Address,
+        index: u64,
+    ) -> std::result::Result<Address, ClientError> {
+        self.rpc.call("derive_sub_address", (&parent, index)).await
+    }
+
+    /// Gets the account in the given data directory that derived a particular sub-address.
+    pub async fn get_parent(
+        &self,
+        address: Address,
+        data_dir: &str,
+    ) -> std::result::Result<SubAccount, ClientError> {
+        self.rpc
+            .call("get_parent_account", (&address, data_dir))
+            .await
+    }
+
+    /// Gets the spending policy of an account in the given data directory, and how much it has spent recently.
+    pub async fn get_spending_status(
+        &self,
+        address: Address,
+        data_dir: &str,
+    ) -> std::result::Result<SpendingStatus, ClientError> {
+        self.rpc
+            .call("get_spending_status", (&address, data_dir))
+            .await
+    }
+
+    /// Gets each of the accounts holding funds that haven't sent a transaction in the given number of days.
+    pub async fn get_dormant_accounts(
+        &self,
+        idle_days: u64,
+        limit: Option<usize>,
+    ) -> std::result::Result<DormancyReport, ClientError> {
+        self.rpc
+            .call("get_dormant_accounts", (idle_days, limit))
+            .await
+    }
+
+    /// Unlocks a locked account in memory for the given number of minutes, leaving its keystore locked on the disk.
+    pub async fn open_unlock_session(
+        &self,
+        address: Address,
+        dec_key: &str,
+        data_dir: &str,
+        minutes: Option<u64>,
+    ) -> std::result::Result<UnlockSession, ClientError> {
+        self.rpc
+            .call(
+                "open_unlock_session",
+                (&address, dec_key, data_dir, minutes),
+            )
+            .await
+    }
+
+    /// Locks the account held by the unlock session with the given handle.
+    pub async fn close_unlock_session(&self, handle: &str) -> std::result::Result<(), ClientError> {
+        self.rpc.call("close_unlock_session", (handle,)).await
     }
 }
@@ -0,0 +1,522 @@
+use jsonrpc_core::{
+    futures::{
+        future::{self, Either},
+        Future,
+    },
+    middleware::{NoopCallFuture, NoopFuture},
+    response::Output,
+    Call, Error, ErrorCode, MetaIoHandler, Metadata, Middleware, Result,
+};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{PubSubMetadata, Session as PubSubSession};
+
+use serde::{Deserialize, Serialize};
+
+use chrono::{DateTime, Duration, Utc};
+
+use rand::RngCore;
+
+use super::{
+    super::super::{
+        accounts::account::Account, common::address::Address, core::types::signature::Signature,
+    },
+    access::ReadOnlyGate,
+    batch::BatchLimit,
+    client::{ClientError, RpcClient},
+    error,
+    failover::RetryPolicy,
+    tenants::TenantGate,
+};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// The number of seconds that a client has to answer a challenge.
+pub const CHALLENGE_TTL_SECS: i64 = 60;
+
+/// The number of seconds that an admin session lasts for.
+pub const SESSION_TTL_SECS: i64 = 900;
+
+/// The most challenges that may be outstanding at once. Anyone can ask for a challenge, so once this many are
+/// outstanding, issuing another drops the one closest to expiring.
+pub const MAX_OUTSTANDING_CHALLENGES: usize = 1024;
+
+/// The prefix prepended to each challenge before it is signed, so that a challenge signature can't be passed off as
+/// anything else.
+pub const CHALLENGE_DOMAIN: &[u8] = b"summercash-admin-session:";
+
+/// The RPC methods that may only be called with an admin session, once any admin accounts have been configured.
+pub const ADMIN_METHODS: [&str; 18] = [
+    "new_account",
+    "get_account",
+    "lock_account",
+    "unlock_account",
+    "open_unlock_session",
+    "delete_account",
+    "sign_transaction",
+    "confirm_proposal",
+    "pin_checkpoint",
+    "unpin_checkpoint",
+    "add_voting_account",
+    "remove_voting_account",
+    "verify_ledger",
+    "set_note",
+    "delete_note",
+    "add_contact",
+    "delete_contact",
+    "dump_lock_holders",
+];
+
+/// An RPC handler that may only serve read-only methods, whose tenants are confined to their namespaces, whose admin
+/// methods are guarded by admin sessions, and whose batches are capped in size.
+pub type RpcHandler = MetaIoHandler<Session, (ReadOnlyGate, TenantGate, AdminGate, BatchLimit)>;
+
+/// An error encountered while opening an admin session.
+#[derive(Debug, Fail)]
+pub enum AuthError {
+    #[fail(display = "the challenge was never issued, has already been answered, or has expired")]
+    NoSuchChallenge,
+    #[fail(display = "the challenge's signature is invalid")]
+    InvalidSignature,
+    #[fail(display = "account {} isn't an admin of this node", address)]
+    NotAnAdmin {
+        address: String, // The address of the account that signed the challenge
+    },
+}
+
+/// A challenge that a client must sign with an admin account in order to open a session.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Challenge {
+    /// The random challenge that must be signed
+    pub challenge: String,
+
+    /// The time after which the challenge can no longer be answered
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A short-lived token granting access to the admin RPC methods.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SessionToken {
+    /// The token that must be presented as a bearer token with each admin request
+    pub token: String,
+
+    /// The admin account that opened the session
+    pub admin: Address,
+
+    /// The time after which the token is no longer accepted
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The outstanding challenges and open sessions of a node, along with the accounts allowed to open a session.
+#[derive(Default)]
+pub struct SessionTable {
+    /// The accounts allowed to open an admin session
+    admins: Vec<Address>,
+
+    /// The time at which each outstanding challenge expires
+    challenges: HashMap<String, DateTime<Utc>>,
+
+    /// Each of the open sessions, keyed by their token
+    sessions: HashMap<String, SessionToken>,
+}
+
+/// Implement a set of session table helper methods.
+impl SessionTable {
+    /// Initializes a new session table, allowing the given accounts to open admin sessions.
+    ///
+    /// # Arguments
+    ///
+    /// * `admins` - The accounts allowed to open an admin session
+    pub fn new(admins: Vec<Address>) -> Self {
+        Self {
+            admins,
+            ..Default::default()
+        }
+    }
+
+    /// Checks whether or not the admin methods are guarded at all. Nodes without any admin accounts leave them open.
+    pub fn is_enforced(&self) -> bool {
+        !self.admins.is_empty()
+    }
+
+    /// Issues a new challenge, which can be answered once before it expires.
+    pub fn issue_challenge(&mut self) -> Challenge {
+        self.prune(); // Drop anything that has expired
+
+        // Make room for the new challenge, if there are already too many
+        if self.challenges.len() >= MAX_OUTSTANDING_CHALLENGES {
+            if let Some(oldest) = self
+                .challenges
+                .iter()
+                .min_by_key(|(_, expires_at)| **expires_at)
+                .map(|(challenge, _)| challenge.clone())
+            {
+                self.challenges.remove(&oldest);
+            }
+        }
+
+        let challenge = Challenge {
+            challenge: random_token(),
+            expires_at: Utc::now() + Duration::seconds(CHALLENGE_TTL_SECS),
+        };
+        self.challenges
+            .insert(challenge.challenge.clone(), challenge.expires_at);
+
+        challenge
+    }
+
+    /// Opens a session for the admin account that signed the given challenge. The challenge can't be answered again,
+    /// whether or not the session is opened.
+    ///
+    /// # Arguments
+    ///
+    /// * `challenge` - The challenge that was signed
+    /// * `signature` - The admin account's signature of the challenge
+    pub fn open_session(
+        &mut self,
+        challenge: &str,
+        signature: &Signature,
+    ) -> std::result::Result<SessionToken, AuthError> {
+        self.prune(); // Drop anything that has expired
+
+        if self.challenges.remove(challenge).is_none() {
+            return Err(AuthError::NoSuchChallenge);
+        }
+
+        if !signature.verify(&challenge_message(challenge)) {
+            return Err(AuthError::InvalidSignature);
+        }
+
+        let admin = signature
+            .address()
+            .map_err(|_| AuthError::InvalidSignature)?;
+        if !self.admins.contains(&admin) {
+            return Err(AuthError::NotAnAdmin {
+                address: admin.to_str(),
+            });
+        }
+
+        let session = SessionToken {
+            token: random_token(),
+            admin,
+            expires_at: Utc::now() + Duration::seconds(SESSION_TTL_SECS),
+        };
+        self.sessions.insert(session.token.clone(), session.clone());
+
+        Ok(session)
+    }
+
+    /// Checks whether or not the given token belongs to an open session.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The bearer token presented with a request, if any
+    pub fn is_valid(&self, token: Option<&str>) -> bool {
+        token
+            .and_then(|token| self.sessions.get(token))
+            .map_or(false, |session| session.expires_at > Utc::now())
+    }
+
+    /// Drops each of the expired challenges and sessions.
+    fn prune(&mut self) {
+        let now = Utc::now();
+
+        self.challenges.retain(|_, expires_at| *expires_at > now);
+        self.sessions.retain(|_, session| session.expires_at > now);
+    }
+}
+
+/// Builds the message that must be signed in order to answer the given challenge.
+///
+/// # Arguments
+///
+/// * `challenge` - The challenge issued by the node
+pub fn challenge_message(challenge: &str) -> Vec<u8> {
+    [CHALLENGE_DOMAIN, challenge.as_bytes()].concat()
+}
+
+/// Generates a random, base58-encoded token.
+fn random_token() -> String {
+    let mut token = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut token);
+
+    bs58::encode(token).into_string()
+}
+
+/// The per-request metadata of the RPC server: the bearer token that the request was made with, if any, and the
+/// connection that it came in over, if it can be pushed notifications.
+#[derive(Clone, Debug, Default)]
+pub struct Session {
+    /// The bearer token presented with the request: an admin session token, or a tenant's API key
+    pub token: Option<String>,
+
+    /// The namespace that the request was confined to, if it was made with a tenant's API key. Only ever set by the
+    /// tenant gate.
+    pub tenant: Option<String>,
+
+    /// The WebSocket connection that the request came in over, which subscription notifications are pushed down. Never
+    /// set for requests made over HTTP.
+    pub pubsub: Option<Arc<PubSubSession>>,
+}
+
+impl Metadata for Session {}
+
+impl PubSubMetadata for Session {
+    /// Gets the connection that notifications for subscriptions opened by the request are pushed down, if any.
+    fn session(&self) -> Option<Arc<PubSubSession>> {
+        self.pubsub.clone()
+    }
+}
+
+impl Session {
+    /// Extracts the session of a request from its Authorization header.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The value of the request's Authorization header, if it has one
+    pub fn from_authorization_header(header: Option<&str>) -> Self {
+        Self {
+            token: header
+                .map(str::trim)
+                .filter(|header| header.starts_with("Bearer "))
+                .map(|header| header["Bearer ".len()..].trim().to_owned()),
+            tenant: None,
+            pubsub: None,
+        }
+    }
+}
+
+/// A middleware rejecting any call to an admin method that isn't made with an open session, or confined to a tenant's
+/// namespace.
+pub struct AdminGate {
+    /// The node's challenges and sessions
+    pub sessions: Arc<RwLock<SessionTable>>,
+}
+
+impl Middleware<Session> for AdminGate {
+    type Future = NoopFuture;
+    type CallFuture = NoopCallFuture;
+
+    /// Rejects the call if it is made to an admin method without an open session.
+    fn on_call<F, X>(&self, call: Call, meta: Session, next: F) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, Session) -> X + Send + Sync,
+        X: Future<Item = Option<Output>, Error = ()> + Send + 'static,
+    {
+        if let Call::MethodCall(ref method_call) = call {
+            let authorized = meta.tenant.is_some()
+                || self.sessions.read().map_or(false, |sessions| {
+                    !sessions.is_enforced() || sessions.is_valid(meta.token.as_deref())
+                });
+
+            if !authorized && ADMIN_METHODS.contains(&method_call.method.as_str()) {
+                return Either::A(Box::new(future::ok(Some(Output::from(
+                    Err(Error::new(ErrorCode::from(error::ERROR_UNAUTHORIZED))),
+                    method_call.id.clone(),
+                    method_call.jsonrpc,
+                )))));
+            }
+        }
+
+        Either::B(next(call, meta))
+    }
+}
+
+/// Defines the standard SummerCash admin session RPC API.
+#[rpc]
+pub trait Auth {
+    /// Issues a challenge that must be signed by an admin account in order to open an admin session.
+    #[rpc(name = "get_auth_challenge")]
+    fn challenge(&self) -> Result<Challenge>;
+
+    /// Opens an admin session for the admin account that signed the given challenge, and returns its token.
+    #[rpc(name = "open_admin_session")]
+    fn open_session(&self, challenge: String, signature: Signature) -> Result<SessionToken>;
+}
+
+/// An implementation of the admin session API.
+pub struct AuthImpl {
+    pub(crate) sessions: Arc<RwLock<SessionTable>>,
+}
+
+impl Auth for AuthImpl {
+    /// Issues a challenge that must be signed by an admin account in order to open an admin session.
+    fn challenge(&self) -> Result<Challenge> {
+        if let Ok(mut sessions) = self.sessions.write() {
+            Ok(sessions.issue_challenge())
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Opens an admin session for the admin account that signed the given challenge, and returns its token.
+    fn open_session(&self, challenge: String, signature: Signature) -> Result<SessionToken> {
+        if let Ok(mut sessions) = self.sessions.write() {
+            sessions.open_session(&challenge, &signature).map_err(|e| {
+                debug!("Refusing to open an admin session: {}", e);
+
+                Error::new(ErrorCode::from(error::ERROR_INVALID_AUTH_RESPONSE))
+            })
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+}
+
+impl AuthImpl {
+    /// Registers the admin session service on the given RPC handler.
+    pub fn register(io: &mut RpcHandler, sessions: Arc<RwLock<SessionTable>>) {
+        // Register this service on the IO handler
+        io.extend_with(Self { sessions }.to_delegate());
+    }
+}
+
+/// A client for the admin session API.
+pub struct Client {
+    /// The underlying JSON-RPC client
+    rpc: RpcClient,
+}
+
+impl From<RpcClient> for Client {
+    /// Builds a client on top of the given JSON-RPC client, sharing its connections.
+    fn from(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+}
+
+impl Client {
+    /// Initializes a new Client with the given remote URL, or comma-separated list of URLs for redundant nodes.
+    pub fn new(server_addr: &str) -> Self {
+        Self {
+            rpc: RpcClient::new(server_addr),
+        }
+    }
+
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.rpc = self.rpc.with_retry_policy(policy);
+
+        self
+    }
+
+    /// Requests a challenge that must be signed by an admin account in order to open an admin session.
+    pub async fn challenge(&self) -> std::result::Result<Challenge, ClientError> {
+        self.rpc.call("get_auth_challenge", ()).await
+    }
+
+    /// Opens an admin session with the given signed challenge.
+    pub async fn open_session(
+        &self,
+        challenge: String,
+        signature: Signature,
+    ) -> std::result::Result<SessionToken, ClientError> {
+        self.rpc
+            .call("open_admin_session", (&challenge, &signature))
+            .await
+    }
+
+    /// Proves possession of the given admin account to the node, and returns the resulting session token.
+    ///
+    /// # Arguments
+    ///
+    /// * `admin` - The admin account that the session should be opened with
+    pub async fn login(
+        &self,
+        admin: &Account,
+    ) -> std::result::Result<SessionToken, failure::Error> {
+        let challenge = self.challenge().await?; // Ask the node for something to sign
+
+        // Sign the challenge with the admin account
+        let signature = Signature::new(&admin.keypair()?, &challenge_message(&challenge.challenge));
+
+        self.open_session(challenge.challenge, signature).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_open_session() {
+        let admin = Account::new(); // Make an admin account
+        let outsider = Account::new(); // Make an account that isn't an admin
+
+        let mut table = SessionTable::new(vec![admin.address().unwrap()]);
+        assert!(table.is_enforced());
+
+        // Answer a challenge with the admin account
+        let challenge = table.issue_challenge().challenge;
+        let session = table
+            .open_session(
+                &challenge,
+                &Signature::new(&admin.keypair().unwrap(), &challenge_message(&challenge)),
+            )
+            .unwrap();
+        assert!(table.is_valid(Some(&session.token))); // The session should be open
+        assert!(!table.is_valid(Some("forged")));
+        assert!(!table.is_valid(None));
+
+        // A challenge can't be answered twice
+        assert!(table
+            .open_session(
+                &challenge,
+                &Signature::new(&admin.keypair().unwrap(), &challenge_message(&challenge)),
+            )
+            .is_err());
+
+        // Only admins can open a session
+        let challenge = table.issue_challenge().challenge;
+        assert!(table
+            .open_session(
+                &challenge,
+                &Signature::new(&outsider.keypair().unwrap(), &challenge_message(&challenge)),
+            )
+            .is_err());
+
+        // The signature has to cover the challenge that was issued
+        let challenge = table.issue_challenge().challenge;
+        assert!(table
+            .open_session(
+                &challenge,
+                &Signature::new(&admin.keypair().unwrap(), b"something else"),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_challenge_cap() {
+        let mut table = SessionTable::new(vec![Account::new().address().unwrap()]);
+
+        let first = table.issue_challenge().challenge;
+        for _ in 0..MAX_OUTSTANDING_CHALLENGES {
+            table.issue_challenge();
+        }
+
+        assert_eq!(table.challenges.len(), MAX_OUTSTANDING_CHALLENGES); // Ensure the table doesn't grow
+        assert!(!table.challenges.contains_key(&first)); // Ensure the oldest challenge made room
+    }
+
+    #[test]
+    fn test_from_authorization_header() {
+        assert_eq!(
+            Session::from_authorization_header(Some("Bearer abc")).token,
+            Some("abc".to_owned())
+        );
+        assert_eq!(
+            Session::from_authorization_header(Some("Basic abc")).token,
+            None
+        );
+        assert_eq!(Session::from_authorization_header(None).token, None);
+    }
+}
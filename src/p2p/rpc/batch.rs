@@ -0,0 +1,110 @@
+use jsonrpc_core::{
+    futures::{
+        future::{self, Either},
+        Future,
+    },
+    middleware::{NoopCallFuture, NoopFuture},
+    Error, ErrorCode, Metadata, Middleware, Request, Response, Version,
+};
+
+use super::error;
+
+/// The greatest number of calls that a node answers in a single JSON-RPC batch, unless told otherwise.
+pub const MAX_BATCH_CALLS: usize = 100;
+
+/// A middleware refusing JSON-RPC batches holding more calls than the node is willing to answer in one request.
+/// Batches under the limit are answered call by call, so each call still passes through any other middleware (e.g.
+/// the admin gate) on its own.
+pub struct BatchLimit {
+    /// The greatest number of calls allowed in a batch
+    pub max_calls: usize,
+}
+
+impl Default for BatchLimit {
+    /// Builds a limit allowing up to MAX_BATCH_CALLS calls in a batch.
+    fn default() -> Self {
+        Self::new(MAX_BATCH_CALLS)
+    }
+}
+
+/// Implement a set of batch limit helper methods.
+impl BatchLimit {
+    /// Initializes a new batch limit allowing up to the given number of calls in a batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_calls` - The greatest number of calls allowed in a batch
+    pub fn new(max_calls: usize) -> Self {
+        Self { max_calls }
+    }
+
+    /// Checks whether or not the given request is a batch holding too many calls.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request made to the node
+    pub fn exceeded_by(&self, request: &Request) -> bool {
+        match request {
+            Request::Batch(calls) => calls.len() > self.max_calls,
+            Request::Single(_) => false,
+        }
+    }
+}
+
+impl<M: Metadata> Middleware<M> for BatchLimit {
+    type Future = NoopFuture;
+    type CallFuture = NoopCallFuture;
+
+    /// Refuses the request outright if it is a batch holding too many calls.
+    fn on_request<F, X>(&self, request: Request, meta: M, next: F) -> Either<Self::Future, X>
+    where
+        F: Fn(Request, M) -> X + Send + Sync,
+        X: Future<Item = Option<Response>, Error = ()> + Send + 'static,
+    {
+        if self.exceeded_by(&request) {
+            return Either::A(Box::new(future::ok(Some(Response::from(
+                Error::new(ErrorCode::from(error::ERROR_BATCH_TOO_LARGE)),
+                Some(Version::V2),
+            )))));
+        }
+
+        Either::B(next(request, meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_exceeded_by() {
+        let limit = BatchLimit::new(2);
+        let batch = |n: usize| {
+            serde_json::from_value::<Request>(serde_json::Value::Array(
+                (0..n)
+                    .map(|id| {
+                        serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "method": "list_transactions",
+                            "params": [],
+                            "id": id
+                        })
+                    })
+                    .collect(),
+            ))
+            .unwrap()
+        };
+
+        assert!(!limit.exceeded_by(&batch(2)));
+        assert!(limit.exceeded_by(&batch(3))); // One call too many
+        assert!(!limit.exceeded_by(
+            &serde_json::from_value(serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "list_transactions",
+                "params": [],
+                "id": 0
+            }))
+            .unwrap()
+        )); // Single calls are never refused
+    }
+}
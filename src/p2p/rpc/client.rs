@@ -0,0 +1,548 @@
+use jsonrpc_core::{response::Output, ErrorCode, Id, Response};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use super::{
+    error,
+    failover::{FailoverError, RetryPolicy, Upstream},
+};
+
+/// The broad reason that a node refused a request, derived from the error code that it answered with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServerErrorKind {
+    /// The request needs an admin session that the caller doesn't have
+    Unauthorized,
+
+    /// The account, transaction, proposal, or other object that the request named doesn't exist
+    NotFound,
+
+    /// The request asked for something that the node's rules don't allow
+    Rejected,
+
+    /// The node failed to encrypt, decrypt, sign, or generate randomness
+    Crypto,
+
+    /// The node failed to read from, or write to, its disk or database
+    Storage,
+
+    /// The node was too busy to serve the request, and it may be retried
+    Busy,
+
+    /// The request wasn't valid JSON-RPC, or named a method or parameters that the node doesn't know
+    Protocol,
+
+    /// The node answered with an error code that this client doesn't know
+    Unknown,
+}
+
+impl fmt::Display for ServerErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Implement a set of server error kind helper methods.
+impl ServerErrorKind {
+    /// Gets the kind of the given error code.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The error code returned by the node
+    pub fn from_code(code: i64) -> Self {
+        match code {
+            error::ERROR_UNAUTHORIZED
+            | error::ERROR_INVALID_AUTH_RESPONSE
+            | error::ERROR_NO_TENANT
+            | error::ERROR_CONFIRMATION_REQUIRED
+            | error::ERROR_METHOD_NOT_EXPOSED => Self::Unauthorized,
+            error::ERROR_UNABLE_TO_OPEN_ACCOUNT
+            | error::ERROR_UNABLE_TO_OPEN_TRANSACTION
+            | error::ERROR_NO_SUCH_RECEIPT
+            | error::ERROR_NO_SUCH_PARENT_ACCOUNT
+            | error::ERROR_NO_SUCH_PROPOSAL
+            | error::ERROR_NO_SUCH_NOTE
+            | error::ERROR_NO_SUCH_STATE
+            | error::ERROR_NO_SUCH_GUARDIANS
+            | error::ERROR_NO_HISTORICAL_STATE
+            | error::ERROR_NO_SUCH_SUBSCRIPTION
+            | error::ERROR_NO_SUCH_UNLOCK_SESSION
+            | error::ERROR_NO_SUCH_CONTACT
+            | error::ERROR_NO_SUCH_EXPORT
+            | error::ERROR_UNKNOWN_SHORT_ID
+            | error::ERROR_NO_SUCH_BALANCE => Self::NotFound,
+            error::ERROR_SIGNATURE_UNDEFINED
+            | error::ERROR_UNABLE_TO_CREATE_PROPOSAL
+            | error::ERROR_INVALID_GUARDIAN_INSTRUCTION
+            | error::ERROR_INVALID_BATCH
+            | error::ERROR_INVALID_CHECKPOINT
+            | error::ERROR_CHECKPOINT_CONFLICT
+            | error::ERROR_BATCH_TOO_LARGE
+            | error::ERROR_INVALID_REPLAY_CURSOR
+            | error::ERROR_INVALID_TIME_LOCK
+            | error::ERROR_ARCHIVE_UNSUPPORTED
+            | error::ERROR_NO_UNCLAIMED_REWARDS
+            | error::ERROR_INVALID_QUERY
+            | error::ERROR_SPENDING_POLICY_VIOLATION
+            | error::ERROR_INVALID_UNLOCK_DURATION
+            | error::ERROR_INVALID_CONTACT
+            | error::ERROR_UNEXPECTED_EXPORT_ACK
+            | error::ERROR_AMBIGUOUS_SHORT_ID
+            | error::ERROR_MALFORMED_HASH
+            | error::ERROR_INVALID_GAS_PRICE
+            | error::ERROR_MEMPOOL_REJECTED
+            | error::ERROR_ACCOUNT_ALREADY_LOCKED
+            | error::ERROR_ACCOUNT_NOT_LOCKED
+            | error::ERROR_INVALID_BALANCE_PROOF => Self::Rejected,
+            error::ERROR_UNABLE_TO_GENERATE_RANODM
+            | error::ERROR_ENCRYPTION_FAILED
+            | error::ERROR_DECRYPTION_FAILED
+            | error::ERROR_REMOTE_SIGNER_FAILED => Self::Crypto,
+            error::ERROR_UNABLE_TO_WRITE_ACCOUNT
+            | error::ERROR_UNABLE_TO_READ_ACCOUNT
+            | error::ERROR_UNABLE_TO_DELETE_ACCOUNT
+            | error::ERROR_UNABLE_TO_OBTAIN_STATE_REF
+            | error::ERROR_UNABLE_TO_OPEN_GENESIS_CONFIG
+            | error::ERROR_UNABLE_TO_CREATE_GENESIS
+            | error::ERROR_SERIALIZATION_FAILED
+            | error::ERROR_UNABLE_TO_WRITE_TRANSACTION
+            | error::ERROR_UNABLE_TO_READ_DAG
+            | error::ERROR_UNABLE_TO_READ_NOTES
+            | error::ERROR_UNABLE_TO_WRITE_NOTES
+            | error::ERROR_UNABLE_TO_READ_CONTACTS
+            | error::ERROR_UNABLE_TO_WRITE_CONTACTS
+            | error::ERROR_UNABLE_TO_WRITE_CHECKPOINTS
+            | error::ERROR_UNABLE_TO_WRITE_VOTING_POLICY
+            | error::ERROR_UNABLE_TO_ACCESS_SPENDING_POLICY
+            | error::ERROR_STORAGE_EXHAUSTED
+            | error::ERROR_DISK_READ_ONLY => Self::Storage,
+            error::ERROR_UNABLE_TO_OBTAIN_LOCK
+            | error::ERROR_TOO_MANY_SUBSCRIPTIONS
+            | error::ERROR_TOO_MANY_EXPORTS
+            | error::ERROR_RATE_LIMITED => Self::Busy,
+            -32768..=-32000 => Self::Protocol,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// An error encountered while making a request to a node's RPC API.
+#[derive(Debug, Fail)]
+pub enum ClientError {
+    #[fail(display = "{}", error)]
+    Unreachable {
+        error: FailoverError, // Why none of the nodes answered
+    },
+    #[fail(
+        display = "the node refused the request ({}, code {}): {}",
+        kind, code, message
+    )]
+    Server {
+        kind: ServerErrorKind, // The broad reason for the refusal
+        code: i64,             // The error code returned by the node
+        message: String,       // The message returned by the node
+    },
+    #[fail(
+        display = "failed to encode the request or decode the response: {}",
+        error
+    )]
+    Serialization {
+        error: String, // The underlying error
+    },
+}
+
+impl From<FailoverError> for ClientError {
+    /// Converts the given failover error into a ClientError.
+    fn from(error: FailoverError) -> Self {
+        Self::Unreachable { error }
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    /// Converts the given serde_json error into a ClientError.
+    fn from(e: serde_json::Error) -> Self {
+        Self::Serialization {
+            error: e.to_string(),
+        }
+    }
+}
+
+impl From<jsonrpc_core::Error> for ClientError {
+    /// Converts the given JSON-RPC error, as returned by a node, into a ClientError. Any strings listed in the
+    /// error's data (e.g. the candidates for an ambiguous short id) are kept in its message.
+    fn from(e: jsonrpc_core::Error) -> Self {
+        let code = e.code.code();
+
+        let message = match e.code {
+            ErrorCode::ServerError(_) => error::describe(code)
+                .map(str::to_owned)
+                .unwrap_or(e.message),
+            _ => e.message,
+        };
+        let details: Vec<&str> = e
+            .data
+            .as_ref()
+            .and_then(|data| data.as_array())
+            .map_or_else(Vec::new, |items| {
+                items.iter().filter_map(|item| item.as_str()).collect()
+            });
+
+        Self::Server {
+            kind: ServerErrorKind::from_code(code),
+            code,
+            message: if details.is_empty() {
+                message
+            } else {
+                format!("{} ({})", message, details.join(", "))
+            },
+        }
+    }
+}
+
+/// Gets the result of the given call, as answered by a node.
+///
+/// # Arguments
+///
+/// * `output` - The node's answer to the call
+fn decode<T: DeserializeOwned>(output: Output) -> Result<T, ClientError> {
+    match output {
+        Output::Success(s) => Ok(serde_json::from_value(s.result)?),
+        Output::Failure(f) => Err(f.error.into()),
+    }
+}
+
+/// A JSON-RPC request, as sent to a node.
+#[derive(Serialize)]
+struct Request<'a, P: Serialize> {
+    /// The version of JSON-RPC spoken
+    jsonrpc: &'static str,
+
+    /// The name of the method being called
+    method: &'a str,
+
+    /// The positional parameters of the method
+    params: P,
+
+    /// An identifier echoed back in the node's response
+    id: u64,
+}
+
+/// The client underlying each of the RPC API clients. Clones share their HTTP connections, and their knowledge of
+/// which nodes are up, so one client may be cloned into each of the API clients that a program uses.
+#[derive(Clone)]
+pub struct RpcClient {
+    /// The nodes hosting the API
+    upstream: Arc<Upstream>,
+
+    /// The HTTP client, whose connections are reused between requests
+    http: reqwest::Client,
+
+    /// The token of the admin session that requests should be made under, if any
+    session: Option<String>,
+
+    /// The identifier of the next request
+    next_id: Arc<AtomicU64>,
+}
+
+impl RpcClient {
+    /// Initializes a new RpcClient with the given remote URL, or comma-separated list of URLs for redundant nodes.
+    pub fn new(server_addr: &str) -> Self {
+        Self {
+            upstream: Arc::new(Upstream::new(server_addr)),
+            http: reqwest::Client::new(),
+            session: None,
+            next_id: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Makes each of the client's requests under the admin session with the given token.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The token of the admin session, if there is one
+    pub fn with_session(mut self, session: Option<String>) -> Self {
+        self.session = session;
+
+        self
+    }
+
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.upstream =
+            Arc::new(Upstream::new(&self.upstream.endpoints().join(",")).with_retry_policy(policy));
+
+        self
+    }
+
+    /// Gets the nodes that the client sends its requests to.
+    pub fn upstream(&self) -> &Upstream {
+        &self.upstream
+    }
+
+    /// Calls the given method with the given positional parameters (a tuple, or () for none), and returns the
+    /// node's result.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The name of the method
+    /// * `params` - The parameters of the method, in order
+    pub async fn call<P, T>(&self, method: &str, params: P) -> Result<T, ClientError>
+    where
+        P: Serialize,
+        T: DeserializeOwned,
+    {
+        let request = Request {
+            jsonrpc: "2.0",
+            method,
+            params,
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+        };
+
+        // Send the request to the first node that answers
+        decode(
+            self.upstream
+                .post::<_, Output>(
+                    &self.http,
+                    self.session.as_ref().map(String::as_str),
+                    &request,
+                )
+                .await?,
+        )
+    }
+
+    /// Starts a batch of calls, each returning a T, that are sent to the node together once flushed.
+    pub fn batch<T: DeserializeOwned>(&self) -> Batch<'_, T> {
+        Batch {
+            rpc: self,
+            calls: Vec::new(),
+            result: PhantomData,
+        }
+    }
+}
+
+/// A set of calls queued up to be sent to a node in a single JSON-RPC batch, so that resolving many items (e.g. the
+/// balances of each of a wallet's accounts) costs a single round trip.
+pub struct Batch<'a, T> {
+    /// The client that the batch is sent through
+    rpc: &'a RpcClient,
+
+    /// Each of the queued calls, in the order in which they were queued
+    calls: Vec<Request<'a, serde_json::Value>>,
+
+    /// The type returned by each of the calls
+    result: PhantomData<T>,
+}
+
+/// Implement a set of batch helper methods.
+impl<'a, T: DeserializeOwned> Batch<'a, T> {
+    /// Queues a call to the given method with the given positional parameters (a tuple, or () for none).
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The name of the method
+    /// * `params` - The parameters of the method, in order
+    pub fn queue<P: Serialize>(&mut self, method: &'a str, params: P) -> Result<(), ClientError> {
+        self.calls.push(Request {
+            jsonrpc: "2.0",
+            method,
+            params: serde_json::to_value(params)?,
+            id: self.rpc.next_id.fetch_add(1, Ordering::SeqCst),
+        });
+
+        Ok(())
+    }
+
+    /// Gets the number of calls queued so far.
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    /// Checks whether or not any calls have been queued.
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Sends each of the queued calls to the node in a single request, and returns the result of each call in the
+    /// order in which they were queued. An error is only returned if the batch as a whole failed.
+    pub async fn flush(self) -> Result<Vec<Result<T, ClientError>>, ClientError> {
+        if self.calls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let response = self
+            .rpc
+            .upstream
+            .post::<_, Response>(
+                &self.rpc.http,
+                self.rpc.session.as_ref().map(String::as_str),
+                &self.calls,
+            )
+            .await?;
+
+        self.sort(response)
+    }
+
+    /// Matches each of the node's answers up with the call that it answers, since a node may answer the calls in a
+    /// batch in any order.
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - The node's answer to the batch
+    fn sort(self, response: Response) -> Result<Vec<Result<T, ClientError>>, ClientError> {
+        let mut outputs = match response {
+            Response::Batch(outputs) => outputs,
+
+            // The node refused the batch as a whole
+            Response::Single(output) => {
+                return Err(decode::<serde_json::Value>(output)
+                    .err()
+                    .unwrap_or_else(|| ClientError::Serialization {
+                        error: "expected a batch response".to_owned(),
+                    }))
+            }
+        };
+
+        Ok(self
+            .calls
+            .iter()
+            .map(|call| {
+                match outputs
+                    .iter()
+                    .position(|output| output.id() == &Id::Num(call.id))
+                {
+                    Some(i) => decode(outputs.swap_remove(i)),
+                    None => Err(ClientError::Unanswered {
+                        method: call.method.to_owned(),
+                    }),
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_request() {
+        let request = Request {
+            jsonrpc: "2.0",
+            method: "get_note",
+            params: ("subject", "data"),
+            id: 1,
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "get_note",
+                "params": ["subject", "data"],
+                "id": 1
+            })
+        ); // Tuples should be sent as positional parameters
+
+        let single = Request {
+            jsonrpc: "2.0",
+            method: "list_notes",
+            params: ("data",),
+            id: 2,
+        };
+        assert_eq!(
+            serde_json::to_value(&single).unwrap()["params"],
+            serde_json::json!(["data"])
+        );
+    }
+
+    #[test]
+    fn test_server_error() {
+        match ClientError::from(jsonrpc_core::Error::new(ErrorCode::from(
+            error::ERROR_UNAUTHORIZED,
+        ))) {
+            ClientError::Server { kind, code, .. } => {
+                assert_eq!(kind, ServerErrorKind::Unauthorized);
+                assert_eq!(code, error::ERROR_UNAUTHORIZED);
+            }
+            _ => panic!("expected a server error"),
+        }
+
+        assert_eq!(
+            ServerErrorKind::from_code(ErrorCode::MethodNotFound.code()),
+            ServerErrorKind::Protocol
+        );
+        assert_eq!(
+            ServerErrorKind::from_code(error::ERROR_UNABLE_TO_OBTAIN_LOCK),
+            ServerErrorKind::Busy
+        );
+        assert_eq!(ServerErrorKind::from_code(9999), ServerErrorKind::Unknown);
+
+        // The candidates for an ambiguous short id make it into the message
+        let mut ambiguous =
+            jsonrpc_core::Error::new(ErrorCode::from(error::ERROR_AMBIGUOUS_SHORT_ID));
+        ambiguous.data = Some(serde_json::json!(["FVPfbg9bA", "FVPfbg9bB"]));
+        match ClientError::from(ambiguous) {
+            ClientError::Server { kind, message, .. } => {
+                assert_eq!(kind, ServerErrorKind::Rejected);
+                assert!(message.ends_with("(FVPfbg9bA, FVPfbg9bB)"));
+            }
+            _ => panic!("expected a server error"),
+        }
+    }
+
+    #[test]
+    fn test_batch_sort() {
+        let rpc = RpcClient::new("http://localhost:8080");
+
+        let mut batch = rpc.batch::<String>();
+        batch.queue("get_note", ("a", "data")).unwrap();
+        batch.queue("get_note", ("b", "data")).unwrap();
+        batch.queue("get_note", ("c", "data")).unwrap();
+        assert_eq!(batch.len(), 3);
+
+        let ids: Vec<u64> = batch.calls.iter().map(|call| call.id).collect();
+
+        // Answer the calls out of order, failing one and leaving another out
+        let response: Response = serde_json::from_value(serde_json::json!([
+            {"jsonrpc": "2.0", "error": {"code": error::ERROR_NO_SUCH_NOTE, "message": ""}, "id": ids[2]},
+            {"jsonrpc": "2.0", "result": "first", "id": ids[0]}
+        ]))
+        .unwrap();
+
+        let results = batch.sort(response).unwrap();
+        assert_eq!(results[0].as_ref().unwrap(), "first");
+        match &results[1] {
+            Err(ClientError::Unanswered { method }) => assert_eq!(method, "get_note"),
+            _ => panic!("expected the second call to go unanswered"),
+        }
+        match &results[2] {
+            Err(ClientError::Server { kind, .. }) => assert_eq!(*kind, ServerErrorKind::NotFound),
+            _ => panic!("expected the third call to fail"),
+        }
+
+        // A batch refused as a whole results in a single error
+        let mut refused = rpc.batch::<String>();
+        refused.queue("list_notes", ("data",)).unwrap();
+        assert!(refused
+            .sort(
+                serde_json::from_value(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "error": {"code": error::ERROR_BATCH_TOO_LARGE, "message": ""},
+                    "id": null
+                }))
+                .unwrap()
+            )
+            .is_err());
+    }
+}
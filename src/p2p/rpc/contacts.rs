@@ -0,0 +1,218 @@
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+
+use super::{
+    super::super::{
+        accounts::contacts::{AddressBook, Contact, ContactError},
+        common::address::Address,
+    },
+    auth::RpcHandler,
+    client::{ClientError, RpcClient},
+    error,
+    failover::RetryPolicy,
+};
+
+use std::collections::BTreeMap;
+
+/// Defines the standard SummerCash address book RPC API. Contacts are kept locally, in the given data directory, and
+/// are never published to the network.
+#[rpc]
+pub trait Contacts {
+    /// Adds a named contact with the given address, replacing any existing contact with that name.
+    #[rpc(name = "add_contact")]
+    fn add(&self, name: String, address: String, memo: String, data_dir: String)
+        -> Result<Contact>;
+
+    /// Gets the contact with the given name.
+    #[rpc(name = "get_contact")]
+    fn get(&self, name: String, data_dir: String) -> Result<Contact>;
+
+    /// Removes the contact with the given name.
+    #[rpc(name = "delete_contact")]
+    fn delete(&self, name: String, data_dir: String) -> Result<()>;
+
+    /// Gets each of the contacts in the given data directory, sorted by name.
+    #[rpc(name = "list_contacts")]
+    fn list(&self, data_dir: String) -> Result<BTreeMap<String, Contact>>;
+
+    /// Gets the address of the contact with the given name, or, if no contact has the name, decodes it as an address.
+    #[rpc(name = "resolve_contact")]
+    fn resolve(&self, name_or_address: String, data_dir: String) -> Result<Address>;
+}
+
+/// An implementation of the address book API.
+pub struct ContactsImpl;
+
+impl Contacts for ContactsImpl {
+    /// Adds a named contact with the given address, replacing any existing contact with that name.
+    fn add(
+        &self,
+        name: String,
+        address: String,
+        memo: String,
+        data_dir: String,
+    ) -> Result<Contact> {
+        let mut book = Self::read_book(&data_dir)?; // Open the address book
+
+        let contact = book.add(&name, &address, memo).map_err(|e| {
+            debug!("Refusing to add contact: {}", e);
+
+            Error::new(ErrorCode::from(error::ERROR_INVALID_CONTACT))
+        })?;
+
+        // Persist the address book
+        match book.write_to_disk_at_data_directory(&data_dir) {
+            Ok(_) => Ok(contact),
+            Err(_) => Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_WRITE_CONTACTS,
+            ))),
+        }
+    }
+
+    /// Gets the contact with the given name.
+    fn get(&self, name: String, data_dir: String) -> Result<Contact> {
+        Self::read_book(&data_dir)?
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| Error::new(ErrorCode::from(error::ERROR_NO_SUCH_CONTACT)))
+    }
+
+    /// Removes the contact with the given name.
+    fn delete(&self, name: String, data_dir: String) -> Result<()> {
+        let mut book = Self::read_book(&data_dir)?; // Open the address book
+
+        // Make sure there's actually something to remove
+        if book.remove(&name).is_none() {
+            return Err(Error::new(ErrorCode::from(error::ERROR_NO_SUCH_CONTACT)));
+        }
+
+        // Persist the address book
+        book.write_to_disk_at_data_directory(&data_dir)
+            .map_err(|_| Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_WRITE_CONTACTS)))
+    }
+
+    /// Gets each of the contacts in the given data directory.
+    fn list(&self, data_dir: String) -> Result<BTreeMap<String, Contact>> {
+        Ok(Self::read_book(&data_dir)?.contacts)
+    }
+
+    /// Gets the address of the contact with the given name, or decodes the name as an address.
+    fn resolve(&self, name_or_address: String, data_dir: String) -> Result<Address> {
+        Self::read_book(&data_dir)?
+            .resolve(&name_or_address)
+            .map_err(|e| {
+                Error::new(ErrorCode::from(match e {
+                    ContactError::NoSuchContact { .. } => error::ERROR_NO_SUCH_CONTACT,
+                    _ => error::ERROR_INVALID_CONTACT,
+                }))
+            })
+    }
+}
+
+impl ContactsImpl {
+    /// Registers the address book service on the given RPC handler.
+    pub fn register(io: &mut RpcHandler) {
+        // Register this service on the IO handler
+        io.extend_with(Self.to_delegate());
+    }
+
+    /// Opens the address book in the given data directory.
+    fn read_book(data_dir: &str) -> Result<AddressBook> {
+        AddressBook::read_from_disk_at_data_directory(data_dir)
+            .map_err(|_| Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_READ_CONTACTS)))
+    }
+}
+
+/// A client for the address book API.
+pub struct Client {
+    /// The underlying JSON-RPC client
+    rpc: RpcClient,
+}
+
+impl From<RpcClient> for Client {
+    /// Builds a client on top of the given JSON-RPC client, sharing its connections.
+    fn from(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+}
+
+impl Client {
+    /// Initializes a new Client with the given remote URL, or comma-separated list of URLs for redundant nodes.
+    pub fn new(server_addr: &str) -> Self {
+        Self {
+            rpc: RpcClient::new(server_addr),
+        }
+    }
+
+    /// Makes each of the client's requests under the admin session, or with the API key, given.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The token of the admin session, or the API key, if there is one
+    pub fn with_session(mut self, session: Option<String>) -> Self {
+        self.rpc = self.rpc.with_session(session);
+
+        self
+    }
+
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.rpc = self.rpc.with_retry_policy(policy);
+
+        self
+    }
+
+    /// Adds a named contact with the given address.
+    pub async fn add(
+        &self,
+        name: String,
+        address: String,
+        memo: String,
+        data_dir: &str,
+    ) -> std::result::Result<Contact, ClientError> {
+        self.rpc
+            .call("add_contact", (&name, &address, &memo, data_dir))
+            .await
+    }
+
+    /// Gets the contact with the given name.
+    pub async fn get(
+        &self,
+        name: String,
+        data_dir: &str,
+    ) -> std::result::Result<Contact, ClientError> {
+        self.rpc.call("get_contact", (&name, data_dir)).await
+    }
+
+    /// Removes the contact with the given name.
+    pub async fn delete(
+        &self,
+        name: String,
+        data_dir: &str,
+    ) -> std::result::Result<(), ClientError> {
+        self.rpc.call("delete_contact", (&name, data_dir)).await
+    }
+
+    /// Gets each of the contacts in the given data directory, sorted by name.
+    pub async fn list(
+        &self,
+        data_dir: &str,
+    ) -> std::result::Result<BTreeMap<String, Contact>, ClientError> {
+        self.rpc.call("list_contacts", (data_dir,)).await
+    }
+
+    /// Gets the address of the contact with the given name, or, if no contact has the name, decodes it as an address.
+    pub async fn resolve(
+        &self,
+        name_or_address: &str,
+        data_dir: &str,
+    ) -> std::result::Result<Address, ClientError> {
+        self.rpc
+            .call("resolve_contact", (name_or_address, data_dir))
+            .await
+    }
+}
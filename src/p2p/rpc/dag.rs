@@ -1,37 +1,56 @@
-use jsonrpc_core::{response::Output, Error, ErrorCode, IoHandler, Result};
+use jsonrpc_core::{Error, ErrorCode, Result};
 use jsonrpc_derive::rpc;
 
 use walkdir::WalkDir;
 
-use serde::Deserialize;
-
 use super::{
     super::super::{
-        accounts::account::Account,
-        common::address::Address,
+        accounts::{
+            account::{self, Account},
+            policy::{PolicyError, SpendingLog, SpendingPolicy},
+            unlock::UnlockError,
+        },
+        common::{
+            address::{self, Address},
+            lock::{InstrumentedRwLock, InstrumentedWriteGuard},
+        },
         core::{
             sys::{
-                proposal::{Operation, Proposal, ProposalData},
+                diagnosis::{AccountDiagnosis, DEFAULT_STUCK_AFTER_SECS},
+                exports::{ExportError, ExportPage, ExportSession},
                 system::System,
             },
             types::{
-                graph::Node,
+                batch::{self, BatchTransfer, Transfer},
+                gas,
+                graph::{Node, StateOrigin, DEFAULT_TIPS_PER_TRANSACTION},
+                light::{LightClientError, LightHeader, ProvenBalance},
+                query::{self, QueryError, TransactionQuery},
+                receipt::Receipt,
+                replay::{
+                    self, ReplayError, ReplayPage, ReplayStart, StateProof, VerificationReport,
+                },
                 signature::Signature,
                 state::Entry,
+                trace::{ExecutionTrace, TraceLevel},
                 transaction::{self, Transaction},
             },
         },
-        crypto::hash::Hash,
+        crypto::{hash::Hash, short_id::ShortIdError},
+        validator::{GraphBoundValidator, ValidationReport},
     },
+    accounts::unlock_error,
+    auth::RpcHandler,
+    client::{ClientError, RpcClient},
     error,
+    failover::RetryPolicy,
 };
 
-use num::BigUint;
+use chrono::Utc;
 
-use std::{
-    collections::HashMap,
-    sync::{Arc, RwLock, RwLockWriteGuard},
-};
+use num::{BigUint, Zero};
+
+use std::{collections::HashSet, sync::Arc};
 
 /// Defines the standard SummerCash DAG RPC API.
 #[rpc]
@@ -40,11 +59,14 @@ pub trait Dag {
     #[rpc(name = "get_dag")]
     fn get(&self) -> Result<Vec<Node>>;
 
-    /// Gets a list of transaction hashes stored in the currently attached DAG.
+    /// Gets a list of transaction hashes stored in the currently attached DAG. If a query is provided, only the hashes
+    /// of the transactions meeting its conditions are returned, sorted and paginated as it asks.
     #[rpc(name = "list_transactions")]
-    fn list(&self) -> Result<Vec<Hash>>;
+    fn list(&self, query: Option<TransactionQuery>) -> Result<Vec<Hash>>;
 
-    /// Creates a new transaction with the provided sender, recipient, value, and payload.
+    /// Creates a new transaction with the provided sender, recipient, value, and payload. If a gas price (in finks,
+    /// in decimal) is provided, the transaction pays for the gas it uses, up to the provided gas limit; the limit
+    /// defaults to exactly the gas that the transaction uses.
     #[rpc(name = "create_transaction")]
     fn create_tx(
         &self,
@@ -52,11 +74,37 @@ pub trait Dag {
         recipient: String,
         value: String,
         payload: String,
+        gas_price: Option<String>,
+        gas_limit: Option<u64>,
     ) -> Result<Transaction>;
 
-    /// Signs the transaction with the provided hash.
+    /// Creates a single transaction paying each of the provided recipients, debiting the sender once for the sum of
+    /// the payments. Each transfer is a recipient address, and a number of finks in decimal.
+    #[rpc(name = "create_batch_transaction")]
+    fn create_batch_tx(
+        &self,
+        sender: String,
+        transfers: Vec<(String, String)>,
+    ) -> Result<Transaction>;
+
+    /// Creates a set of unsigned transactions moving the entire balance of each of the source accounts to the
+    /// destination. Each of the transactions can be signed offline, and published afterwards.
+    #[rpc(name = "sweep")]
+    fn sweep(&self, sources: Vec<String>, destination: String) -> Result<Vec<Transaction>>;
+
+    /// Signs the transaction with the provided hash, as long as the signing account's spending policy allows it.
+    /// Transactions above the account's confirmation threshold must be signed again with the confirmation code
+    /// written to the node's log, or to the remote signer's log if the node delegates its signing. If the handle of an
+    /// unlock session is given, the transaction is signed by the account held by the session, rather than by one whose
+    /// keystore is unlocked on the disk.
     #[rpc(name = "sign_transaction")]
-    fn sign_tx(&self, hash: String, data_dir: String) -> Result<Signature>;
+    fn sign_tx(
+        &self,
+        hash: String,
+        data_dir: String,
+        confirmation: Option<String>,
+        session: Option<String>,
+    ) -> Result<Signature>;
 
     /// Gets a list of transactions contained in the transaction cache.
     #[rpc(name = "get_mem_transactions")]
@@ -65,11 +113,133 @@ pub trait Dag {
     /// Signs a transaction with the provided hash in the provided data directory.
     #[rpc(name = "publish_transaction")]
     fn publish_tx(&self, hash: String, data_dir: String) -> Result<()>;
+
+    /// Builds an unsigned copy of the transaction with the provided hash in the provided data directory on top of the
+    /// DAG's current head, with fresh parents, spending the same nonce. The copy can replace the original once signed,
+    /// if the original hasn't been executed in time.
+    #[rpc(name = "rebuild_transaction")]
+    fn rebuild_tx(&self, hash: String, data_dir: String) -> Result<Transaction>;
+
+    /// Gossips the signed transaction with the provided hash in the provided data directory to the network again,
+    /// since it hasn't been executed in time. A rebuilt transaction takes the place of the one that it replaces.
+    #[rpc(name = "resubmit_transaction")]
+    fn resubmit_tx(&self, hash: String, data_dir: String, replaces: Option<String>) -> Result<()>;
+
+    /// Runs each of the validator's checks against the provided transaction, and reports the result of every check.
+    #[rpc(name = "validate_transaction")]
+    fn validate_tx(&self, tx: Transaction) -> Result<ValidationReport>;
+
+    /// Gets the receipt recorded for the transaction with the provided hash.
+    #[rpc(name = "get_receipt")]
+    fn get_receipt(&self, hash: String) -> Result<Receipt>;
+
+    /// Gets each of the transactions known to have produced, or cited as their parent state, the state entry with
+    /// the provided hash.
+    #[rpc(name = "get_state_origin")]
+    fn get_state_origin(&self, state_hash: String) -> Result<StateOrigin>;
+
+    /// Gets a page of the transactions in the DAG, in ledger order, along with their receipts and the balances they
+    /// left behind. The replay begins at the genesis, unless a cursor, transaction hash, or timestamp is provided.
+    #[rpc(name = "replay_transactions")]
+    fn replay(&self, start: Option<ReplayStart>, limit: Option<usize>) -> Result<ReplayPage>;
+
+    /// Opens a long export of the DAG, beginning at the genesis unless a cursor, transaction hash, or timestamp is
+    /// provided. The node keeps track of the export's progress, so that a consumer cut off partway through can pick it
+    /// back up where it left off.
+    #[rpc(name = "open_export")]
+    fn open_export(&self, start: Option<ReplayStart>) -> Result<ExportSession>;
+
+    /// Gets the next page of the export with the provided id. Passing the cursor handed out with the previous page
+    /// acknowledges it; without one, the page that wasn't acknowledged is handed out again.
+    #[rpc(name = "next_export_page")]
+    fn next_export_page(
+        &self,
+        id: String,
+        ack: Option<String>,
+        limit: Option<usize>,
+    ) -> Result<ExportPage>;
+
+    /// Gets how far along the export with the provided id is, including the number of transactions acknowledged.
+    #[rpc(name = "get_export")]
+    fn get_export(&self, id: String) -> Result<ExportSession>;
+
+    /// Closes the export with the provided id.
+    #[rpc(name = "close_export")]
+    fn close_export(&self, id: String) -> Result<()>;
+
+    /// Re-executes the entire DAG from its genesis, without touching any of it, and reports the first transaction
+    /// citing a parent state that couldn't be reproduced, along with how the recomputed state differs from the stored
+    /// one.
+    #[rpc(name = "verify_ledger")]
+    fn verify(&self) -> Result<VerificationReport>;
+
+    /// Recomputes the state resulting from the node with the provided hash by re-executing its ancestors from the
+    /// genesis, and compares it against the state hash recorded for the node. Nodes whose states have been purged
+    /// from memory can still be checked, since the hash of each purged state is kept.
+    #[rpc(name = "verify_state_at")]
+    fn verify_state_at(&self, node_hash: String) -> Result<StateProof>;
+
+    /// Gets the state of the ledger as it stood after the execution of the node with the provided hash. Only archive
+    /// nodes keep the state of every node.
+    #[rpc(name = "get_state_at")]
+    fn get_state_at(&self, node_hash: String) -> Result<Entry>;
+
+    /// Gets the balance of the account with the provided address as it stood after the execution of the node with
+    /// the provided hash. Only archive nodes keep the state of every node.
+    #[rpc(name = "get_balance_at")]
+    fn get_balance_at(&self, address: Address, node_hash: String) -> Result<BigUint>;
+
+    /// Re-executes the transaction in the DAG with the provided hash on top of its parent state, without touching the
+    /// DAG, and records each step taken along the way. Steps are recorded at the provided level of detail (minimal,
+    /// steps, or full), or at the steps level if none is provided.
+    #[rpc(name = "debug_trace_transaction")]
+    fn trace(&self, hash: String, level: Option<TraceLevel>) -> Result<ExecutionTrace>;
+
+    /// Resolves the provided short id (the first few characters of a transaction's hash) to the full hash of the one
+    /// transaction in the DAG that it begins. If it begins more than one, the candidates are listed in the error.
+    #[rpc(name = "resolve_hash")]
+    fn resolve_hash(&self, id: String) -> Result<Hash>;
+
+    /// Inspects the DAG & mempool for the provided account's transactions that haven't been executed, reporting nonce
+    /// gaps, transactions spending the account's next nonce that have waited longer than the provided number of
+    /// seconds (ten minutes, if none is provided), and transactions that can never be executed, along with what can
+    /// be done about each.
+    #[rpc(name = "diagnose_account")]
+    fn diagnose_account(
+        &self,
+        address: String,
+        stuck_after_secs: Option<u64>,
+    ) -> Result<AccountDiagnosis>;
+
+    /// Gets a page of the headers that light clients follow, starting at the provided offset, and listing up to the
+    /// provided number of headers (100, if none is provided). Each header commits to the state resulting from its
+    /// transaction, once the transaction has been executed.
+    #[rpc(name = "get_light_headers")]
+    fn get_light_headers(&self, offset: usize, limit: Option<usize>) -> Result<Vec<LightHeader>>;
+
+    /// Gets the header of the executed head, which light clients check balances against, if anything has been
+    /// executed yet.
+    #[rpc(name = "get_light_head")]
+    fn get_light_head(&self) -> Result<Option<LightHeader>>;
+
+    /// Proves the balance of the account with the provided address against the header of the node with the provided
+    /// hash (or short id), or against the header of the executed head if no hash is provided.
+    #[rpc(name = "get_balance_proof")]
+    fn get_balance_proof(
+        &self,
+        address: String,
+        node_hash: Option<String>,
+    ) -> Result<ProvenBalance>;
+
+    /// Checks a balance proven by another node against this node's own copy of the header that it was proven
+    /// against, resulting in the proven balance.
+    #[rpc(name = "verify_balance_proof")]
+    fn verify_balance_proof(&self, proven: ProvenBalance) -> Result<BigUint>;
 }
 
 /// An implementation of the DAG API.
 pub struct DagImpl {
-    pub(crate) runtime: Arc<RwLock<System>>,
+    pub(crate) runtime: Arc<InstrumentedRwLock<System>>,
 }
 
 impl Dag for DagImpl {
@@ -104,11 +274,24 @@ impl Dag for DagImpl {
         }
     }
 
-    /// Gets a list of transaction hashes stored in the currently attached DAG.
-    fn list(&self) -> Result<Vec<Hash>> {
+    /// Gets a list of transaction hashes stored in the currently attached DAG, meeting the conditions of the query.
+    fn list(&self, query: Option<TransactionQuery>) -> Result<Vec<Hash>> {
         if let Ok(rt) = self.runtime.read() {
-            // Return all of the keys, which are the node hashes, stored in the DAG
-            Ok(rt.ledger.hash_routes.keys().copied().collect())
+            let query = if let Some(query) = query {
+                query
+            } else {
+                // Return all of the keys, which are the node hashes, stored in the DAG
+                return Ok(rt.ledger.hash_routes.keys().copied().collect());
+            };
+
+            query::run(&rt.ledger, &query).map_err(|e| {
+                debug!("Failed to query the DAG: {}", e);
+
+                Error::new(ErrorCode::from(match e {
+                    QueryError::EmptyRange { .. } => error::ERROR_INVALID_QUERY,
+                    QueryError::StorageFailure { .. } => error::ERROR_UNABLE_TO_READ_DAG,
+                }))
+            })
         } else {
             debug!("Unable to obtain a lock on the client's runtime");
 
@@ -126,10 +309,20 @@ impl Dag for DagImpl {
         recipient: String,
         value: String,
         payload: String,
+        gas_price: Option<String>,
+        gas_limit: Option<u64>,
     ) -> Result<Transaction> {
+        let gas_price = match gas_price.map(|price| price.parse::<BigUint>()) {
+            Some(Ok(price)) => price,
+            Some(Err(_)) => {
+                return Err(Error::new(ErrorCode::from(error::ERROR_INVALID_GAS_PRICE)))
+            }
+            None => BigUint::zero(),
+        };
+
         // Convert the provided sender and recipient values to addresses
-        let sender_address = Address::from(sender);
-        let recipient_address = Address::from(recipient);
+        let sender_address = Self::parse_address(&self.runtime, &sender)?;
+        let recipient_address = Self::parse_address(&self.runtime, &recipient)?;
 
         // Get a lock on the client's runtime
         let runtime = if let Ok(rt) = self.runtime.read() {
@@ -143,95 +336,174 @@ impl Dag for DagImpl {
             )));
         };
 
-        // Get a head from the DAG. This is necessary, as we need to determine what nonce we can use for the tx.
-        let (head, head_entry): (Node, Entry) =
-            if let Some(mut h) = runtime.ledger.obtain_executed_head() {
-                // Load the entry's state data
-                if let Some(state_entry) = h.state_entry.take() {
-                    (h, state_entry)
-                } else {
-                    debug!("Best graph node doesn't contain a state entry; terminating");
+        // Make sure that the DAG has a head for the transaction to be built on
+        Self::head_entry(&runtime)?;
 
-                    // Return a state ref error
-                    return Err(Error::new(ErrorCode::from(
-                        error::ERROR_UNABLE_TO_OBTAIN_STATE_REF,
-                    )));
-                }
-            } else {
-                debug!("Unable to obtain a lock on the client's runtime");
+        // The index of the transaction in the set of user transactions, following any still waiting in the mempool
+        let nonce = runtime.next_nonce(&sender_address);
 
-                // Return a state ref error
-                return Err(Error::new(ErrorCode::from(
-                    error::ERROR_UNABLE_TO_OBTAIN_STATE_REF,
-                )));
-            };
+        // Create a new transaction using the last defined nonce in the global state
+        let tx = Self::build_tx(
+            &runtime,
+            nonce,
+            sender_address,
+            recipient_address,
+            BigUint::from_bytes_be(&value.into_bytes()),
+            payload.as_bytes(),
+        )?;
 
-        // Get a list of children associated with the last cleared node.
-        let head_children_opt = runtime.ledger.node_children.get(&head.hash);
-
-        // The parents of the transaction we're about to generate
-        let mut parent_hashes: Vec<Hash> = Vec::new();
-
-        // Only collect the head children if they actually exist
-        if let Some(head_children) = head_children_opt {
-            // We're going to try to resolve each of the children associated with the last cleared transaction
-            for child in head_children {
-                // Only use the child as a parent of the new transaction if it unresolved.
-                if runtime.ledger.hash_routes.contains_key(child)
-                    && runtime.ledger.nodes[*runtime.ledger.hash_routes.get(child).unwrap()]
-                        .state_entry
-                        .is_none()
-                {
-                    // Add the child as a parent of the new transaction
-                    parent_hashes.push(*child);
-                }
-            }
+        // Free transactions don't need a gas limit
+        if gas_price.is_zero() {
+            return Ok(tx);
         }
 
-        // The index of the transaction in the set of user transactions
-        let mut nonce = 0;
+        let gas_limit = gas_limit.unwrap_or_else(|| gas::required(&tx.transaction_data));
+
+        Ok(tx.with_gas(gas_limit, gas_price))
+    }
+
+    /// Creates a single transaction paying each of the provided recipients.
+    fn create_batch_tx(
+        &self,
+        sender: String,
+        transfers: Vec<(String, String)>,
+    ) -> Result<Transaction> {
+        let sender_address = Self::parse_address(&self.runtime, &sender)?;
+
+        // Decode each of the transfers
+        let mut payments: Vec<Transfer> = Vec::with_capacity(transfers.len());
+        for (recipient, value) in transfers {
+            let value = if let Ok(value) = value.parse::<BigUint>() {
+                value
+            } else {
+                return Err(Error::new(ErrorCode::from(error::ERROR_INVALID_BATCH)));
+            };
 
-        if let Some(last_nonce) = head_entry.data.nonces.get(&sender_address.to_str()) {
-            nonce = last_nonce + 1;
+            payments.push(Transfer {
+                recipient: Self::parse_address(&self.runtime, &recipient)?,
+                value,
+            });
         }
+        let batch = BatchTransfer::new(payments);
 
-        // Create a new transaction using the last defined nonce in the global state
-        let mut transaction = Transaction::new(
+        // Get a lock on the client's runtime
+        let runtime = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return a mutex error
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        // Use the nonce directly following the sender's last transaction, including those still in the mempool
+        Self::head_entry(&runtime)?;
+        let nonce = runtime.next_nonce(&sender_address);
+
+        // The batch's total is sent to the batch address, and paid out from there
+        let tx = Self::build_tx(
+            &runtime,
             nonce,
             sender_address,
-            recipient_address,
-            BigUint::from_bytes_be(&value.into_bytes()),
-            payload.as_bytes(),
-            parent_hashes,
-        );
+            batch::batch_address(),
+            batch.total(),
+            &batch.to_payload(),
+        )?;
 
-        // Calculate a merged state entry for each of the parents of the transaction. We can use this to provide a proof of correctness for this tx.
-        let (merged_state_entry, parent_entries) = if let Ok(res) = runtime
-            .ledger
-            .resolve_parent_nodes(transaction.transaction_data.parents.clone())
-        {
-            res
+        // Don't hand out a batch that validators would reject
+        if let Err(e) = batch::check(&tx.transaction_data) {
+            debug!("Refusing to build batch transfer: {}", e);
+
+            return Err(Error::new(ErrorCode::from(error::ERROR_INVALID_BATCH)));
+        }
+
+        Ok(tx)
+    }
+
+    /// Creates a set of transactions moving the entire balance of each of the source accounts to the destination.
+    fn sweep(&self, sources: Vec<String>, destination: String) -> Result<Vec<Transaction>> {
+        let destination_address = Self::parse_address(&self.runtime, &destination)?;
+
+        // Decode each of the sources before the runtime is locked
+        let sources = sources
+            .iter()
+            .map(|source| Self::parse_address(&self.runtime, source))
+            .collect::<Result<Vec<Address>>>()?;
+
+        // Get a lock on the client's runtime
+        let runtime = if let Ok(rt) = self.runtime.read() {
+            rt
         } else {
-            debug!(
-                "Failed to merge the parent entries required to produce transaction {}",
-                transaction.hash
-            );
+            debug!("Unable to obtain a lock on the client's runtime");
 
-            // Return a state error
+            // Return a mutex error
             return Err(Error::new(ErrorCode::from(
-                error::ERROR_UNABLE_TO_OBTAIN_STATE_REF,
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
             )));
         };
 
-        // Register the parent states
-        transaction.register_parental_state(merged_state_entry, parent_entries);
+        // Get the head of the DAG, so that we know the balance and next nonce of each source
+        let head_entry = Self::head_entry(&runtime)?;
 
-        // Return the transaction
-        Ok(transaction)
+        // The accounts that have already been swept
+        let mut swept: HashSet<Address> = HashSet::new();
+
+        // The finalized set of sweeping transactions
+        let mut transactions: Vec<Transaction> = Vec::new();
+
+        for source_address in sources {
+            // Skip duplicate sources, and don't sweep the destination into itself
+            if source_address == destination_address || !swept.insert(source_address) {
+                continue;
+            }
+
+            // Get the full spendable balance of the source. Sweeps don't set a gas price, so they're
+            // free, and nothing needs to be left behind to pay for them.
+            let balance = head_entry
+                .data
+                .balances
+                .get(&source_address.to_str())
+                .cloned()
+                .unwrap_or_default();
+
+            // Don't bother making a transaction for an empty account
+            if balance == BigUint::default() {
+                debug!("Skipping empty sweep source {}", source_address);
+
+                continue;
+            }
+
+            // Use the nonce directly following the source's last transaction
+            let nonce = head_entry
+                .data
+                .nonces
+                .get(&source_address.to_str())
+                .map_or(0, |last_nonce| last_nonce + 1);
+
+            transactions.push(Self::build_tx(
+                &runtime,
+                nonce,
+                source_address,
+                destination_address,
+                balance,
+                b"",
+            )?);
+        }
+
+        // Return the unsigned transactions
+        Ok(transactions)
     }
 
     /// Signs the transaction with the provided hash.
-    fn sign_tx(&self, hash: String, data_dir: String) -> Result<Signature> {
+    fn sign_tx(
+        &self,
+        hash: String,
+        data_dir: String,
+        confirmation: Option<String>,
+        session: Option<String>,
+    ) -> Result<Signature> {
         // Read the transaction from the disk
         let mut tx: Transaction =
             if let Ok(tx) = Transaction::from_disk_at_data_directory(&data_dir, Hash::from(hash)) {
@@ -243,16 +515,81 @@ impl Dag for DagImpl {
                 )));
             };
 
-        // Read the account from the disk
-        let acc = if let Ok(a) =
-            Account::read_from_disk_at_data_directory(tx.transaction_data.sender, &data_dir)
-        {
-            a
+        // If the node delegates its signing, the remote signer holds the keys, and applies its own spending policy
+        let remote_signer = self
+            .runtime
+            .read()
+            .ok()
+            .and_then(|rt| rt.remote_signer.clone());
+        if let Some(remote_signer) = remote_signer {
+            let signature = remote_signer
+                .sign_transaction(&tx, confirmation)
+                .map_err(|e| {
+                    warn!(
+                        "The remote signer failed to sign transaction {}: {}",
+                        tx.hash, e
+                    );
+
+                    Error::new(ErrorCode::from(error::ERROR_REMOTE_SIGNER_FAILED))
+                })?;
+            tx.signature = Some(signature.clone());
+
+            // Persist the tx to the disk, now that it's been signed
+            return match tx.to_disk_at_data_directory(&data_dir) {
+                Ok(_) => Ok(signature),
+                Err(_) => Err(Error::new(ErrorCode::from(
+                    error::ERROR_UNABLE_TO_WRITE_TRANSACTION,
+                ))),
+            };
+        }
+
+        let (signer, acc) = if let Some(handle) = session {
+            let mut rt = self
+                .runtime
+                .write()
+                .map_err(|_| Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_OBTAIN_LOCK)))?;
+
+            // The session's account can sign for itself, and for any of the deposit sub-addresses it derived
+            let signer = rt.unlock_sessions.address(&handle).map_err(unlock_error)?;
+            if tx.transaction_data.sender != signer
+                && tx
+                    .transaction_data
+                    .sender
+                    .is_sub_address_of(&signer)
+                    .is_none()
+            {
+                return Err(unlock_error(UnlockError::WrongAccount {
+                    address: tx.transaction_data.sender.to_str(),
+                }));
+            }
+
+            let acc = rt
+                .unlock_sessions
+                .account(&handle, &signer, &data_dir)
+                .map_err(unlock_error)?;
+
+            (signer, acc)
         } else {
-            // Return an error
-            return Err(Error::new(ErrorCode::from(
-                error::ERROR_UNABLE_TO_OPEN_ACCOUNT,
-            )));
+            // If the sender is a deposit sub-address, the tx needs to be signed by the account that derived it
+            let signer = account::get_all_unlocked_accounts_in_data_directory(&data_dir)
+                .into_iter()
+                .find(|parent| {
+                    tx.transaction_data
+                        .sender
+                        .is_sub_address_of(parent)
+                        .is_some()
+                })
+                .unwrap_or(tx.transaction_data.sender);
+
+            // Read the account from the disk
+            match Account::read_from_disk_at_data_directory(signer, &data_dir) {
+                Ok(acc) => (signer, acc),
+                Err(_) => {
+                    return Err(Error::new(ErrorCode::from(
+                        error::ERROR_UNABLE_TO_OPEN_ACCOUNT,
+                    )))
+                }
+            }
         };
 
         // Try to get a keypair for the account that we've opened
@@ -265,6 +602,9 @@ impl Dag for DagImpl {
             )));
         };
 
+        // Make sure that the account's spending policy allows the transaction
+        Self::check_spending_policy(&data_dir, &signer, &tx, confirmation.as_deref())?;
+
         // Sign the transaction, and return it
         match transaction::sign_transaction(keypair, &mut tx) {
             Ok(_) => Ok(if let Some(sig) = tx.signature.clone() {
@@ -325,7 +665,7 @@ impl Dag for DagImpl {
 
     /// Signs the transaction with the provided hash in the given data directory.
     fn publish_tx(&self, hash: String, data_dir: String) -> Result<()> {
-        // Open the transaction so that we can use it to publish a proposal derived from it on the network
+        // Open the transaction so that we can publish it on the network
         let tx: Transaction =
             if let Ok(t) = Transaction::from_disk_at_data_directory(&data_dir, Hash::from(hash)) {
                 t
@@ -336,19 +676,15 @@ impl Dag for DagImpl {
                 )));
             };
 
-        // Make a proposal to submit the provided transaction
-        let proposal_data = ProposalData::new(
-            "ledger::transactions".to_owned(),
-            Operation::Append {
-                value_to_append: tx.to_bytes(),
-            },
-        );
-
-        // Make a proposal for the transaction
-        let proposal = Proposal::new(format!("new_tx({})", tx.hash.to_str()), proposal_data);
+        // Peers drop unsigned transactions, so there's no point in sending one
+        if !tx.verify_signature() {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_SIGNATURE_UNDEFINED,
+            )));
+        }
 
         // Try to get a lock on the server's runtime
-        let mut rt: RwLockWriteGuard<System> = if let Ok(rt) = self.runtime.write() {
+        let mut rt: InstrumentedWriteGuard<System> = if let Ok(rt) = self.runtime.write() {
             rt
         } else {
             return Err(Error::new(ErrorCode::from(
@@ -356,151 +692,1097 @@ impl Dag for DagImpl {
             )));
         };
 
-        // Register the proposal
-        rt.register_proposal(proposal);
+        // Gossip the signed transaction itself once the mempool lets it go; each node will wrap it in a proposal of its
+        // own
+        rt.register_local_transaction(tx).map_err(|e| {
+            let mut err = Error::new(ErrorCode::from(error::ERROR_MEMPOOL_REJECTED));
+            err.data = Some(serde_json::json!([e.to_string()]));
 
-        Ok(())
+            err
+        })
     }
-}
 
-impl DagImpl {
-    /// Registers the DAG service on the given IoHandler server.
-    pub fn register(io: &mut IoHandler, runtime: Arc<RwLock<System>>) {
-        // Register this service on the IO handler
-        io.extend_with(Self { runtime }.to_delegate());
-    }
-}
+    /// Builds an unsigned copy of the transaction with the provided hash on top of the DAG's current head.
+    fn rebuild_tx(&self, hash: String, data_dir: String) -> Result<Transaction> {
+        let original: Transaction =
+            if let Ok(t) = Transaction::from_disk_at_data_directory(&data_dir, Hash::from(hash)) {
+                t
+            } else {
+                return Err(Error::new(ErrorCode::from(
+                    error::ERROR_UNABLE_TO_OPEN_TRANSACTION,
+                )));
+            };
 
-/// A client for the SummerCash DAG API.
-pub struct Client {
-    /// The address for the server hosting the APi
-    pub server: String,
+        // Get a lock on the client's runtime
+        let runtime = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
 
-    /// An HTTP client
-    client: reqwest::Client,
-}
+            // Return a mutex error
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
 
-impl Client {
-    /// Initializes a new Client with the given remote URL.
-    pub fn new(server_addr: &str) -> Self {
-        // Initialize and return the client
-        Self {
-            server: server_addr.trim_end_matches('/').to_owned(),
-            client: reqwest::Client::new(),
+        // Make sure that the DAG has a head for the transaction to be built on
+        Self::head_entry(&runtime)?;
+
+        // Spend the same nonce, so that only one of the original & its replacement can ever be executed
+        let data = original.transaction_data;
+        let tx = Self::build_tx(
+            &runtime,
+            data.nonce,
+            data.sender,
+            data.recipient,
+            data.value,
+            &data.payload,
+        )?;
+
+        if data.gas_price.is_zero() {
+            return Ok(tx);
         }
+
+        Ok(tx.with_gas(data.gas_limit, data.gas_price))
     }
 
-    /// Performs a request considering the given method, and returns the response.
-    async fn do_request<T>(
-        &self,
-        method: &str,
-        params: &str,
-    ) -> std::result::Result<T, failure::Error>
-    where
-        T: for<'de> Deserialize<'de>,
-    {
-        // Make a hashmap to store the body of the request in
-        let mut json_body: HashMap<&str, serde_json::Value> = HashMap::new();
-        json_body.insert("jsonrpc", serde_json::Value::String("2.0".to_owned()));
-        json_body.insert("method", serde_json::Value::String(method.to_owned()));
-        json_body.insert("id", serde_json::Value::String("".to_owned()));
-        json_body.insert("params", serde_json::from_str(params)?);
-
-        // Send a request to the endpoint, and pass the given parameters along with the request
-        let res = self
-            .client
-            .post(&self.server)
-            .json(&json_body)
-            .send()
-            .await?
-            .json::<Output>()
-            .await?;
-
-        // Some type conversion black magic fuckery
-        match res {
-            Output::Success(s) => match serde_json::from_value(s.result) {
-                Ok(res) => Ok(res),
-                Err(e) => Err(e.into()),
-            },
-            Output::Failure(e) => Err(e.error.into()),
+    /// Gossips the signed transaction with the provided hash to the network again.
+    fn resubmit_tx(&self, hash: String, data_dir: String, replaces: Option<String>) -> Result<()> {
+        let tx: Transaction =
+            if let Ok(t) = Transaction::from_disk_at_data_directory(&data_dir, Hash::from(hash)) {
+                t
+            } else {
+                return Err(Error::new(ErrorCode::from(
+                    error::ERROR_UNABLE_TO_OPEN_TRANSACTION,
+                )));
+            };
+
+        // Peers drop unsigned transactions, so there's no point in sending one
+        if !tx.verify_signature() {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_SIGNATURE_UNDEFINED,
+            )));
         }
-    }
 
-    /// Gets a list of nodes in the working graph.
-    pub async fn get(&self) -> std::result::Result<Vec<Node>, failure::Error> {
-        self.do_request::<Vec<Node>>("get_dag", "[]").await
-    }
+        let mut rt = if let Ok(rt) = self.runtime.write() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
 
-    /// Gets a list of transaction hashes contained in the working DAG.
-    pub async fn list(&self) -> std::result::Result<Vec<Hash>, failure::Error> {
-        self.do_request::<Vec<Hash>>("list_transactions", "[]")
-            .await
+        rt.resubmit_local_transaction(tx, replaces.map(|replaced| Hash::from(replaced.as_str())))
+            .map_err(|e| {
+                let mut err = Error::new(ErrorCode::from(error::ERROR_MEMPOOL_REJECTED));
+                err.data = Some(serde_json::json!([e.to_string()]));
+
+                err
+            })
     }
 
-    /// Creates a new transaction with the provided parameters.
-    pub async fn create_tx(
-        &self,
-        sender: String,
-        recipient: String,
-        amount: u128,
-        payload: String,
-    ) -> std::result::Result<Transaction, failure::Error> {
-        self.do_request::<Transaction>(
-            "create_transaction",
-            &format!(
-                r#"[{}, {}, "{}", {}]"#,
-                serde_json::to_string(&sender)?,
-                serde_json::to_string(&recipient)?,
-                serde_json::to_string(&amount)?,
-                serde_json::to_string(&payload)?
-            ),
-        )
-        .await
-    }
-
-    /// Signs the transaction with the provided account.
-    pub async fn sign_tx(
-        &self,
-        hash: String,
-        data_dir: String,
-    ) -> std::result::Result<Signature, failure::Error> {
-        self.do_request::<Signature>(
-            "sign_transaction",
-            &format!(
-                "[{}, {}]",
-                &serde_json::to_string(&hash)?,
-                &serde_json::to_string(&data_dir)?
-            ),
-        )
-        .await
+    /// Runs each of the validator's checks against the provided transaction, and reports the result of every check.
+    fn validate_tx(&self, tx: Transaction) -> Result<ValidationReport> {
+        if let Ok(rt) = self.runtime.read() {
+            // Explain how the transaction fares against the current state of the DAG
+            Ok(GraphBoundValidator::new(&rt.ledger).explain(&tx))
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
     }
 
-    /// Gets a list of pending transactions stored on the disk.
-    pub async fn get_mem_txs(
-        &self,
-        data_dir: String,
-    ) -> std::result::Result<Vec<Hash>, failure::Error> {
-        self.do_request::<Vec<Hash>>(
-            "get_mem_transactions",
-            &format!("[{}]", &serde_json::to_string(&data_dir)?),
-        )
-        .await
+    /// Gets the receipt recorded for the transaction with the provided hash.
+    fn get_receipt(&self, hash: String) -> Result<Receipt> {
+        // Get a lock on the client's runtime
+        let rt = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        // Look up the receipt in the DAG's database
+        let hash = rt
+            .ledger
+            .resolve_hash(&hash)
+            .map_err(Self::map_short_id_err)?;
+        match rt.ledger.get_receipt(hash) {
+            Ok(Some(receipt)) => Ok(receipt),
+            Ok(None) => Err(Error::new(ErrorCode::from(error::ERROR_NO_SUCH_RECEIPT))),
+            Err(_) => Err(Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_READ_DAG))),
+        }
     }
 
-    /// Publishes a transaction stored on the disk.
-    pub async fn publish_tx(
-        &self,
-        hash: String,
-        data_dir: String,
-    ) -> std::result::Result<(), failure::Error> {
-        self.do_request::<()>(
-            "publish_transaction",
-            &format!(
-                "[{}, {}]",
-                &serde_json::to_string(&hash)?,
-                serde_json::to_string(&data_dir)?
-            ),
-        )
-        .await
+    /// Gets each of the transactions known to have produced, or cited as their parent state, the state entry with
+    /// the provided hash.
+    fn get_state_origin(&self, state_hash: String) -> Result<StateOrigin> {
+        // Get a lock on the client's runtime
+        let rt = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        // Look up the state's origin in the DAG's database
+        match rt.ledger.get_state_origin(Hash::from(state_hash)) {
+            Ok(Some(origin)) => Ok(origin),
+            Ok(None) => Err(Error::new(ErrorCode::from(error::ERROR_NO_SUCH_STATE))),
+            Err(_) => Err(Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_READ_DAG))),
+        }
+    }
+
+    /// Gets a page of the transactions in the DAG, in ledger order, along with their receipts and the balances they
+    /// left behind.
+    fn replay(&self, start: Option<ReplayStart>, limit: Option<usize>) -> Result<ReplayPage> {
+        // Get a lock on the client's runtime
+        let rt = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        replay::replay(&rt.ledger, start.as_ref(), limit).map_err(Self::map_replay_err)
+    }
+
+    /// Opens a long export of the DAG, beginning at the provided point.
+    fn open_export(&self, start: Option<ReplayStart>) -> Result<ExportSession> {
+        if let Ok(mut rt) = self.runtime.write() {
+            rt.exports
+                .open(start, Utc::now())
+                .map_err(Self::map_export_err)
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets the next page of the export with the provided id, acknowledging the previous page if its cursor is
+    /// provided.
+    fn next_export_page(
+        &self,
+        id: String,
+        ack: Option<String>,
+        limit: Option<usize>,
+    ) -> Result<ExportPage> {
+        let mut rt = if let Ok(rt) = self.runtime.write() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        // Pick up just after the last page that the consumer has safely stored
+        let session = rt
+            .exports
+            .acknowledge(&id, ack.as_deref(), Utc::now())
+            .map_err(Self::map_export_err)?;
+        let page = replay::replay(&rt.ledger, session.resume_from().as_ref(), limit)
+            .map_err(Self::map_replay_err)?;
+
+        Ok(ExportPage {
+            session: rt
+                .exports
+                .handed_out(&id, &page)
+                .map_err(Self::map_export_err)?,
+            page,
+        })
+    }
+
+    /// Gets how far along the export with the provided id is.
+    fn get_export(&self, id: String) -> Result<ExportSession> {
+        if let Ok(mut rt) = self.runtime.write() {
+            rt.exports
+                .get(&id, Utc::now())
+                .map_err(Self::map_export_err)
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Closes the export with the provided id.
+    fn close_export(&self, id: String) -> Result<()> {
+        if let Ok(mut rt) = self.runtime.write() {
+            rt.exports.close(&id).map_err(Self::map_export_err)
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Re-executes the entire DAG from its genesis, and reports the first transaction whose cited parent state
+    /// couldn't be reproduced.
+    fn verify(&self) -> Result<VerificationReport> {
+        // Take a snapshot of the DAG, so that the node can keep going while we re-execute it
+        let ledger = if let Ok(rt) = self.runtime.read() {
+            rt.ledger.snapshot()
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        replay::verify(&ledger).map_err(|e| {
+            debug!("Failed to re-execute the DAG: {}", e);
+
+            Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_READ_DAG))
+        })
+    }
+
+    /// Recomputes the state resulting from the node with the provided hash, and compares it against the state hash
+    /// recorded for the node.
+    fn verify_state_at(&self, node_hash: String) -> Result<StateProof> {
+        // Take a snapshot of the DAG, so that the node can keep going while we re-execute it
+        let ledger = if let Ok(rt) = self.runtime.read() {
+            rt.ledger.snapshot()
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        let node_hash = ledger
+            .resolve_hash(&node_hash)
+            .map_err(Self::map_short_id_err)?;
+        replay::verify_state(&ledger, node_hash).map_err(Self::map_replay_err)
+    }
+
+    /// Gets the state of the ledger as it stood after the execution of the node with the provided hash.
+    fn get_state_at(&self, node_hash: String) -> Result<Entry> {
+        // Get a lock on the client's runtime
+        let rt = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        // Only archive nodes can be relied upon to remember the state of every node
+        if !rt.ledger.is_archive() {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_ARCHIVE_UNSUPPORTED,
+            )));
+        }
+
+        // Look up the node's state in the DAG's database
+        let node_hash = rt
+            .ledger
+            .resolve_hash(&node_hash)
+            .map_err(Self::map_short_id_err)?;
+        match rt.ledger.get_state_at(node_hash) {
+            Ok(Some(entry)) => Ok(entry),
+            Ok(None) => Err(Error::new(ErrorCode::from(
+                error::ERROR_NO_HISTORICAL_STATE,
+            ))),
+            Err(_) => Err(Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_READ_DAG))),
+        }
+    }
+
+    /// Gets the balance of the account with the provided address as it stood after the execution of the node with
+    /// the provided hash.
+    fn get_balance_at(&self, address: Address, node_hash: String) -> Result<BigUint> {
+        Ok(self
+            .get_state_at(node_hash)?
+            .data
+            .balances
+            .remove(&address.to_str())
+            .unwrap_or_default())
+    }
+
+    /// Re-executes the transaction with the provided hash, and records each step taken along the way.
+    fn trace(&self, hash: String, level: Option<TraceLevel>) -> Result<ExecutionTrace> {
+        // Get a lock on the client's runtime
+        let rt = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        let hash = rt
+            .ledger
+            .resolve_hash(&hash)
+            .map_err(Self::map_short_id_err)?;
+        let tx = match rt.ledger.get_with_hash(hash) {
+            Ok(node) => node.transaction.clone(),
+            Err(_) => {
+                return Err(Error::new(ErrorCode::from(
+                    error::ERROR_UNABLE_TO_OPEN_TRANSACTION,
+                )))
+            }
+        };
+
+        // Resolving the parents never touches the ledger itself
+        let (parent_state, _) = rt
+            .ledger
+            .resolve_parent_nodes(tx.transaction_data.parents.clone())
+            .map_err(|_| Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_READ_DAG)))?;
+
+        Ok(tx.trace(parent_state, level.unwrap_or_default()))
+    }
+
+    /// Resolves the provided short id to the full hash of the one transaction in the DAG that it begins.
+    fn resolve_hash(&self, id: String) -> Result<Hash> {
+        if let Ok(rt) = self.runtime.read() {
+            rt.ledger.resolve_hash(&id).map_err(Self::map_short_id_err)
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Inspects the DAG & mempool for the provided account's transactions that haven't been executed.
+    fn diagnose_account(
+        &self,
+        address: String,
+        stuck_after_secs: Option<u64>,
+    ) -> Result<AccountDiagnosis> {
+        let address = Self::parse_address(&self.runtime, &address)?;
+
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.diagnose_account(
+                &address,
+                stuck_after_secs.unwrap_or(DEFAULT_STUCK_AFTER_SECS),
+            ))
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets a page of the headers that light clients follow.
+    fn get_light_headers(&self, offset: usize, limit: Option<usize>) -> Result<Vec<LightHeader>> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.light_headers(offset, limit))
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets the header of the executed head.
+    fn get_light_head(&self) -> Result<Option<LightHeader>> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.executed_light_header())
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Proves the balance of an account against the header of a node in the DAG.
+    fn get_balance_proof(
+        &self,
+        address: String,
+        node_hash: Option<String>,
+    ) -> Result<ProvenBalance> {
+        let address = Self::parse_address(&self.runtime, &address)?;
+
+        if let Ok(rt) = self.runtime.read() {
+            let at = match node_hash {
+                Some(node_hash) => Some(
+                    rt.ledger
+                        .resolve_hash(&node_hash)
+                        .map_err(Self::map_short_id_err)?,
+                ),
+                None => None,
+            };
+
+            rt.prove_balance(&address, at)
+                .map_err(Self::map_light_client_err)
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Checks a balance proven by another node against this node's copy of its header.
+    fn verify_balance_proof(&self, proven: ProvenBalance) -> Result<BigUint> {
+        if let Ok(rt) = self.runtime.read() {
+            rt.verify_balance_proof(&proven)
+                .map_err(Self::map_light_client_err)
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return the corresponding error
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+}
+
+impl DagImpl {
+    /// Registers the DAG service on the given RPC handler.
+    pub fn register(io: &mut RpcHandler, runtime: Arc<InstrumentedRwLock<System>>) {
+        // Register this service on the IO handler
+        io.extend_with(Self { runtime }.to_delegate());
+    }
+
+    /// Converts the given light client error into an RPC error.
+    ///
+    /// # Arguments
+    ///
+    /// * `e` - The error that the proof failed with
+    fn map_light_client_err(e: LightClientError) -> Error {
+        debug!("Failed to prove or verify a balance: {}", e);
+
+        Error::new(ErrorCode::from(match e {
+            LightClientError::UnknownParent { .. }
+            | LightClientError::OutOfOrder { .. }
+            | LightClientError::UnknownHeader { .. } => error::ERROR_UNABLE_TO_OPEN_TRANSACTION,
+            LightClientError::MissingState { .. } | LightClientError::UnattestedState { .. } => {
+                error::ERROR_NO_HISTORICAL_STATE
+            }
+            LightClientError::UnknownAccount { .. } => error::ERROR_NO_SUCH_BALANCE,
+            LightClientError::ConflictingHeader { .. } | LightClientError::InvalidProof { .. } => {
+                error::ERROR_INVALID_BALANCE_PROOF
+            }
+            LightClientError::Serialization { .. } => error::ERROR_SERIALIZATION_FAILED,
+        }))
+    }
+
+    /// Converts the given replay error into an RPC error.
+    ///
+    /// # Arguments
+    ///
+    /// * `e` - The error that the replay failed with
+    fn map_replay_err(e: ReplayError) -> Error {
+        debug!("Failed to replay the DAG: {}", e);
+
+        Error::new(ErrorCode::from(match e {
+            ReplayError::MalformedCursor { .. } | ReplayError::CursorInvalidated { .. } => {
+                error::ERROR_INVALID_REPLAY_CURSOR
+            }
+            ReplayError::NoSuchTransaction { .. } => error::ERROR_UNABLE_TO_OPEN_TRANSACTION,
+            ReplayError::StorageFailure { .. } => error::ERROR_UNABLE_TO_READ_DAG,
+        }))
+    }
+
+    /// Converts the given export error into an RPC error.
+    ///
+    /// # Arguments
+    ///
+    /// * `e` - The error that the export failed with
+    fn map_export_err(e: ExportError) -> Error {
+        Error::new(ErrorCode::from(match e {
+            ExportError::NoSuchExport { .. } => error::ERROR_NO_SUCH_EXPORT,
+            ExportError::TooManyExports { .. } => error::ERROR_TOO_MANY_EXPORTS,
+            ExportError::UnexpectedAck { .. } => error::ERROR_UNEXPECTED_EXPORT_ACK,
+        }))
+    }
+
+    /// Converts the given error, encountered while resolving a short id, into an RPC error. The candidates for an
+    /// ambiguous short id are listed in the error's data, so that the caller can pick one.
+    ///
+    /// # Arguments
+    ///
+    /// * `e` - The error
+    pub(crate) fn map_short_id_err(e: ShortIdError) -> Error {
+        match e {
+            ShortIdError::Unknown { .. } => {
+                Error::new(ErrorCode::from(error::ERROR_UNKNOWN_SHORT_ID))
+            }
+            ShortIdError::Ambiguous { candidates, .. } => {
+                let mut err = Error::new(ErrorCode::from(error::ERROR_AMBIGUOUS_SHORT_ID));
+                err.data = Some(serde_json::json!(candidates
+                    .split(", ")
+                    .collect::<Vec<&str>>()));
+
+                err
+            }
+            ShortIdError::Malformed { .. } | ShortIdError::TooShort { .. } => {
+                Error::new(ErrorCode::from(error::ERROR_MALFORMED_HASH))
+            }
+        }
+    }
+
+    /// Decodes an address given to the API, either bare or in its typed form (network:kind:address). Typed addresses
+    /// meant for a network other than the node's are refused. The caller mustn't hold a lock on the runtime already.
+    ///
+    /// # Arguments
+    ///
+    /// * `runtime` - The runtime whose network the address should belong to
+    /// * `address` - The bare, or typed, address
+    pub(crate) fn parse_address(
+        runtime: &InstrumentedRwLock<System>,
+        address: &str,
+    ) -> Result<Address> {
+        let network = if let Ok(rt) = runtime.read() {
+            rt.config.network_name.clone()
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        address::parse(address, &network).map_err(|e| {
+            debug!("Refusing address: {}", e);
+
+            Error::new(ErrorCode::from(error::ERROR_INVALID_ADDRESS))
+        })
+    }
+
+    /// Gets the state entry of the last executed node in the runtime's DAG.
+    pub(crate) fn head_entry(runtime: &System) -> Result<Entry> {
+        if let Some(mut h) = runtime.ledger.obtain_executed_head() {
+            // Load the entry's state data
+            if let Some(state_entry) = h.state_entry.take() {
+                Ok(state_entry)
+            } else {
+                debug!("Best graph node doesn't contain a state entry; terminating");
+
+                // Return a state ref error
+                Err(Error::new(ErrorCode::from(
+                    error::ERROR_UNABLE_TO_OBTAIN_STATE_REF,
+                )))
+            }
+        } else {
+            debug!("Unable to obtain a lock on the client's runtime");
+
+            // Return a state ref error
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_STATE_REF,
+            )))
+        }
+    }
+
+    /// Checks the given transaction against the spending policy of the account signing it, recording it as spent if it
+    /// is allowed. Confirmation codes are only ever written to the node's log, never returned to the caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The data directory holding the signing account
+    /// * `signer` - The address of the account signing the transaction
+    /// * `tx` - The transaction being signed
+    /// * `confirmation` - The confirmation code given by the caller, if any
+    fn check_spending_policy(
+        data_dir: &str,
+        signer: &Address,
+        tx: &Transaction,
+        confirmation: Option<&str>,
+    ) -> Result<()> {
+        let storage_error = |_| {
+            Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_ACCESS_SPENDING_POLICY,
+            ))
+        };
+
+        let policy =
+            SpendingPolicy::read_from_disk_at_data_directory(data_dir).map_err(storage_error)?;
+        let mut log =
+            SpendingLog::read_from_disk_at_data_directory(data_dir).map_err(storage_error)?;
+
+        let result = policy.authorize(&mut log, signer, tx, confirmation, Utc::now());
+
+        // Record the spend, or the freshly issued confirmation code, even if the transaction was refused
+        log.write_to_disk_at_data_directory(data_dir)
+            .map_err(storage_error)?;
+
+        result.map_err(|e| {
+            warn!("Refusing to sign transaction {}: {}", tx.hash, e);
+
+            Error::new(ErrorCode::from(match e {
+                PolicyError::ConfirmationRequired { .. }
+                | PolicyError::InvalidConfirmation { .. } => error::ERROR_CONFIRMATION_REQUIRED,
+                _ => error::ERROR_SPENDING_POLICY_VIOLATION,
+            }))
+        })
+    }
+
+    /// Builds a transaction on top of a few unresolved tips of the runtime's DAG.
+    ///
+    /// # Arguments
+    ///
+    /// * `runtime` - The runtime containing the DAG that the transaction should be built on
+    /// * `nonce` - The index of the transaction in the set of the sender's transactions
+    /// * `sender` - The address of the sender of the transaction
+    /// * `recipient` - The address of the recipient of the transaction
+    /// * `value` - The number of finks sent through the transaction
+    /// * `payload` - The payload sent along with the transaction
+    pub(crate) fn build_tx(
+        runtime: &System,
+        nonce: u64,
+        sender: Address,
+        recipient: Address,
+        value: BigUint,
+        payload: &[u8],
+    ) -> Result<Transaction> {
+        // Pick a few unresolved tips to use as the parents of the transaction we're about to generate
+        let parent_hashes: Vec<Hash> = runtime
+            .ledger
+            .select_tips(DEFAULT_TIPS_PER_TRANSACTION, true);
+
+        // Create a new transaction
+        let mut transaction =
+            Transaction::new(nonce, sender, recipient, value, payload, parent_hashes);
+
+        // Calculate a merged state entry for each of the parents of the transaction. We can use this to provide a proof of correctness for this tx.
+        let (merged_state_entry, parent_entries) = if let Ok(res) = runtime
+            .ledger
+            .resolve_parent_nodes(transaction.transaction_data.parents.clone())
+        {
+            res
+        } else {
+            debug!(
+                "Failed to merge the parent entries required to produce transaction {}",
+                transaction.hash
+            );
+
+            // Return a state error
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_STATE_REF,
+            )));
+        };
+
+        // Register the parent states
+        transaction.register_parental_state(merged_state_entry, parent_entries);
+
+        // Return the transaction
+        Ok(transaction)
+    }
+}
+
+/// A client for the SummerCash DAG API.
+pub struct Client {
+    /// The underlying JSON-RPC client
+    rpc: RpcClient,
+}
+
+impl From<RpcClient> for Client {
+    /// Builds a client on top of the given JSON-RPC client, sharing its connections.
+    fn from(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+}
+
+impl Client {
+    /// Initializes a new Client with the given remote URL, or comma-separated list of URLs for redundant nodes.
+    pub fn new(server_addr: &str) -> Self {
+        Self {
+            rpc: RpcClient::new(server_addr),
+        }
+    }
+
+    /// Makes each of the client's requests under the admin session with the given token.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The token of the admin session, if there is one
+    pub fn with_session(mut self, session: Option<String>) -> Self {
+        self.rpc = self.rpc.with_session(session);
+
+        self
+    }
+
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.rpc = self.rpc.with_retry_policy(policy);
+
+        self
+    }
+
+    /// Gets a list of nodes in the working graph.
+    pub async fn get(&self) -> std::result::Result<Vec<Node>, ClientError> {
+        self.rpc.call("get_dag", ()).await
+    }
+
+    /// Gets a list of transaction hashes contained in the working DAG, meeting the conditions of the query, if one is
+    /// provided.
+    pub async fn list(
+        &self,
+        query: Option<TransactionQuery>,
+    ) -> std::result::Result<Vec<Hash>, ClientError> {
+        self.rpc.call("list_transactions", (query,)).await
+    }
+
+    /// Creates a new transaction with the provided parameters, paying the provided gas price (in finks) for the gas
+    /// that it uses, if one is provided.
+    pub async fn create_tx(
+        &self,
+        sender: String,
+        recipient: String,
+        amount: u128,
+        payload: String,
+        gas_price: Option<u128>,
+        gas_limit: Option<u64>,
+    ) -> std::result::Result<Transaction, ClientError> {
+        self.rpc
+            .call(
+                "create_transaction",
+                (
+                    &sender,
+                    &recipient,
+                    amount.to_string(),
+                    &payload,
+                    gas_price.map(|price| price.to_string()),
+                    gas_limit,
+                ),
+            )
+            .await
+    }
+
+    /// Creates a single transaction paying each of the provided recipients the provided number of finks.
+    pub async fn create_batch_tx(
+        &self,
+        sender: String,
+        transfers: Vec<(String, u128)>,
+    ) -> std::result::Result<Transaction, ClientError> {
+        // Values are sent as decimal strings, since they may not fit in a JSON number
+        let transfers: Vec<(String, String)> = transfers
+            .into_iter()
+            .map(|(recipient, value)| (recipient, value.to_string()))
+            .collect();
+
+        self.rpc
+            .call("create_batch_transaction", (&sender, &transfers))
+            .await
+    }
+
+    /// Creates a set of unsigned transactions sweeping the balances of the source accounts into the destination.
+    pub async fn sweep(
+        &self,
+        sources: Vec<String>,
+        destination: String,
+    ) -> std::result::Result<Vec<Transaction>, ClientError> {
+        self.rpc.call("sweep", (&sources, &destination)).await
+    }
+
+    /// Signs the transaction with the provided account. Transactions above the account's confirmation threshold must
+    /// be given the confirmation code written to the node's log. To sign with an account unlocked for a limited time,
+    /// pass the handle of its unlock session.
+    pub async fn sign_tx(
+        &self,
+        hash: String,
+        data_dir: String,
+        confirmation: Option<String>,
+        session: Option<String>,
+    ) -> std::result::Result<Signature, ClientError> {
+        self.rpc
+            .call(
+                "sign_transaction",
+                (&hash, &data_dir, &confirmation, &session),
+            )
+            .await
+    }
+
+    /// Gets a list of pending transactions stored on the disk.
+    pub async fn get_mem_txs(
+        &self,
+        data_dir: String,
+    ) -> std::result::Result<Vec<Hash>, ClientError> {
+        self.rpc.call("get_mem_transactions", (&data_dir,)).await
+    }
+
+    /// Publishes a transaction stored on the disk.
+    pub async fn publish_tx(
+        &self,
+        hash: String,
+        data_dir: String,
+    ) -> std::result::Result<(), ClientError> {
+        self.rpc
+            .call("publish_transaction", (&hash, &data_dir))
+            .await
+    }
+
+    /// Builds an unsigned copy of a transaction stored on the disk on top of the DAG's current head, spending the same
+    /// nonce.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction that should be rebuilt
+    /// * `data_dir` - The data directory that the transaction is stored in
+    pub async fn rebuild_tx(
+        &self,
+        hash: &str,
+        data_dir: &str,
+    ) -> std::result::Result<Transaction, ClientError> {
+        self.rpc.call("rebuild_transaction", (hash, data_dir)).await
+    }
+
+    /// Gossips a signed transaction stored on the disk to the network again, in place of the transaction that it was
+    /// rebuilt from, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash of the transaction that should be gossiped again
+    /// * `data_dir` - The data directory that the transaction is stored in
+    /// * `replaces` - The hash of the transaction that it was rebuilt from, if it was rebuilt
+    pub async fn resubmit_tx(
+        &self,
+        hash: &str,
+        data_dir: &str,
+        replaces: Option<&str>,
+    ) -> std::result::Result<(), ClientError> {
+        self.rpc
+            .call("resubmit_transaction", (hash, data_dir, replaces))
+            .await
+    }
+
+    /// Runs each of the validator's checks against the provided transaction.
+    pub async fn validate_tx(
+        &self,
+        tx: &Transaction,
+    ) -> std::result::Result<ValidationReport, ClientError> {
+        self.rpc.call("validate_transaction", (tx,)).await
+    }
+
+    /// Gets the receipt recorded for the transaction with the provided hash.
+    pub async fn get_receipt(&self, hash: String) -> std::result::Result<Receipt, ClientError> {
+        self.rpc.call("get_receipt", (&hash,)).await
+    }
+
+    /// Gets the receipt recorded for each of the transactions with the provided hashes in a single request, in the
+    /// order given.
+    ///
+    /// # Arguments
+    ///
+    /// * `hashes` - The hashes of each of the transactions
+    pub async fn get_receipts(
+        &self,
+        hashes: &[String],
+    ) -> std::result::Result<Vec<std::result::Result<Receipt, ClientError>>, ClientError> {
+        let mut batch = self.rpc.batch();
+        for hash in hashes {
+            batch.queue("get_receipt", (hash,))?;
+        }
+
+        batch.flush().await
+    }
+
+    /// Gets each of the transactions known to have produced or cited the state entry with the provided hash.
+    pub async fn get_state_origin(
+        &self,
+        state_hash: String,
+    ) -> std::result::Result<StateOrigin, ClientError> {
+        self.rpc.call("get_state_origin", (&state_hash,)).await
+    }
+
+    /// Gets a page of the transactions in the DAG, in ledger order, along with their receipts and the balances they
+    /// left behind. Pass the page's next_cursor back as the start of the next call to resume where it left off.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Where the replay should begin, or None to start at the genesis
+    /// * `limit` - The greatest number of transactions that should be returned
+    pub async fn replay(
+        &self,
+        start: Option<ReplayStart>,
+        limit: Option<usize>,
+    ) -> std::result::Result<ReplayPage, ClientError> {
+        self.rpc.call("replay_transactions", (start, limit)).await
+    }
+
+    /// Opens a long export of the DAG, whose progress the node keeps track of so that it can be resumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - Where the export should begin, or None to begin at the genesis
+    pub async fn open_export(
+        &self,
+        start: Option<ReplayStart>,
+    ) -> std::result::Result<ExportSession, ClientError> {
+        self.rpc.call("open_export", (start,)).await
+    }
+
+    /// Gets the next page of an export, acknowledging the previous page if its cursor is provided.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the export
+    /// * `ack` - The cursor handed out with the previous page, once that page has been safely stored
+    /// * `limit` - The greatest number of transactions that should be returned
+    pub async fn next_export_page(
+        &self,
+        id: &str,
+        ack: Option<String>,
+        limit: Option<usize>,
+    ) -> std::result::Result<ExportPage, ClientError> {
+        self.rpc.call("next_export_page", (id, ack, limit)).await
+    }
+
+    /// Gets how far along an export is.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the export
+    pub async fn get_export(&self, id: &str) -> std::result::Result<ExportSession, ClientError> {
+        self.rpc.call("get_export", (id,)).await
+    }
+
+    /// Closes an export.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the export
+    pub async fn close_export(&self, id: &str) -> std::result::Result<(), ClientError> {
+        self.rpc.call("close_export", (id,)).await
+    }
+
+    /// Re-executes the entire DAG from its genesis, and reports the first transaction whose cited parent state
+    /// couldn't be reproduced, along with how the recomputed state differs from the stored one.
+    pub async fn verify(&self) -> std::result::Result<VerificationReport, ClientError> {
+        self.rpc.call("verify_ledger", ()).await
+    }
+
+    /// Recomputes the state resulting from the node with the provided hash (or short id), and compares it against
+    /// the state hash recorded for the node, even if the state itself has been purged.
+    ///
+    /// # Arguments
+    ///
+    /// * `node_hash` - The hash, or short id, of the node whose state should be checked
+    pub async fn verify_state_at(
+        &self,
+        node_hash: &str,
+    ) -> std::result::Result<StateProof, ClientError> {
+        self.rpc.call("verify_state_at", (node_hash,)).await
+    }
+
+    /// Gets the state of the ledger as it stood after the execution of the node with the provided hash. Nodes that
+    /// aren't archives refuse the request.
+    pub async fn get_state_at(&self, node_hash: String) -> std::result::Result<Entry, ClientError> {
+        self.rpc.call("get_state_at", (&node_hash,)).await
+    }
+
+    /// Gets the balance of the account with the provided address as it stood after the execution of the node with
+    /// the provided hash. Nodes that aren't archives refuse the request.
+    pub async fn get_balance_at(
+        &self,
+        address: Address,
+        node_hash: String,
+    ) -> std::result::Result<BigUint, ClientError> {
+        self.rpc
+            .call("get_balance_at", (&address, &node_hash))
+            .await
+    }
+
+    /// Re-executes the transaction with the provided hash, and records each step taken along the way.
+    pub async fn trace(
+        &self,
+        hash: String,
+        level: Option<TraceLevel>,
+    ) -> std::result::Result<ExecutionTrace, ClientError> {
+        self.rpc
+            .call("debug_trace_transaction", (&hash, &level))
+            .await
+    }
+
+    /// Resolves the provided short id to the full hash of the one transaction in the DAG that it begins.
+    pub async fn resolve_hash(&self, id: &str) -> std::result::Result<Hash, ClientError> {
+        self.rpc.call("resolve_hash", (id,)).await
+    }
+
+    /// Inspects the DAG & mempool for the provided account's transactions that haven't been executed, and recommends
+    /// what to do about anything keeping them from being executed.
+    pub async fn diagnose_account(
+        &self,
+        address: &str,
+        stuck_after_secs: Option<u64>,
+    ) -> std::result::Result<AccountDiagnosis, ClientError> {
+        self.rpc
+            .call("diagnose_account", (address, stuck_after_secs))
+            .await
+    }
+
+    /// Gets a page of the headers that light clients follow.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The number of headers that should be skipped
+    /// * `limit` - The largest number of headers that should be listed
+    pub async fn get_light_headers(
+        &self,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> std::result::Result<Vec<LightHeader>, ClientError> {
+        self.rpc.call("get_light_headers", (offset, limit)).await
+    }
+
+    /// Gets the header of the node's executed head, if it has executed anything yet.
+    pub async fn get_light_head(&self) -> std::result::Result<Option<LightHeader>, ClientError> {
+        self.rpc.call("get_light_head", ()).await
+    }
+
+    /// Proves the balance of an account against the header of a node in the DAG.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address of the account
+    /// * `node_hash` - The hash, or short id, of the node (the executed head, if none is given)
+    pub async fn get_balance_proof(
+        &self,
+        address: &str,
+        node_hash: Option<&str>,
+    ) -> std::result::Result<ProvenBalance, ClientError> {
+        self.rpc
+            .call("get_balance_proof", (address, node_hash))
+            .await
+    }
+
+    /// Checks a balance proven by another node against the node's own copy of the header that it was proven against.
+    ///
+    /// # Arguments
+    ///
+    /// * `proven` - The balance, and the header that it was proven against
+    pub async fn verify_balance_proof(
+        &self,
+        proven: &ProvenBalance,
+    ) -> std::result::Result<BigUint, ClientError> {
+        self.rpc.call("verify_balance_proof", (proven,)).await
     }
 }
@@ -49,3 +49,279 @@ pub const ERROR_UNABLE_TO_CREATE_PROPOSAL: i64 = 15;
 
 /// An error code representing the inability of the executor to read the DAG from the disk.
 pub const ERROR_UNABLE_TO_READ_DAG: i64 = 16;
+
+/// An error code representing the absence of a receipt for the requested transaction.
+pub const ERROR_NO_SUCH_RECEIPT: i64 = 17;
+
+/// An error code representing the absence of a local account that derived the given sub-address.
+pub const ERROR_NO_SUCH_PARENT_ACCOUNT: i64 = 18;
+
+/// An error code representing the absence of a pending proposal with the given hash.
+pub const ERROR_NO_SUCH_PROPOSAL: i64 = 19;
+
+/// An error code representing the absence of a note attached to the given transaction hash or address.
+pub const ERROR_NO_SUCH_NOTE: i64 = 20;
+
+/// An error code representing the inability of the executor to read the local notes store.
+pub const ERROR_UNABLE_TO_READ_NOTES: i64 = 21;
+
+/// An error code representing the inability of the executor to persist the local notes store.
+pub const ERROR_UNABLE_TO_WRITE_NOTES: i64 = 22;
+
+/// An error code representing the absence of any known origin for the state entry with the given hash.
+pub const ERROR_NO_SUCH_STATE: i64 = 23;
+
+/// An error code representing the absence of any guardians registered by the given account.
+pub const ERROR_NO_SUCH_GUARDIANS: i64 = 24;
+
+/// An error code representing a guardian instruction that breaks the account recovery rules.
+pub const ERROR_INVALID_GUARDIAN_INSTRUCTION: i64 = 25;
+
+/// An error code representing a call to an admin method made without an open admin session.
+pub const ERROR_UNAUTHORIZED: i64 = 26;
+
+/// An error code representing a signed challenge that doesn't entitle the signer to an admin session.
+pub const ERROR_INVALID_AUTH_RESPONSE: i64 = 27;
+
+/// An error code representing a batch transfer that breaks the batch transfer rules.
+pub const ERROR_INVALID_BATCH: i64 = 28;
+
+/// An error code representing a checkpoint whose hash couldn't be decoded.
+pub const ERROR_INVALID_CHECKPOINT: i64 = 29;
+
+/// An error code representing a checkpoint that disagrees with the transaction already at its index in the local DAG.
+pub const ERROR_CHECKPOINT_CONFLICT: i64 = 30;
+
+/// An error code representing the inability of the executor to persist the pinned checkpoints.
+pub const ERROR_UNABLE_TO_WRITE_CHECKPOINTS: i64 = 31;
+
+/// An error code representing the inability of the executor to persist the voting policy.
+pub const ERROR_UNABLE_TO_WRITE_VOTING_POLICY: i64 = 32;
+
+/// An error code representing a JSON-RPC batch holding more calls than the node answers in one request.
+pub const ERROR_BATCH_TOO_LARGE: i64 = 33;
+
+/// An error code representing a replay cursor that couldn't be parsed, or that points past a transaction no longer in
+/// the DAG.
+pub const ERROR_INVALID_REPLAY_CURSOR: i64 = 34;
+
+/// An error code representing a time lock that doesn't name exactly one unlock condition, or doesn't lock anything.
+pub const ERROR_INVALID_TIME_LOCK: i64 = 35;
+
+/// An error code representing the absence of a contact with the given name in the address book.
+pub const ERROR_NO_SUCH_CONTACT: i64 = 36;
+
+/// An error code representing the inability of the executor to read the address book from the disk.
+pub const ERROR_UNABLE_TO_READ_CONTACTS: i64 = 37;
+
+/// An error code representing the inability of the executor to write the address book to the disk.
+pub const ERROR_UNABLE_TO_WRITE_CONTACTS: i64 = 38;
+
+/// An error code representing a contact whose name is ambiguous, or whose address couldn't be decoded.
+pub const ERROR_INVALID_CONTACT: i64 = 39;
+
+/// An error code representing a call reaching for the node's own data directory without an API key or an admin
+/// session, on a node serving tenants.
+pub const ERROR_NO_TENANT: i64 = 40;
+
+/// An error code representing a request for the state at a historical node, made to a node that isn't an archive.
+pub const ERROR_ARCHIVE_UNSUPPORTED: i64 = 41;
+
+/// An error code representing a historical node that isn't in the DAG, or whose state isn't known.
+pub const ERROR_NO_HISTORICAL_STATE: i64 = 42;
+
+/// An error code representing a claim made by an account without any unclaimed rewards.
+pub const ERROR_NO_UNCLAIMED_REWARDS: i64 = 43;
+
+/// An error code representing a transaction query whose value or time range can't match anything.
+pub const ERROR_INVALID_QUERY: i64 = 44;
+
+/// An error code representing a transaction that the signing account's spending policy doesn't allow.
+pub const ERROR_SPENDING_POLICY_VIOLATION: i64 = 45;
+
+/// An error code representing a transaction that must be confirmed with the code written to the node's log.
+pub const ERROR_CONFIRMATION_REQUIRED: i64 = 46;
+
+/// An error code representing a spending policy, or spending log, that couldn't be read from, or written to, the disk.
+pub const ERROR_UNABLE_TO_ACCESS_SPENDING_POLICY: i64 = 47;
+
+/// An error code representing a remote signer that couldn't be reached, or that refused to sign.
+pub const ERROR_REMOTE_SIGNER_FAILED: i64 = 48;
+
+/// An error code representing an event subscription that was never opened, or that has expired.
+pub const ERROR_NO_SUCH_SUBSCRIPTION: i64 = 49;
+
+/// An error code representing a node that can't keep any more event subscriptions open.
+pub const ERROR_TOO_MANY_SUBSCRIPTIONS: i64 = 50;
+
+/// An error code representing an unlock session that was never opened, that has expired, or that holds another account.
+pub const ERROR_NO_SUCH_UNLOCK_SESSION: i64 = 51;
+
+/// An error code representing an account that can't be unlocked for as long as was asked.
+pub const ERROR_INVALID_UNLOCK_DURATION: i64 = 52;
+
+/// An error code representing a write refused because the node's disk is nearly full.
+pub const ERROR_STORAGE_EXHAUSTED: i64 = 53;
+
+/// An error code representing a method that isn't served on the API that it was called through (e.g. a wallet method
+/// called through a read-only API).
+pub const ERROR_METHOD_NOT_EXPOSED: i64 = 54;
+
+/// An error code representing the absence of an open export with the given id.
+pub const ERROR_NO_SUCH_EXPORT: i64 = 55;
+
+/// An error code representing an export refused because the node already has too many open.
+pub const ERROR_TOO_MANY_EXPORTS: i64 = 56;
+
+/// An error code representing an acknowledged cursor that wasn't handed out with the export's last page.
+pub const ERROR_UNEXPECTED_EXPORT_ACK: i64 = 57;
+
+/// An error code representing a short id that doesn't begin any known hash.
+pub const ERROR_UNKNOWN_SHORT_ID: i64 = 58;
+
+/// An error code representing a short id that begins more than one known hash. The candidates are listed in the
+/// error's data.
+pub const ERROR_AMBIGUOUS_SHORT_ID: i64 = 59;
+
+/// An error code representing a hash (or short id) that isn't base58-encoded, or is too short to look up.
+pub const ERROR_MALFORMED_HASH: i64 = 60;
+
+/// An error code representing a gas price that isn't a whole number of finks.
+pub const ERROR_INVALID_GAS_PRICE: i64 = 61;
+
+/// An error code representing a transaction that the mempool refused. Why it was refused is given in the error's data.
+pub const ERROR_MEMPOOL_REJECTED: i64 = 62;
+
+/// An error code representing an account that was asked to be locked, but is locked already.
+pub const ERROR_ACCOUNT_ALREADY_LOCKED: i64 = 63;
+
+/// An error code representing an account that was asked to be unlocked, but isn't locked.
+pub const ERROR_ACCOUNT_NOT_LOCKED: i64 = 64;
+
+/// An error code representing a call refused because the method has been called too often lately.
+pub const ERROR_RATE_LIMITED: i64 = 65;
+
+/// An error code representing a write refused because the node's disk isn't taking writes.
+pub const ERROR_DISK_READ_ONLY: i64 = 66;
+
+/// An error code representing a balance proof that doesn't match the header that it was checked against.
+pub const ERROR_INVALID_BALANCE_PROOF: i64 = 67;
+
+/// An error code representing a balance proof asked for an account that the state doesn't hold a balance for.
+pub const ERROR_NO_SUCH_BALANCE: i64 = 68;
+
+/// An error code representing an address that doesn't decode, or that's typed for a network other than the node's.
+pub const ERROR_INVALID_ADDRESS: i64 = 69;
+
+/// Gets a short, human-readable description of the given error code, if it's one of ours.
+///
+/// # Arguments
+///
+/// * `code` - The error code
+pub fn describe(code: i64) -> Option<&'static str> {
+    match code {
+        ERROR_SIGNATURE_UNDEFINED => Some("the signature could not be derived"),
+        ERROR_UNABLE_TO_OPEN_ACCOUNT => Some("the account could not be opened (is it locked?)"),
+        ERROR_UNABLE_TO_WRITE_ACCOUNT => Some("the account could not be written to the disk"),
+        ERROR_UNABLE_TO_READ_ACCOUNT => Some("the account could not be read from the disk"),
+        ERROR_UNABLE_TO_GENERATE_RANODM => Some("a random seed could not be generated"),
+        ERROR_ENCRYPTION_FAILED => Some("encryption failed"),
+        ERROR_DECRYPTION_FAILED => Some("decryption failed (is the key right?)"),
+        ERROR_UNABLE_TO_DELETE_ACCOUNT => Some("the account could not be deleted"),
+        ERROR_UNABLE_TO_OBTAIN_LOCK => Some("the node is busy; try again"),
+        ERROR_UNABLE_TO_OBTAIN_STATE_REF => Some("the last valid state could not be obtained"),
+        ERROR_UNABLE_TO_OPEN_GENESIS_CONFIG => Some("the genesis config could not be opened"),
+        ERROR_UNABLE_TO_CREATE_GENESIS => Some("the genesis state could not be created"),
+        ERROR_SERIALIZATION_FAILED => Some("serialization failed"),
+        ERROR_UNABLE_TO_OPEN_TRANSACTION => Some("the transaction could not be opened"),
+        ERROR_UNABLE_TO_WRITE_TRANSACTION => {
+            Some("the transaction could not be written to the disk")
+        }
+        ERROR_UNABLE_TO_CREATE_PROPOSAL => Some("the proposal could not be created"),
+        ERROR_UNABLE_TO_READ_DAG => Some("the DAG could not be read"),
+        ERROR_NO_SUCH_RECEIPT => Some("no receipt has been recorded for the transaction"),
+        ERROR_NO_SUCH_PARENT_ACCOUNT => Some("no local account derived the sub-address"),
+        ERROR_NO_SUCH_PROPOSAL => Some("no such proposal is pending"),
+        ERROR_NO_SUCH_NOTE => Some("no note is attached to the subject"),
+        ERROR_UNABLE_TO_READ_NOTES => Some("the notes could not be read"),
+        ERROR_UNABLE_TO_WRITE_NOTES => Some("the notes could not be written to the disk"),
+        ERROR_NO_SUCH_STATE => Some("no origin is known for the state entry"),
+        ERROR_NO_SUCH_GUARDIANS => Some("the account has not registered any guardians"),
+        ERROR_INVALID_GUARDIAN_INSTRUCTION => {
+            Some("the guardian instruction breaks the account recovery rules")
+        }
+        ERROR_UNAUTHORIZED => Some("an admin session is required"),
+        ERROR_INVALID_AUTH_RESPONSE => Some("the signed challenge does not grant an admin session"),
+        ERROR_INVALID_BATCH => Some("the batch breaks the batch transfer rules"),
+        ERROR_INVALID_CHECKPOINT => Some("the checkpoint hash could not be decoded"),
+        ERROR_CHECKPOINT_CONFLICT => Some("the checkpoint conflicts with the local DAG"),
+        ERROR_UNABLE_TO_WRITE_CHECKPOINTS => {
+            Some("the checkpoints could not be written to the disk")
+        }
+        ERROR_UNABLE_TO_WRITE_VOTING_POLICY => {
+            Some("the voting policy could not be written to the disk")
+        }
+        ERROR_BATCH_TOO_LARGE => Some("the batch holds more calls than the node answers at once"),
+        ERROR_INVALID_REPLAY_CURSOR => {
+            Some("the replay cursor is malformed, or no longer matches the DAG")
+        }
+        ERROR_INVALID_TIME_LOCK => Some("the time lock breaks the vesting rules"),
+        ERROR_NO_SUCH_CONTACT => Some("no contact has the name, and it isn't an address"),
+        ERROR_UNABLE_TO_READ_CONTACTS => Some("the address book could not be read"),
+        ERROR_UNABLE_TO_WRITE_CONTACTS => Some("the address book could not be written to the disk"),
+        ERROR_INVALID_CONTACT => {
+            Some("the contact's name is ambiguous, or its address is malformed")
+        }
+        ERROR_NO_TENANT => Some("an API key (or an admin session) is required"),
+        ERROR_ARCHIVE_UNSUPPORTED => {
+            Some("historical states are only kept by archive nodes (started with --archive)")
+        }
+        ERROR_NO_HISTORICAL_STATE => Some("the node isn't in the DAG, or hasn't been executed"),
+        ERROR_NO_UNCLAIMED_REWARDS => Some("the account hasn't any unclaimed rewards"),
+        ERROR_INVALID_QUERY => Some("the query's lower bounds exceed its upper bounds"),
+        ERROR_SPENDING_POLICY_VIOLATION => {
+            Some("the account's spending policy doesn't allow the transaction")
+        }
+        ERROR_CONFIRMATION_REQUIRED => {
+            Some("the transaction must be confirmed with the code in the node's log")
+        }
+        ERROR_UNABLE_TO_ACCESS_SPENDING_POLICY => {
+            Some("the spending policy could not be read from, or written to, the disk")
+        }
+        ERROR_REMOTE_SIGNER_FAILED => {
+            Some("the remote signer couldn't be reached, or refused to sign (see its log)")
+        }
+        ERROR_NO_SUCH_SUBSCRIPTION => Some("no such subscription is open (it may have expired)"),
+        ERROR_TOO_MANY_SUBSCRIPTIONS => Some("the node can't keep any more subscriptions open"),
+        ERROR_NO_SUCH_UNLOCK_SESSION => {
+            Some("no such unlock session is open (it may have expired)")
+        }
+        ERROR_INVALID_UNLOCK_DURATION => Some("the account can't be unlocked for that long"),
+        ERROR_STORAGE_EXHAUSTED => Some("the node's disk is too full to accept the write"),
+        ERROR_METHOD_NOT_EXPOSED => {
+            Some("the method isn't served on this API (it may be read-only)")
+        }
+        ERROR_NO_SUCH_EXPORT => Some("no such export is open (it may have expired)"),
+        ERROR_TOO_MANY_EXPORTS => Some("the node can't keep any more exports open"),
+        ERROR_UNEXPECTED_EXPORT_ACK => {
+            Some("the acknowledged cursor wasn't handed out with the export's last page")
+        }
+        ERROR_UNKNOWN_SHORT_ID => Some("no known hash begins with the short id"),
+        ERROR_AMBIGUOUS_SHORT_ID => Some("the short id begins more than one hash; give more of it"),
+        ERROR_MALFORMED_HASH => Some("the hash isn't base58-encoded, or is too short to look up"),
+        ERROR_INVALID_GAS_PRICE => Some("the gas price isn't a whole number of finks"),
+        ERROR_MEMPOOL_REJECTED => Some("the mempool refused the transaction"),
+        ERROR_ACCOUNT_ALREADY_LOCKED => Some("the account is already locked"),
+        ERROR_ACCOUNT_NOT_LOCKED => Some("the account isn't locked"),
+        ERROR_RATE_LIMITED => Some("the method has been called too often; try again shortly"),
+        ERROR_DISK_READ_ONLY => {
+            Some("the node's disk isn't taking writes, so it only serves reads")
+        }
+        ERROR_INVALID_BALANCE_PROOF => {
+            Some("the balance proof doesn't match the node's copy of the header")
+        }
+        ERROR_NO_SUCH_BALANCE => Some("the state doesn't hold a balance for the account"),
+        ERROR_INVALID_ADDRESS => Some("the address is malformed, or belongs to another network"),
+        _ => None,
+    }
+}
@@ -0,0 +1,179 @@
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+
+use super::{
+    super::super::{
+        common::lock::InstrumentedRwLock,
+        core::sys::{
+            conflicts::DoubleSpendAlert,
+            events::{EventError, EventFilter, PolledEvents},
+            system::System,
+        },
+    },
+    auth::RpcHandler,
+    client::{ClientError, RpcClient},
+    error,
+    failover::RetryPolicy,
+};
+
+use std::sync::Arc;
+
+/// The number of events handed out by a poll, unless told otherwise.
+pub const DEFAULT_POLL_LIMIT: usize = 100;
+
+/// Defines the standard SummerCash events RPC API. Subscribers choose which proposals, votes & transactions they want
+/// to hear about up front, so that the node only ever hands out the events that they care about.
+#[rpc]
+pub trait Events {
+    /// Opens a subscription to the events that pass the given filter, and gets its id.
+    #[rpc(name = "subscribe")]
+    fn subscribe(&self, filter: EventFilter) -> Result<String>;
+
+    /// Gets the events published since the given subscription was last polled.
+    #[rpc(name = "poll_subscription")]
+    fn poll(&self, id: String, limit: Option<usize>) -> Result<PolledEvents>;
+
+    /// Closes the given subscription.
+    #[rpc(name = "unsubscribe")]
+    fn unsubscribe(&self, id: String) -> Result<()>;
+
+    /// Gets the most recent double spends seen by the node, newest first.
+    #[rpc(name = "get_double_spends")]
+    fn get_double_spends(&self, limit: Option<usize>) -> Result<Vec<DoubleSpendAlert>>;
+}
+
+/// An implementation of the events API.
+pub struct EventsImpl {
+    pub(crate) runtime: Arc<InstrumentedRwLock<System>>,
+}
+
+impl Events for EventsImpl {
+    /// Opens a subscription to the events that pass the given filter, and gets its id.
+    fn subscribe(&self, filter: EventFilter) -> Result<String> {
+        if let Ok(mut rt) = self.runtime.write() {
+            rt.events.subscribe(filter).map_err(Self::map_err)
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets the events published since the given subscription was last polled.
+    fn poll(&self, id: String, limit: Option<usize>) -> Result<PolledEvents> {
+        if let Ok(mut rt) = self.runtime.write() {
+            rt.events
+                .poll(&id, limit.unwrap_or(DEFAULT_POLL_LIMIT))
+                .map_err(Self::map_err)
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Closes the given subscription.
+    fn unsubscribe(&self, id: String) -> Result<()> {
+        if let Ok(mut rt) = self.runtime.write() {
+            rt.events.unsubscribe(&id).map_err(Self::map_err)
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets the most recent double spends seen by the node, newest first.
+    fn get_double_spends(&self, limit: Option<usize>) -> Result<Vec<DoubleSpendAlert>> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.conflicts.recent(limit.unwrap_or(DEFAULT_POLL_LIMIT)))
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+}
+
+impl EventsImpl {
+    /// Registers the events service on the given RPC handler.
+    pub fn register(io: &mut RpcHandler, runtime: Arc<InstrumentedRwLock<System>>) {
+        // Register this service on the IO handler
+        io.extend_with(Self { runtime }.to_delegate());
+    }
+
+    /// Converts the given event feed error into an RPC error.
+    pub(crate) fn map_err(e: EventError) -> Error {
+        match e {
+            EventError::NoSuchSubscription { .. } => {
+                Error::new(ErrorCode::from(error::ERROR_NO_SUCH_SUBSCRIPTION))
+            }
+            EventError::TooManySubscriptions { .. } => {
+                Error::new(ErrorCode::from(error::ERROR_TOO_MANY_SUBSCRIPTIONS))
+            }
+        }
+    }
+}
+
+/// A client for the events API.
+pub struct Client {
+    /// The underlying JSON-RPC client
+    rpc: RpcClient,
+}
+
+impl From<RpcClient> for Client {
+    /// Builds a client on top of the given JSON-RPC client, sharing its connections.
+    fn from(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+}
+
+impl Client {
+    /// Initializes a new Client with the given remote URL, or comma-separated list of URLs for redundant nodes.
+    pub fn new(server_addr: &str) -> Self {
+        Self {
+            rpc: RpcClient::new(server_addr),
+        }
+    }
+
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.rpc = self.rpc.with_retry_policy(policy);
+
+        self
+    }
+
+    /// Opens a subscription to the events that pass the given filter, and gets its id.
+    pub async fn subscribe(
+        &self,
+        filter: &EventFilter,
+    ) -> std::result::Result<String, ClientError> {
+        self.rpc.call("subscribe", (filter,)).await
+    }
+
+    /// Gets the events published since the given subscription was last polled.
+    pub async fn poll(
+        &self,
+        id: &str,
+        limit: Option<usize>,
+    ) -> std::result::Result<PolledEvents, ClientError> {
+        self.rpc.call("poll_subscription", (id, limit)).await
+    }
+
+    /// Closes the given subscription.
+    pub async fn unsubscribe(&self, id: &str) -> std::result::Result<(), ClientError> {
+        self.rpc.call("unsubscribe", (id,)).await
+    }
+
+    /// Gets the most recent double spends seen by the node, newest first.
+    pub async fn get_double_spends(
+        &self,
+        limit: Option<usize>,
+    ) -> std::result::Result<Vec<DoubleSpendAlert>, ClientError> {
+        self.rpc.call("get_double_spends", (limit,)).await
+    }
+}
@@ -0,0 +1,294 @@
+use jsonrpc_core::response::Output;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use std::{
+    cmp,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// The name of the method requested by health checks. Method names starting with "rpc." are reserved by JSON-RPC, so
+/// no node implements it, but any node that's up will answer with an error.
+pub const HEALTH_CHECK_METHOD: &str = "rpc.health";
+
+/// An error encountered while making a request to a set of redundant nodes.
+#[derive(Debug, Fail)]
+pub enum FailoverError {
+    #[fail(display = "no RPC endpoints were given")]
+    NoEndpoints,
+    #[fail(
+        display = "none of the RPC endpoints ({}) answered after {} attempts; last error: {}",
+        endpoints, attempts, last_error
+    )]
+    Exhausted {
+        endpoints: String,  // Each of the endpoints that were tried
+        attempts: u32,      // The number of passes made over the endpoints
+        last_error: String, // The error returned by the last endpoint tried
+    },
+}
+
+/// How hard a client tries to reach a node before giving up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// The number of passes made over every endpoint before a request fails
+    pub attempts: u32,
+
+    /// How long to wait after the first failed pass; each subsequent wait is twice as long
+    pub initial_backoff: Duration,
+
+    /// The longest that the client will wait between passes
+    pub max_backoff: Duration,
+
+    /// How long an endpoint that failed is passed over before it's tried first again
+    pub recheck_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Builds a policy making three passes, waiting 250ms and then 500ms between them.
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(4),
+            recheck_after: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Implement a set of retry policy helper methods.
+impl RetryPolicy {
+    /// Gets how long to wait after the given failed pass (counting from zero).
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - The index of the pass that failed
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        cmp::min(
+            self.initial_backoff
+                .checked_mul(1 << cmp::min(attempt, 16))
+                .unwrap_or(self.max_backoff),
+            self.max_backoff,
+        )
+    }
+}
+
+/// A set of redundant nodes serving the same RPC API. Requests go to the first healthy node, and fail over to the
+/// others when a node can't be reached.
+pub struct Upstream {
+    /// The URL of each of the nodes, in order of preference
+    endpoints: Vec<String>,
+
+    /// When each of the nodes last failed to answer, if it failed the last time it was tried
+    failed_at: Mutex<Vec<Option<Instant>>>,
+
+    /// How hard to try before giving up
+    policy: RetryPolicy,
+}
+
+/// Implement a set of upstream helper methods.
+impl Upstream {
+    /// Initializes a new upstream from a comma-separated list of node URLs (e.g.
+    /// "http://10.0.0.1:8080,http://10.0.0.2:8080").
+    ///
+    /// # Arguments
+    ///
+    /// * `server_addrs` - The URLs of each of the nodes, in order of preference
+    pub fn new(server_addrs: &str) -> Self {
+        let endpoints: Vec<String> = server_addrs
+            .split(',')
+            .map(|addr| addr.trim().trim_end_matches('/').to_owned())
+            .filter(|addr| !addr.is_empty())
+            .collect();
+
+        Self {
+            failed_at: Mutex::new(vec![None; endpoints.len()]),
+            endpoints,
+            policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Uses the given retry policy for each of the upstream's requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+
+        self
+    }
+
+    /// Gets the URL of each of the nodes, in order of preference.
+    pub fn endpoints(&self) -> &[String] {
+        &self.endpoints
+    }
+
+    /// Checks whether or not the node at the given index answered the last time it was tried.
+    ///
+    /// # Arguments
+    ///
+    /// * `i` - The index of the node
+    pub fn is_healthy(&self, i: usize) -> bool {
+        self.failed_at
+            .lock()
+            .map(|failed_at| failed_at.get(i).map_or(false, Option::is_none))
+            .unwrap_or(false)
+    }
+
+    /// Gets the order in which the nodes should be tried: healthy nodes in order of preference, then nodes that
+    /// haven't failed recently, and finally nodes that failed most recently.
+    fn order(&self) -> Vec<usize> {
+        let failed_at = match self.failed_at.lock() {
+            Ok(failed_at) => failed_at.clone(),
+            Err(_) => return (0..self.endpoints.len()).collect(),
+        };
+
+        let mut order: Vec<usize> = (0..self.endpoints.len()).collect();
+        order.sort_by_key(|&i| match failed_at[i] {
+            None => (0, Duration::from_secs(0)),
+            Some(at) if at.elapsed() >= self.policy.recheck_after => (1, Duration::from_secs(0)),
+            Some(at) => (2, self.policy.recheck_after - at.elapsed()),
+        });
+
+        order
+    }
+
+    /// Records whether or not the node at the given index answered.
+    fn record(&self, i: usize, healthy: bool) {
+        if let Ok(mut failed_at) = self.failed_at.lock() {
+            failed_at[i] = if healthy { None } else { Some(Instant::now()) };
+        }
+    }
+
+    /// Posts the given JSON-RPC request (or batch of requests) to the first node that answers, and returns its
+    /// response (an Output for a single request, or a Response for a batch). Nodes that can't be reached, or that
+    /// answer with something other than JSON-RPC, are passed over; errors returned by a node through JSON-RPC are
+    /// returned as-is. A request may reach more than one node if a node fails after receiving it.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The HTTP client that the request should be made with
+    /// * `session` - The token of the admin session that the request should be made under, if any
+    /// * `body` - The JSON-RPC request
+    pub async fn post<B: Serialize, R: DeserializeOwned>(
+        &self,
+        client: &reqwest::Client,
+        session: Option<&str>,
+        body: &B,
+    ) -> Result<R, FailoverError> {
+        if self.endpoints.is_empty() {
+            return Err(FailoverError::NoEndpoints);
+        }
+
+        let mut last_error = String::new(); // The most recent reason that a node failed
+
+        for attempt in 0..self.policy.attempts {
+            for i in self.order() {
+                let mut req = client.post(&self.endpoints[i]).json(body);
+
+                // Prove that we're allowed to call admin methods, if we are
+                if let Some(session) = session {
+                    req = req.bearer_auth(session);
+                }
+
+                let res = match req.send().await {
+                    Ok(resp) => resp.json::<R>().await,
+                    Err(e) => Err(e),
+                };
+
+                match res {
+                    Ok(output) => {
+                        self.record(i, true);
+
+                        return Ok(output);
+                    }
+                    Err(e) => {
+                        debug!("RPC endpoint {} failed: {}", self.endpoints[i], e);
+
+                        self.record(i, false);
+                        last_error = e.to_string();
+                    }
+                }
+            }
+
+            // Give the nodes some time to come back before trying again
+            if attempt + 1 < self.policy.attempts {
+                tokio::time::delay_for(self.policy.backoff(attempt)).await;
+            }
+        }
+
+        Err(FailoverError::Exhausted {
+            endpoints: self.endpoints.join(", "),
+            attempts: self.policy.attempts,
+            last_error,
+        })
+    }
+
+    /// Probes each of the nodes, and returns whether or not each one is up, in order of preference. The upstream
+    /// remembers the result, so that requests skip nodes that are down.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The HTTP client that the probes should be made with
+    pub async fn check_health(&self, client: &reqwest::Client) -> Vec<bool> {
+        let probe = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": HEALTH_CHECK_METHOD,
+            "params": [],
+            "id": "",
+        });
+
+        let mut health = Vec::with_capacity(self.endpoints.len());
+
+        for (i, endpoint) in self.endpoints.iter().enumerate() {
+            // Any JSON-RPC answer, even an error, means that the node is up
+            let healthy = match client.post(endpoint).json(&probe).send().await {
+                Ok(resp) => resp.json::<Output>().await.is_ok(),
+                Err(_) => false,
+            };
+
+            self.record(i, healthy);
+            health.push(healthy);
+        }
+
+        health
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_new() {
+        let upstream = Upstream::new("http://a:8080/, http://b:8080,,");
+
+        assert_eq!(upstream.endpoints(), &["http://a:8080", "http://b:8080"]);
+        assert!(upstream.is_healthy(0) && upstream.is_healthy(1)); // Nodes are assumed to be up at first
+    }
+
+    #[test]
+    fn test_order() {
+        let upstream = Upstream::new("http://a,http://b,http://c");
+
+        upstream.record(0, false); // The preferred node goes down
+        assert_eq!(upstream.order(), vec![1, 2, 0]);
+
+        upstream.record(1, false); // So does the next one
+        assert_eq!(upstream.order(), vec![2, 0, 1]); // The node that failed first is retried first
+
+        upstream.record(0, true); // The preferred node comes back
+        assert_eq!(upstream.order(), vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(250));
+        assert_eq!(policy.backoff(1), Duration::from_millis(500));
+        assert_eq!(policy.backoff(10), policy.max_backoff); // Waits are capped
+        assert_eq!(policy.backoff(u32::max_value()), policy.max_backoff);
+    }
+}
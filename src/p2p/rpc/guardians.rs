@@ -0,0 +1,327 @@
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+
+use super::{
+    super::super::{
+        common::{address::Address, lock::InstrumentedRwLock},
+        core::{
+            sys::system::System,
+            types::{
+                guardians::{self, GuardianInstruction, GuardianRecord},
+                transaction::Transaction,
+            },
+        },
+    },
+    auth::RpcHandler,
+    client::{ClientError, RpcClient},
+    dag::DagImpl,
+    error,
+    failover::RetryPolicy,
+};
+
+use num::BigUint;
+
+use std::sync::Arc;
+
+/// Defines the standard SummerCash account recovery RPC API. Each method building a transaction returns it unsigned;
+/// it must be persisted, signed, and published like any other transaction.
+#[rpc]
+pub trait Guardians {
+    /// Builds a transaction registering the guardians able to recover the given account.
+    #[rpc(name = "register_guardians")]
+    fn register_guardians(
+        &self,
+        account: String,
+        guardians: Vec<String>,
+        threshold: u32,
+        timelock_secs: u64,
+    ) -> Result<Transaction>;
+
+    /// Builds a transaction, sent by one of the account's guardians, beginning the recovery of the given account to
+    /// a new owner.
+    #[rpc(name = "initiate_recovery")]
+    fn initiate_recovery(
+        &self,
+        guardian: String,
+        account: String,
+        new_owner: String,
+    ) -> Result<Transaction>;
+
+    /// Builds a transaction, sent by one of the account's guardians, approving the pending recovery of the given
+    /// account.
+    #[rpc(name = "approve_recovery")]
+    fn approve_recovery(
+        &self,
+        guardian: String,
+        account: String,
+        new_owner: String,
+    ) -> Result<Transaction>;
+
+    /// Builds a transaction, sent by the account itself, calling off its pending recovery.
+    #[rpc(name = "cancel_recovery")]
+    fn cancel_recovery(&self, account: String) -> Result<Transaction>;
+
+    /// Builds a transaction, sent by one of the account's guardians, reassigning the recovered account to its new
+    /// owner once enough guardians have approved and the timelock has passed.
+    #[rpc(name = "execute_recovery")]
+    fn execute_recovery(&self, guardian: String, account: String) -> Result<Transaction>;
+
+    /// Gets the guardians registered by the given account, along with any recovery in progress.
+    #[rpc(name = "get_guardians")]
+    fn get(&self, account: String) -> Result<GuardianRecord>;
+}
+
+/// An implementation of the account recovery API.
+pub struct GuardiansImpl {
+    pub(crate) runtime: Arc<InstrumentedRwLock<System>>,
+}
+
+impl Guardians for GuardiansImpl {
+    /// Builds a transaction registering the guardians able to recover the given account.
+    fn register_guardians(
+        &self,
+        account: String,
+        guardians: Vec<String>,
+        threshold: u32,
+        timelock_secs: u64,
+    ) -> Result<Transaction> {
+        let guardians = guardians
+            .iter()
+            .map(|guardian| DagImpl::parse_address(&self.runtime, guardian))
+            .collect::<Result<Vec<Address>>>()?;
+
+        self.build(
+            DagImpl::parse_address(&self.runtime, &account)?,
+            GuardianInstruction::Register {
+                guardians,
+                threshold,
+                timelock_secs,
+            },
+        )
+    }
+
+    /// Builds a transaction beginning the recovery of the given account to a new owner.
+    fn initiate_recovery(
+        &self,
+        guardian: String,
+        account: String,
+        new_owner: String,
+    ) -> Result<Transaction> {
+        self.build(
+            DagImpl::parse_address(&self.runtime, &guardian)?,
+            GuardianInstruction::InitiateRecovery {
+                account: DagImpl::parse_address(&self.runtime, &account)?,
+                new_owner: DagImpl::parse_address(&self.runtime, &new_owner)?,
+            },
+        )
+    }
+
+    /// Builds a transaction approving the pending recovery of the given account.
+    fn approve_recovery(
+        &self,
+        guardian: String,
+        account: String,
+        new_owner: String,
+    ) -> Result<Transaction> {
+        self.build(
+            DagImpl::parse_address(&self.runtime, &guardian)?,
+            GuardianInstruction::ApproveRecovery {
+                account: DagImpl::parse_address(&self.runtime, &account)?,
+                new_owner: DagImpl::parse_address(&self.runtime, &new_owner)?,
+            },
+        )
+    }
+
+    /// Builds a transaction calling off the pending recovery of the given account.
+    fn cancel_recovery(&self, account: String) -> Result<Transaction> {
+        self.build(
+            DagImpl::parse_address(&self.runtime, &account)?,
+            GuardianInstruction::CancelRecovery,
+        )
+    }
+
+    /// Builds a transaction reassigning the recovered account to its new owner.
+    fn execute_recovery(&self, guardian: String, account: String) -> Result<Transaction> {
+        let account = DagImpl::parse_address(&self.runtime, &account)?;
+
+        // The new owner has already been settled on by the guardians
+        let new_owner = self
+            .get(account.to_str())?
+            .recovery
+            .map(|recovery| recovery.new_owner)
+            .ok_or_else(|| {
+                Error::new(ErrorCode::from(error::ERROR_INVALID_GUARDIAN_INSTRUCTION))
+            })?;
+
+        self.build(
+            DagImpl::parse_address(&self.runtime, &guardian)?,
+            GuardianInstruction::ExecuteRecovery { account, new_owner },
+        )
+    }
+
+    /// Gets the guardians registered by the given account, along with any recovery in progress.
+    fn get(&self, account: String) -> Result<GuardianRecord> {
+        let account = DagImpl::parse_address(&self.runtime, &account)?;
+
+        if let Ok(rt) = self.runtime.read() {
+            rt.ledger
+                .get_guardians(account)
+                .ok_or_else(|| Error::new(ErrorCode::from(error::ERROR_NO_SUCH_GUARDIANS)))
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+}
+
+impl GuardiansImpl {
+    /// Registers the account recovery service on the given RPC handler.
+    pub fn register(io: &mut RpcHandler, runtime: Arc<InstrumentedRwLock<System>>) {
+        // Register this service on the IO handler
+        io.extend_with(Self { runtime }.to_delegate());
+    }
+
+    /// Builds a transaction carrying the given guardian instruction from the given sender, making sure that the
+    /// instruction would currently be accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The address that the instruction should be sent from
+    /// * `instruction` - The instruction that the transaction should carry
+    fn build(&self, sender: Address, instruction: GuardianInstruction) -> Result<Transaction> {
+        // Get a lock on the client's runtime
+        let rt = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        // Use the nonce directly following the sender's last transaction
+        let nonce = DagImpl::head_entry(&rt)?
+            .data
+            .nonces
+            .get(&sender.to_str())
+            .map_or(0, |last_nonce| last_nonce + 1);
+
+        let tx = DagImpl::build_tx(
+            &rt,
+            nonce,
+            sender,
+            guardians::registry_address(),
+            BigUint::default(),
+            &instruction.to_payload(),
+        )?;
+
+        // Don't hand out a transaction that validators would reject
+        let record = rt.ledger.get_guardians(instruction.account(sender));
+        if let Err(e) = guardians::check(record.as_ref(), &tx, &instruction) {
+            debug!("Refusing to build guardian instruction: {}", e);
+
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_INVALID_GUARDIAN_INSTRUCTION,
+            )));
+        }
+
+        Ok(tx)
+    }
+}
+
+/// A client for the account recovery API.
+pub struct Client {
+    /// The underlying JSON-RPC client
+    rpc: RpcClient,
+}
+
+impl From<RpcClient> for Client {
+    /// Builds a client on top of the given JSON-RPC client, sharing its connections.
+    fn from(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+}
+
+impl Client {
+    /// Initializes a new Client with the given remote URL, or comma-separated list of URLs for redundant nodes.
+    pub fn new(server_addr: &str) -> Self {
+        Self {
+            rpc: RpcClient::new(server_addr),
+        }
+    }
+
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.rpc = self.rpc.with_retry_policy(policy);
+
+        self
+    }
+
+    /// Builds a transaction registering the guardians able to recover the given account.
+    pub async fn register_guardians(
+        &self,
+        account: String,
+        guardians: Vec<String>,
+        threshold: u32,
+        timelock_secs: u64,
+    ) -> std::result::Result<Transaction, ClientError> {
+        self.rpc
+            .call(
+                "register_guardians",
+                (&account, &guardians, threshold, timelock_secs),
+            )
+            .await
+    }
+
+    /// Builds a transaction beginning the recovery of the given account to a new owner.
+    pub async fn initiate_recovery(
+        &self,
+        guardian: String,
+        account: String,
+        new_owner: String,
+    ) -> std::result::Result<Transaction, ClientError> {
+        self.rpc
+            .call("initiate_recovery", (&guardian, &account, &new_owner))
+            .await
+    }
+
+    /// Builds a transaction approving the pending recovery of the given account.
+    pub async fn approve_recovery(
+        &self,
+        guardian: String,
+        account: String,
+        new_owner: String,
+    ) -> std::result::Result<Transaction, ClientError> {
+        self.rpc
+            .call("approve_recovery", (&guardian, &account, &new_owner))
+            .await
+    }
+
+    /// Builds a transaction calling off the pending recovery of the given account.
+    pub async fn cancel_recovery(
+        &self,
+        account: String,
+    ) -> std::result::Result<Transaction, ClientError> {
+        self.rpc.call("cancel_recovery", (&account,)).await
+    }
+
+    /// Builds a transaction reassigning the recovered account to its new owner.
+    pub async fn execute_recovery(
+        &self,
+        guardian: String,
+        account: String,
+    ) -> std::result::Result<Transaction, ClientError> {
+        self.rpc
+            .call("execute_recovery", (&guardian, &account))
+            .await
+    }
+
+    /// Gets the guardians registered by the given account, along with any recovery in progress.
+    pub async fn get(&self, account: String) -> std::result::Result<GuardianRecord, ClientError> {
+        self.rpc.call("get_guardians", (&account,)).await
+    }
+}
@@ -1,5 +1,19 @@
 /// Implements a JSON-RPC API for a SummerCash client.
+pub mod access;
 pub mod accounts;
+pub mod auth;
+pub mod batch;
+pub mod client;
+pub mod contacts;
 pub mod dag;
 pub mod error;
+pub mod events;
+pub mod failover;
+pub mod guardians;
+pub mod notes;
+pub mod peers;
+pub mod rewards;
 pub mod runtime;
+pub mod subscriptions;
+pub mod tenants;
+pub mod vesting;
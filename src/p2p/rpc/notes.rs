@@ -0,0 +1,268 @@
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    super::super::{
+        common::{
+            address::Address,
+            lock::InstrumentedRwLock,
+            notes::{Note, NoteStore},
+        },
+        core::sys::system::System,
+        crypto::hash::Hash,
+    },
+    auth::RpcHandler,
+    client::{ClientError, RpcClient},
+    error,
+    failover::RetryPolicy,
+};
+
+use num::BigUint;
+
+use std::{collections::HashMap, sync::Arc};
+
+/// A transaction in the DAG, along with any local notes attached to it or to the accounts involved in it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AnnotatedTransaction {
+    /// The hash of the transaction
+    pub hash: Hash,
+    /// The sender of the transaction
+    pub sender: Address,
+    /// The recipient of the transaction
+    pub recipient: Address,
+    /// The number of finks sent in the transaction
+    pub value: BigUint,
+    /// The note attached to the transaction itself
+    pub note: Option<Note>,
+    /// The note attached to the sender's address
+    pub sender_note: Option<Note>,
+    /// The note attached to the recipient's address
+    pub recipient_note: Option<Note>,
+}
+
+/// Defines the standard SummerCash notes RPC API. Notes are kept locally, in the given data directory, and are never
+/// published to the network.
+#[rpc]
+pub trait Notes {
+    /// Attaches a label and memo to the given transaction hash or address, replacing any existing note.
+    #[rpc(name = "set_note")]
+    fn set(&self, subject: Hash, label: String, memo: String, data_dir: String) -> Result<Note>;
+
+    /// Gets the note attached to the given transaction hash or address.
+    #[rpc(name = "get_note")]
+    fn get(&self, subject: Hash, data_dir: String) -> Result<Note>;
+
+    /// Removes the note attached to the given transaction hash or address.
+    #[rpc(name = "delete_note")]
+    fn delete(&self, subject: Hash, data_dir: String) -> Result<()>;
+
+    /// Gets each of the notes in the given data directory.
+    #[rpc(name = "list_notes")]
+    fn list(&self, data_dir: String) -> Result<HashMap<Hash, Note>>;
+
+    /// Gets each of the transactions stored in the currently attached DAG, along with their notes.
+    #[rpc(name = "list_annotated_transactions")]
+    fn list_annotated_txs(&self, data_dir: String) -> Result<Vec<AnnotatedTransaction>>;
+}
+
+/// An implementation of the notes API.
+pub struct NotesImpl {
+    pub(crate) runtime: Arc<InstrumentedRwLock<System>>,
+}
+
+impl Notes for NotesImpl {
+    /// Attaches a label and memo to the given transaction hash or address, replacing any existing note.
+    fn set(&self, subject: Hash, label: String, memo: String, data_dir: String) -> Result<Note> {
+        // Notes can wait until the node's disk has room for them
+        match self.runtime.write() {
+            Ok(mut rt) => rt
+                .storage
+                .admit_noncritical()
+                .map_err(|_| Error::new(ErrorCode::from(error::ERROR_STORAGE_EXHAUSTED)))?,
+            Err(_) => {
+                return Err(Error::new(ErrorCode::from(
+                    error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+                )))
+            }
+        }
+
+        let mut store = Self::read_store(&data_dir)?; // Open the notes store
+
+        let note = Note { label, memo }; // Make the note
+        store.set(subject, note.clone());
+
+        // Persist the store
+        match store.write_to_disk_at_data_directory(&data_dir) {
+            Ok(_) => Ok(note),
+            Err(_) => Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_WRITE_NOTES,
+            ))),
+        }
+    }
+
+    /// Gets the note attached to the given transaction hash or address.
+    fn get(&self, subject: Hash, data_dir: String) -> Result<Note> {
+        Self::read_store(&data_dir)?
+            .get(&subject)
+            .cloned()
+            .ok_or_else(|| Error::new(ErrorCode::from(error::ERROR_NO_SUCH_NOTE)))
+    }
+
+    /// Removes the note attached to the given transaction hash or address.
+    fn delete(&self, subject: Hash, data_dir: String) -> Result<()> {
+        let mut store = Self::read_store(&data_dir)?; // Open the notes store
+
+        // Make sure there's actually something to remove
+        if store.remove(&subject).is_none() {
+            return Err(Error::new(ErrorCode::from(error::ERROR_NO_SUCH_NOTE)));
+        }
+
+        // Persist the store
+        store
+            .write_to_disk_at_data_directory(&data_dir)
+            .map_err(|_| Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_WRITE_NOTES)))
+    }
+
+    /// Gets each of the notes in the given data directory.
+    fn list(&self, data_dir: String) -> Result<HashMap<Hash, Note>> {
+        Ok(Self::read_store(&data_dir)?.notes)
+    }
+
+    /// Gets each of the transactions stored in the currently attached DAG, along with their notes.
+    fn list_annotated_txs(&self, data_dir: String) -> Result<Vec<AnnotatedTransaction>> {
+        let store = Self::read_store(&data_dir)?; // Open the notes store
+
+        if let Ok(rt) = self.runtime.read() {
+            // Pair each of the transactions in the DAG with its notes
+            Ok((0..rt.ledger.nodes.len())
+                .filter_map(|i| rt.ledger.get_pure(i).ok().flatten())
+                .map(|node| {
+                    let data = node.transaction.transaction_data;
+
+                    AnnotatedTransaction {
+                        hash: node.hash,
+                        note: store.get(&node.hash).cloned(),
+                        sender_note: store.get(&data.sender).cloned(),
+                        recipient_note: store.get(&data.recipient).cloned(),
+                        sender: data.sender,
+                        recipient: data.recipient,
+                        value: data.value,
+                    }
+                })
+                .collect())
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+}
+
+impl NotesImpl {
+    /// Registers the notes service on the given RPC handler.
+    pub fn register(io: &mut RpcHandler, runtime: Arc<InstrumentedRwLock<System>>) {
+        // Register this service on the IO handler
+        io.extend_with(Self { runtime }.to_delegate());
+    }
+
+    /// Opens the notes store in the given data directory.
+    fn read_store(data_dir: &str) -> Result<NoteStore> {
+        NoteStore::read_from_disk_at_data_directory(data_dir)
+            .map_err(|_| Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_READ_NOTES)))
+    }
+}
+
+/// A client for the notes API.
+pub struct Client {
+    /// The underlying JSON-RPC client
+    rpc: RpcClient,
+}
+
+impl From<RpcClient> for Client {
+    /// Builds a client on top of the given JSON-RPC client, sharing its connections.
+    fn from(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+}
+
+impl Client {
+    /// Initializes a new Client with the given remote URL, or comma-separated list of URLs for redundant nodes.
+    pub fn new(server_addr: &str) -> Self {
+        Self {
+            rpc: RpcClient::new(server_addr),
+        }
+    }
+
+    /// Makes each of the client's requests under the admin session, or with the API key, given.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The token of the admin session, or the API key, if there is one
+    pub fn with_session(mut self, session: Option<String>) -> Self {
+        self.rpc = self.rpc.with_session(session);
+
+        self
+    }
+
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.rpc = self.rpc.with_retry_policy(policy);
+
+        self
+    }
+
+    /// Attaches a label and memo to the given transaction hash or address.
+    pub async fn set(
+        &self,
+        subject: Hash,
+        label: String,
+        memo: String,
+        data_dir: &str,
+    ) -> std::result::Result<Note, ClientError> {
+        self.rpc
+            .call("set_note", (&subject, &label, &memo, data_dir))
+            .await
+    }
+
+    /// Gets the note attached to the given transaction hash or address.
+    pub async fn get(
+        &self,
+        subject: Hash,
+        data_dir: &str,
+    ) -> std::result::Result<Note, ClientError> {
+        self.rpc.call("get_note", (&subject, data_dir)).await
+    }
+
+    /// Removes the note attached to the given transaction hash or address.
+    pub async fn delete(
+        &self,
+        subject: Hash,
+        data_dir: &str,
+    ) -> std::result::Result<(), ClientError> {
+        self.rpc.call("delete_note", (&subject, data_dir)).await
+    }
+
+    /// Gets each of the notes in the given data directory.
+    pub async fn list(
+        &self,
+        data_dir: &str,
+    ) -> std::result::Result<HashMap<Hash, Note>, ClientError> {
+        self.rpc.call("list_notes", (data_dir,)).await
+    }
+
+    /// Gets each of the transactions in the DAG, along with their notes.
+    pub async fn list_annotated_txs(
+        &self,
+        data_dir: &str,
+    ) -> std::result::Result<Vec<AnnotatedTransaction>, ClientError> {
+        self.rpc
+            .call("list_annotated_transactions", (data_dir,))
+            .await
+    }
+}
@@ -0,0 +1,208 @@
+use chrono::Utc; // Import time utilities
+
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+
+use super::{
+    super::{
+        super::{common::lock::InstrumentedRwLock, core::sys::system::System},
+        connections::{ConnectionTable, PeerPage},
+        sync_peers::{SyncPeer, SyncPeerTable, SyncStatus},
+    },
+    auth::RpcHandler,
+    client::{ClientError, RpcClient},
+    error,
+    failover::RetryPolicy,
+};
+
+use std::{
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+/// The number of times that the node's peers can be listed in a single window, across every caller, so that
+/// dashboards polling large peer sets can't keep the node's connection table locked.
+pub const PEER_LISTINGS_PER_WINDOW: u32 = 20;
+
+/// The length of each window that peer listings are counted over, in seconds.
+pub const PEER_LISTING_WINDOW_SECS: u64 = 1;
+
+/// Defines the standard SummerCash peers RPC API.
+#[rpc]
+pub trait Peers {
+    /// Gets what the node knows about how synchronization with each of its peers is going, those whose records were
+    /// refused most often first.
+    #[rpc(name = "get_sync_peers")]
+    fn get_sync_peers(&self) -> Result<Vec<SyncPeer>>;
+
+    /// Gets how far along the node is in synchronizing its DAG with the network, and how well it's connected to the
+    /// network (isolated, connecting, or synced).
+    #[rpc(name = "get_sync_status")]
+    fn get_sync_status(&self) -> Result<SyncStatus>;
+
+    /// Gets a page of the node's connected peers, oldest connections first, along with the address, direction, age &
+    /// round-trip time of each connection, and what each peer identified itself as.
+    #[rpc(name = "get_peers")]
+    fn get_peers(&self, offset: Option<usize>, limit: Option<usize>) -> Result<PeerPage>;
+}
+
+/// An implementation of the peers API.
+pub struct PeersImpl {
+    pub(crate) sync_peers: Arc<RwLock<SyncPeerTable>>,
+    pub(crate) connections: Arc<RwLock<ConnectionTable>>,
+    pub(crate) runtime: Arc<InstrumentedRwLock<System>>,
+
+    /// The start of the current peer listing window, and the number of listings made in it
+    listings: Mutex<(Instant, u32)>,
+}
+
+impl Peers for PeersImpl {
+    /// Gets what the node knows about how synchronization with each of its peers is going.
+    fn get_sync_peers(&self) -> Result<Vec<SyncPeer>> {
+        if let Ok(sync_peers) = self.sync_peers.read() {
+            Ok(sync_peers.list())
+        } else {
+            debug!("Unable to obtain a lock on the client's sync peers");
+
+            // Return the corresponding error
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets how far along the node is in synchronizing its DAG with the network.
+    fn get_sync_status(&self) -> Result<SyncStatus> {
+        if let (Ok(sync_peers), Ok(rt)) = (self.sync_peers.read(), self.runtime.read()) {
+            Ok(sync_peers.status(
+                rt.ledger.nodes.len(),
+                rt.ledger.nodes.last().map(|node| node.hash),
+                |hash| rt.ledger.hash_routes.contains_key(hash),
+            ))
+        } else {
+            debug!("Unable to obtain a lock on the client's sync peers or runtime");
+
+            // Return the corresponding error
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets a page of the node's connected peers.
+    fn get_peers(&self, offset: Option<usize>, limit: Option<usize>) -> Result<PeerPage> {
+        if !self.take_listing() {
+            return Err(Error::new(ErrorCode::from(error::ERROR_RATE_LIMITED)));
+        }
+
+        if let Ok(connections) = self.connections.read() {
+            Ok(connections.page(offset.unwrap_or_default(), limit, Utc::now()))
+        } else {
+            debug!("Unable to obtain a lock on the client's connections");
+
+            // Return the corresponding error
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+}
+
+impl PeersImpl {
+    /// Registers the peers service on the given RPC handler.
+    pub fn register(
+        io: &mut RpcHandler,
+        sync_peers: Arc<RwLock<SyncPeerTable>>,
+        connections: Arc<RwLock<ConnectionTable>>,
+        runtime: Arc<InstrumentedRwLock<System>>,
+    ) {
+        // Register this service on the IO handler
+        io.extend_with(
+            Self {
+                sync_peers,
+                connections,
+                runtime,
+                listings: Mutex::new((Instant::now(), 0)),
+            }
+            .to_delegate(),
+        );
+    }
+
+    /// Counts a peer listing against the current window, if the window has room for it. Results in whether or not
+    /// the listing may go ahead.
+    fn take_listing(&self) -> bool {
+        let mut listings = match self.listings.lock() {
+            Ok(listings) => listings,
+            Err(_) => return false,
+        };
+
+        // Start a new window once the current one has passed
+        if listings.0.elapsed() >= Duration::from_secs(PEER_LISTING_WINDOW_SECS) {
+            *listings = (Instant::now(), 0);
+        }
+
+        if listings.1 >= PEER_LISTINGS_PER_WINDOW {
+            return false;
+        }
+        listings.1 += 1;
+
+        true
+    }
+}
+
+/// A client for the SummerCash peers API.
+pub struct Client {
+    /// The underlying JSON-RPC client
+    rpc: RpcClient,
+}
+
+impl From<RpcClient> for Client {
+    /// Builds a client on top of the given JSON-RPC client, sharing its connections.
+    fn from(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+}
+
+impl Client {
+    /// Initializes a new Client with the given remote URL, or comma-separated list of URLs for redundant nodes.
+    pub fn new(server_addr: &str) -> Self {
+        Self {
+            rpc: RpcClient::new(server_addr),
+        }
+    }
+
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.rpc = self.rpc.with_retry_policy(policy);
+
+        self
+    }
+
+    /// Gets what the node knows about how synchronization with each of its peers is going.
+    pub async fn get_sync_peers(&self) -> std::result::Result<Vec<SyncPeer>, ClientError> {
+        self.rpc.call("get_sync_peers", ()).await
+    }
+
+    /// Gets how far along the node is in synchronizing its DAG with the network.
+    pub async fn get_sync_status(&self) -> std::result::Result<SyncStatus, ClientError> {
+        self.rpc.call("get_sync_status", ()).await
+    }
+
+    /// Gets a page of the node's connected peers.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The number of peers that should be skipped
+    /// * `limit` - The largest number of peers that should be listed, if not the default page size
+    pub async fn get_peers(
+        &self,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> std::result::Result<PeerPage, ClientError> {
+        self.rpc.call("get_peers", (Some(offset), limit)).await
+    }
+}
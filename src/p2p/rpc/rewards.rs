@@ -0,0 +1,154 @@
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+
+use super::{
+    super::super::{
+        common::lock::InstrumentedRwLock,
+        core::{
+            sys::system::System,
+            types::{
+                rewards::{self, RewardsReport},
+                transaction::Transaction,
+            },
+        },
+    },
+    auth::RpcHandler,
+    client::{ClientError, RpcClient},
+    dag::DagImpl,
+    error,
+    failover::RetryPolicy,
+};
+
+use num::{BigUint, Zero};
+
+use std::sync::Arc;
+
+/// Defines the standard SummerCash validator rewards RPC API.
+#[rpc]
+pub trait Rewards {
+    /// Gets the rewards earned by the given voting account: those minted but not yet claimed, and the votes counted
+    /// towards the current epoch's reward so far.
+    #[rpc(name = "get_rewards")]
+    fn get_rewards(&self, account: String) -> Result<RewardsReport>;
+
+    /// Builds an unsigned transaction, sent by the given account to itself, moving each of its unclaimed rewards into
+    /// its balance. It must be persisted, signed, and published like any other transaction.
+    #[rpc(name = "claim_rewards")]
+    fn claim_rewards(&self, account: String) -> Result<Transaction>;
+}
+
+/// An implementation of the validator rewards API.
+pub struct RewardsImpl {
+    pub(crate) runtime: Arc<InstrumentedRwLock<System>>,
+}
+
+impl Rewards for RewardsImpl {
+    /// Gets the rewards earned by the given voting account.
+    fn get_rewards(&self, account: String) -> Result<RewardsReport> {
+        let account = DagImpl::parse_address(&self.runtime, &account)?;
+
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.rewards_report(account))
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Builds a transaction claiming each of the given account's unclaimed rewards.
+    fn claim_rewards(&self, account: String) -> Result<Transaction> {
+        let account = DagImpl::parse_address(&self.runtime, &account)?;
+
+        // Get a lock on the client's runtime
+        let rt = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        let head = DagImpl::head_entry(&rt)?;
+
+        // Don't hand out a claim that wouldn't pay anything
+        if rewards::unclaimed(&head.data.balances, account).is_zero() {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_NO_UNCLAIMED_REWARDS,
+            )));
+        }
+
+        // Use the nonce directly following the account's last transaction
+        let nonce = head
+            .data
+            .nonces
+            .get(&account.to_str())
+            .map_or(0, |last_nonce| last_nonce + 1);
+
+        DagImpl::build_tx(
+            &rt,
+            nonce,
+            account,
+            account,
+            BigUint::zero(),
+            rewards::CLAIM_PAYLOAD,
+        )
+    }
+}
+
+impl RewardsImpl {
+    /// Registers the validator rewards service on the given RPC handler.
+    pub fn register(io: &mut RpcHandler, runtime: Arc<InstrumentedRwLock<System>>) {
+        // Register this service on the IO handler
+        io.extend_with(Self { runtime }.to_delegate());
+    }
+}
+
+/// A client for the validator rewards API.
+pub struct Client {
+    /// The underlying JSON-RPC client
+    rpc: RpcClient,
+}
+
+impl From<RpcClient> for Client {
+    /// Builds a client on top of the given JSON-RPC client, sharing its connections.
+    fn from(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+}
+
+impl Client {
+    /// Initializes a new Client with the given remote URL, or comma-separated list of URLs for redundant nodes.
+    pub fn new(server_addr: &str) -> Self {
+        Self {
+            rpc: RpcClient::new(server_addr),
+        }
+    }
+
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.rpc = self.rpc.with_retry_policy(policy);
+
+        self
+    }
+
+    /// Gets the rewards earned by the given voting account.
+    pub async fn get_rewards(
+        &self,
+        account: String,
+    ) -> std::result::Result<RewardsReport, ClientError> {
+        self.rpc.call("get_rewards", (&account,)).await
+    }
+
+    /// Builds an unsigned transaction claiming each of the given account's unclaimed rewards.
+    pub async fn claim_rewards(
+        &self,
+        account: String,
+    ) -> std::result::Result<Transaction, ClientError> {
+        self.rpc.call("claim_rewards", (&account,)).await
+    }
+}
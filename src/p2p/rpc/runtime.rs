@@ -1,28 +1,167 @@
-use jsonrpc_core::{response::Output, Error, ErrorCode, IoHandler, Result};
+use jsonrpc_core::{Error, ErrorCode, Result};
 use jsonrpc_derive::rpc;
 
-use serde::Deserialize;
-
 use super::{
-    super::super::core::sys::{proposal::Proposal, system::System},
+    super::{
+        super::{
+            accounts::account::{self, Account},
+            common::{
+                address::Address,
+                lock::{InstrumentedRwLock, LockHolder, LockReport},
+            },
+            core::{
+                sys::{
+                    checkpoints::{Checkpoint, CheckpointSet},
+                    history::ExecutedProposal,
+                    journal::{JournaledVote, VoteOrigin, DEFAULT_JOURNAL_PAGE},
+                    proposal::Proposal,
+                    simulation::ProposalSimulation,
+                    stats::Sample,
+                    storage::StorageStatus,
+                    system::System,
+                    vote::Vote,
+                    voting_policy::{VotingAccountChange, VotingPolicy},
+                },
+                types::{
+                    burn::{self, BurnReport},
+                    genesis::RoleAssignment,
+                    issuance::SupplyReport,
+                },
+            },
+            crypto::hash::Hash,
+        },
+        envelope, floodsub,
+    },
+    auth::RpcHandler,
+    client::{ClientError, RpcClient},
+    dag::DagImpl,
     error,
+    failover::RetryPolicy,
 };
 
-use std::{
-    collections::HashMap,
-    sync::{Arc, RwLock},
-};
+use std::sync::Arc;
+
+/// The reason journaled for votes cast by the operator confirming a proposal by hand.
+const CONFIRMED_REASON: &str = "confirmed by the node's operator";
 
 #[rpc]
 pub trait Runtime {
     /// Gets a list of proposals contained in the runtime
     #[rpc(name = "list_pending_proposals")]
     fn list_pending_proposals(&self) -> Result<Vec<Proposal>>;
+
+    /// Gets a list of the proposals that the node's voting policy requires a manual vote on
+    #[rpc(name = "list_proposals_awaiting_confirmation")]
+    fn list_awaiting_confirmation(&self) -> Result<Vec<Proposal>>;
+
+    /// Votes on the proposal with the given hash with each of the accounts in the data directory (or held by the
+    /// node's remote signer) that the voting policy allows, and returns the cast votes
+    #[rpc(name = "confirm_proposal")]
+    fn confirm_proposal(
+        &self,
+        proposal_id: String,
+        in_favor: bool,
+        data_dir: String,
+    ) -> Result<Vec<Vote>>;
+
+    /// Works out what would happen if the pending proposal with the given hash were executed right now, without
+    /// committing anything, so that it can be weighed before voting on it
+    #[rpc(name = "simulate_proposal")]
+    fn simulate_proposal(&self, proposal_id: String) -> Result<ProposalSimulation>;
+
+    /// Pins the transaction with the given hash as a checkpoint at the given index in the DAG, persisting it to the
+    /// given data directory. Any synchronized history that disagrees with the checkpoint is refused.
+    #[rpc(name = "pin_checkpoint")]
+    fn pin_checkpoint(&self, index: usize, hash: String, data_dir: String) -> Result<()>;
+
+    /// Unpins the checkpoint at the given index, if there is one, and returns it.
+    #[rpc(name = "unpin_checkpoint")]
+    fn unpin_checkpoint(&self, index: usize, data_dir: String) -> Result<Option<Checkpoint>>;
+
+    /// Gets each of the checkpoints pinned by the node.
+    #[rpc(name = "list_checkpoints")]
+    fn list_checkpoints(&self) -> Result<CheckpointSet>;
+
+    /// Starts voting with the account with the given address, reading it from the keystore in the given data
+    /// directory. Locked accounts are unlocked in memory with the given password. The voting policy is updated, if
+    /// need be, to let the node vote with the account.
+    #[rpc(name = "add_voting_account")]
+    fn add_voting_account(
+        &self,
+        address: String,
+        password: Option<String>,
+        data_dir: String,
+    ) -> Result<()>;
+
+    /// Stops voting with the account with the given address, and removes it from the voting policy.
+    #[rpc(name = "remove_voting_account")]
+    fn remove_voting_account(&self, address: String, data_dir: String) -> Result<()>;
+
+    /// Gets the addresses of the accounts that the node is currently voting with.
+    #[rpc(name = "list_voting_accounts")]
+    fn list_voting_accounts(&self) -> Result<Vec<Address>>;
+
+    /// Gets the network activity observed by the node, as a time series of hourly samples, oldest first.
+    #[rpc(name = "get_network_stats")]
+    fn get_network_stats(&self) -> Result<Vec<Sample>>;
+
+    /// Gets the most recent proposals executed by the node, newest first, along with what came of them. Only those
+    /// touching the given param are included, if one is given.
+    #[rpc(name = "list_executed_proposals")]
+    fn list_executed_proposals(
+        &self,
+        limit: Option<usize>,
+        param: Option<String>,
+    ) -> Result<Vec<ExecutedProposal>>;
+
+    /// Gets an account of the network's supply: what was allocated in the genesis, what has been minted since, and
+    /// the issuance schedule that future mints follow.
+    #[rpc(name = "get_supply")]
+    fn get_supply(&self) -> Result<SupplyReport>;
+
+    /// Gets the number of finks burned so far, along with the address that finks are burned by sending them to.
+    #[rpc(name = "get_burned_supply")]
+    fn get_burned_supply(&self) -> Result<BurnReport>;
+
+    /// Gets each of the accounts designated a role (e.g. the faucet, or the treasury) in the genesis, along with what
+    /// was allocated to them.
+    #[rpc(name = "get_genesis_roles")]
+    fn get_genesis_roles(&self) -> Result<Vec<RoleAssignment>>;
+
+    /// Gets how long each caller has spent waiting on, and holding, the runtime lock, along with who's holding it right
+    /// now. Never waits on the lock itself, so it answers even while the node is stalled.
+    #[rpc(name = "get_lock_metrics")]
+    fn get_lock_metrics(&self) -> Result<LockReport>;
+
+    /// Gets each of the callers holding, or waiting on, the runtime lock right now, those that have been at it longest
+    /// first. Never waits on the lock itself, so it answers even while the node is stalled.
+    #[rpc(name = "dump_lock_holders")]
+    fn dump_lock_holders(&self) -> Result<Vec<LockHolder>>;
+
+    /// Gets the size of the ledger's database, the room left on its disk, and whether the node is pruning or pausing
+    /// writes to make do.
+    #[rpc(name = "get_storage_status")]
+    fn get_storage_status(&self) -> Result<StorageStatus>;
+
+    /// Gets the name of the network that the node serves, so that tools pointed at one network can refuse to act on a
+    /// node serving another.
+    #[rpc(name = "get_network")]
+    fn get_network(&self) -> Result<String>;
+
+    /// Gets the most recent votes that the node has cast with the given account, newest first, along with why each was
+    /// cast the way it was. Only votes with a seq lower than the given one are included, if one is given.
+    #[rpc(name = "get_vote_journal")]
+    fn get_vote_journal(
+        &self,
+        address: String,
+        before: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<Vec<JournaledVote>>;
 }
 
 /// An implementation of the runtime API.
 pub struct RuntimeImpl {
-    pub(crate) runtime: Arc<RwLock<System>>,
+    pub(crate) runtime: Arc<InstrumentedRwLock<System>>,
 }
 
 impl Runtime for RuntimeImpl {
@@ -37,74 +176,610 @@ impl Runtime for RuntimeImpl {
             )))
         }
     }
+
+    /// Gets a list of the proposals that the node's voting policy requires a manual vote on
+    fn list_awaiting_confirmation(&self) -> Result<Vec<Proposal>> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt
+                .awaiting_confirmation
+                .iter()
+                .filter_map(|id| rt.pending_proposals.get(id))
+                .cloned()
+                .collect())
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Votes on the proposal with the given hash with each of the accounts in the data directory that the voting
+    /// policy allows, and returns the cast votes
+    fn confirm_proposal(
+        &self,
+        proposal_id: String,
+        in_favor: bool,
+        data_dir: String,
+    ) -> Result<Vec<Vote>> {
+        // Get a lock on the runtime, so that we can register the votes
+        let mut rt = if let Ok(rt) = self.runtime.write() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        let id = rt
+            .resolve_proposal_id(&proposal_id)
+            .map_err(DagImpl::map_short_id_err)?;
+
+        // We can't vote on a proposal that we don't know about
+        if !rt.pending_proposals.contains_key(&id) {
+            return Err(Error::new(ErrorCode::from(error::ERROR_NO_SUCH_PROPOSAL)));
+        }
+
+        // The votes cast by each of the permitted accounts
+        let mut votes: Vec<Vote> = Vec::new();
+
+        for address in account::get_all_unlocked_accounts_in_data_directory(&data_dir) {
+            // Only vote with the accounts that the voting policy allows
+            if !rt.voting_policy.permits(&address) {
+                continue;
+            }
+
+            // Get the account's keypair, so that we can sign the vote
+            let acc = match Account::read_from_disk_at_data_directory(address, &data_dir) {
+                Ok(acc) => acc,
+                _ => continue,
+            };
+            let (keypair, sealing_keypair) = match (acc.keypair(), acc.keypair()) {
+                (Ok(k), Ok(s)) => (k, s),
+                _ => continue,
+            };
+
+            // Cast the vote, seal it for gossip under the voter's own key, and queue it for publishing
+            let vote = Vote::new(id, in_favor, keypair);
+            let sealed = match envelope::seal(
+                floodsub::Topics::from_config(&rt.config).votes.id(),
+                &vote,
+                &sealing_keypair,
+            ) {
+                Ok(sealed) => sealed,
+                Err(_) => continue,
+            };
+            if rt.register_local_vote(vote.clone(), sealed).is_ok() {
+                rt.journal_vote(&vote, CONFIRMED_REASON, VoteOrigin::Confirmed, false);
+                votes.push(vote);
+            }
+        }
+
+        // Have the remote signer vote with each of its accounts too, if the node delegates its signing
+        let topic = floodsub::Topics::from_config(&rt.config)
+            .votes
+            .id()
+            .to_owned();
+        for (vote, sealed) in rt.cast_remote_votes(id, in_favor, &topic) {
+            if rt.register_local_vote(vote.clone(), sealed).is_ok() {
+                rt.journal_vote(&vote, CONFIRMED_REASON, VoteOrigin::Confirmed, true);
+                votes.push(vote);
+            }
+        }
+
+        // If none of the accounts were able to vote, the operator needs to know
+        if votes.is_empty() {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OPEN_ACCOUNT,
+            )));
+        }
+
+        // The votes we just cast may be enough to decide the proposal
+        if floodsub::potentially_clear_proposal(rt, &id) {
+            info!("Successfully cleared proposal {}!", id);
+        }
+
+        Ok(votes)
+    }
+
+    /// Works out what would happen if the pending proposal with the given hash were executed right now.
+    fn simulate_proposal(&self, proposal_id: String) -> Result<ProposalSimulation> {
+        let rt = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        let id = rt
+            .resolve_proposal_id(&proposal_id)
+            .map_err(DagImpl::map_short_id_err)?;
+
+        rt.simulate_proposal(&id)
+            .map_err(|_| Error::new(ErrorCode::from(error::ERROR_NO_SUCH_PROPOSAL)))
+    }
+
+    /// Pins the transaction with the given hash as a checkpoint at the given index in the DAG.
+    fn pin_checkpoint(&self, index: usize, hash: String, data_dir: String) -> Result<()> {
+        let mut rt = if let Ok(rt) = self.runtime.write() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        // The transaction may be given by its short id
+        let hash = rt
+            .ledger
+            .resolve_hash(&hash)
+            .map_err(DagImpl::map_short_id_err)?;
+        let checkpoint = if let Ok(checkpoint) = format!("{}:{}", index, hash).parse() {
+            checkpoint
+        } else {
+            return Err(Error::new(ErrorCode::from(error::ERROR_INVALID_CHECKPOINT)));
+        };
+
+        // A checkpoint that our own ledger disagrees with would stop the node from starting again
+        let mut checkpoints = rt.checkpoints.clone();
+        checkpoints.pin(checkpoint);
+        if let Err(e) = checkpoints.verify(&rt.ledger) {
+            warn!("Refusing to pin checkpoint: {}", e);
+
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_CHECKPOINT_CONFLICT,
+            )));
+        }
+
+        Self::persist_checkpoints(&checkpoints, &data_dir)?;
+        rt.checkpoints = checkpoints;
+
+        Ok(())
+    }
+
+    /// Unpins the checkpoint at the given index, if there is one, and returns it.
+    fn unpin_checkpoint(&self, index: usize, data_dir: String) -> Result<Option<Checkpoint>> {
+        let mut rt = if let Ok(rt) = self.runtime.write() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        let mut checkpoints = rt.checkpoints.clone();
+        let unpinned = checkpoints.unpin(index);
+
+        Self::persist_checkpoints(&checkpoints, &data_dir)?;
+        rt.checkpoints = checkpoints;
+
+        Ok(unpinned)
+    }
+
+    /// Gets each of the checkpoints pinned by the node.
+    fn list_checkpoints(&self) -> Result<CheckpointSet> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.checkpoints.clone())
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Starts voting with the account with the given address.
+    fn add_voting_account(
+        &self,
+        address: String,
+        password: Option<String>,
+        data_dir: String,
+    ) -> Result<()> {
+        let address = DagImpl::parse_address(&self.runtime, &address)?;
+
+        // Unlock the account, if it's locked
+        let acc = match password {
+            Some(password) => {
+                Account::read_locked_from_disk_at_data_directory(address, &data_dir, &password)
+                    .map_err(|_| Error::new(ErrorCode::from(error::ERROR_DECRYPTION_FAILED)))?
+            }
+            None => Account::read_from_disk_at_data_directory(address, &data_dir)
+                .map_err(|_| Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_OPEN_ACCOUNT)))?,
+        };
+
+        let mut rt = if let Ok(rt) = self.runtime.write() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        // Make sure that the policy doesn't stop us from voting with the account
+        let mut policy = rt.voting_policy.clone();
+        policy.admit(address);
+        Self::persist_voting_policy(&policy, &data_dir)?;
+        rt.voting_policy = policy;
+
+        rt.queue_voting_account_change(VotingAccountChange::Add(acc));
+
+        Ok(())
+    }
+
+    /// Stops voting with the account with the given address.
+    fn remove_voting_account(&self, address: String, data_dir: String) -> Result<()> {
+        let address = DagImpl::parse_address(&self.runtime, &address)?;
+
+        let mut rt = if let Ok(rt) = self.runtime.write() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        let mut policy = rt.voting_policy.clone();
+        policy.dismiss(&address);
+        Self::persist_voting_policy(&policy, &data_dir)?;
+        rt.voting_policy = policy;
+
+        rt.queue_voting_account_change(VotingAccountChange::Remove(address));
+
+        Ok(())
+    }
+
+    /// Gets the addresses of the accounts that the node is currently voting with.
+    fn list_voting_accounts(&self) -> Result<Vec<Address>> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.active_voters.clone())
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets the network activity observed by the node, as a time series of hourly samples, oldest first.
+    fn get_network_stats(&self) -> Result<Vec<Sample>> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.stats.series())
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets the most recent proposals executed by the node, newest first, along with what came of them.
+    fn list_executed_proposals(
+        &self,
+        limit: Option<usize>,
+        param: Option<String>,
+    ) -> Result<Vec<ExecutedProposal>> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt
+                .history
+                .recent(limit.unwrap_or(usize::MAX), param.as_deref()))
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets an account of the network's supply.
+    fn get_supply(&self) -> Result<SupplyReport> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.supply())
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets the number of finks burned so far.
+    fn get_burned_supply(&self) -> Result<BurnReport> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(BurnReport {
+                address: burn::burn_address(),
+                burned: rt.ledger.burned_supply(),
+            })
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets each of the accounts designated a role in the genesis.
+    fn get_genesis_roles(&self) -> Result<Vec<RoleAssignment>> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.ledger.genesis_roles())
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets how long each caller has spent waiting on, and holding, the runtime lock.
+    fn get_lock_metrics(&self) -> Result<LockReport> {
+        Ok(self.runtime.report())
+    }
+
+    /// Gets each of the callers holding, or waiting on, the runtime lock right now.
+    fn dump_lock_holders(&self) -> Result<Vec<LockHolder>> {
+        Ok(self.runtime.report().holders)
+    }
+
+    /// Gets the latest measurement of the ledger's database and the room left on its disk.
+    fn get_storage_status(&self) -> Result<StorageStatus> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.storage.status().clone())
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets the name of the network that the node serves.
+    fn get_network(&self) -> Result<String> {
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt.config.network_name.clone())
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
+
+    /// Gets the most recent votes that the node has cast with the given account, newest first.
+    fn get_vote_journal(
+        &self,
+        address: String,
+        before: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<Vec<JournaledVote>> {
+        let address = DagImpl::parse_address(&self.runtime, &address)?;
+
+        if let Ok(rt) = self.runtime.read() {
+            Ok(rt
+                .vote_journal
+                .list(&address, before, limit.unwrap_or(DEFAULT_JOURNAL_PAGE)))
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        }
+    }
 }
 
 impl RuntimeImpl {
-    /// Registers the DAG service on the given IoHandler server.
-    pub fn register(io: &mut IoHandler, runtime: Arc<RwLock<System>>) {
+    /// Registers the DAG service on the given RPC handler.
+    pub fn register(io: &mut RpcHandler, runtime: Arc<InstrumentedRwLock<System>>) {
         // Register this service on the IO handler
         io.extend_with(Self { runtime }.to_delegate());
     }
+
+    /// Persists the given checkpoints to the given data directory, so that they're still pinned after a restart.
+    ///
+    /// # Arguments
+    ///
+    /// * `checkpoints` - The checkpoints that should be persisted
+    /// * `data_dir` - The data directory that the node was started with
+    fn persist_checkpoints(checkpoints: &CheckpointSet, data_dir: &str) -> Result<()> {
+        checkpoints
+            .write_to_disk_at_data_directory(data_dir)
+            .map_err(|_| Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_WRITE_CHECKPOINTS)))
+    }
+
+    /// Persists the given voting policy to the given data directory, so that it still applies after a restart.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The policy that should be persisted
+    /// * `data_dir` - The data directory that the node was started with
+    fn persist_voting_policy(policy: &VotingPolicy, data_dir: &str) -> Result<()> {
+        policy
+            .write_to_disk_at_data_directory(data_dir)
+            .map_err(|_| Error::new(ErrorCode::from(error::ERROR_UNABLE_TO_WRITE_VOTING_POLICY)))
+    }
 }
 
 /// A client for the runtime API.
 pub struct Client {
-    /// The address for the server hosting the API
-    pub server: String,
+    /// The underlying JSON-RPC client
+    rpc: RpcClient,
+}
 
-    /// The HTTP client
-    client: reqwest::Client,
+impl From<RpcClient> for Client {
+    /// Builds a client on top of the given JSON-RPC client, sharing its connections.
+    fn from(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
 }
 
 impl Client {
-    /// Initializes a new Client with the given remote URL.
+    /// Initializes a new Client with the given remote URL, or comma-separated list of URLs for redundant nodes.
     pub fn new(server_addr: &str) -> Self {
         Self {
-            server: server_addr.trim_end_matches('/').to_owned(),
-            client: reqwest::Client::new(),
-        }
-    }
-    /// Performs a request considering the given method, and returns the response.
-    async fn do_request<T>(
-        &self,
-        method: &str,
-        params: &str,
-    ) -> std::result::Result<T, failure::Error>
-    where
-        T: for<'de> Deserialize<'de>,
-    {
-        // Make a hashmap to store the body of the request in
-        let mut json_body: HashMap<&str, serde_json::Value> = HashMap::new();
-        json_body.insert("jsonrpc", serde_json::Value::String("2.0".to_owned()));
-        json_body.insert("method", serde_json::Value::String(method.to_owned()));
-        json_body.insert("id", serde_json::Value::String("".to_owned()));
-        json_body.insert("params", serde_json::from_str(params)?);
-
-        // Send a request to the endpoint, and pass the given parameters along with the request
-        let res = self
-            .client
-            .post(&self.server)
-            .json(&json_body)
-            .send()
-            .await?
-            .json::<Output>()
-            .await?;
-
-        // Some type conversion black magic fuckery
-        match res {
-            Output::Success(s) => match serde_json::from_value(s.result) {
-                Ok(res) => Ok(res),
-                Err(e) => Err(e.into()),
-            },
-            Output::Failure(e) => Err(e.error.into()),
+            rpc: RpcClient::new(server_addr),
         }
     }
 
+    /// Makes each of the client's requests under the admin session with the given token.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The token of the admin session, if there is one
+    pub fn with_session(mut self, session: Option<String>) -> Self {
+        self.rpc = self.rpc.with_session(session);
+
+        self
+    }
+
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.rpc = self.rpc.with_retry_policy(policy);
+
+        self
+    }
+
     /// Gets a list of proposals contained in the runtime
-    pub async fn list_pending_proposals(
+    pub async fn list_pending_proposals(&self) -> std::result::Result<Vec<Proposal>, ClientError> {
+        self.rpc.call("list_pending_proposals", ()).await
+    }
+
+    /// Gets a list of the proposals that require a manual vote
+    pub async fn list_awaiting_confirmation(
+        &self,
+    ) -> std::result::Result<Vec<Proposal>, ClientError> {
+        self.rpc
+            .call("list_proposals_awaiting_confirmation", ())
+            .await
+    }
+
+    /// Votes on the proposal with the given hash with each of the permitted accounts in the data directory
+    pub async fn confirm_proposal(
+        &self,
+        proposal_id: String,
+        in_favor: bool,
+        data_dir: String,
+    ) -> std::result::Result<Vec<Vote>, ClientError> {
+        self.rpc
+            .call("confirm_proposal", (&proposal_id, in_favor, &data_dir))
+            .await
+    }
+
+    /// Works out what would happen if the pending proposal with the given hash were executed right now
+    pub async fn simulate_proposal(
+        &self,
+        proposal_id: String,
+    ) -> std::result::Result<ProposalSimulation, ClientError> {
+        self.rpc.call("simulate_proposal", (&proposal_id,)).await
+    }
+
+    /// Pins the transaction with the given hash as a checkpoint at the given index in the DAG
+    pub async fn pin_checkpoint(
+        &self,
+        index: usize,
+        hash: String,
+        data_dir: String,
+    ) -> std::result::Result<(), ClientError> {
+        self.rpc
+            .call("pin_checkpoint", (index, &hash, &data_dir))
+            .await
+    }
+
+    /// Unpins the checkpoint at the given index, if there is one
+    pub async fn unpin_checkpoint(
+        &self,
+        index: usize,
+        data_dir: String,
+    ) -> std::result::Result<Option<Checkpoint>, ClientError> {
+        self.rpc.call("unpin_checkpoint", (index, &data_dir)).await
+    }
+
+    /// Gets each of the checkpoints pinned by the node
+    pub async fn list_checkpoints(&self) -> std::result::Result<CheckpointSet, ClientError> {
+        self.rpc.call("list_checkpoints", ()).await
+    }
+
+    /// Starts voting with the account with the given address, unlocking it with the given password if it's locked
+    pub async fn add_voting_account(
+        &self,
+        address: String,
+        password: Option<String>,
+        data_dir: String,
+    ) -> std::result::Result<(), ClientError> {
+        self.rpc
+            .call("add_voting_account", (&address, &password, &data_dir))
+            .await
+    }
+
+    /// Stops voting with the account with the given address
+    pub async fn remove_voting_account(
+        &self,
+        address: String,
+        data_dir: String,
+    ) -> std::result::Result<(), ClientError> {
+        self.rpc
+            .call("remove_voting_account", (&address, &data_dir))
+            .await
+    }
+
+    /// Gets the addresses of the accounts that the node is currently voting with
+    pub async fn list_voting_accounts(&self) -> std::result::Result<Vec<Address>, ClientError> {
+        self.rpc.call("list_voting_accounts", ()).await
+    }
+
+    /// Gets the network activity observed by the node, as a time series of hourly samples, oldest first.
+    pub async fn get_network_stats(&self) -> std::result::Result<Vec<Sample>, ClientError> {
+        self.rpc.call("get_network_stats", ()).await
+    }
+
+    /// Gets the most recent proposals executed by the node, newest first, along with what came of them.
+    pub async fn list_executed_proposals(
+        &self,
+        limit: Option<usize>,
+        param: Option<&str>,
+    ) -> std::result::Result<Vec<ExecutedProposal>, ClientError> {
+        self.rpc
+            .call("list_executed_proposals", (limit, param))
+            .await
+    }
+
+    /// Gets an account of the network's supply.
+    pub async fn get_supply(&self) -> std::result::Result<SupplyReport, ClientError> {
+        self.rpc.call("get_supply", ()).await
+    }
+
+    /// Gets the number of finks burned so far, along with the address that finks are burned by sending them to.
+    pub async fn get_burned_supply(&self) -> std::result::Result<BurnReport, ClientError> {
+        self.rpc.call("get_burned_supply", ()).await
+    }
+
+    /// Gets each of the accounts designated a role in the genesis, along with what was allocated to them.
+    pub async fn get_genesis_roles(&self) -> std::result::Result<Vec<RoleAssignment>, ClientError> {
+        self.rpc.call("get_genesis_roles", ()).await
+    }
+
+    /// Gets how long each caller has spent waiting on, and holding, the runtime lock, along with who's holding it right
+    /// now.
+    pub async fn get_lock_metrics(&self) -> std::result::Result<LockReport, ClientError> {
+        self.rpc.call("get_lock_metrics", ()).await
+    }
+
+    /// Gets each of the callers holding, or waiting on, the runtime lock right now.
+    pub async fn dump_lock_holders(&self) -> std::result::Result<Vec<LockHolder>, ClientError> {
+        self.rpc.call("dump_lock_holders", ()).await
+    }
+
+    /// Gets the size of the ledger's database, the room left on its disk, and whether the node is pruning or pausing
+    /// writes to make do.
+    pub async fn get_storage_status(&self) -> std::result::Result<StorageStatus, ClientError> {
+        self.rpc.call("get_storage_status", ()).await
+    }
+
+    /// Gets the name of the network that the node serves.
+    pub async fn get_network(&self) -> std::result::Result<String, ClientError> {
+        self.rpc.call("get_network", ()).await
+    }
+
+    /// Gets the most recent votes that the node has cast with the given account, newest first.
+    pub async fn get_vote_journal(
         &self,
-    ) -> std::result::Result<Vec<Proposal>, failure::Error> {
-        self.do_request::<Vec<Proposal>>("list_pending_proposals", "[]")
+        address: Address,
+        before: Option<u64>,
+        limit: Option<usize>,
+    ) -> std::result::Result<Vec<JournaledVote>, ClientError> {
+        self.rpc
+            .call("get_vote_journal", (address.to_str(), before, limit))
             .await
     }
 }
@@ -0,0 +1,327 @@
+use jsonrpc_core::{futures::Future, Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{
+    typed::{Sink, Subscriber},
+    SubscriptionId,
+};
+
+use super::{
+    super::super::{
+        common::{address::Address, lock::InstrumentedRwLock},
+        core::sys::{
+            events::{EventFilter, EventKind, SequencedEvent, MAX_POLL_SIZE},
+            system::System,
+        },
+    },
+    auth::{RpcHandler, Session},
+    error,
+    events::EventsImpl,
+};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// The number of milliseconds waited between pushes of newly published events to subscribers.
+pub const PUSH_INTERVAL_MS: u64 = 250;
+
+/// Defines the SummerCash push subscription API. Its methods can only be called over a WebSocket connection, which
+/// each matching event is pushed down as soon as the node publishes it, so that wallets needn't poll get_dag.
+#[rpc]
+pub trait Subscriptions {
+    type Metadata;
+
+    /// Subscribes to each of the transactions proposed for the ledger from now on.
+    #[pubsub(
+        subscription = "new_transactions",
+        subscribe,
+        name = "subscribe_new_transactions"
+    )]
+    fn subscribe_new_transactions(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<SequencedEvent>,
+    );
+
+    /// Closes the given subscription to new transactions.
+    #[pubsub(
+        subscription = "new_transactions",
+        unsubscribe,
+        name = "unsubscribe_new_transactions"
+    )]
+    fn unsubscribe_new_transactions(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool>;
+
+    /// Subscribes to each of the proposals that the node sees from now on.
+    #[pubsub(subscription = "proposals", subscribe, name = "subscribe_proposals")]
+    fn subscribe_proposals(&self, meta: Self::Metadata, subscriber: Subscriber<SequencedEvent>);
+
+    /// Closes the given subscription to proposals.
+    #[pubsub(
+        subscription = "proposals",
+        unsubscribe,
+        name = "unsubscribe_proposals"
+    )]
+    fn unsubscribe_proposals(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool>;
+
+    /// Subscribes to each of the changes made to the balances of the given accounts from now on.
+    #[pubsub(
+        subscription = "balance_changes",
+        subscribe,
+        name = "subscribe_balance_changes"
+    )]
+    fn subscribe_balance_changes(
+        &self,
+        meta: Self::Metadata,
+        subscriber: Subscriber<SequencedEvent>,
+        addresses: Vec<Address>,
+    );
+
+    /// Closes the given subscription to balance changes.
+    #[pubsub(
+        subscription = "balance_changes",
+        unsubscribe,
+        name = "unsubscribe_balance_changes"
+    )]
+    fn unsubscribe_balance_changes(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool>;
+}
+
+/// An implementation of the subscriptions API. Each subscription is backed by a subscription to the node's event
+/// feed, sharing its id, which is drained into the subscriber's connection.
+pub struct SubscriptionsImpl {
+    pub(crate) runtime: Arc<InstrumentedRwLock<System>>,
+
+    /// The connection that each open subscription's events are pushed down, keyed by the subscription's id
+    sinks: Arc<Mutex<HashMap<String, Sink<SequencedEvent>>>>,
+}
+
+impl Subscriptions for SubscriptionsImpl {
+    type Metadata = Session;
+
+    /// Subscribes to each of the transactions proposed for the ledger from now on.
+    fn subscribe_new_transactions(&self, _meta: Session, subscriber: Subscriber<SequencedEvent>) {
+        self.open(
+            subscriber,
+            EventFilter {
+                kinds: Some(vec![EventKind::Transaction]),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Closes the given subscription to new transactions.
+    fn unsubscribe_new_transactions(
+        &self,
+        _meta: Option<Session>,
+        id: SubscriptionId,
+    ) -> Result<bool> {
+        self.close(id)
+    }
+
+    /// Subscribes to each of the proposals that the node sees from now on.
+    fn subscribe_proposals(&self, _meta: Session, subscriber: Subscriber<SequencedEvent>) {
+        self.open(
+            subscriber,
+            EventFilter {
+                kinds: Some(vec![EventKind::Proposal]),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Closes the given subscription to proposals.
+    fn unsubscribe_proposals(&self, _meta: Option<Session>, id: SubscriptionId) -> Result<bool> {
+        self.close(id)
+    }
+
+    /// Subscribes to each of the changes made to the balances of the given accounts from now on.
+    fn subscribe_balance_changes(
+        &self,
+        _meta: Session,
+        subscriber: Subscriber<SequencedEvent>,
+        addresses: Vec<Address>,
+    ) {
+        self.open(
+            subscriber,
+            EventFilter {
+                kinds: Some(vec![EventKind::BalanceChange]),
+                addresses: Some(addresses),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Closes the given subscription to balance changes.
+    fn unsubscribe_balance_changes(
+        &self,
+        _meta: Option<Session>,
+        id: SubscriptionId,
+    ) -> Result<bool> {
+        self.close(id)
+    }
+}
+
+impl SubscriptionsImpl {
+    /// Registers the subscriptions service on the given RPC handler, and starts pushing events to its subscribers.
+    pub fn register(io: &mut RpcHandler, runtime: Arc<InstrumentedRwLock<System>>) {
+        let sinks = Arc::new(Mutex::new(HashMap::new()));
+
+        // Push newly published events to subscribers in the background
+        let (pump_runtime, pump_sinks) = (runtime.clone(), sinks.clone());
+        thread::spawn(move || loop {
+            thread::sleep(Duration::from_millis(PUSH_INTERVAL_MS));
+
+            Self::push(&pump_runtime, &pump_sinks);
+        });
+
+        // Register this service on the IO handler
+        io.extend_with(Self { runtime, sinks }.to_delegate());
+    }
+
+    /// Opens a subscription to the events passing the given filter on behalf of the given subscriber.
+    ///
+    /// # Arguments
+    ///
+    /// * `subscriber` - The subscriber that the events should be pushed to
+    /// * `filter` - The events that the subscriber wants to hear about
+    fn open(&self, subscriber: Subscriber<SequencedEvent>, filter: EventFilter) {
+        let opened = if let Ok(mut rt) = self.runtime.write() {
+            rt.events.subscribe(filter).map_err(EventsImpl::map_err)
+        } else {
+            Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )))
+        };
+
+        let id = match opened {
+            Ok(id) => id,
+            Err(e) => {
+                // The subscriber may have hung up already, in which case there's nobody to tell
+                let _ = subscriber.reject(e);
+
+                return;
+            }
+        };
+
+        match subscriber.assign_id(SubscriptionId::String(id.clone())) {
+            Ok(sink) => {
+                if let Ok(mut sinks) = self.sinks.lock() {
+                    sinks.insert(id, sink);
+                }
+            }
+
+            // The subscriber hung up before it could be told its id, so the feed subscription would never be drained
+            Err(_) => Self::unsubscribe_from(&self.runtime, &id),
+        }
+    }
+
+    /// Closes the subscription with the given id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the subscription
+    fn close(&self, id: SubscriptionId) -> Result<bool> {
+        let id = match id {
+            SubscriptionId::String(id) => id,
+            SubscriptionId::Number(id) => id.to_string(),
+        };
+
+        let removed = if let Ok(mut sinks) = self.sinks.lock() {
+            sinks.remove(&id).is_some()
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        if !removed {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_NO_SUCH_SUBSCRIPTION,
+            )));
+        }
+
+        Self::unsubscribe_from(&self.runtime, &id);
+
+        Ok(true)
+    }
+
+    /// Closes the event feed subscription with the given id on the given runtime, if it's still open.
+    ///
+    /// # Arguments
+    ///
+    /// * `runtime` - The node's runtime
+    /// * `id` - The id of the feed subscription
+    fn unsubscribe_from(runtime: &Arc<InstrumentedRwLock<System>>, id: &str) {
+        if let Ok(mut rt) = runtime.write() {
+            // The subscription may have expired already
+            let _ = rt.events.unsubscribe(id);
+        }
+    }
+
+    /// Pushes each of the events published since the last push to its subscribers, dropping any subscriber that has
+    /// hung up, or whose feed subscription has expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `runtime` - The node's runtime
+    /// * `sinks` - The connection that each open subscription's events are pushed down
+    fn push(
+        runtime: &Arc<InstrumentedRwLock<System>>,
+        sinks: &Mutex<HashMap<String, Sink<SequencedEvent>>>,
+    ) {
+        let mut sinks = match sinks.lock() {
+            Ok(sinks) => sinks,
+            Err(_) => return,
+        };
+
+        if sinks.is_empty() {
+            return;
+        }
+
+        // Drain every feed subscription under a single lock, and send the events once it's been let go of
+        let polled: Vec<(String, Option<Vec<SequencedEvent>>)> = match runtime.write() {
+            Ok(mut rt) => sinks
+                .keys()
+                .map(|id| {
+                    (
+                        id.clone(),
+                        rt.events
+                            .poll(id, MAX_POLL_SIZE)
+                            .ok()
+                            .map(|polled| polled.events),
+                    )
+                })
+                .collect(),
+            Err(_) => return,
+        };
+
+        for (id, events) in polled {
+            let delivered = match (events, sinks.get(&id)) {
+                (Some(events), Some(sink)) => events
+                    .into_iter()
+                    .all(|event| sink.notify(Ok(event)).wait().is_ok()),
+                _ => false,
+            };
+
+            if !delivered {
+                sinks.remove(&id);
+
+                Self::unsubscribe_from(runtime, &id);
+            }
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use jsonrpc_core::{
+    futures::{
+        future::{self, Either},
+        Future,
+    },
+    middleware::{NoopCallFuture, NoopFuture},
+    response::Output,
+    Call, Error, ErrorCode, MethodCall, Middleware, Params, Value,
+};
+
+use super::{
+    auth::{Session, SessionTable},
+    error,
+};
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// The name of the directory in the node's data directory that each tenant's namespace is kept in.
+pub const TENANTS_DIR: &str = "tenants";
+
+/// The RPC methods that read from, or write to, a data directory on the node, along with the position of their
+/// data_dir parameter. Calls made with an API key have that parameter replaced with the key's namespace.
+pub const SCOPED_METHODS: [(&str, usize); 24] = [
+    ("new_account", 0),
+    ("get_account", 1),
+    ("lock_account", 2),
+    ("unlock_account", 2),
+    ("open_unlock_session", 2),
+    ("delete_account", 1),
+    ("list_accounts", 0),
+    ("get_parent_account", 1),
+    ("get_spending_status", 1),
+    ("sign_transaction", 1),
+    ("get_mem_transactions", 0),
+    ("publish_transaction", 1),
+    ("rebuild_transaction", 1),
+    ("resubmit_transaction", 1),
+    ("set_note", 3),
+    ("get_note", 1),
+    ("delete_note", 1),
+    ("list_notes", 0),
+    ("list_annotated_transactions", 0),
+    ("add_contact", 3),
+    ("get_contact", 1),
+    ("delete_contact", 1),
+    ("list_contacts", 0),
+    ("resolve_contact", 1),
+];
+
+/// The RPC methods that take a data directory, but act for the node as a whole: they vote with, or change, the node's
+/// voting accounts, or pin the checkpoints that the node holds its history to. Tenants can't call them, and once the
+/// node serves any tenants, neither can anyone else without an admin session.
+pub const NODE_METHODS: [&str; 5] = [
+    "confirm_proposal",
+    "pin_checkpoint",
+    "unpin_checkpoint",
+    "add_voting_account",
+    "remove_voting_account",
+];
+
+/// An error encountered while configuring a node's tenants.
+#[derive(Debug, Fail, PartialEq)]
+pub enum TenantError {
+    #[fail(
+        display = "invalid tenant {} (expected namespace:api-key, with a namespace made up of letters, digits, - and _)",
+        tenant
+    )]
+    InvalidTenant {
+        tenant: String, // The tenant that was given
+    },
+    #[fail(display = "the API key of tenant {} is already in use", namespace)]
+    DuplicateKey {
+        namespace: String, // The namespace of the tenant reusing the key
+    },
+}
+
+/// The downstream applications served by a node, each confined to its own namespace: a data directory of its own,
+/// holding its keystore, transactions, notes and address book.
+#[derive(Clone, Debug, Default)]
+pub struct TenantTable {
+    /// The data directory of the node, under which each namespace is kept
+    data_dir: String,
+
+    /// The namespace of each tenant, keyed by its API key
+    namespaces: HashMap<String, String>,
+}
+
+/// Implement a set of tenant table helper methods.
+impl TenantTable {
+    /// Initializes a new tenant table without any tenants.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The data directory of the node, under which each namespace is kept
+    pub fn new(data_dir: &str) -> Self {
+        Self {
+            data_dir: data_dir.to_owned(),
+            namespaces: HashMap::new(),
+        }
+    }
+
+    /// Initializes a new tenant table from the given tenants, each written as namespace:api-key.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The data directory of the node, under which each namespace is kept
+    /// * `tenants` - Each of the tenants
+    pub fn parse(data_dir: &str, tenants: &[String]) -> Result<Self, TenantError> {
+        let mut table = Self::new(data_dir);
+
+        for tenant in tenants {
+            let invalid = || TenantError::InvalidTenant {
+                tenant: tenant.clone(),
+            };
+
+            let mut parts = tenant.splitn(2, ':');
+            let (namespace, api_key) = match (parts.next(), parts.next()) {
+                (Some(namespace), Some(api_key)) => (namespace, api_key),
+                _ => return Err(invalid()),
+            };
+
+            // Namespaces become directory names, so keep them to characters that can't escape the tenants directory
+            if namespace.is_empty()
+                || api_key.is_empty()
+                || !namespace
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return Err(invalid());
+            }
+
+            if table
+                .namespaces
+                .insert(api_key.to_owned(), namespace.to_owned())
+                .is_some()
+            {
+                return Err(TenantError::DuplicateKey {
+                    namespace: namespace.to_owned(),
+                });
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Checks whether or not the node serves any tenants. Once it does, the node's own data directory can only be
+    /// reached through the RPC API with an admin session.
+    pub fn is_enforced(&self) -> bool {
+        !self.namespaces.is_empty()
+    }
+
+    /// Gets the namespace of the tenant holding the given API key, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `api_key` - The API key presented with a request, if any
+    pub fn namespace(&self, api_key: Option<&str>) -> Option<&str> {
+        api_key
+            .and_then(|api_key| self.namespaces.get(api_key))
+            .map(String::as_str)
+    }
+
+    /// Gets the data directory of the given namespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace of the tenant
+    pub fn namespace_dir(&self, namespace: &str) -> String {
+        format!("{}/{}/{}", self.data_dir, TENANTS_DIR, namespace)
+    }
+}
+
+/// Gets the position of the data_dir parameter of the given method, if it takes one.
+///
+/// # Arguments
+///
+/// * `method` - The name of the method
+pub fn data_dir_position(method: &str) -> Option<usize> {
+    SCOPED_METHODS
+        .iter()
+        .find(|(scoped, _)| *scoped == method)
+        .map(|(_, position)| *position)
+}
+
+/// Replaces the data_dir parameter of a call with the given data directory, whatever the caller asked for.
+///
+/// # Arguments
+///
+/// * `params` - The parameters of the call
+/// * `position` - The position of the data_dir parameter
+/// * `data_dir` - The data directory that the call should be confined to
+pub fn scope_params(params: Params, position: usize, data_dir: &str) -> Params {
+    match params {
+        Params::Map(mut named) => {
+            named.insert("data_dir".to_owned(), Value::String(data_dir.to_owned()));
+
+            Params::Map(named)
+        }
+        Params::Array(mut positional) => {
+            // Pad out any missing parameters, so that the data directory lands where the method expects it
+            if positional.len() <= position {
+                positional.resize(position + 1, Value::Null);
+            }
+            positional[position] = Value::String(data_dir.to_owned());
+
+            Params::Array(positional)
+        }
+        Params::None => {
+            let mut positional = vec![Value::Null; position + 1];
+            positional[position] = Value::String(data_dir.to_owned());
+
+            Params::Array(positional)
+        }
+    }
+}
+
+/// A middleware confining calls made with an API key to the key's namespace. Calls to methods that touch a data
+/// directory, made without an API key, are refused unless made with an admin session once the node serves any
+/// tenants. Calls made with an API key to methods acting for the node as a whole are always refused.
+pub struct TenantGate {
+    /// The node's tenants
+    pub tenants: Arc<TenantTable>,
+
+    /// The node's challenges and sessions
+    pub sessions: Arc<RwLock<SessionTable>>,
+}
+
+impl Middleware<Session> for TenantGate {
+    type Future = NoopFuture;
+    type CallFuture = NoopCallFuture;
+
+    /// Rewrites the call's data directory if it is made with an API key, or refuses it if it reaches for the node's
+    /// own data directory without an admin session.
+    fn on_call<F, X>(&self, call: Call, meta: Session, next: F) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, Session) -> X + Send + Sync,
+        X: Future<Item = Option<Output>, Error = ()> + Send + 'static,
+    {
+        let mut method_call = match call {
+            Call::MethodCall(method_call) => method_call,
+            call => return Either::B(next(call, meta)),
+        };
+
+        let position = data_dir_position(&method_call.method);
+        let node_wide = NODE_METHODS.contains(&method_call.method.as_str());
+        if position.is_none() && !node_wide {
+            return Either::B(next(Call::MethodCall(method_call), meta));
+        }
+
+        if let Some(namespace) = self.tenants.namespace(meta.token.as_deref()) {
+            // A tenant can't vote with, or pin checkpoints for, the node
+            let position = match position {
+                Some(position) => position,
+                None => return Either::A(refuse(&method_call, error::ERROR_UNAUTHORIZED)),
+            };
+
+            method_call.params = scope_params(
+                method_call.params,
+                position,
+                &self.tenants.namespace_dir(namespace),
+            );
+
+            // The call is confined to the tenant's namespace, so it needn't pass the admin gate
+            return Either::B(next(
+                Call::MethodCall(method_call),
+                Session {
+                    token: None,
+                    tenant: Some(namespace.to_owned()),
+                    pubsub: meta.pubsub.clone(),
+                },
+            ));
+        }
+
+        let authorized = !self.tenants.is_enforced()
+            || self.sessions.read().map_or(false, |sessions| {
+                sessions.is_enforced() && sessions.is_valid(meta.token.as_deref())
+            });
+
+        if !authorized {
+            return Either::A(refuse(&method_call, error::ERROR_NO_TENANT));
+        }
+
+        Either::B(next(Call::MethodCall(method_call), meta))
+    }
+}
+
+/// Refuses the given call with the given error code.
+///
+/// # Arguments
+///
+/// * `method_call` - The call that should be refused
+/// * `code` - The code of the error that the call should be refused with
+fn refuse(method_call: &MethodCall, code: i64) -> NoopCallFuture {
+    Box::new(future::ok(Some(Output::from(
+        Err(Error::new(ErrorCode::from(code))),
+        method_call.id.clone(),
+        method_call.jsonrpc,
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_parse() {
+        let table = TenantTable::parse(
+            "data",
+            &["shop:k1".to_owned(), "exchange-2:k2:with:colons".to_owned()],
+        )
+        .unwrap();
+        assert!(table.is_enforced());
+        assert_eq!(table.namespace(Some("k1")), Some("shop"));
+        assert_eq!(table.namespace(Some("k2:with:colons")), Some("exchange-2"));
+        assert_eq!(table.namespace(Some("k3")), None);
+        assert_eq!(table.namespace(None), None);
+        assert_eq!(table.namespace_dir("shop"), "data/tenants/shop");
+
+        // Namespaces can't reach outside of the tenants directory
+        assert!(TenantTable::parse("data", &["../root:k1".to_owned()]).is_err());
+        assert!(TenantTable::parse("data", &["shop".to_owned()]).is_err());
+        assert!(TenantTable::parse("data", &["shop:".to_owned()]).is_err());
+        assert_eq!(
+            TenantTable::parse("data", &["a:k".to_owned(), "b:k".to_owned()]).unwrap_err(),
+            TenantError::DuplicateKey {
+                namespace: "b".to_owned()
+            }
+        );
+        assert!(!TenantTable::new("data").is_enforced());
+    }
+
+    #[test]
+    fn test_scope_params() {
+        assert_eq!(data_dir_position("lock_account"), Some(2));
+        assert_eq!(data_dir_position("resubmit_transaction"), Some(1));
+        assert_eq!(data_dir_position("get_dag"), None);
+
+        // Methods acting for the node as a whole can't be confined to a namespace
+        for method in &NODE_METHODS {
+            assert_eq!(data_dir_position(method), None);
+        }
+
+        // Whatever directory the caller names is replaced
+        assert_eq!(
+            scope_params(
+                Params::Array(vec![Value::from("addr"), Value::from("/root/data")]),
+                1,
+                "data/tenants/shop"
+            ),
+            Params::Array(vec![Value::from("addr"), Value::from("data/tenants/shop")])
+        );
+        assert_eq!(
+            scope_params(Params::None, 0, "data/tenants/shop"),
+            Params::Array(vec![Value::from("data/tenants/shop")])
+        );
+
+        let mut named = serde_json::Map::new();
+        named.insert("data_dir".to_owned(), Value::from("/root/data"));
+        assert_eq!(
+            scope_params(Params::Map(named), 0, "data/tenants/shop"),
+            Params::Map(
+                vec![("data_dir".to_owned(), Value::from("data/tenants/shop"))]
+                    .into_iter()
+                    .collect()
+            )
+        );
+    }
+}
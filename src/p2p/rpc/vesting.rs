@@ -0,0 +1,188 @@
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+
+use super::{
+    super::super::{
+        common::lock::InstrumentedRwLock,
+        core::{
+            sys::system::System,
+            types::{
+                transaction::Transaction,
+                vesting::{self, LockedBalance, Unlock},
+            },
+        },
+    },
+    auth::RpcHandler,
+    client::{ClientError, RpcClient},
+    dag::DagImpl,
+    error,
+    failover::RetryPolicy,
+};
+
+use chrono::{TimeZone, Utc};
+use num::BigUint;
+
+use std::sync::Arc;
+
+/// Defines the standard SummerCash time-locked balance RPC API.
+#[rpc]
+pub trait Vesting {
+    /// Builds an unsigned transaction sending the given value to the recipient, locked until either the given UNIX
+    /// timestamp or the ledger being the given number of transactions deep. Exactly one of the two must be given.
+    #[rpc(name = "create_locked_transaction")]
+    fn create_locked_tx(
+        &self,
+        sender: String,
+        recipient: String,
+        value: String,
+        unlocks_at: Option<i64>,
+        milestone: Option<u64>,
+    ) -> Result<Transaction>;
+
+    /// Gets how much of the given address's balance is locked, and how much may be spent right away.
+    #[rpc(name = "get_locked_balance")]
+    fn get_locked_balance(&self, address: String) -> Result<LockedBalance>;
+}
+
+/// An implementation of the time-locked balance API.
+pub struct VestingImpl {
+    pub(crate) runtime: Arc<InstrumentedRwLock<System>>,
+}
+
+impl Vesting for VestingImpl {
+    /// Builds an unsigned transaction sending the given value to the recipient, locked until it vests.
+    fn create_locked_tx(
+        &self,
+        sender: String,
+        recipient: String,
+        value: String,
+        unlocks_at: Option<i64>,
+        milestone: Option<u64>,
+    ) -> Result<Transaction> {
+        let invalid = || Error::new(ErrorCode::from(error::ERROR_INVALID_TIME_LOCK));
+
+        let unlock = match (unlocks_at, milestone) {
+            (Some(unlocks_at), None) => Unlock::At(Utc.timestamp(unlocks_at, 0)),
+            (None, Some(milestone)) => Unlock::Milestone(milestone),
+            _ => return Err(invalid()),
+        };
+        let value = value.parse::<BigUint>().map_err(|_| invalid())?;
+        let sender = DagImpl::parse_address(&self.runtime, &sender)?;
+        let recipient = DagImpl::parse_address(&self.runtime, &recipient)?;
+
+        // Get a lock on the client's runtime
+        let rt = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        // Use the nonce directly following the sender's last transaction
+        let nonce = DagImpl::head_entry(&rt)?
+            .data
+            .nonces
+            .get(&sender.to_str())
+            .map_or(0, |last_nonce| last_nonce + 1);
+
+        let tx = DagImpl::build_tx(&rt, nonce, sender, recipient, value, &unlock.to_payload())?;
+
+        // Don't hand out a lock that validators would reject
+        if let Err(e) = vesting::check(&tx.transaction_data) {
+            debug!("Refusing to build time lock: {}", e);
+
+            return Err(invalid());
+        }
+
+        Ok(tx)
+    }
+
+    /// Gets how much of the given address's balance is locked, and how much may be spent right away.
+    fn get_locked_balance(&self, address: String) -> Result<LockedBalance> {
+        let address = DagImpl::parse_address(&self.runtime, &address)?;
+
+        // Get a lock on the client's runtime
+        let rt = if let Ok(rt) = self.runtime.read() {
+            rt
+        } else {
+            return Err(Error::new(ErrorCode::from(
+                error::ERROR_UNABLE_TO_OBTAIN_LOCK,
+            )));
+        };
+
+        Ok(rt.ledger.locked_balance(address, Utc::now()))
+    }
+}
+
+impl VestingImpl {
+    /// Registers the time-locked balance service on the given RPC handler.
+    pub fn register(io: &mut RpcHandler, runtime: Arc<InstrumentedRwLock<System>>) {
+        // Register this service on the IO handler
+        io.extend_with(Self { runtime }.to_delegate());
+    }
+}
+
+/// A client for the time-locked balance API.
+pub struct Client {
+    /// The underlying JSON-RPC client
+    rpc: RpcClient,
+}
+
+impl From<RpcClient> for Client {
+    /// Builds a client on top of the given JSON-RPC client, sharing its connections.
+    fn from(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+}
+
+impl Client {
+    /// Initializes a new Client with the given remote URL, or comma-separated list of URLs for redundant nodes.
+    pub fn new(server_addr: &str) -> Self {
+        Self {
+            rpc: RpcClient::new(server_addr),
+        }
+    }
+
+    /// Retries failed requests, and fails over between nodes, according to the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - How hard to try before giving up
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.rpc = self.rpc.with_retry_policy(policy);
+
+        self
+    }
+
+    /// Builds an unsigned transaction sending the given value to the recipient, locked until it vests.
+    pub async fn create_locked_tx(
+        &self,
+        sender: String,
+        recipient: String,
+        value: u128,
+        unlocks_at: Option<i64>,
+        milestone: Option<u64>,
+    ) -> std::result::Result<Transaction, ClientError> {
+        self.rpc
+            .call(
+                "create_locked_transaction",
+                (
+                    &sender,
+                    &recipient,
+                    value.to_string(),
+                    unlocks_at,
+                    milestone,
+                ),
+            )
+            .await
+    }
+
+    /// Gets how much of the given address's balance is locked, and how much may be spent right away.
+    pub async fn get_locked_balance(
+        &self,
+        address: String,
+    ) -> std::result::Result<LockedBalance, ClientError> {
+        self.rpc.call("get_locked_balance", (&address,)).await
+    }
+}
@@ -5,6 +5,10 @@ use libp2p::kad::record::Key;
 /// Every time we want to synchronize the local DAG, we'll download & then purge 10 transactions at a time.
 pub const TRANSACTIONS_PER_SYNCHRONIZATION_ROUND: u8 = 10;
 
+/// Light clients don't hear about new transactions over gossip, so they poll the network for headers past their head
+/// this often, in seconds.
+pub const LIGHT_HEADER_POLL_INTERVAL_SECS: u64 = 10;
+
 /// The byte-representation fo the proposals key
 pub const PROPOSALS_KEY: &[u8] = b"proposals";
 
@@ -23,6 +27,18 @@ pub const NEXT_TRANSACTION_KEY: &[u8] = b"ledger::transactions::next";
 /// Represents the DHT Key for an entry in the DAG with a particular hash.
 pub const TRANSACTION_KEY: &[u8] = b"ledger::transactions::tx";
 
+/// Represents the DHT Key for the manifest of the latest snapshot of the ledger.
+pub const LATEST_SNAPSHOT_KEY: &[u8] = b"ledger::snapshots::latest";
+
+/// Represents the DHT Key for a chunk of a snapshot of the ledger.
+pub const SNAPSHOT_CHUNK_KEY: &[u8] = b"ledger::snapshots::chunk";
+
+/// Represents the DHT Key for the header of the executed head, which light clients check balances against.
+pub const LIGHT_HEAD_KEY: &[u8] = b"ledger::light::head";
+
+/// Represents the DHT Key for the header of an entry in the DAG with a particular hash.
+pub const LIGHT_HEADER_KEY: &[u8] = b"ledger::light::header";
+
 /// Constructs a new NEXT_TRANSACTION_KEY from the given hash.
 pub fn next_transaction_key(hash: Hash) -> Key {
     // Format the normal next tx path with the given hash
@@ -34,3 +50,15 @@ pub fn transaction_with_hash_key(hash: Hash) -> Key {
     // Format the normal transaction path with the given hash
     Key::new(&format!("ledger::transactions::tx({})", hash.to_str()).as_bytes())
 }
+
+/// Constructs a new LIGHT_HEADER_KEY from the hash of the header's transaction.
+pub fn light_header_key(hash: Hash) -> Key {
+    // Format the normal header path with the given hash
+    Key::new(&format!("ledger::light::header({})", hash.to_str()).as_bytes())
+}
+
+/// Constructs a new SNAPSHOT_CHUNK_KEY from the hash of the chunk.
+pub fn snapshot_chunk_key(hash: Hash) -> Key {
+    // Format the normal chunk path with the given hash
+    Key::new(&format!("ledger::snapshots::chunk({})", hash.to_str()).as_bytes())
+}
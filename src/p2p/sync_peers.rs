@@ -0,0 +1,372 @@
+use std::collections::HashMap; // Import the collections library
+
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use libp2p::PeerId; // Import the libp2p peer identity type
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::{
+    super::crypto::hash::Hash, attestation::AttestedHead, latency::PeerLatency,
+    reconnect::Connectivity, updater::UpdateStatus,
+}; // Import the hash, head, connectivity & update types
+
+/// What we know about how synchronization with a single peer is going.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SyncPeer {
+    /// The base58-encoded ID of the peer
+    pub peer_id: String,
+
+    /// The version of SummerCash that the peer identified itself as running
+    pub agent_version: Option<String>,
+
+    /// The furthest transaction in the DAG that the peer has published a record for, as far as we've synchronized.
+    /// Peers don't announce their heads on their own, so this is only as fresh as the last record fetched.
+    pub advertised_head: Option<Hash>,
+
+    /// The number of DHT records published by the peer that we've fetched
+    pub records_fetched: u64,
+
+    /// The number of those records that we refused, since they couldn't be decoded, conflicted with a pinned
+    /// checkpoint, or held a transaction that couldn't be executed
+    pub records_rejected: u64,
+
+    /// The number of times that transactions supplied by the peer were discarded, along with the rest of their branch,
+    /// for breaking the ledger's supply invariants
+    pub invariant_violations: u64,
+
+    /// The number of pings to the peer that have failed since it last answered one
+    pub ping_failures: u32,
+
+    /// The smoothed round-trip time to the peer, in milliseconds, if it has answered a ping yet
+    pub rtt_ms: Option<u64>,
+
+    /// The last time that we heard anything from the peer
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+/// How far along the node is in synchronizing its DAG with the network.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SyncStatus {
+    /// The number of transactions in the node's DAG
+    pub height: usize,
+
+    /// The hash of the last transaction in the node's DAG, if it has any
+    pub head: Option<Hash>,
+
+    /// The number of peers that we've heard from
+    pub peers: usize,
+
+    /// The number of peers that have published records for transactions that the node doesn't hold yet
+    pub peers_ahead: usize,
+
+    /// The last time that a peer told us about a transaction further along the DAG
+    pub last_progress: Option<DateTime<Utc>>,
+
+    /// The last time that the network had nothing further along the DAG to give us
+    pub caught_up_at: Option<DateTime<Utc>>,
+
+    /// The head that enough stake-weighted publishers have vouched for to be synchronized towards, if any has
+    pub target_head: Option<AttestedHead>,
+
+    /// Whether or not the node holds everything that its peers have published, as far as it can tell
+    pub synchronized: bool,
+
+    /// How well the node is connected to the rest of the network
+    #[serde(default)]
+    pub connectivity: Connectivity,
+
+    /// Where the node stands against the latest release of the node software, if it checks for releases
+    #[serde(default)]
+    pub update: Option<UpdateStatus>,
+}
+
+/// What we know about how synchronization with each of our peers is going, so that operators can tell which peer is
+/// feeding the node bad or slow data.
+#[derive(Default)]
+pub struct SyncPeerTable {
+    /// Each of the peers that we've heard from
+    peers: HashMap<PeerId, SyncPeer>,
+
+    /// The last time that a peer told us about a transaction further along the DAG
+    last_progress: Option<DateTime<Utc>>,
+
+    /// The last time that the network had nothing further along the DAG to give us
+    caught_up_at: Option<DateTime<Utc>>,
+
+    /// The head that enough stake-weighted publishers have vouched for to be synchronized towards
+    target_head: Option<AttestedHead>,
+
+    /// How well the node was connected to the rest of the network when last checked
+    connectivity: Connectivity,
+
+    /// Where the node stood against the latest release of the node software when last checked
+    update: Option<UpdateStatus>,
+}
+
+/// Implement a set of sync peer table helper methods.
+impl SyncPeerTable {
+    /// Gets the entry for the given peer, making one if we haven't heard from the peer yet.
+    fn entry(&mut self, peer: &PeerId) -> &mut SyncPeer {
+        let entry = self.peers.entry(peer.clone()).or_insert_with(|| SyncPeer {
+            peer_id: peer.to_base58(),
+            ..Default::default()
+        });
+        entry.last_seen = Some(Utc::now());
+
+        entry
+    }
+
+    /// Records the version that the given peer identified itself as running.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer that identified itself
+    /// * `agent_version` - The version that the peer is running
+    pub fn record_identity(&mut self, peer: &PeerId, agent_version: &str) {
+        self.entry(peer).agent_version = Some(agent_version.to_owned());
+    }
+
+    /// Records the latest latency measurements of the given peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer that was pinged
+    /// * `latency` - What we've measured about the peer
+    pub fn record_latency(&mut self, peer: &PeerId, latency: &PeerLatency) {
+        let entry = self.entry(peer);
+        entry.rtt_ms = latency.rtt.map(|rtt| rtt.as_millis() as u64);
+        entry.ping_failures = latency.failures;
+    }
+
+    /// Records that a DHT record published by the given peer was fetched.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer that published the record
+    pub fn record_fetched(&mut self, peer: &PeerId) {
+        self.entry(peer).records_fetched += 1;
+    }
+
+    /// Records that a DHT record published by the given peer was refused.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer that published the record
+    pub fn record_rejected(&mut self, peer: &PeerId) {
+        self.entry(peer).records_rejected += 1;
+    }
+
+    /// Records that the given peer supplied part of a branch that was discarded for breaking the ledger's supply
+    /// invariants.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer that supplied part of the branch
+    pub fn record_violation(&mut self, peer: &PeerId) {
+        let entry = self.entry(peer);
+        entry.invariant_violations += 1;
+        entry.records_rejected += 1;
+    }
+
+    /// Records that the given peer has published a record for the transaction with the given hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer that published the record
+    /// * `head` - The hash of the transaction
+    pub fn record_head(&mut self, peer: &PeerId, head: Hash) {
+        self.entry(peer).advertised_head = Some(head);
+        self.last_progress = Some(Utc::now());
+    }
+
+    /// Records that the given head has been vouched for by enough publishers to be synchronized towards. Results in
+    /// whether or not the target changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The head to synchronize towards
+    pub fn record_target_head(&mut self, target: AttestedHead) -> bool {
+        let changed = self
+            .target_head
+            .as_ref()
+            .map(|head| (head.head, head.height))
+            != Some((target.head, target.height));
+        self.target_head = Some(target);
+
+        changed
+    }
+
+    /// Gets the head that enough stake-weighted publishers have vouched for to be synchronized towards, if any has.
+    pub fn target_head(&self) -> Option<&AttestedHead> {
+        self.target_head.as_ref()
+    }
+
+    /// Records that none of our peers had anything further along the DAG to give us.
+    pub fn record_caught_up(&mut self) {
+        self.caught_up_at = Some(Utc::now());
+    }
+
+    /// Records how well the node is connected to the rest of the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `connectivity` - How well the node is connected
+    pub fn record_connectivity(&mut self, connectivity: Connectivity) {
+        self.connectivity = connectivity;
+    }
+
+    /// Records where the node stands against the latest release of the node software.
+    ///
+    /// # Arguments
+    ///
+    /// * `update` - The node's update status
+    pub fn record_update(&mut self, update: UpdateStatus) {
+        self.update = Some(update);
+    }
+
+    /// Forgets everything we know about the given peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The peer that should be forgotten
+    pub fn forget(&mut self, peer: &PeerId) {
+        self.peers.remove(peer);
+    }
+
+    /// Gets each of the peers that we've heard from, those caught supplying fabricated state, then those whose records
+    /// were refused, most often first.
+    pub fn list(&self) -> Vec<SyncPeer> {
+        let mut peers: Vec<SyncPeer> = self.peers.values().cloned().collect();
+        peers.sort_by(|a, b| {
+            b.invariant_violations
+                .cmp(&a.invariant_violations)
+                .then(b.records_rejected.cmp(&a.records_rejected))
+                .then(b.ping_failures.cmp(&a.ping_failures))
+                .then(a.peer_id.cmp(&b.peer_id))
+        });
+
+        peers
+    }
+
+    /// Gets how far along the node is in synchronizing its DAG.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - The number of transactions in the node's DAG
+    /// * `head` - The hash of the last transaction in the node's DAG
+    /// * `holds` - Whether or not the node's DAG holds the transaction with a given hash
+    pub fn status(
+        &self,
+        height: usize,
+        head: Option<Hash>,
+        holds: impl Fn(&Hash) -> bool,
+    ) -> SyncStatus {
+        let peers_ahead = self
+            .peers
+            .values()
+            .filter(|peer| peer.advertised_head.map_or(false, |head| !holds(&head)))
+            .count();
+
+        SyncStatus {
+            height,
+            head,
+            peers: self.peers.len(),
+            peers_ahead,
+            last_progress: self.last_progress,
+            caught_up_at: self.caught_up_at,
+            target_head: self.target_head.clone(),
+            connectivity: self.connectivity,
+            update: self.update.clone(),
+            synchronized: peers_ahead == 0
+                && self
+                    .target_head
+                    .as_ref()
+                    .map_or(true, |target| holds(&target.head))
+                && match (self.caught_up_at, self.last_progress) {
+                    (Some(caught_up_at), Some(last_progress)) => caught_up_at >= last_progress,
+                    (caught_up_at, _) => caught_up_at.is_some(),
+                },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*; // Import names from our parent module
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_list() {
+        let (good, bad) = (PeerId::random(), PeerId::random());
+        let mut table = SyncPeerTable::default();
+
+        table.record_identity(&good, "summercash/0.1.0");
+        table.record_fetched(&good);
+        table.record_head(&good, Hash::new(vec![1; 32]));
+        table.record_latency(
+            &good,
+            &PeerLatency {
+                rtt: Some(Duration::from_millis(40)),
+                failures: 0,
+            },
+        );
+
+        table.record_fetched(&bad);
+        table.record_rejected(&bad);
+
+        let peers = table.list();
+        assert_eq!(peers[0].peer_id, bad.to_base58()); // Peers feeding bad data come first
+        assert_eq!(peers[0].records_rejected, 1);
+        assert_eq!(peers[1].rtt_ms, Some(40));
+        assert_eq!(peers[1].advertised_head, Some(Hash::new(vec![1; 32])));
+
+        table.forget(&bad);
+        assert_eq!(table.list().len(), 1);
+    }
+
+    #[test]
+    fn test_status() {
+        let peer = PeerId::random();
+        let (known, unknown) = (Hash::new(vec![1; 32]), Hash::new(vec![2; 32]));
+        let mut table = SyncPeerTable::default();
+
+        // The peer knows of a transaction that we haven't downloaded yet
+        table.record_head(&peer, unknown);
+        let status = table.status(1, Some(known), |hash| *hash == known);
+        assert_eq!(status.peers_ahead, 1);
+        assert!(!status.synchronized);
+
+        // Once it's downloaded, and the network has nothing more to give, we're synchronized
+        table.record_head(&peer, known);
+        assert!(
+            !table
+                .status(1, Some(known), |hash| *hash == known)
+                .synchronized
+        );
+        table.record_caught_up();
+        assert!(
+            table
+                .status(1, Some(known), |hash| *hash == known)
+                .synchronized
+        );
+
+        // Until we hold the head that the network has vouched for, we aren't synchronized
+        let target = AttestedHead {
+            head: unknown,
+            height: 2,
+            publishers: 2,
+            stake: 10u8.into(),
+            peers: 2,
+            subnets: 2,
+        };
+        assert!(table.record_target_head(target.clone()));
+        assert!(!table.record_target_head(target));
+        assert!(
+            !table
+                .status(1, Some(known), |hash| *hash == known)
+                .synchronized
+        );
+    }
+}
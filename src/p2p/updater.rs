@@ -0,0 +1,464 @@
+use std::{
+    fs,
+    path::Path,
+    sync::{Arc, RwLock},
+    time::Duration,
+}; // Import the filesystem, synchronization & time libraries
+
+use chrono::{DateTime, Utc}; // Import time utilities
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use super::{
+    super::{
+        common::address::Address,
+        core::{sys::config, types::signature::Signature},
+        crypto::{blake3, hash::Hash},
+    },
+    sync_peers::SyncPeerTable,
+}; // Import the address, signature, hash & sync status types
+
+/// The number of seconds between checks of the release manifest, by default.
+pub const DEFAULT_UPDATE_CHECK_INTERVAL_SECS: u64 = 21_600;
+
+/// An error encountered while checking for, or downloading, a release of the node software.
+#[derive(Debug, Fail, PartialEq)]
+pub enum UpdaterError {
+    #[fail(display = "failed to download {}: {}", url, error)]
+    Download {
+        url: String,   // The URL that was requested
+        error: String, // The underlying error
+    },
+    #[fail(display = "the release manifest at {} is malformed: {}", url, reason)]
+    Malformed {
+        url: String,    // The URL that the manifest was downloaded from
+        reason: String, // What's wrong with the manifest
+    },
+    #[fail(
+        display = "the release manifest at {} wasn't signed by the release signer {}",
+        url, signer
+    )]
+    UntrustedManifest {
+        url: String,    // The URL that the manifest was downloaded from
+        signer: String, // The address of the account that releases must be signed by
+    },
+    #[fail(
+        display = "the artifact at {} hashes to {}, not {} as the manifest says",
+        url, actual, expected
+    )]
+    ArtifactMismatch {
+        url: String,      // The URL that the artifact was downloaded from
+        expected: String, // The hash given by the manifest
+        actual: String,   // The hash of the downloaded artifact
+    },
+    #[fail(display = "failed to save the artifact to {}: {}", path, error)]
+    Io {
+        path: String,  // The path that the artifact was written to
+        error: String, // The underlying error
+    },
+}
+
+/// A release of the node software, as announced by the project's release signer.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReleaseManifest {
+    /// The version of the release (e.g. "v0.2.0")
+    pub version: String,
+
+    /// Whether or not nodes must upgrade to keep agreeing with the rest of the network
+    #[serde(default)]
+    pub consensus_critical: bool,
+
+    /// Where the release's build may be downloaded from, if anywhere
+    #[serde(default)]
+    pub artifact_url: Option<String>,
+
+    /// The blake3 hash of the release's build
+    #[serde(default)]
+    pub artifact_hash: Option<Hash>,
+
+    /// A short description of what the release changes
+    #[serde(default)]
+    pub notes: String,
+
+    /// When the release was published
+    pub published_at: DateTime<Utc>,
+
+    /// The release signer's signature of the manifest
+    pub signature: Option<Signature>,
+}
+
+/// Implement a set of release manifest helper methods.
+impl ReleaseManifest {
+    /// Hashes the contents of the manifest, excluding its signature.
+    pub fn hash(&self) -> Hash {
+        let mut to_be_hashed = self.clone();
+        to_be_hashed.signature = None;
+
+        blake3::hash_slice(&bincode::serialize(&to_be_hashed).unwrap_or_default())
+    }
+
+    /// Signs the manifest with the given keypair.
+    ///
+    /// # Arguments
+    ///
+    /// * `keypair` - The release signer's keypair
+    pub fn sign(&mut self, keypair: &ed25519_dalek::Keypair) {
+        self.signature = Some(Signature::new(keypair, &*self.hash()));
+    }
+
+    /// Ensures that the manifest was signed by the given release signer.
+    ///
+    /// # Arguments
+    ///
+    /// * `signer` - The address of the account that releases must be signed by
+    pub fn signed_by(&self, signer: &Address) -> bool {
+        match &self.signature {
+            Some(signature) => {
+                signature.address().ok().as_ref() == Some(signer) && signature.verify(&*self.hash())
+            }
+            None => false,
+        }
+    }
+
+    /// Checks whether or not the release is newer than the running node. Releases whose versions can't be parsed are
+    /// never newer.
+    pub fn is_newer(&self) -> bool {
+        match (
+            parse_version(&self.version),
+            parse_version(config::NODE_VERSION),
+        ) {
+            (Some(release), Some(running)) => release > running,
+            _ => false,
+        }
+    }
+
+    /// Checks whether or not the running node must upgrade to the release to keep agreeing with the rest of the
+    /// network: either the release says so, or peers running it won't talk to us.
+    pub fn is_critical(&self) -> bool {
+        self.is_newer()
+            && (self.consensus_critical || !config::is_compatible_with_client(&self.version))
+    }
+}
+
+/// Parses a version number of the form vMAJOR.MINOR.PATCH. Anything trailing the patch number (e.g. "-rc1") is
+/// ignored.
+///
+/// # Arguments
+///
+/// * `version` - The version number
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version.trim_start_matches('v').splitn(3, '.');
+    let mut next = || -> Option<u64> {
+        let digits: String = parts
+            .next()?
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+
+        digits.parse().ok()
+    };
+
+    Some((next()?, next()?, next()?))
+}
+
+/// Where the node stands against the latest release of the node software.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct UpdateStatus {
+    /// The version of the running node
+    pub running_version: String,
+
+    /// The version of the latest release that the node has heard of, if it has checked yet
+    pub latest_version: Option<String>,
+
+    /// Whether or not a newer release is out
+    pub update_available: bool,
+
+    /// Whether or not the node must upgrade to keep agreeing with the rest of the network
+    pub consensus_critical: bool,
+
+    /// Where the newer release's build was saved, once it has been downloaded and verified
+    pub artifact_path: Option<String>,
+
+    /// The last time that the release manifest was checked
+    pub last_checked: Option<DateTime<Utc>>,
+
+    /// Why the last check failed, if it did
+    pub last_error: Option<String>,
+
+    /// The number of times that the release manifest has been checked
+    pub checks: u64,
+
+    /// The number of those checks that failed
+    pub failed_checks: u64,
+}
+
+/// Where, and how often, the node looks for releases of the node software.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UpdaterConfig {
+    /// The URL of the signed release manifest
+    pub manifest_url: String,
+
+    /// The address of the account that releases must be signed by
+    pub signer: Address,
+
+    /// The number of seconds between checks
+    pub interval_secs: u64,
+
+    /// The directory that newer releases are downloaded to, if they should be downloaded at all
+    pub download_dir: Option<String>,
+}
+
+/// Periodically checks the release manifest, so that operators find out about releases (consensus-critical ones
+/// especially) from their node's logs & sync status, rather than once it has fallen out with the network.
+pub struct Updater {
+    /// Where, and how often, releases are looked for
+    config: UpdaterConfig,
+
+    /// Where the node stands against the latest release
+    status: UpdateStatus,
+
+    /// The sync status that the node's update status is reported through
+    sync_peers: Arc<RwLock<SyncPeerTable>>,
+}
+
+/// Implement a set of updater helper methods.
+impl Updater {
+    /// Makes an updater following the given config, reporting through the given sync status.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Where, and how often, releases are looked for
+    /// * `sync_peers` - The sync status that the node's update status is reported through
+    pub fn new(config: UpdaterConfig, sync_peers: Arc<RwLock<SyncPeerTable>>) -> Self {
+        Self {
+            config,
+            status: UpdateStatus {
+                running_version: config::NODE_VERSION.to_owned(),
+                ..Default::default()
+            },
+            sync_peers,
+        }
+    }
+
+    /// Checks the release manifest every so often, forever.
+    pub async fn run(mut self) {
+        loop {
+            self.check().await;
+
+            tokio::time::delay_for(Duration::from_secs(self.config.interval_secs.max(60))).await;
+        }
+    }
+
+    /// Checks the release manifest once, logging & reporting what came of it.
+    pub async fn check(&mut self) {
+        self.status.checks += 1;
+        self.status.last_checked = Some(Utc::now());
+
+        match self.fetch_manifest().await {
+            Ok(manifest) => {
+                self.status.last_error = None;
+
+                if let Err(e) = self.record(&manifest).await {
+                    warn!("Failed to download release {}: {}", manifest.version, e);
+
+                    self.status.last_error = Some(e.to_string());
+                }
+            }
+            Err(e) => {
+                warn!("Failed to check for releases of the node software: {}", e);
+
+                self.status.failed_checks += 1;
+                self.status.last_error = Some(e.to_string());
+            }
+        }
+
+        if let Ok(mut sync_peers) = self.sync_peers.write() {
+            sync_peers.record_update(self.status.clone());
+        }
+    }
+
+    /// Records the given release, downloading its build if it's newer & the operator asked for downloads.
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest` - The latest release
+    async fn record(&mut self, manifest: &ReleaseManifest) -> Result<(), UpdaterError> {
+        let first_seen = self.status.latest_version.as_ref() != Some(&manifest.version);
+
+        if first_seen {
+            self.status.artifact_path = None;
+        }
+
+        self.status.latest_version = Some(manifest.version.clone());
+        self.status.update_available = manifest.is_newer();
+        self.status.consensus_critical = manifest.is_critical();
+
+        if !self.status.update_available {
+            return Ok(());
+        }
+
+        if self.status.consensus_critical {
+            warn!(
+                "Release {} of the node software is consensus-critical (running {}); upgrade soon",
+                manifest.version,
+                config::NODE_VERSION
+            );
+        } else if first_seen {
+            info!(
+                "Release {} of the node software is out (running {}): {}",
+                manifest.version,
+                config::NODE_VERSION,
+                manifest.notes
+            );
+        }
+
+        // Download the build once, if the operator wants it on hand
+        if let (Some(dir), None) = (&self.config.download_dir, &self.status.artifact_path) {
+            let path = download_artifact(manifest, dir).await?;
+            info!(
+                "Downloaded & verified release {} to {}",
+                manifest.version, path
+            );
+
+            self.status.artifact_path = Some(path);
+        }
+
+        Ok(())
+    }
+
+    /// Downloads the release manifest, making sure that it was signed by the release signer.
+    async fn fetch_manifest(&self) -> Result<ReleaseManifest, UpdaterError> {
+        let url = &self.config.manifest_url;
+        let contents = download(url).await?;
+
+        let manifest: ReleaseManifest =
+            serde_json::from_slice(&contents).map_err(|e| UpdaterError::Malformed {
+                url: url.clone(),
+                reason: e.to_string(),
+            })?;
+
+        if !manifest.signed_by(&self.config.signer) {
+            return Err(UpdaterError::UntrustedManifest {
+                url: url.clone(),
+                signer: self.config.signer.to_str(),
+            });
+        }
+
+        Ok(manifest)
+    }
+}
+
+/// Downloads the build of the given release into the given directory, making sure that it hashes to what the manifest
+/// says. Results in the path that the build was saved to.
+///
+/// # Arguments
+///
+/// * `manifest` - The release
+/// * `dir` - The directory that the build should be saved in
+async fn download_artifact(manifest: &ReleaseManifest, dir: &str) -> Result<String, UpdaterError> {
+    let (url, expected) = match (&manifest.artifact_url, &manifest.artifact_hash) {
+        (Some(url), Some(hash)) => (url, hash),
+        _ => {
+            return Err(UpdaterError::Malformed {
+                url: manifest.version.clone(),
+                reason: "the release has no artifact URL & hash".to_owned(),
+            })
+        }
+    };
+
+    let contents = download(url).await?;
+    verify_artifact(url, &contents, expected)?;
+
+    let path = Path::new(dir).join(format!("smcd-{}", manifest.version));
+    let failed = |e: std::io::Error| UpdaterError::Io {
+        path: path.to_string_lossy().into_owned(),
+        error: e.to_string(),
+    };
+
+    fs::create_dir_all(dir).map_err(failed)?;
+    fs::write(&path, &contents).map_err(failed)?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Makes sure that the given artifact hashes to what its manifest says.
+///
+/// # Arguments
+///
+/// * `url` - The URL that the artifact was downloaded from
+/// * `contents` - The artifact
+/// * `expected` - The hash given by the manifest
+fn verify_artifact(url: &str, contents: &[u8], expected: &Hash) -> Result<(), UpdaterError> {
+    let actual = blake3::hash_slice(contents);
+
+    if actual != *expected {
+        return Err(UpdaterError::ArtifactMismatch {
+            url: url.to_owned(),
+            expected: expected.to_str(),
+            actual: actual.to_str(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Downloads the contents of the given URL.
+///
+/// # Arguments
+///
+/// * `url` - The URL
+async fn download(url: &str) -> Result<Vec<u8>, UpdaterError> {
+    let failed = |e: reqwest::Error| UpdaterError::Download {
+        url: url.to_owned(),
+        error: e.to_string(),
+    };
+
+    Ok(reqwest::get(url)
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .map_err(failed)?
+        .bytes()
+        .await
+        .map_err(failed)?
+        .to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::accounts::account::Account; // Import the account type
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_release_manifest() {
+        let signer = Account::new();
+        let mut manifest = ReleaseManifest {
+            version: "v0.2.0".to_owned(),
+            consensus_critical: false,
+            artifact_url: None,
+            artifact_hash: Some(blake3::hash_slice(b"smcd")),
+            notes: "Faster sync".to_owned(),
+            published_at: Utc::now(),
+            signature: None,
+        };
+        manifest.sign(&signer.keypair().unwrap());
+
+        // Only manifests signed by the release signer are trusted, and tampering breaks the signature
+        assert!(manifest.signed_by(&signer.address().unwrap()));
+        assert!(!manifest.signed_by(&Account::new().address().unwrap()));
+        manifest.version = "v0.1.9".to_owned();
+        assert!(!manifest.signed_by(&signer.address().unwrap()));
+
+        // Breaking releases are critical, even if the manifest doesn't say so
+        manifest.version = "v0.2.0".to_owned();
+        assert!(manifest.is_newer() && manifest.is_critical());
+        manifest.version = "v0.1.1-rc1".to_owned();
+        assert!(manifest.is_newer() && !manifest.is_critical());
+        manifest.version = config::NODE_VERSION.to_owned();
+        assert!(!manifest.is_newer());
+        assert_eq!(parse_version("v1.2"), None);
+
+        // Downloaded builds must match the manifest
+        assert!(verify_artifact("url", b"smcd", &blake3::hash_slice(b"smcd")).is_ok());
+        assert!(verify_artifact("url", b"tampered", &blake3::hash_slice(b"smcd")).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+/// The number of times that a message left in the log is replayed before it's given up on, so that a message that
+/// crashes the client while being handled can't keep the client from starting.
+pub const MAX_REPLAY_ATTEMPTS: u32 = 3;
+
+/// The name of the tree holding the number of times that each entry has been replayed.
+const ATTEMPTS_TREE: &str = "attempts";
+
+/// A raw gossip message that has been received, but not yet fully processed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Entry {
+    /// The topic that the message was received on
+    pub topic: String,
+    /// The raw contents of the message
+    pub data: Vec<u8>,
+}
+
+/// A write-ahead log for incoming gossip. Each message is appended to the log before it is
+/// processed, and removed once processing has finished. Any entries left behind by a crash can be
+/// replayed on the next start.
+pub struct WriteAheadLog {
+    /// The database backing the log
+    db: sled::Db,
+    /// The number of times that each entry has been replayed
+    attempts: sled::Tree,
+}
+
+/// Implement a set of WAL helper methods.
+impl WriteAheadLog {
+    /// Opens the write-ahead log for the given network in the provided data directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - The directory in which the log should be stored
+    /// * `network_name` - The name of the network that the log records messages for
+    pub fn open(data_dir: &str, network_name: &str) -> Result<Self, sled::Error> {
+        let db = sled::open(format!("{}/wal/{}", data_dir, network_name))?;
+
+        Ok(Self {
+            attempts: db.open_tree(ATTEMPTS_TREE)?,
+            db,
+        }) // Return the opened log
+    }
+
+    /// Appends a raw message to the log, and flushes it to the disk. Returns the sequence number
+    /// of the entry, which must be passed to complete() once the message has been handled.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic that the message was received on
+    /// * `data` - The raw contents of the message
+    pub fn append(&self, topic: &str, data: &[u8]) -> Result<u64, sled::Error> {
+        let seq = self.db.generate_id()?; // Get a monotonically increasing sequence number
+
+        // Serialize the entry so that we can put it in the db
+        let entry = bincode::serialize(&Entry {
+            topic: topic.to_owned(),
+            data: data.to_vec(),
+        })
+        .map_err(|e| sled::Error::Unsupported(e.to_string()))?;
+
+        self.db.insert(seq.to_be_bytes(), entry)?; // Record the entry
+        self.db.flush()?; // Make sure the entry survives a crash
+
+        Ok(seq) // Return the sequence number of the entry
+    }
+
+    /// Marks the entry with the given sequence number as processed, removing it from the log.
+    ///
+    /// # Arguments
+    ///
+    /// * `seq` - The sequence number returned by append()
+    pub fn complete(&self, seq: u64) -> Result<(), sled::Error> {
+        self.db.remove(seq.to_be_bytes())?; // Remove the entry
+        self.attempts.remove(seq.to_be_bytes())?; // Forget how often it was replayed
+
+        Ok(()) // Done!
+    }
+
+    /// Records an attempt at replaying the entry with the given sequence number, and flushes it to the disk before the
+    /// entry is replayed, so that an entry that crashes the client is given up on eventually. Returns the number of
+    /// attempts made so far, including this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `seq` - The sequence number of the entry that is about to be replayed
+    pub fn attempt(&self, seq: u64) -> Result<u32, sled::Error> {
+        let attempts: u32 = self
+            .attempts
+            .get(seq.to_be_bytes())?
+            .and_then(|encoded| bincode::deserialize(&encoded).ok())
+            .unwrap_or(0)
+            + 1;
+
+        self.attempts.insert(
+            seq.to_be_bytes(),
+            bincode::serialize(&attempts).map_err(|e| sled::Error::Unsupported(e.to_string()))?,
+        )?; // Record the attempt
+        self.db.flush()?; // Make sure the attempt survives a crash
+
+        Ok(attempts) // Return the number of attempts so far
+    }
+
+    /// Gets each of the entries that have not yet been processed, in the order they were received.
+    pub fn pending(&self) -> Vec<(u64, Entry)> {
+        self.db
+            .iter()
+            .filter_map(|pair| {
+                let (key, value) = pair.ok()?; // Skip any unreadable entries
+
+                // Keys are always big-endian u64s
+                let mut seq_bytes = [0u8; 8];
+                seq_bytes.copy_from_slice(key.get(..8)?);
+
+                Some((
+                    u64::from_be_bytes(seq_bytes),
+                    bincode::deserialize(&value).ok()?,
+                ))
+            })
+            .collect() // Return the collected entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::common::io; // Import the io module
+    use super::*; // Import names from parent module
+    use rand::Rng; // Import rand
+
+    #[test]
+    fn test_append_complete() {
+        let rand: u16 = rand::thread_rng().gen(); // Generate random number
+
+        let wal = WriteAheadLog::open(&format!("{}/.tests/wal", io::data_dir()), &rand.to_string())
+            .unwrap(); // Open the log
+
+        let first = wal.append("proposals", b"first").unwrap(); // Append a message
+        let second = wal.append("votes", b"second").unwrap(); // Append another message
+
+        wal.complete(first).unwrap(); // Mark the first message as processed
+
+        let pending = wal.pending(); // Get the remaining messages
+
+        assert_eq!(pending.len(), 1); // Only the second message should remain
+        assert_eq!(pending[0].0, second); // Ensure has the correct sequence number
+        assert_eq!(pending[0].1.topic, "votes"); // Ensure has the correct topic
+        assert_eq!(pending[0].1.data, b"second"); // Ensure has the correct data
+    }
+
+    #[test]
+    fn test_attempt() {
+        let rand: u16 = rand::thread_rng().gen(); // Generate random number
+
+        let wal = WriteAheadLog::open(&format!("{}/.tests/wal", io::data_dir()), &rand.to_string())
+            .unwrap(); // Open the log
+
+        let seq = wal.append("proposals", b"poison").unwrap(); // Append a message
+
+        assert_eq!(wal.attempt(seq).unwrap(), 1); // Ensure the first attempt is counted
+        assert_eq!(wal.attempt(seq).unwrap(), 2); // Ensure attempts add up
+        assert_eq!(wal.pending().len(), 1); // Attempts shouldn't show up as entries
+
+        wal.complete(seq).unwrap(); // Mark the message as processed
+
+        assert!(wal.pending().is_empty()); // Ensure the message is gone
+        assert_eq!(wal.attempt(seq).unwrap(), 1); // Ensure its attempts were forgotten
+    }
+}
@@ -0,0 +1,570 @@
+use cryptolib::{hmac::Hmac, mac::Mac, sha2::Sha256}; // Import the notification signing primitives
+
+use num::BigUint; // Add support for large unsigned integers
+
+use reqwest::{header::CONTENT_TYPE, StatusCode}; // Import the HTTP header & status types
+
+use serde::{Deserialize, Serialize}; // Import serde serialization
+
+use std::{collections::HashSet, sync::mpsc, thread, time::Duration}; // Import the set, channel, thread & time types
+
+use super::{
+    super::{
+        common::address::Address,
+        core::{
+            sys::{
+                conflicts::DoubleSpendAlert,
+                history::{ExecutedProposal, ExecutionOutcome},
+            },
+            types::{receipt::ReceiptItem, transaction::Transaction},
+        },
+        crypto::hash::Hash,
+    },
+    attestation::AttestedHead,
+}; // Import the address, execution, receipt, transaction, hash & attested head types
+
+/// The name of the header carrying the HMAC-SHA256 signature of each notification's body, if the operator gave a
+/// secret.
+pub const SIGNATURE_HEADER: &str = "X-SummerCash-Signature";
+
+/// The number of times that delivering a notification is attempted before it is given up on, by default.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// The number of milliseconds waited before retrying a failed delivery for the first time, by default. The wait
+/// doubles with each attempt after that.
+pub const DEFAULT_INITIAL_BACKOFF_MS: u64 = 500;
+
+/// The greatest number of milliseconds waited between attempts to deliver a notification.
+pub const MAX_BACKOFF_MS: u64 = 60 * 1000;
+
+/// The number of seconds that a single delivery attempt may take before it is abandoned.
+pub const DELIVERY_TIMEOUT_SECS: u64 = 10;
+
+/// The number of transactions that the node may trail the attested head of the network by before the webhooks are
+/// told that it has fallen behind, by default.
+pub const DEFAULT_BEHIND_BY: u64 = 32;
+
+/// An error encountered while setting up a webhook.
+#[derive(Debug, Fail)]
+pub enum WebhookError {
+    #[fail(
+        display = "invalid webhook URL {} (expected an http or https URL)",
+        url
+    )]
+    InvalidUrl {
+        url: String, // The URL that was given
+    },
+    #[fail(display = "failed to start delivering to the webhook: {}", error)]
+    Unavailable {
+        error: String, // Why the webhook couldn't be started
+    },
+}
+
+/// How notifications are delivered to a webhook.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebhookPolicy {
+    /// The secret that each notification's body is signed with, if any
+    pub secret: Option<String>,
+
+    /// The number of times that delivering a notification is attempted before it is given up on
+    pub max_attempts: u32,
+
+    /// The number of milliseconds waited before retrying a failed delivery for the first time
+    pub initial_backoff_ms: u64,
+}
+
+impl Default for WebhookPolicy {
+    /// Builds a policy that leaves notifications unsigned, retrying each up to five times.
+    fn default() -> Self {
+        Self {
+            secret: None,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff_ms: DEFAULT_INITIAL_BACKOFF_MS,
+        }
+    }
+}
+
+/// Implement a set of webhook policy helper methods.
+impl WebhookPolicy {
+    /// Gets the time to wait before retrying a delivery that has failed the given number of times.
+    ///
+    /// # Arguments
+    ///
+    /// * `failures` - The number of attempts that have failed so far
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let factor = 1u64
+            .checked_shl(failures.saturating_sub(1))
+            .unwrap_or(u64::max_value());
+
+        Duration::from_millis(
+            self.initial_backoff_ms
+                .saturating_mul(factor)
+                .min(MAX_BACKOFF_MS),
+        )
+    }
+}
+
+/// Signs the given notification body with the given secret, so that receivers can tell that it came from the node.
+/// Results in the value of the signature header: the hex-encoded HMAC-SHA256 of the body, prefixed with "sha256=".
+///
+/// # Arguments
+///
+/// * `secret` - The secret shared with the receiver
+/// * `body` - The body of the notification
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut hmac = Hmac::new(Sha256::new(), secret.as_bytes());
+    hmac.input(body);
+
+    let digest: String = hmac
+        .result()
+        .code()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    format!("sha256={}", digest)
+}
+
+/// An event that the node POSTs to its webhooks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Notification {
+    /// A transaction sent by, or paying, one of the watched accounts was executed
+    TransactionExecuted {
+        hash: Hash,
+        proposal_id: Hash,
+        sender: Address,
+        recipient: Address,
+        value: BigUint,
+        outcome: ExecutionOutcome,
+        items: Vec<ReceiptItem>,
+    },
+
+    /// A proposal was executed
+    ProposalExecuted { execution: ExecutedProposal },
+
+    /// The node trails the head that the network has vouched for by more than the operator allows
+    FellBehind {
+        height: u64,
+        target_head: Hash,
+        target_height: u64,
+    },
+
+    /// The node has caught back up to the head that the network has vouched for
+    CaughtUp { height: u64 },
+
+    /// Competing transactions spending the same nonce were seen
+    DoubleSpend { alert: DoubleSpendAlert },
+}
+
+/// An operator-provided URL that the node POSTs JSON notifications to. Notifications are delivered in order, from a
+/// thread of their own, so a slow or unreachable endpoint never holds up the node.
+pub struct Webhook {
+    /// The URL that notifications are POSTed to
+    url: String,
+
+    /// The queue of serialized notifications waiting to be delivered
+    sender: mpsc::Sender<Vec<u8>>,
+}
+
+/// Implement a set of webhook helper methods.
+impl Webhook {
+    /// Starts delivering notifications to the given URL, following the default policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The http or https URL that notifications should be POSTed to
+    pub fn new(url: &str) -> Result<Self, WebhookError> {
+        Self::with_policy(url, WebhookPolicy::default())
+    }
+
+    /// Starts delivering notifications to the given URL, following the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The http or https URL that notifications should be POSTed to
+    /// * `policy` - How notifications should be signed & retried
+    pub fn with_policy(url: &str, policy: WebhookPolicy) -> Result<Self, WebhookError> {
+        match reqwest::Url::parse(url) {
+            Ok(parsed) if parsed.scheme() == "http" || parsed.scheme() == "https" => (),
+            _ => {
+                return Err(WebhookError::InvalidUrl {
+                    url: url.to_owned(),
+                })
+            }
+        };
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .build()
+            .map_err(|e| WebhookError::Unavailable {
+                error: e.to_string(),
+            })?;
+
+        let (sender, receiver) = mpsc::channel::<Vec<u8>>();
+        let target = url.to_owned();
+
+        thread::Builder::new()
+            .name("webhook".to_owned())
+            .spawn(move || {
+                let mut rt = match tokio::runtime::Builder::new()
+                    .basic_scheduler()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(rt) => rt,
+                    Err(e) => {
+                        error!("Failed to start delivering to webhook {}: {}", target, e);
+
+                        return;
+                    }
+                };
+
+                // Deliver each notification until the node shuts down
+                for body in receiver {
+                    let signature = policy.secret.as_ref().map(|secret| sign(secret, &body));
+
+                    for attempt in 1..=policy.max_attempts.max(1) {
+                        let mut request = client
+                            .post(&target)
+                            .header(CONTENT_TYPE, "application/json")
+                            .body(body.clone());
+                        if let Some(signature) = &signature {
+                            request = request.header(SIGNATURE_HEADER, signature.as_str());
+                        }
+
+                        // Only retry failures that might go away on their own
+                        let (retryable, error) = match rt.block_on(request.send()) {
+                            Ok(resp) if resp.status().is_success() => break,
+                            Ok(resp) => (
+                                resp.status().is_server_error()
+                                    || resp.status() == StatusCode::TOO_MANY_REQUESTS,
+                                resp.status().to_string(),
+                            ),
+                            Err(e) => (true, e.to_string()),
+                        };
+
+                        if !retryable || attempt >= policy.max_attempts {
+                            warn!(
+                                "Giving up on delivering a notification to webhook {} after {} attempt(s): {}",
+                                target, attempt, error
+                            );
+
+                            break;
+                        }
+
+                        debug!(
+                            "Failed to deliver a notification to webhook {} (attempt {}): {}",
+                            target, attempt, error
+                        );
+
+                        thread::sleep(policy.backoff(attempt));
+                    }
+                }
+            })
+            .map_err(|e| WebhookError::Unavailable {
+                error: e.to_string(),
+            })?;
+
+        Ok(Self {
+            url: url.to_owned(),
+            sender,
+        })
+    }
+
+    /// Gets the URL that notifications are POSTed to.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Queues the given notification to be POSTed to the webhook as JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `notification` - The notification that should be delivered
+    pub fn notify<T: Serialize>(&self, notification: &T) {
+        match serde_json::to_vec(notification) {
+            Ok(body) => {
+                if self.sender.send(body).is_err() {
+                    warn!("Webhook {} is no longer being delivered to", self.url);
+                }
+            }
+            Err(e) => warn!("Failed to serialize a webhook notification: {}", e),
+        }
+    }
+}
+
+/// Which webhooks the node notifies, and of what.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebhookConfig {
+    /// Each of the URLs that notifications are POSTed to
+    pub urls: Vec<String>,
+
+    /// How notifications are signed & retried
+    pub policy: WebhookPolicy,
+
+    /// The accounts whose executed transactions the webhooks are told about
+    pub watched: Vec<Address>,
+
+    /// The number of transactions that the node may trail the attested head of the network by before the webhooks
+    /// are told that it has fallen behind
+    pub behind_by: u64,
+}
+
+impl Default for WebhookConfig {
+    /// Builds a config that notifies no webhooks.
+    fn default() -> Self {
+        Self {
+            urls: Vec::new(),
+            policy: WebhookPolicy::default(),
+            watched: Vec::new(),
+            behind_by: DEFAULT_BEHIND_BY,
+        }
+    }
+}
+
+/// Hands the events that the operator asked to hear about to each of the node's webhooks.
+#[derive(Default)]
+pub struct WebhookDispatcher {
+    /// Each of the webhooks that notifications are delivered to
+    webhooks: Vec<Webhook>,
+
+    /// The addresses of the accounts whose executed transactions are delivered
+    watched: HashSet<String>,
+
+    /// The number of transactions that the node may trail the attested head of the network by
+    behind_by: u64,
+
+    /// Whether or not the webhooks have been told that the node has fallen behind, and not yet that it caught up
+    behind: bool,
+}
+
+/// Implement a set of webhook dispatcher helper methods.
+impl WebhookDispatcher {
+    /// Starts delivering notifications to each of the webhooks in the given config.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Which webhooks should be notified, and of what
+    pub fn new(config: &WebhookConfig) -> Result<Self, WebhookError> {
+        Ok(Self {
+            webhooks: config
+                .urls
+                .iter()
+                .map(|url| Webhook::with_policy(url, config.policy.clone()))
+                .collect::<Result<Vec<Webhook>, WebhookError>>()?,
+            watched: config.watched.iter().map(|addr| addr.to_str()).collect(),
+            behind_by: config.behind_by,
+            behind: false,
+        })
+    }
+
+    /// Checks whether or not there are any webhooks to notify.
+    pub fn is_empty(&self) -> bool {
+        self.webhooks.is_empty()
+    }
+
+    /// Queues the given notification to be delivered to each of the webhooks.
+    ///
+    /// # Arguments
+    ///
+    /// * `notification` - The notification that should be delivered
+    pub fn dispatch(&self, notification: &Notification) {
+        for webhook in &self.webhooks {
+            webhook.notify(notification);
+        }
+    }
+
+    /// Builds the notifications describing the execution of a proposal: the execution itself, and the transaction
+    /// that it appended, if the transaction moved value to or from a watched account.
+    ///
+    /// # Arguments
+    ///
+    /// * `execution` - The executed proposal
+    /// * `tx` - The transaction that the proposal appended to the ledger, if any
+    /// * `items` - Each movement of value made by the transaction
+    pub fn executed(
+        &self,
+        execution: &ExecutedProposal,
+        tx: Option<&Transaction>,
+        items: Vec<ReceiptItem>,
+    ) -> Vec<Notification> {
+        let mut notifications = vec![Notification::ProposalExecuted {
+            execution: execution.clone(),
+        }];
+
+        if let Some(tx) = tx {
+            let watched = |addr: &Address| self.watched.contains(&addr.to_str());
+
+            if watched(&tx.transaction_data.sender)
+                || watched(&tx.transaction_data.recipient)
+                || items
+                    .iter()
+                    .any(|item| self.watched.contains(&item.account))
+            {
+                notifications.push(Notification::TransactionExecuted {
+                    hash: tx.hash,
+                    proposal_id: execution.proposal_id,
+                    sender: tx.transaction_data.sender,
+                    recipient: tx.transaction_data.recipient,
+                    value: tx.transaction_data.value.clone(),
+                    outcome: execution.outcome.clone(),
+                    items,
+                });
+            }
+        }
+
+        notifications
+    }
+
+    /// Checks how far the node trails the head that the network has vouched for. Results in a notification the
+    /// first time that the node falls too far behind, and again once it catches back up.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - The number of transactions in the node's DAG
+    /// * `target` - The head that the node is synchronizing towards, if any
+    pub fn sync_progress(
+        &mut self,
+        height: u64,
+        target: Option<&AttestedHead>,
+    ) -> Option<Notification> {
+        let trailing = target.map_or(0, |target| target.height.saturating_sub(height));
+
+        match (self.behind, target) {
+            (false, Some(target)) if trailing > self.behind_by => {
+                self.behind = true;
+
+                Some(Notification::FellBehind {
+                    height,
+                    target_head: target.head,
+                    target_height: target.height,
+                })
+            }
+            (true, _) if trailing == 0 => {
+                self.behind = false;
+
+                Some(Notification::CaughtUp { height })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::crypto::blake3; // Import the blake3 hashing module
+    use super::*; // Import names from our parent module
+
+    #[test]
+    fn test_new() {
+        assert!(Webhook::new("not a url").is_err());
+        assert!(Webhook::new("ftp://example.com/alerts").is_err());
+
+        let webhook = Webhook::new("https://example.com/alerts").unwrap();
+        assert_eq!(webhook.url(), "https://example.com/alerts");
+    }
+
+    #[test]
+    fn test_sign() {
+        // Test vector 2 of RFC 4231
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = WebhookPolicy::default();
+
+        assert_eq!(
+            policy.backoff(1),
+            Duration::from_millis(DEFAULT_INITIAL_BACKOFF_MS)
+        );
+        assert_eq!(
+            policy.backoff(3),
+            Duration::from_millis(DEFAULT_INITIAL_BACKOFF_MS * 4)
+        );
+        assert_eq!(policy.backoff(100), Duration::from_millis(MAX_BACKOFF_MS));
+    }
+
+    #[test]
+    fn test_dispatcher() {
+        let (alice, bob, carol) = (
+            blake3::hash_slice(b"alice"),
+            blake3::hash_slice(b"bob"),
+            blake3::hash_slice(b"carol"),
+        );
+        let mut dispatcher = WebhookDispatcher::new(&WebhookConfig {
+            watched: vec![bob],
+            behind_by: 2,
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(dispatcher.is_empty());
+
+        let execution = ExecutedProposal {
+            seq: 0,
+            proposal_id: blake3::hash_slice(b"proposal"),
+            proposal_name: "transfer".to_owned(),
+            params: vec!["ledger::transactions".to_owned()],
+            operation: "append".to_owned(),
+            outcome: ExecutionOutcome::Applied,
+            executed_at: chrono::Utc::now(),
+            state_hash: None,
+        };
+
+        // Only transactions touching watched accounts are reported
+        let to_bob = Transaction::new(0, alice, bob, BigUint::from(5 as u8), b"", vec![]);
+        let to_carol = Transaction::new(1, alice, carol, BigUint::from(5 as u8), b"", vec![]);
+        assert_eq!(
+            dispatcher.executed(&execution, Some(&to_bob), Vec::new()),
+            vec![
+                Notification::ProposalExecuted {
+                    execution: execution.clone()
+                },
+                Notification::TransactionExecuted {
+                    hash: to_bob.hash,
+                    proposal_id: execution.proposal_id,
+                    sender: alice,
+                    recipient: bob,
+                    value: BigUint::from(5 as u8),
+                    outcome: ExecutionOutcome::Applied,
+                    items: Vec::new(),
+                }
+            ]
+        );
+        assert_eq!(
+            dispatcher
+                .executed(&execution, Some(&to_carol), Vec::new())
+                .len(),
+            1
+        );
+
+        // Falling behind is only reported once, until the node catches back up
+        let target = AttestedHead {
+            head: blake3::hash_slice(b"head"),
+            height: 10,
+            publishers: 2,
+            stake: BigUint::from(1 as u8),
+            peers: 2,
+            subnets: 2,
+        };
+        assert_eq!(dispatcher.sync_progress(8, Some(&target)), None);
+        assert_eq!(
+            dispatcher.sync_progress(7, Some(&target)),
+            Some(Notification::FellBehind {
+                height: 7,
+                target_head: target.head,
+                target_height: 10,
+            })
+        );
+        assert_eq!(dispatcher.sync_progress(6, Some(&target)), None);
+        assert_eq!(dispatcher.sync_progress(9, Some(&target)), None);
+        assert_eq!(
+            dispatcher.sync_progress(10, Some(&target)),
+            Some(Notification::CaughtUp { height: 10 })
+        );
+    }
+}
@@ -1,9 +1,18 @@
 use super::{
-    common::address::Address,
-    core::types::{graph::Graph, transaction::Transaction},
-    crypto::{blake3, hash::Hash},
+    common::address::{Address, AddressKind},
+    core::types::{
+        batch, burn, gas,
+        graph::Graph,
+        guardians::{self, GuardianInstruction},
+        rewards,
+        transaction::Transaction,
+        vesting,
+    },
+    crypto::hash::Hash,
 };
+use chrono::Utc;
 use num::{BigUint, Zero};
+use serde::{Deserialize, Serialize};
 
 /// A generic rule-enforcing transactional system.
 pub trait Validator {
@@ -27,6 +36,11 @@ pub struct GraphBoundValidator<'a> {
 pub enum GraphBoundValidatorReason {
     #[fail(display = "transaction {} is not unique", tx_hash)]
     NotUnique { tx_hash: Hash },
+    #[fail(
+        display = "transaction {} cites parent {}, which hasn't been seen yet",
+        tx_hash, parent_hash
+    )]
+    MissingParent { tx_hash: Hash, parent_hash: Hash },
     #[fail(
         display = "transaction {} is too old; parent node {} has already been executed",
         tx_hash, invalid_parent_hash
@@ -67,8 +81,115 @@ pub enum GraphBoundValidatorReason {
         tx_hash: Hash,
         offending_account: Address,
         found: u64,
-        target: BigUint,
+        target: u64,
+    },
+    #[fail(
+        display = "transaction {} breaks the account recovery rules: {}",
+        tx_hash, reason
+    )]
+    GuardianRuleViolated { tx_hash: Hash, reason: String },
+    #[fail(
+        display = "transaction {} breaks the batch transfer rules: {}",
+        tx_hash, reason
+    )]
+    BatchRuleViolated { tx_hash: Hash, reason: String },
+    #[fail(
+        display = "transaction {} breaks the time lock rules: {}",
+        tx_hash, reason
+    )]
+    VestingRuleViolated { tx_hash: Hash, reason: String },
+    #[fail(
+        display = "the sender ({}) of transaction {} has {} finks locked, leaving {} spendable; insufficient to send {}",
+        sender, tx_hash, locked, spendable, tx_value
+    )]
+    LockedFunds {
+        tx_hash: Hash,
+        sender: Address,
+        locked: BigUint,
+        spendable: BigUint,
+        tx_value: BigUint,
     },
+    #[fail(display = "transaction {} breaks the burn rules: {}", tx_hash, reason)]
+    BurnRuleViolated { tx_hash: Hash, reason: String },
+    #[fail(display = "transaction {} breaks the gas rules: {}", tx_hash, reason)]
+    GasRuleViolated { tx_hash: Hash, reason: String },
+    #[fail(
+        display = "transaction {} sends funds to contract {} without a call payload",
+        tx_hash, recipient
+    )]
+    PlainTransferToContract { tx_hash: Hash, recipient: Address },
+}
+
+/// A single check performed by a GraphBoundValidator.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Check {
+    Uniqueness,
+    Recency,
+    Signature,
+    Hash,
+    ParentExecution,
+    SenderBalance,
+    Identity,
+    Nonce,
+    Guardians,
+    Batch,
+    Vesting,
+    Burn,
+    Recipient,
+    Gas,
+}
+
+/// Each of the checks performed by a GraphBoundValidator, in the order in which they are run.
+pub const CHECKS: [Check; 14] = [
+    Check::Uniqueness,
+    Check::Recency,
+    Check::Signature,
+    Check::Hash,
+    Check::ParentExecution,
+    Check::SenderBalance,
+    Check::Identity,
+    Check::Nonce,
+    Check::Guardians,
+    Check::Batch,
+    Check::Vesting,
+    Check::Burn,
+    Check::Recipient,
+    Check::Gas,
+];
+
+/// The outcome of a single validation check.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CheckResult {
+    /// The check that was performed
+    pub check: Check,
+
+    /// Whether or not the transaction passed the check
+    pub passed: bool,
+
+    /// Why the transaction failed the check, if it did
+    pub reason: Option<String>,
+}
+
+/// A complete account of how a transaction fared against each of a validator's checks.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ValidationReport {
+    /// The hash of the transaction that was validated
+    pub tx_hash: Hash,
+
+    /// Whether or not the transaction passed every check
+    pub valid: bool,
+
+    /// The result of each check, in the order in which they were run
+    pub checks: Vec<CheckResult>,
+
+    /// The nonce that the validator expected the transaction to have
+    pub expected_nonce: u64,
+
+    /// The hash of the merged state of the transaction's parents, if they could be resolved
+    pub expected_parent_state_hash: Option<Hash>,
+
+    /// The balance of the sender of the transaction at the executed head of the graph
+    pub sender_balance: BigUint,
 }
 
 impl<'a> GraphBoundValidator<'a> {
@@ -95,6 +216,24 @@ impl<'a> GraphBoundValidator<'a> {
         !self.graph.hash_routes.contains_key(&tx.hash)
     }
 
+    /// Gets each of the transaction's parents that the graph doesn't hold yet. Until they arrive, the transaction can't
+    /// be judged either way.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction whose parents should be looked up
+    pub fn missing_parents(&self, tx: &Transaction) -> Vec<Hash> {
+        tx.transaction_data
+            .parents
+            .iter()
+            .filter(|parent| match self.graph.hash_routes.get(parent) {
+                Some(parent_index) => !matches!(self.graph.get_pure(*parent_index), Ok(Some(_))),
+                None => true,
+            })
+            .copied()
+            .collect()
+    }
+
     /// Checks whether or not the transaction exists along an incomplete head. In other words, the transaction must be
     /// recent enough in order to be valid.
     ///
@@ -110,15 +249,12 @@ impl<'a> GraphBoundValidator<'a> {
             // If we know what the index of this parent transaction is in the state graph, we can
             // try to pull out a fully-formed state matching this parent transaction
             if let Some(parent_index) = self.graph.hash_routes.get(&parent) {
-                // Check that the parent exists. If it doesen't, return false.
+                // Parents that don't exist yet are reported as missing, rather than as too old
                 if let Ok(Some(parent)) = self.graph.get_pure(*parent_index) {
                     // The parent node shouldn't have already been resolved. The transaction is, thus, invalid.
                     if parent.state_entry.is_some() {
                         return (false, parent.hash);
                     }
-                } else {
-                    // Since the transaction's parents don't exist, the tx isn't valid
-                    return (false, Hash::new("NILPARENT".to_owned().into_bytes()));
                 }
             }
         }
@@ -133,8 +269,7 @@ impl<'a> GraphBoundValidator<'a> {
     /// * `tx` - The transaction that should be checked for uniqueness among the graph's txs
     fn transaction_hash_is_valid(&self, tx: &Transaction) -> (bool, Hash) {
         // Hash the transaction
-        let target =
-            blake3::hash_slice(&bincode::serialize(&tx.transaction_data).unwrap_or_default());
+        let target = tx.transaction_data.hash();
 
         // Make sure that the transaction's hash can be reproduced
         (tx.hash == target, target)
@@ -174,7 +309,8 @@ impl<'a> GraphBoundValidator<'a> {
         }
     }
 
-    /// Ensures that the sender of the provided transaction has enough SummerCash to perform the transaction.
+    /// Ensures that the sender of the provided transaction has enough SummerCash to perform the transaction, and to
+    /// pay the most that it could in fees. Results in the sender's balance, and the most that the transaction costs.
     ///
     /// # Arguments
     ///
@@ -194,21 +330,19 @@ impl<'a> GraphBoundValidator<'a> {
                     .get(&tx.transaction_data.sender.to_str())
                     .unwrap_or(&self.minimum_balance);
 
-                // The sender must have at least enough coins to send the transaction
-                return (
-                    sender_balance.clone(),
-                    tx.transaction_data.value.clone(),
-                    *sender_balance >= tx.transaction_data.value,
-                );
+                // The sender must have at least enough coins to send the transaction, and pay its max fee
+                let cost = gas::max_cost(&tx.transaction_data);
+                let ok = *sender_balance >= cost;
+
+                return (sender_balance.clone(), cost, ok);
             }
         }
 
-        // If the sender doesn't have any SMC, they can't send any. Therefore, the value of the transaction must be zero.
-        (
-            self.minimum_balance.clone(),
-            tx.transaction_data.value.clone(),
-            tx.transaction_data.value == BigUint::default(),
-        )
+        // If the sender doesn't have any SMC, they can't send any. Therefore, the transaction must cost nothing.
+        let cost = gas::max_cost(&tx.transaction_data);
+        let ok = cost.is_zero();
+
+        (self.minimum_balance.clone(), cost, ok)
     }
 
     /// Ensures that the nonce provided by the transaction matches the target nonce for such a
@@ -217,104 +351,355 @@ impl<'a> GraphBoundValidator<'a> {
     /// # Arguments
     ///
     /// * `tx` - The transaction that the nonce should be checked of
-    fn transaction_nonce_is_valid(&self, tx: &Transaction) -> (bool, BigUint) {
+    fn transaction_nonce_is_valid(&self, tx: &Transaction) -> (bool, u64) {
         // Check for a latest state entry in the graph. This will serve as the point from where we calculate the account's nonce.
         if let Some(last_state) = self.graph.obtain_executed_head() {
             // Ensure that the provided transaction has in fact been executed
             if let Some(state) = last_state.state_entry {
                 // Ensure that the current nonce is equal to exactly the last nonce +1
-                if let Some(last_nonce) = state
-                    .data
-                    .balances
-                    .get(&tx.transaction_data.sender.to_str())
+                if let Some(last_nonce) =
+                    state.data.nonces.get(&tx.transaction_data.sender.to_str())
                 {
-                    return (
-                        last_nonce.clone() == BigUint::from(tx.transaction_data.nonce - 1),
-                        last_nonce.clone() + (1 as u8),
-                    );
+                    return (tx.transaction_data.nonce == last_nonce + 1, last_nonce + 1);
                 }
             }
         }
 
-        // The nonce must be 0 since there is not a parent
-        (tx.transaction_data.nonce == 0, BigUint::zero())
+        // The nonce must be 0 since the sender hasn't sent any transactions yet
+        (tx.transaction_data.nonce == 0, 0)
     }
-}
 
-impl<'a> Validator for GraphBoundValidator<'a> {
-    /// Validates the contents of a transaction.
+    /// Ensures that any guardian instruction carried by the transaction follows the account
+    /// recovery rules, considering the guardians registered as of the executed head of the graph,
+    /// and that a recovery isn't being initiated or executed at a time of the sender's choosing.
     ///
     /// # Arguments
     ///
-    /// * `tx` - The transaction that should be validated
-    fn transaction_is_valid(&self, tx: &Transaction) -> Result<(), failure::Error> {
-        // Ensure that all of the properties of the transaction are in fact valid
-        if !self.transaction_is_unique(tx) {
-            Err(GraphBoundValidatorReason::NotUnique { tx_hash: tx.hash }.into())
-        } else {
-            let (ok, offending_parent_hash) = self.transaction_is_head(tx);
+    /// * `tx` - The transaction that should be checked
+    fn transaction_guardian_instruction_is_valid(
+        &self,
+        tx: &Transaction,
+    ) -> Result<(), failure::Error> {
+        // Ordinary transactions aren't subject to any of the rules
+        let instruction = match GuardianInstruction::from_payload(&tx.transaction_data.payload)? {
+            Some(instruction) => instruction,
+            None => return Ok(()),
+        };
+
+        // Look up the guardians of the account that the instruction concerns
+        let record = self
+            .graph
+            .get_guardians(instruction.account(tx.transaction_data.sender));
+
+        guardians::check_timestamp(tx, &instruction, Utc::now())?;
+
+        Ok(guardians::check(record.as_ref(), tx, &instruction)?)
+    }
+
+    /// Ensures that the transaction doesn't send any of its sender's locked funds, that any time lock it carries is
+    /// well-formed, and that it doesn't push the ledger's clock ahead of time. Locks are evaluated against the state of
+    /// the transaction's parents, at its timestamp bounded by the parents' clock; transactions whose parents can't be
+    /// resolved are turned away by the parent execution check instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that should be checked
+    fn transaction_vesting_is_valid(
+        &self,
+        tx: &Transaction,
+    ) -> Result<(), GraphBoundValidatorReason> {
+        let violated = |reason: String| GraphBoundValidatorReason::VestingRuleViolated {
+            tx_hash: tx.hash,
+            reason,
+        };
 
-            // Ensure that the transaction is young enough
-            if !ok {
-                Err(GraphBoundValidatorReason::TooOld {
-                    tx_hash: tx.hash,
-                    invalid_parent_hash: offending_parent_hash,
+        vesting::check(&tx.transaction_data).map_err(|e| violated(e.to_string()))?;
+        vesting::check_timestamp(&tx.transaction_data, Utc::now())
+            .map_err(|e| violated(e.to_string()))?;
+
+        let funds = match self
+            .graph
+            .resolve_parent_nodes(tx.transaction_data.parents.clone())
+        {
+            Ok((parent_state, _)) => {
+                vesting::sender_funds(&parent_state.data, &tx.transaction_data)
+            }
+            Err(_) => return Ok(()),
+        };
+
+        let cost = gas::max_cost(&tx.transaction_data);
+        if cost > funds.spendable && !funds.locked.is_zero() {
+            return Err(GraphBoundValidatorReason::LockedFunds {
+                tx_hash: tx.hash,
+                sender: tx.transaction_data.sender,
+                locked: funds.locked,
+                spendable: funds.spendable,
+                tx_value: cost,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Runs a single check against the provided transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `check` - The check that should be performed
+    /// * `tx` - The transaction that should be checked
+    fn run_check(&self, check: Check, tx: &Transaction) -> Result<(), GraphBoundValidatorReason> {
+        match check {
+            Check::Uniqueness => {
+                if !self.transaction_is_unique(tx) {
+                    return Err(GraphBoundValidatorReason::NotUnique { tx_hash: tx.hash });
                 }
-                .into())
-            } else if !self.transaction_signature_is_valid(tx) {
-                Err(GraphBoundValidatorReason::InvalidSignature { tx_hash: tx.hash }.into())
-            } else {
+            }
+            Check::Recency => {
+                // A transaction can't be too old for parents that haven't been seen yet
+                if let Some(parent_hash) = self.missing_parents(tx).first() {
+                    return Err(GraphBoundValidatorReason::MissingParent {
+                        tx_hash: tx.hash,
+                        parent_hash: *parent_hash,
+                    });
+                }
+
+                let (ok, offending_parent_hash) = self.transaction_is_head(tx);
+
+                // Ensure that the transaction is young enough
+                if !ok {
+                    return Err(GraphBoundValidatorReason::TooOld {
+                        tx_hash: tx.hash,
+                        invalid_parent_hash: offending_parent_hash,
+                    });
+                }
+            }
+            Check::Signature => {
+                if !self.transaction_signature_is_valid(tx) {
+                    return Err(GraphBoundValidatorReason::InvalidSignature { tx_hash: tx.hash });
+                }
+            }
+            Check::Hash => {
                 // Ensure that the transaction's hash can be reproduced
                 let (ok, target_hash) = self.transaction_hash_is_valid(tx);
 
                 // If the hash can't be reproduced, the tx is invalid
                 if !ok {
-                    Err(GraphBoundValidatorReason::InvalidHash {
+                    return Err(GraphBoundValidatorReason::InvalidHash {
                         tx_hash: tx.hash,
                         desired_hash: target_hash,
-                    }
-                    .into())
-                } else if !self.transaction_parent_execution_is_valid(tx) {
-                    Err(GraphBoundValidatorReason::ParentReceiptInvalid { tx_hash: tx.hash }.into())
-                } else {
-                    // Check that the sender of the transaction has enough coins to send the
-                    // transaction
-                    let (sender_balance, value, ok) =
-                        self.transaction_sender_balance_is_sufficient(tx);
-
-                    // If the user sending the transaction doesn't have enough SMC to actually send this transaction, return an error
-                    if !ok {
-                        Err(GraphBoundValidatorReason::InsufficientSenderBalance {
-                            tx_hash: tx.hash,
-                            sender: tx.transaction_data.sender,
-                            balance: sender_balance,
-                            tx_value: value,
-                        }
-                        .into())
-                    } else if tx.transaction_data.sender == tx.transaction_data.recipient {
-                        Err(GraphBoundValidatorReason::AttemptedIdentityOperation {
-                            tx_hash: tx.hash,
-                            sender: tx.transaction_data.sender,
-                        }
-                        .into())
-                    } else {
-                        // Make sure the transaction's nonce is valid
-                        let (ok, target) = self.transaction_nonce_is_valid(tx);
-
-                        if !ok {
-                            Err(GraphBoundValidatorReason::InvalidNonce {
-                                tx_hash: tx.hash,
-                                found: tx.transaction_data.nonce,
-                                target,
-                                offending_account: tx.transaction_data.sender,
-                            }
-                            .into())
-                        } else {
-                            Ok(())
-                        }
-                    }
+                    });
+                }
+            }
+            Check::ParentExecution => {
+                if !self.transaction_parent_execution_is_valid(tx) {
+                    return Err(GraphBoundValidatorReason::ParentReceiptInvalid {
+                        tx_hash: tx.hash,
+                    });
+                }
+            }
+            Check::SenderBalance => {
+                // Check that the sender of the transaction has enough coins to send the
+                // transaction
+                let (sender_balance, value, ok) = self.transaction_sender_balance_is_sufficient(tx);
+
+                // If the user sending the transaction doesn't have enough SMC to actually send this transaction, return an error
+                if !ok {
+                    return Err(GraphBoundValidatorReason::InsufficientSenderBalance {
+                        tx_hash: tx.hash,
+                        sender: tx.transaction_data.sender,
+                        balance: sender_balance,
+                        tx_value: value,
+                    });
+                }
+            }
+            Check::Identity => {
+                // Reward claims are the only transactions that an account may send to itself
+                if tx.transaction_data.sender == tx.transaction_data.recipient
+                    && !rewards::is_claim(&tx.transaction_data)
+                {
+                    return Err(GraphBoundValidatorReason::AttemptedIdentityOperation {
+                        tx_hash: tx.hash,
+                        sender: tx.transaction_data.sender,
+                    });
                 }
             }
+            Check::Nonce => {
+                // Make sure the transaction's nonce is valid
+                let (ok, target) = self.transaction_nonce_is_valid(tx);
+
+                if !ok {
+                    return Err(GraphBoundValidatorReason::InvalidNonce {
+                        tx_hash: tx.hash,
+                        found: tx.transaction_data.nonce,
+                        target,
+                        offending_account: tx.transaction_data.sender,
+                    });
+                }
+            }
+            Check::Guardians => {
+                // Make sure any guardian instruction carried by the transaction is allowed
+                if let Err(e) = self.transaction_guardian_instruction_is_valid(tx) {
+                    return Err(GraphBoundValidatorReason::GuardianRuleViolated {
+                        tx_hash: tx.hash,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+            Check::Batch => {
+                // Make sure any batch carried by the transaction adds up, and is sent where it should be
+                if let Err(e) = batch::check(&tx.transaction_data) {
+                    return Err(GraphBoundValidatorReason::BatchRuleViolated {
+                        tx_hash: tx.hash,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+            Check::Vesting => {
+                // Make sure the sender isn't spending funds that are still locked
+                self.transaction_vesting_is_valid(tx)?;
+            }
+            Check::Burn => {
+                // Make sure nobody is trying to spend burned funds
+                if let Err(e) = burn::check(&tx.transaction_data) {
+                    return Err(GraphBoundValidatorReason::BurnRuleViolated {
+                        tx_hash: tx.hash,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+            Check::Gas => {
+                // Make sure the transaction's gas limit covers the gas it uses, if it pays for gas
+                if let Err(e) = gas::check(&tx.transaction_data) {
+                    return Err(GraphBoundValidatorReason::GasRuleViolated {
+                        tx_hash: tx.hash,
+                        reason: e.to_string(),
+                    });
+                }
+            }
+            Check::Recipient => {
+                // Funds sent to a contract without calling it would be stuck there
+                let recipient = tx.transaction_data.recipient;
+                if tx.transaction_data.payload.is_empty()
+                    && self.graph.address_kind(&recipient) == AddressKind::Contract
+                {
+                    return Err(GraphBoundValidatorReason::PlainTransferToContract {
+                        tx_hash: tx.hash,
+                        recipient,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs every check against the provided transaction, rather than stopping at the first
+    /// failure, and reports the outcome of each alongside the values the validator expected.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that should be explained
+    pub fn explain(&self, tx: &Transaction) -> ValidationReport {
+        // Run each of the checks, in order
+        let checks: Vec<CheckResult> = CHECKS
+            .iter()
+            .map(|check| {
+                let res = self.run_check(*check, tx);
+
+                CheckResult {
+                    check: *check,
+                    passed: res.is_ok(),
+                    reason: res.err().map(|e| e.to_string()),
+                }
+            })
+            .collect();
+
+        ValidationReport {
+            tx_hash: tx.hash,
+            valid: checks.iter().all(|res| res.passed),
+            checks,
+            expected_nonce: self.transaction_nonce_is_valid(tx).1,
+            expected_parent_state_hash: self
+                .graph
+                .resolve_parent_nodes(tx.transaction_data.parents.clone())
+                .ok()
+                .map(|(merged, _)| merged.hash),
+            sender_balance: self.transaction_sender_balance_is_sufficient(tx).0,
         }
     }
 }
+
+impl<'a> Validator for GraphBoundValidator<'a> {
+    /// Validates the contents of a transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - The transaction that should be validated
+    fn transaction_is_valid(&self, tx: &Transaction) -> Result<(), failure::Error> {
+        // Ensure that all of the properties of the transaction are in fact valid, stopping at the first that isn't
+        for check in CHECKS.iter() {
+            self.run_check(*check, tx)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rand::Rng; // Import rand
+    use ed25519_dalek::Keypair; // Import the edwards25519 digital signature library
+    use rand::rngs::OsRng; // Import the os's rng
+    use std::collections::HashMap; // Import the hashmap type
+
+    use super::super::{
+        common::io,
+        core::types::state,
+        crypto::hash::{self, Hash},
+    };
+
+    use super::*; // Import names from parent module
+
+    #[test]
+    fn test_transaction_nonce_is_valid() {
+        let mut csprng = OsRng {}; // Generate source of randomness
+        let rand: u16 = rand::thread_rng().gen(); // Generate random number
+
+        let sender = Address::from_key_pair(&Keypair::generate(&mut csprng)); // Derive sender address
+        let recipient = Address::from_key_pair(&Keypair::generate(&mut csprng)); // Derive recipient address
+
+        let root_tx = Transaction::new(
+            0,
+            sender,
+            recipient,
+            BigUint::zero(),
+            b"test transaction payload",
+            vec![Hash::new(vec![0; hash::HASH_SIZE])],
+        ); // Initialize root transaction
+
+        let mut dag = Graph::new_with_db_path(
+            root_tx.clone(),
+            &format!("{}/.tests/{}", io::db_dir(), rand.to_string()),
+        ); // Initialize graph
+
+        // Give the sender a balance that differs from its last nonce
+        let mut nonces = HashMap::new();
+        nonces.insert(sender.to_str(), 0);
+        let mut balances = HashMap::new();
+        balances.insert(sender.to_str(), BigUint::from(1000 as u16));
+
+        dag.update(0, root_tx, Some(state::Entry::new(nonces, balances))); // Execute the root transaction
+
+        let validator = GraphBoundValidator::new(&dag); // Initialize validator
+
+        let next = Transaction::new(1, sender, recipient, BigUint::zero(), b"", vec![]); // Follows the last nonce
+        let skipped = Transaction::new(2, sender, recipient, BigUint::zero(), b"", vec![]); // Skips a nonce
+        let fresh = Transaction::new(0, recipient, sender, BigUint::zero(), b"", vec![]); // First tx from recipient
+
+        assert_eq!(validator.transaction_nonce_is_valid(&next), (true, 1)); // Ensure the next nonce is accepted
+        assert_eq!(validator.transaction_nonce_is_valid(&skipped), (false, 1)); // Ensure a gap is rejected
+        assert_eq!(validator.transaction_nonce_is_valid(&fresh), (true, 0)); // Ensure a new sender starts at 0
+
+        assert_eq!(dag.write_to_disk(), Ok(())); // Close dag
+    }
+}